@@ -0,0 +1,58 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use darkfi_sdk::crypto::{note::AeadEncryptedNote, Keypair, SecretKey};
+use rand::rngs::OsRng;
+
+/// Trial-decrypt a block worth of notes with a wallet's keys, none of
+/// them being ours, which is the common case when scanning.
+fn note_decrypt(c: &mut Criterion) {
+    let stranger = Keypair::random(&mut OsRng);
+    let secrets: Vec<SecretKey> = (0..10).map(|_| SecretKey::random(&mut OsRng)).collect();
+
+    let mut group = c.benchmark_group("trial_decrypt");
+    group.significance_level(0.01).sample_size(10);
+    for n_notes in [100, 1000] {
+        let notes: Vec<AeadEncryptedNote> = (0..n_notes)
+            .map(|i| AeadEncryptedNote::encrypt(&(i as u64), &stranger.public, &mut OsRng).unwrap())
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("sequential", n_notes), &notes, |b, notes| {
+            b.iter(|| {
+                for note in notes {
+                    for secret in &secrets {
+                        let _ = note.decrypt::<u64>(secret);
+                    }
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("batched", n_notes), &notes, |b, notes| {
+            b.iter(|| AeadEncryptedNote::trial_decrypt_batch::<u64>(notes, &secrets))
+        });
+
+        group.bench_with_input(BenchmarkId::new("parallel", n_notes), &notes, |b, notes| {
+            b.iter(|| AeadEncryptedNote::trial_decrypt::<u64>(notes, &secrets))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(bench, note_decrypt);
+criterion_main!(bench);
@@ -0,0 +1,64 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{fs::File, io::Write, path::Path};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{
+        bench::{bench_circuits, results_to_json, CIRCUITS},
+        empty_witnesses, import_witness_json, Proof, ProvingKey, VerifyingKey, ZkCircuit,
+    },
+    zkas::ZkBinary,
+};
+
+// Prove and verify every shipped circuit with its stored witnesses.
+// Set ZK_BENCH_JSON=<file> to also write a machine-readable summary,
+// including key sizes and memory use, to <file>.
+fn zk_circuits(c: &mut Criterion) {
+    for (name, zkbin_path, witness_path) in CIRCUITS {
+        let zkbin = ZkBinary::decode(&std::fs::read(zkbin_path).unwrap()).unwrap();
+        let (prover_witnesses, public_inputs) = import_witness_json(witness_path);
+
+        let empty_circuit = ZkCircuit::new(empty_witnesses(&zkbin).unwrap(), &zkbin);
+        let proving_key = ProvingKey::build(zkbin.k, &empty_circuit);
+        let verifying_key = VerifyingKey::build(zkbin.k, &empty_circuit);
+
+        let circuit = ZkCircuit::new(prover_witnesses, &zkbin);
+        c.bench_function(&format!("prove {}", name), |b| {
+            b.iter(|| Proof::create(&proving_key, &[circuit.clone()], &public_inputs, OsRng))
+        });
+
+        let proof = Proof::create(&proving_key, &[circuit], &public_inputs, OsRng).unwrap();
+        c.bench_function(&format!("verify {}", name), |b| {
+            b.iter(|| proof.verify(&verifying_key, &public_inputs))
+        });
+    }
+
+    if let Ok(output) = std::env::var("ZK_BENCH_JSON") {
+        let results = bench_circuits(Path::new("."), 3).unwrap();
+        let mut file = File::create(&output).unwrap();
+        file.write_all(results_to_json(&results).as_bytes()).unwrap();
+        println!("Wrote ZK benchmark results to {}", output);
+    }
+}
+
+criterion_group!(bench, zk_circuits);
+criterion_main!(bench);
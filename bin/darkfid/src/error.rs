@@ -28,15 +28,23 @@ pub enum RpcError {
     // State-related errors,
     NotSynced = -32120,
     UnknownBlockHeight = -32121,
+    UnknownBlock = -32122,
+    UnknownTransaction = -32123,
+
+    // Mining-related errors
+    UnknownBlockTemplate = -32130,
+    BlockNotAccepted = -32131,
 
     // Parsing errors
     ParseError = -32190,
 
     // Contract-related errors
     ContractZkasDbNotFound = -32200,
+    ContractDbNotFound = -32201,
 
     // Misc errors
     PingFailed = -32300,
+    ReloadConfigFailed = -32301,
 }
 
 fn to_tuple(e: RpcError) -> (i32, String) {
@@ -47,12 +55,19 @@ fn to_tuple(e: RpcError) -> (i32, String) {
         // State-related errors
         RpcError::NotSynced => "Blockchain is not synced",
         RpcError::UnknownBlockHeight => "Did not find block height",
+        RpcError::UnknownBlock => "Did not find block",
+        RpcError::UnknownTransaction => "Did not find transaction",
+        // Mining-related errors
+        RpcError::UnknownBlockTemplate => "Block was not mined from a known template",
+        RpcError::BlockNotAccepted => "Block not accepted",
         // Parsing errors
         RpcError::ParseError => "Parse error",
         // Contract-related errors
         RpcError::ContractZkasDbNotFound => "zkas database not found for given contract",
+        RpcError::ContractDbNotFound => "Database not found for given contract",
         // Misc errors
         RpcError::PingFailed => "Miner daemon ping error",
+        RpcError::ReloadConfigFailed => "Failed reloading configuration",
     };
 
     (e as i32, msg.to_string())
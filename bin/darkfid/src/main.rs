@@ -19,11 +19,12 @@
 use std::{
     collections::{HashMap, HashSet},
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
 
 use log::{error, info};
-use smol::{lock::Mutex, stream::StreamExt};
+use smol::{future::FutureExt, lock::Mutex, stream::StreamExt};
 use structopt_toml::{serde::Deserialize, structopt::StructOpt, StructOptToml};
 use url::Url;
 
@@ -31,13 +32,21 @@ use darkfi::{
     async_daemonize,
     blockchain::BlockInfo,
     cli_desc,
-    net::{settings::SettingsOpt, P2pPtr},
+    net::{
+        settings::SettingsOpt,
+        transport::{identity_public_key, load_or_create_identity},
+        P2pPtr,
+    },
     rpc::{
+        auth::RpcAuth,
         client::RpcChadClient,
         jsonrpc::JsonSubscriber,
-        server::{listen_and_serve, RequestHandler},
+        ratelimit::RpcRateLimit,
+        rest::listen_and_serve_rest,
+        server::{listen_and_serve, listen_and_serve_tls, RequestHandler},
     },
-    system::{StoppableTask, StoppableTaskPtr},
+    runtime::module_cache::ModuleCache,
+    system::{RestartPolicy, StoppableTask, StoppableTaskPtr, Supervisor},
     util::{encoding::base64, path::expand_path},
     validator::{Validator, ValidatorConfig, ValidatorPtr},
     Error, Result,
@@ -53,12 +62,26 @@ use error::{server_error, RpcError};
 
 /// JSON-RPC requests handler and methods
 mod rpc;
+use rpc::REST_ROUTES;
 mod rpc_blockchain;
+mod rpc_contract;
+mod rpc_mempool;
+mod rpc_pool;
+use rpc_pool::PoolState;
+mod rpc_scan;
+use rpc_scan::ScanSubscription;
 mod rpc_tx;
 
+/// Runtime configuration reloading
+mod reload;
+use reload::rpc_rate_limit_from_args;
+
 /// Validator async tasks
 mod task;
-use task::{consensus_task, miner_task, sync_task};
+use task::{
+    clock::ClockSkew, clock_task, consensus_task, disk::DiskUsage, disk_task, miner_task,
+    scan_task, sync_task,
+};
 
 /// P2P net protocols
 mod proto;
@@ -67,6 +90,11 @@ mod proto;
 mod utils;
 use utils::{parse_blockchain_config, spawn_p2p};
 
+/// Failures tolerated within the supervisor failure window before giving up on a task
+const SUPERVISOR_MAX_FAILURES: usize = 5;
+/// Window in which supervised task failures are counted, in seconds
+const SUPERVISOR_FAILURE_WINDOW: u64 = 300;
+
 const CONFIG_FILE: &str = "darkfid_config.toml";
 const CONFIG_FILE_CONTENTS: &str = include_str!("../darkfid_config.toml");
 /// Note:
@@ -88,6 +116,42 @@ struct Args {
     /// JSON-RPC listen URL
     rpc_listen: Url,
 
+    #[structopt(long)]
+    /// Path to a persistent TLS identity keypair for `tcp+tls://` JSON-RPC
+    /// listen URLs, so clients can pin its public key. Created if missing.
+    rpc_tls_identity: Option<String>,
+
+    #[structopt(long)]
+    /// JSON-RPC auth tokens, in the form of
+    /// `<bearer|hmac>:<id>:<secret>[:<method>,<method>,...]`
+    rpc_auth: Vec<String>,
+
+    #[structopt(long)]
+    /// JSON-RPC per-connection rate limit, in the form of `<rate>[:<burst>]`
+    rpc_rate_limit: Option<String>,
+
+    #[structopt(long)]
+    /// JSON-RPC per-method rate limits, in the form of
+    /// `<method>:<rate>[:<burst>]`
+    rpc_method_limit: Vec<String>,
+
+    #[structopt(long)]
+    /// Ban JSON-RPC clients after this many rate limited requests in a minute
+    rpc_ban_threshold: Option<u32>,
+
+    #[structopt(long, default_value = "600")]
+    /// JSON-RPC client ban duration, in seconds
+    rpc_ban_duration: u64,
+
+    #[structopt(long)]
+    /// Optional read-only REST gateway listen URL
+    rest_listen: Option<Url>,
+
+    #[structopt(long)]
+    /// Optional OpenTelemetry collector endpoint to export tracing spans to,
+    /// e.g. `http://127.0.0.1:4318/v1/traces` (requires the `otlp` feature)
+    otlp_endpoint: Option<Url>,
+
     #[structopt(short, long, default_value = "testnet")]
     /// Blockchain network to use
     network: String,
@@ -110,6 +174,10 @@ pub struct BlockchainNetwork {
     /// Path to blockchain database
     pub database: String,
 
+    #[structopt(long)]
+    /// Optional directory to cache compiled WASM contracts in
+    pub wasm_cache: Option<String>,
+
     #[structopt(long, default_value = "3")]
     /// Finalization threshold, denominated by number of blocks
     pub threshold: usize,
@@ -138,6 +206,15 @@ pub struct BlockchainNetwork {
     /// Skip syncing process and start node right away
     pub skip_sync: bool,
 
+    #[structopt(long)]
+    /// Sample NTP time for clock sanity checks, instead of peer-reported times
+    pub ntp_clock_check: bool,
+
+    #[structopt(long, default_value = "1024")]
+    /// Minimum free disk space to keep for the database, in MB. Below it
+    /// the node stops writing new blocks until space is freed, 0 disables it.
+    pub min_free_space: u64,
+
     /// P2P network settings
     #[structopt(flatten)]
     pub net: SettingsOpt,
@@ -157,6 +234,22 @@ pub struct Darkfid {
     rpc_connections: Mutex<HashSet<StoppableTaskPtr>>,
     /// JSON-RPC client to execute requests to the miner daemon
     rpc_client: Option<RpcChadClient>,
+    /// Optional JSON-RPC authentication policy, swappable on config reload
+    rpc_auth: RwLock<Option<Arc<RpcAuth>>>,
+    /// Optional JSON-RPC rate limiting policy, swappable on config reload
+    rpc_rate_limit: RwLock<Option<Arc<RpcRateLimit>>>,
+    /// Mining pool compatibility methods state
+    pool: Mutex<PoolState>,
+    /// Registered wallet scan subscriptions
+    scan_subscriptions: Mutex<Vec<ScanSubscription>>,
+    /// Node start time, used to report its uptime
+    started: Instant,
+    /// Last sampled local clock skew from network time, in seconds
+    clock_skew: Mutex<Option<ClockSkew>>,
+    /// Last sampled disk usage
+    disk_usage: Mutex<Option<DiskUsage>>,
+    /// Running configuration, compared against on config reload
+    running_config: Mutex<Option<(Args, BlockchainNetwork)>>,
 }
 
 impl Darkfid {
@@ -166,6 +259,8 @@ impl Darkfid {
         miner: bool,
         subscribers: HashMap<&'static str, JsonSubscriber>,
         rpc_client: Option<RpcChadClient>,
+        rpc_auth: Option<RpcAuth>,
+        rpc_rate_limit: Option<RpcRateLimit>,
     ) -> Self {
         Self {
             p2p,
@@ -174,6 +269,14 @@ impl Darkfid {
             subscribers,
             rpc_connections: Mutex::new(HashSet::new()),
             rpc_client,
+            rpc_auth: RwLock::new(rpc_auth.map(Arc::new)),
+            rpc_rate_limit: RwLock::new(rpc_rate_limit.map(Arc::new)),
+            pool: Mutex::new(PoolState::default()),
+            scan_subscriptions: Mutex::new(vec![]),
+            started: Instant::now(),
+            clock_skew: Mutex::new(None),
+            disk_usage: Mutex::new(None),
+            running_config: Mutex::new(None),
         }
     }
 }
@@ -182,6 +285,20 @@ async_daemonize!(realmain);
 async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
     info!(target: "darkfid", "Initializing DarkFi node...");
 
+    // Export tracing spans to an OpenTelemetry collector, if configured
+    if let Some(ref endpoint) = args.otlp_endpoint {
+        #[cfg(feature = "otlp")]
+        {
+            darkfi::util::telemetry::init_otlp_tracing(endpoint, "darkfid")?;
+            info!(target: "darkfid", "Exporting tracing spans to {}", endpoint);
+        }
+        #[cfg(not(feature = "otlp"))]
+        log::warn!(target: "darkfid", "Built without the `otlp` feature, ignoring OTLP endpoint {}", endpoint);
+    }
+
+    // Keep the running configuration around for config reloads
+    let running_args = args.clone();
+
     // Grab blockchain network configuration
     let (blockchain_config, genesis_block) = match args.network.as_str() {
         "localnet" => {
@@ -199,6 +316,8 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
         }
     };
 
+    let running_config = (running_args, blockchain_config.clone());
+
     // Parse the genesis block
     let bytes = base64::decode(genesis_block.trim()).unwrap();
     let genesis_block: BlockInfo = deserialize_async(&bytes).await?;
@@ -207,6 +326,11 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
     let db_path = expand_path(&blockchain_config.database)?;
     let sled_db = sled::open(&db_path)?;
 
+    // Cache compiled WASM contracts on disk, if configured
+    if let Some(ref wasm_cache) = blockchain_config.wasm_cache {
+        ModuleCache::global().set_disk_cache(Some(expand_path(wasm_cache)?))?;
+    }
+
     // Initialize validator configuration
     let pow_fixed_difficulty = if let Some(diff) = blockchain_config.pow_fixed_difficulty {
         info!(target: "darkfid", "Node is configured to run with fixed PoW difficulty: {}", diff);
@@ -248,6 +372,18 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
         None
     };
 
+    // Parse JSON-RPC authentication tokens, if any
+    let rpc_auth = RpcAuth::from_config(&args.rpc_auth)?;
+    if rpc_auth.is_some() {
+        info!(target: "darkfid", "JSON-RPC authentication is enabled");
+    }
+
+    // Parse JSON-RPC rate limits, if any
+    let rpc_rate_limit = rpc_rate_limit_from_args(&args)?;
+    if rpc_rate_limit.is_some() {
+        info!(target: "darkfid", "JSON-RPC rate limiting is enabled");
+    }
+
     // Initialize node
     let darkfid = Darkfid::new(
         p2p.clone(),
@@ -255,8 +391,11 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
         blockchain_config.miner,
         subscribers,
         rpc_client,
+        rpc_auth,
+        rpc_rate_limit,
     )
     .await;
+    *darkfid.running_config.lock().await = Some(running_config);
     let darkfid = Arc::new(darkfid);
     info!(target: "darkfid", "Node initialized successfully!");
 
@@ -276,8 +415,20 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
     // created for it.
     let rpc_task = StoppableTask::new();
     let darkfid_ = darkfid.clone();
+    let rpc_server = match args.rpc_tls_identity {
+        Some(ref path) => {
+            let tls_identity = load_or_create_identity(&expand_path(path)?)?;
+            info!(
+                target: "darkfid",
+                "JSON-RPC TLS public key: {}", identity_public_key(&tls_identity)?,
+            );
+            listen_and_serve_tls(args.rpc_listen, tls_identity, darkfid.clone(), None, ex.clone())
+                .boxed()
+        }
+        None => listen_and_serve(args.rpc_listen, darkfid.clone(), None, ex.clone()).boxed(),
+    };
     rpc_task.clone().start(
-        listen_and_serve(args.rpc_listen, darkfid.clone(), None, ex.clone()),
+        rpc_server,
         |res| async move {
             match res {
                 Ok(()) | Err(Error::RpcServerStopped) => darkfid_.stop_connections().await,
@@ -288,9 +439,91 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
         ex.clone(),
     );
 
+    // REST gateway
+    let rest_task = match args.rest_listen {
+        Some(rest_listen) => {
+            info!(target: "darkfid", "Starting REST gateway");
+            let task = StoppableTask::new();
+            task.clone().start(
+                listen_and_serve_rest(rest_listen, REST_ROUTES, darkfid.clone(), ex.clone()),
+                |res| async move {
+                    match res {
+                        Ok(()) | Err(Error::RpcServerStopped) => {}
+                        Err(e) => error!(target: "darkfid", "Failed starting REST gateway: {}", e),
+                    }
+                },
+                Error::RpcServerStopped,
+                ex.clone(),
+            );
+            Some(task)
+        }
+        None => None,
+    };
+
+    // Long-running background tasks are supervised, so they get restarted
+    // if they fail or panic, and repeated failures are reported.
+    let supervisor = Supervisor::new(
+        SUPERVISOR_MAX_FAILURES,
+        Duration::from_secs(SUPERVISOR_FAILURE_WINDOW),
+        Arc::new(
+            |name: &str, err: &Error| error!(target: "darkfid", "Task {} keeps failing, giving up on it: {}", name, err),
+        ),
+    );
+    let restart_policy =
+        RestartPolicy::Backoff { initial: Duration::from_secs(1), max: Duration::from_secs(60) };
+
+    // Disk usage monitoring, started before syncing so it can't fill up the disk
+    info!(target: "darkfid", "Starting disk usage task");
+    let darkfid_ = darkfid.clone();
+    let log_path = args.log.as_deref().map(expand_path).transpose()?;
+    let min_free_space = blockchain_config.min_free_space * 1024 * 1024;
+    supervisor
+        .spawn(
+            "disk",
+            restart_policy,
+            move || {
+                let darkfid = darkfid_.clone();
+                let (db_path, log_path) = (db_path.clone(), log_path.clone());
+                async move { disk_task(&darkfid, db_path, log_path, min_free_space).await }
+            },
+            ex.clone(),
+        )
+        .await;
+
+    // Wallet scan subscriptions, started before syncing so synced blocks get pushed too
+    info!(target: "darkfid", "Starting scan task");
+    let darkfid_ = darkfid.clone();
+    supervisor
+        .spawn(
+            "scan",
+            restart_policy,
+            move || {
+                let darkfid = darkfid_.clone();
+                async move { scan_task(&darkfid).await }
+            },
+            ex.clone(),
+        )
+        .await;
+
     info!(target: "darkfid", "Starting P2P network");
     p2p.clone().start().await?;
 
+    // Clock sanity checks, sampling peers' reported times once connected
+    info!(target: "darkfid", "Starting clock sanity task");
+    let darkfid_ = darkfid.clone();
+    let ntp_clock_check = blockchain_config.ntp_clock_check;
+    supervisor
+        .spawn(
+            "clock",
+            restart_policy,
+            move || {
+                let darkfid = darkfid_.clone();
+                async move { clock_task(&darkfid, ntp_clock_check).await }
+            },
+            ex.clone(),
+        )
+        .await;
+
     // Sync blockchain
     if !blockchain_config.skip_sync {
         sync_task(&darkfid).await?;
@@ -301,6 +534,9 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
     // Clean node pending transactions
     darkfid.validator.purge_pending_txs().await?;
 
+    // Keep a node reference around for configuration reloads
+    let darkfid_ = darkfid.clone();
+
     // Consensus protocol
     info!(target: "darkfid", "Starting consensus protocol task");
     let consensus_task = if blockchain_config.miner {
@@ -347,19 +583,52 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
     };
 
     // Signal handling for graceful termination.
-    let (signals_handler, signals_task) = SignalHandler::new(ex)?;
+    let (signals_handler, signals_task) = SignalHandler::new(ex.clone())?;
+
+    // Reload the configuration on SIGHUP
+    let reload_task = StoppableTask::new();
+    let sighup_sub = signals_handler.sighup_sub.clone().subscribe().await;
+    reload_task.clone().start(
+        async move {
+            loop {
+                let args = sighup_sub.receive().await;
+                if let Err(e) = darkfid_.apply_config(args).await {
+                    error!(target: "darkfid", "Failed reloading configuration: {}", e);
+                }
+            }
+        },
+        |_| async { /* Do nothing */ },
+        Error::DetachedTaskStopped,
+        ex,
+    );
+
     signals_handler.wait_termination(signals_task).await?;
     info!(target: "darkfid", "Caught termination signal, cleaning up and exiting...");
 
+    reload_task.stop().await;
+
     info!(target: "darkfid", "Stopping JSON-RPC server...");
     rpc_task.stop().await;
 
+    if let Some(rest_task) = rest_task {
+        info!(target: "darkfid", "Stopping REST gateway...");
+        rest_task.stop().await;
+    }
+
     info!(target: "darkfid", "Stopping P2P network...");
     p2p.stop().await;
 
     info!(target: "darkfid", "Stopping consensus task...");
     consensus_task.stop().await;
 
+    info!(target: "darkfid", "Stopping supervised tasks...");
+    supervisor.stop().await;
+
+    #[cfg(feature = "otlp")]
+    if args.otlp_endpoint.is_some() {
+        darkfi::util::telemetry::shutdown_otlp_tracing();
+    }
+
     info!(target: "darkfid", "Flushing sled database...");
     let flushed_bytes = sled_db.flush_async().await?;
     info!(target: "darkfid", "Flushed {} bytes", flushed_bytes);
@@ -31,7 +31,10 @@ use darkfi::{
     },
     rpc::jsonrpc::JsonSubscriber,
     util::encoding::base64,
-    validator::{consensus::Proposal, ValidatorPtr},
+    validator::{
+        consensus::{Proposal, MAX_BLOCK_SIZE},
+        ValidatorPtr,
+    },
     Error, Result,
 };
 use darkfi_serial::{serialize_async, SerialDecodable, SerialEncodable};
@@ -42,7 +45,7 @@ use crate::proto::{ForkSyncRequest, ForkSyncResponse, COMMS_TIMEOUT};
 #[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
 pub struct ProposalMessage(pub Proposal);
 
-impl_p2p_message!(ProposalMessage, "proposal");
+impl_p2p_message!(ProposalMessage, "proposal", MAX_BLOCK_SIZE);
 
 pub struct ProtocolProposal {
     proposal_sub: MessageSubscription<ProposalMessage>,
@@ -29,7 +29,10 @@ use darkfi::{
         ChannelPtr, Message, MessageSubscription, ProtocolBase, ProtocolBasePtr,
         ProtocolJobsManager, ProtocolJobsManagerPtr,
     },
-    validator::{consensus::Proposal, ValidatorPtr},
+    validator::{
+        consensus::{Proposal, MAX_BLOCK_SIZE},
+        ValidatorPtr,
+    },
     Result,
 };
 use darkfi_serial::{SerialDecodable, SerialEncodable};
@@ -68,7 +71,7 @@ pub struct SyncResponse {
     pub blocks: Vec<BlockInfo>,
 }
 
-impl_p2p_message!(SyncResponse, "syncresponse");
+impl_p2p_message!(SyncResponse, "syncresponse", BATCH * MAX_BLOCK_SIZE);
 
 /// Auxiliary structure used for fork chain syncing.
 #[derive(Debug, SerialEncodable, SerialDecodable)]
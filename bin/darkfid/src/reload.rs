@@ -0,0 +1,194 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::HashMap, sync::Arc};
+
+use log::{error, info};
+use structopt_toml::StructOptToml;
+use tinyjson::JsonValue;
+
+use darkfi::{
+    net::Settings,
+    rpc::{auth::RpcAuth, ratelimit::RpcRateLimit},
+    util::{cli::set_log_level, path::get_config_path},
+    Error, Result,
+};
+
+use crate::{utils::parse_blockchain_config, Args, BlockchainNetwork, Darkfid, CONFIG_FILE};
+
+/// Outcome of a configuration reload
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// Changed settings that were applied at runtime
+    pub applied: Vec<&'static str>,
+    /// Changed settings that only take effect after a restart
+    pub restart_required: Vec<&'static str>,
+}
+
+impl ReloadReport {
+    pub fn to_json(&self) -> JsonValue {
+        let names = |v: &[&str]| {
+            JsonValue::Array(v.iter().map(|n| JsonValue::String(n.to_string())).collect())
+        };
+        JsonValue::Object(HashMap::from([
+            ("applied".to_string(), names(&self.applied)),
+            ("restart_required".to_string(), names(&self.restart_required)),
+        ]))
+    }
+}
+
+/// Build the JSON-RPC rate limiting policy from given arguments
+pub fn rpc_rate_limit_from_args(args: &Args) -> Result<Option<RpcRateLimit>> {
+    let mut rpc_rate_limit =
+        RpcRateLimit::from_config(args.rpc_rate_limit.as_deref(), &args.rpc_method_limit)?;
+    if let Some(threshold) = args.rpc_ban_threshold {
+        rpc_rate_limit = rpc_rate_limit.map(|rl| rl.with_ban(threshold, args.rpc_ban_duration));
+    }
+
+    Ok(rpc_rate_limit)
+}
+
+impl Darkfid {
+    /// Re-read the configuration file and apply its safe-to-change settings.
+    pub async fn reload_config(&self) -> Result<ReloadReport> {
+        let args = match Args::from_args_with_toml("") {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::reload", "Failed parsing arguments: {}", e);
+                return Err(Error::ParseFailed("Failed parsing arguments"))
+            }
+        };
+        let cfg_path = get_config_path(args.config, CONFIG_FILE)?;
+        let contents = smol::fs::read_to_string(cfg_path).await?;
+        let args = match Args::from_args_with_toml(&contents) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::reload", "Failed parsing configuration file: {}", e);
+                return Err(Error::ParseFailed("Failed parsing configuration file"))
+            }
+        };
+
+        self.apply_config(args).await
+    }
+
+    /// Apply the settings of given configuration that can change at runtime:
+    /// log verbosity, JSON-RPC authentication and rate limits, and the
+    /// inbound peer limit. Other changed settings are only reported, as
+    /// they need a restart to take effect.
+    pub async fn apply_config(&self, args: Args) -> Result<ReloadReport> {
+        let mut running = self.running_config.lock().await;
+        let Some((ref old_args, ref old_net)) = *running else {
+            return Err(Error::Custom("Node configuration is not loaded".to_string()))
+        };
+
+        // The network configuration is looked up the same way as on startup
+        let network = parse_blockchain_config(args.config.clone(), &args.network).await?;
+
+        // Parse everything first, so a bad config doesn't get half applied
+        let rpc_auth_changed = args.rpc_auth != old_args.rpc_auth;
+        let rpc_auth = if rpc_auth_changed { RpcAuth::from_config(&args.rpc_auth)? } else { None };
+        let rpc_rate_limit_changed = args.rpc_rate_limit != old_args.rpc_rate_limit ||
+            args.rpc_method_limit != old_args.rpc_method_limit ||
+            args.rpc_ban_threshold != old_args.rpc_ban_threshold ||
+            args.rpc_ban_duration != old_args.rpc_ban_duration;
+        let rpc_rate_limit =
+            if rpc_rate_limit_changed { rpc_rate_limit_from_args(&args)? } else { None };
+
+        let mut report = ReloadReport::default();
+
+        if args.verbose != old_args.verbose {
+            set_log_level(args.verbose);
+            report.applied.push("verbose");
+        }
+
+        if rpc_auth_changed {
+            *self.rpc_auth.write().unwrap() = rpc_auth.map(Arc::new);
+            report.applied.push("rpc_auth");
+        }
+
+        // Rebuilding the policy resets bans, and open connections
+        // keep the limits they were accepted with.
+        if rpc_rate_limit_changed {
+            *self.rpc_rate_limit.write().unwrap() = rpc_rate_limit.map(Arc::new);
+            report.applied.push("rpc_rate_limit");
+        }
+
+        if network.net.inbound_connections != old_net.net.inbound_connections {
+            let settings: Settings = network.net.clone().into();
+            self.p2p.set_inbound_connections(settings.inbound_connections);
+            report.applied.push("net.inbound_connections");
+        }
+
+        let restart_required = [
+            ("rpc_listen", args.rpc_listen != old_args.rpc_listen),
+            ("rpc_tls_identity", args.rpc_tls_identity != old_args.rpc_tls_identity),
+            ("rest_listen", args.rest_listen != old_args.rest_listen),
+            ("otlp_endpoint", args.otlp_endpoint != old_args.otlp_endpoint),
+            ("network", args.network != old_args.network),
+            ("log", args.log != old_args.log),
+            ("database", network.database != old_net.database),
+            ("wasm_cache", network.wasm_cache != old_net.wasm_cache),
+            ("threshold", network.threshold != old_net.threshold),
+            ("minerd_endpoint", network.minerd_endpoint != old_net.minerd_endpoint),
+            ("pow_target", network.pow_target != old_net.pow_target),
+            ("pow_fixed_difficulty", network.pow_fixed_difficulty != old_net.pow_fixed_difficulty),
+            ("miner", network.miner != old_net.miner),
+            ("recipient", network.recipient != old_net.recipient),
+            ("skip_sync", network.skip_sync != old_net.skip_sync),
+            ("ntp_clock_check", network.ntp_clock_check != old_net.ntp_clock_check),
+            ("min_free_space", network.min_free_space != old_net.min_free_space),
+            (
+                "net.outbound_connections",
+                network.net.outbound_connections != old_net.net.outbound_connections,
+            ),
+            ("net", !net_unchanged_besides_limits(&network, old_net)),
+        ];
+        report.restart_required =
+            restart_required.into_iter().filter(|(_, changed)| *changed).map(|(n, _)| n).collect();
+
+        info!(
+            target: "darkfid::reload",
+            "Configuration reloaded, applied: {:?}, restart required: {:?}",
+            report.applied, report.restart_required,
+        );
+
+        // Restart-required settings keep their running values, so they
+        // are reported again until the node is restarted.
+        let mut new_args = old_args.clone();
+        new_args.verbose = args.verbose;
+        new_args.rpc_auth = args.rpc_auth;
+        new_args.rpc_rate_limit = args.rpc_rate_limit;
+        new_args.rpc_method_limit = args.rpc_method_limit;
+        new_args.rpc_ban_threshold = args.rpc_ban_threshold;
+        new_args.rpc_ban_duration = args.rpc_ban_duration;
+        let mut new_net = old_net.clone();
+        new_net.net.inbound_connections = network.net.inbound_connections;
+        *running = Some((new_args, new_net));
+
+        Ok(report)
+    }
+}
+
+/// Check if the P2P settings only differ in their runtime adjustable or
+/// separately reported peer limits.
+fn net_unchanged_besides_limits(new: &BlockchainNetwork, old: &BlockchainNetwork) -> bool {
+    let mut net = new.net.clone();
+    net.inbound_connections = old.net.inbound_connections;
+    net.outbound_connections = old.net.outbound_connections;
+    net == old.net
+}
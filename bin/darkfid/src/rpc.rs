@@ -16,7 +16,11 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{collections::HashSet, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
 
 use async_trait::async_trait;
 use log::{debug, error};
@@ -24,12 +28,22 @@ use smol::lock::MutexGuard;
 use tinyjson::JsonValue;
 
 use darkfi::{
+    net::{
+        session::{SESSION_INBOUND, SESSION_MANUAL, SESSION_OUTBOUND, SESSION_SEED},
+        P2pPtr,
+    },
     rpc::{
+        auth::RpcAuth,
         jsonrpc::{ErrorCode, JsonError, JsonRequest, JsonResponse, JsonResult},
+        p2p_method::HandlerP2p,
+        ratelimit::RpcRateLimit,
+        rest::RestRoute,
+        schema::{RpcMethod, RpcParam},
         server::RequestHandler,
     },
     system::StoppableTaskPtr,
     util::time::Timestamp,
+    validator::utils::best_fork_index,
     Error, Result,
 };
 
@@ -38,6 +52,69 @@ use crate::{
     Darkfid,
 };
 
+/// Routes of the optional REST gateway, mapping `GET` endpoints onto
+/// the read-only JSON-RPC methods
+#[rustfmt::skip]
+pub const REST_ROUTES: &[RestRoute] = &[
+    RestRoute { path: "/status", method: "node.status", query: &[], paged: false },
+    RestRoute { path: "/blocks", method: "blockchain.get_blocks", query: &[], paged: true },
+    RestRoute { path: "/blocks/last", method: "blockchain.last_known_block", query: &[], paged: false },
+    RestRoute { path: "/blocks/{height}", method: "blockchain.get_block_by_height", query: &[], paged: false },
+    RestRoute { path: "/blocks/hash/{hash}", method: "blockchain.get_block_by_hash", query: &[], paged: false },
+    RestRoute { path: "/txs/{hash}", method: "blockchain.get_transaction", query: &[], paged: false },
+    RestRoute { path: "/peers", method: "p2p.get_info", query: &[], paged: false },
+    RestRoute { path: "/hosts/{color}", method: "p2p.get_hosts", query: &[], paged: true },
+    RestRoute { path: "/mempool", method: "mempool.list", query: &[], paged: true },
+    RestRoute { path: "/mempool/stats", method: "mempool.stats", query: &[], paged: false },
+    RestRoute { path: "/mempool/{hash}", method: "mempool.get", query: &[], paged: false },
+    RestRoute { path: "/contracts/{contract_id}/{tree}", method: "contract.db_get", query: &["key"], paged: false },
+    RestRoute { path: "/contracts/{contract_id}/{tree}/keys", method: "contract.db_keys", query: &["prefix"], paged: true },
+];
+
+/// Registry of the served JSON-RPC methods, listed by `rpc.methods`.
+/// Methods missing from it are refused by the server.
+#[rustfmt::skip]
+pub const RPC_METHODS: &[RpcMethod] = &[
+    RpcMethod { name: "rpc.methods", params: &[], returns: "array of method descriptions" },
+    RpcMethod { name: "ping", params: &[], returns: "string" },
+    RpcMethod { name: "clock", params: &[], returns: "string" },
+    RpcMethod { name: "dnet_switch", params: &[RpcParam::required("enabled", "bool")], returns: "bool" },
+    RpcMethod { name: "ping_miner", params: &[], returns: "bool" },
+    RpcMethod { name: "node.status", params: &[], returns: "object" },
+    RpcMethod { name: "node.reload_config", params: &[], returns: "object" },
+    RpcMethod { name: "p2p.get_info", params: &[], returns: "object" },
+    RpcMethod { name: "p2p.get_hosts", params: &[RpcParam::required("color", "string"), RpcParam::optional("page", "page")], returns: "page of host addresses" },
+    RpcMethod { name: "blockchain.get_block", params: &[RpcParam::required("height", "string")], returns: "base64 BlockInfo" },
+    RpcMethod { name: "blockchain.get_blocks", params: &[RpcParam::optional("page", "page")], returns: "page of base64 BlockInfo" },
+    RpcMethod { name: "blockchain.get_tx", params: &[RpcParam::required("hash", "string")], returns: "base64 Transaction" },
+    RpcMethod { name: "blockchain.get_block_by_height", params: &[RpcParam::required("height", "string")], returns: "base64 BlockInfo" },
+    RpcMethod { name: "blockchain.get_block_by_hash", params: &[RpcParam::required("hash", "string")], returns: "base64 BlockInfo" },
+    RpcMethod { name: "blockchain.get_transaction", params: &[RpcParam::required("hash", "string")], returns: "base64 Transaction" },
+    RpcMethod { name: "blockchain.last_known_block", params: &[], returns: "number" },
+    RpcMethod { name: "blockchain.lookup_zkas", params: &[RpcParam::required("contract_id", "string")], returns: "array of zkas namespaces and base64 bincodes" },
+    RpcMethod { name: "blockchain.lookup_token_metadata", params: &[RpcParam::required("token_id", "string")], returns: "base64 TokenMetadata or null" },
+    RpcMethod { name: "blockchain.subscribe_blocks", params: &[], returns: "subscription of base64 BlockInfo" },
+    RpcMethod { name: "blockchain.subscribe_txs", params: &[], returns: "subscription of transaction hashes" },
+    RpcMethod { name: "blockchain.subscribe_proposals", params: &[], returns: "subscription of base64 BlockInfo" },
+    RpcMethod { name: "blockchain.subscribe_scan", params: &[RpcParam::required("filter", "object")], returns: "subscription of filtered block data" },
+    RpcMethod { name: "blockchain.scan_block", params: &[RpcParam::required("height", "string"), RpcParam::required("filter", "object")], returns: "filtered block data" },
+    RpcMethod { name: "merge_mining_get_chain_id", params: &[], returns: "object" },
+    RpcMethod { name: "tx.simulate", params: &[RpcParam::required("tx", "string")], returns: "bool" },
+    RpcMethod { name: "tx.broadcast", params: &[RpcParam::required("tx", "string")], returns: "string" },
+    RpcMethod { name: "tx.calculate_fee", params: &[RpcParam::required("tx", "string"), RpcParam::required("include_fee", "bool")], returns: "number" },
+    RpcMethod { name: "tx.decode", params: &[RpcParam::required("tx", "string")], returns: "object" },
+    RpcMethod { name: "tx.pending", params: &[RpcParam::optional("page", "page")], returns: "page of transaction hashes" },
+    RpcMethod { name: "tx.clean_pending", params: &[RpcParam::optional("page", "page")], returns: "page of transaction hashes" },
+    RpcMethod { name: "contract.db_get", params: &[RpcParam::required("contract_id", "string"), RpcParam::required("tree", "string"), RpcParam::required("key", "string")], returns: "base64 value" },
+    RpcMethod { name: "contract.db_keys", params: &[RpcParam::required("contract_id", "string"), RpcParam::required("tree", "string"), RpcParam::required("prefix", "string"), RpcParam::optional("page", "page")], returns: "page of base64 keys" },
+    RpcMethod { name: "mempool.list", params: &[RpcParam::optional("page", "page")], returns: "page of transaction hashes" },
+    RpcMethod { name: "mempool.get", params: &[RpcParam::required("hash", "string")], returns: "base64 Transaction" },
+    RpcMethod { name: "mempool.stats", params: &[], returns: "object" },
+    RpcMethod { name: "get_block_template", params: &[RpcParam::required("wallet_address", "string"), RpcParam::optional("reserve_size", "number")], returns: "object" },
+    RpcMethod { name: "submit_block", params: &[RpcParam::required("blob", "string")], returns: "object" },
+    RpcMethod { name: "get_info", params: &[], returns: "object" },
+];
+
 #[async_trait]
 #[rustfmt::skip]
 impl RequestHandler for Darkfid {
@@ -48,21 +125,37 @@ impl RequestHandler for Darkfid {
             // =====================
             // Miscellaneous methods
             // =====================
+            "rpc.methods" => self.rpc_methods(req.id, req.params).await,
             "ping" => self.pong(req.id, req.params).await,
             "clock" => self.clock(req.id, req.params).await,
             "dnet_switch" => self.dnet_switch(req.id, req.params).await,
             "ping_miner" => self.ping_miner(req.id, req.params).await,
+            "node.status" => self.node_status(req.id, req.params).await,
+            "node.reload_config" => self.node_reload_config(req.id, req.params).await,
+
+            // ===========
+            // P2P methods
+            // ===========
+            "p2p.get_info" => self.p2p_get_info(req.id, req.params).await,
+            "p2p.get_hosts" => self.p2p_get_hosts(req.id, req.params).await,
 
             // ==================
             // Blockchain methods
             // ==================
             "blockchain.get_block" => self.blockchain_get_block(req.id, req.params).await,
+            "blockchain.get_blocks" => self.blockchain_get_blocks(req.id, req.params).await,
             "blockchain.get_tx" => self.blockchain_get_tx(req.id, req.params).await,
+            "blockchain.get_block_by_height" => self.blockchain_get_block_by_height(req.id, req.params).await,
+            "blockchain.get_block_by_hash" => self.blockchain_get_block_by_hash(req.id, req.params).await,
+            "blockchain.get_transaction" => self.blockchain_get_transaction(req.id, req.params).await,
             "blockchain.last_known_block" => self.blockchain_last_known_block(req.id, req.params).await,
             "blockchain.lookup_zkas" => self.blockchain_lookup_zkas(req.id, req.params).await,
+            "blockchain.lookup_token_metadata" => self.blockchain_lookup_token_metadata(req.id, req.params).await,
             "blockchain.subscribe_blocks" => self.blockchain_subscribe_blocks(req.id, req.params).await,
             "blockchain.subscribe_txs" =>  self.blockchain_subscribe_txs(req.id, req.params).await,
             "blockchain.subscribe_proposals" => self.blockchain_subscribe_proposals(req.id, req.params).await,
+            "blockchain.subscribe_scan" => self.blockchain_subscribe_scan(req.id, req.params).await,
+            "blockchain.scan_block" => self.blockchain_scan_block(req.id, req.params).await,
             "merge_mining_get_chain_id" => self.merge_mining_get_chain_id(req.id, req.params).await,
 
             // ===================
@@ -70,9 +163,31 @@ impl RequestHandler for Darkfid {
             // ===================
             "tx.simulate" => self.tx_simulate(req.id, req.params).await,
             "tx.broadcast" => self.tx_broadcast(req.id, req.params).await,
+            "tx.calculate_fee" => self.tx_calculate_fee(req.id, req.params).await,
+            "tx.decode" => self.tx_decode(req.id, req.params).await,
             "tx.pending" => self.tx_pending(req.id, req.params).await,
             "tx.clean_pending" => self.tx_pending(req.id, req.params).await,
 
+            // ================
+            // Contract methods
+            // ================
+            "contract.db_get" => self.contract_db_get(req.id, req.params).await,
+            "contract.db_keys" => self.contract_db_keys(req.id, req.params).await,
+
+            // ===============
+            // Mempool methods
+            // ===============
+            "mempool.list" => self.mempool_list(req.id, req.params).await,
+            "mempool.get" => self.mempool_get(req.id, req.params).await,
+            "mempool.stats" => self.mempool_stats(req.id, req.params).await,
+
+            // ====================================
+            // Mining pool compatibility methods
+            // ====================================
+            "get_block_template" => self.get_block_template(req.id, req.params).await,
+            "submit_block" => self.submit_block(req.id, req.params).await,
+            "get_info" => self.get_info(req.id, req.params).await,
+
             // ==============
             // Invalid method
             // ==============
@@ -83,6 +198,24 @@ impl RequestHandler for Darkfid {
     async fn connections_mut(&self) -> MutexGuard<'_, HashSet<StoppableTaskPtr>> {
         self.rpc_connections.lock().await
     }
+
+    fn auth(&self) -> Option<Arc<RpcAuth>> {
+        self.rpc_auth.read().unwrap().clone()
+    }
+
+    fn rate_limit(&self) -> Option<Arc<RpcRateLimit>> {
+        self.rpc_rate_limit.read().unwrap().clone()
+    }
+
+    fn methods(&self) -> &'static [RpcMethod] {
+        RPC_METHODS
+    }
+}
+
+impl HandlerP2p for Darkfid {
+    fn p2p(&self) -> P2pPtr {
+        self.p2p.clone()
+    }
 }
 
 impl Darkfid {
@@ -134,6 +267,165 @@ impl Darkfid {
         JsonResponse::new(JsonValue::Boolean(true), id).into()
     }
 
+    // RPCAPI:
+    // Returns an aggregated view of the node health, suitable for
+    // monitoring probes.
+    //
+    // **Params:**
+    // * `None`
+    //
+    // **Returns:**
+    // * `version`: Node version
+    // * `uptime`: Seconds since the node started
+    // * `synced`: Marks if the node is synced with the network
+    // * `miner`: Marks if the node is a miner
+    // * `finalized`: Height and hash of the last finalized block
+    // * `best`: Height and hash of the best fork tip, `null` if there are no forks
+    // * `forks`: Number of consensus forks
+    // * `peers`: Connected peer counts, in total, per session and per transport
+    // * `mempool`: Number of pending transactions
+    // * `database`: Database size on disk in bytes, along with blocks and transactions counts
+    // * `disk`: Last sampled database and log sizes and free disk space in bytes, and
+    //   whether free space is running low, `null` if not sampled yet
+    // * `read_only`: Marks if the node stopped writing new blocks due to low disk space
+    //
+    // --> {"jsonrpc": "2.0", "method": "node.status", "params": [], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"version": "0.4.1", "uptime": 3600, "synced": true, "miner": false, "finalized": {"height": 42, "hash": "b2c9..."}, "best": {"height": 45, "hash": "c3d1..."}, "forks": 1, "peers": {"total": 8, "sessions": {"inbound": 0, "outbound": 8, "manual": 0, "seed": 0}, "transports": {"tcp+tls": 6, "tor": 2}}, "mempool": 12, "database": {"size": 123456789, "blocks": 43, "txs": 57}, "disk": {"database": 123460000, "log": 4096, "free": 52000000000, "low_space": false}, "read_only": false}, "id": 1}
+    async fn node_status(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if !params.is_empty() {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        }
+
+        let blockchain = &self.validator.blockchain;
+        let (height, hash) = match blockchain.last() {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::node_status", "Failed fetching last block: {}", e);
+                return JsonError::new(ErrorCode::InternalError, None, id).into()
+            }
+        };
+        let finalized = block_ref(height, hash.to_string());
+
+        let forks = self.validator.consensus.forks.read().await;
+        let forks_len = forks.len();
+        let best = if forks.is_empty() {
+            JsonValue::Null
+        } else {
+            match best_fork_index(&forks).and_then(|i| forks[i].last_proposal()) {
+                Ok(proposal) => block_ref(proposal.block.header.height, proposal.hash.to_string()),
+                Err(e) => {
+                    error!(target: "darkfid::rpc::node_status", "Failed fetching best fork tip: {}", e);
+                    return JsonError::new(ErrorCode::InternalError, None, id).into()
+                }
+            }
+        };
+        drop(forks);
+
+        let mut sessions =
+            HashMap::from([("inbound", 0), ("outbound", 0), ("manual", 0), ("seed", 0)]);
+        let mut transports = HashMap::new();
+        let channels = self.p2p.hosts().channels().await;
+        for channel in &channels {
+            let session = match channel.session_type_id() {
+                SESSION_INBOUND => "inbound",
+                SESSION_OUTBOUND => "outbound",
+                SESSION_MANUAL => "manual",
+                SESSION_SEED => "seed",
+                _ => continue,
+            };
+            *sessions.get_mut(session).unwrap() += 1;
+            *transports.entry(channel.address().scheme().to_string()).or_insert(0) += 1;
+        }
+        let counts = |map: HashMap<String, usize>| {
+            JsonValue::Object(
+                map.into_iter().map(|(k, v)| (k, JsonValue::Number(v as f64))).collect(),
+            )
+        };
+        let peers = JsonValue::Object(HashMap::from([
+            ("total".to_string(), JsonValue::Number(channels.len() as f64)),
+            (
+                "sessions".to_string(),
+                counts(sessions.into_iter().map(|(k, v)| (k.to_string(), v)).collect()),
+            ),
+            ("transports".to_string(), counts(transports)),
+        ]));
+
+        let db_size = match blockchain.sled_db.size_on_disk() {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::node_status", "Failed fetching database size: {}", e);
+                return JsonError::new(ErrorCode::InternalError, None, id).into()
+            }
+        };
+        let database = JsonValue::Object(HashMap::from([
+            ("size".to_string(), JsonValue::Number(db_size as f64)),
+            ("blocks".to_string(), JsonValue::Number(blockchain.len() as f64)),
+            ("txs".to_string(), JsonValue::Number(blockchain.txs_len() as f64)),
+        ]));
+
+        let disk = match self.disk_usage.lock().await.clone() {
+            Some(usage) => JsonValue::Object(HashMap::from([
+                ("database".to_string(), JsonValue::Number(usage.database as f64)),
+                ("log".to_string(), JsonValue::Number(usage.log as f64)),
+                ("free".to_string(), JsonValue::Number(usage.free as f64)),
+                ("low_space".to_string(), JsonValue::Boolean(usage.low_space)),
+            ])),
+            None => JsonValue::Null,
+        };
+
+        let status = JsonValue::Object(HashMap::from([
+            ("version".to_string(), JsonValue::String(env!("CARGO_PKG_VERSION").to_string())),
+            ("uptime".to_string(), JsonValue::Number(self.started.elapsed().as_secs() as f64)),
+            ("synced".to_string(), JsonValue::Boolean(*self.validator.synced.read().await)),
+            ("miner".to_string(), JsonValue::Boolean(self.miner)),
+            ("finalized".to_string(), finalized),
+            ("best".to_string(), best),
+            ("forks".to_string(), JsonValue::Number(forks_len as f64)),
+            ("peers".to_string(), peers),
+            (
+                "mempool".to_string(),
+                JsonValue::Number(blockchain.transactions.pending.len() as f64),
+            ),
+            ("database".to_string(), database),
+            ("disk".to_string(), disk),
+            ("read_only".to_string(), JsonValue::Boolean(*self.validator.read_only.read().await)),
+        ]));
+
+        JsonResponse::new(status, id).into()
+    }
+
+    // RPCAPI:
+    // Re-reads the node configuration file and applies the settings that can
+    // change at runtime: log verbosity, JSON-RPC authentication tokens and
+    // rate limits, and the inbound peer limit. Changed settings that need a
+    // node restart to take effect are reported and keep their running values.
+    // The same reload is triggered by sending SIGHUP to the node.
+    //
+    // **Params:**
+    // * `None`
+    //
+    // **Returns:**
+    // * `applied`: Names of the changed settings that were applied
+    // * `restart_required`: Names of the changed settings that require a restart
+    //
+    // --> {"jsonrpc": "2.0", "method": "node.reload_config", "params": [], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"applied": ["verbose", "rpc_rate_limit"], "restart_required": ["rpc_listen"]}, "id": 1}
+    async fn node_reload_config(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if !params.is_empty() {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        }
+
+        match self.reload_config().await {
+            Ok(report) => JsonResponse::new(report.to_json(), id).into(),
+            Err(e) => {
+                error!(target: "darkfid::rpc::node_reload_config", "Failed reloading configuration: {}", e);
+                server_error(RpcError::ReloadConfigFailed, id, Some(&e.to_string()))
+            }
+        }
+    }
+
     pub async fn ping_miner_daemon(&self) -> Result<()> {
         debug!(target: "darkfid::ping_miner_daemon", "Pinging miner daemon...");
         self.miner_daemon_request("ping", JsonValue::Array(vec![])).await?;
@@ -152,3 +444,11 @@ impl Darkfid {
         Ok(rep)
     }
 }
+
+/// Auxiliary function to encode a block height and hash reference
+fn block_ref(height: u64, hash: String) -> JsonValue {
+    JsonValue::Object(HashMap::from([
+        ("height".to_string(), JsonValue::Number(height as f64)),
+        ("hash".to_string(), JsonValue::String(hash)),
+    ]))
+}
@@ -18,16 +18,24 @@
 
 use std::{collections::HashMap, str::FromStr};
 
-use darkfi_sdk::{crypto::ContractId, tx::TransactionHash};
+use darkfi_money_contract::model::TokenId;
+use darkfi_registry_contract::REGISTRY_CONTRACT_METADATA_TREE;
+use darkfi_sdk::{
+    crypto::{ContractId, REGISTRY_CONTRACT_ID},
+    tx::TransactionHash,
+};
 use darkfi_serial::{deserialize_async, serialize_async};
 use log::{debug, error};
 use tinyjson::JsonValue;
 
 use darkfi::{
-    blockchain::contract_store::SMART_CONTRACT_ZKAS_DB_NAME,
-    rpc::jsonrpc::{
-        ErrorCode::{InternalError, InvalidParams, ParseError},
-        JsonError, JsonResponse, JsonResult,
+    blockchain::{contract_store::SMART_CONTRACT_ZKAS_DB_NAME, HeaderHash},
+    rpc::{
+        jsonrpc::{
+            ErrorCode::{InternalError, InvalidParams, ParseError},
+            JsonError, JsonResponse, JsonResult,
+        },
+        pagination::{Page, PageRequest},
     },
     util::encoding::base64,
 };
@@ -75,6 +83,214 @@ impl Darkfid {
         JsonResponse::new(JsonValue::String(block), id).into()
     }
 
+    // RPCAPI:
+    // Pages through the blockchain database blocks, ordered by height.
+    // Returns a page of blocks upon success.
+    //
+    // **Params:**
+    // * `array[0]`: Optional pagination object `{"cursor": "...", "limit": n}`
+    //
+    // **Returns:**
+    // * `items`: [`BlockInfo`](https://darkrenaissance.github.io/darkfi/dev/darkfi/blockchain/block_store/struct.BlockInfo.html)
+    //   structs serialized into base64, ordered by height.
+    // * `next_cursor`: Cursor to request the next page with, `null` on the last page
+    //
+    // --> {"jsonrpc": "2.0", "method": "blockchain.get_blocks", "params": [{"limit": 10}], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"items": [...], "next_cursor": "9"}, "id": 1}
+    pub async fn blockchain_get_blocks(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        let Ok((params, page)) = PageRequest::split_params(params) else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+        if !params.is_empty() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let page = page.unwrap_or_default();
+        let Ok(cursor) = page.u64_cursor() else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+
+        // Fetch one extra record so we know if there is a next page
+        let blockchain = &self.validator.blockchain;
+        let order = match blockchain.blocks.get_order_page(cursor, page.limit + 1) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_get_blocks", "Failed fetching blocks order: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let hashes: Vec<_> = order.iter().take(page.limit).map(|(_, hash)| *hash).collect();
+        let blocks = match blockchain.get_blocks_by_hash(&hashes) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_get_blocks", "Failed fetching blocks by hash: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let mut items = Vec::with_capacity(blocks.len());
+        for block in &blocks {
+            items.push(JsonValue::String(base64::encode(&serialize_async(block).await)));
+        }
+
+        // The extra record only tells us that another page follows
+        let next_cursor =
+            if order.len() > page.limit { Some(order[page.limit - 1].0.to_string()) } else { None };
+
+        JsonResponse::new(Page { items, next_cursor }.to_json(), id).into()
+    }
+
+    // RPCAPI:
+    // Queries the blockchain database for a block in the given height.
+    // Returns the decoded block upon success.
+    //
+    // **Params:**
+    // * `array[0]`: `u64` Block height (as string)
+    //
+    // **Returns:**
+    // * Block object with its `hash`, `header` fields, `txs` hashes and `signature`
+    //
+    // --> {"jsonrpc": "2.0", "method": "blockchain.get_block_by_height", "params": ["0"], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"hash": "...", "header": {...}, "txs": [...], "signature": "..."}, "id": 1}
+    pub async fn blockchain_get_block_by_height(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let block_height = match params[0].get::<String>().unwrap().parse::<u64>() {
+            Ok(v) => v,
+            Err(_) => return JsonError::new(ParseError, None, id).into(),
+        };
+
+        let mut blocks = match self.validator.blockchain.get_blocks_by_heights(&[block_height]) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_get_block_by_height", "Failed fetching block by height: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let Some(block) = blocks.pop() else {
+            return server_error(RpcError::UnknownBlockHeight, id, None)
+        };
+
+        JsonResponse::new(block.into(), id).into()
+    }
+
+    // RPCAPI:
+    // Queries the blockchain database for a block with the given hash.
+    // Returns the decoded block upon success.
+    //
+    // **Params:**
+    // * `array[0]`: Hex-encoded block hash string
+    //
+    // **Returns:**
+    // * Block object with its `hash`, `header` fields, `txs` hashes and `signature`
+    //
+    // --> {"jsonrpc": "2.0", "method": "blockchain.get_block_by_hash", "params": ["BlockHash"], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"hash": "...", "header": {...}, "txs": [...], "signature": "..."}, "id": 1}
+    pub async fn blockchain_get_block_by_hash(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let Ok(block_hash) = HeaderHash::from_str(params[0].get::<String>().unwrap()) else {
+            return JsonError::new(ParseError, None, id).into()
+        };
+
+        let blockchain = &self.validator.blockchain;
+        match blockchain.blocks.contains(&block_hash) {
+            Ok(true) => {}
+            Ok(false) => return server_error(RpcError::UnknownBlock, id, None),
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_get_block_by_hash", "Failed checking block existence: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        }
+
+        let mut blocks = match blockchain.get_blocks_by_hash(&[block_hash]) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_get_block_by_hash", "Failed fetching block by hash: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        // Existence was checked above, and `get_blocks_by_hash` is strict
+        JsonResponse::new(blocks.pop().unwrap().into(), id).into()
+    }
+
+    // RPCAPI:
+    // Queries the blockchain database, and then the pending transactions
+    // store, for a given transaction.
+    // Returns the decoded transaction, along with its location.
+    //
+    // **Params:**
+    // * `array[0]`: Hex-encoded transaction hash string
+    //
+    // **Returns:**
+    // * `tx`: Transaction object with its `hash` and decoded `calls`
+    // * `block_height`: Height of the block containing it, `null` if pending
+    // * `block_index`: Position of it inside its block, `null` if pending
+    //
+    // --> {"jsonrpc": "2.0", "method": "blockchain.get_transaction", "params": ["TxHash"], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"tx": {...}, "block_height": 42, "block_index": 0}, "id": 1}
+    pub async fn blockchain_get_transaction(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let Ok(tx_hash) = TransactionHash::from_str(params[0].get::<String>().unwrap()) else {
+            return JsonError::new(ParseError, None, id).into()
+        };
+
+        let transactions = &self.validator.blockchain.transactions;
+        let (tx, location) = match transactions.get(&[tx_hash], false) {
+            Ok(mut txs) => match txs.pop().unwrap() {
+                Some(tx) => match transactions.get_location(&[tx_hash], true) {
+                    Ok(mut locations) => (tx, locations.pop().unwrap()),
+                    Err(e) => {
+                        error!(target: "darkfid::rpc::blockchain_get_transaction", "Failed fetching tx location: {}", e);
+                        return JsonError::new(InternalError, None, id).into()
+                    }
+                },
+                None => match transactions.get_pending(&[tx_hash], false) {
+                    Ok(mut txs) => match txs.pop().unwrap() {
+                        Some(tx) => (tx, None),
+                        None => return server_error(RpcError::UnknownTransaction, id, None),
+                    },
+                    Err(e) => {
+                        error!(target: "darkfid::rpc::blockchain_get_transaction", "Failed fetching pending tx: {}", e);
+                        return JsonError::new(InternalError, None, id).into()
+                    }
+                },
+            },
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_get_transaction", "Failed fetching tx by hash: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let (block_height, block_index) = match location {
+            Some((height, index)) => {
+                (JsonValue::Number(height as f64), JsonValue::Number(index as f64))
+            }
+            None => (JsonValue::Null, JsonValue::Null),
+        };
+
+        let result = HashMap::from([
+            ("tx".to_string(), tx.into()),
+            ("block_height".to_string(), block_height),
+            ("block_index".to_string(), block_index),
+        ]);
+        JsonResponse::new(JsonValue::Object(result), id).into()
+    }
+
     // RPCAPI:
     // Queries the blockchain database for a given transaction.
     // Returns a serialized `Transaction` object.
@@ -254,6 +470,67 @@ impl Darkfid {
         JsonResponse::new(JsonValue::Array(ret), id).into()
     }
 
+    // RPCAPI:
+    // Performs a lookup of the metadata registered for a given token ID in the
+    // native Registry contract.
+    //
+    // **Params:**
+    // * `array[0]`: base58-encoded token ID string
+    //
+    // **Returns:**
+    // * Serialized `TokenMetadata` object encoded with base64, or `null` if
+    //   the token has no registered metadata
+    //
+    // --> {"jsonrpc": "2.0", "method": "blockchain.lookup_token_metadata", "params": ["241vANigf1Cy3ytjM1KHXiVECxgxdK4yApddL8KcLssb"], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": "ABCD...", "id": 1}
+    pub async fn blockchain_lookup_token_metadata(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let token_id = params[0].get::<String>().unwrap();
+        let token_id = match TokenId::from_str(token_id) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_lookup_token_metadata", "Error decoding string to TokenId: {}", e);
+                return JsonError::new(InvalidParams, None, id).into()
+            }
+        };
+
+        let blockchain = self.validator.blockchain.clone();
+
+        let Ok(metadata_db) = blockchain.contracts.lookup(
+            &blockchain.sled_db,
+            &REGISTRY_CONTRACT_ID,
+            REGISTRY_CONTRACT_METADATA_TREE,
+        ) else {
+            error!(
+                target: "darkfid::rpc::blockchain_lookup_token_metadata",
+                "Did not find Registry contract metadata db"
+            );
+            return JsonError::new(InternalError, None, id).into()
+        };
+
+        let metadata = match metadata_db.get(serialize_async(&token_id).await) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(
+                    target: "darkfid::rpc::blockchain_lookup_token_metadata",
+                    "Internal sled error looking up token metadata: {}", e,
+                );
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        match metadata {
+            Some(metadata) => {
+                JsonResponse::new(JsonValue::String(base64::encode(&metadata)), id).into()
+            }
+            None => JsonResponse::new(JsonValue::Null, id).into(),
+        }
+    }
+
     // RPCAPI:
     // Returns the `chain_id` used for merge mining. A 32-byte hash of the genesis block.
     //
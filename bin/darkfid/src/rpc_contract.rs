@@ -0,0 +1,189 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    ops::Bound::{Excluded, Unbounded},
+    str::FromStr,
+};
+
+use darkfi_sdk::crypto::ContractId;
+use log::error;
+use tinyjson::JsonValue;
+
+use darkfi::{
+    rpc::{
+        jsonrpc::{
+            ErrorCode::{InternalError, InvalidParams},
+            JsonError, JsonResponse, JsonResult,
+        },
+        pagination::{Page, PageRequest},
+    },
+    util::encoding::base64,
+};
+
+use crate::{server_error, Darkfid, RpcError};
+
+impl Darkfid {
+    /// Auxiliary function to parse the `contract_id` and `tree_name` params
+    /// of a contract state query and open the corresponding canonical
+    /// state tree.
+    fn contract_db(
+        &self,
+        id: u16,
+        method: &str,
+        params: &[JsonValue],
+    ) -> std::result::Result<sled::Tree, JsonResult> {
+        if !params[0].is_string() || !params[1].is_string() {
+            return Err(JsonError::new(InvalidParams, None, id).into())
+        }
+
+        let contract_id = match ContractId::from_str(params[0].get::<String>().unwrap()) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::contract", "[{}] Error decoding string to ContractId: {}", method, e);
+                return Err(JsonError::new(InvalidParams, None, id).into())
+            }
+        };
+        let tree_name = params[1].get::<String>().unwrap();
+
+        let blockchain = &self.validator.blockchain;
+        match blockchain.contracts.lookup(&blockchain.sled_db, &contract_id, tree_name) {
+            Ok(db) => Ok(db),
+            Err(_) => {
+                error!(
+                    target: "darkfid::rpc::contract", "[{}] Did not find db {} for ContractId: {}",
+                    method, tree_name, contract_id,
+                );
+                Err(server_error(RpcError::ContractDbNotFound, id, None))
+            }
+        }
+    }
+
+    // RPCAPI:
+    // Queries the canonical state of a contract database for the value of
+    // the given key. Returns `null` if the key doesn't exist.
+    //
+    // **Params:**
+    // * `array[0]`: base58-encoded contract ID string
+    // * `array[1]`: Contract database (tree) name string
+    // * `array[2]`: base64-encoded key
+    //
+    // **Returns:**
+    // * base64-encoded value, or `null`
+    //
+    // --> {"jsonrpc": "2.0", "method": "contract.db_get", "params": ["BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o", "info", "ABCD..."], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": "EFGH...", "id": 1}
+    pub async fn contract_db_get(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 3 || !params[2].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let Some(key) = base64::decode(params[2].get::<String>().unwrap()) else {
+            return server_error(RpcError::ParseError, id, None)
+        };
+
+        let db = match self.contract_db(id, "contract_db_get", params) {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
+
+        match db.get(key) {
+            Ok(Some(value)) => {
+                JsonResponse::new(JsonValue::String(base64::encode(&value)), id).into()
+            }
+            Ok(None) => JsonResponse::new(JsonValue::Null, id).into(),
+            Err(e) => {
+                error!(target: "darkfid::rpc::contract_db_get", "Internal sled error: {}", e);
+                JsonError::new(InternalError, None, id).into()
+            }
+        }
+    }
+
+    // RPCAPI:
+    // Queries the canonical state of a contract database for the keys
+    // starting with the given prefix, in lexicographic order. An empty
+    // prefix lists all keys. Accepts an optional pagination object.
+    //
+    // **Params:**
+    // * `array[0]`: base58-encoded contract ID string
+    // * `array[1]`: Contract database (tree) name string
+    // * `array[2]`: base64-encoded key prefix
+    // * `array[3]`: Optional pagination object `{"cursor": "...", "limit": n}`
+    //
+    // **Returns:**
+    // * Page of base64-encoded keys
+    //
+    // --> {"jsonrpc": "2.0", "method": "contract.db_keys", "params": ["BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o", "info", "", {"limit": 100}], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"items": ["ABCD...", ...], "next_cursor": "EFGH..."}, "id": 1}
+    pub async fn contract_db_keys(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        let Ok((params, page)) = PageRequest::split_params(params) else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+        if params.len() != 3 || !params[2].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+        let page = page.unwrap_or_default();
+
+        let Some(prefix) = base64::decode(params[2].get::<String>().unwrap()) else {
+            return server_error(RpcError::ParseError, id, None)
+        };
+        let cursor = match page.cursor {
+            Some(ref c) => match base64::decode(c) {
+                Some(v) => Some(v),
+                None => return JsonError::new(InvalidParams, None, id).into(),
+            },
+            None => None,
+        };
+
+        let db = match self.contract_db(id, "contract_db_keys", params) {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
+
+        // Continue right after the cursor key, if any
+        let records = match cursor {
+            Some(ref c) => db.range::<&[u8], _>((Excluded(c.as_slice()), Unbounded)),
+            None => db.scan_prefix(&prefix),
+        };
+
+        let mut entries = Vec::with_capacity(page.limit + 1);
+        for record in records {
+            let key = match record {
+                Ok((key, _)) => key,
+                Err(e) => {
+                    error!(target: "darkfid::rpc::contract_db_keys", "Internal sled error: {}", e);
+                    return JsonError::new(InternalError, None, id).into()
+                }
+            };
+
+            if !key.starts_with(&prefix) {
+                break
+            }
+
+            let key = base64::encode(&key);
+            entries.push((key.clone(), JsonValue::String(key)));
+            if entries.len() > page.limit {
+                break
+            }
+        }
+
+        JsonResponse::new(Page::collect(entries, page.limit).to_json(), id).into()
+    }
+}
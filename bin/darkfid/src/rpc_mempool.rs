@@ -0,0 +1,319 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::HashMap, str::FromStr};
+
+use darkfi_money_contract::MoneyFunction;
+use darkfi_sdk::{crypto::MONEY_CONTRACT_ID, tx::TransactionHash};
+use darkfi_serial::{deserialize_async, serialize_async};
+use log::error;
+use tinyjson::JsonValue;
+
+use darkfi::{
+    rpc::{
+        jsonrpc::{
+            ErrorCode::{InternalError, InvalidParams, ParseError},
+            JsonError, JsonResponse, JsonResult,
+        },
+        pagination::{Page, PageRequest},
+    },
+    tx::Transaction,
+    Result,
+};
+
+use crate::{server_error, Darkfid, RpcError};
+
+/// Auxiliary function to grab the fee paid by a transaction, as found
+/// in its `Money::FeeV1` call. Returns `None` if the transaction
+/// doesn't contain a fee call.
+pub(crate) async fn tx_paid_fee(tx: &Transaction) -> Option<u64> {
+    for call in &tx.calls {
+        if call.data.contract_id == *MONEY_CONTRACT_ID &&
+            call.data.data.first() == Some(&(MoneyFunction::FeeV1 as u8)) &&
+            call.data.data.len() >= 9
+        {
+            return deserialize_async(&call.data.data[1..9]).await.ok()
+        }
+    }
+
+    None
+}
+
+/// Auxiliary function to convert an optional `u64` into a `JsonValue`.
+pub(crate) fn optional_number(value: Option<u64>) -> JsonValue {
+    match value {
+        Some(v) => JsonValue::Number(v as f64),
+        None => JsonValue::Null,
+    }
+}
+
+impl Darkfid {
+    /// Auxiliary function to build the mempool summary of a pending
+    /// transaction: its size, paid fee, receive time and age, along
+    /// with the indexes of the forks it is valid on.
+    async fn mempool_entry(
+        &self,
+        tx: &Transaction,
+        tx_hash: &TransactionHash,
+        forks: &[Vec<TransactionHash>],
+    ) -> Result<HashMap<String, JsonValue>> {
+        let received = self.validator.blockchain.transactions.get_pending_received(tx_hash)?;
+        let age = match received {
+            Some(received) => Some(received.elapsed()?.inner()),
+            None => None,
+        };
+
+        let valid_forks = forks
+            .iter()
+            .enumerate()
+            .filter(|(_, mempool)| mempool.contains(tx_hash))
+            .map(|(index, _)| JsonValue::Number(index as f64))
+            .collect();
+
+        Ok(HashMap::from([
+            ("hash".to_string(), JsonValue::String(tx_hash.to_string())),
+            ("size".to_string(), JsonValue::Number(serialize_async(tx).await.len() as f64)),
+            ("fee".to_string(), optional_number(tx_paid_fee(tx).await)),
+            ("received".to_string(), optional_number(received.map(|r| r.inner()))),
+            ("age".to_string(), optional_number(age)),
+            ("forks".to_string(), JsonValue::Array(valid_forks)),
+        ]))
+    }
+
+    /// Auxiliary function to grab a snapshot of each consensus fork's mempool.
+    async fn mempool_forks(&self) -> Vec<Vec<TransactionHash>> {
+        self.validator.consensus.forks.read().await.iter().map(|f| f.mempool.clone()).collect()
+    }
+
+    // RPCAPI:
+    // Queries the node mempool for its pending transactions, in the order
+    // they were received. Accepts an optional pagination object.
+    //
+    // **Params:**
+    // * `array[0]`: Optional pagination object `{"cursor": "...", "limit": n}`
+    //
+    // **Returns:**
+    // * Page of transaction summaries, where `received` is the UNIX timestamp
+    //   the node received the transaction at, `age` is in seconds, and `forks`
+    //   holds the indexes of the consensus forks the transaction is valid on.
+    //
+    // --> {"jsonrpc": "2.0", "method": "mempool.list", "params": [{"limit": 100}], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"items": [{"hash": "TxHash", "size": 1234, "fee": 5678, "received": 1700000000, "age": 42, "forks": [0, 1]}, ...], "next_cursor": "99"}, "id": 1}
+    pub async fn mempool_list(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        let Ok((params, page)) = PageRequest::split_params(params) else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+        if !params.is_empty() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+        let page = page.unwrap_or_default();
+        let Ok(cursor) = page.u64_cursor() else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+
+        if !*self.validator.synced.read().await {
+            error!(target: "darkfid::rpc::mempool_list", "Blockchain is not synced");
+            return server_error(RpcError::NotSynced, id, None)
+        }
+
+        let transactions = &self.validator.blockchain.transactions;
+        let order = match transactions.get_pending_order_page(cursor, page.limit + 1) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::mempool_list", "Failed fetching pending txs order: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let hashes: Vec<TransactionHash> = order.iter().map(|(_, hash)| *hash).collect();
+        let txs = match transactions.get_pending(&hashes, true) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::mempool_list", "Failed fetching pending txs: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let forks = self.mempool_forks().await;
+        let mut entries = Vec::with_capacity(order.len());
+        for ((index, tx_hash), tx) in order.iter().zip(txs.iter()) {
+            // Strict was used during .get_pending()
+            let tx = tx.as_ref().unwrap();
+            match self.mempool_entry(tx, tx_hash, &forks).await {
+                Ok(entry) => entries.push((index.to_string(), JsonValue::Object(entry))),
+                Err(e) => {
+                    error!(target: "darkfid::rpc::mempool_list", "Failed building mempool entry: {}", e);
+                    return JsonError::new(InternalError, None, id).into()
+                }
+            }
+        }
+
+        JsonResponse::new(Page::collect(entries, page.limit).to_json(), id).into()
+    }
+
+    // RPCAPI:
+    // Queries the node mempool for a given pending transaction.
+    // Along with the mempool summary returned by `mempool.list`, this
+    // includes the decoded transaction and the gas it uses when applied
+    // on top of the canonical chain. `gas` is `null` if the transaction
+    // is only valid on some fork.
+    //
+    // **Params:**
+    // * `array[0]`: Hex-encoded transaction hash string
+    //
+    // **Returns:**
+    // * Transaction mempool details
+    //
+    // --> {"jsonrpc": "2.0", "method": "mempool.get", "params": ["TxHash"], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"hash": "TxHash", "tx": {...}, "size": 1234, "fee": 5678, "gas": 4321, "received": 1700000000, "age": 42, "forks": [0]}, "id": 1}
+    pub async fn mempool_get(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let Ok(tx_hash) = TransactionHash::from_str(params[0].get::<String>().unwrap()) else {
+            return JsonError::new(ParseError, None, id).into()
+        };
+
+        if !*self.validator.synced.read().await {
+            error!(target: "darkfid::rpc::mempool_get", "Blockchain is not synced");
+            return server_error(RpcError::NotSynced, id, None)
+        }
+
+        let tx = match self.validator.blockchain.transactions.get_pending(&[tx_hash], false) {
+            Ok(mut txs) => match txs.pop().unwrap() {
+                Some(tx) => tx,
+                None => return server_error(RpcError::UnknownTransaction, id, None),
+            },
+            Err(e) => {
+                error!(target: "darkfid::rpc::mempool_get", "Failed fetching pending tx: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let forks = self.mempool_forks().await;
+        let mut entry = match self.mempool_entry(&tx, &tx_hash, &forks).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::mempool_get", "Failed building mempool entry: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        // Compute the gas used by simulating the transaction on the canonical chain
+        let next_block_height = match self.validator.blockchain.last() {
+            Ok((height, _)) => height + 1,
+            Err(e) => {
+                error!(target: "darkfid::rpc::mempool_get", "Failed fetching last block: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+        let gas = self
+            .validator
+            .add_transactions(&[tx.clone()], next_block_height, false, false)
+            .await
+            .ok();
+
+        entry.insert("tx".to_string(), tx.into());
+        entry.insert("gas".to_string(), optional_number(gas));
+        JsonResponse::new(JsonValue::Object(entry), id).into()
+    }
+
+    // RPCAPI:
+    // Queries the node mempool for aggregate statistics.
+    //
+    // **Params:**
+    // * `None`
+    //
+    // **Returns:**
+    // * `count`: Number of pending transactions
+    // * `size`: Total size of pending transactions, in bytes
+    // * `fees`: Total fees paid by pending transactions
+    // * `oldest_age`: Age of the oldest pending transaction in seconds, `null` if empty
+    // * `forks`: Number of pending transactions valid on each consensus fork
+    //
+    // --> {"jsonrpc": "2.0", "method": "mempool.stats", "params": [], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"count": 12, "size": 45678, "fees": 123456, "oldest_age": 300, "forks": [12, 10]}, "id": 1}
+    pub async fn mempool_stats(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if !params.is_empty() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        if !*self.validator.synced.read().await {
+            error!(target: "darkfid::rpc::mempool_stats", "Blockchain is not synced");
+            return server_error(RpcError::NotSynced, id, None)
+        }
+
+        let pending_txs = match self.validator.blockchain.get_pending_txs() {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::mempool_stats", "Failed fetching pending txs: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let transactions = &self.validator.blockchain.transactions;
+        let mut size = 0;
+        let mut fees: u64 = 0;
+        let mut oldest = None;
+        for tx in &pending_txs {
+            size += serialize_async(tx).await.len();
+            fees = fees.saturating_add(tx_paid_fee(tx).await.unwrap_or(0));
+            match transactions.get_pending_received(&tx.hash()) {
+                Ok(Some(received)) => {
+                    if oldest.map_or(true, |o| received < o) {
+                        oldest = Some(received);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!(target: "darkfid::rpc::mempool_stats", "Failed fetching pending tx receive time: {}", e);
+                    return JsonError::new(InternalError, None, id).into()
+                }
+            }
+        }
+
+        let oldest_age = match oldest.map(|o| o.elapsed()) {
+            Some(Ok(age)) => Some(age.inner()),
+            Some(Err(e)) => {
+                error!(target: "darkfid::rpc::mempool_stats", "Failed computing pending tx age: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+            None => None,
+        };
+
+        let forks = self
+            .mempool_forks()
+            .await
+            .iter()
+            .map(|mempool| JsonValue::Number(mempool.len() as f64))
+            .collect();
+
+        let result = HashMap::from([
+            ("count".to_string(), JsonValue::Number(pending_txs.len() as f64)),
+            ("size".to_string(), JsonValue::Number(size as f64)),
+            ("fees".to_string(), JsonValue::Number(fees as f64)),
+            ("oldest_age".to_string(), optional_number(oldest_age)),
+            ("forks".to_string(), JsonValue::Array(forks)),
+        ]);
+        JsonResponse::new(JsonValue::Object(result), id).into()
+    }
+}
@@ -0,0 +1,381 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Mining pool compatibility methods.
+//!
+//! These mirror the `get_block_template`, `submit_block` and `get_info`
+//! methods of the Monero daemon, using the same field names and
+//! difficulty encoding, so existing RandomX pool software can be pointed
+//! at darkfid with minimal adaptation. The differences pool software has
+//! to account for are:
+//!
+//! * The RandomX input is the BLAKE3 hash of the `blockhashing_blob`,
+//!   rather than the blob itself.
+//! * The nonce is an 8-byte little-endian integer, located at
+//!   `reserved_offset` in both blobs.
+//! * A hash is valid if its RandomX output, read as a big-endian integer,
+//!   is lower or equal to `(2^256 - 1) / difficulty`.
+//! * The RandomX key is the previous block hash, given as `seed_hash`.
+use std::{collections::HashMap, str::FromStr};
+
+use darkfi_money_contract::MONEY_CONTRACT_ZKAS_MINT_NS_V1;
+use darkfi_sdk::{
+    blockchain::expected_reward,
+    crypto::{PublicKey, SecretKey, MONEY_CONTRACT_ID},
+    hex::decode_hex,
+    AsHex,
+};
+use darkfi_serial::{deserialize_async_strict_limited, serialize_async};
+use log::{error, info};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use tinyjson::JsonValue;
+
+use darkfi::{
+    blockchain::{BlockInfo, HEADER_NONCE_OFFSET},
+    net,
+    rpc::jsonrpc::{
+        ErrorCode::{InternalError, InvalidParams},
+        JsonError, JsonResponse, JsonResult,
+    },
+    util::encoding::base64,
+    validator::{
+        consensus::{Fork, Proposal, MAX_BLOCK_SIZE},
+        pow::PoWModule,
+        utils::best_fork_index,
+    },
+    zk::{empty_witnesses, ProvingKey, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use crate::{
+    proto::ProposalMessage, server_error, task::miner::generate_transaction, Darkfid, RpcError,
+};
+
+/// Maximum number of outstanding block templates kept for submission
+const MAX_TEMPLATES: usize = 32;
+
+/// A block template handed out to a mining pool
+struct PoolTemplate {
+    /// Unsigned block, with a zero nonce
+    block: BlockInfo,
+    /// Secret key the block reward transaction was signed with,
+    /// used to sign the block once mined
+    secret: SecretKey,
+    /// Block reward recipient
+    recipient: PublicKey,
+}
+
+/// State of the mining pool compatibility methods
+#[derive(Default)]
+pub struct PoolState {
+    /// `Money::PoWRewardV1` mint circuit and its proving key, built on first use
+    mint: Option<(ZkBinary, ProvingKey)>,
+    /// Outstanding block templates, oldest first
+    templates: Vec<PoolTemplate>,
+}
+
+/// Auxiliary function to encode a difficulty the way the Monero daemon
+/// does: its low 64 bits as `{name}`, its next 64 bits as `{name}_top64`,
+/// and its full hex representation as `wide_{name}`.
+fn difficulty_fields(name: &str, difficulty: &BigUint) -> Vec<(String, JsonValue)> {
+    let digits = difficulty.to_u64_digits();
+    let low = digits.first().copied().unwrap_or(0);
+    let top = digits.get(1).copied().unwrap_or(0);
+
+    vec![
+        (name.to_string(), JsonValue::Number(low as f64)),
+        (format!("{name}_top64"), JsonValue::Number(top as f64)),
+        (format!("wide_{name}"), JsonValue::String(format!("{difficulty:#x}"))),
+    ]
+}
+
+impl Darkfid {
+    /// Auxiliary function to grab a copy of the best fork to mine on top
+    /// of, or a fresh fork over the canonical chain if there are none yet.
+    async fn pool_best_fork(&self) -> Result<Fork> {
+        let forks = self.validator.consensus.forks.read().await;
+        if forks.is_empty() {
+            drop(forks);
+            let module: PoWModule = self.validator.consensus.module.read().await.clone();
+            return Fork::new(self.validator.blockchain.clone(), module).await
+        }
+
+        forks[best_fork_index(&forks)?].full_clone()
+    }
+
+    /// Auxiliary function to create a new block template on top of the
+    /// given fork, paying the block reward to the given recipient.
+    async fn pool_new_template(&self, fork: &Fork, recipient: &PublicKey) -> Result<BlockInfo> {
+        let mut pool = self.pool.lock().await;
+        if pool.mint.is_none() {
+            info!(target: "darkfid::rpc::pool", "Generating zkas bin and proving keys...");
+            let (zkbin, _) = self.validator.blockchain.contracts.get_zkas(
+                &self.validator.blockchain.sled_db,
+                &MONEY_CONTRACT_ID,
+                MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+            )?;
+            let circuit = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin);
+            let pk = ProvingKey::build(zkbin.k, &circuit);
+            pool.mint = Some((zkbin, pk));
+        }
+        let (zkbin, pk) = pool.mint.as_ref().unwrap();
+
+        let next_block_height = fork.last_proposal()?.block.header.height + 1;
+        let secret = SecretKey::random(&mut OsRng);
+        let tx = generate_transaction(next_block_height, &secret, recipient, zkbin, pk)?;
+        let block = fork.generate_unsigned_block(tx).await?;
+
+        if pool.templates.len() >= MAX_TEMPLATES {
+            pool.templates.remove(0);
+        }
+        pool.templates.push(PoolTemplate { block: block.clone(), secret, recipient: *recipient });
+
+        Ok(block)
+    }
+
+    // RPCAPI:
+    // Mining pool compatibility method, mirroring the Monero daemon one.
+    // Returns a block template on top of the best fork, paying the block
+    // reward to the given address. Templates are reused until the best
+    // fork changes. The nonce is located at `reserved_offset` in both blobs,
+    // and the RandomX input is the BLAKE3 hash of the `blockhashing_blob`.
+    //
+    // **Params:**
+    // * `wallet_address`: base58-encoded block reward recipient public key
+    // * `reserve_size`: Ignored, accepted for compatibility
+    //
+    // **Returns:**
+    // * Block template object
+    //
+    // --> {"jsonrpc": "2.0", "method": "get_block_template", "params": {"wallet_address": "5ZHf...", "reserve_size": 8}, "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"blocktemplate_blob": "01b2...", "blockhashing_blob": "01b2...", "difficulty": 1234, "difficulty_top64": 0, "wide_difficulty": "0x4d2", "expected_reward": 2000000000, "height": 42, "prev_hash": "b2c9...", "reserved_offset": 49, "seed_hash": "b2c9...", "next_seed_hash": "", "status": "OK", "untrusted": false}, "id": 1}
+    pub async fn get_block_template(&self, id: u16, params: JsonValue) -> JsonResult {
+        let Some(params) = params.get::<HashMap<String, JsonValue>>() else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+        let Some(JsonValue::String(address)) = params.get("wallet_address") else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+        let Ok(recipient) = PublicKey::from_str(address) else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+
+        if !*self.validator.synced.read().await {
+            error!(target: "darkfid::rpc::get_block_template", "Blockchain is not synced");
+            return server_error(RpcError::NotSynced, id, None)
+        }
+
+        let fork = match self.pool_best_fork().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::get_block_template", "Failed grabbing best fork: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let (last, difficulty) = match (
+            fork.last_proposal(),
+            fork.module.next_mine_target_and_difficulty(),
+        ) {
+            (Ok(last), Ok((_, difficulty))) => (last, difficulty),
+            (Err(e), _) | (_, Err(e)) => {
+                error!(target: "darkfid::rpc::get_block_template", "Failed reading fork state: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        // Reuse an outstanding template if the fork tip didn't change
+        let pool = self.pool.lock().await;
+        let template = pool
+            .templates
+            .iter()
+            .find(|t| t.block.header.previous == last.hash && t.recipient == recipient)
+            .map(|t| t.block.clone());
+        drop(pool);
+
+        let block = match template {
+            Some(v) => v,
+            None => match self.pool_new_template(&fork, &recipient).await {
+                Ok(block) => block,
+                Err(e) => {
+                    error!(target: "darkfid::rpc::get_block_template", "Failed generating block template: {}", e);
+                    return JsonError::new(InternalError, None, id).into()
+                }
+            },
+        };
+
+        let height = block.header.height;
+        let prev_hash = block.header.previous.as_string();
+        let mut result = vec![
+            (
+                "blocktemplate_blob".to_string(),
+                JsonValue::String(serialize_async(&block).await.hex()),
+            ),
+            ("blockhashing_blob".to_string(), JsonValue::String(block.header.hashing_blob().hex())),
+            ("expected_reward".to_string(), JsonValue::Number(expected_reward(height) as f64)),
+            ("height".to_string(), JsonValue::Number(height as f64)),
+            ("prev_hash".to_string(), JsonValue::String(prev_hash.clone())),
+            ("reserved_offset".to_string(), JsonValue::Number(HEADER_NONCE_OFFSET as f64)),
+            ("seed_hash".to_string(), JsonValue::String(prev_hash)),
+            ("next_seed_hash".to_string(), JsonValue::String(String::new())),
+            ("status".to_string(), JsonValue::String("OK".to_string())),
+            ("untrusted".to_string(), JsonValue::Boolean(false)),
+        ];
+        result.extend(difficulty_fields("difficulty", &difficulty));
+
+        JsonResponse::new(JsonValue::Object(result.into_iter().collect()), id).into()
+    }
+
+    // RPCAPI:
+    // Mining pool compatibility method, mirroring the Monero daemon one.
+    // Submits a mined block, given as its block template blob with the
+    // nonce set. The block gets signed, appended as a proposal, and
+    // broadcasted to the network.
+    //
+    // **Params:**
+    // * `array[0]`: hex-encoded mined block template blob
+    //
+    // **Returns:**
+    // * Status object
+    //
+    // --> {"jsonrpc": "2.0", "method": "submit_block", "params": ["01b2..."], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"status": "OK"}, "id": 1}
+    pub async fn submit_block(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = match params.get::<Vec<JsonValue>>() {
+            Some(v) => v,
+            None => return JsonError::new(InvalidParams, None, id).into(),
+        };
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let bytes: std::result::Result<Vec<u8>, _> =
+            decode_hex(params[0].get::<String>().unwrap()).collect();
+        let Ok(bytes) = bytes else { return server_error(RpcError::ParseError, id, None) };
+        let Ok(mined) = deserialize_async_strict_limited::<BlockInfo>(&bytes, MAX_BLOCK_SIZE).await
+        else {
+            return server_error(RpcError::ParseError, id, None)
+        };
+
+        // Find the template the block was mined from
+        let mut header = mined.header.clone();
+        header.nonce = 0;
+        let mut pool = self.pool.lock().await;
+        let Some(index) = pool.templates.iter().position(|t| t.block.header == header) else {
+            return server_error(RpcError::UnknownBlockTemplate, id, None)
+        };
+        let template = pool.templates.remove(index);
+        drop(pool);
+
+        let mut block = template.block;
+        block.header.nonce = mined.header.nonce;
+        block.sign(&template.secret);
+
+        // Verify it extends the best fork and satisfies its target
+        let fork = match self.pool_best_fork().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::submit_block", "Failed grabbing best fork: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+        let extends = fork.last_proposal().is_ok_and(|p| p.hash == block.header.previous);
+        if !extends || fork.module.verify_current_block(&block).is_err() {
+            return server_error(RpcError::BlockNotAccepted, id, None)
+        }
+
+        // Append the mined block as a proposal
+        let proposal = Proposal::new(block);
+        if let Err(e) = self.validator.append_proposal(&proposal).await {
+            error!(target: "darkfid::rpc::submit_block", "Failed appending proposal: {}", e);
+            return server_error(RpcError::BlockNotAccepted, id, Some(&e.to_string()))
+        }
+        info!(target: "darkfid::rpc::submit_block", "Pool mined block {} appended", proposal.hash);
+
+        // Broadcast proposal to the network and notify subscribers
+        let message = ProposalMessage(proposal);
+        self.p2p.broadcast(&message).await;
+        let enc_prop = JsonValue::String(base64::encode(&serialize_async(&message).await));
+        self.subscribers.get("proposals").unwrap().notify(vec![enc_prop].into()).await;
+
+        let result = HashMap::from([("status".to_string(), JsonValue::String("OK".to_string()))]);
+        JsonResponse::new(JsonValue::Object(result), id).into()
+    }
+
+    // RPCAPI:
+    // Mining pool compatibility method, mirroring the Monero daemon one.
+    // Returns general information about the node state, based on its best fork.
+    //
+    // **Params:**
+    // * `None`
+    //
+    // **Returns:**
+    // * Node information object
+    //
+    // --> {"jsonrpc": "2.0", "method": "get_info", "params": [], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"height": 43, "top_block_hash": "b2c9...", "difficulty": 1234, "difficulty_top64": 0, "wide_difficulty": "0x4d2", "cumulative_difficulty": 98765, "cumulative_difficulty_top64": 0, "wide_cumulative_difficulty": "0x181cd", "target": 90, "tx_pool_size": 3, "incoming_connections_count": 2, "outgoing_connections_count": 8, "synchronized": true, "status": "OK", "untrusted": false}, "id": 1}
+    pub async fn get_info(&self, id: u16, _params: JsonValue) -> JsonResult {
+        let fork = match self.pool_best_fork().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::get_info", "Failed grabbing best fork: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let (last, difficulty) = match (fork.last_proposal(), fork.module.next_difficulty()) {
+            (Ok(last), Ok(difficulty)) => (last, difficulty),
+            (Err(e), _) | (_, Err(e)) => {
+                error!(target: "darkfid::rpc::get_info", "Failed reading fork state: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        let (mut incoming, mut outgoing) = (0, 0);
+        for channel in self.p2p.hosts().channels().await {
+            match channel.session_type_id() {
+                net::session::SESSION_INBOUND => incoming += 1,
+                _ => outgoing += 1,
+            }
+        }
+
+        let tx_pool_size = self.validator.blockchain.transactions.pending_order.len();
+        let synced = *self.validator.synced.read().await;
+
+        let mut result = vec![
+            ("height".to_string(), JsonValue::Number((last.block.header.height + 1) as f64)),
+            ("top_block_hash".to_string(), JsonValue::String(last.hash.as_string())),
+            ("target".to_string(), JsonValue::Number(fork.module.target as f64)),
+            ("tx_pool_size".to_string(), JsonValue::Number(tx_pool_size as f64)),
+            ("incoming_connections_count".to_string(), JsonValue::Number(incoming as f64)),
+            ("outgoing_connections_count".to_string(), JsonValue::Number(outgoing as f64)),
+            ("synchronized".to_string(), JsonValue::Boolean(synced)),
+            ("status".to_string(), JsonValue::String("OK".to_string())),
+            ("untrusted".to_string(), JsonValue::Boolean(false)),
+        ];
+        result.extend(difficulty_fields("difficulty", &difficulty));
+        result.extend(difficulty_fields(
+            "cumulative_difficulty",
+            &fork.module.cummulative_difficulty,
+        ));
+
+        JsonResponse::new(JsonValue::Object(result.into_iter().collect()), id).into()
+    }
+}
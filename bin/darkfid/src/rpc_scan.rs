@@ -0,0 +1,345 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::HashMap, str::FromStr};
+
+use darkfi_money_contract::{
+    client::MoneyNote,
+    model::{
+        Coin, MoneyBridgeBurnParamsV1, MoneyBridgeMintParamsV1, MoneyFeeParamsV1,
+        MoneyGenesisMintParamsV1, MoneyNftBurnParamsV1, MoneyNftMintParamsV1,
+        MoneyPoWRewardParamsV1, MoneyTokenMintParamsV1, MoneyTransferParamsV1, Nullifier,
+    },
+    MoneyFunction,
+};
+use darkfi_sdk::crypto::{note::AeadEncryptedNote, SecretKey, MONEY_CONTRACT_ID};
+use darkfi_serial::{deserialize_async, serialize_async};
+use log::{error, warn};
+use tinyjson::JsonValue;
+
+use darkfi::{
+    blockchain::BlockInfo,
+    rpc::jsonrpc::{
+        ErrorCode::{InternalError, InvalidParams, ParseError},
+        JsonError, JsonResponse, JsonResult, JsonSubscriber,
+    },
+    tx::Transaction,
+    util::encoding::base64,
+    Result,
+};
+
+use crate::{server_error, Darkfid, RpcError};
+
+/// Wallet scanning filter of a scan subscription
+pub struct ScanFilter {
+    /// Secret keys to trial-decrypt output notes with
+    secrets: Vec<SecretKey>,
+    /// Explicit coins to match outputs against
+    coins: Vec<Coin>,
+    /// Explicit nullifiers to match spends against
+    nullifiers: Vec<Nullifier>,
+}
+
+impl ScanFilter {
+    /// Parse a filter from its JSON-RPC object. Returns `None` if it is
+    /// malformed or matches nothing.
+    fn from_json(filter: &HashMap<String, JsonValue>) -> Option<Self> {
+        fn parse_list<T: FromStr>(
+            filter: &HashMap<String, JsonValue>,
+            key: &str,
+        ) -> Option<Vec<T>> {
+            let Some(value) = filter.get(key) else { return Some(vec![]) };
+            let list = value.get::<Vec<JsonValue>>()?;
+            list.iter().map(|v| T::from_str(v.get::<String>()?).ok()).collect()
+        }
+
+        let filter = Self {
+            secrets: parse_list(filter, "secrets")?,
+            coins: parse_list(filter, "coins")?,
+            nullifiers: parse_list(filter, "nullifiers")?,
+        };
+
+        if filter.secrets.is_empty() && filter.coins.is_empty() && filter.nullifiers.is_empty() {
+            return None
+        }
+
+        Some(filter)
+    }
+
+    /// Check if the given output matches the filter
+    fn matches_output(&self, coin: &Coin, note: &AeadEncryptedNote) -> bool {
+        self.coins.contains(coin) ||
+            self.secrets.iter().any(|secret| note.decrypt::<MoneyNote>(secret).is_ok())
+    }
+}
+
+/// A registered scan subscription
+pub struct ScanSubscription {
+    /// Subscription filter
+    filter: ScanFilter,
+    /// Subscriber the filtered data gets pushed to
+    subscriber: JsonSubscriber,
+    /// Marks if the subscription was notified at least once, so the server
+    /// had time to subscribe the client to it
+    notified: bool,
+}
+
+/// Money contract data of a block relevant for wallet scanning
+#[derive(Default)]
+struct BlockMoneyData {
+    /// Published nullifiers
+    nullifiers: Vec<Nullifier>,
+    /// New coins in Merkle tree order, along with their encrypted notes
+    outputs: Vec<(Coin, Option<AeadEncryptedNote>)>,
+}
+
+impl BlockMoneyData {
+    /// Append the Money contract data of the given transaction
+    async fn append_tx(&mut self, tx: &Transaction) -> Result<()> {
+        for call in &tx.calls {
+            let call = &call.data;
+            if call.contract_id != *MONEY_CONTRACT_ID || call.data.is_empty() {
+                continue
+            }
+
+            let data = &call.data[1..];
+            match MoneyFunction::try_from(call.data[0]) {
+                Ok(MoneyFunction::FeeV1) => {
+                    let params: MoneyFeeParamsV1 = deserialize_async(data).await?;
+                    self.nullifiers.push(params.input.nullifier);
+                    self.outputs.push((params.output.coin, Some(params.output.note)));
+                }
+                Ok(MoneyFunction::GenesisMintV1) => {
+                    let params: MoneyGenesisMintParamsV1 = deserialize_async(data).await?;
+                    self.outputs.push((params.output.coin, Some(params.output.note)));
+                }
+                Ok(MoneyFunction::TransferV1) | Ok(MoneyFunction::OtcSwapV1) => {
+                    let params: MoneyTransferParamsV1 = deserialize_async(data).await?;
+                    self.nullifiers.extend(params.inputs.iter().map(|i| i.nullifier));
+                    self.outputs.extend(params.outputs.into_iter().map(|o| (o.coin, Some(o.note))));
+                }
+                Ok(MoneyFunction::TokenMintV1) => {
+                    let params: MoneyTokenMintParamsV1 = deserialize_async(data).await?;
+                    self.outputs.push((params.coin, None));
+                }
+                Ok(MoneyFunction::PoWRewardV1) => {
+                    let params: MoneyPoWRewardParamsV1 = deserialize_async(data).await?;
+                    self.outputs.push((params.output.coin, Some(params.output.note)));
+                }
+                Ok(MoneyFunction::NftMintV1) => {
+                    let params: MoneyNftMintParamsV1 = deserialize_async(data).await?;
+                    self.outputs.push((params.coin, Some(params.note)));
+                }
+                Ok(MoneyFunction::NftBurnV1) => {
+                    let params: MoneyNftBurnParamsV1 = deserialize_async(data).await?;
+                    self.nullifiers.push(params.input.nullifier);
+                }
+                Ok(MoneyFunction::BridgeBurnV1) => {
+                    let params: MoneyBridgeBurnParamsV1 = deserialize_async(data).await?;
+                    self.nullifiers.push(params.input.nullifier);
+                }
+                Ok(MoneyFunction::BridgeMintV1) => {
+                    let params: MoneyBridgeMintParamsV1 = deserialize_async(data).await?;
+                    self.outputs.push((params.output.coin, Some(params.output.note)));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply the given filter, producing the JSON notification of the block.
+    /// Every coin is included since wallets need them to keep their Merkle
+    /// tree in sync, but only matching output notes and nullifiers are.
+    async fn filter(&self, block: &BlockInfo, filter: &ScanFilter) -> JsonValue {
+        let mut coins = Vec::with_capacity(self.outputs.len());
+        let mut outputs = vec![];
+        for (index, (coin, note)) in self.outputs.iter().enumerate() {
+            coins.push(JsonValue::String(coin.to_string()));
+
+            let Some(note) = note else { continue };
+            if !filter.matches_output(coin, note) {
+                continue
+            }
+
+            outputs.push(JsonValue::Object(HashMap::from([
+                ("index".to_string(), JsonValue::Number(index as f64)),
+                ("coin".to_string(), JsonValue::String(coin.to_string())),
+                (
+                    "note".to_string(),
+                    JsonValue::String(base64::encode(&serialize_async(note).await)),
+                ),
+            ])));
+        }
+
+        let nullifiers = self
+            .nullifiers
+            .iter()
+            .filter(|n| filter.nullifiers.contains(n))
+            .map(|n| JsonValue::String(n.to_string()))
+            .collect();
+
+        JsonValue::Object(HashMap::from([
+            ("height".to_string(), JsonValue::Number(block.header.height as f64)),
+            ("hash".to_string(), JsonValue::String(block.hash().to_string())),
+            ("coins".to_string(), JsonValue::Array(coins)),
+            ("outputs".to_string(), JsonValue::Array(outputs)),
+            ("nullifiers".to_string(), JsonValue::Array(nullifiers)),
+        ]))
+    }
+}
+
+impl Darkfid {
+    // RPCAPI:
+    // Initializes a wallet scanning subscription. Instead of full blocks,
+    // `darkfid` will only push the Money contract data of new finalized
+    // blocks relevant to the given filter: every new coin, so the wallet can
+    // keep its Merkle tree in sync, the encrypted notes of the matching
+    // outputs, and the matching nullifiers. An output matches if one of the
+    // given secret keys decrypts its note, or if its coin was given
+    // explicitly. Note that handing out secret keys lets the node learn
+    // which outputs belong to the wallet, so explicit filters should be
+    // preferred for untrusted nodes.
+    //
+    // **Params:**
+    // * `array[0]`: Filter object, with optional lists of base58-encoded
+    //   `secrets`, `coins` and `nullifiers`. At least one must be non-empty.
+    //
+    // **Returns:**
+    // * Notifications with the filtered data of each new block
+    //
+    // --> {"jsonrpc": "2.0", "method": "blockchain.subscribe_scan", "params": [{"secrets": ["7Cbh..."], "nullifiers": ["Bq3p..."]}], "id": 1}
+    // <-- {"jsonrpc": "2.0", "method": "blockchain.subscribe_scan", "params": [{"height": 42, "hash": "b2c9...", "coins": ["5qpc...", ...], "outputs": [{"index": 1, "coin": "5qpc...", "note": "ABCD..."}], "nullifiers": ["Bq3p..."]}]}
+    pub async fn blockchain_subscribe_scan(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 1 || !params[0].is_object() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let Some(filter) =
+            ScanFilter::from_json(params[0].get::<HashMap<String, JsonValue>>().unwrap())
+        else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+
+        let subscriber = JsonSubscriber::new("blockchain.subscribe_scan");
+        self.scan_subscriptions.lock().await.push(ScanSubscription {
+            filter,
+            subscriber: subscriber.clone(),
+            notified: false,
+        });
+
+        subscriber.into()
+    }
+
+    // RPCAPI:
+    // Queries the blockchain database for the block in the given height,
+    // and returns its Money contract data filtered the same way as the
+    // `blockchain.subscribe_scan` notifications. Wallets use it to replay
+    // past blocks without downloading them in full.
+    //
+    // **Params:**
+    // * `array[0]`: `u64` Block height (as string)
+    // * `array[1]`: Filter object, as in `blockchain.subscribe_scan`
+    //
+    // **Returns:**
+    // * Filtered data of the block
+    //
+    // --> {"jsonrpc": "2.0", "method": "blockchain.scan_block", "params": ["42", {"secrets": ["7Cbh..."]}], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"height": 42, "hash": "b2c9...", "coins": ["5qpc...", ...], "outputs": [...], "nullifiers": []}, "id": 1}
+    pub async fn blockchain_scan_block(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 2 || !params[0].is_string() || !params[1].is_object() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let block_height = match params[0].get::<String>().unwrap().parse::<u64>() {
+            Ok(v) => v,
+            Err(_) => return JsonError::new(ParseError, None, id).into(),
+        };
+
+        let Some(filter) =
+            ScanFilter::from_json(params[1].get::<HashMap<String, JsonValue>>().unwrap())
+        else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
+
+        let blocks = match self.validator.blockchain.get_blocks_by_heights(&[block_height]) {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::blockchain_scan_block", "Failed fetching block by height: {}", e);
+                return JsonError::new(InternalError, None, id).into()
+            }
+        };
+
+        if blocks.is_empty() {
+            return server_error(RpcError::UnknownBlockHeight, id, None)
+        }
+
+        let mut data = BlockMoneyData::default();
+        for tx in &blocks[0].txs {
+            if let Err(e) = data.append_tx(tx).await {
+                error!(
+                    target: "darkfid::rpc::blockchain_scan_block",
+                    "Failed parsing Money data of tx {} in block {}: {}", tx.hash(), block_height, e,
+                );
+                return JsonError::new(InternalError, None, id).into()
+            }
+        }
+
+        JsonResponse::new(data.filter(&blocks[0], &filter).await, id).into()
+    }
+
+    /// Push the filtered data of the given blocks to every scan subscription,
+    /// dropping the ones whose clients went away.
+    pub async fn notify_scan_subscriptions(&self, blocks: &[BlockInfo]) {
+        let mut subscriptions = self.scan_subscriptions.lock().await;
+
+        let mut i = 0;
+        while i < subscriptions.len() {
+            if subscriptions[i].notified && subscriptions[i].subscriber.sub.is_empty().await {
+                subscriptions.swap_remove(i);
+                continue
+            }
+            i += 1;
+        }
+
+        if subscriptions.is_empty() {
+            return
+        }
+
+        for block in blocks {
+            let mut data = BlockMoneyData::default();
+            for tx in &block.txs {
+                if let Err(e) = data.append_tx(tx).await {
+                    warn!(
+                        target: "darkfid::rpc::notify_scan_subscriptions",
+                        "Failed parsing Money data of tx {} in block {}: {}", tx.hash(), block.header.height, e,
+                    );
+                }
+            }
+
+            for subscription in subscriptions.iter_mut() {
+                let notification = data.filter(block, &subscription.filter).await;
+                subscription.subscriber.notify(JsonValue::Array(vec![notification])).await;
+                subscription.notified = true;
+            }
+        }
+    }
+}
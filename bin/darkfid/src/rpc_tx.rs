@@ -16,21 +16,54 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use darkfi_serial::deserialize_async;
+use std::collections::HashMap;
+
+use darkfi_money_contract::client::fee_v1::FEE_CALL_GAS;
+use darkfi_sdk::{
+    crypto::{
+        ContractId, DAO_CONTRACT_ID, DEPLOYOOOR_CONTRACT_ID, MONEY_CONTRACT_ID,
+        REGISTRY_CONTRACT_ID,
+    },
+    hex::decode_hex,
+};
+use darkfi_serial::{deserialize_async_strict_limited, serialize_async};
 use log::error;
 use tinyjson::JsonValue;
 
 use darkfi::{
-    rpc::jsonrpc::{
-        ErrorCode::{InternalError, InvalidParams},
-        JsonError, JsonResponse, JsonResult,
+    rpc::{
+        jsonrpc::{
+            ErrorCode::{InternalError, InvalidParams},
+            JsonError, JsonResponse, JsonResult,
+        },
+        pagination::{Page, PageRequest},
     },
-    tx::Transaction,
+    tx::{Transaction, MAX_TX_SIZE},
     util::encoding::base64,
 };
 
 use super::Darkfid;
-use crate::{server_error, RpcError};
+use crate::{
+    rpc_mempool::{optional_number, tx_paid_fee},
+    server_error, RpcError,
+};
+
+/// Auxiliary function to grab the name of a native contract
+fn native_contract_name(contract_id: &ContractId) -> JsonValue {
+    let name = if *contract_id == *MONEY_CONTRACT_ID {
+        "money"
+    } else if *contract_id == *DAO_CONTRACT_ID {
+        "dao"
+    } else if *contract_id == *DEPLOYOOOR_CONTRACT_ID {
+        "deployooor"
+    } else if *contract_id == *REGISTRY_CONTRACT_ID {
+        "registry"
+    } else {
+        return JsonValue::Null
+    };
+
+    JsonValue::String(name.to_string())
+}
 
 impl Darkfid {
     // RPCAPI:
@@ -61,7 +94,7 @@ impl Darkfid {
             }
         };
 
-        let tx: Transaction = match deserialize_async(&tx_bytes).await {
+        let tx: Transaction = match deserialize_async_strict_limited(&tx_bytes, MAX_TX_SIZE).await {
             Ok(v) => v,
             Err(e) => {
                 error!(target: "darkfid::rpc::tx_simulate", "Failed deserializing bytes into Transaction: {}", e);
@@ -111,7 +144,7 @@ impl Darkfid {
             }
         };
 
-        let tx: Transaction = match deserialize_async(&tx_bytes).await {
+        let tx: Transaction = match deserialize_async_strict_limited(&tx_bytes, MAX_TX_SIZE).await {
             Ok(v) => v,
             Err(e) => {
                 error!(target: "darkfid::rpc::tx_broadcast", "Failed deserializing bytes into Transaction: {}", e);
@@ -146,11 +179,19 @@ impl Darkfid {
     // RPCAPI:
     // Queries the node pending transactions store to retrieve all transactions.
     // Returns a vector of hex-encoded transaction hashes.
+    // When given a pagination object `{"cursor": "...", "limit": n}`, returns
+    // a page of hashes as `{"items": [TxHash,...], "next_cursor": "..."}` instead.
     //
     // --> {"jsonrpc": "2.0", "method": "tx.pending", "params": [], "id": 1}
     // <-- {"jsonrpc": "2.0", "result": "[TxHash,...]", "id": 1}
+    //
+    // --> {"jsonrpc": "2.0", "method": "tx.pending", "params": [{"limit": 100}], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"items": [TxHash,...], "next_cursor": "99"}, "id": 1}
     pub async fn tx_pending(&self, id: u16, params: JsonValue) -> JsonResult {
         let params = params.get::<Vec<JsonValue>>().unwrap();
+        let Ok((params, page)) = PageRequest::split_params(params) else {
+            return JsonError::new(InvalidParams, None, id).into()
+        };
         if !params.is_empty() {
             return JsonError::new(InvalidParams, None, id).into()
         }
@@ -160,6 +201,26 @@ impl Darkfid {
             return server_error(RpcError::NotSynced, id, None)
         }
 
+        if let Some(page) = page {
+            let Ok(cursor) = page.u64_cursor() else {
+                return JsonError::new(InvalidParams, None, id).into()
+            };
+
+            let transactions = &self.validator.blockchain.transactions;
+            let order = match transactions.get_pending_order_page(cursor, page.limit + 1) {
+                Ok(v) => v,
+                Err(e) => {
+                    error!(target: "darkfid::rpc::tx_pending", "Failed fetching pending txs order: {}", e);
+                    return JsonError::new(InternalError, None, id).into()
+                }
+            };
+
+            let entries = order
+                .into_iter()
+                .map(|(index, hash)| (index.to_string(), JsonValue::String(hash.to_string())));
+            return JsonResponse::new(Page::collect(entries, page.limit).to_json(), id).into()
+        }
+
         let pending_txs = match self.validator.blockchain.get_pending_txs() {
             Ok(v) => v,
             Err(e) => {
@@ -209,4 +270,171 @@ impl Darkfid {
 
         JsonResponse::new(JsonValue::Array(pending_txs), id).into()
     }
+
+    // RPCAPI:
+    // Compute the fee a transaction has to pay, by running it through the
+    // validator's gas accounting on top of the canonical chain, without
+    // verifying its fee or applying its state changes. Wallets use it to
+    // set the fee before signing, instead of having the transaction rejected.
+    // 1 gas is paid with 1 unit of the native token.
+    //
+    // **Params:**
+    // * `array[0]`: base64-encoded serialized `Transaction`
+    // * `array[1]`: `true` if a `Money::FeeV1` call is yet to be appended to a
+    //   transaction without one, so its gas gets included
+    //
+    // **Returns:**
+    // * Required fee, in native token units
+    //
+    // --> {"jsonrpc": "2.0", "method": "tx.calculate_fee", "params": ["base64encodedTX", true], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": 41234567, "id": 1}
+    pub async fn tx_calculate_fee(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 2 || !params[0].is_string() || !params[1].is_bool() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        if !*self.validator.synced.read().await {
+            error!(target: "darkfid::rpc::tx_calculate_fee", "Blockchain is not synced");
+            return server_error(RpcError::NotSynced, id, None)
+        }
+
+        // Try to deserialize the transaction
+        let tx_enc = params[0].get::<String>().unwrap().trim();
+        let Some(tx_bytes) = base64::decode(tx_enc) else {
+            error!(target: "darkfid::rpc::tx_calculate_fee", "Failed decoding base64 transaction");
+            return server_error(RpcError::ParseError, id, None)
+        };
+
+        let tx: Transaction = match deserialize_async_strict_limited(&tx_bytes, MAX_TX_SIZE).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::tx_calculate_fee", "Failed deserializing bytes into Transaction: {}", e);
+                return server_error(RpcError::ParseError, id, None)
+            }
+        };
+
+        // Simulate the transaction on the canonical chain to find its gas
+        let simulation = match self.validator.blockchain.last() {
+            Ok((height, _)) => {
+                self.validator.add_transactions(&[tx.clone()], height + 1, false, false).await
+            }
+            Err(e) => Err(e),
+        };
+        let mut gas = match simulation {
+            Ok(gas) => gas,
+            Err(e) => {
+                error!(target: "darkfid::rpc::tx_calculate_fee", "Failed to simulate transaction: {}", e);
+                return server_error(RpcError::TxSimulationFail, id, None)
+            }
+        };
+
+        // The fee call to be appended uses a fixed amount of gas
+        let include_fee = *params[1].get::<bool>().unwrap();
+        if include_fee && tx_paid_fee(&tx).await.is_none() {
+            gas += FEE_CALL_GAS;
+        }
+
+        JsonResponse::new(JsonValue::Number(gas as f64), id).into()
+    }
+
+    // RPCAPI:
+    // Decodes a raw transaction without broadcasting it, returning a
+    // breakdown of its contents, useful for debugging transaction builders.
+    // The gas the transaction uses is estimated by simulating it on top of
+    // the canonical chain, without verifying its fee. If the simulation
+    // fails, `gas` is `null` and `error` holds the failure reason.
+    //
+    // **Params:**
+    // * `array[0]`: hex-encoded serialized `Transaction`
+    //
+    // **Returns:**
+    // * `hash`: Transaction hash
+    // * `size`: Serialized transaction size, in bytes
+    // * `calls`: Contract calls in order, along with their position in the call tree
+    //   and the number of proofs and signatures attached to them
+    // * `proofs`: Total number of ZK proofs
+    // * `signatures`: Total number of signatures
+    // * `fee`: Fee paid in the `Money::FeeV1` call, `null` if there is none
+    // * `gas`: Estimated gas used, which is also the minimum required fee
+    // * `error`: Simulation failure reason, `null` on success
+    //
+    // --> {"jsonrpc": "2.0", "method": "tx.decode", "params": ["0a1b2c..."], "id": 1}
+    // <-- {"jsonrpc": "2.0", "result": {"hash": "TxHash", "size": 1234, "calls": [{"index": 0, "contract_id": "BZHK...", "contract": "money", "function": 0, "data_size": 9, "parent": null, "children": [], "proofs": 1, "signatures": 1}, ...], "proofs": 3, "signatures": 2, "fee": 5678, "gas": 4321, "error": null}, "id": 1}
+    pub async fn tx_decode(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(InvalidParams, None, id).into()
+        }
+
+        let tx_bytes: std::result::Result<Vec<u8>, _> =
+            decode_hex(params[0].get::<String>().unwrap().trim()).collect();
+        let Ok(tx_bytes) = tx_bytes else {
+            error!(target: "darkfid::rpc::tx_decode", "Failed decoding hex transaction");
+            return server_error(RpcError::ParseError, id, None)
+        };
+
+        let tx: Transaction = match deserialize_async_strict_limited(&tx_bytes, MAX_TX_SIZE).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!(target: "darkfid::rpc::tx_decode", "Failed deserializing bytes into Transaction: {}", e);
+                return server_error(RpcError::ParseError, id, None)
+            }
+        };
+
+        let mut calls = Vec::with_capacity(tx.calls.len());
+        for (index, call) in tx.calls.iter().enumerate() {
+            let proofs = tx.proofs.get(index).map_or(0, |p| p.len());
+            let signatures = tx.signatures.get(index).map_or(0, |s| s.len());
+            calls.push(JsonValue::Object(HashMap::from([
+                ("index".to_string(), JsonValue::Number(index as f64)),
+                ("contract_id".to_string(), JsonValue::String(call.data.contract_id.to_string())),
+                ("contract".to_string(), native_contract_name(&call.data.contract_id)),
+                (
+                    "function".to_string(),
+                    optional_number(call.data.data.first().map(|f| *f as u64)),
+                ),
+                ("data_size".to_string(), JsonValue::Number(call.data.data.len() as f64)),
+                ("parent".to_string(), optional_number(call.parent_index.map(|p| p as u64))),
+                (
+                    "children".to_string(),
+                    JsonValue::Array(
+                        call.children_indexes
+                            .iter()
+                            .map(|c| JsonValue::Number(*c as f64))
+                            .collect(),
+                    ),
+                ),
+                ("proofs".to_string(), JsonValue::Number(proofs as f64)),
+                ("signatures".to_string(), JsonValue::Number(signatures as f64)),
+            ])));
+        }
+
+        // Estimate the gas used by simulating the transaction on the canonical chain
+        let simulation = match self.validator.blockchain.last() {
+            Ok((height, _)) => {
+                self.validator.add_transactions(&[tx.clone()], height + 1, false, false).await
+            }
+            Err(e) => Err(e),
+        };
+        let (gas, sim_error) = match simulation {
+            Ok(gas) => (JsonValue::Number(gas as f64), JsonValue::Null),
+            Err(e) => (JsonValue::Null, JsonValue::String(e.to_string())),
+        };
+
+        let proofs: usize = tx.proofs.iter().map(|p| p.len()).sum();
+        let signatures: usize = tx.signatures.iter().map(|s| s.len()).sum();
+        let decoded = JsonValue::Object(HashMap::from([
+            ("hash".to_string(), JsonValue::String(tx.hash().to_string())),
+            ("size".to_string(), JsonValue::Number(serialize_async(&tx).await.len() as f64)),
+            ("calls".to_string(), JsonValue::Array(calls)),
+            ("proofs".to_string(), JsonValue::Number(proofs as f64)),
+            ("signatures".to_string(), JsonValue::Number(signatures as f64)),
+            ("fee".to_string(), optional_number(tx_paid_fee(&tx).await)),
+            ("gas".to_string(), gas),
+            ("error".to_string(), sim_error),
+        ]));
+
+        JsonResponse::new(decoded, id).into()
+    }
 }
@@ -0,0 +1,141 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    rpc::clock_sync::ntp_clock_offset, system::sleep, validator::pow::BLOCK_FUTURE_TIME_LIMIT,
+    Result,
+};
+use log::{debug, info, warn};
+
+use crate::Darkfid;
+
+/// Interval between clock sanity checks, in seconds
+const CLOCK_CHECK_INTERVAL: u64 = 300;
+/// Clock skew above which we warn, in seconds. Blocks with skewed timestamps
+/// risk falling behind the median timestamp of their ancestors well before
+/// they exceed the consensus drift window.
+const CLOCK_SKEW_WARN_THRESHOLD: u64 = 60;
+
+/// Local clock skew from network time in seconds, by where it was sampled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockSkew {
+    /// Sampled from NTP
+    Ntp(i64),
+    /// Median of the times reported by connected peers
+    Peers(i64),
+}
+
+impl ClockSkew {
+    /// Skew in seconds, regardless of where it was sampled
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Self::Ntp(skew) | Self::Peers(skew) => *skew,
+        }
+    }
+}
+
+/// Async task used for periodically sampling the local clock skew, from
+/// NTP if enabled, otherwise from the median time reported by connected
+/// peers in their version messages. Warns when the skew gets large, and
+/// records it so the miner refuses to produce blocks while an NTP sampled
+/// skew exceeds the consensus drift window.
+pub async fn clock_task(node: &Darkfid, ntp: bool) -> Result<()> {
+    info!(target: "darkfid::task::clock_task", "Starting clock sanity task...");
+
+    loop {
+        let mut skew = None;
+        if ntp {
+            match ntp_clock_offset().await {
+                Ok(offset) => skew = Some(ClockSkew::Ntp(offset)),
+                Err(e) => warn!(
+                    target: "darkfid::task::clock_task",
+                    "NTP clock check failed, falling back to peers: {}", e,
+                ),
+            }
+        }
+        if skew.is_none() {
+            skew = node.p2p.peer_clock_offset().await.map(ClockSkew::Peers);
+        }
+
+        match skew {
+            Some(ClockSkew::Ntp(skew)) if skew.unsigned_abs() > BLOCK_FUTURE_TIME_LIMIT.inner() => {
+                warn!(
+                    target: "darkfid::task::clock_task",
+                    "Local clock deviates {}s from NTP time, beyond the consensus drift window!", skew,
+                )
+            }
+            Some(ClockSkew::Peers(skew))
+                if skew.unsigned_abs() > BLOCK_FUTURE_TIME_LIMIT.inner() =>
+            {
+                warn!(
+                    target: "darkfid::task::clock_task",
+                    "Peers report our clock deviating {}s, beyond the consensus drift window!", skew,
+                )
+            }
+            Some(skew) if skew.seconds().unsigned_abs() > CLOCK_SKEW_WARN_THRESHOLD => warn!(
+                target: "darkfid::task::clock_task",
+                "Local clock deviates {}s from network time, please check your system clock",
+                skew.seconds(),
+            ),
+            Some(skew) => {
+                debug!(target: "darkfid::task::clock_task", "Local clock skew: {:?}", skew)
+            }
+            None => debug!(target: "darkfid::task::clock_task", "No clock samples available"),
+        }
+        *node.clock_skew.lock().await = skew;
+
+        sleep(CLOCK_CHECK_INTERVAL).await;
+    }
+}
+
+/// Returns the recorded local clock skew, if it should stop block production.
+/// See [`mining_halting_skew`].
+pub async fn excessive_clock_skew(node: &Darkfid) -> Option<i64> {
+    mining_halting_skew(*node.clock_skew.lock().await)
+}
+
+/// Returns the given skew if it was sampled from NTP and exceeds the
+/// consensus drift window, in which case produced blocks would be rejected
+/// by the network. Peers report their own times, so peer sampled skews never
+/// halt the miner, or Sybil peers could stop it from producing blocks.
+fn mining_halting_skew(skew: Option<ClockSkew>) -> Option<i64> {
+    match skew? {
+        ClockSkew::Ntp(skew) if skew.unsigned_abs() > BLOCK_FUTURE_TIME_LIMIT.inner() => Some(skew),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mining_halting_skew() {
+        let limit = BLOCK_FUTURE_TIME_LIMIT.inner() as i64;
+
+        assert_eq!(mining_halting_skew(None), None);
+        assert_eq!(mining_halting_skew(Some(ClockSkew::Ntp(limit))), None);
+        assert_eq!(mining_halting_skew(Some(ClockSkew::Ntp(-limit))), None);
+        assert_eq!(mining_halting_skew(Some(ClockSkew::Ntp(limit + 1))), Some(limit + 1));
+        assert_eq!(mining_halting_skew(Some(ClockSkew::Ntp(-limit - 1))), Some(-limit - 1));
+
+        // Peer reported skews only warn
+        assert_eq!(mining_halting_skew(Some(ClockSkew::Peers(limit + 1))), None);
+        assert_eq!(mining_halting_skew(Some(ClockSkew::Peers(-limit - 1))), None);
+    }
+}
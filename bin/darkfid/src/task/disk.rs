@@ -0,0 +1,104 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::PathBuf;
+
+use darkfi::{
+    system::sleep,
+    util::file::{dir_size, free_space},
+    Result,
+};
+use log::{debug, error, info, warn};
+
+use crate::Darkfid;
+
+/// Interval between disk usage checks, in seconds
+const DISK_CHECK_INTERVAL: u64 = 60;
+
+/// Sampled disk usage of the node, in bytes
+#[derive(Clone, Debug, Default)]
+pub struct DiskUsage {
+    /// Size of the database directory
+    pub database: u64,
+    /// Size of the log file, if one is configured
+    pub log: u64,
+    /// Free space on the filesystem holding the database
+    pub free: u64,
+    /// Marks if free space is getting close to the configured minimum
+    pub low_space: bool,
+}
+
+/// Async task used for periodically sampling the database and log sizes,
+/// along with the free space left for the database. Warns when free space
+/// gets below twice `min_free_space`, and once below `min_free_space` puts
+/// the validator in read-only mode, so it stops writing new blocks and
+/// transactions instead of running out of space mid-write. Writes resume
+/// once free space recovers above the warning threshold. A zero
+/// `min_free_space` disables the read-only safeguard.
+pub async fn disk_task(
+    node: &Darkfid,
+    db_path: PathBuf,
+    log_path: Option<PathBuf>,
+    min_free_space: u64,
+) -> Result<()> {
+    info!(target: "darkfid::task::disk_task", "Starting disk usage task...");
+    let warn_free_space = min_free_space.saturating_mul(2);
+
+    loop {
+        let (db_path_, log_path_) = (db_path.clone(), log_path.clone());
+        let (database, log, free) = smol::unblock(move || -> Result<(u64, u64, u64)> {
+            let log = match log_path_ {
+                Some(path) if path.exists() => dir_size(&path)?,
+                _ => 0,
+            };
+            Ok((dir_size(&db_path_)?, log, free_space(&db_path_)?))
+        })
+        .await?;
+        debug!(
+            target: "darkfid::task::disk_task",
+            "Database: {} bytes, log: {} bytes, free: {} bytes", database, log, free,
+        );
+
+        let mut read_only = node.validator.read_only.write().await;
+        if min_free_space > 0 && free < min_free_space && !*read_only {
+            error!(
+                target: "darkfid::task::disk_task",
+                "Free disk space {} bytes is below {} bytes, entering read-only mode!",
+                free, min_free_space,
+            );
+            *read_only = true;
+        } else if *read_only && free >= warn_free_space {
+            info!(
+                target: "darkfid::task::disk_task",
+                "Free disk space recovered to {} bytes, leaving read-only mode", free,
+            );
+            *read_only = false;
+        } else if free < warn_free_space {
+            warn!(
+                target: "darkfid::task::disk_task",
+                "Free disk space is running low: {} bytes left", free,
+            );
+        }
+        drop(read_only);
+
+        let low_space = free < warn_free_space;
+        *node.disk_usage.lock().await = Some(DiskUsage { database, log, free, low_space });
+
+        sleep(DISK_CHECK_INTERVAL).await;
+    }
+}
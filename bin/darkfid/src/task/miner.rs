@@ -19,7 +19,7 @@
 use darkfi::{
     blockchain::BlockInfo,
     rpc::{jsonrpc::JsonNotification, util::JsonValue},
-    system::Subscription,
+    system::{sleep, Subscription},
     tx::{ContractCallLeaf, Transaction, TransactionBuilder},
     util::encoding::base64,
     validator::{
@@ -39,12 +39,17 @@ use darkfi_sdk::{
     ContractCall,
 };
 use darkfi_serial::{serialize_async, Encodable};
-use log::info;
+use log::{error, info};
 use num_bigint::BigUint;
 use rand::rngs::OsRng;
 use smol::channel::{Receiver, Sender};
 
-use crate::{proto::ProposalMessage, Darkfid};
+use crate::{proto::ProposalMessage, task::clock::excessive_clock_skew, Darkfid};
+
+/// Interval to wait before rechecking an excessive clock skew, in seconds
+const CLOCK_SKEW_RETRY_INTERVAL: u64 = 30;
+/// Interval to wait before rechecking if the database is writable again, in seconds
+const READ_ONLY_RETRY_INTERVAL: u64 = 60;
 
 // TODO: handle all ? so the task don't stop on errors
 
@@ -117,6 +122,27 @@ pub async fn miner_task(node: &Darkfid, recipient: &PublicKey, skip_sync: bool)
 
     // Start miner loop
     loop {
+        // Refuse to mine while NTP reports our clock skewed beyond the consensus
+        // drift window, since the network would reject our blocks.
+        if let Some(skew) = excessive_clock_skew(node).await {
+            error!(
+                target: "darkfid::task::miner_task",
+                "Local clock deviates {}s from NTP time, refusing to mine!", skew,
+            );
+            sleep(CLOCK_SKEW_RETRY_INTERVAL).await;
+            continue
+        }
+
+        // Mined blocks can't be appended while the database is read-only
+        if *node.validator.read_only.read().await {
+            error!(
+                target: "darkfid::task::miner_task",
+                "Database is in read-only mode, refusing to mine!",
+            );
+            sleep(READ_ONLY_RETRY_INTERVAL).await;
+            continue
+        }
+
         // Grab best current fork
         let forks = node.validator.consensus.forks.read().await;
         let extended_fork = forks[best_fork_index(&forks)?].full_clone()?;
@@ -274,7 +300,7 @@ async fn generate_next_block(
 }
 
 /// Auxiliary function to generate a Money::PoWReward transaction
-fn generate_transaction(
+pub(crate) fn generate_transaction(
     block_height: u64,
     secret: &SecretKey,
     recipient: &PublicKey,
@@ -26,3 +26,12 @@ pub use miner::miner_task;
 
 pub mod sync;
 pub use sync::sync_task;
+
+pub mod scan;
+pub use scan::scan_task;
+
+pub mod clock;
+pub use clock::clock_task;
+
+pub mod disk;
+pub use disk::disk_task;
@@ -0,0 +1,57 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    blockchain::BlockInfo, util::encoding::base64, validator::consensus::MAX_BLOCK_SIZE, Result,
+};
+use darkfi_serial::deserialize_async_strict_limited;
+use log::{info, warn};
+use tinyjson::JsonValue;
+
+use crate::Darkfid;
+
+/// Async task used for listening for new finalized blocks and pushing
+/// their filtered data to the wallet scan subscriptions.
+pub async fn scan_task(node: &Darkfid) -> Result<()> {
+    info!(target: "darkfid::task::scan_task", "Starting scan task...");
+
+    // Grab blocks subscriber and subscribe to it
+    let block_sub = node.subscribers.get("blocks").unwrap();
+    let subscription = block_sub.sub.clone().subscribe().await;
+
+    loop {
+        let notification = subscription.receive().await;
+        let Some(encoded_blocks) = notification.params.get::<Vec<JsonValue>>() else { continue };
+
+        let mut blocks = Vec::with_capacity(encoded_blocks.len());
+        for encoded_block in encoded_blocks {
+            let Some(bytes) = encoded_block.get::<String>().and_then(|b| base64::decode(b)) else {
+                warn!(target: "darkfid::task::scan_task", "Failed decoding block notification");
+                continue
+            };
+            match deserialize_async_strict_limited::<BlockInfo>(&bytes, MAX_BLOCK_SIZE).await {
+                Ok(block) => blocks.push(block),
+                Err(e) => {
+                    warn!(target: "darkfid::task::scan_task", "Failed deserializing block: {}", e)
+                }
+            }
+        }
+
+        node.notify_scan_subscriptions(&blocks).await;
+    }
+}
@@ -238,7 +238,7 @@ pub async fn generate_node(
     subscribers.insert("proposals", JsonSubscriber::new("blockchain.subscribe_proposals"));
 
     let p2p = spawn_p2p(settings, &validator, &subscribers, ex.clone()).await;
-    let node = Darkfid::new(p2p.clone(), validator, miner, subscribers, None).await;
+    let node = Darkfid::new(p2p.clone(), validator, miner, subscribers, None, None, None).await;
 
     p2p.start().await?;
 
@@ -20,3 +20,6 @@
 
 /// ChaCha box, used for channel encryption, and optionally DM encryption.
 pub mod saltbox;
+
+/// Double ratchet sessions, used for forward-secret DM encryption.
+pub mod ratchet;
@@ -0,0 +1,435 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Double ratchet sessions for forward-secret DMs, following
+//! <https://signal.org/docs/specifications/doubleratchet/>.
+//!
+//! Contacts only know each other's long-term ChaCha keys, so a session
+//! is bootstrapped with a one-time prekey, like in X3DH: a side wanting
+//! to talk publishes a fresh ratchet public key in a [`PREKEY_MAGIC`]
+//! DM, and the contact uses it as the first remote ratchet key. The
+//! prekey secret is deleted once a session was started with it, so no
+//! message, including the first ones, can be recovered from the
+//! long-term keys. The long-term shared secret is still mixed into the
+//! root key, authenticating both sides.
+
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit};
+use crypto_box::{aead::Aead, ChaChaBox};
+use darkfi_sdk::crypto::{diffie_hellman::sapling_ka_agree, PublicKey, SecretKey};
+use darkfi_serial::{async_trait, deserialize, serialize, SerialDecodable, SerialEncodable};
+use rand::rngs::OsRng;
+
+/// Prefix of a DM payload carrying a ratchet message, so it can be
+/// told apart from a plain `ChaChaBox` DM.
+pub const RATCHET_MAGIC: &[u8; 8] = b"DRATCHET";
+
+/// Prefix of a DM payload carrying a serialized prekey [`PublicKey`]
+/// the contact can start a ratchet session with.
+pub const PREKEY_MAGIC: &[u8; 8] = b"DRPREKEY";
+
+/// Maximum number of message keys we keep for out-of-order messages
+const MAX_SKIP: u32 = 1000;
+
+/// Header sent in the clear (within the `ChaChaBox`) along each message
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+struct RatchetHeader {
+    /// Sender's current ratchet public key
+    dh: PublicKey,
+    /// Number of messages in the sender's previous sending chain
+    pn: u32,
+    /// Number of this message in the sender's current sending chain
+    n: u32,
+}
+
+/// A message encrypted within a ratchet session
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+struct RatchetMessage {
+    header: RatchetHeader,
+    ciphertext: Vec<u8>,
+}
+
+/// Message key kept for a message we haven't received yet
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+struct SkippedKey {
+    dh: PublicKey,
+    n: u32,
+    key: [u8; 32],
+}
+
+/// State of a double ratchet session with a contact
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct RatchetSession {
+    /// Root chain key
+    root_key: [u8; 32],
+    /// Our current ratchet secret key
+    dh_self: SecretKey,
+    /// Contact's current ratchet public key
+    dh_remote: Option<PublicKey>,
+    /// Sending chain key
+    send_chain: Option<[u8; 32]>,
+    /// Receiving chain key
+    recv_chain: Option<[u8; 32]>,
+    /// Number of messages sent in the current sending chain
+    send_n: u32,
+    /// Number of messages received in the current receiving chain
+    recv_n: u32,
+    /// Number of messages sent in the previous sending chain
+    prev_send_n: u32,
+    /// Message keys of skipped messages
+    skipped: Vec<SkippedKey>,
+}
+
+/// Ratchet state kept for a contact
+#[derive(Clone, Debug, Default, SerialEncodable, SerialDecodable)]
+pub struct ContactRatchet {
+    /// Current session, if one was started
+    pub session: Option<RatchetSession>,
+    /// Secret of the prekey we published to the contact, deleted once
+    /// a session was started with it
+    pub prekey: Option<SecretKey>,
+    /// Latest prekey the contact published to us, used at most once
+    pub remote_prekey: Option<PublicKey>,
+}
+
+/// Derive the shared secret of a contact from its long-term `ChaChaBox`,
+/// by using the box as a PRF over a fixed input.
+pub fn shared_root(salt_box: &ChaChaBox) -> [u8; 32] {
+    let out = salt_box.encrypt((&[0u8; 24][..]).into(), b"darkirc-dm-ratchet".as_slice()).unwrap();
+    blake3::derive_key("darkirc dm ratchet root", &out)
+}
+
+/// Diffie-Hellman between a ratchet secret and public key
+fn dh(secret: &SecretKey, public: &PublicKey) -> Option<[u8; 32]> {
+    sapling_ka_agree(secret, public).ok().map(|shared| shared.to_bytes())
+}
+
+/// Root chain KDF, returning the new root key and a new chain key
+fn kdf_root(root_key: &[u8; 32], dh_out: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut hasher = blake3::Hasher::new_keyed(root_key);
+    hasher.update(dh_out);
+    let mut out = [0u8; 64];
+    hasher.finalize_xof().fill(&mut out);
+    (out[..32].try_into().unwrap(), out[32..].try_into().unwrap())
+}
+
+/// Symmetric chain KDF, returning the next chain key and a message key
+fn kdf_chain(chain_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let next = blake3::keyed_hash(chain_key, &[0x01]);
+    let message_key = blake3::keyed_hash(chain_key, &[0x02]);
+    (*next.as_bytes(), *message_key.as_bytes())
+}
+
+impl RatchetSession {
+    /// Start a session as the sending side, with the prekey the contact
+    /// published as the remote key. Returns `None` for an invalid prekey.
+    pub fn initiator(root: [u8; 32], prekey: &PublicKey) -> Option<Self> {
+        let dh_self = SecretKey::random(&mut OsRng);
+        let dh_remote = *prekey;
+        let (root_key, send_chain) = kdf_root(&root, &dh(&dh_self, &dh_remote)?);
+
+        Some(Self {
+            root_key,
+            dh_self,
+            dh_remote: Some(dh_remote),
+            send_chain: Some(send_chain),
+            recv_chain: None,
+            send_n: 0,
+            recv_n: 0,
+            prev_send_n: 0,
+            skipped: vec![],
+        })
+    }
+
+    /// Start a session as the receiving side with the secret of the
+    /// prekey we published, waiting for the contact's first message.
+    /// The prekey secret is replaced on the first ratchet step.
+    pub fn responder(root: [u8; 32], prekey: SecretKey) -> Self {
+        Self {
+            root_key: root,
+            dh_self: prekey,
+            dh_remote: None,
+            send_chain: None,
+            recv_chain: None,
+            send_n: 0,
+            recv_n: 0,
+            prev_send_n: 0,
+            skipped: vec![],
+        }
+    }
+
+    /// Check if the session has received any message from the contact.
+    pub fn has_received(&self) -> bool {
+        self.recv_chain.is_some()
+    }
+
+    /// Encrypt a message, advancing the sending chain.
+    /// Returns `None` if the session can't send yet, which is the case
+    /// for a responder until the contact's first message arrived.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Option<Vec<u8>> {
+        let (send_chain, message_key) = kdf_chain(self.send_chain.as_ref()?);
+        self.send_chain = Some(send_chain);
+
+        let header = RatchetHeader {
+            dh: PublicKey::from_secret(self.dh_self),
+            pn: self.prev_send_n,
+            n: self.send_n,
+        };
+        self.send_n += 1;
+
+        // Every message key is only used once, so a zero nonce is fine
+        let mut ciphertext = plaintext.to_vec();
+        ChaCha20Poly1305::new_from_slice(&message_key)
+            .unwrap()
+            .encrypt_in_place((&[0u8; 12][..]).into(), &serialize(&header), &mut ciphertext)
+            .unwrap();
+
+        Some(serialize(&RatchetMessage { header, ciphertext }))
+    }
+
+    /// Decrypt a message, advancing the receiving chain and performing a
+    /// ratchet step if the contact's ratchet key changed. The session is
+    /// left untouched if decryption fails.
+    pub fn decrypt(&mut self, data: &[u8]) -> Option<Vec<u8>> {
+        let message: RatchetMessage = deserialize(data).ok()?;
+        let mut session = self.clone();
+        let plaintext = session.decrypt_message(&message)?;
+        *self = session;
+        Some(plaintext)
+    }
+
+    fn decrypt_message(&mut self, message: &RatchetMessage) -> Option<Vec<u8>> {
+        let header = &message.header;
+
+        // Check if it's a message we skipped earlier
+        if let Some(i) = self.skipped.iter().position(|k| k.dh == header.dh && k.n == header.n) {
+            let key = self.skipped.remove(i).key;
+            return Self::open(&key, header, &message.ciphertext)
+        }
+
+        if self.dh_remote != Some(header.dh) {
+            self.skip_message_keys(header.pn)?;
+            self.ratchet_step(&header.dh)?;
+        }
+
+        self.skip_message_keys(header.n)?;
+        let (recv_chain, message_key) = kdf_chain(self.recv_chain.as_ref()?);
+        self.recv_chain = Some(recv_chain);
+        self.recv_n += 1;
+
+        Self::open(&message_key, header, &message.ciphertext)
+    }
+
+    /// Keep the message keys of the current receiving chain up to `until`.
+    fn skip_message_keys(&mut self, until: u32) -> Option<()> {
+        let Some(mut recv_chain) = self.recv_chain else { return Some(()) };
+        if until > self.recv_n + MAX_SKIP {
+            return None
+        }
+
+        let dh_remote = self.dh_remote?;
+        while self.recv_n < until {
+            let (next, key) = kdf_chain(&recv_chain);
+            recv_chain = next;
+            self.skipped.push(SkippedKey { dh: dh_remote, n: self.recv_n, key });
+            self.recv_n += 1;
+        }
+        self.recv_chain = Some(recv_chain);
+
+        // Drop the oldest keys if we're over the limit
+        let excess = self.skipped.len().saturating_sub(MAX_SKIP as usize);
+        self.skipped.drain(..excess);
+
+        Some(())
+    }
+
+    /// Perform a Diffie-Hellman ratchet step with the contact's new key.
+    fn ratchet_step(&mut self, dh_remote: &PublicKey) -> Option<()> {
+        self.prev_send_n = self.send_n;
+        self.send_n = 0;
+        self.recv_n = 0;
+        self.dh_remote = Some(*dh_remote);
+
+        let (root_key, recv_chain) = kdf_root(&self.root_key, &dh(&self.dh_self, dh_remote)?);
+        self.dh_self = SecretKey::random(&mut OsRng);
+        let (root_key, send_chain) = kdf_root(&root_key, &dh(&self.dh_self, dh_remote)?);
+
+        self.root_key = root_key;
+        self.recv_chain = Some(recv_chain);
+        self.send_chain = Some(send_chain);
+
+        Some(())
+    }
+
+    fn open(key: &[u8; 32], header: &RatchetHeader, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        let mut plaintext = ciphertext.to_vec();
+        ChaCha20Poly1305::new_from_slice(key)
+            .unwrap()
+            .decrypt_in_place((&[0u8; 12][..]).into(), &serialize(header), &mut plaintext)
+            .ok()?;
+        Some(plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start a session between an initiator and a responder, the
+    /// responder having published its prekey to the initiator.
+    fn session_pair() -> (RatchetSession, RatchetSession) {
+        let root = [42u8; 32];
+        let prekey = SecretKey::random(&mut OsRng);
+        let initiator = RatchetSession::initiator(root, &PublicKey::from_secret(prekey)).unwrap();
+        let responder = RatchetSession::responder(root, prekey);
+        (initiator, responder)
+    }
+
+    fn header(data: &[u8]) -> RatchetHeader {
+        deserialize::<RatchetMessage>(data).unwrap().header
+    }
+
+    #[test]
+    fn test_prekey_round_trip() {
+        let (mut alice, mut bob) = session_pair();
+
+        // The responder can't send before hearing from the initiator
+        assert!(bob.encrypt(b"too early").is_none());
+        assert!(!bob.has_received());
+
+        let msg = alice.encrypt(b"hello bob").unwrap();
+        assert_eq!(bob.decrypt(&msg).unwrap(), b"hello bob");
+        assert!(bob.has_received());
+
+        let msg = bob.encrypt(b"hello alice").unwrap();
+        assert_eq!(alice.decrypt(&msg).unwrap(), b"hello alice");
+        assert!(alice.has_received());
+    }
+
+    #[test]
+    fn test_ratchet_steps() {
+        let (mut alice, mut bob) = session_pair();
+
+        let mut alice_keys = vec![];
+        let mut bob_keys = vec![];
+        for round in 0..5u8 {
+            for i in 0..3u8 {
+                let msg = alice.encrypt(&[round, i]).unwrap();
+                alice_keys.push(header(&msg).dh);
+                assert_eq!(bob.decrypt(&msg).unwrap(), [round, i]);
+            }
+
+            for i in 0..2u8 {
+                let msg = bob.encrypt(&[i, round]).unwrap();
+                bob_keys.push(header(&msg).dh);
+                assert_eq!(alice.decrypt(&msg).unwrap(), [i, round]);
+            }
+        }
+
+        // Every round uses a fresh ratchet key on both sides
+        alice_keys.dedup();
+        bob_keys.dedup();
+        assert_eq!(alice_keys.len(), 5);
+        assert_eq!(bob_keys.len(), 5);
+        assert!(alice_keys.iter().all(|k| !bob_keys.contains(k)));
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let (mut alice, mut bob) = session_pair();
+
+        // Within a single chain
+        let msgs: Vec<_> = (0..3u8).map(|i| alice.encrypt(&[i]).unwrap()).collect();
+        assert_eq!(bob.decrypt(&msgs[2]).unwrap(), [2]);
+        assert_eq!(bob.skipped.len(), 2);
+        assert_eq!(bob.decrypt(&msgs[0]).unwrap(), [0]);
+        assert_eq!(bob.decrypt(&msgs[1]).unwrap(), [1]);
+        assert!(bob.skipped.is_empty());
+
+        // Across a ratchet step, the late message is from the previous chain
+        let late = alice.encrypt(b"late").unwrap();
+        let reply = bob.encrypt(b"reply").unwrap();
+        assert_eq!(alice.decrypt(&reply).unwrap(), b"reply");
+        let next = alice.encrypt(b"next").unwrap();
+        assert_eq!(header(&next).pn, 4);
+
+        assert_eq!(bob.decrypt(&next).unwrap(), b"next");
+        assert_eq!(bob.skipped.len(), 1);
+        assert_eq!(bob.decrypt(&late).unwrap(), b"late");
+        assert!(bob.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_max_skip() {
+        let (mut alice, mut bob) = session_pair();
+
+        let msgs: Vec<_> =
+            (0..MAX_SKIP + 2).map(|i| alice.encrypt(&i.to_le_bytes()).unwrap()).collect();
+
+        // Skipping more than `MAX_SKIP` messages is refused
+        let before = serialize(&bob);
+        assert!(bob.decrypt(&msgs[MAX_SKIP as usize + 1]).is_none());
+        assert_eq!(serialize(&bob), before);
+
+        // Up to `MAX_SKIP` is fine
+        let n = MAX_SKIP;
+        assert_eq!(bob.decrypt(&msgs[n as usize]).unwrap(), n.to_le_bytes());
+        assert_eq!(bob.skipped.len(), MAX_SKIP as usize);
+        assert_eq!(bob.decrypt(&msgs[0]).unwrap(), 0u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_replay_and_tampering() {
+        let (mut alice, mut bob) = session_pair();
+
+        let msg = alice.encrypt(b"once").unwrap();
+        assert_eq!(bob.decrypt(&msg).unwrap(), b"once");
+
+        // A replayed message is rejected
+        let before = serialize(&bob);
+        assert!(bob.decrypt(&msg).is_none());
+        assert_eq!(serialize(&bob), before);
+
+        let msg = alice.encrypt(b"twice").unwrap();
+        let message: RatchetMessage = deserialize(&msg).unwrap();
+
+        // Tampered ciphertext
+        let mut tampered = message.clone();
+        *tampered.ciphertext.last_mut().unwrap() ^= 1;
+        assert!(bob.decrypt(&serialize(&tampered)).is_none());
+        assert_eq!(serialize(&bob), before);
+
+        // Tampered message number
+        let mut tampered = message.clone();
+        tampered.header.n += 1;
+        assert!(bob.decrypt(&serialize(&tampered)).is_none());
+        assert_eq!(serialize(&bob), before);
+
+        // Tampered ratchet key
+        let mut tampered = message.clone();
+        tampered.header.dh = PublicKey::from_secret(SecretKey::random(&mut OsRng));
+        assert!(bob.decrypt(&serialize(&tampered)).is_none());
+        assert_eq!(serialize(&bob), before);
+
+        // Garbage
+        assert!(bob.decrypt(b"garbage").is_none());
+        assert_eq!(serialize(&bob), before);
+
+        // The untampered message still goes through
+        assert_eq!(bob.decrypt(&msg).unwrap(), b"twice");
+    }
+}
@@ -25,7 +25,7 @@ use std::{
 };
 
 use darkfi::{
-    event_graph::{proto::EventPut, Event, NULL_ID},
+    event_graph::{moderation::Moderation, proto::EventPut, Event, NULL_ID},
     system::Subscription,
     Error, Result,
 };
@@ -216,6 +216,12 @@ impl Client {
                         }
                     }
 
+                    // Collect moderation actions, they're applied when showing messages.
+                    if let Some(moderation) = Moderation::from_content(r.content()) {
+                        self.server.moderation.write().await.insert(moderation);
+                        continue
+                    }
+
                     // Try to deserialize the `Event`'s content into a `Privmsg`
                     let mut privmsg: Privmsg = match deserialize_async_partial(r.content()).await {
                         Ok((v, _)) => v,
@@ -241,7 +247,7 @@ impl Client {
                     let have_channel = self.channels.read().await.contains(&privmsg.channel);
                     let msg_for_self = !privmsg.channel.starts_with('#') && privmsg.channel.as_bytes().len() <= MAX_NICK_LEN;
 
-                    if have_channel || msg_for_self {
+                    if (have_channel || msg_for_self) && !self.server.is_moderated(&privmsg, &r).await {
                         // Add the nickname to the list of nicks on the channel, if it's a channel.
                         let mut chans_lock = self.server.channels.write().await;
                         if let Some(chan) = chans_lock.get_mut(&privmsg.channel) {
@@ -374,7 +380,12 @@ impl Client {
             };
 
             // Encrypt the Privmsg if an encryption method is available.
-            self.server.try_encrypt(&mut privmsg).await;
+            // Tell the client if something else got sent in its place.
+            let target = privmsg.channel.clone();
+            if let Some(notice) = self.server.try_encrypt(&mut privmsg).await {
+                let nick = self.nickname.read().await.to_string();
+                self.reply(writer, &ReplyType::Notice((target, nick, notice))).await?;
+            }
 
             // Build a DAG event and return it.
             let event =
@@ -383,6 +394,17 @@ impl Client {
             return Ok(Some(event))
         }
 
+        // If an operator set a channel ban, we broadcast it as a moderation action.
+        if cmd.as_str() == "MODE" {
+            if let Some(moderation) = self.ban_moderation(&args).await {
+                self.server.moderation.write().await.insert(moderation.clone());
+                let event =
+                    Event::new(moderation.to_content(), &self.server.darkirc.event_graph).await;
+
+                return Ok(Some(event))
+            }
+        }
+
         Ok(None)
     }
 
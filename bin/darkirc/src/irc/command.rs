@@ -50,9 +50,12 @@
 //! Some of the above commands could actually be implemented and could
 //! work in respect to the P2P network.
 
-use std::{collections::HashSet, sync::atomic::Ordering::SeqCst};
+use std::{collections::HashSet, sync::atomic::Ordering::SeqCst, time::UNIX_EPOCH};
 
-use darkfi::Result;
+use darkfi::{
+    event_graph::moderation::{Moderation, ModerationKind},
+    Result,
+};
 use darkfi_serial::deserialize_async_partial;
 use log::{error, info};
 
@@ -316,6 +319,8 @@ impl Client {
                     topic: String::new(),
                     nicks: HashSet::from([nick.clone()]),
                     saltbox: None,
+                    operators: vec![],
+                    operator_key: None,
                 };
                 server_channels.insert(channel.clone(), chan);
             }
@@ -426,9 +431,58 @@ impl Client {
             ))])
         }
 
+        // Channel bans are set by operators as signed moderation actions.
+        // The event itself is created in `process_client_line()`.
+        if let Some(mode @ ("+b" | "-b")) = tokens.next() {
+            let Some(mask) = tokens.next() else {
+                self.penalty.fetch_add(1, SeqCst);
+                return Ok(vec![ReplyType::Server((
+                    ERR_NEEDMOREPARAMS,
+                    format!("{} MODE :{}", nick, INVALID_SYNTAX),
+                ))])
+            };
+
+            let is_operator = self
+                .server
+                .channels
+                .read()
+                .await
+                .get(target)
+                .is_some_and(|chan| chan.operator_key.is_some());
+
+            if !is_operator {
+                return Ok(vec![ReplyType::Server((
+                    ERR_CHANOPRIVSNEEDED,
+                    format!("{} {} :You're not channel operator", nick, target),
+                ))])
+            }
+
+            return Ok(vec![ReplyType::Client((nick, format!("MODE {} {} {}", target, mode, mask)))])
+        }
+
         Ok(vec![ReplyType::Server((RPL_CHANNELMODEIS, format!("{} {} +", nick, target)))])
     }
 
+    /// Build a signed moderation action out of a `MODE <channel> +b|-b <mask>`
+    /// command, if we have the operator key of the channel. Bans apply to the
+    /// nickname part of the mask.
+    pub async fn ban_moderation(&self, args: &str) -> Option<Moderation> {
+        let mut tokens = args.split_ascii_whitespace();
+        let channel = tokens.next()?;
+        let kind = match tokens.next()? {
+            "+b" => ModerationKind::Ban,
+            "-b" => ModerationKind::Unban,
+            _ => return None,
+        };
+        let target = tokens.next()?.split('!').next()?;
+
+        let channels = self.server.channels.read().await;
+        let keypair = channels.get(channel)?.operator_key.as_ref()?;
+        let timestamp = UNIX_EPOCH.elapsed().unwrap().as_secs();
+
+        Some(Moderation::new(channel, kind, target, timestamp, keypair))
+    }
+
     /// `MOTD [<server>]`
     ///
     /// Returns the message of the day on `<server>` or the current server if
@@ -939,12 +993,27 @@ impl Client {
         // Fetch and order all the events from the DAG
         let dag_events = self.server.darkirc.event_graph.order_events().await;
 
+        // Collect the moderation actions first, since they can come after
+        // the messages they apply to.
+        let mut events = Vec::with_capacity(dag_events.len());
+        for event_id in dag_events.iter() {
+            let event = self.server.darkirc.event_graph.dag_get(event_id).await.unwrap().unwrap();
+            match Moderation::from_content(event.content()) {
+                Some(moderation) => {
+                    self.server.moderation.write().await.insert(moderation);
+                }
+                None => events.push(event),
+            }
+        }
+
         // Here we'll hold the events in order we'll push to the client
         let mut replies = vec![];
 
-        for event_id in dag_events.iter() {
+        for event in events.iter() {
+            let event_id = event.id();
+
             // If it was seen, skip
-            match self.is_seen(event_id).await {
+            match self.is_seen(&event_id).await {
                 Ok(true) => continue,
                 Ok(false) => {}
                 Err(e) => {
@@ -953,9 +1022,6 @@ impl Client {
                 }
             }
 
-            // Get the event from the DAG
-            let event = self.server.darkirc.event_graph.dag_get(event_id).await.unwrap().unwrap();
-
             // Try to deserialize it. (Here we skip errors)
             let Ok((mut privmsg, _)) = deserialize_async_partial(event.content()).await else {
                 continue
@@ -970,9 +1036,14 @@ impl Client {
                 continue
             }
 
+            // Skip it if the channel operators hid it or banned its sender.
+            if self.server.is_moderated(&privmsg, event).await {
+                continue
+            }
+
             let msg = format!("PRIVMSG {} :{}", privmsg.channel, privmsg.msg);
             replies.push(ReplyType::Client((privmsg.nick, msg)));
-            if let Err(e) = self.mark_seen(event_id).await {
+            if let Err(e) = self.mark_seen(&event_id).await {
                 error!("[IRC CLIENT] (get_history) self.mark_seen({}) failed: {}", event_id, e);
                 return Err(e)
             }
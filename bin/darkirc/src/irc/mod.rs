@@ -54,10 +54,18 @@ pub struct IrcChannel {
     pub topic: String,
     pub nicks: HashSet<String>,
     pub saltbox: Option<Arc<ChaChaBox>>,
+    /// Public keys of the operators whose moderation actions we apply
+    pub operators: Vec<[u8; 32]>,
+    /// Our operator keypair, used to sign moderation actions
+    pub operator_key: Option<Arc<ed25519_compact::KeyPair>>,
 }
 
 /// IRC contact definition
 #[derive(Clone)]
 pub struct IrcContact {
     pub saltbox: Option<Arc<ChaChaBox>>,
+    /// Use double ratchet sessions for DMs with this contact
+    pub forward_secrecy: bool,
+    /// Whether we keep our ratchet session if both sides start one at once
+    pub initiator: bool,
 }
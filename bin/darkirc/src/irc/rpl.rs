@@ -198,6 +198,12 @@ pub const ERR_NEEDMOREPARAMS: u16 = 461;
 /// set during registration.
 pub const ERR_ALREADYREGISTERED: u16 = 462;
 
+/// `<client> <channel> :You're not channel operator`
+///
+/// Indicates that a command failed because the client does not have
+/// the operator key of `<channel>` configured.
+pub const ERR_CHANOPRIVSNEEDED: u16 = 482;
+
 /// `<client> :Cant change mode for other users`
 ///
 /// Indicates that a MODE command affecting a user failed because they
@@ -16,19 +16,30 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{collections::HashMap, fs::File, io::BufReader, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use crypto_box::ChaChaBox;
 use darkfi::{
-    event_graph::Event,
+    event_graph::{moderation::ModerationView, Event},
     system::{StoppableTask, StoppableTaskPtr, Subscription},
     util::path::expand_path,
     Error, Result,
 };
+use darkfi_sdk::crypto::{PublicKey, SecretKey};
+use darkfi_serial::{deserialize, serialize};
 use futures_rustls::{
     rustls::{self, pki_types::PrivateKeyDer},
     TlsAcceptor,
 };
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use rand::rngs::OsRng;
 use smol::{
     fs,
     lock::{Mutex, RwLock},
@@ -40,7 +51,10 @@ use url::Url;
 
 use super::{client::Client, IrcChannel, IrcContact, Privmsg};
 use crate::{
-    crypto::saltbox,
+    crypto::{
+        ratchet::{shared_root, ContactRatchet, RatchetSession, PREKEY_MAGIC, RATCHET_MAGIC},
+        saltbox,
+    },
     settings::{parse_autojoin_channels, parse_configured_channels, parse_configured_contacts},
     DarkIrc,
 };
@@ -48,6 +62,25 @@ use crate::{
 /// Max channel/nick length
 pub const MAX_NICK_LEN: usize = 24;
 
+/// Sled tree holding DM ratchet state, keyed by contact name
+const DM_SESSIONS_TREE: &str = "darkirc_dm_sessions";
+/// Sled tree that used to hold decrypted ratchet DMs. It is dropped on
+/// start, since persisted plaintexts defeat forward secrecy.
+const DM_PLAINTEXTS_TREE: &str = "darkirc_dm_plaintexts";
+/// How long decrypted ratchet DMs are kept in memory. Message keys are
+/// deleted once used, so this is the only way to show a DM again, e.g.
+/// to another client.
+const DM_PLAINTEXT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// DM payload produced for a forward-secret contact
+enum RatchetPayload {
+    /// The message, encrypted within a ratchet session
+    Message(Vec<u8>),
+    /// Our prekey, sent instead of the message while the contact hasn't
+    /// published one we can start a session with
+    Prekey(Vec<u8>),
+}
+
 /// IRC server instance
 pub struct IrcServer {
     /// DarkIrc instance
@@ -64,8 +97,16 @@ pub struct IrcServer {
     pub channels: RwLock<HashMap<String, IrcChannel>>,
     /// Configured IRC contacts
     pub contacts: RwLock<HashMap<String, IrcContact>>,
+    /// Moderation actions seen in the DAG
+    pub moderation: RwLock<ModerationView>,
     /// Active client connections
     clients: Mutex<HashMap<u16, StoppableTaskPtr>>,
+    /// DM ratchet state of contacts
+    dm_sessions: sled::Tree,
+    /// Recent plaintexts of ratchet DMs, keyed by ciphertext hash
+    dm_plaintexts: Mutex<HashMap<blake3::Hash, (Vec<u8>, Instant)>>,
+    /// Lock serializing access to DM ratchet state
+    dm_lock: Mutex<()>,
 }
 
 impl IrcServer {
@@ -119,6 +160,9 @@ impl IrcServer {
             _ => None,
         };
 
+        let dm_sessions = darkirc.sled.open_tree(DM_SESSIONS_TREE)?;
+        darkirc.sled.drop_tree(DM_PLAINTEXTS_TREE)?;
+
         let self_ = Arc::new(Self {
             darkirc,
             config_path,
@@ -127,7 +171,11 @@ impl IrcServer {
             autojoin: RwLock::new(Vec::new()),
             channels: RwLock::new(HashMap::new()),
             contacts: RwLock::new(HashMap::new()),
+            moderation: RwLock::new(ModerationView::new()),
             clients: Mutex::new(HashMap::new()),
+            dm_sessions,
+            dm_plaintexts: Mutex::new(HashMap::new()),
+            dm_lock: Mutex::new(()),
         });
 
         // Load any channel/contact configuration.
@@ -275,7 +323,8 @@ impl IrcServer {
     }
 
     /// Try encrypting a given `Privmsg` if there is such a channel/contact.
-    pub async fn try_encrypt(&self, privmsg: &mut Privmsg) {
+    /// Returns a notice for the client if the message itself wasn't sent.
+    pub async fn try_encrypt(&self, privmsg: &mut Privmsg) -> Option<String> {
         if let Some((name, channel)) = self.channels.read().await.get_key_value(&privmsg.channel) {
             if let Some(saltbox) = &channel.saltbox {
                 // We will pad the name and nick to MAX_NICK_LEN so they all look the same.
@@ -283,19 +332,57 @@ impl IrcServer {
                 privmsg.nick = saltbox::encrypt(saltbox, &Self::pad(&privmsg.nick));
                 privmsg.msg = saltbox::encrypt(saltbox, privmsg.msg.as_bytes());
                 debug!("Successfully encrypted message for {}", name);
-                return
+                return None
             }
         };
 
         if let Some((name, contact)) = self.contacts.read().await.get_key_value(&privmsg.channel) {
             if let Some(saltbox) = &contact.saltbox {
+                let plaintext = privmsg.msg.clone();
+
+                // Wrap the message in a ratchet session if we want forward secrecy.
+                let mut notice = None;
+                let msg = if contact.forward_secrecy {
+                    match self.ratchet_encrypt(name, saltbox, plaintext.as_bytes()).await {
+                        Ok(RatchetPayload::Message(v)) => Some(v),
+                        Ok(RatchetPayload::Prekey(v)) => {
+                            notice = Some(format!(
+                                "Message not sent: started a forward-secret session, \
+                                 resend once {} replied",
+                                name
+                            ));
+                            Some(v)
+                        }
+                        Err(e) => {
+                            error!("Failed ratchet encryption for {}: {}", name, e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
                 // We will pad the nicks to MAX_NICK_LEN so they all look the same.
                 privmsg.channel = saltbox::encrypt(saltbox, &Self::pad(&privmsg.channel));
                 privmsg.nick = saltbox::encrypt(saltbox, &Self::pad(&privmsg.nick));
-                privmsg.msg = saltbox::encrypt(saltbox, privmsg.msg.as_bytes());
+
+                if let Some(msg) = msg {
+                    privmsg.msg = saltbox::encrypt(saltbox, &msg);
+                    // Remember the plaintext since we can't decrypt our own message.
+                    if notice.is_none() {
+                        let cache_key = blake3::hash(privmsg.msg.as_bytes());
+                        self.cache_plaintext(cache_key, plaintext.into_bytes()).await;
+                    }
+                } else {
+                    privmsg.msg = saltbox::encrypt(saltbox, plaintext.as_bytes());
+                }
+
                 debug!("Successfully encrypted message for {}", name);
+                return notice
             }
         };
+
+        None
     }
 
     /// Try decrypting a given potentially encrypted `Privmsg` object.
@@ -356,10 +443,20 @@ impl IrcServer {
                     continue
                 };
 
-                let Some(msg_dec) = saltbox::try_decrypt(saltbox, &msg_ciphertext) else {
+                let Some(mut msg_dec) = saltbox::try_decrypt(saltbox, &msg_ciphertext) else {
                     continue
                 };
 
+                if msg_dec.starts_with(RATCHET_MAGIC) || msg_dec.starts_with(PREKEY_MAGIC) {
+                    let Some(plaintext) =
+                        self.ratchet_decrypt(name, contact, saltbox, &privmsg.msg, &msg_dec).await
+                    else {
+                        warn!("Failed decrypting forward-secret message from {}", name);
+                        return
+                    };
+                    msg_dec = plaintext;
+                }
+
                 Self::unpad(&mut channel_dec);
                 Self::unpad(&mut nick_dec);
 
@@ -373,4 +470,158 @@ impl IrcServer {
             }
         }
     }
+
+    /// Check if a channel message should not be shown, because the
+    /// configured channel operators hid it or banned its sender.
+    pub async fn is_moderated(&self, privmsg: &Privmsg, event: &Event) -> bool {
+        let channels = self.channels.read().await;
+        let Some(channel) = channels.get(&privmsg.channel) else { return false };
+        if channel.operators.is_empty() {
+            return false
+        }
+
+        let moderation = self.moderation.read().await;
+        moderation.is_hidden(&privmsg.channel, &event.id(), &channel.operators) ||
+            moderation.is_banned(
+                &privmsg.channel,
+                &privmsg.nick,
+                event.timestamp(),
+                &channel.operators,
+            )
+    }
+
+    /// Load the ratchet state of the given contact.
+    fn dm_state(&self, name: &str) -> ContactRatchet {
+        match self.dm_sessions.get(name) {
+            Ok(Some(v)) => deserialize(&v).unwrap_or_default(),
+            Ok(None) => ContactRatchet::default(),
+            Err(e) => {
+                error!("Failed reading DM session for {}: {}", name, e);
+                ContactRatchet::default()
+            }
+        }
+    }
+
+    /// Keep the plaintext of a ratchet DM in memory for a while,
+    /// dropping expired ones.
+    async fn cache_plaintext(&self, key: blake3::Hash, plaintext: Vec<u8>) {
+        let mut cache = self.dm_plaintexts.lock().await;
+        cache.retain(|_, (_, added)| added.elapsed() < DM_PLAINTEXT_TTL);
+        cache.insert(key, (plaintext, Instant::now()));
+    }
+
+    /// Encrypt a DM within the ratchet session of the given contact,
+    /// starting a new session with the contact's prekey if we can't send
+    /// yet. Without a prekey, returns our own prekey to publish instead.
+    /// The returned payload is prefixed with its magic.
+    async fn ratchet_encrypt(
+        &self,
+        name: &str,
+        saltbox: &ChaChaBox,
+        plaintext: &[u8],
+    ) -> Result<RatchetPayload> {
+        let _lock = self.dm_lock.lock().await;
+        let mut state = self.dm_state(name);
+
+        let mut message = state.session.as_mut().and_then(|s| s.encrypt(plaintext));
+        if message.is_none() {
+            // The contact's prekey is only used once
+            let session = state
+                .remote_prekey
+                .take()
+                .and_then(|prekey| RatchetSession::initiator(shared_root(saltbox), &prekey));
+
+            let Some(mut session) = session else {
+                // Ask the contact to start a session by publishing a prekey
+                let prekey = *state.prekey.get_or_insert_with(|| SecretKey::random(&mut OsRng));
+                self.dm_sessions.insert(name, serialize(&state))?;
+
+                let mut payload = PREKEY_MAGIC.to_vec();
+                payload.append(&mut serialize(&PublicKey::from_secret(prekey)));
+                return Ok(RatchetPayload::Prekey(payload))
+            };
+
+            message = session.encrypt(plaintext);
+            state.session = Some(session);
+        }
+
+        let Some(mut message) = message else {
+            return Err(Error::Custom(format!("[IRC] Ratchet session with {} can't send", name)))
+        };
+
+        let mut payload = RATCHET_MAGIC.to_vec();
+        payload.append(&mut message);
+        self.dm_sessions.insert(name, serialize(&state))?;
+
+        Ok(RatchetPayload::Message(payload))
+    }
+
+    /// Decrypt a ratchet DM payload from the given contact.
+    /// `ciphertext` is the encoded message as seen in the event graph,
+    /// used to find the plaintext if we've recently seen this message.
+    async fn ratchet_decrypt(
+        &self,
+        name: &str,
+        contact: &IrcContact,
+        saltbox: &ChaChaBox,
+        ciphertext: &str,
+        payload: &[u8],
+    ) -> Option<Vec<u8>> {
+        let _lock = self.dm_lock.lock().await;
+
+        let cache_key = blake3::hash(ciphertext.as_bytes());
+        if let Some((plaintext, added)) = self.dm_plaintexts.lock().await.get(&cache_key) {
+            if added.elapsed() < DM_PLAINTEXT_TTL {
+                return Some(plaintext.clone())
+            }
+        }
+
+        let mut state = self.dm_state(name);
+
+        // The contact wants to start a session, which happens with our
+        // next message to them.
+        if let Some(data) = payload.strip_prefix(PREKEY_MAGIC) {
+            state.remote_prekey = Some(deserialize(data).ok()?);
+            if let Err(e) = self.dm_sessions.insert(name, serialize(&state)) {
+                error!("Failed storing DM session for {}: {}", name, e);
+            }
+
+            let notice = b"*** Requested a forward-secret session, reply to start it".to_vec();
+            self.cache_plaintext(cache_key, notice.clone()).await;
+            return Some(notice)
+        }
+
+        let data = payload.strip_prefix(RATCHET_MAGIC)?;
+        let (plaintext, session) = 'decrypt: {
+            if let Some(mut session) = state.session.clone() {
+                if let Some(plaintext) = session.decrypt(data) {
+                    break 'decrypt (plaintext, session)
+                }
+            }
+
+            // The contact started a session with the prekey we published
+            let mut fresh = RatchetSession::responder(shared_root(saltbox), state.prekey?);
+            let plaintext = fresh.decrypt(data)?;
+            state.prekey = None;
+
+            // If both of us started a session at once, the initiator keeps theirs.
+            match state.session.take() {
+                Some(session) if contact.initiator && !session.has_received() => {
+                    (plaintext, session)
+                }
+                _ => {
+                    info!("Started new DM ratchet session with {}", name);
+                    (plaintext, fresh)
+                }
+            }
+        };
+
+        state.session = Some(session);
+        if let Err(e) = self.dm_sessions.insert(name, serialize(&state)) {
+            error!("Failed storing DM session for {}: {}", name, e);
+        }
+
+        self.cache_plaintext(cache_key, plaintext.clone()).await;
+        Some(plaintext)
+    }
 }
@@ -100,9 +100,17 @@ struct Args {
     #[structopt(long)]
     get_chacha_pubkey: Option<String>,
 
+    /// Generate a new channel operator keypair and exit
+    #[structopt(long)]
+    gen_operator_keypair: bool,
+
     #[structopt(long)]
     skip_dag_sync: bool,
 
+    #[structopt(long, default_value = "0")]
+    /// Prune DAG events older than given days (0 keeps them)
+    dag_retention: u64,
+
     /// P2P network settings
     #[structopt(flatten)]
     net: SettingsOpt,
@@ -165,6 +173,18 @@ async fn realmain(args: Args, ex: Arc<Executor<'static>>) -> Result<()> {
         return Ok(())
     }
 
+    if args.gen_operator_keypair {
+        let seed = ed25519_compact::Seed::generate();
+        let secret = bs58::encode(*seed).into_string();
+        let keypair = ed25519_compact::KeyPair::from_seed(seed);
+        let public = bs58::encode(*keypair.pk).into_string();
+        println!("Place this in your config file:\n");
+        println!("[channel.\"#yourchannelname\"]");
+        println!("operators = [\"{}\"]", public);
+        println!("operator_secret = \"{}\"", secret);
+        return Ok(())
+    }
+
     if let Some(chacha_secret) = args.get_chacha_pubkey {
         let bytes = match bs58::decode(chacha_secret).into_vec() {
             Ok(v) => v,
@@ -193,8 +213,15 @@ async fn realmain(args: Args, ex: Arc<Executor<'static>>) -> Result<()> {
     info!("Instantiating event DAG");
     let sled_db = sled::open(datastore)?;
     let p2p = P2p::new(args.net.into(), ex.clone()).await;
-    let event_graph =
-        EventGraph::new(p2p.clone(), sled_db.clone(), "darkirc_dag", 1, ex.clone()).await?;
+    let event_graph = EventGraph::new(
+        p2p.clone(),
+        sled_db.clone(),
+        "darkirc_dag",
+        1,
+        args.dag_retention,
+        ex.clone(),
+    )
+    .await?;
 
     info!("Registering EventGraph P2P protocol");
     let event_graph_ = Arc::clone(&event_graph);
@@ -95,6 +95,7 @@ fn parse_dm_chacha_secret(data: &toml::Value) -> Result<Option<crypto_box::Secre
 /// ```toml
 /// [contact."anon"]
 /// dm_chacha_public = "7CkVuFgwTUpJn5Sv67Q3fyEDpa28yrSeL5Hg2GqQ4jfM"
+/// forward_secrecy = true
 /// ```
 pub fn parse_configured_contacts(data: &toml::Value) -> Result<HashMap<String, IrcContact>> {
     let mut ret = HashMap::new();
@@ -131,12 +132,25 @@ pub fn parse_configured_contacts(data: &toml::Value) -> Result<HashMap<String, I
         let public = crypto_box::PublicKey::from(public_bytes);
         let saltbox = Some(Arc::new(crypto_box::ChaChaBox::new(&public, &secret)));
 
+        let forward_secrecy = match items.get("forward_secrecy") {
+            Some(v) => {
+                let Some(v) = v.as_bool() else {
+                    return Err(ParseFailed("Invalid contact forward_secrecy (not a bool)"))
+                };
+                v
+            }
+            None => false,
+        };
+
+        // Deterministic tie-break both sides agree on
+        let initiator = secret.public_key().as_bytes() < public.as_bytes();
+
         if ret.contains_key(name) {
             return Err(ParseFailed("Duplicate contact found"))
         }
 
         info!("Instantiated ChaChaBox for contact \"{}\"", name);
-        ret.insert(name.to_string(), IrcContact { saltbox });
+        ret.insert(name.to_string(), IrcContact { saltbox, forward_secrecy, initiator });
     }
 
     Ok(ret)
@@ -149,6 +163,8 @@ pub fn parse_configured_contacts(data: &toml::Value) -> Result<HashMap<String, I
 /// [channel."#memes"]
 /// secret = "7CkVuFgwTUpJn5Sv67Q3fyEDpa28yrSeL5Hg2GqQ4jfM"
 /// topic = "Dank Memes"
+/// operators = ["9sfMEVLphJ4dTX3SEvm6NBhTbWDqfsxu7R2bo88CtV8g"]
+/// operator_secret = "AKfyoKxnHb8smqP2zt9BVvXkcN7pm9GnqqyuYRmxmWtR"
 /// ```
 pub fn parse_configured_channels(data: &toml::Value) -> Result<HashMap<String, IrcChannel>> {
     let mut ret = HashMap::new();
@@ -158,7 +174,13 @@ pub fn parse_configured_channels(data: &toml::Value) -> Result<HashMap<String, I
     let Some(chans) = chans.as_table() else { return Err(ParseFailed("`channel` not a map")) };
 
     for (name, items) in chans {
-        let mut chan = IrcChannel { topic: String::new(), nicks: HashSet::new(), saltbox: None };
+        let mut chan = IrcChannel {
+            topic: String::new(),
+            nicks: HashSet::new(),
+            saltbox: None,
+            operators: vec![],
+            operator_key: None,
+        };
 
         if let Some(topic) = items.get("topic") {
             if let Some(topic) = topic.as_str() {
@@ -189,6 +211,51 @@ pub fn parse_configured_channels(data: &toml::Value) -> Result<HashMap<String, I
             }
         }
 
+        if let Some(operators) = items.get("operators") {
+            let Some(operators) = operators.as_array() else {
+                return Err(ParseFailed("Channel operators not an array"))
+            };
+
+            for operator in operators {
+                let Some(operator) = operator.as_str() else {
+                    return Err(ParseFailed("Channel operator not a string"))
+                };
+
+                let Ok(operator_bytes) = bs58::decode(operator).into_vec() else {
+                    return Err(ParseFailed("Channel operator not valid base58"))
+                };
+
+                let Ok(operator_bytes) = operator_bytes.try_into() else {
+                    return Err(ParseFailed("Channel operator not 32 bytes long"))
+                };
+
+                chan.operators.push(operator_bytes);
+            }
+
+            info!("Configured {} operators for channel {}", chan.operators.len(), name);
+        }
+
+        if let Some(secret) = items.get("operator_secret") {
+            let Some(secret) = secret.as_str() else {
+                return Err(ParseFailed("Channel operator secret not a string"))
+            };
+
+            let Ok(secret_bytes) = bs58::decode(secret).into_vec() else {
+                return Err(ParseFailed("Channel operator secret not valid base58"))
+            };
+
+            let Ok(seed) = ed25519_compact::Seed::from_slice(&secret_bytes) else {
+                return Err(ParseFailed("Channel operator secret not 32 bytes long"))
+            };
+
+            let keypair = ed25519_compact::KeyPair::from_seed(seed);
+            if !chan.operators.contains(&*keypair.pk) {
+                chan.operators.push(*keypair.pk);
+            }
+            chan.operator_key = Some(Arc::new(keypair));
+            info!("Configured operator key for channel {}", name);
+        }
+
         info!("Configured channel {}", name);
         ret.insert(name.to_string(), chan);
     }
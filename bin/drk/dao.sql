@@ -107,15 +107,23 @@ CREATE TABLE IF NOT EXISTS Fd8kfCuqU8BoFFp6GcXv5pC8XXRkBK7gUPQX5XDz7iXj_dao_daos
 	dao_id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     name BLOB UNIQUE NOT NULL,
     proposer_limit BLOB NOT NULL,
+    -- governance tokens bonded when proposing, slashed if overwhelmingly rejected
+    proposal_deposit BLOB NOT NULL,
     -- minimum threshold for total number of votes for proposal to pass.
     -- If there's too little activity then it cannot pass.
     quorum BLOB NOT NULL,
+    -- yes votes needed to execute a proposal before its voting period ends
+    early_exec_quorum BLOB NOT NULL,
     -- Needed ratio of yes/total for proposal to pass.
     -- approval_ratio = approval_ratio_quot / approval_ratio_base
     approval_ratio_base INTEGER NOT NULL,
     approval_ratio_quot INTEGER NOT NULL,
+    -- serialized DaoVoteWeight applied to voters' balances
+    vote_weight BLOB NOT NULL,
 	gov_token_id BLOB NOT NULL,
 	secret BLOB NOT NULL,
+    -- serialized Option<PublicKey> of the guardian who can veto proposals
+    guardian_public_key BLOB NOT NULL,
 	bulla_blind BLOB NOT NULL,
     -- these values are NULL until the DAO is minted on chain and received
 	leaf_position BLOB,
@@ -167,3 +175,20 @@ CREATE TABLE IF NOT EXISTS Fd8kfCuqU8BoFFp6GcXv5pC8XXRkBK7gUPQX5XDz7iXj_dao_vote
     -- that might be a mistake...
     FOREIGN KEY(proposal_id) REFERENCES Fd8kfCuqU8BoFFp6GcXv5pC8XXRkBK7gUPQX5XDz7iXj_dao_proposals(proposal_id) ON DELETE CASCADE ON UPDATE CASCADE
 );
+
+-- Amended DAOs seen on chain, which can no longer propose or exec
+CREATE TABLE IF NOT EXISTS Fd8kfCuqU8BoFFp6GcXv5pC8XXRkBK7gUPQX5XDz7iXj_dao_superseded (
+    dao_bulla BLOB PRIMARY KEY NOT NULL,
+    -- Latest DAO succeeding this one, which can spend its treasury
+    successor BLOB NOT NULL
+);
+
+-- Voting periods of our DAOs' proposals, tracked to emit wallet events
+CREATE TABLE IF NOT EXISTS Fd8kfCuqU8BoFFp6GcXv5pC8XXRkBK7gUPQX5XDz7iXj_dao_proposal_windows (
+    proposal_bulla BLOB PRIMARY KEY NOT NULL,
+    dao_name TEXT NOT NULL,
+    -- Block height at which voting on the proposal ends
+    end_height INTEGER NOT NULL,
+    -- Whether the closing window event has been emitted
+    notified INTEGER NOT NULL
+);
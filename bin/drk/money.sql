@@ -12,12 +12,20 @@ CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_tr
 	tree BLOB NOT NULL
 );
 
+-- Snapshots of the Merkle tree after every `MONEY_TREE_CHECKPOINT_INTERVAL`
+-- scanned blocks, to rescan from
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_tree_checkpoints (
+	height INTEGER PRIMARY KEY NOT NULL,
+	tree BLOB NOT NULL
+);
+
 -- The keypairs in our wallet
 CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_keys (
 	key_id INTEGER PRIMARY KEY NOT NULL,
 	is_default INTEGER NOT NULL,
 	public BLOB NOT NULL,
-	secret BLOB NOT NULL
+	secret BLOB NOT NULL,
+	account INTEGER NOT NULL DEFAULT 0
 );
 
 -- The coins we have the information to and can spend
@@ -34,9 +42,22 @@ CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_co
 	secret BLOB NOT NULL,
 	nullifier BLOB NOT NULL,
 	leaf_position BLOB NOT NULL,
+	payment_id BLOB,
 	memo BLOB
 );
 
+-- Viewing keys of watch-only accounts, able to see but not spend their coins
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_watch_keys (
+	viewing_key BLOB PRIMARY KEY NOT NULL
+);
+
+-- The coins received by watch-only accounts
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_watch_coins (
+	coin BLOB PRIMARY KEY NOT NULL,
+	viewing_key BLOB NOT NULL,
+	note BLOB NOT NULL
+);
+
 -- Arbitrary tokens
 CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_tokens (
 	mint_authority BLOB PRIMARY KEY NOT NULL,
@@ -44,15 +65,71 @@ CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_to
 	is_frozen INTEGER NOT NULL
 );
 
+-- Tokens whose transfers are frozen by their authority
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_frozen_tokens (
+	token_id BLOB PRIMARY KEY NOT NULL
+);
+
+-- Supply caps and mint authority revocations of tokens seen on chain
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_token_policies (
+	token_id BLOB PRIMARY KEY NOT NULL,
+	supply_cap BLOB,
+	is_revoked INTEGER NOT NULL
+);
+
+-- Mints we built with our mint authorities, along with the value blinds
+-- needed to prove the supply of capped tokens stays within the cap
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_token_mints (
+	coin BLOB PRIMARY KEY NOT NULL,
+	token_id BLOB NOT NULL,
+	value BLOB NOT NULL,
+	value_blind BLOB NOT NULL,
+	is_executed INTEGER NOT NULL
+);
+
+-- Non-fungible tokens seen on chain
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_nfts (
+	token_id BLOB PRIMARY KEY NOT NULL,
+	metadata BLOB NOT NULL,
+	is_burned INTEGER NOT NULL
+);
+
+-- Key shares of the FROST threshold groups we are a member of
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_groups (
+	group_public BLOB PRIMARY KEY NOT NULL,
+	key_package BLOB NOT NULL,
+	public_key_package BLOB NOT NULL
+);
+
 -- The token aliases in our wallet
 CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_aliases (
 	alias BLOB PRIMARY KEY NOT NULL,
 	token_id BLOB NOT NULL
 );
 
+-- Funds of each token moved by confirmed transactions, per account
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_history (
+	transaction_hash TEXT NOT NULL,
+	token_id BLOB NOT NULL,
+	account INTEGER NOT NULL,
+	block_height INTEGER NOT NULL,
+	timestamp INTEGER NOT NULL,
+	received BLOB NOT NULL,
+	sent BLOB NOT NULL,
+	fee BLOB NOT NULL,
+	PRIMARY KEY (transaction_hash, token_id, account)
+);
+
+-- Recipients of the payments made by this wallet
+CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_money_counterparties (
+	transaction_hash TEXT PRIMARY KEY NOT NULL,
+	address BLOB NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS BZHKGQ26bzmBithTQYTJtjo2QdCqpkR9tjSBopT4yf4o_transactions_history (
 	id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
 	transaction_hash TEXT UNIQUE NOT NULL,
 	status TEXT NOT NULL,
-	tx TEXT UNIQUE NOT NULL
+	tx TEXT UNIQUE NOT NULL,
+	account INTEGER NOT NULL DEFAULT 0
 );
@@ -0,0 +1,136 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use rusqlite::types::Value;
+
+use darkfi::{Error, Result};
+
+use crate::{convert_named_params, Drk};
+
+// Wallet SQL table constant names. These have to represent the `wallet.sql`
+// SQL schema.
+const WALLET_ACCOUNTS_TABLE: &str = "accounts";
+const WALLET_ACCOUNTS_COL_ACCOUNT_ID: &str = "account_id";
+const WALLET_ACCOUNTS_COL_NAME: &str = "name";
+const WALLET_ACCOUNTS_COL_IS_ACTIVE: &str = "is_active";
+
+/// ID of the account every wallet starts with
+pub const DEFAULT_ACCOUNT: u64 = 0;
+
+impl Drk {
+    /// Create a new account with the given name, returning its ID.
+    pub async fn create_account(&self, name: &str) -> Result<u64> {
+        let accounts = self.list_accounts().await?;
+        if accounts.iter().any(|(_, n, _)| n == name) {
+            return Err(Error::Custom(format!("[create_account] Account {name} already exists")))
+        }
+        let account_id = accounts.iter().map(|(id, _, _)| id + 1).max().unwrap_or(DEFAULT_ACCOUNT);
+
+        let query = format!(
+            "INSERT INTO {WALLET_ACCOUNTS_TABLE} ({WALLET_ACCOUNTS_COL_ACCOUNT_ID}, {WALLET_ACCOUNTS_COL_NAME}, {WALLET_ACCOUNTS_COL_IS_ACTIVE}) VALUES (?1, ?2, ?3);"
+        );
+        if let Err(e) = self.wallet.exec_sql(&query, rusqlite::params![account_id, name, 0]).await {
+            return Err(Error::RusqliteError(format!(
+                "[create_account] Account insertion failed: {e:?}"
+            )))
+        }
+
+        Ok(account_id)
+    }
+
+    /// Fetch all accounts of the wallet, along with their names and a flag
+    /// marking the active one.
+    pub async fn list_accounts(&self) -> Result<Vec<(u64, String, bool)>> {
+        let rows = match self.wallet.query_multiple(WALLET_ACCOUNTS_TABLE, &[], &[]).await {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[list_accounts] Accounts retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut accounts = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Integer(account_id) = row[0] else {
+                return Err(Error::ParseFailed("[list_accounts] Account ID parsing failed"))
+            };
+            let Ok(account_id) = u64::try_from(account_id) else {
+                return Err(Error::ParseFailed("[list_accounts] Account ID parsing failed"))
+            };
+
+            let Value::Text(ref name) = row[1] else {
+                return Err(Error::ParseFailed("[list_accounts] Name parsing failed"))
+            };
+
+            let Value::Integer(is_active) = row[2] else {
+                return Err(Error::ParseFailed("[list_accounts] Is active parsing failed"))
+            };
+
+            accounts.push((account_id, name.clone(), is_active > 0));
+        }
+
+        Ok(accounts)
+    }
+
+    /// Make the account with the given name the active one. Keys, balances,
+    /// coins and transactions history are scoped to the active account.
+    pub async fn switch_account(&self, name: &str) -> Result<()> {
+        let accounts = self.list_accounts().await?;
+        if !accounts.iter().any(|(_, n, _)| n == name) {
+            return Err(Error::Custom(format!("[switch_account] Account {name} not found")))
+        }
+
+        let query = format!(
+            "UPDATE {WALLET_ACCOUNTS_TABLE} SET {WALLET_ACCOUNTS_COL_IS_ACTIVE} = ({WALLET_ACCOUNTS_COL_NAME} = ?1);"
+        );
+        if let Err(e) = self.wallet.exec_sql(&query, rusqlite::params![name]).await {
+            return Err(Error::RusqliteError(format!(
+                "[switch_account] Account activation failed: {e:?}"
+            )))
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the ID of the active account. Wallets without accounts use the
+    /// default one.
+    pub async fn active_account(&self) -> Result<u64> {
+        let row = match self
+            .wallet
+            .query_single(
+                WALLET_ACCOUNTS_TABLE,
+                &[WALLET_ACCOUNTS_COL_ACCOUNT_ID],
+                convert_named_params! {(WALLET_ACCOUNTS_COL_IS_ACTIVE, 1)},
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => return Ok(DEFAULT_ACCOUNT),
+        };
+
+        let Value::Integer(account_id) = row[0] else {
+            return Err(Error::ParseFailed("[active_account] Account ID parsing failed"))
+        };
+        let Ok(account_id) = u64::try_from(account_id) else {
+            return Err(Error::ParseFailed("[active_account] Account ID parsing failed"))
+        };
+
+        Ok(account_id)
+    }
+}
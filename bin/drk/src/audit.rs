@@ -0,0 +1,280 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use darkfi::{Error, Result};
+use darkfi_money_contract::{
+    client::{MoneyNote, OwnCoin},
+    model::Coin,
+};
+use darkfi_sdk::{
+    bridgetree::{Hashable, Level, Position},
+    crypto::{FuncId, MerkleNode, MerkleTree, ViewingKey},
+    pasta::group::ff::PrimeField,
+};
+
+use crate::{money::MoneyTxData, Drk};
+
+/// A mismatch between the wallet state and the state recovered from the chain
+pub enum AuditDiscrepancy {
+    /// The wallet Merkle tree root differs from the recomputed one
+    TreeRoot { wallet: Option<MerkleNode>, chain: Option<MerkleNode> },
+    /// A coin decryptable with the wallet keys is missing from the wallet
+    MissingCoin(Coin),
+    /// A wallet coin was not found on chain
+    UnknownCoin(Coin),
+    /// A wallet coin is stored at a different leaf position than on chain
+    LeafPosition { coin: Coin, wallet: Position, chain: Position },
+    /// A wallet coin spent status differs from its nullifier status on chain
+    SpentStatus { coin: Coin, wallet: bool, chain: bool },
+    /// A wallet coin Merkle witness doesn't lead to the recomputed root
+    InvalidWitness(Coin),
+    /// A wallet token balance differs from the one recomputed from chain
+    Balance { token_id: String, wallet: u64, chain: u64 },
+}
+
+impl fmt::Display for AuditDiscrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let root = |r: &Option<MerkleNode>| match r {
+            Some(r) => r.to_string(),
+            None => "-".to_string(),
+        };
+
+        match self {
+            Self::TreeRoot { wallet, chain } => {
+                write!(f, "Merkle root mismatch: wallet {}, chain {}", root(wallet), root(chain))
+            }
+            Self::MissingCoin(coin) => write!(f, "Coin {coin} is missing from the wallet"),
+            Self::UnknownCoin(coin) => write!(f, "Coin {coin} was not found on chain"),
+            Self::LeafPosition { coin, wallet, chain } => write!(
+                f,
+                "Coin {coin} leaf position mismatch: wallet {}, chain {}",
+                u64::from(*wallet),
+                u64::from(*chain)
+            ),
+            Self::SpentStatus { coin, wallet, chain } => write!(
+                f,
+                "Coin {coin} spent status mismatch: wallet {}, chain {}",
+                if *wallet { "spent" } else { "unspent" },
+                if *chain { "spent" } else { "unspent" }
+            ),
+            Self::InvalidWitness(coin) => write!(f, "Coin {coin} Merkle witness is invalid"),
+            Self::Balance { token_id, wallet, chain } => {
+                write!(f, "Token {token_id} balance mismatch: wallet {wallet}, chain {chain}")
+            }
+        }
+    }
+}
+
+/// Auxiliary function to compute the Merkle root a leaf and its
+/// authentication path lead to.
+fn merkle_path_root(leaf: MerkleNode, position: Position, path: &[MerkleNode]) -> MerkleNode {
+    let position = u64::from(position);
+    path.iter().enumerate().fold(leaf, |node, (level, sibling)| {
+        let altitude = Level::from(level as u8);
+        if (position >> level) & 1 == 0 {
+            MerkleNode::combine(altitude, &node, sibling)
+        } else {
+            MerkleNode::combine(altitude, sibling, &node)
+        }
+    })
+}
+
+impl Drk {
+    /// Cross-check the wallet Money state against the chain, up to the last
+    /// scanned block. The Merkle tree and the wallet coins are recovered from
+    /// scratch by requesting every block from darkfid and trying to decrypt
+    /// every note with the wallet keys, without touching the wallet database.
+    /// Returns every discrepancy found, so an empty vector means the wallet
+    /// is consistent with the chain.
+    pub async fn audit(&self) -> Result<Vec<AuditDiscrepancy>> {
+        let last = match self.last_scanned_block().await {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[audit] Retrieving last scanned block failed: {e:?}"
+                )))
+            }
+        };
+
+        let secrets = self.get_money_secrets().await?;
+        let dao_secrets = self.get_dao_secrets().await?;
+        let group_secrets = self.get_group_secrets().await?;
+
+        // Replay the chain Money state
+        let mut tree = MerkleTree::new(1);
+        let mut positions: HashMap<[u8; 32], Position> = HashMap::new();
+        let mut nullifiers: HashSet<[u8; 32]> = HashSet::new();
+        let mut recovered: Vec<OwnCoin> = vec![];
+        for height in 0..=last {
+            eprint!("\rAuditing block {height}/{last}...");
+            let block = self.get_block_by_height(height).await?;
+            for tx in &block.txs {
+                let MoneyTxData { nullifiers: tx_nullifiers, coins, notes, .. } =
+                    Self::parse_tx_money_data(tx)?;
+
+                nullifiers.extend(tx_nullifiers.iter().map(|n| n.to_bytes()));
+
+                for (coin, note) in coins.iter().zip(notes.iter()) {
+                    tree.append(MerkleNode::from(coin.inner()));
+                    let leaf_position = tree.current_position().unwrap();
+                    positions.insert(coin.inner().to_repr(), leaf_position);
+
+                    for secret in
+                        secrets.iter().chain(dao_secrets.iter()).chain(group_secrets.iter())
+                    {
+                        if let Ok(note) = MoneyNote::decrypt(note, secret) {
+                            recovered.push(OwnCoin {
+                                coin: *coin,
+                                note,
+                                secret: *secret,
+                                leaf_position,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        eprintln!();
+
+        let mut discrepancies = vec![];
+
+        // Check the Merkle tree roots match
+        let wallet_tree = self.get_money_tree().await?;
+        let wallet_root = wallet_tree.root(0);
+        let chain_root = tree.root(0);
+        if wallet_root != chain_root {
+            discrepancies
+                .push(AuditDiscrepancy::TreeRoot { wallet: wallet_root, chain: chain_root });
+        }
+
+        // Check every wallet coin against the chain
+        let wallet_coins = self.get_coins(true).await?;
+        let wallet_coin_ids: HashSet<[u8; 32]> =
+            wallet_coins.iter().map(|(c, _)| c.coin.inner().to_repr()).collect();
+        for (owncoin, is_spent) in &wallet_coins {
+            let Some(position) = positions.get(&owncoin.coin.inner().to_repr()) else {
+                discrepancies.push(AuditDiscrepancy::UnknownCoin(owncoin.coin));
+                continue
+            };
+
+            if *position != owncoin.leaf_position {
+                discrepancies.push(AuditDiscrepancy::LeafPosition {
+                    coin: owncoin.coin,
+                    wallet: owncoin.leaf_position,
+                    chain: *position,
+                });
+            }
+
+            let spent = nullifiers.contains(&owncoin.nullifier().to_bytes());
+            if spent != *is_spent {
+                discrepancies.push(AuditDiscrepancy::SpentStatus {
+                    coin: owncoin.coin,
+                    wallet: *is_spent,
+                    chain: spent,
+                });
+            }
+
+            let leaf = MerkleNode::from(owncoin.coin.inner());
+            let valid = match (wallet_tree.witness(owncoin.leaf_position, 0), chain_root) {
+                (Some(path), Some(root)) => {
+                    merkle_path_root(leaf, owncoin.leaf_position, &path) == root
+                }
+                _ => false,
+            };
+            if !valid {
+                discrepancies.push(AuditDiscrepancy::InvalidWitness(owncoin.coin));
+            }
+        }
+
+        // Check every recovered coin is in the wallet
+        for owncoin in &recovered {
+            if !wallet_coin_ids.contains(&owncoin.coin.inner().to_repr()) {
+                discrepancies.push(AuditDiscrepancy::MissingCoin(owncoin.coin));
+            }
+        }
+
+        // Recompute balances from scratch and compare them with the wallet ones
+        let mut chain_balances: HashMap<String, u64> = HashMap::new();
+        for owncoin in &recovered {
+            if owncoin.note.spend_hook != FuncId::none() ||
+                nullifiers.contains(&owncoin.nullifier().to_bytes())
+            {
+                continue
+            }
+            *chain_balances.entry(owncoin.note.token_id.to_string()).or_default() +=
+                owncoin.note.value;
+        }
+        let wallet_balances = self.money_balance().await?;
+        let token_ids: HashSet<&String> =
+            wallet_balances.keys().chain(chain_balances.keys()).collect();
+        for token_id in token_ids {
+            let wallet = wallet_balances.get(token_id).copied().unwrap_or(0);
+            let chain = chain_balances.get(token_id).copied().unwrap_or(0);
+            if wallet != chain {
+                discrepancies.push(AuditDiscrepancy::Balance {
+                    token_id: token_id.clone(),
+                    wallet,
+                    chain,
+                });
+            }
+        }
+
+        Ok(discrepancies)
+    }
+    /// Scan the chain, up to the last scanned block, for notes that can be
+    /// decrypted with the given viewing key. This only needs the viewing key,
+    /// so it can be used by a third party to audit incoming payments without
+    /// being able to spend them. Returns the block height, the coin and the
+    /// decrypted note of every match.
+    pub async fn view_incoming(
+        &self,
+        viewing_key: &ViewingKey,
+    ) -> Result<Vec<(u64, Coin, MoneyNote)>> {
+        let last = match self.last_scanned_block().await {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[view_incoming] Retrieving last scanned block failed: {e:?}"
+                )))
+            }
+        };
+
+        let secret = viewing_key.inner();
+        let mut found = vec![];
+        for height in 0..=last {
+            eprint!("\rScanning block {height}/{last}...");
+            let block = self.get_block_by_height(height).await?;
+            for tx in &block.txs {
+                let MoneyTxData { coins, notes, .. } = Self::parse_tx_money_data(tx)?;
+                for (coin, note) in coins.iter().zip(notes.iter()) {
+                    if let Ok(note) = note.decrypt::<MoneyNote>(&secret) {
+                        found.push((height, *coin, note));
+                    }
+                }
+            }
+        }
+        eprintln!();
+
+        Ok(found)
+    }
+}
@@ -22,6 +22,7 @@ use structopt_toml::clap::{App, Arg, Shell, SubCommand};
 
 use darkfi::{cli_desc, system::sleep, util::parse::decode_base10, Error, Result};
 use darkfi_money_contract::model::TokenId;
+use darkfi_sdk::crypto::{Addressable, DiversifiedAddress, Network, PublicKey};
 
 use crate::{money::BALANCE_BASE10_DECIMALS, Drk};
 
@@ -44,6 +45,36 @@ pub fn parse_value_pair(s: &str) -> Result<(u64, u64)> {
     Ok((val0.unwrap(), val1.unwrap()))
 }
 
+/// Auxiliary function to parse a public key, given either as an address
+/// of the configured network, or as a legacy base58-encoded key.
+/// Addresses carrying a viewing key resolve to their public key.
+pub fn parse_public_key(network: Network, s: &str) -> Result<PublicKey> {
+    if let Ok(a) = DiversifiedAddress::from_address(network, s) {
+        return Ok(a.public_key)
+    }
+
+    match PublicKey::from_address(network, s) {
+        Ok(p) => Ok(p),
+        // Bare base58 keys carry no network or type to validate
+        Err(e) => PublicKey::from_str(s).map_err(|_| Error::Custom(e.to_string())),
+    }
+}
+
+/// Auxiliary function to parse a payment recipient, either a public key
+/// or a diversified address also carrying the viewing key to encrypt
+/// the note to.
+pub fn parse_recipient(network: Network, s: &str) -> Result<(PublicKey, Option<PublicKey>)> {
+    if let Ok(a) = DiversifiedAddress::from_address(network, s) {
+        return Ok((a.public_key, Some(a.viewing_key)))
+    }
+
+    if let Ok(a) = DiversifiedAddress::from_str(s) {
+        return Ok((a.public_key, Some(a.viewing_key)))
+    }
+
+    Ok((parse_public_key(network, s)?, None))
+}
+
 /// Auxiliary function to parse provided string into a tokens pair.
 pub async fn parse_token_pair(drk: &Drk, s: &str) -> Result<(TokenId, TokenId)> {
     let v: Vec<&str> = s.split(':').collect();
@@ -132,6 +163,18 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         .long("import-secrets")
         .help("Import secret keys from stdin into the wallet, separated by newlines");
 
+    let seed = Arg::with_name("seed")
+        .long("seed")
+        .help("Print the base58-encoded seed all the wallet keys derive from");
+
+    let import_seed = Arg::with_name("import-seed")
+        .long("import-seed")
+        .help("Import a base58-encoded seed from stdin, replacing the wallet one");
+
+    let change_password = Arg::with_name("change-password")
+        .long("change-password")
+        .help("Change the password the wallet database is encrypted with");
+
     let tree = Arg::with_name("tree").long("tree").help("Print the Merkle tree in the wallet");
 
     let coins = Arg::with_name("coins").long("coins").help("Print all the coins in the wallet");
@@ -145,6 +188,9 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         default_address,
         secrets,
         import_secrets,
+        seed,
+        import_seed,
+        change_password,
         tree,
         coins,
     ]);
@@ -203,12 +249,19 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         SubCommand::with_name("broadcast").about("Read a transaction from stdin and broadcast it");
 
     // Subscribe
-    let subscribe = SubCommand::with_name("subscribe").about(
-        "This subscription will listen for incoming blocks from darkfid and look \
+    let events = Arg::with_name("events")
+        .long("events")
+        .takes_value(true)
+        .help("Append wallet events to given file, as JSON lines");
+
+    let subscribe = SubCommand::with_name("subscribe")
+        .about(
+            "This subscription will listen for incoming blocks from darkfid and look \
                     through their transactions to see if there's any that interest us. \
                     With `drk` we look at transactions calling the money contract so we can \
                     find coins sent to us and fill our wallet with the necessary metadata.",
-    );
+        )
+        .arg(events);
 
     // DAO
     let proposer_limit = Arg::with_name("proposer-limit")
@@ -305,9 +358,18 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         .takes_value(true)
         .help("Reset Merkle tree to checkpoint index and start scanning");
 
+    let rescan = Arg::with_name("rescan")
+        .long("rescan")
+        .takes_value(true)
+        .help("Rescan the Money state starting from given block height");
+
     let scan = SubCommand::with_name("scan")
         .about("Scan the blockchain and parse relevant transactions")
-        .args(&vec![reset, list, checkpoint]);
+        .args(&vec![reset, list, checkpoint, rescan]);
+
+    // Audit
+    let audit = SubCommand::with_name("audit")
+        .about("Cross-check the wallet state against the blockchain and report discrepancies");
 
     // Explorer
     let tx_hash = Arg::with_name("tx-hash").help("Transaction hash");
@@ -333,9 +395,18 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         .about("Fetch broadcasted transactions history")
         .args(&vec![tx_hash, encode]);
 
+    let format = Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .help("Output format, either csv or json");
+
+    let export_history = SubCommand::with_name("export-history")
+        .about("Export the decrypted funds history of the active account")
+        .arg(format);
+
     let explorer = SubCommand::with_name("explorer")
         .about("Explorer related subcommands")
-        .subcommands(vec![fetch_tx, simulate_tx, txs_history]);
+        .subcommands(vec![fetch_tx, simulate_tx, txs_history, export_history]);
 
     // Alias
     let alias = Arg::with_name("alias").help("Token alias");
@@ -371,6 +442,21 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         .about("Manage Token aliases")
         .subcommands(vec![add, show, remove]);
 
+    // Account
+    let name = Arg::with_name("name").help("Account name");
+
+    let create = SubCommand::with_name("create").about("Create a new account").arg(name);
+
+    let list = SubCommand::with_name("list").about("List all the accounts in the wallet");
+
+    let name = Arg::with_name("name").help("Account name to switch to");
+
+    let switch = SubCommand::with_name("switch").about("Switch the active account").arg(name);
+
+    let account = SubCommand::with_name("account")
+        .about("Manage wallet accounts")
+        .subcommands(vec![create, list, switch]);
+
     // Token
     let import = SubCommand::with_name("import").about("Import a mint authority secret from stdin");
 
@@ -416,7 +502,7 @@ pub fn generate_completions(shell: &str) -> Result<()> {
     let wallet_pass = Arg::with_name("wallet_pass")
         .long("wallet-pass")
         .takes_value(true)
-        .help("Password for the wallet database");
+        .help("Password for the wallet database, prompted for if not set");
 
     let endpoint = Arg::with_name("endpoint")
         .short("e")
@@ -424,6 +510,11 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         .takes_value(true)
         .help("darkfid JSON-RPC endpoint");
 
+    let network = Arg::with_name("network")
+        .long("network")
+        .takes_value(true)
+        .help("Network addresses are encoded for (mainnet, testnet, localnet)");
+
     let command = vec![
         kaching,
         ping,
@@ -437,8 +528,10 @@ pub fn generate_completions(shell: &str) -> Result<()> {
         subscribe,
         dao,
         scan,
+        audit,
         explorer,
         alias,
+        account,
         token,
     ];
 
@@ -455,7 +548,7 @@ pub fn generate_completions(shell: &str) -> Result<()> {
 
     let mut app = App::new("drk")
         .about(cli_desc!())
-        .args(&vec![config, wallet_path, wallet_pass, endpoint, log, verbose])
+        .args(&vec![config, wallet_path, wallet_pass, endpoint, network, log, verbose])
         .subcommands(command);
 
     let shell = match Shell::from_str(shell) {
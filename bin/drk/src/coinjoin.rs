@@ -0,0 +1,194 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use rand::rngs::OsRng;
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    util::parse::{decode_base10, encode_base10},
+    zk::halo2::Field,
+    Error, Result,
+};
+use darkfi_money_contract::{
+    client::{
+        coinjoin::{
+            collect_signatures, CoinJoinCallBuilder, CoinJoinContribution, CoinJoinSession,
+        },
+        transfer_v1::{select_coins, TransferCallInput, TransferCallOutput},
+        OwnCoin,
+    },
+    model::MoneyTransferParamsV1,
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{contract_id::MONEY_CONTRACT_ID, schnorr::Signature, Blind, FuncId, PublicKey},
+    pasta::pallas,
+    tx::ContractCall,
+};
+use darkfi_serial::{deserialize, Encodable};
+
+use crate::{money::BALANCE_BASE10_DECIMALS, Drk};
+
+impl Drk {
+    /// Create our contribution to a CoinJoin, paying `amount` of the session
+    /// token to `recipient` and sending any change back to us.
+    pub async fn contribute_coinjoin(
+        &self,
+        session: CoinJoinSession,
+        amount: &str,
+        recipient: PublicKey,
+    ) -> Result<CoinJoinContribution> {
+        self.check_spendable().await?;
+
+        // First get all unspent OwnCoins of the session token,
+        // not owned by some protocol.
+        let owncoins = self.get_account_coins(false).await?;
+        let mut owncoins: Vec<OwnCoin> = owncoins.iter().map(|x| x.0.clone()).collect();
+        owncoins.retain(|x| x.note.token_id == session.token_id);
+        owncoins.retain(|x| x.note.spend_hook == FuncId::none());
+        if owncoins.is_empty() {
+            return Err(Error::Custom(format!(
+                "Did not find any coins with token ID: {}",
+                session.token_id
+            )))
+        }
+
+        let amount = decode_base10(amount, BALANCE_BASE10_DECIMALS, false)?;
+        let (spent_coins, change_value) = select_coins(owncoins, amount)?;
+
+        // We'll also need our Merkle tree
+        let tree = self.get_money_tree().await?;
+        let frozen_tokens = self.get_money_frozen_smt().await?;
+
+        let mut inputs = vec![];
+        for coin in spent_coins {
+            inputs.push(TransferCallInput {
+                merkle_path: tree.witness(coin.leaf_position, 0).unwrap(),
+                user_data_blind: Blind::random(&mut OsRng),
+                frozen_root: frozen_tokens.root(),
+                frozen_path: frozen_tokens.prove_membership(&session.token_id.inner()),
+                coin,
+            });
+        }
+
+        let mut outputs = vec![TransferCallOutput {
+            public_key: recipient,
+            value: amount,
+            token_id: session.token_id,
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+            blind: Blind::random(&mut OsRng),
+        }];
+
+        if change_value > 0 {
+            outputs.push(TransferCallOutput {
+                public_key: self.default_address().await?,
+                value: change_value,
+                token_id: session.token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            });
+        }
+
+        // Now we need to do a lookup for the zkas proof bincodes, and create
+        // the circuit objects and proving keys so we can build the contribution.
+        // We also do this through the RPC.
+        let zkas_bins = self.lookup_zkas(&MONEY_CONTRACT_ID).await?;
+
+        let Some(mint_zkbin) = zkas_bins.iter().find(|x| x.0 == MONEY_CONTRACT_ZKAS_MINT_NS_V1)
+        else {
+            return Err(Error::Custom("Mint circuit not found".to_string()))
+        };
+
+        let Some(burn_zkbin) = zkas_bins.iter().find(|x| x.0 == MONEY_CONTRACT_ZKAS_BURN_NS_V1)
+        else {
+            return Err(Error::Custom("Burn circuit not found".to_string()))
+        };
+
+        println!("Creating Mint and Burn circuit proving keys");
+        let (mint_zkbin, mint_pk) = self.key_cache.proving_key(&mint_zkbin.1)?;
+        let (burn_zkbin, burn_pk) = self.key_cache.proving_key(&burn_zkbin.1)?;
+        let builder = CoinJoinCallBuilder {
+            session,
+            inputs,
+            outputs,
+            mint_zkbin,
+            mint_pk,
+            burn_zkbin,
+            burn_pk,
+        };
+
+        println!("Building CoinJoin contribution");
+        builder.build()
+    }
+
+    /// Verify and merge the given contributions into an unsigned CoinJoin
+    /// transaction, to be handed over to the participants for signing.
+    pub fn join_coinjoin(
+        &self,
+        session: &CoinJoinSession,
+        contributions: &[CoinJoinContribution],
+    ) -> Result<Transaction> {
+        let (params, proofs) = session.join(contributions)?;
+        println!(
+            "Joined {} contributions with {} inputs and {} outputs",
+            contributions.len(),
+            params.inputs.len(),
+            params.outputs.len()
+        );
+
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        params.encode(&mut data)?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+        let mut tx_builder = TransactionBuilder::new(ContractCallLeaf { call, proofs }, vec![])?;
+        Ok(tx_builder.build()?)
+    }
+
+    /// Verify our contribution is part of the given CoinJoin transaction,
+    /// and sign the inputs spending our coins.
+    pub async fn sign_coinjoin(
+        &self,
+        contribution: &CoinJoinContribution,
+        tx: &Transaction,
+    ) -> Result<Vec<(u64, Signature)>> {
+        let owncoins = self.get_account_coins(false).await?;
+        let owncoins: Vec<OwnCoin> = owncoins.iter().map(|x| x.0.clone()).collect();
+
+        let value: u64 = owncoins
+            .iter()
+            .filter(|x| contribution.params.inputs.iter().any(|i| i.nullifier == x.nullifier()))
+            .map(|x| x.note.value)
+            .sum();
+        println!("Signing inputs spending {}", encode_base10(value, BALANCE_BASE10_DECIMALS));
+
+        contribution.sign(tx, 0, &owncoins)
+    }
+
+    /// Put the participants' signatures into the CoinJoin transaction
+    pub fn finalize_coinjoin(
+        &self,
+        tx: &mut Transaction,
+        partial_sigs: &[Vec<(u64, Signature)>],
+    ) -> Result<()> {
+        let params: MoneyTransferParamsV1 = deserialize(&tx.calls[0].data.data[1..])?;
+        let sigs = collect_signatures(tx, 0, &params, partial_sigs)?;
+        tx.signatures = vec![sigs];
+        Ok(())
+    }
+}
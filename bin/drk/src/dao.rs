@@ -25,13 +25,18 @@ use rusqlite::types::Value;
 use darkfi::{
     tx::{ContractCallLeaf, Transaction, TransactionBuilder},
     util::parse::encode_base10,
-    zk::{empty_witnesses, halo2::Field, ProvingKey, ZkCircuit},
-    zkas::ZkBinary,
+    zk::halo2::Field,
     Error, Result,
 };
 use darkfi_dao_contract::{
-    client::{make_mint_call, DaoProposeCall, DaoProposeStakeInput, DaoVoteCall, DaoVoteInput},
-    model::{DaoAuthCall, DaoBulla, DaoMintParams, DaoProposeParams, DaoVoteParams},
+    client::{
+        make_mint_call, proposer_secret, DaoProposeCall, DaoProposeStakeInput, DaoVoteCall,
+        DaoVoteInput,
+    },
+    model::{
+        DaoAmendParams, DaoAuthCall, DaoBulla, DaoMintParams, DaoProposeParams, DaoVoteParams,
+        DaoVoteWeight,
+    },
     DaoFunction, DAO_CONTRACT_ZKAS_DAO_MINT_NS, DAO_CONTRACT_ZKAS_DAO_PROPOSE_INPUT_NS,
     DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS, DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS,
     DAO_CONTRACT_ZKAS_DAO_VOTE_MAIN_NS,
@@ -69,17 +74,23 @@ lazy_static! {
     pub static ref DAO_PROPOSALS_TABLE: String =
         format!("{}_dao_proposals", DAO_CONTRACT_ID.to_string());
     pub static ref DAO_VOTES_TABLE: String = format!("{}_dao_votes", DAO_CONTRACT_ID.to_string());
+    pub static ref DAO_SUPERSEDED_TABLE: String =
+        format!("{}_dao_superseded", DAO_CONTRACT_ID.to_string());
 }
 
 // DAO_DAOS_TABLE
 pub const DAO_DAOS_COL_DAO_ID: &str = "dao_id";
 pub const DAO_DAOS_COL_NAME: &str = "name";
 pub const DAO_DAOS_COL_PROPOSER_LIMIT: &str = "proposer_limit";
+pub const DAO_DAOS_COL_PROPOSAL_DEPOSIT: &str = "proposal_deposit";
 pub const DAO_DAOS_COL_QUORUM: &str = "quorum";
+pub const DAO_DAOS_COL_EARLY_EXEC_QUORUM: &str = "early_exec_quorum";
 pub const DAO_DAOS_COL_APPROVAL_RATIO_BASE: &str = "approval_ratio_base";
 pub const DAO_DAOS_COL_APPROVAL_RATIO_QUOT: &str = "approval_ratio_quot";
+pub const DAO_DAOS_COL_VOTE_WEIGHT: &str = "vote_weight";
 pub const DAO_DAOS_COL_GOV_TOKEN_ID: &str = "gov_token_id";
 pub const DAO_DAOS_COL_SECRET: &str = "secret";
+pub const DAO_DAOS_COL_GUARDIAN_PUBLIC_KEY: &str = "guardian_public_key";
 pub const DAO_DAOS_COL_BULLA_BLIND: &str = "bulla_blind";
 pub const DAO_DAOS_COL_LEAF_POSITION: &str = "leaf_position";
 pub const DAO_DAOS_COL_TX_HASH: &str = "tx_hash";
@@ -116,6 +127,13 @@ pub const DAO_VOTES_COL_ALL_VOTE_BLIND: &str = "all_vote_blind";
 pub const DAO_VOTES_COL_TX_HASH: &str = "tx_hash";
 pub const DAO_VOTES_COL_CALL_INDEX: &str = "call_index";
 
+// DAO_SUPERSEDED_TABLE
+pub const DAO_SUPERSEDED_COL_DAO_BULLA: &str = "dao_bulla";
+pub const DAO_SUPERSEDED_COL_SUCCESSOR: &str = "successor";
+
+/// Number of blocks a proposal stays open for voting
+pub const DAO_PROPOSAL_DURATION_BLOCKS: u64 = 4800;
+
 #[derive(SerialEncodable, SerialDecodable, Clone)]
 pub struct DaoProposalInfo {
     pub dest: PublicKey,
@@ -134,15 +152,23 @@ pub struct DaoProposeNote {
 pub struct DaoParams {
     /// The minimum amount of governance tokens needed to open a proposal
     pub proposer_limit: u64,
+    /// Governance tokens bonded when proposing, slashed if overwhelmingly rejected
+    pub proposal_deposit: u64,
     /// Minimal threshold of participating total tokens needed for a proposal to pass
     pub quorum: u64,
+    /// Yes votes needed to execute a proposal before its voting period ends
+    pub early_exec_quorum: u64,
     /// The ratio of winning/total votes needed for a proposal to pass
     pub approval_ratio_base: u64,
     pub approval_ratio_quot: u64,
+    /// Function applied to voters' balances to get their vote weight
+    pub vote_weight: DaoVoteWeight,
     /// DAO's governance token ID
     pub gov_token_id: TokenId,
     /// Secret key for the DAO
     pub secret_key: SecretKey,
+    /// Optional guardian who can veto proposals before they're executed
+    pub guardian_public_key: Option<PublicKey>,
     /// DAO bulla blind
     pub bulla_blind: pallas::Base,
 }
@@ -150,23 +176,33 @@ pub struct DaoParams {
 impl fmt::Display for DaoParams {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let s = format!(
-            "{}\n{}\n{}: {} ({})\n{}: {} ({})\n{}: {}\n{}: {}\n{}: {}\n{}: {}\n{}: {:?}",
+            "{}\n{}\n{}: {} ({})\n{}: {} ({})\n{}: {} ({})\n{}: {} ({})\n{}: {}\n{}: {:?}\n{}: {}\n{}: {}\n{}: {}\n{}: {:?}\n{}: {:?}",
             "DAO Parameters",
             "==============",
             "Proposer limit",
             encode_base10(self.proposer_limit, BALANCE_BASE10_DECIMALS),
             self.proposer_limit,
+            "Proposal deposit",
+            encode_base10(self.proposal_deposit, BALANCE_BASE10_DECIMALS),
+            self.proposal_deposit,
             "Quorum",
             encode_base10(self.quorum, BALANCE_BASE10_DECIMALS),
             self.quorum,
+            "Early execution quorum",
+            encode_base10(self.early_exec_quorum, BALANCE_BASE10_DECIMALS),
+            self.early_exec_quorum,
             "Approval ratio",
             self.approval_ratio_quot as f64 / self.approval_ratio_base as f64,
+            "Vote weight",
+            self.vote_weight,
             "Governance Token ID",
             self.gov_token_id,
             "Public key",
             PublicKey::from_secret(self.secret_key),
             "Secret key",
             self.secret_key,
+            "Guardian public key",
+            self.guardian_public_key,
             "Bulla blind",
             self.bulla_blind,
         );
@@ -184,15 +220,23 @@ pub struct Dao {
     pub name: String,
     /// The minimum amount of governance tokens needed to open a proposal
     pub proposer_limit: u64,
+    /// Governance tokens bonded when proposing, slashed if overwhelmingly rejected
+    pub proposal_deposit: u64,
     /// Minimal threshold of participating total tokens needed for a proposal to pass
     pub quorum: u64,
+    /// Yes votes needed to execute a proposal before its voting period ends
+    pub early_exec_quorum: u64,
     /// The ratio of winning/total votes needed for a proposal to pass
     pub approval_ratio_base: u64,
     pub approval_ratio_quot: u64,
+    /// Function applied to voters' balances to get their vote weight
+    pub vote_weight: DaoVoteWeight,
     /// DAO's governance token ID
     pub gov_token_id: TokenId,
     /// Secret key for the DAO
     pub secret_key: SecretKey,
+    /// Optional guardian who can veto proposals before they're executed
+    pub guardian_public_key: Option<PublicKey>,
     /// DAO bulla blind
     pub bulla_blind: BaseBlind,
     /// Leaf position of the DAO in the Merkle tree of DAOs
@@ -206,15 +250,25 @@ pub struct Dao {
 impl Dao {
     pub fn bulla(&self) -> DaoBulla {
         let (x, y) = PublicKey::from_secret(self.secret_key).xy();
+        let (guardian_x, guardian_y) = match self.guardian_public_key {
+            Some(guardian_public_key) => guardian_public_key.xy(),
+            None => (pallas::Base::ZERO, pallas::Base::ZERO),
+        };
 
         DaoBulla::from(poseidon_hash([
             pallas::Base::from(self.proposer_limit),
+            pallas::Base::from(self.proposal_deposit),
             pallas::Base::from(self.quorum),
+            pallas::Base::from(self.early_exec_quorum),
             pallas::Base::from(self.approval_ratio_quot),
             pallas::Base::from(self.approval_ratio_base),
+            pallas::Base::from(self.vote_weight.mode()),
+            pallas::Base::from(self.vote_weight.cap()),
             self.gov_token_id.inner(),
             x,
             y,
+            guardian_x,
+            guardian_y,
             self.bulla_blind.inner(),
         ]))
     }
@@ -228,7 +282,7 @@ impl Dao {
 impl fmt::Display for Dao {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let s = format!(
-            "{}\n{}\n{}: {}\n{}: {}\n{}: {} ({})\n{}: {} ({})\n{}: {}\n{}: {}\n{}: {}\n{}: {}\n{}: {:?}\n{}: {:?}\n{}: {:?}\n{}: {:?}",
+            "{}\n{}\n{}: {}\n{}: {}\n{}: {} ({})\n{}: {} ({})\n{}: {} ({})\n{}: {} ({})\n{}: {}\n{}: {:?}\n{}: {}\n{}: {}\n{}: {}\n{}: {:?}\n{}: {:?}\n{}: {:?}\n{}: {:?}\n{}: {:?}",
             "DAO Parameters",
             "==============",
             "Name",
@@ -238,17 +292,27 @@ impl fmt::Display for Dao {
             "Proposer limit",
             encode_base10(self.proposer_limit, BALANCE_BASE10_DECIMALS),
             self.proposer_limit,
+            "Proposal deposit",
+            encode_base10(self.proposal_deposit, BALANCE_BASE10_DECIMALS),
+            self.proposal_deposit,
             "Quorum",
             encode_base10(self.quorum, BALANCE_BASE10_DECIMALS),
             self.quorum,
+            "Early execution quorum",
+            encode_base10(self.early_exec_quorum, BALANCE_BASE10_DECIMALS),
+            self.early_exec_quorum,
             "Approval ratio",
             self.approval_ratio_quot as f64 / self.approval_ratio_base as f64,
+            "Vote weight",
+            self.vote_weight,
             "Governance Token ID",
             self.gov_token_id,
             "Public key",
             PublicKey::from_secret(self.secret_key),
             "Secret key",
             self.secret_key,
+            "Guardian public key",
+            self.guardian_public_key,
             "Bulla blind",
             self.bulla_blind,
             "Leaf position",
@@ -468,41 +532,63 @@ impl Drk {
             };
             let proposer_limit = deserialize(proposer_limit_bytes)?;
 
-            let Value::Blob(ref quorum_bytes) = row[3] else {
+            let Value::Blob(ref proposal_deposit_bytes) = row[3] else {
+                return Err(Error::ParseFailed("[get_daos] Proposal deposit bytes parsing failed"))
+            };
+            let proposal_deposit = deserialize(proposal_deposit_bytes)?;
+
+            let Value::Blob(ref quorum_bytes) = row[4] else {
                 return Err(Error::ParseFailed("[get_daos] Quorum bytes parsing failed"))
             };
             let quorum = deserialize(quorum_bytes)?;
 
-            let Value::Integer(approval_ratio_base) = row[4] else {
+            let Value::Blob(ref early_exec_quorum_bytes) = row[5] else {
+                return Err(Error::ParseFailed("[get_daos] Early exec quorum bytes parsing failed"))
+            };
+            let early_exec_quorum = deserialize(early_exec_quorum_bytes)?;
+
+            let Value::Integer(approval_ratio_base) = row[6] else {
                 return Err(Error::ParseFailed("[get_daos] Approval ratio base parsing failed"))
             };
             let Ok(approval_ratio_base) = u64::try_from(approval_ratio_base) else {
                 return Err(Error::ParseFailed("[get_daos] Approval ratio base parsing failed"))
             };
 
-            let Value::Integer(approval_ratio_quot) = row[5] else {
+            let Value::Integer(approval_ratio_quot) = row[7] else {
                 return Err(Error::ParseFailed("[get_daos] Approval ratio quot parsing failed"))
             };
             let Ok(approval_ratio_quot) = u64::try_from(approval_ratio_quot) else {
                 return Err(Error::ParseFailed("[get_daos] Approval ratio quot parsing failed"))
             };
 
-            let Value::Blob(ref gov_token_bytes) = row[6] else {
+            let Value::Blob(ref vote_weight_bytes) = row[8] else {
+                return Err(Error::ParseFailed("[get_daos] Vote weight bytes parsing failed"))
+            };
+            let vote_weight = deserialize(vote_weight_bytes)?;
+
+            let Value::Blob(ref gov_token_bytes) = row[9] else {
                 return Err(Error::ParseFailed("[get_daos] Gov token bytes parsing failed"))
             };
             let gov_token_id = deserialize(gov_token_bytes)?;
 
-            let Value::Blob(ref secret_bytes) = row[7] else {
+            let Value::Blob(ref secret_bytes) = row[10] else {
                 return Err(Error::ParseFailed("[get_daos] Secret key bytes parsing failed"))
             };
             let secret_key = deserialize(secret_bytes)?;
 
-            let Value::Blob(ref bulla_blind_bytes) = row[8] else {
+            let Value::Blob(ref guardian_public_key_bytes) = row[11] else {
+                return Err(Error::ParseFailed(
+                    "[get_daos] Guardian public key bytes parsing failed",
+                ))
+            };
+            let guardian_public_key = deserialize(guardian_public_key_bytes)?;
+
+            let Value::Blob(ref bulla_blind_bytes) = row[12] else {
                 return Err(Error::ParseFailed("[get_daos] Bulla blind bytes parsing failed"))
             };
             let bulla_blind = deserialize(bulla_blind_bytes)?;
 
-            let Value::Blob(ref leaf_position_bytes) = row[9] else {
+            let Value::Blob(ref leaf_position_bytes) = row[13] else {
                 return Err(Error::ParseFailed("[get_daos] Leaf position bytes parsing failed"))
             };
             let leaf_position = if leaf_position_bytes.is_empty() {
@@ -511,13 +597,13 @@ impl Drk {
                 Some(deserialize(leaf_position_bytes)?)
             };
 
-            let Value::Blob(ref tx_hash_bytes) = row[10] else {
+            let Value::Blob(ref tx_hash_bytes) = row[14] else {
                 return Err(Error::ParseFailed("[get_daos] Transaction hash bytes parsing failed"))
             };
             let tx_hash =
                 if tx_hash_bytes.is_empty() { None } else { Some(deserialize(tx_hash_bytes)?) };
 
-            let Value::Integer(call_index) = row[11] else {
+            let Value::Integer(call_index) = row[15] else {
                 return Err(Error::ParseFailed("[get_daos] Call index parsing failed"))
             };
             let Ok(call_index) = u32::try_from(call_index) else {
@@ -529,11 +615,15 @@ impl Drk {
                 id,
                 name,
                 proposer_limit,
+                proposal_deposit,
                 quorum,
+                early_exec_quorum,
                 approval_ratio_base,
                 approval_ratio_quot,
+                vote_weight,
                 gov_token_id,
                 secret_key,
+                guardian_public_key,
                 bulla_blind,
                 leaf_position,
                 tx_hash,
@@ -702,9 +792,13 @@ impl Drk {
             u32,
         )> = vec![];
         let mut our_proposals: Vec<DaoProposal> = vec![];
+        // Names of the DAOs our proposals belong to
+        let mut our_proposals_daos: Vec<String> = vec![];
         // DAO votes that have been seen
         let mut new_dao_votes: Vec<(DaoVoteParams, Option<blake3::Hash>, u32)> = vec![];
         let mut dao_votes: Vec<DaoVote> = vec![];
+        // DAOs that have been amended, and their successors
+        let mut superseded_daos: Vec<(DaoBulla, DaoBulla)> = vec![];
 
         // Run through the transaction and see what we got:
         for (i, call) in tx.calls.iter().enumerate() {
@@ -739,6 +833,26 @@ impl Drk {
                 println!("Found Dao::Exec in call {i}");
                 continue
             }
+
+            if call.data.contract_id == cid && call.data.data[0] == DaoFunction::Cancel as u8 {
+                // The proposal can no longer be voted on or executed
+                println!("Found Dao::Cancel in call {i}");
+                continue
+            }
+
+            if call.data.contract_id == cid && call.data.data[0] == DaoFunction::Veto as u8 {
+                // The proposal can no longer be executed
+                println!("Found Dao::Veto in call {i}");
+                continue
+            }
+
+            if call.data.contract_id == cid && call.data.data[0] == DaoFunction::Amend as u8 {
+                // The old DAO can no longer make or execute proposals
+                println!("Found Dao::Amend in call {i}");
+                let params: DaoAmendParams = deserialize(&call.data.data[1..])?;
+                superseded_daos.push((params.old_dao_bulla, params.new_dao_bulla));
+                continue
+            }
         }
 
         // This code should only be executed when finalized blocks are being scanned.
@@ -789,6 +903,7 @@ impl Drk {
                         };
 
                         our_proposals.push(our_prop);
+                        our_proposals_daos.push(dao.name.clone());
                         break
                     }
                 }
@@ -849,11 +964,32 @@ impl Drk {
                 )))
             }
             self.put_dao_proposals(&our_proposals).await?;
+            if !our_proposals.is_empty() {
+                let creation_height = match self.last_scanned_block().await {
+                    Ok(h) => h,
+                    Err(e) => {
+                        return Err(Error::RusqliteError(format!(
+                            "[apply_tx_dao_data] Retrieving last scanned block failed: {e:?}"
+                        )))
+                    }
+                };
+                for (proposal, dao_name) in our_proposals.iter().zip(our_proposals_daos.iter()) {
+                    self.track_dao_proposal(dao_name, proposal.bulla().into(), creation_height)
+                        .await?;
+                }
+            }
             if let Err(e) = self.put_dao_votes(&dao_votes).await {
                 return Err(Error::RusqliteError(format!(
                     "[apply_tx_dao_data] Put DAO votes failed: {e:?}"
                 )))
             }
+            for (old_dao_bulla, new_dao_bulla) in superseded_daos {
+                if let Err(e) = self.put_dao_superseded(&old_dao_bulla, &new_dao_bulla).await {
+                    return Err(Error::RusqliteError(format!(
+                        "[apply_tx_dao_data] Superseding amended DAO failed: {e:?}"
+                    )))
+                }
+            }
         }
 
         Ok(())
@@ -1038,6 +1174,84 @@ impl Drk {
         self.wallet.exec_sql(&query, &[]).await
     }
 
+    /// Mark `old_dao_bulla` superseded by `new_dao_bulla` in the wallet,
+    /// along with every DAO it superseded itself.
+    pub async fn put_dao_superseded(
+        &self,
+        old_dao_bulla: &DaoBulla,
+        new_dao_bulla: &DaoBulla,
+    ) -> WalletDbResult<()> {
+        let query = format!(
+            "UPDATE {} SET {} = ?1 WHERE {} = ?2;",
+            *DAO_SUPERSEDED_TABLE, DAO_SUPERSEDED_COL_SUCCESSOR, DAO_SUPERSEDED_COL_SUCCESSOR,
+        );
+        self.wallet
+            .exec_sql(&query, rusqlite::params![serialize(new_dao_bulla), serialize(old_dao_bulla)])
+            .await?;
+
+        let query = format!(
+            "INSERT OR REPLACE INTO {} ({}, {}) VALUES (?1, ?2);",
+            *DAO_SUPERSEDED_TABLE, DAO_SUPERSEDED_COL_DAO_BULLA, DAO_SUPERSEDED_COL_SUCCESSOR,
+        );
+        self.wallet
+            .exec_sql(&query, rusqlite::params![serialize(old_dao_bulla), serialize(new_dao_bulla)])
+            .await
+    }
+
+    /// Reset all amended DAOs in the wallet.
+    pub async fn reset_dao_superseded(&self) -> WalletDbResult<()> {
+        println!("Resetting amended DAOs");
+        let query = format!("DELETE FROM {};", *DAO_SUPERSEDED_TABLE);
+        self.wallet.exec_sql(&query, &[]).await
+    }
+
+    /// Build the SMT of amended DAOs, used to prove a DAO making a
+    /// proposal was not superseded.
+    pub async fn get_dao_superseded_smt(&self) -> Result<SmtMemoryFp> {
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &DAO_SUPERSEDED_TABLE,
+                &[DAO_SUPERSEDED_COL_DAO_BULLA, DAO_SUPERSEDED_COL_SUCCESSOR],
+                &[],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_dao_superseded_smt] Amended DAOs retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut leaves = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Blob(ref dao_bulla_bytes) = row[0] else {
+                return Err(Error::ParseFailed(
+                    "[get_dao_superseded_smt] DAO bulla bytes parsing failed",
+                ))
+            };
+            let dao_bulla: DaoBulla = deserialize(dao_bulla_bytes)?;
+
+            let Value::Blob(ref successor_bytes) = row[1] else {
+                return Err(Error::ParseFailed(
+                    "[get_dao_superseded_smt] Successor bytes parsing failed",
+                ))
+            };
+            let successor: DaoBulla = deserialize(successor_bytes)?;
+
+            leaves.push((dao_bulla.inner(), successor.inner()));
+        }
+
+        let hasher = PoseidonFp::new();
+        let store = MemoryStorageFp::new();
+        let mut smt = SmtMemoryFp::new(store, hasher, &EMPTY_NODES_FP);
+        smt.insert_batch(leaves)?;
+
+        Ok(smt)
+    }
+
     /// Import given DAO params into the wallet with a given name.
     pub async fn import_dao(&self, dao_name: String, dao_params: DaoParams) -> Result<()> {
         // First let's check if we've imported this DAO with the given name before.
@@ -1054,15 +1268,19 @@ impl Drk {
         println!("Importing \"{dao_name}\" DAO into the wallet");
 
         let query = format!(
-            "INSERT INTO {} ({}, {}, {}, {}, {}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);",
+            "INSERT INTO {} ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12);",
             *DAO_DAOS_TABLE,
             DAO_DAOS_COL_NAME,
             DAO_DAOS_COL_PROPOSER_LIMIT,
+            DAO_DAOS_COL_PROPOSAL_DEPOSIT,
             DAO_DAOS_COL_QUORUM,
+            DAO_DAOS_COL_EARLY_EXEC_QUORUM,
             DAO_DAOS_COL_APPROVAL_RATIO_BASE,
             DAO_DAOS_COL_APPROVAL_RATIO_QUOT,
+            DAO_DAOS_COL_VOTE_WEIGHT,
             DAO_DAOS_COL_GOV_TOKEN_ID,
             DAO_DAOS_COL_SECRET,
+            DAO_DAOS_COL_GUARDIAN_PUBLIC_KEY,
             DAO_DAOS_COL_BULLA_BLIND,
         );
         if let Err(e) = self
@@ -1072,11 +1290,15 @@ impl Drk {
                 rusqlite::params![
                     dao_name,
                     serialize(&dao_params.proposer_limit),
+                    serialize(&dao_params.proposal_deposit),
                     serialize(&dao_params.quorum),
+                    serialize(&dao_params.early_exec_quorum),
                     dao_params.approval_ratio_base,
                     dao_params.approval_ratio_quot,
+                    serialize(&dao_params.vote_weight),
                     serialize(&dao_params.gov_token_id),
                     serialize(&dao_params.secret_key),
+                    serialize(&dao_params.guardian_public_key),
                     serialize(&dao_params.bulla_blind),
                 ],
             )
@@ -1349,11 +1571,15 @@ impl Drk {
         // we use the structs from contract everwhere
         let dao_info = darkfi_dao_contract::model::Dao {
             proposer_limit: dao.proposer_limit,
+            proposal_deposit: dao.proposal_deposit,
             quorum: dao.quorum,
+            early_exec_quorum: dao.early_exec_quorum,
             approval_ratio_base: dao.approval_ratio_base,
             approval_ratio_quot: dao.approval_ratio_quot,
+            vote_weight: dao.vote_weight,
             gov_token_id: dao.gov_token_id,
             public_key: PublicKey::from_secret(dao.secret_key),
+            guardian_public_key: dao.guardian_public_key,
             bulla_blind: dao.bulla_blind,
         };
 
@@ -1363,10 +1589,8 @@ impl Drk {
             return Err(Error::RusqliteError("[dao_mint] DAO Mint circuit not found".to_string()))
         };
 
-        let dao_mint_zkbin = ZkBinary::decode(&dao_mint_zkbin.1)?;
-        let dao_mint_circuit = ZkCircuit::new(empty_witnesses(&dao_mint_zkbin)?, &dao_mint_zkbin);
         println!("Creating DAO Mint proving key");
-        let dao_mint_pk = ProvingKey::build(dao_mint_zkbin.k, &dao_mint_circuit);
+        let (dao_mint_zkbin, dao_mint_pk) = self.key_cache.proving_key(&dao_mint_zkbin.1)?;
 
         let (params, proofs) =
             make_mint_call(&dao_info, &dao.secret_key, &dao_mint_zkbin, &dao_mint_pk)?;
@@ -1390,6 +1614,8 @@ impl Drk {
         amount: u64,
         token_id: TokenId,
     ) -> Result<Transaction> {
+        self.check_spendable().await?;
+
         let Ok(dao) = self.get_dao_by_id(dao_id).await else {
             return Err(Error::RusqliteError("[dao_propose] DAO not found in wallet".to_string()))
         };
@@ -1400,6 +1626,15 @@ impl Drk {
             ))
         }
 
+        // The deposit coin has to be minted by a `Money::Transfer` in the
+        // same transaction, which isn't built here yet.
+        if dao.proposal_deposit != 0 {
+            return Err(Error::Custom(
+                "[dao_propose] Proposing to DAOs with a proposal deposit is not supported yet"
+                    .to_string(),
+            ))
+        }
+
         let bulla = dao.bulla();
         let owncoins = self.get_coins(false).await?;
 
@@ -1469,18 +1704,12 @@ impl Drk {
             return Err(Error::Custom("[dao_propose] Propose Main circuit not found".to_string()))
         };
 
-        let propose_burn_zkbin = ZkBinary::decode(&propose_burn_zkbin.1)?;
-        let propose_main_zkbin = ZkBinary::decode(&propose_main_zkbin.1)?;
-
-        let propose_burn_circuit =
-            ZkCircuit::new(empty_witnesses(&propose_burn_zkbin)?, &propose_burn_zkbin);
-        let propose_main_circuit =
-            ZkCircuit::new(empty_witnesses(&propose_main_zkbin)?, &propose_main_zkbin);
-
         println!("Creating Propose Burn circuit proving key");
-        let propose_burn_pk = ProvingKey::build(propose_burn_zkbin.k, &propose_burn_circuit);
+        let (propose_burn_zkbin, propose_burn_pk) =
+            self.key_cache.proving_key(&propose_burn_zkbin.1)?;
         println!("Creating Propose Main circuit proving key");
-        let propose_main_pk = ProvingKey::build(propose_main_zkbin.k, &propose_main_circuit);
+        let (propose_main_zkbin, propose_main_pk) =
+            self.key_cache.proving_key(&propose_main_zkbin.1)?;
 
         // Now create the parameters for the proposal tx
         let signature_secret = SecretKey::random(&mut OsRng);
@@ -1514,17 +1743,17 @@ impl Drk {
             let dao_merkle_path = daos_tree.witness(leaf_pos, 0).unwrap();
             (dao_merkle_path, root)
         };
+        let dao_superseded_smt = self.get_dao_superseded_smt().await?;
 
         // TODO:
         /*
         // Convert coin_params to actual coins
-        let mut proposal_coins = vec![];
-        for coin_params in proposal_coinattrs {
-            proposal_coins.push(coin_params.to_coin());
-        }
+        let auth_data = DaoAuthMoneyTransferData {
+            coins: proposal_coinattrs.iter().map(|c| c.to_coin()).collect(),
+        };
         */
         let proposal_data = vec![];
-        //proposal_coins.encode(&mut proposal_data).unwrap();
+        //auth_data.encode(&mut proposal_data).unwrap();
 
         let auth_calls = vec![
             DaoAuthCall {
@@ -1539,30 +1768,46 @@ impl Drk {
             },
         ];
 
-        // TODO: get current height to calculate day
-        // Also contract must check we don't mint a proposal that its creation day is
-        // less than current height
+        // The proposal is created at our last scanned block height, so the
+        // transaction must be confirmed soon after that.
+        let creation_height = match self.last_scanned_block().await {
+            Ok(h) => h,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[dao_propose] Retrieving last scanned block failed: {e:?}"
+                )))
+            }
+        };
 
         // TODO: Simplify this model struct import once
         // we use the structs from contract everwhere
         let proposal = darkfi_dao_contract::model::DaoProposal {
             auth_calls,
-            creation_day: 0,
-            duration_days: 30,
+            options: vec![],
+            creation_height,
+            duration_blocks: DAO_PROPOSAL_DURATION_BLOCKS,
             user_data: pallas::Base::ZERO,
             dao_bulla: dao.bulla(),
             blind: Blind::random(&mut OsRng),
         };
 
+        // Only we can cancel the proposal, with a key derived from our
+        // default secret
+        let proposer_secret = proposer_secret(&self.default_secret().await?, &proposal);
+
         // TODO: Simplify this model struct import once
         // we use the structs from contract everwhere
         let daoinfo = darkfi_dao_contract::model::Dao {
             proposer_limit: dao.proposer_limit,
+            proposal_deposit: dao.proposal_deposit,
             quorum: dao.quorum,
+            early_exec_quorum: dao.early_exec_quorum,
             approval_ratio_quot: dao.approval_ratio_quot,
             approval_ratio_base: dao.approval_ratio_base,
+            vote_weight: dao.vote_weight,
             gov_token_id: dao.gov_token_id,
             public_key: PublicKey::from_secret(dao.secret_key),
+            guardian_public_key: dao.guardian_public_key,
             bulla_blind: dao.bulla_blind,
         };
 
@@ -1573,6 +1818,12 @@ impl Drk {
             dao_leaf_position: dao.leaf_position.unwrap(),
             dao_merkle_path,
             dao_merkle_root,
+            dao_superseded_smt: &dao_superseded_smt,
+            tally_public_key: None,
+            proposer_public_key: PublicKey::from_secret(proposer_secret),
+            memo: None,
+            deposit_refund_public_key: self.default_address().await?,
+            deposit_coin_blind: Blind::random(&mut OsRng),
         };
 
         println!("Creating ZK proofs...");
@@ -1602,6 +1853,8 @@ impl Drk {
         vote_option: bool,
         weight: u64,
     ) -> Result<Transaction> {
+        self.check_spendable().await?;
+
         let dao = self.get_dao_by_id(dao_id).await?;
         let proposals = self.get_dao_proposals(dao_id).await?;
         let Some(proposal) = proposals.iter().find(|x| x.id == proposal_id) else {
@@ -1611,7 +1864,7 @@ impl Drk {
         let money_tree = proposal.money_snapshot_tree.clone().unwrap();
 
         let mut coins: Vec<OwnCoin> =
-            self.get_coins(false).await?.iter().map(|x| x.0.clone()).collect();
+            self.get_account_coins(false).await?.iter().map(|x| x.0.clone()).collect();
 
         coins.retain(|x| x.note.token_id == dao.gov_token_id);
         coins.retain(|x| x.note.spend_hook == FuncId::none());
@@ -1660,8 +1913,9 @@ impl Drk {
         // we use the structs from contract everwhere
         let proposal = darkfi_dao_contract::model::DaoProposal {
             auth_calls: vec![],
-            creation_day: 0,
-            duration_days: 30,
+            options: vec![],
+            creation_height: 0,
+            duration_blocks: DAO_PROPOSAL_DURATION_BLOCKS,
             user_data: pallas::Base::ZERO,
             dao_bulla: dao.bulla(),
             blind: Blind::random(&mut OsRng),
@@ -1671,15 +1925,18 @@ impl Drk {
         // we use the structs from contract everwhere
         let dao_info = darkfi_dao_contract::model::Dao {
             proposer_limit: dao.proposer_limit,
+            proposal_deposit: dao.proposal_deposit,
             quorum: dao.quorum,
+            early_exec_quorum: dao.early_exec_quorum,
             approval_ratio_quot: dao.approval_ratio_quot,
             approval_ratio_base: dao.approval_ratio_base,
+            vote_weight: dao.vote_weight,
             gov_token_id: dao.gov_token_id,
             public_key: PublicKey::from_secret(dao.secret_key),
+            guardian_public_key: dao.guardian_public_key,
             bulla_blind: dao.bulla_blind,
         };
 
-        // TODO: get current height to calculate day
         let hasher = PoseidonFp::new();
         let store = MemoryStorageFp::new();
         let money_null_smt = SmtMemoryFp::new(store, hasher.clone(), &EMPTY_NODES_FP);
@@ -1687,11 +1944,13 @@ impl Drk {
         let call = DaoVoteCall {
             inputs,
             vote_option,
-            current_day: 0,
+            abstain: false,
+            max_block_height: proposal.creation_height + proposal.duration_blocks - 1,
             dao_keypair,
             proposal,
             money_null_smt: &money_null_smt,
             dao: dao_info,
+            tally_public_key: None,
         };
 
         let zkas_bins = self.lookup_zkas(&DAO_CONTRACT_ID).await?;
@@ -1707,18 +1966,12 @@ impl Drk {
             return Err(Error::Custom("[dao_vote] DAO Vote Main circuit not found".to_string()))
         };
 
-        let dao_vote_burn_zkbin = ZkBinary::decode(&dao_vote_burn_zkbin.1)?;
-        let dao_vote_main_zkbin = ZkBinary::decode(&dao_vote_main_zkbin.1)?;
-
-        let dao_vote_burn_circuit =
-            ZkCircuit::new(empty_witnesses(&dao_vote_burn_zkbin)?, &dao_vote_burn_zkbin);
-        let dao_vote_main_circuit =
-            ZkCircuit::new(empty_witnesses(&dao_vote_main_zkbin)?, &dao_vote_main_zkbin);
-
         println!("Creating DAO Vote Burn proving key");
-        let dao_vote_burn_pk = ProvingKey::build(dao_vote_burn_zkbin.k, &dao_vote_burn_circuit);
+        let (dao_vote_burn_zkbin, dao_vote_burn_pk) =
+            self.key_cache.proving_key(&dao_vote_burn_zkbin.1)?;
         println!("Creating DAO Vote Main proving key");
-        let dao_vote_main_pk = ProvingKey::build(dao_vote_main_zkbin.k, &dao_vote_main_circuit);
+        let (dao_vote_main_zkbin, dao_vote_main_pk) =
+            self.key_cache.proving_key(&dao_vote_main_zkbin.1)?;
 
         let (params, proofs) = call.make(
             &dao_vote_burn_zkbin,
@@ -28,6 +28,9 @@ pub enum WalletDbError {
 
     // Configuration related errors
     PragmaUpdateError = -32110,
+    KeyDerivationFailed = -32111,
+    SaltStorageFailed = -32112,
+    InvalidPassword = -32113,
 
     // Query execution related errors
     QueryPreparationFailed = -32120,
@@ -0,0 +1,232 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use lazy_static::lazy_static;
+use rusqlite::types::Value;
+
+use darkfi::{rpc::util::JsonValue, util::parse::encode_base10, Error, Result};
+use darkfi_dao_contract::model::DaoProposalBulla;
+use darkfi_money_contract::client::OwnCoin;
+use darkfi_sdk::crypto::DAO_CONTRACT_ID;
+use darkfi_serial::{deserialize, serialize};
+
+use crate::{
+    convert_named_params, dao::DAO_PROPOSAL_DURATION_BLOCKS, money::BALANCE_BASE10_DECIMALS, Drk,
+};
+
+// Wallet SQL table constant names. These have to represent the `dao.sql`
+// SQL schema. Table names are prefixed with the contract ID to avoid collisions.
+lazy_static! {
+    pub static ref DAO_PROPOSAL_WINDOWS_TABLE: String =
+        format!("{}_dao_proposal_windows", DAO_CONTRACT_ID.to_string());
+}
+
+// DAO_PROPOSAL_WINDOWS_TABLE
+const DAO_PROPOSAL_WINDOWS_COL_PROPOSAL_BULLA: &str = "proposal_bulla";
+const DAO_PROPOSAL_WINDOWS_COL_DAO_NAME: &str = "dao_name";
+const DAO_PROPOSAL_WINDOWS_COL_END_HEIGHT: &str = "end_height";
+const DAO_PROPOSAL_WINDOWS_COL_NOTIFIED: &str = "notified";
+
+/// Number of blocks before the end of a proposal's voting period
+/// at which a [`WalletEvent::VoteWindowClosing`] is emitted.
+pub const VOTE_WINDOW_CLOSING_BLOCKS: u64 = 100;
+
+/// Events emitted by the wallet while scanning blocks, so frontends
+/// and bots can react to changes without diffing the wallet state.
+#[derive(Clone, Debug)]
+pub enum WalletEvent {
+    /// A new coin belonging to us was found
+    CoinReceived(OwnCoin),
+    /// One of our coins got spent on-chain
+    CoinSpent(OwnCoin),
+    /// A proposal was created for one of our DAOs
+    DaoProposalCreated { dao_name: String, proposal: DaoProposalBulla, end_height: u64 },
+    /// The voting period of one of our DAOs' proposals is about to end
+    VoteWindowClosing { dao_name: String, proposal: DaoProposalBulla, end_height: u64 },
+}
+
+impl WalletEvent {
+    /// Name of the event, as exposed to consumers
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CoinReceived(_) => "coin_received",
+            Self::CoinSpent(_) => "coin_spent",
+            Self::DaoProposalCreated { .. } => "dao_proposal_created",
+            Self::VoteWindowClosing { .. } => "vote_window_closing",
+        }
+    }
+
+    /// Encode the event as a JSON object, tagged with its name.
+    pub fn to_json(&self) -> JsonValue {
+        let mut fields = vec![("event".to_string(), JsonValue::String(self.name().to_string()))];
+
+        match self {
+            Self::CoinReceived(coin) | Self::CoinSpent(coin) => {
+                fields.push(("coin".to_string(), JsonValue::String(coin.coin.to_string())));
+                fields.push((
+                    "token_id".to_string(),
+                    JsonValue::String(coin.note.token_id.to_string()),
+                ));
+                fields.push((
+                    "value".to_string(),
+                    JsonValue::String(encode_base10(coin.note.value, BALANCE_BASE10_DECIMALS)),
+                ));
+            }
+            Self::DaoProposalCreated { dao_name, proposal, end_height } |
+            Self::VoteWindowClosing { dao_name, proposal, end_height } => {
+                fields.push(("dao".to_string(), JsonValue::String(dao_name.clone())));
+                fields.push(("proposal".to_string(), JsonValue::String(proposal.to_string())));
+                fields.push(("end_height".to_string(), JsonValue::Number(*end_height as f64)));
+            }
+        }
+
+        JsonValue::Object(fields.into_iter().collect())
+    }
+}
+
+impl Drk {
+    /// Emit a newly found proposal of one of our DAOs, and start tracking
+    /// its voting period so we can warn before it closes.
+    pub async fn track_dao_proposal(
+        &self,
+        dao_name: &str,
+        proposal: DaoProposalBulla,
+        creation_height: u64,
+    ) -> Result<()> {
+        let end_height = creation_height + DAO_PROPOSAL_DURATION_BLOCKS;
+
+        let query = format!(
+            "INSERT OR REPLACE INTO {} ({}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4);",
+            *DAO_PROPOSAL_WINDOWS_TABLE,
+            DAO_PROPOSAL_WINDOWS_COL_PROPOSAL_BULLA,
+            DAO_PROPOSAL_WINDOWS_COL_DAO_NAME,
+            DAO_PROPOSAL_WINDOWS_COL_END_HEIGHT,
+            DAO_PROPOSAL_WINDOWS_COL_NOTIFIED,
+        );
+        if let Err(e) = self
+            .wallet
+            .exec_sql(&query, rusqlite::params![serialize(&proposal), dao_name, end_height, 0])
+            .await
+        {
+            return Err(Error::RusqliteError(format!(
+                "[track_dao_proposal] Inserting proposal window failed: {e:?}"
+            )))
+        }
+
+        self.events
+            .notify(WalletEvent::DaoProposalCreated {
+                dao_name: dao_name.to_string(),
+                proposal,
+                end_height,
+            })
+            .await;
+
+        Ok(())
+    }
+
+    /// Emit a [`WalletEvent::VoteWindowClosing`] for every tracked proposal
+    /// whose voting period ends within [`VOTE_WINDOW_CLOSING_BLOCKS`] of the
+    /// given block height. Each proposal is only notified once.
+    pub async fn check_vote_windows(&self, height: u64) -> Result<()> {
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &DAO_PROPOSAL_WINDOWS_TABLE,
+                &[
+                    DAO_PROPOSAL_WINDOWS_COL_PROPOSAL_BULLA,
+                    DAO_PROPOSAL_WINDOWS_COL_DAO_NAME,
+                    DAO_PROPOSAL_WINDOWS_COL_END_HEIGHT,
+                ],
+                convert_named_params! {(DAO_PROPOSAL_WINDOWS_COL_NOTIFIED, 0)},
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[check_vote_windows] Proposal windows retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let query = format!(
+            "UPDATE {} SET {} = 1 WHERE {} = ?1;",
+            *DAO_PROPOSAL_WINDOWS_TABLE,
+            DAO_PROPOSAL_WINDOWS_COL_NOTIFIED,
+            DAO_PROPOSAL_WINDOWS_COL_PROPOSAL_BULLA,
+        );
+
+        for row in rows {
+            let Value::Blob(ref proposal_bytes) = row[0] else {
+                return Err(Error::ParseFailed("[check_vote_windows] Proposal bulla parsing failed"))
+            };
+            let proposal: DaoProposalBulla = deserialize(proposal_bytes)?;
+
+            let Value::Text(ref dao_name) = row[1] else {
+                return Err(Error::ParseFailed("[check_vote_windows] DAO name parsing failed"))
+            };
+
+            let Value::Integer(end_height) = row[2] else {
+                return Err(Error::ParseFailed("[check_vote_windows] End height parsing failed"))
+            };
+            let Ok(end_height) = u64::try_from(end_height) else {
+                return Err(Error::ParseFailed("[check_vote_windows] End height parsing failed"))
+            };
+
+            if height + VOTE_WINDOW_CLOSING_BLOCKS < end_height {
+                continue
+            }
+
+            if let Err(e) =
+                self.wallet.exec_sql(&query, rusqlite::params![serialize(&proposal)]).await
+            {
+                return Err(Error::RusqliteError(format!(
+                    "[check_vote_windows] Marking proposal window failed: {e:?}"
+                )))
+            }
+
+            // Don't warn about windows that already closed while we
+            // weren't scanning.
+            if height >= end_height {
+                continue
+            }
+
+            self.events
+                .notify(WalletEvent::VoteWindowClosing {
+                    dao_name: dao_name.clone(),
+                    proposal,
+                    end_height,
+                })
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Reset all tracked DAO proposal voting windows in the wallet.
+    pub async fn reset_dao_proposal_windows(&self) -> Result<()> {
+        let query = format!("DELETE FROM {};", *DAO_PROPOSAL_WINDOWS_TABLE);
+        if let Err(e) = self.wallet.exec_sql(&query, &[]).await {
+            return Err(Error::RusqliteError(format!(
+                "[reset_dao_proposal_windows] Resetting proposal windows failed: {e:?}"
+            )))
+        }
+
+        Ok(())
+    }
+}
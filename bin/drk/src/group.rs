@@ -0,0 +1,565 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use rand::rngs::OsRng;
+use rusqlite::types::Value;
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    util::parse::{decode_base10, encode_base10},
+    zk::{halo2::Field, Proof},
+    Error, Result,
+};
+use darkfi_money_contract::{
+    client::{
+        group_spend_v1::{group_custody, group_escrow_keypair, group_lock_coin, group_spend_hook},
+        transfer_v1::{select_coins, TransferCallBuilder, TransferCallInput, TransferCallOutput},
+        OwnCoin,
+    },
+    model::{GroupCustody, MoneyGroupSpendParamsV1, MoneyTransferParamsV1, TokenId},
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{
+        contract_id::MONEY_CONTRACT_ID,
+        frost::{
+            self, DkgRound1Package, DkgRound2Package, DkgSecretPackage, KeyPackage,
+            PublicKeyPackage, SignatureShare, SigningCommitments, SigningNonces, SigningPackage,
+        },
+        note::AeadEncryptedNote,
+        BaseBlind, Blind, FuncId, PublicKey, SecretKey,
+    },
+    dark_tree::DarkTree,
+    pasta::pallas,
+    tx::ContractCall,
+};
+use darkfi_serial::{
+    async_trait, deserialize, serialize, Encodable, SerialDecodable, SerialEncodable,
+};
+
+use crate::{
+    money::{
+        BALANCE_BASE10_DECIMALS, MONEY_GROUPS_COL_GROUP_PUBLIC, MONEY_GROUPS_COL_KEY_PACKAGE,
+        MONEY_GROUPS_COL_PUBLIC_KEY_PACKAGE, MONEY_GROUPS_TABLE,
+    },
+    Drk,
+};
+
+/// Our secret state during a group key generation, along with the
+/// ephemeral key the other members encrypt our shares to.
+#[derive(SerialEncodable, SerialDecodable)]
+pub struct GroupKeygenState {
+    pub secret: DkgSecretPackage,
+    pub encryption_secret: SecretKey,
+}
+
+/// Key generation round one message, broadcast to the other members
+#[derive(SerialEncodable, SerialDecodable)]
+pub struct GroupKeygenCommitment {
+    pub package: DkgRound1Package,
+    pub encryption_public: PublicKey,
+}
+
+/// Key generation round two share, encrypted to its receiver so it can
+/// be broadcast along with the others.
+#[derive(SerialEncodable, SerialDecodable)]
+pub struct GroupKeygenShare {
+    pub receiver: u16,
+    pub note: AeadEncryptedNote,
+}
+
+impl Drk {
+    /// Start a `threshold`-of-`participants` group key generation as the
+    /// member with the given index.
+    pub fn group_keygen(
+        &self,
+        index: u16,
+        threshold: u16,
+        participants: u16,
+    ) -> Result<(GroupKeygenState, GroupKeygenCommitment)> {
+        let (secret, package) = frost::dkg_part1(index, threshold, participants, &mut OsRng)?;
+        let encryption_secret = SecretKey::random(&mut OsRng);
+        let commitment = GroupKeygenCommitment {
+            package,
+            encryption_public: PublicKey::from_secret(encryption_secret),
+        };
+
+        Ok((GroupKeygenState { secret, encryption_secret }, commitment))
+    }
+
+    /// Check the other members' commitments, and deal their shares of our
+    /// polynomial, each encrypted to its receiver.
+    pub fn group_deal(
+        &self,
+        state: &GroupKeygenState,
+        commitments: &[GroupKeygenCommitment],
+    ) -> Result<Vec<GroupKeygenShare>> {
+        let round1: Vec<DkgRound1Package> = commitments.iter().map(|c| c.package.clone()).collect();
+
+        let mut shares = vec![];
+        for package in frost::dkg_part2(&state.secret, &round1)? {
+            let Some(receiver) =
+                commitments.iter().find(|c| c.package.index() == package.receiver())
+            else {
+                return Err(Error::Custom(format!("Missing commitment of {}", package.receiver())))
+            };
+
+            let note =
+                AeadEncryptedNote::encrypt(&package, &receiver.encryption_public, &mut OsRng)?;
+            shares.push(GroupKeygenShare { receiver: package.receiver(), note });
+        }
+
+        Ok(shares)
+    }
+
+    /// Decrypt the shares dealt to us, and combine them into our key share,
+    /// which is stored in the wallet. Returns the custody terms of the group.
+    pub async fn group_finish(
+        &self,
+        state: &GroupKeygenState,
+        commitments: &[GroupKeygenCommitment],
+        shares: &[GroupKeygenShare],
+    ) -> Result<GroupCustody> {
+        let round1: Vec<DkgRound1Package> = commitments.iter().map(|c| c.package.clone()).collect();
+
+        let mut round2 = vec![];
+        for share in shares.iter().filter(|s| s.receiver == state.secret.index()) {
+            let package: DkgRound2Package = share.note.decrypt(&state.encryption_secret)?;
+            round2.push(package);
+        }
+
+        let (key, pubkeys) = frost::dkg_part3(&state.secret, &round1, &round2)?;
+        self.put_group(&key, &pubkeys).await?;
+
+        Ok(group_custody(&pubkeys))
+    }
+
+    /// Store our key share of a group in the wallet
+    pub async fn put_group(&self, key: &KeyPackage, pubkeys: &PublicKeyPackage) -> Result<()> {
+        let query = format!(
+            "INSERT OR REPLACE INTO {} ({}, {}, {}) VALUES (?1, ?2, ?3);",
+            *MONEY_GROUPS_TABLE,
+            MONEY_GROUPS_COL_GROUP_PUBLIC,
+            MONEY_GROUPS_COL_KEY_PACKAGE,
+            MONEY_GROUPS_COL_PUBLIC_KEY_PACKAGE,
+        );
+        if let Err(e) = self
+            .wallet
+            .exec_sql(
+                &query,
+                rusqlite::params![
+                    serialize(&pubkeys.group_public()),
+                    serialize(key),
+                    serialize(pubkeys)
+                ],
+            )
+            .await
+        {
+            return Err(Error::RusqliteError(format!("[put_group] Inserting group failed: {e:?}")))
+        }
+
+        Ok(())
+    }
+
+    /// Fetch our key shares of all the groups we are a member of
+    pub async fn get_groups(&self) -> Result<Vec<(KeyPackage, PublicKeyPackage)>> {
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &MONEY_GROUPS_TABLE,
+                &[MONEY_GROUPS_COL_KEY_PACKAGE, MONEY_GROUPS_COL_PUBLIC_KEY_PACKAGE],
+                &[],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_groups] Groups retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut ret = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Blob(ref key_bytes) = row[0] else {
+                return Err(Error::ParseFailed("[get_groups] Key package bytes parsing failed"))
+            };
+            let key = deserialize(key_bytes)?;
+
+            let Value::Blob(ref pubkeys_bytes) = row[1] else {
+                return Err(Error::ParseFailed(
+                    "[get_groups] Public key package bytes parsing failed",
+                ))
+            };
+            let pubkeys = deserialize(pubkeys_bytes)?;
+
+            ret.push((key, pubkeys));
+        }
+
+        Ok(ret)
+    }
+
+    /// Fetch our key share of the group with the given public key
+    pub async fn get_group(&self, group: &PublicKey) -> Result<(KeyPackage, PublicKeyPackage)> {
+        let Some(ret) =
+            self.get_groups().await?.into_iter().find(|(key, _)| key.group_public() == *group)
+        else {
+            return Err(Error::Custom(format!("Group {group} not found in wallet")))
+        };
+
+        Ok(ret)
+    }
+
+    /// Fetch the escrow secret keys of the coins held by our groups,
+    /// so they are found when scanning.
+    pub async fn get_group_secrets(&self) -> Result<Vec<SecretKey>> {
+        let groups = self.get_groups().await?;
+        Ok(groups
+            .iter()
+            .map(|(_, pubkeys)| group_escrow_keypair(&group_custody(pubkeys)).secret)
+            .collect())
+    }
+
+    /// Fetch the unspent coins held under the given custody terms
+    async fn get_group_coins(&self, custody: &GroupCustody) -> Result<Vec<OwnCoin>> {
+        let spend_hook = group_spend_hook();
+        let user_data = custody.to_bulla();
+        let mut coins: Vec<OwnCoin> =
+            self.get_coins(false).await?.into_iter().map(|(coin, _)| coin).collect();
+        coins.retain(|x| x.note.spend_hook == spend_hook && x.note.user_data == user_data);
+        Ok(coins)
+    }
+
+    /// Create a payment transaction locking `amount` of our coins into a
+    /// coin held by the group with the given custody terms.
+    pub async fn group_lock(
+        &self,
+        custody: &GroupCustody,
+        amount: &str,
+        token_id: TokenId,
+    ) -> Result<Transaction> {
+        self.check_spendable().await?;
+
+        // First get all unspent OwnCoins of the token, not owned by some protocol
+        let owncoins = self.get_account_coins(false).await?;
+        let mut owncoins: Vec<OwnCoin> = owncoins.iter().map(|x| x.0.clone()).collect();
+        owncoins.retain(|x| x.note.token_id == token_id);
+        owncoins.retain(|x| x.note.spend_hook == FuncId::none());
+        if owncoins.is_empty() {
+            return Err(Error::Custom(format!("Did not find any coins with token ID: {token_id}")))
+        }
+
+        let amount = decode_base10(amount, BALANCE_BASE10_DECIMALS, false)?;
+        let (spent_coins, change_value) = select_coins(owncoins, amount)?;
+
+        let mut outputs =
+            vec![group_lock_coin(custody, amount, token_id, Blind::random(&mut OsRng))];
+        if change_value > 0 {
+            outputs.push(TransferCallOutput {
+                public_key: self.default_address().await?,
+                value: change_value,
+                token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            });
+        }
+
+        let (call, proofs, signature_secrets, _) =
+            self.group_transfer_call(spent_coins.clone(), outputs).await?;
+        let mut tx_builder = TransactionBuilder::new(ContractCallLeaf { call, proofs }, vec![])?;
+
+        // We first build the transaction without a fee, to find out how
+        // much gas it uses, and then append the fee call paying for it.
+        let mut tx = tx_builder.build()?;
+        tx.signatures = vec![tx.create_sigs(&signature_secrets)?];
+
+        let (fee_call, fee_proofs, fee_secrets, fee_coin) =
+            self.append_fee_call(&tx, &spent_coins).await?;
+        tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&signature_secrets)?;
+        let fee_sigs = tx.create_sigs(&fee_secrets)?;
+        tx.signatures = vec![sigs, fee_sigs];
+
+        // We need to mark the coins we've spent in our wallet
+        for spent_coin in spent_coins.iter().chain([&fee_coin]) {
+            if let Err(e) = self.mark_spent_coin(&spent_coin.coin).await {
+                return Err(Error::Custom(format!("Mark spent coin {spent_coin:?} failed: {e:?}")))
+            };
+        }
+
+        Ok(tx)
+    }
+
+    /// Build a `Money::Transfer` call spending `coins` into `outputs`.
+    /// Returns the call along with its proofs, signature secrets and the
+    /// user data blinds of its inputs.
+    async fn group_transfer_call(
+        &self,
+        coins: Vec<OwnCoin>,
+        outputs: Vec<TransferCallOutput>,
+    ) -> Result<(ContractCall, Vec<Proof>, Vec<SecretKey>, Vec<BaseBlind>)> {
+        // We'll also need our Merkle tree
+        let tree = self.get_money_tree().await?;
+        let frozen_tokens = self.get_money_frozen_smt().await?;
+
+        let user_data_blinds: Vec<BaseBlind> =
+            coins.iter().map(|_| Blind::random(&mut OsRng)).collect();
+
+        let mut inputs = vec![];
+        for (coin, user_data_blind) in coins.into_iter().zip(&user_data_blinds) {
+            let Some(merkle_path) = tree.witness(coin.leaf_position, 0) else {
+                return Err(Error::Custom(format!(
+                    "Coin {} is not witnessed in the Money Merkle tree",
+                    coin.coin
+                )))
+            };
+            inputs.push(TransferCallInput {
+                merkle_path,
+                user_data_blind: *user_data_blind,
+                frozen_root: frozen_tokens.root(),
+                frozen_path: frozen_tokens.prove_membership(&coin.note.token_id.inner()),
+                coin,
+            });
+        }
+
+        // Now we need to do a lookup for the zkas proof bincodes, and create
+        // the circuit objects and proving keys so we can build the transaction.
+        // We also do this through the RPC.
+        let zkas_bins = self.lookup_zkas(&MONEY_CONTRACT_ID).await?;
+
+        let Some(mint_zkbin) = zkas_bins.iter().find(|x| x.0 == MONEY_CONTRACT_ZKAS_MINT_NS_V1)
+        else {
+            return Err(Error::Custom("Mint circuit not found".to_string()))
+        };
+
+        let Some(burn_zkbin) = zkas_bins.iter().find(|x| x.0 == MONEY_CONTRACT_ZKAS_BURN_NS_V1)
+        else {
+            return Err(Error::Custom("Burn circuit not found".to_string()))
+        };
+
+        eprintln!("Creating Mint and Burn circuit proving keys");
+        let (mint_zkbin, mint_pk) = self.key_cache.proving_key(&mint_zkbin.1)?;
+        let (burn_zkbin, burn_pk) = self.key_cache.proving_key(&burn_zkbin.1)?;
+
+        eprintln!("Building transaction parameters");
+        let builder = TransferCallBuilder {
+            clear_inputs: vec![],
+            inputs,
+            outputs,
+            payment_ids: vec![],
+            viewing_keys: vec![],
+            memos: vec![],
+            mint_zkbin,
+            mint_pk,
+            burn_zkbin,
+            burn_pk,
+        };
+        let (params, secrets) = builder.build()?;
+
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        params.encode(&mut data)?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        Ok((call, secrets.proofs, secrets.signature_secrets, user_data_blinds))
+    }
+
+    /// Create a transaction spending `amount` of the coins held by the
+    /// given group to `recipient`, sending any change back to the group.
+    /// The transfer inputs are signed, while the group signature has to be
+    /// produced by enough members with [`Drk::group_sign`] and put into the
+    /// transaction with [`Drk::group_finalize`].
+    ///
+    /// Since the fee depends on the signed transaction, group spends don't
+    /// pay a fee, like the other multi-party transactions.
+    pub async fn group_spend(
+        &self,
+        group: &PublicKey,
+        amount: &str,
+        token_id: TokenId,
+        recipient: PublicKey,
+    ) -> Result<Transaction> {
+        let (_, pubkeys) = self.get_group(group).await?;
+        let custody = group_custody(&pubkeys);
+
+        let mut coins = self.get_group_coins(&custody).await?;
+        coins.retain(|x| x.note.token_id == token_id);
+        if coins.is_empty() {
+            return Err(Error::Custom(format!(
+                "Did not find any group coins with token ID: {token_id}"
+            )))
+        }
+
+        let amount = decode_base10(amount, BALANCE_BASE10_DECIMALS, false)?;
+        let (spent_coins, change_value) = select_coins(coins, amount)?;
+
+        let mut outputs = vec![TransferCallOutput {
+            public_key: recipient,
+            value: amount,
+            token_id,
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+            blind: Blind::random(&mut OsRng),
+        }];
+        if change_value > 0 {
+            outputs.push(group_lock_coin(
+                &custody,
+                change_value,
+                token_id,
+                Blind::random(&mut OsRng),
+            ));
+        }
+
+        let (xfer_call, xfer_proofs, signature_secrets, user_data_blinds) =
+            self.group_transfer_call(spent_coins, outputs).await?;
+
+        let params = MoneyGroupSpendParamsV1 { custody, user_data_blinds };
+        let mut data = vec![MoneyFunction::GroupSpendV1 as u8];
+        params.encode(&mut data)?;
+        let spend_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        // We need to construct this tree, where spend is the parent:
+        //
+        //   spend ->
+        //       xfer
+        //
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: spend_call, proofs: vec![] },
+            vec![DarkTree::new(
+                ContractCallLeaf { call: xfer_call, proofs: xfer_proofs },
+                vec![],
+                None,
+                None,
+            )],
+        )?;
+
+        let mut tx = tx_builder.build()?;
+        let xfer_sigs = tx.create_sigs(&signature_secrets)?;
+        tx.signatures = vec![xfer_sigs, vec![]];
+
+        Ok(tx)
+    }
+
+    /// Parse the `Money::GroupSpend` call of the given transaction and its
+    /// child transfer, checking it spends coins of the given group.
+    fn group_spend_params(
+        &self,
+        tx: &Transaction,
+        group: &PublicKey,
+    ) -> Result<(MoneyGroupSpendParamsV1, MoneyTransferParamsV1)> {
+        let find_call = |func: MoneyFunction| {
+            tx.calls.iter().find(|c| {
+                c.data.contract_id == *MONEY_CONTRACT_ID &&
+                    c.data.data.first() == Some(&(func as u8))
+            })
+        };
+
+        let (Some(spend_call), Some(xfer_call)) =
+            (find_call(MoneyFunction::GroupSpendV1), find_call(MoneyFunction::TransferV1))
+        else {
+            return Err(Error::Custom("Transaction is not a group spend".to_string()))
+        };
+
+        let params: MoneyGroupSpendParamsV1 = deserialize(&spend_call.data.data[1..])?;
+        if params.custody.group_public != *group {
+            return Err(Error::Custom(format!("Transaction doesn't spend coins of group {group}")))
+        }
+
+        let xfer_params: MoneyTransferParamsV1 = deserialize(&xfer_call.data.data[1..])?;
+
+        Ok((params, xfer_params))
+    }
+
+    /// Signing round one: create fresh nonces for a group signature, and
+    /// the commitments to send to the other signers. The nonces must only
+    /// be used once.
+    pub async fn group_commit(
+        &self,
+        group: &PublicKey,
+    ) -> Result<(SigningNonces, SigningCommitments)> {
+        let (key, _) = self.get_group(group).await?;
+        Ok(frost::commit(&key, &mut OsRng))
+    }
+
+    /// Choose the signers of a group spend transaction, given their round
+    /// one commitments, and create the package they sign in round two.
+    pub fn group_package(
+        &self,
+        group: &PublicKey,
+        tx: &Transaction,
+        commitments: Vec<SigningCommitments>,
+    ) -> Result<SigningPackage> {
+        self.group_spend_params(tx, group)?;
+        Ok(SigningPackage::new(commitments, tx.sighash()?.as_bytes())?)
+    }
+
+    /// Signing round two: sign the given group spend transaction with our
+    /// nonces, checking the signing package is for it.
+    pub async fn group_sign(
+        &self,
+        group: &PublicKey,
+        nonces: SigningNonces,
+        tx: &Transaction,
+        package: &SigningPackage,
+    ) -> Result<SignatureShare> {
+        let (params, xfer_params) = self.group_spend_params(tx, group)?;
+        if package.message() != tx.sighash()?.as_bytes() {
+            return Err(Error::Custom("Signing package is not for this transaction".to_string()))
+        }
+        let (key, _) = self.get_group(group).await?;
+
+        let value: u64 = self
+            .get_group_coins(&params.custody)
+            .await?
+            .iter()
+            .filter(|x| xfer_params.inputs.iter().any(|i| i.nullifier == x.nullifier()))
+            .map(|x| x.note.value)
+            .sum();
+        eprintln!("Signing group spend of {}", encode_base10(value, BALANCE_BASE10_DECIMALS));
+
+        Ok(frost::sign(package, nonces, &key)?)
+    }
+
+    /// Aggregate the signers' shares into the group signature, and put it
+    /// into the group spend transaction.
+    pub async fn group_finalize(
+        &self,
+        group: &PublicKey,
+        tx: &mut Transaction,
+        package: &SigningPackage,
+        shares: &[SignatureShare],
+    ) -> Result<()> {
+        self.group_spend_params(tx, group)?;
+        if package.message() != tx.sighash()?.as_bytes() {
+            return Err(Error::Custom("Signing package is not for this transaction".to_string()))
+        }
+        let (_, pubkeys) = self.get_group(group).await?;
+
+        let signature = frost::aggregate(package, shares, &pubkeys)?;
+
+        // The spend call is the parent of the transfer, so it comes last
+        let Some(sigs) = tx.signatures.last_mut() else {
+            return Err(Error::Custom("Transaction is missing its signatures".to_string()))
+        };
+        *sigs = vec![signature];
+
+        Ok(())
+    }
+}
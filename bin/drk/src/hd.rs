@@ -0,0 +1,214 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::str::FromStr;
+
+use rand::rngs::OsRng;
+use rusqlite::types::Value;
+use zeroize::{Zeroize, Zeroizing};
+
+use darkfi::{Error, Result};
+use darkfi_sdk::crypto::{DerivationPath, ExtendedSecretKey, Mnemonic, SecretKey};
+
+use crate::{convert_named_params, Drk};
+
+// Wallet SQL table constant names. These have to represent the `wallet.sql`
+// SQL schema.
+const WALLET_HD_SEED_TABLE: &str = "hd_seed";
+const WALLET_HD_SEED_COL_SEED: &str = "seed";
+const WALLET_HD_MNEMONIC_TABLE: &str = "hd_mnemonic";
+const WALLET_HD_MNEMONIC_COL_PHRASE: &str = "phrase";
+const WALLET_HD_INDEXES_TABLE: &str = "hd_indexes";
+const WALLET_HD_INDEXES_COL_ACCOUNT: &str = "account";
+const WALLET_HD_INDEXES_COL_PURPOSE: &str = "purpose";
+const WALLET_HD_INDEXES_COL_NEXT_INDEX: &str = "next_index";
+
+/// Purposes of the wallet keys. Keys are derived along `m/account'/purpose'/n'`,
+/// so each account and purpose gets its own branch and they never collide.
+#[derive(Copy, Clone, Debug)]
+pub enum KeyPurpose {
+    /// Money keypairs, `m/account'/0'/n'`
+    Money = 0,
+    /// DAO secret keys, `m/account'/1'/n'`
+    Dao = 1,
+    /// Token mint authorities, `m/account'/2'/n'`
+    TokenMint = 2,
+}
+
+impl Drk {
+    /// Generate a random 24-word mnemonic and create the master seed from
+    /// it, unless the wallet already has one.
+    pub async fn initialize_hd_seed(&self) -> Result<()> {
+        if self.get_hd_seed().await.is_ok() {
+            return Ok(())
+        }
+
+        let mnemonic = match Mnemonic::generate(&mut OsRng, 24) {
+            Ok(m) => m,
+            Err(e) => return Err(Error::Custom(format!("[initialize_hd_seed] {e}"))),
+        };
+        self.import_hd_mnemonic(&mnemonic, "").await
+    }
+
+    /// Fetch the mnemonic phrase the master seed was created from.
+    pub async fn get_hd_mnemonic(&self) -> Result<Mnemonic> {
+        let row = match self
+            .wallet
+            .query_single(WALLET_HD_MNEMONIC_TABLE, &[WALLET_HD_MNEMONIC_COL_PHRASE], &[])
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_hd_mnemonic] Mnemonic retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let Some(Value::Text(phrase)) = row.into_iter().next() else {
+            return Err(Error::ParseFailed("[get_hd_mnemonic] Mnemonic parsing failed"))
+        };
+
+        let phrase = Zeroizing::new(phrase);
+        let Ok(mnemonic) = Mnemonic::from_str(&phrase) else {
+            return Err(Error::ParseFailed("[get_hd_mnemonic] Mnemonic parsing failed"))
+        };
+
+        Ok(mnemonic)
+    }
+
+    /// Replace the master seed of the wallet with the one of the given
+    /// mnemonic and passphrase. The passphrase is not stored.
+    pub async fn import_hd_mnemonic(&self, mnemonic: &Mnemonic, passphrase: &str) -> Result<()> {
+        let seed = Zeroizing::new(mnemonic.to_seed(passphrase));
+        self.import_hd_seed(seed.as_slice()).await?;
+
+        let query = format!(
+            "INSERT INTO {WALLET_HD_MNEMONIC_TABLE} ({WALLET_HD_MNEMONIC_COL_PHRASE}) VALUES (?1);"
+        );
+        let phrase = Zeroizing::new(mnemonic.to_string());
+        if let Err(e) = self.wallet.exec_sql(&query, rusqlite::params![phrase.as_str()]).await {
+            return Err(Error::RusqliteError(format!(
+                "[import_hd_mnemonic] Mnemonic insertion failed: {e:?}"
+            )))
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the master seed from the wallet. The returned buffer is
+    /// wiped when dropped.
+    pub async fn get_hd_seed(&self) -> Result<Zeroizing<Vec<u8>>> {
+        let row = match self
+            .wallet
+            .query_single(WALLET_HD_SEED_TABLE, &[WALLET_HD_SEED_COL_SEED], &[])
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_hd_seed] Seed retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        // Take the blob out of the row instead of cloning it, so no
+        // unwiped copy of the seed is left behind.
+        let Some(Value::Blob(seed)) = row.into_iter().next() else {
+            return Err(Error::ParseFailed("[get_hd_seed] Seed parsing failed"))
+        };
+
+        Ok(Zeroizing::new(seed))
+    }
+
+    /// Replace the master seed of the wallet, restarting the derivation
+    /// of every key purpose from its first index. Any stored mnemonic is
+    /// removed, since it no longer matches the seed.
+    pub async fn import_hd_seed(&self, seed: &[u8]) -> Result<()> {
+        let query = format!(
+            "DELETE FROM {WALLET_HD_SEED_TABLE}; DELETE FROM {WALLET_HD_MNEMONIC_TABLE}; DELETE FROM {WALLET_HD_INDEXES_TABLE};"
+        );
+        if let Err(e) = self.wallet.exec_batch_sql(&query).await {
+            return Err(Error::RusqliteError(format!("[import_hd_seed] Seed reset failed: {e:?}")))
+        }
+
+        let query =
+            format!("INSERT INTO {WALLET_HD_SEED_TABLE} ({WALLET_HD_SEED_COL_SEED}) VALUES (?1);");
+        if let Err(e) = self.wallet.exec_sql(&query, rusqlite::params![seed]).await {
+            return Err(Error::RusqliteError(format!(
+                "[import_hd_seed] Seed insertion failed: {e:?}"
+            )))
+        }
+
+        Ok(())
+    }
+
+    /// Derive the next secret key of the given purpose of the active account
+    /// from the master seed, and advance the purpose index.
+    pub async fn derive_next_secret(&self, purpose: KeyPurpose) -> Result<SecretKey> {
+        let seed = self.get_hd_seed().await?;
+
+        let Ok(account) = u32::try_from(self.active_account().await?) else {
+            return Err(Error::Custom("[derive_next_secret] Account ID out of range".to_string()))
+        };
+        let purpose = purpose as u32;
+        let index = match self
+            .wallet
+            .query_single(
+                WALLET_HD_INDEXES_TABLE,
+                &[WALLET_HD_INDEXES_COL_NEXT_INDEX],
+                convert_named_params! {
+                    (WALLET_HD_INDEXES_COL_ACCOUNT, account),
+                    (WALLET_HD_INDEXES_COL_PURPOSE, purpose)
+                },
+            )
+            .await
+        {
+            Ok(row) => {
+                let Value::Integer(index) = row[0] else {
+                    return Err(Error::ParseFailed("[derive_next_secret] Index parsing failed"))
+                };
+                let Ok(index) = u32::try_from(index) else {
+                    return Err(Error::ParseFailed("[derive_next_secret] Index parsing failed"))
+                };
+                index
+            }
+            Err(_) => 0,
+        };
+
+        let path = DerivationPath::new(&[account, purpose, index]);
+        let mut master = ExtendedSecretKey::from_seed(&seed);
+        let mut child = master.derive_path(&path);
+        let secret = child.secret;
+        master.zeroize();
+        child.zeroize();
+
+        let query = format!(
+            "INSERT OR REPLACE INTO {WALLET_HD_INDEXES_TABLE} ({WALLET_HD_INDEXES_COL_ACCOUNT}, {WALLET_HD_INDEXES_COL_PURPOSE}, {WALLET_HD_INDEXES_COL_NEXT_INDEX}) VALUES (?1, ?2, ?3);"
+        );
+        if let Err(e) =
+            self.wallet.exec_sql(&query, rusqlite::params![account, purpose, index + 1]).await
+        {
+            return Err(Error::RusqliteError(format!(
+                "[derive_next_secret] Index update failed: {e:?}"
+            )))
+        }
+
+        Ok(secret)
+    }
+}
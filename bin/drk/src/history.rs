@@ -0,0 +1,454 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{collections::BTreeMap, fmt};
+
+use lazy_static::lazy_static;
+use rusqlite::types::Value;
+
+use darkfi::{
+    rpc::util::JsonValue,
+    tx::Transaction,
+    util::{parse::encode_base10, time::Timestamp},
+    Error, Result,
+};
+use darkfi_money_contract::{
+    model::{Coin, MoneyFeeParamsV1, Nullifier, TokenId},
+    MoneyFunction,
+};
+use darkfi_sdk::crypto::{PublicKey, SecretKey, MONEY_CONTRACT_ID};
+use darkfi_serial::{deserialize, serialize};
+
+use crate::{
+    convert_named_params,
+    money::{
+        BALANCE_BASE10_DECIMALS, MONEY_KEYS_COL_ACCOUNT, MONEY_KEYS_COL_SECRET, MONEY_KEYS_TABLE,
+    },
+    Drk,
+};
+
+// Wallet SQL table constant names. These have to represent the `money.sql`
+// SQL schema. Table names are prefixed with the contract ID to avoid collisions.
+lazy_static! {
+    pub static ref MONEY_HISTORY_TABLE: String =
+        format!("{}_money_history", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_COUNTERPARTIES_TABLE: String =
+        format!("{}_money_counterparties", MONEY_CONTRACT_ID.to_string());
+}
+
+// MONEY_HISTORY_TABLE
+const MONEY_HISTORY_COL_TX_HASH: &str = "transaction_hash";
+const MONEY_HISTORY_COL_TOKEN_ID: &str = "token_id";
+const MONEY_HISTORY_COL_ACCOUNT: &str = "account";
+const MONEY_HISTORY_COL_BLOCK_HEIGHT: &str = "block_height";
+const MONEY_HISTORY_COL_TIMESTAMP: &str = "timestamp";
+const MONEY_HISTORY_COL_RECEIVED: &str = "received";
+const MONEY_HISTORY_COL_SENT: &str = "sent";
+const MONEY_HISTORY_COL_FEE: &str = "fee";
+
+// MONEY_COUNTERPARTIES_TABLE
+const MONEY_COUNTERPARTIES_COL_TX_HASH: &str = "transaction_hash";
+const MONEY_COUNTERPARTIES_COL_ADDRESS: &str = "address";
+
+/// Direction of the funds moved by a transaction, from the wallet's
+/// point of view.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// Funds received from someone else
+    Incoming,
+    /// Funds sent to someone else
+    Outgoing,
+    /// Funds moved between our own keys
+    Internal,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Incoming => write!(f, "incoming"),
+            Self::Outgoing => write!(f, "outgoing"),
+            Self::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+/// A decrypted movement of the wallet funds of a single token, made by
+/// a confirmed transaction.
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    /// Hash of the transaction
+    pub tx_hash: String,
+    /// Direction of the funds
+    pub direction: Direction,
+    /// Recipient address of outgoing payments made by this wallet
+    pub counterparty: Option<PublicKey>,
+    /// Token moved
+    pub token_id: TokenId,
+    /// Net amount that entered or left the wallet, excluding the fee
+    pub amount: u64,
+    /// Fee paid by the wallet in this token
+    pub fee: u64,
+    /// Height of the block containing the transaction
+    pub block_height: u64,
+    /// Timestamp of the block containing the transaction
+    pub timestamp: Timestamp,
+}
+
+impl HistoryEntry {
+    /// Column names of the CSV export, matching [`HistoryEntry::to_csv`].
+    pub const CSV_HEADER: &'static str =
+        "transaction_hash,direction,counterparty,token_id,amount,fee,block_height,timestamp";
+
+    /// Encode the entry as a CSV record. Amounts are in decimal notation.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.tx_hash,
+            self.direction,
+            self.counterparty.map_or(String::new(), |c| c.to_string()),
+            self.token_id,
+            encode_base10(self.amount, BALANCE_BASE10_DECIMALS),
+            encode_base10(self.fee, BALANCE_BASE10_DECIMALS),
+            self.block_height,
+            self.timestamp.inner(),
+        )
+    }
+
+    /// Encode the entry as a JSON object. Amounts are in decimal notation,
+    /// and an unknown counterparty is `null`.
+    pub fn to_json(&self) -> JsonValue {
+        let counterparty = match self.counterparty {
+            Some(c) => JsonValue::String(c.to_string()),
+            None => JsonValue::Null,
+        };
+
+        JsonValue::Object(
+            [
+                ("transaction_hash".to_string(), JsonValue::String(self.tx_hash.clone())),
+                ("direction".to_string(), JsonValue::String(self.direction.to_string())),
+                ("counterparty".to_string(), counterparty),
+                ("token_id".to_string(), JsonValue::String(self.token_id.to_string())),
+                (
+                    "amount".to_string(),
+                    JsonValue::String(encode_base10(self.amount, BALANCE_BASE10_DECIMALS)),
+                ),
+                (
+                    "fee".to_string(),
+                    JsonValue::String(encode_base10(self.fee, BALANCE_BASE10_DECIMALS)),
+                ),
+                ("block_height".to_string(), JsonValue::Number(self.block_height as f64)),
+                ("timestamp".to_string(), JsonValue::Number(self.timestamp.inner() as f64)),
+            ]
+            .into(),
+        )
+    }
+}
+
+/// Funds of a single token moved by a transaction for a single account
+#[derive(Default)]
+struct Movement {
+    received: u64,
+    sent: u64,
+    fee: u64,
+}
+
+impl Drk {
+    /// Record the movements of the wallet funds made by the given confirmed
+    /// transaction, using the coins and nullifiers returned when applying its
+    /// Money data. Recording the same transaction again overwrites its
+    /// previous records.
+    pub async fn record_money_history(
+        &self,
+        tx: &Transaction,
+        coins: &[Coin],
+        nullifiers: &[Nullifier],
+        block_height: u64,
+        timestamp: Timestamp,
+    ) -> Result<()> {
+        // Fees are paid in their own call, publicly revealing their amount
+        let mut fees = vec![];
+        for call in tx.calls.iter() {
+            if call.data.contract_id != *MONEY_CONTRACT_ID ||
+                call.data.data[0] != MoneyFunction::FeeV1 as u8
+            {
+                continue
+            }
+            let fee: u64 = deserialize(&call.data.data[1..9])?;
+            let params: MoneyFeeParamsV1 = deserialize(&call.data.data[9..])?;
+            fees.push((params.input.nullifier, params.token_id, fee));
+        }
+
+        let key_accounts = self.get_money_key_accounts().await?;
+        let account_of = |secret: &SecretKey| {
+            key_accounts.iter().find(|(s, _)| s == secret).map(|(_, account)| *account)
+        };
+
+        let mut movements: BTreeMap<(u64, String), (TokenId, Movement)> = BTreeMap::new();
+        for (coin, _) in self.get_coins(true).await? {
+            // Coins owned by DAOs are not part of any account
+            let Some(account) = account_of(&coin.secret) else { continue };
+            let token_id = coin.note.token_id;
+            let entry = movements
+                .entry((account, token_id.to_string()))
+                .or_insert_with(|| (token_id, Movement::default()));
+
+            if coins.contains(&coin.coin) {
+                entry.1.received += coin.note.value;
+            }
+
+            let nullifier = coin.nullifier();
+            if nullifiers.contains(&nullifier) {
+                entry.1.sent += coin.note.value;
+            }
+            for (fee_nullifier, fee_token_id, fee) in &fees {
+                if *fee_nullifier == nullifier && *fee_token_id == token_id {
+                    entry.1.fee += fee;
+                }
+            }
+        }
+
+        let tx_hash = tx.hash().to_string();
+        let query = format!(
+            "INSERT OR REPLACE INTO {} ({}, {}, {}, {}, {}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);",
+            *MONEY_HISTORY_TABLE,
+            MONEY_HISTORY_COL_TX_HASH,
+            MONEY_HISTORY_COL_TOKEN_ID,
+            MONEY_HISTORY_COL_ACCOUNT,
+            MONEY_HISTORY_COL_BLOCK_HEIGHT,
+            MONEY_HISTORY_COL_TIMESTAMP,
+            MONEY_HISTORY_COL_RECEIVED,
+            MONEY_HISTORY_COL_SENT,
+            MONEY_HISTORY_COL_FEE,
+        );
+        for ((account, _), (token_id, movement)) in movements {
+            if movement.received == 0 && movement.sent == 0 && movement.fee == 0 {
+                continue
+            }
+
+            let params = rusqlite::params![
+                tx_hash,
+                serialize(&token_id),
+                account,
+                block_height,
+                timestamp.inner(),
+                serialize(&movement.received),
+                serialize(&movement.sent),
+                serialize(&movement.fee),
+            ];
+            if let Err(e) = self.wallet.exec_sql(&query, params).await {
+                return Err(Error::RusqliteError(format!(
+                    "[record_money_history] Inserting history record failed: {e:?}"
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remember the recipient of a payment made by the given transaction,
+    /// so it can be shown in the history once it gets confirmed.
+    pub async fn put_money_counterparty(&self, tx_hash: &str, address: &PublicKey) -> Result<()> {
+        let query = format!(
+            "INSERT OR REPLACE INTO {} ({}, {}) VALUES (?1, ?2);",
+            *MONEY_COUNTERPARTIES_TABLE,
+            MONEY_COUNTERPARTIES_COL_TX_HASH,
+            MONEY_COUNTERPARTIES_COL_ADDRESS,
+        );
+        if let Err(e) =
+            self.wallet.exec_sql(&query, rusqlite::params![tx_hash, serialize(address)]).await
+        {
+            return Err(Error::RusqliteError(format!(
+                "[put_money_counterparty] Inserting counterparty failed: {e:?}"
+            )))
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the recipient of a payment made by the given transaction, if known.
+    async fn get_money_counterparty(&self, tx_hash: &str) -> Result<Option<PublicKey>> {
+        let Ok(row) = self
+            .wallet
+            .query_single(
+                &MONEY_COUNTERPARTIES_TABLE,
+                &[MONEY_COUNTERPARTIES_COL_ADDRESS],
+                convert_named_params! {(MONEY_COUNTERPARTIES_COL_TX_HASH, tx_hash)},
+            )
+            .await
+        else {
+            return Ok(None)
+        };
+
+        let Value::Blob(ref address_bytes) = row[0] else {
+            return Err(Error::ParseFailed("[get_money_counterparty] Address parsing failed"))
+        };
+
+        Ok(Some(deserialize(address_bytes)?))
+    }
+
+    /// Fetch the complete, decrypted funds history of the active account,
+    /// ordered by block height.
+    pub async fn get_money_history(&self) -> Result<Vec<HistoryEntry>> {
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &MONEY_HISTORY_TABLE,
+                &[
+                    MONEY_HISTORY_COL_TX_HASH,
+                    MONEY_HISTORY_COL_TOKEN_ID,
+                    MONEY_HISTORY_COL_BLOCK_HEIGHT,
+                    MONEY_HISTORY_COL_TIMESTAMP,
+                    MONEY_HISTORY_COL_RECEIVED,
+                    MONEY_HISTORY_COL_SENT,
+                    MONEY_HISTORY_COL_FEE,
+                ],
+                convert_named_params! {(MONEY_HISTORY_COL_ACCOUNT, self.active_account().await?)},
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_history] History records retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Text(ref tx_hash) = row[0] else {
+                return Err(Error::ParseFailed(
+                    "[get_money_history] Transaction hash parsing failed",
+                ))
+            };
+
+            let Value::Blob(ref token_id_bytes) = row[1] else {
+                return Err(Error::ParseFailed("[get_money_history] Token ID parsing failed"))
+            };
+            let token_id: TokenId = deserialize(token_id_bytes)?;
+
+            let Value::Integer(block_height) = row[2] else {
+                return Err(Error::ParseFailed("[get_money_history] Block height parsing failed"))
+            };
+            let Ok(block_height) = u64::try_from(block_height) else {
+                return Err(Error::ParseFailed("[get_money_history] Block height parsing failed"))
+            };
+
+            let Value::Integer(timestamp) = row[3] else {
+                return Err(Error::ParseFailed("[get_money_history] Timestamp parsing failed"))
+            };
+            let Ok(timestamp) = u64::try_from(timestamp) else {
+                return Err(Error::ParseFailed("[get_money_history] Timestamp parsing failed"))
+            };
+
+            let Value::Blob(ref received_bytes) = row[4] else {
+                return Err(Error::ParseFailed("[get_money_history] Received parsing failed"))
+            };
+            let received: u64 = deserialize(received_bytes)?;
+
+            let Value::Blob(ref sent_bytes) = row[5] else {
+                return Err(Error::ParseFailed("[get_money_history] Sent parsing failed"))
+            };
+            let sent: u64 = deserialize(sent_bytes)?;
+
+            let Value::Blob(ref fee_bytes) = row[6] else {
+                return Err(Error::ParseFailed("[get_money_history] Fee parsing failed"))
+            };
+            let fee: u64 = deserialize(fee_bytes)?;
+
+            // Change returning to us, fee change included, is not part of
+            // the moved amount, and the fee is reported on its own. Moving
+            // funds between our own keys leaves nothing but the fee.
+            let (direction, amount, counterparty) = if sent == 0 {
+                (Direction::Incoming, received, None)
+            } else if received + fee < sent {
+                let counterparty = self.get_money_counterparty(tx_hash).await?;
+                (Direction::Outgoing, sent - received - fee, counterparty)
+            } else {
+                (Direction::Internal, 0, None)
+            };
+
+            entries.push(HistoryEntry {
+                tx_hash: tx_hash.clone(),
+                direction,
+                counterparty,
+                token_id,
+                amount,
+                fee,
+                block_height,
+                timestamp: Timestamp::from_u64(timestamp),
+            });
+        }
+        entries.sort_by_key(|e| e.block_height);
+
+        Ok(entries)
+    }
+
+    /// Fetch the secret keys of all accounts along with their account IDs.
+    async fn get_money_key_accounts(&self) -> Result<Vec<(SecretKey, u64)>> {
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &MONEY_KEYS_TABLE,
+                &[MONEY_KEYS_COL_SECRET, MONEY_KEYS_COL_ACCOUNT],
+                &[],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_key_accounts] Keys retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut keys = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Blob(ref secret_bytes) = row[0] else {
+                return Err(Error::ParseFailed("[get_money_key_accounts] Secret key parsing failed"))
+            };
+            let secret: SecretKey = deserialize(secret_bytes)?;
+
+            let Value::Integer(account) = row[1] else {
+                return Err(Error::ParseFailed("[get_money_key_accounts] Account parsing failed"))
+            };
+            let Ok(account) = u64::try_from(account) else {
+                return Err(Error::ParseFailed("[get_money_key_accounts] Account parsing failed"))
+            };
+
+            keys.push((secret, account));
+        }
+
+        Ok(keys)
+    }
+
+    /// Reset the funds history in the wallet.
+    pub async fn reset_money_history(&self) -> Result<()> {
+        println!("Resetting funds history");
+        let query = format!("DELETE FROM {};", *MONEY_HISTORY_TABLE);
+        if let Err(e) = self.wallet.exec_sql(&query, &[]).await {
+            return Err(Error::RusqliteError(format!(
+                "[reset_money_history] Resetting history failed: {e:?}"
+            )))
+        }
+        println!("Successfully reset funds history");
+
+        Ok(())
+    }
+}
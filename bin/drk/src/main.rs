@@ -18,7 +18,8 @@
 
 use std::{
     fs,
-    io::{stdin, Read},
+    io::{stdin, Read, Write},
+    path::Path,
     process::exit,
     str::FromStr,
     sync::Arc,
@@ -30,22 +31,38 @@ use rand::rngs::OsRng;
 use smol::stream::StreamExt;
 use structopt_toml::{serde::Deserialize, structopt::StructOpt, StructOptToml};
 use url::Url;
+use zeroize::Zeroizing;
 
 use darkfi::{
     async_daemonize, cli_desc,
     rpc::{client::RpcClient, jsonrpc::JsonRequest, util::JsonValue},
-    tx::Transaction,
+    system::{Subscriber, SubscriberPtr},
+    tx::{bundle::TransactionBundle, Transaction},
     util::{
         encoding::base64,
         parse::{decode_base10, encode_base10},
         path::expand_path,
     },
-    zk::halo2::Field,
+    zk::{halo2::Field, KeyCache},
     Result,
 };
-use darkfi_money_contract::model::{Coin, TokenId};
+use darkfi_dao_contract::{client as dao_client, model::DaoVoteWeight};
+use darkfi_money_contract::{
+    client::{
+        self as money_client,
+        coin_selection::{BranchAndBound, CoinSelection, LargestFirst, RandomSelection},
+        coinjoin::{CoinJoinContribution, CoinJoinSession},
+        swap_v1::PartialSwapData,
+        CoinSpendHook, PaymentId,
+    },
+    model::{Coin, TokenId},
+    MONEY_NOTE_MEMO_MAX_LEN,
+};
 use darkfi_sdk::{
-    crypto::{FuncId, PublicKey, SecretKey},
+    crypto::{
+        Addressable, DiversifiedAddress, Mnemonic, Network, PublicKey, SecretKey,
+        SpendHookRegistry, ViewingKey,
+    },
     pasta::{group::ff::PrimeField, pallas},
     tx::TransactionHash,
 };
@@ -62,14 +79,26 @@ mod transfer;
 
 /// Swap methods
 mod swap;
-use swap::PartialSwapData;
+
+/// CoinJoin methods
+mod coinjoin;
+
+/// FROST threshold group methods
+mod group;
+use group::{GroupKeygenCommitment, GroupKeygenShare, GroupKeygenState};
+
+/// External transaction signers
+mod signer;
 
 /// Token methods
 mod token;
 
 /// CLI utility functions
 mod cli_util;
-use cli_util::{generate_completions, kaching, parse_token_pair, parse_value_pair};
+use cli_util::{
+    generate_completions, kaching, parse_public_key, parse_recipient, parse_token_pair,
+    parse_value_pair,
+};
 
 /// Wallet functionality related to Money
 mod money;
@@ -82,6 +111,24 @@ use dao::DaoParams;
 /// Wallet functionality related to transactions history
 mod txs_history;
 
+/// Decrypted funds history of the wallet
+mod history;
+use history::HistoryEntry;
+
+/// Hierarchical deterministic derivation of the wallet keys
+mod hd;
+use hd::KeyPurpose;
+
+/// Wallet accounts
+mod account;
+
+/// Wallet events emitted while scanning blocks
+mod events;
+use events::WalletEvent;
+
+/// Wallet state audit against the blockchain
+mod audit;
+
 /// Wallet database operations handler
 mod walletdb;
 use walletdb::{WalletDb, WalletPtr};
@@ -103,14 +150,22 @@ struct Args {
     /// Path to wallet database
     wallet_path: String,
 
-    #[structopt(long, default_value = "changeme")]
-    /// Password for the wallet database
-    wallet_pass: String,
+    #[structopt(long)]
+    /// Password for the wallet database, prompted for if not set
+    wallet_pass: Option<String>,
 
     #[structopt(short, long, default_value = "tcp://127.0.0.1:8340")]
     /// darkfid JSON-RPC endpoint
     endpoint: Url,
 
+    #[structopt(long)]
+    /// Pinned base64 TLS public key of a `tcp+tls://` darkfid endpoint
+    endpoint_key: Option<String>,
+
+    #[structopt(long, default_value = "testnet")]
+    /// Network addresses are encoded for (mainnet, testnet, localnet)
+    network: String,
+
     #[structopt(subcommand)]
     /// Sub command to execute
     command: Subcmd,
@@ -150,6 +205,10 @@ enum Subcmd {
         /// Generate a new keypair in the wallet
         keygen: bool,
 
+        #[structopt(long)]
+        /// Derive a new diversified address of the default keypair
+        diversify: bool,
+
         #[structopt(long)]
         /// Query the wallet for known balances
         balance: bool,
@@ -174,6 +233,31 @@ enum Subcmd {
         /// Import secret keys from stdin into the wallet, separated by newlines
         import_secrets: bool,
 
+        #[structopt(long)]
+        /// Import viewing keys from stdin as watch-only accounts, separated by newlines
+        import_viewing_keys: bool,
+
+        #[structopt(long)]
+        /// Print the base58-encoded seed all the wallet keys derive from
+        seed: bool,
+
+        #[structopt(long)]
+        /// Import a base58-encoded seed from stdin, replacing the wallet one
+        import_seed: bool,
+
+        #[structopt(long)]
+        /// Print the mnemonic phrase the wallet seed was created from
+        mnemonic: bool,
+
+        #[structopt(long)]
+        /// Import a mnemonic phrase from stdin, optionally followed by a
+        /// passphrase line, replacing the wallet seed
+        import_mnemonic: bool,
+
+        #[structopt(long)]
+        /// Change the password the wallet database is encrypted with
+        change_password: bool,
+
         #[structopt(long)]
         /// Print the Merkle tree in the wallet
         tree: bool,
@@ -181,6 +265,22 @@ enum Subcmd {
         #[structopt(long)]
         /// Print all the coins in the wallet
         coins: bool,
+
+        #[structopt(long)]
+        /// Print all the coins received by watch-only accounts
+        watch_coins: bool,
+
+        #[structopt(long)]
+        /// Print the totals received by watch-only accounts (spends aren't visible)
+        watch_received: bool,
+
+        #[structopt(long)]
+        /// Generate a new random payment ID to hand out along with an invoice
+        payment_id: bool,
+
+        #[structopt(long)]
+        /// Print the viewing key of the default address and its public key
+        viewing_key: bool,
     },
 
     /// Unspend a coin
@@ -197,8 +297,24 @@ enum Subcmd {
         /// Token ID to send
         token: String,
 
-        /// Recipient address
+        /// Recipient address, or a bare public key
         recipient: String,
+
+        #[structopt(long)]
+        /// Optional recipient-supplied payment ID to bind into the payment
+        payment_id: Option<String>,
+
+        #[structopt(long)]
+        /// Optional recipient viewing key to encrypt the payment note to
+        viewing_key: Option<String>,
+
+        #[structopt(long)]
+        /// Optional memo visible only to the recipient, e.g. an invoice reference
+        memo: Option<String>,
+
+        #[structopt(long, default_value = "exact")]
+        /// Coin selection strategy: exact, largest or random
+        selection: String,
     },
 
     /// OTC atomic swap
@@ -208,6 +324,27 @@ enum Subcmd {
         command: OtcSubcmd,
     },
 
+    /// Collaborative CoinJoin transfer
+    Coinjoin {
+        #[structopt(subcommand)]
+        /// Sub command to execute
+        command: CoinjoinSubcmd,
+    },
+
+    /// Funds held by a FROST threshold group
+    Group {
+        #[structopt(subcommand)]
+        /// Sub command to execute
+        command: GroupSubcmd,
+    },
+
+    /// Sign transaction bundles through an external signer
+    Signer {
+        #[structopt(subcommand)]
+        /// Sub command to execute
+        command: SignerSubcmd,
+    },
+
     /// Inspect a transaction from stdin
     Inspect,
 
@@ -218,7 +355,11 @@ enum Subcmd {
     /// through their transactions to see if there's any that interest us.
     /// With `drk` we look at transactions calling the money contract so we can
     /// find coins sent to us and fill our wallet with the necessary metadata.
-    Subscribe,
+    Subscribe {
+        #[structopt(long)]
+        /// Append wallet events to given file, as JSON lines
+        events: Option<String>,
+    },
 
     /// DAO functionalities
     Dao {
@@ -240,6 +381,19 @@ enum Subcmd {
         #[structopt(long)]
         /// Reset Merkle tree to checkpoint index and start scanning
         checkpoint: Option<u64>,
+
+        #[structopt(long)]
+        /// Rescan the Money state starting from given block height
+        rescan: Option<u64>,
+    },
+
+    /// Cross-check the wallet state against the blockchain and report discrepancies
+    Audit,
+
+    /// Scan the blockchain for payments received under a viewing key
+    View {
+        /// base58-encoded viewing key
+        viewing_key: String,
     },
 
     /// Explorer related subcommands
@@ -256,6 +410,13 @@ enum Subcmd {
         command: AliasSubcmd,
     },
 
+    /// Manage wallet accounts
+    Account {
+        #[structopt(subcommand)]
+        /// Sub command to execute
+        command: AccountSubcmd,
+    },
+
     /// Token functionalities
     Token {
         #[structopt(subcommand)]
@@ -287,6 +448,126 @@ enum OtcSubcmd {
     Sign,
 }
 
+#[derive(Clone, Debug, Deserialize, StructOpt)]
+enum CoinjoinSubcmd {
+    /// Open a CoinJoin session for a token, to be shared with participants
+    Init {
+        /// Token ID to transfer
+        token: String,
+    },
+
+    /// Create our contribution given the session from stdin
+    Contribute {
+        /// Amount to pay
+        amount: String,
+
+        /// Recipient address
+        recipient: String,
+    },
+
+    /// Build the unsigned tx given the session and contributions from stdin, one per line
+    Join,
+
+    /// Sign our inputs given our contribution and the unsigned tx from stdin, one per line
+    Sign,
+
+    /// Assemble the signed tx given the unsigned tx and signatures from stdin, one per line
+    Finalize,
+}
+
+#[derive(Clone, Debug, Deserialize, StructOpt)]
+enum GroupSubcmd {
+    /// Start a group key generation, printing our secret state, then our commitment to broadcast
+    Keygen {
+        /// Our member index, from 1 to the number of participants
+        index: u16,
+
+        /// Number of members needed to spend the group funds
+        threshold: u16,
+
+        /// Number of members in the group
+        participants: u16,
+    },
+
+    /// Deal the others' shares given our state and all commitments from stdin, one per line
+    Deal,
+
+    /// Store our key share given our state, all commitments and shares from stdin, one per line
+    Finish,
+
+    /// List the groups we are a member of, along with their custody terms
+    List,
+
+    /// Lock funds into a coin held by a group, given its custody terms
+    Lock {
+        /// Amount to lock
+        amount: String,
+
+        /// Token ID to lock
+        token: String,
+
+        /// Custody terms of the group
+        custody: String,
+    },
+
+    /// Build the unsigned tx spending group funds, to be signed by enough members
+    Spend {
+        /// Group public key
+        group: String,
+
+        /// Amount to send
+        amount: String,
+
+        /// Token ID to send
+        token: String,
+
+        /// Recipient address
+        recipient: String,
+    },
+
+    /// Print our signing nonces to keep, then our commitments to send to the coordinator
+    Commit {
+        /// Group public key
+        group: String,
+    },
+
+    /// Create the signing package given the unsigned tx and commitments from stdin, one per line
+    Package {
+        /// Group public key
+        group: String,
+    },
+
+    /// Sign given our nonces, the unsigned tx and signing package from stdin, one per line
+    Sign {
+        /// Group public key
+        group: String,
+    },
+
+    /// Assemble the signed tx given the unsigned tx, signing package and shares from stdin
+    Finalize {
+        /// Group public key
+        group: String,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, StructOpt)]
+enum SignerSubcmd {
+    /// Serve signing requests for the wallet keys on a local socket
+    Serve {
+        /// Path of the socket to listen on
+        socket: String,
+    },
+
+    /// Add the signatures a socket signer holds the keys of to a transaction bundle from stdin
+    Sign {
+        /// Path of the signer socket
+        socket: String,
+    },
+
+    /// Assemble the signed transaction from a complete transaction bundle from stdin
+    Finalize,
+}
+
 #[derive(Clone, Debug, Deserialize, StructOpt)]
 enum DaoSubcmd {
     /// Create DAO parameters
@@ -299,6 +580,19 @@ enum DaoSubcmd {
         approval_ratio: f64,
         /// DAO's governance token ID
         gov_token_id: String,
+        #[structopt(long)]
+        /// Optional guardian public key, who can veto proposals before they're executed
+        guardian: Option<String>,
+        #[structopt(long)]
+        /// Governance tokens bonded when proposing, slashed if overwhelmingly rejected
+        proposal_deposit: Option<String>,
+        #[structopt(long)]
+        /// Yes votes needed to execute a proposal before its voting period ends,
+        /// at least the quorum (early execution is disabled if omitted)
+        early_exec_quorum: Option<String>,
+        #[structopt(long)]
+        /// Vote weight applied to voters' balances: linear (default), sqrt, or capped:<amount>
+        vote_weight: Option<String>,
     },
 
     /// View DAO data from stdin
@@ -411,6 +705,13 @@ enum ExplorerSubcmd {
         /// Encode specific history record transaction to base58
         encode: bool,
     },
+
+    /// Export the decrypted funds history of the active account
+    ExportHistory {
+        #[structopt(long, default_value = "csv")]
+        /// Output format, either csv or json
+        format: String,
+    },
 }
 
 #[derive(Clone, Debug, Deserialize, StructOpt)]
@@ -443,6 +744,24 @@ enum AliasSubcmd {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, StructOpt)]
+enum AccountSubcmd {
+    /// Create a new account
+    Create {
+        /// Account name
+        name: String,
+    },
+
+    /// List all the accounts in the wallet
+    List,
+
+    /// Switch the active account
+    Switch {
+        /// Account name to switch to
+        name: String,
+    },
+}
+
 #[derive(Clone, Debug, Deserialize, StructOpt)]
 enum TokenSubcmd {
     /// Import a mint authority secret from stdin
@@ -454,6 +773,21 @@ enum TokenSubcmd {
     /// List token IDs with available mint authorities
     List,
 
+    /// List minted non-fungible tokens
+    Nfts,
+
+    /// Display the supply cap of a token and whether its mint authority is revoked
+    Supply {
+        /// Token ID or alias to look up
+        token: String,
+    },
+
+    /// Display the registered metadata of a token
+    Info {
+        /// Token ID or alias to look up
+        token: String,
+    },
+
     /// Mint tokens
     Mint {
         /// Token ID to mint
@@ -479,15 +813,27 @@ pub struct Drk {
     pub wallet: WalletPtr,
     /// JSON-RPC client to execute requests to darkfid daemon
     pub rpc_client: RpcClient,
+    /// Pinned TLS public key of the darkfid endpoint, if any
+    pub endpoint_key: Option<String>,
+    /// On-disk cache of zkas circuit keys, stored next to the wallet
+    pub key_cache: KeyCache,
+    /// Publisher of wallet events found while scanning blocks
+    pub events: SubscriberPtr<WalletEvent>,
 }
 
 impl Drk {
     async fn new(
         wallet_path: String,
-        wallet_pass: String,
+        wallet_pass: Option<String>,
         endpoint: Url,
+        endpoint_key: Option<String>,
         ex: Arc<smol::Executor<'static>>,
     ) -> Result<Self> {
+        let wallet_pass = match wallet_pass {
+            Some(p) => p,
+            None => rpassword::prompt_password("Wallet password: ")?,
+        };
+
         // Script kiddies protection
         if wallet_pass == "changeme" {
             eprintln!("Please don't use default wallet password...");
@@ -501,6 +847,10 @@ impl Drk {
                 fs::create_dir_all(parent)?;
             }
         }
+        let key_cache = match wallet_path.parent() {
+            Some(parent) => KeyCache::new(&parent.join("zkcache"))?,
+            None => KeyCache::new(Path::new("zkcache"))?,
+        };
         let wallet = match WalletDb::new(Some(wallet_path), Some(&wallet_pass)) {
             Ok(w) => w,
             Err(e) => {
@@ -510,9 +860,12 @@ impl Drk {
         };
 
         // Initialize rpc client
-        let rpc_client = RpcClient::new(endpoint, ex).await?;
+        let rpc_client = match endpoint_key {
+            Some(ref key) => RpcClient::new_pinned(endpoint, key, ex).await?,
+            None => RpcClient::new(endpoint, ex).await?,
+        };
 
-        Ok(Self { wallet, rpc_client })
+        Ok(Self { wallet, rpc_client, endpoint_key, key_cache, events: Subscriber::new() })
     }
 
     /// Initialize wallet with tables for drk
@@ -541,6 +894,14 @@ impl Drk {
 
 async_daemonize!(realmain);
 async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
+    let network = match Network::from_str(&args.network) {
+        Ok(n) => n,
+        Err(e) => {
+            eprintln!("Invalid network: {e}");
+            exit(2);
+        }
+    };
+
     match args.command {
         Subcmd::Kaching => {
             kaching().await;
@@ -548,7 +909,9 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
         }
 
         Subcmd::Ping => {
-            let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
             drk.ping().await
         }
 
@@ -557,17 +920,29 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
         Subcmd::Wallet {
             initialize,
             keygen,
+            diversify,
             balance,
             address,
             addresses,
             default_address,
             secrets,
             import_secrets,
+            import_viewing_keys,
+            seed,
+            import_seed,
+            mnemonic,
+            import_mnemonic,
+            change_password,
             tree,
             coins,
+            watch_coins,
+            watch_received,
+            payment_id,
+            viewing_key,
         } => {
             if !initialize &&
                 !keygen &&
+                !diversify &&
                 !balance &&
                 !address &&
                 !addresses &&
@@ -575,17 +950,39 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 !secrets &&
                 !tree &&
                 !coins &&
-                !import_secrets
+                !watch_coins &&
+                !watch_received &&
+                !payment_id &&
+                !viewing_key &&
+                !import_secrets &&
+                !import_viewing_keys &&
+                !seed &&
+                !import_seed &&
+                !mnemonic &&
+                !import_mnemonic &&
+                !change_password
             {
                 eprintln!("Error: You must use at least one flag for this subcommand");
                 eprintln!("Run with \"wallet -h\" to see the subcommand usage.");
                 exit(2);
             }
 
-            let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+            // Payment IDs are random and don't need the wallet
+            if payment_id {
+                println!("{}", PaymentId::random(&mut OsRng));
+                return Ok(())
+            }
+
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
 
             if initialize {
                 drk.initialize_wallet().await?;
+                if let Err(e) = drk.initialize_hd_seed().await {
+                    eprintln!("Failed to initialize wallet seed: {e:?}");
+                    exit(2);
+                }
                 if let Err(e) = drk.initialize_money().await {
                     eprintln!("Failed to initialize Money: {e:?}");
                     exit(2);
@@ -598,9 +995,29 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             if keygen {
-                if let Err(e) = drk.money_keygen().await {
-                    eprintln!("Failed to generate keypair: {e:?}");
-                    exit(2);
+                match drk.money_keygen().await {
+                    Ok(address) => {
+                        println!("New address:");
+                        println!("{}", address.to_address(network));
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to generate keypair: {e:?}");
+                        exit(2);
+                    }
+                }
+                return Ok(())
+            }
+
+            if diversify {
+                match drk.money_diversify().await {
+                    Ok(address) => {
+                        println!("New diversified address:");
+                        println!("{}", address.to_address(network));
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to derive diversified address: {e:?}");
+                        exit(2);
+                    }
                 }
                 return Ok(())
             }
@@ -637,15 +1054,33 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             if address {
-                let address = match drk.default_address().await {
-                    Ok(a) => a,
+                // Hand out the address carrying our viewing key, so payments
+                // made to it are always visible to that key.
+                let address = match drk.default_secret().await {
+                    Ok(s) => DiversifiedAddress::from_secret(&s),
                     Err(e) => {
                         eprintln!("Failed to fetch default address: {e:?}");
                         exit(2);
                     }
                 };
 
-                println!("{address}");
+                println!("{}", address.to_address(network));
+
+                return Ok(())
+            }
+
+            if viewing_key {
+                let secret = match drk.default_secret().await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        eprintln!("Failed to fetch default secret key: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                let viewing_key = ViewingKey::derive(&secret);
+                println!("Viewing key: {viewing_key}");
+                println!("Viewing public key: {}", viewing_key.public_key());
 
                 return Ok(())
             }
@@ -656,13 +1091,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 // Create a prettytable with the new data:
                 let mut table = Table::new();
                 table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
-                table.set_titles(row!["Key ID", "Public Key", "Secret Key", "Is Default"]);
-                for (key_id, public_key, secret_key, is_default) in addresses {
+                table.set_titles(row!["Key ID", "Address", "Secret Key", "Is Default"]);
+                for (key_id, _, secret_key, is_default) in addresses {
                     let is_default = match is_default {
                         1 => "*",
                         _ => "",
                     };
-                    table.add_row(row![key_id, public_key, secret_key, is_default]);
+                    let address = DiversifiedAddress::from_secret(&secret_key).to_address(network);
+                    table.add_row(row![key_id, address, secret_key, is_default]);
                 }
 
                 if table.is_empty() {
@@ -692,6 +1128,92 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 return Ok(())
             }
 
+            if change_password {
+                let password = rpassword::prompt_password("New wallet password: ")?;
+                if password != rpassword::prompt_password("Repeat new wallet password: ")? {
+                    eprintln!("Passwords do not match");
+                    exit(2);
+                }
+                if password.is_empty() || password == "changeme" {
+                    eprintln!("Please use a proper wallet password");
+                    exit(2);
+                }
+
+                if let Err(e) = drk.wallet.change_password(&password).await {
+                    eprintln!("Failed to change wallet password: {e:?}");
+                    exit(2);
+                }
+
+                println!("Wallet password changed, remember to update your configuration");
+                return Ok(())
+            }
+
+            if seed {
+                match drk.get_hd_seed().await {
+                    Ok(seed) => {
+                        let encoded = Zeroizing::new(bs58::encode(seed.as_slice()).into_string());
+                        println!("{}", encoded.as_str());
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to retrieve wallet seed: {e:?}");
+                        exit(2);
+                    }
+                }
+
+                return Ok(())
+            }
+
+            if import_seed {
+                let mut line = Zeroizing::new(String::new());
+                stdin().read_line(&mut line)?;
+                let Ok(seed) = bs58::decode(line.trim()).into_vec() else {
+                    eprintln!("Invalid base58-encoded seed");
+                    exit(2);
+                };
+                let seed = Zeroizing::new(seed);
+
+                if let Err(e) = drk.import_hd_seed(&seed).await {
+                    eprintln!("Failed to import wallet seed: {e:?}");
+                    exit(2);
+                }
+
+                println!("Imported wallet seed, run the key generating commands again to recover the keys");
+                return Ok(())
+            }
+
+            if mnemonic {
+                match drk.get_hd_mnemonic().await {
+                    Ok(mnemonic) => println!("{}", Zeroizing::new(mnemonic.to_string()).as_str()),
+                    Err(e) => {
+                        eprintln!("Failed to retrieve wallet mnemonic: {e:?}");
+                        exit(2);
+                    }
+                }
+
+                return Ok(())
+            }
+
+            if import_mnemonic {
+                let mut lines = stdin().lines();
+                let phrase = Zeroizing::new(lines.next().unwrap_or(Ok(String::new()))?);
+                let passphrase = Zeroizing::new(lines.next().unwrap_or(Ok(String::new()))?);
+                let mnemonic = match Mnemonic::from_str(&phrase) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        eprintln!("Invalid mnemonic phrase: {e}");
+                        exit(2);
+                    }
+                };
+
+                if let Err(e) = drk.import_hd_mnemonic(&mnemonic, &passphrase).await {
+                    eprintln!("Failed to import wallet mnemonic: {e:?}");
+                    exit(2);
+                }
+
+                println!("Imported wallet mnemonic, run the key generating commands again to recover the keys");
+                return Ok(())
+            }
+
             if import_secrets {
                 let mut secrets = vec![];
                 let lines = stdin().lines();
@@ -721,16 +1243,44 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 return Ok(())
             }
 
-            if tree {
-                let tree = drk.get_money_tree().await?;
+            if import_viewing_keys {
+                let mut keys = vec![];
+                let lines = stdin().lines();
+                for (i, line) in lines.enumerate() {
+                    if let Ok(line) = line {
+                        let Ok(key) = ViewingKey::from_str(line.trim()) else {
+                            println!("Warning: Failed to parse viewing key on line {i}");
+                            continue
+                        };
+                        keys.push(key);
+                    }
+                }
 
-                println!("{tree:#?}");
+                let pubkeys = match drk.import_watch_keys(keys).await {
+                    Ok(p) => p,
+                    Err(e) => {
+                        eprintln!("Failed to import viewing keys into wallet: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                for key in pubkeys {
+                    println!("{key}");
+                }
 
                 return Ok(())
             }
 
-            if coins {
-                let coins = drk.get_coins(true).await?;
+            if tree {
+                let tree = drk.get_money_tree().await?;
+
+                println!("{tree:#?}");
+
+                return Ok(())
+            }
+
+            if coins {
+                let coins = drk.get_account_coins(true).await?;
 
                 let aliases_map = drk.get_aliases_mapped_by_token().await?;
 
@@ -747,20 +1297,30 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                     "Aliases",
                     "Value",
                     "Spend Hook",
-                    "User Data"
+                    "User Data",
+                    "Payment ID",
+                    "Memo"
                 ]);
+
+                // Known hooked functions, used to name the coins' spend hooks
+                let mut registry = SpendHookRegistry::default();
+                money_client::register_spend_hooks(&mut registry);
+                dao_client::register_spend_hooks(&mut registry);
+
                 for coin in coins {
                     let aliases = match aliases_map.get(&coin.0.note.token_id.to_string()) {
                         Some(a) => a,
                         None => "-",
                     };
 
-                    let spend_hook = if coin.0.note.spend_hook != FuncId::none() {
-                        bs58::encode(&serialize_async(&coin.0.note.spend_hook.inner()).await)
-                            .into_string()
-                            .to_string()
-                    } else {
-                        String::from("-")
+                    let spend_hook = match coin.0.spend_hook(&registry) {
+                        CoinSpendHook::None => String::from("-"),
+                        CoinSpendHook::Known { name, .. } => name,
+                        CoinSpendHook::Unknown(func_id) => {
+                            bs58::encode(&serialize_async(&func_id.inner()).await)
+                                .into_string()
+                                .to_string()
+                        }
                     };
 
                     let user_data = if coin.0.note.user_data != pallas::Base::ZERO {
@@ -771,6 +1331,11 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                         String::from("-")
                     };
 
+                    let payment_id = match coin.0.note.verified_payment_id() {
+                        Some(p) => p.to_string(),
+                        None => String::from("-"),
+                    };
+
                     table.add_row(row![
                         bs58::encode(&serialize_async(&coin.0.coin.inner()).await)
                             .into_string()
@@ -784,7 +1349,9 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                             encode_base10(coin.0.note.value, BALANCE_BASE10_DECIMALS)
                         ),
                         spend_hook,
-                        user_data
+                        user_data,
+                        payment_id,
+                        coin.0.note.memo_str().unwrap_or("-")
                     ]);
                 }
 
@@ -793,6 +1360,86 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 return Ok(())
             }
 
+            if watch_coins {
+                let coins = drk.get_watch_coins().await?;
+
+                let aliases_map = drk.get_aliases_mapped_by_token().await?;
+
+                if coins.is_empty() {
+                    return Ok(())
+                }
+
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+                table.set_titles(row![
+                    "Coin",
+                    "Viewing Public Key",
+                    "Token ID",
+                    "Aliases",
+                    "Value",
+                    "Payment ID",
+                    "Memo"
+                ]);
+
+                for (coin, viewing_key, note) in coins {
+                    let aliases = match aliases_map.get(&note.token_id.to_string()) {
+                        Some(a) => a,
+                        None => "-",
+                    };
+
+                    let payment_id = match note.verified_payment_id() {
+                        Some(p) => p.to_string(),
+                        None => String::from("-"),
+                    };
+
+                    table.add_row(row![
+                        bs58::encode(&serialize_async(&coin.inner()).await).into_string(),
+                        viewing_key.public_key(),
+                        note.token_id,
+                        aliases,
+                        encode_base10(note.value, BALANCE_BASE10_DECIMALS),
+                        payment_id,
+                        note.memo_str().unwrap_or("-")
+                    ]);
+                }
+
+                println!("{table}");
+
+                return Ok(())
+            }
+
+            if watch_received {
+                // Watch-only accounts can't see their spends, so we can
+                // only show what they received.
+                let received = drk.watch_received().await?;
+
+                let aliases_map = drk.get_aliases_mapped_by_token().await?;
+
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+                table.set_titles(row!["Token ID", "Aliases", "Received"]);
+                for (token_id, value) in received.iter() {
+                    let aliases = match aliases_map.get(token_id) {
+                        Some(a) => a,
+                        None => "-",
+                    };
+
+                    table.add_row(row![
+                        token_id,
+                        aliases,
+                        encode_base10(*value, BALANCE_BASE10_DECIMALS)
+                    ]);
+                }
+
+                if table.is_empty() {
+                    println!("No watch-only payments received");
+                } else {
+                    println!("{table}");
+                }
+
+                return Ok(())
+            }
+
             unreachable!()
         }
 
@@ -814,7 +1461,9 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             };
 
             let coin = Coin::from(elem);
-            let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
             if let Err(e) = drk.unspend_coin(&coin).await {
                 eprintln!("Failed to mark coin as unspent: {e:?}");
                 exit(2);
@@ -823,15 +1472,19 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             Ok(())
         }
 
-        Subcmd::Transfer { amount, token, recipient } => {
-            let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+        Subcmd::Transfer { amount, token, recipient, payment_id, viewing_key, memo, selection } => {
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
 
             if let Err(e) = f64::from_str(&amount) {
                 eprintln!("Invalid amount: {e:?}");
                 exit(2);
             }
 
-            let rcpt = match PublicKey::from_str(&recipient) {
+            // Recipient addresses carry the viewing key to encrypt the note to,
+            // while bare public keys don't.
+            let (rcpt, rcpt_viewing_key) = match parse_recipient(network, &recipient) {
                 Ok(r) => r,
                 Err(e) => {
                     eprintln!("Invalid recipient: {e:?}");
@@ -847,7 +1500,59 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 }
             };
 
-            let tx = match drk.transfer(&amount, token_id, rcpt).await {
+            let payment_id = match payment_id {
+                Some(p) => match PaymentId::from_str(&p) {
+                    Ok(p) => Some(p),
+                    Err(e) => {
+                        eprintln!("Invalid payment ID: {e:?}");
+                        exit(2);
+                    }
+                },
+                None => None,
+            };
+
+            let viewing_key = match viewing_key {
+                Some(v) => match PublicKey::from_str(&v) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        eprintln!("Invalid viewing key: {e:?}");
+                        exit(2);
+                    }
+                },
+                None => rcpt_viewing_key,
+            };
+            if viewing_key.is_none() {
+                eprintln!("Warning: Recipient is a bare public key, so its viewing key won't see this payment");
+            }
+
+            let memo = memo.map(|m| m.into_bytes()).unwrap_or_default();
+            if memo.len() > MONEY_NOTE_MEMO_MAX_LEN {
+                eprintln!("Memo is too long, max length is {MONEY_NOTE_MEMO_MAX_LEN} bytes");
+                exit(2);
+            }
+
+            let selection: Box<dyn CoinSelection> = match selection.as_str() {
+                "exact" => Box::new(BranchAndBound::default()),
+                "largest" => Box::new(LargestFirst),
+                "random" => Box::new(RandomSelection),
+                _ => {
+                    eprintln!("Invalid coin selection strategy: {selection}");
+                    exit(2);
+                }
+            };
+
+            let tx = match drk
+                .transfer(
+                    &amount,
+                    token_id,
+                    rcpt,
+                    viewing_key,
+                    payment_id,
+                    memo,
+                    selection.as_ref(),
+                )
+                .await
+            {
                 Ok(t) => t,
                 Err(e) => {
                     eprintln!("Failed to create payment transaction: {e:?}");
@@ -861,7 +1566,9 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
         }
 
         Subcmd::Otc { command } => {
-            let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
 
             match command {
                 OtcSubcmd::Init { value_pair, token_pair } => {
@@ -926,12 +1633,443 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                         exit(1);
                     };
 
-                    let mut tx: Transaction = deserialize_async(&bytes).await?;
+                    let mut tx: Transaction = deserialize_async(&bytes).await?;
+
+                    if let Err(e) = drk.sign_swap(&mut tx).await {
+                        eprintln!("Failed to sign joined swap transaction: {e:?}");
+                        exit(2);
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&tx).await));
+                    Ok(())
+                }
+            }
+        }
+
+        Subcmd::Coinjoin { command } => {
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
+
+            // All the CoinJoin data is passed around as base64 lines
+            let mut buf = String::new();
+            if !matches!(command, CoinjoinSubcmd::Init { .. }) {
+                stdin().read_to_string(&mut buf)?;
+            }
+            let mut lines = vec![];
+            for line in buf.lines().filter(|x| !x.trim().is_empty()) {
+                let Some(bytes) = base64::decode(line.trim()) else {
+                    eprintln!("Failed to decode CoinJoin data");
+                    exit(2);
+                };
+                lines.push(bytes);
+            }
+
+            match command {
+                CoinjoinSubcmd::Init { token } => {
+                    let token_id = match drk.get_token(token).await {
+                        Ok(t) => t,
+                        Err(e) => {
+                            eprintln!("Invalid token alias: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    let session = CoinJoinSession::new(token_id);
+                    println!("{}", base64::encode(&serialize_async(&session).await));
+                    Ok(())
+                }
+
+                CoinjoinSubcmd::Contribute { amount, recipient } => {
+                    if let Err(e) = f64::from_str(&amount) {
+                        eprintln!("Invalid amount: {e:?}");
+                        exit(2);
+                    }
+
+                    let rcpt = match parse_public_key(network, &recipient) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            eprintln!("Invalid recipient: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    if lines.len() != 1 {
+                        eprintln!("Expected the CoinJoin session from stdin");
+                        exit(2);
+                    }
+                    let session: CoinJoinSession = deserialize_async(&lines[0]).await?;
+
+                    let contribution = match drk.contribute_coinjoin(session, &amount, rcpt).await {
+                        Ok(c) => c,
+                        Err(e) => {
+                            eprintln!("Failed to create CoinJoin contribution: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&contribution).await));
+                    Ok(())
+                }
+
+                CoinjoinSubcmd::Join => {
+                    if lines.len() < 2 {
+                        eprintln!("Expected the CoinJoin session and contributions from stdin");
+                        exit(2);
+                    }
+                    let session: CoinJoinSession = deserialize_async(&lines[0]).await?;
+                    let mut contributions: Vec<CoinJoinContribution> = vec![];
+                    for bytes in &lines[1..] {
+                        contributions.push(deserialize_async(bytes).await?);
+                    }
+
+                    let tx = match drk.join_coinjoin(&session, &contributions) {
+                        Ok(tx) => tx,
+                        Err(e) => {
+                            eprintln!("Failed to join CoinJoin contributions: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&tx).await));
+                    Ok(())
+                }
+
+                CoinjoinSubcmd::Sign => {
+                    if lines.len() != 2 {
+                        eprintln!("Expected our contribution and the CoinJoin tx from stdin");
+                        exit(2);
+                    }
+                    let contribution: CoinJoinContribution = deserialize_async(&lines[0]).await?;
+                    let tx: Transaction = deserialize_async(&lines[1]).await?;
+
+                    let sigs = match drk.sign_coinjoin(&contribution, &tx).await {
+                        Ok(s) => s,
+                        Err(e) => {
+                            eprintln!("Failed to sign CoinJoin transaction: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&sigs).await));
+                    Ok(())
+                }
+
+                CoinjoinSubcmd::Finalize => {
+                    if lines.len() < 2 {
+                        eprintln!("Expected the CoinJoin tx and signatures from stdin");
+                        exit(2);
+                    }
+                    let mut tx: Transaction = deserialize_async(&lines[0]).await?;
+                    let mut partial_sigs = vec![];
+                    for bytes in &lines[1..] {
+                        partial_sigs.push(deserialize_async(bytes).await?);
+                    }
+
+                    if let Err(e) = drk.finalize_coinjoin(&mut tx, &partial_sigs) {
+                        eprintln!("Failed to finalize CoinJoin transaction: {e:?}");
+                        exit(2);
+                    }
+
+                    println!("{}", base64::encode(&serialize_async(&tx).await));
+                    Ok(())
+                }
+            }
+        }
+
+        Subcmd::Group { command } => {
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
+
+            // All the group data is passed around as base64 lines
+            let mut buf = String::new();
+            if matches!(
+                command,
+                GroupSubcmd::Deal |
+                    GroupSubcmd::Finish |
+                    GroupSubcmd::Package { .. } |
+                    GroupSubcmd::Sign { .. } |
+                    GroupSubcmd::Finalize { .. }
+            ) {
+                stdin().read_to_string(&mut buf)?;
+            }
+            let mut lines = vec![];
+            for line in buf.lines().filter(|x| !x.trim().is_empty()) {
+                let Some(bytes) = base64::decode(line.trim()) else {
+                    eprintln!("Failed to decode group data");
+                    exit(2);
+                };
+                lines.push(bytes);
+            }
+
+            let parse_group = |group: &str| match PublicKey::from_str(group) {
+                Ok(g) => g,
+                Err(e) => {
+                    eprintln!("Invalid group public key: {e:?}");
+                    exit(2);
+                }
+            };
+
+            match command {
+                GroupSubcmd::Keygen { index, threshold, participants } => {
+                    let (state, commitment) = match drk.group_keygen(index, threshold, participants)
+                    {
+                        Ok(v) => v,
+                        Err(e) => {
+                            eprintln!("Failed to start group key generation: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&state).await));
+                    println!("{}", base64::encode(&serialize_async(&commitment).await));
+                    Ok(())
+                }
+
+                GroupSubcmd::Deal => {
+                    if lines.len() < 2 {
+                        eprintln!("Expected our key generation state and commitments from stdin");
+                        exit(2);
+                    }
+                    let state: GroupKeygenState = deserialize_async(&lines[0]).await?;
+                    let mut commitments: Vec<GroupKeygenCommitment> = vec![];
+                    for bytes in &lines[1..] {
+                        commitments.push(deserialize_async(bytes).await?);
+                    }
+
+                    let shares = match drk.group_deal(&state, &commitments) {
+                        Ok(s) => s,
+                        Err(e) => {
+                            eprintln!("Failed to deal group key shares: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    for share in shares {
+                        println!("{}", base64::encode(&serialize_async(&share).await));
+                    }
+                    Ok(())
+                }
+
+                GroupSubcmd::Finish => {
+                    if lines.len() < 2 {
+                        eprintln!(
+                            "Expected our key generation state, commitments and shares from stdin"
+                        );
+                        exit(2);
+                    }
+                    let state: GroupKeygenState = deserialize_async(&lines[0]).await?;
+                    // Everyone's commitments come first, then the shares
+                    let participants = state.secret.participants() as usize;
+                    if lines.len() < participants + 2 {
+                        eprintln!("Expected the commitments of all {participants} participants");
+                        exit(2);
+                    }
+                    let mut commitments: Vec<GroupKeygenCommitment> = vec![];
+                    for bytes in &lines[1..=participants] {
+                        commitments.push(deserialize_async(bytes).await?);
+                    }
+                    let mut shares: Vec<GroupKeygenShare> = vec![];
+                    for bytes in &lines[participants + 1..] {
+                        shares.push(deserialize_async(bytes).await?);
+                    }
+
+                    let custody = match drk.group_finish(&state, &commitments, &shares).await {
+                        Ok(c) => c,
+                        Err(e) => {
+                            eprintln!("Failed to finish group key generation: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("Group public key: {}", custody.group_public);
+                    println!("Custody terms: {}", base64::encode(&serialize_async(&custody).await));
+                    Ok(())
+                }
+
+                GroupSubcmd::List => {
+                    let groups = drk.get_groups().await?;
+
+                    let mut table = Table::new();
+                    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+                    table.set_titles(row![
+                        "Group public key",
+                        "Index",
+                        "Threshold",
+                        "Custody terms"
+                    ]);
+                    for (key, pubkeys) in groups {
+                        let custody = money_client::group_spend_v1::group_custody(&pubkeys);
+                        table.add_row(row![
+                            key.group_public(),
+                            key.index(),
+                            key.threshold(),
+                            base64::encode(&serialize_async(&custody).await)
+                        ]);
+                    }
+
+                    if table.is_empty() {
+                        println!("No groups found");
+                    } else {
+                        println!("{table}");
+                    }
+
+                    Ok(())
+                }
+
+                GroupSubcmd::Lock { amount, token, custody } => {
+                    if let Err(e) = f64::from_str(&amount) {
+                        eprintln!("Invalid amount: {e:?}");
+                        exit(2);
+                    }
+
+                    let token_id = match drk.get_token(token).await {
+                        Ok(t) => t,
+                        Err(e) => {
+                            eprintln!("Invalid token alias: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    let Some(custody) = base64::decode(&custody) else {
+                        eprintln!("Failed to decode custody terms");
+                        exit(2);
+                    };
+                    let custody = deserialize_async(&custody).await?;
+
+                    let tx = match drk.group_lock(&custody, &amount, token_id).await {
+                        Ok(t) => t,
+                        Err(e) => {
+                            eprintln!("Failed to create group lock transaction: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&tx).await));
+                    Ok(())
+                }
+
+                GroupSubcmd::Spend { group, amount, token, recipient } => {
+                    let group = parse_group(&group);
+
+                    if let Err(e) = f64::from_str(&amount) {
+                        eprintln!("Invalid amount: {e:?}");
+                        exit(2);
+                    }
+
+                    let token_id = match drk.get_token(token).await {
+                        Ok(t) => t,
+                        Err(e) => {
+                            eprintln!("Invalid token alias: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    let rcpt = match parse_public_key(network, &recipient) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            eprintln!("Invalid recipient: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    let tx = match drk.group_spend(&group, &amount, token_id, rcpt).await {
+                        Ok(t) => t,
+                        Err(e) => {
+                            eprintln!("Failed to create group spend transaction: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&tx).await));
+                    Ok(())
+                }
+
+                GroupSubcmd::Commit { group } => {
+                    let group = parse_group(&group);
+
+                    let (nonces, commitments) = match drk.group_commit(&group).await {
+                        Ok(v) => v,
+                        Err(e) => {
+                            eprintln!("Failed to create signing commitments: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&nonces).await));
+                    println!("{}", base64::encode(&serialize_async(&commitments).await));
+                    Ok(())
+                }
+
+                GroupSubcmd::Package { group } => {
+                    let group = parse_group(&group);
+
+                    if lines.len() < 2 {
+                        eprintln!("Expected the group spend tx and commitments from stdin");
+                        exit(2);
+                    }
+                    let tx: Transaction = deserialize_async(&lines[0]).await?;
+                    let mut commitments = vec![];
+                    for bytes in &lines[1..] {
+                        commitments.push(deserialize_async(bytes).await?);
+                    }
+
+                    let package = match drk.group_package(&group, &tx, commitments) {
+                        Ok(p) => p,
+                        Err(e) => {
+                            eprintln!("Failed to create signing package: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&package).await));
+                    Ok(())
+                }
+
+                GroupSubcmd::Sign { group } => {
+                    let group = parse_group(&group);
+
+                    if lines.len() != 3 {
+                        eprintln!("Expected our nonces, the group spend tx and signing package from stdin");
+                        exit(2);
+                    }
+                    let nonces = deserialize_async(&lines[0]).await?;
+                    let tx: Transaction = deserialize_async(&lines[1]).await?;
+                    let package = deserialize_async(&lines[2]).await?;
+
+                    let share = match drk.group_sign(&group, nonces, &tx, &package).await {
+                        Ok(s) => s,
+                        Err(e) => {
+                            eprintln!("Failed to sign group spend transaction: {e:?}");
+                            exit(2);
+                        }
+                    };
+
+                    println!("{}", base64::encode(&serialize_async(&share).await));
+                    Ok(())
+                }
 
-                    if let Err(e) = drk.sign_swap(&mut tx).await {
-                        eprintln!("Failed to sign joined swap transaction: {e:?}");
+                GroupSubcmd::Finalize { group } => {
+                    let group = parse_group(&group);
+
+                    if lines.len() < 3 {
+                        eprintln!(
+                            "Expected the group spend tx, signing package and shares from stdin"
+                        );
                         exit(2);
-                    };
+                    }
+                    let mut tx: Transaction = deserialize_async(&lines[0]).await?;
+                    let package = deserialize_async(&lines[1]).await?;
+                    let mut shares = vec![];
+                    for bytes in &lines[2..] {
+                        shares.push(deserialize_async(bytes).await?);
+                    }
+
+                    if let Err(e) = drk.group_finalize(&group, &mut tx, &package, &shares).await {
+                        eprintln!("Failed to finalize group spend transaction: {e:?}");
+                        exit(2);
+                    }
 
                     println!("{}", base64::encode(&serialize_async(&tx).await));
                     Ok(())
@@ -939,8 +2077,80 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
         }
 
+        Subcmd::Signer { command } => match command {
+            SignerSubcmd::Serve { socket } => {
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+
+                if let Err(e) = drk.serve_signer(&expand_path(&socket)?).await {
+                    eprintln!("Failed to serve signer: {e:?}");
+                    exit(2);
+                }
+
+                Ok(())
+            }
+
+            SignerSubcmd::Sign { socket } => {
+                let mut buf = String::new();
+                stdin().read_to_string(&mut buf)?;
+                let Some(bytes) = base64::decode(buf.trim()) else {
+                    eprintln!("Failed to decode transaction bundle");
+                    exit(2);
+                };
+
+                let mut bundle: TransactionBundle = deserialize_async(&bytes).await?;
+                let added = match signer::sign_bundle(&expand_path(&socket)?, &mut bundle) {
+                    Ok(a) => a,
+                    Err(e) => {
+                        eprintln!("Failed to sign transaction bundle: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                eprintln!("Added {added} signatures");
+                println!("{}", base64::encode(&serialize_async(&bundle).await));
+                Ok(())
+            }
+
+            SignerSubcmd::Finalize => {
+                let mut buf = String::new();
+                stdin().read_to_string(&mut buf)?;
+                let Some(bytes) = base64::decode(buf.trim()) else {
+                    eprintln!("Failed to decode transaction bundle");
+                    exit(2);
+                };
+
+                let bundle: TransactionBundle = deserialize_async(&bytes).await?;
+                let tx = match bundle.finalize() {
+                    Ok(t) => t,
+                    Err(e) => {
+                        eprintln!("Failed to finalize transaction bundle: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                println!("{}", base64::encode(&serialize_async(&tx).await));
+                Ok(())
+            }
+        },
+
         Subcmd::Dao { command } => match command {
-            DaoSubcmd::Create { proposer_limit, quorum, approval_ratio, gov_token_id } => {
+            DaoSubcmd::Create {
+                proposer_limit,
+                quorum,
+                approval_ratio,
+                gov_token_id,
+                guardian,
+                proposal_deposit,
+                early_exec_quorum,
+                vote_weight,
+            } => {
                 if let Err(e) = f64::from_str(&proposer_limit) {
                     eprintln!("Invalid proposer limit: {e:?}");
                     exit(2);
@@ -953,6 +2163,49 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 let proposer_limit = decode_base10(&proposer_limit, BALANCE_BASE10_DECIMALS, true)?;
                 let quorum = decode_base10(&quorum, BALANCE_BASE10_DECIMALS, true)?;
 
+                let early_exec_quorum = match early_exec_quorum {
+                    Some(early_exec_quorum) => {
+                        if let Err(e) = f64::from_str(&early_exec_quorum) {
+                            eprintln!("Invalid early execution quorum: {e:?}");
+                            exit(2);
+                        }
+                        decode_base10(&early_exec_quorum, BALANCE_BASE10_DECIMALS, true)?
+                    }
+                    None => u64::MAX,
+                };
+
+                if early_exec_quorum < quorum {
+                    eprintln!("Error: Early execution quorum cannot be less than the quorum");
+                    exit(2);
+                }
+
+                let proposal_deposit = match proposal_deposit {
+                    Some(proposal_deposit) => {
+                        if let Err(e) = f64::from_str(&proposal_deposit) {
+                            eprintln!("Invalid proposal deposit: {e:?}");
+                            exit(2);
+                        }
+                        decode_base10(&proposal_deposit, BALANCE_BASE10_DECIMALS, true)?
+                    }
+                    None => 0,
+                };
+
+                let vote_weight = match vote_weight.as_deref() {
+                    None | Some("linear") => DaoVoteWeight::Linear,
+                    Some("sqrt") => DaoVoteWeight::SquareRoot,
+                    Some(vote_weight) => {
+                        let Some(cap) = vote_weight.strip_prefix("capped:") else {
+                            eprintln!("Invalid vote weight: {vote_weight}");
+                            exit(2);
+                        };
+                        if let Err(e) = f64::from_str(cap) {
+                            eprintln!("Invalid vote weight cap: {e:?}");
+                            exit(2);
+                        }
+                        DaoVoteWeight::Capped(decode_base10(cap, BALANCE_BASE10_DECIMALS, true)?)
+                    }
+                };
+
                 if approval_ratio > 1.0 {
                     eprintln!("Error: Approval ratio cannot be >1.0");
                     exit(2);
@@ -961,7 +2214,25 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 let approval_ratio_base = 100_u64;
                 let approval_ratio_quot = (approval_ratio * approval_ratio_base as f64) as u64;
 
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let guardian_public_key = match guardian {
+                    Some(guardian) => match parse_public_key(network, &guardian) {
+                        Ok(p) => Some(p),
+                        Err(e) => {
+                            eprintln!("Invalid guardian public key: {e:?}");
+                            exit(2);
+                        }
+                    },
+                    None => None,
+                };
+
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let gov_token_id = match drk.get_token(gov_token_id).await {
                     Ok(g) => g,
                     Err(e) => {
@@ -970,16 +2241,26 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                     }
                 };
 
-                let secret_key = SecretKey::random(&mut OsRng);
+                let secret_key = match drk.derive_next_secret(KeyPurpose::Dao).await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        eprintln!("Failed to derive DAO secret key: {e:?}");
+                        exit(2);
+                    }
+                };
                 let bulla_blind = pallas::Base::random(&mut OsRng);
 
                 let dao_params = DaoParams {
                     proposer_limit,
+                    proposal_deposit,
                     quorum,
+                    early_exec_quorum,
                     approval_ratio_base,
                     approval_ratio_quot,
+                    vote_weight,
                     gov_token_id,
                     secret_key,
+                    guardian_public_key,
                     bulla_blind,
                 };
 
@@ -1005,7 +2286,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 let bytes = bs58::decode(&buf.trim()).into_vec()?;
                 let dao_params: DaoParams = deserialize_async(&bytes).await?;
 
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
 
                 if let Err(e) = drk.import_dao(dao_name, dao_params).await {
                     eprintln!("Failed to import DAO: {e:?}");
@@ -1016,7 +2304,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             DaoSubcmd::List { dao_alias } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 // We cannot use .map() since get_dao_id() uses ?
                 let dao_id = match dao_alias {
                     Some(alias) => Some(drk.get_dao_id(&alias).await?),
@@ -1032,7 +2327,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             DaoSubcmd::Balance { dao_alias } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let dao_id = drk.get_dao_id(&dao_alias).await?;
 
                 let balmap = match drk.dao_balance(dao_id).await {
@@ -1078,7 +2380,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             DaoSubcmd::Mint { dao_alias } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let dao_id = drk.get_dao_id(&dao_alias).await?;
 
                 let tx = match drk.dao_mint(dao_id).await {
@@ -1098,7 +2407,7 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                     exit(2);
                 }
                 let amount = decode_base10(&amount, BALANCE_BASE10_DECIMALS, true)?;
-                let rcpt = match PublicKey::from_str(&recipient) {
+                let rcpt = match parse_public_key(network, &recipient) {
                     Ok(r) => r,
                     Err(e) => {
                         eprintln!("Invalid recipient: {e:?}");
@@ -1106,7 +2415,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                     }
                 };
 
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let dao_id = drk.get_dao_id(&dao_alias).await?;
                 let token_id = match drk.get_token(token).await {
                     Ok(t) => t,
@@ -1128,7 +2444,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             DaoSubcmd::Proposals { dao_alias } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let dao_id = drk.get_dao_id(&dao_alias).await?;
 
                 let proposals = drk.get_dao_proposals(dao_id).await?;
@@ -1141,7 +2464,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             DaoSubcmd::Proposal { dao_alias, proposal_id } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let dao_id = drk.get_dao_id(&dao_alias).await?;
 
                 let proposals = drk.get_dao_proposals(dao_id).await?;
@@ -1163,7 +2493,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             DaoSubcmd::Vote { dao_alias, proposal_id, vote, vote_weight } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let dao_id = drk.get_dao_id(&dao_alias).await?;
 
                 if let Err(e) = f64::from_str(&vote_weight) {
@@ -1194,7 +2531,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             DaoSubcmd::Exec { dao_alias, proposal_id } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let dao_id = drk.get_dao_id(&dao_alias).await?;
                 let dao = drk.get_dao_by_id(dao_id).await?;
                 let proposal = drk.get_dao_proposal_by_id(proposal_id).await?;
@@ -1237,7 +2581,9 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
 
             let tx = deserialize_async(&bytes).await?;
 
-            let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
 
             let txid = match drk.broadcast_tx(&tx).await {
                 Ok(t) => t,
@@ -1252,10 +2598,31 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             Ok(())
         }
 
-        Subcmd::Subscribe => {
-            let drk =
-                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint.clone(), ex.clone())
-                    .await?;
+        Subcmd::Subscribe { events } => {
+            let drk = Drk::new(
+                args.wallet_path,
+                args.wallet_pass,
+                args.endpoint.clone(),
+                args.endpoint_key.clone(),
+                ex.clone(),
+            )
+            .await?;
+
+            if let Some(path) = events {
+                let path = expand_path(&path)?;
+                let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+                let subscription = drk.events.clone().subscribe().await;
+                ex.spawn(async move {
+                    loop {
+                        let event = subscription.receive().await;
+                        if let Err(e) = writeln!(file, "{}", event.to_json().stringify().unwrap()) {
+                            eprintln!("Writing wallet event failed: {e:?}");
+                            break
+                        }
+                    }
+                })
+                .detach();
+            }
 
             if let Err(e) = drk.subscribe_blocks(args.endpoint, ex).await {
                 eprintln!("Block subscription failed: {e:?}");
@@ -1265,10 +2632,15 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             Ok(())
         }
 
-        Subcmd::Scan { reset, list, checkpoint } => {
-            let drk =
-                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint.clone(), ex.clone())
-                    .await?;
+        Subcmd::Scan { reset, list, checkpoint, rescan } => {
+            let drk = Drk::new(
+                args.wallet_path,
+                args.wallet_pass,
+                args.endpoint.clone(),
+                args.endpoint_key.clone(),
+                ex.clone(),
+            )
+            .await?;
 
             if reset {
                 println!("Reset requested.");
@@ -1282,9 +2654,36 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             if list {
-                println!("List requested.");
-                // TODO: implement
-                unimplemented!()
+                let checkpoints = match drk.get_money_tree_checkpoints().await {
+                    Ok(c) => c,
+                    Err(e) => {
+                        eprintln!("Failed to retrieve checkpoints: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                if checkpoints.is_empty() {
+                    println!("No checkpoints found");
+                    return Ok(())
+                }
+
+                println!("Merkle tree checkpoints at block heights:");
+                for height in checkpoints {
+                    println!("{height}");
+                }
+
+                return Ok(())
+            }
+
+            if let Some(height) = rescan {
+                println!("Rescan requested from block {height}");
+                if let Err(e) = drk.rescan(height).await {
+                    eprintln!("Failed during rescanning: {e:?}");
+                    exit(2);
+                }
+                println!("Finished rescanning blockchain");
+
+                return Ok(())
             }
 
             if let Some(c) = checkpoint {
@@ -1302,13 +2701,96 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             Ok(())
         }
 
+        Subcmd::Audit => {
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
+
+            let discrepancies = match drk.audit().await {
+                Ok(d) => d,
+                Err(e) => {
+                    eprintln!("Failed to audit wallet: {e:?}");
+                    exit(2);
+                }
+            };
+
+            if discrepancies.is_empty() {
+                println!("Wallet state is consistent with the blockchain");
+                return Ok(())
+            }
+
+            println!("Found {} discrepancies:", discrepancies.len());
+            for discrepancy in discrepancies {
+                println!("{discrepancy}");
+            }
+            exit(1);
+        }
+
+        Subcmd::View { viewing_key } => {
+            let viewing_key = match ViewingKey::from_str(&viewing_key) {
+                Ok(v) => v,
+                Err(e) => {
+                    eprintln!("Invalid viewing key: {e:?}");
+                    exit(2);
+                }
+            };
+
+            let drk =
+                Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, args.endpoint_key, ex)
+                    .await?;
+
+            let notes = match drk.view_incoming(&viewing_key).await {
+                Ok(n) => n,
+                Err(e) => {
+                    eprintln!("Failed to scan for viewing key notes: {e:?}");
+                    exit(2);
+                }
+            };
+
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+            table.set_titles(row!["Block", "Coin", "Token ID", "Value", "Payment ID", "Memo"]);
+            for (height, coin, note) in notes {
+                let payment_id = match note.verified_payment_id() {
+                    Some(p) => p.to_string(),
+                    None => String::from("-"),
+                };
+
+                table.add_row(row![
+                    height,
+                    coin,
+                    note.token_id,
+                    format!(
+                        "{} ({})",
+                        note.value,
+                        encode_base10(note.value, BALANCE_BASE10_DECIMALS)
+                    ),
+                    payment_id,
+                    note.memo_str().unwrap_or("-")
+                ]);
+            }
+
+            if table.is_empty() {
+                println!("No notes found for viewing key");
+            } else {
+                println!("{table}");
+            }
+
+            Ok(())
+        }
+
         Subcmd::Explorer { command } => match command {
             ExplorerSubcmd::FetchTx { tx_hash, full, encode } => {
                 let tx_hash = TransactionHash(*blake3::Hash::from_hex(&tx_hash)?.as_bytes());
 
-                let drk =
-                    Drk::new(args.wallet_path, args.wallet_pass, args.endpoint.clone(), ex.clone())
-                        .await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint.clone(),
+                    args.endpoint_key.clone(),
+                    ex.clone(),
+                )
+                .await?;
 
                 let tx = match drk.get_tx(&tx_hash).await {
                     Ok(tx) => tx,
@@ -1350,9 +2832,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
 
                 let tx = deserialize_async(&bytes).await?;
 
-                let drk =
-                    Drk::new(args.wallet_path, args.wallet_pass, args.endpoint.clone(), ex.clone())
-                        .await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint.clone(),
+                    args.endpoint_key.clone(),
+                    ex.clone(),
+                )
+                .await?;
 
                 let is_valid = match drk.simulate_tx(&tx).await {
                     Ok(b) => b,
@@ -1369,9 +2856,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             ExplorerSubcmd::TxsHistory { tx_hash, encode } => {
-                let drk =
-                    Drk::new(args.wallet_path, args.wallet_pass, args.endpoint.clone(), ex.clone())
-                        .await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint.clone(),
+                    args.endpoint_key.clone(),
+                    ex.clone(),
+                )
+                .await?;
 
                 if let Some(c) = tx_hash {
                     let (tx_hash, status, tx) = drk.get_tx_history_record(&c).await?;
@@ -1412,6 +2904,42 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
 
                 Ok(())
             }
+
+            ExplorerSubcmd::ExportHistory { format } => {
+                if format != "csv" && format != "json" {
+                    eprintln!("Invalid format: {format}, expected csv or json");
+                    exit(2);
+                }
+
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+                let entries = match drk.get_money_history().await {
+                    Ok(e) => e,
+                    Err(e) => {
+                        eprintln!("Failed to retrieve funds history: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                if format == "json" {
+                    let entries = entries.iter().map(|e| e.to_json()).collect();
+                    println!("{}", JsonValue::Array(entries).stringify()?);
+                    return Ok(())
+                }
+
+                println!("{}", HistoryEntry::CSV_HEADER);
+                for entry in entries {
+                    println!("{}", entry.to_csv());
+                }
+
+                Ok(())
+            }
         },
 
         Subcmd::Alias { command } => match command {
@@ -1429,7 +2957,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                     }
                 };
 
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 if let Err(e) = drk.add_alias(alias, token_id).await {
                     eprintln!("Failed to add alias: {e:?}");
                     exit(2);
@@ -1450,7 +2985,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                     None => None,
                 };
 
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let map = drk.get_aliases(alias, token_id).await?;
 
                 // Create a prettytable with the new data:
@@ -1471,7 +3013,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             AliasSubcmd::Remove { alias } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 if let Err(e) = drk.remove_alias(alias).await {
                     eprintln!("Failed to remove alias: {e:?}");
                     exit(2);
@@ -1481,6 +3030,73 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
         },
 
+        Subcmd::Account { command } => match command {
+            AccountSubcmd::Create { name } => {
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+                match drk.create_account(&name).await {
+                    Ok(account_id) => println!("Created account {name} with ID {account_id}"),
+                    Err(e) => {
+                        eprintln!("Failed to create account: {e:?}");
+                        exit(2);
+                    }
+                }
+
+                Ok(())
+            }
+
+            AccountSubcmd::List => {
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+                let accounts = drk.list_accounts().await?;
+
+                // Create a prettytable with the new data:
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+                table.set_titles(row!["ID", "Name", "Active"]);
+                for (account_id, name, is_active) in accounts.iter() {
+                    table.add_row(row![account_id, name, is_active]);
+                }
+
+                if table.is_empty() {
+                    println!("No accounts found");
+                } else {
+                    println!("{table}");
+                }
+
+                Ok(())
+            }
+
+            AccountSubcmd::Switch { name } => {
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+                if let Err(e) = drk.switch_account(&name).await {
+                    eprintln!("Failed to switch account: {e:?}");
+                    exit(2);
+                }
+
+                Ok(())
+            }
+        },
+
         Subcmd::Token { command } => match command {
             TokenSubcmd::Import => {
                 let mut buf = String::new();
@@ -1493,7 +3109,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                     }
                 };
 
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 if let Err(e) = drk.import_mint_authority(mint_authority).await {
                     eprintln!("Importing mint authority failed: {e:?}");
                     exit(2);
@@ -1506,9 +3129,22 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             TokenSubcmd::GenerateMint => {
-                let mint_authority = SecretKey::random(&mut OsRng);
-
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+
+                let mint_authority = match drk.derive_next_secret(KeyPurpose::TokenMint).await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        eprintln!("Failed to derive mint authority: {e:?}");
+                        exit(2);
+                    }
+                };
 
                 if let Err(e) = drk.import_mint_authority(mint_authority).await {
                     eprintln!("Importing mint authority failed: {e:?}");
@@ -1523,7 +3159,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             TokenSubcmd::List => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let tokens = drk.list_tokens().await?;
                 let aliases_map = match drk.get_aliases_mapped_by_token().await {
                     Ok(map) => map,
@@ -1555,16 +3198,131 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
                 Ok(())
             }
 
+            TokenSubcmd::Nfts => {
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+                let nfts = drk.get_money_nfts().await?;
+
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+                table.set_titles(row!["Token ID", "Metadata", "Burned"]);
+
+                for (token_id, metadata, burned) in nfts {
+                    let metadata = bs58::encode(&serialize_async(&metadata).await).into_string();
+                    table.add_row(row![token_id, metadata, burned]);
+                }
+
+                if table.is_empty() {
+                    println!("No NFTs found");
+                } else {
+                    println!("{table}");
+                }
+
+                Ok(())
+            }
+
+            TokenSubcmd::Supply { token } => {
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+                let token_id = match drk.get_token(token).await {
+                    Ok(t) => t,
+                    Err(e) => {
+                        eprintln!("Invalid Token ID: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                let (supply_cap, revoked) = match drk.get_money_token_policy(&token_id).await {
+                    Ok(p) => p,
+                    Err(e) => {
+                        eprintln!("Failed to fetch token supply policy: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                println!("Token ID: {token_id}");
+                match supply_cap {
+                    Some(cap) => {
+                        println!("Supply cap: {}", encode_base10(cap, BALANCE_BASE10_DECIMALS))
+                    }
+                    None => println!("Supply cap: None"),
+                }
+                if revoked {
+                    println!("Mint authority: Revoked (fixed supply)");
+                } else {
+                    println!("Mint authority: Active");
+                }
+
+                Ok(())
+            }
+
+            TokenSubcmd::Info { token } => {
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
+                let token_id = match drk.get_token(token).await {
+                    Ok(t) => t,
+                    Err(e) => {
+                        eprintln!("Invalid Token ID: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                let metadata = match drk.lookup_token_metadata(&token_id).await {
+                    Ok(m) => m,
+                    Err(e) => {
+                        eprintln!("Failed to fetch token metadata: {e:?}");
+                        exit(2);
+                    }
+                };
+
+                let Some(metadata) = metadata else {
+                    println!("No metadata registered for token {token_id}");
+                    return Ok(())
+                };
+
+                println!("Token ID: {token_id}");
+                println!("Ticker: {}", metadata.ticker);
+                println!("Decimals: {}", metadata.decimals);
+                println!("Description hash: {}", blake3::Hash::from(metadata.description_hash));
+
+                Ok(())
+            }
+
             // TODO: Mint directly into DAO treasury
             TokenSubcmd::Mint { token, amount, recipient } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
 
                 if let Err(e) = f64::from_str(&amount) {
                     eprintln!("Invalid amount: {e:?}");
                     exit(2);
                 }
 
-                let _rcpt = match PublicKey::from_str(&recipient) {
+                let _rcpt = match parse_public_key(network, &recipient) {
                     Ok(r) => r,
                     Err(e) => {
                         eprintln!("Invalid recipient: {e:?}");
@@ -1595,7 +3353,14 @@ async fn realmain(args: Args, ex: Arc<smol::Executor<'static>>) -> Result<()> {
             }
 
             TokenSubcmd::Freeze { token } => {
-                let drk = Drk::new(args.wallet_path, args.wallet_pass, args.endpoint, ex).await?;
+                let drk = Drk::new(
+                    args.wallet_path,
+                    args.wallet_pass,
+                    args.endpoint,
+                    args.endpoint_key,
+                    ex,
+                )
+                .await?;
                 let _token_id = match drk.get_token(token).await {
                     Ok(t) => t,
                     Err(e) => {
@@ -22,28 +22,46 @@ use lazy_static::lazy_static;
 use rand::rngs::OsRng;
 use rusqlite::types::Value;
 
-use darkfi::{tx::Transaction, zk::halo2::Field, Error, Result};
+use darkfi::{
+    tx::Transaction,
+    util::parse::encode_base10,
+    zk::{halo2::Field, Proof},
+    Error, Result,
+};
 use darkfi_money_contract::{
-    client::{MoneyNote, OwnCoin},
+    client::{
+        compute_remainder_blind,
+        fee_v1::{create_fee_proof, FeeCallInput, FeeCallOutput},
+        MoneyNote, OwnCoin, PaymentIdOpening,
+    },
     model::{
-        Coin, MoneyPoWRewardParamsV1, MoneyTokenFreezeParamsV1, MoneyTokenMintParamsV1,
-        MoneyTransferParamsV1, Nullifier, TokenId, DARK_TOKEN_ID,
+        Coin, Input, MoneyBridgeBurnParamsV1, MoneyBridgeMintParamsV1, MoneyFeeParamsV1,
+        MoneyNftBurnParamsV1, MoneyNftMintParamsV1, MoneyPoWRewardParamsV1,
+        MoneyTokenFreezeParamsV1, MoneyTokenMintParamsV1, MoneyTokenSupplyCapParamsV1,
+        MoneyTransferFreezeParamsV1, MoneyTransferParamsV1, Nullifier, Output, TokenId,
+        DARK_TOKEN_ID,
     },
-    MoneyFunction,
+    MoneyFunction, MONEY_CONTRACT_ZKAS_FEE_NS_V1,
 };
 use darkfi_sdk::{
     bridgetree,
     crypto::{
-        note::AeadEncryptedNote, BaseBlind, FuncId, Keypair, MerkleNode, MerkleTree, PublicKey,
-        ScalarBlind, SecretKey, MONEY_CONTRACT_ID,
+        diversify,
+        note::AeadEncryptedNote,
+        smt::{MemoryStorageFp, PoseidonFp, SmtMemoryFp, EMPTY_NODES_FP},
+        BaseBlind, DiversifiedAddress, FuncId, Keypair, MerkleNode, MerkleTree, PublicKey,
+        ScalarBlind, SecretKey, ViewingKey, MONEY_CONTRACT_ID,
     },
     pasta::pallas,
+    ContractCall,
 };
-use darkfi_serial::{deserialize, serialize};
+use darkfi_serial::{deserialize, serialize, Encodable};
 
 use crate::{
     convert_named_params,
     error::{WalletDbError, WalletDbResult},
+    events::WalletEvent,
+    hd::KeyPurpose,
     kaching, Drk,
 };
 
@@ -54,12 +72,28 @@ lazy_static! {
         format!("{}_money_info", MONEY_CONTRACT_ID.to_string());
     pub static ref MONEY_TREE_TABLE: String =
         format!("{}_money_tree", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_TREE_CHECKPOINTS_TABLE: String =
+        format!("{}_money_tree_checkpoints", MONEY_CONTRACT_ID.to_string());
     pub static ref MONEY_KEYS_TABLE: String =
         format!("{}_money_keys", MONEY_CONTRACT_ID.to_string());
     pub static ref MONEY_COINS_TABLE: String =
         format!("{}_money_coins", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_WATCH_KEYS_TABLE: String =
+        format!("{}_money_watch_keys", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_WATCH_COINS_TABLE: String =
+        format!("{}_money_watch_coins", MONEY_CONTRACT_ID.to_string());
     pub static ref MONEY_TOKENS_TABLE: String =
         format!("{}_money_tokens", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_FROZEN_TOKENS_TABLE: String =
+        format!("{}_money_frozen_tokens", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_TOKEN_POLICIES_TABLE: String =
+        format!("{}_money_token_policies", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_TOKEN_MINTS_TABLE: String =
+        format!("{}_money_token_mints", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_NFTS_TABLE: String =
+        format!("{}_money_nfts", MONEY_CONTRACT_ID.to_string());
+    pub static ref MONEY_GROUPS_TABLE: String =
+        format!("{}_money_groups", MONEY_CONTRACT_ID.to_string());
     pub static ref MONEY_ALIASES_TABLE: String =
         format!("{}_money_aliases", MONEY_CONTRACT_ID.to_string());
 }
@@ -70,11 +104,16 @@ pub const MONEY_INFO_COL_LAST_SCANNED_BLOCK: &str = "last_scanned_block";
 // MONEY_TREE_TABLE
 pub const MONEY_TREE_COL_TREE: &str = "tree";
 
+// MONEY_TREE_CHECKPOINTS_TABLE
+pub const MONEY_TREE_CHECKPOINTS_COL_HEIGHT: &str = "height";
+pub const MONEY_TREE_CHECKPOINTS_COL_TREE: &str = "tree";
+
 // MONEY_KEYS_TABLE
 pub const MONEY_KEYS_COL_KEY_ID: &str = "key_id";
 pub const MONEY_KEYS_COL_IS_DEFAULT: &str = "is_default";
 pub const MONEY_KEYS_COL_PUBLIC: &str = "public";
 pub const MONEY_KEYS_COL_SECRET: &str = "secret";
+pub const MONEY_KEYS_COL_ACCOUNT: &str = "account";
 
 // MONEY_COINS_TABLE
 pub const MONEY_COINS_COL_COIN: &str = "coin";
@@ -89,19 +128,82 @@ pub const MONEY_COINS_COL_TOKEN_BLIND: &str = "token_blind";
 pub const MONEY_COINS_COL_SECRET: &str = "secret";
 pub const MONEY_COINS_COL_NULLIFIER: &str = "nullifier";
 pub const MONEY_COINS_COL_LEAF_POSITION: &str = "leaf_position";
+pub const MONEY_COINS_COL_PAYMENT_ID: &str = "payment_id";
 pub const MONEY_COINS_COL_MEMO: &str = "memo";
 
+// MONEY_WATCH_KEYS_TABLE
+pub const MONEY_WATCH_KEYS_COL_VIEWING_KEY: &str = "viewing_key";
+
+// MONEY_WATCH_COINS_TABLE
+pub const MONEY_WATCH_COINS_COL_COIN: &str = "coin";
+pub const MONEY_WATCH_COINS_COL_VIEWING_KEY: &str = "viewing_key";
+pub const MONEY_WATCH_COINS_COL_NOTE: &str = "note";
+
 // MONEY_TOKENS_TABLE
 pub const MONEY_TOKENS_COL_MINT_AUTHORITY: &str = "mint_authority";
 pub const MONEY_TOKENS_COL_TOKEN_ID: &str = "token_id";
 pub const MONEY_TOKENS_COL_IS_FROZEN: &str = "is_frozen";
 
+// MONEY_FROZEN_TOKENS_TABLE
+pub const MONEY_FROZEN_TOKENS_COL_TOKEN_ID: &str = "token_id";
+
+// MONEY_TOKEN_POLICIES_TABLE
+pub const MONEY_TOKEN_POLICIES_COL_TOKEN_ID: &str = "token_id";
+pub const MONEY_TOKEN_POLICIES_COL_SUPPLY_CAP: &str = "supply_cap";
+pub const MONEY_TOKEN_POLICIES_COL_IS_REVOKED: &str = "is_revoked";
+
+// MONEY_TOKEN_MINTS_TABLE
+pub const MONEY_TOKEN_MINTS_COL_COIN: &str = "coin";
+pub const MONEY_TOKEN_MINTS_COL_TOKEN_ID: &str = "token_id";
+pub const MONEY_TOKEN_MINTS_COL_VALUE: &str = "value";
+pub const MONEY_TOKEN_MINTS_COL_VALUE_BLIND: &str = "value_blind";
+pub const MONEY_TOKEN_MINTS_COL_IS_EXECUTED: &str = "is_executed";
+
+// MONEY_NFTS_TABLE
+pub const MONEY_NFTS_COL_TOKEN_ID: &str = "token_id";
+pub const MONEY_NFTS_COL_METADATA: &str = "metadata";
+pub const MONEY_NFTS_COL_IS_BURNED: &str = "is_burned";
+
+// MONEY_GROUPS_TABLE
+pub const MONEY_GROUPS_COL_GROUP_PUBLIC: &str = "group_public";
+pub const MONEY_GROUPS_COL_KEY_PACKAGE: &str = "key_package";
+pub const MONEY_GROUPS_COL_PUBLIC_KEY_PACKAGE: &str = "public_key_package";
+
 // MONEY_ALIASES_TABLE
 pub const MONEY_ALIASES_COL_ALIAS: &str = "alias";
 pub const MONEY_ALIASES_COL_TOKEN_ID: &str = "token_id";
 
 pub const BALANCE_BASE10_DECIMALS: usize = 8;
 
+/// Number of scanned blocks between two Merkle tree checkpoints
+pub const MONEY_TREE_CHECKPOINT_INTERVAL: u64 = 1000;
+
+/// Money contract state changes found in a transaction
+pub struct MoneyTxData {
+    /// Nullifiers of the spent coins
+    pub nullifiers: Vec<Nullifier>,
+    /// Newly minted coins, in Merkle tree order
+    pub coins: Vec<Coin>,
+    /// Encrypted notes of the minted coins
+    pub notes: Vec<AeadEncryptedNote>,
+    /// Frozen tokens
+    pub freezes: Vec<TokenId>,
+    /// Tokens whose mint authority got revoked
+    pub revocations: Vec<TokenId>,
+    /// Tokens whose supply got capped, along with their cap
+    pub supply_caps: Vec<(TokenId, u64)>,
+    /// Coins minted through `Money::TokenMint`
+    pub token_mints: Vec<Coin>,
+    /// Tokens whose transfers got frozen
+    pub transfer_freezes: Vec<TokenId>,
+    /// Tokens whose transfers got unfrozen
+    pub transfer_unfreezes: Vec<TokenId>,
+    /// Minted NFTs along with their metadata commitments
+    pub nft_mints: Vec<(TokenId, pallas::Base)>,
+    /// Burned NFTs
+    pub nft_burns: Vec<TokenId>,
+}
+
 impl Drk {
     /// Initialize wallet with tables for the Money contract.
     pub async fn initialize_money(&self) -> WalletDbResult<()> {
@@ -140,45 +242,101 @@ impl Drk {
     }
 
     /// Generate a new keypair and place it into the wallet.
-    pub async fn money_keygen(&self) -> WalletDbResult<()> {
+    /// The keypair is derived from the wallet seed, so it can be recovered
+    /// from a seed backup by generating the same number of keypairs.
+    /// Returns the address of the new keypair, which carries its viewing
+    /// key so senders always encrypt their notes to it.
+    pub async fn money_keygen(&self) -> Result<DiversifiedAddress> {
         println!("Generating a new keypair");
 
-        // TODO: We might want to have hierarchical deterministic key derivation.
-        let keypair = Keypair::random(&mut OsRng);
-        let is_default = 0;
+        let keypair = Keypair::new(self.derive_next_secret(KeyPurpose::Money).await?);
+        // The first keypair of an account becomes its default one
+        let is_default = self.default_secret().await.is_err() as u64;
 
         let query = format!(
-            "INSERT INTO {} ({}, {}, {}) VALUES (?1, ?2, ?3);",
+            "INSERT INTO {} ({}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4);",
             *MONEY_KEYS_TABLE,
             MONEY_KEYS_COL_IS_DEFAULT,
             MONEY_KEYS_COL_PUBLIC,
-            MONEY_KEYS_COL_SECRET
+            MONEY_KEYS_COL_SECRET,
+            MONEY_KEYS_COL_ACCOUNT,
         );
-        self.wallet
+        if let Err(e) = self
+            .wallet
             .exec_sql(
                 &query,
                 rusqlite::params![
                     is_default,
                     serialize(&keypair.public),
-                    serialize(&keypair.secret)
+                    serialize(&keypair.secret),
+                    self.active_account().await?,
                 ],
             )
-            .await?;
+            .await
+        {
+            return Err(Error::RusqliteError(format!(
+                "[money_keygen] Keypair insertion failed: {e:?}"
+            )))
+        }
 
-        println!("New address:");
-        println!("{}", keypair.public);
+        Ok(DiversifiedAddress::from_secret(&keypair.secret))
+    }
 
-        Ok(())
+    /// Derive the next unused diversified address of the default secret key,
+    /// and store its keypair in the wallet so payments to it get scanned.
+    pub async fn money_diversify(&self) -> Result<DiversifiedAddress> {
+        let secret = self.default_secret().await?;
+        let existing_secrets = self.get_money_secrets().await?;
+
+        let mut index = 0;
+        let keypair = loop {
+            let keypair = diversify(&secret, index);
+            if !existing_secrets.contains(&keypair.secret) {
+                break keypair
+            }
+            index += 1;
+        };
+
+        let query = format!(
+            "INSERT INTO {} ({}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4);",
+            *MONEY_KEYS_TABLE,
+            MONEY_KEYS_COL_IS_DEFAULT,
+            MONEY_KEYS_COL_PUBLIC,
+            MONEY_KEYS_COL_SECRET,
+            MONEY_KEYS_COL_ACCOUNT,
+        );
+        if let Err(e) = self
+            .wallet
+            .exec_sql(
+                &query,
+                rusqlite::params![
+                    0,
+                    serialize(&keypair.public),
+                    serialize(&keypair.secret),
+                    self.active_account().await?,
+                ],
+            )
+            .await
+        {
+            return Err(Error::RusqliteError(format!(
+                "[money_diversify] Inserting diversified address failed: {e:?}"
+            )))
+        }
+
+        Ok(DiversifiedAddress::from_secret(&keypair.secret))
     }
 
-    /// Fetch default secret key from the wallet.
+    /// Fetch the default secret key of the active account from the wallet.
     pub async fn default_secret(&self) -> Result<SecretKey> {
         let row = match self
             .wallet
             .query_single(
                 &MONEY_KEYS_TABLE,
                 &[MONEY_KEYS_COL_SECRET],
-                convert_named_params! {(MONEY_KEYS_COL_IS_DEFAULT, 1)},
+                convert_named_params! {
+                    (MONEY_KEYS_COL_IS_DEFAULT, 1),
+                    (MONEY_KEYS_COL_ACCOUNT, self.active_account().await?)
+                },
             )
             .await
         {
@@ -198,14 +356,17 @@ impl Drk {
         Ok(secret_key)
     }
 
-    /// Fetch default pubkey from the wallet.
+    /// Fetch the default pubkey of the active account from the wallet.
     pub async fn default_address(&self) -> Result<PublicKey> {
         let row = match self
             .wallet
             .query_single(
                 &MONEY_KEYS_TABLE,
                 &[MONEY_KEYS_COL_PUBLIC],
-                convert_named_params! {(MONEY_KEYS_COL_IS_DEFAULT, 1)},
+                convert_named_params! {
+                    (MONEY_KEYS_COL_IS_DEFAULT, 1),
+                    (MONEY_KEYS_COL_ACCOUNT, self.active_account().await?)
+                },
             )
             .await
         {
@@ -225,12 +386,26 @@ impl Drk {
         Ok(public_key)
     }
 
-    /// Set provided index address as default in the wallet.
-    pub async fn set_default_address(&self, idx: usize) -> WalletDbResult<()> {
+    /// Set provided index address as default of the active account in the wallet.
+    pub async fn set_default_address(&self, idx: usize) -> Result<()> {
+        let account = self.active_account().await?;
+        if !self.addresses().await?.iter().any(|(key_id, ..)| *key_id == idx as u64) {
+            return Err(Error::Custom(format!(
+                "[set_default_address] Address {idx} is not part of the active account"
+            )))
+        }
+
         // First we update previous default record
         let is_default = 0;
-        let query = format!("UPDATE {} SET {} = ?1", *MONEY_KEYS_TABLE, MONEY_KEYS_COL_IS_DEFAULT,);
-        self.wallet.exec_sql(&query, rusqlite::params![is_default]).await?;
+        let query = format!(
+            "UPDATE {} SET {} = ?1 WHERE {} = ?2",
+            *MONEY_KEYS_TABLE, MONEY_KEYS_COL_IS_DEFAULT, MONEY_KEYS_COL_ACCOUNT,
+        );
+        if let Err(e) = self.wallet.exec_sql(&query, rusqlite::params![is_default, account]).await {
+            return Err(Error::RusqliteError(format!(
+                "[set_default_address] Clearing default address failed: {e:?}"
+            )))
+        }
 
         // and then we set the new one
         let is_default = 1;
@@ -238,12 +413,31 @@ impl Drk {
             "UPDATE {} SET {} = ?1 WHERE {} = ?2",
             *MONEY_KEYS_TABLE, MONEY_KEYS_COL_IS_DEFAULT, MONEY_KEYS_COL_KEY_ID,
         );
-        self.wallet.exec_sql(&query, rusqlite::params![is_default, idx]).await
+        if let Err(e) = self.wallet.exec_sql(&query, rusqlite::params![is_default, idx]).await {
+            return Err(Error::RusqliteError(format!(
+                "[set_default_address] Setting default address failed: {e:?}"
+            )))
+        }
+
+        Ok(())
     }
 
-    /// Fetch all pukeys from the wallet.
+    /// Fetch all pukeys of the active account from the wallet.
     pub async fn addresses(&self) -> Result<Vec<(u64, PublicKey, SecretKey, u64)>> {
-        let rows = match self.wallet.query_multiple(&MONEY_KEYS_TABLE, &[], &[]).await {
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &MONEY_KEYS_TABLE,
+                &[
+                    MONEY_KEYS_COL_KEY_ID,
+                    MONEY_KEYS_COL_IS_DEFAULT,
+                    MONEY_KEYS_COL_PUBLIC,
+                    MONEY_KEYS_COL_SECRET,
+                ],
+                convert_named_params! {(MONEY_KEYS_COL_ACCOUNT, self.active_account().await?)},
+            )
+            .await
+        {
             Ok(r) => r,
             Err(e) => {
                 return Err(Error::RusqliteError(format!(
@@ -315,11 +509,17 @@ impl Drk {
         Ok(secrets)
     }
 
-    /// Import given secret keys into the wallet.
+    /// Fetch the secret keys of the active account from the wallet.
+    pub async fn get_account_secrets(&self) -> Result<Vec<SecretKey>> {
+        Ok(self.addresses().await?.into_iter().map(|(_, _, secret, _)| secret).collect())
+    }
+
+    /// Import given secret keys into the active account of the wallet.
     /// If the key already exists, it will be skipped.
     /// Returns the respective PublicKey objects for the imported keys.
     pub async fn import_money_secrets(&self, secrets: Vec<SecretKey>) -> Result<Vec<PublicKey>> {
         let existing_secrets = self.get_money_secrets().await?;
+        let account = self.active_account().await?;
 
         let mut ret = Vec::with_capacity(secrets.len());
 
@@ -336,14 +536,17 @@ impl Drk {
             let secret = serialize(&secret);
 
             let query = format!(
-                "INSERT INTO {} ({}, {}, {}) VALUES (?1, ?2, ?3);",
+                "INSERT INTO {} ({}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4);",
                 *MONEY_KEYS_TABLE,
                 MONEY_KEYS_COL_IS_DEFAULT,
                 MONEY_KEYS_COL_PUBLIC,
-                MONEY_KEYS_COL_SECRET
+                MONEY_KEYS_COL_SECRET,
+                MONEY_KEYS_COL_ACCOUNT,
             );
-            if let Err(e) =
-                self.wallet.exec_sql(&query, rusqlite::params![is_default, public, secret]).await
+            if let Err(e) = self
+                .wallet
+                .exec_sql(&query, rusqlite::params![is_default, public, secret, account])
+                .await
             {
                 return Err(Error::RusqliteError(format!(
                     "[import_money_secrets] Inserting new address failed: {e:?}"
@@ -354,9 +557,10 @@ impl Drk {
         Ok(ret)
     }
 
-    /// Fetch known unspent balances from the wallet and return them as a hashmap.
+    /// Fetch known unspent balances of the active account from the wallet and
+    /// return them as a hashmap.
     pub async fn money_balance(&self) -> Result<HashMap<String, u64>> {
-        let mut coins = self.get_coins(false).await?;
+        let mut coins = self.get_account_coins(false).await?;
         coins.retain(|x| x.0.note.spend_hook == FuncId::none());
 
         // Fill this map with balances
@@ -467,9 +671,15 @@ impl Drk {
             };
             let leaf_position: bridgetree::Position = deserialize(leaf_position_bytes)?;
 
-            let Value::Blob(ref memo) = row[12] else {
-                return Err(Error::ParseFailed("[get_coins] Memo parsing failed"))
+            let Value::Blob(ref payment_id_bytes) = row[12] else {
+                return Err(Error::ParseFailed("[get_coins] Payment ID bytes parsing failed"))
+            };
+            let payment_id: Option<PaymentIdOpening> = deserialize(payment_id_bytes)?;
+
+            let Value::Blob(ref memo_bytes) = row[13] else {
+                return Err(Error::ParseFailed("[get_coins] Memo bytes parsing failed"))
             };
+            let memo: Vec<u8> = deserialize(memo_bytes)?;
 
             let note = MoneyNote {
                 value,
@@ -479,7 +689,8 @@ impl Drk {
                 coin_blind,
                 value_blind,
                 token_blind,
-                memo: memo.clone(),
+                payment_id,
+                memo,
             };
             let owncoin = OwnCoin { coin, note, secret, leaf_position };
 
@@ -489,6 +700,141 @@ impl Drk {
         Ok(owncoins)
     }
 
+    /// Fetch the coins of the active account, meaning the ones received by
+    /// its keys. Optionally also fetch spent ones.
+    pub async fn get_account_coins(&self, fetch_spent: bool) -> Result<Vec<(OwnCoin, bool)>> {
+        let secrets = self.get_account_secrets().await?;
+        let mut coins = self.get_coins(fetch_spent).await?;
+        coins.retain(|(coin, _)| secrets.contains(&coin.secret));
+        Ok(coins)
+    }
+
+    /// Fetch all viewing keys of watch-only accounts from the wallet.
+    pub async fn get_watch_keys(&self) -> Result<Vec<ViewingKey>> {
+        let rows = match self.wallet.query_multiple(&MONEY_WATCH_KEYS_TABLE, &[], &[]).await {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_watch_keys] Viewing keys retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut keys = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Blob(ref key_bytes) = row[0] else {
+                return Err(Error::ParseFailed("[get_watch_keys] Viewing key bytes parsing failed"))
+            };
+            keys.push(deserialize(key_bytes)?);
+        }
+
+        Ok(keys)
+    }
+
+    /// Import given viewing keys into the wallet as watch-only accounts.
+    /// If the key already exists, it will be skipped.
+    /// Returns the respective viewing public keys of the imported keys.
+    pub async fn import_watch_keys(&self, keys: Vec<ViewingKey>) -> Result<Vec<PublicKey>> {
+        let existing_keys = self.get_watch_keys().await?;
+
+        let mut ret = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            if existing_keys.contains(&key) {
+                println!("Existing viewing key found: {}", key.public_key());
+                continue
+            }
+
+            ret.push(key.public_key());
+
+            let query = format!(
+                "INSERT INTO {} ({}) VALUES (?1);",
+                *MONEY_WATCH_KEYS_TABLE, MONEY_WATCH_KEYS_COL_VIEWING_KEY,
+            );
+            if let Err(e) = self.wallet.exec_sql(&query, rusqlite::params![serialize(&key)]).await {
+                return Err(Error::RusqliteError(format!(
+                    "[import_watch_keys] Inserting viewing key failed: {e:?}"
+                )))
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Check if the wallet only holds watch-only accounts, i.e. it has
+    /// viewing keys but no spending keys.
+    pub async fn is_watch_only(&self) -> Result<bool> {
+        if !self.get_money_secrets().await?.is_empty() {
+            return Ok(false)
+        }
+
+        Ok(!self.get_watch_keys().await?.is_empty())
+    }
+
+    /// Return an error if the wallet is watch-only, so spending
+    /// operations fail before doing any work.
+    pub async fn check_spendable(&self) -> Result<()> {
+        if self.is_watch_only().await? {
+            return Err(Error::Custom(
+                "Wallet is watch-only, spending operations are disabled".to_string(),
+            ))
+        }
+
+        Ok(())
+    }
+
+    /// Fetch all coins received by watch-only accounts from the wallet,
+    /// along with the viewing key that found them.
+    pub async fn get_watch_coins(&self) -> Result<Vec<(Coin, ViewingKey, MoneyNote)>> {
+        let rows = match self.wallet.query_multiple(&MONEY_WATCH_COINS_TABLE, &[], &[]).await {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_watch_coins] Coins retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut coins = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Blob(ref coin_bytes) = row[0] else {
+                return Err(Error::ParseFailed("[get_watch_coins] Coin bytes parsing failed"))
+            };
+            let coin: Coin = deserialize(coin_bytes)?;
+
+            let Value::Blob(ref key_bytes) = row[1] else {
+                return Err(Error::ParseFailed("[get_watch_coins] Viewing key bytes parsing failed"))
+            };
+            let viewing_key: ViewingKey = deserialize(key_bytes)?;
+
+            let Value::Blob(ref note_bytes) = row[2] else {
+                return Err(Error::ParseFailed("[get_watch_coins] Note bytes parsing failed"))
+            };
+            let note: MoneyNote = deserialize(note_bytes)?;
+
+            coins.push((coin, viewing_key, note));
+        }
+
+        Ok(coins)
+    }
+
+    /// Fetch the total amounts received by watch-only accounts and return
+    /// them as a hashmap. Nullifiers can't be derived without the spending
+    /// key, so spends aren't visible and these are not balances.
+    pub async fn watch_received(&self) -> Result<HashMap<String, u64>> {
+        let mut received: HashMap<String, u64> = HashMap::new();
+
+        for (_, _, note) in self.get_watch_coins().await? {
+            if note.spend_hook != FuncId::none() {
+                continue
+            }
+
+            *received.entry(note.token_id.to_string()).or_insert(0) += note.value;
+        }
+
+        Ok(received)
+    }
+
     /// Create an alias record for provided Token ID.
     pub async fn add_alias(&self, alias: String, token_id: TokenId) -> WalletDbResult<()> {
         println!("Generating alias {alias} for Token: {token_id}");
@@ -610,6 +956,126 @@ impl Drk {
         Ok(tree)
     }
 
+    /// Store a snapshot of the Money Merkle tree after the block at the
+    /// given height was scanned.
+    pub async fn put_money_tree_checkpoint(
+        &self,
+        height: u64,
+        tree: &MerkleTree,
+    ) -> WalletDbResult<()> {
+        let query = format!(
+            "INSERT OR REPLACE INTO {} ({}, {}) VALUES (?1, ?2);",
+            *MONEY_TREE_CHECKPOINTS_TABLE,
+            MONEY_TREE_CHECKPOINTS_COL_HEIGHT,
+            MONEY_TREE_CHECKPOINTS_COL_TREE,
+        );
+        self.wallet.exec_sql(&query, rusqlite::params![height, serialize(tree)]).await
+    }
+
+    /// Fetch the block heights of all Money Merkle tree checkpoints in the wallet.
+    pub async fn get_money_tree_checkpoints(&self) -> WalletDbResult<Vec<u64>> {
+        let rows = self
+            .wallet
+            .query_multiple(
+                &MONEY_TREE_CHECKPOINTS_TABLE,
+                &[MONEY_TREE_CHECKPOINTS_COL_HEIGHT],
+                &[],
+            )
+            .await?;
+
+        let mut heights = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Integer(height) = row[0] else {
+                return Err(WalletDbError::ParseColumnValueError)
+            };
+            let Ok(height) = u64::try_from(height) else {
+                return Err(WalletDbError::ParseColumnValueError)
+            };
+            heights.push(height);
+        }
+        heights.sort_unstable();
+
+        Ok(heights)
+    }
+
+    /// Fetch the latest Money Merkle tree checkpoint taken before the given
+    /// block height, along with its own height.
+    pub async fn get_money_tree_checkpoint_before(
+        &self,
+        height: u64,
+    ) -> Result<Option<(u64, MerkleTree)>> {
+        let heights = match self.get_money_tree_checkpoints().await {
+            Ok(h) => h,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_tree_checkpoint_before] Checkpoints retrieval failed: {e:?}"
+                )))
+            }
+        };
+        let Some(checkpoint) = heights.into_iter().filter(|h| *h < height).last() else {
+            return Ok(None)
+        };
+
+        let row = match self
+            .wallet
+            .query_single(
+                &MONEY_TREE_CHECKPOINTS_TABLE,
+                &[MONEY_TREE_CHECKPOINTS_COL_TREE],
+                convert_named_params! {(MONEY_TREE_CHECKPOINTS_COL_HEIGHT, checkpoint)},
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_tree_checkpoint_before] Checkpoint retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let Value::Blob(ref tree_bytes) = row[0] else {
+            return Err(Error::ParseFailed(
+                "[get_money_tree_checkpoint_before] Tree bytes parsing failed",
+            ))
+        };
+        let tree = deserialize(tree_bytes)?;
+
+        Ok(Some((checkpoint, tree)))
+    }
+
+    /// Remove all Money Merkle tree checkpoints taken after the given block height.
+    pub async fn rewind_money_tree_checkpoints(&self, height: u64) -> WalletDbResult<()> {
+        let query = format!(
+            "DELETE FROM {} WHERE {} > ?1;",
+            *MONEY_TREE_CHECKPOINTS_TABLE, MONEY_TREE_CHECKPOINTS_COL_HEIGHT,
+        );
+        self.wallet.exec_sql(&query, rusqlite::params![height]).await
+    }
+
+    /// Remove the coins that are not part of the given Money Merkle tree,
+    /// as they were found in blocks scanned after it.
+    pub async fn rewind_money_coins(&self, tree: &MerkleTree) -> Result<()> {
+        let last_position = tree.current_position();
+
+        let query =
+            format!("DELETE FROM {} WHERE {} = ?1;", *MONEY_COINS_TABLE, MONEY_COINS_COL_COIN);
+        for (coin, _) in self.get_coins(true).await? {
+            if Some(coin.leaf_position) <= last_position {
+                continue
+            }
+
+            if let Err(e) =
+                self.wallet.exec_sql(&query, rusqlite::params![serialize(&coin.coin)]).await
+            {
+                return Err(Error::RusqliteError(format!(
+                    "[rewind_money_coins] Removing coin failed: {e:?}"
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get the last scanned block height from the wallet.
     pub async fn last_scanned_block(&self) -> WalletDbResult<u64> {
         let ret = self
@@ -626,16 +1092,36 @@ impl Drk {
         Ok(height)
     }
 
-    /// Append data related to Money contract transactions into the wallet database.
-    pub async fn apply_tx_money_data(&self, tx: &Transaction, _confirm: bool) -> Result<()> {
+    /// Parse the Money contract state changes of a transaction: the published
+    /// nullifiers, the new coins along with their encrypted notes, the
+    /// frozen tokens, mints and transfers alike, and the token supply caps.
+    pub fn parse_tx_money_data(tx: &Transaction) -> Result<MoneyTxData> {
         let cid = *MONEY_CONTRACT_ID;
 
         let mut nullifiers: Vec<Nullifier> = vec![];
         let mut coins: Vec<Coin> = vec![];
         let mut notes: Vec<AeadEncryptedNote> = vec![];
         let mut freezes: Vec<TokenId> = vec![];
+        let mut revocations: Vec<TokenId> = vec![];
+        let mut supply_caps: Vec<(TokenId, u64)> = vec![];
+        let mut token_mints: Vec<Coin> = vec![];
+        let mut transfer_freezes: Vec<TokenId> = vec![];
+        let mut transfer_unfreezes: Vec<TokenId> = vec![];
+        let mut nft_mints: Vec<(TokenId, pallas::Base)> = vec![];
+        let mut nft_burns: Vec<TokenId> = vec![];
 
         for (i, call) in tx.calls.iter().enumerate() {
+            if call.data.contract_id == cid && call.data.data[0] == MoneyFunction::FeeV1 as u8 {
+                println!("Found Money::FeeV1 in call {i}");
+                let params: MoneyFeeParamsV1 = deserialize(&call.data.data[9..])?;
+
+                nullifiers.push(params.input.nullifier);
+                coins.push(params.output.coin);
+                notes.push(params.output.note);
+
+                continue
+            }
+
             if call.data.contract_id == cid && call.data.data[0] == MoneyFunction::PoWRewardV1 as u8
             {
                 println!("Found Money::PoWRewardV1 in call {i}");
@@ -685,6 +1171,7 @@ impl Drk {
                 println!("Found Money::MintV1 in call {i}");
                 let params: MoneyTokenMintParamsV1 = deserialize(&call.data.data[1..])?;
                 coins.push(params.coin);
+                token_mints.push(params.coin);
                 //notes.push(output.note);
                 continue
             }
@@ -696,47 +1183,287 @@ impl Drk {
                 let params: MoneyTokenFreezeParamsV1 = deserialize(&call.data.data[1..])?;
                 let token_id = TokenId::derive_public(params.mint_public);
                 freezes.push(token_id);
+                revocations.push(params.token_id);
+                continue
             }
-        }
 
-        let secrets = self.get_money_secrets().await?;
-        let dao_secrets = self.get_dao_secrets().await?;
-        let mut tree = self.get_money_tree().await?;
+            if call.data.contract_id == cid &&
+                call.data.data[0] == MoneyFunction::TokenSupplyCapV1 as u8
+            {
+                println!("Found Money::TokenSupplyCapV1 in call {i}");
+                let params: MoneyTokenSupplyCapParamsV1 = deserialize(&call.data.data[1..])?;
+                supply_caps.push((params.token_id, params.supply_cap));
+                continue
+            }
 
-        let mut owncoins = vec![];
+            if call.data.contract_id == cid &&
+                call.data.data[0] == MoneyFunction::TransferFreezeV1 as u8
+            {
+                println!("Found Money::TransferFreezeV1 in call {i}");
+                let params: MoneyTransferFreezeParamsV1 = deserialize(&call.data.data[1..])?;
+                transfer_freezes.push(params.token_id);
+                continue
+            }
 
-        for (coin, note) in coins.iter().zip(notes.iter()) {
-            // Append the new coin to the Merkle tree. Every coin has to be added.
-            tree.append(MerkleNode::from(coin.inner()));
+            if call.data.contract_id == cid &&
+                call.data.data[0] == MoneyFunction::TransferUnfreezeV1 as u8
+            {
+                println!("Found Money::TransferUnfreezeV1 in call {i}");
+                let params: MoneyTransferFreezeParamsV1 = deserialize(&call.data.data[1..])?;
+                transfer_unfreezes.push(params.token_id);
+                continue
+            }
 
-            // Attempt to decrypt the note
-            for secret in secrets.iter().chain(dao_secrets.iter()) {
-                if let Ok(note) = note.decrypt::<MoneyNote>(secret) {
-                    println!("Successfully decrypted a Money Note");
-                    println!("Witnessing coin in Merkle tree");
-                    let leaf_position = tree.mark().unwrap();
+            if call.data.contract_id == cid && call.data.data[0] == MoneyFunction::NftMintV1 as u8 {
+                println!("Found Money::NftMintV1 in call {i}");
+                let params: MoneyNftMintParamsV1 = deserialize(&call.data.data[1..])?;
+                coins.push(params.coin);
+                notes.push(params.note);
+                nft_mints.push((params.token_id, params.metadata));
+                continue
+            }
 
-                    let owncoin =
-                        OwnCoin { coin: *coin, note: note.clone(), secret: *secret, leaf_position };
+            if call.data.contract_id == cid && call.data.data[0] == MoneyFunction::NftBurnV1 as u8 {
+                println!("Found Money::NftBurnV1 in call {i}");
+                let params: MoneyNftBurnParamsV1 = deserialize(&call.data.data[1..])?;
+                nullifiers.push(params.input.nullifier);
+                nft_burns.push(params.token_id);
+                continue
+            }
 
-                    owncoins.push(owncoin);
-                }
+            if call.data.contract_id == cid &&
+                call.data.data[0] == MoneyFunction::BridgeBurnV1 as u8
+            {
+                println!("Found Money::BridgeBurnV1 in call {i}");
+                let params: MoneyBridgeBurnParamsV1 = deserialize(&call.data.data[1..])?;
+                nullifiers.push(params.input.nullifier);
+                continue
             }
-        }
 
-        if let Err(e) = self.put_money_tree(&tree).await {
-            return Err(Error::RusqliteError(format!(
-                "[apply_tx_money_data] Put Money tree failed: {e:?}"
-            )))
-        }
-        if !nullifiers.is_empty() {
-            self.mark_spent_coins(&nullifiers).await?;
+            if call.data.contract_id == cid &&
+                call.data.data[0] == MoneyFunction::BridgeMintV1 as u8
+            {
+                println!("Found Money::BridgeMintV1 in call {i}");
+                let params: MoneyBridgeMintParamsV1 = deserialize(&call.data.data[1..])?;
+                coins.push(params.output.coin);
+                notes.push(params.output.note);
+            }
         }
 
-        // This is the SQL query we'll be executing to insert new coins
+        Ok(MoneyTxData {
+            nullifiers,
+            coins,
+            notes,
+            freezes,
+            revocations,
+            supply_caps,
+            token_mints,
+            transfer_freezes,
+            transfer_unfreezes,
+            nft_mints,
+            nft_burns,
+        })
+    }
+
+    /// Append data related to Money contract transactions into the wallet database.
+    /// Returns the coins minted and the nullifiers revealed by the transaction.
+    pub async fn apply_tx_money_data(
+        &self,
+        tx: &Transaction,
+        _confirm: bool,
+    ) -> Result<(Vec<Coin>, Vec<Nullifier>)> {
+        let MoneyTxData {
+            nullifiers,
+            coins,
+            notes,
+            freezes,
+            revocations,
+            supply_caps,
+            token_mints,
+            transfer_freezes,
+            transfer_unfreezes,
+            nft_mints,
+            nft_burns,
+        } = Self::parse_tx_money_data(tx)?;
+
+        let outputs: Vec<_> = coins.iter().copied().zip(notes).map(|(c, n)| (c, Some(n))).collect();
+        self.apply_money_outputs(&outputs, &nullifiers).await?;
+
+        for token_id in freezes {
+            let query = format!(
+                "UPDATE {} SET {} = 1 WHERE {} = ?1;",
+                *MONEY_TOKENS_TABLE, MONEY_TOKENS_COL_IS_FROZEN, MONEY_TOKENS_COL_TOKEN_ID,
+            );
+
+            if let Err(e) =
+                self.wallet.exec_sql(&query, rusqlite::params![serialize(&token_id)]).await
+            {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Inserting Money coin failed: {e:?}"
+                )))
+            }
+        }
+
+        // Supply policies are public, so we track them for all tokens in
+        // order to tell which ones have a fixed supply.
+        for token_id in revocations {
+            if let Err(e) = self.put_money_token_revocation(&token_id).await {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Revoking token mint authority failed: {e:?}"
+                )))
+            }
+        }
+
+        for (token_id, supply_cap) in supply_caps {
+            if let Err(e) = self.put_money_token_supply_cap(&token_id, supply_cap).await {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Capping token supply failed: {e:?}"
+                )))
+            }
+        }
+
+        // Mark the mints we built as executed, so they count towards
+        // the token's supply.
+        for coin in token_mints {
+            if let Err(e) = self.confirm_money_token_mint(&coin).await {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Confirming token mint failed: {e:?}"
+                )))
+            }
+        }
+
+        for token_id in transfer_freezes {
+            let query = format!(
+                "INSERT OR IGNORE INTO {} ({}) VALUES (?1);",
+                *MONEY_FROZEN_TOKENS_TABLE, MONEY_FROZEN_TOKENS_COL_TOKEN_ID,
+            );
+
+            if let Err(e) =
+                self.wallet.exec_sql(&query, rusqlite::params![serialize(&token_id)]).await
+            {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Freezing token transfers failed: {e:?}"
+                )))
+            }
+        }
+
+        for token_id in transfer_unfreezes {
+            let query = format!(
+                "DELETE FROM {} WHERE {} = ?1;",
+                *MONEY_FROZEN_TOKENS_TABLE, MONEY_FROZEN_TOKENS_COL_TOKEN_ID,
+            );
+
+            if let Err(e) =
+                self.wallet.exec_sql(&query, rusqlite::params![serialize(&token_id)]).await
+            {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Unfreezing token transfers failed: {e:?}"
+                )))
+            }
+        }
+
+        // NFT mints are public, so we track all of them in order to
+        // recognize the NFTs we receive through regular transfers.
+        for (token_id, metadata) in nft_mints {
+            if let Err(e) = self.put_money_nft(&token_id, &metadata).await {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Inserting NFT failed: {e:?}"
+                )))
+            }
+        }
+
+        for token_id in nft_burns {
+            let query = format!(
+                "UPDATE {} SET {} = 1 WHERE {} = ?1;",
+                *MONEY_NFTS_TABLE, MONEY_NFTS_COL_IS_BURNED, MONEY_NFTS_COL_TOKEN_ID,
+            );
+
+            if let Err(e) =
+                self.wallet.exec_sql(&query, rusqlite::params![serialize(&token_id)]).await
+            {
+                return Err(Error::RusqliteError(format!(
+                    "[apply_tx_money_data] Burning NFT failed: {e:?}"
+                )))
+            }
+        }
+
+        Ok((coins, nullifiers))
+    }
+
+    /// Append the given coins to the Money Merkle tree, store the ones whose
+    /// notes we can decrypt, and mark the coins with the given nullifiers as
+    /// spent. Coins without a note are only appended to the tree.
+    pub async fn apply_money_outputs(
+        &self,
+        outputs: &[(Coin, Option<AeadEncryptedNote>)],
+        nullifiers: &[Nullifier],
+    ) -> Result<()> {
+        let secrets = self.get_money_secrets().await?;
+        let dao_secrets = self.get_dao_secrets().await?;
+        let group_secrets = self.get_group_secrets().await?;
+        let watch_keys = self.get_watch_keys().await?;
+        let mut tree = self.get_money_tree().await?;
+
+        // Notes can be decrypted by the secret key they were sent to, or its
+        // viewing key. Watch-only accounts only hold the latter.
+        let own_secrets: Vec<SecretKey> =
+            secrets.iter().chain(dao_secrets.iter()).chain(group_secrets.iter()).copied().collect();
+        let mut trial_keys = Vec::with_capacity(own_secrets.len() * 2 + watch_keys.len());
+        for secret in &own_secrets {
+            trial_keys.push(*secret);
+            trial_keys.push(ViewingKey::derive(secret).inner());
+        }
+        trial_keys.extend(watch_keys.iter().map(|viewing_key| viewing_key.inner()));
+
+        // Trial-decrypt all the notes at once, spread over the available cores
+        let notes: Vec<AeadEncryptedNote> = outputs.iter().filter_map(|(_, n)| n.clone()).collect();
+        let mut decrypted =
+            AeadEncryptedNote::trial_decrypt::<MoneyNote>(&notes, &trial_keys).into_iter();
+
+        let mut owncoins = vec![];
+        let mut watched_coins = vec![];
+
+        for (coin, note) in outputs {
+            // Append the new coin to the Merkle tree. Every coin has to be added.
+            tree.append(MerkleNode::from(coin.inner()));
+
+            if note.is_none() {
+                continue
+            }
+            let Some((key_idx, note)) = decrypted.next().flatten() else { continue };
+
+            if key_idx >= own_secrets.len() * 2 {
+                // Watch-only accounts can only see notes sent to their viewing key
+                println!("Successfully decrypted a watch-only Money Note");
+                let viewing_key = watch_keys[key_idx - own_secrets.len() * 2];
+                watched_coins.push((*coin, viewing_key, note));
+                continue
+            }
+
+            println!("Successfully decrypted a Money Note");
+            if let Some(payment_id) = note.verified_payment_id() {
+                println!("Note carries payment ID: {payment_id}");
+            }
+            println!("Witnessing coin in Merkle tree");
+            let leaf_position = tree.mark().unwrap();
+
+            let secret = own_secrets[key_idx / 2];
+            owncoins.push(OwnCoin { coin: *coin, note, secret, leaf_position });
+        }
+
+        if let Err(e) = self.put_money_tree(&tree).await {
+            return Err(Error::RusqliteError(format!(
+                "[apply_money_outputs] Put Money tree failed: {e:?}"
+            )))
+        }
+        if !nullifiers.is_empty() {
+            self.mark_spent_coins(nullifiers).await?;
+        }
+
+        // This is the SQL query we'll be executing to insert new coins
         // into the wallet
         let query = format!(
-            "INSERT INTO {} ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13);",
+            "INSERT INTO {} ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14);",
             *MONEY_COINS_TABLE,
             MONEY_COINS_COL_COIN,
             MONEY_COINS_COL_IS_SPENT,
@@ -750,6 +1477,7 @@ impl Drk {
             MONEY_COINS_COL_SECRET,
             MONEY_COINS_COL_NULLIFIER,
             MONEY_COINS_COL_LEAF_POSITION,
+            MONEY_COINS_COL_PAYMENT_ID,
             MONEY_COINS_COL_MEMO,
         );
 
@@ -769,27 +1497,33 @@ impl Drk {
                 serialize(&owncoin.secret),
                 serialize(&owncoin.nullifier()),
                 serialize(&owncoin.leaf_position),
+                serialize(&owncoin.note.payment_id),
                 serialize(&owncoin.note.memo),
             ];
 
             if let Err(e) = self.wallet.exec_sql(&query, params).await {
                 return Err(Error::RusqliteError(format!(
-                    "[apply_tx_money_data] Inserting Money coin failed: {e:?}"
+                    "[apply_money_outputs] Inserting Money coin failed: {e:?}"
                 )))
             }
         }
 
-        for token_id in freezes {
-            let query = format!(
-                "UPDATE {} SET {} = 1 WHERE {} = ?1;",
-                *MONEY_TOKENS_TABLE, MONEY_TOKENS_COL_IS_FROZEN, MONEY_TOKENS_COL_TOKEN_ID,
-            );
+        let query = format!(
+            "INSERT OR IGNORE INTO {} ({}, {}, {}) VALUES (?1, ?2, ?3);",
+            *MONEY_WATCH_COINS_TABLE,
+            MONEY_WATCH_COINS_COL_COIN,
+            MONEY_WATCH_COINS_COL_VIEWING_KEY,
+            MONEY_WATCH_COINS_COL_NOTE,
+        );
 
-            if let Err(e) =
-                self.wallet.exec_sql(&query, rusqlite::params![serialize(&token_id)]).await
-            {
+        for (coin, viewing_key, note) in &watched_coins {
+            println!("Watch-only coin: {coin:?}");
+            let params =
+                rusqlite::params![serialize(coin), serialize(viewing_key), serialize(note)];
+
+            if let Err(e) = self.wallet.exec_sql(&query, params).await {
                 return Err(Error::RusqliteError(format!(
-                    "[apply_tx_money_data] Inserting Money coin failed: {e:?}"
+                    "[apply_money_outputs] Inserting watch-only coin failed: {e:?}"
                 )))
             }
         }
@@ -798,9 +1532,144 @@ impl Drk {
             kaching().await;
         }
 
+        for owncoin in owncoins {
+            self.events.notify(WalletEvent::CoinReceived(owncoin)).await;
+        }
+
         Ok(())
     }
 
+    /// Create a `Money::Fee` call paying the fee the given signed transaction
+    /// requires, as computed by darkfid's gas accounting. The fee is paid with
+    /// a native token coin of the active account that is not part of
+    /// `spent_coins`, and its change returns to our default address. Since
+    /// appending the call changes the transaction, all of its calls have to
+    /// be signed again afterwards.
+    ///
+    /// Returns the `Fee` call, along with its proofs, signature secrets and
+    /// the coin it spends.
+    pub async fn append_fee_call(
+        &self,
+        tx: &Transaction,
+        spent_coins: &[OwnCoin],
+    ) -> Result<(ContractCall, Vec<Proof>, Vec<SecretKey>, OwnCoin)> {
+        let fee = self.get_tx_fee(tx, true).await?;
+        println!("Transaction requires a fee of {}", encode_base10(fee, BALANCE_BASE10_DECIMALS));
+
+        // Pick the smallest native token coin able to cover the fee
+        let mut coins: Vec<OwnCoin> =
+            self.get_account_coins(false).await?.into_iter().map(|(coin, _)| coin).collect();
+        coins.retain(|x| x.note.token_id == *DARK_TOKEN_ID && x.note.value >= fee);
+        coins.retain(|x| x.note.spend_hook == FuncId::none());
+        coins.retain(|x| !spent_coins.iter().any(|s| s.coin == x.coin));
+        let Some(coin) = coins.into_iter().min_by_key(|x| x.note.value) else {
+            return Err(Error::Custom(format!(
+                "Did not find a native token coin able to pay the fee of {}",
+                encode_base10(fee, BALANCE_BASE10_DECIMALS)
+            )))
+        };
+
+        let tree = self.get_money_tree().await?;
+        let Some(merkle_path) = tree.witness(coin.leaf_position, 0) else {
+            return Err(Error::Custom(format!(
+                "Coin {} is not witnessed in the Money Merkle tree",
+                coin.coin
+            )))
+        };
+
+        let input = FeeCallInput {
+            coin: coin.clone(),
+            merkle_path,
+            user_data_blind: BaseBlind::random(&mut OsRng),
+        };
+
+        let output = FeeCallOutput {
+            public_key: PublicKey::from_secret(self.default_secret().await?),
+            value: coin.note.value - fee,
+            token_id: coin.note.token_id,
+            blind: BaseBlind::random(&mut OsRng),
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+        };
+
+        // Create blinding factors
+        let token_blind = BaseBlind::random(&mut OsRng);
+        let input_value_blind = ScalarBlind::random(&mut OsRng);
+        let fee_value_blind = ScalarBlind::random(&mut OsRng);
+        let output_value_blind = compute_remainder_blind(&[input_value_blind], &[fee_value_blind]);
+
+        // Create an ephemeral signing key
+        let signature_secret = SecretKey::random(&mut OsRng);
+
+        let zkas_bins = self.lookup_zkas(&MONEY_CONTRACT_ID).await?;
+        let Some(fee_zkbin) = zkas_bins.iter().find(|x| x.0 == MONEY_CONTRACT_ZKAS_FEE_NS_V1)
+        else {
+            return Err(Error::Custom("Fee circuit not found".to_string()))
+        };
+
+        println!("Creating Fee circuit proving key");
+        let (fee_zkbin, fee_pk) = self.key_cache.proving_key(&fee_zkbin.1)?;
+
+        println!("Creating Fee call ZK proof");
+        let (proof, public_inputs) = create_fee_proof(
+            &fee_zkbin,
+            &fee_pk,
+            &input,
+            input_value_blind,
+            &output,
+            output_value_blind,
+            output.spend_hook,
+            output.user_data,
+            output.blind,
+            token_blind,
+            signature_secret,
+        )?;
+
+        // Encrypted note for the output
+        let note = MoneyNote {
+            coin_blind: output.blind,
+            value: output.value,
+            token_id: output.token_id,
+            spend_hook: output.spend_hook,
+            user_data: output.user_data,
+            value_blind: output_value_blind,
+            token_blind,
+            payment_id: None,
+            memo: vec![],
+        };
+
+        let encrypted_note = AeadEncryptedNote::encrypt(&note, &output.public_key, &mut OsRng)?;
+
+        let params = MoneyFeeParamsV1 {
+            input: Input {
+                value_commit: public_inputs.input_value_commit,
+                token_commit: public_inputs.token_commit,
+                nullifier: public_inputs.nullifier,
+                merkle_root: public_inputs.merkle_root,
+                frozen_root: pallas::Base::ZERO,
+                user_data_enc: public_inputs.input_user_data_enc,
+                signature_public: public_inputs.signature_public,
+            },
+            output: Output {
+                value_commit: public_inputs.output_value_commit,
+                token_commit: public_inputs.token_commit,
+                coin: public_inputs.output_coin,
+                note: encrypted_note,
+            },
+            fee_value_blind,
+            token_id: coin.note.token_id,
+            token_blind,
+        };
+
+        // Encode the contract call
+        let mut data = vec![MoneyFunction::FeeV1 as u8];
+        fee.encode(&mut data)?;
+        params.encode(&mut data)?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        Ok((call, vec![proof], vec![signature_secret], coin))
+    }
+
     /// Mark a coin in the wallet as spent
     pub async fn mark_spent_coin(&self, coin: &Coin) -> WalletDbResult<()> {
         let query = format!(
@@ -817,14 +1686,22 @@ impl Drk {
             return Ok(())
         }
 
-        for (coin, _) in self.get_coins(false).await? {
-            if nullifiers.contains(&coin.nullifier()) {
+        // Coins we spent ourselves are already marked locally, but we
+        // still want to notify that their spend got on-chain.
+        for (coin, is_spent) in self.get_coins(true).await? {
+            if !nullifiers.contains(&coin.nullifier()) {
+                continue
+            }
+
+            if !is_spent {
                 if let Err(e) = self.mark_spent_coin(&coin.coin).await {
                     return Err(Error::RusqliteError(format!(
                         "[mark_spent_coins] Marking spent coin failed: {e:?}"
                     )))
                 }
             }
+
+            self.events.notify(WalletEvent::CoinSpent(coin)).await;
         }
 
         Ok(())
@@ -842,11 +1719,317 @@ impl Drk {
         Ok(())
     }
 
+    /// Reset the Money Merkle tree checkpoints in the wallet
+    pub async fn reset_money_tree_checkpoints(&self) -> WalletDbResult<()> {
+        println!("Resetting Money Merkle tree checkpoints");
+        let query = format!("DELETE FROM {};", *MONEY_TREE_CHECKPOINTS_TABLE);
+        self.wallet.exec_sql(&query, &[]).await
+    }
+
+    /// Build the SMT of tokens whose transfers are frozen, used to prove
+    /// a spent coin's token is not frozen.
+    pub async fn get_money_frozen_smt(&self) -> Result<SmtMemoryFp> {
+        let rows = match self
+            .wallet
+            .query_multiple(&MONEY_FROZEN_TOKENS_TABLE, &[MONEY_FROZEN_TOKENS_COL_TOKEN_ID], &[])
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_frozen_smt] Frozen tokens retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut leaves = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Blob(ref token_id_bytes) = row[0] else {
+                return Err(Error::ParseFailed(
+                    "[get_money_frozen_smt] Token ID bytes parsing failed",
+                ))
+            };
+            let token_id: TokenId = deserialize(token_id_bytes)?;
+            leaves.push((token_id.inner(), token_id.inner()));
+        }
+
+        let hasher = PoseidonFp::new();
+        let store = MemoryStorageFp::new();
+        let mut smt = SmtMemoryFp::new(store, hasher, &EMPTY_NODES_FP);
+        smt.insert_batch(leaves)?;
+
+        Ok(smt)
+    }
+
+    /// Reset the frozen token transfers in the wallet
+    pub async fn reset_money_frozen_tokens(&self) -> WalletDbResult<()> {
+        println!("Resetting frozen tokens");
+        let query = format!("DELETE FROM {};", *MONEY_FROZEN_TOKENS_TABLE);
+        self.wallet.exec_sql(&query, &[]).await?;
+        println!("Successfully reset frozen tokens");
+
+        Ok(())
+    }
+
+    /// Record the revocation of a token's mint authority in the wallet.
+    pub async fn put_money_token_revocation(&self, token_id: &TokenId) -> WalletDbResult<()> {
+        let query = format!(
+            "INSERT INTO {} ({}, {}) VALUES (?1, 1) ON CONFLICT({}) DO UPDATE SET {} = 1;",
+            *MONEY_TOKEN_POLICIES_TABLE,
+            MONEY_TOKEN_POLICIES_COL_TOKEN_ID,
+            MONEY_TOKEN_POLICIES_COL_IS_REVOKED,
+            MONEY_TOKEN_POLICIES_COL_TOKEN_ID,
+            MONEY_TOKEN_POLICIES_COL_IS_REVOKED,
+        );
+        self.wallet.exec_sql(&query, rusqlite::params![serialize(token_id)]).await
+    }
+
+    /// Record the supply cap of a token in the wallet.
+    pub async fn put_money_token_supply_cap(
+        &self,
+        token_id: &TokenId,
+        supply_cap: u64,
+    ) -> WalletDbResult<()> {
+        let query = format!(
+            "INSERT INTO {} ({}, {}, {}) VALUES (?1, ?2, 0) ON CONFLICT({}) DO UPDATE SET {} = ?2;",
+            *MONEY_TOKEN_POLICIES_TABLE,
+            MONEY_TOKEN_POLICIES_COL_TOKEN_ID,
+            MONEY_TOKEN_POLICIES_COL_SUPPLY_CAP,
+            MONEY_TOKEN_POLICIES_COL_IS_REVOKED,
+            MONEY_TOKEN_POLICIES_COL_TOKEN_ID,
+            MONEY_TOKEN_POLICIES_COL_SUPPLY_CAP,
+        );
+        self.wallet
+            .exec_sql(&query, rusqlite::params![serialize(token_id), serialize(&supply_cap)])
+            .await
+    }
+
+    /// Fetch the supply cap of a token, if any, and whether its mint
+    /// authority has been revoked.
+    pub async fn get_money_token_policy(&self, token_id: &TokenId) -> Result<(Option<u64>, bool)> {
+        let token_id_bytes = serialize(token_id);
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &MONEY_TOKEN_POLICIES_TABLE,
+                &[MONEY_TOKEN_POLICIES_COL_SUPPLY_CAP, MONEY_TOKEN_POLICIES_COL_IS_REVOKED],
+                convert_named_params! {(MONEY_TOKEN_POLICIES_COL_TOKEN_ID, token_id_bytes)},
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_token_policy] Token policy retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let Some(row) = rows.first() else { return Ok((None, false)) };
+
+        let supply_cap = match row[0] {
+            Value::Blob(ref supply_cap_bytes) => Some(deserialize(supply_cap_bytes)?),
+            Value::Null => None,
+            _ => {
+                return Err(Error::ParseFailed(
+                    "[get_money_token_policy] Supply cap bytes parsing failed",
+                ))
+            }
+        };
+
+        let Value::Integer(revoked) = row[1] else {
+            return Err(Error::ParseFailed("[get_money_token_policy] Is revoked parsing failed"))
+        };
+
+        Ok((supply_cap, revoked != 0))
+    }
+
+    /// Reset the token supply policies in the wallet
+    pub async fn reset_money_token_policies(&self) -> WalletDbResult<()> {
+        println!("Resetting token supply policies");
+        let query = format!("DELETE FROM {};", *MONEY_TOKEN_POLICIES_TABLE);
+        self.wallet.exec_sql(&query, &[]).await?;
+        println!("Successfully reset token supply policies");
+
+        Ok(())
+    }
+
+    /// Record a mint we built with one of our mint authorities, along
+    /// with its value blind, until it gets executed on chain.
+    pub async fn put_money_token_mint(
+        &self,
+        coin: &Coin,
+        token_id: &TokenId,
+        value: u64,
+        value_blind: &ScalarBlind,
+    ) -> WalletDbResult<()> {
+        let query = format!(
+            "INSERT INTO {} ({}, {}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4, 0);",
+            *MONEY_TOKEN_MINTS_TABLE,
+            MONEY_TOKEN_MINTS_COL_COIN,
+            MONEY_TOKEN_MINTS_COL_TOKEN_ID,
+            MONEY_TOKEN_MINTS_COL_VALUE,
+            MONEY_TOKEN_MINTS_COL_VALUE_BLIND,
+            MONEY_TOKEN_MINTS_COL_IS_EXECUTED,
+        );
+        self.wallet
+            .exec_sql(
+                &query,
+                rusqlite::params![
+                    serialize(coin),
+                    serialize(token_id),
+                    serialize(&value),
+                    serialize(value_blind),
+                ],
+            )
+            .await
+    }
+
+    /// Mark the mint of given coin as executed, if we built it.
+    pub async fn confirm_money_token_mint(&self, coin: &Coin) -> WalletDbResult<()> {
+        let query = format!(
+            "UPDATE {} SET {} = 1 WHERE {} = ?1;",
+            *MONEY_TOKEN_MINTS_TABLE, MONEY_TOKEN_MINTS_COL_IS_EXECUTED, MONEY_TOKEN_MINTS_COL_COIN,
+        );
+        self.wallet.exec_sql(&query, rusqlite::params![serialize(coin)]).await
+    }
+
+    /// Compute the total supply of a token and the sum of its mints' value
+    /// blinds, from the executed mints we built.
+    pub async fn get_money_token_supply(&self, token_id: &TokenId) -> Result<(u64, ScalarBlind)> {
+        let token_id_bytes = serialize(token_id);
+        let rows = match self
+            .wallet
+            .query_multiple(
+                &MONEY_TOKEN_MINTS_TABLE,
+                &[MONEY_TOKEN_MINTS_COL_VALUE, MONEY_TOKEN_MINTS_COL_VALUE_BLIND],
+                convert_named_params! {
+                    (MONEY_TOKEN_MINTS_COL_TOKEN_ID, token_id_bytes),
+                    (MONEY_TOKEN_MINTS_COL_IS_EXECUTED, 1)
+                },
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_token_supply] Token mints retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut supply: u64 = 0;
+        let mut supply_blind = ScalarBlind::ZERO;
+        for row in rows {
+            let Value::Blob(ref value_bytes) = row[0] else {
+                return Err(Error::ParseFailed(
+                    "[get_money_token_supply] Value bytes parsing failed",
+                ))
+            };
+            let value: u64 = deserialize(value_bytes)?;
+
+            let Value::Blob(ref value_blind_bytes) = row[1] else {
+                return Err(Error::ParseFailed(
+                    "[get_money_token_supply] Value blind bytes parsing failed",
+                ))
+            };
+            let value_blind: ScalarBlind = deserialize(value_blind_bytes)?;
+
+            let Some(new_supply) = supply.checked_add(value) else {
+                return Err(Error::Custom(format!("Supply of token {token_id} overflows")))
+            };
+            supply = new_supply;
+            supply_blind += value_blind;
+        }
+
+        Ok((supply, supply_blind))
+    }
+
+    /// Mark all the mints we built as not executed, so rescanning the
+    /// chain confirms them again. The mints themselves are kept, since
+    /// their value blinds can't be recovered from the chain.
+    pub async fn reset_money_token_mints(&self) -> WalletDbResult<()> {
+        println!("Resetting token mints");
+        let query = format!(
+            "UPDATE {} SET {} = 0;",
+            *MONEY_TOKEN_MINTS_TABLE, MONEY_TOKEN_MINTS_COL_IS_EXECUTED
+        );
+        self.wallet.exec_sql(&query, &[]).await?;
+        println!("Successfully reset token mints");
+
+        Ok(())
+    }
+
+    /// Insert an NFT along with its metadata commitment into the wallet,
+    /// if it's not already tracked.
+    pub async fn put_money_nft(
+        &self,
+        token_id: &TokenId,
+        metadata: &pallas::Base,
+    ) -> WalletDbResult<()> {
+        let query = format!(
+            "INSERT OR IGNORE INTO {} ({}, {}, {}) VALUES (?1, ?2, ?3);",
+            *MONEY_NFTS_TABLE,
+            MONEY_NFTS_COL_TOKEN_ID,
+            MONEY_NFTS_COL_METADATA,
+            MONEY_NFTS_COL_IS_BURNED,
+        );
+        self.wallet
+            .exec_sql(&query, rusqlite::params![serialize(token_id), serialize(metadata), 0])
+            .await
+    }
+
+    /// Fetch all NFTs seen on chain, along with their metadata commitments
+    /// and whether they have been burned.
+    pub async fn get_money_nfts(&self) -> Result<Vec<(TokenId, pallas::Base, bool)>> {
+        let rows = match self.wallet.query_multiple(&MONEY_NFTS_TABLE, &[], &[]).await {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_money_nfts] NFTs retrieval failed: {e:?}"
+                )))
+            }
+        };
+
+        let mut ret = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Blob(ref token_id_bytes) = row[0] else {
+                return Err(Error::ParseFailed("[get_money_nfts] Token ID bytes parsing failed"))
+            };
+            let token_id = deserialize(token_id_bytes)?;
+
+            let Value::Blob(ref metadata_bytes) = row[1] else {
+                return Err(Error::ParseFailed("[get_money_nfts] Metadata bytes parsing failed"))
+            };
+            let metadata = deserialize(metadata_bytes)?;
+
+            let Value::Integer(burned) = row[2] else {
+                return Err(Error::ParseFailed("[get_money_nfts] Is burned parsing failed"))
+            };
+
+            ret.push((token_id, metadata, burned != 0));
+        }
+
+        Ok(ret)
+    }
+
+    /// Reset the NFTs in the wallet
+    pub async fn reset_money_nfts(&self) -> WalletDbResult<()> {
+        println!("Resetting NFTs");
+        let query = format!("DELETE FROM {};", *MONEY_NFTS_TABLE);
+        self.wallet.exec_sql(&query, &[]).await?;
+        println!("Successfully reset NFTs");
+
+        Ok(())
+    }
+
     /// Reset the Money coins in the wallet
     pub async fn reset_money_coins(&self) -> WalletDbResult<()> {
         println!("Resetting coins");
         let query = format!("DELETE FROM {};", *MONEY_COINS_TABLE);
         self.wallet.exec_sql(&query, &[]).await?;
+        let query = format!("DELETE FROM {};", *MONEY_WATCH_COINS_TABLE);
+        self.wallet.exec_sql(&query, &[]).await?;
         println!("Successfully reset coins");
 
         Ok(())
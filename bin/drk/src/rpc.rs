@@ -16,7 +16,7 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::sync::Arc;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use url::Url;
 
@@ -32,12 +32,17 @@ use darkfi::{
     util::encoding::base64,
     Error, Result,
 };
-use darkfi_sdk::{crypto::ContractId, tx::TransactionHash};
+use darkfi_money_contract::model::{Coin, Nullifier, TokenId};
+use darkfi_registry_contract::model::TokenMetadata;
+use darkfi_sdk::{
+    crypto::{note::AeadEncryptedNote, ContractId},
+    tx::TransactionHash,
+};
 use darkfi_serial::{deserialize_async, serialize_async};
 
 use crate::{
     error::{WalletDbError, WalletDbResult},
-    money::{MONEY_INFO_COL_LAST_SCANNED_BLOCK, MONEY_INFO_TABLE},
+    money::{MONEY_INFO_COL_LAST_SCANNED_BLOCK, MONEY_INFO_TABLE, MONEY_TREE_CHECKPOINT_INTERVAL},
     Drk,
 };
 
@@ -76,11 +81,15 @@ impl Drk {
         let subscriber = Subscriber::new();
         let subscription = subscriber.clone().subscribe().await;
         let _ex = ex.clone();
+        let endpoint_key = self.endpoint_key.clone();
         StoppableTask::new().start(
             // Weird hack to prevent lifetimes hell
             async move {
                 let ex = _ex.clone();
-                let rpc_client = RpcClient::new(endpoint, ex).await?;
+                let rpc_client = match endpoint_key {
+                    Some(ref key) => RpcClient::new_pinned(endpoint, key, ex).await?,
+                    None => RpcClient::new(endpoint, ex).await?,
+                };
                 let req = JsonRequest::new("blockchain.subscribe_blocks", JsonValue::Array(vec![]));
                 rpc_client.subscribe(req, subscriber).await
             },
@@ -174,7 +183,19 @@ impl Drk {
         println!("[Money] Iterating over {} transactions", block.txs.len());
 
         for tx in block.txs.iter() {
-            self.apply_tx_money_data(tx, true).await?;
+            let (coins, nullifiers) = self.apply_tx_money_data(tx, true).await?;
+            self.record_money_history(
+                tx,
+                &coins,
+                &nullifiers,
+                block.header.height,
+                block.header.timestamp,
+            )
+            .await?;
+        }
+
+        if block.header.height % MONEY_TREE_CHECKPOINT_INTERVAL == 0 {
+            self.checkpoint_money_tree(block.header.height).await?;
         }
 
         // Write this block height into `last_scanned_block`
@@ -199,6 +220,8 @@ impl Drk {
             self.apply_tx_dao_data(tx, true).await?;
         }
 
+        self.check_vote_windows(block.header.height).await?;
+
         Ok(())
     }
 
@@ -214,11 +237,25 @@ impl Drk {
         // the next block height
         if height == 0 || reset {
             self.reset_money_tree().await?;
+            self.reset_money_tree_checkpoints().await?;
             self.reset_money_coins().await?;
+            self.reset_money_frozen_tokens().await?;
+            self.reset_money_token_policies().await?;
+            self.reset_money_token_mints().await?;
+            self.reset_money_nfts().await?;
             self.reset_dao_trees().await?;
             self.reset_daos().await?;
             self.reset_dao_proposals().await?;
             self.reset_dao_votes().await?;
+            self.reset_dao_superseded().await?;
+            if let Err(e) = self.reset_money_history().await {
+                eprintln!("[scan_blocks] Resetting funds history failed: {e:?}");
+                return Err(WalletDbError::GenericError)
+            }
+            if let Err(e) = self.reset_dao_proposal_windows().await {
+                eprintln!("[scan_blocks] Resetting DAO proposal windows failed: {e:?}");
+                return Err(WalletDbError::GenericError)
+            }
             self.update_all_tx_history_records_status("Rejected").await?;
             height = 0;
         } else {
@@ -267,8 +304,200 @@ impl Drk {
         }
     }
 
-    // Queries darkfid for a block with given height
-    async fn get_block_by_height(&self, height: u64) -> Result<BlockInfo> {
+    /// Store a checkpoint of the current Money Merkle tree for the given
+    /// block height.
+    async fn checkpoint_money_tree(&self, height: u64) -> Result<()> {
+        let tree = self.get_money_tree().await?;
+        if let Err(e) = self.put_money_tree_checkpoint(height, &tree).await {
+            return Err(Error::RusqliteError(format!(
+                "[checkpoint_money_tree] Storing Money tree checkpoint failed: {e:?}"
+            )))
+        }
+
+        Ok(())
+    }
+
+    /// Rescan the Money contract state starting from the given block height,
+    /// to find the coins of keys imported after their blocks were scanned.
+    /// The Merkle tree is rewound to the latest checkpoint preceding that
+    /// height, along with the coins found after it, and the blocks up to the
+    /// last scanned one get replayed. If darkfid supports it, blocks are
+    /// replayed through `blockchain.scan_block`, which only returns their
+    /// Money data relevant to the wallet, otherwise they are fetched in full.
+    /// Only full blocks carry the transaction context needed to record the
+    /// funds history. DAO state is not affected.
+    pub async fn rescan(&self, from_height: u64) -> Result<()> {
+        let last_scanned = match self.last_scanned_block().await {
+            Ok(l) => l,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[rescan] Retrieving last scanned block failed: {e:?}"
+                )))
+            }
+        };
+
+        if from_height > last_scanned {
+            println!("Block {from_height} has not been scanned yet, nothing to rescan");
+            return Ok(())
+        }
+
+        // Rewind the tree to its latest checkpoint before the requested height
+        let (checkpoint, tree) = match self.get_money_tree_checkpoint_before(from_height).await? {
+            Some((height, tree)) => {
+                println!("Rewinding Money Merkle tree to checkpoint of block {height}");
+                if let Err(e) = self.put_money_tree(&tree).await {
+                    return Err(Error::RusqliteError(format!(
+                        "[rescan] Put Money tree failed: {e:?}"
+                    )))
+                }
+                (Some(height), tree)
+            }
+            None => {
+                if let Err(e) = self.reset_money_tree().await {
+                    return Err(Error::RusqliteError(format!(
+                        "[rescan] Resetting Money tree failed: {e:?}"
+                    )))
+                }
+                (None, self.get_money_tree().await?)
+            }
+        };
+
+        // Checkpoints after it lack the coins we are about to find
+        let query_result = match checkpoint {
+            Some(height) => self.rewind_money_tree_checkpoints(height).await,
+            None => self.reset_money_tree_checkpoints().await,
+        };
+        if let Err(e) = query_result {
+            return Err(Error::RusqliteError(format!(
+                "[rescan] Rewinding Money tree checkpoints failed: {e:?}"
+            )))
+        }
+        self.rewind_money_coins(&tree).await?;
+
+        let mut use_filter = true;
+        let first = checkpoint.map_or(0, |h| h + 1);
+        for height in first..=last_scanned {
+            eprint!("Rescanning block {height}... ");
+
+            if use_filter {
+                match self.scan_block_filtered(height).await {
+                    Ok((outputs, nullifiers)) => {
+                        self.apply_money_outputs(&outputs, &nullifiers).await?;
+                    }
+                    Err(e) => {
+                        eprintln!("Filtered scanning unavailable, fetching full blocks: {e:?}");
+                        use_filter = false;
+                    }
+                }
+            }
+
+            if !use_filter {
+                let block = self.get_block_by_height(height).await?;
+                for tx in block.txs.iter() {
+                    let (coins, nullifiers) = self.apply_tx_money_data(tx, true).await?;
+                    self.record_money_history(
+                        tx,
+                        &coins,
+                        &nullifiers,
+                        height,
+                        block.header.timestamp,
+                    )
+                    .await?;
+                }
+            }
+
+            if height % MONEY_TREE_CHECKPOINT_INTERVAL == 0 {
+                self.checkpoint_money_tree(height).await?;
+            }
+            eprintln!("done");
+        }
+
+        Ok(())
+    }
+
+    /// Queries darkfid for the Money data of the block with given height,
+    /// filtered for the wallet keys and unspent coins. Returns the new coins
+    /// along with the notes of the matching ones, and the matching nullifiers.
+    async fn scan_block_filtered(
+        &self,
+        height: u64,
+    ) -> Result<(Vec<(Coin, Option<AeadEncryptedNote>)>, Vec<Nullifier>)> {
+        let money_secrets = self.get_money_secrets().await?;
+        let dao_secrets = self.get_dao_secrets().await?;
+        let group_secrets = self.get_group_secrets().await?;
+        let watch_keys = self.get_watch_keys().await?;
+
+        let mut secrets: Vec<_> = money_secrets
+            .iter()
+            .chain(dao_secrets.iter())
+            .chain(group_secrets.iter())
+            .map(|secret| JsonValue::String(secret.to_string()))
+            .collect();
+        secrets.extend(watch_keys.iter().map(|key| JsonValue::String(key.inner().to_string())));
+
+        let nullifiers = self
+            .get_coins(false)
+            .await?
+            .iter()
+            .map(|(coin, _)| JsonValue::String(coin.nullifier().to_string()))
+            .collect();
+
+        let filter = HashMap::from([
+            ("secrets".to_string(), JsonValue::Array(secrets)),
+            ("nullifiers".to_string(), JsonValue::Array(nullifiers)),
+        ]);
+        let req = JsonRequest::new(
+            "blockchain.scan_block",
+            JsonValue::Array(vec![
+                JsonValue::String(height.to_string()),
+                JsonValue::Object(filter),
+            ]),
+        );
+        let rep = self.rpc_client.request(req).await?;
+
+        let parse_error =
+            || Error::UnexpectedJsonRpc("Received malformed filtered block data".to_string());
+        let Some(data) = rep.get::<HashMap<String, JsonValue>>() else { return Err(parse_error()) };
+        let (Some(coins), Some(matched), Some(spent)) = (
+            data.get("coins").and_then(|v| v.get::<Vec<JsonValue>>()),
+            data.get("outputs").and_then(|v| v.get::<Vec<JsonValue>>()),
+            data.get("nullifiers").and_then(|v| v.get::<Vec<JsonValue>>()),
+        ) else {
+            return Err(parse_error())
+        };
+
+        let mut outputs = Vec::with_capacity(coins.len());
+        for coin in coins {
+            let Some(coin) = coin.get::<String>() else { return Err(parse_error()) };
+            outputs.push((Coin::from_str(coin)?, None));
+        }
+
+        for output in matched {
+            let Some(output) = output.get::<HashMap<String, JsonValue>>() else {
+                return Err(parse_error())
+            };
+            let (Some(index), Some(note)) = (
+                output.get("index").and_then(|v| v.get::<f64>()),
+                output.get("note").and_then(|v| v.get::<String>()),
+            ) else {
+                return Err(parse_error())
+            };
+            let Some(note) = base64::decode(note) else { return Err(parse_error()) };
+            let Some(entry) = outputs.get_mut(*index as usize) else { return Err(parse_error()) };
+            entry.1 = Some(deserialize_async(&note).await?);
+        }
+
+        let mut nullifiers = Vec::with_capacity(spent.len());
+        for nullifier in spent {
+            let Some(nullifier) = nullifier.get::<String>() else { return Err(parse_error()) };
+            nullifiers.push(Nullifier::from_str(nullifier)?);
+        }
+
+        Ok((outputs, nullifiers))
+    }
+
+    /// Queries darkfid for a block with given height
+    pub async fn get_block_by_height(&self, height: u64) -> Result<BlockInfo> {
         let req = JsonRequest::new(
             "blockchain.get_block",
             JsonValue::Array(vec![JsonValue::String(height.to_string())]),
@@ -333,6 +562,23 @@ impl Drk {
         Ok(is_valid)
     }
 
+    /// Queries darkfid for the fee the given transaction has to pay, found by
+    /// running it through the validator's gas accounting. If `include_fee` is
+    /// set, the gas of a fee call yet to be appended to it is included.
+    pub async fn get_tx_fee(&self, tx: &Transaction, include_fee: bool) -> Result<u64> {
+        let tx_str = base64::encode(&serialize_async(tx).await);
+        let req = JsonRequest::new(
+            "tx.calculate_fee",
+            JsonValue::Array(vec![JsonValue::String(tx_str), JsonValue::Boolean(include_fee)]),
+        );
+        let rep = self.rpc_client.request(req).await?;
+
+        let Some(fee) = rep.get::<f64>() else {
+            return Err(Error::UnexpectedJsonRpc("Received malformed fee".to_string()))
+        };
+        Ok(*fee as u64)
+    }
+
     /// Try to fetch zkas bincodes for the given `ContractId`.
     pub async fn lookup_zkas(&self, contract_id: &ContractId) -> Result<Vec<(String, Vec<u8>)>> {
         println!("Querying zkas bincode for {contract_id}");
@@ -353,4 +599,20 @@ impl Drk {
 
         Ok(ret)
     }
+
+    /// Try to fetch the metadata registered for the given `TokenId`.
+    pub async fn lookup_token_metadata(&self, token_id: &TokenId) -> Result<Option<TokenMetadata>> {
+        let params = JsonValue::Array(vec![JsonValue::String(format!("{token_id}"))]);
+        let req = JsonRequest::new("blockchain.lookup_token_metadata", params);
+
+        let rep = self.rpc_client.request(req).await?;
+        if rep.is_null() {
+            return Ok(None)
+        }
+
+        let metadata_bytes = base64::decode(rep.get::<String>().unwrap()).unwrap();
+        let metadata = deserialize_async(&metadata_bytes).await?;
+
+        Ok(Some(metadata))
+    }
 }
@@ -0,0 +1,95 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    os::unix::{
+        fs::{OpenOptionsExt, PermissionsExt},
+        net::UnixListener,
+    },
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::OsRng, RngCore};
+
+use darkfi::{
+    tx::{
+        bundle::TransactionBundle,
+        signer::{serve_signer, LocalSigner, SocketSigner},
+    },
+    Error, Result,
+};
+
+use crate::Drk;
+
+/// Path of the authentication key of the signer socket at the given path
+fn signer_key_path(socket: &Path) -> PathBuf {
+    let mut key_path = socket.as_os_str().to_owned();
+    key_path.push(".key");
+    PathBuf::from(key_path)
+}
+
+impl Drk {
+    /// Serve signing requests for the wallet keys on a local socket at
+    /// the given path, until interrupted. A fresh authentication key is
+    /// written next to the socket, readable only by the current user,
+    /// so only clients able to read it get their requests answered.
+    pub async fn serve_signer(&self, socket: &Path) -> Result<()> {
+        let mut secrets = self.get_money_secrets().await?;
+        secrets.extend(self.list_tokens().await?.into_iter().map(|(_, secret, _)| secret));
+        secrets.extend(self.get_dao_secrets().await?);
+        let signer = LocalSigner::new(&secrets);
+
+        let mut auth_key = [0u8; 32];
+        OsRng.fill_bytes(&mut auth_key);
+
+        let key_path = signer_key_path(socket);
+        if let Err(e) = fs::remove_file(&key_path) {
+            if e.kind() != ErrorKind::NotFound {
+                return Err(e.into())
+            }
+        }
+        let mut key_file =
+            OpenOptions::new().write(true).create_new(true).mode(0o600).open(&key_path)?;
+        key_file.write_all(&auth_key)?;
+        key_file.sync_all()?;
+
+        if let Err(e) = fs::remove_file(socket) {
+            if e.kind() != ErrorKind::NotFound {
+                return Err(e.into())
+            }
+        }
+        let listener = UnixListener::bind(socket)?;
+        fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+
+        println!("Serving signing requests for {} keys on {}", secrets.len(), socket.display());
+        serve_signer(&listener, &signer, &auth_key)
+    }
+}
+
+/// Add the signatures of a bundle that the signer serving on the socket at
+/// the given path holds the keys of, returning how many were added.
+pub fn sign_bundle(socket: &Path, bundle: &mut TransactionBundle) -> Result<usize> {
+    let auth_key = fs::read(signer_key_path(socket))?;
+    let Ok(auth_key) = auth_key.try_into() else {
+        return Err(Error::Custom("Malformed signer authentication key".to_string()))
+    };
+
+    bundle.sign(&SocketSigner::new(socket, auth_key))
+}
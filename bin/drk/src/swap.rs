@@ -15,59 +15,34 @@
  * You should have received a copy of the GNU Affero General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use std::fmt;
-
 use rand::rngs::OsRng;
 
 use darkfi::{
     tx::{ContractCallLeaf, Transaction, TransactionBuilder},
     util::parse::encode_base10,
-    zk::{halo2::Field, proof::ProvingKey, vm::ZkCircuit, vm_heap::empty_witnesses, Proof},
-    zkas::ZkBinary,
+    zk::halo2::Field,
     Error, Result,
 };
 use darkfi_money_contract::{
-    client::{swap_v1::SwapCallBuilder, MoneyNote},
+    client::{
+        swap_v1::{PartialSwapData, SwapCallBuilder},
+        MoneyNote,
+    },
     model::{Coin, MoneyTransferParamsV1, TokenId},
     MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
 };
 use darkfi_sdk::{
     crypto::{
-        contract_id::MONEY_CONTRACT_ID, pedersen::pedersen_commitment_u64, poseidon_hash,
-        BaseBlind, Blind, FuncId, PublicKey, ScalarBlind, SecretKey,
+        contract_id::MONEY_CONTRACT_ID, pedersen::pedersen_commitment_u64, poseidon_hash, Blind,
+        FuncId, PublicKey, SecretKey,
     },
     pasta::pallas,
     tx::ContractCall,
 };
-use darkfi_serial::{async_trait, deserialize, Encodable, SerialDecodable, SerialEncodable};
+use darkfi_serial::{deserialize, Encodable};
 
 use super::{money::BALANCE_BASE10_DECIMALS, Drk};
 
-#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
-/// Half of the swap data, includes the coin that is supposed to be sent,
-/// and the coin that is supposed to be received.
-pub struct PartialSwapData {
-    params: MoneyTransferParamsV1,
-    proofs: Vec<Proof>,
-    value_pair: (u64, u64),
-    token_pair: (TokenId, TokenId),
-    value_blinds: Vec<ScalarBlind>,
-    token_blinds: Vec<BaseBlind>,
-}
-
-impl fmt::Display for PartialSwapData {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let s =
-            format!(
-            "{:#?}\nValue pair: {}:{}\nToken pair: {}:{}\nValue blinds: {:?}\nToken blinds: {:?}\n",
-            self.params, self.value_pair.0, self.value_pair.1, self.token_pair.0, self.token_pair.1,
-            self.value_blinds, self.token_blinds,
-        );
-
-        write!(f, "{}", s)
-    }
-}
-
 impl Drk {
     /// Initialize the first half of an atomic swap
     pub async fn init_swap(
@@ -77,8 +52,10 @@ impl Drk {
         value_recv: u64,
         token_recv: TokenId,
     ) -> Result<PartialSwapData> {
+        self.check_spendable().await?;
+
         // First we'll fetch all of our unspent coins from the wallet.
-        let mut owncoins = self.get_coins(false).await?;
+        let mut owncoins = self.get_account_coins(false).await?;
         // Then we see if we have one that we can send.
         owncoins.retain(|x| {
             x.0.note.value == value_send &&
@@ -100,6 +77,7 @@ impl Drk {
 
         // We'll also need our Merkle tree
         let tree = self.get_money_tree().await?;
+        let frozen_tokens = self.get_money_frozen_smt().await?;
 
         let contract_id = *MONEY_CONTRACT_ID;
 
@@ -118,20 +96,14 @@ impl Drk {
             return Err(Error::Custom("Burn circuit not found".to_string()))
         };
 
-        let mint_zkbin = ZkBinary::decode(&mint_zkbin.1)?;
-        let burn_zkbin = ZkBinary::decode(&burn_zkbin.1)?;
-
-        let mint_circuit = ZkCircuit::new(empty_witnesses(&mint_zkbin)?, &mint_zkbin);
-        let burn_circuit = ZkCircuit::new(empty_witnesses(&burn_zkbin)?, &burn_zkbin);
-
         // Since we're creating the first half, we generate the blinds.
         let value_blinds = [Blind::random(&mut OsRng), Blind::random(&mut OsRng)];
         let token_blinds = [Blind::random(&mut OsRng), Blind::random(&mut OsRng)];
 
         // Now we should have everything we need to build the swap half
         println!("Creating Mint and Burn circuit proving keys");
-        let mint_pk = ProvingKey::build(mint_zkbin.k, &mint_circuit);
-        let burn_pk = ProvingKey::build(burn_zkbin.k, &burn_circuit);
+        let (mint_zkbin, mint_pk) = self.key_cache.proving_key(&mint_zkbin.1)?;
+        let (burn_zkbin, burn_pk) = self.key_cache.proving_key(&burn_zkbin.1)?;
         let builder = SwapCallBuilder {
             pubkey: address,
             value_send,
@@ -145,6 +117,7 @@ impl Drk {
             token_blinds,
             coin: burn_coin,
             tree,
+            frozen_tokens,
             mint_zkbin,
             mint_pk,
             burn_zkbin,
@@ -152,29 +125,21 @@ impl Drk {
         };
 
         println!("Building first half of the swap transaction");
-        let debris = builder.build()?;
-
-        // Now we have the half, so we can build `PartialSwapData` and return it.
-        let ret = PartialSwapData {
-            params: debris.params,
-            proofs: debris.proofs,
-            value_pair: (value_send, value_recv),
-            token_pair: (token_send, token_recv),
-            value_blinds: value_blinds.to_vec(),
-            token_blinds: token_blinds.to_vec(),
-        };
-
-        Ok(ret)
+        builder.build_partial()
     }
 
     /// Create a full transaction by inspecting and verifying given partial swap data,
     /// making the other half, and joining all this into a `Transaction` object.
     pub async fn join_swap(&self, partial: PartialSwapData) -> Result<Transaction> {
+        self.check_spendable().await?;
+
         // Our side of the tx in the pairs is the second half, so we try to find
         // an unspent coin like that in our wallet.
-        let mut owncoins = self.get_coins(false).await?;
+        let mut owncoins = self.get_account_coins(false).await?;
         owncoins.retain(|x| {
-            x.0.note.value == partial.value_pair.1 && x.0.note.token_id == partial.token_pair.1
+            x.0.note.value == partial.value_pair.1 &&
+                x.0.note.token_id == partial.token_pair.1 &&
+                x.0.note.spend_hook == FuncId::none()
         });
 
         if owncoins.is_empty() {
@@ -192,6 +157,7 @@ impl Drk {
 
         // We'll also need our Merkle tree
         let tree = self.get_money_tree().await?;
+        let frozen_tokens = self.get_money_frozen_smt().await?;
 
         let contract_id = *MONEY_CONTRACT_ID;
 
@@ -210,51 +176,28 @@ impl Drk {
             return Err(Error::Custom("Burn circuit not found".to_string()))
         };
 
-        let mint_zkbin = ZkBinary::decode(&mint_zkbin.1)?;
-        let burn_zkbin = ZkBinary::decode(&burn_zkbin.1)?;
-
-        let mint_circuit = ZkCircuit::new(empty_witnesses(&mint_zkbin)?, &mint_zkbin);
-        let burn_circuit = ZkCircuit::new(empty_witnesses(&burn_zkbin)?, &burn_zkbin);
-
-        // TODO: Maybe some kind of verification at this point
-
         // Now we should have everything we need to build the swap half
         println!("Creating Mint and Burn circuit proving keys");
-        let mint_pk = ProvingKey::build(mint_zkbin.k, &mint_circuit);
-        let burn_pk = ProvingKey::build(burn_zkbin.k, &burn_circuit);
-        let builder = SwapCallBuilder {
-            pubkey: address,
-            value_send: partial.value_pair.1,
-            token_id_send: partial.token_pair.1,
-            value_recv: partial.value_pair.0,
-            token_id_recv: partial.token_pair.0,
-            user_data_blind_send: Blind::random(&mut OsRng), // <-- FIXME: Perhaps should be passed in
-            spend_hook_recv: FuncId::none(),                 // <-- FIXME: Should be passed in
-            user_data_recv: pallas::Base::ZERO,              // <-- FIXME: Should be passed in
-            value_blinds: [partial.value_blinds[1], partial.value_blinds[0]],
-            token_blinds: [partial.token_blinds[1], partial.token_blinds[0]],
-            coin: burn_coin,
+        let (mint_zkbin, mint_pk) = self.key_cache.proving_key(&mint_zkbin.1)?;
+        let (burn_zkbin, burn_pk) = self.key_cache.proving_key(&burn_zkbin.1)?;
+        let builder = partial.counterparty_builder(
+            address,
+            burn_coin,
             tree,
+            frozen_tokens,
             mint_zkbin,
             mint_pk,
             burn_zkbin,
             burn_pk,
-        };
+        );
 
         println!("Building second half of the swap transaction");
         let debris = builder.build()?;
 
-        let full_params = MoneyTransferParamsV1 {
-            inputs: vec![partial.params.inputs[0].clone(), debris.params.inputs[0].clone()],
-            outputs: vec![partial.params.outputs[0].clone(), debris.params.outputs[0].clone()],
-        };
-
-        let full_proofs = vec![
-            partial.proofs[0].clone(),
-            debris.proofs[0].clone(),
-            partial.proofs[1].clone(),
-            debris.proofs[1].clone(),
-        ];
+        println!("Verifying and joining the swap halves");
+        let debris = partial.complete(debris)?;
+        let full_params = debris.params;
+        let full_proofs = debris.proofs;
 
         let mut data = vec![MoneyFunction::OtcSwapV1 as u8];
         full_params.encode(&mut data)?;
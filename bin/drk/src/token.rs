@@ -21,19 +21,18 @@ use rusqlite::types::Value;
 
 use darkfi::{
     tx::{ContractCallLeaf, Transaction, TransactionBuilder},
-    util::parse::decode_base10,
-    zk::{proof::ProvingKey, vm::ZkCircuit, vm_heap::empty_witnesses},
-    zkas::ZkBinary,
+    util::parse::{decode_base10, encode_base10},
     Error, Result,
 };
 use darkfi_money_contract::{
     client::{
-        auth_token_mint_v1::AuthTokenMintCallBuilder, token_freeze_v1::TokenFreezeCallBuilder,
+        auth_token_mint_v1::{AuthTokenMintCallBuilder, AuthTokenMintSupplyCap},
+        token_freeze_v1::TokenFreezeCallBuilder,
         token_mint_v1::TokenMintCallBuilder,
     },
     model::{CoinAttributes, TokenAttributes, TokenId},
     MoneyFunction, MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1,
-    MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1,
+    MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1,
 };
 use darkfi_sdk::{
     crypto::{
@@ -142,6 +141,8 @@ impl Drk {
             ))
         }
 
+        let supply_cap = self.get_money_token_policy(&token_id).await?.0;
+
         // Now we need to do a lookup for the zkas proof bincodes, and create
         // the circuit objects and proving keys so we can build the transaction.
         // We also do this through the RPC.
@@ -154,11 +155,8 @@ impl Drk {
                 return Err(Error::Custom("Token mint circuit not found".to_string()))
             };
 
-            let mint_zkbin = ZkBinary::decode(&token_mint_zkbin.1)?;
-            let token_mint_circuit = ZkCircuit::new(empty_witnesses(&mint_zkbin)?, &mint_zkbin);
-
             println!("Creating token mint circuit proving keys");
-            let mint_pk = ProvingKey::build(mint_zkbin.k, &token_mint_circuit);
+            let (mint_zkbin, mint_pk) = self.key_cache.proving_key(&token_mint_zkbin.1)?;
 
             (mint_zkbin, mint_pk)
         };
@@ -170,16 +168,46 @@ impl Drk {
                 return Err(Error::Custom("Token mint circuit not found".to_string()))
             };
 
-            let auth_mint_zkbin = ZkBinary::decode(&token_auth_mint_zkbin.1)?;
-            let token_auth_mint_circuit =
-                ZkCircuit::new(empty_witnesses(&auth_mint_zkbin)?, &auth_mint_zkbin);
-
             println!("Creating token mint circuit proving keys");
-            let auth_mint_pk = ProvingKey::build(auth_mint_zkbin.k, &token_auth_mint_circuit);
+            let (auth_mint_zkbin, auth_mint_pk) =
+                self.key_cache.proving_key(&token_auth_mint_zkbin.1)?;
 
             (auth_mint_zkbin, auth_mint_pk)
         };
 
+        // Capped tokens have to prove the new supply stays within the cap,
+        // using the value blinds of all our executed mints of the token.
+        let supply_cap = match supply_cap {
+            Some(supply_cap) => {
+                let (supply, supply_blind) = self.get_money_token_supply(&token_id).await?;
+                if supply.checked_add(amount).is_none_or(|s| s > supply_cap) {
+                    return Err(Error::Custom(format!(
+                        "Minting {} would exceed the token's supply cap of {}",
+                        encode_base10(amount, BALANCE_BASE10_DECIMALS),
+                        encode_base10(supply_cap, BALANCE_BASE10_DECIMALS),
+                    )))
+                }
+
+                let zkas_ns = MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1;
+                let Some(supply_cap_zkbin) = zkas_bins.iter().find(|x| x.0 == zkas_ns) else {
+                    return Err(Error::Custom("Token supply cap circuit not found".to_string()))
+                };
+
+                println!("Creating token supply cap circuit proving keys");
+                let (supply_cap_zkbin, supply_cap_pk) =
+                    self.key_cache.proving_key(&supply_cap_zkbin.1)?;
+
+                Some(AuthTokenMintSupplyCap {
+                    supply_cap,
+                    supply,
+                    supply_blind,
+                    supply_cap_zkbin,
+                    supply_cap_pk,
+                })
+            }
+            None => None,
+        };
+
         /*
         let mint_builder = TokenMintCallBuilder {
             mint_keypair: mint_authority,
@@ -244,8 +272,26 @@ impl Drk {
             mint_keypair: mint_authority,
             auth_mint_zkbin,
             auth_mint_pk,
+            supply_cap,
         };
         let auth_debris = builder.build()?;
+
+        // Keep the value blind around, so we can prove the token's supply
+        // once the mint gets executed.
+        if let Err(e) = self
+            .put_money_token_mint(
+                &mint_debris.params.coin,
+                &token_id,
+                amount,
+                &auth_debris.value_blind,
+            )
+            .await
+        {
+            return Err(Error::RusqliteError(format!(
+                "[mint_token] Inserting token mint failed: {e:?}"
+            )))
+        }
+
         let mut data = vec![MoneyFunction::AuthTokenMintV1 as u8];
         auth_debris.params.encode(&mut data)?;
         let auth_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
@@ -292,11 +338,8 @@ impl Drk {
             return Err(Error::Custom("Token freeze circuit not found".to_string()))
         };
 
-        let freeze_zkbin = ZkBinary::decode(&token_freeze_zkbin.1)?;
-        let token_freeze_circuit = ZkCircuit::new(empty_witnesses(&freeze_zkbin)?, &freeze_zkbin);
-
         println!("Creating token freeze circuit proving keys");
-        let freeze_pk = ProvingKey::build(freeze_zkbin.k, &token_freeze_circuit);
+        let (freeze_zkbin, freeze_pk) = self.key_cache.proving_key(&token_freeze_zkbin.1)?;
         let freeze_builder = TokenFreezeCallBuilder {
             mint_keypair: mint_authority,
             token_attrs,
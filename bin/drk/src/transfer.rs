@@ -19,12 +19,10 @@
 use darkfi::{
     tx::{ContractCallLeaf, Transaction, TransactionBuilder},
     util::parse::{decode_base10, encode_base10},
-    zk::{proof::ProvingKey, vm::ZkCircuit, vm_heap::empty_witnesses},
-    zkas::ZkBinary,
     Error, Result,
 };
 use darkfi_money_contract::{
-    client::{transfer_v1::make_transfer_call, OwnCoin},
+    client::{coin_selection::CoinSelection, transfer_v1::make_transfer_call, OwnCoin, PaymentId},
     model::TokenId,
     MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
 };
@@ -37,16 +35,26 @@ use darkfi_serial::Encodable;
 use crate::{money::BALANCE_BASE10_DECIMALS, Drk};
 
 impl Drk {
-    /// Create a payment transaction. Returns the transaction object on success.
+    /// Create a payment transaction, optionally binding a recipient-supplied
+    /// payment ID and a memo into the output, and encrypting its note to a
+    /// recipient viewing key. The spent coins are picked using the given
+    /// coin selection strategy. Returns the transaction object on success.
+    #[allow(clippy::too_many_arguments)]
     pub async fn transfer(
         &self,
         amount: &str,
         token_id: TokenId,
         recipient: PublicKey,
+        recipient_viewing_key: Option<PublicKey>,
+        payment_id: Option<PaymentId>,
+        memo: Vec<u8>,
+        selection: &dyn CoinSelection,
     ) -> Result<Transaction> {
+        self.check_spendable().await?;
+
         // First get all unspent OwnCoins to see what our balance is.
         println!("Fetching OwnCoins");
-        let owncoins = self.get_coins(false).await?;
+        let owncoins = self.get_account_coins(false).await?;
         let mut owncoins: Vec<OwnCoin> = owncoins.iter().map(|x| x.0.clone()).collect();
         // We're only interested in the ones for the token_id we're sending
         // And the ones not owned by some protocol (meaning spend-hook should be 0)
@@ -71,6 +79,7 @@ impl Drk {
 
         // We'll also need our Merkle tree
         let tree = self.get_money_tree().await?;
+        let frozen_tokens = self.get_money_frozen_smt().await?;
 
         let secret = self.default_secret().await?;
         let keypair = Keypair::new(secret);
@@ -92,19 +101,26 @@ impl Drk {
             return Err(Error::Custom("Burn circuit not found".to_string()))
         };
 
-        let mint_zkbin = ZkBinary::decode(&mint_zkbin.1)?;
-        let burn_zkbin = ZkBinary::decode(&burn_zkbin.1)?;
-
-        let mint_circuit = ZkCircuit::new(empty_witnesses(&mint_zkbin)?, &mint_zkbin);
-        let burn_circuit = ZkCircuit::new(empty_witnesses(&burn_zkbin)?, &burn_zkbin);
-
         println!("Creating Mint and Burn circuit proving keys");
-        let mint_pk = ProvingKey::build(mint_zkbin.k, &mint_circuit);
-        let burn_pk = ProvingKey::build(burn_zkbin.k, &burn_circuit);
+        let (mint_zkbin, mint_pk) = self.key_cache.proving_key(&mint_zkbin.1)?;
+        let (burn_zkbin, burn_pk) = self.key_cache.proving_key(&burn_zkbin.1)?;
 
         println!("Building transaction parameters");
-        let (params, secrets, spent_coins) = make_transfer_call(
-            keypair, recipient, amount, token_id, owncoins, tree, mint_zkbin, mint_pk, burn_zkbin,
+        let (params, secrets, mut spent_coins) = make_transfer_call(
+            keypair,
+            recipient,
+            recipient_viewing_key,
+            amount,
+            token_id,
+            payment_id,
+            memo,
+            owncoins,
+            selection,
+            tree,
+            &frozen_tokens,
+            mint_zkbin,
+            mint_pk,
+            burn_zkbin,
             burn_pk,
         )?;
 
@@ -114,10 +130,26 @@ impl Drk {
         let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
         let mut tx_builder =
             TransactionBuilder::new(ContractCallLeaf { call, proofs: secrets.proofs }, vec![])?;
+
+        // We first build the transaction without a fee, to find out how
+        // much gas it uses, and then append the fee call paying for it.
         let mut tx = tx_builder.build()?;
         let sigs = tx.create_sigs(&secrets.signature_secrets)?;
         tx.signatures = vec![sigs];
 
+        let (fee_call, fee_proofs, fee_secrets, fee_coin) =
+            self.append_fee_call(&tx, &spent_coins).await?;
+        tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+        spent_coins.push(fee_coin);
+
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&secrets.signature_secrets)?;
+        let fee_sigs = tx.create_sigs(&fee_secrets)?;
+        tx.signatures = vec![sigs, fee_sigs];
+
+        // Remember who we paid, for the funds history
+        self.put_money_counterparty(&tx.hash().to_string(), &recipient).await?;
+
         // We need to mark the coins we've spent in our wallet
         for spent_coin in spent_coins {
             if let Err(e) = self.mark_spent_coin(&spent_coin.coin).await {
@@ -23,11 +23,7 @@ use darkfi::{tx::Transaction, util::encoding::base64, Error, Result};
 use darkfi_sdk::crypto::MONEY_CONTRACT_ID;
 use darkfi_serial::{deserialize_async, serialize_async};
 
-use crate::{
-    convert_named_params,
-    error::{WalletDbError, WalletDbResult},
-    Drk,
-};
+use crate::{convert_named_params, error::WalletDbResult, Drk};
 
 // Wallet SQL table constant names. These have to represent the `wallet.sql`
 // SQL schema. Table names are prefixed with the contract ID to avoid collisions.
@@ -38,28 +34,39 @@ lazy_static! {
 const WALLET_TXS_HISTORY_COL_TX_HASH: &str = "transaction_hash";
 const WALLET_TXS_HISTORY_COL_STATUS: &str = "status";
 const WALLET_TXS_HISTORY_COL_TX: &str = "tx";
+const WALLET_TXS_HISTORY_COL_ACCOUNT: &str = "account";
 
 impl Drk {
-    /// Insert a [`Transaction`] history record into the wallet.
-    pub async fn insert_tx_history_record(&self, tx: &Transaction) -> WalletDbResult<()> {
+    /// Insert a [`Transaction`] history record of the active account into the wallet.
+    pub async fn insert_tx_history_record(&self, tx: &Transaction) -> Result<()> {
         let query = format!(
-            "INSERT INTO {} ({}, {}, {}) VALUES (?1, ?2, ?3);",
+            "INSERT INTO {} ({}, {}, {}, {}) VALUES (?1, ?2, ?3, ?4);",
             *WALLET_TXS_HISTORY_TABLE,
             WALLET_TXS_HISTORY_COL_TX_HASH,
             WALLET_TXS_HISTORY_COL_STATUS,
             WALLET_TXS_HISTORY_COL_TX,
+            WALLET_TXS_HISTORY_COL_ACCOUNT,
         );
         let tx_hash = tx.hash();
-        self.wallet
+        if let Err(e) = self
+            .wallet
             .exec_sql(
                 &query,
                 rusqlite::params![
                     tx_hash.to_string(),
                     "Broadcasted",
                     base64::encode(&serialize_async(tx).await),
+                    self.active_account().await?,
                 ],
             )
             .await
+        {
+            return Err(Error::RusqliteError(format!(
+                "[insert_tx_history_record] Transaction history record insertion failed: {e:?}"
+            )))
+        }
+
+        Ok(())
     }
 
     /// Get a transaction history record.
@@ -113,26 +120,35 @@ impl Drk {
         Ok((tx_hash, status, tx))
     }
 
-    /// Fetch all transactions history records, excluding bytes column.
-    pub async fn get_txs_history(&self) -> WalletDbResult<Vec<(String, String)>> {
-        let rows = self
+    /// Fetch all transactions history records of the active account,
+    /// excluding bytes column.
+    pub async fn get_txs_history(&self) -> Result<Vec<(String, String)>> {
+        let rows = match self
             .wallet
             .query_multiple(
                 &WALLET_TXS_HISTORY_TABLE,
                 &[WALLET_TXS_HISTORY_COL_TX_HASH, WALLET_TXS_HISTORY_COL_STATUS],
-                &[],
+                convert_named_params! {(WALLET_TXS_HISTORY_COL_ACCOUNT, self.active_account().await?)},
             )
-            .await?;
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                return Err(Error::RusqliteError(format!(
+                    "[get_txs_history] Transactions history records retrieval failed: {e:?}"
+                )))
+            }
+        };
 
         let mut ret = Vec::with_capacity(rows.len());
         for row in rows {
             let Value::Text(ref tx_hash) = row[0] else {
-                return Err(WalletDbError::ParseColumnValueError)
+                return Err(Error::ParseFailed("[get_txs_history] Transaction hash parsing failed"))
             };
             let tx_hash = tx_hash.clone();
 
             let Value::Text(ref status) = row[1] else {
-                return Err(WalletDbError::ParseColumnValueError)
+                return Err(Error::ParseFailed("[get_txs_history] Status parsing failed"))
             };
             let status = status.clone();
 
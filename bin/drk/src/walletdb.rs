@@ -16,14 +16,22 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+use argon2::Argon2;
 use log::{debug, error};
+use rand::{rngs::OsRng, RngCore};
 use rusqlite::{
     types::{ToSql, Value},
     Connection,
 };
 use smol::lock::Mutex;
+use zeroize::Zeroizing;
 
 use crate::error::{WalletDbError, WalletDbResult};
 
@@ -33,10 +41,15 @@ pub type WalletPtr = Arc<WalletDb>;
 pub struct WalletDb {
     /// Connection to the SQLite database
     pub conn: Mutex<Connection>,
+    /// Path of the SQLite database, `None` if it is in memory
+    path: Option<PathBuf>,
 }
 
 impl WalletDb {
     /// Create a new wallet database handler. If `path` is `None`, create it in memory.
+    /// If a `password` is provided, the database is encrypted with a key derived
+    /// from it using argon2. The key derivation salt is stored next to the database,
+    /// with a `.salt` extension appended to its file name.
     pub fn new(path: Option<PathBuf>, password: Option<&str>) -> WalletDbResult<WalletPtr> {
         let Ok(conn) = (match path.clone() {
             Some(p) => Connection::open(p),
@@ -46,10 +59,7 @@ impl WalletDb {
         };
 
         if let Some(password) = password {
-            if let Err(e) = conn.pragma_update(None, "key", password) {
-                error!(target: "walletdb::new", "[WalletDb] Pragma update failed: {e}");
-                return Err(WalletDbError::PragmaUpdateError);
-            };
+            Self::unlock(&conn, path.as_deref(), password)?;
         }
         if let Err(e) = conn.pragma_update(None, "foreign_keys", "ON") {
             error!(target: "walletdb::new", "[WalletDb] Pragma update failed: {e}");
@@ -57,7 +67,159 @@ impl WalletDb {
         };
 
         debug!(target: "walletdb::new", "[WalletDb] Opened Sqlite connection at \"{path:?}\"");
-        Ok(Arc::new(Self { conn: Mutex::new(conn) }))
+        Ok(Arc::new(Self { conn: Mutex::new(conn), path }))
+    }
+
+    /// Re-encrypt the database with a key derived from the given password,
+    /// using a new key derivation salt.
+    pub async fn change_password(&self, password: &str) -> WalletDbResult<()> {
+        let conn = self.conn.lock().await;
+        Self::rekey(&conn, self.path.as_deref(), password)
+    }
+
+    /// Key the connection with the given password. Databases without a salt
+    /// file are either new, or were keyed with the password itself by older
+    /// versions, so they get rekeyed with a derived key.
+    fn unlock(conn: &Connection, path: Option<&Path>, password: &str) -> WalletDbResult<()> {
+        let Some(path) = path else { return Self::rekey(conn, None, password) };
+
+        Self::recover_rekey(path, password)?;
+
+        match fs::read(Self::salt_path(path)) {
+            Ok(salt) => {
+                Self::set_key(conn, "key", &Self::derive_key(password, &salt)?)?;
+
+                // The database opened with the old salt, so any new salt
+                // left behind belongs to a rekey that never happened.
+                if let Err(e) = fs::remove_file(Self::new_salt_path(path)) {
+                    if e.kind() != ErrorKind::NotFound {
+                        error!(target: "walletdb::unlock", "[WalletDb] Removing new salt failed: {e}");
+                        return Err(WalletDbError::SaltStorageFailed)
+                    }
+                }
+
+                Ok(())
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Self::set_key(conn, "key", password)?;
+                Self::rekey(conn, Some(path), password)
+            }
+            Err(e) => {
+                error!(target: "walletdb::unlock", "[WalletDb] Reading salt failed: {e}");
+                Err(WalletDbError::SaltStorageFailed)
+            }
+        }
+    }
+
+    /// Finish a rekey interrupted between rekeying the database and replacing
+    /// its salt. If a new salt was left behind and opens the database, it
+    /// replaces the old one. Otherwise the database was never rekeyed, and
+    /// the old salt still applies.
+    fn recover_rekey(path: &Path, password: &str) -> WalletDbResult<()> {
+        let new_salt_path = Self::new_salt_path(path);
+        let new_salt = match fs::read(&new_salt_path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                error!(target: "walletdb::recover_rekey", "[WalletDb] Reading new salt failed: {e}");
+                return Err(WalletDbError::SaltStorageFailed)
+            }
+        };
+
+        // Probe the new salt on its own connection, so a failed attempt
+        // doesn't leave a wrong key on the one we unlock.
+        let Ok(probe) = Connection::open(path) else {
+            return Err(WalletDbError::ConnectionFailed);
+        };
+        if Self::set_key(&probe, "key", &Self::derive_key(password, &new_salt)?).is_err() {
+            return Ok(())
+        }
+        drop(probe);
+
+        debug!(target: "walletdb::recover_rekey", "[WalletDb] Finishing interrupted rekey");
+        if let Err(e) = fs::rename(&new_salt_path, Self::salt_path(path)) {
+            error!(target: "walletdb::recover_rekey", "[WalletDb] Replacing salt failed: {e}");
+            return Err(WalletDbError::SaltStorageFailed)
+        }
+
+        Ok(())
+    }
+
+    /// Encrypt the database with a key derived from the given password and a
+    /// new random salt. The new salt is synced to disk before the database
+    /// gets rekeyed, and only replaces the old one afterwards, so `unlock`
+    /// can always finish an interrupted rekey.
+    fn rekey(conn: &Connection, path: Option<&Path>, password: &str) -> WalletDbResult<()> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let key = Self::derive_key(password, &salt)?;
+
+        let Some(path) = path else { return Self::set_key(conn, "key", &key) };
+
+        let salt_path = Self::salt_path(path);
+        let new_salt_path = Self::new_salt_path(path);
+        let written = fs::File::create(&new_salt_path).and_then(|mut f| {
+            f.write_all(&salt)?;
+            f.sync_all()
+        });
+        if let Err(e) = written {
+            error!(target: "walletdb::rekey", "[WalletDb] Writing salt failed: {e}");
+            return Err(WalletDbError::SaltStorageFailed)
+        }
+
+        Self::set_key(conn, "rekey", &key)?;
+
+        if let Err(e) = fs::rename(&new_salt_path, &salt_path) {
+            error!(target: "walletdb::rekey", "[WalletDb] Replacing salt failed: {e}");
+            return Err(WalletDbError::SaltStorageFailed)
+        }
+
+        Ok(())
+    }
+
+    /// Apply the given `key` or `rekey` pragma, and check the database
+    /// can be read with it.
+    fn set_key(conn: &Connection, pragma: &str, key: &str) -> WalletDbResult<()> {
+        if let Err(e) = conn.pragma_update(None, pragma, key) {
+            error!(target: "walletdb::set_key", "[WalletDb] Pragma update failed: {e}");
+            return Err(WalletDbError::PragmaUpdateError);
+        };
+
+        if conn.query_row("SELECT count(*) FROM sqlite_master;", (), |_| Ok(())).is_err() {
+            return Err(WalletDbError::InvalidPassword)
+        }
+
+        Ok(())
+    }
+
+    /// Derive a raw SQLCipher key from the given password and salt using argon2.
+    /// The returned key is wiped when dropped.
+    fn derive_key(password: &str, salt: &[u8]) -> WalletDbResult<Zeroizing<String>> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        if let Err(e) =
+            Argon2::default().hash_password_into(password.as_bytes(), salt, key.as_mut_slice())
+        {
+            error!(target: "walletdb::derive_key", "[WalletDb] Key derivation failed: {e}");
+            return Err(WalletDbError::KeyDerivationFailed)
+        }
+
+        let hex: Zeroizing<String> =
+            Zeroizing::new(key.iter().map(|b| format!("{b:02x}")).collect());
+        Ok(Zeroizing::new(format!("x'{}'", hex.as_str())))
+    }
+
+    /// Path of the key derivation salt of the database at the given path.
+    fn salt_path(path: &Path) -> PathBuf {
+        let mut salt_path = path.as_os_str().to_owned();
+        salt_path.push(".salt");
+        PathBuf::from(salt_path)
+    }
+
+    /// Path of the new key derivation salt written during a rekey.
+    fn new_salt_path(path: &Path) -> PathBuf {
+        let mut new_salt_path = Self::salt_path(path).into_os_string();
+        new_salt_path.push(".new");
+        PathBuf::from(new_salt_path)
     }
 
     /// This function executes a given SQL query that contains multiple SQL statements,
@@ -283,6 +445,76 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_change_password() {
+        smol::block_on(async {
+            let path = std::env::temp_dir().join(format!("drk_test_{}.db", std::process::id()));
+
+            let wallet = WalletDb::new(Some(path.clone()), Some("foobar")).unwrap();
+            wallet.exec_sql("CREATE TABLE mista ( numba INTEGER );", &[]).await.unwrap();
+            wallet.exec_sql("INSERT INTO mista ( numba ) VALUES ( 42 );", &[]).await.unwrap();
+            drop(wallet);
+
+            assert!(WalletDb::new(Some(path.clone()), Some("barfoo")).is_err());
+
+            let wallet = WalletDb::new(Some(path.clone()), Some("foobar")).unwrap();
+            wallet.change_password("barfoo").await.unwrap();
+            drop(wallet);
+
+            assert!(WalletDb::new(Some(path.clone()), Some("foobar")).is_err());
+
+            let wallet = WalletDb::new(Some(path.clone()), Some("barfoo")).unwrap();
+            let ret = wallet.query_single("mista", &["numba"], &[]).await.unwrap();
+            let numba: i64 = if let Value::Integer(numba) = ret[0] { numba } else { -1 };
+            assert_eq!(numba, 42);
+            drop(wallet);
+
+            let _ = std::fs::remove_file(WalletDb::salt_path(&path));
+            let _ = std::fs::remove_file(path);
+        });
+    }
+
+    #[test]
+    fn test_interrupted_rekey() {
+        smol::block_on(async {
+            let path = std::env::temp_dir().join(format!("drk_rekey_{}.db", std::process::id()));
+            let salt_path = WalletDb::salt_path(&path);
+            let new_salt_path = WalletDb::new_salt_path(&path);
+
+            let wallet = WalletDb::new(Some(path.clone()), Some("foobar")).unwrap();
+            wallet.exec_sql("CREATE TABLE mista ( numba INTEGER );", &[]).await.unwrap();
+            drop(wallet);
+
+            // Interrupted before the database got rekeyed: the new salt
+            // is stale and gets dropped.
+            std::fs::write(&new_salt_path, [0u8; 16]).unwrap();
+            let wallet = WalletDb::new(Some(path.clone()), Some("foobar")).unwrap();
+            assert!(wallet.query_multiple("mista", &[], &[]).await.is_ok());
+            drop(wallet);
+            assert!(!new_salt_path.exists());
+
+            // Interrupted after the database got rekeyed: the new salt
+            // replaces the old one.
+            let wallet = WalletDb::new(Some(path.clone()), Some("foobar")).unwrap();
+            wallet.change_password("barfoo").await.unwrap();
+            drop(wallet);
+            std::fs::rename(&salt_path, &new_salt_path).unwrap();
+            std::fs::write(&salt_path, [0u8; 16]).unwrap();
+
+            // A wrong password leaves both salts in place
+            assert!(WalletDb::new(Some(path.clone()), Some("foobar")).is_err());
+            assert!(new_salt_path.exists());
+
+            let wallet = WalletDb::new(Some(path.clone()), Some("barfoo")).unwrap();
+            assert!(wallet.query_multiple("mista", &[], &[]).await.is_ok());
+            drop(wallet);
+            assert!(!new_salt_path.exists());
+
+            let _ = std::fs::remove_file(salt_path);
+            let _ = std::fs::remove_file(path);
+        });
+    }
+
     #[test]
     fn test_query_single() {
         smol::block_on(async {
@@ -7,3 +7,29 @@ CREATE TABLE IF NOT EXISTS transactions_history (
     status TEXT NOT NULL,
 	tx BLOB NOT NULL
 );
+
+-- Master seed of the hierarchical deterministic key derivation
+CREATE TABLE IF NOT EXISTS hd_seed (
+    seed BLOB NOT NULL
+);
+
+-- BIP39 mnemonic phrase the master seed was created from, if any
+CREATE TABLE IF NOT EXISTS hd_mnemonic (
+    phrase TEXT NOT NULL
+);
+
+-- Next derivation index of each key purpose, per account
+CREATE TABLE IF NOT EXISTS hd_indexes (
+    account INTEGER NOT NULL,
+    purpose INTEGER NOT NULL,
+    next_index INTEGER NOT NULL,
+    PRIMARY KEY (account, purpose)
+);
+
+-- Wallet accounts, each with its own keys, coins and history
+CREATE TABLE IF NOT EXISTS accounts (
+    account_id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT UNIQUE NOT NULL,
+    is_active INTEGER NOT NULL
+);
+INSERT OR IGNORE INTO accounts (account_id, name, is_active) VALUES (0, 'default', 1);
@@ -106,7 +106,7 @@ async fn realmain(settings: Args, executor: Arc<smol::Executor<'static>>) -> Res
     let sled_db = sled::open(datastore_path.clone())?;
     let p2p = P2p::new(settings.net.into(), executor.clone()).await;
     let event_graph =
-        EventGraph::new(p2p.clone(), sled_db.clone(), "genevd_dag", 1, executor.clone()).await?;
+        EventGraph::new(p2p.clone(), sled_db.clone(), "genevd_dag", 1, 0, executor.clone()).await?;
 
     info!("Registering EventGraph P2P protocol");
     let event_graph_ = Arc::clone(&event_graph);
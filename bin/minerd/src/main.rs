@@ -18,7 +18,7 @@
 
 use std::{collections::HashSet, sync::Arc};
 
-use log::{error, info};
+use log::{error, info, warn};
 use smol::{
     channel::{Receiver, Sender},
     lock::Mutex,
@@ -32,6 +32,7 @@ use darkfi::{
     async_daemonize, cli_desc,
     rpc::server::{listen_and_serve, RequestHandler},
     system::{StoppableTask, StoppableTaskPtr},
+    validator::pow::{cpu_features, randomx_benchmark},
     Error, Result,
 };
 
@@ -60,6 +61,14 @@ struct Args {
     /// PoW miner number of threads to use
     threads: usize,
 
+    #[structopt(long)]
+    /// Run a RandomX self-benchmark with the configured threads and exit
+    bench: bool,
+
+    #[structopt(long, default_value = "1000")]
+    /// Number of hashes to compute in each self-benchmark mode
+    bench_hashes: u64,
+
     #[structopt(short, long)]
     /// Set log file to ouput into
     log: Option<String>,
@@ -89,7 +98,21 @@ impl Minerd {
 
 async_daemonize!(realmain);
 async fn realmain(args: Args, ex: Arc<Executor<'static>>) -> Result<()> {
+    if args.bench {
+        println!("Benchmarking RandomX using {} threads...", args.threads);
+        let (threads, hashes) = (args.threads, args.bench_hashes);
+        let results = smol::unblock(move || randomx_benchmark(threads, hashes)).await?;
+        println!("{results}");
+        return Ok(())
+    }
+
     info!(target: "minerd", "Starting DarkFi Mining Daemon...");
+    let features = cpu_features();
+    info!(target: "minerd", "Detected CPU features: {}", features);
+    if !features.aes {
+        warn!(target: "minerd", "No hardware AES support, RandomX will use slower software AES");
+    }
+
     let (sender, recvr) = smol::channel::bounded(1);
     let minerd = Arc::new(Minerd::new(args.threads, sender.clone(), recvr));
 
@@ -31,10 +31,10 @@ use darkfi::{
     },
     system::{sleep, StoppableTaskPtr},
     util::encoding::base64,
-    validator::pow::mine_block,
+    validator::{consensus::MAX_BLOCK_SIZE, pow::mine_block},
 };
 use darkfi_sdk::num_traits::Num;
-use darkfi_serial::{async_trait, deserialize_async};
+use darkfi_serial::{async_trait, deserialize_async_limited};
 
 use crate::{
     error::{server_error, RpcError},
@@ -97,7 +97,9 @@ impl Minerd {
             error!(target: "minerd::rpc", "Failed to parse block bytes");
             return server_error(RpcError::BlockParseError, id, None)
         };
-        let Ok(mut block) = deserialize_async::<BlockInfo>(&block_bytes).await else {
+        let Ok(mut block) =
+            deserialize_async_limited::<BlockInfo>(&block_bytes, MAX_BLOCK_SIZE).await
+        else {
             error!(target: "minerd::rpc", "Failed to parse block");
             return server_error(RpcError::BlockParseError, id, None)
         };
@@ -89,9 +89,12 @@ impl RequestHandler for JsonRpcInterface {
             "deg.switch" => self.deg_switch(req.id, req.params).await,
             "deg.subscribe_events" => return self.deg_subscribe_events(req.id, req.params).await,
             "eventgraph.get_info" => return self.eg_get_info(req.id, req.params).await,
+            "eventgraph.get_state" => return self.eg_get_state(req.id, req.params).await,
+            "eventgraph.get_event" => return self.eg_get_event(req.id, req.params).await,
 
             // TODO: make this optional
             "p2p.get_info" => return self.p2p_get_info(req.id, req.params).await,
+            "p2p.get_hosts" => return self.p2p_get_hosts(req.id, req.params).await,
             _ => return JsonError::new(ErrorCode::MethodNotFound, None, req.id).into(),
         };
 
@@ -234,6 +237,33 @@ impl JsonRpcInterface {
         self.event_graph.eventgraph_info(id, params).await
     }
 
+    // RPCAPI:
+    // Get the EVENTGRAPH state, useful for debugging sync divergence between
+    // nodes: the heads the next event would reference, the DAG depth, the
+    // unreferenced tips and the event count of each layer, along with the
+    // checkpoint heads left by retention pruning.
+    //
+    // --> {"jsonrpc": "2.0", "method": "eventgraph.get_state", "params": [], "id": 42}
+    // <-- {"jsonrpc": "2.0", "result": {"current_genesis": "..", "synced": true, "depth": 12, "n_events": 40, "heads": [..], "unreferenced_tips": {"12": [..]}, "layers": {"0": 1, ..}, "checkpoints": {}}, "id": 42}
+    async fn eg_get_state(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params_ = params.get::<Vec<JsonValue>>().unwrap();
+        if !params_.is_empty() {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        }
+
+        self.event_graph.eventgraph_state(id, params).await
+    }
+
+    // RPCAPI:
+    // Get an EVENTGRAPH event by its ID, along with its parents.
+    // Returns `null` if the event is not in the DAG.
+    //
+    // --> {"jsonrpc": "2.0", "method": "eventgraph.get_event", "params": ["event_id"], "id": 42}
+    // <-- {"jsonrpc": "2.0", "result": {"id": "..", "event": {..}, "parents": [{"id": "..", "event": {..}}]}, "id": 42}
+    async fn eg_get_event(&self, id: u16, params: JsonValue) -> JsonResult {
+        self.event_graph.eventgraph_get_event(id, params).await
+    }
+
     // RPCAPI:
     // Add new task and returns `true` upon success.
     // --> {"jsonrpc": "2.0", "method": "add",
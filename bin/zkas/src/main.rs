@@ -20,14 +20,17 @@ use std::{
     fs::{read_to_string, File},
     io::Write,
     process::ExitCode,
+    str::FromStr,
 };
 
 use arg::Args;
 
 use darkfi::{
-    zkas::{Analyzer, Compiler, Lexer, Parser, ZkBinary},
+    zk::{empty_witnesses, ZkCircuit},
+    zkas::{Analyzer, Compiler, Lexer, Optimizer, Parser, ZkBinary},
     ANSI_LOGO,
 };
+use darkfi_sdk::crypto::{PublicKey, SecretKey};
 
 const ABOUT: &str =
     concat!("zkas ", env!("CARGO_PKG_VERSION"), '\n', env!("CARGO_PKG_DESCRIPTION"));
@@ -40,10 +43,13 @@ Arguments:
 
 Options:
   -o <FILE>  Place the output into <FILE>
+  -k <FILE>  Sign the output with the base58 secret key in <FILE>
   -s         Strip debug symbols
+  -O         Evaluate constant expressions at compile time
   -p         Preprocess only; do not compile
   -i         Interactive semantic analysis
   -e         Examine decoded bytecode
+  -c         Print circuit size and cost statistics
   -h         Print this help
 "#;
 
@@ -56,17 +62,23 @@ fn main() -> ExitCode {
     let mut pflag = false;
     let mut iflag = false;
     let mut eflag = false;
+    let mut cflag = false;
     let mut sflag = false;
+    let mut oflag = false;
     let mut hflag = false;
     let mut output = String::new();
+    let mut keyfile = String::new();
 
     {
         let mut args = Args::new().with_cb(|args, flag| match flag {
             'p' => pflag = true,
             'i' => iflag = true,
             'e' => eflag = true,
+            'c' => cflag = true,
             's' => sflag = true,
+            'O' => oflag = true,
             'o' => output = args.eargf().to_string(),
+            'k' => keyfile = args.eargf().to_string(),
             _ => hflag = true,
         });
 
@@ -87,6 +99,26 @@ fn main() -> ExitCode {
         }
     };
 
+    let author = if keyfile.is_empty() {
+        None
+    } else {
+        let secret = match read_to_string(&keyfile) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("Error: Failed reading from \"{}\". {}", keyfile, e);
+                return ExitCode::FAILURE
+            }
+        };
+
+        match SecretKey::from_str(secret.trim()) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                eprintln!("Error: Invalid secret key in \"{}\". {}", keyfile, e);
+                return ExitCode::FAILURE
+            }
+        }
+    };
+
     // Clean up tabs, and convert CRLF to LF.
     let source = source.replace('\t', "    ").replace("\r\n", "\n");
 
@@ -120,6 +152,14 @@ fn main() -> ExitCode {
         return ExitCode::FAILURE
     }
 
+    // The optimizer evaluates constant expressions and merges repeated
+    // constants, producing a smaller circuit.
+    if oflag {
+        let (statements, literals) = Optimizer::new(analyzer.statements).optimize();
+        analyzer.statements = statements;
+        analyzer.literals = literals;
+    }
+
     if pflag {
         println!("{:#?}", analyzer.constants);
         println!("{:#?}", analyzer.witnesses);
@@ -128,7 +168,7 @@ fn main() -> ExitCode {
         return ExitCode::SUCCESS
     }
 
-    let compiler = Compiler::new(
+    let mut compiler = Compiler::new(
         filename,
         source.chars(),
         namespace,
@@ -140,6 +180,12 @@ fn main() -> ExitCode {
         !sflag,
     );
 
+    // The binary always carries its hash, and the author's signature of
+    // it when a key was given.
+    if let Some(secret) = author {
+        compiler = compiler.with_author(secret);
+    }
+
     let bincode = match compiler.compile() {
         Ok(v) => v,
         Err(_) => return ExitCode::FAILURE,
@@ -163,10 +209,26 @@ fn main() -> ExitCode {
 
     println!("Wrote output to {}", &output);
 
+    if let Some(secret) = author {
+        println!("Signed by {}", PublicKey::from_secret(secret));
+    }
+
     if eflag {
         let zkbin = ZkBinary::decode(&bincode).unwrap();
         println!("{:#?}", zkbin);
     }
 
+    if cflag {
+        let zkbin = ZkBinary::decode(&bincode).unwrap();
+        let circuit = ZkCircuit::new(empty_witnesses(&zkbin).unwrap(), &zkbin);
+        match circuit.stats() {
+            Ok(stats) => println!("{}", stats),
+            Err(e) => {
+                eprintln!("Error: Failed laying out the circuit. {}", e);
+                return ExitCode::FAILURE
+            }
+        }
+    }
+
     ExitCode::SUCCESS
 }
@@ -61,7 +61,7 @@ wait_tokens() {
 }
 
 mint_dao() {
-    $DRK dao create 20 10 0.67 MLDY > /tmp/dao.dat
+    $DRK dao create 20 10 0.67 MLDY --early-exec-quorum 15 > /tmp/dao.dat
     $DRK dao import MiladyMakerDAO < /tmp/dao.dat
     $DRK dao list
     $DRK dao list MiladyMakerDAO
@@ -507,6 +507,26 @@ impl BlockStore {
         Ok(ret)
     }
 
+    /// Fetch up to n `(order number, hash)` records strictly after given
+    /// order number, or from the start of the `BlockOrderStore` if `None`.
+    /// Used to page through the chain without loading all of it.
+    pub fn get_order_page(&self, after: Option<u64>, n: usize) -> Result<Vec<(u64, HeaderHash)>> {
+        let iter = match after {
+            Some(number) => match number.checked_add(1) {
+                Some(start) => self.order.range(start.to_be_bytes()..),
+                None => return Ok(vec![]),
+            },
+            None => self.order.iter(),
+        };
+
+        let mut ret = Vec::with_capacity(n);
+        for record in iter.take(n) {
+            ret.push(parse_u64_key_record(record?)?);
+        }
+
+        Ok(ret)
+    }
+
     /// Fetch the first block hash in the order tree, based on the `Ord`
     /// implementation for `Vec<u8>`.
     pub fn get_first(&self) -> Result<(u64, HeaderHash)> {
@@ -542,11 +562,12 @@ impl BlockStore {
         // Build an iterator to retrieve last N records
         let records = self.difficulty.iter().rev().take(n);
         // Since the iterator grabs in right -> left order,
-        // we deserialize found records, and push them in reverse order
-        let mut last_n = vec![];
+        // we deserialize found records, and reverse them at the end
+        let mut last_n = Vec::with_capacity(n);
         for record in records {
-            last_n.insert(0, deserialize(&record?.1)?);
+            last_n.push(deserialize(&record?.1)?);
         }
+        last_n.reverse();
 
         Ok(last_n)
     }
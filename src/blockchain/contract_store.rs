@@ -19,7 +19,7 @@ r* This program is distributed in the hope that it will be useful,
 use std::io::Cursor;
 
 use darkfi_sdk::crypto::ContractId;
-use darkfi_serial::{deserialize, serialize};
+use darkfi_serial::{deserialize, serialize, SerialDecodable, SerialEncodable};
 use log::{debug, error};
 
 use crate::{
@@ -32,10 +32,143 @@ use super::{parse_record, SledDbOverlayPtr};
 
 const SLED_CONTRACTS_TREE: &[u8] = b"_contracts";
 const SLED_BINCODE_TREE: &[u8] = b"_wasm_bincode";
+const SLED_ZKAS_VERSIONS_TREE: &[u8] = b"_zkas_versions";
+const SLED_GAS_SCHEDULES_TREE: &[u8] = b"_gas_schedules";
 
 /// The hardcoded db name for the zkas circuits database tree
 pub const SMART_CONTRACT_ZKAS_DB_NAME: &str = "_zkas";
 
+/// A deployed version of a contract's zkas circuit
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct ZkasVersion {
+    /// Version number, starting from 1
+    pub version: u32,
+    /// Block height the version was deployed at
+    pub height: u64,
+    /// Compiled zkas binary
+    pub zkbin: Vec<u8>,
+    /// Serialized `VerifyingKey` of the circuit
+    pub vk: Vec<u8>,
+}
+
+/// Gas costs of WASM opcodes, by opcode class
+#[derive(Clone, Copy, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct OpcodeCosts {
+    /// Cost of opcodes not in any of the classes below
+    pub default: u64,
+    /// Cost of function calls
+    pub call: u64,
+    /// Cost of memory loads, stores, copies and fills
+    pub memory: u64,
+    /// Cost of growing the linear memory
+    pub memory_grow: u64,
+}
+
+/// Gas costs charged by the runtime host functions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct HostCosts {
+    /// Cost per byte read from or written to the guest memory and databases
+    pub byte: u64,
+    /// Cost per opcode, witness and literal of deployed zkas circuits
+    pub zkas_item: u64,
+    /// Fixed cost of verifying a VRF proof
+    pub vrf_verify: u64,
+}
+
+/// A versioned table of the WASM runtime gas costs, active from its
+/// activation height until the next version's. Version 1 is built in
+/// and active since genesis, further versions are stored on-chain, so
+/// old blocks always re-verify against the costs active at their height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct GasSchedule {
+    /// Version number, starting from 1
+    pub version: u32,
+    /// Block height the version activates at
+    pub height: u64,
+    /// WASM opcode costs
+    pub opcodes: OpcodeCosts,
+    /// Host function costs
+    pub host: HostCosts,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            height: 0,
+            opcodes: OpcodeCosts { default: 1, call: 1, memory: 1, memory_grow: 1 },
+            host: HostCosts { byte: 1, zkas_item: 100, vrf_verify: 10_000 },
+        }
+    }
+}
+
+/// Gas schedule upgrades hard-forked into the protocol, oldest first.
+/// Every node writes these into its chain state on startup, so all nodes
+/// price contract execution the same way. A new upgrade must be the next
+/// version and activate above the chain tip of every node at release.
+pub const GAS_SCHEDULE_UPGRADES: &[GasSchedule] = &[];
+
+/// Check that `schedule` may be stored as the upgrade following
+/// `previous`, given the current chain tip height. An upgrade activating
+/// at or below the tip would retroactively reprice verified blocks.
+fn validate_gas_schedule_upgrade(
+    previous: &GasSchedule,
+    schedule: &GasSchedule,
+    tip_height: Option<u64>,
+) -> Result<()> {
+    if schedule.version != previous.version + 1 || schedule.height <= previous.height {
+        return Err(Error::InvalidGasSchedule(format!(
+            "Version {} must follow version {} at a later height",
+            schedule.version, previous.version
+        )))
+    }
+
+    if let Some(tip_height) = tip_height {
+        if schedule.height <= tip_height {
+            return Err(Error::InvalidGasSchedule(format!(
+                "Version {} activates at height {}, not above the chain tip {}",
+                schedule.version, schedule.height, tip_height
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick the gas schedule active at the given block height, falling back
+/// to the built-in one.
+fn gas_schedule_at(schedules: Vec<GasSchedule>, height: u64) -> GasSchedule {
+    schedules.into_iter().rev().find(|s| s.height <= height).unwrap_or_default()
+}
+
+/// Key of a zkas circuit in the versions tree
+fn zkas_versions_key(contract_id: &ContractId, zkas_ns: &str) -> Vec<u8> {
+    serialize(&(*contract_id, zkas_ns.to_string()))
+}
+
+/// Decode a zkas binary and read its `VerifyingKey`.
+/// If anything in this function panics, that means corrupted data managed
+/// to get into the sled trees. This should not be possible.
+fn decode_zkas(zkbin: &[u8], vkbin: Vec<u8>) -> (ZkBinary, VerifyingKey) {
+    let zkbin = ZkBinary::decode(zkbin).unwrap();
+
+    // Construct the circuit to be able to read the VerifyingKey
+    let circuit = ZkCircuit::new(empty_witnesses(&zkbin).unwrap(), &zkbin);
+
+    let mut vk_buf = Cursor::new(vkbin);
+    let vk = VerifyingKey::read::<Cursor<Vec<u8>>, ZkCircuit>(&mut vk_buf, circuit).unwrap();
+
+    (zkbin, vk)
+}
+
+/// Pick the version of a zkas circuit active at the given block height
+fn zkas_version_at(versions: Vec<ZkasVersion>, height: u64) -> Result<ZkasVersion> {
+    match versions.into_iter().rev().find(|v| v.height <= height) {
+        Some(version) => Ok(version),
+        None => Err(Error::ZkasVersionNotFound(height)),
+    }
+}
+
 /// The `ContractStore` is a structure representing all `sled` trees related
 /// to storing the blockchain's contracts information.
 #[derive(Clone)]
@@ -57,6 +190,25 @@ pub struct ContractStore {
     /// ```
     /// These values get mutated with `init()` and `remove()`.
     pub state: sled::Tree,
+    /// The `sled` tree storing every deployed version of the contracts'
+    /// zkas circuits, so superseded verifying keys remain available for
+    /// verifying historical blocks.
+    /// The layout looks like this:
+    /// ```plaintext
+    ///  tree: "_zkas_versions"
+    ///   key: (ContractId, zkas_ns)
+    /// value: Vec<ZkasVersion>
+    /// ```
+    pub zkas_versions: sled::Tree,
+    /// The `sled` tree storing the gas schedule upgrades, part of the
+    /// system (contract zero) state rather than any contract's.
+    /// The layout looks like this:
+    /// ```plaintext
+    ///  tree: "_gas_schedules"
+    ///   key: u32 (big-endian version)
+    /// value: GasSchedule
+    /// ```
+    pub gas_schedules: sled::Tree,
 }
 
 impl ContractStore {
@@ -64,7 +216,9 @@ impl ContractStore {
     pub fn new(db: &sled::Db) -> Result<Self> {
         let wasm = db.open_tree(SLED_BINCODE_TREE)?;
         let state = db.open_tree(SLED_CONTRACTS_TREE)?;
-        Ok(Self { wasm, state })
+        let zkas_versions = db.open_tree(SLED_ZKAS_VERSIONS_TREE)?;
+        let gas_schedules = db.open_tree(SLED_GAS_SCHEDULES_TREE)?;
+        Ok(Self { wasm, state, zkas_versions, gas_schedules })
     }
 
     /// Fetches the bincode for a given ContractId from the store's wasm tree.
@@ -164,21 +318,32 @@ impl ContractStore {
             return Err(Error::ZkasBincodeNotFound)
         };
 
-        // If anything in this function panics, that means corrupted data managed
-        // to get into this sled tree. This should not be possible.
+        // The first vec is the compiled zkas binary, and the second one
+        // is the serialized VerifyingKey for it.
         let (zkbin, vkbin): (Vec<u8>, Vec<u8>) = deserialize(&zkas_bytes).unwrap();
+        Ok(decode_zkas(&zkbin, vkbin))
+    }
 
-        // The first vec is the compiled zkas binary
-        let zkbin = ZkBinary::decode(&zkbin).unwrap();
-
-        // Construct the circuit to be able to read the VerifyingKey
-        let circuit = ZkCircuit::new(empty_witnesses(&zkbin).unwrap(), &zkbin);
+    /// Fetch all deployed versions of a contract's zkas circuit, oldest first.
+    pub fn get_zkas_versions(
+        &self,
+        contract_id: &ContractId,
+        zkas_ns: &str,
+    ) -> Result<Vec<ZkasVersion>> {
+        match self.zkas_versions.get(zkas_versions_key(contract_id, zkas_ns))? {
+            Some(bytes) => Ok(deserialize(&bytes)?),
+            None => Ok(vec![]),
+        }
+    }
 
-        // The second one is the serialized VerifyingKey for it
-        let mut vk_buf = Cursor::new(vkbin);
-        let vk = VerifyingKey::read::<Cursor<Vec<u8>>, ZkCircuit>(&mut vk_buf, circuit).unwrap();
+    /// Fetch all stored gas schedule upgrades, oldest first.
+    pub fn get_gas_schedules(&self) -> Result<Vec<GasSchedule>> {
+        let mut schedules = vec![];
+        for record in self.gas_schedules.iter() {
+            schedules.push(deserialize(&record?.1)?);
+        }
 
-        Ok((zkbin, vk))
+        Ok(schedules)
     }
 
     /// Retrieve all wasm bincodes from the store's wasm tree in the form
@@ -217,6 +382,8 @@ impl ContractStoreOverlay {
     pub fn new(overlay: &SledDbOverlayPtr) -> Result<Self> {
         overlay.lock().unwrap().open_tree(SLED_BINCODE_TREE)?;
         overlay.lock().unwrap().open_tree(SLED_CONTRACTS_TREE)?;
+        overlay.lock().unwrap().open_tree(SLED_ZKAS_VERSIONS_TREE)?;
+        overlay.lock().unwrap().open_tree(SLED_GAS_SCHEDULES_TREE)?;
         Ok(Self(overlay.clone()))
     }
 
@@ -330,20 +497,168 @@ impl ContractStoreOverlay {
             return Err(Error::ZkasBincodeNotFound)
         };
 
-        // If anything in this function panics, that means corrupted data managed
-        // to get into this sled tree. This should not be possible.
+        // The first vec is the compiled zkas binary, and the second one
+        // is the serialized VerifyingKey for it.
         let (zkbin, vkbin): (Vec<u8>, Vec<u8>) = deserialize(&zkas_bytes).unwrap();
+        Ok(decode_zkas(&zkbin, vkbin))
+    }
+
+    /// Fetch all deployed versions of a contract's zkas circuit, oldest first.
+    pub fn get_zkas_versions(
+        &self,
+        contract_id: &ContractId,
+        zkas_ns: &str,
+    ) -> Result<Vec<ZkasVersion>> {
+        let key = zkas_versions_key(contract_id, zkas_ns);
+        match self.0.lock().unwrap().get(SLED_ZKAS_VERSIONS_TREE, &key)? {
+            Some(bytes) => Ok(deserialize(&bytes)?),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Fetch the `ZkBinary` and `VerifyingKey` of a contract's zkas circuit
+    /// version that was active at the given block height. Circuits deployed
+    /// before versions were tracked have no version records, so the current
+    /// one is returned for them. Returns an error if the circuit wasn't yet
+    /// deployed at that height.
+    pub fn get_zkas_at(
+        &self,
+        contract_id: &ContractId,
+        zkas_ns: &str,
+        height: u64,
+    ) -> Result<(ZkBinary, VerifyingKey)> {
+        let versions = self.get_zkas_versions(contract_id, zkas_ns)?;
+        if versions.is_empty() {
+            return self.get_zkas(contract_id, zkas_ns)
+        }
+
+        let version = zkas_version_at(versions, height)?;
+        debug!(target: "blockchain::contractstore", "Using version {} of \"{}:{}\" zkas circuit", version.version, contract_id, zkas_ns);
+        Ok(decode_zkas(&version.zkbin, version.vk))
+    }
+
+    /// Record a new version of a contract's zkas circuit, deployed at the
+    /// given block height. Returns the new version number.
+    pub fn insert_zkas_version(
+        &self,
+        contract_id: &ContractId,
+        zkas_ns: &str,
+        height: u64,
+        zkbin: Vec<u8>,
+        vk: Vec<u8>,
+    ) -> Result<u32> {
+        let mut versions = self.get_zkas_versions(contract_id, zkas_ns)?;
+
+        // Circuits deployed before versions were tracked become the first
+        // version, valid since genesis.
+        if versions.is_empty() {
+            let zkas_tree = self.lookup(contract_id, SMART_CONTRACT_ZKAS_DB_NAME)?;
+            if let Some(bytes) = self.0.lock().unwrap().get(&zkas_tree, &serialize(&zkas_ns))? {
+                let (zkbin, vk): (Vec<u8>, Vec<u8>) = deserialize(&bytes)?;
+                versions.push(ZkasVersion { version: 1, height: 0, zkbin, vk });
+            }
+        }
+
+        let version = versions.last().map_or(1, |v| v.version + 1);
+        versions.push(ZkasVersion { version, height, zkbin, vk });
 
-        // The first vec is the compiled zkas binary
-        let zkbin = ZkBinary::decode(&zkbin).unwrap();
+        let key = zkas_versions_key(contract_id, zkas_ns);
+        self.0.lock().unwrap().insert(SLED_ZKAS_VERSIONS_TREE, &key, &serialize(&versions))?;
 
-        // Construct the circuit to be able to read the VerifyingKey
-        let circuit = ZkCircuit::new(empty_witnesses(&zkbin).unwrap(), &zkbin);
+        Ok(version)
+    }
 
-        // The second one is the serialized VerifyingKey for it
-        let mut vk_buf = Cursor::new(vkbin);
-        let vk = VerifyingKey::read::<Cursor<Vec<u8>>, ZkCircuit>(&mut vk_buf, circuit).unwrap();
+    /// Fetch all stored gas schedule upgrades, oldest first.
+    pub fn get_gas_schedules(&self) -> Result<Vec<GasSchedule>> {
+        let mut schedules = vec![];
+        for version in 2.. {
+            let key = u32::to_be_bytes(version);
+            let Some(bytes) = self.0.lock().unwrap().get(SLED_GAS_SCHEDULES_TREE, &key)? else {
+                break
+            };
+            schedules.push(deserialize(&bytes)?);
+        }
 
-        Ok((zkbin, vk))
+        Ok(schedules)
+    }
+
+    /// Fetch the gas schedule active at the given block height.
+    pub fn get_gas_schedule_at(&self, height: u64) -> Result<GasSchedule> {
+        Ok(gas_schedule_at(self.get_gas_schedules()?, height))
+    }
+
+    /// Store a gas schedule upgrade. It must be the next version after the
+    /// stored ones, activating above the given chain tip height. Already
+    /// stored versions are accepted if unchanged, so the hard-forked
+    /// upgrades can be reapplied on every start.
+    pub fn insert_gas_schedule(
+        &self,
+        schedule: &GasSchedule,
+        tip_height: Option<u64>,
+    ) -> Result<()> {
+        let schedules = self.get_gas_schedules()?;
+        if let Some(stored) = schedules.iter().find(|s| s.version == schedule.version) {
+            if stored != schedule {
+                return Err(Error::InvalidGasSchedule(format!(
+                    "Version {} conflicts with the stored one",
+                    schedule.version
+                )))
+            }
+            return Ok(())
+        }
+
+        let previous = schedules.last().copied().unwrap_or_default();
+        validate_gas_schedule_upgrade(&previous, schedule, tip_height)?;
+
+        let key = u32::to_be_bytes(schedule.version);
+        self.0.lock().unwrap().insert(SLED_GAS_SCHEDULES_TREE, &key, &serialize(schedule))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zkas_version_selection() {
+        let versions: Vec<ZkasVersion> = [(1, 0), (2, 10), (3, 20)]
+            .into_iter()
+            .map(|(version, height)| ZkasVersion { version, height, zkbin: vec![], vk: vec![] })
+            .collect();
+
+        assert_eq!(zkas_version_at(versions.clone(), 0).unwrap().version, 1);
+        assert_eq!(zkas_version_at(versions.clone(), 9).unwrap().version, 1);
+        assert_eq!(zkas_version_at(versions.clone(), 10).unwrap().version, 2);
+        assert_eq!(zkas_version_at(versions.clone(), 100).unwrap().version, 3);
+        assert!(zkas_version_at(versions[1..].to_vec(), 5).is_err());
+    }
+
+    #[test]
+    fn gas_schedule_selection() {
+        let v1 = GasSchedule::default();
+        let v2 = GasSchedule {
+            version: 2,
+            height: 10,
+            opcodes: OpcodeCosts { call: 5, ..v1.opcodes },
+            ..v1
+        };
+        let v3 =
+            GasSchedule { version: 3, height: 20, host: HostCosts { byte: 2, ..v1.host }, ..v2 };
+        let schedules = vec![v2, v3];
+
+        assert_eq!(gas_schedule_at(schedules.clone(), 0), GasSchedule::default());
+        assert_eq!(gas_schedule_at(schedules.clone(), 9).version, 1);
+        assert_eq!(gas_schedule_at(schedules.clone(), 10).version, 2);
+        assert_eq!(gas_schedule_at(schedules, 100).version, 3);
+
+        assert!(validate_gas_schedule_upgrade(&v1, &v2, None).is_ok());
+        assert!(validate_gas_schedule_upgrade(&v1, &v2, Some(9)).is_ok());
+        assert!(validate_gas_schedule_upgrade(&v1, &v2, Some(10)).is_err());
+        assert!(validate_gas_schedule_upgrade(&v1, &v3, None).is_err());
+        assert!(
+            validate_gas_schedule_upgrade(&v2, &GasSchedule { height: 10, ..v3 }, None).is_err()
+        );
     }
 }
@@ -16,9 +16,11 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::fmt;
+use std::{fmt, str::FromStr};
 
-use darkfi_sdk::{blockchain::block_version, crypto::MerkleTree, AsHex};
+use darkfi_sdk::{
+    blockchain::block_version, crypto::MerkleTree, hex::decode_hex_arr, AsHex, ContractError,
+};
 
 #[cfg(feature = "async-serial")]
 use darkfi_serial::async_trait;
@@ -47,12 +49,24 @@ impl HeaderHash {
     }
 }
 
+impl FromStr for HeaderHash {
+    type Err = ContractError;
+
+    fn from_str(header_hash_str: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(Self(decode_hex_arr(header_hash_str)?))
+    }
+}
+
 impl fmt::Display for HeaderHash {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0.hex())
     }
 }
 
+/// Offset of the nonce in both the serialized [`Header`] and its
+/// hashing blob: `version (1) || previous (32) || height (8) || timestamp (8)`.
+pub const HEADER_NONCE_OFFSET: usize = 49;
+
 /// This struct represents a tuple of the form (version, previous, height, timestamp, nonce, merkle_tree).
 #[derive(Debug, Clone, PartialEq, Eq, SerialEncodable, SerialDecodable)]
 pub struct Header {
@@ -84,15 +98,29 @@ impl Header {
         // Blake3 hasher .update() method never fails.
         // This call returns a Result due to how the Write trait is specified.
         // Calling unwrap() here should be safe.
-        self.version.encode(&mut hasher).expect("blake3 hasher");
-        self.previous.encode(&mut hasher).expect("blake3 hasher");
-        self.height.encode(&mut hasher).expect("blake3 hasher");
-        self.timestamp.encode(&mut hasher).expect("blake3 hasher");
-        self.nonce.encode(&mut hasher).expect("blake3 hasher");
-        self.tree.root(0).unwrap().encode(&mut hasher).expect("blake3 hasher");
+        self.encode_hashed_fields(&mut hasher).expect("blake3 hasher");
 
         HeaderHash(hasher.finalize().into())
     }
+
+    /// Serialize the fields the header's hash is computed over, so external
+    /// miners can set the nonce in place and hash the blob themselves.
+    /// The nonce is located at [`HEADER_NONCE_OFFSET`].
+    pub fn hashing_blob(&self) -> Vec<u8> {
+        let mut blob = vec![];
+        self.encode_hashed_fields(&mut blob).expect("vec writer");
+        blob
+    }
+
+    fn encode_hashed_fields<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        self.version.encode(&mut *w)?;
+        self.previous.encode(&mut *w)?;
+        self.height.encode(&mut *w)?;
+        self.timestamp.encode(&mut *w)?;
+        self.nonce.encode(&mut *w)?;
+        self.tree.root(0).unwrap().encode(&mut *w)?;
+        Ok(())
+    }
 }
 
 impl Default for Header {
@@ -32,7 +32,7 @@ pub use block_store::{Block, BlockDifficulty, BlockInfo, BlockStore, BlockStoreO
 
 /// Header definition and storage implementation
 pub mod header_store;
-pub use header_store::{Header, HeaderHash, HeaderStore, HeaderStoreOverlay};
+pub use header_store::{Header, HeaderHash, HeaderStore, HeaderStoreOverlay, HEADER_NONCE_OFFSET};
 
 /// Transactions related storage implementations
 pub mod tx_store;
@@ -241,10 +241,16 @@ impl Blockchain {
     pub fn add_pending_txs(&self, txs: &[Transaction]) -> Result<Vec<TransactionHash>> {
         let (txs_batch, txs_hashes) = self.transactions.insert_batch_pending(txs);
         let txs_order_batch = self.transactions.insert_batch_pending_order(&txs_hashes)?;
+        let txs_received_batch =
+            self.transactions.insert_batch_pending_received(&txs_hashes, Timestamp::current_time());
 
         // Perform an atomic transaction over the trees and apply the batches.
-        let trees = [self.transactions.pending.clone(), self.transactions.pending_order.clone()];
-        let batches = [txs_batch, txs_order_batch];
+        let trees = [
+            self.transactions.pending.clone(),
+            self.transactions.pending_order.clone(),
+            self.transactions.pending_received.clone(),
+        ];
+        let batches = [txs_batch, txs_order_batch, txs_received_batch];
         self.atomic_write(&trees, &batches)?;
 
         Ok(txs_hashes)
@@ -282,10 +288,15 @@ impl Blockchain {
 
         let txs_batch = self.transactions.remove_batch_pending(&txs_hashes);
         let txs_order_batch = self.transactions.remove_batch_pending_order(&removed_indexes);
+        let txs_received_batch = self.transactions.remove_batch_pending_received(&txs_hashes);
 
         // Perform an atomic transaction over the trees and apply the batches.
-        let trees = [self.transactions.pending.clone(), self.transactions.pending_order.clone()];
-        let batches = [txs_batch, txs_order_batch];
+        let trees = [
+            self.transactions.pending.clone(),
+            self.transactions.pending_order.clone(),
+            self.transactions.pending_received.clone(),
+        ];
+        let batches = [txs_batch, txs_order_batch, txs_received_batch];
         self.atomic_write(&trees, &batches)?;
 
         Ok(())
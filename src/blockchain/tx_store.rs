@@ -21,7 +21,7 @@ use std::collections::HashMap;
 use darkfi_sdk::tx::TransactionHash;
 use darkfi_serial::{deserialize, serialize};
 
-use crate::{tx::Transaction, Error, Result};
+use crate::{tx::Transaction, util::time::Timestamp, Error, Result};
 
 use super::{parse_record, parse_u64_key_record, SledDbOverlayPtr};
 
@@ -29,6 +29,7 @@ const SLED_TX_TREE: &[u8] = b"_transactions";
 const SLED_TX_LOCATION_TREE: &[u8] = b"_transaction_location";
 const SLED_PENDING_TX_TREE: &[u8] = b"_pending_transactions";
 const SLED_PENDING_TX_ORDER_TREE: &[u8] = b"_pending_transactions_order";
+const SLED_PENDING_TX_RECEIVED_TREE: &[u8] = b"_pending_transactions_received";
 
 /// The `TxStore` is a structure representing all `sled` trees related
 /// to storing the blockchain's transactions information.
@@ -51,6 +52,10 @@ pub struct TxStore {
     /// where the key is an incremental value, and the value is the serialized
     /// transaction.
     pub pending_order: sled::Tree,
+    /// The `sled` tree storing the time the node received each of its pending
+    /// transactions, where the key is the transaction hash, and the value is
+    /// the serialized [`Timestamp`].
+    pub pending_received: sled::Tree,
 }
 
 impl TxStore {
@@ -60,7 +65,8 @@ impl TxStore {
         let location = db.open_tree(SLED_TX_LOCATION_TREE)?;
         let pending = db.open_tree(SLED_PENDING_TX_TREE)?;
         let pending_order = db.open_tree(SLED_PENDING_TX_ORDER_TREE)?;
-        Ok(Self { main, location, pending, pending_order })
+        let pending_received = db.open_tree(SLED_PENDING_TX_RECEIVED_TREE)?;
+        Ok(Self { main, location, pending, pending_order, pending_received })
     }
 
     /// Insert a slice of [`Transaction`] into the store's main tree.
@@ -178,6 +184,23 @@ impl TxStore {
         Ok(batch)
     }
 
+    /// Generate the sled batch corresponding to an insert to the pending txs
+    /// received tree, so caller can handle the write operation.
+    pub fn insert_batch_pending_received(
+        &self,
+        tx_hashes: &[TransactionHash],
+        received: Timestamp,
+    ) -> sled::Batch {
+        let mut batch = sled::Batch::default();
+        let serialized = serialize(&received);
+
+        for tx_hash in tx_hashes {
+            batch.insert(tx_hash.inner(), serialized.clone());
+        }
+
+        batch
+    }
+
     /// Check if the store's main tree contains a given transaction hash.
     pub fn contains(&self, tx_hash: &TransactionHash) -> Result<bool> {
         Ok(self.main.contains_key(tx_hash.inner())?)
@@ -295,6 +318,15 @@ impl TxStore {
         Ok(locations)
     }
 
+    /// Fetch the time the given pending transaction was received, if it exists
+    /// in the store's pending txs received tree.
+    pub fn get_pending_received(&self, tx_hash: &TransactionHash) -> Result<Option<Timestamp>> {
+        match self.pending_received.get(tx_hash.inner())? {
+            Some(found) => Ok(Some(deserialize(&found)?)),
+            None => Ok(None),
+        }
+    }
+
     /// Retrieve all transactions from the store's pending txs tree in the
     /// form of a HashMap with key the transaction hash and value the
     /// transaction itself.
@@ -323,6 +355,30 @@ impl TxStore {
         Ok(txs)
     }
 
+    /// Retrieve up to n records from the store's pending txs order tree,
+    /// strictly after given index, or from its start if `None`, in the
+    /// form of a tuple (`u64`, `TransactionHash`).
+    pub fn get_pending_order_page(
+        &self,
+        after: Option<u64>,
+        n: usize,
+    ) -> Result<Vec<(u64, TransactionHash)>> {
+        let iter = match after {
+            Some(index) => match index.checked_add(1) {
+                Some(start) => self.pending_order.range(start.to_be_bytes()..),
+                None => return Ok(vec![]),
+            },
+            None => self.pending_order.iter(),
+        };
+
+        let mut txs = Vec::with_capacity(n);
+        for record in iter.take(n) {
+            txs.push(parse_u64_key_record(record?)?);
+        }
+
+        Ok(txs)
+    }
+
     /// Retrieve records count of the store's main tree.
     pub fn len(&self) -> usize {
         self.main.len()
@@ -359,6 +415,18 @@ impl TxStore {
         batch
     }
 
+    /// Generate the sled batch corresponding to a remove from the store's pending
+    /// txs received tree, so caller can handle the write operation.
+    pub fn remove_batch_pending_received(&self, txs_hashes: &[TransactionHash]) -> sled::Batch {
+        let mut batch = sled::Batch::default();
+
+        for tx_hash in txs_hashes {
+            batch.remove(tx_hash.inner());
+        }
+
+        batch
+    }
+
     /// Generate the sled batch corresponding to a remove from the store's pending
     /// txs order tree, so caller can handle the write operation.
     pub fn remove_batch_pending_order(&self, indexes: &[u64]) -> sled::Batch {
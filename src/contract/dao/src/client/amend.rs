@@ -0,0 +1,114 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use darkfi_sdk::{crypto::DAO_CONTRACT_ID, pasta::pallas};
+use darkfi_serial::serialize;
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use crate::{
+    model::{Dao, DaoAmendParams, DaoAuthCall, DaoProposal, VecAuthCallCommit},
+    DaoFunction,
+};
+
+/// Create the proposal auth call amending a DAO's parameters to the
+/// ones of `new_dao`.
+pub fn make_amend_auth_call(new_dao: &Dao) -> DaoAuthCall {
+    DaoAuthCall {
+        contract_id: *DAO_CONTRACT_ID,
+        function_code: DaoFunction::Amend as u8,
+        auth_data: serialize(&new_dao.to_bulla()),
+    }
+}
+
+pub struct DaoAmendCall {
+    pub proposal: DaoProposal,
+    pub dao: Dao,
+    pub new_dao: Dao,
+}
+
+impl DaoAmendCall {
+    pub fn make(
+        self,
+        amend_zkbin: &ZkBinary,
+        amend_pk: &ProvingKey,
+    ) -> Result<(DaoAmendParams, Vec<Proof>)> {
+        // Only the voting parameters and guardian can be amended
+        assert_eq!(self.dao.gov_token_id, self.new_dao.gov_token_id);
+        assert_eq!(self.dao.public_key, self.new_dao.public_key);
+
+        let old_dao_bulla = self.dao.to_bulla();
+        assert_eq!(old_dao_bulla, self.proposal.dao_bulla);
+        let new_dao_bulla = self.new_dao.to_bulla();
+
+        let (dao_pub_x, dao_pub_y) = self.dao.public_key.xy();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
+        let (new_dao_guardian_x, new_dao_guardian_y) = self.new_dao.guardian_xy();
+
+        let prover_witnesses = vec![
+            // proposal params
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
+            Witness::Base(Value::known(self.proposal.user_data)),
+            Witness::Base(Value::known(self.proposal.blind.inner())),
+            // DAO params
+            Witness::Base(Value::known(pallas::Base::from(self.dao.proposer_limit))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.proposal_deposit))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.early_exec_quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.approval_ratio_quot))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.approval_ratio_base))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.vote_weight.mode()))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.vote_weight.cap()))),
+            Witness::Base(Value::known(self.dao.gov_token_id.inner())),
+            Witness::Base(Value::known(dao_pub_x)),
+            Witness::Base(Value::known(dao_pub_y)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
+            Witness::Base(Value::known(self.dao.bulla_blind.inner())),
+            // Amended DAO params
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.proposer_limit))),
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.proposal_deposit))),
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.early_exec_quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.approval_ratio_quot))),
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.approval_ratio_base))),
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.vote_weight.mode()))),
+            Witness::Base(Value::known(pallas::Base::from(self.new_dao.vote_weight.cap()))),
+            Witness::Base(Value::known(new_dao_guardian_x)),
+            Witness::Base(Value::known(new_dao_guardian_y)),
+            Witness::Base(Value::known(self.new_dao.bulla_blind.inner())),
+        ];
+
+        let public_inputs =
+            vec![self.proposal.to_bulla().inner(), old_dao_bulla.inner(), new_dao_bulla.inner()];
+
+        //darkfi::zk::export_witness_json("proof/witness/amend.json", &prover_witnesses, &public_inputs);
+        let circuit = ZkCircuit::new(prover_witnesses, amend_zkbin);
+        let proof = Proof::create(amend_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        let params = DaoAmendParams { old_dao_bulla, new_dao_bulla };
+
+        Ok((params, vec![proof]))
+    }
+}
@@ -18,9 +18,13 @@
 
 use darkfi_money_contract::model::CoinAttributes;
 use darkfi_sdk::{
-    crypto::{note::ElGamalEncryptedNote, poseidon_hash, BaseBlind, PublicKey, SecretKey},
+    crypto::{
+        note::ElGamalEncryptedNote, poseidon_hash, smt::SmtMemoryFp, BaseBlind, PublicKey,
+        SecretKey, DAO_CONTRACT_ID,
+    },
     pasta::pallas,
 };
+use darkfi_serial::serialize;
 
 use rand::rngs::OsRng;
 
@@ -30,17 +34,42 @@ use darkfi::{
     Result,
 };
 
-use crate::model::{Dao, DaoAuthMoneyTransferParams, DaoProposal, VecAuthCallCommit};
+use crate::{
+    model::{
+        Dao, DaoAuthCall, DaoAuthMoneyTransferData, DaoAuthMoneyTransferParams, DaoBulla,
+        DaoProposal, VecAuthCallCommit,
+    },
+    DaoFunction,
+};
+
+/// Create the proposal auth call sending the given coins from the DAO
+/// treasury. The coins may go to several recipients, and since each coin
+/// commits to its value, the proposal fixes the total sent as well.
+pub fn make_auth_money_transfer_call(proposal_coinattrs: &[CoinAttributes]) -> DaoAuthCall {
+    let auth_data = DaoAuthMoneyTransferData {
+        coins: proposal_coinattrs.iter().map(|c| c.to_coin()).collect(),
+    };
+
+    DaoAuthCall {
+        contract_id: *DAO_CONTRACT_ID,
+        function_code: DaoFunction::AuthMoneyTransfer as u8,
+        auth_data: serialize(&auth_data),
+    }
+}
 
-pub struct DaoAuthMoneyTransferCall {
+pub struct DaoAuthMoneyTransferCall<'a> {
     pub proposal: DaoProposal,
     pub proposal_coinattrs: Vec<CoinAttributes>,
     pub dao: Dao,
     pub input_user_data_blind: BaseBlind,
+    /// DAO whose treasury coins are spent, either `dao` or a DAO it superseded
+    pub treasury_dao_bulla: DaoBulla,
+    /// SMT of amended DAOs, mapping each to its latest successor
+    pub dao_superseded_smt: &'a SmtMemoryFp,
     pub dao_coin_attrs: CoinAttributes,
 }
 
-impl DaoAuthMoneyTransferCall {
+impl DaoAuthMoneyTransferCall<'_> {
     pub fn make(
         self,
         auth_xfer_zkbin: &ZkBinary,
@@ -111,6 +140,7 @@ impl DaoAuthMoneyTransferCall {
         let (ephem_x, ephem_y) = change_ephem_pubkey.xy();
 
         let dao_public_key = self.dao.public_key.inner();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
         let dao_change_value = pallas::Base::from(self.dao_coin_attrs.value);
 
         let note = [
@@ -125,30 +155,45 @@ impl DaoAuthMoneyTransferCall {
         let params = DaoAuthMoneyTransferParams { enc_attrs, dao_change_attrs };
 
         let dao_proposer_limit = pallas::Base::from(self.dao.proposer_limit);
+        let dao_proposal_deposit = pallas::Base::from(self.dao.proposal_deposit);
         let dao_quorum = pallas::Base::from(self.dao.quorum);
+        let dao_early_exec_quorum = pallas::Base::from(self.dao.early_exec_quorum);
         let dao_approval_ratio_quot = pallas::Base::from(self.dao.approval_ratio_quot);
         let dao_approval_ratio_base = pallas::Base::from(self.dao.approval_ratio_base);
+        let dao_vote_weight_mode = pallas::Base::from(self.dao.vote_weight.mode());
+        let dao_vote_weight_cap = pallas::Base::from(self.dao.vote_weight.cap());
 
         let input_user_data_enc =
-            poseidon_hash([self.dao.to_bulla().inner(), self.input_user_data_blind.inner()]);
+            poseidon_hash([self.treasury_dao_bulla.inner(), self.input_user_data_blind.inner()]);
+        let dao_superseded_root = self.dao_superseded_smt.root();
+        let treasury_superseded_path =
+            self.dao_superseded_smt.prove_membership(&self.treasury_dao_bulla.inner());
 
         let prover_witnesses = vec![
             // proposal params
-            Witness::Base(Value::known(self.proposal.auth_calls.commit())),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_day))),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_days))),
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
             Witness::Base(Value::known(self.proposal.user_data)),
             Witness::Base(Value::known(self.proposal.blind.inner())),
             // DAO params
             Witness::Base(Value::known(dao_proposer_limit)),
+            Witness::Base(Value::known(dao_proposal_deposit)),
             Witness::Base(Value::known(dao_quorum)),
+            Witness::Base(Value::known(dao_early_exec_quorum)),
             Witness::Base(Value::known(dao_approval_ratio_quot)),
             Witness::Base(Value::known(dao_approval_ratio_base)),
+            Witness::Base(Value::known(dao_vote_weight_mode)),
+            Witness::Base(Value::known(dao_vote_weight_cap)),
             Witness::Base(Value::known(self.dao.gov_token_id.inner())),
             Witness::EcNiPoint(Value::known(dao_public_key)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
             Witness::Base(Value::known(self.dao.bulla_blind.inner())),
             // Dao input user data blind
             Witness::Base(Value::known(self.input_user_data_blind.inner())),
+            Witness::Base(Value::known(self.treasury_dao_bulla.inner())),
+            Witness::SparseMerklePath(Value::known(treasury_superseded_path.path)),
             // Dao output coin attrs
             Witness::Base(Value::known(dao_change_value)),
             Witness::Base(Value::known(self.dao_coin_attrs.token_id.inner())),
@@ -162,9 +207,9 @@ impl DaoAuthMoneyTransferCall {
         let public_inputs = vec![
             self.proposal.to_bulla().inner(),
             input_user_data_enc,
+            dao_superseded_root,
             self.dao_coin_attrs.to_coin().inner(),
             self.dao_coin_attrs.spend_hook.inner(),
-            self.proposal.auth_calls.commit(),
             ephem_x,
             ephem_y,
             dao_change_attrs.encrypted_values[0],
@@ -0,0 +1,88 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{poseidon_hash, PublicKey, SecretKey},
+    pasta::pallas,
+};
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use crate::model::{DaoCancelParams, DaoProposal};
+
+/// Derive the key the proposer commits to in `Dao::Propose` and signs
+/// `Dao::Cancel` with from their `secret`, so it needs no storage and
+/// doesn't link their proposals together.
+pub fn proposer_secret(secret: &SecretKey, proposal: &DaoProposal) -> SecretKey {
+    SecretKey::from(poseidon_hash([secret.inner(), proposal.blind.inner()]))
+}
+
+/// Cancels a proposal while voting is still open, proving knowledge of
+/// its blind. The transaction has to be signed with the secret key of
+/// `proposer_public_key`.
+pub struct DaoCancelCall {
+    pub proposal: DaoProposal,
+    /// Highest block height the cancel can be confirmed at
+    pub max_block_height: u64,
+    /// Key the proposer committed to in `Dao::Propose`
+    pub proposer_public_key: PublicKey,
+}
+
+impl DaoCancelCall {
+    pub fn make(
+        self,
+        cancel_zkbin: &ZkBinary,
+        cancel_pk: &ProvingKey,
+    ) -> Result<(DaoCancelParams, Vec<Proof>)> {
+        let end_height = self.proposal.creation_height + self.proposal.duration_blocks;
+        assert!(self.max_block_height < end_height);
+
+        let proposal_bulla = self.proposal.to_bulla();
+
+        let prover_witnesses = vec![
+            // proposal params
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
+            Witness::Base(Value::known(self.proposal.user_data)),
+            Witness::Base(Value::known(self.proposal.dao_bulla.inner())),
+            Witness::Base(Value::known(self.proposal.blind.inner())),
+            // cancel time
+            Witness::Base(Value::known(pallas::Base::from(self.max_block_height))),
+        ];
+
+        let public_inputs = vec![proposal_bulla.inner(), pallas::Base::from(self.max_block_height)];
+
+        //darkfi::zk::export_witness_json("proof/witness/cancel.json", &prover_witnesses, &public_inputs);
+        let circuit = ZkCircuit::new(prover_witnesses, cancel_zkbin);
+        let proof = Proof::create(cancel_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        let params = DaoCancelParams {
+            proposal_bulla,
+            max_block_height: self.max_block_height,
+            proposer_public_key: self.proposer_public_key,
+        };
+
+        Ok((params, vec![proof]))
+    }
+}
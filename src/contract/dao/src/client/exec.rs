@@ -17,9 +17,7 @@
  */
 
 use darkfi_sdk::{
-    crypto::{
-        pasta_prelude::*, pedersen_commitment_u64, BaseBlind, PublicKey, ScalarBlind, SecretKey,
-    },
+    crypto::{commitment, smt::SmtMemoryFp, BaseBlind, PublicKey, ScalarBlind, SecretKey},
     pasta::pallas,
 };
 
@@ -32,23 +30,32 @@ use darkfi::{
     Result,
 };
 
-use crate::model::{Dao, DaoBlindAggregateVote, DaoExecParams, DaoProposal, VecAuthCallCommit};
+use crate::{
+    model::{Dao, DaoBlindAggregateVote, DaoExecParams, DaoProposal, VecAuthCallCommit},
+    PROPOSAL_VETO_GRACE_BLOCKS,
+};
 
-pub struct DaoExecCall {
+pub struct DaoExecCall<'a> {
     pub proposal: DaoProposal,
     pub dao: Dao,
     pub yes_vote_value: u64,
+    pub abstain_vote_value: u64,
     pub all_vote_value: u64,
     pub yes_vote_blind: ScalarBlind,
+    pub abstain_vote_blind: ScalarBlind,
     pub all_vote_blind: ScalarBlind,
     pub input_value: u64,
     pub input_value_blind: ScalarBlind,
     pub input_user_data_blind: BaseBlind,
     pub hook_dao_exec: pallas::Base,
     pub signature_secret: SecretKey,
+    /// Lowest block height the exec can be confirmed at
+    pub min_block_height: u64,
+    /// SMT of amended DAOs, which the DAO is shown to be absent from
+    pub dao_superseded_smt: &'a SmtMemoryFp,
 }
 
-impl DaoExecCall {
+impl DaoExecCall<'_> {
     pub fn make(
         self,
         exec_zkbin: &ZkBinary,
@@ -58,61 +65,100 @@ impl DaoExecCall {
         let mut proofs = vec![];
 
         let dao_proposer_limit = pallas::Base::from(self.dao.proposer_limit);
+        let dao_proposal_deposit = pallas::Base::from(self.dao.proposal_deposit);
         let dao_quorum = pallas::Base::from(self.dao.quorum);
+        let dao_early_exec_quorum = pallas::Base::from(self.dao.early_exec_quorum);
         let dao_approval_ratio_quot = pallas::Base::from(self.dao.approval_ratio_quot);
         let dao_approval_ratio_base = pallas::Base::from(self.dao.approval_ratio_base);
+        let dao_vote_weight_mode = pallas::Base::from(self.dao.vote_weight.mode());
+        let dao_vote_weight_cap = pallas::Base::from(self.dao.vote_weight.cap());
 
         let (dao_pub_x, dao_pub_y) = self.dao.public_key.xy();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
 
         let dao_bulla = self.dao.to_bulla();
         assert_eq!(dao_bulla, self.proposal.dao_bulla);
         let proposal_bulla = self.proposal.to_bulla();
 
-        let yes_vote_commit = pedersen_commitment_u64(self.yes_vote_value, self.yes_vote_blind);
-        let yes_vote_commit_coords = yes_vote_commit.to_affine().coordinates().unwrap();
+        let yes_vote_commit = commitment::commit(self.yes_vote_value, self.yes_vote_blind);
+        let (yes_vote_commit_x, yes_vote_commit_y) = commitment::xy(&yes_vote_commit);
+
+        let abstain_vote_commit =
+            commitment::commit(self.abstain_vote_value, self.abstain_vote_blind);
+        let (abstain_vote_commit_x, abstain_vote_commit_y) = commitment::xy(&abstain_vote_commit);
 
-        let all_vote_commit = pedersen_commitment_u64(self.all_vote_value, self.all_vote_blind);
-        let all_vote_commit_coords = all_vote_commit.to_affine().coordinates().unwrap();
+        let all_vote_commit = commitment::commit(self.all_vote_value, self.all_vote_blind);
+        let (all_vote_commit_x, all_vote_commit_y) = commitment::xy(&all_vote_commit);
 
         let proposal_auth_calls_commit = self.proposal.auth_calls.commit();
 
         let signature_public = PublicKey::from_secret(self.signature_secret);
 
+        let dao_superseded_root = self.dao_superseded_smt.root();
+        let dao_superseded_path = self.dao_superseded_smt.prove_membership(&dao_bulla.inner());
+
+        // Before the voting period ends, the proposal can only be executed
+        // when the yes votes reach the DAO's early_exec_quorum.
+        // DAOs with a guardian can't execute early.
+        let end_height = self.proposal.creation_height + self.proposal.duration_blocks;
+        let early_exec =
+            self.dao.guardian_public_key.is_none() && self.min_block_height < end_height;
+        debug!(target: "dao", "early_exec: {}", early_exec);
+
         let prover_witnesses = vec![
             // proposal params
             Witness::Base(Value::known(proposal_auth_calls_commit)),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_day))),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_days))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
             Witness::Base(Value::known(self.proposal.user_data)),
             Witness::Base(Value::known(self.proposal.blind.inner())),
             // DAO params
             Witness::Base(Value::known(dao_proposer_limit)),
+            Witness::Base(Value::known(dao_proposal_deposit)),
             Witness::Base(Value::known(dao_quorum)),
+            Witness::Base(Value::known(dao_early_exec_quorum)),
             Witness::Base(Value::known(dao_approval_ratio_quot)),
             Witness::Base(Value::known(dao_approval_ratio_base)),
+            Witness::Base(Value::known(dao_vote_weight_mode)),
+            Witness::Base(Value::known(dao_vote_weight_cap)),
             Witness::Base(Value::known(self.dao.gov_token_id.inner())),
             Witness::Base(Value::known(dao_pub_x)),
             Witness::Base(Value::known(dao_pub_y)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
             Witness::Base(Value::known(self.dao.bulla_blind.inner())),
             // votes
             Witness::Base(Value::known(pallas::Base::from(self.yes_vote_value))),
+            Witness::Base(Value::known(pallas::Base::from(self.abstain_vote_value))),
             Witness::Base(Value::known(pallas::Base::from(self.all_vote_value))),
             Witness::Scalar(Value::known(self.yes_vote_blind.inner())),
+            Witness::Scalar(Value::known(self.abstain_vote_blind.inner())),
             Witness::Scalar(Value::known(self.all_vote_blind.inner())),
             // signature secret
             Witness::Base(Value::known(self.signature_secret.inner())),
+            // execution time
+            Witness::Base(Value::known(pallas::Base::from(self.min_block_height))),
+            Witness::Base(Value::known(pallas::Base::from(early_exec as u64))),
+            Witness::Base(Value::known(pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS))),
+            // superseded DAOs
+            Witness::SparseMerklePath(Value::known(dao_superseded_path.path)),
         ];
 
         debug!(target: "dao", "proposal_bulla: {:?}", proposal_bulla);
         let public_inputs = vec![
             proposal_bulla.inner(),
             proposal_auth_calls_commit,
-            *yes_vote_commit_coords.x(),
-            *yes_vote_commit_coords.y(),
-            *all_vote_commit_coords.x(),
-            *all_vote_commit_coords.y(),
+            yes_vote_commit_x,
+            yes_vote_commit_y,
+            abstain_vote_commit_x,
+            abstain_vote_commit_y,
+            all_vote_commit_x,
+            all_vote_commit_y,
+            pallas::Base::from(self.min_block_height),
+            pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS),
             signature_public.x(),
             signature_public.y(),
+            dao_superseded_root,
         ];
         //darkfi::zk::export_witness_json("proof/witness/exec.json", &prover_witnesses, &public_inputs);
 
@@ -123,8 +169,16 @@ impl DaoExecCall {
         let params = DaoExecParams {
             proposal_bulla,
             proposal_auth_calls: self.proposal.auth_calls,
-            blind_total_vote: DaoBlindAggregateVote { yes_vote_commit, all_vote_commit },
+            multi_choice: false,
+            blind_total_vote: DaoBlindAggregateVote {
+                yes_vote_commit,
+                abstain_vote_commit,
+                all_vote_commit,
+                ..Default::default()
+            },
+            min_block_height: self.min_block_height,
             signature_public,
+            dao_superseded_root,
         };
 
         Ok((params, proofs))
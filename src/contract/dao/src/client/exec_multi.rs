@@ -0,0 +1,203 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{commitment, smt::SmtMemoryFp, PublicKey, ScalarBlind, SecretKey},
+    pasta::pallas,
+};
+
+use log::debug;
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use crate::{
+    model::{Dao, DaoBlindAggregateVote, DaoExecParams, DaoProposal, VecAuthCallCommit},
+    PROPOSAL_MAX_OPTIONS, PROPOSAL_VETO_GRACE_BLOCKS,
+};
+
+pub struct DaoExecMultiCall<'a> {
+    pub proposal: DaoProposal,
+    pub dao: Dao,
+    pub option_vote_values: [u64; PROPOSAL_MAX_OPTIONS],
+    pub all_vote_value: u64,
+    pub option_vote_blinds: [ScalarBlind; PROPOSAL_MAX_OPTIONS],
+    pub all_vote_blind: ScalarBlind,
+    pub signature_secret: SecretKey,
+    /// Lowest block height the exec can be confirmed at
+    pub min_block_height: u64,
+    /// SMT of amended DAOs, which the DAO is shown to be absent from
+    pub dao_superseded_smt: &'a SmtMemoryFp,
+}
+
+impl DaoExecMultiCall<'_> {
+    /// Index of the option with the most votes. Ties have no winner.
+    pub fn winner(&self) -> Option<usize> {
+        let max_votes = *self.option_vote_values.iter().max().unwrap();
+        let mut winners =
+            self.option_vote_values.iter().enumerate().filter(|(_, votes)| **votes == max_votes);
+        let (winner, _) = winners.next().unwrap();
+        if winners.next().is_some() || winner >= self.proposal.options.len() {
+            return None
+        }
+        Some(winner)
+    }
+
+    pub fn make(
+        self,
+        exec_zkbin: &ZkBinary,
+        exec_pk: &ProvingKey,
+    ) -> Result<(DaoExecParams, Vec<Proof>)> {
+        debug!(target: "dao", "build()");
+        let mut proofs = vec![];
+
+        let winner = self.winner().expect("Proposal has no winning option");
+        debug!(target: "dao", "winner: {}", winner);
+
+        let dao_proposer_limit = pallas::Base::from(self.dao.proposer_limit);
+        let dao_proposal_deposit = pallas::Base::from(self.dao.proposal_deposit);
+        let dao_quorum = pallas::Base::from(self.dao.quorum);
+        let dao_early_exec_quorum = pallas::Base::from(self.dao.early_exec_quorum);
+        let dao_approval_ratio_quot = pallas::Base::from(self.dao.approval_ratio_quot);
+        let dao_approval_ratio_base = pallas::Base::from(self.dao.approval_ratio_base);
+        let dao_vote_weight_mode = pallas::Base::from(self.dao.vote_weight.mode());
+        let dao_vote_weight_cap = pallas::Base::from(self.dao.vote_weight.cap());
+
+        let (dao_pub_x, dao_pub_y) = self.dao.public_key.xy();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
+
+        let dao_bulla = self.dao.to_bulla();
+        assert_eq!(dao_bulla, self.proposal.dao_bulla);
+        let proposal_bulla = self.proposal.to_bulla();
+
+        let option_vote_commits: [pallas::Point; PROPOSAL_MAX_OPTIONS] = std::array::from_fn(|i| {
+            commitment::commit(self.option_vote_values[i], self.option_vote_blinds[i])
+        });
+
+        let all_vote_commit = commitment::commit(self.all_vote_value, self.all_vote_blind);
+        let (all_vote_commit_x, all_vote_commit_y) = commitment::xy(&all_vote_commit);
+
+        let winner_auth_calls = self.proposal.options[winner].clone();
+        let winner_auth_calls_commit = winner_auth_calls.commit();
+
+        let signature_public = PublicKey::from_secret(self.signature_secret);
+
+        let dao_superseded_root = self.dao_superseded_smt.root();
+        let dao_superseded_path = self.dao_superseded_smt.prove_membership(&dao_bulla.inner());
+
+        // Before the voting period ends, the proposal can only be executed
+        // when the winning option reaches the DAO's early_exec_quorum.
+        // DAOs with a guardian can't execute early.
+        let end_height = self.proposal.creation_height + self.proposal.duration_blocks;
+        let early_exec =
+            self.dao.guardian_public_key.is_none() && self.min_block_height < end_height;
+        debug!(target: "dao", "early_exec: {}", early_exec);
+
+        let mut prover_witnesses = vec![];
+        // proposal params
+        for option_commit in self.proposal.option_commits() {
+            prover_witnesses.push(Witness::Base(Value::known(option_commit)));
+        }
+        prover_witnesses.extend([
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
+            Witness::Base(Value::known(self.proposal.user_data)),
+            Witness::Base(Value::known(self.proposal.blind.inner())),
+            // DAO params
+            Witness::Base(Value::known(dao_proposer_limit)),
+            Witness::Base(Value::known(dao_proposal_deposit)),
+            Witness::Base(Value::known(dao_quorum)),
+            Witness::Base(Value::known(dao_early_exec_quorum)),
+            Witness::Base(Value::known(dao_approval_ratio_quot)),
+            Witness::Base(Value::known(dao_approval_ratio_base)),
+            Witness::Base(Value::known(dao_vote_weight_mode)),
+            Witness::Base(Value::known(dao_vote_weight_cap)),
+            Witness::Base(Value::known(self.dao.gov_token_id.inner())),
+            Witness::Base(Value::known(dao_pub_x)),
+            Witness::Base(Value::known(dao_pub_y)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
+            Witness::Base(Value::known(self.dao.bulla_blind.inner())),
+        ]);
+        // votes
+        for value in self.option_vote_values {
+            prover_witnesses.push(Witness::Base(Value::known(pallas::Base::from(value))));
+        }
+        prover_witnesses.push(Witness::Base(Value::known(pallas::Base::from(self.all_vote_value))));
+        for blind in self.option_vote_blinds {
+            prover_witnesses.push(Witness::Scalar(Value::known(blind.inner())));
+        }
+        prover_witnesses.push(Witness::Scalar(Value::known(self.all_vote_blind.inner())));
+        // winning option
+        for i in 0..PROPOSAL_MAX_OPTIONS {
+            let is_winner = pallas::Base::from((i == winner) as u64);
+            prover_witnesses.push(Witness::Base(Value::known(is_winner)));
+        }
+        prover_witnesses.extend([
+            // signature secret
+            Witness::Base(Value::known(self.signature_secret.inner())),
+            // execution time
+            Witness::Base(Value::known(pallas::Base::from(self.min_block_height))),
+            Witness::Base(Value::known(pallas::Base::from(early_exec as u64))),
+            Witness::Base(Value::known(pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS))),
+            // superseded DAOs
+            Witness::SparseMerklePath(Value::known(dao_superseded_path.path)),
+        ]);
+
+        debug!(target: "dao", "proposal_bulla: {:?}", proposal_bulla);
+        let mut public_inputs = vec![proposal_bulla.inner(), winner_auth_calls_commit];
+        for option_vote_commit in &option_vote_commits {
+            let (commit_x, commit_y) = commitment::xy(option_vote_commit);
+            public_inputs.push(commit_x);
+            public_inputs.push(commit_y);
+        }
+        public_inputs.extend([
+            all_vote_commit_x,
+            all_vote_commit_y,
+            pallas::Base::from(self.min_block_height),
+            pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS),
+            signature_public.x(),
+            signature_public.y(),
+            dao_superseded_root,
+        ]);
+
+        let circuit = ZkCircuit::new(prover_witnesses, exec_zkbin);
+        let input_proof = Proof::create(exec_pk, &[circuit], &public_inputs, &mut OsRng)?;
+        proofs.push(input_proof);
+
+        let params = DaoExecParams {
+            proposal_bulla,
+            proposal_auth_calls: winner_auth_calls,
+            multi_choice: true,
+            blind_total_vote: DaoBlindAggregateVote {
+                all_vote_commit,
+                option_vote_commits,
+                ..Default::default()
+            },
+            min_block_height: self.min_block_height,
+            signature_public,
+            dao_superseded_root,
+        };
+
+        Ok((params, proofs))
+    }
+}
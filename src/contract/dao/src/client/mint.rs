@@ -30,16 +30,20 @@ use rand::rngs::OsRng;
 
 use darkfi_money_contract::model::TokenId;
 
-use crate::model::{Dao, DaoMintParams};
+use crate::model::{Dao, DaoMintParams, DaoVoteWeight};
 
 #[derive(Clone)]
 pub struct DaoInfo {
     pub proposer_limit: u64,
+    pub proposal_deposit: u64,
     pub quorum: u64,
+    pub early_exec_quorum: u64,
     pub approval_ratio_quot: u64,
     pub approval_ratio_base: u64,
+    pub vote_weight: DaoVoteWeight,
     pub gov_token_id: TokenId,
     pub public_key: PublicKey,
+    pub guardian_public_key: Option<PublicKey>,
     pub bulla_blind: pallas::Base,
 }
 
@@ -52,18 +56,29 @@ pub fn make_mint_call(
     debug!(target: "dao", "Building DAO contract mint transaction");
 
     let dao_proposer_limit = pallas::Base::from(dao.proposer_limit);
+    let dao_proposal_deposit = pallas::Base::from(dao.proposal_deposit);
     let dao_quorum = pallas::Base::from(dao.quorum);
+    let dao_early_exec_quorum = pallas::Base::from(dao.early_exec_quorum);
     let dao_approval_ratio_quot = pallas::Base::from(dao.approval_ratio_quot);
     let dao_approval_ratio_base = pallas::Base::from(dao.approval_ratio_base);
+    let dao_vote_weight_mode = pallas::Base::from(dao.vote_weight.mode());
+    let dao_vote_weight_cap = pallas::Base::from(dao.vote_weight.cap());
+    let (dao_guardian_x, dao_guardian_y) = dao.guardian_xy();
 
     // NOTE: It's important to keep these in the same order as the zkas code.
     let prover_witnesses = vec![
         Witness::Base(halo2::Value::known(dao_proposer_limit)),
+        Witness::Base(halo2::Value::known(dao_proposal_deposit)),
         Witness::Base(halo2::Value::known(dao_quorum)),
+        Witness::Base(halo2::Value::known(dao_early_exec_quorum)),
         Witness::Base(halo2::Value::known(dao_approval_ratio_quot)),
         Witness::Base(halo2::Value::known(dao_approval_ratio_base)),
+        Witness::Base(halo2::Value::known(dao_vote_weight_mode)),
+        Witness::Base(halo2::Value::known(dao_vote_weight_cap)),
         Witness::Base(halo2::Value::known(dao.gov_token_id.inner())),
         Witness::Base(halo2::Value::known(dao_secret_key.inner())),
+        Witness::Base(halo2::Value::known(dao_guardian_x)),
+        Witness::Base(halo2::Value::known(dao_guardian_y)),
         Witness::Base(halo2::Value::known(dao.bulla_blind.inner())),
     ];
 
@@ -23,8 +23,14 @@ pub use mint::{make_mint_call, DaoInfo};
 ///
 /// * `DaoProposeStakeInput` are the staking inputs used to meet the `proposer_limit` threshold.
 /// * `DaoProposeCall` is what creates the call data used on chain.
+/// * `make_proposal_memo()`, `encrypt_propose_note()` and `decrypt_propose_note()` handle the
+///   standard proposal metadata carried in the encrypted note.
+/// * `make_proposal_deposit()` creates the coin bonding the DAO's proposal deposit.
 pub mod propose;
-pub use propose::{DaoProposeCall, DaoProposeStakeInput};
+pub use propose::{
+    decrypt_propose_note, encrypt_propose_note, make_proposal_deposit, make_proposal_memo,
+    verify_proposal_description, DaoProposeCall, DaoProposeStakeInput,
+};
 
 /// Provides core structs for DAO::vote()
 ///
@@ -34,8 +40,70 @@ pub use propose::{DaoProposeCall, DaoProposeStakeInput};
 pub mod vote;
 pub use vote::{DaoVoteCall, DaoVoteInput};
 
+/// Provides core structs for DAO::vote_multi()
+///
+/// * `DaoVoteMultiCall` creates the call data voting on a multiple-choice proposal.
+pub mod vote_multi;
+pub use vote_multi::DaoVoteMultiCall;
+
 pub mod exec;
 pub use exec::DaoExecCall;
 
+pub mod exec_multi;
+pub use exec_multi::DaoExecMultiCall;
+
+/// Provides core structs for DAO::auth_money_transfer()
+///
+/// * `make_auth_money_transfer_call()` creates the proposal auth call sending the DAO treasury
+///   coins to one or more recipients.
+/// * `DaoAuthMoneyTransferCall` is what creates the call data used on chain.
 pub mod auth_xfer;
-pub use auth_xfer::DaoAuthMoneyTransferCall;
+pub use auth_xfer::{make_auth_money_transfer_call, DaoAuthMoneyTransferCall};
+
+/// Provides core structs for DAO::amend()
+///
+/// * `make_amend_auth_call()` creates the proposal auth call amending a DAO.
+/// * `DaoAmendCall` is what creates the call data used on chain.
+pub mod amend;
+pub use amend::{make_amend_auth_call, DaoAmendCall};
+
+/// Provides core structs for DAO::reveal_tally()
+///
+/// * `DaoRevealTallyCall` reveals the tally of a sealed proposal once voting ended.
+pub mod reveal;
+pub use reveal::DaoRevealTallyCall;
+
+/// Provides core structs for DAO::cancel()
+///
+/// * `DaoCancelCall` cancels a proposal while voting is still open.
+/// * `proposer_secret()` derives the key a proposer signs the cancel with.
+pub mod cancel;
+pub use cancel::{proposer_secret, DaoCancelCall};
+
+/// Provides core structs for DAO::veto()
+///
+/// * `DaoVetoCall` vetoes a proposal of a DAO with a guardian.
+pub mod veto;
+pub use veto::DaoVetoCall;
+
+/// Provides core structs for DAO::settle_deposit()
+///
+/// * `DaoSettleDepositCall` refunds or slashes a proposal deposit once voting ended.
+pub mod settle_deposit;
+pub use settle_deposit::DaoSettleDepositCall;
+
+use darkfi_sdk::crypto::{FuncRef, SpendHookRegistry, DAO_CONTRACT_ID};
+
+use crate::DaoFunction;
+
+/// Register the DAO functions coins can be hooked to
+pub fn register_spend_hooks(registry: &mut SpendHookRegistry) {
+    registry.register(
+        "Dao::Exec",
+        FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::Exec as u8 },
+    );
+    registry.register(
+        "Dao::SettleDeposit",
+        FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::SettleDeposit as u8 },
+    );
+}
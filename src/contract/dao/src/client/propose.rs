@@ -16,14 +16,14 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use darkfi_money_contract::model::CoinAttributes;
+use darkfi_money_contract::model::{CoinAttributes, Nullifier};
 use darkfi_sdk::{
     bridgetree,
     bridgetree::Hashable,
     crypto::{
-        note::AeadEncryptedNote, pasta_prelude::*, pedersen::pedersen_commitment_u64,
-        poseidon_hash, smt::SmtMemoryFp, Blind, FuncId, MerkleNode, PublicKey, ScalarBlind,
-        SecretKey,
+        commitment, note::AeadEncryptedNote, pasta_prelude::*, poseidon_hash, smt::SmtMemoryFp,
+        BaseBlind, Blind, FuncId, FuncRef, MerkleNode, PublicKey, ScalarBlind, SecretKey,
+        DAO_CONTRACT_ID,
     },
     pasta::pallas,
 };
@@ -35,7 +35,70 @@ use darkfi::{
     Result,
 };
 
-use crate::model::{Dao, DaoProposal, DaoProposeParams, DaoProposeParamsInput, VecAuthCallCommit};
+use crate::{
+    model::{
+        Dao, DaoProposal, DaoProposalBulla, DaoProposalMemo, DaoProposeNote, DaoProposeParams,
+        DaoProposeParamsInput, VecAuthCallCommit,
+    },
+    DaoFunction,
+};
+
+/// Create the memo of a proposal, committing to its full `description`
+/// which is shared with the DAO members off-chain.
+pub fn make_proposal_memo(title: &str, description: &str, link: &str) -> DaoProposalMemo {
+    DaoProposalMemo {
+        title: title.to_string(),
+        description_hash: *blake3::hash(description.as_bytes()).as_bytes(),
+        link: link.to_string(),
+    }
+}
+
+/// Check `description` is the one the proposal memo commits to.
+pub fn verify_proposal_description(memo: &DaoProposalMemo, description: &str) -> bool {
+    memo.description_hash == *blake3::hash(description.as_bytes()).as_bytes()
+}
+
+/// Encrypt the note of `Dao::Propose` to the DAO public key.
+pub fn encrypt_propose_note(
+    proposal: &DaoProposal,
+    memo: Option<DaoProposalMemo>,
+    dao_public_key: &PublicKey,
+) -> Result<AeadEncryptedNote> {
+    let note = DaoProposeNote { proposal: proposal.clone(), memo };
+    Ok(AeadEncryptedNote::encrypt(&note, dao_public_key, &mut OsRng)?)
+}
+
+/// Decrypt the note of `Dao::Propose` with the DAO secret key.
+/// Fails if the proposal wasn't made for this DAO.
+pub fn decrypt_propose_note(
+    note: &AeadEncryptedNote,
+    dao_secret_key: &SecretKey,
+) -> Result<DaoProposeNote> {
+    Ok(note.decrypt(dao_secret_key)?)
+}
+
+/// Attributes of the coin bonding the DAO's proposal deposit, which is
+/// refunded to `refund_public_key` unless the proposal is slashed.
+pub fn make_proposal_deposit(
+    dao: &Dao,
+    proposal_bulla: &DaoProposalBulla,
+    refund_public_key: &PublicKey,
+    blind: BaseBlind,
+) -> CoinAttributes {
+    let (refund_x, refund_y) = refund_public_key.xy();
+    CoinAttributes {
+        public_key: dao.public_key,
+        value: dao.proposal_deposit,
+        token_id: dao.gov_token_id,
+        spend_hook: FuncRef {
+            contract_id: *DAO_CONTRACT_ID,
+            func_code: DaoFunction::SettleDeposit as u8,
+        }
+        .to_func_id(),
+        user_data: poseidon_hash([proposal_bulla.inner(), refund_x, refund_y]),
+        blind,
+    }
+}
 
 pub struct DaoProposeStakeInput<'a> {
     pub secret: SecretKey,
@@ -53,6 +116,18 @@ pub struct DaoProposeCall<'a> {
     pub dao_leaf_position: bridgetree::Position,
     pub dao_merkle_path: Vec<MerkleNode>,
     pub dao_merkle_root: MerkleNode,
+    /// SMT of amended DAOs, which the DAO is shown to be absent from
+    pub dao_superseded_smt: &'a SmtMemoryFp,
+    /// Seals the proposal, encrypting votes to this key until voting ends
+    pub tally_public_key: Option<PublicKey>,
+    /// Key the proposer can cancel the proposal with
+    pub proposer_public_key: PublicKey,
+    /// Human-readable metadata shared with the DAO members
+    pub memo: Option<DaoProposalMemo>,
+    /// Key the proposal deposit is refunded to
+    pub deposit_refund_public_key: PublicKey,
+    /// Blind of the coin bonding the proposal deposit
+    pub deposit_coin_blind: BaseBlind,
 }
 
 impl<'a> DaoProposeCall<'a> {
@@ -92,7 +167,7 @@ impl<'a> DaoProposeCall<'a> {
                 blind: note.coin_blind,
             }
             .to_coin();
-            let nullifier = poseidon_hash([input.secret.inner(), coin.inner()]);
+            let nullifier = Nullifier::derive(&input.secret, &coin).inner();
 
             let smt_null_root = input.money_null_smt.root();
             let smt_null_path = input.money_null_smt.prove_membership(&nullifier);
@@ -132,15 +207,15 @@ impl<'a> DaoProposeCall<'a> {
             let token_commit = poseidon_hash([note.token_id.inner(), gov_token_blind.inner()]);
             assert_eq!(self.dao.gov_token_id, note.token_id);
 
-            let value_commit = pedersen_commitment_u64(note.value, funds_blind);
-            let value_coords = value_commit.to_affine().coordinates().unwrap();
+            let value_commit = commitment::commit(note.value, funds_blind);
+            let (value_x, value_y) = commitment::xy(&value_commit);
 
             let (sig_x, sig_y) = signature_public.xy();
 
             let public_inputs = vec![
                 smt_null_root,
-                *value_coords.x(),
-                *value_coords.y(),
+                value_x,
+                value_y,
                 token_commit,
                 merkle_coin_root.inner(),
                 sig_x,
@@ -162,23 +237,42 @@ impl<'a> DaoProposeCall<'a> {
             inputs.push(input);
         }
 
-        let total_funds_commit = pedersen_commitment_u64(total_funds, total_funds_blinds);
-        let total_funds_coords = total_funds_commit.to_affine().coordinates().unwrap();
+        let total_funds_commit = commitment::commit(total_funds, total_funds_blinds);
+        let (total_funds_x, total_funds_y) = commitment::xy(&total_funds_commit);
         let total_funds = pallas::Base::from(total_funds);
 
         let token_commit = poseidon_hash([self.dao.gov_token_id.inner(), gov_token_blind.inner()]);
 
         let dao_proposer_limit = pallas::Base::from(self.dao.proposer_limit);
+        let dao_proposal_deposit = pallas::Base::from(self.dao.proposal_deposit);
         let dao_quorum = pallas::Base::from(self.dao.quorum);
+        let dao_early_exec_quorum = pallas::Base::from(self.dao.early_exec_quorum);
         let dao_approval_ratio_quot = pallas::Base::from(self.dao.approval_ratio_quot);
         let dao_approval_ratio_base = pallas::Base::from(self.dao.approval_ratio_base);
+        let dao_vote_weight_mode = pallas::Base::from(self.dao.vote_weight.mode());
+        let dao_vote_weight_cap = pallas::Base::from(self.dao.vote_weight.cap());
         let (dao_pub_x, dao_pub_y) = self.dao.public_key.xy();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
 
         let dao_leaf_position: u64 = self.dao_leaf_position.into();
 
         assert_eq!(self.dao.to_bulla(), self.proposal.dao_bulla);
         let proposal_bulla = self.proposal.to_bulla();
 
+        let dao_superseded_root = self.dao_superseded_smt.root();
+        let dao_superseded_path =
+            self.dao_superseded_smt.prove_membership(&self.proposal.dao_bulla.inner());
+
+        let deposit_attrs = make_proposal_deposit(
+            &self.dao,
+            &proposal_bulla,
+            &self.deposit_refund_public_key,
+            self.deposit_coin_blind,
+        );
+        let (deposit_refund_x, deposit_refund_y) = self.deposit_refund_public_key.xy();
+        let deposit_coin =
+            if self.dao.proposal_deposit == 0 { None } else { Some(deposit_attrs.to_coin()) };
+
         let prover_witnesses = vec![
             // Proposers total number of gov tokens
             Witness::Base(Value::known(total_funds)),
@@ -186,30 +280,45 @@ impl<'a> DaoProposeCall<'a> {
             // Used for blinding exported gov token ID
             Witness::Base(Value::known(gov_token_blind.inner())),
             // proposal params
-            Witness::Base(Value::known(self.proposal.auth_calls.commit())),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_day))),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_days))),
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
             Witness::Base(Value::known(self.proposal.user_data)),
             Witness::Base(Value::known(self.proposal.blind.inner())),
             // DAO params
             Witness::Base(Value::known(dao_proposer_limit)),
+            Witness::Base(Value::known(dao_proposal_deposit)),
             Witness::Base(Value::known(dao_quorum)),
+            Witness::Base(Value::known(dao_early_exec_quorum)),
             Witness::Base(Value::known(dao_approval_ratio_quot)),
             Witness::Base(Value::known(dao_approval_ratio_base)),
+            Witness::Base(Value::known(dao_vote_weight_mode)),
+            Witness::Base(Value::known(dao_vote_weight_cap)),
             Witness::Base(Value::known(self.dao.gov_token_id.inner())),
             Witness::Base(Value::known(dao_pub_x)),
             Witness::Base(Value::known(dao_pub_y)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
             Witness::Base(Value::known(self.dao.bulla_blind.inner())),
             Witness::Uint32(Value::known(dao_leaf_position.try_into().unwrap())),
             Witness::MerklePath(Value::known(self.dao_merkle_path.try_into().unwrap())),
+            Witness::SparseMerklePath(Value::known(dao_superseded_path.path)),
+            // Deposit coin
+            Witness::Base(Value::known(deposit_refund_x)),
+            Witness::Base(Value::known(deposit_refund_y)),
+            Witness::Base(Value::known(deposit_attrs.spend_hook.inner())),
+            Witness::Base(Value::known(self.deposit_coin_blind.inner())),
         ];
         let public_inputs = vec![
             token_commit,
             self.dao_merkle_root.inner(),
+            dao_superseded_root,
             proposal_bulla.inner(),
-            pallas::Base::from(self.proposal.creation_day),
-            *total_funds_coords.x(),
-            *total_funds_coords.y(),
+            pallas::Base::from(self.proposal.creation_height),
+            total_funds_x,
+            total_funds_y,
+            deposit_coin.map_or(pallas::Base::ZERO, |coin| coin.inner()),
+            deposit_attrs.spend_hook.inner(),
         ];
         //darkfi::zk::export_witness_json("proof/witness/propose-main.json", &prover_witnesses, &public_inputs);
         let circuit = ZkCircuit::new(prover_witnesses, main_zkbin);
@@ -217,13 +326,17 @@ impl<'a> DaoProposeCall<'a> {
         let main_proof = Proof::create(main_pk, &[circuit], &public_inputs, &mut OsRng)?;
         proofs.push(main_proof);
 
-        let enc_note =
-            AeadEncryptedNote::encrypt(&self.proposal, &self.dao.public_key, &mut OsRng).unwrap();
+        let enc_note = encrypt_propose_note(&self.proposal, self.memo, &self.dao.public_key)?;
         let params = DaoProposeParams {
             dao_merkle_root: self.dao_merkle_root,
+            dao_superseded_root,
             proposal_bulla,
             token_commit,
+            creation_height: self.proposal.creation_height,
             note: enc_note,
+            tally_public_key: self.tally_public_key,
+            proposer_public_key: self.proposer_public_key,
+            deposit_coin,
             inputs,
         };
 
@@ -0,0 +1,117 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{commitment, ScalarBlind},
+    pasta::pallas,
+};
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use crate::model::{DaoProposal, DaoRevealTallyParams};
+
+/// Reveals the tally of a sealed proposal once voting ended. The vote
+/// values and blinds are decrypted from the vote notes with the
+/// decryption shares published by the tally trustees, see
+/// [`darkfi_sdk::crypto::frost::combine_decryption_shares`].
+pub struct DaoRevealTallyCall {
+    pub proposal: DaoProposal,
+    pub yes_vote_value: u64,
+    pub abstain_vote_value: u64,
+    pub all_vote_value: u64,
+    pub yes_vote_blind: ScalarBlind,
+    pub abstain_vote_blind: ScalarBlind,
+    pub all_vote_blind: ScalarBlind,
+    /// Lowest block height the reveal can be confirmed at
+    pub min_block_height: u64,
+}
+
+impl DaoRevealTallyCall {
+    pub fn make(
+        self,
+        reveal_zkbin: &ZkBinary,
+        reveal_pk: &ProvingKey,
+    ) -> Result<(DaoRevealTallyParams, Vec<Proof>)> {
+        let end_height = self.proposal.creation_height + self.proposal.duration_blocks;
+        assert!(end_height <= self.min_block_height);
+
+        let proposal_bulla = self.proposal.to_bulla();
+
+        let yes_vote_commit = commitment::commit(self.yes_vote_value, self.yes_vote_blind);
+        let (yes_vote_commit_x, yes_vote_commit_y) = commitment::xy(&yes_vote_commit);
+
+        let abstain_vote_commit =
+            commitment::commit(self.abstain_vote_value, self.abstain_vote_blind);
+        let (abstain_vote_commit_x, abstain_vote_commit_y) = commitment::xy(&abstain_vote_commit);
+
+        let all_vote_commit = commitment::commit(self.all_vote_value, self.all_vote_blind);
+        let (all_vote_commit_x, all_vote_commit_y) = commitment::xy(&all_vote_commit);
+
+        let prover_witnesses = vec![
+            // proposal params
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
+            Witness::Base(Value::known(self.proposal.user_data)),
+            Witness::Base(Value::known(self.proposal.dao_bulla.inner())),
+            Witness::Base(Value::known(self.proposal.blind.inner())),
+            // votes
+            Witness::Base(Value::known(pallas::Base::from(self.yes_vote_value))),
+            Witness::Base(Value::known(pallas::Base::from(self.abstain_vote_value))),
+            Witness::Base(Value::known(pallas::Base::from(self.all_vote_value))),
+            Witness::Scalar(Value::known(self.yes_vote_blind.inner())),
+            Witness::Scalar(Value::known(self.abstain_vote_blind.inner())),
+            Witness::Scalar(Value::known(self.all_vote_blind.inner())),
+            // reveal time
+            Witness::Base(Value::known(pallas::Base::from(self.min_block_height))),
+        ];
+
+        let public_inputs = vec![
+            proposal_bulla.inner(),
+            yes_vote_commit_x,
+            yes_vote_commit_y,
+            pallas::Base::from(self.yes_vote_value),
+            abstain_vote_commit_x,
+            abstain_vote_commit_y,
+            pallas::Base::from(self.abstain_vote_value),
+            all_vote_commit_x,
+            all_vote_commit_y,
+            pallas::Base::from(self.all_vote_value),
+            pallas::Base::from(self.min_block_height),
+        ];
+
+        //darkfi::zk::export_witness_json("proof/witness/reveal-tally.json", &prover_witnesses, &public_inputs);
+        let circuit = ZkCircuit::new(prover_witnesses, reveal_zkbin);
+        let proof = Proof::create(reveal_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        let params = DaoRevealTallyParams {
+            proposal_bulla,
+            yes_vote_value: self.yes_vote_value,
+            abstain_vote_value: self.abstain_vote_value,
+            all_vote_value: self.all_vote_value,
+            min_block_height: self.min_block_height,
+        };
+
+        Ok((params, vec![proof]))
+    }
+}
@@ -0,0 +1,218 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_money_contract::model::CoinAttributes;
+use darkfi_sdk::{
+    crypto::{
+        commitment, pasta_prelude::*, poseidon_hash, BaseBlind, FuncId, FuncRef, PublicKey,
+        ScalarBlind, DAO_CONTRACT_ID,
+    },
+    pasta::pallas,
+};
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use crate::{
+    model::{Dao, DaoBlindAggregateVote, DaoProposal, DaoSettleDepositParams},
+    DaoFunction, PROPOSAL_DEPOSIT_SLASH_DIVISOR,
+};
+
+/// Settles the deposit bonded for a proposal once voting ended. It's
+/// slashed to the DAO treasury if the proposal reached quorum with less
+/// than `1 / PROPOSAL_DEPOSIT_SLASH_DIVISOR` of the cast votes in favour,
+/// and otherwise refunded.
+///
+/// This is the parent of a `Money::Transfer` spending the deposit coin
+/// into `settled_coin()`. Deposits of executed or cancelled proposals are
+/// refunded with zero votes.
+pub struct DaoSettleDepositCall {
+    pub proposal: DaoProposal,
+    pub dao: Dao,
+    pub yes_vote_value: u64,
+    pub abstain_vote_value: u64,
+    pub all_vote_value: u64,
+    pub yes_vote_blind: ScalarBlind,
+    pub abstain_vote_blind: ScalarBlind,
+    pub all_vote_blind: ScalarBlind,
+    /// Key the deposit is refunded to
+    pub refund_public_key: PublicKey,
+    /// User data blind of the spent deposit coin
+    pub input_user_data_blind: BaseBlind,
+    /// Blind of the settled coin
+    pub output_coin_blind: BaseBlind,
+    /// Lowest block height the settlement can be confirmed at
+    pub min_block_height: u64,
+}
+
+impl DaoSettleDepositCall {
+    /// Whether the votes slash the deposit
+    pub fn slashed(&self) -> bool {
+        let cast_vote_value = self.all_vote_value - self.abstain_vote_value;
+        self.all_vote_value >= self.dao.quorum &&
+            self.yes_vote_value * PROPOSAL_DEPOSIT_SLASH_DIVISOR < cast_vote_value
+    }
+
+    /// Attributes of the coin the deposit is settled into
+    pub fn settled_coin(&self) -> CoinAttributes {
+        if self.slashed() {
+            CoinAttributes {
+                public_key: self.dao.public_key,
+                value: self.dao.proposal_deposit,
+                token_id: self.dao.gov_token_id,
+                spend_hook: FuncRef {
+                    contract_id: *DAO_CONTRACT_ID,
+                    func_code: DaoFunction::Exec as u8,
+                }
+                .to_func_id(),
+                user_data: self.dao.to_bulla().inner(),
+                blind: self.output_coin_blind,
+            }
+        } else {
+            CoinAttributes {
+                public_key: self.refund_public_key,
+                value: self.dao.proposal_deposit,
+                token_id: self.dao.gov_token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: self.output_coin_blind,
+            }
+        }
+    }
+
+    pub fn make(
+        self,
+        settle_zkbin: &ZkBinary,
+        settle_pk: &ProvingKey,
+    ) -> Result<(DaoSettleDepositParams, Vec<Proof>)> {
+        let end_height = self.proposal.creation_height + self.proposal.duration_blocks;
+        assert!(self.min_block_height >= end_height);
+
+        let slashed = self.slashed();
+        let quorum_failed = self.all_vote_value < self.dao.quorum;
+        let settled_coin = self.settled_coin().to_coin();
+
+        let dao_bulla = self.dao.to_bulla();
+        assert_eq!(dao_bulla, self.proposal.dao_bulla);
+        let proposal_bulla = self.proposal.to_bulla();
+
+        let (dao_pub_x, dao_pub_y) = self.dao.public_key.xy();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
+        let (refund_x, refund_y) = self.refund_public_key.xy();
+
+        let deposit_user_data = poseidon_hash([proposal_bulla.inner(), refund_x, refund_y]);
+        let input_user_data_enc =
+            poseidon_hash([deposit_user_data, self.input_user_data_blind.inner()]);
+
+        let yes_vote_commit = commitment::commit(self.yes_vote_value, self.yes_vote_blind);
+        let (yes_vote_x, yes_vote_y) = commitment::xy(&yes_vote_commit);
+
+        let abstain_vote_commit =
+            commitment::commit(self.abstain_vote_value, self.abstain_vote_blind);
+        let (abstain_vote_x, abstain_vote_y) = commitment::xy(&abstain_vote_commit);
+
+        let all_vote_commit = commitment::commit(self.all_vote_value, self.all_vote_blind);
+        let (all_vote_x, all_vote_y) = commitment::xy(&all_vote_commit);
+
+        let dao_spend_hook =
+            FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::Exec as u8 }
+                .to_func_id();
+
+        let prover_witnesses = vec![
+            // proposal params
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
+            Witness::Base(Value::known(self.proposal.user_data)),
+            Witness::Base(Value::known(self.proposal.blind.inner())),
+            // DAO params
+            Witness::Base(Value::known(pallas::Base::from(self.dao.proposer_limit))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.proposal_deposit))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.early_exec_quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.approval_ratio_quot))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.approval_ratio_base))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.vote_weight.mode()))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.vote_weight.cap()))),
+            Witness::Base(Value::known(self.dao.gov_token_id.inner())),
+            Witness::Base(Value::known(dao_pub_x)),
+            Witness::Base(Value::known(dao_pub_y)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
+            Witness::Base(Value::known(self.dao.bulla_blind.inner())),
+            // deposit coin
+            Witness::Base(Value::known(refund_x)),
+            Witness::Base(Value::known(refund_y)),
+            Witness::Base(Value::known(self.input_user_data_blind.inner())),
+            // votes
+            Witness::Base(Value::known(pallas::Base::from(self.yes_vote_value))),
+            Witness::Base(Value::known(pallas::Base::from(self.abstain_vote_value))),
+            Witness::Base(Value::known(pallas::Base::from(self.all_vote_value))),
+            Witness::Scalar(Value::known(self.yes_vote_blind.inner())),
+            Witness::Scalar(Value::known(self.abstain_vote_blind.inner())),
+            Witness::Scalar(Value::known(self.all_vote_blind.inner())),
+            // settlement time
+            Witness::Base(Value::known(pallas::Base::from(self.min_block_height))),
+            // outcome
+            Witness::Base(Value::known(pallas::Base::from(slashed as u64))),
+            Witness::Base(Value::known(pallas::Base::from(quorum_failed as u64))),
+            Witness::Base(Value::known(pallas::Base::from(PROPOSAL_DEPOSIT_SLASH_DIVISOR))),
+            // settled coin
+            Witness::Base(Value::known(dao_spend_hook.inner())),
+            Witness::Base(Value::known(self.output_coin_blind.inner())),
+        ];
+
+        let public_inputs = vec![
+            proposal_bulla.inner(),
+            input_user_data_enc,
+            yes_vote_x,
+            yes_vote_y,
+            abstain_vote_x,
+            abstain_vote_y,
+            all_vote_x,
+            all_vote_y,
+            pallas::Base::from(self.min_block_height),
+            pallas::Base::from(slashed as u64),
+            pallas::Base::from(PROPOSAL_DEPOSIT_SLASH_DIVISOR),
+            settled_coin.inner(),
+            dao_spend_hook.inner(),
+        ];
+
+        //darkfi::zk::export_witness_json("proof/witness/settle-deposit.json", &prover_witnesses, &public_inputs);
+        let circuit = ZkCircuit::new(prover_witnesses, settle_zkbin);
+        let proof = Proof::create(settle_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        let params = DaoSettleDepositParams {
+            proposal_bulla,
+            blind_total_vote: DaoBlindAggregateVote {
+                yes_vote_commit,
+                abstain_vote_commit,
+                all_vote_commit,
+                ..Default::default()
+            },
+            slashed,
+            min_block_height: self.min_block_height,
+        };
+
+        Ok((params, vec![proof]))
+    }
+}
@@ -0,0 +1,110 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::pasta::pallas;
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use crate::{
+    model::{Dao, DaoProposal, DaoVetoParams},
+    PROPOSAL_VETO_GRACE_BLOCKS,
+};
+
+/// Vetoes a proposal of a DAO with a guardian, before the grace period
+/// after voting ends is over. The transaction must be signed with the
+/// guardian secret key.
+pub struct DaoVetoCall {
+    pub proposal: DaoProposal,
+    pub dao: Dao,
+    /// Highest block height the veto can be confirmed at
+    pub max_block_height: u64,
+}
+
+impl DaoVetoCall {
+    pub fn make(
+        self,
+        veto_zkbin: &ZkBinary,
+        veto_pk: &ProvingKey,
+    ) -> Result<(DaoVetoParams, Vec<Proof>)> {
+        let guardian_public_key = self.dao.guardian_public_key.expect("DAO has no guardian");
+
+        let end_height = self.proposal.creation_height +
+            self.proposal.duration_blocks +
+            PROPOSAL_VETO_GRACE_BLOCKS;
+        assert!(self.max_block_height < end_height);
+
+        let dao_bulla = self.dao.to_bulla();
+        assert_eq!(dao_bulla, self.proposal.dao_bulla);
+        let proposal_bulla = self.proposal.to_bulla();
+
+        let (dao_pub_x, dao_pub_y) = self.dao.public_key.xy();
+        let (dao_guardian_x, dao_guardian_y) = guardian_public_key.xy();
+
+        let prover_witnesses = vec![
+            // proposal params
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
+            Witness::Base(Value::known(self.proposal.user_data)),
+            Witness::Base(Value::known(self.proposal.blind.inner())),
+            // DAO params
+            Witness::Base(Value::known(pallas::Base::from(self.dao.proposer_limit))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.proposal_deposit))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.early_exec_quorum))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.approval_ratio_quot))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.approval_ratio_base))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.vote_weight.mode()))),
+            Witness::Base(Value::known(pallas::Base::from(self.dao.vote_weight.cap()))),
+            Witness::Base(Value::known(self.dao.gov_token_id.inner())),
+            Witness::Base(Value::known(dao_pub_x)),
+            Witness::Base(Value::known(dao_pub_y)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
+            Witness::Base(Value::known(self.dao.bulla_blind.inner())),
+            // veto time
+            Witness::Base(Value::known(pallas::Base::from(self.max_block_height))),
+            Witness::Base(Value::known(pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS))),
+        ];
+
+        let public_inputs = vec![
+            proposal_bulla.inner(),
+            dao_guardian_x,
+            dao_guardian_y,
+            pallas::Base::from(self.max_block_height),
+            pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS),
+        ];
+
+        //darkfi::zk::export_witness_json("proof/witness/veto.json", &prover_witnesses, &public_inputs);
+        let circuit = ZkCircuit::new(prover_witnesses, veto_zkbin);
+        let proof = Proof::create(veto_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        let params = DaoVetoParams {
+            proposal_bulla,
+            guardian_public_key,
+            max_block_height: self.max_block_height,
+        };
+
+        Ok((params, vec![proof]))
+    }
+}
@@ -16,14 +16,13 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use darkfi_money_contract::model::CoinAttributes;
+use darkfi_money_contract::model::{CoinAttributes, Nullifier};
 use darkfi_sdk::{
     bridgetree,
     bridgetree::Hashable,
     crypto::{
-        note::ElGamalEncryptedNote, pasta_prelude::*, pedersen_commitment_u64, poseidon_hash,
-        smt::SmtMemoryFp, util::fv_mod_fp_unsafe, Blind, FuncId, Keypair, MerkleNode, PublicKey,
-        SecretKey,
+        commitment, note::ElGamalEncryptedNote, pasta_prelude::*, poseidon_hash, smt::SmtMemoryFp,
+        util::fv_mod_fp_unsafe, Blind, FuncId, Keypair, MerkleNode, PublicKey, SecretKey,
     },
     pasta::pallas,
 };
@@ -36,7 +35,9 @@ use darkfi::{
     Result,
 };
 
-use crate::model::{Dao, DaoProposal, DaoVoteParams, DaoVoteParamsInput, VecAuthCallCommit};
+use crate::model::{
+    Dao, DaoProposal, DaoProposalBulla, DaoVoteParams, DaoVoteParamsInput, DaoVoteWeight,
+};
 
 pub struct DaoVoteInput {
     pub secret: SecretKey,
@@ -51,10 +52,17 @@ pub struct DaoVoteCall<'a> {
     pub money_null_smt: &'a SmtMemoryFp,
     pub inputs: Vec<DaoVoteInput>,
     pub vote_option: bool,
+    /// Abstaining votes count towards quorum only.
+    /// `vote_option` must be false when abstaining.
+    pub abstain: bool,
     pub proposal: DaoProposal,
     pub dao: Dao,
     pub dao_keypair: Keypair,
-    pub current_day: u64,
+    /// Highest block height the vote can be confirmed at
+    pub max_block_height: u64,
+    /// Tally key of a sealed proposal, which the vote note is
+    /// encrypted to instead of the DAO key
+    pub tally_public_key: Option<PublicKey>,
 }
 
 impl<'a> DaoVoteCall<'a> {
@@ -74,211 +82,164 @@ impl<'a> DaoVoteCall<'a> {
 
         let gov_token_blind = pallas::Base::random(&mut OsRng);
 
-        let mut inputs = vec![];
-        let mut all_vote_value = 0;
-        let mut all_vote_blind = pallas::Scalar::from(0);
-
-        let last_input_idx = self.inputs.len() - 1;
-        for (i, input) in self.inputs.into_iter().enumerate() {
-            // Last input
-            // Choose a blinding factor that can be converted to pallas::Base exactly.
-            // We need this so we can verifiably encrypt the sum of input blinds
-            // in the next section.
-            // TODO: make a generalized widget for this, and also picking blinds in money::transfer()
-            let mut value_blind = pallas::Scalar::random(&mut OsRng);
-
-            if i == last_input_idx {
-                // It's near zero chance it ever loops at all.
-                // P(random 𝔽ᵥ ∉ 𝔽ₚ) = (q - p)/q = 2.99 × 10⁻⁵¹
-                loop {
-                    let av_blind = fv_mod_fp_unsafe(all_vote_blind + value_blind);
-
-                    if av_blind.is_none().into() {
-                        value_blind = pallas::Scalar::random(&mut OsRng);
-                        continue
-                    }
-
-                    break
-                }
-            }
-
-            all_vote_value += input.note.value;
-            all_vote_blind += value_blind;
-
-            let signature_public = PublicKey::from_secret(input.signature_secret);
-
-            // Note from the previous output
-            let note = input.note;
-            let leaf_pos: u64 = input.leaf_position.into();
-
-            let public_key = PublicKey::from_secret(input.secret);
-            let coin = CoinAttributes {
-                public_key,
-                value: note.value,
-                token_id: note.token_id,
-                spend_hook: FuncId::none(),
-                user_data: pallas::Base::ZERO,
-                blind: note.coin_blind,
-            }
-            .to_coin();
-            let nullifier = poseidon_hash([input.secret.inner(), coin.inner()]);
-
-            let smt_null_root = self.money_null_smt.root();
-            let smt_null_path = self.money_null_smt.prove_membership(&nullifier);
-            assert!(smt_null_path.verify(&smt_null_root, &pallas::Base::ZERO, &nullifier));
-
-            let prover_witnesses = vec![
-                Witness::Base(Value::known(input.secret.inner())),
-                Witness::Base(Value::known(pallas::Base::from(note.value))),
-                Witness::Base(Value::known(note.token_id.inner())),
-                Witness::Base(Value::known(pallas::Base::ZERO)),
-                Witness::Base(Value::known(pallas::Base::ZERO)),
-                Witness::Base(Value::known(note.coin_blind.inner())),
-                Witness::Base(Value::known(proposal_bulla.inner())),
-                Witness::Scalar(Value::known(value_blind)),
-                Witness::Base(Value::known(gov_token_blind)),
-                Witness::Uint32(Value::known(leaf_pos.try_into().unwrap())),
-                Witness::MerklePath(Value::known(input.merkle_path.clone().try_into().unwrap())),
-                Witness::SparseMerklePath(Value::known(smt_null_path.path)),
-                Witness::Base(Value::known(input.signature_secret.inner())),
-            ];
-
-            let merkle_root = {
-                let position: u64 = input.leaf_position.into();
-                let mut current = MerkleNode::from(coin.inner());
-                for (level, sibling) in input.merkle_path.iter().enumerate() {
-                    let level = level as u8;
-                    current = if position & (1 << level) == 0 {
-                        MerkleNode::combine(level.into(), &current, sibling)
-                    } else {
-                        MerkleNode::combine(level.into(), sibling, &current)
-                    };
-                }
-                current
-            };
-
-            let token_commit = poseidon_hash([note.token_id.inner(), gov_token_blind]);
-            assert_eq!(self.dao.gov_token_id, note.token_id);
-
-            let vote_commit = pedersen_commitment_u64(note.value, Blind(value_blind));
-            let vote_commit_coords = vote_commit.to_affine().coordinates().unwrap();
-
-            let (sig_x, sig_y) = signature_public.xy();
-
-            let vote_nullifier =
-                poseidon_hash([nullifier, input.secret.inner(), proposal_bulla.inner()]);
-
-            let public_inputs = vec![
-                smt_null_root,
-                proposal_bulla.inner(),
-                vote_nullifier,
-                *vote_commit_coords.x(),
-                *vote_commit_coords.y(),
-                token_commit,
-                merkle_root.inner(),
-                sig_x,
-                sig_y,
-            ];
-
-            //darkfi::zk::export_witness_json("proof/witness/vote-input.json", &prover_witnesses, &public_inputs);
-            let circuit = ZkCircuit::new(prover_witnesses, burn_zkbin);
-            debug!(target: "dao", "input_proof Proof::create()");
-            let input_proof = Proof::create(burn_pk, &[circuit], &public_inputs, &mut OsRng)?;
-            proofs.push(input_proof);
-
-            let input = DaoVoteParamsInput {
-                vote_commit,
-                vote_nullifier: vote_nullifier.into(),
-                signature_public,
-            };
-            inputs.push(input);
-        }
+        let (inputs, balance, balance_blind) = make_vote_inputs(
+            self.money_null_smt,
+            self.inputs,
+            &self.dao,
+            proposal_bulla,
+            gov_token_blind,
+            burn_zkbin,
+            burn_pk,
+            &mut proofs,
+        )?;
 
         let token_commit = poseidon_hash([self.dao.gov_token_id.inner(), gov_token_blind]);
 
         let dao_proposer_limit = pallas::Base::from(self.dao.proposer_limit);
+        let dao_proposal_deposit = pallas::Base::from(self.dao.proposal_deposit);
         let dao_quorum = pallas::Base::from(self.dao.quorum);
+        let dao_early_exec_quorum = pallas::Base::from(self.dao.early_exec_quorum);
         let dao_approval_ratio_quot = pallas::Base::from(self.dao.approval_ratio_quot);
         let dao_approval_ratio_base = pallas::Base::from(self.dao.approval_ratio_base);
+        let dao_vote_weight_mode = pallas::Base::from(self.dao.vote_weight.mode());
+        let dao_vote_weight_cap = pallas::Base::from(self.dao.vote_weight.cap());
         let dao_public_key = self.dao.public_key.inner();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
+
+        let (all_vote_value, vote_weight_hints) = make_vote_weight(&self.dao, balance);
 
         let vote_option = self.vote_option as u64;
         assert!(vote_option == 0 || vote_option == 1);
+        let abstain = self.abstain as u64;
+        assert!(vote_option + abstain <= 1);
 
-        // Create a random blind b ∈ 𝔽ᵥ, such that b ∈ 𝔽ₚ
-        let yes_vote_blind = loop {
-            let blind = pallas::Scalar::random(&mut OsRng);
-            if fv_mod_fp_unsafe(blind).is_some().into() {
-                break blind
-            }
-        };
+        let yes_vote_blind = random_vote_blind();
         let yes_vote_commit =
-            pedersen_commitment_u64(vote_option * all_vote_value, Blind(yes_vote_blind));
-        let yes_vote_commit_coords = yes_vote_commit.to_affine().coordinates().unwrap();
+            commitment::commit(vote_option * all_vote_value, Blind(yes_vote_blind));
+        let (yes_vote_commit_x, yes_vote_commit_y) = commitment::xy(&yes_vote_commit);
+
+        let abstain_vote_blind = random_vote_blind();
+        let abstain_vote_commit =
+            commitment::commit(abstain * all_vote_value, Blind(abstain_vote_blind));
+        let (abstain_vote_commit_x, abstain_vote_commit_y) = commitment::xy(&abstain_vote_commit);
+
+        let balance_commit = commitment::commit(balance, Blind(balance_blind));
+        assert_eq!(balance_commit, inputs.iter().map(|i| i.vote_commit).sum());
+        let (balance_commit_x, balance_commit_y) = commitment::xy(&balance_commit);
 
-        let all_vote_commit = pedersen_commitment_u64(all_vote_value, Blind(all_vote_blind));
-        assert_eq!(all_vote_commit, inputs.iter().map(|i| i.vote_commit).sum());
-        let all_vote_commit_coords = all_vote_commit.to_affine().coordinates().unwrap();
+        let all_vote_blind = random_vote_blind();
+        let all_vote_commit = commitment::commit(all_vote_value, Blind(all_vote_blind));
+        let (all_vote_commit_x, all_vote_commit_y) = commitment::xy(&all_vote_commit);
 
         // Convert blinds to 𝔽ₚ, which should work fine since we selected them
         // to be convertable.
         let yes_vote_blind = Blind(fv_mod_fp_unsafe(yes_vote_blind).unwrap());
+        let abstain_vote_blind = Blind(fv_mod_fp_unsafe(abstain_vote_blind).unwrap());
+        let balance_blind = Blind(fv_mod_fp_unsafe(balance_blind).unwrap());
         let all_vote_blind = Blind(fv_mod_fp_unsafe(all_vote_blind).unwrap());
 
         let vote_option = pallas::Base::from(vote_option);
+        let abstain = pallas::Base::from(abstain);
         let all_vote_value_fp = pallas::Base::from(all_vote_value);
         let ephem_secret = SecretKey::random(&mut OsRng);
         let ephem_pubkey = PublicKey::from_secret(ephem_secret);
         let (ephem_x, ephem_y) = ephem_pubkey.xy();
 
-        let current_day = pallas::Base::from(self.current_day);
+        let max_block_height = pallas::Base::from(self.max_block_height);
+        let (sealed, tally_public_x, tally_public_y, note_public_key) = match self.tally_public_key
+        {
+            Some(tally_public_key) => {
+                let (x, y) = tally_public_key.xy();
+                (pallas::Base::ONE, x, y, tally_public_key)
+            }
+            None => (
+                pallas::Base::ZERO,
+                pallas::Base::ZERO,
+                pallas::Base::ZERO,
+                self.dao_keypair.public,
+            ),
+        };
+
         let prover_witnesses = vec![
             // proposal params
-            Witness::Base(Value::known(self.proposal.auth_calls.commit())),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_day))),
-            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_days))),
+            Witness::Base(Value::known(self.proposal.auth_calls_commit())),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
             Witness::Base(Value::known(self.proposal.user_data)),
             Witness::Base(Value::known(self.proposal.blind.inner())),
             // DAO params
             Witness::Base(Value::known(dao_proposer_limit)),
+            Witness::Base(Value::known(dao_proposal_deposit)),
             Witness::Base(Value::known(dao_quorum)),
+            Witness::Base(Value::known(dao_early_exec_quorum)),
             Witness::Base(Value::known(dao_approval_ratio_quot)),
             Witness::Base(Value::known(dao_approval_ratio_base)),
+            Witness::Base(Value::known(dao_vote_weight_mode)),
+            Witness::Base(Value::known(dao_vote_weight_cap)),
             Witness::Base(Value::known(self.dao.gov_token_id.inner())),
             Witness::EcNiPoint(Value::known(dao_public_key)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
             Witness::Base(Value::known(self.dao.bulla_blind.inner())),
             // Vote
             Witness::Base(Value::known(vote_option)),
             Witness::Base(Value::known(yes_vote_blind.inner())),
-            // Total number of gov tokens allocated
-            Witness::Base(Value::known(all_vote_value_fp)),
+            Witness::Base(Value::known(abstain)),
+            Witness::Base(Value::known(abstain_vote_blind.inner())),
+            // Total number of gov tokens allocated, and the vote weight
+            Witness::Base(Value::known(pallas::Base::from(balance))),
+            Witness::Base(Value::known(balance_blind.inner())),
+            Witness::Base(Value::known(vote_weight_hints[0])),
+            Witness::Base(Value::known(vote_weight_hints[1])),
+            Witness::Base(Value::known(vote_weight_hints[2])),
+            Witness::Base(Value::known(vote_weight_hints[3])),
             Witness::Base(Value::known(all_vote_blind.inner())),
             // gov token
             Witness::Base(Value::known(gov_token_blind)),
             // time checks
-            Witness::Base(Value::known(current_day)),
+            Witness::Base(Value::known(max_block_height)),
+            // sealed proposal
+            Witness::Base(Value::known(sealed)),
+            Witness::Base(Value::known(tally_public_x)),
+            Witness::Base(Value::known(tally_public_y)),
+            Witness::EcNiPoint(Value::known(note_public_key.inner())),
             // verifiable encryption
             Witness::Base(Value::known(ephem_secret.inner())),
         ];
 
-        let note = [vote_option, yes_vote_blind.inner(), all_vote_value_fp, all_vote_blind.inner()];
-        let enc_note =
-            ElGamalEncryptedNote::encrypt_unsafe(note, &ephem_secret, &self.dao_keypair.public)?;
+        let note = [
+            vote_option,
+            yes_vote_blind.inner(),
+            all_vote_value_fp,
+            all_vote_blind.inner(),
+            abstain,
+            abstain_vote_blind.inner(),
+        ];
+        let enc_note = ElGamalEncryptedNote::encrypt_unsafe(note, &ephem_secret, &note_public_key)?;
 
         let public_inputs = vec![
             token_commit,
             proposal_bulla.inner(),
-            *yes_vote_commit_coords.x(),
-            *yes_vote_commit_coords.y(),
-            *all_vote_commit_coords.x(),
-            *all_vote_commit_coords.y(),
-            current_day,
+            balance_commit_x,
+            balance_commit_y,
+            yes_vote_commit_x,
+            yes_vote_commit_y,
+            abstain_vote_commit_x,
+            abstain_vote_commit_y,
+            all_vote_commit_x,
+            all_vote_commit_y,
+            max_block_height,
+            sealed,
+            tally_public_x,
+            tally_public_y,
             ephem_x,
             ephem_y,
             enc_note.encrypted_values[0],
             enc_note.encrypted_values[1],
             enc_note.encrypted_values[2],
             enc_note.encrypted_values[3],
+            enc_note.encrypted_values[4],
+            enc_note.encrypted_values[5],
         ];
 
         //darkfi::zk::export_witness_json("proof/witness/vote-main.json", &prover_witnesses, &public_inputs);
@@ -288,9 +249,182 @@ impl<'a> DaoVoteCall<'a> {
         let main_proof = Proof::create(main_pk, &[circuit], &public_inputs, &mut OsRng)?;
         proofs.push(main_proof);
 
-        let params =
-            DaoVoteParams { token_commit, proposal_bulla, yes_vote_commit, note: enc_note, inputs };
+        let params = DaoVoteParams {
+            token_commit,
+            proposal_bulla,
+            yes_vote_commit,
+            abstain_vote_commit,
+            all_vote_commit,
+            max_block_height: self.max_block_height,
+            note: enc_note,
+            inputs,
+        };
 
         Ok((params, proofs))
     }
 }
+
+/// Create a random blind b ∈ 𝔽ᵥ, such that b ∈ 𝔽ₚ
+pub(crate) fn random_vote_blind() -> pallas::Scalar {
+    loop {
+        let blind = pallas::Scalar::random(&mut OsRng);
+        if fv_mod_fp_unsafe(blind).is_some().into() {
+            break blind
+        }
+    }
+}
+
+/// Vote weight the DAO gives to the balance, along with the witnesses
+/// deriving it in the vote proofs: the vote weight mode flags, the
+/// balance's square root and whether it exceeds the cap.
+pub(crate) fn make_vote_weight(dao: &Dao, balance: u64) -> (u64, [pallas::Base; 4]) {
+    let is_sqrt = dao.vote_weight == DaoVoteWeight::SquareRoot;
+    let is_capped = matches!(dao.vote_weight, DaoVoteWeight::Capped(_));
+    let balance_capped = balance > dao.vote_weight.cap();
+
+    let hints = [
+        pallas::Base::from(is_sqrt as u64),
+        pallas::Base::from(is_capped as u64),
+        pallas::Base::from(balance.isqrt()),
+        pallas::Base::from(balance_capped as u64),
+    ];
+
+    (dao.vote_weight.apply(balance), hints)
+}
+
+/// Create the proofs for the coins used to vote, shared by yes/no and
+/// multiple-choice votes. Returns the vote inputs along with the total
+/// balance and blind of their commitments.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn make_vote_inputs(
+    money_null_smt: &SmtMemoryFp,
+    inputs: Vec<DaoVoteInput>,
+    dao: &Dao,
+    proposal_bulla: DaoProposalBulla,
+    gov_token_blind: pallas::Base,
+    burn_zkbin: &ZkBinary,
+    burn_pk: &ProvingKey,
+    proofs: &mut Vec<Proof>,
+) -> Result<(Vec<DaoVoteParamsInput>, u64, pallas::Scalar)> {
+    let mut vote_inputs = vec![];
+    let mut all_vote_value = 0;
+    let mut all_vote_blind = pallas::Scalar::from(0);
+
+    let last_input_idx = inputs.len() - 1;
+    for (i, input) in inputs.into_iter().enumerate() {
+        // Last input
+        // Choose a blinding factor that can be converted to pallas::Base exactly.
+        // We need this so we can verifiably encrypt the sum of input blinds
+        // in the next section.
+        // TODO: make a generalized widget for this, and also picking blinds in money::transfer()
+        let mut value_blind = pallas::Scalar::random(&mut OsRng);
+
+        if i == last_input_idx {
+            // It's near zero chance it ever loops at all.
+            // P(random 𝔽ᵥ ∉ 𝔽ₚ) = (q - p)/q = 2.99 × 10⁻⁵¹
+            loop {
+                let av_blind = fv_mod_fp_unsafe(all_vote_blind + value_blind);
+
+                if av_blind.is_none().into() {
+                    value_blind = pallas::Scalar::random(&mut OsRng);
+                    continue
+                }
+
+                break
+            }
+        }
+
+        all_vote_value += input.note.value;
+        all_vote_blind += value_blind;
+
+        let signature_public = PublicKey::from_secret(input.signature_secret);
+
+        // Note from the previous output
+        let note = input.note;
+        let leaf_pos: u64 = input.leaf_position.into();
+
+        let public_key = PublicKey::from_secret(input.secret);
+        let coin = CoinAttributes {
+            public_key,
+            value: note.value,
+            token_id: note.token_id,
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+            blind: note.coin_blind,
+        }
+        .to_coin();
+        let nullifier = Nullifier::derive(&input.secret, &coin).inner();
+
+        let smt_null_root = money_null_smt.root();
+        let smt_null_path = money_null_smt.prove_membership(&nullifier);
+        assert!(smt_null_path.verify(&smt_null_root, &pallas::Base::ZERO, &nullifier));
+
+        let prover_witnesses = vec![
+            Witness::Base(Value::known(input.secret.inner())),
+            Witness::Base(Value::known(pallas::Base::from(note.value))),
+            Witness::Base(Value::known(note.token_id.inner())),
+            Witness::Base(Value::known(pallas::Base::ZERO)),
+            Witness::Base(Value::known(pallas::Base::ZERO)),
+            Witness::Base(Value::known(note.coin_blind.inner())),
+            Witness::Base(Value::known(proposal_bulla.inner())),
+            Witness::Scalar(Value::known(value_blind)),
+            Witness::Base(Value::known(gov_token_blind)),
+            Witness::Uint32(Value::known(leaf_pos.try_into().unwrap())),
+            Witness::MerklePath(Value::known(input.merkle_path.clone().try_into().unwrap())),
+            Witness::SparseMerklePath(Value::known(smt_null_path.path)),
+            Witness::Base(Value::known(input.signature_secret.inner())),
+        ];
+
+        let merkle_root = {
+            let position: u64 = input.leaf_position.into();
+            let mut current = MerkleNode::from(coin.inner());
+            for (level, sibling) in input.merkle_path.iter().enumerate() {
+                let level = level as u8;
+                current = if position & (1 << level) == 0 {
+                    MerkleNode::combine(level.into(), &current, sibling)
+                } else {
+                    MerkleNode::combine(level.into(), sibling, &current)
+                };
+            }
+            current
+        };
+
+        let token_commit = poseidon_hash([note.token_id.inner(), gov_token_blind]);
+        assert_eq!(dao.gov_token_id, note.token_id);
+
+        let vote_commit = commitment::commit(note.value, Blind(value_blind));
+        let (vote_commit_x, vote_commit_y) = commitment::xy(&vote_commit);
+
+        let (sig_x, sig_y) = signature_public.xy();
+
+        let vote_nullifier =
+            poseidon_hash([nullifier, input.secret.inner(), proposal_bulla.inner()]);
+
+        let public_inputs = vec![
+            smt_null_root,
+            proposal_bulla.inner(),
+            vote_nullifier,
+            vote_commit_x,
+            vote_commit_y,
+            token_commit,
+            merkle_root.inner(),
+            sig_x,
+            sig_y,
+        ];
+
+        //darkfi::zk::export_witness_json("proof/witness/vote-input.json", &prover_witnesses, &public_inputs);
+        let circuit = ZkCircuit::new(prover_witnesses, burn_zkbin);
+        debug!(target: "dao", "input_proof Proof::create()");
+        let input_proof = Proof::create(burn_pk, &[circuit], &public_inputs, &mut OsRng)?;
+        proofs.push(input_proof);
+
+        let input = DaoVoteParamsInput {
+            vote_commit,
+            vote_nullifier: vote_nullifier.into(),
+            signature_public,
+        };
+        vote_inputs.push(input);
+    }
+
+    Ok((vote_inputs, all_vote_value, all_vote_blind))
+}
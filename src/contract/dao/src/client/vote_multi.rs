@@ -0,0 +1,229 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{
+        commitment, note::ElGamalEncryptedNote, pasta_prelude::*, poseidon_hash, smt::SmtMemoryFp,
+        util::fv_mod_fp_unsafe, Blind, Keypair, PublicKey, SecretKey,
+    },
+    pasta::pallas,
+};
+use log::debug;
+use rand::rngs::OsRng;
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+
+use super::vote::{make_vote_inputs, make_vote_weight, random_vote_blind, DaoVoteInput};
+use crate::{
+    model::{Dao, DaoProposal, DaoVoteMultiParams},
+    PROPOSAL_MAX_OPTIONS,
+};
+
+// Inside ZK proof, check proposal is correct.
+pub struct DaoVoteMultiCall<'a> {
+    pub money_null_smt: &'a SmtMemoryFp,
+    pub inputs: Vec<DaoVoteInput>,
+    /// Index of the chosen proposal option, or `None` to abstain
+    pub option: Option<usize>,
+    pub proposal: DaoProposal,
+    pub dao: Dao,
+    pub dao_keypair: Keypair,
+    /// Highest block height the vote can be confirmed at
+    pub max_block_height: u64,
+}
+
+impl<'a> DaoVoteMultiCall<'a> {
+    pub fn make(
+        self,
+        burn_zkbin: &ZkBinary,
+        burn_pk: &ProvingKey,
+        main_zkbin: &ZkBinary,
+        main_pk: &ProvingKey,
+    ) -> Result<(DaoVoteMultiParams, Vec<Proof>)> {
+        debug!(target: "dao", "build()");
+
+        assert_eq!(self.dao.to_bulla(), self.proposal.dao_bulla);
+        let proposal_bulla = self.proposal.to_bulla();
+
+        if let Some(option) = self.option {
+            assert!(option < self.proposal.options.len());
+        }
+
+        let mut proofs = vec![];
+
+        let gov_token_blind = pallas::Base::random(&mut OsRng);
+
+        let (inputs, balance, balance_blind) = make_vote_inputs(
+            self.money_null_smt,
+            self.inputs,
+            &self.dao,
+            proposal_bulla,
+            gov_token_blind,
+            burn_zkbin,
+            burn_pk,
+            &mut proofs,
+        )?;
+
+        let token_commit = poseidon_hash([self.dao.gov_token_id.inner(), gov_token_blind]);
+
+        let dao_proposer_limit = pallas::Base::from(self.dao.proposer_limit);
+        let dao_proposal_deposit = pallas::Base::from(self.dao.proposal_deposit);
+        let dao_quorum = pallas::Base::from(self.dao.quorum);
+        let dao_early_exec_quorum = pallas::Base::from(self.dao.early_exec_quorum);
+        let dao_approval_ratio_quot = pallas::Base::from(self.dao.approval_ratio_quot);
+        let dao_approval_ratio_base = pallas::Base::from(self.dao.approval_ratio_base);
+        let dao_vote_weight_mode = pallas::Base::from(self.dao.vote_weight.mode());
+        let dao_vote_weight_cap = pallas::Base::from(self.dao.vote_weight.cap());
+        let dao_public_key = self.dao.public_key.inner();
+        let (dao_guardian_x, dao_guardian_y) = self.dao.guardian_xy();
+
+        let (all_vote_value, vote_weight_hints) = make_vote_weight(&self.dao, balance);
+
+        // Only the chosen option commits to the vote value
+        let mut option_selectors = [0u64; PROPOSAL_MAX_OPTIONS];
+        if let Some(option) = self.option {
+            option_selectors[option] = 1;
+        }
+
+        let option_vote_blinds: [pallas::Scalar; PROPOSAL_MAX_OPTIONS] =
+            std::array::from_fn(|_| random_vote_blind());
+        let option_vote_commits = std::array::from_fn(|i| {
+            commitment::commit(option_selectors[i] * all_vote_value, Blind(option_vote_blinds[i]))
+        });
+        // Convert blinds to 𝔽ₚ, which should work fine since we selected them
+        // to be convertable.
+        let option_vote_blinds = option_vote_blinds.map(|blind| fv_mod_fp_unsafe(blind).unwrap());
+
+        let balance_commit = commitment::commit(balance, Blind(balance_blind));
+        assert_eq!(balance_commit, inputs.iter().map(|i| i.vote_commit).sum());
+        let (balance_commit_x, balance_commit_y) = commitment::xy(&balance_commit);
+        let balance_blind = Blind(fv_mod_fp_unsafe(balance_blind).unwrap());
+
+        let all_vote_blind = random_vote_blind();
+        let all_vote_commit = commitment::commit(all_vote_value, Blind(all_vote_blind));
+        let (all_vote_commit_x, all_vote_commit_y) = commitment::xy(&all_vote_commit);
+        let all_vote_blind = Blind(fv_mod_fp_unsafe(all_vote_blind).unwrap());
+
+        // The option is encoded as its index plus one, with zero meaning abstain
+        let vote_option = pallas::Base::from(self.option.map_or(0, |option| option as u64 + 1));
+        let all_vote_value_fp = pallas::Base::from(all_vote_value);
+        let ephem_secret = SecretKey::random(&mut OsRng);
+        let ephem_pubkey = PublicKey::from_secret(ephem_secret);
+        let (ephem_x, ephem_y) = ephem_pubkey.xy();
+
+        let max_block_height = pallas::Base::from(self.max_block_height);
+        let mut prover_witnesses = vec![];
+        // proposal params
+        for option_commit in self.proposal.option_commits() {
+            prover_witnesses.push(Witness::Base(Value::known(option_commit)));
+        }
+        prover_witnesses.extend([
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.creation_height))),
+            Witness::Base(Value::known(pallas::Base::from(self.proposal.duration_blocks))),
+            Witness::Base(Value::known(self.proposal.user_data)),
+            Witness::Base(Value::known(self.proposal.blind.inner())),
+            // DAO params
+            Witness::Base(Value::known(dao_proposer_limit)),
+            Witness::Base(Value::known(dao_proposal_deposit)),
+            Witness::Base(Value::known(dao_quorum)),
+            Witness::Base(Value::known(dao_early_exec_quorum)),
+            Witness::Base(Value::known(dao_approval_ratio_quot)),
+            Witness::Base(Value::known(dao_approval_ratio_base)),
+            Witness::Base(Value::known(dao_vote_weight_mode)),
+            Witness::Base(Value::known(dao_vote_weight_cap)),
+            Witness::Base(Value::known(self.dao.gov_token_id.inner())),
+            Witness::EcNiPoint(Value::known(dao_public_key)),
+            Witness::Base(Value::known(dao_guardian_x)),
+            Witness::Base(Value::known(dao_guardian_y)),
+            Witness::Base(Value::known(self.dao.bulla_blind.inner())),
+        ]);
+        // Vote
+        for selector in option_selectors {
+            prover_witnesses.push(Witness::Base(Value::known(pallas::Base::from(selector))));
+        }
+        for blind in option_vote_blinds {
+            prover_witnesses.push(Witness::Base(Value::known(blind)));
+        }
+        prover_witnesses.extend([
+            // Total number of gov tokens allocated, and the vote weight
+            Witness::Base(Value::known(pallas::Base::from(balance))),
+            Witness::Base(Value::known(balance_blind.inner())),
+            Witness::Base(Value::known(vote_weight_hints[0])),
+            Witness::Base(Value::known(vote_weight_hints[1])),
+            Witness::Base(Value::known(vote_weight_hints[2])),
+            Witness::Base(Value::known(vote_weight_hints[3])),
+            Witness::Base(Value::known(all_vote_blind.inner())),
+            // gov token
+            Witness::Base(Value::known(gov_token_blind)),
+            // time checks
+            Witness::Base(Value::known(max_block_height)),
+            // verifiable encryption
+            Witness::Base(Value::known(ephem_secret.inner())),
+        ]);
+
+        let note = [
+            vote_option,
+            option_vote_blinds[0],
+            option_vote_blinds[1],
+            option_vote_blinds[2],
+            option_vote_blinds[3],
+            all_vote_value_fp,
+            all_vote_blind.inner(),
+        ];
+        let enc_note =
+            ElGamalEncryptedNote::encrypt_unsafe(note, &ephem_secret, &self.dao_keypair.public)?;
+
+        let mut public_inputs =
+            vec![token_commit, proposal_bulla.inner(), balance_commit_x, balance_commit_y];
+        for option_vote_commit in &option_vote_commits {
+            let (commit_x, commit_y) = commitment::xy(option_vote_commit);
+            public_inputs.push(commit_x);
+            public_inputs.push(commit_y);
+        }
+        public_inputs.extend([
+            all_vote_commit_x,
+            all_vote_commit_y,
+            max_block_height,
+            ephem_x,
+            ephem_y,
+        ]);
+        public_inputs.extend_from_slice(&enc_note.encrypted_values);
+
+        let circuit = ZkCircuit::new(prover_witnesses, main_zkbin);
+
+        debug!(target: "dao", "main_proof = Proof::create()");
+        let main_proof = Proof::create(main_pk, &[circuit], &public_inputs, &mut OsRng)?;
+        proofs.push(main_proof);
+
+        let params = DaoVoteMultiParams {
+            token_commit,
+            proposal_bulla,
+            option_vote_commits,
+            all_vote_commit,
+            max_block_height: self.max_block_height,
+            note: enc_note,
+            inputs,
+        };
+
+        Ok((params, proofs))
+    }
+}
@@ -0,0 +1,255 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use darkfi_sdk::{
+    crypto::{
+        smt::{
+            wasmdb::{SmtWasmDbStorage, SmtWasmFp},
+            PoseidonFp, EMPTY_NODES_FP,
+        },
+        ContractId, MerkleNode, PublicKey,
+    },
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use super::{auth_xfer::find_auth_in_parent, events::dao_record_event};
+use crate::{
+    error::DaoError,
+    model::{DaoAmendParams, DaoAmendUpdate, DaoBulla, DaoEvent, DaoExecParams, VecAuthCallCommit},
+    DaoFunction, DAO_CONTRACT_DB_DAO_AMENDMENTS, DAO_CONTRACT_DB_DAO_BULLAS,
+    DAO_CONTRACT_DB_DAO_LINEAGES, DAO_CONTRACT_DB_DAO_MERKLE_ROOTS, DAO_CONTRACT_DB_INFO_TREE,
+    DAO_CONTRACT_DB_SUPERSEDED_DAOS, DAO_CONTRACT_KEY_DAO_MERKLE_TREE,
+    DAO_CONTRACT_KEY_LATEST_DAO_ROOT, DAO_CONTRACT_ZKAS_DAO_AMEND_NS,
+};
+
+/// Open the SMT holding the set of amended DAOs, which can no longer
+/// make or execute proposals. Each leaf is the latest successor of the
+/// amended DAO, which can spend its treasury.
+pub(crate) fn superseded_daos_smt(cid: ContractId) -> Result<SmtWasmFp, ContractError> {
+    let superseded_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_SUPERSEDED_DAOS)?;
+    let smt_store = SmtWasmDbStorage::new(superseded_db);
+    Ok(SmtWasmFp::new(smt_store, PoseidonFp::new(), &EMPTY_NODES_FP))
+}
+
+/// Grab the old and new bulla of the DAO amended by a `Dao::Amend`
+/// child of the `Dao::Exec` call at `call_idx`, if it has one.
+pub(crate) fn amended_dao_in_children(
+    cid: ContractId,
+    call_idx: u32,
+    calls: &[DarkLeaf<ContractCall>],
+) -> Result<Option<(DaoBulla, DaoBulla)>, ContractError> {
+    for child_idx in &calls[call_idx as usize].children_indexes {
+        let child_call = &calls[*child_idx].data;
+        if child_call.contract_id != cid || child_call.data[0] != DaoFunction::Amend as u8 {
+            continue
+        }
+
+        let params: DaoAmendParams = deserialize_strict(&child_call.data[1..])?;
+        return Ok(Some((params.old_dao_bulla, params.new_dao_bulla)))
+    }
+
+    Ok(None)
+}
+
+/// Mark `old_dao_bulla` superseded by `new_dao_bulla`, along with every
+/// DAO it superseded itself, so the new DAO can spend their treasuries.
+pub(crate) fn dao_supersede(
+    cid: ContractId,
+    old_dao_bulla: &DaoBulla,
+    new_dao_bulla: &DaoBulla,
+) -> ContractResult {
+    let lineages_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_LINEAGES)?;
+
+    let mut lineage: Vec<DaoBulla> = match wasm::db::db_get(lineages_db, &serialize(old_dao_bulla))?
+    {
+        Some(data) => deserialize(&data)?,
+        None => vec![],
+    };
+    lineage.push(*old_dao_bulla);
+
+    let leaves = lineage.iter().map(|dao| (dao.inner(), new_dao_bulla.inner())).collect();
+    superseded_daos_smt(cid)?.insert_batch(leaves)?;
+
+    wasm::db::db_del(lineages_db, &serialize(old_dao_bulla))?;
+    wasm::db::db_set(lineages_db, &serialize(new_dao_bulla), &serialize(&lineage))?;
+
+    Ok(())
+}
+
+/// Grab the parent `Dao::Exec` call of `Dao::Amend`, erroring if the
+/// call isn't made from one.
+fn exec_parent(
+    cid: ContractId,
+    call_idx: u32,
+    calls: &[DarkLeaf<ContractCall>],
+) -> Result<&DarkLeaf<ContractCall>, ContractError> {
+    let Some(parent_idx) = calls[call_idx as usize].parent_index else {
+        msg!("[Dao::Amend] Error: Call has no parent");
+        return Err(DaoError::AmendCallInvalidParent.into())
+    };
+
+    let exec_callnode = &calls[parent_idx];
+    if exec_callnode.data.contract_id != cid ||
+        exec_callnode.data.data[0] != DaoFunction::Exec as u8
+    {
+        msg!("[Dao::Amend] Error: Parent call is not Dao::Exec");
+        return Err(DaoError::AmendCallInvalidParent.into())
+    }
+
+    Ok(exec_callnode)
+}
+
+/// `get_metadata` function for `Dao::Amend`
+pub(crate) fn dao_amend_get_metadata(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoAmendParams = deserialize_strict(&self_.data[1..])?;
+
+    let exec_callnode = exec_parent(cid, call_idx, &calls)?;
+    let exec_params: DaoExecParams = deserialize_strict(&exec_callnode.data.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let signature_pubkeys: Vec<PublicKey> = vec![];
+
+    // The proof links the executed proposal to the old DAO, and the
+    // new DAO to the old one.
+    zk_public_inputs.push((
+        DAO_CONTRACT_ZKAS_DAO_AMEND_NS.to_string(),
+        vec![
+            exec_params.proposal_bulla.inner(),
+            params.old_dao_bulla.inner(),
+            params.new_dao_bulla.inner(),
+        ],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Dao::Amend`
+pub(crate) fn dao_amend_process_instruction(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoAmendParams = deserialize_strict(&self_.data[1..])?;
+
+    ///////////////////////////////////////////////////
+    // 1. The proposal voted for this exact DAO
+    ///////////////////////////////////////////////////
+
+    let exec_callnode = exec_parent(cid, call_idx, &calls)?;
+    let exec_params: DaoExecParams = deserialize_strict(&exec_callnode.data.data[1..])?;
+
+    let Some(auth_call) =
+        find_auth_in_parent(exec_callnode, exec_params.proposal_auth_calls, call_idx)
+    else {
+        return Err(DaoError::AmendCallNotFoundInParent.into())
+    };
+
+    // The proposal auth data is the bulla of the amended DAO
+    let proposal_dao_bulla: DaoBulla = deserialize_strict(&auth_call.auth_data[..])?;
+    if proposal_dao_bulla != params.new_dao_bulla {
+        msg!("[Dao::Amend] Error: Proposal is for DAO {}", proposal_dao_bulla);
+        return Err(DaoError::AmendDaoBullaMismatch.into())
+    }
+
+    ///////////////////////////////////////////////////
+    // 2. Check the state of both DAOs
+    ///////////////////////////////////////////////////
+
+    let bulla_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_BULLAS)?;
+    if !wasm::db::db_contains_key(bulla_db, &serialize(&params.old_dao_bulla))? {
+        msg!("[Dao::Amend] Error: DAO {} doesn't exist", params.old_dao_bulla);
+        return Err(DaoError::DaoNonexistent.into())
+    }
+
+    // A DAO can only be amended once, after which its successor is the
+    // one that gets amended.
+    let amendments_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_AMENDMENTS)?;
+    if wasm::db::db_contains_key(amendments_db, &serialize(&params.old_dao_bulla))? {
+        msg!("[Dao::Amend] Error: DAO {} was already amended", params.old_dao_bulla);
+        return Err(DaoError::DaoAlreadyAmended.into())
+    }
+
+    if wasm::db::db_contains_key(bulla_db, &serialize(&params.new_dao_bulla))? {
+        msg!("[Dao::Amend] Error: DAO already exists {}", params.new_dao_bulla);
+        return Err(DaoError::DaoAlreadyExists.into())
+    }
+
+    // Create state update
+    let update = DaoAmendUpdate {
+        old_dao_bulla: params.old_dao_bulla,
+        new_dao_bulla: params.new_dao_bulla,
+        block_height: wasm::util::get_verifying_block_height()?,
+        event: DaoEvent::Amend {
+            old_dao_bulla: params.old_dao_bulla,
+            new_dao_bulla: params.new_dao_bulla,
+        },
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(DaoFunction::Amend as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Dao::Amend`
+pub(crate) fn dao_amend_process_update(cid: ContractId, update: DaoAmendUpdate) -> ContractResult {
+    // Grab all db handles we want to work on
+    let info_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_INFO_TREE)?;
+    let bulla_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_BULLAS)?;
+    let roots_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_MERKLE_ROOTS)?;
+    let amendments_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_AMENDMENTS)?;
+
+    // Link the old DAO to its successor
+    wasm::db::db_set(
+        amendments_db,
+        &serialize(&update.old_dao_bulla),
+        &serialize(&update.new_dao_bulla),
+    )?;
+
+    wasm::db::db_set(bulla_db, &serialize(&update.new_dao_bulla), &[])?;
+
+    let dao = vec![MerkleNode::from(update.new_dao_bulla.inner())];
+    wasm::merkle::merkle_add(
+        info_db,
+        roots_db,
+        DAO_CONTRACT_KEY_LATEST_DAO_ROOT,
+        DAO_CONTRACT_KEY_DAO_MERKLE_TREE,
+        &dao,
+    )?;
+
+    dao_record_event(cid, update.block_height, update.event)?;
+
+    Ok(())
+}
@@ -16,10 +16,7 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use darkfi_money_contract::{
-    model::{Coin, MoneyTransferParamsV1},
-    MoneyFunction,
-};
+use darkfi_money_contract::{model::MoneyTransferParamsV1, MoneyFunction};
 use darkfi_sdk::{
     crypto::{ContractId, FuncRef, PublicKey, DAO_CONTRACT_ID, MONEY_CONTRACT_ID},
     dark_tree::DarkLeaf,
@@ -28,11 +25,14 @@ use darkfi_sdk::{
     pasta::pallas,
     ContractCall,
 };
-use darkfi_serial::{deserialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize_strict, Encodable, WriteExt};
 
 use crate::{
     error::DaoError,
-    model::{DaoAuthCall, DaoAuthMoneyTransferParams, DaoExecParams, VecAuthCallCommit},
+    model::{
+        DaoAuthCall, DaoAuthMoneyTransferData, DaoAuthMoneyTransferParams, DaoExecParams,
+        VecAuthCallCommit,
+    },
     DaoFunction, DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_ENC_COIN_NS,
     DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_NS,
 };
@@ -44,15 +44,15 @@ pub(crate) fn dao_authxfer_get_metadata(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let self_params: DaoAuthMoneyTransferParams = deserialize(&self_.data.data[1..])?;
+    let self_params: DaoAuthMoneyTransferParams = deserialize_strict(&self_.data.data[1..])?;
 
     let sibling_idx = call_idx + 1;
     let xfer_call = &calls[sibling_idx as usize].data;
-    let xfer_params: MoneyTransferParamsV1 = deserialize(&xfer_call.data[1..])?;
+    let xfer_params: MoneyTransferParamsV1 = deserialize_strict(&xfer_call.data[1..])?;
 
     let parent_idx = calls[call_idx as usize].parent_index.unwrap();
     let exec_callnode = &calls[parent_idx];
-    let exec_params: DaoExecParams = deserialize(&exec_callnode.data.data[1..])?;
+    let exec_params: DaoExecParams = deserialize_strict(&exec_callnode.data.data[1..])?;
 
     assert!(!xfer_params.inputs.is_empty());
     assert!(!xfer_params.outputs.is_empty());
@@ -93,9 +93,9 @@ pub(crate) fn dao_authxfer_get_metadata(
         vec![
             exec_params.proposal_bulla.inner(),
             input_user_data_enc,
+            exec_params.dao_superseded_root,
             last_coin.inner(),
             spend_hook.inner(),
-            exec_params.proposal_auth_calls.commit(),
             ephem_x,
             ephem_y,
             self_params.dao_change_attrs.encrypted_values[0],
@@ -111,7 +111,7 @@ pub(crate) fn dao_authxfer_get_metadata(
     Ok(metadata)
 }
 
-fn find_auth_in_parent(
+pub(crate) fn find_auth_in_parent(
     exec_callnode: &DarkLeaf<ContractCall>,
     proposal_auth_calls: Vec<DaoAuthCall>,
     self_call_idx: u32,
@@ -152,7 +152,7 @@ pub(crate) fn dao_authxfer_process_instruction(
     // 2. money::transfer() inputs should all have the same user_data
     ///////////////////////////////////////////////////
 
-    let xfer_params: MoneyTransferParamsV1 = deserialize(&xfer_call.data[1..])?;
+    let xfer_params: MoneyTransferParamsV1 = deserialize_strict(&xfer_call.data[1..])?;
     assert!(!xfer_params.inputs.is_empty());
     // We need the last output to be the change
     assert!(xfer_params.outputs.len() > 1);
@@ -175,15 +175,17 @@ pub(crate) fn dao_authxfer_process_instruction(
     // Find this auth_call in the parent DAO::exec()
     let parent_idx = calls[call_idx as usize].parent_index.unwrap();
     let exec_callnode = &calls[parent_idx];
-    let exec_params: DaoExecParams = deserialize(&exec_callnode.data.data[1..])?;
+    let exec_params: DaoExecParams = deserialize_strict(&exec_callnode.data.data[1..])?;
 
     let auth_call = find_auth_in_parent(exec_callnode, exec_params.proposal_auth_calls, call_idx);
     if auth_call.is_none() {
         return Err(DaoError::AuthXferCallNotFoundInParent.into())
     }
 
-    // Read the proposal auth data which should be Vec<CoinAttributes>
-    let proposal_coins: Vec<Coin> = deserialize(&auth_call.unwrap().auth_data[..])?;
+    // Read the proposal auth data holding the recipient coins
+    let auth_data: DaoAuthMoneyTransferData =
+        deserialize_strict(&auth_call.unwrap().auth_data[..])?;
+    let proposal_coins = auth_data.coins;
 
     // Check all the outputs except the last match
     // There is the additional DAO change output which is always last.
@@ -205,7 +207,8 @@ pub(crate) fn dao_authxfer_process_instruction(
     // Also the public_key should match.
 
     // We do not need to check the amounts, since sum(input values) == sum(output values)
-    // otherwise the money::transfer() call is invalid.
+    // otherwise the money::transfer() call is invalid. The values sent to the recipients
+    // are fixed by the proposal coins matched above, which commit to them.
 
     let mut update_data = vec![];
     update_data.write_u8(DaoFunction::AuthMoneyTransfer as u8)?;
@@ -0,0 +1,125 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use super::{events::dao_record_event, settle_deposit::dao_bury_proposal};
+use crate::{
+    error::DaoError,
+    model::{DaoCancelParams, DaoCancelUpdate, DaoEvent, DaoProposalMetadata},
+    DaoFunction, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_ZKAS_DAO_CANCEL_NS,
+};
+
+/// `get_metdata` function for `Dao::Cancel`
+pub(crate) fn dao_cancel_get_metadata(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoCancelParams = deserialize_strict(&self_.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify.
+    // The proposal metadata is checked to commit to this key.
+    let signature_pubkeys: Vec<PublicKey> = vec![params.proposer_public_key];
+
+    zk_public_inputs.push((
+        DAO_CONTRACT_ZKAS_DAO_CANCEL_NS.to_string(),
+        vec![params.proposal_bulla.inner(), pallas::Base::from(params.max_block_height)],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Dao::Cancel`
+pub(crate) fn dao_cancel_process_instruction(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoCancelParams = deserialize_strict(&self_.data[1..])?;
+
+    // Executed or already cancelled proposals are gone from the db
+    let proposal_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
+    let Some(data) = wasm::db::db_get(proposal_db, &serialize(&params.proposal_bulla))? else {
+        msg!("[Dao::Cancel] Error: Proposal doesn't exist: {:?}", params.proposal_bulla);
+        return Err(DaoError::ProposalNonexistent.into())
+    };
+    let proposal_metadata: DaoProposalMetadata = deserialize(&data)?;
+
+    // Only the proposer can cancel, and they signed with this key
+    if params.proposer_public_key != proposal_metadata.proposer_public_key {
+        msg!(
+            "[Dao::Cancel] Error: Proposal {:?} wasn't made by {}",
+            params.proposal_bulla,
+            params.proposer_public_key
+        );
+        return Err(DaoError::CancelProposerMismatch.into())
+    }
+
+    // The proof shows voting is still open at max_block_height
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if current_height > params.max_block_height {
+        msg!(
+            "[Dao::Cancel] Error: Cancel max block height {} passed, current height: {}",
+            params.max_block_height,
+            current_height
+        );
+        return Err(DaoError::CancelHeightExceeded.into())
+    }
+
+    // Create state update
+    let update = DaoCancelUpdate {
+        proposal_bulla: params.proposal_bulla,
+        block_height: current_height as u32,
+        event: DaoEvent::Cancel { proposal_bulla: params.proposal_bulla },
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(DaoFunction::Cancel as u8)?;
+    update.encode(&mut update_data)?;
+    Ok(update_data)
+}
+
+/// `process_update` function for `Dao::Cancel`
+pub(crate) fn dao_cancel_process_update(
+    cid: ContractId,
+    update: DaoCancelUpdate,
+) -> ContractResult {
+    // Move the proposal from the db to its tombstone
+    dao_bury_proposal(cid, &update.proposal_bulla)?;
+
+    dao_record_event(cid, update.block_height, update.event)?;
+
+    Ok(())
+}
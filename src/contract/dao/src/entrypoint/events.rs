@@ -0,0 +1,77 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::ContractId,
+    error::{ContractError, ContractResult},
+    wasm::{self, db::DbHandle},
+};
+use darkfi_serial::{deserialize, serialize};
+
+use crate::{
+    model::{DaoEvent, DaoEventRecord},
+    DAO_CONTRACT_DB_EVENTS, DAO_CONTRACT_DB_INFO_TREE, DAO_CONTRACT_KEY_FIRST_EVENT,
+    DAO_CONTRACT_KEY_NEXT_EVENT, DAO_EVENTS_PRUNE_LIMIT, DAO_EVENTS_RETENTION_BLOCKS,
+};
+
+/// Record `event` under the next event sequence number, and prune the
+/// oldest events that fell out of the retention window.
+///
+/// Pruning is bounded by `DAO_EVENTS_PRUNE_LIMIT`, which is more than the
+/// single event recorded, so expired events don't pile up.
+pub(crate) fn dao_record_event(
+    cid: ContractId,
+    block_height: u32,
+    event: DaoEvent,
+) -> ContractResult {
+    let info_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_INFO_TREE)?;
+    let events_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_EVENTS)?;
+
+    let next = get_event_counter(info_db, DAO_CONTRACT_KEY_NEXT_EVENT)?;
+    let record = DaoEventRecord { block_height, event };
+    wasm::db::db_set(events_db, &next.to_be_bytes(), &serialize(&record))?;
+    wasm::db::db_set(info_db, DAO_CONTRACT_KEY_NEXT_EVENT, &serialize(&(next + 1)))?;
+
+    let mut first = get_event_counter(info_db, DAO_CONTRACT_KEY_FIRST_EVENT)?;
+    let mut pruned = 0;
+    while first < next && pruned < DAO_EVENTS_PRUNE_LIMIT {
+        let key = first.to_be_bytes();
+        let Some(data) = wasm::db::db_get(events_db, &key)? else { break };
+        let record: DaoEventRecord = deserialize(&data)?;
+        if record.block_height.saturating_add(DAO_EVENTS_RETENTION_BLOCKS) > block_height {
+            break
+        }
+
+        wasm::db::db_del(events_db, &key)?;
+        first += 1;
+        pruned += 1;
+    }
+    if pruned > 0 {
+        wasm::db::db_set(info_db, DAO_CONTRACT_KEY_FIRST_EVENT, &serialize(&first))?;
+    }
+
+    Ok(())
+}
+
+/// Fetch an event sequence counter from the info tree, defaulting to 0
+fn get_event_counter(info_db: DbHandle, key: &[u8]) -> Result<u64, ContractError> {
+    match wasm::db::db_get(info_db, key)? {
+        Some(data) => Ok(deserialize(&data)?),
+        None => Ok(0),
+    }
+}
@@ -24,12 +24,18 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
 
+use super::{
+    amend::{amended_dao_in_children, dao_supersede, superseded_daos_smt},
+    events::dao_record_event,
+    settle_deposit::dao_bury_proposal,
+};
 use crate::{
     error::DaoError,
-    model::{DaoExecParams, DaoExecUpdate, DaoProposalMetadata, VecAuthCallCommit},
-    DaoFunction, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_ZKAS_DAO_EXEC_NS,
+    model::{DaoEvent, DaoExecParams, DaoExecUpdate, DaoProposalMetadata, VecAuthCallCommit},
+    DaoFunction, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_ZKAS_DAO_EXEC_MULTI_NS,
+    DAO_CONTRACT_ZKAS_DAO_EXEC_NS, PROPOSAL_VETO_GRACE_BLOCKS,
 };
 
 /// `get_metdata` function for `Dao::Exec`
@@ -39,7 +45,7 @@ pub(crate) fn dao_exec_get_metadata(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let params: DaoExecParams = deserialize(&self_.data.data[1..])?;
+    let params: DaoExecParams = deserialize_strict(&self_.data.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -47,22 +53,49 @@ pub(crate) fn dao_exec_get_metadata(
     let signature_pubkeys: Vec<PublicKey> = vec![params.signature_public];
 
     let blind_vote = params.blind_total_vote;
-    let yes_vote_coords = blind_vote.yes_vote_commit.to_affine().coordinates().unwrap();
     let all_vote_coords = blind_vote.all_vote_commit.to_affine().coordinates().unwrap();
 
-    zk_public_inputs.push((
-        DAO_CONTRACT_ZKAS_DAO_EXEC_NS.to_string(),
-        vec![
-            params.proposal_bulla.inner(),
-            params.proposal_auth_calls.commit(),
-            *yes_vote_coords.x(),
-            *yes_vote_coords.y(),
-            *all_vote_coords.x(),
-            *all_vote_coords.y(),
-            params.signature_public.x(),
-            params.signature_public.y(),
-        ],
-    ));
+    if params.multi_choice {
+        // The proof reveals the auth calls of the winning option
+        let mut public_inputs =
+            vec![params.proposal_bulla.inner(), params.proposal_auth_calls.commit()];
+        for option_vote_commit in &blind_vote.option_vote_commits {
+            let coords = option_vote_commit.to_affine().coordinates().unwrap();
+            public_inputs.push(*coords.x());
+            public_inputs.push(*coords.y());
+        }
+        public_inputs.push(*all_vote_coords.x());
+        public_inputs.push(*all_vote_coords.y());
+        public_inputs.push(pallas::Base::from(params.min_block_height));
+        public_inputs.push(pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS));
+        public_inputs.push(params.signature_public.x());
+        public_inputs.push(params.signature_public.y());
+        public_inputs.push(params.dao_superseded_root);
+
+        zk_public_inputs.push((DAO_CONTRACT_ZKAS_DAO_EXEC_MULTI_NS.to_string(), public_inputs));
+    } else {
+        let yes_vote_coords = blind_vote.yes_vote_commit.to_affine().coordinates().unwrap();
+        let abstain_vote_coords = blind_vote.abstain_vote_commit.to_affine().coordinates().unwrap();
+
+        zk_public_inputs.push((
+            DAO_CONTRACT_ZKAS_DAO_EXEC_NS.to_string(),
+            vec![
+                params.proposal_bulla.inner(),
+                params.proposal_auth_calls.commit(),
+                *yes_vote_coords.x(),
+                *yes_vote_coords.y(),
+                *abstain_vote_coords.x(),
+                *abstain_vote_coords.y(),
+                *all_vote_coords.x(),
+                *all_vote_coords.y(),
+                pallas::Base::from(params.min_block_height),
+                pallas::Base::from(PROPOSAL_VETO_GRACE_BLOCKS),
+                params.signature_public.x(),
+                params.signature_public.y(),
+                params.dao_superseded_root,
+            ],
+        ));
+    }
 
     // Serialize everything gathered and return it
     let mut metadata = vec![];
@@ -79,7 +112,7 @@ pub(crate) fn dao_exec_process_instruction(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let params: DaoExecParams = deserialize(&self_.data.data[1..])?;
+    let params: DaoExecParams = deserialize_strict(&self_.data.data[1..])?;
 
     ///////////////////////////////////////////////////
     // 1. Verify the correct calling formats match the proposal
@@ -121,15 +154,65 @@ pub(crate) fn dao_exec_process_instruction(
     };
     let proposal: DaoProposalMetadata = deserialize(&data)?;
 
-    // Check yes_vote commit and all_vote_commit are the same as in BlindAggregateVote
-    if proposal.vote_aggregate.yes_vote_commit != params.blind_total_vote.yes_vote_commit ||
-        proposal.vote_aggregate.all_vote_commit != params.blind_total_vote.all_vote_commit
-    {
+    // The DAO guardian can block the proposal from being executed
+    if proposal.vetoed {
+        msg!("[Dao::Exec] Error: Proposal {:?} was vetoed", params.proposal_bulla);
+        return Err(DaoError::ProposalVetoed.into())
+    }
+
+    // Sealed proposals can only be executed once their tally is public
+    if proposal.is_sealed() && proposal.revealed_tally.is_none() {
+        msg!("[Dao::Exec] Error: Proposal {:?} tally not revealed", params.proposal_bulla);
+        return Err(DaoError::TallyNotRevealed.into())
+    }
+
+    // The proof shows voting, and the veto grace period if the DAO has a guardian,
+    // ended by min_block_height, unless executing early
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if current_height < params.min_block_height {
+        msg!(
+            "[Dao::Exec] Error: Exec min block height {} not reached, current height: {}",
+            params.min_block_height,
+            current_height
+        );
+        return Err(DaoError::ExecHeightNotReached.into())
+    }
+
+    // Check the vote commits are the same as in BlindAggregateVote.
+    // Only the ones used by the proof are compared.
+    let vote_aggregate = &proposal.vote_aggregate;
+    let blind_vote = &params.blind_total_vote;
+    let votes_match = if params.multi_choice {
+        vote_aggregate.option_vote_commits == blind_vote.option_vote_commits
+    } else {
+        vote_aggregate.yes_vote_commit == blind_vote.yes_vote_commit &&
+            vote_aggregate.abstain_vote_commit == blind_vote.abstain_vote_commit
+    };
+    if !votes_match || vote_aggregate.all_vote_commit != blind_vote.all_vote_commit {
         return Err(DaoError::VoteCommitMismatch.into())
     }
 
+    ///////////////////////////////////////////////////
+    // 3. Verify the DAO was not amended
+    ///////////////////////////////////////////////////
+
+    // The proof shows the DAO is absent from the superseded DAOs SMT
+    if params.dao_superseded_root != superseded_daos_smt(cid)?.root() {
+        msg!("[Dao::Exec] Error: Invalid superseded DAOs root");
+        return Err(DaoError::InvalidSupersededRoot.into())
+    }
+
+    // Amend children run before this call, so the amended DAO is only
+    // marked superseded here, once the proof above has been checked.
+    let amended_dao = amended_dao_in_children(cid, call_idx, &calls)?;
+
     // Create state update
-    let update = DaoExecUpdate { proposal_bulla: params.proposal_bulla };
+    let update = DaoExecUpdate {
+        proposal_bulla: params.proposal_bulla,
+        amended_dao,
+        block_height: current_height as u32,
+        event: DaoEvent::Exec { proposal_bulla: params.proposal_bulla },
+    };
     let mut update_data = vec![];
     update_data.write_u8(DaoFunction::Exec as u8)?;
     update.encode(&mut update_data)?;
@@ -138,9 +221,15 @@ pub(crate) fn dao_exec_process_instruction(
 
 /// `process_update` function for `Dao::Exec`
 pub(crate) fn dao_exec_process_update(cid: ContractId, update: DaoExecUpdate) -> ContractResult {
-    // Remove proposal from db
-    let proposal_vote_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
-    wasm::db::db_del(proposal_vote_db, &serialize(&update.proposal_bulla))?;
+    // Move the proposal from the db to its tombstone
+    dao_bury_proposal(cid, &update.proposal_bulla)?;
+
+    // Amended DAOs can no longer make or execute proposals
+    if let Some((old_dao_bulla, new_dao_bulla)) = update.amended_dao {
+        dao_supersede(cid, &old_dao_bulla, &new_dao_bulla)?;
+    }
+
+    dao_record_event(cid, update.block_height, update.event)?;
 
     Ok(())
 }
@@ -24,11 +24,12 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
 
+use super::events::dao_record_event;
 use crate::{
     error::DaoError,
-    model::{DaoMintParams, DaoMintUpdate},
+    model::{DaoEvent, DaoMintParams, DaoMintUpdate},
     DaoFunction, DAO_CONTRACT_DB_DAO_BULLAS, DAO_CONTRACT_DB_DAO_MERKLE_ROOTS,
     DAO_CONTRACT_DB_INFO_TREE, DAO_CONTRACT_KEY_DAO_MERKLE_TREE, DAO_CONTRACT_KEY_LATEST_DAO_ROOT,
     DAO_CONTRACT_ZKAS_DAO_MINT_NS,
@@ -41,7 +42,7 @@ pub(crate) fn dao_mint_get_metadata(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: DaoMintParams = deserialize(&self_.data[1..])?;
+    let params: DaoMintParams = deserialize_strict(&self_.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -71,7 +72,7 @@ pub(crate) fn dao_mint_process_instruction(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: DaoMintParams = deserialize(&self_.data[1..])?;
+    let params: DaoMintParams = deserialize_strict(&self_.data[1..])?;
 
     // Check the DAO bulla doesn't already exist
     let bulla_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_BULLAS)?;
@@ -81,7 +82,11 @@ pub(crate) fn dao_mint_process_instruction(
     }
 
     // Create state update
-    let update = DaoMintUpdate { dao_bulla: params.dao_bulla };
+    let update = DaoMintUpdate {
+        dao_bulla: params.dao_bulla,
+        block_height: wasm::util::get_verifying_block_height()?,
+        event: DaoEvent::Mint { dao_bulla: params.dao_bulla },
+    };
     let mut update_data = vec![];
     update_data.write_u8(DaoFunction::Mint as u8)?;
     update.encode(&mut update_data)?;
@@ -107,5 +112,7 @@ pub(crate) fn dao_mint_process_update(cid: ContractId, update: DaoMintUpdate) ->
         &dao,
     )?;
 
+    dao_record_event(cid, update.block_height, update.event)?;
+
     Ok(())
 }
@@ -27,12 +27,20 @@ use darkfi_sdk::{
 use darkfi_serial::{deserialize, serialize, Decodable, Encodable, WriteExt};
 
 use crate::{
-    model::{DaoExecUpdate, DaoMintUpdate, DaoProposeUpdate, DaoVoteUpdate},
-    DaoFunction, DAO_CONTRACT_DB_DAO_BULLAS, DAO_CONTRACT_DB_DAO_MERKLE_ROOTS,
-    DAO_CONTRACT_DB_INFO_TREE, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_DB_VOTE_NULLIFIERS,
-    DAO_CONTRACT_KEY_DAO_MERKLE_TREE, DAO_CONTRACT_KEY_DB_VERSION,
+    model::{
+        DaoAmendUpdate, DaoCancelUpdate, DaoExecUpdate, DaoMintUpdate, DaoProposeUpdate,
+        DaoRevealTallyUpdate, DaoSettleDepositUpdate, DaoVetoUpdate, DaoVoteUpdate,
+    },
+    DaoFunction, DAO_CONTRACT_DB_DAO_AMENDMENTS, DAO_CONTRACT_DB_DAO_BULLAS,
+    DAO_CONTRACT_DB_DAO_LINEAGES, DAO_CONTRACT_DB_DAO_MERKLE_ROOTS, DAO_CONTRACT_DB_EVENTS,
+    DAO_CONTRACT_DB_INFO_TREE, DAO_CONTRACT_DB_PROPOSAL_BULLAS,
+    DAO_CONTRACT_DB_PROPOSAL_TOMBSTONES, DAO_CONTRACT_DB_SUPERSEDED_DAOS,
+    DAO_CONTRACT_DB_VOTE_NULLIFIERS, DAO_CONTRACT_KEY_DAO_MERKLE_TREE, DAO_CONTRACT_KEY_DB_VERSION,
 };
 
+/// State-change events for wallet indexing
+mod events;
+
 /// `Dao::Mint` functions
 mod mint;
 use mint::{dao_mint_get_metadata, dao_mint_process_instruction, dao_mint_process_update};
@@ -47,6 +55,10 @@ use propose::{
 mod vote;
 use vote::{dao_vote_get_metadata, dao_vote_process_instruction, dao_vote_process_update};
 
+/// `Dao::VoteMulti` functions
+mod vote_multi;
+use vote_multi::{dao_vote_multi_get_metadata, dao_vote_multi_process_instruction};
+
 /// `Dao::Exec` functions
 mod exec;
 use exec::{dao_exec_get_metadata, dao_exec_process_instruction, dao_exec_process_update};
@@ -54,6 +66,32 @@ use exec::{dao_exec_get_metadata, dao_exec_process_instruction, dao_exec_process
 mod auth_xfer;
 use auth_xfer::{dao_authxfer_get_metadata, dao_authxfer_process_instruction};
 
+/// `Dao::Amend` functions
+mod amend;
+use amend::{dao_amend_get_metadata, dao_amend_process_instruction, dao_amend_process_update};
+
+/// `Dao::RevealTally` functions
+mod reveal;
+use reveal::{
+    dao_reveal_tally_get_metadata, dao_reveal_tally_process_instruction,
+    dao_reveal_tally_process_update,
+};
+
+/// `Dao::Cancel` functions
+mod cancel;
+use cancel::{dao_cancel_get_metadata, dao_cancel_process_instruction, dao_cancel_process_update};
+
+/// `Dao::Veto` functions
+mod veto;
+use veto::{dao_veto_get_metadata, dao_veto_process_instruction, dao_veto_process_update};
+
+/// `Dao::SettleDeposit` functions
+mod settle_deposit;
+use settle_deposit::{
+    dao_settle_deposit_get_metadata, dao_settle_deposit_process_instruction,
+    dao_settle_deposit_process_update,
+};
+
 darkfi_sdk::define_contract!(
     init: init_contract,
     exec: process_instruction,
@@ -73,8 +111,15 @@ fn init_contract(cid: ContractId, _ix: &[u8]) -> ContractResult {
     wasm::db::zkas_db_set(&include_bytes!("../../proof/propose-main.zk.bin")[..])?;
     wasm::db::zkas_db_set(&include_bytes!("../../proof/vote-input.zk.bin")[..])?;
     wasm::db::zkas_db_set(&include_bytes!("../../proof/vote-main.zk.bin")[..])?;
+    wasm::db::zkas_db_set(&include_bytes!("../../proof/vote-multi.zk.bin")[..])?;
     wasm::db::zkas_db_set(&include_bytes!("../../proof/exec.zk.bin")[..])?;
+    wasm::db::zkas_db_set(&include_bytes!("../../proof/exec-multi.zk.bin")[..])?;
     wasm::db::zkas_db_set(&include_bytes!("../../proof/auth-money-transfer.zk.bin")[..])?;
+    wasm::db::zkas_db_set(&include_bytes!("../../proof/amend.zk.bin")[..])?;
+    wasm::db::zkas_db_set(&include_bytes!("../../proof/reveal-tally.zk.bin")[..])?;
+    wasm::db::zkas_db_set(&include_bytes!("../../proof/cancel.zk.bin")[..])?;
+    wasm::db::zkas_db_set(&include_bytes!("../../proof/veto.zk.bin")[..])?;
+    wasm::db::zkas_db_set(&include_bytes!("../../proof/settle-deposit.zk.bin")[..])?;
 
     // Set up db for general info
     let dao_info_db = match wasm::db::db_lookup(cid, DAO_CONTRACT_DB_INFO_TREE) {
@@ -123,12 +168,52 @@ fn init_contract(cid: ContractId, _ix: &[u8]) -> ContractResult {
         Err(_) => wasm::db::db_init(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?,
     };
 
+    // Set up db keeping the final state of executed or cancelled
+    // proposals, so their deposits can still be settled
+    // k: ProposalBulla
+    // v: DaoProposalMetadata
+    let _ = match wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_TOMBSTONES) {
+        Ok(v) => v,
+        Err(_) => wasm::db::db_init(cid, DAO_CONTRACT_DB_PROPOSAL_TOMBSTONES)?,
+    };
+
     // TODO: These nullifiers should exist per-proposal
     let _ = match wasm::db::db_lookup(cid, DAO_CONTRACT_DB_VOTE_NULLIFIERS) {
         Ok(v) => v,
         Err(_) => wasm::db::db_init(cid, DAO_CONTRACT_DB_VOTE_NULLIFIERS)?,
     };
 
+    // Set up db linking amended DAOs to their successors
+    // k: DaoBulla
+    // v: DaoBulla
+    let _ = match wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_AMENDMENTS) {
+        Ok(v) => v,
+        Err(_) => wasm::db::db_init(cid, DAO_CONTRACT_DB_DAO_AMENDMENTS)?,
+    };
+
+    // Set up the SMT of amended DAOs, which can no longer propose or exec.
+    // Each leaf is the latest successor of the amended DAO.
+    let _ = match wasm::db::db_lookup(cid, DAO_CONTRACT_DB_SUPERSEDED_DAOS) {
+        Ok(v) => v,
+        Err(_) => wasm::db::db_init(cid, DAO_CONTRACT_DB_SUPERSEDED_DAOS)?,
+    };
+
+    // Set up db linking the latest successor of amended DAOs to them
+    // k: DaoBulla
+    // v: Vec<DaoBulla>
+    let _ = match wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_LINEAGES) {
+        Ok(v) => v,
+        Err(_) => wasm::db::db_init(cid, DAO_CONTRACT_DB_DAO_LINEAGES)?,
+    };
+
+    // Set up db for the state-change events, pruned after DAO_EVENTS_RETENTION_BLOCKS
+    // k: u64 (big-endian event sequence number)
+    // v: DaoEventRecord
+    let _ = match wasm::db::db_lookup(cid, DAO_CONTRACT_DB_EVENTS) {
+        Ok(v) => v,
+        Err(_) => wasm::db::db_init(cid, DAO_CONTRACT_DB_EVENTS)?,
+    };
+
     // Update db version
     wasm::db::db_set(
         dao_info_db,
@@ -154,6 +239,12 @@ fn get_metadata(cid: ContractId, ix: &[u8]) -> ContractResult {
         DaoFunction::Vote => dao_vote_get_metadata(cid, call_idx, calls)?,
         DaoFunction::Exec => dao_exec_get_metadata(cid, call_idx, calls)?,
         DaoFunction::AuthMoneyTransfer => dao_authxfer_get_metadata(cid, call_idx, calls)?,
+        DaoFunction::Amend => dao_amend_get_metadata(cid, call_idx, calls)?,
+        DaoFunction::VoteMulti => dao_vote_multi_get_metadata(cid, call_idx, calls)?,
+        DaoFunction::RevealTally => dao_reveal_tally_get_metadata(cid, call_idx, calls)?,
+        DaoFunction::Cancel => dao_cancel_get_metadata(cid, call_idx, calls)?,
+        DaoFunction::Veto => dao_veto_get_metadata(cid, call_idx, calls)?,
+        DaoFunction::SettleDeposit => dao_settle_deposit_get_metadata(cid, call_idx, calls)?,
     };
 
     wasm::util::set_return_data(&metadata)
@@ -173,6 +264,12 @@ fn process_instruction(cid: ContractId, ix: &[u8]) -> ContractResult {
         DaoFunction::Vote => dao_vote_process_instruction(cid, call_idx, calls)?,
         DaoFunction::Exec => dao_exec_process_instruction(cid, call_idx, calls)?,
         DaoFunction::AuthMoneyTransfer => dao_authxfer_process_instruction(cid, call_idx, calls)?,
+        DaoFunction::Amend => dao_amend_process_instruction(cid, call_idx, calls)?,
+        DaoFunction::VoteMulti => dao_vote_multi_process_instruction(cid, call_idx, calls)?,
+        DaoFunction::RevealTally => dao_reveal_tally_process_instruction(cid, call_idx, calls)?,
+        DaoFunction::Cancel => dao_cancel_process_instruction(cid, call_idx, calls)?,
+        DaoFunction::Veto => dao_veto_process_instruction(cid, call_idx, calls)?,
+        DaoFunction::SettleDeposit => dao_settle_deposit_process_instruction(cid, call_idx, calls)?,
     };
 
     wasm::util::set_return_data(&update_data)
@@ -207,5 +304,36 @@ fn process_update(cid: ContractId, update_data: &[u8]) -> ContractResult {
             // Does nothing, just verifies the other calls are correct
             Ok(())
         }
+
+        DaoFunction::Amend => {
+            let update: DaoAmendUpdate = deserialize(&update_data[1..])?;
+            Ok(dao_amend_process_update(cid, update)?)
+        }
+
+        DaoFunction::VoteMulti => {
+            // Applied the same way as regular votes
+            let update: DaoVoteUpdate = deserialize(&update_data[1..])?;
+            Ok(dao_vote_process_update(cid, update)?)
+        }
+
+        DaoFunction::RevealTally => {
+            let update: DaoRevealTallyUpdate = deserialize(&update_data[1..])?;
+            Ok(dao_reveal_tally_process_update(cid, update)?)
+        }
+
+        DaoFunction::Cancel => {
+            let update: DaoCancelUpdate = deserialize(&update_data[1..])?;
+            Ok(dao_cancel_process_update(cid, update)?)
+        }
+
+        DaoFunction::Veto => {
+            let update: DaoVetoUpdate = deserialize(&update_data[1..])?;
+            Ok(dao_veto_process_update(cid, update)?)
+        }
+
+        DaoFunction::SettleDeposit => {
+            let update: DaoSettleDepositUpdate = deserialize(&update_data[1..])?;
+            Ok(dao_settle_deposit_process_update(cid, update)?)
+        }
     }
 }
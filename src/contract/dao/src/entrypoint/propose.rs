@@ -17,11 +17,15 @@
  */
 
 use darkfi_money_contract::{
-    MONEY_CONTRACT_COIN_ROOTS_TREE, MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
+    model::MoneyTransferParamsV1, MoneyFunction, MONEY_CONTRACT_COIN_ROOTS_TREE,
+    MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
     MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NULLIFIER_ROOTS_TREE,
 };
 use darkfi_sdk::{
-    crypto::{contract_id::MONEY_CONTRACT_ID, pasta_prelude::*, ContractId, MerkleNode, PublicKey},
+    crypto::{
+        contract_id::MONEY_CONTRACT_ID, pasta_prelude::*, ContractId, FuncRef, MerkleNode,
+        PublicKey,
+    },
     dark_tree::DarkLeaf,
     error::{ContractError, ContractResult},
     msg,
@@ -29,25 +33,27 @@ use darkfi_sdk::{
     tx::TransactionHash,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
 
+use super::{amend::superseded_daos_smt, events::dao_record_event};
 use crate::{
-    blockwindow,
     error::DaoError,
-    model::{DaoBlindAggregateVote, DaoProposalMetadata, DaoProposeParams, DaoProposeUpdate},
+    model::{
+        DaoBlindAggregateVote, DaoEvent, DaoProposalMetadata, DaoProposeParams, DaoProposeUpdate,
+    },
     DaoFunction, DAO_CONTRACT_DB_DAO_MERKLE_ROOTS, DAO_CONTRACT_DB_PROPOSAL_BULLAS,
     DAO_CONTRACT_ZKAS_DAO_PROPOSE_INPUT_NS, DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS,
-    PROPOSAL_SNAPSHOT_CUTOFF_LIMIT,
+    PROPOSAL_CREATION_CUTOFF_LIMIT, PROPOSAL_SNAPSHOT_CUTOFF_LIMIT,
 };
 
 /// `get_metdata` function for `Dao::Propose`
 pub(crate) fn dao_propose_get_metadata(
-    _cid: ContractId,
+    cid: ContractId,
     call_idx: u32,
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: DaoProposeParams = deserialize(&self_.data[1..])?;
+    let params: DaoProposeParams = deserialize_strict(&self_.data[1..])?;
 
     if params.inputs.is_empty() {
         msg!("[DAO::Propose] Error: Proposal inputs are empty");
@@ -84,20 +90,25 @@ pub(crate) fn dao_propose_get_metadata(
         ));
     }
 
-    // ANCHOR: dao-blockwindow-example-usage
-    let current_day = blockwindow(wasm::util::get_verifying_block_height()?);
-    // ANCHOR_END: dao-blockwindow-example-usage
-
     let total_funds_coords = total_funds_commit.to_affine().coordinates().unwrap();
+
+    // The deposit coin can only be spent by settling it
+    let deposit_coin = params.deposit_coin.map_or(pallas::Base::ZERO, |coin| coin.inner());
+    let deposit_spend_hook =
+        FuncRef { contract_id: cid, func_code: DaoFunction::SettleDeposit as u8 }.to_func_id();
+
     zk_public_inputs.push((
         DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS.to_string(),
         vec![
             params.token_commit,
             params.dao_merkle_root.inner(),
+            params.dao_superseded_root,
             params.proposal_bulla.inner(),
-            pallas::Base::from(current_day),
+            pallas::Base::from(params.creation_height),
             *total_funds_coords.x(),
             *total_funds_coords.y(),
+            deposit_coin,
+            deposit_spend_hook.inner(),
         ],
     ));
 
@@ -116,7 +127,7 @@ pub(crate) fn dao_propose_process_instruction(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: DaoProposeParams = deserialize(&self_.data[1..])?;
+    let params: DaoProposeParams = deserialize_strict(&self_.data[1..])?;
 
     let coin_roots_db = wasm::db::db_lookup(*MONEY_CONTRACT_ID, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
     let null_roots_db =
@@ -162,6 +173,21 @@ pub(crate) fn dao_propose_process_instruction(
         }
     }
 
+    // ANCHOR: dao-proposal-creation-height
+    // The proposal can't be created in the future, nor too far in the past
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if params.creation_height > current_height ||
+        current_height - params.creation_height > PROPOSAL_CREATION_CUTOFF_LIMIT
+    {
+        msg!(
+            "[Dao::Propose] Error: Invalid creation height {}, current height: {}",
+            params.creation_height,
+            current_height
+        );
+        return Err(DaoError::InvalidProposalCreationHeight.into())
+    }
+    // ANCHOR_END: dao-proposal-creation-height
+
     // Is the DAO bulla generated in the ZK proof valid
     let dao_roots_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_DAO_MERKLE_ROOTS)?;
     if !wasm::db::db_contains_key(dao_roots_db, &serialize(&params.dao_merkle_root))? {
@@ -169,6 +195,13 @@ pub(crate) fn dao_propose_process_instruction(
         return Err(DaoError::InvalidDaoMerkleRoot.into())
     }
 
+    // Amended DAOs can't make new proposals. The proof shows the DAO
+    // is absent from the superseded DAOs SMT.
+    if params.dao_superseded_root != superseded_daos_smt(cid)?.root() {
+        msg!("[Dao::Propose] Error: Invalid superseded DAOs root");
+        return Err(DaoError::InvalidSupersededRoot.into())
+    }
+
     // Make sure the proposal doesn't already exist
     let proposal_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
     if wasm::db::db_contains_key(proposal_db, &serialize(&params.proposal_bulla))? {
@@ -176,6 +209,30 @@ pub(crate) fn dao_propose_process_instruction(
         return Err(DaoError::ProposalAlreadyExists.into())
     }
 
+    // The deposit coin has to be minted by a money::transfer() in this transaction
+    if let Some(deposit_coin) = params.deposit_coin {
+        let mut found = false;
+        for call in &calls {
+            let call = &call.data;
+            if call.contract_id != *MONEY_CONTRACT_ID ||
+                call.data[0] != MoneyFunction::TransferV1 as u8
+            {
+                continue
+            }
+
+            let xfer_params: MoneyTransferParamsV1 = deserialize_strict(&call.data[1..])?;
+            if xfer_params.outputs.iter().any(|output| output.coin == deposit_coin) {
+                found = true;
+                break
+            }
+        }
+
+        if !found {
+            msg!("[Dao::Propose] Error: Deposit coin {:?} not found", deposit_coin);
+            return Err(DaoError::ProposalDepositNotFound.into())
+        }
+    }
+
     // Snapshot the latest Money merkle tree
     let money_info_db = wasm::db::db_lookup(*MONEY_CONTRACT_ID, MONEY_CONTRACT_INFO_TREE)?;
     let Some(data) = wasm::db::db_get(money_info_db, MONEY_CONTRACT_LATEST_COIN_ROOT)? else {
@@ -197,8 +254,15 @@ pub(crate) fn dao_propose_process_instruction(
     );
 
     // Create state update
-    let update =
-        DaoProposeUpdate { proposal_bulla: params.proposal_bulla, snapshot_coins, snapshot_nulls };
+    let update = DaoProposeUpdate {
+        proposal_bulla: params.proposal_bulla,
+        snapshot_coins,
+        snapshot_nulls,
+        tally_public_key: params.tally_public_key,
+        proposer_public_key: params.proposer_public_key,
+        block_height: current_height as u32,
+        event: DaoEvent::Propose { proposal_bulla: params.proposal_bulla, note: params.note },
+    };
     let mut update_data = vec![];
     update_data.write_u8(DaoFunction::Propose as u8)?;
     update.encode(&mut update_data)?;
@@ -218,6 +282,10 @@ pub(crate) fn dao_propose_process_update(
         vote_aggregate: DaoBlindAggregateVote::default(),
         snapshot_coins: update.snapshot_coins,
         snapshot_nulls: update.snapshot_nulls,
+        tally_public_key: update.tally_public_key,
+        revealed_tally: None,
+        vetoed: false,
+        proposer_public_key: update.proposer_public_key,
     };
 
     // Set the new proposal in the db
@@ -227,5 +295,7 @@ pub(crate) fn dao_propose_process_update(
         &serialize(&proposal_metadata),
     )?;
 
+    dao_record_event(cid, update.block_height, update.event)?;
+
     Ok(())
 }
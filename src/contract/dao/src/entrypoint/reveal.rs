@@ -0,0 +1,168 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{pasta_prelude::*, ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use super::events::dao_record_event;
+use crate::{
+    error::DaoError,
+    model::{
+        DaoEvent, DaoProposalMetadata, DaoRevealTallyParams, DaoRevealTallyUpdate, DaoRevealedTally,
+    },
+    DaoFunction, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_ZKAS_DAO_REVEAL_TALLY_NS,
+};
+
+/// Fetch the metadata of the proposal the tally is revealed for
+fn proposal_metadata(
+    cid: ContractId,
+    params: &DaoRevealTallyParams,
+) -> Result<DaoProposalMetadata, ContractError> {
+    let proposal_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
+    let Some(data) = wasm::db::db_get(proposal_db, &serialize(&params.proposal_bulla))? else {
+        msg!("[Dao::RevealTally] Error: Proposal doesn't exist: {:?}", params.proposal_bulla);
+        return Err(DaoError::ProposalNonexistent.into())
+    };
+    Ok(deserialize(&data)?)
+}
+
+/// `get_metdata` function for `Dao::RevealTally`
+pub(crate) fn dao_reveal_tally_get_metadata(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoRevealTallyParams = deserialize_strict(&self_.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let signature_pubkeys: Vec<PublicKey> = vec![];
+
+    // The proof opens the vote aggregate stored on chain
+    let proposal_metadata = proposal_metadata(cid, &params)?;
+    let vote_aggregate = proposal_metadata.vote_aggregate;
+    let yes_vote_coords = vote_aggregate.yes_vote_commit.to_affine().coordinates().unwrap();
+    let abstain_vote_coords = vote_aggregate.abstain_vote_commit.to_affine().coordinates().unwrap();
+    let all_vote_coords = vote_aggregate.all_vote_commit.to_affine().coordinates().unwrap();
+
+    zk_public_inputs.push((
+        DAO_CONTRACT_ZKAS_DAO_REVEAL_TALLY_NS.to_string(),
+        vec![
+            params.proposal_bulla.inner(),
+            *yes_vote_coords.x(),
+            *yes_vote_coords.y(),
+            pallas::Base::from(params.yes_vote_value),
+            *abstain_vote_coords.x(),
+            *abstain_vote_coords.y(),
+            pallas::Base::from(params.abstain_vote_value),
+            *all_vote_coords.x(),
+            *all_vote_coords.y(),
+            pallas::Base::from(params.all_vote_value),
+            pallas::Base::from(params.min_block_height),
+        ],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Dao::RevealTally`
+pub(crate) fn dao_reveal_tally_process_instruction(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoRevealTallyParams = deserialize_strict(&self_.data[1..])?;
+
+    let mut proposal_metadata = proposal_metadata(cid, &params)?;
+
+    if !proposal_metadata.is_sealed() {
+        msg!("[Dao::RevealTally] Error: Proposal {:?} is not sealed", params.proposal_bulla);
+        return Err(DaoError::ProposalNotSealed.into())
+    }
+
+    if proposal_metadata.revealed_tally.is_some() {
+        msg!("[Dao::RevealTally] Error: Proposal {:?} was already revealed", params.proposal_bulla);
+        return Err(DaoError::TallyAlreadyRevealed.into())
+    }
+
+    // The proof shows voting ended by min_block_height
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if current_height < params.min_block_height {
+        msg!(
+            "[Dao::RevealTally] Error: Reveal min block height {} not reached, current height: {}",
+            params.min_block_height,
+            current_height
+        );
+        return Err(DaoError::RevealHeightNotReached.into())
+    }
+
+    proposal_metadata.revealed_tally = Some(DaoRevealedTally {
+        yes_vote_value: params.yes_vote_value,
+        abstain_vote_value: params.abstain_vote_value,
+        all_vote_value: params.all_vote_value,
+    });
+
+    // Create state update
+    let update = DaoRevealTallyUpdate {
+        proposal_bulla: params.proposal_bulla,
+        proposal_metadata,
+        block_height: current_height as u32,
+        event: DaoEvent::RevealTally {
+            proposal_bulla: params.proposal_bulla,
+            yes_vote_value: params.yes_vote_value,
+            abstain_vote_value: params.abstain_vote_value,
+            all_vote_value: params.all_vote_value,
+        },
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(DaoFunction::RevealTally as u8)?;
+    update.encode(&mut update_data)?;
+    Ok(update_data)
+}
+
+/// `process_update` function for `Dao::RevealTally`
+pub(crate) fn dao_reveal_tally_process_update(
+    cid: ContractId,
+    update: DaoRevealTallyUpdate,
+) -> ContractResult {
+    let proposal_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
+    wasm::db::db_set(
+        proposal_db,
+        &serialize(&update.proposal_bulla),
+        &serialize(&update.proposal_metadata),
+    )?;
+
+    dao_record_event(cid, update.block_height, update.event)?;
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_money_contract::spend_hook::spend_hook_transfer;
+use darkfi_sdk::{
+    crypto::{pasta_prelude::*, ContractId, FuncRef, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use super::events::dao_record_event;
+use crate::{
+    error::DaoError,
+    model::{
+        DaoEvent, DaoProposalBulla, DaoProposalMetadata, DaoSettleDepositParams,
+        DaoSettleDepositUpdate,
+    },
+    DaoFunction, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_DB_PROPOSAL_TOMBSTONES,
+    DAO_CONTRACT_ZKAS_DAO_SETTLE_DEPOSIT_NS, PROPOSAL_DEPOSIT_SLASH_DIVISOR,
+};
+
+/// Remove an executed or cancelled proposal from the db, keeping its
+/// final state as a tombstone so its deposit is settled against the
+/// votes it got, and can't be refunded by cancelling it.
+pub(crate) fn dao_bury_proposal(
+    cid: ContractId,
+    proposal_bulla: &DaoProposalBulla,
+) -> ContractResult {
+    let proposal_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
+    let tombstones_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_TOMBSTONES)?;
+
+    let key = serialize(proposal_bulla);
+    if let Some(data) = wasm::db::db_get(proposal_db, &key)? {
+        wasm::db::db_set(tombstones_db, &key, &data)?;
+    }
+    wasm::db::db_del(proposal_db, &key)?;
+
+    Ok(())
+}
+
+/// `get_metdata` function for `Dao::SettleDeposit`
+pub(crate) fn dao_settle_deposit_get_metadata(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoSettleDepositParams = deserialize_strict(&self_.data[1..])?;
+    // The deposit is spent into a single coin
+    let xfer_params = spend_hook_transfer(&calls, call_idx, 1, 1)?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // The deposit input of the child transfer is signed there
+    let signature_pubkeys: Vec<PublicKey> = vec![];
+
+    let blind_vote = &params.blind_total_vote;
+    let yes_vote_coords = blind_vote.yes_vote_commit.to_affine().coordinates().unwrap();
+    let abstain_vote_coords = blind_vote.abstain_vote_commit.to_affine().coordinates().unwrap();
+    let all_vote_coords = blind_vote.all_vote_commit.to_affine().coordinates().unwrap();
+
+    // Slashed deposits are sent to the DAO treasury
+    let dao_spend_hook =
+        FuncRef { contract_id: cid, func_code: DaoFunction::Exec as u8 }.to_func_id();
+
+    zk_public_inputs.push((
+        DAO_CONTRACT_ZKAS_DAO_SETTLE_DEPOSIT_NS.to_string(),
+        vec![
+            params.proposal_bulla.inner(),
+            xfer_params.inputs[0].user_data_enc,
+            *yes_vote_coords.x(),
+            *yes_vote_coords.y(),
+            *abstain_vote_coords.x(),
+            *abstain_vote_coords.y(),
+            *all_vote_coords.x(),
+            *all_vote_coords.y(),
+            pallas::Base::from(params.min_block_height),
+            pallas::Base::from(params.slashed as u64),
+            pallas::Base::from(PROPOSAL_DEPOSIT_SLASH_DIVISOR),
+            xfer_params.outputs[0].coin.inner(),
+            dao_spend_hook.inner(),
+        ],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Dao::SettleDeposit`
+pub(crate) fn dao_settle_deposit_process_instruction(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoSettleDepositParams = deserialize_strict(&self_.data[1..])?;
+
+    // The child transfer spends the deposit coin, whose spend hook
+    // makes money::transfer() check this is its parent call.
+    spend_hook_transfer(&calls, call_idx, 1, 1)?;
+
+    // The proof shows voting ended by min_block_height
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if current_height < params.min_block_height {
+        msg!(
+            "[Dao::SettleDeposit] Error: Settle min block height {} not reached, current height: {}",
+            params.min_block_height,
+            current_height
+        );
+        return Err(DaoError::SettleDepositHeightNotReached.into())
+    }
+
+    // Executed or cancelled proposals are settled against the final
+    // state kept in their tombstone.
+    let proposal_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
+    let tombstones_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_TOMBSTONES)?;
+    let key = serialize(&params.proposal_bulla);
+    let data = match wasm::db::db_get(proposal_db, &key)? {
+        Some(data) => data,
+        None => match wasm::db::db_get(tombstones_db, &key)? {
+            Some(data) => data,
+            None => {
+                msg!(
+                    "[Dao::SettleDeposit] Error: Proposal doesn't exist: {:?}",
+                    params.proposal_bulla
+                );
+                return Err(DaoError::ProposalNonexistent.into())
+            }
+        },
+    };
+    let proposal: DaoProposalMetadata = deserialize(&data)?;
+
+    // Check the vote commits are the same as in BlindAggregateVote
+    let vote_aggregate = &proposal.vote_aggregate;
+    let blind_vote = &params.blind_total_vote;
+    if vote_aggregate.yes_vote_commit != blind_vote.yes_vote_commit ||
+        vote_aggregate.abstain_vote_commit != blind_vote.abstain_vote_commit ||
+        vote_aggregate.all_vote_commit != blind_vote.all_vote_commit
+    {
+        return Err(DaoError::VoteCommitMismatch.into())
+    }
+
+    // Multiple-choice proposals have no yes votes to weigh
+    let multi_choice = vote_aggregate
+        .option_vote_commits
+        .iter()
+        .any(|commit| *commit != pallas::Point::identity());
+    if params.slashed && multi_choice {
+        msg!(
+            "[Dao::SettleDeposit] Error: Multiple-choice proposal {:?} can't be slashed",
+            params.proposal_bulla
+        );
+        return Err(DaoError::ProposalDepositNotSlashable.into())
+    }
+
+    // Create state update
+    let update = DaoSettleDepositUpdate {
+        block_height: current_height as u32,
+        event: DaoEvent::SettleDeposit {
+            proposal_bulla: params.proposal_bulla,
+            slashed: params.slashed,
+        },
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(DaoFunction::SettleDeposit as u8)?;
+    update.encode(&mut update_data)?;
+    Ok(update_data)
+}
+
+/// `process_update` function for `Dao::SettleDeposit`
+pub(crate) fn dao_settle_deposit_process_update(
+    cid: ContractId,
+    update: DaoSettleDepositUpdate,
+) -> ContractResult {
+    // The child money::transfer() spends the deposit, so only the event
+    // is recorded here.
+    dao_record_event(cid, update.block_height, update.event)?;
+
+    Ok(())
+}
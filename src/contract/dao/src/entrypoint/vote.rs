@@ -24,12 +24,12 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
 
+use super::events::dao_record_event;
 use crate::{
-    blockwindow,
     error::DaoError,
-    model::{DaoProposalMetadata, DaoVoteParams, DaoVoteUpdate},
+    model::{DaoEvent, DaoProposalMetadata, DaoVoteParams, DaoVoteUpdate},
     DaoFunction, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_DB_VOTE_NULLIFIERS,
     DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS, DAO_CONTRACT_ZKAS_DAO_VOTE_MAIN_NS,
 };
@@ -41,7 +41,7 @@ pub(crate) fn dao_vote_get_metadata(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: DaoVoteParams = deserialize(&self_.data[1..])?;
+    let params: DaoVoteParams = deserialize_strict(&self_.data[1..])?;
 
     if params.inputs.is_empty() {
         msg!("[Dao::Vote] Error: Vote inputs are empty");
@@ -53,8 +53,9 @@ pub(crate) fn dao_vote_get_metadata(
     // Public keys for the transaction signatures we have to verify
     let mut signature_pubkeys: Vec<PublicKey> = vec![];
 
-    // Commitment calculation for all votes
-    let mut all_vote_commit = pallas::Point::identity();
+    // Commitment for the balance of the input coins, which the vote
+    // weight is derived from
+    let mut balance_commit = pallas::Point::identity();
 
     let proposal_votes_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
     let Some(data) = wasm::db::db_get(proposal_votes_db, &serialize(&params.proposal_bulla))?
@@ -68,7 +69,7 @@ pub(crate) fn dao_vote_get_metadata(
     // Iterate through inputs
     for input in &params.inputs {
         signature_pubkeys.push(input.signature_public);
-        all_vote_commit += input.vote_commit;
+        balance_commit += input.vote_commit;
 
         let value_coords = input.vote_commit.to_affine().coordinates().unwrap();
         let (sig_x, sig_y) = input.signature_public.xy();
@@ -89,10 +90,19 @@ pub(crate) fn dao_vote_get_metadata(
         ));
     }
 
-    let current_day = blockwindow(wasm::util::get_verifying_block_height()?);
-
     let yes_vote_commit_coords = params.yes_vote_commit.to_affine().coordinates().unwrap();
-    let all_vote_commit_coords = all_vote_commit.to_affine().coordinates().unwrap();
+    let abstain_vote_commit_coords = params.abstain_vote_commit.to_affine().coordinates().unwrap();
+    let balance_commit_coords = balance_commit.to_affine().coordinates().unwrap();
+    let all_vote_commit_coords = params.all_vote_commit.to_affine().coordinates().unwrap();
+
+    // Sealed proposals have their votes encrypted to the tally key
+    let (sealed, tally_public_x, tally_public_y) = match proposal_metadata.tally_public_key {
+        Some(tally_public_key) => {
+            let (x, y) = tally_public_key.xy();
+            (pallas::Base::ONE, x, y)
+        }
+        None => (pallas::Base::ZERO, pallas::Base::ZERO, pallas::Base::ZERO),
+    };
 
     let (ephem_x, ephem_y) = params.note.ephem_public.xy();
     zk_public_inputs.push((
@@ -100,17 +110,26 @@ pub(crate) fn dao_vote_get_metadata(
         vec![
             params.token_commit,
             params.proposal_bulla.inner(),
+            *balance_commit_coords.x(),
+            *balance_commit_coords.y(),
             *yes_vote_commit_coords.x(),
             *yes_vote_commit_coords.y(),
+            *abstain_vote_commit_coords.x(),
+            *abstain_vote_commit_coords.y(),
             *all_vote_commit_coords.x(),
             *all_vote_commit_coords.y(),
-            pallas::Base::from(current_day),
+            pallas::Base::from(params.max_block_height),
+            sealed,
+            tally_public_x,
+            tally_public_y,
             ephem_x,
             ephem_y,
             params.note.encrypted_values[0],
             params.note.encrypted_values[1],
             params.note.encrypted_values[2],
             params.note.encrypted_values[3],
+            params.note.encrypted_values[4],
+            params.note.encrypted_values[5],
         ],
     ));
 
@@ -129,7 +148,7 @@ pub(crate) fn dao_vote_process_instruction(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: DaoVoteParams = deserialize(&self_.data[1..])?;
+    let params: DaoVoteParams = deserialize_strict(&self_.data[1..])?;
 
     // Check proposal bulla exists
     let proposal_votes_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
@@ -139,6 +158,17 @@ pub(crate) fn dao_vote_process_instruction(
         return Err(DaoError::ProposalNonexistent.into())
     };
 
+    // The proof shows voting is still open at max_block_height
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if current_height > params.max_block_height {
+        msg!(
+            "[Dao::Vote] Error: Vote max block height {} passed, current height: {}",
+            params.max_block_height,
+            current_height
+        );
+        return Err(DaoError::VoteHeightExceeded.into())
+    }
+
     // Get the current votes
     let mut proposal_metadata: DaoProposalMetadata = deserialize(&data)?;
 
@@ -158,15 +188,21 @@ pub(crate) fn dao_vote_process_instruction(
             return Err(DaoError::DoubleVote.into())
         }
 
-        proposal_metadata.vote_aggregate.all_vote_commit += input.vote_commit;
         vote_nullifiers.push(input.vote_nullifier);
     }
 
+    proposal_metadata.vote_aggregate.all_vote_commit += params.all_vote_commit;
     proposal_metadata.vote_aggregate.yes_vote_commit += params.yes_vote_commit;
+    proposal_metadata.vote_aggregate.abstain_vote_commit += params.abstain_vote_commit;
 
     // Create state update
-    let update =
-        DaoVoteUpdate { proposal_bulla: params.proposal_bulla, proposal_metadata, vote_nullifiers };
+    let update = DaoVoteUpdate {
+        proposal_bulla: params.proposal_bulla,
+        proposal_metadata,
+        vote_nullifiers,
+        block_height: current_height as u32,
+        event: DaoEvent::Vote { proposal_bulla: params.proposal_bulla, note: params.note },
+    };
 
     let mut update_data = vec![];
     update_data.write_u8(DaoFunction::Vote as u8)?;
@@ -181,6 +217,7 @@ pub(crate) fn dao_vote_process_update(cid: ContractId, update: DaoVoteUpdate) ->
 
     // Perform this code:
     //   total_yes_vote_commit += update.yes_vote_commit
+    //   total_abstain_vote_commit += update.abstain_vote_commit
     //   total_all_vote_commit += update.all_vote_commit
     wasm::db::db_set(
         proposal_vote_db,
@@ -197,5 +234,7 @@ pub(crate) fn dao_vote_process_update(cid: ContractId, update: DaoVoteUpdate) ->
         wasm::db::db_set(dao_vote_nulls_db, &key, &[])?;
     }
 
+    dao_record_event(cid, update.block_height, update.event)?;
+
     Ok(())
 }
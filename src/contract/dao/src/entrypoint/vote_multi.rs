@@ -0,0 +1,205 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{pasta_prelude::*, ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::ContractError,
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::DaoError,
+    model::{DaoEvent, DaoProposalMetadata, DaoVoteMultiParams, DaoVoteUpdate},
+    DaoFunction, DAO_CONTRACT_DB_PROPOSAL_BULLAS, DAO_CONTRACT_DB_VOTE_NULLIFIERS,
+    DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS, DAO_CONTRACT_ZKAS_DAO_VOTE_MULTI_NS,
+};
+
+/// `get_metdata` function for `Dao::VoteMulti`
+pub(crate) fn dao_vote_multi_get_metadata(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoVoteMultiParams = deserialize_strict(&self_.data[1..])?;
+
+    if params.inputs.is_empty() {
+        msg!("[Dao::VoteMulti] Error: Vote inputs are empty");
+        return Err(DaoError::VoteInputsEmpty.into())
+    }
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let mut signature_pubkeys: Vec<PublicKey> = vec![];
+
+    // Commitment for the balance of the input coins, which the vote
+    // weight is derived from
+    let mut balance_commit = pallas::Point::identity();
+
+    let proposal_votes_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
+    let Some(data) = wasm::db::db_get(proposal_votes_db, &serialize(&params.proposal_bulla))?
+    else {
+        msg!("[Dao::VoteMulti] Error: Proposal doesn't exist: {:?}", params.proposal_bulla);
+        return Err(DaoError::ProposalNonexistent.into())
+    };
+    // Get the current votes
+    let proposal_metadata: DaoProposalMetadata = deserialize(&data)?;
+
+    // Iterate through inputs
+    for input in &params.inputs {
+        signature_pubkeys.push(input.signature_public);
+        balance_commit += input.vote_commit;
+
+        let value_coords = input.vote_commit.to_affine().coordinates().unwrap();
+        let (sig_x, sig_y) = input.signature_public.xy();
+
+        zk_public_inputs.push((
+            DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS.to_string(),
+            vec![
+                proposal_metadata.snapshot_nulls,
+                params.proposal_bulla.inner(),
+                input.vote_nullifier.inner(),
+                *value_coords.x(),
+                *value_coords.y(),
+                params.token_commit,
+                proposal_metadata.snapshot_coins.inner(),
+                sig_x,
+                sig_y,
+            ],
+        ));
+    }
+
+    let balance_commit_coords = balance_commit.to_affine().coordinates().unwrap();
+    let mut public_inputs = vec![
+        params.token_commit,
+        params.proposal_bulla.inner(),
+        *balance_commit_coords.x(),
+        *balance_commit_coords.y(),
+    ];
+    for option_vote_commit in &params.option_vote_commits {
+        let coords = option_vote_commit.to_affine().coordinates().unwrap();
+        public_inputs.push(*coords.x());
+        public_inputs.push(*coords.y());
+    }
+
+    let all_vote_commit_coords = params.all_vote_commit.to_affine().coordinates().unwrap();
+    public_inputs.push(*all_vote_commit_coords.x());
+    public_inputs.push(*all_vote_commit_coords.y());
+    public_inputs.push(pallas::Base::from(params.max_block_height));
+
+    let (ephem_x, ephem_y) = params.note.ephem_public.xy();
+    public_inputs.push(ephem_x);
+    public_inputs.push(ephem_y);
+    public_inputs.extend_from_slice(&params.note.encrypted_values);
+
+    zk_public_inputs.push((DAO_CONTRACT_ZKAS_DAO_VOTE_MULTI_NS.to_string(), public_inputs));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Dao::VoteMulti`
+pub(crate) fn dao_vote_multi_process_instruction(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: DaoVoteMultiParams = deserialize_strict(&self_.data[1..])?;
+
+    // Check proposal bulla exists
+    let proposal_votes_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_PROPOSAL_BULLAS)?;
+    let Some(data) = wasm::db::db_get(proposal_votes_db, &serialize(&params.proposal_bulla))?
+    else {
+        msg!("[Dao::VoteMulti] Error: Proposal doesn't exist: {:?}", params.proposal_bulla);
+        return Err(DaoError::ProposalNonexistent.into())
+    };
+
+    // The proof shows voting is still open at max_block_height
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if current_height > params.max_block_height {
+        msg!(
+            "[Dao::VoteMulti] Error: Vote max block height {} passed, current height: {}",
+            params.max_block_height,
+            current_height
+        );
+        return Err(DaoError::VoteHeightExceeded.into())
+    }
+
+    // Get the current votes
+    let mut proposal_metadata: DaoProposalMetadata = deserialize(&data)?;
+
+    // Sealed proposals only support yes/no votes
+    if proposal_metadata.is_sealed() {
+        msg!("[Dao::VoteMulti] Error: Proposal {:?} is sealed", params.proposal_bulla);
+        return Err(DaoError::VoteMultiSealed.into())
+    }
+
+    // Check the Merkle root and nullifiers for the input coins are valid
+    let dao_vote_nullifier_db = wasm::db::db_lookup(cid, DAO_CONTRACT_DB_VOTE_NULLIFIERS)?;
+    let mut vote_nullifiers = vec![];
+
+    for input in &params.inputs {
+        // Prefix nullifier with proposal bulla so nullifiers from different proposals
+        // don't interfere with each other.
+        let null_key = serialize(&(params.proposal_bulla, input.vote_nullifier));
+
+        if vote_nullifiers.contains(&input.vote_nullifier) ||
+            wasm::db::db_contains_key(dao_vote_nullifier_db, &null_key)?
+        {
+            msg!("[Dao::VoteMulti] Error: Attempted double vote");
+            return Err(DaoError::DoubleVote.into())
+        }
+
+        vote_nullifiers.push(input.vote_nullifier);
+    }
+
+    proposal_metadata.vote_aggregate.all_vote_commit += params.all_vote_commit;
+
+    for (total, option_vote_commit) in proposal_metadata
+        .vote_aggregate
+        .option_vote_commits
+        .iter_mut()
+        .zip(params.option_vote_commits.iter())
+    {
+        *total += option_vote_commit;
+    }
+
+    // Create state update
+    let update = DaoVoteUpdate {
+        proposal_bulla: params.proposal_bulla,
+        proposal_metadata,
+        vote_nullifiers,
+        block_height: current_height as u32,
+        event: DaoEvent::VoteMulti { proposal_bulla: params.proposal_bulla, note: params.note },
+    };
+
+    let mut update_data = vec![];
+    update_data.write_u8(DaoFunction::VoteMulti as u8)?;
+    update.encode(&mut update_data)?;
+    Ok(update_data)
+}
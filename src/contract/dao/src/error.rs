@@ -91,6 +91,69 @@ pub enum DaoError {
 
     #[error("Wrong output coin")]
     AuthXferWrongOutputCoin,
+
+    #[error("Amend call parent is not DAO::exec()")]
+    AmendCallInvalidParent,
+
+    #[error("Amend call not found in parent")]
+    AmendCallNotFoundInParent,
+
+    #[error("Amended DAO bulla does not match proposal")]
+    AmendDaoBullaMismatch,
+
+    #[error("DAO doesn't exist")]
+    DaoNonexistent,
+
+    #[error("DAO was already amended")]
+    DaoAlreadyAmended,
+
+    #[error("Invalid proposal creation height")]
+    InvalidProposalCreationHeight,
+
+    #[error("Vote is past its max block height")]
+    VoteHeightExceeded,
+
+    #[error("Exec is before its min block height")]
+    ExecHeightNotReached,
+
+    #[error("Proposal is not sealed")]
+    ProposalNotSealed,
+
+    #[error("Proposal tally was already revealed")]
+    TallyAlreadyRevealed,
+
+    #[error("Sealed proposal tally was not revealed")]
+    TallyNotRevealed,
+
+    #[error("Multiple-choice votes can't be sealed")]
+    VoteMultiSealed,
+
+    #[error("Reveal is before its min block height")]
+    RevealHeightNotReached,
+
+    #[error("Cancel is past its max block height")]
+    CancelHeightExceeded,
+
+    #[error("Proposal was vetoed by the DAO guardian")]
+    ProposalVetoed,
+
+    #[error("Veto is past its max block height")]
+    VetoHeightExceeded,
+
+    #[error("Proposal deposit coin not found in transaction")]
+    ProposalDepositNotFound,
+
+    #[error("Settle deposit is before its min block height")]
+    SettleDepositHeightNotReached,
+
+    #[error("Proposal deposit can't be slashed")]
+    ProposalDepositNotSlashable,
+
+    #[error("Cancel is not signed by the proposer")]
+    CancelProposerMismatch,
+
+    #[error("Invalid superseded DAOs root")]
+    InvalidSupersededRoot,
 }
 
 impl From<DaoError> for ContractError {
@@ -120,6 +183,27 @@ impl From<DaoError> for ContractError {
             DaoError::AuthXferCallNotFoundInParent => Self::Custom(22),
             DaoError::AuthXferWrongNumberOutputs => Self::Custom(23),
             DaoError::AuthXferWrongOutputCoin => Self::Custom(24),
+            DaoError::AmendCallInvalidParent => Self::Custom(25),
+            DaoError::AmendCallNotFoundInParent => Self::Custom(26),
+            DaoError::AmendDaoBullaMismatch => Self::Custom(27),
+            DaoError::DaoNonexistent => Self::Custom(28),
+            DaoError::DaoAlreadyAmended => Self::Custom(29),
+            DaoError::InvalidProposalCreationHeight => Self::Custom(30),
+            DaoError::VoteHeightExceeded => Self::Custom(31),
+            DaoError::ExecHeightNotReached => Self::Custom(32),
+            DaoError::ProposalNotSealed => Self::Custom(33),
+            DaoError::TallyAlreadyRevealed => Self::Custom(34),
+            DaoError::TallyNotRevealed => Self::Custom(35),
+            DaoError::VoteMultiSealed => Self::Custom(36),
+            DaoError::RevealHeightNotReached => Self::Custom(37),
+            DaoError::CancelHeightExceeded => Self::Custom(38),
+            DaoError::ProposalVetoed => Self::Custom(39),
+            DaoError::VetoHeightExceeded => Self::Custom(40),
+            DaoError::ProposalDepositNotFound => Self::Custom(41),
+            DaoError::SettleDepositHeightNotReached => Self::Custom(42),
+            DaoError::ProposalDepositNotSlashable => Self::Custom(43),
+            DaoError::CancelProposerMismatch => Self::Custom(44),
+            DaoError::InvalidSupersededRoot => Self::Custom(45),
         }
     }
 }
@@ -29,6 +29,12 @@ pub enum DaoFunction {
     Vote = 0x02,
     Exec = 0x03,
     AuthMoneyTransfer = 0x04,
+    Amend = 0x05,
+    VoteMulti = 0x06,
+    RevealTally = 0x07,
+    Cancel = 0x08,
+    Veto = 0x09,
+    SettleDeposit = 0x0a,
 }
 
 impl TryFrom<u8> for DaoFunction {
@@ -41,6 +47,12 @@ impl TryFrom<u8> for DaoFunction {
             0x02 => Ok(DaoFunction::Vote),
             0x03 => Ok(DaoFunction::Exec),
             0x04 => Ok(DaoFunction::AuthMoneyTransfer),
+            0x05 => Ok(DaoFunction::Amend),
+            0x06 => Ok(DaoFunction::VoteMulti),
+            0x07 => Ok(DaoFunction::RevealTally),
+            0x08 => Ok(DaoFunction::Cancel),
+            0x09 => Ok(DaoFunction::Veto),
+            0x0a => Ok(DaoFunction::SettleDeposit),
             _ => Err(ContractError::InvalidFunction),
         }
     }
@@ -65,12 +77,19 @@ pub const DAO_CONTRACT_DB_INFO_TREE: &str = "dao_info";
 pub const DAO_CONTRACT_DB_DAO_BULLAS: &str = "dao_bullas";
 pub const DAO_CONTRACT_DB_DAO_MERKLE_ROOTS: &str = "dao_roots";
 pub const DAO_CONTRACT_DB_PROPOSAL_BULLAS: &str = "dao_proposals";
+pub const DAO_CONTRACT_DB_PROPOSAL_TOMBSTONES: &str = "dao_proposal_tombstones";
 pub const DAO_CONTRACT_DB_VOTE_NULLIFIERS: &str = "dao_vote_nullifiers";
+pub const DAO_CONTRACT_DB_DAO_AMENDMENTS: &str = "dao_amendments";
+pub const DAO_CONTRACT_DB_SUPERSEDED_DAOS: &str = "dao_superseded";
+pub const DAO_CONTRACT_DB_DAO_LINEAGES: &str = "dao_lineages";
+pub const DAO_CONTRACT_DB_EVENTS: &str = "dao_events";
 
 // These are keys inside the info tree
 pub const DAO_CONTRACT_KEY_DB_VERSION: &[u8] = b"db_version";
 pub const DAO_CONTRACT_KEY_DAO_MERKLE_TREE: &[u8] = b"dao_merkle_tree";
 pub const DAO_CONTRACT_KEY_LATEST_DAO_ROOT: &[u8] = b"dao_last_root";
+pub const DAO_CONTRACT_KEY_NEXT_EVENT: &[u8] = b"dao_next_event";
+pub const DAO_CONTRACT_KEY_FIRST_EVENT: &[u8] = b"dao_first_event";
 
 /// zkas dao mint circuit namespace
 pub const DAO_CONTRACT_ZKAS_DAO_MINT_NS: &str = "Mint";
@@ -78,28 +97,50 @@ pub const DAO_CONTRACT_ZKAS_DAO_MINT_NS: &str = "Mint";
 pub const DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS: &str = "VoteInput";
 /// zkas dao vote main circuit namespace
 pub const DAO_CONTRACT_ZKAS_DAO_VOTE_MAIN_NS: &str = "VoteMain";
+/// zkas dao multiple-choice vote circuit namespace
+pub const DAO_CONTRACT_ZKAS_DAO_VOTE_MULTI_NS: &str = "VoteMulti";
 /// zkas dao propose input circuit namespace
 pub const DAO_CONTRACT_ZKAS_DAO_PROPOSE_INPUT_NS: &str = "ProposeInput";
 /// zkas dao propose main circuit namespace
 pub const DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS: &str = "ProposeMain";
 /// zkas dao exec circuit namespace
 pub const DAO_CONTRACT_ZKAS_DAO_EXEC_NS: &str = "Exec";
+/// zkas dao multiple-choice exec circuit namespace
+pub const DAO_CONTRACT_ZKAS_DAO_EXEC_MULTI_NS: &str = "ExecMulti";
 /// zkas dao auth money_transfer circuit namespace
 pub const DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_NS: &str = "AuthMoneyTransfer";
 /// zkas dao auth money_transfer encrypted coin circuit namespace
 pub const DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_ENC_COIN_NS: &str = "AuthMoneyTransferEncCoin";
+/// zkas dao amend circuit namespace
+pub const DAO_CONTRACT_ZKAS_DAO_AMEND_NS: &str = "Amend";
+/// zkas dao sealed tally reveal circuit namespace
+pub const DAO_CONTRACT_ZKAS_DAO_REVEAL_TALLY_NS: &str = "RevealTally";
+/// zkas dao proposal cancel circuit namespace
+pub const DAO_CONTRACT_ZKAS_DAO_CANCEL_NS: &str = "Cancel";
+/// zkas dao proposal veto circuit namespace
+pub const DAO_CONTRACT_ZKAS_DAO_VETO_NS: &str = "Veto";
+/// zkas dao proposal deposit settlement circuit namespace
+pub const DAO_CONTRACT_ZKAS_DAO_SETTLE_DEPOSIT_NS: &str = "SettleDeposit";
 
 /// Not allowed to make proposals using snapshots with block heights older than this depth
 pub const PROPOSAL_SNAPSHOT_CUTOFF_LIMIT: u32 = 100;
 
-// ANCHOR: dao-blockwindow
-const BLOCK_TIME: u64 = 90;
-const SECS_IN_HOUR: u64 = 60 * 60;
-const WINDOW_TIME_HR: u64 = 4;
+/// Not allowed to make proposals with a creation height older than this depth
+pub const PROPOSAL_CREATION_CUTOFF_LIMIT: u64 = 10;
 
-/// Blockwindow from blockheight. Used for time limit on DAO proposals.
-pub fn blockwindow(height: u32) -> u64 {
-    let timestamp_secs = height as u64 * BLOCK_TIME;
-    timestamp_secs / (WINDOW_TIME_HR * SECS_IN_HOUR)
-}
-// ANCHOR_END: dao-blockwindow
+/// Maximum number of options in a multiple-choice proposal
+pub const PROPOSAL_MAX_OPTIONS: usize = 4;
+
+/// Blocks after voting ends in which a DAO guardian can veto a proposal
+pub const PROPOSAL_VETO_GRACE_BLOCKS: u64 = 100;
+
+/// A proposal's deposit is slashed when more than this many times its yes
+/// votes were cast, so less than 1/4 of the cast votes were in favour
+pub const PROPOSAL_DEPOSIT_SLASH_DIVISOR: u64 = 4;
+
+/// Blocks a recorded DAO event is kept for before it's pruned. Wallets
+/// that haven't scanned for longer have to trial-decrypt transactions.
+pub const DAO_EVENTS_RETENTION_BLOCKS: u32 = 100_000;
+
+/// Maximum number of expired DAO events pruned when recording an event
+pub const DAO_EVENTS_PRUNE_LIMIT: usize = 4;
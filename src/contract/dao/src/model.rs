@@ -18,9 +18,10 @@
 
 use core::str::FromStr;
 
-use darkfi_money_contract::model::{Nullifier, TokenId};
+use darkfi_money_contract::model::{Coin, Nullifier, TokenId};
 use darkfi_sdk::{
     crypto::{
+        address::{AddressKind, Addressable},
         note::{AeadEncryptedNote, ElGamalEncryptedNote},
         pasta_prelude::*,
         poseidon_hash, BaseBlind, ContractId, MerkleNode, PublicKey,
@@ -30,6 +31,8 @@ use darkfi_sdk::{
 };
 use darkfi_serial::{Encodable, SerialDecodable, SerialEncodable};
 
+use crate::PROPOSAL_MAX_OPTIONS;
+
 #[cfg(feature = "client")]
 use darkfi_serial::async_trait;
 
@@ -38,11 +41,22 @@ use darkfi_serial::async_trait;
 /// DAOs are represented on chain as a commitment to this object
 pub struct Dao {
     pub proposer_limit: u64,
+    /// Governance tokens bonded when making a proposal, which are slashed
+    /// to the treasury if it's overwhelmingly rejected. Zero disables it.
+    pub proposal_deposit: u64,
     pub quorum: u64,
+    /// Yes votes after which a proposal can be executed before its
+    /// voting period ends. Set it to at least the approval ratio of the
+    /// governance token supply, so the outcome can no longer flip.
+    pub early_exec_quorum: u64,
     pub approval_ratio_quot: u64,
     pub approval_ratio_base: u64,
+    /// Function applied to a voter's snapshotted balance to get their vote weight
+    pub vote_weight: DaoVoteWeight,
     pub gov_token_id: TokenId,
     pub public_key: PublicKey,
+    /// Optional guardian who can veto proposals before they're executed
+    pub guardian_public_key: Option<PublicKey>,
     pub bulla_blind: BaseBlind,
 }
 // ANCHOR_END: dao
@@ -50,22 +64,83 @@ pub struct Dao {
 impl Dao {
     pub fn to_bulla(&self) -> DaoBulla {
         let proposer_limit = pallas::Base::from(self.proposer_limit);
+        let proposal_deposit = pallas::Base::from(self.proposal_deposit);
         let quorum = pallas::Base::from(self.quorum);
+        let early_exec_quorum = pallas::Base::from(self.early_exec_quorum);
         let approval_ratio_quot = pallas::Base::from(self.approval_ratio_quot);
         let approval_ratio_base = pallas::Base::from(self.approval_ratio_base);
+        let vote_weight_mode = pallas::Base::from(self.vote_weight.mode());
+        let vote_weight_cap = pallas::Base::from(self.vote_weight.cap());
         let (pub_x, pub_y) = self.public_key.xy();
+        let (guardian_x, guardian_y) = self.guardian_xy();
         let bulla = poseidon_hash([
             proposer_limit,
+            proposal_deposit,
             quorum,
+            early_exec_quorum,
             approval_ratio_quot,
             approval_ratio_base,
+            vote_weight_mode,
+            vote_weight_cap,
             self.gov_token_id.inner(),
             pub_x,
             pub_y,
+            guardian_x,
+            guardian_y,
             self.bulla_blind.inner(),
         ]);
         DaoBulla(bulla)
     }
+
+    /// Coordinates of the guardian public key, or zero when there's none
+    pub fn guardian_xy(&self) -> (pallas::Base, pallas::Base) {
+        match self.guardian_public_key {
+            Some(guardian_public_key) => guardian_public_key.xy(),
+            None => (pallas::Base::ZERO, pallas::Base::ZERO),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-vote-weight
+/// How a voter's snapshotted governance token balance is turned into
+/// vote weight. Weights other than linear resist whale dominance.
+pub enum DaoVoteWeight {
+    /// One vote per token
+    Linear,
+    /// Square root of the balance, rounded down
+    SquareRoot,
+    /// The balance, up to the given cap
+    Capped(u64),
+}
+// ANCHOR_END: dao-vote-weight
+
+impl DaoVoteWeight {
+    /// Mode committed to in the DAO bulla
+    pub fn mode(&self) -> u64 {
+        match self {
+            Self::Linear => 0,
+            Self::SquareRoot => 1,
+            Self::Capped(_) => 2,
+        }
+    }
+
+    /// Cap committed to in the DAO bulla, zero when uncapped
+    pub fn cap(&self) -> u64 {
+        match self {
+            Self::Capped(cap) => *cap,
+            _ => 0,
+        }
+    }
+
+    /// Vote weight of the given balance
+    pub fn apply(&self, balance: u64) -> u64 {
+        match self {
+            Self::Linear => balance,
+            Self::SquareRoot => balance.isqrt(),
+            Self::Capped(cap) => balance.min(*cap),
+        }
+    }
 }
 
 /// A `DaoBulla` represented in the state
@@ -101,6 +176,21 @@ impl std::hash::Hash for DaoBulla {
     }
 }
 
+impl Addressable for DaoBulla {
+    const KIND: AddressKind = AddressKind::DaoBulla;
+
+    fn address_payload(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+
+    fn from_address_payload(payload: &[u8]) -> Result<Self, ContractError> {
+        let Ok(bytes) = payload.try_into() else {
+            return Err(ContractError::IoError("Invalid DaoBulla address length".to_string()))
+        };
+        Self::from_bytes(bytes)
+    }
+}
+
 darkfi_sdk::fp_from_bs58!(DaoBulla);
 darkfi_sdk::fp_to_bs58!(DaoBulla);
 darkfi_sdk::ty_from_fp!(DaoBulla);
@@ -143,8 +233,14 @@ impl VecAuthCallCommit for Vec<DaoAuthCall> {
 // ANCHOR: dao-proposal
 pub struct DaoProposal {
     pub auth_calls: Vec<DaoAuthCall>,
-    pub creation_day: u64,
-    pub duration_days: u64,
+    /// Options of a multiple-choice proposal, each being the auth calls
+    /// executed when it wins. Empty for yes/no proposals, which execute
+    /// `auth_calls` instead.
+    pub options: Vec<Vec<DaoAuthCall>>,
+    /// Block height the proposal was created at
+    pub creation_height: u64,
+    /// Number of blocks voting stays open for
+    pub duration_blocks: u64,
     /// Arbitrary data provided by the user. We don't use this.
     pub user_data: pallas::Base,
     pub dao_bulla: DaoBulla,
@@ -153,11 +249,32 @@ pub struct DaoProposal {
 // ANCHOR_END: dao-proposal
 
 impl DaoProposal {
+    /// Commitments to the auth calls of each option, padded with zeros
+    /// up to `PROPOSAL_MAX_OPTIONS`. The padding can never be executed
+    /// since no set of auth calls commits to zero.
+    pub fn option_commits(&self) -> [pallas::Base; PROPOSAL_MAX_OPTIONS] {
+        assert!(self.options.len() <= PROPOSAL_MAX_OPTIONS);
+        let mut commits = [pallas::Base::ZERO; PROPOSAL_MAX_OPTIONS];
+        for (commit, option) in commits.iter_mut().zip(self.options.iter()) {
+            *commit = option.commit();
+        }
+        commits
+    }
+
+    /// Commitment to the auth calls contained in the proposal bulla.
+    /// For multiple-choice proposals this commits to every option.
+    pub fn auth_calls_commit(&self) -> pallas::Base {
+        if self.options.is_empty() {
+            return self.auth_calls.commit()
+        }
+        poseidon_hash(self.option_commits())
+    }
+
     pub fn to_bulla(&self) -> DaoProposalBulla {
         let bulla = poseidon_hash([
-            self.auth_calls.commit(),
-            pallas::Base::from(self.creation_day),
-            pallas::Base::from(self.duration_days),
+            self.auth_calls_commit(),
+            pallas::Base::from(self.creation_height),
+            pallas::Base::from(self.duration_blocks),
             self.user_data,
             self.dao_bulla.inner(),
             self.blind.inner(),
@@ -166,6 +283,31 @@ impl DaoProposal {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-proposal-memo
+/// Human-readable proposal metadata, shared with the DAO members
+/// inside the encrypted note of `Dao::Propose`
+pub struct DaoProposalMemo {
+    /// Short title of the proposal
+    pub title: String,
+    /// BLAKE3 hash of the full description, which is shared off-chain
+    pub description_hash: [u8; 32],
+    /// Link to the description or discussion. Empty if there is none.
+    pub link: String,
+}
+// ANCHOR_END: dao-proposal-memo
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-propose-note
+/// Plaintext of the encrypted note of `Dao::Propose`
+pub struct DaoProposeNote {
+    /// The proposal, letting DAO members open its bulla
+    pub proposal: DaoProposal,
+    /// Optional human-readable metadata
+    pub memo: Option<DaoProposalMemo>,
+}
+// ANCHOR_END: dao-propose-note
+
 /// A `DaoProposalBulla` represented in the state
 #[derive(Debug, Copy, Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
 pub struct DaoProposalBulla(pallas::Base);
@@ -219,6 +361,10 @@ pub struct DaoMintParams {
 pub struct DaoMintUpdate {
     /// Revealed DAO bulla
     pub dao_bulla: DaoBulla,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
 }
 
 #[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
@@ -227,12 +373,25 @@ pub struct DaoMintUpdate {
 pub struct DaoProposeParams {
     /// Merkle root of the DAO in the DAO state
     pub dao_merkle_root: MerkleNode,
+    /// Root of the superseded DAOs SMT the proof shows the DAO is absent from
+    pub dao_superseded_root: pallas::Base,
     /// Token ID commitment for the proposal
     pub token_commit: pallas::Base,
     /// Bulla of the DAO proposal
     pub proposal_bulla: DaoProposalBulla,
+    /// Creation height of the proposal, which can't be in the future
+    pub creation_height: u64,
     /// Encrypted note
     pub note: AeadEncryptedNote,
+    /// Threshold group key of the tally trustees, which votes are
+    /// encrypted to for sealed proposals whose tally is only revealed
+    /// once voting ended
+    pub tally_public_key: Option<PublicKey>,
+    /// Key the proposer signs `Dao::Cancel` with
+    pub proposer_public_key: PublicKey,
+    /// Coin bonding the DAO's proposal deposit, which a `Money::Transfer`
+    /// in the same transaction has to mint
+    pub deposit_coin: Option<Coin>,
     /// Inputs for the proposal
     pub inputs: Vec<DaoProposeParamsInput>,
 }
@@ -262,6 +421,14 @@ pub struct DaoProposeUpdate {
     pub snapshot_coins: MerkleNode,
     /// Snapshotted SMT root in the Money state
     pub snapshot_nulls: pallas::Base,
+    /// Tally key of a sealed proposal
+    pub tally_public_key: Option<PublicKey>,
+    /// Key the proposer signs `Dao::Cancel` with
+    pub proposer_public_key: PublicKey,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
 }
 
 /// Metadata for a DAO proposal on the blockchain
@@ -273,6 +440,32 @@ pub struct DaoProposalMetadata {
     pub snapshot_coins: MerkleNode,
     /// Snapshotted SMT root in the Money state
     pub snapshot_nulls: pallas::Base,
+    /// Votes of a sealed proposal are encrypted to this key
+    pub tally_public_key: Option<PublicKey>,
+    /// Tally of a sealed proposal, once revealed
+    pub revealed_tally: Option<DaoRevealedTally>,
+    /// Whether the DAO guardian vetoed the proposal
+    pub vetoed: bool,
+    /// Only this key can cancel the proposal
+    pub proposer_public_key: PublicKey,
+}
+
+impl DaoProposalMetadata {
+    /// Whether the proposal tally is hidden until voting ends
+    pub fn is_sealed(&self) -> bool {
+        self.tally_public_key.is_some()
+    }
+}
+
+/// Vote values of a sealed proposal, revealed after voting ended
+#[derive(Debug, Copy, Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct DaoRevealedTally {
+    /// Total yes votes
+    pub yes_vote_value: u64,
+    /// Total abstaining votes
+    pub abstain_vote_value: u64,
+    /// Total votes
+    pub all_vote_value: u64,
 }
 
 #[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
@@ -285,8 +478,17 @@ pub struct DaoVoteParams {
     pub proposal_bulla: DaoProposalBulla,
     /// Commitment for yes votes
     pub yes_vote_commit: pallas::Point,
+    /// Commitment for abstaining votes, which count towards quorum
+    /// but not towards the approval ratio
+    pub abstain_vote_commit: pallas::Point,
+    /// Commitment for the vote weight, derived in the proof from the
+    /// balance committed to by the inputs
+    pub all_vote_commit: pallas::Point,
+    /// Highest block height the vote can be confirmed at.
+    /// The proof shows voting is still open at this height.
+    pub max_block_height: u64,
     /// Encrypted note
-    pub note: ElGamalEncryptedNote<4>,
+    pub note: ElGamalEncryptedNote<6>,
     /// Inputs for the vote
     pub inputs: Vec<DaoVoteParamsInput>,
 }
@@ -315,8 +517,36 @@ pub struct DaoVoteUpdate {
     pub proposal_metadata: DaoProposalMetadata,
     /// Vote nullifiers,
     pub vote_nullifiers: Vec<Nullifier>,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
 }
 
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-vote-multi-params
+/// Parameters for `Dao::VoteMulti`, voting on a multiple-choice proposal
+pub struct DaoVoteMultiParams {
+    /// Token commitment for the vote inputs
+    pub token_commit: pallas::Base,
+    /// Proposal bulla being voted on
+    pub proposal_bulla: DaoProposalBulla,
+    /// Commitment for the votes of each option. Only the chosen one
+    /// commits to a nonzero value, or none of them when abstaining.
+    pub option_vote_commits: [pallas::Point; PROPOSAL_MAX_OPTIONS],
+    /// Commitment for the vote weight, derived in the proof from the
+    /// balance committed to by the inputs
+    pub all_vote_commit: pallas::Point,
+    /// Highest block height the vote can be confirmed at.
+    /// The proof shows voting is still open at this height.
+    pub max_block_height: u64,
+    /// Encrypted note
+    pub note: ElGamalEncryptedNote<{ PROPOSAL_MAX_OPTIONS + 3 }>,
+    /// Inputs for the vote
+    pub inputs: Vec<DaoVoteParamsInput>,
+}
+// ANCHOR_END: dao-vote-multi-params
+
 #[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
 // ANCHOR: dao-blind-aggregate-vote
 /// Represents a single or multiple blinded votes.
@@ -324,8 +554,12 @@ pub struct DaoVoteUpdate {
 pub struct DaoBlindAggregateVote {
     /// Weighted vote commit
     pub yes_vote_commit: pallas::Point,
+    /// Abstaining vote commit
+    pub abstain_vote_commit: pallas::Point,
     /// All value staked in the vote
     pub all_vote_commit: pallas::Point,
+    /// Vote commits of each option, for multiple-choice proposals
+    pub option_vote_commits: [pallas::Point; PROPOSAL_MAX_OPTIONS],
 }
 // ANCHOR_END: dao-blind-aggregate-vote
 
@@ -333,7 +567,11 @@ impl DaoBlindAggregateVote {
     /// Aggregate a vote with existing one
     pub fn aggregate(&mut self, other: Self) {
         self.yes_vote_commit += other.yes_vote_commit;
+        self.abstain_vote_commit += other.abstain_vote_commit;
         self.all_vote_commit += other.all_vote_commit;
+        for (commit, other) in self.option_vote_commits.iter_mut().zip(other.option_vote_commits) {
+            *commit += other;
+        }
     }
 }
 
@@ -341,7 +579,9 @@ impl Default for DaoBlindAggregateVote {
     fn default() -> Self {
         Self {
             yes_vote_commit: pallas::Point::identity(),
+            abstain_vote_commit: pallas::Point::identity(),
             all_vote_commit: pallas::Point::identity(),
+            option_vote_commits: [pallas::Point::identity(); PROPOSAL_MAX_OPTIONS],
         }
     }
 }
@@ -353,11 +593,19 @@ pub struct DaoExecParams {
     /// The proposal bulla
     pub proposal_bulla: DaoProposalBulla,
     pub proposal_auth_calls: Vec<DaoAuthCall>,
+    /// Whether this executes the winning option of a multiple-choice
+    /// proposal, rather than a yes/no proposal.
+    pub multi_choice: bool,
     /// Aggregated blinds for the vote commitments
     pub blind_total_vote: DaoBlindAggregateVote,
+    /// Lowest block height the exec can be confirmed at.
+    /// The proof shows voting has ended at this height, unless executing early.
+    pub min_block_height: u64,
     /// Public key for the signature.
     /// The signature ensures this DAO::exec call cannot be modified with other calls.
     pub signature_public: PublicKey,
+    /// Root of the superseded DAOs SMT the proof shows the DAO is absent from
+    pub dao_superseded_root: pallas::Base,
 }
 // ANCHOR_END: dao-exec-params
 
@@ -366,6 +614,12 @@ pub struct DaoExecParams {
 pub struct DaoExecUpdate {
     /// The proposal bulla
     pub proposal_bulla: DaoProposalBulla,
+    /// Old and new bulla of the DAO amended by a child `Dao::Amend` call
+    pub amended_dao: Option<(DaoBulla, DaoBulla)>,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
 }
 
 #[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
@@ -376,3 +630,194 @@ pub struct DaoAuthMoneyTransferParams {
     pub dao_change_attrs: ElGamalEncryptedNote<3>,
 }
 // ANCHOR_END: dao-auth_xfer-params
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-auth_xfer-data
+/// Auth data of a proposal's `Dao::AuthMoneyTransfer` call
+pub struct DaoAuthMoneyTransferData {
+    /// Coins sent to the recipients. Each coin commits to its value, so
+    /// this also fixes the total the proposal sends.
+    pub coins: Vec<Coin>,
+}
+// ANCHOR_END: dao-auth_xfer-data
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-reveal-tally-params
+/// Parameters for `Dao::RevealTally`
+pub struct DaoRevealTallyParams {
+    /// The sealed proposal bulla
+    pub proposal_bulla: DaoProposalBulla,
+    /// Total yes votes
+    pub yes_vote_value: u64,
+    /// Total abstaining votes
+    pub abstain_vote_value: u64,
+    /// Total votes
+    pub all_vote_value: u64,
+    /// Lowest block height the reveal can be confirmed at.
+    /// The proof shows voting has ended at this height.
+    pub min_block_height: u64,
+}
+// ANCHOR_END: dao-reveal-tally-params
+
+/// State update for `Dao::RevealTally`
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct DaoRevealTallyUpdate {
+    /// The sealed proposal bulla
+    pub proposal_bulla: DaoProposalBulla,
+    /// The updated proposal metadata
+    pub proposal_metadata: DaoProposalMetadata,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
+}
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-cancel-params
+/// Parameters for `Dao::Cancel`
+pub struct DaoCancelParams {
+    /// The proposal bulla being cancelled
+    pub proposal_bulla: DaoProposalBulla,
+    /// Highest block height the cancel can be confirmed at.
+    /// The proof shows voting is still open at this height.
+    pub max_block_height: u64,
+    /// Key committed by the proposer in `Dao::Propose`, which has
+    /// to sign the cancel
+    pub proposer_public_key: PublicKey,
+}
+// ANCHOR_END: dao-cancel-params
+
+/// State update for `Dao::Cancel`
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct DaoCancelUpdate {
+    /// The cancelled proposal bulla
+    pub proposal_bulla: DaoProposalBulla,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
+}
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-veto-params
+/// Parameters for `Dao::Veto`
+pub struct DaoVetoParams {
+    /// The proposal bulla being vetoed
+    pub proposal_bulla: DaoProposalBulla,
+    /// Guardian of the proposal DAO, who signs the veto
+    pub guardian_public_key: PublicKey,
+    /// Highest block height the veto can be confirmed at.
+    /// The proof shows the veto grace period hasn't ended at this height.
+    pub max_block_height: u64,
+}
+// ANCHOR_END: dao-veto-params
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-settle-deposit-params
+/// Parameters for `Dao::SettleDeposit`
+pub struct DaoSettleDepositParams {
+    /// The proposal bulla the deposit was bonded for
+    pub proposal_bulla: DaoProposalBulla,
+    /// Aggregated vote commits of the proposal, which are only checked
+    /// while it's still in the DAO state
+    pub blind_total_vote: DaoBlindAggregateVote,
+    /// Whether the deposit is slashed to the DAO treasury, instead of
+    /// refunded to the proposer
+    pub slashed: bool,
+    /// Lowest block height the settlement can be confirmed at.
+    /// The proof shows voting ended by this height.
+    pub min_block_height: u64,
+}
+// ANCHOR_END: dao-settle-deposit-params
+
+/// State update for `Dao::SettleDeposit`
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct DaoSettleDepositUpdate {
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
+}
+
+/// State update for `Dao::Veto`
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct DaoVetoUpdate {
+    /// The vetoed proposal bulla
+    pub proposal_bulla: DaoProposalBulla,
+    /// Updated proposal metadata
+    pub proposal_metadata: DaoProposalMetadata,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
+}
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-amend-params
+/// Parameters for `Dao::Amend`
+pub struct DaoAmendParams {
+    /// Bulla of the DAO being amended
+    pub old_dao_bulla: DaoBulla,
+    /// Bulla of the DAO with the amended parameters
+    pub new_dao_bulla: DaoBulla,
+}
+// ANCHOR_END: dao-amend-params
+
+/// State update for `Dao::Amend`
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct DaoAmendUpdate {
+    /// Bulla of the DAO being amended
+    pub old_dao_bulla: DaoBulla,
+    /// Minted DAO bulla
+    pub new_dao_bulla: DaoBulla,
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// Event for wallets scanning the DAO state
+    pub event: DaoEvent,
+}
+
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+// ANCHOR: dao-event
+/// State-change event recorded in the DAO events tree. Wallets scan these
+/// to discover DAOs and proposals and to follow voting progress, instead
+/// of trial-decrypting every transaction.
+pub enum DaoEvent {
+    /// A DAO was minted
+    Mint { dao_bulla: DaoBulla },
+    /// A proposal was made. The note is encrypted to the DAO.
+    Propose { proposal_bulla: DaoProposalBulla, note: AeadEncryptedNote },
+    /// A vote was cast. The note is encrypted to the DAO.
+    Vote { proposal_bulla: DaoProposalBulla, note: ElGamalEncryptedNote<6> },
+    /// A multiple-choice vote was cast. The note is encrypted to the DAO.
+    VoteMulti {
+        proposal_bulla: DaoProposalBulla,
+        note: ElGamalEncryptedNote<{ PROPOSAL_MAX_OPTIONS + 3 }>,
+    },
+    /// A proposal was executed
+    Exec { proposal_bulla: DaoProposalBulla },
+    /// A DAO was amended, minting its successor
+    Amend { old_dao_bulla: DaoBulla, new_dao_bulla: DaoBulla },
+    /// The tally of a sealed proposal was revealed
+    RevealTally {
+        proposal_bulla: DaoProposalBulla,
+        yes_vote_value: u64,
+        abstain_vote_value: u64,
+        all_vote_value: u64,
+    },
+    /// A proposal was cancelled by its proposer
+    Cancel { proposal_bulla: DaoProposalBulla },
+    /// A proposal was vetoed by the DAO guardian
+    Veto { proposal_bulla: DaoProposalBulla },
+    /// A proposal deposit was refunded, or slashed to the DAO treasury
+    SettleDeposit { proposal_bulla: DaoProposalBulla, slashed: bool },
+}
+
+/// A [`DaoEvent`] along with the block height it was recorded at
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct DaoEventRecord {
+    /// Block height the event is recorded at
+    pub block_height: u32,
+    /// The recorded event
+    pub event: DaoEvent,
+}
+// ANCHOR_END: dao-event
@@ -19,8 +19,11 @@
 use darkfi::Result;
 use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
 use darkfi_dao_contract::{
-    model::{Dao, DaoBlindAggregateVote},
-    DaoFunction,
+    client::{
+        decrypt_propose_note, make_amend_auth_call, make_proposal_memo, verify_proposal_description,
+    },
+    model::{Dao, DaoBlindAggregateVote, DaoEvent, DaoVoteWeight, VecAuthCallCommit},
+    DaoFunction, PROPOSAL_MAX_OPTIONS, PROPOSAL_VETO_GRACE_BLOCKS,
 };
 use darkfi_money_contract::{
     model::{CoinAttributes, TokenAttributes, DARK_TOKEN_ID},
@@ -28,10 +31,11 @@ use darkfi_money_contract::{
 };
 use darkfi_sdk::{
     crypto::{
+        frost::{combine_decryption_shares, decryption_share, split_secret, KeyPackage},
         pasta_prelude::*,
         pedersen_commitment_u64, poseidon_hash,
         util::{fp_mod_fv, fp_to_u64},
-        BaseBlind, Blind, FuncId, FuncRef, DAO_CONTRACT_ID, MONEY_CONTRACT_ID,
+        BaseBlind, Blind, FuncId, FuncRef, Keypair, SecretKey, DAO_CONTRACT_ID, MONEY_CONTRACT_ID,
     },
     pasta::pallas,
 };
@@ -46,7 +50,7 @@ fn integration_test() -> Result<()> {
         // Holders this test will use:
         // * Alice, Bob, and Charlie are members of the DAO.
         // * Dao is the DAO wallet
-        // * Rachel is the proposal recipient.
+        // * Rachel and Charlie are the proposal recipients.
         const HOLDERS: [Holder; 5] =
             [Holder::Alice, Holder::Bob, Holder::Charlie, Holder::Dao, Holder::Rachel];
 
@@ -77,8 +81,10 @@ fn integration_test() -> Result<()> {
         // And the DRK token as the treasury token
         let drk_token_id = *DARK_TOKEN_ID;
         const DRK_TOKEN_SUPPLY: u64 = 1_000_000_000;
-        // The tokens we want to send via the proposal
+        // The tokens we want to send via the proposal, split between two recipients
         const PROPOSAL_AMOUNT: u64 = 250_000_000;
+        const RACHEL_PROPOSAL_AMOUNT: u64 = 200_000_000;
+        const CHARLIE_PROPOSAL_AMOUNT: u64 = PROPOSAL_AMOUNT - RACHEL_PROPOSAL_AMOUNT;
 
         // Block height to verify against
         let mut current_block_height = 0;
@@ -87,11 +93,17 @@ fn integration_test() -> Result<()> {
         let dao_keypair = th.holders.get(&Holder::Dao).unwrap().keypair;
         let dao = Dao {
             proposer_limit: 100_000_000,
+            proposal_deposit: 0,
             quorum: 199_999_999,
+            // Yes votes from two thirds of the governance token supply
+            // decide the outcome, so proposals are executed without waiting.
+            early_exec_quorum: 200_000_000,
             approval_ratio_base: 2,
             approval_ratio_quot: 1,
+            vote_weight: DaoVoteWeight::Linear,
             gov_token_id,
             public_key: dao_keypair.public,
+            guardian_public_key: None,
             bulla_blind: Blind::random(&mut OsRng),
         };
 
@@ -159,6 +171,14 @@ fn integration_test() -> Result<()> {
 
         th.assert_trees(&HOLDERS);
 
+        // Wallets discover the DAO by scanning the events of each block
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.len() == 1);
+        assert!(matches!(
+            events[0],
+            DaoEvent::Mint { dao_bulla } if dao_bulla == dao_mint_params.dao_bulla
+        ));
+
         current_block_height += 1;
 
         // ======================================
@@ -281,23 +301,39 @@ fn integration_test() -> Result<()> {
         info!("[Alice] Building DAO proposal tx");
 
         // These coins are passed around to all DAO members who verify its validity
-        // They also check hashing them equals the proposal_commit
-        let proposal_coinattrs = vec![CoinAttributes {
-            public_key: th.holders.get(&Holder::Rachel).unwrap().keypair.public,
-            value: PROPOSAL_AMOUNT,
-            token_id: drk_token_id,
-            spend_hook: FuncId::none(),
-            user_data: pallas::Base::ZERO,
-            blind: Blind::random(&mut OsRng),
-        }];
+        // They also check hashing them equals the proposal_commit.
+        // A single proposal can pay several recipients.
+        let proposal_coinattrs = vec![
+            CoinAttributes {
+                public_key: th.holders.get(&Holder::Rachel).unwrap().keypair.public,
+                value: RACHEL_PROPOSAL_AMOUNT,
+                token_id: drk_token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            },
+            CoinAttributes {
+                public_key: th.holders.get(&Holder::Charlie).unwrap().keypair.public,
+                value: CHARLIE_PROPOSAL_AMOUNT,
+                token_id: drk_token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            },
+        ];
+
+        // The memo tells DAO members what the proposal is about
+        let description = "Pay Rachel and Charlie for their work on the DAO tooling.";
+        let memo = make_proposal_memo("Contributor payouts", description, "https://dark.fi");
         // We can add whatever we want in here, even arbitrary text
         // It's up to the auth module to decide what to do with it.
         let user_data = pallas::Base::ZERO;
 
-        let (propose_tx, (propose_params, fee_params), propose_info) = th
+        let (propose_tx, (propose_params, deposit_params, fee_params), propose_info) = th
             .dao_propose(
                 &Holder::Alice,
                 &proposal_coinattrs,
+                Some(memo.clone()),
                 user_data,
                 &dao,
                 &dao_mint_params.dao_bulla,
@@ -311,6 +347,7 @@ fn integration_test() -> Result<()> {
                 holder,
                 propose_tx.clone(),
                 &propose_params,
+                &deposit_params,
                 &fee_params,
                 current_block_height,
                 true,
@@ -320,6 +357,18 @@ fn integration_test() -> Result<()> {
 
         th.assert_trees(&HOLDERS);
 
+        // The proposal event holds the proposal encrypted to the DAO
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.len() == 1);
+        let DaoEvent::Propose { proposal_bulla, note } = &events[0] else {
+            panic!("Expected a Dao::Propose event")
+        };
+        let propose_note = decrypt_propose_note(note, &dao_keypair.secret)?;
+        assert!(*proposal_bulla == propose_params.proposal_bulla);
+        assert!(propose_note.proposal.to_bulla() == propose_params.proposal_bulla);
+        assert!(propose_note.memo == Some(memo));
+        assert!(verify_proposal_description(&propose_note.memo.unwrap(), description));
+
         current_block_height += 1;
 
         // =====================================
@@ -333,6 +382,7 @@ fn integration_test() -> Result<()> {
             .dao_vote(
                 &Holder::Alice,
                 true,
+                false,
                 &dao,
                 &dao_keypair,
                 &propose_info,
@@ -346,6 +396,7 @@ fn integration_test() -> Result<()> {
             .dao_vote(
                 &Holder::Bob,
                 false,
+                false,
                 &dao,
                 &dao_keypair,
                 &propose_info,
@@ -359,6 +410,7 @@ fn integration_test() -> Result<()> {
             .dao_vote(
                 &Holder::Charlie,
                 true,
+                false,
                 &dao,
                 &dao_keypair,
                 &propose_info,
@@ -372,7 +424,6 @@ fn integration_test() -> Result<()> {
             th.execute_dao_vote_tx(
                 holder,
                 alice_vote_tx.clone(),
-                &alice_vote_params,
                 &alice_vote_fee_params,
                 current_block_height,
                 true,
@@ -383,7 +434,6 @@ fn integration_test() -> Result<()> {
             th.execute_dao_vote_tx(
                 holder,
                 bob_vote_tx.clone(),
-                &bob_vote_params,
                 &bob_vote_fee_params,
                 current_block_height,
                 true,
@@ -394,7 +444,6 @@ fn integration_test() -> Result<()> {
             th.execute_dao_vote_tx(
                 holder,
                 charlie_vote_tx.clone(),
-                &charlie_vote_params,
                 &charlie_vote_fee_params,
                 current_block_height,
                 true,
@@ -402,6 +451,16 @@ fn integration_test() -> Result<()> {
             .await?;
         }
 
+        // DAO members can follow voting progress from the vote events,
+        // whose notes are encrypted to the DAO.
+        let mut event_vote_notes = vec![];
+        for event in th.dao_events(&Holder::Dao, current_block_height)? {
+            let DaoEvent::Vote { proposal_bulla, note } = event else { continue };
+            assert!(proposal_bulla == propose_params.proposal_bulla);
+            event_vote_notes.push(note.decrypt_unsafe(&dao_keypair.secret).unwrap());
+        }
+        assert!(event_vote_notes.len() == 3);
+
         // Gather and decrypt all vote notes
         let vote_note_1 = alice_vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap();
         let vote_note_2 = bob_vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap();
@@ -409,9 +468,11 @@ fn integration_test() -> Result<()> {
 
         // Count the votes
         let mut total_yes_vote_value = 0;
+        let mut total_abstain_vote_value = 0;
         let mut total_all_vote_value = 0;
         let mut blind_total_vote = DaoBlindAggregateVote::default();
         let mut total_yes_vote_blind = Blind::ZERO;
+        let mut total_abstain_vote_blind = Blind::ZERO;
         let mut total_all_vote_blind = Blind::ZERO;
 
         for (i, (note, params)) in [
@@ -427,26 +488,36 @@ fn integration_test() -> Result<()> {
             //   yes_vote_blind,
             //   all_vote_value_fp,
             //   all_vote_blind,
+            //   abstain,
+            //   abstain_vote_blind,
             // ]
             let vote_option = fp_to_u64(note[0]).unwrap();
             let yes_vote_blind = Blind(fp_mod_fv(note[1]));
             let all_vote_value = fp_to_u64(note[2]).unwrap();
             let all_vote_blind = Blind(fp_mod_fv(note[3]));
+            let abstain = fp_to_u64(note[4]).unwrap();
+            let abstain_vote_blind = Blind(fp_mod_fv(note[5]));
             assert!(vote_option == 0 || vote_option == 1);
+            assert_eq!(abstain, 0);
 
             total_yes_vote_blind += yes_vote_blind;
+            total_abstain_vote_blind += abstain_vote_blind;
             total_all_vote_blind += all_vote_blind;
 
             // Update private values
             // vote_option is either 0 or 1
             let yes_vote_value = vote_option * all_vote_value;
             total_yes_vote_value += yes_vote_value;
+            total_abstain_vote_value += abstain * all_vote_value;
             total_all_vote_value += all_vote_value;
 
             // Update public values
-            let yes_vote_commit = params.yes_vote_commit;
-            let all_vote_commit = params.inputs.iter().map(|i| i.vote_commit).sum();
-            let blind_vote = DaoBlindAggregateVote { yes_vote_commit, all_vote_commit };
+            let blind_vote = DaoBlindAggregateVote {
+                yes_vote_commit: params.yes_vote_commit,
+                abstain_vote_commit: params.abstain_vote_commit,
+                all_vote_commit: params.inputs.iter().map(|i| i.vote_commit).sum(),
+                ..Default::default()
+            };
             blind_total_vote.aggregate(blind_vote);
 
             // Just for the debug
@@ -469,6 +540,11 @@ fn integration_test() -> Result<()> {
                 pedersen_commitment_u64(total_yes_vote_value, total_yes_vote_blind)
         );
 
+        assert!(
+            blind_total_vote.abstain_vote_commit ==
+                pedersen_commitment_u64(total_abstain_vote_value, total_abstain_vote_blind)
+        );
+
         current_block_height += 1;
 
         // ================
@@ -486,8 +562,10 @@ fn integration_test() -> Result<()> {
                 &propose_info,
                 proposal_coinattrs,
                 total_yes_vote_value,
+                total_abstain_vote_value,
                 total_all_vote_value,
                 total_yes_vote_blind,
+                total_abstain_vote_blind,
                 total_all_vote_blind,
                 current_block_height,
             )
@@ -510,13 +588,1239 @@ fn integration_test() -> Result<()> {
         th.assert_trees(&HOLDERS);
 
         let rachel_wallet = th.holders.get(&Holder::Rachel).unwrap();
-        assert!(rachel_wallet.unspent_money_coins[0].note.value == PROPOSAL_AMOUNT);
+        assert!(rachel_wallet.unspent_money_coins[0].note.value == RACHEL_PROPOSAL_AMOUNT);
         assert!(rachel_wallet.unspent_money_coins[0].note.token_id == drk_token_id);
 
+        let charlie_drk_coin = th
+            .holders
+            .get(&Holder::Charlie)
+            .unwrap()
+            .unspent_money_coins
+            .iter()
+            .find(|x| x.note.token_id == drk_token_id)
+            .unwrap();
+        assert!(charlie_drk_coin.note.value == CHARLIE_PROPOSAL_AMOUNT);
+
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DaoEvent::Exec { proposal_bulla } if *proposal_bulla == propose_params.proposal_bulla
+        )));
+
         let dao_wallet = th.holders.get(&Holder::Dao).unwrap();
         assert!(dao_wallet.unspent_money_coins[0].note.value == DRK_TOKEN_SUPPLY - PROPOSAL_AMOUNT);
         assert!(dao_wallet.unspent_money_coins[0].note.token_id == drk_token_id);
 
+        current_block_height += 1;
+
+        // ==========================================
+        // Dao::Amend
+        // Vote on and execute a DAO parameter change
+        // ==========================================
+        info!("Stage 7. Amend the DAO parameters");
+
+        let new_dao = Dao {
+            quorum: 200_000_000,
+            approval_ratio_base: 3,
+            approval_ratio_quot: 2,
+            bulla_blind: Blind::random(&mut OsRng),
+            ..dao.clone()
+        };
+
+        info!("[Alice] Building DAO amend proposal tx");
+        let (propose_tx, (propose_params, deposit_params, fee_params), propose_info) = th
+            .dao_propose_auth_calls(
+                &Holder::Alice,
+                vec![make_amend_auth_call(&new_dao)],
+                pallas::Base::ZERO,
+                &dao,
+                &dao_mint_params.dao_bulla,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing DAO amend proposal tx");
+            th.execute_dao_propose_tx(
+                holder,
+                propose_tx.clone(),
+                &propose_params,
+                &deposit_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        // Alice and Bob vote yes, while Charlie abstains. Charlie's tokens
+        // still count towards quorum.
+        let mut total_yes_vote_value = 0;
+        let mut total_abstain_vote_value = 0;
+        let mut total_all_vote_value = 0;
+        let mut total_yes_vote_blind = Blind::ZERO;
+        let mut total_abstain_vote_blind = Blind::ZERO;
+        let mut total_all_vote_blind = Blind::ZERO;
+        for (voter, abstain) in
+            [(Holder::Alice, false), (Holder::Bob, false), (Holder::Charlie, true)]
+        {
+            info!("[{voter:?}] Building amend vote tx (abstain: {abstain})");
+            let (vote_tx, vote_params, vote_fee_params) = th
+                .dao_vote(
+                    &voter,
+                    !abstain,
+                    abstain,
+                    &dao,
+                    &dao_keypair,
+                    &propose_info,
+                    &propose_params.proposal_bulla,
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing {voter:?} amend vote tx");
+                th.execute_dao_vote_tx(
+                    holder,
+                    vote_tx.clone(),
+                    &vote_fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            let note = vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap();
+            let all_vote_value = fp_to_u64(note[2]).unwrap();
+            assert_eq!(fp_to_u64(note[0]).unwrap(), !abstain as u64);
+            assert_eq!(fp_to_u64(note[4]).unwrap(), abstain as u64);
+            total_yes_vote_blind += Blind(fp_mod_fv(note[1]));
+            total_all_vote_blind += Blind(fp_mod_fv(note[3]));
+            total_abstain_vote_blind += Blind(fp_mod_fv(note[5]));
+            total_all_vote_value += all_vote_value;
+            match abstain {
+                true => total_abstain_vote_value += all_vote_value,
+                false => total_yes_vote_value += all_vote_value,
+            }
+        }
+
+        current_block_height += 1;
+
+        info!("[Dao] Building Dao::Amend tx");
+        let (amend_tx, amend_params, _exec_params, amend_fee_params) = th
+            .dao_amend(
+                &Holder::Alice,
+                &dao,
+                &new_dao,
+                &propose_info,
+                total_yes_vote_value,
+                total_abstain_vote_value,
+                total_all_vote_value,
+                total_yes_vote_blind,
+                total_abstain_vote_blind,
+                total_all_vote_blind,
+                current_block_height,
+            )
+            .await?;
+
+        assert_eq!(amend_params.old_dao_bulla, dao_mint_params.dao_bulla);
+        assert_eq!(amend_params.new_dao_bulla, new_dao.to_bulla());
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing Dao::Amend tx");
+            th.execute_dao_amend_tx(
+                holder,
+                amend_tx.clone(),
+                &amend_params,
+                &amend_fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DaoEvent::Amend { old_dao_bulla, new_dao_bulla }
+                if *old_dao_bulla == amend_params.old_dao_bulla &&
+                    *new_dao_bulla == amend_params.new_dao_bulla
+        )));
+
+        // The amended DAO is now in the DAO tree, so proposals can be made for it
+        let dao_wallet = th.holders.get(&Holder::Dao).unwrap();
+        assert!(dao_wallet.dao_leafs.contains_key(&new_dao.to_bulla()));
+
+        // The old DAO is superseded by its successor, so it can't make
+        // new proposals even though its bulla is still in the DAO tree
+        info!("[Alice] Checking Dao::Propose tx fails for the amended DAO");
+        let (propose_tx, (propose_params, deposit_params, fee_params), _propose_info) = th
+            .dao_propose_auth_calls(
+                &Holder::Alice,
+                vec![make_amend_auth_call(&new_dao)],
+                pallas::Base::ZERO,
+                &dao,
+                &dao_mint_params.dao_bulla,
+                current_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_dao_propose_tx(
+                &Holder::Alice,
+                propose_tx,
+                &propose_params,
+                &deposit_params,
+                &fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        current_block_height += 1;
+
+        // ==========================================
+        // Dao::VoteMulti
+        // Vote on a multiple-choice proposal and execute the winning option
+        // ==========================================
+        info!("Stage 8. Multiple-choice proposal");
+
+        // The winning option also appoints a guardian for the DAO
+        let guardian_keypair = Keypair::random(&mut OsRng);
+        let option_daos = [
+            Dao {
+                proposer_limit: 50_000_000,
+                bulla_blind: Blind::random(&mut OsRng),
+                ..new_dao.clone()
+            },
+            Dao {
+                quorum: 199_999_999,
+                guardian_public_key: Some(guardian_keypair.public),
+                bulla_blind: Blind::random(&mut OsRng),
+                ..new_dao.clone()
+            },
+        ];
+
+        info!("[Alice] Building multiple-choice proposal tx");
+        let (propose_tx, (propose_params, deposit_params, fee_params), propose_info) = th
+            .dao_propose_options(
+                &Holder::Alice,
+                option_daos
+                    .iter()
+                    .map(|option_dao| vec![make_amend_auth_call(option_dao)])
+                    .collect(),
+                pallas::Base::ZERO,
+                &new_dao,
+                &new_dao.to_bulla(),
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing multiple-choice proposal tx");
+            th.execute_dao_propose_tx(
+                holder,
+                propose_tx.clone(),
+                &propose_params,
+                &deposit_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        // Alice votes for the first option, while Bob and Charlie vote for the second
+        let mut total_option_vote_values = [0; PROPOSAL_MAX_OPTIONS];
+        let mut total_all_vote_value = 0;
+        let mut total_option_vote_blinds = [Blind::ZERO; PROPOSAL_MAX_OPTIONS];
+        let mut total_all_vote_blind = Blind::ZERO;
+        for (voter, option) in [(Holder::Alice, 0), (Holder::Bob, 1), (Holder::Charlie, 1)] {
+            info!("[{voter:?}] Building multiple-choice vote tx (option: {option})");
+            let (vote_tx, vote_params, vote_fee_params) = th
+                .dao_vote_multi(
+                    &voter,
+                    Some(option),
+                    &new_dao,
+                    &dao_keypair,
+                    &propose_info,
+                    &propose_params.proposal_bulla,
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing {voter:?} multiple-choice vote tx");
+                th.execute_dao_vote_tx(
+                    holder,
+                    vote_tx.clone(),
+                    &vote_fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            // The note holds the option index plus one, the option blinds,
+            // and the total vote value and blind.
+            let note = vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap();
+            assert_eq!(fp_to_u64(note[0]).unwrap(), option as u64 + 1);
+            let all_vote_value = fp_to_u64(note[PROPOSAL_MAX_OPTIONS + 1]).unwrap();
+            for (i, blind) in total_option_vote_blinds.iter_mut().enumerate() {
+                *blind += Blind(fp_mod_fv(note[i + 1]));
+            }
+            total_all_vote_blind += Blind(fp_mod_fv(note[PROPOSAL_MAX_OPTIONS + 2]));
+            total_option_vote_values[option] += all_vote_value;
+            total_all_vote_value += all_vote_value;
+        }
+
+        current_block_height += 1;
+
+        info!("[Dao] Building multiple-choice Dao::Amend tx");
+        let (amend_tx, amend_params, exec_params, amend_fee_params) = th
+            .dao_amend_multi(
+                &Holder::Alice,
+                &new_dao,
+                &option_daos[1],
+                &propose_info,
+                total_option_vote_values,
+                total_all_vote_value,
+                total_option_vote_blinds,
+                total_all_vote_blind,
+                current_block_height,
+            )
+            .await?;
+
+        // The second option won, so its auth calls are executed
+        assert!(exec_params.multi_choice);
+        assert_eq!(exec_params.proposal_auth_calls.commit(), propose_info.options[1].commit());
+        assert_eq!(amend_params.new_dao_bulla, option_daos[1].to_bulla());
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing multiple-choice Dao::Amend tx");
+            th.execute_dao_amend_tx(
+                holder,
+                amend_tx.clone(),
+                &amend_params,
+                &amend_fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let dao_wallet = th.holders.get(&Holder::Dao).unwrap();
+        assert!(dao_wallet.dao_leafs.contains_key(&option_daos[1].to_bulla()));
+
+        current_block_height += 1;
+
+        // ==========================================
+        // Dao::Cancel
+        // Cancel a proposal before voting ends
+        // ==========================================
+        info!("Stage 9. Cancel proposal");
+
+        let amended_dao = Dao { bulla_blind: Blind::random(&mut OsRng), ..option_daos[1].clone() };
+
+        info!("[Alice] Building proposal tx");
+        let (propose_tx, (propose_params, deposit_params, fee_params), propose_info) = th
+            .dao_propose_auth_calls(
+                &Holder::Alice,
+                vec![make_amend_auth_call(&amended_dao)],
+                pallas::Base::ZERO,
+                &option_daos[1],
+                &option_daos[1].to_bulla(),
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing proposal tx");
+            th.execute_dao_propose_tx(
+                holder,
+                propose_tx.clone(),
+                &propose_params,
+                &deposit_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        // DAO members know the proposal blind, but only the proposer
+        // can sign the cancel
+        info!("[Bob] Checking Dao::Cancel tx fails for Alice's proposal");
+        let (cancel_tx, _, cancel_fee_params) =
+            th.dao_cancel(&Holder::Bob, &propose_info, current_block_height).await?;
+        assert!(th
+            .execute_dao_cancel_tx(
+                &Holder::Bob,
+                cancel_tx,
+                &cancel_fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        info!("[Alice] Building Dao::Cancel tx");
+        let (cancel_tx, cancel_params, cancel_fee_params) =
+            th.dao_cancel(&Holder::Alice, &propose_info, current_block_height).await?;
+
+        assert_eq!(cancel_params.proposal_bulla, propose_params.proposal_bulla);
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing Dao::Cancel tx");
+            th.execute_dao_cancel_tx(
+                holder,
+                cancel_tx.clone(),
+                &cancel_fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DaoEvent::Cancel { proposal_bulla } if *proposal_bulla == cancel_params.proposal_bulla
+        )));
+
+        current_block_height += 1;
+
+        // The proposal is gone, so it can't be cancelled again or voted on
+        info!("[Alice] Checking Dao::Cancel tx fails for a cancelled proposal");
+        let (cancel_tx, _, cancel_fee_params) =
+            th.dao_cancel(&Holder::Alice, &propose_info, current_block_height).await?;
+        assert!(th
+            .execute_dao_cancel_tx(
+                &Holder::Alice,
+                cancel_tx,
+                &cancel_fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        info!("[Bob] Checking vote tx fails for a cancelled proposal");
+        let (vote_tx, _, vote_fee_params) = th
+            .dao_vote(
+                &Holder::Bob,
+                true,
+                false,
+                &option_daos[1],
+                &dao_keypair,
+                &propose_info,
+                &propose_params.proposal_bulla,
+                current_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_dao_vote_tx(
+                &Holder::Bob,
+                vote_tx,
+                &vote_fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        current_block_height += 1;
+
+        // ==========================================
+        // Dao::Veto
+        // The DAO guardian vetoes a passed proposal
+        // ==========================================
+        info!("Stage 10. Guardian veto");
+
+        let amended_dao = Dao { bulla_blind: Blind::random(&mut OsRng), ..option_daos[1].clone() };
+
+        info!("[Alice] Building proposal tx");
+        let (propose_tx, (propose_params, deposit_params, fee_params), propose_info) = th
+            .dao_propose_auth_calls(
+                &Holder::Alice,
+                vec![make_amend_auth_call(&amended_dao)],
+                pallas::Base::ZERO,
+                &option_daos[1],
+                &option_daos[1].to_bulla(),
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing proposal tx");
+            th.execute_dao_propose_tx(
+                holder,
+                propose_tx.clone(),
+                &propose_params,
+                &deposit_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        // Everyone votes yes
+        let mut total_yes_vote_value = 0;
+        let mut total_yes_vote_blind = Blind::ZERO;
+        let mut total_abstain_vote_blind = Blind::ZERO;
+        let mut total_all_vote_blind = Blind::ZERO;
+        for voter in [Holder::Alice, Holder::Bob, Holder::Charlie] {
+            info!("[{voter:?}] Building vote tx");
+            let (vote_tx, vote_params, vote_fee_params) = th
+                .dao_vote(
+                    &voter,
+                    true,
+                    false,
+                    &option_daos[1],
+                    &dao_keypair,
+                    &propose_info,
+                    &propose_params.proposal_bulla,
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing {voter:?} vote tx");
+                th.execute_dao_vote_tx(
+                    holder,
+                    vote_tx.clone(),
+                    &vote_fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            let note = vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap();
+            total_yes_vote_value += fp_to_u64(note[2]).unwrap();
+            total_yes_vote_blind += Blind(fp_mod_fv(note[1]));
+            total_all_vote_blind += Blind(fp_mod_fv(note[3]));
+            total_abstain_vote_blind += Blind(fp_mod_fv(note[5]));
+        }
+
+        current_block_height += 1;
+
+        info!("[Alice] Building Dao::Veto tx");
+        let (veto_tx, veto_params, veto_fee_params) = th
+            .dao_veto(
+                &Holder::Alice,
+                &option_daos[1],
+                &guardian_keypair.secret,
+                &propose_info,
+                current_block_height,
+            )
+            .await?;
+
+        assert_eq!(veto_params.proposal_bulla, propose_params.proposal_bulla);
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing Dao::Veto tx");
+            th.execute_dao_veto_tx(
+                holder,
+                veto_tx.clone(),
+                &veto_fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DaoEvent::Veto { proposal_bulla } if *proposal_bulla == veto_params.proposal_bulla
+        )));
+
+        current_block_height += 1;
+
+        info!("[Alice] Checking Dao::Veto tx fails for a vetoed proposal");
+        let (veto_tx, _, veto_fee_params) = th
+            .dao_veto(
+                &Holder::Alice,
+                &option_daos[1],
+                &guardian_keypair.secret,
+                &propose_info,
+                current_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_dao_veto_tx(
+                &Holder::Alice,
+                veto_tx,
+                &veto_fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        // Even once the grace period ends, the vetoed proposal can't be executed
+        info!("[Alice] Checking Dao::Amend tx fails for a vetoed proposal");
+        let exec_block_height = propose_info.creation_height +
+            propose_info.duration_blocks +
+            PROPOSAL_VETO_GRACE_BLOCKS;
+        let (amend_tx, amend_params, _exec_params, amend_fee_params) = th
+            .dao_amend(
+                &Holder::Alice,
+                &option_daos[1],
+                &amended_dao,
+                &propose_info,
+                total_yes_vote_value,
+                0,
+                total_yes_vote_value,
+                total_yes_vote_blind,
+                total_abstain_vote_blind,
+                total_all_vote_blind,
+                exec_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_dao_amend_tx(
+                &Holder::Alice,
+                amend_tx,
+                &amend_params,
+                &amend_fee_params,
+                exec_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        current_block_height += 1;
+
+        // ==========================================
+        // Proposal deposit
+        // Proposing to a DAO with a deposit bonds governance tokens,
+        // which get slashed if the proposal is overwhelmingly rejected
+        // ==========================================
+        info!("Stage 11. Proposal deposit");
+
+        const PROPOSAL_DEPOSIT: u64 = 10_000_000;
+        let deposit_dao = Dao {
+            proposal_deposit: PROPOSAL_DEPOSIT,
+            bulla_blind: Blind::random(&mut OsRng),
+            ..dao.clone()
+        };
+
+        info!("[Alice] Building DAO mint tx for the deposit DAO");
+        let (dao_mint_tx, dao_mint_params, fee_params) =
+            th.dao_mint(&Holder::Alice, &deposit_dao, &dao_keypair, current_block_height).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing DAO Mint tx");
+            th.execute_dao_mint_tx(
+                holder,
+                dao_mint_tx.clone(),
+                &dao_mint_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // Alice needs spare governance tokens to bond, besides the ones she proposes with
+        info!("[Alice] Building governance token mint tx for the deposit");
+        let (token_mint_tx, token_mint_params, auth_token_mint_params, fee_params) = th
+            .token_mint(
+                PROPOSAL_DEPOSIT,
+                &Holder::Alice,
+                &Holder::Alice,
+                gov_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing governance token mint tx for Alice");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &auth_token_mint_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        let new_dao = Dao { bulla_blind: Blind::random(&mut OsRng), ..deposit_dao.clone() };
+
+        info!("[Alice] Building proposal tx bonding a deposit");
+        let (propose_tx, (propose_params, deposit_params, fee_params), deposit_propose_info) = th
+            .dao_propose_auth_calls(
+                &Holder::Alice,
+                vec![make_amend_auth_call(&new_dao)],
+                pallas::Base::ZERO,
+                &deposit_dao,
+                &deposit_dao.to_bulla(),
+                current_block_height,
+            )
+            .await?;
+
+        assert!(deposit_params.is_some());
+        assert!(propose_params.deposit_coin.is_some());
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing proposal tx");
+            th.execute_dao_propose_tx(
+                holder,
+                propose_tx.clone(),
+                &propose_params,
+                &deposit_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // The DAO wallet now holds the bonded deposit
+        let deposit_spend_hook =
+            FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::SettleDeposit as u8 }
+                .to_func_id();
+        let dao_wallet = th.holders.get(&Holder::Dao).unwrap();
+        assert!(dao_wallet
+            .unspent_money_coins
+            .iter()
+            .any(|x| x.note.spend_hook == deposit_spend_hook && x.note.value == PROPOSAL_DEPOSIT));
+
+        current_block_height += 1;
+
+        // Everyone votes no
+        let mut deposit_all_vote_value = 0;
+        let mut deposit_yes_vote_blind = Blind::ZERO;
+        let mut deposit_abstain_vote_blind = Blind::ZERO;
+        let mut deposit_all_vote_blind = Blind::ZERO;
+        for voter in [Holder::Alice, Holder::Bob, Holder::Charlie] {
+            info!("[{voter:?}] Building vote tx");
+            let (vote_tx, vote_params, vote_fee_params) = th
+                .dao_vote(
+                    &voter,
+                    false,
+                    false,
+                    &deposit_dao,
+                    &dao_keypair,
+                    &deposit_propose_info,
+                    &propose_params.proposal_bulla,
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing {voter:?} vote tx");
+                th.execute_dao_vote_tx(
+                    holder,
+                    vote_tx.clone(),
+                    &vote_fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            let note = vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap();
+            deposit_all_vote_value += fp_to_u64(note[2]).unwrap();
+            deposit_yes_vote_blind += Blind(fp_mod_fv(note[1]));
+            deposit_all_vote_blind += Blind(fp_mod_fv(note[3]));
+            deposit_abstain_vote_blind += Blind(fp_mod_fv(note[5]));
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        // Cancelling the rejected proposal doesn't dodge the slashing,
+        // since its deposit is settled against the cancelled tally
+        info!("[Alice] Building Dao::Cancel tx for the deposit proposal");
+        let (cancel_tx, _, cancel_fee_params) =
+            th.dao_cancel(&Holder::Alice, &deposit_propose_info, current_block_height).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing Dao::Cancel tx");
+            th.execute_dao_cancel_tx(
+                holder,
+                cancel_tx.clone(),
+                &cancel_fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        // ==========================================
+        // Dao::RevealTally
+        // Vote on a sealed proposal, whose tally is only revealed once
+        // voting ended
+        // ==========================================
+        info!("Stage 12. Sealed proposal");
+
+        let dao = option_daos[1].clone();
+        let new_dao = Dao { bulla_blind: Blind::random(&mut OsRng), ..dao.clone() };
+
+        // The tally key is shared by 2-of-3 trustees, so no single one of
+        // them can decrypt votes before voting ends
+        let (tally_shares, tally_pubkeys) =
+            split_secret(&SecretKey::random(&mut OsRng), 2, 3, &mut OsRng).unwrap();
+        let tally_keys: Vec<KeyPackage> =
+            tally_shares.iter().map(|share| share.verify().unwrap()).collect();
+        let tally_public_key = tally_pubkeys.group_public();
+
+        info!("[Alice] Building sealed proposal tx");
+        let (propose_tx, (propose_params, deposit_params, fee_params), propose_info) = th
+            .dao_propose_sealed(
+                &Holder::Alice,
+                vec![make_amend_auth_call(&new_dao)],
+                tally_public_key,
+                pallas::Base::ZERO,
+                &dao,
+                &dao.to_bulla(),
+                current_block_height,
+            )
+            .await?;
+
+        assert_eq!(propose_params.tally_public_key, Some(tally_public_key));
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing sealed proposal tx");
+            th.execute_dao_propose_tx(
+                holder,
+                propose_tx.clone(),
+                &propose_params,
+                &deposit_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        current_block_height += 1;
+
+        // Alice and Bob vote yes, Charlie votes no
+        let mut total_yes_vote_value = 0;
+        let mut total_all_vote_value = 0;
+        let mut total_yes_vote_blind = Blind::ZERO;
+        let mut total_abstain_vote_blind = Blind::ZERO;
+        let mut total_all_vote_blind = Blind::ZERO;
+        let mut vote_notes = vec![];
+        for (voter, vote_option) in
+            [(Holder::Alice, true), (Holder::Bob, true), (Holder::Charlie, false)]
+        {
+            info!("[{voter:?}] Building sealed vote tx (vote_option: {vote_option})");
+            let (vote_tx, vote_params, vote_fee_params) = th
+                .dao_vote_sealed(
+                    &voter,
+                    vote_option,
+                    &dao,
+                    &dao_keypair,
+                    tally_public_key,
+                    &propose_info,
+                    &propose_params.proposal_bulla,
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing {voter:?} sealed vote tx");
+                th.execute_dao_vote_tx(
+                    holder,
+                    vote_tx.clone(),
+                    &vote_fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            // The DAO key can't decrypt the vote
+            assert!(fp_to_u64(vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap()[0])
+                .is_none());
+            vote_notes.push((vote_option, vote_params.note));
+        }
+
+        // Wait for voting and the guardian veto grace period to end
+        current_block_height = propose_info.creation_height +
+            propose_info.duration_blocks +
+            PROPOSAL_VETO_GRACE_BLOCKS;
+
+        // Two trustees publish their decryption shares once voting ended
+        for (vote_option, vote_note) in vote_notes {
+            let dec_shares: Vec<_> = tally_keys[1..]
+                .iter()
+                .map(|key| decryption_share(key, &vote_note.ephem_public, &mut OsRng))
+                .collect();
+
+            // A single trustee can't decrypt the vote
+            let shared_point = combine_decryption_shares(
+                &vote_note.ephem_public,
+                &dec_shares[..1],
+                &tally_pubkeys,
+            );
+            assert!(shared_point.is_err());
+
+            let shared_point =
+                combine_decryption_shares(&vote_note.ephem_public, &dec_shares, &tally_pubkeys)
+                    .unwrap();
+            let note = vote_note.decrypt_unsafe_shared(&shared_point);
+            let all_vote_value = fp_to_u64(note[2]).unwrap();
+            assert_eq!(fp_to_u64(note[0]).unwrap(), vote_option as u64);
+            total_yes_vote_blind += Blind(fp_mod_fv(note[1]));
+            total_all_vote_blind += Blind(fp_mod_fv(note[3]));
+            total_abstain_vote_blind += Blind(fp_mod_fv(note[5]));
+            total_all_vote_value += all_vote_value;
+            if vote_option {
+                total_yes_vote_value += all_vote_value;
+            }
+        }
+
+        // The proposal can't be executed before its tally is revealed
+        info!("[Dao] Building sealed Dao::Amend tx");
+        let (amend_tx, amend_params, _exec_params, amend_fee_params) = th
+            .dao_amend(
+                &Holder::Alice,
+                &dao,
+                &new_dao,
+                &propose_info,
+                total_yes_vote_value,
+                0,
+                total_all_vote_value,
+                total_yes_vote_blind,
+                total_abstain_vote_blind,
+                total_all_vote_blind,
+                current_block_height,
+            )
+            .await?;
+
+        info!("[Alice] Checking sealed Dao::Amend tx fails before the reveal");
+        assert!(th
+            .execute_dao_amend_tx(
+                &Holder::Alice,
+                amend_tx.clone(),
+                &amend_params,
+                &amend_fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        info!("[Alice] Building Dao::RevealTally tx");
+        let (reveal_tx, reveal_params, reveal_fee_params) = th
+            .dao_reveal_tally(
+                &Holder::Alice,
+                &propose_info,
+                total_yes_vote_value,
+                0,
+                total_all_vote_value,
+                total_yes_vote_blind,
+                total_abstain_vote_blind,
+                total_all_vote_blind,
+                current_block_height,
+            )
+            .await?;
+
+        assert_eq!(reveal_params.yes_vote_value, 200_000_000);
+        assert_eq!(reveal_params.all_vote_value, 300_000_000);
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing Dao::RevealTally tx");
+            th.execute_dao_reveal_tally_tx(
+                holder,
+                reveal_tx.clone(),
+                &reveal_fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // The revealed tally is public, so it's recorded in the clear
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DaoEvent::RevealTally { proposal_bulla, yes_vote_value, all_vote_value, .. }
+                if *proposal_bulla == reveal_params.proposal_bulla &&
+                    *yes_vote_value == reveal_params.yes_vote_value &&
+                    *all_vote_value == reveal_params.all_vote_value
+        )));
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing sealed Dao::Amend tx");
+            th.execute_dao_amend_tx(
+                holder,
+                amend_tx.clone(),
+                &amend_params,
+                &amend_fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let dao_wallet = th.holders.get(&Holder::Dao).unwrap();
+        assert!(dao_wallet.dao_leafs.contains_key(&new_dao.to_bulla()));
+
+        current_block_height += 1;
+
+        // ==========================================
+        // Dao::SettleDeposit
+        // Voting on the cancelled deposit proposal ended, everyone voted
+        // no so the bonded deposit gets slashed into the DAO treasury
+        // ==========================================
+        info!("Stage 13. Settle proposal deposit");
+
+        assert!(
+            current_block_height >=
+                deposit_propose_info.creation_height + deposit_propose_info.duration_blocks
+        );
+
+        let alice_public_key = th.holders.get(&Holder::Alice).unwrap().keypair.public;
+
+        // Lying about the tally to claim a refund fails, since the vote
+        // commitments don't match the ones aggregated on-chain
+        info!("[Alice] Checking Dao::SettleDeposit tx fails for a forged tally");
+        let (settle_tx, settle_xfer_params, _, settle_fee_params) = th
+            .dao_settle_deposit(
+                &Holder::Alice,
+                &deposit_dao,
+                &deposit_propose_info,
+                alice_public_key,
+                deposit_all_vote_value,
+                0,
+                deposit_all_vote_value,
+                deposit_yes_vote_blind,
+                deposit_abstain_vote_blind,
+                deposit_all_vote_blind,
+                current_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_dao_settle_deposit_tx(
+                &Holder::Alice,
+                settle_tx,
+                &settle_xfer_params,
+                &settle_fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        info!("[Alice] Building Dao::SettleDeposit tx");
+        let (settle_tx, settle_xfer_params, settle_params, settle_fee_params) = th
+            .dao_settle_deposit(
+                &Holder::Alice,
+                &deposit_dao,
+                &deposit_propose_info,
+                alice_public_key,
+                0,
+                0,
+                deposit_all_vote_value,
+                deposit_yes_vote_blind,
+                deposit_abstain_vote_blind,
+                deposit_all_vote_blind,
+                current_block_height,
+            )
+            .await?;
+
+        assert!(settle_params.slashed);
+
+        let mut slashed_coins = vec![];
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing Dao::SettleDeposit tx");
+            let found_coins = th
+                .execute_dao_settle_deposit_tx(
+                    holder,
+                    settle_tx.clone(),
+                    &settle_xfer_params,
+                    &settle_fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            if holder == &Holder::Dao {
+                slashed_coins = found_coins;
+            }
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let events = th.dao_events(&Holder::Dao, current_block_height)?;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DaoEvent::SettleDeposit { proposal_bulla, slashed: true }
+                if *proposal_bulla == settle_params.proposal_bulla
+        )));
+
+        // The deposit was paid into the DAO treasury
+        let exec_spend_hook =
+            FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::Exec as u8 }
+                .to_func_id();
+        assert!(slashed_coins.len() == 1);
+        assert!(slashed_coins[0].note.value == PROPOSAL_DEPOSIT);
+        assert!(slashed_coins[0].note.token_id == gov_token_id);
+        assert!(slashed_coins[0].note.spend_hook == exec_spend_hook);
+        assert!(slashed_coins[0].note.user_data == deposit_dao.to_bulla().inner());
+
+        current_block_height += 1;
+
+        // ==========================================
+        // Vote weights
+        // DAOs can weigh votes by the square root of the voter's balance,
+        // or cap it, instead of counting one vote per token
+        // ==========================================
+        info!("Stage 14. Vote weights");
+
+        for vote_weight in [DaoVoteWeight::SquareRoot, DaoVoteWeight::Capped(40_000_000)] {
+            let weighted_dao =
+                Dao { vote_weight, bulla_blind: Blind::random(&mut OsRng), ..dao.clone() };
+
+            info!("[Alice] Building DAO mint tx for a {vote_weight:?} DAO");
+            let (dao_mint_tx, dao_mint_params, fee_params) = th
+                .dao_mint(&Holder::Alice, &weighted_dao, &dao_keypair, current_block_height)
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing DAO Mint tx");
+                th.execute_dao_mint_tx(
+                    holder,
+                    dao_mint_tx.clone(),
+                    &dao_mint_params,
+                    &fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            th.assert_trees(&HOLDERS);
+
+            current_block_height += 1;
+
+            let new_dao = Dao { bulla_blind: Blind::random(&mut OsRng), ..weighted_dao.clone() };
+
+            info!("[Alice] Building proposal tx");
+            let (propose_tx, (propose_params, deposit_params, fee_params), propose_info) = th
+                .dao_propose_auth_calls(
+                    &Holder::Alice,
+                    vec![make_amend_auth_call(&new_dao)],
+                    pallas::Base::ZERO,
+                    &weighted_dao,
+                    &weighted_dao.to_bulla(),
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing proposal tx");
+                th.execute_dao_propose_tx(
+                    holder,
+                    propose_tx.clone(),
+                    &propose_params,
+                    &deposit_params,
+                    &fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            th.assert_trees(&HOLDERS);
+
+            current_block_height += 1;
+
+            info!("[Alice] Building vote tx");
+            let (vote_tx, vote_params, vote_fee_params) = th
+                .dao_vote(
+                    &Holder::Alice,
+                    true,
+                    false,
+                    &weighted_dao,
+                    &dao_keypair,
+                    &propose_info,
+                    &propose_params.proposal_bulla,
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing Alice vote tx");
+                th.execute_dao_vote_tx(
+                    holder,
+                    vote_tx.clone(),
+                    &vote_fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+
+            th.assert_trees(&HOLDERS);
+
+            // Alice's vote only counts with the weight of her balance
+            let note = vote_params.note.decrypt_unsafe(&dao_keypair.secret).unwrap();
+            let weight = fp_to_u64(note[2]).unwrap();
+            assert_eq!(weight, vote_weight.apply(ALICE_GOV_SUPPLY));
+            assert_eq!(
+                vote_params.all_vote_commit,
+                pedersen_commitment_u64(weight, Blind(fp_mod_fv(note[3])))
+            );
+
+            current_block_height += 1;
+        }
+
         // Thanks for reading
         Ok(())
     })
@@ -25,7 +25,7 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
 use wasmparser::{
     ExternalKind::{Func, Memory},
     Payload::ExportSection,
@@ -40,7 +40,7 @@ pub(crate) fn deploy_get_metadata_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let params: DeployParamsV1 = deserialize(&self_.data.data[1..])?;
+    let params: DeployParamsV1 = deserialize_strict(&self_.data.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -62,7 +62,7 @@ pub(crate) fn deploy_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let params: DeployParamsV1 = deserialize(&self_.data.data[1..])?;
+    let params: DeployParamsV1 = deserialize_strict(&self_.data.data[1..])?;
 
     // In this function, we have to check that the contract isn't locked.
     let lock_db = wasm::db::db_lookup(cid, DEPLOY_CONTRACT_LOCK_TREE)?;
@@ -24,7 +24,7 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
 
 use crate::{
     error::DeployError,
@@ -39,7 +39,7 @@ pub(crate) fn lock_get_metadata_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let params: LockParamsV1 = deserialize(&self_.data.data[1..])?;
+    let params: LockParamsV1 = deserialize_strict(&self_.data.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -61,7 +61,7 @@ pub(crate) fn lock_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let params: LockParamsV1 = deserialize(&self_.data.data[1..])?;
+    let params: LockParamsV1 = deserialize_strict(&self_.data.data[1..])?;
 
     // In this function, we check that the contract exists, and that it isn't
     // already locked.
@@ -22,20 +22,38 @@ use darkfi::{
     Result,
 };
 use darkfi_sdk::{
-    crypto::{note::AeadEncryptedNote, pasta_prelude::*, pedersen_commitment_u64, Blind, Keypair},
+    crypto::{commitment, note::AeadEncryptedNote, pasta_prelude::*, Blind, Keypair, ScalarBlind},
     pasta::pallas,
 };
 use log::info;
 use rand::rngs::OsRng;
 
 use crate::{
-    client::MoneyNote,
+    client::{token_supply_cap_v1::create_token_supply_cap_proof, MoneyNote},
     model::{CoinAttributes, MoneyAuthTokenMintParamsV1, TokenAttributes},
 };
 
 pub struct AuthTokenMintCallDebris {
     pub params: MoneyAuthTokenMintParamsV1,
     pub proofs: Vec<Proof>,
+    /// Blind of the minted value commitment. The mint authority has to
+    /// keep it in order to create `TokenSupplyProof`s.
+    pub value_blind: ScalarBlind,
+}
+
+/// Information needed to prove a mint keeps a capped token's total supply
+/// within its cap.
+pub struct AuthTokenMintSupplyCap {
+    /// Hard cap on the token's total supply
+    pub supply_cap: u64,
+    /// Total supply minted before this mint
+    pub supply: u64,
+    /// Sum of the value blinds of all the mints before this one
+    pub supply_blind: ScalarBlind,
+    /// `TokenSupplyCap_V1` zkas circuit ZkBinary
+    pub supply_cap_zkbin: ZkBinary,
+    /// Proving key for the `TokenSupplyCap_V1` zk circuit
+    pub supply_cap_pk: ProvingKey,
 }
 
 /// Struct holding necessary information to build a `Money::AuthTokenMintV1` contract call.
@@ -50,6 +68,9 @@ pub struct AuthTokenMintCallBuilder {
     pub auth_mint_zkbin: ZkBinary,
     /// Proving key for the `AuthTokenMint_V1` zk circuit,
     pub auth_mint_pk: ProvingKey,
+
+    /// Supply cap information, required if the token's supply is capped
+    pub supply_cap: Option<AuthTokenMintSupplyCap>,
 }
 
 impl AuthTokenMintCallBuilder {
@@ -57,7 +78,7 @@ impl AuthTokenMintCallBuilder {
         info!("Building Money::AuthTokenMintV1 contract call");
 
         let value_blind = Blind::random(&mut OsRng);
-        let value_commit = pedersen_commitment_u64(self.coin_attrs.value, value_blind);
+        let value_commit = commitment::commit(self.coin_attrs.value, value_blind);
 
         // Create the proof
 
@@ -81,20 +102,35 @@ impl AuthTokenMintCallBuilder {
         ];
 
         let mint_pubkey = self.mint_keypair.public;
-        let value_coords = value_commit.to_affine().coordinates().unwrap();
+        let (value_x, value_y) = commitment::xy(&value_commit);
 
         let public_inputs = vec![
             mint_pubkey.x(),
             mint_pubkey.y(),
             self.token_attrs.to_token_id().inner(),
             self.coin_attrs.to_coin().inner(),
-            *value_coords.x(),
-            *value_coords.y(),
+            value_x,
+            value_y,
         ];
 
         //darkfi::zk::export_witness_json("proof/witness/auth_token_mint_v1.json", &prover_witnesses, &public_inputs);
         let circuit = ZkCircuit::new(prover_witnesses, &self.auth_mint_zkbin);
         let proof = Proof::create(&self.auth_mint_pk, &[circuit], &public_inputs, &mut OsRng)?;
+        let mut proofs = vec![proof];
+
+        // For capped tokens, prove the new total supply is within the cap
+        if let Some(cap) = &self.supply_cap {
+            let proof = create_token_supply_cap_proof(
+                &cap.supply_cap_zkbin,
+                &cap.supply_cap_pk,
+                &self.mint_keypair,
+                &self.token_attrs,
+                cap.supply + self.coin_attrs.value,
+                &cap.supply_blind + &value_blind,
+                cap.supply_cap,
+            )?;
+            proofs.push(proof);
+        }
 
         // Create the note
 
@@ -106,6 +142,7 @@ impl AuthTokenMintCallBuilder {
             coin_blind: self.coin_attrs.blind,
             value_blind,
             token_blind: Blind::ZERO,
+            payment_id: None,
             memo: vec![],
         };
 
@@ -117,7 +154,7 @@ impl AuthTokenMintCallBuilder {
             enc_note,
             mint_pubkey,
         };
-        let debris = AuthTokenMintCallDebris { params, proofs: vec![proof] };
+        let debris = AuthTokenMintCallDebris { params, proofs, value_blind };
         Ok(debris)
     }
 }
@@ -0,0 +1,79 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    ClientFailed, Result,
+};
+use darkfi_sdk::crypto::Keypair;
+use log::info;
+use rand::rngs::OsRng;
+
+use crate::model::{BridgeAuthoritySet, MoneyBridgeAuthoritySetParamsV1, TokenAttributes};
+
+pub struct BridgeAuthoritySetCallDebris {
+    pub params: MoneyBridgeAuthoritySetParamsV1,
+    pub proofs: Vec<Proof>,
+}
+
+/// Struct holding necessary information to build a `Money::BridgeAuthoritySetV1` contract call.
+pub struct BridgeAuthoritySetCallBuilder {
+    /// Mint authority keypair
+    pub mint_keypair: Keypair,
+    pub token_attrs: TokenAttributes,
+    /// Authorities allowed to mint the token through the bridge
+    pub authority_set: BridgeAuthoritySet,
+    /// `TokenFreeze_V1` zkas circuit ZkBinary
+    pub freeze_zkbin: ZkBinary,
+    /// Proving key for the `TokenFreeze_V1` zk circuit,
+    pub freeze_pk: ProvingKey,
+}
+
+impl BridgeAuthoritySetCallBuilder {
+    pub fn build(&self) -> Result<BridgeAuthoritySetCallDebris> {
+        info!("Building Money::BridgeAuthoritySetV1 contract call");
+        if !self.authority_set.is_valid() {
+            return Err(ClientFailed::VerifyError("Invalid bridge authority set".to_string()).into())
+        }
+
+        // We just need to produce a valid signature, and enforce the
+        // token ID derivation from the mint authority inside ZK.
+        let prover_witnesses = vec![
+            // Token attributes
+            Witness::Base(Value::known(self.token_attrs.auth_parent.inner())),
+            Witness::Base(Value::known(self.token_attrs.blind.inner())),
+            // Secret key used by mint
+            Witness::Base(Value::known(self.mint_keypair.secret.inner())),
+        ];
+
+        let mint_pubkey = self.mint_keypair.public;
+        let token_id = self.token_attrs.to_token_id();
+
+        let public_inputs = vec![mint_pubkey.x(), mint_pubkey.y(), token_id.inner()];
+        let circuit = ZkCircuit::new(prover_witnesses, &self.freeze_zkbin);
+        let proof = Proof::create(&self.freeze_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        let params = MoneyBridgeAuthoritySetParamsV1 {
+            mint_public: mint_pubkey,
+            token_id,
+            authority_set: self.authority_set.clone(),
+        };
+        Ok(BridgeAuthoritySetCallDebris { params, proofs: vec![proof] })
+    }
+}
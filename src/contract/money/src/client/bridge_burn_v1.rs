@@ -0,0 +1,187 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    zk::{Proof, ProvingKey},
+    zkas::ZkBinary,
+    ClientFailed, Result,
+};
+use darkfi_sdk::{
+    bridgetree::Position,
+    crypto::{
+        pasta_prelude::*, smt::PathFp, util::hash_to_base, BaseBlind, Blind, FuncId, MerkleNode,
+        MerkleTree, SecretKey,
+    },
+    pasta::pallas,
+};
+use darkfi_serial::{async_trait, SerialDecodable, SerialEncodable};
+use log::info;
+use rand::rngs::OsRng;
+
+use crate::{
+    client::{
+        transfer_v1::{proof::create_transfer_burn_proof, TransferCallInput},
+        OwnCoin,
+    },
+    model::{BridgeBurnReceipt, Input, MoneyBridgeBurnParamsV1},
+};
+
+/// Destination of coins bridged out of DarkFi. Only its commitment is
+/// published on-chain, and the opening is handed over to the bridge
+/// operators releasing the funds.
+#[derive(Debug, Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct BridgeDestination {
+    /// Identifier of the destination chain
+    pub chain_id: u64,
+    /// Recipient address on the destination chain
+    pub address: Vec<u8>,
+    /// Blinding factor hiding the destination
+    pub blind: BaseBlind,
+}
+
+impl BridgeDestination {
+    /// Commitment to the destination, as found in the burn receipt
+    pub fn commit(&self) -> pallas::Base {
+        hash_to_base(
+            b"DarkFi:Bridge",
+            &[&self.chain_id.to_le_bytes(), &self.address, &self.blind.inner().to_repr()],
+        )
+    }
+}
+
+/// Inclusion proof of a [`BridgeBurnReceipt`] in the Merkle tree of
+/// bridge burns. Bridge operators check the computed root against the
+/// on-chain set of bridge burn roots.
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct BridgeBurnProof {
+    /// The proven receipt
+    pub receipt: BridgeBurnReceipt,
+    /// Position of the receipt's leaf in the tree
+    pub leaf_position: u64,
+    /// Merkle path of the receipt's leaf
+    pub merkle_path: Vec<MerkleNode>,
+}
+
+impl BridgeBurnProof {
+    /// Create the proof for a receipt marked at `leaf_position` in `tree`
+    pub fn new(
+        receipt: BridgeBurnReceipt,
+        tree: &MerkleTree,
+        leaf_position: Position,
+    ) -> Option<Self> {
+        let merkle_path = tree.witness(leaf_position, 0).ok()?;
+        Some(Self { receipt, leaf_position: leaf_position.into(), merkle_path })
+    }
+
+    /// Compute the Merkle root the receipt is included in
+    pub fn root(&self) -> MerkleNode {
+        let mut current = self.receipt.to_leaf();
+        for (level, sibling) in self.merkle_path.iter().enumerate() {
+            let level = level as u8;
+            current = if self.leaf_position & (1 << level) == 0 {
+                MerkleNode::combine(level.into(), &current, sibling)
+            } else {
+                MerkleNode::combine(level.into(), sibling, &current)
+            };
+        }
+        current
+    }
+
+    /// Verify the receipt is included in the tree with the given root,
+    /// and that it burned coins towards the given destination.
+    pub fn verify(&self, root: &MerkleNode, destination: &BridgeDestination) -> bool {
+        self.receipt.destination == destination.commit() && &self.root() == root
+    }
+}
+
+pub struct BridgeBurnCallDebris {
+    pub params: MoneyBridgeBurnParamsV1,
+    pub proofs: Vec<Proof>,
+    /// Secret key signing the burn
+    pub signature_secret: SecretKey,
+}
+
+/// Struct holding necessary information to build a `Money::BridgeBurnV1` contract call.
+pub struct BridgeBurnCallBuilder {
+    /// The coin being burned
+    pub coin: OwnCoin,
+    /// Merkle path in the Money Merkle tree for `coin`
+    pub merkle_path: Vec<MerkleNode>,
+    /// Root of the Money SMT of frozen tokens
+    pub frozen_root: pallas::Base,
+    /// Path in the frozen tokens SMT showing the coin's token is not frozen
+    pub frozen_path: PathFp,
+    /// Where the burned value is bridged to
+    pub destination: BridgeDestination,
+
+    /// `Burn_V1` zkas circuit ZkBinary
+    pub burn_zkbin: ZkBinary,
+    /// Proving key for the `Burn_V1` zk circuit
+    pub burn_pk: ProvingKey,
+}
+
+impl BridgeBurnCallBuilder {
+    pub fn build(self) -> Result<BridgeBurnCallDebris> {
+        info!("Building Money::BridgeBurnV1 contract call");
+        if self.coin.note.spend_hook != FuncId::none() {
+            return Err(ClientFailed::VerifyError("Coin has a spend hook".to_string()).into())
+        }
+
+        let value = self.coin.note.value;
+        let token_id = self.coin.note.token_id;
+        let value_blind = Blind::random(&mut OsRng);
+        let token_blind = Blind::random(&mut OsRng);
+        let signature_secret = SecretKey::random(&mut OsRng);
+
+        let input = TransferCallInput {
+            coin: self.coin,
+            merkle_path: self.merkle_path,
+            user_data_blind: Blind::random(&mut OsRng),
+            frozen_root: self.frozen_root,
+            frozen_path: self.frozen_path,
+        };
+
+        let (proof, public_inputs) = create_transfer_burn_proof(
+            &self.burn_zkbin,
+            &self.burn_pk,
+            &input,
+            value_blind,
+            token_blind,
+            signature_secret,
+        )?;
+
+        let params = MoneyBridgeBurnParamsV1 {
+            input: Input {
+                value_commit: public_inputs.value_commit,
+                token_commit: public_inputs.token_commit,
+                nullifier: public_inputs.nullifier,
+                merkle_root: public_inputs.merkle_root,
+                frozen_root: public_inputs.frozen_root,
+                user_data_enc: public_inputs.user_data_enc,
+                signature_public: public_inputs.signature_public,
+            },
+            value,
+            value_blind,
+            token_id,
+            token_blind,
+            destination: self.destination.commit(),
+        };
+
+        Ok(BridgeBurnCallDebris { params, proofs: vec![proof], signature_secret })
+    }
+}
@@ -0,0 +1,121 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    zk::{Proof, ProvingKey},
+    zkas::ZkBinary,
+    Result,
+};
+use darkfi_sdk::{
+    crypto::{note::AeadEncryptedNote, Blind, FuncId, PublicKey},
+    pasta::pallas,
+};
+use log::info;
+use rand::rngs::OsRng;
+
+use crate::{
+    client::{transfer_v1::proof::create_transfer_mint_proof, MoneyNote},
+    model::{CoinAttributes, MoneyBridgeMintParamsV1, Output, TokenId},
+};
+
+pub struct BridgeMintCallDebris {
+    pub params: MoneyBridgeMintParamsV1,
+    pub proofs: Vec<Proof>,
+}
+
+/// Struct holding necessary information to build a `Money::BridgeMintV1` contract call.
+/// The transaction has to be signed by every authority listed in `signers`.
+pub struct BridgeMintCallBuilder {
+    /// Value being bridged in
+    pub value: u64,
+    /// Token ID being bridged in
+    pub token_id: TokenId,
+    /// Reference to the lock on the source chain
+    pub source: [u8; 32],
+    /// Recipient of the minted coin
+    pub recipient: PublicKey,
+    /// Spend hook for the minted coin
+    pub spend_hook: FuncId,
+    /// User data for the minted coin
+    pub user_data: pallas::Base,
+    /// Bridge authorities signing the mint
+    pub signers: Vec<PublicKey>,
+    /// `Mint_V1` zkas circuit ZkBinary
+    pub mint_zkbin: ZkBinary,
+    /// Proving key for the `Mint_V1` zk circuit
+    pub mint_pk: ProvingKey,
+}
+
+impl BridgeMintCallBuilder {
+    pub fn build(&self) -> Result<BridgeMintCallDebris> {
+        info!("Building Money::BridgeMintV1 contract call");
+        let value_blind = Blind::random(&mut OsRng);
+        let token_blind = Blind::random(&mut OsRng);
+        let coin_blind = Blind::random(&mut OsRng);
+
+        let output = CoinAttributes {
+            public_key: self.recipient,
+            value: self.value,
+            token_id: self.token_id,
+            spend_hook: self.spend_hook,
+            user_data: self.user_data,
+            blind: coin_blind,
+        };
+
+        let (proof, public_inputs) = create_transfer_mint_proof(
+            &self.mint_zkbin,
+            &self.mint_pk,
+            &output,
+            value_blind,
+            token_blind,
+            self.spend_hook,
+            self.user_data,
+            coin_blind,
+        )?;
+
+        let note = MoneyNote {
+            value: self.value,
+            token_id: self.token_id,
+            spend_hook: self.spend_hook,
+            user_data: self.user_data,
+            coin_blind,
+            value_blind,
+            token_blind,
+            payment_id: None,
+            memo: vec![],
+        };
+        let encrypted_note = AeadEncryptedNote::encrypt(&note, &self.recipient, &mut OsRng)?;
+
+        let params = MoneyBridgeMintParamsV1 {
+            value: self.value,
+            value_blind,
+            token_id: self.token_id,
+            token_blind,
+            source: self.source,
+            signers: self.signers.clone(),
+            output: Output {
+                value_commit: public_inputs.value_commit,
+                token_commit: public_inputs.token_commit,
+                coin: public_inputs.coin,
+                note: encrypted_note,
+            },
+        };
+
+        Ok(BridgeMintCallDebris { params, proofs: vec![proof] })
+    }
+}
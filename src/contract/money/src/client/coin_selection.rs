@@ -0,0 +1,167 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Coin selection strategies used when building transaction inputs.
+//!
+//! How coins are picked reveals information about a wallet. Always
+//! spending coins in the same order links transactions of the same
+//! wallet together, and overshooting the paid value creates a change
+//! output that is easy to tell apart from the payment. The strategies
+//! here let the caller choose the trade-off between the number of
+//! inputs, change outputs and predictability.
+
+use darkfi::{ClientFailed, Result};
+use log::{debug, error};
+use rand::{rngs::OsRng, seq::SliceRandom};
+
+use super::OwnCoin;
+
+/// Strategy selecting a subset of coins to spend
+pub trait CoinSelection {
+    /// Select coins from `coins` of at least `target` value in total.
+    /// Returns the selected coins and the change value left over.
+    fn select(&self, coins: Vec<OwnCoin>, target: u64) -> Result<(Vec<OwnCoin>, u64)>;
+}
+
+/// Accumulate the given coins in order until reaching `target`
+fn accumulate(coins: Vec<OwnCoin>, target: u64) -> Result<(Vec<OwnCoin>, u64)> {
+    let mut total_value: u64 = 0;
+    let mut selected = vec![];
+
+    for coin in coins {
+        if total_value >= target {
+            break
+        }
+
+        total_value = total_value.saturating_add(coin.note.value);
+        selected.push(coin);
+    }
+
+    if total_value < target {
+        error!("Not enough value to build tx inputs");
+        return Err(ClientFailed::NotEnoughValue(total_value).into())
+    }
+
+    Ok((selected, total_value - target))
+}
+
+/// Select the coins with the largest value first. This minimizes the
+/// number of inputs, at the cost of always creating change.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LargestFirst;
+
+impl CoinSelection for LargestFirst {
+    fn select(&self, mut coins: Vec<OwnCoin>, target: u64) -> Result<(Vec<OwnCoin>, u64)> {
+        coins.sort_by(|a, b| b.note.value.cmp(&a.note.value));
+        accumulate(coins, target)
+    }
+}
+
+/// Select coins in a random order, so the selection does not depend
+/// on the order or values of the wallet's coins.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomSelection;
+
+impl CoinSelection for RandomSelection {
+    fn select(&self, mut coins: Vec<OwnCoin>, target: u64) -> Result<(Vec<OwnCoin>, u64)> {
+        coins.shuffle(&mut OsRng);
+        accumulate(coins, target)
+    }
+}
+
+/// Search for a set of coins adding up to exactly `target`, so no
+/// change output is needed. The depth-first search is bounded by
+/// `max_tries` steps, and falls back to [`RandomSelection`] when no
+/// exact match is found.
+#[derive(Debug, Clone, Copy)]
+pub struct BranchAndBound {
+    /// Maximum number of search steps
+    pub max_tries: usize,
+}
+
+impl Default for BranchAndBound {
+    fn default() -> Self {
+        Self { max_tries: 100000 }
+    }
+}
+
+impl BranchAndBound {
+    /// Find the indexes of `values`, sorted in descending order, adding
+    /// up to `target`. `remaining[i]` holds the sum of `values[i..]`.
+    fn search(
+        values: &[u64],
+        remaining: &[u128],
+        idx: usize,
+        current: u128,
+        target: u128,
+        tries: &mut usize,
+        selected: &mut Vec<usize>,
+    ) -> bool {
+        if current == target {
+            return true
+        }
+
+        if idx == values.len() || *tries == 0 || current + remaining[idx] < target {
+            return false
+        }
+        *tries -= 1;
+
+        // Branch including the coin
+        let value = values[idx] as u128;
+        if current + value <= target {
+            selected.push(idx);
+            if Self::search(values, remaining, idx + 1, current + value, target, tries, selected) {
+                return true
+            }
+            selected.pop();
+        }
+
+        // Branch excluding the coin. Coins of the same value would
+        // lead to the same subsets, so they are skipped as well.
+        let mut next = idx + 1;
+        while next < values.len() && values[next] == values[idx] {
+            next += 1;
+        }
+        Self::search(values, remaining, next, current, target, tries, selected)
+    }
+}
+
+impl CoinSelection for BranchAndBound {
+    fn select(&self, mut coins: Vec<OwnCoin>, target: u64) -> Result<(Vec<OwnCoin>, u64)> {
+        coins.sort_by(|a, b| b.note.value.cmp(&a.note.value));
+        let values: Vec<u64> = coins.iter().map(|x| x.note.value).collect();
+
+        let mut remaining = vec![0u128; values.len() + 1];
+        for (i, value) in values.iter().enumerate().rev() {
+            remaining[i] = remaining[i + 1] + *value as u128;
+        }
+
+        let mut tries = self.max_tries;
+        let mut selected = vec![];
+        if target > 0 &&
+            Self::search(&values, &remaining, 0, 0, target as u128, &mut tries, &mut selected)
+        {
+            debug!("Found exact coin selection with {} inputs", selected.len());
+            let selected = selected.into_iter().map(|i| coins[i].clone()).collect();
+            return Ok((selected, 0))
+        }
+
+        debug!("No exact coin selection found, falling back to random selection");
+        RandomSelection.select(coins, target)
+    }
+}
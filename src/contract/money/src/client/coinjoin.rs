@@ -0,0 +1,448 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Client API for building collaborative `Money::TransferV1` calls.
+//!
+//! A CoinJoin lets multiple independent parties contribute inputs and
+//! outputs to a single transfer, so an observer can not tell which
+//! outputs are paid for by which inputs. It is built in three rounds:
+//!
+//! 1. The coordinator creates a [`CoinJoinSession`] for the token being
+//!    transferred and hands it over to every participant. All the token
+//!    commitments in a transfer have to match, so the session carries the
+//!    token commitment blind shared by everyone.
+//! 2. Every participant uses a [`CoinJoinCallBuilder`] to create their
+//!    [`CoinJoinContribution`], whose inputs and outputs balance on their
+//!    own, and sends it back. The coordinator merges the contributions in
+//!    a shuffled order with [`CoinJoinSession::join`] and builds the
+//!    transaction, optionally appending their fee call.
+//! 3. Every participant checks their contribution made it unmodified into
+//!    the transaction and signs their own inputs with
+//!    [`CoinJoinContribution::sign`]. The coordinator then puts the
+//!    signatures in place with [`collect_signatures`].
+//!
+//! The signature secrets are derived from the spent coins with
+//! [`coinjoin_signature_secret`], so participants don't have to keep any
+//! state between the rounds besides their own contribution.
+
+use darkfi::{
+    tx::Transaction,
+    zk::{Proof, ProvingKey, ProvingSession},
+    zkas::ZkBinary,
+    ClientFailed, Result,
+};
+use darkfi_sdk::{
+    crypto::{
+        note::AeadEncryptedNote,
+        pasta_prelude::*,
+        poseidon_hash,
+        schnorr::{SchnorrPublic, Signature},
+        BaseBlind, Blind, SecretKey, MONEY_CONTRACT_ID,
+    },
+    pasta::pallas,
+};
+use darkfi_serial::{async_trait, deserialize, Encodable, SerialDecodable, SerialEncodable};
+use log::{debug, error, info};
+use rand::{rngs::OsRng, seq::SliceRandom};
+
+use super::transfer_v1::{
+    proof::{transfer_burn_circuit, transfer_mint_circuit},
+    TransferCallInput, TransferCallOutput,
+};
+use crate::{
+    client::{compute_remainder_blind, MoneyNote, OwnCoin},
+    model::{Input, MoneyTransferParamsV1, Output, TokenId},
+    MoneyFunction, MONEY_NOTE_MEMO_MAX_LEN,
+};
+
+/// Domain separator for deriving CoinJoin input signature secrets
+const COINJOIN_SIGNATURE_DERIVATION_PREFIX: u64 = 0x636a_7369_67;
+
+/// Derive the signature secret used for spending `coin` in a CoinJoin.
+/// Only the coin owner knows the coin secret, and the derivation is
+/// deterministic, so the owner can recompute it when asked to sign.
+pub fn coinjoin_signature_secret(coin: &OwnCoin) -> SecretKey {
+    SecretKey::from(poseidon_hash([
+        pallas::Base::from(COINJOIN_SIGNATURE_DERIVATION_PREFIX),
+        coin.secret.inner(),
+        coin.coin.inner(),
+    ]))
+}
+
+/// Parameters shared by every party of a CoinJoin
+#[derive(Debug, Clone, Copy, SerialEncodable, SerialDecodable)]
+pub struct CoinJoinSession {
+    /// Token ID being transferred
+    pub token_id: TokenId,
+    /// Token commitment blind used by all inputs and outputs
+    pub token_blind: BaseBlind,
+}
+
+impl CoinJoinSession {
+    /// Create a new session for transferring `token_id`
+    pub fn new(token_id: TokenId) -> Self {
+        Self { token_id, token_blind: Blind::random(&mut OsRng) }
+    }
+
+    /// The token commitment every input and output has to use
+    pub fn token_commit(&self) -> pallas::Base {
+        poseidon_hash([self.token_id.inner(), self.token_blind.inner()])
+    }
+
+    /// Verify the given contributions and merge them into a single set of
+    /// `Money::TransferV1` call parameters and proofs. Inputs and outputs
+    /// are shuffled so their position does not reveal their contributor.
+    pub fn join(
+        &self,
+        contributions: &[CoinJoinContribution],
+    ) -> Result<(MoneyTransferParamsV1, Vec<Proof>)> {
+        debug!("Joining {} CoinJoin contributions", contributions.len());
+        if contributions.is_empty() {
+            return Err(ClientFailed::VerifyError("No contributions to join".to_string()).into())
+        }
+
+        let mut inputs = vec![];
+        let mut outputs = vec![];
+        for (i, contribution) in contributions.iter().enumerate() {
+            if let Err(e) = contribution.verify(self) {
+                error!("CoinJoin contribution {} is invalid: {}", i, e);
+                return Err(e)
+            }
+
+            let n_inputs = contribution.params.inputs.len();
+            for (input, proof) in
+                contribution.params.inputs.iter().zip(&contribution.proofs[..n_inputs])
+            {
+                if inputs.iter().any(|(x, _): &(Input, Proof)| x.nullifier == input.nullifier) {
+                    return Err(ClientFailed::VerifyError(format!(
+                        "Duplicate nullifier in contribution {}",
+                        i
+                    ))
+                    .into())
+                }
+                inputs.push((input.clone(), proof.clone()));
+            }
+
+            for (output, proof) in
+                contribution.params.outputs.iter().zip(&contribution.proofs[n_inputs..])
+            {
+                if outputs.iter().any(|(x, _): &(Output, Proof)| x.coin == output.coin) {
+                    return Err(ClientFailed::VerifyError(format!(
+                        "Duplicate coin in contribution {}",
+                        i
+                    ))
+                    .into())
+                }
+                outputs.push((output.clone(), proof.clone()));
+            }
+        }
+
+        inputs.shuffle(&mut OsRng);
+        outputs.shuffle(&mut OsRng);
+
+        // Burn proofs come first, followed by the mint proofs
+        let mut params = MoneyTransferParamsV1 { inputs: vec![], outputs: vec![] };
+        let mut proofs = vec![];
+        for (input, proof) in inputs {
+            params.inputs.push(input);
+            proofs.push(proof);
+        }
+        for (output, proof) in outputs {
+            params.outputs.push(output);
+            proofs.push(proof);
+        }
+
+        Ok((params, proofs))
+    }
+}
+
+/// A single party's share of a CoinJoin
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct CoinJoinContribution {
+    /// The party's inputs and outputs
+    pub params: MoneyTransferParamsV1,
+    /// The burn proofs for the inputs, followed by the mint proofs for the outputs
+    pub proofs: Vec<Proof>,
+}
+
+impl CoinJoinContribution {
+    /// Verify the contribution is well formed for the given session: it
+    /// has to use the session token commitment and its value commitments
+    /// have to balance. The ZK proofs themselves are verified on-chain.
+    pub fn verify(&self, session: &CoinJoinSession) -> Result<()> {
+        if self.params.inputs.is_empty() ||
+            self.params.outputs.is_empty() ||
+            self.proofs.len() != self.params.inputs.len() + self.params.outputs.len()
+        {
+            return Err(ClientFailed::VerifyError("Malformed contribution".to_string()).into())
+        }
+
+        let token_commit = session.token_commit();
+        if self.params.inputs.iter().any(|x| x.token_commit != token_commit) ||
+            self.params.outputs.iter().any(|x| x.token_commit != token_commit)
+        {
+            return Err(ClientFailed::VerifyError("Token commitments mismatch".to_string()).into())
+        }
+
+        let mut valcom_total = pallas::Point::identity();
+        for input in &self.params.inputs {
+            valcom_total += input.value_commit;
+        }
+        for output in &self.params.outputs {
+            valcom_total -= output.value_commit;
+        }
+
+        if valcom_total != pallas::Point::identity() {
+            return Err(ClientFailed::VerifyError("Value commitments mismatch".to_string()).into())
+        }
+
+        Ok(())
+    }
+
+    /// Sign our inputs in the `Money::TransferV1` call found at `call_idx` of
+    /// the joined transaction. `coins` are the coins we spent in this
+    /// contribution. Before signing, we verify all of our inputs and outputs
+    /// are part of the call unmodified.
+    ///
+    /// Returns the signatures along with the call input index they belong to.
+    pub fn sign(
+        &self,
+        tx: &Transaction,
+        call_idx: usize,
+        coins: &[OwnCoin],
+    ) -> Result<Vec<(u64, Signature)>> {
+        debug!("Signing CoinJoin contribution in call {}", call_idx);
+        let Some(call) = tx.calls.get(call_idx) else {
+            return Err(ClientFailed::VerifyError("Call index out of bounds".to_string()).into())
+        };
+
+        let call = &call.data;
+        if call.contract_id != *MONEY_CONTRACT_ID ||
+            call.data.first() != Some(&(MoneyFunction::TransferV1 as u8))
+        {
+            return Err(ClientFailed::VerifyError("Call is not a Money transfer".to_string()).into())
+        }
+
+        let params: MoneyTransferParamsV1 = deserialize(&call.data[1..])?;
+
+        if self.params.outputs.iter().any(|x| !params.outputs.contains(x)) {
+            return Err(ClientFailed::VerifyError("Our outputs are missing".to_string()).into())
+        }
+
+        let mut indexes = vec![];
+        let mut secrets = vec![];
+        for input in &self.params.inputs {
+            let Some(idx) = params.inputs.iter().position(|x| x == input) else {
+                return Err(ClientFailed::VerifyError("Our inputs are missing".to_string()).into())
+            };
+
+            let Some(coin) = coins.iter().find(|x| x.nullifier() == input.nullifier) else {
+                return Err(
+                    ClientFailed::VerifyError(format!("Coin for input {} not found", idx)).into()
+                )
+            };
+
+            indexes.push(idx as u64);
+            secrets.push(coinjoin_signature_secret(coin));
+        }
+
+        let sigs = tx.create_sigs(&secrets)?;
+        Ok(indexes.into_iter().zip(sigs).collect())
+    }
+}
+
+/// Put the participants' signatures for the `Money::TransferV1` call at
+/// `call_idx` in input order. Every signature is verified against the
+/// signature public key of the input it claims to sign, so a misbehaving
+/// participant can be identified.
+pub fn collect_signatures(
+    tx: &Transaction,
+    call_idx: usize,
+    params: &MoneyTransferParamsV1,
+    partial_sigs: &[Vec<(u64, Signature)>],
+) -> Result<Vec<Signature>> {
+    debug!("Collecting CoinJoin signatures for call {}", call_idx);
+
+    // Hash the transaction without the signatures
+    let mut hasher = blake3::Hasher::new();
+    tx.calls.encode(&mut hasher)?;
+    tx.proofs.encode(&mut hasher)?;
+    let data_hash = hasher.finalize();
+
+    let mut sigs: Vec<Option<Signature>> = vec![None; params.inputs.len()];
+    for (i, partial) in partial_sigs.iter().enumerate() {
+        for (idx, signature) in partial {
+            let idx = *idx as usize;
+            let Some(input) = params.inputs.get(idx) else {
+                return Err(ClientFailed::VerifyError(format!(
+                    "Participant {} signed an unknown input",
+                    i
+                ))
+                .into())
+            };
+
+            if sigs[idx].is_some() {
+                return Err(ClientFailed::VerifyError(format!("Input {} signed twice", idx)).into())
+            }
+
+            if !input.signature_public.verify(&data_hash.as_bytes()[..], signature) {
+                return Err(ClientFailed::VerifyError(format!(
+                    "Participant {} created an invalid signature for input {}",
+                    i, idx
+                ))
+                .into())
+            }
+
+            sigs[idx] = Some(*signature);
+        }
+    }
+
+    let Some(sigs): Option<Vec<Signature>> = sigs.into_iter().collect() else {
+        return Err(ClientFailed::VerifyError("Missing input signatures".to_string()).into())
+    };
+
+    Ok(sigs)
+}
+
+/// Struct holding necessary information to build a party's contribution
+/// to a CoinJoin. The inputs and outputs must balance, so any change has
+/// to be included as an output.
+pub struct CoinJoinCallBuilder {
+    /// The session this contribution is created for
+    pub session: CoinJoinSession,
+    /// Anonymous inputs
+    pub inputs: Vec<TransferCallInput>,
+    /// Anonymous outputs
+    pub outputs: Vec<TransferCallOutput>,
+    /// `Mint_V1` zkas circuit ZkBinary
+    pub mint_zkbin: ZkBinary,
+    /// Proving key for the `Mint_V1` zk circuit
+    pub mint_pk: ProvingKey,
+    /// `Burn_V1` zkas circuit ZkBinary
+    pub burn_zkbin: ZkBinary,
+    /// Proving key for the `Burn_V1` zk circuit
+    pub burn_pk: ProvingKey,
+}
+
+impl CoinJoinCallBuilder {
+    pub fn build(&self) -> Result<CoinJoinContribution> {
+        debug!("Building CoinJoin contribution");
+        assert!(!self.inputs.is_empty());
+        assert!(!self.outputs.is_empty());
+
+        let mut inputs_value: u64 = 0;
+        for input in &self.inputs {
+            if input.coin.note.token_id != self.session.token_id {
+                return Err(ClientFailed::InvalidTokenId(input.coin.note.token_id.to_string()).into())
+            }
+            inputs_value += input.coin.note.value;
+        }
+
+        let mut outputs_value: u64 = 0;
+        for output in &self.outputs {
+            if output.token_id != self.session.token_id {
+                return Err(ClientFailed::InvalidTokenId(output.token_id.to_string()).into())
+            }
+            outputs_value += output.value;
+        }
+
+        if inputs_value != outputs_value {
+            error!("Contribution inputs value {} != outputs value {}", inputs_value, outputs_value);
+            return Err(ClientFailed::InvalidAmount(outputs_value).into())
+        }
+
+        let mut params = MoneyTransferParamsV1 { inputs: vec![], outputs: vec![] };
+        let mut session = ProvingSession::new();
+        let mut input_blinds = vec![];
+        let mut output_blinds = vec![];
+
+        for (i, input) in self.inputs.iter().enumerate() {
+            let value_blind = Blind::random(&mut OsRng);
+            input_blinds.push(value_blind);
+
+            info!("Building transfer burn circuit for input {}", i);
+            let (circuit, public_inputs) = transfer_burn_circuit(
+                &self.burn_zkbin,
+                input,
+                value_blind,
+                self.session.token_blind,
+                coinjoin_signature_secret(&input.coin),
+            );
+            session.push(&self.burn_pk, circuit, public_inputs.to_vec());
+
+            params.inputs.push(Input {
+                value_commit: public_inputs.value_commit,
+                token_commit: public_inputs.token_commit,
+                nullifier: public_inputs.nullifier,
+                merkle_root: public_inputs.merkle_root,
+                frozen_root: public_inputs.frozen_root,
+                user_data_enc: public_inputs.user_data_enc,
+                signature_public: public_inputs.signature_public,
+            });
+        }
+
+        for (i, output) in self.outputs.iter().enumerate() {
+            // The last blind makes our own value commitments balance
+            let value_blind = if i == self.outputs.len() - 1 {
+                compute_remainder_blind(&input_blinds, &output_blinds)
+            } else {
+                Blind::random(&mut OsRng)
+            };
+            output_blinds.push(value_blind);
+
+            info!("Building transfer mint circuit for output {}", i);
+            let (circuit, public_inputs) = transfer_mint_circuit(
+                &self.mint_zkbin,
+                output,
+                value_blind,
+                self.session.token_blind,
+                output.spend_hook,
+                output.user_data,
+                output.blind,
+            );
+            session.push(&self.mint_pk, circuit, public_inputs.to_vec());
+
+            let note = MoneyNote {
+                value: output.value,
+                token_id: output.token_id,
+                spend_hook: output.spend_hook,
+                user_data: output.user_data,
+                coin_blind: output.blind,
+                value_blind,
+                token_blind: self.session.token_blind,
+                payment_id: None,
+                // Padded like any other transfer memo
+                memo: vec![0; MONEY_NOTE_MEMO_MAX_LEN],
+            };
+
+            let encrypted_note = AeadEncryptedNote::encrypt(&note, &output.public_key, &mut OsRng)?;
+
+            params.outputs.push(Output {
+                value_commit: public_inputs.value_commit,
+                token_commit: public_inputs.token_commit,
+                coin: public_inputs.coin,
+                note: encrypted_note,
+            });
+        }
+
+        // Proofs come back in the order they were queued: inputs, then outputs
+        let proofs = session.prove()?;
+
+        Ok(CoinJoinContribution { params, proofs })
+    }
+}
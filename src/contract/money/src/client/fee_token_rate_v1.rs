@@ -0,0 +1,56 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::Result;
+use darkfi_sdk::crypto::{Keypair, PublicKey};
+use log::info;
+
+use crate::model::{FeeTokenRate, MoneyFeeTokenRateParamsV1, TokenId};
+
+pub struct FeeTokenRateCallDebris {
+    pub params: MoneyFeeTokenRateParamsV1,
+}
+
+/// Struct holding necessary information to build a `Money::FeeTokenRateV1` contract call.
+pub struct FeeTokenRateCallBuilder {
+    /// Fee governance keypair
+    pub governance_keypair: Keypair,
+    /// Token to whitelist or remove from the whitelist
+    pub token_id: TokenId,
+    /// New conversion rate, or `None` to remove the token
+    pub rate: Option<FeeTokenRate>,
+    /// Optional key to hand the fee governance over to
+    pub new_governance_public: Option<PublicKey>,
+}
+
+impl FeeTokenRateCallBuilder {
+    pub fn build(&self) -> Result<FeeTokenRateCallDebris> {
+        info!("Building Money::FeeTokenRateV1 contract call");
+
+        // There are no ZK proofs here, the call only has to be signed
+        // with the governance secret key.
+        let params = MoneyFeeTokenRateParamsV1 {
+            governance_public: self.governance_keypair.public,
+            token_id: self.token_id,
+            rate: self.rate,
+            new_governance_public: self.new_governance_public,
+        };
+
+        Ok(FeeTokenRateCallDebris { params })
+    }
+}
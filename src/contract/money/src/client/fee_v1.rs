@@ -24,9 +24,9 @@ use darkfi::{
 use darkfi_sdk::{
     bridgetree::Hashable,
     crypto::{
+        commitment,
         pasta_prelude::{Curve, CurveAffine},
-        pedersen_commitment_u64, poseidon_hash, BaseBlind, FuncId, MerkleNode, PublicKey,
-        ScalarBlind, SecretKey,
+        poseidon_hash, BaseBlind, FuncId, MerkleNode, PublicKey, ScalarBlind, SecretKey,
     },
     pasta::pallas,
 };
@@ -79,24 +79,24 @@ impl FeeRevealed {
     /// Transform the struct into a `Vec<pallas::Base>` ready for
     /// proof verification.
     pub fn to_vec(&self) -> Vec<pallas::Base> {
-        let input_vc_coords = self.input_value_commit.to_affine().coordinates().unwrap();
-        let output_vc_coords = self.output_value_commit.to_affine().coordinates().unwrap();
+        let (input_vc_x, input_vc_y) = commitment::xy(&self.input_value_commit);
+        let (output_vc_x, output_vc_y) = commitment::xy(&self.output_value_commit);
         let sigpub_coords = self.signature_public.inner().to_affine().coordinates().unwrap();
 
         // NOTE: It's important to keep these in the same order
         // as the `constrain_instance` calls in the zkas code.
         vec![
             self.nullifier.inner(),
-            *input_vc_coords.x(),
-            *input_vc_coords.y(),
+            input_vc_x,
+            input_vc_y,
             self.token_commit,
             self.merkle_root.inner(),
             self.input_user_data_enc,
             *sigpub_coords.x(),
             *sigpub_coords.y(),
             self.output_coin.inner(),
-            *output_vc_coords.x(),
-            *output_vc_coords.y(),
+            output_vc_x,
+            output_vc_y,
         ]
     }
 }
@@ -157,8 +157,8 @@ pub fn create_fee_proof(
 
     let input_user_data_enc =
         poseidon_hash([input.coin.note.user_data, input.user_data_blind.inner()]);
-    let input_value_commit = pedersen_commitment_u64(input.coin.note.value, input_value_blind);
-    let output_value_commit = pedersen_commitment_u64(output.value, output_value_blind);
+    let input_value_commit = commitment::commit(input.coin.note.value, input_value_blind);
+    let output_value_commit = commitment::commit(output.value, output_value_blind);
     let token_commit = poseidon_hash([input.coin.note.token_id.inner(), token_blind.inner()]);
 
     // Create output coin
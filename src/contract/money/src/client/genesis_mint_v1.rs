@@ -22,7 +22,9 @@ use darkfi::{
     Result,
 };
 use darkfi_sdk::{
-    crypto::{note::AeadEncryptedNote, pasta_prelude::*, Blind, FuncId, Keypair, PublicKey},
+    crypto::{
+        commitment, note::AeadEncryptedNote, pasta_prelude::*, Blind, FuncId, Keypair, PublicKey,
+    },
     pasta::pallas,
 };
 use log::{debug, info};
@@ -51,11 +53,11 @@ pub struct GenesisMintRevealed {
 
 impl GenesisMintRevealed {
     pub fn to_vec(&self) -> Vec<pallas::Base> {
-        let valcom_coords = self.value_commit.to_affine().coordinates().unwrap();
+        let (valcom_x, valcom_y) = commitment::xy(&self.value_commit);
 
         // NOTE: It's important to keep these in the same order
         // as the `constrain_instance` calls in the zkas code.
-        vec![self.coin.inner(), *valcom_coords.x(), *valcom_coords.y(), self.token_commit]
+        vec![self.coin.inner(), valcom_x, valcom_y, self.token_commit]
     }
 }
 
@@ -135,6 +137,7 @@ impl GenesisMintCallBuilder {
             coin_blind,
             value_blind,
             token_blind,
+            payment_id: None,
             memo: vec![],
         };
 
@@ -0,0 +1,66 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use darkfi_sdk::crypto::{
+    frost::PublicKeyPackage, poseidon_hash, util::hash_to_base, BaseBlind, Blind, FuncId, FuncRef,
+    Keypair, SecretKey, MONEY_CONTRACT_ID,
+};
+use darkfi_serial::serialize;
+
+use crate::{
+    model::{CoinAttributes, GroupCustody, TokenId},
+    MoneyFunction,
+};
+
+/// Spend hook of coins held under a [`GroupCustody`]
+pub fn group_spend_hook() -> FuncId {
+    FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::GroupSpendV1 as u8 }
+        .to_func_id()
+}
+
+/// Custody terms of a group, with the blind derived from its public key
+/// package, which only the members learn from the key generation. Every
+/// member computes the same terms, and payers get them from any member.
+pub fn group_custody(pubkeys: &PublicKeyPackage) -> GroupCustody {
+    let blind = hash_to_base(b"DarkFi:GroupCustody", &[&serialize(pubkeys)]);
+    GroupCustody { group_public: pubkeys.group_public(), blind: Blind(blind) }
+}
+
+/// Escrow keypair owning coins held under a [`GroupCustody`]. Every member
+/// derives it from the terms, so any of them can build the spend, while
+/// `Money::GroupSpend` requires it to be signed by the group.
+pub fn group_escrow_keypair(custody: &GroupCustody) -> Keypair {
+    Keypair::new(SecretKey::from(poseidon_hash([custody.to_bulla(), custody.blind.inner()])))
+}
+
+/// Attributes of a coin holding `value` under the given terms.
+/// The payer sends it as a regular `Money::Transfer` output.
+pub fn group_lock_coin(
+    custody: &GroupCustody,
+    value: u64,
+    token_id: TokenId,
+    blind: BaseBlind,
+) -> CoinAttributes {
+    CoinAttributes {
+        public_key: group_escrow_keypair(custody).public,
+        value,
+        token_id,
+        spend_hook: group_spend_hook(),
+        user_data: custody.to_bulla(),
+        blind,
+    }
+}
@@ -0,0 +1,57 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::crypto::{
+    poseidon_hash, BaseBlind, FuncId, FuncRef, Keypair, SecretKey, MONEY_CONTRACT_ID,
+};
+
+use crate::{
+    model::{CoinAttributes, Htlc, TokenId},
+    MoneyFunction,
+};
+
+/// Spend hook of coins locked under an [`Htlc`]
+pub fn htlc_spend_hook() -> FuncId {
+    FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::HtlcSpendV1 as u8 }
+        .to_func_id()
+}
+
+/// Escrow keypair owning coins locked under an [`Htlc`]. Both parties
+/// derive it from the terms, so either can spend the coin, while
+/// `Money::HtlcSpend` checks which of them has to sign.
+pub fn htlc_escrow_keypair(htlc: &Htlc) -> Keypair {
+    Keypair::new(SecretKey::from(poseidon_hash([htlc.to_bulla(), htlc.blind.inner()])))
+}
+
+/// Attributes of the coin locking `value` under the given terms.
+/// The sender pays it as a regular `Money::Transfer` output.
+pub fn htlc_lock_coin(
+    htlc: &Htlc,
+    value: u64,
+    token_id: TokenId,
+    blind: BaseBlind,
+) -> CoinAttributes {
+    CoinAttributes {
+        public_key: htlc_escrow_keypair(htlc).public,
+        value,
+        token_id,
+        spend_hook: htlc_spend_hook(),
+        user_data: htlc.to_bulla(),
+        blind,
+    }
+}
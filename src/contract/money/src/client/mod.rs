@@ -31,24 +31,40 @@ use std::hash::{Hash, Hasher};
 use darkfi_sdk::{
     bridgetree,
     crypto::{
+        note::AeadEncryptedNote,
         pasta_prelude::{Field, PrimeField},
-        poseidon_hash, BaseBlind, Blind, FuncId, ScalarBlind, SecretKey,
+        BaseBlind, Blind, FuncId, FuncRef, ScalarBlind, SecretKey, SpendHook, SpendHookRegistry,
+        ViewingKey, MONEY_CONTRACT_ID,
     },
+    error::ContractError,
     pasta::pallas,
+    subtle::{Choice, ConstantTimeEq},
 };
 use darkfi_serial::{async_trait, SerialDecodable, SerialEncodable};
 
-use crate::model::{Coin, Nullifier, TokenId};
+use crate::{
+    model::{Coin, Nullifier, TokenId},
+    MoneyFunction,
+};
 
 /// `Money::FeeV1` API
 pub mod fee_v1;
 
+/// `Money::FeeTokenRateV1` API
+pub mod fee_token_rate_v1;
+
 /// `Money::TransferV1` API
 pub mod transfer_v1;
 
+/// Coin selection strategies
+pub mod coin_selection;
+
 /// `Money::OtcSwapV1` API
 pub mod swap_v1;
 
+/// Collaborative multi-party `Money::TransferV1` API
+pub mod coinjoin;
+
 /// `Money::GenesisMintV1` API
 pub mod genesis_mint_v1;
 
@@ -58,17 +74,55 @@ pub mod token_mint_v1;
 /// `Money::TokenFreezeV1` API
 pub mod token_freeze_v1;
 
+/// `Money::TokenSupplyCapV1` API
+pub mod token_supply_cap_v1;
+
+/// `Money::TransferFreezeV1` and `Money::TransferUnfreezeV1` API
+pub mod transfer_freeze_v1;
+
 /// `Money::PoWRewardV1` API
 pub mod pow_reward_v1;
 
 /// `Money::AuthTokenMintV1` API
 pub mod auth_token_mint_v1;
 
+/// `Money::VestingClaimV1` API
+pub mod vesting_claim_v1;
+
+/// `Money::HtlcSpendV1` API
+pub mod htlc_spend_v1;
+
+/// `Money::GroupSpendV1` API
+pub mod group_spend_v1;
+
+/// `Money::NftMintV1` API
+pub mod nft_mint_v1;
+
+/// `Money::NftBurnV1` API
+pub mod nft_burn_v1;
+
+/// `Money::BridgeBurnV1` API
+pub mod bridge_burn_v1;
+
+/// `Money::BridgeAuthoritySetV1` API
+pub mod bridge_authority_set_v1;
+
+/// `Money::BridgeMintV1` API
+pub mod bridge_mint_v1;
+
+/// Token supply proofs over the authorized mints accumulator
+pub mod token_supply;
+
+/// Recipient-supplied payment IDs bound into output coins
+pub mod payment_id;
+pub use payment_id::{PaymentId, PaymentIdOpening};
+
 /// `MoneyNote` holds the inner attributes of a `Coin`
 /// It does not store the public key since it's encrypted for that key,
 /// and so is not needed to infer the coin attributes.
 /// All other coin attributes must be present.
-#[derive(Debug, Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+/// The coin attributes are compared in constant time.
+#[derive(Debug, Clone, Eq, SerialEncodable, SerialDecodable)]
 pub struct MoneyNote {
     /// Value of the coin
     pub value: u64,
@@ -89,10 +143,77 @@ pub struct MoneyNote {
     // ] ^ the receiver is not interested in the value commit / token commits.
     // we just want to examine the coins in the outputs. The money::transfer() contract
     // should ensure everything else is correct.
-    /// Attached memo (arbitrary data)
+    /// Optional payment ID the coin blind commits to
+    pub payment_id: Option<PaymentIdOpening>,
+    /// Attached memo (arbitrary data), zero-padded to
+    /// [`MONEY_NOTE_MEMO_MAX_LEN`](crate::MONEY_NOTE_MEMO_MAX_LEN) bytes
     pub memo: Vec<u8>,
 }
 
+impl MoneyNote {
+    /// Decrypt an encrypted note belonging to the given spending secret key.
+    /// Notes can be encrypted either to the public key or to the viewing key
+    /// derived from the secret, so both are tried.
+    pub fn decrypt(
+        encrypted_note: &AeadEncryptedNote,
+        secret: &SecretKey,
+    ) -> Result<Self, ContractError> {
+        encrypted_note
+            .decrypt(secret)
+            .or_else(|_| encrypted_note.decrypt(&ViewingKey::derive(secret).inner()))
+    }
+
+    /// Return the attached memo with its zero padding stripped.
+    pub fn memo_bytes(&self) -> &[u8] {
+        let len = self.memo.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+        &self.memo[..len]
+    }
+
+    /// Return the attached memo as a UTF-8 string, if it is non-empty and valid.
+    pub fn memo_str(&self) -> Option<&str> {
+        let memo = self.memo_bytes();
+        if memo.is_empty() {
+            return None
+        }
+
+        std::str::from_utf8(memo).ok()
+    }
+
+    /// Return the attached [`PaymentId`], if any, as long as the coin blind
+    /// actually commits to it. A note carrying a payment ID that does not
+    /// match its coin blind returns `None`.
+    pub fn verified_payment_id(&self) -> Option<PaymentId> {
+        let opening = self.payment_id.as_ref()?;
+        if !bool::from(opening.coin_blind().ct_eq(&self.coin_blind)) {
+            return None
+        }
+
+        Some(opening.payment_id)
+    }
+}
+
+impl ConstantTimeEq for MoneyNote {
+    /// Compare the coin attributes of both notes in constant time.
+    /// The payment ID and memo are not covered.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.value.ct_eq(&other.value) &
+            self.token_id.inner().ct_eq(&other.token_id.inner()) &
+            self.spend_hook.inner().ct_eq(&other.spend_hook.inner()) &
+            self.user_data.ct_eq(&other.user_data) &
+            self.coin_blind.ct_eq(&other.coin_blind) &
+            self.value_blind.ct_eq(&other.value_blind) &
+            self.token_blind.ct_eq(&other.token_blind)
+    }
+}
+
+impl PartialEq for MoneyNote {
+    fn eq(&self, other: &Self) -> bool {
+        bool::from(self.ct_eq(other)) &&
+            self.payment_id == other.payment_id &&
+            self.memo == other.memo
+    }
+}
+
 /// `OwnCoin` is a representation of `Coin` with its respective metadata.
 #[derive(Debug, Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
 pub struct OwnCoin {
@@ -109,10 +230,53 @@ pub struct OwnCoin {
 impl OwnCoin {
     /// Derive the [`Nullifier`] for this [`OwnCoin`]
     pub fn nullifier(&self) -> Nullifier {
-        Nullifier::from(poseidon_hash([self.secret.inner(), self.coin.inner()]))
+        Nullifier::derive(&self.secret, &self.coin)
+    }
+
+    /// Find which spend hook encumbers this [`OwnCoin`], using the given
+    /// registry of known hooked functions.
+    pub fn spend_hook(&self, registry: &SpendHookRegistry) -> CoinSpendHook {
+        if self.note.spend_hook == FuncId::none() {
+            return CoinSpendHook::None
+        }
+
+        match registry.lookup(&self.note.spend_hook) {
+            Some((func, name)) => CoinSpendHook::Known {
+                name: name.to_string(),
+                hook: SpendHook::new(func, self.note.user_data),
+            },
+            None => CoinSpendHook::Unknown(self.note.spend_hook),
+        }
     }
 }
 
+/// Spend hook encumbering an [`OwnCoin`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CoinSpendHook {
+    /// The coin can be spent by a plain `Money::Transfer`
+    None,
+    /// The coin can only be spent through a known hooked function
+    Known { name: String, hook: SpendHook },
+    /// The coin can only be spent through a function missing from the registry
+    Unknown(FuncId),
+}
+
+/// Register the Money functions coins can be hooked to
+pub fn register_spend_hooks(registry: &mut SpendHookRegistry) {
+    registry.register(
+        "Money::VestingClaim",
+        FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::VestingClaimV1 as u8 },
+    );
+    registry.register(
+        "Money::HtlcSpend",
+        FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::HtlcSpendV1 as u8 },
+    );
+    registry.register(
+        "Money::GroupSpend",
+        FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::GroupSpendV1 as u8 },
+    );
+}
+
 impl Hash for OwnCoin {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.coin.inner().to_repr().hash(state);
@@ -0,0 +1,109 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    zk::{Proof, ProvingKey},
+    zkas::ZkBinary,
+    ClientFailed, Result,
+};
+use darkfi_sdk::{
+    crypto::{smt::PathFp, Blind, FuncId, MerkleNode, SecretKey},
+    pasta::pallas,
+};
+use log::info;
+use rand::rngs::OsRng;
+
+use crate::{
+    client::{
+        transfer_v1::{proof::create_transfer_burn_proof, TransferCallInput},
+        OwnCoin,
+    },
+    model::{Input, MoneyNftBurnParamsV1},
+};
+
+pub struct NftBurnCallDebris {
+    pub params: MoneyNftBurnParamsV1,
+    pub proofs: Vec<Proof>,
+    /// Secret key signing the burn
+    pub signature_secret: SecretKey,
+}
+
+/// Struct holding necessary information to build a `Money::NftBurnV1` contract call.
+pub struct NftBurnCallBuilder {
+    /// The NFT coin being burned
+    pub coin: OwnCoin,
+    /// Merkle path in the Money Merkle tree for `coin`
+    pub merkle_path: Vec<MerkleNode>,
+    /// Root of the Money SMT of frozen tokens
+    pub frozen_root: pallas::Base,
+    /// Path in the frozen tokens SMT showing the NFT is not frozen
+    pub frozen_path: PathFp,
+
+    /// `Burn_V1` zkas circuit ZkBinary
+    pub burn_zkbin: ZkBinary,
+    /// Proving key for the `Burn_V1` zk circuit
+    pub burn_pk: ProvingKey,
+}
+
+impl NftBurnCallBuilder {
+    pub fn build(self) -> Result<NftBurnCallDebris> {
+        info!("Building Money::NftBurnV1 contract call");
+        if self.coin.note.value != 1 || self.coin.note.spend_hook != FuncId::none() {
+            return Err(ClientFailed::VerifyError("Coin is not a burnable NFT".to_string()).into())
+        }
+
+        let token_id = self.coin.note.token_id;
+        let value_blind = Blind::random(&mut OsRng);
+        let token_blind = Blind::random(&mut OsRng);
+        let signature_secret = SecretKey::random(&mut OsRng);
+
+        let input = TransferCallInput {
+            coin: self.coin,
+            merkle_path: self.merkle_path,
+            user_data_blind: Blind::random(&mut OsRng),
+            frozen_root: self.frozen_root,
+            frozen_path: self.frozen_path,
+        };
+
+        let (proof, public_inputs) = create_transfer_burn_proof(
+            &self.burn_zkbin,
+            &self.burn_pk,
+            &input,
+            value_blind,
+            token_blind,
+            signature_secret,
+        )?;
+
+        let params = MoneyNftBurnParamsV1 {
+            input: Input {
+                value_commit: public_inputs.value_commit,
+                token_commit: public_inputs.token_commit,
+                nullifier: public_inputs.nullifier,
+                merkle_root: public_inputs.merkle_root,
+                frozen_root: public_inputs.frozen_root,
+                user_data_enc: public_inputs.user_data_enc,
+                signature_public: public_inputs.signature_public,
+            },
+            value_blind,
+            token_id,
+            token_blind,
+        };
+
+        Ok(NftBurnCallDebris { params, proofs: vec![proof], signature_secret })
+    }
+}
@@ -0,0 +1,130 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+use darkfi_sdk::{
+    crypto::{
+        note::AeadEncryptedNote, pasta_prelude::*, BaseBlind, Blind, FuncId, FuncRef, PublicKey,
+        MONEY_CONTRACT_ID,
+    },
+    pasta::pallas,
+};
+use log::info;
+use rand::rngs::OsRng;
+
+use crate::{
+    client::MoneyNote,
+    model::{CoinAttributes, MoneyNftMintParamsV1, TokenAttributes},
+    MoneyFunction,
+};
+
+/// Auth parent of all NFT token IDs
+pub fn nft_auth_parent() -> FuncId {
+    FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::NftMintV1 as u8 }
+        .to_func_id()
+}
+
+/// Token attributes of the NFT committing to `metadata`
+pub fn nft_token_attrs(metadata: pallas::Base, blind: BaseBlind) -> TokenAttributes {
+    TokenAttributes { auth_parent: nft_auth_parent(), user_data: metadata, blind }
+}
+
+pub struct NftMintCallDebris {
+    pub params: MoneyNftMintParamsV1,
+    pub proofs: Vec<Proof>,
+    /// Attributes of the minted coin
+    pub coin_attrs: CoinAttributes,
+}
+
+/// Struct holding necessary information to build a `Money::NftMintV1` contract call.
+pub struct NftMintCallBuilder {
+    /// Recipient of the NFT
+    pub recipient: PublicKey,
+    /// Commitment to the NFT's metadata
+    pub metadata: pallas::Base,
+    /// Blind of the NFT's token ID
+    pub token_blind: BaseBlind,
+
+    /// `NftMint_V1` zkas circuit ZkBinary
+    pub mint_zkbin: ZkBinary,
+    /// Proving key for the `NftMint_V1` zk circuit,
+    pub mint_pk: ProvingKey,
+}
+
+impl NftMintCallBuilder {
+    pub fn build(&self) -> Result<NftMintCallDebris> {
+        info!("Building Money::NftMintV1 contract call");
+        let token_attrs = nft_token_attrs(self.metadata, self.token_blind);
+        let token_id = token_attrs.to_token_id();
+
+        let coin_attrs = CoinAttributes {
+            public_key: self.recipient,
+            value: 1,
+            token_id,
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+            blind: Blind::random(&mut OsRng),
+        };
+
+        let (public_x, public_y) = coin_attrs.public_key.xy();
+
+        let prover_witnesses = vec![
+            // Coin attributes
+            Witness::Base(Value::known(public_x)),
+            Witness::Base(Value::known(public_y)),
+            Witness::Base(Value::known(coin_attrs.spend_hook.inner())),
+            Witness::Base(Value::known(coin_attrs.user_data)),
+            Witness::Base(Value::known(coin_attrs.blind.inner())),
+            // Token attributes
+            Witness::Base(Value::known(token_attrs.auth_parent.inner())),
+            Witness::Base(Value::known(token_attrs.user_data)),
+            Witness::Base(Value::known(token_attrs.blind.inner())),
+        ];
+
+        let coin = coin_attrs.to_coin();
+
+        let public_inputs =
+            vec![token_attrs.auth_parent.inner(), self.metadata, token_id.inner(), coin.inner()];
+
+        let circuit = ZkCircuit::new(prover_witnesses, &self.mint_zkbin);
+        let proof = Proof::create(&self.mint_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        // Create the note
+        let note = MoneyNote {
+            value: coin_attrs.value,
+            token_id,
+            spend_hook: coin_attrs.spend_hook,
+            user_data: coin_attrs.user_data,
+            coin_blind: coin_attrs.blind,
+            value_blind: Blind::random(&mut OsRng),
+            token_blind: Blind::ZERO,
+            payment_id: None,
+            memo: vec![],
+        };
+
+        let note = AeadEncryptedNote::encrypt(&note, &self.recipient, &mut OsRng)?;
+
+        let params = MoneyNftMintParamsV1 { token_id, metadata: self.metadata, coin, note };
+        let debris = NftMintCallDebris { params, proofs: vec![proof], coin_attrs };
+        Ok(debris)
+    }
+}
@@ -0,0 +1,161 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use core::str::FromStr;
+
+use darkfi_sdk::{
+    crypto::{
+        domains::{self, domain},
+        pasta_prelude::{Field, PrimeField},
+        poseidon_hash, BaseBlind, Blind,
+    },
+    error::ContractError,
+    pasta::pallas,
+};
+use darkfi_serial::{async_trait, SerialDecodable, SerialEncodable};
+use lazy_static::lazy_static;
+use rand::{CryptoRng, RngCore};
+
+lazy_static! {
+    /// Derivation prefix for coin blinds committing to a `PaymentId`
+    pub static ref PAYMENT_ID_PREFIX: pallas::Base = domain(domains::PAYMENT_ID);
+}
+
+/// Recipient-supplied payment identifier, e.g. an invoice nonce.
+/// Merchants hand this out along with their address, and the sender
+/// attaches it to the output paying them.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct PaymentId(pallas::Base);
+
+impl PaymentId {
+    /// Generate a new random `PaymentId`
+    pub fn random(rng: &mut (impl CryptoRng + RngCore)) -> Self {
+        Self(pallas::Base::random(rng))
+    }
+
+    /// Get the inner `pallas::Base` element.
+    pub fn inner(&self) -> pallas::Base {
+        self.0
+    }
+
+    /// Create a `PaymentId` object from given bytes, erroring if the
+    /// input bytes are noncanonical.
+    pub fn from_bytes(x: [u8; 32]) -> Result<Self, ContractError> {
+        match pallas::Base::from_repr(x).into() {
+            Some(v) => Ok(Self(v)),
+            None => Err(ContractError::IoError(
+                "Failed to instantiate PaymentId from bytes".to_string(),
+            )),
+        }
+    }
+
+    /// Convert the `PaymentId` type into 32 raw bytes
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_repr()
+    }
+}
+
+darkfi_sdk::fp_from_bs58!(PaymentId);
+darkfi_sdk::fp_to_bs58!(PaymentId);
+darkfi_sdk::ty_from_fp!(PaymentId);
+
+/// Opening of a `PaymentId` commitment, carried inside the encrypted
+/// `MoneyNote`. The output coin blind is derived from it, so the coin
+/// committed on-chain binds the payment ID, and the recipient can
+/// check that binding after decrypting the note.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct PaymentIdOpening {
+    /// The attached payment ID
+    pub payment_id: PaymentId,
+    /// Random nonce keeping the derived coin blind unpredictable
+    pub nonce: pallas::Base,
+}
+
+impl PaymentIdOpening {
+    /// Create a new opening for the given `PaymentId` with a random nonce
+    pub fn new(payment_id: PaymentId, rng: &mut (impl CryptoRng + RngCore)) -> Self {
+        Self { payment_id, nonce: pallas::Base::random(rng) }
+    }
+
+    /// Derive the coin blind committing to this opening
+    pub fn coin_blind(&self) -> BaseBlind {
+        Blind(poseidon_hash([*PAYMENT_ID_PREFIX, self.payment_id.inner(), self.nonce]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkfi_sdk::crypto::{FuncId, ScalarBlind};
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::{client::MoneyNote, model::TokenId};
+
+    fn note_with(payment_id: Option<PaymentIdOpening>, coin_blind: BaseBlind) -> MoneyNote {
+        MoneyNote {
+            value: 42,
+            token_id: TokenId::from(pallas::Base::random(&mut OsRng)),
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+            coin_blind,
+            value_blind: ScalarBlind::random(&mut OsRng),
+            token_blind: BaseBlind::random(&mut OsRng),
+            payment_id,
+            memo: vec![],
+        }
+    }
+
+    #[test]
+    fn coin_blind_derivation() {
+        let opening = PaymentIdOpening::new(PaymentId::random(&mut OsRng), &mut OsRng);
+
+        let expected =
+            poseidon_hash([pallas::Base::from(70), opening.payment_id.inner(), opening.nonce]);
+        assert_eq!(opening.coin_blind().inner(), expected);
+
+        // The nonce keeps blinds of the same payment ID apart
+        let other = PaymentIdOpening::new(opening.payment_id, &mut OsRng);
+        assert_ne!(opening.coin_blind(), other.coin_blind());
+    }
+
+    #[test]
+    fn verified_payment_id() {
+        let opening = PaymentIdOpening::new(PaymentId::random(&mut OsRng), &mut OsRng);
+
+        let note = note_with(Some(opening), opening.coin_blind());
+        assert_eq!(note.verified_payment_id(), Some(opening.payment_id));
+
+        // No payment ID attached
+        let note = note_with(None, opening.coin_blind());
+        assert_eq!(note.verified_payment_id(), None);
+
+        // Wrong payment ID
+        let wrong_pid = PaymentIdOpening { payment_id: PaymentId::random(&mut OsRng), ..opening };
+        let note = note_with(Some(wrong_pid), opening.coin_blind());
+        assert_eq!(note.verified_payment_id(), None);
+
+        // Wrong nonce
+        let wrong_nonce = PaymentIdOpening { nonce: pallas::Base::random(&mut OsRng), ..opening };
+        let note = note_with(Some(wrong_nonce), opening.coin_blind());
+        assert_eq!(note.verified_payment_id(), None);
+
+        // Coin blind not derived from the opening
+        let note = note_with(Some(opening), BaseBlind::random(&mut OsRng));
+        assert_eq!(note.verified_payment_id(), None);
+    }
+}
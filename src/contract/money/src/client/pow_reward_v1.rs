@@ -23,7 +23,9 @@ use darkfi::{
 };
 use darkfi_sdk::{
     blockchain::expected_reward,
-    crypto::{note::AeadEncryptedNote, pasta_prelude::*, Blind, FuncId, PublicKey, SecretKey},
+    crypto::{
+        commitment, note::AeadEncryptedNote, pasta_prelude::*, Blind, FuncId, PublicKey, SecretKey,
+    },
     pasta::pallas,
 };
 use log::{debug, info};
@@ -52,11 +54,11 @@ pub struct PoWRewardRevealed {
 
 impl PoWRewardRevealed {
     pub fn to_vec(&self) -> Vec<pallas::Base> {
-        let valcom_coords = self.value_commit.to_affine().coordinates().unwrap();
+        let (valcom_x, valcom_y) = commitment::xy(&self.value_commit);
 
         // NOTE: It's important to keep these in the same order
         // as the `constrain_instance` calls in the zkas code.
-        vec![self.coin.inner(), *valcom_coords.x(), *valcom_coords.y(), self.token_commit]
+        vec![self.coin.inner(), valcom_x, valcom_y, self.token_commit]
     }
 }
 
@@ -134,6 +136,7 @@ impl PoWRewardCallBuilder {
             coin_blind,
             value_blind,
             token_blind,
+            payment_id: None,
             memo: vec![],
         };
 
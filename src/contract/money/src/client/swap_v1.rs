@@ -16,7 +16,18 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-//! This API is crufty. Please rework it into something nice to read and nice to use.
+//! Client API for building `Money::OtcSwapV1` contract calls.
+//!
+//! An atomic swap is built in two steps. The initiating party uses
+//! [`SwapCallBuilder::build_partial`] to create their half of the call,
+//! which is a [`PartialSwapData`] that can be sent to the counterparty.
+//! The counterparty builds their own half with the mirrored parameters
+//! returned by [`PartialSwapData::counterparty_builder`], and then calls
+//! [`PartialSwapData::complete`] to verify the halves match and to join
+//! them into the full set of call parameters and proofs. Both parties
+//! then sign the transaction with their ephemeral signature secrets.
+
+use std::fmt;
 
 use darkfi::{
     zk::{Proof, ProvingKey},
@@ -25,12 +36,12 @@ use darkfi::{
 };
 use darkfi_sdk::{
     crypto::{
-        note::AeadEncryptedNote, pasta_prelude::*, BaseBlind, Blind, FuncId, MerkleTree, PublicKey,
-        ScalarBlind, SecretKey,
+        note::AeadEncryptedNote, pasta_prelude::*, smt::SmtMemoryFp, BaseBlind, Blind, FuncId,
+        MerkleTree, PublicKey, ScalarBlind, SecretKey,
     },
     pasta::pallas,
 };
-use darkfi_serial::serialize;
+use darkfi_serial::{async_trait, serialize, SerialDecodable, SerialEncodable};
 use log::{debug, info};
 use rand::rngs::OsRng;
 
@@ -51,6 +62,123 @@ pub struct SwapCallDebris {
     pub signature_secret: SecretKey,
 }
 
+/// Half of the swap data, includes the coin that is supposed to be sent,
+/// and the coin that is supposed to be received.
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct PartialSwapData {
+    /// Call parameters holding a single input and a single output
+    pub params: MoneyTransferParamsV1,
+    /// The burn and mint proofs for the input and output
+    pub proofs: Vec<Proof>,
+    /// Values being sent and received by the initiating party
+    pub value_pair: (u64, u64),
+    /// Token IDs being sent and received by the initiating party
+    pub token_pair: (TokenId, TokenId),
+    /// Value commitment blinds used by the initiating party
+    pub value_blinds: [ScalarBlind; 2],
+    /// Token commitment blinds used by the initiating party
+    pub token_blinds: [BaseBlind; 2],
+}
+
+impl fmt::Display for PartialSwapData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s =
+            format!(
+            "{:#?}\nValue pair: {}:{}\nToken pair: {}:{}\nValue blinds: {:?}\nToken blinds: {:?}\n",
+            self.params, self.value_pair.0, self.value_pair.1, self.token_pair.0, self.token_pair.1,
+            self.value_blinds, self.token_blinds,
+        );
+
+        write!(f, "{}", s)
+    }
+}
+
+impl PartialSwapData {
+    /// Create the [`SwapCallBuilder`] for the counterparty's half of the swap.
+    /// The values, token IDs and commitment blinds are mirrored from this
+    /// half, so the resulting halves are guaranteed to line up.
+    #[allow(clippy::too_many_arguments)]
+    pub fn counterparty_builder(
+        &self,
+        pubkey: PublicKey,
+        coin: OwnCoin,
+        tree: MerkleTree,
+        frozen_tokens: SmtMemoryFp,
+        mint_zkbin: ZkBinary,
+        mint_pk: ProvingKey,
+        burn_zkbin: ZkBinary,
+        burn_pk: ProvingKey,
+    ) -> SwapCallBuilder {
+        SwapCallBuilder {
+            pubkey,
+            value_send: self.value_pair.1,
+            token_id_send: self.token_pair.1,
+            value_recv: self.value_pair.0,
+            token_id_recv: self.token_pair.0,
+            user_data_blind_send: Blind::random(&mut OsRng),
+            spend_hook_recv: FuncId::none(),
+            user_data_recv: pallas::Base::ZERO,
+            value_blinds: [self.value_blinds[1], self.value_blinds[0]],
+            token_blinds: [self.token_blinds[1], self.token_blinds[0]],
+            coin,
+            tree,
+            frozen_tokens,
+            mint_zkbin,
+            mint_pk,
+            burn_zkbin,
+            burn_pk,
+        }
+    }
+
+    /// Verify the counterparty's half against this one and join them into
+    /// the full `Money::OtcSwapV1` call parameters and proofs. The returned
+    /// debris carries the counterparty's signature secret.
+    pub fn complete(&self, other: SwapCallDebris) -> Result<SwapCallDebris> {
+        debug!("Joining halves of Money::OtcSwapV1 contract call");
+        if self.params.inputs.len() != 1 || self.params.outputs.len() != 1 || self.proofs.len() != 2
+        {
+            return Err(ClientFailed::VerifyError("Malformed partial swap data".to_string()).into())
+        }
+
+        if other.params.inputs.len() != 1 ||
+            other.params.outputs.len() != 1 ||
+            other.proofs.len() != 2
+        {
+            return Err(ClientFailed::VerifyError("Malformed swap half".to_string()).into())
+        }
+
+        // Our input is the counterparty's output and vice versa, so
+        // the commitments must match crosswise.
+        let (ours, theirs) = (&self.params, &other.params);
+        if ours.inputs[0].value_commit != theirs.outputs[0].value_commit ||
+            ours.outputs[0].value_commit != theirs.inputs[0].value_commit
+        {
+            return Err(ClientFailed::VerifyError("Value commitments mismatch".to_string()).into())
+        }
+
+        if ours.inputs[0].token_commit != theirs.outputs[0].token_commit ||
+            ours.outputs[0].token_commit != theirs.inputs[0].token_commit
+        {
+            return Err(ClientFailed::VerifyError("Token commitments mismatch".to_string()).into())
+        }
+
+        let params = MoneyTransferParamsV1 {
+            inputs: vec![ours.inputs[0].clone(), theirs.inputs[0].clone()],
+            outputs: vec![ours.outputs[0].clone(), theirs.outputs[0].clone()],
+        };
+
+        // Burn proofs come first, followed by the mint proofs
+        let proofs = vec![
+            self.proofs[0].clone(),
+            other.proofs[0].clone(),
+            self.proofs[1].clone(),
+            other.proofs[1].clone(),
+        ];
+
+        Ok(SwapCallDebris { params, proofs, signature_secret: other.signature_secret })
+    }
+}
+
 /// Struct holding necessary information to build a `Money::OtcSwapV1` contract call.
 /// This is used to build half of the swap transaction, so both parties have to build
 /// their halves and combine them.
@@ -82,6 +210,8 @@ pub struct SwapCallBuilder {
     pub coin: OwnCoin,
     /// Merkle tree of coins used to create inclusion proofs
     pub tree: MerkleTree,
+    /// SMT of frozen tokens used to prove the input token is not frozen
+    pub frozen_tokens: SmtMemoryFp,
     /// `Mint_V1` zkas circuit ZkBinary
     pub mint_zkbin: ZkBinary,
     /// Proving key for the `Mint_V1` zk circuit
@@ -112,14 +242,16 @@ impl SwapCallBuilder {
             coin: self.coin.clone(),
             merkle_path: self.tree.witness(self.coin.leaf_position, 0).unwrap(),
             user_data_blind: self.user_data_blind_send,
+            frozen_root: self.frozen_tokens.root(),
+            frozen_path: self.frozen_tokens.prove_membership(&self.token_id_send.inner()),
         };
 
         let output = TransferCallOutput {
             public_key: self.pubkey,
             value: self.value_recv,
             token_id: self.token_id_recv,
-            spend_hook: FuncId::none(),
-            user_data: pallas::Base::ZERO,
+            spend_hook: self.spend_hook_recv,
+            user_data: self.user_data_recv,
             blind: Blind::random(&mut OsRng),
         };
 
@@ -145,6 +277,7 @@ impl SwapCallBuilder {
             token_commit: public_inputs.token_commit,
             nullifier: public_inputs.nullifier,
             merkle_root: public_inputs.merkle_root,
+            frozen_root: public_inputs.frozen_root,
             user_data_enc: public_inputs.user_data_enc,
             signature_public: public_inputs.signature_public,
         });
@@ -178,6 +311,7 @@ impl SwapCallBuilder {
             value_blind: self.value_blinds[1],
             token_blind: self.token_blinds[1],
             // Here we store our secret key we use for signing
+            payment_id: None,
             memo: serialize(&signature_secret),
         };
 
@@ -195,4 +329,20 @@ impl SwapCallBuilder {
         let debris = SwapCallDebris { params, proofs, signature_secret };
         Ok(debris)
     }
+
+    /// Build the initiating half of the swap, packaged so it can be handed
+    /// over to the counterparty. The signature secret is kept in the memo
+    /// of our output note, so it can be recovered later for signing.
+    pub fn build_partial(&self) -> Result<PartialSwapData> {
+        let debris = self.build()?;
+
+        Ok(PartialSwapData {
+            params: debris.params,
+            proofs: debris.proofs,
+            value_pair: (self.value_send, self.value_recv),
+            token_pair: (self.token_id_send, self.token_id_recv),
+            value_blinds: self.value_blinds,
+            token_blinds: self.token_blinds,
+        })
+    }
 }
@@ -0,0 +1,62 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{Blind, ScalarBlind, ValueOpeningProof},
+    pasta::pallas,
+};
+use darkfi_serial::{async_trait, SerialDecodable, SerialEncodable};
+use rand::rngs::OsRng;
+
+use crate::model::TokenId;
+
+/// Proof that the total supply of a token minted through
+/// `Money::AuthTokenMint` equals `supply`.
+///
+/// Every authorized mint adds its value commitment to the token's
+/// accumulator in the Money state. The mint authority knows the value
+/// blinds of its mints, so it can open the accumulator to the total
+/// supply, without revealing any individual mint.
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct TokenSupplyProof {
+    pub token_id: TokenId,
+    pub supply: u64,
+    pub opening: ValueOpeningProof,
+}
+
+impl TokenSupplyProof {
+    /// Create the proof from the value and value blind of every mint of
+    /// the token, as returned in `AuthTokenMintCallDebris`.
+    pub fn create(token_id: TokenId, mints: &[(u64, ScalarBlind)]) -> Self {
+        let mut supply = 0;
+        let mut blind = Blind::ZERO;
+        for (value, value_blind) in mints {
+            supply += value;
+            blind += *value_blind;
+        }
+
+        let opening = ValueOpeningProof::create(supply, blind, &mut OsRng);
+        Self { token_id, supply, opening }
+    }
+
+    /// Verify the proof against the token's accumulated supply commitment,
+    /// as read from the Money state.
+    pub fn verify(&self, supply_commit: &pallas::Point) -> bool {
+        self.opening.verify(supply_commit, self.supply)
+    }
+}
@@ -0,0 +1,140 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+use darkfi_sdk::{
+    crypto::{commitment, Keypair, ScalarBlind},
+    pasta::pallas,
+};
+use log::info;
+use rand::rngs::OsRng;
+
+use crate::model::{MoneyTokenSupplyCapParamsV1, TokenAttributes};
+
+pub struct TokenSupplyCapCallDebris {
+    pub params: MoneyTokenSupplyCapParamsV1,
+    pub proofs: Vec<Proof>,
+}
+
+/// Struct holding necessary information to build a `Money::TokenSupplyCapV1` contract call.
+pub struct TokenSupplyCapCallBuilder {
+    /// Mint authority keypair
+    pub mint_keypair: Keypair,
+    pub token_attrs: TokenAttributes,
+    /// Hard cap to set on the token's total supply
+    pub supply_cap: u64,
+    /// Total supply minted so far along with the sum of the mints' value
+    /// blinds, or `None` if the token hasn't been minted yet
+    pub supply: Option<(u64, ScalarBlind)>,
+    /// `TokenFreeze_V1` zkas circuit ZkBinary, used when there's no supply yet
+    pub freeze_zkbin: ZkBinary,
+    /// Proving key for the `TokenFreeze_V1` zk circuit
+    pub freeze_pk: ProvingKey,
+    /// `TokenSupplyCap_V1` zkas circuit ZkBinary
+    pub supply_cap_zkbin: ZkBinary,
+    /// Proving key for the `TokenSupplyCap_V1` zk circuit
+    pub supply_cap_pk: ProvingKey,
+}
+
+impl TokenSupplyCapCallBuilder {
+    pub fn build(&self) -> Result<TokenSupplyCapCallDebris> {
+        info!("Building Money::TokenSupplyCapV1 contract call");
+
+        let mint_pubkey = self.mint_keypair.public;
+        let token_id = self.token_attrs.to_token_id();
+
+        let proof = match self.supply {
+            Some((supply, supply_blind)) => create_token_supply_cap_proof(
+                &self.supply_cap_zkbin,
+                &self.supply_cap_pk,
+                &self.mint_keypair,
+                &self.token_attrs,
+                supply,
+                supply_blind,
+                self.supply_cap,
+            )?,
+
+            // Nothing was minted yet, so we only have to enforce
+            // the correct token ID derivation inside ZK.
+            None => {
+                let prover_witnesses = vec![
+                    Witness::Base(Value::known(self.token_attrs.auth_parent.inner())),
+                    Witness::Base(Value::known(self.token_attrs.blind.inner())),
+                    Witness::Base(Value::known(self.mint_keypair.secret.inner())),
+                ];
+                let public_inputs = vec![mint_pubkey.x(), mint_pubkey.y(), token_id.inner()];
+                let circuit = ZkCircuit::new(prover_witnesses, &self.freeze_zkbin);
+                Proof::create(&self.freeze_pk, &[circuit], &public_inputs, &mut OsRng)?
+            }
+        };
+
+        let params = MoneyTokenSupplyCapParamsV1 {
+            mint_public: mint_pubkey,
+            token_id,
+            supply_cap: self.supply_cap,
+        };
+        let debris = TokenSupplyCapCallDebris { params, proofs: vec![proof] };
+        Ok(debris)
+    }
+}
+
+/// Create a `TokenSupplyCap_V1` proof that the total `supply` of a token,
+/// committed to with `supply_blind`, doesn't exceed `supply_cap`.
+pub fn create_token_supply_cap_proof(
+    zkbin: &ZkBinary,
+    pk: &ProvingKey,
+    mint_keypair: &Keypair,
+    token_attrs: &TokenAttributes,
+    supply: u64,
+    supply_blind: ScalarBlind,
+    supply_cap: u64,
+) -> Result<Proof> {
+    let prover_witnesses = vec![
+        // Token attributes
+        Witness::Base(Value::known(token_attrs.auth_parent.inner())),
+        Witness::Base(Value::known(token_attrs.blind.inner())),
+        // Secret key used by mint
+        Witness::Base(Value::known(mint_keypair.secret.inner())),
+        // Total supply and its blind
+        Witness::Base(Value::known(pallas::Base::from(supply))),
+        Witness::Scalar(Value::known(supply_blind.inner())),
+        // Hard cap on the supply
+        Witness::Base(Value::known(pallas::Base::from(supply_cap))),
+    ];
+
+    let supply_commit = commitment::commit(supply, supply_blind);
+    let (supply_x, supply_y) = commitment::xy(&supply_commit);
+
+    let public_inputs = vec![
+        mint_keypair.public.x(),
+        mint_keypair.public.y(),
+        token_attrs.to_token_id().inner(),
+        supply_x,
+        supply_y,
+        pallas::Base::from(supply_cap),
+    ];
+
+    let circuit = ZkCircuit::new(prover_witnesses, zkbin);
+    let proof = Proof::create(pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+    Ok(proof)
+}
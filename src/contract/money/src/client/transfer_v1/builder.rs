@@ -16,24 +16,25 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 use darkfi::{
-    zk::{Proof, ProvingKey},
+    zk::{Proof, ProvingKey, ProvingSession},
     zkas::ZkBinary,
-    Result,
+    ClientFailed, Result,
 };
 use darkfi_sdk::{
     crypto::{
-        note::AeadEncryptedNote, pasta_prelude::*, BaseBlind, Blind, MerkleNode, ScalarBlind,
-        SecretKey,
+        note::AeadEncryptedNote, pasta_prelude::*, smt::PathFp, BaseBlind, Blind, MerkleNode,
+        PublicKey, ScalarBlind, SecretKey,
     },
     pasta::pallas,
 };
-use log::{debug, info};
+use log::{debug, error, info};
 use rand::rngs::OsRng;
 
-use super::proof::{create_transfer_burn_proof, create_transfer_mint_proof};
+use super::proof::{transfer_burn_circuit, transfer_mint_circuit};
 use crate::{
-    client::{compute_remainder_blind, MoneyNote, OwnCoin, TokenId},
+    client::{compute_remainder_blind, MoneyNote, OwnCoin, PaymentIdOpening, TokenId},
     model::{CoinAttributes, Input, MoneyTransferParamsV1, Output},
+    MONEY_NOTE_MEMO_MAX_LEN,
 };
 
 /// Struct holding necessary information to build a `Money::TransferV1` contract call.
@@ -44,6 +45,17 @@ pub struct TransferCallBuilder {
     pub inputs: Vec<TransferCallInput>,
     /// Anonymous outputs
     pub outputs: Vec<TransferCallOutput>,
+    /// Optional payment ID openings, indexed like `outputs`.
+    /// An output with an opening must use the coin blind derived from it.
+    pub payment_ids: Vec<Option<PaymentIdOpening>>,
+    /// Optional recipient viewing keys, indexed like `outputs`.
+    /// An output with a viewing key has its note encrypted to it
+    /// rather than to the output public key.
+    pub viewing_keys: Vec<Option<PublicKey>>,
+    /// Optional memos attached to the output notes, indexed like `outputs`.
+    /// Each must be at most [`MONEY_NOTE_MEMO_MAX_LEN`] bytes long, and is
+    /// zero-padded to that length in the note.
+    pub memos: Vec<Vec<u8>>,
     /// `Mint_V1` zkas circuit ZkBinary
     pub mint_zkbin: ZkBinary,
     /// Proving key for the `Mint_V1` zk circuit
@@ -68,6 +80,10 @@ pub struct TransferCallInput {
     // In the DAO all inputs must have the same user_data_enc and use the same blind
     // So support allowing the user to set their own blind.
     pub user_data_blind: BaseBlind,
+    /// Root of the Money SMT of frozen tokens
+    pub frozen_root: pallas::Base,
+    /// Path in the frozen tokens SMT showing the coin's token is not frozen
+    pub frozen_path: PathFp,
 }
 
 pub type TransferCallOutput = CoinAttributes;
@@ -77,9 +93,18 @@ impl TransferCallBuilder {
         debug!("Building Money::TransferV1 contract call");
         assert!(self.clear_inputs.len() + self.inputs.len() > 0);
 
+        for (i, memo) in self.memos.iter().enumerate() {
+            if memo.len() > MONEY_NOTE_MEMO_MAX_LEN {
+                error!("Memo for output {} is {} bytes long", i, memo.len());
+                return Err(ClientFailed::InvalidMemo(memo.len()).into())
+            }
+        }
+
         let mut params = MoneyTransferParamsV1 { inputs: vec![], outputs: vec![] };
         let mut signature_secrets = vec![];
-        let mut proofs = vec![];
+        // The proofs don't depend on each other, so they are queued here
+        // and created concurrently once all the circuits are built.
+        let mut session = ProvingSession::new();
 
         let token_blind = BaseBlind::random(&mut OsRng);
         let mut input_blinds = vec![];
@@ -93,26 +118,25 @@ impl TransferCallBuilder {
             let signature_secret = SecretKey::random(&mut OsRng);
             signature_secrets.push(signature_secret);
 
-            info!("Creating transfer burn proof for input {}", i);
-            let (proof, public_inputs) = create_transfer_burn_proof(
+            info!("Building transfer burn circuit for input {}", i);
+            let (circuit, public_inputs) = transfer_burn_circuit(
                 &self.burn_zkbin,
-                &self.burn_pk,
                 input,
                 value_blind,
                 token_blind,
                 signature_secret,
-            )?;
+            );
+            session.push(&self.burn_pk, circuit, public_inputs.to_vec());
 
             params.inputs.push(Input {
                 value_commit: public_inputs.value_commit,
                 token_commit: public_inputs.token_commit,
                 nullifier: public_inputs.nullifier,
                 merkle_root: public_inputs.merkle_root,
+                frozen_root: public_inputs.frozen_root,
                 user_data_enc: public_inputs.user_data_enc,
                 signature_public: public_inputs.signature_public,
             });
-
-            proofs.push(proof);
         }
 
         // This value_blind calc assumes there will always be at least a single output
@@ -129,21 +153,32 @@ impl TransferCallBuilder {
 
             output_blinds.push(value_blind);
 
-            info!("Creating transfer mint proof for output {}", i);
-            let (proof, public_inputs) = create_transfer_mint_proof(
+            let payment_id = self.payment_ids.get(i).copied().flatten();
+            if let Some(ref opening) = payment_id {
+                if opening.coin_blind() != output.blind {
+                    error!("Coin blind of output {} doesn't commit to its payment ID", i);
+                    return Err(ClientFailed::InvalidPaymentId(i).into())
+                }
+            }
+
+            info!("Building transfer mint circuit for output {}", i);
+            let (circuit, public_inputs) = transfer_mint_circuit(
                 &self.mint_zkbin,
-                &self.mint_pk,
                 output,
                 value_blind,
                 token_blind,
                 output.spend_hook,
                 output.user_data,
                 output.blind,
-            )?;
-
-            proofs.push(proof);
+            );
+            session.push(&self.mint_pk, circuit, public_inputs.to_vec());
 
             // Encrypted note
+            // Memos are padded to a fixed size, so the encrypted
+            // notes don't reveal how long they are.
+            let mut memo = self.memos.get(i).cloned().unwrap_or_default();
+            memo.resize(MONEY_NOTE_MEMO_MAX_LEN, 0);
+
             let note = MoneyNote {
                 value: output.value,
                 token_id: output.token_id,
@@ -152,10 +187,12 @@ impl TransferCallBuilder {
                 coin_blind: output.blind,
                 value_blind,
                 token_blind,
-                memo: vec![],
+                payment_id,
+                memo,
             };
 
-            let encrypted_note = AeadEncryptedNote::encrypt(&note, &output.public_key, &mut OsRng)?;
+            let note_key = self.viewing_keys.get(i).copied().flatten().unwrap_or(output.public_key);
+            let encrypted_note = AeadEncryptedNote::encrypt(&note, &note_key, &mut OsRng)?;
             output_notes.push(note);
 
             params.outputs.push(Output {
@@ -166,6 +203,9 @@ impl TransferCallBuilder {
             });
         }
 
+        // Proofs come back in the order they were queued: inputs, then outputs
+        let proofs = session.prove()?;
+
         // Now we should have all the params, zk proofs, and signature secrets.
         // We return it all and let the caller deal with it.
         let secrets = TransferCallSecrets {
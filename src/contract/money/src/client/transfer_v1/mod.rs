@@ -15,16 +15,22 @@
  * You should have received a copy of the GNU Affero General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use darkfi::{zk::ProvingKey, zkas::ZkBinary, ClientFailed, Result};
+use darkfi::{zk::ProvingKey, zkas::ZkBinary, Result};
 use darkfi_sdk::{
-    crypto::{pasta_prelude::*, Blind, FuncId, Keypair, MerkleTree, PublicKey},
+    crypto::{
+        pasta_prelude::*, smt::SmtMemoryFp, Blind, FuncId, Keypair, MerkleTree, PublicKey,
+        ViewingKey,
+    },
     pasta::pallas,
 };
-use log::{debug, error};
+use log::debug;
 use rand::rngs::OsRng;
 
 use crate::{
-    client::OwnCoin,
+    client::{
+        coin_selection::{BranchAndBound, CoinSelection},
+        OwnCoin, PaymentId, PaymentIdOpening,
+    },
     model::{MoneyTransferParamsV1, TokenId},
 };
 
@@ -36,40 +42,25 @@ pub use builder::{
 
 pub(crate) mod proof;
 
-/// Select coins from `coins` of at least `min_value` in total.
-/// Different strategies can be used. This function uses the dumb strategy
-/// of selecting coins until we reach `min_value`.
+/// Select coins from `coins` of at least `min_value` in total, using the
+/// default [`BranchAndBound`] strategy.
 pub fn select_coins(coins: Vec<OwnCoin>, min_value: u64) -> Result<(Vec<OwnCoin>, u64)> {
-    let mut total_value = 0;
-    let mut selected = vec![];
-
-    for coin in coins {
-        if total_value >= min_value {
-            break
-        }
-
-        total_value += coin.note.value;
-        selected.push(coin);
-    }
-
-    if total_value < min_value {
-        error!("Not enough value to build tx inputs");
-        return Err(ClientFailed::NotEnoughValue(total_value).into())
-    }
-
-    let change_value = total_value - min_value;
-
-    Ok((selected, change_value))
+    BranchAndBound::default().select(coins, min_value)
 }
 
 /// Make a simple anonymous transfer call.
 ///
 /// * `keypair`: Caller's keypair
 /// * `recipient`: Recipient's public key
+/// * `recipient_viewing_key`: Optional recipient viewing key the note is encrypted to
 /// * `value`: Amount that we want to send to the recipient
 /// * `token_id`: Token ID that we want to send to the recipient
+/// * `payment_id`: Optional recipient-supplied payment ID to bind into the output
+/// * `memo`: Memo attached to the recipient's note, e.g. an invoice reference
 /// * `coins`: Set of `OwnCoin` we're given to use in this builder
+/// * `selection`: Strategy selecting the spent coins out of `coins`
 /// * `tree`: Merkle tree of coins used to create inclusion proofs
+/// * `frozen_tokens`: SMT of frozen tokens used to prove `token_id` is not frozen
 /// * `mint_zkbin`: `Mint_V1` zkas circuit ZkBinary
 /// * `mint_pk`: Proving key for the `Mint_V1` zk circuit
 /// * `burn_zkbin`: `Burn_V1` zkas circuit ZkBinary
@@ -84,10 +75,15 @@ pub fn select_coins(coins: Vec<OwnCoin>, min_value: u64) -> Result<(Vec<OwnCoin>
 pub fn make_transfer_call(
     keypair: Keypair,
     recipient: PublicKey,
+    recipient_viewing_key: Option<PublicKey>,
     value: u64,
     token_id: TokenId,
+    payment_id: Option<PaymentId>,
+    memo: Vec<u8>,
     coins: Vec<OwnCoin>,
+    selection: &dyn CoinSelection,
     tree: MerkleTree,
+    frozen_tokens: &SmtMemoryFp,
     mint_zkbin: ZkBinary,
     mint_pk: ProvingKey,
     burn_zkbin: ZkBinary,
@@ -107,26 +103,35 @@ pub fn make_transfer_call(
     let mut inputs = vec![];
     let mut outputs = vec![];
 
-    let (spent_coins, change_value) = select_coins(coins, value)?;
+    let (spent_coins, change_value) = selection.select(coins, value)?;
 
     for coin in spent_coins.iter() {
         let input = TransferCallInput {
             coin: coin.clone(),
             merkle_path: tree.witness(coin.leaf_position, 0).unwrap(),
             user_data_blind: Blind::random(&mut OsRng),
+            frozen_root: frozen_tokens.root(),
+            frozen_path: frozen_tokens.prove_membership(&token_id.inner()),
         };
 
         inputs.push(input);
     }
     debug!("Selected inputs");
 
+    // If a payment ID was given, the recipient coin blind commits to it
+    let payment_id = payment_id.map(|id| PaymentIdOpening::new(id, &mut OsRng));
+    let blind = match payment_id {
+        Some(ref opening) => opening.coin_blind(),
+        None => Blind::random(&mut OsRng),
+    };
+
     outputs.push(TransferCallOutput {
         public_key: recipient,
         value,
         token_id,
         spend_hook: FuncId::none(),
         user_data: pallas::Base::ZERO,
-        blind: Blind::random(&mut OsRng),
+        blind,
     });
 
     if change_value > 0 {
@@ -146,6 +151,14 @@ pub fn make_transfer_call(
         clear_inputs: vec![],
         inputs,
         outputs,
+        payment_ids: vec![payment_id],
+        // Change is encrypted to our own viewing key, so it shows up
+        // to anyone we disclosed it to along with our incoming payments.
+        viewing_keys: vec![
+            recipient_viewing_key,
+            Some(ViewingKey::derive(&keypair.secret).public_key()),
+        ],
+        memos: vec![memo],
         mint_zkbin,
         mint_pk,
         burn_zkbin,
@@ -24,8 +24,7 @@ use darkfi::{
 use darkfi_sdk::{
     bridgetree::Hashable,
     crypto::{
-        pasta_prelude::*, pedersen_commitment_u64, poseidon_hash, BaseBlind, FuncId, MerkleNode,
-        PublicKey, ScalarBlind, SecretKey,
+        commitment, poseidon_hash, BaseBlind, FuncId, MerkleNode, PublicKey, ScalarBlind, SecretKey,
     },
     pasta::pallas,
 };
@@ -43,11 +42,11 @@ pub struct TransferMintRevealed {
 
 impl TransferMintRevealed {
     pub fn to_vec(&self) -> Vec<pallas::Base> {
-        let valcom_coords = self.value_commit.to_affine().coordinates().unwrap();
+        let (valcom_x, valcom_y) = commitment::xy(&self.value_commit);
 
         // NOTE: It's important to keep these in the same order
         // as the `constrain_instance` calls in the zkas code.
-        vec![self.coin.inner(), *valcom_coords.x(), *valcom_coords.y(), self.token_commit]
+        vec![self.coin.inner(), valcom_x, valcom_y, self.token_commit]
     }
 }
 
@@ -56,6 +55,7 @@ pub struct TransferBurnRevealed {
     pub token_commit: pallas::Base,
     pub nullifier: Nullifier,
     pub merkle_root: MerkleNode,
+    pub frozen_root: pallas::Base,
     pub spend_hook: FuncId,
     pub user_data_enc: pallas::Base,
     pub signature_public: PublicKey,
@@ -63,16 +63,17 @@ pub struct TransferBurnRevealed {
 
 impl TransferBurnRevealed {
     pub fn to_vec(&self) -> Vec<pallas::Base> {
-        let valcom_coords = self.value_commit.to_affine().coordinates().unwrap();
+        let (valcom_x, valcom_y) = commitment::xy(&self.value_commit);
 
         // NOTE: It's important to keep these in the same order
         // as the `constrain_instance` calls in the zkas code.
         vec![
             self.nullifier.inner(),
-            *valcom_coords.x(),
-            *valcom_coords.y(),
+            valcom_x,
+            valcom_y,
             self.token_commit,
             self.merkle_root.inner(),
+            self.frozen_root,
             self.user_data_enc,
             self.spend_hook.inner(),
             self.signature_public.x(),
@@ -89,6 +90,22 @@ pub fn create_transfer_burn_proof(
     token_blind: BaseBlind,
     signature_secret: SecretKey,
 ) -> Result<(Proof, TransferBurnRevealed)> {
+    let (circuit, public_inputs) =
+        transfer_burn_circuit(zkbin, input, value_blind, token_blind, signature_secret);
+    let proof = Proof::create(pk, &[circuit], &public_inputs.to_vec(), &mut OsRng)?;
+
+    Ok((proof, public_inputs))
+}
+
+/// Build the `Burn_V1` circuit for `input` without proving it, so the
+/// proof can be created later, e.g. in a [`darkfi::zk::ProvingSession`].
+pub fn transfer_burn_circuit(
+    zkbin: &ZkBinary,
+    input: &TransferCallInput,
+    value_blind: ScalarBlind,
+    token_blind: BaseBlind,
+    signature_secret: SecretKey,
+) -> (ZkCircuit, TransferBurnRevealed) {
     let public_key = PublicKey::from_secret(input.coin.secret);
     let signature_public = PublicKey::from_secret(signature_secret);
 
@@ -117,7 +134,7 @@ pub fn create_transfer_burn_proof(
     };
 
     let user_data_enc = poseidon_hash([input.coin.note.user_data, input.user_data_blind.inner()]);
-    let value_commit = pedersen_commitment_u64(input.coin.note.value, value_blind);
+    let value_commit = commitment::commit(input.coin.note.value, value_blind);
     let token_commit = poseidon_hash([input.coin.note.token_id.inner(), token_blind.inner()]);
 
     let public_inputs = TransferBurnRevealed {
@@ -125,6 +142,7 @@ pub fn create_transfer_burn_proof(
         token_commit,
         nullifier: input.coin.nullifier(),
         merkle_root,
+        frozen_root: input.frozen_root,
         spend_hook: input.coin.note.spend_hook,
         user_data_enc,
         signature_public,
@@ -142,14 +160,14 @@ pub fn create_transfer_burn_proof(
         Witness::Base(Value::known(input.user_data_blind.inner())),
         Witness::Uint32(Value::known(u64::from(input.coin.leaf_position).try_into().unwrap())),
         Witness::MerklePath(Value::known(input.merkle_path.clone().try_into().unwrap())),
+        Witness::SparseMerklePath(Value::known(input.frozen_path.path)),
         Witness::Base(Value::known(signature_secret.inner())),
     ];
 
     //darkfi::zk::export_witness_json("proof/witness/burn_v1.json", &prover_witnesses, &public_inputs.to_vec());
     let circuit = ZkCircuit::new(prover_witnesses, zkbin);
-    let proof = Proof::create(pk, &[circuit], &public_inputs.to_vec(), &mut OsRng)?;
 
-    Ok((proof, public_inputs))
+    (circuit, public_inputs)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -163,7 +181,32 @@ pub fn create_transfer_mint_proof(
     user_data: pallas::Base,
     coin_blind: BaseBlind,
 ) -> Result<(Proof, TransferMintRevealed)> {
-    let value_commit = pedersen_commitment_u64(output.value, value_blind);
+    let (circuit, public_inputs) = transfer_mint_circuit(
+        zkbin,
+        output,
+        value_blind,
+        token_blind,
+        spend_hook,
+        user_data,
+        coin_blind,
+    );
+    let proof = Proof::create(pk, &[circuit], &public_inputs.to_vec(), &mut OsRng)?;
+
+    Ok((proof, public_inputs))
+}
+
+/// Build the `Mint_V1` circuit for `output` without proving it, so the
+/// proof can be created later, e.g. in a [`darkfi::zk::ProvingSession`].
+pub fn transfer_mint_circuit(
+    zkbin: &ZkBinary,
+    output: &TransferCallOutput,
+    value_blind: ScalarBlind,
+    token_blind: BaseBlind,
+    spend_hook: FuncId,
+    user_data: pallas::Base,
+    coin_blind: BaseBlind,
+) -> (ZkCircuit, TransferMintRevealed) {
+    let value_commit = commitment::commit(output.value, value_blind);
     let token_commit = poseidon_hash([output.token_id.inner(), token_blind.inner()]);
     let (pub_x, pub_y) = output.public_key.xy();
 
@@ -194,7 +237,6 @@ pub fn create_transfer_mint_proof(
 
     //darkfi::zk::export_witness_json("proof/witness/mint_v1.json", &prover_witnesses, &public_inputs.to_vec());
     let circuit = ZkCircuit::new(prover_witnesses, zkbin);
-    let proof = Proof::create(pk, &[circuit], &public_inputs.to_vec(), &mut OsRng)?;
 
-    Ok((proof, public_inputs))
+    (circuit, public_inputs)
 }
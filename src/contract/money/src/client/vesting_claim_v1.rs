@@ -0,0 +1,153 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    zk::{halo2::Value, Proof, ProvingKey, Witness, ZkCircuit},
+    zkas::ZkBinary,
+    Result,
+};
+use darkfi_sdk::{
+    crypto::{pasta_prelude::*, poseidon_hash, BaseBlind, FuncId, FuncRef, MONEY_CONTRACT_ID},
+    pasta::pallas,
+};
+use log::info;
+use rand::rngs::OsRng;
+
+use crate::{
+    model::{CoinAttributes, MoneyVestingClaimParamsV1, VestingSchedule},
+    MoneyFunction,
+};
+
+/// Spend hook of coins locked under a [`VestingSchedule`]
+pub fn vesting_spend_hook() -> FuncId {
+    FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::VestingClaimV1 as u8 }
+        .to_func_id()
+}
+
+/// Attributes of the coin locking the schedule's total value for the
+/// recipient. The payer sends this as a regular `Money::Transfer` output.
+pub fn vesting_lock_coin(schedule: &VestingSchedule, blind: BaseBlind) -> CoinAttributes {
+    CoinAttributes {
+        public_key: schedule.recipient,
+        value: schedule.total_value,
+        token_id: schedule.token_id,
+        spend_hook: vesting_spend_hook(),
+        user_data: schedule.to_bulla(),
+        blind,
+    }
+}
+
+/// Claims unlocked funds from a vesting coin.
+///
+/// This is the parent of a `Money::Transfer` spending the vesting coin
+/// into `claim_coin()` and `change_coin()`, in that order. The recipient
+/// can claim any amount, as long as the total claimed does not exceed
+/// what the schedule unlocked at `claim_height`.
+pub struct VestingClaimCallBuilder {
+    pub schedule: VestingSchedule,
+    /// Value of the spent vesting coin
+    pub input_value: u64,
+    /// Value claimed by this call
+    pub claim_value: u64,
+    /// User data blind of the spent vesting coin
+    pub input_user_data_blind: BaseBlind,
+    pub claim_coin_blind: BaseBlind,
+    pub change_coin_blind: BaseBlind,
+    /// Lowest block height the claim can be confirmed at
+    pub claim_height: u64,
+}
+
+impl VestingClaimCallBuilder {
+    /// Attributes of the coin sent to the recipient
+    pub fn claim_coin(&self) -> CoinAttributes {
+        CoinAttributes {
+            public_key: self.schedule.recipient,
+            value: self.claim_value,
+            token_id: self.schedule.token_id,
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+            blind: self.claim_coin_blind,
+        }
+    }
+
+    /// Attributes of the coin holding the remaining vesting funds
+    pub fn change_coin(&self) -> CoinAttributes {
+        CoinAttributes {
+            public_key: self.schedule.recipient,
+            value: self.input_value - self.claim_value,
+            token_id: self.schedule.token_id,
+            spend_hook: vesting_spend_hook(),
+            user_data: self.schedule.to_bulla(),
+            blind: self.change_coin_blind,
+        }
+    }
+
+    pub fn make(
+        &self,
+        claim_zkbin: &ZkBinary,
+        claim_pk: &ProvingKey,
+    ) -> Result<(MoneyVestingClaimParamsV1, Vec<Proof>)> {
+        info!("Building Money::VestingClaimV1 contract call");
+        assert!(self.claim_value <= self.input_value);
+
+        let schedule = &self.schedule;
+        let (recipient_x, recipient_y) = schedule.recipient.xy();
+        let input_user_data_enc =
+            poseidon_hash([schedule.to_bulla(), self.input_user_data_blind.inner()]);
+        let claim_coin = self.claim_coin();
+        let change_coin = self.change_coin();
+
+        let prover_witnesses = vec![
+            // Vesting schedule
+            Witness::Base(Value::known(recipient_x)),
+            Witness::Base(Value::known(recipient_y)),
+            Witness::Base(Value::known(pallas::Base::from(schedule.total_value))),
+            Witness::Base(Value::known(schedule.token_id.inner())),
+            Witness::Base(Value::known(pallas::Base::from(schedule.start_height))),
+            Witness::Base(Value::known(pallas::Base::from(schedule.cliff_height))),
+            Witness::Base(Value::known(pallas::Base::from(schedule.end_height))),
+            Witness::Base(Value::known(schedule.blind.inner())),
+            // Vesting input
+            Witness::Base(Value::known(self.input_user_data_blind.inner())),
+            // Claimed coin
+            Witness::Base(Value::known(pallas::Base::from(claim_coin.value))),
+            Witness::Base(Value::known(claim_coin.blind.inner())),
+            // Change coin
+            Witness::Base(Value::known(pallas::Base::from(change_coin.value))),
+            Witness::Base(Value::known(change_coin.blind.inner())),
+            // Claim time
+            Witness::Base(Value::known(pallas::Base::from(self.claim_height))),
+            Witness::Base(Value::known(change_coin.spend_hook.inner())),
+        ];
+
+        let public_inputs = vec![
+            input_user_data_enc,
+            pallas::Base::from(self.claim_height),
+            claim_coin.to_coin().inner(),
+            change_coin.to_coin().inner(),
+            change_coin.spend_hook.inner(),
+        ];
+
+        //darkfi::zk::export_witness_json("proof/witness/vesting_claim_v1.json", &prover_witnesses, &public_inputs);
+        let circuit = ZkCircuit::new(prover_witnesses, claim_zkbin);
+        let proof = Proof::create(claim_pk, &[circuit], &public_inputs, &mut OsRng)?;
+
+        let params = MoneyVestingClaimParamsV1 { claim_height: self.claim_height };
+        Ok((params, vec![proof]))
+    }
+}
@@ -27,16 +27,22 @@ use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
 
 use crate::{
     model::{
-        MoneyAuthTokenMintUpdateV1, MoneyFeeUpdateV1, MoneyGenesisMintUpdateV1,
+        MoneyAuthTokenMintUpdateV1, MoneyBridgeAuthoritySetUpdateV1, MoneyBridgeBurnUpdateV1,
+        MoneyBridgeMintUpdateV1, MoneyFeeTokenRateUpdateV1, MoneyFeeUpdateV1,
+        MoneyGenesisMintUpdateV1, MoneyNftBurnUpdateV1, MoneyNftMintUpdateV1,
         MoneyPoWRewardUpdateV1, MoneyTokenFreezeUpdateV1, MoneyTokenMintUpdateV1,
-        MoneyTransferUpdateV1,
+        MoneyTokenSupplyCapUpdateV1, MoneyTransferFreezeUpdateV1, MoneyTransferUpdateV1,
     },
-    MoneyFunction, EMPTY_COINS_TREE_ROOT, MONEY_CONTRACT_COINS_TREE,
-    MONEY_CONTRACT_COIN_MERKLE_TREE, MONEY_CONTRACT_COIN_ROOTS_TREE, MONEY_CONTRACT_DB_VERSION,
-    MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
-    MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NULLIFIERS_TREE,
-    MONEY_CONTRACT_NULLIFIER_ROOTS_TREE, MONEY_CONTRACT_TOKEN_FREEZE_TREE,
-    MONEY_CONTRACT_TOTAL_FEES_PAID,
+    MoneyFunction, EMPTY_COINS_TREE_ROOT, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE,
+    MONEY_CONTRACT_BRIDGE_BURNS_TREE, MONEY_CONTRACT_BRIDGE_BURN_MERKLE_TREE,
+    MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE, MONEY_CONTRACT_BRIDGE_MINTS_TREE,
+    MONEY_CONTRACT_COINS_TREE, MONEY_CONTRACT_COIN_MERKLE_TREE, MONEY_CONTRACT_COIN_ROOTS_TREE,
+    MONEY_CONTRACT_DB_VERSION, MONEY_CONTRACT_FEE_TOKEN_RATES_TREE,
+    MONEY_CONTRACT_FROZEN_ROOTS_TREE, MONEY_CONTRACT_FROZEN_TOKENS_TREE, MONEY_CONTRACT_INFO_TREE,
+    MONEY_CONTRACT_LATEST_COIN_ROOT, MONEY_CONTRACT_LATEST_NULLIFIER_ROOT,
+    MONEY_CONTRACT_NFTS_TREE, MONEY_CONTRACT_NULLIFIERS_TREE, MONEY_CONTRACT_NULLIFIER_ROOTS_TREE,
+    MONEY_CONTRACT_TOKEN_FREEZE_TREE, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE,
+    MONEY_CONTRACT_TOKEN_SUPPLY_TREE, MONEY_CONTRACT_TOTAL_FEES_PAID,
 };
 
 /// `Money::Fee` functions
@@ -45,6 +51,13 @@ use fee_v1::{
     money_fee_get_metadata_v1, money_fee_process_instruction_v1, money_fee_process_update_v1,
 };
 
+/// `Money::FeeTokenRate` functions
+mod fee_token_rate_v1;
+use fee_token_rate_v1::{
+    money_fee_token_rate_get_metadata_v1, money_fee_token_rate_process_instruction_v1,
+    money_fee_token_rate_process_update_v1,
+};
+
 /// `Money::Transfer` functions
 mod transfer_v1;
 use transfer_v1::{
@@ -80,6 +93,70 @@ use token_freeze_v1::{
     money_token_freeze_process_update_v1,
 };
 
+/// `Money::TokenSupplyCap` functions
+mod token_supply_cap_v1;
+use token_supply_cap_v1::{
+    money_token_supply_cap_get_metadata_v1, money_token_supply_cap_process_instruction_v1,
+    money_token_supply_cap_process_update_v1,
+};
+
+/// `Money::TransferFreeze` and `Money::TransferUnfreeze` functions
+mod transfer_freeze_v1;
+use transfer_freeze_v1::{
+    money_transfer_freeze_get_metadata_v1, money_transfer_freeze_process_instruction_v1,
+    money_transfer_freeze_process_update_v1, money_transfer_unfreeze_process_instruction_v1,
+    money_transfer_unfreeze_process_update_v1,
+};
+
+/// `Money::VestingClaim` functions
+mod vesting_claim_v1;
+use vesting_claim_v1::{
+    money_vesting_claim_get_metadata_v1, money_vesting_claim_process_instruction_v1,
+};
+
+/// `Money::HtlcSpend` functions
+mod htlc_spend_v1;
+use htlc_spend_v1::{money_htlc_spend_get_metadata_v1, money_htlc_spend_process_instruction_v1};
+
+/// `Money::GroupSpend` functions
+mod group_spend_v1;
+use group_spend_v1::{money_group_spend_get_metadata_v1, money_group_spend_process_instruction_v1};
+
+/// `Money::NftMint` functions
+mod nft_mint_v1;
+use nft_mint_v1::{
+    money_nft_mint_get_metadata_v1, money_nft_mint_process_instruction_v1,
+    money_nft_mint_process_update_v1,
+};
+
+/// `Money::NftBurn` functions
+mod nft_burn_v1;
+use nft_burn_v1::{
+    money_nft_burn_get_metadata_v1, money_nft_burn_process_instruction_v1,
+    money_nft_burn_process_update_v1,
+};
+
+/// `Money::BridgeBurn` functions
+mod bridge_burn_v1;
+use bridge_burn_v1::{
+    money_bridge_burn_get_metadata_v1, money_bridge_burn_process_instruction_v1,
+    money_bridge_burn_process_update_v1,
+};
+
+/// `Money::BridgeAuthoritySet` functions
+mod bridge_authority_set_v1;
+use bridge_authority_set_v1::{
+    money_bridge_authority_set_get_metadata_v1, money_bridge_authority_set_process_instruction_v1,
+    money_bridge_authority_set_process_update_v1,
+};
+
+/// `Money::BridgeMint` functions
+mod bridge_mint_v1;
+use bridge_mint_v1::{
+    money_bridge_mint_get_metadata_v1, money_bridge_mint_process_instruction_v1,
+    money_bridge_mint_process_update_v1,
+};
+
 /// `Money::PoWReward` functions
 mod pow_reward_v1;
 use pow_reward_v1::{
@@ -115,6 +192,9 @@ fn init_contract(cid: ContractId, _ix: &[u8]) -> ContractResult {
     let burn_v1_bincode = include_bytes!("../proof/burn_v1.zk.bin");
     let token_mint_v1_bincode = include_bytes!("../proof/token_mint_v1.zk.bin");
     let token_frz_v1_bincode = include_bytes!("../proof/token_freeze_v1.zk.bin");
+    let vesting_claim_v1_bincode = include_bytes!("../proof/vesting_claim_v1.zk.bin");
+    let nft_mint_v1_bincode = include_bytes!("../proof/nft_mint_v1.zk.bin");
+    let token_supply_cap_v1_bincode = include_bytes!("../proof/token_supply_cap_v1.zk.bin");
 
     // For that, we use `wasm::db::zkas_wasm::db::db_set` and pass in the bincode.
     wasm::db::zkas_db_set(&fee_v1_bincode[..])?;
@@ -122,6 +202,9 @@ fn init_contract(cid: ContractId, _ix: &[u8]) -> ContractResult {
     wasm::db::zkas_db_set(&burn_v1_bincode[..])?;
     wasm::db::zkas_db_set(&token_mint_v1_bincode[..])?;
     wasm::db::zkas_db_set(&token_frz_v1_bincode[..])?;
+    wasm::db::zkas_db_set(&vesting_claim_v1_bincode[..])?;
+    wasm::db::zkas_db_set(&nft_mint_v1_bincode[..])?;
+    wasm::db::zkas_db_set(&token_supply_cap_v1_bincode[..])?;
 
     let tx_hash = wasm::util::get_tx_hash()?;
     // The max outputs for a tx in BTC is 2501
@@ -163,6 +246,69 @@ fn init_contract(cid: ContractId, _ix: &[u8]) -> ContractResult {
         wasm::db::db_init(cid, MONEY_CONTRACT_TOKEN_FREEZE_TREE)?;
     }
 
+    // Set up a database tree to hold the SMT of tokens whose transfers
+    // are frozen. k=SMT node index, v=SMT node
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_FROZEN_TOKENS_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_FROZEN_TOKENS_TREE)?;
+    }
+
+    // Set up a database tree to hold the roots of the frozen tokens SMT
+    // that `Burn_V1` proofs may be made against.
+    // k=root, v=Option<block height the root was superseded at>
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_FROZEN_ROOTS_TREE).is_err() {
+        let db_frozen_roots = wasm::db::db_init(cid, MONEY_CONTRACT_FROZEN_ROOTS_TREE)?;
+        let frozen_root = transfer_freeze_v1::frozen_tokens_smt(cid)?.root();
+        wasm::db::db_set(db_frozen_roots, &serialize(&frozen_root), &serialize(&None::<u32>))?;
+    }
+
+    // Set up a database tree to hold the conversion rates of tokens
+    // whitelisted for paying fees. k=TokenId, v=FeeTokenRate
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_FEE_TOKEN_RATES_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_FEE_TOKEN_RATES_TREE)?;
+    }
+
+    // Set up a database tree to accumulate the value commitments of
+    // authorized token mints. k=TokenId, v=pallas::Point
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE)?;
+    }
+
+    // Set up a database tree to hold the hard supply caps of tokens
+    // k=TokenId, v=u64
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE)?;
+    }
+
+    // Set up a database tree to hold all minted non-fungible tokens
+    // k=TokenId, v=Nft
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_NFTS_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_NFTS_TREE)?;
+    }
+
+    // Set up a database tree to hold the receipts of coins burned
+    // through the bridge. k=Nullifier, v=BridgeBurnReceipt
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_BURNS_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_BRIDGE_BURNS_TREE)?;
+    }
+
+    // Set up a database tree to hold Merkle roots of all bridge burn trees
+    // k=root_hash:32, v=(tx_hash:32, call_idx: 2)
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE)?;
+    }
+
+    // Set up a database tree to hold the bridge authority sets of tokens
+    // k=TokenId, v=BridgeAuthoritySet
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE)?;
+    }
+
+    // Set up a database tree to hold the source chain locks already
+    // minted through the bridge. k=[u8; 32], v=[]
+    if wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_MINTS_TREE).is_err() {
+        wasm::db::db_init(cid, MONEY_CONTRACT_BRIDGE_MINTS_TREE)?;
+    }
+
     // Set up a database tree for arbitrary data
     let info_db = match wasm::db::db_lookup(cid, MONEY_CONTRACT_INFO_TREE) {
         Ok(v) => v,
@@ -199,6 +345,15 @@ fn init_contract(cid: ContractId, _ix: &[u8]) -> ContractResult {
         }
     };
 
+    // Create the incrementalmerkletree for bridge burn receipts
+    if wasm::db::db_get(info_db, MONEY_CONTRACT_BRIDGE_BURN_MERKLE_TREE)?.is_none() {
+        let bridge_burn_tree = MerkleTree::new(100);
+        let mut bridge_burn_tree_data = vec![];
+        bridge_burn_tree_data.write_u32(0)?;
+        bridge_burn_tree.encode(&mut bridge_burn_tree_data)?;
+        wasm::db::db_set(info_db, MONEY_CONTRACT_BRIDGE_BURN_MERKLE_TREE, &bridge_burn_tree_data)?;
+    }
+
     // Update db version
     wasm::db::db_set(info_db, MONEY_CONTRACT_DB_VERSION, &serialize(&env!("CARGO_PKG_VERSION")))?;
 
@@ -231,6 +386,25 @@ fn get_metadata(cid: ContractId, ix: &[u8]) -> ContractResult {
         MoneyFunction::AuthTokenMintV1 => {
             money_auth_token_mint_get_metadata_v1(cid, call_idx, calls)?
         }
+        MoneyFunction::TransferFreezeV1 | MoneyFunction::TransferUnfreezeV1 => {
+            money_transfer_freeze_get_metadata_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::FeeTokenRateV1 => {
+            money_fee_token_rate_get_metadata_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::VestingClaimV1 => money_vesting_claim_get_metadata_v1(cid, call_idx, calls)?,
+        MoneyFunction::HtlcSpendV1 => money_htlc_spend_get_metadata_v1(cid, call_idx, calls)?,
+        MoneyFunction::NftMintV1 => money_nft_mint_get_metadata_v1(cid, call_idx, calls)?,
+        MoneyFunction::NftBurnV1 => money_nft_burn_get_metadata_v1(cid, call_idx, calls)?,
+        MoneyFunction::TokenSupplyCapV1 => {
+            money_token_supply_cap_get_metadata_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::BridgeBurnV1 => money_bridge_burn_get_metadata_v1(cid, call_idx, calls)?,
+        MoneyFunction::BridgeAuthoritySetV1 => {
+            money_bridge_authority_set_get_metadata_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::BridgeMintV1 => money_bridge_mint_get_metadata_v1(cid, call_idx, calls)?,
+        MoneyFunction::GroupSpendV1 => money_group_spend_get_metadata_v1(cid, call_idx, calls)?,
     };
 
     wasm::util::set_return_data(&metadata)
@@ -271,6 +445,38 @@ fn process_instruction(cid: ContractId, ix: &[u8]) -> ContractResult {
         MoneyFunction::AuthTokenMintV1 => {
             money_auth_token_mint_process_instruction_v1(cid, call_idx, calls)?
         }
+        MoneyFunction::TransferFreezeV1 => {
+            money_transfer_freeze_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::TransferUnfreezeV1 => {
+            money_transfer_unfreeze_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::FeeTokenRateV1 => {
+            money_fee_token_rate_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::VestingClaimV1 => {
+            money_vesting_claim_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::HtlcSpendV1 => {
+            money_htlc_spend_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::NftMintV1 => money_nft_mint_process_instruction_v1(cid, call_idx, calls)?,
+        MoneyFunction::NftBurnV1 => money_nft_burn_process_instruction_v1(cid, call_idx, calls)?,
+        MoneyFunction::TokenSupplyCapV1 => {
+            money_token_supply_cap_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::BridgeBurnV1 => {
+            money_bridge_burn_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::BridgeAuthoritySetV1 => {
+            money_bridge_authority_set_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::BridgeMintV1 => {
+            money_bridge_mint_process_instruction_v1(cid, call_idx, calls)?
+        }
+        MoneyFunction::GroupSpendV1 => {
+            money_group_spend_process_instruction_v1(cid, call_idx, calls)?
+        }
     };
 
     wasm::util::set_return_data(&update_data)
@@ -323,5 +529,65 @@ fn process_update(cid: ContractId, update_data: &[u8]) -> ContractResult {
             let update: MoneyAuthTokenMintUpdateV1 = deserialize(&update_data[1..])?;
             Ok(money_auth_token_mint_process_update_v1(cid, update)?)
         }
+
+        MoneyFunction::TransferFreezeV1 => {
+            let update: MoneyTransferFreezeUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_transfer_freeze_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::TransferUnfreezeV1 => {
+            let update: MoneyTransferFreezeUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_transfer_unfreeze_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::FeeTokenRateV1 => {
+            let update: MoneyFeeTokenRateUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_fee_token_rate_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::VestingClaimV1 => {
+            // Does nothing, the child Money::Transfer spends the vesting coin
+            Ok(())
+        }
+
+        MoneyFunction::HtlcSpendV1 => {
+            // Does nothing, the child Money::Transfer spends the HTLC coin
+            Ok(())
+        }
+
+        MoneyFunction::NftMintV1 => {
+            let update: MoneyNftMintUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_nft_mint_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::NftBurnV1 => {
+            let update: MoneyNftBurnUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_nft_burn_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::TokenSupplyCapV1 => {
+            let update: MoneyTokenSupplyCapUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_token_supply_cap_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::BridgeBurnV1 => {
+            let update: MoneyBridgeBurnUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_bridge_burn_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::BridgeAuthoritySetV1 => {
+            let update: MoneyBridgeAuthoritySetUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_bridge_authority_set_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::BridgeMintV1 => {
+            let update: MoneyBridgeMintUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(money_bridge_mint_process_update_v1(cid, update)?)
+        }
+
+        MoneyFunction::GroupSpendV1 => {
+            // Does nothing, the child Money::Transfer spends the group coins
+            Ok(())
+        }
     }
 }
@@ -24,29 +24,31 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
 
 use crate::{
     error::MoneyError,
     model::{MoneyAuthTokenMintParamsV1, MoneyAuthTokenMintUpdateV1, MoneyTokenMintParamsV1},
-    MoneyFunction, MONEY_CONTRACT_TOKEN_FREEZE_TREE, MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1,
+    MoneyFunction, MONEY_CONTRACT_TOKEN_FREEZE_TREE, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE,
+    MONEY_CONTRACT_TOKEN_SUPPLY_TREE, MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1,
+    MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1,
 };
 
 /// `get_metadata` function for `Money::AuthTokenMintV1`
 pub(crate) fn money_auth_token_mint_get_metadata_v1(
-    _cid: ContractId,
+    cid: ContractId,
     call_idx: u32,
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_node = &calls[call_idx as usize];
     let self_data = &self_node.data;
-    let self_params: MoneyAuthTokenMintParamsV1 = deserialize(&self_data.data[1..])?;
+    let self_params: MoneyAuthTokenMintParamsV1 = deserialize_strict(&self_data.data[1..])?;
 
     assert_eq!(self_node.children_indexes.len(), 1);
     let child_idx = self_node.children_indexes[0];
     let child_node = &calls[child_idx];
     let child_data = &child_node.data;
-    let child_params: MoneyTokenMintParamsV1 = deserialize(&child_data.data[1..])?;
+    let child_params: MoneyTokenMintParamsV1 = deserialize_strict(&child_data.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -66,6 +68,33 @@ pub(crate) fn money_auth_token_mint_get_metadata_v1(
         ],
     ));
 
+    // If the token's supply is capped, the mint authority also has to prove
+    // the accumulated supply including this mint doesn't exceed the cap.
+    let token_supply_caps_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE)?;
+    if let Some(v) = wasm::db::db_get(token_supply_caps_db, &serialize(&self_params.token_id))? {
+        let supply_cap: u64 = deserialize(&v)?;
+
+        let token_supply_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE)?;
+        let supply_commit =
+            match wasm::db::db_get(token_supply_db, &serialize(&self_params.token_id))? {
+                Some(v) => deserialize::<pallas::Point>(&v)? + self_params.value_commit,
+                None => self_params.value_commit,
+            };
+        let supply_coords = supply_commit.to_affine().coordinates().unwrap();
+
+        zk_public_inputs.push((
+            MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1.to_string(),
+            vec![
+                self_params.mint_pubkey.x(),
+                self_params.mint_pubkey.y(),
+                self_params.token_id.inner(),
+                *supply_coords.x(),
+                *supply_coords.y(),
+                pallas::Base::from(supply_cap),
+            ],
+        ));
+    }
+
     // Serialize everything gathered and return it
     let mut metadata = vec![];
     zk_public_inputs.encode(&mut metadata)?;
@@ -81,7 +110,7 @@ pub(crate) fn money_auth_token_mint_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyAuthTokenMintParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyAuthTokenMintParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // We have to check if the token mint is frozen.
     let token_freeze_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_FREEZE_TREE)?;
@@ -92,8 +121,17 @@ pub(crate) fn money_auth_token_mint_process_instruction_v1(
         return Err(MoneyError::TokenMintFrozen.into())
     }
 
+    // Add the minted value to the token's supply accumulator. Its opening
+    // is only known to the mint authority, who can use it to prove the
+    // total supply without revealing individual mints.
+    let token_supply_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE)?;
+    let supply_commit = match wasm::db::db_get(token_supply_db, &serialize(&params.token_id))? {
+        Some(v) => deserialize::<pallas::Point>(&v)? + params.value_commit,
+        None => params.value_commit,
+    };
+
     // Create a state update.
-    let update = MoneyAuthTokenMintUpdateV1 {};
+    let update = MoneyAuthTokenMintUpdateV1 { token_id: params.token_id, supply_commit };
     let mut update_data = vec![];
     update_data.write_u8(MoneyFunction::AuthTokenMintV1 as u8)?;
     update.encode(&mut update_data)?;
@@ -103,9 +141,16 @@ pub(crate) fn money_auth_token_mint_process_instruction_v1(
 
 /// `process_update` function for `Money::AuthTokenMintV1`
 pub(crate) fn money_auth_token_mint_process_update_v1(
-    _cid: ContractId,
-    _update: MoneyAuthTokenMintUpdateV1,
+    cid: ContractId,
+    update: MoneyAuthTokenMintUpdateV1,
 ) -> ContractResult {
-    // Do nothing... Coin is added with token_mint() call instead.
+    // The coin is added with token_mint() call instead
+    let token_supply_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE)?;
+    wasm::db::db_set(
+        token_supply_db,
+        &serialize(&update.token_id),
+        &serialize(&update.supply_commit),
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,125 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError,
+    model::{MoneyBridgeAuthoritySetParamsV1, MoneyBridgeAuthoritySetUpdateV1},
+    MoneyFunction, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE, MONEY_CONTRACT_TOKEN_FREEZE_TREE,
+    MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE, MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1,
+};
+
+/// `get_metadata` function for `Money::BridgeAuthoritySetV1`
+pub(crate) fn money_bridge_authority_set_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyBridgeAuthoritySetParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let signature_pubkeys: Vec<PublicKey> = vec![params.mint_public];
+
+    // In ZK we just verify that the token ID is properly derived from the authority.
+    let (mint_x, mint_y) = params.mint_public.xy();
+    zk_public_inputs.push((
+        MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1.to_string(),
+        vec![mint_x, mint_y, params.token_id.inner()],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::BridgeAuthoritySetV1`
+pub(crate) fn money_bridge_authority_set_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyBridgeAuthoritySetParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    if !params.authority_set.is_valid() {
+        msg!("[BridgeAuthoritySetV1] Error: Invalid authority set for {}", params.token_id);
+        return Err(MoneyError::BridgeAuthoritySetInvalid.into())
+    }
+
+    // A frozen mint can't issue anything anymore, including through the bridge
+    let token_freeze_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_FREEZE_TREE)?;
+    if wasm::db::db_contains_key(token_freeze_db, &serialize(&params.token_id))? {
+        msg!("[BridgeAuthoritySetV1] Error: Token mint for {} is frozen", params.token_id);
+        return Err(MoneyError::TokenMintFrozen.into())
+    }
+
+    // Bridge mints are accounted in the supply accumulator, but they
+    // don't prove it stays under the cap, so capped tokens can't be bridged.
+    let token_supply_caps_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE)?;
+    if wasm::db::db_contains_key(token_supply_caps_db, &serialize(&params.token_id))? {
+        msg!("[BridgeAuthoritySetV1] Error: Supply of {} is capped", params.token_id);
+        return Err(MoneyError::TokenSupplyCapExists.into())
+    }
+
+    // Create a state update. The mint authority can rotate the set later on.
+    let update = MoneyBridgeAuthoritySetUpdateV1 {
+        token_id: params.token_id,
+        authority_set: params.authority_set,
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::BridgeAuthoritySetV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::BridgeAuthoritySetV1`
+pub(crate) fn money_bridge_authority_set_process_update_v1(
+    cid: ContractId,
+    update: MoneyBridgeAuthoritySetUpdateV1,
+) -> ContractResult {
+    let bridge_authorities_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE)?;
+    msg!(
+        "[BridgeAuthoritySetV1] Setting {}-of-{} bridge authorities for token {}",
+        update.authority_set.threshold,
+        update.authority_set.authorities.len(),
+        update.token_id
+    );
+    wasm::db::db_set(
+        bridge_authorities_db,
+        &serialize(&update.token_id),
+        &serialize(&update.authority_set),
+    )?;
+
+    Ok(())
+}
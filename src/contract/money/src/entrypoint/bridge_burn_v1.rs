@@ -0,0 +1,193 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{
+        pasta_prelude::*,
+        pedersen_commitment_u64, poseidon_hash,
+        smt::{
+            wasmdb::{SmtWasmDbStorage, SmtWasmFp},
+            PoseidonFp, EMPTY_NODES_FP,
+        },
+        ContractId, FuncId, PublicKey,
+    },
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
+
+use super::transfer_freeze_v1::frozen_root_is_valid;
+use crate::{
+    error::MoneyError,
+    model::{MoneyBridgeBurnParamsV1, MoneyBridgeBurnUpdateV1},
+    MoneyFunction, MONEY_CONTRACT_BRIDGE_BURNS_TREE, MONEY_CONTRACT_BRIDGE_BURN_MERKLE_TREE,
+    MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE, MONEY_CONTRACT_COIN_MERKLE_TREE,
+    MONEY_CONTRACT_COIN_ROOTS_TREE, MONEY_CONTRACT_INFO_TREE,
+    MONEY_CONTRACT_LATEST_BRIDGE_BURN_ROOT, MONEY_CONTRACT_LATEST_COIN_ROOT,
+    MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NULLIFIERS_TREE,
+    MONEY_CONTRACT_NULLIFIER_ROOTS_TREE, MONEY_CONTRACT_ZKAS_BURN_NS_V1,
+};
+
+/// `get_metadata` function for `Money::BridgeBurnV1`
+pub(crate) fn money_bridge_burn_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyBridgeBurnParamsV1 = deserialize_strict(&self_.data[1..])?;
+    let input = &params.input;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let signature_pubkeys: Vec<PublicKey> = vec![input.signature_public];
+
+    let value_coords = input.value_commit.to_affine().coordinates().unwrap();
+    let (sig_x, sig_y) = input.signature_public.xy();
+
+    // The burned coin is spent with the regular `Burn_V1` proof. Hooked
+    // coins can't be bridged out, since the hook would be bypassed.
+    zk_public_inputs.push((
+        MONEY_CONTRACT_ZKAS_BURN_NS_V1.to_string(),
+        vec![
+            input.nullifier.inner(),
+            *value_coords.x(),
+            *value_coords.y(),
+            input.token_commit,
+            input.merkle_root.inner(),
+            input.frozen_root,
+            input.user_data_enc,
+            FuncId::none().inner(),
+            sig_x,
+            sig_y,
+        ],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::BridgeBurnV1`
+pub(crate) fn money_bridge_burn_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyBridgeBurnParamsV1 = deserialize_strict(&self_.data[1..])?;
+    let input = &params.input;
+
+    let nullifiers_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIERS_TREE)?;
+    let coin_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
+
+    if !wasm::db::db_contains_key(coin_roots_db, &serialize(&input.merkle_root))? {
+        msg!("[BridgeBurnV1] Error: Merkle root not found in previous state");
+        return Err(MoneyError::TransferMerkleRootNotFound.into())
+    }
+
+    if !frozen_root_is_valid(cid, &input.frozen_root)? {
+        msg!("[BridgeBurnV1] Error: Frozen tokens root not found or expired");
+        return Err(MoneyError::FrozenRootNotFound.into())
+    }
+
+    let smt_store = SmtWasmDbStorage::new(nullifiers_db);
+    let smt = SmtWasmFp::new(smt_store, PoseidonFp::new(), &EMPTY_NODES_FP);
+    if smt.get_leaf(&input.nullifier.inner()) != pallas::Base::ZERO {
+        msg!("[BridgeBurnV1] Error: Duplicate nullifier found");
+        return Err(MoneyError::DuplicateNullifier.into())
+    }
+
+    // The bridge operators need to know what was burned, so the
+    // input commitments are opened.
+    if input.value_commit != pedersen_commitment_u64(params.value, params.value_blind) {
+        msg!("[BridgeBurnV1] Error: Input value commitment doesn't open to {}", params.value);
+        return Err(MoneyError::ValueMismatch.into())
+    }
+
+    if input.token_commit != poseidon_hash([params.token_id.inner(), params.token_blind.inner()]) {
+        msg!("[BridgeBurnV1] Error: Input token commitment doesn't open to {}", params.token_id);
+        return Err(MoneyError::TokenMismatch.into())
+    }
+
+    // Create a state update
+    let update = MoneyBridgeBurnUpdateV1 { receipt: params.receipt() };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::BridgeBurnV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::BridgeBurnV1`
+pub(crate) fn money_bridge_burn_process_update_v1(
+    cid: ContractId,
+    update: MoneyBridgeBurnUpdateV1,
+) -> ContractResult {
+    // Grab all db handles we want to work on
+    let info_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_INFO_TREE)?;
+    let nullifiers_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIERS_TREE)?;
+    let coin_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
+    let nullifier_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIER_ROOTS_TREE)?;
+    let bridge_burns_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_BURNS_TREE)?;
+    let bridge_burn_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE)?;
+
+    msg!("[BridgeBurnV1] Adding nullifier to the set");
+    wasm::merkle::sparse_merkle_insert_batch(
+        info_db,
+        nullifiers_db,
+        nullifier_roots_db,
+        MONEY_CONTRACT_LATEST_NULLIFIER_ROOT,
+        &[update.receipt.nullifier.inner()],
+    )?;
+
+    // This will just make a snapshot to match the nullifiers one
+    msg!("[BridgeBurnV1] Updating coins snapshot");
+    wasm::merkle::merkle_add(
+        info_db,
+        coin_roots_db,
+        MONEY_CONTRACT_LATEST_COIN_ROOT,
+        MONEY_CONTRACT_COIN_MERKLE_TREE,
+        &[],
+    )?;
+
+    msg!("[BridgeBurnV1] Recording burn receipt");
+    wasm::db::db_set(
+        bridge_burns_db,
+        &serialize(&update.receipt.nullifier),
+        &serialize(&update.receipt),
+    )?;
+
+    msg!("[BridgeBurnV1] Adding burn receipt to the Merkle tree");
+    wasm::merkle::merkle_add(
+        info_db,
+        bridge_burn_roots_db,
+        MONEY_CONTRACT_LATEST_BRIDGE_BURN_ROOT,
+        MONEY_CONTRACT_BRIDGE_BURN_MERKLE_TREE,
+        &[update.receipt.to_leaf()],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,211 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{
+        pasta_prelude::*, pedersen_commitment_u64, poseidon_hash, ContractId, MerkleNode, PublicKey,
+    },
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError,
+    model::{BridgeAuthoritySet, MoneyBridgeMintParamsV1, MoneyBridgeMintUpdateV1},
+    MoneyFunction, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE, MONEY_CONTRACT_BRIDGE_MINTS_TREE,
+    MONEY_CONTRACT_COINS_TREE, MONEY_CONTRACT_COIN_MERKLE_TREE, MONEY_CONTRACT_COIN_ROOTS_TREE,
+    MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
+    MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NULLIFIERS_TREE,
+    MONEY_CONTRACT_NULLIFIER_ROOTS_TREE, MONEY_CONTRACT_TOKEN_FREEZE_TREE,
+    MONEY_CONTRACT_TOKEN_SUPPLY_TREE, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+
+/// `get_metadata` function for `Money::BridgeMintV1`
+pub(crate) fn money_bridge_mint_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyBridgeMintParamsV1 = deserialize_strict(&self_.data[1..])?;
+    let output = &params.output;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Every listed bridge authority has to sign the transaction
+    let signature_pubkeys: Vec<PublicKey> = params.signers.clone();
+
+    // The output is created with the regular `Mint_V1` proof
+    let value_coords = output.value_commit.to_affine().coordinates().unwrap();
+    zk_public_inputs.push((
+        MONEY_CONTRACT_ZKAS_MINT_NS_V1.to_string(),
+        vec![output.coin.inner(), *value_coords.x(), *value_coords.y(), output.token_commit],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::BridgeMintV1`
+pub(crate) fn money_bridge_mint_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyBridgeMintParamsV1 = deserialize_strict(&self_.data[1..])?;
+    let output = &params.output;
+
+    let bridge_authorities_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE)?;
+    let bridge_mints_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_MINTS_TREE)?;
+    let token_freeze_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_FREEZE_TREE)?;
+    let coins_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COINS_TREE)?;
+
+    let Some(authority_set) =
+        wasm::db::db_get(bridge_authorities_db, &serialize(&params.token_id))?
+    else {
+        msg!("[BridgeMintV1] Error: No bridge authorities for token {}", params.token_id);
+        return Err(MoneyError::BridgeAuthoritiesNotFound.into())
+    };
+    let authority_set: BridgeAuthoritySet = deserialize(&authority_set)?;
+
+    // The signatures were verified against the listed signers, so we
+    // make sure enough distinct authorities are among them.
+    let mut signers: Vec<PublicKey> = vec![];
+    for signer in &params.signers {
+        if !authority_set.authorities.contains(signer) || signers.contains(signer) {
+            msg!("[BridgeMintV1] Error: Signer {} is not a bridge authority", signer);
+            return Err(MoneyError::BridgeMintUnauthorized.into())
+        }
+        signers.push(*signer);
+    }
+
+    if signers.len() < authority_set.threshold as usize {
+        msg!(
+            "[BridgeMintV1] Error: Got {} signers, threshold is {}",
+            signers.len(),
+            authority_set.threshold
+        );
+        return Err(MoneyError::BridgeMintUnauthorized.into())
+    }
+
+    if wasm::db::db_contains_key(bridge_mints_db, &serialize(&params.source))? {
+        msg!("[BridgeMintV1] Error: Source lock was already minted");
+        return Err(MoneyError::BridgeMintReplayed.into())
+    }
+
+    if wasm::db::db_contains_key(token_freeze_db, &serialize(&params.token_id))? {
+        msg!("[BridgeMintV1] Error: Token mint for {} is frozen", params.token_id);
+        return Err(MoneyError::TokenMintFrozen.into())
+    }
+
+    // The authorities sign the minted value and token in the clear,
+    // so the output commitments have to open to them.
+    if output.value_commit != pedersen_commitment_u64(params.value, params.value_blind) {
+        msg!("[BridgeMintV1] Error: Output value commitment doesn't open to {}", params.value);
+        return Err(MoneyError::ValueMismatch.into())
+    }
+
+    if output.token_commit != poseidon_hash([params.token_id.inner(), params.token_blind.inner()]) {
+        msg!("[BridgeMintV1] Error: Output token commitment doesn't open to {}", params.token_id);
+        return Err(MoneyError::TokenMismatch.into())
+    }
+
+    if wasm::db::db_contains_key(coins_db, &serialize(&output.coin))? {
+        msg!("[BridgeMintV1] Error: Duplicate coin in output");
+        return Err(MoneyError::DuplicateCoin.into())
+    }
+
+    // Bridged value counts towards the token's supply accumulator,
+    // just like the mint authority's own mints.
+    let token_supply_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE)?;
+    let supply_commit = match wasm::db::db_get(token_supply_db, &serialize(&params.token_id))? {
+        Some(v) => deserialize::<pallas::Point>(&v)? + output.value_commit,
+        None => output.value_commit,
+    };
+
+    // Create a state update
+    let update = MoneyBridgeMintUpdateV1 {
+        source: params.source,
+        coin: output.coin,
+        token_id: params.token_id,
+        supply_commit,
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::BridgeMintV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::BridgeMintV1`
+pub(crate) fn money_bridge_mint_process_update_v1(
+    cid: ContractId,
+    update: MoneyBridgeMintUpdateV1,
+) -> ContractResult {
+    // Grab all db handles we want to work on
+    let info_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_INFO_TREE)?;
+    let coins_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COINS_TREE)?;
+    let nullifiers_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIERS_TREE)?;
+    let coin_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
+    let nullifier_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIER_ROOTS_TREE)?;
+    let bridge_mints_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_MINTS_TREE)?;
+    let token_supply_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE)?;
+
+    msg!("[BridgeMintV1] Marking source lock as minted");
+    wasm::db::db_set(bridge_mints_db, &serialize(&update.source), &[])?;
+
+    msg!("[BridgeMintV1] Updating token supply accumulator");
+    wasm::db::db_set(
+        token_supply_db,
+        &serialize(&update.token_id),
+        &serialize(&update.supply_commit),
+    )?;
+
+    // This will just make a snapshot to match the coins one
+    msg!("[BridgeMintV1] Updating nullifiers snapshot");
+    wasm::merkle::sparse_merkle_insert_batch(
+        info_db,
+        nullifiers_db,
+        nullifier_roots_db,
+        MONEY_CONTRACT_LATEST_NULLIFIER_ROOT,
+        &[],
+    )?;
+
+    msg!("[BridgeMintV1] Adding new coin to the set");
+    wasm::db::db_set(coins_db, &serialize(&update.coin), &[])?;
+
+    msg!("[BridgeMintV1] Adding new coin to the Merkle tree");
+    wasm::merkle::merkle_add(
+        info_db,
+        coin_roots_db,
+        MONEY_CONTRACT_LATEST_COIN_ROOT,
+        MONEY_CONTRACT_COIN_MERKLE_TREE,
+        &[MerkleNode::from(update.coin.inner())],
+    )?;
+
+    Ok(())
+}
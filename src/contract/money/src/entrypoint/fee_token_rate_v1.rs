@@ -0,0 +1,142 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError,
+    model::{MoneyFeeTokenRateParamsV1, MoneyFeeTokenRateUpdateV1},
+    MoneyFunction, MONEY_CONTRACT_FEE_GOVERNANCE_ACTIVATION_HEIGHT,
+    MONEY_CONTRACT_FEE_GOVERNANCE_KEY, MONEY_CONTRACT_FEE_TOKEN_RATES_TREE,
+    MONEY_CONTRACT_INFO_TREE,
+};
+
+/// `get_metadata` function for `Money::FeeTokenRateV1`
+pub(crate) fn money_fee_token_rate_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyFeeTokenRateParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // No ZK proofs are involved, the call is authorized by the
+    // fee governance key signature.
+    let zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    let signature_pubkeys: Vec<PublicKey> = vec![params.governance_public];
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::FeeTokenRateV1`
+pub(crate) fn money_fee_token_rate_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyFeeTokenRateParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // The fee governance key is set by the first call, which has to be
+    // executed in the genesis block, or from the activation height on for
+    // networks that launched without one. Afterwards, only the holder of
+    // that key can update the whitelist or hand the key over.
+    let info_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_INFO_TREE)?;
+    match wasm::db::db_get(info_db, MONEY_CONTRACT_FEE_GOVERNANCE_KEY)? {
+        Some(governance_public) => {
+            let governance_public: PublicKey = deserialize(&governance_public)?;
+            if params.governance_public != governance_public {
+                msg!("[FeeTokenRateV1] Error: Call is not signed by the fee governance key");
+                return Err(MoneyError::FeeGovernanceKeyMismatch.into())
+            }
+        }
+        None => {
+            let verifying_block_height = wasm::util::get_verifying_block_height()?;
+            if verifying_block_height != 0 &&
+                verifying_block_height < MONEY_CONTRACT_FEE_GOVERNANCE_ACTIVATION_HEIGHT
+            {
+                msg!(
+                    "[FeeTokenRateV1] Error: Governance key is set in block {}, before activation",
+                    verifying_block_height
+                );
+                return Err(MoneyError::GenesisCallNonGenesisBlock.into())
+            }
+        }
+    }
+
+    if let Some(rate) = params.rate {
+        if rate.native == 0 || rate.token == 0 {
+            msg!("[FeeTokenRateV1] Error: Invalid conversion rate {:?}", rate);
+            return Err(MoneyError::ValueMismatch.into())
+        }
+    }
+
+    // Create a state update
+    let update = MoneyFeeTokenRateUpdateV1 {
+        governance_public: params.new_governance_public.unwrap_or(params.governance_public),
+        token_id: params.token_id,
+        rate: params.rate,
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::FeeTokenRateV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::FeeTokenRateV1`
+pub(crate) fn money_fee_token_rate_process_update_v1(
+    cid: ContractId,
+    update: MoneyFeeTokenRateUpdateV1,
+) -> ContractResult {
+    let info_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_INFO_TREE)?;
+    msg!("[FeeTokenRateV1] Setting fee governance key");
+    wasm::db::db_set(
+        info_db,
+        MONEY_CONTRACT_FEE_GOVERNANCE_KEY,
+        &serialize(&update.governance_public),
+    )?;
+
+    let fee_token_rates_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_FEE_TOKEN_RATES_TREE)?;
+    let key = serialize(&update.token_id);
+    match update.rate {
+        Some(rate) => {
+            msg!("[FeeTokenRateV1] Whitelisting {} for fees at {:?}", update.token_id, rate);
+            wasm::db::db_set(fee_token_rates_db, &key, &serialize(&rate))?;
+        }
+        None => {
+            msg!("[FeeTokenRateV1] Removing {} from fee whitelist", update.token_id);
+            wasm::db::db_del(fee_token_rates_db, &key)?;
+        }
+    }
+
+    Ok(())
+}
@@ -32,16 +32,17 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
 
+use super::transfer_freeze_v1::frozen_tokens_smt;
 use crate::{
     error::MoneyError,
-    model::{MoneyFeeParamsV1, MoneyFeeUpdateV1, DARK_TOKEN_ID},
+    model::{FeeTokenRate, MoneyFeeParamsV1, MoneyFeeUpdateV1, DARK_TOKEN_ID},
     MoneyFunction, MONEY_CONTRACT_COINS_TREE, MONEY_CONTRACT_COIN_MERKLE_TREE,
-    MONEY_CONTRACT_COIN_ROOTS_TREE, MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
-    MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NULLIFIERS_TREE,
-    MONEY_CONTRACT_NULLIFIER_ROOTS_TREE, MONEY_CONTRACT_TOTAL_FEES_PAID,
-    MONEY_CONTRACT_ZKAS_FEE_NS_V1,
+    MONEY_CONTRACT_COIN_ROOTS_TREE, MONEY_CONTRACT_FEE_TOKEN_RATES_TREE, MONEY_CONTRACT_INFO_TREE,
+    MONEY_CONTRACT_LATEST_COIN_ROOT, MONEY_CONTRACT_LATEST_NULLIFIER_ROOT,
+    MONEY_CONTRACT_NULLIFIERS_TREE, MONEY_CONTRACT_NULLIFIER_ROOTS_TREE,
+    MONEY_CONTRACT_TOTAL_FEES_PAID, MONEY_CONTRACT_ZKAS_FEE_NS_V1,
 };
 
 /// `get_metadata` function for `Money::FeeV1`
@@ -53,7 +54,7 @@ pub(crate) fn money_fee_get_metadata_v1(
     let self_ = &calls[call_idx as usize].data;
     // The first 8 bytes here is the u64 fee, so we get the params from that offset.
     // (Plus 1, which is the function identifier byte)
-    let params: MoneyFeeParamsV1 = deserialize(&self_.data[9..])?;
+    let params: MoneyFeeParamsV1 = deserialize_strict(&self_.data[9..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -97,8 +98,8 @@ pub(crate) fn money_fee_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let fee: u64 = deserialize(&self_.data.data[1..9])?;
-    let params: MoneyFeeParamsV1 = deserialize(&self_.data.data[9..])?;
+    let fee: u64 = deserialize_strict(&self_.data.data[1..9])?;
+    let params: MoneyFeeParamsV1 = deserialize_strict(&self_.data.data[9..])?;
 
     // We should have _some_ fee paid...
     if fee == 0 {
@@ -113,21 +114,48 @@ pub(crate) fn money_fee_process_instruction_v1(
     let nullifiers_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIERS_TREE)?;
     let coin_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
 
-    // Fees can only be paid using the native token, so we'll compare
-    // the token commitments with this one:
-    let native_token_commit = poseidon_hash([DARK_TOKEN_ID.inner(), params.token_blind.inner()]);
+    // Fees are denominated in the native token. They can also be paid using
+    // a whitelisted token, in which case we convert the fee to the amount of
+    // that token using its conversion rate.
+    let token_fee = if params.token_id == *DARK_TOKEN_ID {
+        fee
+    } else {
+        let fee_token_rates_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_FEE_TOKEN_RATES_TREE)?;
+        let Some(rate) = wasm::db::db_get(fee_token_rates_db, &serialize(&params.token_id))? else {
+            msg!("[FeeV1] Error: Token {} is not whitelisted for fees", params.token_id);
+            return Err(MoneyError::FeeTokenNotWhitelisted.into())
+        };
+        let rate: FeeTokenRate = deserialize(&rate)?;
+
+        // Transfers of the token must not be frozen. The fee circuit does
+        // not prove this, but here the token is revealed so we can check it.
+        let frozen_leaf = frozen_tokens_smt(cid)?.get_leaf(&params.token_id.inner());
+        if frozen_leaf != pallas::Base::ZERO {
+            msg!("[FeeV1] Error: Token {} transfers are frozen", params.token_id);
+            return Err(MoneyError::TokenTransferFrozen.into())
+        }
+
+        let Some(token_fee) = rate.convert(fee) else {
+            msg!("[FeeV1] Error: Fee conversion overflowed");
+            return Err(MoneyError::InsufficientFee.into())
+        };
+        token_fee
+    };
+
+    // We compare the token commitments with the one of the paid token:
+    let fee_token_commit = poseidon_hash([params.token_id.inner(), params.token_blind.inner()]);
 
     // ===================================
     // Perform the actual state transition
     // ===================================
-    if params.input.token_commit != native_token_commit {
-        msg!("[FeeV1] Error: Input token commitment is not the native token");
+    if params.input.token_commit != fee_token_commit {
+        msg!("[FeeV1] Error: Input token commitment does not match the fee token");
         return Err(MoneyError::TokenMismatch.into())
     }
 
     // Verify that the token commitment matches
-    if params.output.token_commit != native_token_commit {
-        msg!("[FeeV1] Error: Output token commitment is not native token");
+    if params.output.token_commit != fee_token_commit {
+        msg!("[FeeV1] Error: Output token commitment does not match the fee token");
         return Err(MoneyError::TokenMismatch.into())
     }
 
@@ -167,8 +195,8 @@ pub(crate) fn money_fee_process_instruction_v1(
     // Subtract the output value commitment
     valcom_total -= params.output.value_commit;
 
-    // Now subtract the fee from the accumulator
-    valcom_total -= pedersen_commitment_u64(fee, params.fee_value_blind);
+    // Now subtract the fee, in the paid token, from the accumulator
+    valcom_total -= pedersen_commitment_u64(token_fee, params.fee_value_blind);
 
     // If the accumulator is not back in its initial; state, that means there
     // is a value mismatch betweeen inputs and outputs.
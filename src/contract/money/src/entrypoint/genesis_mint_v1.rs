@@ -24,7 +24,7 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
 
 use crate::{
     error::MoneyError,
@@ -42,7 +42,7 @@ pub(crate) fn money_genesis_mint_get_metadata_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyGenesisMintParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyGenesisMintParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -77,7 +77,7 @@ pub(crate) fn money_genesis_mint_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyGenesisMintParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyGenesisMintParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // Verify this contract call is verified against genesis block(0).
     let verifying_block_height = wasm::util::get_verifying_block_height()?;
@@ -0,0 +1,108 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use darkfi_sdk::{
+    crypto::{
+        poseidon_hash, spend_hook::spend_hook_child, ContractId, FuncRef, PublicKey,
+        MONEY_CONTRACT_ID,
+    },
+    dark_tree::DarkLeaf,
+    error::{ContractError, GenericResult},
+    msg,
+    pasta::pallas,
+    ContractCall,
+};
+use darkfi_serial::{deserialize_strict, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError,
+    model::{MoneyGroupSpendParamsV1, MoneyTransferParamsV1},
+    MoneyFunction,
+};
+
+/// Read the `Money::Transfer` call spending the group coins, and check
+/// the revealed custody terms open every input's `user_data_enc`.
+/// Unlike the other hooked functions, the group can spend any number of
+/// coins into any number of outputs, e.g. a payment and its change.
+fn group_spend_transfer(
+    calls: &[DarkLeaf<ContractCall>],
+    call_idx: u32,
+    params: &MoneyGroupSpendParamsV1,
+) -> GenericResult<MoneyTransferParamsV1> {
+    let transfer_func =
+        FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::TransferV1 as u8 };
+    let xfer_call = spend_hook_child(calls, call_idx, &transfer_func)?;
+    let xfer_params: MoneyTransferParamsV1 = deserialize_strict(&xfer_call.data[1..])?;
+
+    if xfer_params.inputs.len() != params.user_data_blinds.len() {
+        msg!("[GroupSpendV1] Error: Expected a user data blind for each transfer input");
+        return Err(MoneyError::GroupTermsMismatch.into())
+    }
+
+    let bulla = params.custody.to_bulla();
+    for (input, blind) in xfer_params.inputs.iter().zip(&params.user_data_blinds) {
+        if input.user_data_enc != poseidon_hash([bulla, blind.inner()]) {
+            msg!("[GroupSpendV1] Error: Group custody terms mismatch");
+            return Err(MoneyError::GroupTermsMismatch.into())
+        }
+    }
+
+    Ok(xfer_params)
+}
+
+/// `get_metadata` function for `Money::GroupSpendV1`
+pub(crate) fn money_group_spend_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyGroupSpendParamsV1 = deserialize_strict(&self_.data[1..])?;
+    group_spend_transfer(&calls, call_idx, &params)?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+
+    // The spend must be signed by the group, which the members
+    // do with an aggregated FROST signature.
+    let signature_pubkeys: Vec<PublicKey> = vec![params.custody.group_public];
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::GroupSpendV1`
+pub(crate) fn money_group_spend_process_instruction_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyGroupSpendParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // The child transfer spends the group coins, whose spend hook
+    // makes Money::Transfer check this is its parent call.
+    group_spend_transfer(&calls, call_idx, &params)?;
+
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::GroupSpendV1 as u8)?;
+    Ok(update_data)
+}
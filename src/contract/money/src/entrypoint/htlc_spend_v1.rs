@@ -0,0 +1,114 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{poseidon_hash, ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::ContractError,
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize_strict, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError, model::MoneyHtlcSpendParamsV1, spend_hook::spend_hook_transfer,
+    MoneyFunction,
+};
+
+/// `get_metadata` function for `Money::HtlcSpendV1`
+pub(crate) fn money_htlc_spend_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyHtlcSpendParamsV1 = deserialize_strict(&self_.data[1..])?;
+    // The HTLC coin is spent whole into a single coin
+    let xfer_params = spend_hook_transfer(&calls, call_idx, 1, 1)?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+
+    // The revealed terms must be the ones the spent coin commits to
+    let user_data_enc = poseidon_hash([params.htlc.to_bulla(), params.user_data_blind.inner()]);
+    if xfer_params.inputs[0].user_data_enc != user_data_enc {
+        msg!("[HtlcSpendV1] Error: HTLC terms mismatch");
+        return Err(MoneyError::HtlcTermsMismatch.into())
+    }
+
+    // Claims are signed by the recipient and refunds by the sender.
+    // process_instruction() checks the expiry against the block height.
+    let signer = match params.preimage {
+        Some(ref preimage) => {
+            if !params.htlc.unlocks(preimage) {
+                msg!("[HtlcSpendV1] Error: Preimage doesn't match the hashlock");
+                return Err(MoneyError::HtlcInvalidPreimage.into())
+            }
+            params.htlc.recipient
+        }
+        None => params.htlc.sender,
+    };
+    let signature_pubkeys: Vec<PublicKey> = vec![signer];
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::HtlcSpendV1`
+pub(crate) fn money_htlc_spend_process_instruction_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyHtlcSpendParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // The child transfer spends the HTLC coin, whose spend hook
+    // makes Money::Transfer check this is its parent call.
+    spend_hook_transfer(&calls, call_idx, 1, 1)?;
+
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    match params.preimage {
+        Some(_) if current_height >= params.htlc.expiry => {
+            msg!(
+                "[HtlcSpendV1] Error: Claim after expiry {}, current height: {}",
+                params.htlc.expiry,
+                current_height
+            );
+            return Err(MoneyError::HtlcExpired.into())
+        }
+        None if current_height < params.htlc.expiry => {
+            msg!(
+                "[HtlcSpendV1] Error: Refund before expiry {}, current height: {}",
+                params.htlc.expiry,
+                current_height
+            );
+            return Err(MoneyError::HtlcNotExpired.into())
+        }
+        _ => {}
+    }
+
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::HtlcSpendV1 as u8)?;
+    Ok(update_data)
+}
@@ -0,0 +1,189 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{
+        pasta_prelude::*,
+        pedersen_commitment_u64, poseidon_hash,
+        smt::{
+            wasmdb::{SmtWasmDbStorage, SmtWasmFp},
+            PoseidonFp, EMPTY_NODES_FP,
+        },
+        ContractId, FuncId, PublicKey,
+    },
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use super::transfer_freeze_v1::frozen_root_is_valid;
+use crate::{
+    error::MoneyError,
+    model::{MoneyNftBurnParamsV1, MoneyNftBurnUpdateV1, Nft},
+    MoneyFunction, MONEY_CONTRACT_COIN_MERKLE_TREE, MONEY_CONTRACT_COIN_ROOTS_TREE,
+    MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
+    MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NFTS_TREE, MONEY_CONTRACT_NULLIFIERS_TREE,
+    MONEY_CONTRACT_NULLIFIER_ROOTS_TREE, MONEY_CONTRACT_ZKAS_BURN_NS_V1,
+};
+
+/// `get_metadata` function for `Money::NftBurnV1`
+pub(crate) fn money_nft_burn_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyNftBurnParamsV1 = deserialize_strict(&self_.data[1..])?;
+    let input = &params.input;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let signature_pubkeys: Vec<PublicKey> = vec![input.signature_public];
+
+    let value_coords = input.value_commit.to_affine().coordinates().unwrap();
+    let (sig_x, sig_y) = input.signature_public.xy();
+
+    // The burned coin is spent with the regular `Burn_V1` proof. Hooked
+    // coins can't be burned, since the hook would be bypassed.
+    zk_public_inputs.push((
+        MONEY_CONTRACT_ZKAS_BURN_NS_V1.to_string(),
+        vec![
+            input.nullifier.inner(),
+            *value_coords.x(),
+            *value_coords.y(),
+            input.token_commit,
+            input.merkle_root.inner(),
+            input.frozen_root,
+            input.user_data_enc,
+            FuncId::none().inner(),
+            sig_x,
+            sig_y,
+        ],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::NftBurnV1`
+pub(crate) fn money_nft_burn_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyNftBurnParamsV1 = deserialize_strict(&self_.data[1..])?;
+    let input = &params.input;
+
+    let nullifiers_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIERS_TREE)?;
+    let coin_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
+    let nfts_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NFTS_TREE)?;
+
+    if !wasm::db::db_contains_key(coin_roots_db, &serialize(&input.merkle_root))? {
+        msg!("[NftBurnV1] Error: Merkle root not found in previous state");
+        return Err(MoneyError::TransferMerkleRootNotFound.into())
+    }
+
+    if !frozen_root_is_valid(cid, &input.frozen_root)? {
+        msg!("[NftBurnV1] Error: Frozen tokens root not found or expired");
+        return Err(MoneyError::FrozenRootNotFound.into())
+    }
+
+    let smt_store = SmtWasmDbStorage::new(nullifiers_db);
+    let smt = SmtWasmFp::new(smt_store, PoseidonFp::new(), &EMPTY_NODES_FP);
+    if smt.get_leaf(&input.nullifier.inner()) != pallas::Base::ZERO {
+        msg!("[NftBurnV1] Error: Duplicate nullifier found");
+        return Err(MoneyError::DuplicateNullifier.into())
+    }
+
+    // Opening the commitments shows the spent coin is the NFT's single unit
+    if input.value_commit != pedersen_commitment_u64(1, params.value_blind) ||
+        input.token_commit !=
+            poseidon_hash([params.token_id.inner(), params.token_blind.inner()])
+    {
+        msg!("[NftBurnV1] Error: Input commitments don't open to NFT {}", params.token_id);
+        return Err(MoneyError::NftCommitMismatch.into())
+    }
+
+    let Some(nft) = wasm::db::db_get(nfts_db, &serialize(&params.token_id))? else {
+        msg!("[NftBurnV1] Error: NFT {} not found", params.token_id);
+        return Err(MoneyError::NftNotFound.into())
+    };
+    let nft: Nft = deserialize(&nft)?;
+
+    if nft.burned {
+        msg!("[NftBurnV1] Error: NFT {} already burned", params.token_id);
+        return Err(MoneyError::NftAlreadyBurned.into())
+    }
+
+    // Create a state update
+    let update = MoneyNftBurnUpdateV1 { token_id: params.token_id, nullifier: input.nullifier };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::NftBurnV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::NftBurnV1`
+pub(crate) fn money_nft_burn_process_update_v1(
+    cid: ContractId,
+    update: MoneyNftBurnUpdateV1,
+) -> ContractResult {
+    // Grab all db handles we want to work on
+    let info_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_INFO_TREE)?;
+    let nullifiers_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIERS_TREE)?;
+    let coin_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
+    let nullifier_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIER_ROOTS_TREE)?;
+    let nfts_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NFTS_TREE)?;
+
+    msg!("[NftBurnV1] Marking NFT {} as burned", update.token_id);
+    let mut nft: Nft =
+        deserialize(&wasm::db::db_get(nfts_db, &serialize(&update.token_id))?.unwrap())?;
+    nft.burned = true;
+    wasm::db::db_set(nfts_db, &serialize(&update.token_id), &serialize(&nft))?;
+
+    msg!("[NftBurnV1] Adding nullifier to the set");
+    wasm::merkle::sparse_merkle_insert_batch(
+        info_db,
+        nullifiers_db,
+        nullifier_roots_db,
+        MONEY_CONTRACT_LATEST_NULLIFIER_ROOT,
+        &[update.nullifier.inner()],
+    )?;
+
+    // This will just make a snapshot to match the nullifiers one
+    msg!("[NftBurnV1] Updating coins snapshot");
+    wasm::merkle::merkle_add(
+        info_db,
+        coin_roots_db,
+        MONEY_CONTRACT_LATEST_COIN_ROOT,
+        MONEY_CONTRACT_COIN_MERKLE_TREE,
+        &[],
+    )?;
+
+    Ok(())
+}
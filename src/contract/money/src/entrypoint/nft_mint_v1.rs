@@ -0,0 +1,147 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{ContractId, FuncRef, MerkleNode, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError,
+    model::{MoneyNftMintParamsV1, MoneyNftMintUpdateV1, Nft},
+    MoneyFunction, MONEY_CONTRACT_COINS_TREE, MONEY_CONTRACT_COIN_MERKLE_TREE,
+    MONEY_CONTRACT_COIN_ROOTS_TREE, MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
+    MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NFTS_TREE, MONEY_CONTRACT_NULLIFIERS_TREE,
+    MONEY_CONTRACT_NULLIFIER_ROOTS_TREE, MONEY_CONTRACT_ZKAS_NFT_MINT_NS_V1,
+};
+
+/// `get_metadata` function for `Money::NftMintV1`
+pub(crate) fn money_nft_mint_get_metadata_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyNftMintParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify.
+    let signature_pubkeys: Vec<PublicKey> = vec![];
+
+    // The token ID is derived with this function as its auth parent,
+    // so NFT token IDs can never collide with fungible ones.
+    let func_id =
+        FuncRef { contract_id: cid, func_code: MoneyFunction::NftMintV1 as u8 }.to_func_id();
+
+    zk_public_inputs.push((
+        MONEY_CONTRACT_ZKAS_NFT_MINT_NS_V1.to_string(),
+        vec![func_id.inner(), params.metadata, params.token_id.inner(), params.coin.inner()],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::NftMintV1`
+pub(crate) fn money_nft_mint_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyNftMintParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // The circuit fixes the coin value to 1, so the supply can only be
+    // kept at 1 by allowing each token ID to be minted once.
+    let nfts_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NFTS_TREE)?;
+    if wasm::db::db_contains_key(nfts_db, &serialize(&params.token_id))? {
+        msg!("[NftMintV1] Error: NFT {} already minted", params.token_id);
+        return Err(MoneyError::NftAlreadyMinted.into())
+    }
+
+    let coins_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COINS_TREE)?;
+    if wasm::db::db_contains_key(coins_db, &serialize(&params.coin))? {
+        msg!("[NftMintV1] Error: Duplicate coin in output");
+        return Err(MoneyError::DuplicateCoin.into())
+    }
+
+    // Create a state update
+    let update = MoneyNftMintUpdateV1 {
+        token_id: params.token_id,
+        metadata: params.metadata,
+        coin: params.coin,
+    };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::NftMintV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::NftMintV1`
+pub(crate) fn money_nft_mint_process_update_v1(
+    cid: ContractId,
+    update: MoneyNftMintUpdateV1,
+) -> ContractResult {
+    // Grab all db handles we want to work on
+    let info_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_INFO_TREE)?;
+    let coins_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COINS_TREE)?;
+    let nullifiers_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIERS_TREE)?;
+    let coin_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_COIN_ROOTS_TREE)?;
+    let nullifier_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NULLIFIER_ROOTS_TREE)?;
+    let nfts_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_NFTS_TREE)?;
+
+    msg!("[NftMintV1] Recording NFT {}", update.token_id);
+    let nft = Nft { metadata: update.metadata, burned: false };
+    wasm::db::db_set(nfts_db, &serialize(&update.token_id), &serialize(&nft))?;
+
+    // This will just make a snapshot to match the coins one
+    msg!("[NftMintV1] Updating nullifiers snapshot");
+    wasm::merkle::sparse_merkle_insert_batch(
+        info_db,
+        nullifiers_db,
+        nullifier_roots_db,
+        MONEY_CONTRACT_LATEST_NULLIFIER_ROOT,
+        &vec![],
+    )?;
+
+    msg!("[NftMintV1] Adding new coin to the set");
+    wasm::db::db_set(coins_db, &serialize(&update.coin), &[])?;
+
+    msg!("[NftMintV1] Adding new coin to the Merkle tree");
+    let coins = vec![MerkleNode::from(update.coin.inner())];
+    wasm::merkle::merkle_add(
+        info_db,
+        coin_roots_db,
+        MONEY_CONTRACT_LATEST_COIN_ROOT,
+        MONEY_CONTRACT_COIN_MERKLE_TREE,
+        &coins,
+    )?;
+
+    Ok(())
+}
@@ -31,9 +31,12 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
 
-use super::transfer_v1::{money_transfer_get_metadata_v1, money_transfer_process_update_v1};
+use super::{
+    transfer_freeze_v1::frozen_root_is_valid,
+    transfer_v1::{money_transfer_get_metadata_v1, money_transfer_process_update_v1},
+};
 use crate::{
     error::MoneyError,
     model::{MoneyTransferParamsV1, MoneyTransferUpdateV1},
@@ -58,7 +61,7 @@ pub(crate) fn money_otcswap_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyTransferParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyTransferParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // The atomic swap is able to use the same parameters as `TransferV1`.
     // In here we just have a different state transition where we enforce
@@ -113,16 +116,22 @@ pub(crate) fn money_otcswap_process_instruction_v1(
     let smt_store = SmtWasmDbStorage::new(nullifiers_db);
     let smt = SmtWasmFp::new(smt_store, hasher, &EMPTY_NODES_FP);
 
+    // For now, make sure that the inputs' spend hooks are zero.
+    // This should however be allowed to some extent, e.g. if we
+    // want a DAO to be able to do an atomic swap.
+    if calls[call_idx as usize].parent_index.is_some() {
+        msg!("[OtcSwapV1] Error: Unable to swap coins with spend_hook != 0");
+        return Err(MoneyError::SpendHookNonZero.into())
+    }
+
+    // A swap does not enforce any other calls, so it must not have children.
+    if !calls[call_idx as usize].children_indexes.is_empty() {
+        msg!("[OtcSwapV1] Error: Call must not have any children");
+        return Err(MoneyError::ChildrenIndexesNonEmpty.into())
+    }
+
     msg!("[OtcSwapV1] Iterating over anonymous inputs");
     for (i, input) in params.inputs.iter().enumerate() {
-        // For now, make sure that the inputs' spend hooks are zero.
-        // This should however be allowed to some extent, e.g. if we
-        // want a DAO to be able to do an atomic swap.
-        if calls[call_idx as usize].parent_index.is_some() {
-            msg!("[OtcSwapV1] Error: Unable to swap coins with spend_hook != 0 (input {})", i);
-            return Err(MoneyError::SpendHookNonZero.into())
-        }
-
         // The Merkle root is used to know whether this coin
         // has existed in a previous state.
         if !wasm::db::db_contains_key(coin_roots_db, &serialize(&input.merkle_root))? {
@@ -130,6 +139,11 @@ pub(crate) fn money_otcswap_process_instruction_v1(
             return Err(MoneyError::SwapMerkleRootNotFound.into())
         }
 
+        if !frozen_root_is_valid(cid, &input.frozen_root)? {
+            msg!("[OtcSwapV1] Error: Frozen tokens root not found or expired (input {})", i);
+            return Err(MoneyError::FrozenRootNotFound.into())
+        }
+
         // The nullifiers should not already exist. It is the double-spend protection.
         if new_nullifiers.contains(&input.nullifier) ||
             smt.get_leaf(&input.nullifier.inner()) != empty_leaf
@@ -24,7 +24,7 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
 
 use crate::{
     error::MoneyError,
@@ -39,7 +39,7 @@ pub(crate) fn money_token_freeze_get_metadata_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyTokenFreezeParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyTokenFreezeParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -70,7 +70,7 @@ pub(crate) fn money_token_freeze_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyTokenFreezeParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyTokenFreezeParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // We just check if the mint was already frozen beforehand
     let token_freeze_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_FREEZE_TREE)?;
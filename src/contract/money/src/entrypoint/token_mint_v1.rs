@@ -24,7 +24,7 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
 
 use crate::{
     error::MoneyError,
@@ -42,7 +42,7 @@ pub(crate) fn money_token_mint_get_metadata_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyTokenMintParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyTokenMintParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     let parent_idx = calls[call_idx as usize].parent_index.unwrap();
     let parent_call = &calls[parent_idx].data;
@@ -77,7 +77,7 @@ pub(crate) fn money_token_mint_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyTokenMintParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyTokenMintParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // We have to check if the token mint is frozen, and if by some chance
     // the minted coin has existed already.
@@ -0,0 +1,144 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{pasta_prelude::*, ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError,
+    model::{MoneyTokenSupplyCapParamsV1, MoneyTokenSupplyCapUpdateV1},
+    MoneyFunction, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE, MONEY_CONTRACT_TOKEN_FREEZE_TREE,
+    MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE, MONEY_CONTRACT_TOKEN_SUPPLY_TREE,
+    MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1,
+};
+
+/// `get_metadata` function for `Money::TokenSupplyCapV1`
+pub(crate) fn money_token_supply_cap_get_metadata_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyTokenSupplyCapParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let signature_pubkeys: Vec<PublicKey> = vec![params.mint_public];
+
+    let (mint_x, mint_y) = params.mint_public.xy();
+
+    // If the token has already been minted, the mint authority has to prove
+    // the supply so far doesn't exceed the cap. Otherwise, any cap holds and
+    // we just verify that the token ID is properly derived from the authority.
+    let token_supply_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_TREE)?;
+    match wasm::db::db_get(token_supply_db, &serialize(&params.token_id))? {
+        Some(v) => {
+            let supply_commit: pallas::Point = deserialize(&v)?;
+            let supply_coords = supply_commit.to_affine().coordinates().unwrap();
+            zk_public_inputs.push((
+                MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1.to_string(),
+                vec![
+                    mint_x,
+                    mint_y,
+                    params.token_id.inner(),
+                    *supply_coords.x(),
+                    *supply_coords.y(),
+                    pallas::Base::from(params.supply_cap),
+                ],
+            ));
+        }
+        None => {
+            zk_public_inputs.push((
+                MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1.to_string(),
+                vec![mint_x, mint_y, params.token_id.inner()],
+            ));
+        }
+    }
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::TokenSupplyCapV1`
+pub(crate) fn money_token_supply_cap_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyTokenSupplyCapParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // A frozen mint can't issue anything anymore, so there is nothing to cap
+    let token_freeze_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_FREEZE_TREE)?;
+    if wasm::db::db_contains_key(token_freeze_db, &serialize(&params.token_id))? {
+        msg!("[TokenSupplyCapV1] Error: Token mint for {} is frozen", params.token_id);
+        return Err(MoneyError::TokenMintFrozen.into())
+    }
+
+    // Bridge mints are accounted in the supply accumulator, but they
+    // don't prove it stays under the cap, so bridged tokens can't be capped.
+    let bridge_authorities_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE)?;
+    if wasm::db::db_contains_key(bridge_authorities_db, &serialize(&params.token_id))? {
+        msg!("[TokenSupplyCapV1] Error: Token {} is bridged", params.token_id);
+        return Err(MoneyError::TokenBridged.into())
+    }
+
+    // The cap can only be set once
+    let token_supply_caps_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE)?;
+    if wasm::db::db_contains_key(token_supply_caps_db, &serialize(&params.token_id))? {
+        msg!("[TokenSupplyCapV1] Error: Supply cap for {} is already set", params.token_id);
+        return Err(MoneyError::TokenSupplyCapExists.into())
+    }
+
+    // Create a state update.
+    let update =
+        MoneyTokenSupplyCapUpdateV1 { token_id: params.token_id, supply_cap: params.supply_cap };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::TokenSupplyCapV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::TokenSupplyCapV1`
+pub(crate) fn money_token_supply_cap_process_update_v1(
+    cid: ContractId,
+    update: MoneyTokenSupplyCapUpdateV1,
+) -> ContractResult {
+    let token_supply_caps_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE)?;
+    msg!("[TokenSupplyCapV1] Capping supply of token {} at {}", update.token_id, update.supply_cap);
+    wasm::db::db_set(
+        token_supply_caps_db,
+        &serialize(&update.token_id),
+        &serialize(&update.supply_cap),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{
+        pasta_prelude::*,
+        smt::{
+            wasmdb::{SmtWasmDbStorage, SmtWasmFp},
+            PoseidonFp, EMPTY_NODES_FP,
+        },
+        ContractId, PublicKey,
+    },
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError,
+    model::{MoneyTransferFreezeParamsV1, MoneyTransferFreezeUpdateV1},
+    MoneyFunction, MONEY_CONTRACT_FROZEN_ROOTS_TREE, MONEY_CONTRACT_FROZEN_ROOT_GRACE_BLOCKS,
+    MONEY_CONTRACT_FROZEN_TOKENS_TREE, MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1,
+};
+
+/// Open the SMT holding the set of tokens whose transfers are frozen
+pub(crate) fn frozen_tokens_smt(cid: ContractId) -> Result<SmtWasmFp, ContractError> {
+    let frozen_tokens_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_FROZEN_TOKENS_TREE)?;
+    let smt_store = SmtWasmDbStorage::new(frozen_tokens_db);
+    Ok(SmtWasmFp::new(smt_store, PoseidonFp::new(), &EMPTY_NODES_FP))
+}
+
+/// Check that `root` is a frozen tokens root a `Burn_V1` proof may be made
+/// against: either the current root, or one superseded at most
+/// [`MONEY_CONTRACT_FROZEN_ROOT_GRACE_BLOCKS`] blocks ago.
+pub(crate) fn frozen_root_is_valid(
+    cid: ContractId,
+    root: &pallas::Base,
+) -> Result<bool, ContractError> {
+    let frozen_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_FROZEN_ROOTS_TREE)?;
+    let Some(superseded_at) = wasm::db::db_get(frozen_roots_db, &serialize(root))? else {
+        return Ok(false)
+    };
+
+    let superseded_at: Option<u32> = deserialize(&superseded_at)?;
+    let Some(superseded_at) = superseded_at else { return Ok(true) };
+
+    let verifying_block_height = wasm::util::get_verifying_block_height()?;
+    Ok(verifying_block_height <=
+        superseded_at.saturating_add(MONEY_CONTRACT_FROZEN_ROOT_GRACE_BLOCKS))
+}
+
+/// Record the frozen tokens root change from `old_root` to `new_root`.
+/// k=root, v=Option<block height the root was superseded at>
+fn record_frozen_root(
+    cid: ContractId,
+    old_root: pallas::Base,
+    new_root: pallas::Base,
+    block_height: u32,
+) -> ContractResult {
+    let frozen_roots_db = wasm::db::db_lookup(cid, MONEY_CONTRACT_FROZEN_ROOTS_TREE)?;
+    wasm::db::db_set(frozen_roots_db, &serialize(&old_root), &serialize(&Some(block_height)))?;
+    wasm::db::db_set(frozen_roots_db, &serialize(&new_root), &serialize(&None::<u32>))?;
+    Ok(())
+}
+
+/// `get_metadata` function for `Money::TransferFreezeV1` and `Money::TransferUnfreezeV1`
+pub(crate) fn money_transfer_freeze_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyTransferFreezeParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify
+    let signature_pubkeys: Vec<PublicKey> = vec![params.mint_public];
+
+    // The freeze authority is the mint authority, so we reuse the
+    // `TokenFreeze_V1` circuit to verify the token ID derives from it.
+    let (mint_x, mint_y) = params.mint_public.xy();
+    zk_public_inputs.push((
+        MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1.to_string(),
+        vec![mint_x, mint_y, params.token_id.inner()],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::TransferFreezeV1`
+pub(crate) fn money_transfer_freeze_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyTransferFreezeParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    let smt = frozen_tokens_smt(cid)?;
+    if smt.get_leaf(&params.token_id.inner()) != pallas::Base::ZERO {
+        msg!("[TransferFreezeV1] Error: Transfers of {} are already frozen", params.token_id);
+        return Err(MoneyError::TokenTransferAlreadyFrozen.into())
+    }
+
+    let block_height = wasm::util::get_verifying_block_height()?;
+    let update = MoneyTransferFreezeUpdateV1 { token_id: params.token_id, block_height };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::TransferFreezeV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::TransferFreezeV1`
+pub(crate) fn money_transfer_freeze_process_update_v1(
+    cid: ContractId,
+    update: MoneyTransferFreezeUpdateV1,
+) -> ContractResult {
+    let mut smt = frozen_tokens_smt(cid)?;
+    msg!("[TransferFreezeV1] Freezing transfers of token {}", update.token_id);
+    let token_id = update.token_id.inner();
+    let old_root = smt.root();
+    smt.insert_batch(vec![(token_id, token_id)])?;
+    record_frozen_root(cid, old_root, smt.root(), update.block_height)?;
+
+    Ok(())
+}
+
+/// `process_instruction` function for `Money::TransferUnfreezeV1`
+pub(crate) fn money_transfer_unfreeze_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyTransferFreezeParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    let smt = frozen_tokens_smt(cid)?;
+    if smt.get_leaf(&params.token_id.inner()) == pallas::Base::ZERO {
+        msg!("[TransferUnfreezeV1] Error: Transfers of {} are not frozen", params.token_id);
+        return Err(MoneyError::TokenTransferNotFrozen.into())
+    }
+
+    let block_height = wasm::util::get_verifying_block_height()?;
+    let update = MoneyTransferFreezeUpdateV1 { token_id: params.token_id, block_height };
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::TransferUnfreezeV1 as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_update` function for `Money::TransferUnfreezeV1`
+pub(crate) fn money_transfer_unfreeze_process_update_v1(
+    cid: ContractId,
+    update: MoneyTransferFreezeUpdateV1,
+) -> ContractResult {
+    let mut smt = frozen_tokens_smt(cid)?;
+    msg!("[TransferUnfreezeV1] Unfreezing transfers of token {}", update.token_id);
+    let token_id = update.token_id.inner();
+    let old_root = smt.root();
+    smt.remove_leaves(vec![(token_id, token_id)])?;
+    record_frozen_root(cid, old_root, smt.root(), update.block_height)?;
+
+    Ok(())
+}
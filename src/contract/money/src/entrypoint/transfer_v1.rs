@@ -31,8 +31,9 @@ use darkfi_sdk::{
     pasta::pallas,
     wasm, ContractCall,
 };
-use darkfi_serial::{deserialize, serialize, Encodable, WriteExt};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
 
+use super::transfer_freeze_v1::frozen_root_is_valid;
 use crate::{
     error::MoneyError,
     model::{MoneyTransferParamsV1, MoneyTransferUpdateV1},
@@ -40,7 +41,7 @@ use crate::{
     MONEY_CONTRACT_COIN_ROOTS_TREE, MONEY_CONTRACT_INFO_TREE, MONEY_CONTRACT_LATEST_COIN_ROOT,
     MONEY_CONTRACT_LATEST_NULLIFIER_ROOT, MONEY_CONTRACT_NULLIFIERS_TREE,
     MONEY_CONTRACT_NULLIFIER_ROOTS_TREE, MONEY_CONTRACT_ZKAS_BURN_NS_V1,
-    MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+    MONEY_CONTRACT_ZKAS_MINT_NS_V1, MONEY_NOTE_CIPHERTEXT_MAX_LEN,
 };
 
 /// `get_metadata` function for `Money::TransferV1`
@@ -50,7 +51,7 @@ pub(crate) fn money_transfer_get_metadata_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize].data;
-    let params: MoneyTransferParamsV1 = deserialize(&self_.data[1..])?;
+    let params: MoneyTransferParamsV1 = deserialize_strict(&self_.data[1..])?;
 
     // Public inputs for the ZK proofs we have to verify
     let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
@@ -86,6 +87,7 @@ pub(crate) fn money_transfer_get_metadata_v1(
                 *value_coords.y(),
                 input.token_commit,
                 input.merkle_root.inner(),
+                input.frozen_root,
                 input.user_data_enc,
                 spend_hook.inner(),
                 sig_x,
@@ -121,7 +123,7 @@ pub(crate) fn money_transfer_process_instruction_v1(
     calls: Vec<DarkLeaf<ContractCall>>,
 ) -> Result<Vec<u8>, ContractError> {
     let self_ = &calls[call_idx as usize];
-    let params: MoneyTransferParamsV1 = deserialize(&self_.data.data[1..])?;
+    let params: MoneyTransferParamsV1 = deserialize_strict(&self_.data.data[1..])?;
 
     if params.inputs.is_empty() {
         msg!("[TransferV1] Error: No inputs in the call");
@@ -165,6 +167,14 @@ pub(crate) fn money_transfer_process_instruction_v1(
             return Err(MoneyError::TransferMerkleRootNotFound.into())
         }
 
+        // Inputs prove their token is not frozen against a recent root of
+        // the frozen tokens SMT, so a freeze toggle doesn't invalidate the
+        // proofs of pending transactions.
+        if !frozen_root_is_valid(cid, &input.frozen_root)? {
+            msg!("[TransferV1] Error: Frozen tokens root not found or expired (input {})", i);
+            return Err(MoneyError::FrozenRootNotFound.into())
+        }
+
         // The nullifiers should not already exist. It is the double-spend protection.
         if new_nullifiers.contains(&input.nullifier) ||
             smt.get_leaf(&input.nullifier.inner()) != empty_leaf
@@ -183,6 +193,12 @@ pub(crate) fn money_transfer_process_instruction_v1(
     let mut new_coins = Vec::with_capacity(params.outputs.len());
     msg!("[TransferV1] Iterating over anonymous outputs");
     for (i, output) in params.outputs.iter().enumerate() {
+        // Memos are bounded, so the note ciphertext is as well
+        if output.note.ciphertext.len() > MONEY_NOTE_CIPHERTEXT_MAX_LEN {
+            msg!("[TransferV1] Error: Note too large in output {}", i);
+            return Err(MoneyError::TransferNoteTooLarge.into())
+        }
+
         if new_coins.contains(&output.coin) ||
             wasm::db::db_contains_key(coins_db, &serialize(&output.coin))?
         {
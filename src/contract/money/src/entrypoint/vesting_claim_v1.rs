@@ -0,0 +1,100 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{ContractId, FuncRef, PublicKey},
+    dark_tree::DarkLeaf,
+    error::ContractError,
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize_strict, Encodable, WriteExt};
+
+use crate::{
+    error::MoneyError, model::MoneyVestingClaimParamsV1, spend_hook::spend_hook_transfer,
+    MoneyFunction, MONEY_CONTRACT_ZKAS_VESTING_CLAIM_NS_V1,
+};
+
+/// `get_metadata` function for `Money::VestingClaimV1`
+pub(crate) fn money_vesting_claim_get_metadata_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyVestingClaimParamsV1 = deserialize_strict(&self_.data[1..])?;
+    // The vesting coin is spent into the claimed coin and the change coin
+    let xfer_params = spend_hook_transfer(&calls, call_idx, 1, 2)?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let mut zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // The vesting input of the child transfer is signed there
+    let signature_pubkeys: Vec<PublicKey> = vec![];
+
+    // The remaining funds stay locked under this function
+    let vesting_spend_hook =
+        FuncRef { contract_id: cid, func_code: MoneyFunction::VestingClaimV1 as u8 }.to_func_id();
+
+    zk_public_inputs.push((
+        MONEY_CONTRACT_ZKAS_VESTING_CLAIM_NS_V1.to_string(),
+        vec![
+            xfer_params.inputs[0].user_data_enc,
+            pallas::Base::from(params.claim_height),
+            xfer_params.outputs[0].coin.inner(),
+            xfer_params.outputs[1].coin.inner(),
+            vesting_spend_hook.inner(),
+        ],
+    ));
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// `process_instruction` function for `Money::VestingClaimV1`
+pub(crate) fn money_vesting_claim_process_instruction_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize].data;
+    let params: MoneyVestingClaimParamsV1 = deserialize_strict(&self_.data[1..])?;
+
+    // The child transfer spends the vesting coin, whose spend hook
+    // makes Money::Transfer check this is its parent call.
+    spend_hook_transfer(&calls, call_idx, 1, 2)?;
+
+    // The proof shows the funds are unlocked at claim_height
+    let current_height = wasm::util::get_verifying_block_height()? as u64;
+    if current_height < params.claim_height {
+        msg!(
+            "[VestingClaimV1] Error: Claim height {} not reached, current height: {}",
+            params.claim_height,
+            current_height
+        );
+        return Err(MoneyError::VestingClaimHeightNotReached.into())
+    }
+
+    let mut update_data = vec![];
+    update_data.write_u8(MoneyFunction::VestingClaimV1 as u8)?;
+    Ok(update_data)
+}
@@ -103,6 +103,78 @@ pub enum MoneyError {
     // TODO: This should catch-all (TransferMerkle../SwapMerkle...)
     #[error("Coin merkle root not found")]
     CoinMerkleRootNotFound,
+
+    #[error("Token transfers are already frozen")]
+    TokenTransferAlreadyFrozen,
+
+    #[error("Token transfers are not frozen")]
+    TokenTransferNotFrozen,
+
+    #[error("Call is not expected to have children")]
+    ChildrenIndexesNonEmpty,
+
+    #[error("Token is not whitelisted for fee payment")]
+    FeeTokenNotWhitelisted,
+
+    #[error("Fee governance key mismatch")]
+    FeeGovernanceKeyMismatch,
+
+    #[error("Token transfers are frozen")]
+    TokenTransferFrozen,
+
+    #[error("Vesting claim height not reached")]
+    VestingClaimHeightNotReached,
+
+    #[error("HTLC terms don't match the spent coin")]
+    HtlcTermsMismatch,
+
+    #[error("HTLC preimage doesn't match the hashlock")]
+    HtlcInvalidPreimage,
+
+    #[error("HTLC expired")]
+    HtlcExpired,
+
+    #[error("HTLC not expired")]
+    HtlcNotExpired,
+
+    #[error("NFT already minted")]
+    NftAlreadyMinted,
+
+    #[error("NFT not found")]
+    NftNotFound,
+
+    #[error("NFT already burned")]
+    NftAlreadyBurned,
+
+    #[error("NFT burn doesn't open the input commitments")]
+    NftCommitMismatch,
+
+    #[error("Token supply cap already set")]
+    TokenSupplyCapExists,
+
+    #[error("Invalid bridge authority set")]
+    BridgeAuthoritySetInvalid,
+
+    #[error("Bridge authority set not found")]
+    BridgeAuthoritiesNotFound,
+
+    #[error("Bridge mint not authorized by the authority set")]
+    BridgeMintUnauthorized,
+
+    #[error("Bridge mint source already used")]
+    BridgeMintReplayed,
+
+    #[error("Token is bridged")]
+    TokenBridged,
+
+    #[error("Frozen tokens root not found or expired")]
+    FrozenRootNotFound,
+
+    #[error("Group custody terms don't match the spent coins")]
+    GroupTermsMismatch,
+
+    #[error("Output note exceeds the maximum size")]
+    TransferNoteTooLarge,
 }
 
 impl From<MoneyError> for ContractError {
@@ -138,6 +210,30 @@ impl From<MoneyError> for ContractError {
             MoneyError::FeeMissingInputs => Self::Custom(28),
             MoneyError::InsufficientFee => Self::Custom(29),
             MoneyError::CoinMerkleRootNotFound => Self::Custom(30),
+            MoneyError::TokenTransferAlreadyFrozen => Self::Custom(31),
+            MoneyError::TokenTransferNotFrozen => Self::Custom(32),
+            MoneyError::ChildrenIndexesNonEmpty => Self::Custom(33),
+            MoneyError::FeeTokenNotWhitelisted => Self::Custom(34),
+            MoneyError::FeeGovernanceKeyMismatch => Self::Custom(35),
+            MoneyError::TokenTransferFrozen => Self::Custom(36),
+            MoneyError::VestingClaimHeightNotReached => Self::Custom(37),
+            MoneyError::HtlcTermsMismatch => Self::Custom(38),
+            MoneyError::HtlcInvalidPreimage => Self::Custom(39),
+            MoneyError::HtlcExpired => Self::Custom(40),
+            MoneyError::HtlcNotExpired => Self::Custom(41),
+            MoneyError::NftAlreadyMinted => Self::Custom(42),
+            MoneyError::NftNotFound => Self::Custom(43),
+            MoneyError::NftAlreadyBurned => Self::Custom(44),
+            MoneyError::NftCommitMismatch => Self::Custom(45),
+            MoneyError::TokenSupplyCapExists => Self::Custom(46),
+            MoneyError::BridgeAuthoritySetInvalid => Self::Custom(47),
+            MoneyError::BridgeAuthoritiesNotFound => Self::Custom(48),
+            MoneyError::BridgeMintUnauthorized => Self::Custom(49),
+            MoneyError::BridgeMintReplayed => Self::Custom(50),
+            MoneyError::TokenBridged => Self::Custom(51),
+            MoneyError::FrozenRootNotFound => Self::Custom(52),
+            MoneyError::GroupTermsMismatch => Self::Custom(53),
+            MoneyError::TransferNoteTooLarge => Self::Custom(54),
         }
     }
 }
@@ -17,9 +17,15 @@
  */
 
 //! Smart contract implementing money transfers, atomic swaps, token
-//! minting and freezing, and staking/unstaking of consensus tokens.
+//! minting, supply capping and freezing, transfer freezing, fee payment in whitelisted
+//! tokens, vesting payments, hash time-locked payments, non-fungible
+//! tokens, bridging to and from external chains, and staking/unstaking
+//! of consensus tokens.
 
-use darkfi_sdk::error::ContractError;
+use darkfi_sdk::{
+    crypto::note::{AEAD_TAG_SIZE, XCHACHA_NONCE_SIZE},
+    error::ContractError,
+};
 
 /// Functions available in the contract
 #[repr(u8)]
@@ -33,6 +39,18 @@ pub enum MoneyFunction {
     TokenFreezeV1 = 0x05,
     PoWRewardV1 = 0x06,
     AuthTokenMintV1 = 0x07,
+    TransferFreezeV1 = 0x08,
+    TransferUnfreezeV1 = 0x09,
+    FeeTokenRateV1 = 0x0a,
+    VestingClaimV1 = 0x0b,
+    HtlcSpendV1 = 0x0c,
+    NftMintV1 = 0x0d,
+    NftBurnV1 = 0x0e,
+    TokenSupplyCapV1 = 0x0f,
+    BridgeBurnV1 = 0x10,
+    BridgeAuthoritySetV1 = 0x11,
+    BridgeMintV1 = 0x12,
+    GroupSpendV1 = 0x13,
 }
 // ANCHOR_END: money-function
 
@@ -49,6 +67,18 @@ impl TryFrom<u8> for MoneyFunction {
             0x05 => Ok(Self::TokenFreezeV1),
             0x06 => Ok(Self::PoWRewardV1),
             0x07 => Ok(Self::AuthTokenMintV1),
+            0x08 => Ok(Self::TransferFreezeV1),
+            0x09 => Ok(Self::TransferUnfreezeV1),
+            0x0a => Ok(Self::FeeTokenRateV1),
+            0x0b => Ok(Self::VestingClaimV1),
+            0x0c => Ok(Self::HtlcSpendV1),
+            0x0d => Ok(Self::NftMintV1),
+            0x0e => Ok(Self::NftBurnV1),
+            0x0f => Ok(Self::TokenSupplyCapV1),
+            0x10 => Ok(Self::BridgeBurnV1),
+            0x11 => Ok(Self::BridgeAuthoritySetV1),
+            0x12 => Ok(Self::BridgeMintV1),
+            0x13 => Ok(Self::GroupSpendV1),
             _ => Err(ContractError::InvalidFunction),
         }
     }
@@ -60,6 +90,9 @@ pub mod error;
 /// Call parameters definitions
 pub mod model;
 
+/// Helpers for contracts hooking Money coins
+pub mod spend_hook;
+
 #[cfg(not(feature = "no-entrypoint"))]
 /// WASM entrypoint functions
 pub mod entrypoint;
@@ -75,6 +108,16 @@ pub const MONEY_CONTRACT_COIN_ROOTS_TREE: &str = "coin_roots";
 pub const MONEY_CONTRACT_NULLIFIERS_TREE: &str = "nullifiers";
 pub const MONEY_CONTRACT_NULLIFIER_ROOTS_TREE: &str = "nullifier_roots";
 pub const MONEY_CONTRACT_TOKEN_FREEZE_TREE: &str = "token_freezes";
+pub const MONEY_CONTRACT_FROZEN_TOKENS_TREE: &str = "frozen_tokens";
+pub const MONEY_CONTRACT_FROZEN_ROOTS_TREE: &str = "frozen_roots";
+pub const MONEY_CONTRACT_FEE_TOKEN_RATES_TREE: &str = "fee_token_rates";
+pub const MONEY_CONTRACT_TOKEN_SUPPLY_TREE: &str = "token_supply";
+pub const MONEY_CONTRACT_NFTS_TREE: &str = "nfts";
+pub const MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE: &str = "token_supply_caps";
+pub const MONEY_CONTRACT_BRIDGE_BURNS_TREE: &str = "bridge_burns";
+pub const MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE: &str = "bridge_burn_roots";
+pub const MONEY_CONTRACT_BRIDGE_AUTHORITIES_TREE: &str = "bridge_authorities";
+pub const MONEY_CONTRACT_BRIDGE_MINTS_TREE: &str = "bridge_mints";
 
 // These are keys inside the info tree
 pub const MONEY_CONTRACT_DB_VERSION: &[u8] = b"db_version";
@@ -82,6 +125,30 @@ pub const MONEY_CONTRACT_COIN_MERKLE_TREE: &[u8] = b"coins_tree";
 pub const MONEY_CONTRACT_LATEST_COIN_ROOT: &[u8] = b"last_coins_root";
 pub const MONEY_CONTRACT_LATEST_NULLIFIER_ROOT: &[u8] = b"last_nullifiers_root";
 pub const MONEY_CONTRACT_TOTAL_FEES_PAID: &[u8] = b"total_fees_paid";
+pub const MONEY_CONTRACT_FEE_GOVERNANCE_KEY: &[u8] = b"fee_governance_key";
+pub const MONEY_CONTRACT_BRIDGE_BURN_MERKLE_TREE: &[u8] = b"bridge_burns_tree";
+pub const MONEY_CONTRACT_LATEST_BRIDGE_BURN_ROOT: &[u8] = b"last_bridge_burns_root";
+
+/// Number of blocks a superseded frozen tokens root stays valid for
+/// `Burn_V1` proofs, so a freeze toggle doesn't invalidate every pending
+/// transaction. Tokens frozen less than this many blocks ago can still
+/// be moved by proofs made against an older root.
+pub const MONEY_CONTRACT_FROZEN_ROOT_GRACE_BLOCKS: u32 = 10;
+
+/// Block height from which networks that didn't set a fee governance key
+/// in their genesis block can set one. Until then, fees can only be paid
+/// in the native token.
+pub const MONEY_CONTRACT_FEE_GOVERNANCE_ACTIVATION_HEIGHT: u32 = 50_000;
+
+/// Maximum length in bytes of the memo attached to a coin's note.
+/// Memos are zero-padded to this length, so notes don't leak it.
+pub const MONEY_NOTE_MEMO_MAX_LEN: usize = 512;
+
+/// Maximum length in bytes of an output note ciphertext. This fits the
+/// fixed-size note fields, a payment ID opening, a full memo along with
+/// its length prefix, and the AEAD nonce and tag.
+pub const MONEY_NOTE_CIPHERTEXT_MAX_LEN: usize =
+    8 + 6 * 32 + (1 + 2 * 32) + (3 + MONEY_NOTE_MEMO_MAX_LEN) + XCHACHA_NONCE_SIZE + AEAD_TAG_SIZE;
 
 /// Precalculated root hash for a tree containing only a single Fp::ZERO coin.
 /// Used to save gas.
@@ -102,3 +169,9 @@ pub const MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1: &str = "TokenMint_V1";
 pub const MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1: &str = "TokenFreeze_V1";
 /// zkas token auth mint circuit namespace
 pub const MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1: &str = "AuthTokenMint_V1";
+/// zkas vesting claim circuit namespace
+pub const MONEY_CONTRACT_ZKAS_VESTING_CLAIM_NS_V1: &str = "VestingClaim_V1";
+/// zkas NFT mint circuit namespace
+pub const MONEY_CONTRACT_ZKAS_NFT_MINT_NS_V1: &str = "NftMint_V1";
+/// zkas token supply cap circuit namespace
+pub const MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1: &str = "TokenSupplyCap_V1";
@@ -113,6 +113,97 @@ impl TokenAttributes {
     }
 }
 
+/// Schedule of funds locked for a recipient, unlocking linearly by block
+/// height between `start_height` and `end_height`. Nothing can be claimed
+/// before `cliff_height`. Setting `cliff_height` to `end_height` makes
+/// everything unlock at once.
+///
+/// Vesting coins are owned by the recipient, with their spend hook set to
+/// `Money::VestingClaim` and their user data set to the schedule hash.
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct VestingSchedule {
+    pub recipient: PublicKey,
+    pub total_value: u64,
+    pub token_id: TokenId,
+    pub start_height: u64,
+    pub cliff_height: u64,
+    pub end_height: u64,
+    pub blind: BaseBlind,
+}
+
+impl VestingSchedule {
+    pub fn to_bulla(&self) -> pallas::Base {
+        let (recipient_x, recipient_y) = self.recipient.xy();
+        poseidon_hash([
+            recipient_x,
+            recipient_y,
+            pallas::Base::from(self.total_value),
+            self.token_id.inner(),
+            pallas::Base::from(self.start_height),
+            pallas::Base::from(self.cliff_height),
+            pallas::Base::from(self.end_height),
+            self.blind.inner(),
+        ])
+    }
+
+    /// Total amount unlocked at the given block height
+    pub fn unlocked(&self, height: u64) -> u64 {
+        if height < self.cliff_height {
+            return 0
+        }
+
+        if height >= self.end_height {
+            return self.total_value
+        }
+
+        let elapsed = (height - self.start_height) as u128;
+        let duration = (self.end_height - self.start_height) as u128;
+        (self.total_value as u128 * elapsed / duration) as u64
+    }
+}
+
+/// Terms of a hash time-locked payment. The `recipient` can claim it by
+/// revealing a preimage of `hashlock` before block height `expiry`, and
+/// from `expiry` onwards the `sender` can reclaim it instead.
+///
+/// HTLC coins are owned by an escrow key both parties derive from the
+/// terms, with their spend hook set to `Money::HtlcSpend` and their user
+/// data set to the terms hash. The hooked call decides which party has
+/// to sign the spend.
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct Htlc {
+    pub recipient: PublicKey,
+    pub sender: PublicKey,
+    /// BLAKE3 hash of the preimage unlocking the claim
+    pub hashlock: [u8; 32],
+    pub expiry: u64,
+    pub blind: BaseBlind,
+}
+
+impl Htlc {
+    pub fn to_bulla(&self) -> pallas::Base {
+        let (recipient_x, recipient_y) = self.recipient.xy();
+        let (sender_x, sender_y) = self.sender.xy();
+        let hashlock_lo = u128::from_le_bytes(self.hashlock[..16].try_into().unwrap());
+        let hashlock_hi = u128::from_le_bytes(self.hashlock[16..].try_into().unwrap());
+        poseidon_hash([
+            recipient_x,
+            recipient_y,
+            sender_x,
+            sender_y,
+            pallas::Base::from_u128(hashlock_lo),
+            pallas::Base::from_u128(hashlock_hi),
+            pallas::Base::from(self.expiry),
+            self.blind.inner(),
+        ])
+    }
+
+    /// Check if `preimage` unlocks the claim
+    pub fn unlocks(&self, preimage: &[u8; 32]) -> bool {
+        blake3::hash(preimage).as_bytes() == &self.hashlock
+    }
+}
+
 #[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
 // ANCHOR: money-clear-input
 /// A contract call's clear input
@@ -142,6 +233,9 @@ pub struct Input {
     pub nullifier: Nullifier,
     /// Revealed Merkle root
     pub merkle_root: MerkleNode,
+    /// Root of the frozen tokens SMT the input's token was proven to be
+    /// absent from. `Fee_V1` checks the frozen set directly and ignores it.
+    pub frozen_root: pallas::Base,
     /// Encrypted user data field. An encrypted commitment to arbitrary data.
     /// When spend hook is nonzero, then this field may be used to pass data
     /// to the invoked contract.
@@ -175,6 +269,9 @@ pub struct MoneyFeeParamsV1 {
     pub output: Output,
     /// Fee value blind
     pub fee_value_blind: ScalarBlind,
+    /// Token ID the fee is paid in. This is either the native token,
+    /// or a token whitelisted with a [`FeeTokenRate`].
+    pub token_id: TokenId,
     /// Token ID blind
     pub token_blind: BaseBlind,
 }
@@ -251,7 +348,11 @@ pub struct MoneyAuthTokenMintParamsV1 {
 
 /// State update for `Money::auth_token_mint()`
 #[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
-pub struct MoneyAuthTokenMintUpdateV1 {}
+pub struct MoneyAuthTokenMintUpdateV1 {
+    pub token_id: TokenId,
+    /// Sum of the value commitments of all the token's mints so far
+    pub supply_commit: pallas::Point,
+}
 
 /// Parameters for `Money::TokenFreeze`
 #[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
@@ -269,6 +370,320 @@ pub struct MoneyTokenFreezeUpdateV1 {
     pub token_id: TokenId,
 }
 
+/// Parameters for `Money::TokenSupplyCap`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyTokenSupplyCapParamsV1 {
+    /// Mint authority public key
+    ///
+    /// We use this to derive the token ID and verify the signature.
+    pub mint_public: PublicKey,
+    pub token_id: TokenId,
+    /// Hard cap on the token's total supply. It can't be changed once set.
+    pub supply_cap: u64,
+}
+
+/// State update for `Money::TokenSupplyCap`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyTokenSupplyCapUpdateV1 {
+    pub token_id: TokenId,
+    pub supply_cap: u64,
+}
+
+/// Parameters for `Money::TransferFreeze` and `Money::TransferUnfreeze`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyTransferFreezeParamsV1 {
+    /// Mint authority public key
+    ///
+    /// The mint authority is also the freeze authority of the token.
+    /// We use this to derive the token ID and verify the signature.
+    pub mint_public: PublicKey,
+    pub token_id: TokenId,
+}
+
+/// State update for `Money::TransferFreeze` and `Money::TransferUnfreeze`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyTransferFreezeUpdateV1 {
+    pub token_id: TokenId,
+    /// Block height the frozen set changed at, used to expire the
+    /// superseded frozen tokens root
+    pub block_height: u32,
+}
+
+/// Conversion rate of a token whitelisted for paying fees.
+/// `token` units of the token are worth `native` units of the native token.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct FeeTokenRate {
+    pub native: u64,
+    pub token: u64,
+}
+
+impl FeeTokenRate {
+    /// Convert a fee denominated in the native token into the amount of
+    /// the whitelisted token to be paid, rounding up. Returns `None` if
+    /// the rate is invalid or the amount overflows.
+    pub fn convert(&self, fee: u64) -> Option<u64> {
+        if self.native == 0 || self.token == 0 {
+            return None
+        }
+
+        let amount = (fee as u128 * self.token as u128).div_ceil(self.native as u128);
+        u64::try_from(amount).ok()
+    }
+}
+
+/// Parameters for `Money::FeeTokenRate`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyFeeTokenRateParamsV1 {
+    /// Fee governance public key, used to verify the signature
+    pub governance_public: PublicKey,
+    /// Token to whitelist or remove from the whitelist
+    pub token_id: TokenId,
+    /// New conversion rate, or `None` to remove the token from the whitelist
+    pub rate: Option<FeeTokenRate>,
+    /// Optional key to hand the fee governance over to
+    pub new_governance_public: Option<PublicKey>,
+}
+
+/// State update for `Money::FeeTokenRate`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyFeeTokenRateUpdateV1 {
+    /// Fee governance public key, stored on the first call or on a handover
+    pub governance_public: PublicKey,
+    pub token_id: TokenId,
+    pub rate: Option<FeeTokenRate>,
+}
+
+/// Parameters for `Money::VestingClaim`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyVestingClaimParamsV1 {
+    /// Lowest block height the claim can be confirmed at
+    pub claim_height: u64,
+}
+
+/// Parameters for `Money::HtlcSpend`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyHtlcSpendParamsV1 {
+    /// Terms of the spent HTLC coin
+    pub htlc: Htlc,
+    /// Blind of the spent coin's encrypted user data
+    pub user_data_blind: BaseBlind,
+    /// Preimage of the hashlock when claiming, `None` when refunding
+    pub preimage: Option<[u8; 32]>,
+}
+
+/// Custody terms of coins held by a FROST threshold group, see
+/// [`darkfi_sdk::crypto::frost`].
+///
+/// Group coins are owned by an escrow key every member derives from the
+/// terms, with their spend hook set to `Money::GroupSpend` and their user
+/// data set to the terms hash. The hooked call must be signed by the
+/// group public key, so any threshold of members can spend them.
+#[derive(Debug, Clone, SerialEncodable, SerialDecodable)]
+pub struct GroupCustody {
+    pub group_public: PublicKey,
+    pub blind: BaseBlind,
+}
+
+impl GroupCustody {
+    pub fn to_bulla(&self) -> pallas::Base {
+        let (group_x, group_y) = self.group_public.xy();
+        poseidon_hash([group_x, group_y, self.blind.inner()])
+    }
+}
+
+/// Parameters for `Money::GroupSpend`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyGroupSpendParamsV1 {
+    /// Custody terms of the spent group coins
+    pub custody: GroupCustody,
+    /// Blinds of the encrypted user data of each spent coin, in input order
+    pub user_data_blinds: Vec<BaseBlind>,
+}
+
+/// On-chain record of a non-fungible token. The metadata commitment
+/// is fixed at mint time, as it is part of the token ID derivation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct Nft {
+    /// Commitment to the token's metadata
+    pub metadata: pallas::Base,
+    /// Whether the token's single unit has been burned
+    pub burned: bool,
+}
+
+/// Parameters for `Money::NftMint`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyNftMintParamsV1 {
+    /// Token ID of the NFT
+    pub token_id: TokenId,
+    /// Commitment to the NFT's metadata
+    pub metadata: pallas::Base,
+    /// The newly minted coin, holding the token's supply of 1
+    pub coin: Coin,
+    /// AEAD encrypted note for the coin's recipient
+    pub note: AeadEncryptedNote,
+}
+
+/// State update for `Money::NftMint`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyNftMintUpdateV1 {
+    pub token_id: TokenId,
+    pub metadata: pallas::Base,
+    /// The newly minted coin
+    pub coin: Coin,
+}
+
+/// Parameters for `Money::NftBurn`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyNftBurnParamsV1 {
+    /// Anonymous input spending the NFT coin
+    pub input: Input,
+    /// Opening of the input's value commitment to 1
+    pub value_blind: ScalarBlind,
+    /// Token ID of the burned NFT
+    pub token_id: TokenId,
+    /// Opening of the input's token commitment
+    pub token_blind: BaseBlind,
+}
+
+/// State update for `Money::NftBurn`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyNftBurnUpdateV1 {
+    pub token_id: TokenId,
+    /// Revealed nullifier
+    pub nullifier: Nullifier,
+}
+
+/// Receipt of a coin burned through `Money::BridgeBurn`. Bridge operators
+/// release the burned value on the destination chain after verifying the
+/// receipt's inclusion in the Merkle tree of bridge burns.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct BridgeBurnReceipt {
+    /// Nullifier of the burned coin, making the receipt unique
+    pub nullifier: Nullifier,
+    /// Token ID of the burned coin
+    pub token_id: TokenId,
+    /// Value of the burned coin
+    pub value: u64,
+    /// Commitment to the destination chain and address
+    pub destination: pallas::Base,
+}
+
+impl BridgeBurnReceipt {
+    /// The receipt's leaf in the Merkle tree of bridge burns
+    pub fn to_leaf(&self) -> MerkleNode {
+        MerkleNode::from(poseidon_hash([
+            self.nullifier.inner(),
+            self.token_id.inner(),
+            pallas::Base::from(self.value),
+            self.destination,
+        ]))
+    }
+}
+
+/// Set of authorities allowed to mint a token through `Money::BridgeMint`
+#[derive(Clone, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct BridgeAuthoritySet {
+    /// Public keys of the bridge authorities
+    pub authorities: Vec<PublicKey>,
+    /// Number of authorities that have to sign a mint
+    pub threshold: u32,
+}
+
+impl BridgeAuthoritySet {
+    /// Check the threshold can be met and no authority is listed twice
+    pub fn is_valid(&self) -> bool {
+        if self.threshold == 0 || self.threshold as usize > self.authorities.len() {
+            return false
+        }
+
+        !self.authorities.iter().enumerate().any(|(i, x)| self.authorities[..i].contains(x))
+    }
+}
+
+/// Parameters for `Money::BridgeBurn`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyBridgeBurnParamsV1 {
+    /// Anonymous input spending the burned coin
+    pub input: Input,
+    /// Value of the burned coin
+    pub value: u64,
+    /// Opening of the input's value commitment
+    pub value_blind: ScalarBlind,
+    /// Token ID of the burned coin
+    pub token_id: TokenId,
+    /// Opening of the input's token commitment
+    pub token_blind: BaseBlind,
+    /// Commitment to the destination chain and address
+    pub destination: pallas::Base,
+}
+
+impl MoneyBridgeBurnParamsV1 {
+    /// The burn receipt recorded on-chain for these parameters
+    pub fn receipt(&self) -> BridgeBurnReceipt {
+        BridgeBurnReceipt {
+            nullifier: self.input.nullifier,
+            token_id: self.token_id,
+            value: self.value,
+            destination: self.destination,
+        }
+    }
+}
+
+/// State update for `Money::BridgeBurn`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyBridgeBurnUpdateV1 {
+    pub receipt: BridgeBurnReceipt,
+}
+
+/// Parameters for `Money::BridgeAuthoritySet`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyBridgeAuthoritySetParamsV1 {
+    /// Mint authority public key, used to verify the signature
+    pub mint_public: PublicKey,
+    /// Token allowed to be minted by the bridge authorities
+    pub token_id: TokenId,
+    /// The new bridge authority set
+    pub authority_set: BridgeAuthoritySet,
+}
+
+/// State update for `Money::BridgeAuthoritySet`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyBridgeAuthoritySetUpdateV1 {
+    pub token_id: TokenId,
+    pub authority_set: BridgeAuthoritySet,
+}
+
+/// Parameters for `Money::BridgeMint`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyBridgeMintParamsV1 {
+    /// Value being bridged in
+    pub value: u64,
+    /// Opening of the output's value commitment
+    pub value_blind: ScalarBlind,
+    /// Token ID being bridged in
+    pub token_id: TokenId,
+    /// Opening of the output's token commitment
+    pub token_blind: BaseBlind,
+    /// Reference to the lock on the source chain, which can only be minted once
+    pub source: [u8; 32],
+    /// Bridge authorities signing the mint
+    pub signers: Vec<PublicKey>,
+    /// Anonymous output
+    pub output: Output,
+}
+
+/// State update for `Money::BridgeMint`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct MoneyBridgeMintUpdateV1 {
+    pub source: [u8; 32],
+    /// The newly minted coin
+    pub coin: Coin,
+    /// Token ID of the minted coin
+    pub token_id: TokenId,
+    /// Sum of the value commitments of all the token's mints so far
+    pub supply_commit: pallas::Point,
+}
+
 /// Parameters for `Money::PoWReward`
 #[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
 pub struct MoneyPoWRewardParamsV1 {
@@ -16,18 +16,37 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use darkfi_sdk::{crypto::pasta_prelude::PrimeField, error::ContractError, pasta::pallas};
+use darkfi_sdk::{
+    crypto::{pasta_prelude::PrimeField, poseidon_hash, SecretKey},
+    error::ContractError,
+    pasta::pallas,
+    subtle::{Choice, ConstantTimeEq},
+};
 use darkfi_serial::{SerialDecodable, SerialEncodable};
 
 #[cfg(feature = "client")]
 use darkfi_serial::async_trait;
 
+use super::Coin;
+
 /// The `Nullifier` is represented as a base field element.
+/// Equality is checked in constant time.
 #[repr(C)]
-#[derive(Debug, Clone, Copy, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+#[derive(Debug, Clone, Copy, Eq, SerialEncodable, SerialDecodable)]
 pub struct Nullifier(pallas::Base);
 
 impl Nullifier {
+    /// Derive the `Nullifier` revealed when spending `coin` with its
+    /// `secret`. The `Burn_V1` and `Fee_V1` circuits compute the same.
+    ///
+    /// This is not tagged with [`darkfi_sdk::crypto::domains::NULLIFIER`]:
+    /// nullifiers of coins spent before a tag would not match the new
+    /// derivation, so it can only be applied by new circuit versions that
+    /// also check the nullifiers of the old derivation.
+    pub fn derive(secret: &SecretKey, coin: &Coin) -> Self {
+        Self(poseidon_hash([secret.inner(), coin.inner()]))
+    }
+
     /// Reference the raw inner base field element
     pub fn inner(&self) -> pallas::Base {
         self.0
@@ -47,6 +66,18 @@ impl Nullifier {
     }
 }
 
+impl ConstantTimeEq for Nullifier {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for Nullifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
 use core::str::FromStr;
 darkfi_sdk::fp_from_bs58!(Nullifier);
 darkfi_sdk::fp_to_bs58!(Nullifier);
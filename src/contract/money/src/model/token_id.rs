@@ -18,7 +18,10 @@
 
 use darkfi_sdk::{
     crypto::{
-        constants::DRK_TOKEN_ID_PERSONALIZATION, pasta_prelude::PrimeField, util::hash_to_base,
+        constants::DRK_TOKEN_ID_PERSONALIZATION,
+        domains::{self, domain},
+        pasta_prelude::PrimeField,
+        util::hash_to_base,
     },
     error::ContractError,
     pasta::pallas,
@@ -34,7 +37,7 @@ use super::{poseidon_hash, PublicKey, SecretKey};
 lazy_static! {
     // Is this even needed? Not used elsewhere except here.
     /// Derivation prefix for `TokenId`
-    pub static ref TOKEN_ID_PREFIX: pallas::Base = pallas::Base::from(69);
+    pub static ref TOKEN_ID_PREFIX: pallas::Base = domain(domains::TOKEN_ID);
 
     /// Native DARK token ID.
     /// It does not correspond to any real commitment since we only rely on this value as
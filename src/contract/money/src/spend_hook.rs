@@ -0,0 +1,56 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for contracts taking ownership of Money coins through their
+//! `spend_hook`. See [`darkfi_sdk::crypto::spend_hook`].
+
+use darkfi_sdk::{
+    crypto::{spend_hook::spend_hook_child, FuncRef, MONEY_CONTRACT_ID},
+    dark_tree::DarkLeaf,
+    error::{ContractError, GenericResult},
+    msg, ContractCall,
+};
+use darkfi_serial::deserialize_strict;
+
+use crate::{model::MoneyTransferParamsV1, MoneyFunction};
+
+/// Used by a hooked function to read the `Money::Transfer` call spending
+/// its coins. The transfer must be the single child of the call at
+/// `call_idx`, with `inputs` inputs and `outputs` outputs.
+pub fn spend_hook_transfer(
+    calls: &[DarkLeaf<ContractCall>],
+    call_idx: u32,
+    inputs: usize,
+    outputs: usize,
+) -> GenericResult<MoneyTransferParamsV1> {
+    let transfer_func =
+        FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: MoneyFunction::TransferV1 as u8 };
+    let xfer_call = spend_hook_child(calls, call_idx, &transfer_func)?;
+
+    let xfer_params: MoneyTransferParamsV1 = deserialize_strict(&xfer_call.data[1..])?;
+    if xfer_params.inputs.len() != inputs || xfer_params.outputs.len() != outputs {
+        msg!(
+            "[SpendHook] Error: Expected a transfer with {} inputs and {} outputs",
+            inputs,
+            outputs
+        );
+        return Err(ContractError::SpendHookInvalidChild)
+    }
+
+    Ok(xfer_params)
+}
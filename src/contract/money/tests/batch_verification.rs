@@ -0,0 +1,169 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test batch verification of transaction ZK proofs.
+//!
+//! Bob pays Alice with three transfers, one of which carries a proof
+//! belonging to another transfer. The batched proof check fails, and the
+//! fallback to individual verification rejects only the tampered transfer.
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::{
+    client::{
+        coin_selection::LargestFirst,
+        transfer_v1::{make_transfer_call, TransferCallSecrets},
+    },
+    model::MoneyTransferParamsV1,
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{BaseBlind, MONEY_CONTRACT_ID},
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use log::info;
+use rand::rngs::OsRng;
+
+/// Build and sign a transaction holding a single `Money::Transfer` call
+async fn transfer_tx(
+    params: &MoneyTransferParamsV1,
+    secrets: &TransferCallSecrets,
+) -> Result<Transaction> {
+    let mut data = vec![MoneyFunction::TransferV1 as u8];
+    params.encode_async(&mut data).await?;
+    let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+    let mut tx =
+        TransactionBuilder::new(ContractCallLeaf { call, proofs: secrets.proofs.clone() }, vec![])?
+            .build()?;
+    let sigs = tx.create_sigs(&secrets.signature_secrets)?;
+    tx.signatures = vec![sigs];
+
+    Ok(tx)
+}
+
+#[test]
+fn batch_verification() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const BOB_SUPPLY: u64 = 2000000000; // 20 BOB
+        const TRANSFERS: usize = 3;
+        const TAMPERED: usize = 1;
+
+        // Block height to verify against
+        let current_block_height = 0;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        // Bob mints a separate token for each transfer, so each one spends its own coin
+        for _ in 0..TRANSFERS {
+            info!("[Bob] Building BOB token mint tx");
+            let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+                .token_mint(
+                    BOB_SUPPLY,
+                    &Holder::Bob,
+                    &Holder::Bob,
+                    BaseBlind::random(&mut OsRng),
+                    None,
+                    None,
+                    current_block_height,
+                )
+                .await?;
+
+            for holder in &HOLDERS {
+                info!("[{holder:?}] Executing BOB token mint tx");
+                th.execute_token_mint_tx(
+                    holder,
+                    token_mint_tx.clone(),
+                    &token_mint_params,
+                    &token_auth_mint_params,
+                    &fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await?;
+            }
+        }
+
+        let bob = th.holders.get(&Holder::Bob).unwrap();
+        assert_eq!(bob.unspent_money_coins.len(), TRANSFERS);
+        let alice_public = th.holders.get(&Holder::Alice).unwrap().keypair.public;
+
+        let (mint_pk, mint_zkbin) = th.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = th.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        info!("[Bob] Building BOB payment txs to Alice");
+        let mut calls = Vec::with_capacity(TRANSFERS);
+        for coin in &bob.unspent_money_coins {
+            let (params, secrets, _) = make_transfer_call(
+                bob.keypair,
+                alice_public,
+                None,
+                coin.note.value,
+                coin.note.token_id,
+                None,
+                vec![],
+                vec![coin.clone()],
+                &LargestFirst,
+                bob.money_merkle_tree.clone(),
+                &bob.money_frozen_smt,
+                mint_zkbin.clone(),
+                mint_pk.clone(),
+                burn_zkbin.clone(),
+                burn_pk.clone(),
+            )?;
+            calls.push((params, secrets));
+        }
+
+        // Swap in a valid proof created for another transfer's public inputs,
+        // and sign over it so only the proof check can catch it.
+        calls[TAMPERED].1.proofs[0] = calls[0].1.proofs[0].clone();
+
+        let mut txs = Vec::with_capacity(TRANSFERS);
+        for (params, secrets) in &calls {
+            txs.push(transfer_tx(params, secrets).await?);
+        }
+
+        info!("[Alice] Checking only the tampered tx is rejected from the batch");
+        let alice = th.holders.get(&Holder::Alice).unwrap();
+        let err = alice
+            .validator
+            .add_transactions(&txs, current_block_height, false, false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.retrieve_erroneous_txs()?, vec![txs[TAMPERED].clone()]);
+
+        info!("[Alice] Checking the rest of the batch verifies");
+        let mut valid_txs = txs.clone();
+        valid_txs.remove(TAMPERED);
+        alice.validator.add_transactions(&valid_txs, current_block_height, false, false).await?;
+
+        // Thanks for reading
+        Ok(())
+    })
+}
@@ -0,0 +1,251 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test bridging through `Money::BridgeBurn` and `Money::BridgeMint`.
+//!
+//! Alice mints some ALICE tokens and burns them towards an external
+//! chain, proving the burn receipt against the on-chain roots. She then
+//! appoints a set of bridge authorities for her token, which mint the
+//! tokens locked on the external chain back to Bob. Replaying a lock,
+//! or minting without enough authority signatures, is rejected.
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::{client::bridge_burn_v1::BridgeDestination, model::BridgeAuthoritySet};
+use darkfi_sdk::crypto::{BaseBlind, Keypair};
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn bridge() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const ALICE_MINT: u64 = 1000000000; // 10 ALICE
+        const BRIDGE_MINT: u64 = 400000000; // 4 ALICE
+
+        // Block height to verify against
+        let current_block_height = 0;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Alice] Building ALICE token mint tx");
+        let alice_token_blind = BaseBlind::random(&mut OsRng);
+        let (mint_tx, mint_params, auth_params, fee_params) = th
+            .token_mint(
+                ALICE_MINT,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+        let alice_token_id = auth_params.token_id;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                mint_tx.clone(),
+                &mint_params,
+                &auth_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_owncoins = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.clone();
+        assert_eq!(alice_owncoins.len(), 1);
+
+        let destination = BridgeDestination {
+            chain_id: 1,
+            address: b"0x00000000000000000000000000000000deadbeef".to_vec(),
+            blind: BaseBlind::random(&mut OsRng),
+        };
+
+        info!("[Alice] Building ALICE bridge burn tx");
+        let (burn_tx, burn_params, fee_params) = th
+            .bridge_burn(&Holder::Alice, &alice_owncoins[0], &destination, current_block_height)
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE bridge burn tx");
+            th.execute_bridge_burn_tx(
+                holder,
+                burn_tx.clone(),
+                &burn_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        assert!(th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.is_empty());
+
+        info!("[Bob] Verifying ALICE bridge burn receipt");
+        let proof = th.bridge_burn_proof(&Holder::Bob, &burn_params).unwrap();
+        assert_eq!(proof.receipt.token_id, alice_token_id);
+        assert_eq!(proof.receipt.value, ALICE_MINT);
+        assert!(th.bridge_burn_root_exists(&Holder::Bob, &proof.root())?);
+        assert!(proof.verify(&proof.root(), &destination));
+        let wrong_destination = BridgeDestination { chain_id: 2, ..destination.clone() };
+        assert!(!proof.verify(&proof.root(), &wrong_destination));
+
+        // Gather the bridge authorities, any two of them are able to mint
+        let authorities: Vec<Keypair> = (0..3).map(|_| Keypair::random(&mut OsRng)).collect();
+        let authority_set = BridgeAuthoritySet {
+            authorities: authorities.iter().map(|x| x.public).collect(),
+            threshold: 2,
+        };
+
+        info!("[Alice] Building ALICE bridge authority set tx");
+        let (set_tx, set_params, fee_params) = th
+            .bridge_authority_set(
+                &Holder::Alice,
+                alice_token_blind,
+                authority_set,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE bridge authority set tx");
+            th.execute_bridge_authority_set_tx(
+                holder,
+                set_tx.clone(),
+                &set_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let source = [1u8; 32];
+
+        info!("[Alice] Checking a single authority can't mint");
+        let (mint_tx, mint_params, fee_params) = th
+            .bridge_mint(
+                &Holder::Alice,
+                &authorities[..1],
+                &Holder::Bob,
+                alice_token_id,
+                BRIDGE_MINT,
+                source,
+                current_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_bridge_mint_tx(
+                &Holder::Alice,
+                mint_tx,
+                &mint_params,
+                &fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        let supply_commit = th.token_supply_commit(&Holder::Alice, alice_token_id)?.unwrap();
+
+        info!("[Alice] Building ALICE bridge mint tx to Bob");
+        let (mint_tx, mint_params, fee_params) = th
+            .bridge_mint(
+                &Holder::Alice,
+                &authorities[1..],
+                &Holder::Bob,
+                alice_token_id,
+                BRIDGE_MINT,
+                source,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE bridge mint tx");
+            th.execute_bridge_mint_tx(
+                holder,
+                mint_tx.clone(),
+                &mint_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_owncoins = th.holders.get(&Holder::Bob).unwrap().unspent_money_coins.clone();
+        assert_eq!(bob_owncoins.len(), 1);
+        assert_eq!(bob_owncoins[0].note.value, BRIDGE_MINT);
+        assert_eq!(bob_owncoins[0].note.token_id, alice_token_id);
+
+        // The bridged value is accumulated into the token's supply
+        for holder in &HOLDERS {
+            assert_eq!(
+                th.token_supply_commit(holder, alice_token_id)?,
+                Some(supply_commit + mint_params.output.value_commit)
+            );
+        }
+
+        info!("[Alice] Checking the same lock can't be minted twice");
+        let (mint_tx, mint_params, fee_params) = th
+            .bridge_mint(
+                &Holder::Alice,
+                &authorities[..2],
+                &Holder::Bob,
+                alice_token_id,
+                BRIDGE_MINT,
+                source,
+                current_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_bridge_mint_tx(
+                &Holder::Alice,
+                mint_tx,
+                &mint_params,
+                &fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        // Thanks for reading
+        Ok(())
+    })
+}
@@ -0,0 +1,103 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test the coin selection strategies used by the transfer builder.
+
+use darkfi::{zk::halo2::Field, Result};
+use darkfi_money_contract::{
+    client::{
+        coin_selection::{BranchAndBound, CoinSelection, LargestFirst, RandomSelection},
+        MoneyNote, OwnCoin,
+    },
+    model::{Coin, TokenId},
+};
+use darkfi_sdk::{
+    bridgetree,
+    crypto::{Blind, FuncId, SecretKey},
+    pasta::pallas,
+};
+use rand::rngs::OsRng;
+
+/// Create dummy coins holding the given values
+fn coins(values: &[u64]) -> Vec<OwnCoin> {
+    let token_id = TokenId::from(pallas::Base::random(&mut OsRng));
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| OwnCoin {
+            coin: Coin::from(pallas::Base::random(&mut OsRng)),
+            note: MoneyNote {
+                value: *value,
+                token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                coin_blind: Blind::random(&mut OsRng),
+                value_blind: Blind::random(&mut OsRng),
+                token_blind: Blind::random(&mut OsRng),
+                payment_id: None,
+                memo: vec![],
+            },
+            secret: SecretKey::random(&mut OsRng),
+            leaf_position: bridgetree::Position::from(i as u64),
+        })
+        .collect()
+}
+
+fn values(coins: &[OwnCoin]) -> Vec<u64> {
+    let mut values: Vec<u64> = coins.iter().map(|x| x.note.value).collect();
+    values.sort();
+    values
+}
+
+#[test]
+fn coin_selection() -> Result<()> {
+    let wallet = coins(&[5, 1, 20, 8, 3, 12]);
+
+    // Largest-first spends the fewest coins
+    let (selected, change) = LargestFirst.select(wallet.clone(), 25)?;
+    assert_eq!(values(&selected), vec![12, 20]);
+    assert_eq!(change, 7);
+
+    // Branch-and-bound finds an exact match without change
+    let (selected, change) = BranchAndBound::default().select(wallet.clone(), 24)?;
+    assert_eq!(selected.iter().map(|x| x.note.value).sum::<u64>(), 24);
+    assert_eq!(change, 0);
+
+    let (selected, change) = BranchAndBound::default().select(wallet.clone(), 4)?;
+    assert_eq!(values(&selected), vec![1, 3]);
+    assert_eq!(change, 0);
+
+    // Without an exact match, it falls back to random selection
+    let (selected, change) = BranchAndBound::default().select(coins(&[10, 10, 10]), 15)?;
+    assert_eq!(selected.len(), 2);
+    assert_eq!(change, 5);
+
+    // Random selection always covers the target
+    for _ in 0..10 {
+        let (selected, change) = RandomSelection.select(wallet.clone(), 30)?;
+        assert_eq!(selected.iter().map(|x| x.note.value).sum::<u64>(), 30 + change);
+    }
+
+    // Every strategy fails when the balance is too low
+    assert!(LargestFirst.select(wallet.clone(), 50).is_err());
+    assert!(BranchAndBound::default().select(wallet.clone(), 50).is_err());
+    assert!(RandomSelection.select(wallet, 50).is_err());
+
+    Ok(())
+}
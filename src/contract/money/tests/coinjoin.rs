@@ -0,0 +1,152 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test a collaborative `Money::Transfer` built as a CoinJoin.
+//!
+//! Alice mints ALICE tokens and pays some of them to Bob. Alice and Bob
+//! then both pay Charlie in a single CoinJoin transfer coordinated by
+//! Alice, each signing only their own input.
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_sdk::crypto::BaseBlind;
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn coinjoin() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 3] = [Holder::Alice, Holder::Bob, Holder::Charlie];
+
+        // Some numbers we want to assert
+        const ALICE_SUPPLY: u64 = 2000000000; // 20 ALICE
+        const BOB_RECV: u64 = 500000000; // 5 ALICE
+        const ALICE_PAYS: u64 = 300000000; // 3 ALICE
+        const BOB_PAYS: u64 = 200000000; // 2 ALICE
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Alice] Building ALICE token mint tx");
+        let alice_token_blind = BaseBlind::random(&mut OsRng);
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                ALICE_SUPPLY,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                0,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                0,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_owncoins = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.clone();
+        let alice_token_id = alice_owncoins[0].note.token_id;
+
+        info!("[Alice] Building ALICE payment tx to Bob");
+        let (transfer_tx, (transfer_params, fee_params), _) = th
+            .transfer(BOB_RECV, &Holder::Alice, &Holder::Bob, &alice_owncoins, alice_token_id, 1)
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE payment tx to Bob");
+            th.execute_transfer_tx(
+                holder,
+                transfer_tx.clone(),
+                &transfer_params,
+                &fee_params,
+                1,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_oc = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins[0].clone();
+        let bob_oc = th.holders.get(&Holder::Bob).unwrap().unspent_money_coins[0].clone();
+        assert_eq!(alice_oc.note.value, ALICE_SUPPLY - BOB_RECV);
+        assert_eq!(bob_oc.note.value, BOB_RECV);
+
+        info!("[Alice] Coordinating CoinJoin tx paying Charlie");
+        let payments = [
+            (Holder::Alice, alice_oc, Holder::Charlie, ALICE_PAYS),
+            (Holder::Bob, bob_oc, Holder::Charlie, BOB_PAYS),
+        ];
+        let (coinjoin_tx, coinjoin_params, fee_params) =
+            th.coinjoin(&Holder::Alice, &payments, 2).await?;
+
+        assert_eq!(coinjoin_params.inputs.len(), 2);
+        assert_eq!(coinjoin_params.outputs.len(), 4);
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing CoinJoin tx");
+            th.execute_transfer_tx(
+                holder,
+                coinjoin_tx.clone(),
+                &coinjoin_params,
+                &fee_params,
+                2,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_coins = &th.holders.get(&Holder::Alice).unwrap().unspent_money_coins;
+        assert_eq!(alice_coins.len(), 1);
+        assert_eq!(alice_coins[0].note.value, ALICE_SUPPLY - BOB_RECV - ALICE_PAYS);
+
+        let bob_coins = &th.holders.get(&Holder::Bob).unwrap().unspent_money_coins;
+        assert_eq!(bob_coins.len(), 1);
+        assert_eq!(bob_coins[0].note.value, BOB_RECV - BOB_PAYS);
+
+        let charlie_coins = &th.holders.get(&Holder::Charlie).unwrap().unspent_money_coins;
+        assert_eq!(charlie_coins.len(), 2);
+        let charlie_total: u64 = charlie_coins.iter().map(|x| x.note.value).sum();
+        assert_eq!(charlie_total, ALICE_PAYS + BOB_PAYS);
+
+        info!("[Bob] Checking the CoinJoin can't be replayed");
+        let bob = th.holders.get_mut(&Holder::Bob).unwrap();
+        assert!(bob.add_transaction("money::transfer", coinjoin_tx, 3, false).await.is_err());
+
+        // Thanks for reading
+        Ok(())
+    })
+}
@@ -0,0 +1,255 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test paying transaction fees with a whitelisted token.
+//!
+//! We mint a BOB token, whitelist it for fees through `Money::FeeTokenRate`,
+//! pay a fee with it, and check that only the fee governance key can update
+//! the whitelist and that removed tokens can't be used anymore. The key is
+//! then handed over, and a network without a genesis key activates one.
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::{
+    client::fee_v1::FEE_CALL_GAS,
+    model::{FeeTokenRate, TokenId},
+    MONEY_CONTRACT_FEE_GOVERNANCE_ACTIVATION_HEIGHT,
+};
+use darkfi_sdk::crypto::{BaseBlind, Keypair};
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn fee_token() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const BOB_SUPPLY: u64 = 2000000000; // 20 BOB
+
+        // 2 BOB are worth 1 DRK
+        const BOB_RATE: FeeTokenRate = FeeTokenRate { native: 1, token: 2 };
+
+        // Block heights to verify against
+        let genesis_block_height = 0;
+        let current_block_height = 1;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Bob] Building BOB token mint tx");
+        let bob_token_blind = BaseBlind::random(&mut OsRng);
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                BOB_SUPPLY,
+                &Holder::Bob,
+                &Holder::Bob,
+                bob_token_blind,
+                None,
+                None,
+                genesis_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                genesis_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_token_id =
+            th.holders.get(&Holder::Bob).unwrap().unspent_money_coins[0].note.token_id;
+
+        // BOB is not whitelisted yet, so it can't be used for fees
+        info!("[Bob] Checking BOB can't pay fees before being whitelisted");
+        let token_fee = BOB_RATE.convert(FEE_CALL_GAS).unwrap();
+        let (fee_tx, _) = th.create_empty_fee_call(&Holder::Bob, bob_token_id, token_fee).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice
+            .add_transaction("money::fee", fee_tx, current_block_height, false)
+            .await
+            .is_err());
+
+        // The first call sets Alice as the fee governance key. This can only
+        // happen in the genesis block.
+        info!("[Alice] Building BOB whitelist tx");
+        let (rate_tx, _) =
+            th.fee_token_rate(&Holder::Alice, bob_token_id, Some(BOB_RATE), None).await?;
+        assert!(th
+            .execute_fee_token_rate_tx(&Holder::Alice, rate_tx.clone(), current_block_height)
+            .await
+            .is_err());
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB whitelist tx");
+            th.execute_fee_token_rate_tx(holder, rate_tx.clone(), genesis_block_height).await?;
+        }
+
+        // Bob is not the governance key, so the rate update is rejected
+        info!("[Bob] Checking Bob can't update the BOB rate");
+        let cheap_rate = FeeTokenRate { native: 1000, token: 1 };
+        let (rate_tx, _) =
+            th.fee_token_rate(&Holder::Bob, bob_token_id, Some(cheap_rate), None).await?;
+        assert!(th
+            .execute_fee_token_rate_tx(&Holder::Alice, rate_tx, current_block_height)
+            .await
+            .is_err());
+
+        // Paying less BOB than the rate requires fails
+        info!("[Bob] Checking BOB fee below the rate is rejected");
+        let (fee_tx, _) =
+            th.create_empty_fee_call(&Holder::Bob, bob_token_id, FEE_CALL_GAS).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice
+            .add_transaction("money::fee", fee_tx, current_block_height, false)
+            .await
+            .is_err());
+
+        info!("[Bob] Building BOB fee tx");
+        let (fee_tx, fee_params) =
+            th.create_empty_fee_call(&Holder::Bob, bob_token_id, token_fee).await?;
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB fee tx");
+            th.execute_empty_fee_call_tx(holder, fee_tx.clone(), &fee_params, current_block_height)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_balance: u64 = th
+            .holders
+            .get(&Holder::Bob)
+            .unwrap()
+            .unspent_money_coins
+            .iter()
+            .map(|x| x.note.value)
+            .sum();
+        assert_eq!(bob_balance, BOB_SUPPLY - token_fee);
+
+        // Alice removes BOB from the whitelist
+        info!("[Alice] Building BOB whitelist removal tx");
+        let (rate_tx, _) = th.fee_token_rate(&Holder::Alice, bob_token_id, None, None).await?;
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB whitelist removal tx");
+            th.execute_fee_token_rate_tx(holder, rate_tx.clone(), current_block_height).await?;
+        }
+
+        info!("[Bob] Checking BOB can't pay fees after being removed");
+        let (fee_tx, _) = th.create_empty_fee_call(&Holder::Bob, bob_token_id, token_fee).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice
+            .add_transaction("money::fee", fee_tx, current_block_height, false)
+            .await
+            .is_err());
+
+        // Alice hands the fee governance over to Bob
+        info!("[Alice] Building fee governance handover tx");
+        let bob_public = th.holders.get(&Holder::Bob).unwrap().keypair.public;
+        let (rate_tx, _) =
+            th.fee_token_rate(&Holder::Alice, bob_token_id, None, Some(bob_public)).await?;
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing fee governance handover tx");
+            th.execute_fee_token_rate_tx(holder, rate_tx.clone(), current_block_height).await?;
+        }
+
+        info!("[Alice] Checking Alice can't update the BOB rate anymore");
+        let (rate_tx, _) =
+            th.fee_token_rate(&Holder::Alice, bob_token_id, Some(BOB_RATE), None).await?;
+        assert!(th
+            .execute_fee_token_rate_tx(&Holder::Alice, rate_tx, current_block_height)
+            .await
+            .is_err());
+
+        info!("[Bob] Building BOB whitelist tx");
+        let (rate_tx, _) =
+            th.fee_token_rate(&Holder::Bob, bob_token_id, Some(BOB_RATE), None).await?;
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB whitelist tx");
+            th.execute_fee_token_rate_tx(holder, rate_tx.clone(), current_block_height).await?;
+        }
+
+        // Thanks for reading
+        Ok(())
+    })
+}
+
+#[test]
+fn fee_governance_activation() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        let token_id = TokenId::derive(Keypair::random(&mut OsRng).secret);
+        let rate = FeeTokenRate { native: 1, token: 2 };
+
+        // No key was set in the genesis block, so it can't be set
+        // until the activation height.
+        info!("[Alice] Building fee governance activation tx");
+        let (rate_tx, _) = th.fee_token_rate(&Holder::Alice, token_id, Some(rate), None).await?;
+        assert!(th
+            .execute_fee_token_rate_tx(
+                &Holder::Alice,
+                rate_tx.clone(),
+                (MONEY_CONTRACT_FEE_GOVERNANCE_ACTIVATION_HEIGHT - 1) as u64,
+            )
+            .await
+            .is_err());
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing fee governance activation tx");
+            th.execute_fee_token_rate_tx(
+                holder,
+                rate_tx.clone(),
+                MONEY_CONTRACT_FEE_GOVERNANCE_ACTIVATION_HEIGHT as u64,
+            )
+            .await?;
+        }
+
+        // Once activated, only Alice's key can update the whitelist
+        info!("[Bob] Checking Bob can't take over the fee governance");
+        let (rate_tx, _) = th.fee_token_rate(&Holder::Bob, token_id, None, None).await?;
+        assert!(th
+            .execute_fee_token_rate_tx(
+                &Holder::Alice,
+                rate_tx,
+                MONEY_CONTRACT_FEE_GOVERNANCE_ACTIVATION_HEIGHT as u64,
+            )
+            .await
+            .is_err());
+
+        // Thanks for reading
+        Ok(())
+    })
+}
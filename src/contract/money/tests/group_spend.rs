@@ -0,0 +1,178 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+//! Test coins held by a FROST threshold group through `Money::GroupSpend`.
+//!
+//! Alice, Bob and Charlie run a dealerless 2-of-3 key generation, and
+//! Alice pays ALICE tokens into a coin held by the group. A single member
+//! can't spend it, nor can a different group, but Bob and Charlie together
+//! send it to Charlie.
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::client::group_spend_v1::{group_custody, group_spend_hook};
+use darkfi_sdk::crypto::{
+    frost::{dkg_part1, dkg_part2, dkg_part3, KeyPackage, PublicKeyPackage},
+    BaseBlind, FuncId,
+};
+use log::info;
+use rand::rngs::OsRng;
+
+/// Run a dealerless key generation between `participants` members
+fn dkg(threshold: u16, participants: u16) -> (Vec<KeyPackage>, PublicKeyPackage) {
+    let mut secrets = vec![];
+    let mut round1 = vec![];
+    for index in 1..=participants {
+        let (secret, package) = dkg_part1(index, threshold, participants, &mut OsRng).unwrap();
+        secrets.push(secret);
+        round1.push(package);
+    }
+
+    let mut round2 = vec![];
+    for secret in &secrets {
+        round2.extend(dkg_part2(secret, &round1).unwrap());
+    }
+
+    let mut keys = vec![];
+    let mut pubkeys = None;
+    for secret in &secrets {
+        let (key, pubkey) = dkg_part3(secret, &round1, &round2).unwrap();
+        keys.push(key);
+        pubkeys = Some(pubkey);
+    }
+
+    (keys, pubkeys.unwrap())
+}
+
+#[test]
+fn group_spend() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 3] = [Holder::Alice, Holder::Bob, Holder::Charlie];
+
+        // Some numbers we want to assert
+        const ALICE_SUPPLY: u64 = 2000000000; // 20 ALICE
+        const GROUP_VALUE: u64 = 1000000000; // 10 ALICE
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Alice] Building ALICE token mint tx");
+        let alice_token_blind = BaseBlind::random(&mut OsRng);
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                ALICE_SUPPLY,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                0,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                0,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        info!("[Alice, Bob, Charlie] Running the group key generation");
+        let (keys, pubkeys) = dkg(2, 3);
+        let custody = group_custody(&pubkeys);
+
+        let alice_owncoins = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.clone();
+        let alice_token_id = alice_owncoins[0].note.token_id;
+
+        info!("[Alice] Building group lock tx");
+        let (lock_tx, lock_params, fee_params, _) = th
+            .group_lock(
+                &Holder::Alice,
+                &HOLDERS,
+                &custody,
+                GROUP_VALUE,
+                alice_token_id,
+                &alice_owncoins,
+                1,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing group lock tx");
+            th.execute_transfer_tx(holder, lock_tx.clone(), &lock_params, &fee_params, 1, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_wallet = th.holders.get(&Holder::Bob).unwrap();
+        assert_eq!(bob_wallet.unspent_money_coins.len(), 1);
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.spend_hook, group_spend_hook());
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.user_data, custody.to_bulla());
+
+        info!("[Bob] Checking a single member can't sign for the group");
+        assert!(th
+            .group_spend(&Holder::Bob, &custody, &keys[1..2], &pubkeys, &Holder::Bob, 2)
+            .await
+            .is_err());
+
+        info!("[Bob] Checking another group can't spend the coin");
+        let (other_keys, other_pubkeys) = dkg(2, 3);
+        let (spend_tx, _, _, _) = th
+            .group_spend(&Holder::Bob, &custody, &other_keys[1..], &other_pubkeys, &Holder::Bob, 2)
+            .await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice.add_transaction("money::group_spend", spend_tx, 2, false).await.is_err());
+
+        info!("[Bob, Charlie] Building group spend tx");
+        let (spend_tx, xfer_params, _, fee_params) = th
+            .group_spend(&Holder::Bob, &custody, &keys[1..], &pubkeys, &Holder::Charlie, 2)
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing group spend tx");
+            th.execute_transfer_tx(holder, spend_tx.clone(), &xfer_params, &fee_params, 2, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let charlie_wallet = th.holders.get(&Holder::Charlie).unwrap();
+        let charlie_coins: Vec<_> = charlie_wallet
+            .unspent_money_coins
+            .iter()
+            .filter(|x| x.note.spend_hook == FuncId::none())
+            .collect();
+        assert_eq!(charlie_coins.len(), 1);
+        assert_eq!(charlie_coins[0].note.value, GROUP_VALUE);
+
+        // Thanks for reading
+        Ok(())
+    })
+}
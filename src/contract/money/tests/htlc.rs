@@ -0,0 +1,215 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test hash time-locked payments through `Money::HtlcSpend`.
+//!
+//! Alice locks ALICE tokens for Bob behind a hashlock. Bob claims them
+//! by revealing the preimage before expiry. Alice locks more tokens,
+//! which Bob doesn't claim in time, so Alice refunds them after expiry.
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::{client::htlc_spend_v1::htlc_spend_hook, model::Htlc};
+use darkfi_sdk::crypto::{BaseBlind, FuncId};
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn htlc() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const ALICE_SUPPLY: u64 = 2000000000; // 20 ALICE
+        const HTLC_VALUE: u64 = 1000000000; // 10 ALICE
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Alice] Building ALICE token mint tx");
+        let alice_token_blind = BaseBlind::random(&mut OsRng);
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                ALICE_SUPPLY,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                0,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                0,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_owncoins = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.clone();
+        let alice_token_id = alice_owncoins[0].note.token_id;
+        let alice_public = th.holders.get(&Holder::Alice).unwrap().keypair.public;
+        let bob_public = th.holders.get(&Holder::Bob).unwrap().keypair.public;
+
+        // Bob can claim until block 10
+        let preimage = [42u8; 32];
+        let htlc = Htlc {
+            recipient: bob_public,
+            sender: alice_public,
+            hashlock: *blake3::hash(&preimage).as_bytes(),
+            expiry: 10,
+            blind: BaseBlind::random(&mut OsRng),
+        };
+
+        info!("[Alice] Building HTLC lock tx for Bob");
+        let (lock_tx, lock_params, fee_params, _) = th
+            .htlc_lock(
+                &Holder::Alice,
+                &Holder::Bob,
+                &htlc,
+                HTLC_VALUE,
+                alice_token_id,
+                &alice_owncoins,
+                1,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing HTLC lock tx");
+            th.execute_transfer_tx(holder, lock_tx.clone(), &lock_params, &fee_params, 1, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_wallet = th.holders.get(&Holder::Bob).unwrap();
+        assert_eq!(bob_wallet.unspent_money_coins.len(), 1);
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.spend_hook, htlc_spend_hook());
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.user_data, htlc.to_bulla());
+
+        info!("[Alice] Checking the HTLC can't be refunded before expiry");
+        let (refund_tx, _, _, _) = th.htlc_spend(&Holder::Alice, &htlc, None, 5).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice.add_transaction("money::htlc_spend", refund_tx, 5, false).await.is_err());
+
+        info!("[Bob] Checking the HTLC can't be claimed with a wrong preimage");
+        let (claim_tx, _, _, _) = th.htlc_spend(&Holder::Bob, &htlc, Some([0u8; 32]), 5).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice.add_transaction("money::htlc_spend", claim_tx, 5, false).await.is_err());
+
+        info!("[Bob] Building HTLC claim tx");
+        let (claim_tx, claim_params, spend_params, fee_params) =
+            th.htlc_spend(&Holder::Bob, &htlc, Some(preimage), 5).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing HTLC claim tx");
+            th.execute_transfer_tx(holder, claim_tx.clone(), &claim_params, &fee_params, 5, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // The claim published the preimage, e.g. for the other leg of a swap
+        assert_eq!(spend_params.preimage, Some(preimage));
+
+        let bob_wallet = th.holders.get(&Holder::Bob).unwrap();
+        assert_eq!(bob_wallet.unspent_money_coins.len(), 1);
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.value, HTLC_VALUE);
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.spend_hook, FuncId::none());
+
+        // Lock the rest, which Bob lets expire at block 20
+        let htlc = Htlc {
+            recipient: bob_public,
+            sender: alice_public,
+            hashlock: *blake3::hash(&preimage).as_bytes(),
+            expiry: 20,
+            blind: BaseBlind::random(&mut OsRng),
+        };
+
+        let alice_owncoins = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.clone();
+        info!("[Alice] Building second HTLC lock tx for Bob");
+        let (lock_tx, lock_params, fee_params, _) = th
+            .htlc_lock(
+                &Holder::Alice,
+                &Holder::Bob,
+                &htlc,
+                ALICE_SUPPLY - HTLC_VALUE,
+                alice_token_id,
+                &alice_owncoins,
+                6,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing second HTLC lock tx");
+            th.execute_transfer_tx(holder, lock_tx.clone(), &lock_params, &fee_params, 6, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        info!("[Bob] Checking the HTLC can't be claimed after expiry");
+        let (claim_tx, _, _, _) = th.htlc_spend(&Holder::Bob, &htlc, Some(preimage), 20).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice.add_transaction("money::htlc_spend", claim_tx, 20, false).await.is_err());
+
+        info!("[Alice] Building HTLC refund tx");
+        let (refund_tx, refund_params, _, fee_params) =
+            th.htlc_spend(&Holder::Alice, &htlc, None, 20).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing HTLC refund tx");
+            th.execute_transfer_tx(
+                holder,
+                refund_tx.clone(),
+                &refund_params,
+                &fee_params,
+                20,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_wallet = th.holders.get(&Holder::Alice).unwrap();
+        let alice_balance: u64 = alice_wallet
+            .unspent_money_coins
+            .iter()
+            .filter(|x| x.note.spend_hook == FuncId::none())
+            .map(|x| x.note.value)
+            .sum();
+        assert_eq!(alice_balance, ALICE_SUPPLY - HTLC_VALUE);
+
+        // Thanks for reading
+        Ok(())
+    })
+}
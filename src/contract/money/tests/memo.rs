@@ -0,0 +1,190 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test memos attached to `Money::Transfer` notes.
+//!
+//! Bob pays Alice with an invoice reference in the memo. Memos are padded
+//! to a fixed size, and transfers with oversized notes are rejected.
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::{
+    client::{
+        coin_selection::LargestFirst,
+        transfer_v1::{make_transfer_call, TransferCallSecrets},
+    },
+    model::MoneyTransferParamsV1,
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+    MONEY_NOTE_CIPHERTEXT_MAX_LEN, MONEY_NOTE_MEMO_MAX_LEN,
+};
+use darkfi_sdk::{
+    crypto::{note::AeadEncryptedNote, BaseBlind, MONEY_CONTRACT_ID},
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use log::info;
+use rand::rngs::OsRng;
+
+/// Build and sign a transaction holding a single `Money::Transfer` call
+async fn transfer_tx(
+    params: &MoneyTransferParamsV1,
+    secrets: &TransferCallSecrets,
+) -> Result<Transaction> {
+    let mut data = vec![MoneyFunction::TransferV1 as u8];
+    params.encode_async(&mut data).await?;
+    let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+    let mut tx =
+        TransactionBuilder::new(ContractCallLeaf { call, proofs: secrets.proofs.clone() }, vec![])?
+            .build()?;
+    let sigs = tx.create_sigs(&secrets.signature_secrets)?;
+    tx.signatures = vec![sigs];
+
+    Ok(tx)
+}
+
+#[test]
+fn memo() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const BOB_SUPPLY: u64 = 2000000000; // 20 BOB
+        const BOB_SEND: u64 = 500000000; // 5 BOB
+        const INVOICE: &str = "Invoice #42";
+
+        // Block height to verify against
+        let current_block_height = 0;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Bob] Building BOB token mint tx");
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                BOB_SUPPLY,
+                &Holder::Bob,
+                &Holder::Bob,
+                BaseBlind::random(&mut OsRng),
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        let bob = th.holders.get(&Holder::Bob).unwrap();
+        let bob_owncoins = bob.unspent_money_coins.clone();
+        let bob_token_id = bob_owncoins[0].note.token_id;
+        let alice_public = th.holders.get(&Holder::Alice).unwrap().keypair.public;
+
+        let (mint_pk, mint_zkbin) = th.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = th.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        let make_call = |memo: Vec<u8>| {
+            make_transfer_call(
+                bob.keypair,
+                alice_public,
+                None,
+                BOB_SEND,
+                bob_token_id,
+                None,
+                memo,
+                bob_owncoins.clone(),
+                &LargestFirst,
+                bob.money_merkle_tree.clone(),
+                &bob.money_frozen_smt,
+                mint_zkbin.clone(),
+                mint_pk.clone(),
+                burn_zkbin.clone(),
+                burn_pk.clone(),
+            )
+        };
+
+        info!("[Bob] Checking an oversized memo can't be built");
+        assert!(make_call(vec![b'x'; MONEY_NOTE_MEMO_MAX_LEN + 1]).is_err());
+
+        info!("[Bob] Building BOB payment tx to Alice with a memo");
+        let (params, secrets, _) = make_call(INVOICE.as_bytes().to_vec())?;
+
+        // The payment and the memo-less change notes are the same size
+        let payment = &params.outputs[0].note;
+        let change = &params.outputs[1].note;
+        assert_eq!(payment.ciphertext.len(), change.ciphertext.len());
+        assert!(payment.ciphertext.len() <= MONEY_NOTE_CIPHERTEXT_MAX_LEN);
+
+        info!("[Bob] Building BOB payment tx to Alice with an oversized note");
+        let mut note = secrets.output_notes[0].clone();
+        note.memo = vec![b'x'; MONEY_NOTE_CIPHERTEXT_MAX_LEN];
+        let mut oversized_params = params.clone();
+        oversized_params.outputs[0].note =
+            AeadEncryptedNote::encrypt(&note, &alice_public, &mut OsRng)?;
+        let oversized_tx = transfer_tx(&oversized_params, &secrets).await?;
+
+        info!("[Alice] Checking the oversized note is rejected");
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice
+            .add_transaction("money::transfer", oversized_tx, current_block_height, false)
+            .await
+            .is_err());
+
+        let tx = transfer_tx(&params, &secrets).await?;
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB payment tx to Alice");
+            th.execute_transfer_tx(holder, tx.clone(), &params, &None, current_block_height, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // Alice reads the memo back, with its padding stripped
+        let alice_owncoins = &th.holders.get(&Holder::Alice).unwrap().unspent_money_coins;
+        assert_eq!(alice_owncoins.len(), 1);
+        let note = &alice_owncoins[0].note;
+        assert_eq!(note.value, BOB_SEND);
+        assert_eq!(note.memo.len(), MONEY_NOTE_MEMO_MAX_LEN);
+        assert_eq!(note.memo_str(), Some(INVOICE));
+
+        // Bob's change carries no memo
+        let bob_owncoins = &th.holders.get(&Holder::Bob).unwrap().unspent_money_coins;
+        assert_eq!(bob_owncoins.len(), 1);
+        assert_eq!(bob_owncoins[0].note.memo_str(), None);
+
+        // Thanks for reading
+        Ok(())
+    })
+}
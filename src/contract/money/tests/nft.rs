@@ -0,0 +1,112 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test non-fungible tokens through `Money::NftMint` and `Money::NftBurn`.
+//!
+//! Alice mints an NFT committing to some metadata and transfers it to
+//! Bob with a regular `Money::Transfer`. Minting the same token again
+//! is rejected. Bob then burns the NFT.
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::model::Nft;
+use darkfi_sdk::{
+    crypto::{poseidon_hash, BaseBlind},
+    pasta::pallas,
+};
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn nft() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        // Commitment to the NFT's off-chain metadata
+        let metadata = poseidon_hash([pallas::Base::from(42), pallas::Base::from(1337)]);
+        let token_blind = BaseBlind::random(&mut OsRng);
+
+        info!("[Alice] Building NFT mint tx");
+        let (mint_tx, mint_params, fee_params) =
+            th.nft_mint(&Holder::Alice, &Holder::Alice, metadata, token_blind, 0).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing NFT mint tx");
+            th.execute_nft_mint_tx(holder, mint_tx.clone(), &mint_params, &fee_params, 0, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let token_id = mint_params.token_id;
+        assert_eq!(th.nft(&Holder::Alice, token_id)?, Some(Nft { metadata, burned: false }));
+
+        let alice_owncoins = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.clone();
+        assert_eq!(alice_owncoins.len(), 1);
+        assert_eq!(alice_owncoins[0].note.value, 1);
+        assert_eq!(alice_owncoins[0].note.token_id, token_id);
+
+        info!("[Alice] Checking the NFT can't be minted twice");
+        let (mint_tx, _, _) =
+            th.nft_mint(&Holder::Alice, &Holder::Alice, metadata, token_blind, 1).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice.add_transaction("money::nft_mint", mint_tx, 1, false).await.is_err());
+
+        info!("[Alice] Building NFT transfer tx to Bob");
+        let (xfer_tx, (xfer_params, fee_params), _) =
+            th.transfer(1, &Holder::Alice, &Holder::Bob, &alice_owncoins, token_id, 1).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing NFT transfer tx");
+            th.execute_transfer_tx(holder, xfer_tx.clone(), &xfer_params, &fee_params, 1, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        assert!(th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.is_empty());
+        let bob_owncoins = th.holders.get(&Holder::Bob).unwrap().unspent_money_coins.clone();
+        assert_eq!(bob_owncoins.len(), 1);
+        assert_eq!(bob_owncoins[0].note.value, 1);
+        assert_eq!(bob_owncoins[0].note.token_id, token_id);
+
+        info!("[Bob] Building NFT burn tx");
+        let (burn_tx, burn_params, fee_params) =
+            th.nft_burn(&Holder::Bob, &bob_owncoins[0], 2).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing NFT burn tx");
+            th.execute_nft_burn_tx(holder, burn_tx.clone(), &burn_params, &fee_params, 2, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        assert!(th.holders.get(&Holder::Bob).unwrap().unspent_money_coins.is_empty());
+        assert_eq!(th.nft(&Holder::Bob, token_id)?, Some(Nft { metadata, burned: true }));
+
+        // Thanks for reading
+        Ok(())
+    })
+}
@@ -18,6 +18,7 @@
 
 use darkfi::Result;
 use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::client::token_supply::TokenSupplyProof;
 use darkfi_sdk::crypto::BaseBlind;
 use log::info;
 use rand::rngs::OsRng;
@@ -69,9 +70,54 @@ fn token_mint() -> Result<()> {
 
         th.assert_trees(&HOLDERS);
 
+        info!("[Bob] Building second BOB token mint tx");
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                BOB_SUPPLY,
+                &Holder::Bob,
+                &Holder::Bob,
+                bob_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing second BOB token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // Bob minted the coins to themselves, so the notes hold the value
+        // blinds needed to prove the total supply.
+        info!("[Bob] Proving BOB token supply");
+        let bob_owncoins = &th.holders.get(&Holder::Bob).unwrap().unspent_money_coins;
+        let bob_token_id = bob_owncoins[0].note.token_id;
+        let mints: Vec<_> =
+            bob_owncoins.iter().map(|x| (x.note.value, x.note.value_blind)).collect();
+        let supply_proof = TokenSupplyProof::create(bob_token_id, &mints);
+        assert_eq!(supply_proof.supply, 2 * BOB_SUPPLY);
+
+        // Anyone can check it against the Money state
+        let supply_commit = th.token_supply_commit(&Holder::Alice, bob_token_id)?.unwrap();
+        assert!(supply_proof.verify(&supply_commit));
+        let lying_proof = TokenSupplyProof::create(bob_token_id, &mints[..1]);
+        assert!(!lying_proof.verify(&supply_commit));
+
         info!("[Bob] Building BOB token freeze tx");
         let (token_frz_tx, token_frz_params, fee_params) =
-            th.token_freeze(&Holder::Bob, current_block_height).await?;
+            th.token_freeze(&Holder::Bob, bob_token_blind, current_block_height).await?;
 
         for holder in &HOLDERS {
             info!("[{holder:?}] Executing BOB token freeze tx");
@@ -87,6 +133,7 @@ fn token_mint() -> Result<()> {
         }
 
         th.assert_trees(&HOLDERS);
+        assert!(th.token_mint_frozen(&Holder::Alice, bob_token_id)?);
 
         // Thanks for reading
         Ok(())
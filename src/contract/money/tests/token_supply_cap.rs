@@ -0,0 +1,271 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_sdk::crypto::BaseBlind;
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn token_supply_cap() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const ALICE_MINT: u64 = 1000000000; // 10 ALICE
+        const ALICE_CAP: u64 = 1500000000; // 15 ALICE
+        const BOB_CAP: u64 = 2000000000; // 20 BOB
+
+        // Block height to verify against
+        let current_block_height = 0;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Alice] Building ALICE token mint tx");
+        let alice_token_blind = BaseBlind::random(&mut OsRng);
+        let (mint_tx, mint_params, auth_params, fee_params) = th
+            .token_mint(
+                ALICE_MINT,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+        let alice_token_id = auth_params.token_id;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                mint_tx.clone(),
+                &mint_params,
+                &auth_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        // The token was already minted, so Alice has to prove the
+        // supply so far fits under the cap.
+        info!("[Alice] Building ALICE supply cap tx");
+        let (cap_tx, cap_params, fee_params) = th
+            .token_cap(&Holder::Alice, alice_token_blind, ALICE_CAP, current_block_height)
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE supply cap tx");
+            th.execute_token_cap_tx(
+                holder,
+                cap_tx.clone(),
+                &cap_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        assert_eq!(th.token_supply_cap(&Holder::Bob, alice_token_id)?, Some(ALICE_CAP));
+
+        info!("[Alice] Building second ALICE supply cap tx");
+        let (cap_tx, cap_params, fee_params) = th
+            .token_cap(&Holder::Alice, alice_token_blind, 2 * ALICE_CAP, current_block_height)
+            .await?;
+
+        info!("[Alice] Checking the supply cap can't be changed");
+        assert!(th
+            .execute_token_cap_tx(
+                &Holder::Alice,
+                cap_tx,
+                &cap_params,
+                &fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        info!("[Alice] Building ALICE token mint tx reaching the cap");
+        let (mint_tx, mint_params, auth_params, fee_params) = th
+            .token_mint(
+                ALICE_CAP - ALICE_MINT,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE token mint tx reaching the cap");
+            th.execute_token_mint_tx(
+                holder,
+                mint_tx.clone(),
+                &mint_params,
+                &auth_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        assert_eq!(th.token_supply(&Holder::Alice, alice_token_id).0, ALICE_CAP);
+
+        info!("[Alice] Building ALICE token mint tx exceeding the cap");
+        let (mint_tx, mint_params, auth_params, fee_params) = th
+            .token_mint(
+                1,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        info!("[Alice] Checking ALICE token mint tx exceeding the cap fails");
+        assert!(th
+            .execute_token_mint_tx(
+                &Holder::Alice,
+                mint_tx,
+                &mint_params,
+                &auth_params,
+                &fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        th.assert_trees(&HOLDERS);
+
+        // A cap can also be set before anything is minted
+        info!("[Bob] Building BOB supply cap tx");
+        let bob_token_blind = BaseBlind::random(&mut OsRng);
+        let (cap_tx, cap_params, fee_params) =
+            th.token_cap(&Holder::Bob, bob_token_blind, BOB_CAP, current_block_height).await?;
+        let bob_token_id = cap_params.token_id;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB supply cap tx");
+            th.execute_token_cap_tx(
+                holder,
+                cap_tx.clone(),
+                &cap_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        info!("[Bob] Building BOB token mint tx");
+        let (mint_tx, mint_params, auth_params, fee_params) = th
+            .token_mint(
+                BOB_CAP,
+                &Holder::Bob,
+                &Holder::Bob,
+                bob_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                mint_tx.clone(),
+                &mint_params,
+                &auth_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        // Revoking the mint authority fixes the supply forever
+        info!("[Bob] Building BOB token freeze tx");
+        let (frz_tx, frz_params, fee_params) =
+            th.token_freeze(&Holder::Bob, bob_token_blind, current_block_height).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB token freeze tx");
+            th.execute_token_freeze_tx(
+                holder,
+                frz_tx.clone(),
+                &frz_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        assert!(th.token_mint_frozen(&Holder::Alice, bob_token_id)?);
+        assert!(!th.token_mint_frozen(&Holder::Alice, alice_token_id)?);
+
+        info!("[Bob] Building BOB token mint tx after revocation");
+        let (mint_tx, mint_params, auth_params, fee_params) = th
+            .token_mint(
+                0,
+                &Holder::Bob,
+                &Holder::Bob,
+                bob_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        info!("[Bob] Checking BOB token mint tx after revocation fails");
+        assert!(th
+            .execute_token_mint_tx(
+                &Holder::Bob,
+                mint_tx,
+                &mint_params,
+                &auth_params,
+                &fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        th.assert_trees(&HOLDERS);
+
+        // Thanks for reading
+        Ok(())
+    })
+}
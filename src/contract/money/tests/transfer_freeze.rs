@@ -0,0 +1,274 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::{MoneyFunction, MONEY_CONTRACT_FROZEN_ROOT_GRACE_BLOCKS};
+use darkfi_sdk::crypto::BaseBlind;
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn transfer_freeze() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const BOB_SUPPLY: u64 = 2000000000; // 20 BOB
+        const BOB_SEND: u64 = 500000000; // 5 BOB
+
+        // Block height to verify against
+        let current_block_height = 0;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Bob] Building BOB token mint tx");
+        let bob_token_blind = BaseBlind::random(&mut OsRng);
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                BOB_SUPPLY,
+                &Holder::Bob,
+                &Holder::Bob,
+                bob_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_owncoins = th.holders.get(&Holder::Bob).unwrap().unspent_money_coins.clone();
+        let bob_token_id = bob_owncoins[0].note.token_id;
+
+        info!("[Bob] Building BOB transfer freeze tx");
+        let (freeze_tx, freeze_params, fee_params) = th
+            .transfer_freeze(
+                &Holder::Bob,
+                bob_token_blind,
+                MoneyFunction::TransferFreezeV1,
+                current_block_height,
+            )
+            .await?;
+        assert_eq!(freeze_params.token_id, bob_token_id);
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB transfer freeze tx");
+            th.execute_transfer_freeze_tx(
+                holder,
+                freeze_tx.clone(),
+                &freeze_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        // Freezing twice is not allowed
+        info!("[Bob] Checking BOB can't be frozen twice");
+        let (freeze_tx, freeze_params, fee_params) = th
+            .transfer_freeze(
+                &Holder::Bob,
+                bob_token_blind,
+                MoneyFunction::TransferFreezeV1,
+                current_block_height,
+            )
+            .await?;
+        assert!(th
+            .execute_transfer_freeze_tx(
+                &Holder::Alice,
+                freeze_tx,
+                &freeze_params,
+                &fee_params,
+                current_block_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        // Now BOB can't be transferred
+        info!("[Bob] Checking frozen BOB can't be transferred");
+        let (transfer_tx, (transfer_params, fee_params), _) = th
+            .transfer(
+                BOB_SEND,
+                &Holder::Bob,
+                &Holder::Alice,
+                &bob_owncoins,
+                bob_token_id,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            assert!(th
+                .execute_transfer_tx(
+                    holder,
+                    transfer_tx.clone(),
+                    &transfer_params,
+                    &fee_params,
+                    current_block_height,
+                    true,
+                )
+                .await
+                .is_err());
+        }
+
+        info!("[Bob] Building BOB transfer unfreeze tx");
+        let (unfreeze_tx, unfreeze_params, fee_params) = th
+            .transfer_freeze(
+                &Holder::Bob,
+                bob_token_blind,
+                MoneyFunction::TransferUnfreezeV1,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB transfer unfreeze tx");
+            th.execute_transfer_freeze_tx(
+                holder,
+                unfreeze_tx.clone(),
+                &unfreeze_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        // And after unfreezing, the transfer goes through
+        info!("[Bob] Building BOB transfer to Alice");
+        let (transfer_tx, (transfer_params, fee_params), _) = th
+            .transfer(
+                BOB_SEND,
+                &Holder::Bob,
+                &Holder::Alice,
+                &bob_owncoins,
+                bob_token_id,
+                current_block_height,
+            )
+            .await?;
+
+        // Freezing another token changes the frozen tokens root, but the
+        // pending transfer was proven against a recent root so it stays
+        // valid until the grace period runs out.
+        info!("[Bob] Building and freezing a second token");
+        let other_token_blind = BaseBlind::random(&mut OsRng);
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params_) = th
+            .token_mint(
+                BOB_SUPPLY,
+                &Holder::Bob,
+                &Holder::Bob,
+                other_token_blind,
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params_,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        let (freeze_tx, freeze_params, fee_params_) = th
+            .transfer_freeze(
+                &Holder::Bob,
+                other_token_blind,
+                MoneyFunction::TransferFreezeV1,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            th.execute_transfer_freeze_tx(
+                holder,
+                freeze_tx.clone(),
+                &freeze_params,
+                &fee_params_,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        info!("[Bob] Checking the superseded frozen root expires");
+        let expired_height =
+            current_block_height + MONEY_CONTRACT_FROZEN_ROOT_GRACE_BLOCKS as u64 + 1;
+        assert!(th
+            .execute_transfer_tx(
+                &Holder::Alice,
+                transfer_tx.clone(),
+                &transfer_params,
+                &fee_params,
+                expired_height,
+                false,
+            )
+            .await
+            .is_err());
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB transfer to Alice");
+            th.execute_transfer_tx(
+                holder,
+                transfer_tx.clone(),
+                &transfer_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_owncoins = &th.holders.get(&Holder::Alice).unwrap().unspent_money_coins;
+        assert_eq!(alice_owncoins.len(), 1);
+        assert_eq!(alice_owncoins[0].note.value, BOB_SEND);
+
+        // Thanks for reading
+        Ok(())
+    })
+}
@@ -0,0 +1,155 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Test vesting payments through `Money::VestingClaim`.
+//!
+//! Alice locks ALICE tokens for Bob, unlocking linearly after a cliff.
+//! Bob claims what is unlocked along the way, and the rest at the end.
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::{client::vesting_claim_v1::vesting_spend_hook, model::VestingSchedule};
+use darkfi_sdk::crypto::{BaseBlind, FuncId};
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn vesting() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const ALICE_SUPPLY: u64 = 2000000000; // 20 ALICE
+        const VESTING_VALUE: u64 = 1000000000; // 10 ALICE
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Alice] Building ALICE token mint tx");
+        let alice_token_blind = BaseBlind::random(&mut OsRng);
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                ALICE_SUPPLY,
+                &Holder::Alice,
+                &Holder::Alice,
+                alice_token_blind,
+                None,
+                None,
+                0,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing ALICE token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                0,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let alice_owncoins = th.holders.get(&Holder::Alice).unwrap().unspent_money_coins.clone();
+        let alice_token_id = alice_owncoins[0].note.token_id;
+
+        // Nothing unlocks before block 20, then it unlocks linearly until block 110
+        let schedule = VestingSchedule {
+            recipient: th.holders.get(&Holder::Bob).unwrap().keypair.public,
+            total_value: VESTING_VALUE,
+            token_id: alice_token_id,
+            start_height: 10,
+            cliff_height: 20,
+            end_height: 110,
+            blind: BaseBlind::random(&mut OsRng),
+        };
+        assert_eq!(schedule.unlocked(19), 0);
+        assert_eq!(schedule.unlocked(20), VESTING_VALUE / 10);
+        assert_eq!(schedule.unlocked(110), VESTING_VALUE);
+
+        info!("[Alice] Building vesting lock tx for Bob");
+        let (lock_tx, lock_params, fee_params, _) =
+            th.vesting_lock(&Holder::Alice, &schedule, &alice_owncoins, 1).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing vesting lock tx");
+            th.execute_transfer_tx(holder, lock_tx.clone(), &lock_params, &fee_params, 1, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_wallet = th.holders.get(&Holder::Bob).unwrap();
+        assert_eq!(bob_wallet.unspent_money_coins.len(), 1);
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.spend_hook, vesting_spend_hook());
+        assert_eq!(bob_wallet.unspent_money_coins[0].note.user_data, schedule.to_bulla());
+
+        // A claim can't be confirmed before its claim height
+        info!("[Bob] Checking a claim can't be executed before its height");
+        let claim_value = schedule.unlocked(60);
+        let (claim_tx, _, _, _) =
+            th.vesting_claim(&Holder::Bob, &schedule, claim_value, 60).await?;
+        let alice = th.holders.get_mut(&Holder::Alice).unwrap();
+        assert!(alice.add_transaction("money::vesting_claim", claim_tx, 50, false).await.is_err());
+
+        info!("[Bob] Building vesting claim tx at height 60");
+        let (claim_tx, claim_params, _, fee_params) =
+            th.vesting_claim(&Holder::Bob, &schedule, claim_value, 60).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing vesting claim tx");
+            th.execute_transfer_tx(holder, claim_tx.clone(), &claim_params, &fee_params, 60, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // Bob can claim the rest once the schedule ended
+        info!("[Bob] Building vesting claim tx at height 110");
+        let (claim_tx, claim_params, _, fee_params) =
+            th.vesting_claim(&Holder::Bob, &schedule, VESTING_VALUE - claim_value, 110).await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing vesting claim tx");
+            th.execute_transfer_tx(holder, claim_tx.clone(), &claim_params, &fee_params, 110, true)
+                .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        let bob_wallet = th.holders.get(&Holder::Bob).unwrap();
+        let bob_balance: u64 = bob_wallet
+            .unspent_money_coins
+            .iter()
+            .filter(|x| x.note.spend_hook == FuncId::none())
+            .map(|x| x.note.value)
+            .sum();
+        assert_eq!(bob_balance, VESTING_VALUE);
+
+        // Thanks for reading
+        Ok(())
+    })
+}
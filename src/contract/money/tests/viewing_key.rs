@@ -0,0 +1,130 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_money_contract::client::MoneyNote;
+use darkfi_sdk::crypto::{BaseBlind, ViewingKey};
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn viewing_key() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Some numbers we want to assert
+        const BOB_SUPPLY: u64 = 2000000000; // 20 BOB
+        const BOB_SEND: u64 = 500000000; // 5 BOB
+
+        // Block height to verify against
+        let current_block_height = 0;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        info!("[Bob] Building BOB token mint tx");
+        let (token_mint_tx, token_mint_params, token_auth_mint_params, fee_params) = th
+            .token_mint(
+                BOB_SUPPLY,
+                &Holder::Bob,
+                &Holder::Bob,
+                BaseBlind::random(&mut OsRng),
+                None,
+                None,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB token mint tx");
+            th.execute_token_mint_tx(
+                holder,
+                token_mint_tx.clone(),
+                &token_mint_params,
+                &token_auth_mint_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        let bob_owncoins = th.holders.get(&Holder::Bob).unwrap().unspent_money_coins.clone();
+        let bob_token_id = bob_owncoins[0].note.token_id;
+
+        // The harness encrypts transfer notes to the recipient viewing key
+        info!("[Bob] Building BOB transfer to Alice");
+        let (transfer_tx, (transfer_params, fee_params), _) = th
+            .transfer(
+                BOB_SEND,
+                &Holder::Bob,
+                &Holder::Alice,
+                &bob_owncoins,
+                bob_token_id,
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing BOB transfer to Alice");
+            th.execute_transfer_tx(
+                holder,
+                transfer_tx.clone(),
+                &transfer_params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        th.assert_trees(&HOLDERS);
+
+        // Alice found her coin through the viewing key fallback
+        let alice_owncoins = &th.holders.get(&Holder::Alice).unwrap().unspent_money_coins;
+        assert_eq!(alice_owncoins.len(), 1);
+        assert_eq!(alice_owncoins[0].note.value, BOB_SEND);
+
+        let alice_secret = th.holders.get(&Holder::Alice).unwrap().keypair.secret;
+        let bob_secret = th.holders.get(&Holder::Bob).unwrap().keypair.secret;
+        let alice_viewing_key = ViewingKey::derive(&alice_secret);
+        let bob_viewing_key = ViewingKey::derive(&bob_secret);
+
+        // An auditor holding only Alice's viewing key can read the payment,
+        // but the note is not readable with the spending key itself.
+        let payment = &transfer_params.outputs[0].note;
+        assert!(payment.decrypt::<MoneyNote>(&alice_secret).is_err());
+        let note: MoneyNote = payment.decrypt(&alice_viewing_key.inner())?;
+        assert_eq!(note.value, BOB_SEND);
+        assert_eq!(note.token_id, bob_token_id);
+        assert!(payment.decrypt::<MoneyNote>(&bob_viewing_key.inner()).is_err());
+
+        // Bob's change is encrypted to his own viewing key
+        let change = &transfer_params.outputs[1].note;
+        let note: MoneyNote = change.decrypt(&bob_viewing_key.inner())?;
+        assert_eq!(note.value, BOB_SUPPLY - BOB_SEND);
+        assert!(change.decrypt::<MoneyNote>(&alice_viewing_key.inner()).is_err());
+
+        // Thanks for reading
+        Ok(())
+    })
+}
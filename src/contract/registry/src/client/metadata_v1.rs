@@ -0,0 +1,54 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::Result;
+use darkfi_sdk::crypto::{BaseBlind, Keypair};
+use log::info;
+
+use crate::model::{RegistryParamsV1, TokenMetadata};
+
+pub struct RegistryCallDebris {
+    pub params: RegistryParamsV1,
+}
+
+/// Struct holding necessary information to build a `Registry::DeployV1`
+/// or `Registry::UpdateV1` contract call. Both calls take the same
+/// parameters, so the caller picks the function when building the transaction.
+pub struct RegistryCallBuilder {
+    /// Mint authority keypair of the token
+    pub mint_keypair: Keypair,
+    /// Blind of the token ID
+    pub token_blind: BaseBlind,
+    /// Metadata to set for the token
+    pub metadata: TokenMetadata,
+}
+
+impl RegistryCallBuilder {
+    pub fn build(&self) -> Result<RegistryCallDebris> {
+        info!("Building Registry::DeployV1 contract call");
+
+        let params = RegistryParamsV1 {
+            mint_public: self.mint_keypair.public,
+            token_blind: self.token_blind,
+            metadata: self.metadata.clone(),
+        };
+        let debris = RegistryCallDebris { params };
+
+        Ok(debris)
+    }
+}
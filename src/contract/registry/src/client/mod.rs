@@ -0,0 +1,48 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! This module implements the client-side API for registering token
+//! metadata on the DarkFi network, and querying it.
+
+use darkfi::{blockchain::Blockchain, Result};
+use darkfi_money_contract::model::TokenId;
+use darkfi_sdk::crypto::REGISTRY_CONTRACT_ID;
+use darkfi_serial::{deserialize, serialize};
+
+use crate::{model::TokenMetadata, REGISTRY_CONTRACT_METADATA_TREE};
+
+/// `Registry::DeployV1` and `Registry::UpdateV1` API
+pub mod metadata_v1;
+
+/// Fetch the registered metadata of the given token from the blockchain
+/// state, if any.
+pub fn lookup_token_metadata(
+    blockchain: &Blockchain,
+    token_id: &TokenId,
+) -> Result<Option<TokenMetadata>> {
+    let metadata_db = blockchain.contracts.lookup(
+        &blockchain.sled_db,
+        &REGISTRY_CONTRACT_ID,
+        REGISTRY_CONTRACT_METADATA_TREE,
+    )?;
+
+    match metadata_db.get(serialize(token_id))? {
+        Some(data) => Ok(Some(deserialize(&data)?)),
+        None => Ok(None),
+    }
+}
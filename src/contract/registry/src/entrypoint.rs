@@ -0,0 +1,114 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::ContractId, dark_tree::DarkLeaf, error::ContractResult, wasm, ContractCall,
+};
+use darkfi_serial::{deserialize, serialize};
+
+use crate::{
+    model::RegistryUpdateV1, RegistryFunction, REGISTRY_CONTRACT_DB_VERSION,
+    REGISTRY_CONTRACT_INFO_TREE, REGISTRY_CONTRACT_METADATA_TREE,
+};
+
+/// `Registry::Deploy` and `Registry::Update` functions
+mod metadata_v1;
+use metadata_v1::{
+    registry_deploy_process_instruction_v1, registry_get_metadata_v1, registry_process_update_v1,
+    registry_update_process_instruction_v1,
+};
+
+darkfi_sdk::define_contract!(
+    init: init_contract,
+    exec: process_instruction,
+    apply: process_update,
+    metadata: get_metadata
+);
+
+/// This entrypoint function runs when the contract is (re)deployed and initialized.
+/// We use this function to initialize all the necessary databases and prepare them
+/// with initial data if necessary.
+fn init_contract(cid: ContractId, _ix: &[u8]) -> ContractResult {
+    // Set up a database tree for arbitrary data
+    let info_db = match wasm::db::db_lookup(cid, REGISTRY_CONTRACT_INFO_TREE) {
+        Ok(v) => v,
+        Err(_) => wasm::db::db_init(cid, REGISTRY_CONTRACT_INFO_TREE)?,
+    };
+
+    // Set up a database to hold the registered token metadata
+    // k=TokenId, v=TokenMetadata
+    if wasm::db::db_lookup(cid, REGISTRY_CONTRACT_METADATA_TREE).is_err() {
+        wasm::db::db_init(cid, REGISTRY_CONTRACT_METADATA_TREE)?;
+    }
+
+    // Update db version
+    wasm::db::db_set(
+        info_db,
+        REGISTRY_CONTRACT_DB_VERSION,
+        &serialize(&env!("CARGO_PKG_VERSION")),
+    )?;
+
+    Ok(())
+}
+
+/// This function is used by the wasm VM's host to fetch the necessary metadata
+/// for verifying signatures and zk proofs. The payload given here are all the
+/// contract calls in the transaction.
+fn get_metadata(cid: ContractId, ix: &[u8]) -> ContractResult {
+    let call_idx = wasm::util::get_call_index()?;
+    let calls: Vec<DarkLeaf<ContractCall>> = deserialize(ix)?;
+    let self_ = &calls[call_idx as usize].data;
+    let func = RegistryFunction::try_from(self_.data[0])?;
+
+    let metadata = match func {
+        RegistryFunction::DeployV1 | RegistryFunction::UpdateV1 => {
+            registry_get_metadata_v1(cid, call_idx, calls)?
+        }
+    };
+
+    wasm::util::set_return_data(&metadata)
+}
+
+/// This function verifies a state transition and produces a state update
+/// if everything is successful.
+fn process_instruction(cid: ContractId, ix: &[u8]) -> ContractResult {
+    let call_idx = wasm::util::get_call_index()?;
+    let calls: Vec<DarkLeaf<ContractCall>> = deserialize(ix)?;
+    let self_ = &calls[call_idx as usize].data;
+    let func = RegistryFunction::try_from(self_.data[0])?;
+
+    let update_data = match func {
+        RegistryFunction::DeployV1 => registry_deploy_process_instruction_v1(cid, call_idx, calls)?,
+        RegistryFunction::UpdateV1 => registry_update_process_instruction_v1(cid, call_idx, calls)?,
+    };
+
+    wasm::util::set_return_data(&update_data)
+}
+
+/// This function attempts to write a given state update provided the previous
+/// steps of the contract call execution were all successful. It's the last in
+/// line, and assumes that the transaction/call was successful. The payload
+/// given to the function is the update data retrieved from `process_instruction()`.
+fn process_update(cid: ContractId, update_data: &[u8]) -> ContractResult {
+    match RegistryFunction::try_from(update_data[0])? {
+        RegistryFunction::DeployV1 | RegistryFunction::UpdateV1 => {
+            let update: RegistryUpdateV1 = deserialize(&update_data[1..])?;
+            Ok(registry_process_update_v1(cid, update)?)
+        }
+    }
+}
@@ -0,0 +1,126 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::{
+    crypto::{ContractId, PublicKey},
+    dark_tree::DarkLeaf,
+    error::{ContractError, ContractResult},
+    msg,
+    pasta::pallas,
+    wasm, ContractCall,
+};
+use darkfi_serial::{deserialize_strict, serialize, Encodable, WriteExt};
+
+use crate::{
+    error::RegistryError,
+    model::{RegistryParamsV1, RegistryUpdateV1},
+    RegistryFunction, REGISTRY_CONTRACT_METADATA_TREE,
+};
+
+/// `get_metadata` function for `Registry::DeployV1` and `Registry::UpdateV1`
+pub(crate) fn registry_get_metadata_v1(
+    _cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize];
+    let params: RegistryParamsV1 = deserialize_strict(&self_.data.data[1..])?;
+
+    // Public inputs for the ZK proofs we have to verify
+    let zk_public_inputs: Vec<(String, Vec<pallas::Base>)> = vec![];
+    // Public keys for the transaction signatures we have to verify.
+    // The metadata is signed by the token's mint authority.
+    let signature_pubkeys: Vec<PublicKey> = vec![params.mint_public];
+
+    // Serialize everything gathered and return it
+    let mut metadata = vec![];
+    zk_public_inputs.encode(&mut metadata)?;
+    signature_pubkeys.encode(&mut metadata)?;
+
+    Ok(metadata)
+}
+
+/// Shared checks of `Registry::DeployV1` and `Registry::UpdateV1`, creating
+/// the state update for the given function when the token's metadata
+/// existence matches `exists`.
+fn registry_process_instruction(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+    func: RegistryFunction,
+    exists: bool,
+) -> Result<Vec<u8>, ContractError> {
+    let self_ = &calls[call_idx as usize];
+    let params: RegistryParamsV1 = deserialize_strict(&self_.data.data[1..])?;
+
+    if !params.metadata.is_valid() {
+        msg!("[RegistryV1] Error: Invalid token metadata");
+        return Err(RegistryError::MetadataInvalid.into())
+    }
+
+    // The token ID is derived from the authority that signed the call
+    let token_id = params.token_id();
+    let metadata_db = wasm::db::db_lookup(cid, REGISTRY_CONTRACT_METADATA_TREE)?;
+
+    if wasm::db::db_contains_key(metadata_db, &serialize(&token_id))? != exists {
+        if exists {
+            msg!("[RegistryV1] Error: Metadata of token {} doesn't exist", token_id);
+            return Err(RegistryError::MetadataNonExistent.into())
+        }
+
+        msg!("[RegistryV1] Error: Metadata of token {} already deployed", token_id);
+        return Err(RegistryError::MetadataExists.into())
+    }
+
+    let update = RegistryUpdateV1 { token_id, metadata: params.metadata };
+    let mut update_data = vec![];
+    update_data.write_u8(func as u8)?;
+    update.encode(&mut update_data)?;
+
+    Ok(update_data)
+}
+
+/// `process_instruction` function for `Registry::DeployV1`
+pub(crate) fn registry_deploy_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    registry_process_instruction(cid, call_idx, calls, RegistryFunction::DeployV1, false)
+}
+
+/// `process_instruction` function for `Registry::UpdateV1`
+pub(crate) fn registry_update_process_instruction_v1(
+    cid: ContractId,
+    call_idx: u32,
+    calls: Vec<DarkLeaf<ContractCall>>,
+) -> Result<Vec<u8>, ContractError> {
+    registry_process_instruction(cid, call_idx, calls, RegistryFunction::UpdateV1, true)
+}
+
+/// `process_update` function for `Registry::DeployV1` and `Registry::UpdateV1`
+pub(crate) fn registry_process_update_v1(
+    cid: ContractId,
+    update: RegistryUpdateV1,
+) -> ContractResult {
+    msg!("[RegistryV1] Setting metadata of token {}", update.token_id);
+    let metadata_db = wasm::db::db_lookup(cid, REGISTRY_CONTRACT_METADATA_TREE)?;
+    wasm::db::db_set(metadata_db, &serialize(&update.token_id), &serialize(&update.metadata))?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_sdk::error::ContractError;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum RegistryError {
+    #[error("Token metadata already deployed.")]
+    MetadataExists,
+
+    #[error("Token metadata does not exist.")]
+    MetadataNonExistent,
+
+    #[error("Token metadata invalid.")]
+    MetadataInvalid,
+}
+
+impl From<RegistryError> for ContractError {
+    fn from(e: RegistryError) -> Self {
+        match e {
+            RegistryError::MetadataExists => Self::Custom(1),
+            RegistryError::MetadataNonExistent => Self::Custom(2),
+            RegistryError::MetadataInvalid => Self::Custom(3),
+        }
+    }
+}
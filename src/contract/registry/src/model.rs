@@ -0,0 +1,92 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "client")]
+use darkfi_serial::async_trait;
+
+use darkfi_money_contract::{
+    model::{TokenAttributes, TokenId},
+    MoneyFunction,
+};
+use darkfi_sdk::crypto::{poseidon_hash, BaseBlind, FuncRef, PublicKey, MONEY_CONTRACT_ID};
+use darkfi_serial::{SerialDecodable, SerialEncodable};
+
+use crate::{REGISTRY_MAX_DECIMALS, REGISTRY_TICKER_MAX_LEN};
+
+/// Human-readable metadata of a token
+#[derive(Clone, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct TokenMetadata {
+    /// Short uppercase ticker, e.g. `DRK`
+    pub ticker: String,
+    /// Number of decimals the token's values are displayed with
+    pub decimals: u8,
+    /// BLAKE3 hash of the token's off-chain description
+    pub description_hash: [u8; 32],
+}
+
+impl TokenMetadata {
+    /// Check the ticker is made of 1 to [`REGISTRY_TICKER_MAX_LEN`] uppercase
+    /// ASCII letters and digits, and the decimals don't exceed
+    /// [`REGISTRY_MAX_DECIMALS`].
+    pub fn is_valid(&self) -> bool {
+        !self.ticker.is_empty() &&
+            self.ticker.len() <= REGISTRY_TICKER_MAX_LEN &&
+            self.ticker.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit()) &&
+            self.decimals <= REGISTRY_MAX_DECIMALS
+    }
+}
+
+/// Parameters for `Registry::Deploy` and `Registry::Update`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct RegistryParamsV1 {
+    /// Mint authority public key, used to derive the token ID and
+    /// verify the signature
+    pub mint_public: PublicKey,
+    /// Blind of the token ID
+    pub token_blind: BaseBlind,
+    /// Metadata to set for the token
+    pub metadata: TokenMetadata,
+}
+
+impl RegistryParamsV1 {
+    /// Derive the ID of the token minted by `mint_public` through
+    /// `Money::AuthTokenMint`
+    pub fn token_id(&self) -> TokenId {
+        let auth_parent = FuncRef {
+            contract_id: *MONEY_CONTRACT_ID,
+            func_code: MoneyFunction::AuthTokenMintV1 as u8,
+        }
+        .to_func_id();
+
+        let (mint_x, mint_y) = self.mint_public.xy();
+        let token_attrs = TokenAttributes {
+            auth_parent,
+            user_data: poseidon_hash([mint_x, mint_y]),
+            blind: self.token_blind,
+        };
+
+        token_attrs.to_token_id()
+    }
+}
+
+/// State update for `Registry::Deploy` and `Registry::Update`
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct RegistryUpdateV1 {
+    pub token_id: TokenId,
+    pub metadata: TokenMetadata,
+}
@@ -0,0 +1,181 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::Result;
+use darkfi_contract_test_harness::{init_logger, Holder, TestHarness};
+use darkfi_registry_contract::{model::TokenMetadata, RegistryFunction};
+use darkfi_sdk::crypto::BaseBlind;
+use log::info;
+use rand::rngs::OsRng;
+
+#[test]
+fn registry_integration() -> Result<()> {
+    smol::block_on(async {
+        init_logger();
+
+        // Holders this test will use
+        const HOLDERS: [Holder; 2] = [Holder::Alice, Holder::Bob];
+
+        // Block height to verify against
+        let current_block_height = 0;
+
+        // Initialize harness
+        let mut th = TestHarness::new(&HOLDERS, false).await?;
+
+        // Blind of the token Alice registers metadata for
+        let token_blind = BaseBlind::random(&mut OsRng);
+
+        let metadata =
+            TokenMetadata { ticker: "ALICE".to_string(), decimals: 8, description_hash: [1; 32] };
+
+        info!("[Alice] Building registry deploy tx");
+        let (tx, params, fee_params) = th
+            .registry_set_metadata(
+                &Holder::Alice,
+                RegistryFunction::DeployV1,
+                token_blind,
+                metadata.clone(),
+                current_block_height,
+            )
+            .await?;
+        let token_id = params.token_id();
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing registry deploy tx");
+            th.execute_registry_tx(
+                holder,
+                tx.clone(),
+                &params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        assert_eq!(th.token_metadata(&Holder::Bob, token_id)?, Some(metadata.clone()));
+
+        info!("[Alice] Building duplicate registry deploy tx");
+        let (tx, params, fee_params) = th
+            .registry_set_metadata(
+                &Holder::Alice,
+                RegistryFunction::DeployV1,
+                token_blind,
+                metadata,
+                current_block_height,
+            )
+            .await?;
+
+        info!("[Alice] Checking duplicate registry deploy tx fails");
+        assert!(th
+            .execute_registry_tx(
+                &Holder::Alice,
+                tx,
+                &params,
+                &fee_params,
+                current_block_height,
+                false
+            )
+            .await
+            .is_err());
+
+        info!("[Alice] Building registry update tx with invalid metadata");
+        let invalid =
+            TokenMetadata { ticker: "alice".to_string(), decimals: 8, description_hash: [1; 32] };
+        let (tx, params, fee_params) = th
+            .registry_set_metadata(
+                &Holder::Alice,
+                RegistryFunction::UpdateV1,
+                token_blind,
+                invalid,
+                current_block_height,
+            )
+            .await?;
+
+        info!("[Alice] Checking registry update tx with invalid metadata fails");
+        assert!(th
+            .execute_registry_tx(
+                &Holder::Alice,
+                tx,
+                &params,
+                &fee_params,
+                current_block_height,
+                false
+            )
+            .await
+            .is_err());
+
+        // Bob doesn't hold Alice's mint authority, so the update targets
+        // a token derived from Bob's own key, which has no metadata.
+        info!("[Bob] Building registry update tx for Alice's token");
+        let (tx, params, fee_params) = th
+            .registry_set_metadata(
+                &Holder::Bob,
+                RegistryFunction::UpdateV1,
+                token_blind,
+                TokenMetadata { ticker: "BOB".to_string(), decimals: 0, description_hash: [2; 32] },
+                current_block_height,
+            )
+            .await?;
+        assert_ne!(params.token_id(), token_id);
+
+        info!("[Bob] Checking registry update tx fails");
+        assert!(th
+            .execute_registry_tx(
+                &Holder::Bob,
+                tx,
+                &params,
+                &fee_params,
+                current_block_height,
+                false
+            )
+            .await
+            .is_err());
+
+        info!("[Alice] Building registry update tx");
+        let updated =
+            TokenMetadata { ticker: "ALC".to_string(), decimals: 6, description_hash: [3; 32] };
+        let (tx, params, fee_params) = th
+            .registry_set_metadata(
+                &Holder::Alice,
+                RegistryFunction::UpdateV1,
+                token_blind,
+                updated.clone(),
+                current_block_height,
+            )
+            .await?;
+
+        for holder in &HOLDERS {
+            info!("[{holder:?}] Executing registry update tx");
+            th.execute_registry_tx(
+                holder,
+                tx.clone(),
+                &params,
+                &fee_params,
+                current_block_height,
+                true,
+            )
+            .await?;
+        }
+
+        assert_eq!(th.token_metadata(&Holder::Alice, token_id)?, Some(updated));
+
+        // Thanks for reading
+        Ok(())
+    })
+}
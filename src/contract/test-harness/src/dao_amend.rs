@@ -0,0 +1,303 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    zk::Proof,
+    Result,
+};
+use darkfi_dao_contract::{
+    client::{DaoAmendCall, DaoExecCall, DaoExecMultiCall},
+    model::{Dao, DaoAmendParams, DaoExecParams, DaoProposal},
+    DaoFunction, DAO_CONTRACT_ZKAS_DAO_AMEND_NS, DAO_CONTRACT_ZKAS_DAO_EXEC_MULTI_NS,
+    DAO_CONTRACT_ZKAS_DAO_EXEC_NS, PROPOSAL_MAX_OPTIONS,
+};
+use darkfi_money_contract::{
+    client::{MoneyNote, OwnCoin},
+    model::MoneyFeeParamsV1,
+};
+use darkfi_sdk::{
+    crypto::{contract_id::DAO_CONTRACT_ID, Blind, MerkleNode, ScalarBlind, SecretKey},
+    dark_tree::DarkTree,
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use log::debug;
+use rand::rngs::OsRng;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Create a `Dao::Exec` transaction executing a proposal made with
+    /// `make_amend_auth_call()`, amending `dao` into `new_dao`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dao_amend(
+        &mut self,
+        holder: &Holder,
+        dao: &Dao,
+        new_dao: &Dao,
+        proposal: &DaoProposal,
+        yes_vote_value: u64,
+        abstain_vote_value: u64,
+        all_vote_value: u64,
+        yes_vote_blind: ScalarBlind,
+        abstain_vote_blind: ScalarBlind,
+        all_vote_blind: ScalarBlind,
+        block_height: u64,
+    ) -> Result<(Transaction, DaoAmendParams, DaoExecParams, Option<MoneyFeeParamsV1>)> {
+        let (dao_exec_pk, dao_exec_zkbin) =
+            self.proving_keys.get(DAO_CONTRACT_ZKAS_DAO_EXEC_NS).unwrap();
+
+        let wallet = self.holders.get(holder).unwrap();
+        let exec_signature_secret = SecretKey::random(&mut OsRng);
+
+        // Nothing is spent from the treasury
+        let exec_builder = DaoExecCall {
+            proposal: proposal.clone(),
+            dao: dao.clone(),
+            yes_vote_value,
+            abstain_vote_value,
+            all_vote_value,
+            yes_vote_blind,
+            abstain_vote_blind,
+            all_vote_blind,
+            input_value: 0,
+            input_value_blind: Blind::ZERO,
+            input_user_data_blind: Blind::ZERO,
+            hook_dao_exec: DAO_CONTRACT_ID.inner(),
+            signature_secret: exec_signature_secret,
+            min_block_height: block_height,
+            dao_superseded_smt: &wallet.dao_superseded_smt,
+        };
+
+        let (exec_params, exec_proofs) = exec_builder.make(dao_exec_zkbin, dao_exec_pk)?;
+
+        self.dao_amend_tx(
+            holder,
+            dao,
+            new_dao,
+            proposal,
+            exec_params,
+            exec_proofs,
+            exec_signature_secret,
+            block_height,
+        )
+        .await
+    }
+
+    /// Create a `Dao::Exec` transaction executing the winning option of a
+    /// multiple-choice proposal, where that option was made with
+    /// `make_amend_auth_call()`, amending `dao` into `new_dao`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dao_amend_multi(
+        &mut self,
+        holder: &Holder,
+        dao: &Dao,
+        new_dao: &Dao,
+        proposal: &DaoProposal,
+        option_vote_values: [u64; PROPOSAL_MAX_OPTIONS],
+        all_vote_value: u64,
+        option_vote_blinds: [ScalarBlind; PROPOSAL_MAX_OPTIONS],
+        all_vote_blind: ScalarBlind,
+        block_height: u64,
+    ) -> Result<(Transaction, DaoAmendParams, DaoExecParams, Option<MoneyFeeParamsV1>)> {
+        let (dao_exec_pk, dao_exec_zkbin) =
+            self.proving_keys.get(DAO_CONTRACT_ZKAS_DAO_EXEC_MULTI_NS).unwrap();
+
+        let wallet = self.holders.get(holder).unwrap();
+        let exec_signature_secret = SecretKey::random(&mut OsRng);
+
+        let exec_builder = DaoExecMultiCall {
+            proposal: proposal.clone(),
+            dao: dao.clone(),
+            option_vote_values,
+            all_vote_value,
+            option_vote_blinds,
+            all_vote_blind,
+            signature_secret: exec_signature_secret,
+            min_block_height: block_height,
+            dao_superseded_smt: &wallet.dao_superseded_smt,
+        };
+
+        let (exec_params, exec_proofs) = exec_builder.make(dao_exec_zkbin, dao_exec_pk)?;
+
+        self.dao_amend_tx(
+            holder,
+            dao,
+            new_dao,
+            proposal,
+            exec_params,
+            exec_proofs,
+            exec_signature_secret,
+            block_height,
+        )
+        .await
+    }
+
+    /// Build the amend transaction with the given exec call as its parent.
+    #[allow(clippy::too_many_arguments)]
+    async fn dao_amend_tx(
+        &mut self,
+        holder: &Holder,
+        dao: &Dao,
+        new_dao: &Dao,
+        proposal: &DaoProposal,
+        exec_params: DaoExecParams,
+        exec_proofs: Vec<Proof>,
+        exec_signature_secret: SecretKey,
+        block_height: u64,
+    ) -> Result<(Transaction, DaoAmendParams, DaoExecParams, Option<MoneyFeeParamsV1>)> {
+        let (dao_amend_pk, dao_amend_zkbin) =
+            self.proving_keys.get(DAO_CONTRACT_ZKAS_DAO_AMEND_NS).unwrap();
+
+        let mut data = vec![DaoFunction::Exec as u8];
+        exec_params.encode_async(&mut data).await?;
+        let exec_call = ContractCall { contract_id: *DAO_CONTRACT_ID, data };
+
+        let amend_builder =
+            DaoAmendCall { proposal: proposal.clone(), dao: dao.clone(), new_dao: new_dao.clone() };
+        let (amend_params, amend_proofs) = amend_builder.make(dao_amend_zkbin, dao_amend_pk)?;
+        let mut data = vec![DaoFunction::Amend as u8];
+        amend_params.encode_async(&mut data).await?;
+        let amend_call = ContractCall { contract_id: *DAO_CONTRACT_ID, data };
+
+        // We need to construct this tree, where exec is the parent:
+        //
+        //   exec ->
+        //       amend
+        //
+
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: exec_call, proofs: exec_proofs },
+            vec![DarkTree::new(
+                ContractCallLeaf { call: amend_call, proofs: amend_proofs },
+                vec![],
+                None,
+                None,
+            )],
+        )?;
+
+        // If fees are enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let amend_sigs = vec![];
+            let exec_sigs = tx.create_sigs(&[exec_signature_secret])?;
+            tx.signatures = vec![amend_sigs, exec_sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let amend_sigs = vec![];
+        let exec_sigs = tx.create_sigs(&[exec_signature_secret])?;
+        tx.signatures = vec![amend_sigs, exec_sigs];
+
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, amend_params, exec_params, fee_params))
+    }
+
+    /// Execute the transaction made by `dao_amend()` or `dao_amend_multi()`
+    /// for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_dao_amend_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        params: &DaoAmendParams,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("dao::amend", tx, block_height, self.verify_fees).await?;
+
+        if !append {
+            return Ok(vec![])
+        }
+
+        // The new DAO succeeds the old one, and every DAO it superseded
+        let (old_dao_bulla, new_dao_bulla) = (params.old_dao_bulla, params.new_dao_bulla);
+        let mut leaves = vec![(old_dao_bulla.inner(), new_dao_bulla.inner())];
+        for (dao_bulla, successor) in wallet.dao_successors.iter_mut() {
+            if *successor == old_dao_bulla {
+                *successor = new_dao_bulla;
+                leaves.push((dao_bulla.inner(), new_dao_bulla.inner()));
+            }
+        }
+        wallet.dao_successors.insert(old_dao_bulla, new_dao_bulla);
+        wallet.dao_superseded_smt.insert_batch(leaves).expect("smt.insert_batch()");
+
+        wallet.dao_merkle_tree.append(MerkleNode::from(params.new_dao_bulla.inner()));
+        let leaf_pos = wallet.dao_merkle_tree.mark().unwrap();
+        wallet.dao_leafs.insert(params.new_dao_bulla, leaf_pos);
+
+        if let Some(ref fee_params) = fee_params {
+            let nullifier = fee_params.input.nullifier.inner();
+            wallet
+                .money_null_smt
+                .insert_batch(vec![(nullifier, nullifier)])
+                .expect("smt.insert_batch()");
+
+            if let Some(spent_coin) = wallet
+                .unspent_money_coins
+                .iter()
+                .find(|x| x.nullifier() == fee_params.input.nullifier)
+                .cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != fee_params.input.nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+
+            wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+            let Ok(note) = fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret)
+            else {
+                return Ok(vec![])
+            };
+
+            let owncoin = OwnCoin {
+                coin: fee_params.output.coin,
+                note: note.clone(),
+                secret: wallet.keypair.secret,
+                leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+            };
+
+            debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+            wallet.unspent_money_coins.push(owncoin.clone());
+            return Ok(vec![owncoin])
+        }
+
+        Ok(vec![])
+    }
+}
@@ -0,0 +1,55 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::Result;
+use darkfi_dao_contract::{
+    model::{DaoEvent, DaoEventRecord},
+    DAO_CONTRACT_DB_EVENTS,
+};
+use darkfi_sdk::crypto::contract_id::DAO_CONTRACT_ID;
+use darkfi_serial::deserialize;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Fetch the `Dao` state-change events recorded at `block_height`
+    /// in the given holder's state, in the order they were recorded.
+    pub fn dao_events(&self, holder: &Holder, block_height: u64) -> Result<Vec<DaoEvent>> {
+        let wallet = self.holders.get(holder).unwrap();
+        let blockchain = &wallet.validator.blockchain;
+
+        let events_db = blockchain.contracts.lookup(
+            &blockchain.sled_db,
+            &DAO_CONTRACT_ID,
+            DAO_CONTRACT_DB_EVENTS,
+        )?;
+
+        // Events are keyed by their big-endian sequence number, so the
+        // tree iterates them in the order they were recorded.
+        let mut events = vec![];
+        for record in events_db.iter() {
+            let (_, data) = record?;
+            let record: DaoEventRecord = deserialize(&data)?;
+            if record.block_height as u64 == block_height {
+                events.push(record.event);
+            }
+        }
+
+        Ok(events)
+    }
+}
@@ -56,8 +56,10 @@ impl TestHarness {
         proposal: &DaoProposal,
         proposal_coinattrs: Vec<CoinAttributes>,
         yes_vote_value: u64,
+        abstain_vote_value: u64,
         all_vote_value: u64,
         yes_vote_blind: ScalarBlind,
+        abstain_vote_blind: ScalarBlind,
         all_vote_blind: ScalarBlind,
         block_height: u64,
     ) -> Result<(Transaction, MoneyTransferParamsV1, DaoExecParams, Option<MoneyFeeParamsV1>)> {
@@ -81,10 +83,20 @@ impl TestHarness {
         assert!(proposal_coinattrs.iter().all(|c| c.token_id == proposal_token_id));
         let proposal_amount = proposal_coinattrs.iter().map(|c| c.value).sum();
 
+        let spend_hook =
+            FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::Exec as u8 }
+                .to_func_id();
+
+        // Proposal deposits held by the DAO aren't part of the treasury.
+        // The treasury is `dao_bulla`, which is `dao` or a DAO it superseded.
         let dao_coins = dao_wallet
             .unspent_money_coins
             .iter()
-            .filter(|x| x.note.token_id == proposal_token_id)
+            .filter(|x| {
+                x.note.token_id == proposal_token_id &&
+                    x.note.spend_hook == spend_hook &&
+                    x.note.user_data == dao_bulla.inner()
+            })
             .cloned()
             .collect();
         let (spent_coins, change_value) = xfer::select_coins(dao_coins, proposal_amount)?;
@@ -96,6 +108,10 @@ impl TestHarness {
                 coin: coin.clone(),
                 merkle_path: tree.witness(coin.leaf_position, 0).unwrap(),
                 user_data_blind: input_user_data_blind,
+                frozen_root: dao_wallet.money_frozen_smt.root(),
+                frozen_path: dao_wallet
+                    .money_frozen_smt
+                    .prove_membership(&coin.note.token_id.inner()),
             });
         }
 
@@ -105,16 +121,12 @@ impl TestHarness {
             outputs.push(coin_attr);
         }
 
-        let spend_hook =
-            FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::Exec as u8 }
-                .to_func_id();
-
         let dao_coin_attrs = CoinAttributes {
             public_key: dao_wallet.keypair.public,
             value: change_value,
             token_id: proposal_token_id,
             spend_hook,
-            user_data: dao_bulla.inner(),
+            user_data: dao.to_bulla().inner(),
             blind: Blind::random(&mut OsRng),
         };
         outputs.push(dao_coin_attrs.clone());
@@ -123,6 +135,9 @@ impl TestHarness {
             clear_inputs: vec![],
             inputs,
             outputs,
+            payment_ids: vec![],
+            viewing_keys: vec![],
+            memos: vec![],
             mint_zkbin: mint_zkbin.clone(),
             mint_pk: mint_pk.clone(),
             burn_zkbin: burn_zkbin.clone(),
@@ -151,14 +166,18 @@ impl TestHarness {
             proposal: proposal.clone(),
             dao: dao.clone(),
             yes_vote_value,
+            abstain_vote_value,
             all_vote_value,
             yes_vote_blind,
+            abstain_vote_blind,
             all_vote_blind,
             input_value,
             input_value_blind,
             input_user_data_blind,
             hook_dao_exec: DAO_CONTRACT_ID.inner(),
             signature_secret: exec_signature_secret,
+            min_block_height: block_height,
+            dao_superseded_smt: &dao_wallet.dao_superseded_smt,
         };
 
         let (exec_params, exec_proofs) = exec_builder.make(dao_exec_zkbin, dao_exec_pk)?;
@@ -172,6 +191,8 @@ impl TestHarness {
             proposal_coinattrs,
             dao: dao.clone(),
             input_user_data_blind,
+            treasury_dao_bulla: *dao_bulla,
+            dao_superseded_smt: &dao_wallet.dao_superseded_smt,
             dao_coin_attrs,
         };
         let (auth_xfer_params, auth_xfer_proofs) = auth_xfer_builder.make(
@@ -21,20 +21,22 @@ use darkfi::{
     Result,
 };
 use darkfi_dao_contract::{
-    blockwindow,
-    client::{DaoProposeCall, DaoProposeStakeInput},
-    model::{Dao, DaoAuthCall, DaoBulla, DaoProposal, DaoProposeParams},
+    client::{
+        make_auth_money_transfer_call, make_proposal_deposit, proposer_secret, DaoProposeCall,
+        DaoProposeStakeInput,
+    },
+    model::{Dao, DaoAuthCall, DaoBulla, DaoProposal, DaoProposalMemo, DaoProposeParams},
     DaoFunction, DAO_CONTRACT_ZKAS_DAO_PROPOSE_INPUT_NS, DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS,
 };
 use darkfi_money_contract::{
-    client::{MoneyNote, OwnCoin},
-    model::{CoinAttributes, MoneyFeeParamsV1},
-    MoneyFunction,
+    client::{transfer_v1 as xfer, MoneyNote, OwnCoin},
+    model::{CoinAttributes, MoneyFeeParamsV1, MoneyTransferParamsV1},
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
 };
 use darkfi_sdk::{
     crypto::{
         contract_id::{DAO_CONTRACT_ID, MONEY_CONTRACT_ID},
-        Blind, MerkleNode, SecretKey,
+        Blind, FuncId, MerkleNode, PublicKey, SecretKey,
     },
     pasta::pallas,
     ContractCall,
@@ -46,16 +48,154 @@ use rand::rngs::OsRng;
 use super::{Holder, TestHarness};
 
 impl TestHarness {
-    /// Create a `Dao::Propose` transaction.
+    /// Create a `Dao::Propose` transaction sending the given coins from the DAO treasury.
+    ///
+    /// DAOs with a proposal deposit also get it bonded by a `Money::Transfer` in the
+    /// transaction, paid from the proposer's governance coins other than the staked one.
+    #[allow(clippy::too_many_arguments)]
     pub async fn dao_propose(
         &mut self,
         proposer: &Holder,
         proposal_coinattrs: &[CoinAttributes],
+        memo: Option<DaoProposalMemo>,
+        user_data: pallas::Base,
+        dao: &Dao,
+        dao_bulla: &DaoBulla,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        (DaoProposeParams, Option<MoneyTransferParamsV1>, Option<MoneyFeeParamsV1>),
+        DaoProposal,
+    )> {
+        // Create Auth calls
+        let auth_calls = vec![
+            make_auth_money_transfer_call(proposal_coinattrs),
+            DaoAuthCall {
+                contract_id: *MONEY_CONTRACT_ID,
+                function_code: MoneyFunction::TransferV1 as u8,
+                auth_data: vec![],
+            },
+        ];
+
+        self.dao_propose_inner(
+            proposer,
+            auth_calls,
+            vec![],
+            None,
+            memo,
+            user_data,
+            dao,
+            dao_bulla,
+            block_height,
+        )
+        .await
+    }
+
+    /// Create a `Dao::Propose` transaction for the given auth calls.
+    pub async fn dao_propose_auth_calls(
+        &mut self,
+        proposer: &Holder,
+        auth_calls: Vec<DaoAuthCall>,
         user_data: pallas::Base,
         dao: &Dao,
         dao_bulla: &DaoBulla,
         block_height: u64,
-    ) -> Result<(Transaction, (DaoProposeParams, Option<MoneyFeeParamsV1>), DaoProposal)> {
+    ) -> Result<(
+        Transaction,
+        (DaoProposeParams, Option<MoneyTransferParamsV1>, Option<MoneyFeeParamsV1>),
+        DaoProposal,
+    )> {
+        self.dao_propose_inner(
+            proposer,
+            auth_calls,
+            vec![],
+            None,
+            None,
+            user_data,
+            dao,
+            dao_bulla,
+            block_height,
+        )
+        .await
+    }
+
+    /// Create a sealed `Dao::Propose` transaction for the given auth calls,
+    /// whose votes are encrypted to `tally_public_key` until voting ends.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dao_propose_sealed(
+        &mut self,
+        proposer: &Holder,
+        auth_calls: Vec<DaoAuthCall>,
+        tally_public_key: PublicKey,
+        user_data: pallas::Base,
+        dao: &Dao,
+        dao_bulla: &DaoBulla,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        (DaoProposeParams, Option<MoneyTransferParamsV1>, Option<MoneyFeeParamsV1>),
+        DaoProposal,
+    )> {
+        self.dao_propose_inner(
+            proposer,
+            auth_calls,
+            vec![],
+            Some(tally_public_key),
+            None,
+            user_data,
+            dao,
+            dao_bulla,
+            block_height,
+        )
+        .await
+    }
+
+    /// Create a multiple-choice `Dao::Propose` transaction, where each
+    /// option holds the auth calls executed if it wins.
+    pub async fn dao_propose_options(
+        &mut self,
+        proposer: &Holder,
+        options: Vec<Vec<DaoAuthCall>>,
+        user_data: pallas::Base,
+        dao: &Dao,
+        dao_bulla: &DaoBulla,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        (DaoProposeParams, Option<MoneyTransferParamsV1>, Option<MoneyFeeParamsV1>),
+        DaoProposal,
+    )> {
+        self.dao_propose_inner(
+            proposer,
+            vec![],
+            options,
+            None,
+            None,
+            user_data,
+            dao,
+            dao_bulla,
+            block_height,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn dao_propose_inner(
+        &mut self,
+        proposer: &Holder,
+        auth_calls: Vec<DaoAuthCall>,
+        options: Vec<Vec<DaoAuthCall>>,
+        tally_public_key: Option<PublicKey>,
+        memo: Option<DaoProposalMemo>,
+        user_data: pallas::Base,
+        dao: &Dao,
+        dao_bulla: &DaoBulla,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        (DaoProposeParams, Option<MoneyTransferParamsV1>, Option<MoneyFeeParamsV1>),
+        DaoProposal,
+    )> {
         let wallet = self.holders.get(proposer).unwrap();
 
         let (dao_propose_burn_pk, dao_propose_burn_zkbin) =
@@ -72,6 +212,7 @@ impl TestHarness {
             .clone();
 
         let signature_secret = SecretKey::random(&mut OsRng);
+        let deposit_coin_blind = Blind::random(&mut OsRng);
 
         // Useful code snippet to dump a sled contract DB
         /*{
@@ -99,33 +240,11 @@ impl TestHarness {
             signature_secret,
         };
 
-        // Convert coin_params to actual coins
-        let mut proposal_coins = vec![];
-        for coin_params in proposal_coinattrs {
-            proposal_coins.push(coin_params.to_coin());
-        }
-        let mut proposal_data = vec![];
-        proposal_coins.encode_async(&mut proposal_data).await?;
-
-        // Create Auth calls
-        let auth_calls = vec![
-            DaoAuthCall {
-                contract_id: *DAO_CONTRACT_ID,
-                function_code: DaoFunction::AuthMoneyTransfer as u8,
-                auth_data: proposal_data,
-            },
-            DaoAuthCall {
-                contract_id: *MONEY_CONTRACT_ID,
-                function_code: MoneyFunction::TransferV1 as u8,
-                auth_data: vec![],
-            },
-        ];
-
-        let creation_day = blockwindow(block_height as u32);
         let proposal = DaoProposal {
             auth_calls,
-            creation_day,
-            duration_days: 30,
+            options,
+            creation_height: block_height,
+            duration_blocks: 4800,
             user_data,
             dao_bulla: dao.to_bulla(),
             blind: Blind::random(&mut OsRng),
@@ -141,6 +260,15 @@ impl TestHarness {
                 .witness(*wallet.dao_leafs.get(dao_bulla).unwrap(), 0)
                 .unwrap(),
             dao_merkle_root: wallet.dao_merkle_tree.root(0).unwrap(),
+            dao_superseded_smt: &wallet.dao_superseded_smt,
+            tally_public_key,
+            proposer_public_key: PublicKey::from_secret(proposer_secret(
+                &wallet.keypair.secret,
+                &proposal,
+            )),
+            memo,
+            deposit_refund_public_key: wallet.keypair.public,
+            deposit_coin_blind,
         };
 
         let (params, proofs) = call.make(
@@ -156,6 +284,81 @@ impl TestHarness {
         let call = ContractCall { contract_id: *DAO_CONTRACT_ID, data };
         let mut tx_builder = TransactionBuilder::new(ContractCallLeaf { call, proofs }, vec![])?;
 
+        // Bond the proposal deposit
+        let mut deposit_params = None;
+        let mut deposit_signature_secrets = vec![];
+        let mut deposit_spent_coins = vec![];
+        if dao.proposal_deposit > 0 {
+            let (mint_pk, mint_zkbin) =
+                self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+            let (burn_pk, burn_zkbin) =
+                self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+            let gov_coins = wallet
+                .unspent_money_coins
+                .iter()
+                .filter(|x| x.note.token_id == dao.gov_token_id && x.coin != propose_owncoin.coin)
+                .cloned()
+                .collect();
+            let (spent_coins, change_value) = xfer::select_coins(gov_coins, dao.proposal_deposit)?;
+
+            let mut inputs = vec![];
+            for coin in &spent_coins {
+                inputs.push(xfer::TransferCallInput {
+                    coin: coin.clone(),
+                    merkle_path: wallet.money_merkle_tree.witness(coin.leaf_position, 0).unwrap(),
+                    user_data_blind: Blind::random(&mut OsRng),
+                    frozen_root: wallet.money_frozen_smt.root(),
+                    frozen_path: wallet
+                        .money_frozen_smt
+                        .prove_membership(&coin.note.token_id.inner()),
+                });
+            }
+
+            let mut outputs = vec![make_proposal_deposit(
+                dao,
+                &params.proposal_bulla,
+                &wallet.keypair.public,
+                deposit_coin_blind,
+            )];
+            if change_value > 0 {
+                outputs.push(CoinAttributes {
+                    public_key: wallet.keypair.public,
+                    value: change_value,
+                    token_id: dao.gov_token_id,
+                    spend_hook: FuncId::none(),
+                    user_data: pallas::Base::ZERO,
+                    blind: Blind::random(&mut OsRng),
+                });
+            }
+
+            let xfer_builder = xfer::TransferCallBuilder {
+                clear_inputs: vec![],
+                inputs,
+                outputs,
+                payment_ids: vec![],
+                viewing_keys: vec![],
+                memos: vec![],
+                mint_zkbin: mint_zkbin.clone(),
+                mint_pk: mint_pk.clone(),
+                burn_zkbin: burn_zkbin.clone(),
+                burn_pk: burn_pk.clone(),
+            };
+
+            let (xfer_params, xfer_secrets) = xfer_builder.build()?;
+            let mut data = vec![MoneyFunction::TransferV1 as u8];
+            xfer_params.encode_async(&mut data).await?;
+            let xfer_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+            tx_builder.append(
+                ContractCallLeaf { call: xfer_call, proofs: xfer_secrets.proofs },
+                vec![],
+            )?;
+            deposit_signature_secrets = xfer_secrets.signature_secrets;
+            deposit_params = Some(xfer_params);
+            deposit_spent_coins = spent_coins;
+        }
+
         // If fees are enabled, make an offering
         let mut fee_params = None;
         let mut fee_signature_secrets = None;
@@ -163,9 +366,13 @@ impl TestHarness {
             let mut tx = tx_builder.build()?;
             let sigs = tx.create_sigs(&[signature_secret])?;
             tx.signatures = vec![sigs];
+            if deposit_params.is_some() {
+                let sigs = tx.create_sigs(&deposit_signature_secrets)?;
+                tx.signatures.push(sigs);
+            }
 
             let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
-                self.append_fee_call(proposer, tx, block_height, &[]).await?;
+                self.append_fee_call(proposer, tx, block_height, &deposit_spent_coins).await?;
 
             // Append the fee call to the transaction
             tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
@@ -177,22 +384,28 @@ impl TestHarness {
         let mut tx = tx_builder.build()?;
         let sigs = tx.create_sigs(&[signature_secret])?;
         tx.signatures = vec![sigs];
+        if deposit_params.is_some() {
+            let sigs = tx.create_sigs(&deposit_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
         if let Some(fee_signature_secrets) = fee_signature_secrets {
             let sigs = tx.create_sigs(&fee_signature_secrets)?;
             tx.signatures.push(sigs);
         }
 
-        Ok((tx, (params, fee_params), proposal))
+        Ok((tx, (params, deposit_params, fee_params), proposal))
     }
 
     /// Execute the transaction created by `dao_propose()` for a given [`Holder`].
     ///
     /// Returns any found [`OwnCoin`]s.
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute_dao_propose_tx(
         &mut self,
         holder: &Holder,
         tx: Transaction,
         params: &DaoProposeParams,
+        deposit_params: &Option<MoneyTransferParamsV1>,
         fee_params: &Option<MoneyFeeParamsV1>,
         block_height: u64,
         append: bool,
@@ -213,43 +426,55 @@ impl TestHarness {
         let prop_money_snapshot = wallet.money_merkle_tree.clone();
         wallet.dao_prop_leafs.insert(params.proposal_bulla, (prop_leaf_pos, prop_money_snapshot));
 
+        let mut inputs = vec![];
+        let mut outputs = vec![];
+
+        if let Some(ref deposit_params) = deposit_params {
+            inputs.extend_from_slice(&deposit_params.inputs);
+            outputs.extend_from_slice(&deposit_params.outputs);
+        }
+
         if let Some(ref fee_params) = fee_params {
-            let nullifier = fee_params.input.nullifier.inner();
-            wallet
-                .money_null_smt
-                .insert_batch(vec![(nullifier, nullifier)])
-                .expect("smt.insert_batch()");
+            inputs.push(fee_params.input.clone());
+            outputs.push(fee_params.output.clone());
+        }
 
+        let nullifiers = inputs.iter().map(|i| i.nullifier.inner()).map(|l| (l, l)).collect();
+        wallet.money_null_smt.insert_batch(nullifiers).expect("smt.insert_batch()");
+
+        for input in inputs {
             if let Some(spent_coin) = wallet
                 .unspent_money_coins
                 .iter()
-                .find(|x| x.nullifier() == fee_params.input.nullifier)
+                .find(|x| x.nullifier() == input.nullifier)
                 .cloned()
             {
                 debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
-                wallet.unspent_money_coins.retain(|x| x.nullifier() != fee_params.input.nullifier);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != input.nullifier);
                 wallet.spent_money_coins.push(spent_coin.clone());
             }
+        }
 
-            wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+        let mut found_owncoins = vec![];
+        for output in outputs {
+            wallet.money_merkle_tree.append(MerkleNode::from(output.coin.inner()));
 
-            let Ok(note) = fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret)
-            else {
-                return Ok(vec![])
+            let Ok(note) = output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) else {
+                continue
             };
 
             let owncoin = OwnCoin {
-                coin: fee_params.output.coin,
+                coin: output.coin,
                 note: note.clone(),
                 secret: wallet.keypair.secret,
                 leaf_position: wallet.money_merkle_tree.mark().unwrap(),
             };
 
-            debug!("Found new OwnCoin({}) for {:?}:", owncoin.coin, holder);
+            debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
             wallet.unspent_money_coins.push(owncoin.clone());
-            return Ok(vec![owncoin])
+            found_owncoins.push(owncoin);
         }
 
-        Ok(vec![])
+        Ok(found_owncoins)
     }
 }
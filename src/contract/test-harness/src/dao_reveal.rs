@@ -0,0 +1,164 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_dao_contract::{
+    client::DaoRevealTallyCall,
+    model::{DaoProposal, DaoRevealTallyParams},
+    DaoFunction, DAO_CONTRACT_ZKAS_DAO_REVEAL_TALLY_NS,
+};
+use darkfi_money_contract::{
+    client::{MoneyNote, OwnCoin},
+    model::MoneyFeeParamsV1,
+};
+use darkfi_sdk::{
+    crypto::{contract_id::DAO_CONTRACT_ID, MerkleNode, ScalarBlind},
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use log::debug;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Create a `Dao::RevealTally` transaction for a sealed proposal.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dao_reveal_tally(
+        &mut self,
+        holder: &Holder,
+        proposal: &DaoProposal,
+        yes_vote_value: u64,
+        abstain_vote_value: u64,
+        all_vote_value: u64,
+        yes_vote_blind: ScalarBlind,
+        abstain_vote_blind: ScalarBlind,
+        all_vote_blind: ScalarBlind,
+        block_height: u64,
+    ) -> Result<(Transaction, DaoRevealTallyParams, Option<MoneyFeeParamsV1>)> {
+        let (dao_reveal_pk, dao_reveal_zkbin) =
+            self.proving_keys.get(DAO_CONTRACT_ZKAS_DAO_REVEAL_TALLY_NS).unwrap();
+
+        let call = DaoRevealTallyCall {
+            proposal: proposal.clone(),
+            yes_vote_value,
+            abstain_vote_value,
+            all_vote_value,
+            yes_vote_blind,
+            abstain_vote_blind,
+            all_vote_blind,
+            min_block_height: block_height,
+        };
+
+        let (params, proofs) = call.make(dao_reveal_zkbin, dao_reveal_pk)?;
+
+        // Encode the call
+        let mut data = vec![DaoFunction::RevealTally as u8];
+        params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *DAO_CONTRACT_ID, data };
+        let mut tx_builder = TransactionBuilder::new(ContractCallLeaf { call, proofs }, vec![])?;
+
+        // If fees are enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            tx.signatures = vec![vec![]];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction. The reveal itself is unsigned.
+        let mut tx = tx_builder.build()?;
+        tx.signatures = vec![vec![]];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, params, fee_params))
+    }
+
+    /// Execute the transaction made by `dao_reveal_tally()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_dao_reveal_tally_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("dao::reveal_tally", tx, block_height, self.verify_fees).await?;
+
+        if !append {
+            return Ok(vec![])
+        }
+
+        if let Some(ref fee_params) = fee_params {
+            let nullifier = fee_params.input.nullifier.inner();
+            wallet
+                .money_null_smt
+                .insert_batch(vec![(nullifier, nullifier)])
+                .expect("smt.insert_batch()");
+
+            if let Some(spent_coin) = wallet
+                .unspent_money_coins
+                .iter()
+                .find(|x| x.nullifier() == fee_params.input.nullifier)
+                .cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != fee_params.input.nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+
+            wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+            let Ok(note) = fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret)
+            else {
+                return Ok(vec![])
+            };
+
+            let owncoin = OwnCoin {
+                coin: fee_params.output.coin,
+                note: note.clone(),
+                secret: wallet.keypair.secret,
+                leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+            };
+
+            debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+            wallet.unspent_money_coins.push(owncoin.clone());
+            return Ok(vec![owncoin])
+        }
+
+        Ok(vec![])
+    }
+}
@@ -0,0 +1,254 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_dao_contract::{
+    client::DaoSettleDepositCall,
+    model::{Dao, DaoProposal, DaoSettleDepositParams},
+    DaoFunction, DAO_CONTRACT_ZKAS_DAO_SETTLE_DEPOSIT_NS,
+};
+use darkfi_money_contract::{
+    client::{transfer_v1 as xfer, MoneyNote, OwnCoin},
+    model::{MoneyFeeParamsV1, MoneyTransferParamsV1},
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{
+        contract_id::{DAO_CONTRACT_ID, MONEY_CONTRACT_ID},
+        poseidon_hash, Blind, FuncRef, MerkleNode, PublicKey, ScalarBlind,
+    },
+    dark_tree::DarkTree,
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use log::debug;
+use rand::rngs::OsRng;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Create a `Dao::SettleDeposit` transaction, spending the deposit bonded for
+    /// the proposal into a refund to `refund_public_key`, or into the DAO treasury
+    /// if the votes slash it.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dao_settle_deposit(
+        &mut self,
+        holder: &Holder,
+        dao: &Dao,
+        proposal: &DaoProposal,
+        refund_public_key: PublicKey,
+        yes_vote_value: u64,
+        abstain_vote_value: u64,
+        all_vote_value: u64,
+        yes_vote_blind: ScalarBlind,
+        abstain_vote_blind: ScalarBlind,
+        all_vote_blind: ScalarBlind,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        MoneyTransferParamsV1,
+        DaoSettleDepositParams,
+        Option<MoneyFeeParamsV1>,
+    )> {
+        let dao_wallet = self.holders.get(&Holder::Dao).unwrap();
+
+        let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+        let (dao_settle_pk, dao_settle_zkbin) =
+            self.proving_keys.get(DAO_CONTRACT_ZKAS_DAO_SETTLE_DEPOSIT_NS).unwrap();
+
+        // Find the deposit coin bonded for this proposal
+        let spend_hook =
+            FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: DaoFunction::SettleDeposit as u8 }
+                .to_func_id();
+        let (refund_x, refund_y) = refund_public_key.xy();
+        let user_data = poseidon_hash([proposal.to_bulla().inner(), refund_x, refund_y]);
+        let deposit_coin = dao_wallet
+            .unspent_money_coins
+            .iter()
+            .find(|x| x.note.spend_hook == spend_hook && x.note.user_data == user_data)
+            .unwrap()
+            .clone();
+
+        let settle_builder = DaoSettleDepositCall {
+            proposal: proposal.clone(),
+            dao: dao.clone(),
+            yes_vote_value,
+            abstain_vote_value,
+            all_vote_value,
+            yes_vote_blind,
+            abstain_vote_blind,
+            all_vote_blind,
+            refund_public_key,
+            input_user_data_blind: Blind::random(&mut OsRng),
+            output_coin_blind: Blind::random(&mut OsRng),
+            min_block_height: block_height,
+        };
+
+        let xfer_builder = xfer::TransferCallBuilder {
+            clear_inputs: vec![],
+            inputs: vec![xfer::TransferCallInput {
+                coin: deposit_coin.clone(),
+                merkle_path: dao_wallet
+                    .money_merkle_tree
+                    .witness(deposit_coin.leaf_position, 0)
+                    .unwrap(),
+                user_data_blind: settle_builder.input_user_data_blind,
+                frozen_root: dao_wallet.money_frozen_smt.root(),
+                frozen_path: dao_wallet
+                    .money_frozen_smt
+                    .prove_membership(&deposit_coin.note.token_id.inner()),
+            }],
+            outputs: vec![settle_builder.settled_coin()],
+            payment_ids: vec![],
+            viewing_keys: vec![],
+            memos: vec![],
+            mint_zkbin: mint_zkbin.clone(),
+            mint_pk: mint_pk.clone(),
+            burn_zkbin: burn_zkbin.clone(),
+            burn_pk: burn_pk.clone(),
+        };
+
+        let (xfer_params, xfer_secrets) = xfer_builder.build()?;
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        xfer_params.encode_async(&mut data).await?;
+        let xfer_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let (settle_params, settle_proofs) =
+            settle_builder.make(dao_settle_zkbin, dao_settle_pk)?;
+        let mut data = vec![DaoFunction::SettleDeposit as u8];
+        settle_params.encode_async(&mut data).await?;
+        let settle_call = ContractCall { contract_id: *DAO_CONTRACT_ID, data };
+
+        // We need to construct this tree, where settle is the parent:
+        //
+        //   settle ->
+        //       xfer
+        //
+
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: settle_call, proofs: settle_proofs },
+            vec![DarkTree::new(
+                ContractCallLeaf { call: xfer_call, proofs: xfer_secrets.proofs },
+                vec![],
+                None,
+                None,
+            )],
+        )?;
+
+        // If fees are enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+            tx.signatures = vec![xfer_sigs, vec![]];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+        tx.signatures = vec![xfer_sigs, vec![]];
+
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, xfer_params, settle_params, fee_params))
+    }
+
+    /// Execute the transaction made by `dao_settle_deposit()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_dao_settle_deposit_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        xfer_params: &MoneyTransferParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("dao::settle_deposit", tx, block_height, self.verify_fees).await?;
+
+        if !append {
+            return Ok(vec![])
+        }
+
+        let mut inputs = xfer_params.inputs.to_vec();
+        let mut outputs = xfer_params.outputs.to_vec();
+
+        if let Some(ref fee_params) = fee_params {
+            inputs.push(fee_params.input.clone());
+            outputs.push(fee_params.output.clone());
+        }
+
+        let nullifiers = inputs.iter().map(|i| i.nullifier.inner()).map(|l| (l, l)).collect();
+        wallet.money_null_smt.insert_batch(nullifiers).expect("smt.insert_batch()");
+
+        for input in inputs {
+            if let Some(spent_coin) = wallet
+                .unspent_money_coins
+                .iter()
+                .find(|x| x.nullifier() == input.nullifier)
+                .cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != input.nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+        }
+
+        let mut found_owncoins = vec![];
+        for output in outputs {
+            wallet.money_merkle_tree.append(MerkleNode::from(output.coin.inner()));
+
+            let Ok(note) = output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) else {
+                continue
+            };
+
+            let owncoin = OwnCoin {
+                coin: output.coin,
+                note: note.clone(),
+                secret: wallet.keypair.secret,
+                leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+            };
+
+            debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+            wallet.unspent_money_coins.push(owncoin.clone());
+            found_owncoins.push(owncoin);
+        }
+
+        Ok(found_owncoins)
+    }
+}
@@ -18,20 +18,21 @@
 
 use darkfi::{
     tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    zk::Proof,
     Result,
 };
 use darkfi_dao_contract::{
-    blockwindow,
-    client::{DaoVoteCall, DaoVoteInput},
-    model::{Dao, DaoProposal, DaoProposalBulla, DaoVoteParams},
+    client::{DaoVoteCall, DaoVoteInput, DaoVoteMultiCall},
+    model::{Dao, DaoProposal, DaoProposalBulla, DaoVoteMultiParams, DaoVoteParams},
     DaoFunction, DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS, DAO_CONTRACT_ZKAS_DAO_VOTE_MAIN_NS,
+    DAO_CONTRACT_ZKAS_DAO_VOTE_MULTI_NS,
 };
 use darkfi_money_contract::{
     client::{MoneyNote, OwnCoin},
     model::MoneyFeeParamsV1,
 };
 use darkfi_sdk::{
-    crypto::{contract_id::DAO_CONTRACT_ID, Keypair, MerkleNode, SecretKey},
+    crypto::{contract_id::DAO_CONTRACT_ID, Keypair, MerkleNode, PublicKey, SecretKey},
     ContractCall,
 };
 use darkfi_serial::AsyncEncodable;
@@ -47,11 +48,67 @@ impl TestHarness {
         &mut self,
         voter: &Holder,
         vote_option: bool,
+        abstain: bool,
         dao: &Dao,
         dao_keypair: &Keypair,
         proposal: &DaoProposal,
         proposal_bulla: &DaoProposalBulla,
         block_height: u64,
+    ) -> Result<(Transaction, DaoVoteParams, Option<MoneyFeeParamsV1>)> {
+        self.dao_vote_inner(
+            voter,
+            vote_option,
+            abstain,
+            dao,
+            dao_keypair,
+            None,
+            proposal,
+            proposal_bulla,
+            block_height,
+        )
+        .await
+    }
+
+    /// Create a `Dao::Vote` transaction on a sealed proposal, encrypting
+    /// the vote to its tally key.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dao_vote_sealed(
+        &mut self,
+        voter: &Holder,
+        vote_option: bool,
+        dao: &Dao,
+        dao_keypair: &Keypair,
+        tally_public_key: PublicKey,
+        proposal: &DaoProposal,
+        proposal_bulla: &DaoProposalBulla,
+        block_height: u64,
+    ) -> Result<(Transaction, DaoVoteParams, Option<MoneyFeeParamsV1>)> {
+        self.dao_vote_inner(
+            voter,
+            vote_option,
+            false,
+            dao,
+            dao_keypair,
+            Some(tally_public_key),
+            proposal,
+            proposal_bulla,
+            block_height,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn dao_vote_inner(
+        &mut self,
+        voter: &Holder,
+        vote_option: bool,
+        abstain: bool,
+        dao: &Dao,
+        dao_keypair: &Keypair,
+        tally_public_key: Option<PublicKey>,
+        proposal: &DaoProposal,
+        proposal_bulla: &DaoProposalBulla,
+        block_height: u64,
     ) -> Result<(Transaction, DaoVoteParams, Option<MoneyFeeParamsV1>)> {
         let wallet = self.holders.get(voter).unwrap();
 
@@ -81,15 +138,16 @@ impl TestHarness {
             signature_secret,
         };
 
-        let current_day = blockwindow(block_height as u32);
         let call = DaoVoteCall {
             money_null_smt: wallet.money_null_smt_snapshot.as_ref().unwrap(),
             inputs: vec![input],
             vote_option,
+            abstain,
             proposal: proposal.clone(),
             dao: dao.clone(),
             dao_keypair: *dao_keypair,
-            current_day,
+            max_block_height: block_height,
+            tally_public_key,
         };
 
         let (params, proofs) = call.make(
@@ -102,6 +160,88 @@ impl TestHarness {
         // Encode the call
         let mut data = vec![DaoFunction::Vote as u8];
         params.encode_async(&mut data).await?;
+        let (tx, fee_params) =
+            self.dao_vote_tx(voter, data, proofs, signature_secret, block_height).await?;
+
+        Ok((tx, params, fee_params))
+    }
+
+    /// Create a `Dao::VoteMulti` transaction, voting for the given option
+    /// of a multiple-choice proposal, or abstaining if it is `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn dao_vote_multi(
+        &mut self,
+        voter: &Holder,
+        option: Option<usize>,
+        dao: &Dao,
+        dao_keypair: &Keypair,
+        proposal: &DaoProposal,
+        proposal_bulla: &DaoProposalBulla,
+        block_height: u64,
+    ) -> Result<(Transaction, DaoVoteMultiParams, Option<MoneyFeeParamsV1>)> {
+        let wallet = self.holders.get(voter).unwrap();
+
+        let (dao_vote_burn_pk, dao_vote_burn_zkbin) =
+            self.proving_keys.get(DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS).unwrap();
+
+        let (dao_vote_multi_pk, dao_vote_multi_zkbin) =
+            self.proving_keys.get(DAO_CONTRACT_ZKAS_DAO_VOTE_MULTI_NS).unwrap();
+
+        let (_proposal_leaf_pos, snapshot_money_merkle_tree) =
+            wallet.dao_prop_leafs.get(proposal_bulla).unwrap();
+
+        let vote_owncoin: OwnCoin = wallet
+            .unspent_money_coins
+            .iter()
+            .find(|x| x.note.token_id == dao.gov_token_id)
+            .unwrap()
+            .clone();
+
+        let signature_secret = SecretKey::random(&mut OsRng);
+
+        let input = DaoVoteInput {
+            secret: wallet.keypair.secret,
+            note: vote_owncoin.note.clone(),
+            leaf_position: vote_owncoin.leaf_position,
+            merkle_path: snapshot_money_merkle_tree.witness(vote_owncoin.leaf_position, 0).unwrap(),
+            signature_secret,
+        };
+
+        let call = DaoVoteMultiCall {
+            money_null_smt: wallet.money_null_smt_snapshot.as_ref().unwrap(),
+            inputs: vec![input],
+            option,
+            proposal: proposal.clone(),
+            dao: dao.clone(),
+            dao_keypair: *dao_keypair,
+            max_block_height: block_height,
+        };
+
+        let (params, proofs) = call.make(
+            dao_vote_burn_zkbin,
+            dao_vote_burn_pk,
+            dao_vote_multi_zkbin,
+            dao_vote_multi_pk,
+        )?;
+
+        // Encode the call
+        let mut data = vec![DaoFunction::VoteMulti as u8];
+        params.encode_async(&mut data).await?;
+        let (tx, fee_params) =
+            self.dao_vote_tx(voter, data, proofs, signature_secret, block_height).await?;
+
+        Ok((tx, params, fee_params))
+    }
+
+    /// Build a vote transaction from the encoded call, adding a fee call if enabled.
+    async fn dao_vote_tx(
+        &mut self,
+        voter: &Holder,
+        data: Vec<u8>,
+        proofs: Vec<Proof>,
+        signature_secret: SecretKey,
+        block_height: u64,
+    ) -> Result<(Transaction, Option<MoneyFeeParamsV1>)> {
         let call = ContractCall { contract_id: *DAO_CONTRACT_ID, data };
         let mut tx_builder = TransactionBuilder::new(ContractCallLeaf { call, proofs }, vec![])?;
 
@@ -131,17 +271,17 @@ impl TestHarness {
             tx.signatures.push(sigs);
         }
 
-        Ok((tx, params, fee_params))
+        Ok((tx, fee_params))
     }
 
-    /// Execute the transaction made by `dao_vote()` for a given [`Holder`].
+    /// Execute the transaction made by `dao_vote()` or `dao_vote_multi()`
+    /// for a given [`Holder`].
     ///
     /// Returns any found [`OwnCoin`]s.
     pub async fn execute_dao_vote_tx(
         &mut self,
         holder: &Holder,
         tx: Transaction,
-        _params: &DaoVoteParams,
         fee_params: &Option<MoneyFeeParamsV1>,
         block_height: u64,
         append: bool,
@@ -19,6 +19,7 @@
 use std::{
     collections::HashMap,
     io::{Cursor, Write},
+    time::Instant,
 };
 
 use darkfi::{
@@ -32,12 +33,15 @@ use darkfi::{
     Result,
 };
 use darkfi_dao_contract::model::{DaoBulla, DaoProposalBulla};
-use darkfi_money_contract::client::OwnCoin;
+use darkfi_money_contract::{
+    client::OwnCoin,
+    model::{Coin, Nullifier, TokenId},
+};
 use darkfi_sdk::{
     bridgetree,
     crypto::{
         smt::{MemoryStorageFp, PoseidonFp, SmtMemoryFp, EMPTY_NODES_FP},
-        Keypair, MerkleNode, MerkleTree,
+        Keypair, MerkleNode, MerkleTree, ScalarBlind, SecretKey,
     },
     pasta::pallas,
 };
@@ -48,12 +52,19 @@ use num_bigint::BigUint;
 /// Utility module for caching ZK proof PKs and VKs
 pub mod vks;
 
+/// Gas, proof size and verification time tracking
+pub mod stats;
+use stats::{CallLimits, TxStats, TxStatsRecorder, TxStatsRecorderPtr};
+
 /// `Money::PoWReward` functionality
 mod money_pow_reward;
 
 /// `Money::Fee` functionality
 mod money_fee;
 
+/// `Money::FeeTokenRate` functionality
+mod money_fee_token_rate;
+
 /// `Money::GenesisMint` functionality
 mod money_genesis_mint;
 
@@ -66,9 +77,33 @@ mod money_token;
 /// `Money::OtcSwap` functionality
 mod money_otc_swap;
 
+/// CoinJoin `Money::Transfer` functionality
+mod money_coinjoin;
+
+/// `Money::TransferFreeze` and `Money::TransferUnfreeze` functionality
+mod money_transfer_freeze;
+
+/// `Money::VestingClaim` functionality
+mod money_vesting;
+
+/// `Money::HtlcSpend` functionality
+mod money_htlc;
+
+/// `Money::GroupSpend` functionality
+mod money_group;
+
+/// `Money::NftMint` and `Money::NftBurn` functionality
+mod money_nft;
+
+/// `Money::BridgeBurn`, `Money::BridgeAuthoritySet` and `Money::BridgeMint` functionality
+mod money_bridge;
+
 /// `Deployooor::Deploy` functionality
 mod contract_deploy;
 
+/// `Registry::Deploy` and `Registry::Update` functionality
+mod registry;
+
 /// `Dao::Mint` functionality
 mod dao_mint;
 
@@ -81,6 +116,24 @@ mod dao_vote;
 /// `Dao::Exec` functionality
 mod dao_exec;
 
+/// `Dao::Amend` functionality
+mod dao_amend;
+
+/// `Dao::RevealTally` functionality
+mod dao_reveal;
+
+/// `Dao::Cancel` functionality
+mod dao_cancel;
+
+/// `Dao::Veto` functionality
+mod dao_veto;
+
+/// `Dao::SettleDeposit` functionality
+mod dao_settle_deposit;
+
+/// `Dao` state-change events
+mod dao_events;
+
 /// Initialize the logging mechanism
 pub fn init_logger() {
     let mut cfg = simplelog::ConfigBuilder::new();
@@ -129,20 +182,38 @@ pub struct Wallet {
     pub money_null_smt: SmtMemoryFp,
     /// Holder's instance of the SMT tree for the `Money` contract (snapshotted for DAO::propose())
     pub money_null_smt_snapshot: Option<SmtMemoryFp>,
+    /// Holder's instance of the SMT of frozen tokens for the `Money` contract
+    pub money_frozen_smt: SmtMemoryFp,
+    /// Holder's instance of the Merkle tree of bridge burn receipts for the `Money` contract
+    pub bridge_burn_tree: MerkleTree,
     /// Holder's instance of the Merkle tree for the `DAO` contract (holding DAO bullas)
     pub dao_merkle_tree: MerkleTree,
     /// Holder's instance of the Merkle tree for the `DAO` contract (holding DAO proposals)
     pub dao_proposals_tree: MerkleTree,
+    /// Holder's instance of the SMT of amended DAOs for the `DAO` contract
+    pub dao_superseded_smt: SmtMemoryFp,
+    /// Latest successors of amended DAOs, the leaves of `dao_superseded_smt`
+    pub dao_successors: HashMap<DaoBulla, DaoBulla>,
     /// Holder's set of unspent [`OwnCoin`]s from the `Money` contract
     pub unspent_money_coins: Vec<OwnCoin>,
     /// Holder's set of spent [`OwnCoin`]s from the `Money` contract
     pub spent_money_coins: Vec<OwnCoin>,
+    /// Escrow secret keys of HTLC and group coins the holder is party to
+    pub escrow_secrets: Vec<SecretKey>,
+    /// Value and value blind of the holder's built token mints, by minted coin
+    pub pending_token_mints: Vec<(Coin, u64, ScalarBlind)>,
+    /// Value and value blind of the holder's executed token mints
+    pub token_mints: Vec<(TokenId, u64, ScalarBlind)>,
+    /// Witnessed leaf positions of bridge burn receipts in the `bridge_burn_tree`
+    pub bridge_burn_leafs: Vec<(Nullifier, bridgetree::Position)>,
     /// Witnessed leaf positions of DAO bullas in the `dao_merkle_tree`
     pub dao_leafs: HashMap<DaoBulla, bridgetree::Position>,
     /// Dao Proposal snapshots
     pub dao_prop_leafs: HashMap<DaoProposalBulla, (bridgetree::Position, MerkleTree)>,
     /// Create bench.csv file
     pub bench_wasm: bool,
+    /// Recorder of executed transactions costs, shared between holders
+    pub tx_stats: TxStatsRecorderPtr,
 }
 
 impl Wallet {
@@ -154,6 +225,7 @@ impl Wallet {
         genesis_block: BlockInfo,
         vks: &vks::Vks,
         verify_fees: bool,
+        tx_stats: TxStatsRecorderPtr,
     ) -> Result<Self> {
         // Create an in-memory sled db instance for this wallet
         let sled_db = sled::Config::new().temporary(true).open()?;
@@ -180,6 +252,10 @@ impl Wallet {
         let hasher = PoseidonFp::new();
         let store = MemoryStorageFp::new();
         let money_null_smt = SmtMemoryFp::new(store, hasher.clone(), &EMPTY_NODES_FP);
+        let money_frozen_smt =
+            SmtMemoryFp::new(MemoryStorageFp::new(), hasher.clone(), &EMPTY_NODES_FP);
+        let dao_superseded_smt =
+            SmtMemoryFp::new(MemoryStorageFp::new(), hasher.clone(), &EMPTY_NODES_FP);
 
         Ok(Self {
             keypair,
@@ -189,13 +265,22 @@ impl Wallet {
             money_merkle_tree,
             money_null_smt,
             money_null_smt_snapshot: None,
+            money_frozen_smt,
+            bridge_burn_tree: MerkleTree::new(100),
             dao_merkle_tree: MerkleTree::new(100),
             dao_proposals_tree: MerkleTree::new(100),
+            dao_superseded_smt,
+            dao_successors: HashMap::new(),
             unspent_money_coins: vec![],
             spent_money_coins: vec![],
+            escrow_secrets: vec![],
+            pending_token_mints: vec![],
+            token_mints: vec![],
+            bridge_burn_leafs: vec![],
             dao_leafs: HashMap::new(),
             dao_prop_leafs: HashMap::new(),
             bench_wasm: false,
+            tx_stats,
         })
     }
 
@@ -210,7 +295,11 @@ impl Wallet {
             benchmark_wasm_calls(callname, &self.validator, &tx, block_height);
         }
 
-        self.validator.add_transactions(&[tx.clone()], block_height, true, verify_fees).await?;
+        let now = Instant::now();
+        let gas_used =
+            self.validator.add_transactions(&[tx.clone()], block_height, true, verify_fees).await?;
+        let stats = TxStats::new(callname, &tx, gas_used, now.elapsed());
+        self.tx_stats.lock().unwrap().record(stats);
 
         // Write the data
         {
@@ -234,6 +323,8 @@ pub struct TestHarness {
     pub genesis_block: BlockInfo,
     /// Marker to know if we're supposed to include tx fees
     pub verify_fees: bool,
+    /// Recorder of executed transactions costs
+    pub tx_stats: TxStatsRecorderPtr,
 }
 
 impl TestHarness {
@@ -261,6 +352,7 @@ impl TestHarness {
         }
 
         // Create `Wallet` instances
+        let tx_stats = TxStatsRecorder::new();
         let mut holders_map = HashMap::new();
         for holder in holders {
             let keypair = Keypair::random(&mut rng);
@@ -274,13 +366,14 @@ impl TestHarness {
                 genesis_block.clone(),
                 &vks,
                 verify_fees,
+                tx_stats.clone(),
             )
             .await?;
 
             holders_map.insert(*holder, wallet);
         }
 
-        Ok(Self { holders: holders_map, proving_keys, genesis_block, verify_fees })
+        Ok(Self { holders: holders_map, proving_keys, genesis_block, verify_fees, tx_stats })
     }
 
     /// Assert that all holders' trees are the same
@@ -298,6 +391,19 @@ impl TestHarness {
             assert!(money_root == wallet.money_merkle_tree.root(0).unwrap());
         }
     }
+
+    /// Assert that all executed transactions of the given call name stay
+    /// within the given limits.
+    pub fn assert_limits(&self, callname: &str, limits: &CallLimits) {
+        let violations = self.tx_stats.lock().unwrap().check_limits(callname, limits);
+        assert!(violations.is_empty(), "Call limits exceeded:\n{}", violations.join("\n"));
+    }
+
+    /// Write the costs of all executed transactions as a CSV report to the
+    /// given path.
+    pub fn write_tx_stats(&self, path: &str) -> Result<()> {
+        self.tx_stats.lock().unwrap().write_csv(path)
+    }
 }
 
 fn benchmark_wasm_calls(
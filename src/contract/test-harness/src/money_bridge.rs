@@ -0,0 +1,472 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    zk::halo2::Field,
+    Result,
+};
+use darkfi_money_contract::{
+    client::{
+        bridge_authority_set_v1::BridgeAuthoritySetCallBuilder,
+        bridge_burn_v1::{BridgeBurnCallBuilder, BridgeBurnProof, BridgeDestination},
+        bridge_mint_v1::BridgeMintCallBuilder,
+        MoneyNote, OwnCoin,
+    },
+    model::{
+        BridgeAuthoritySet, MoneyBridgeAuthoritySetParamsV1, MoneyBridgeBurnParamsV1,
+        MoneyBridgeMintParamsV1, MoneyFeeParamsV1, TokenAttributes, TokenId,
+    },
+    MoneyFunction, MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE, MONEY_CONTRACT_ZKAS_BURN_NS_V1,
+    MONEY_CONTRACT_ZKAS_MINT_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{
+        contract_id::MONEY_CONTRACT_ID, poseidon_hash, BaseBlind, FuncId, FuncRef, Keypair,
+        MerkleNode,
+    },
+    pasta::pallas,
+    ContractCall,
+};
+use darkfi_serial::{serialize, AsyncEncodable};
+use log::debug;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Burn an [`OwnCoin`] towards an external chain using `Money::BridgeBurn`
+    pub async fn bridge_burn(
+        &mut self,
+        holder: &Holder,
+        owncoin: &OwnCoin,
+        destination: &BridgeDestination,
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyBridgeBurnParamsV1, Option<MoneyFeeParamsV1>)> {
+        let wallet = self.holders.get(holder).unwrap();
+
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        let builder = BridgeBurnCallBuilder {
+            coin: owncoin.clone(),
+            merkle_path: wallet.money_merkle_tree.witness(owncoin.leaf_position, 0).unwrap(),
+            frozen_root: wallet.money_frozen_smt.root(),
+            frozen_path: wallet.money_frozen_smt.prove_membership(&owncoin.note.token_id.inner()),
+            destination: destination.clone(),
+            burn_zkbin: burn_zkbin.clone(),
+            burn_pk: burn_pk.clone(),
+        };
+        let debris = builder.build()?;
+        let mut data = vec![MoneyFunction::BridgeBurnV1 as u8];
+        debris.params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let mut tx_builder =
+            TransactionBuilder::new(ContractCallLeaf { call, proofs: debris.proofs }, vec![])?;
+
+        // If we have tx fees enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let sigs = tx.create_sigs(&[debris.signature_secret])?;
+            tx.signatures = vec![sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[owncoin.clone()]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&[debris.signature_secret])?;
+        tx.signatures = vec![sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, debris.params, fee_params))
+    }
+
+    /// Execute the transaction created by `bridge_burn()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_bridge_burn_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        params: &MoneyBridgeBurnParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("money::bridge_burn", tx, block_height, self.verify_fees).await?;
+
+        let mut found_owncoins = vec![];
+        if !append {
+            return Ok(found_owncoins)
+        }
+
+        let mut nullifiers = vec![params.input.nullifier];
+        if let Some(ref fee_params) = fee_params {
+            nullifiers.push(fee_params.input.nullifier);
+        }
+
+        for nullifier in nullifiers {
+            let n = nullifier.inner();
+            wallet.money_null_smt.insert_batch(vec![(n, n)]).expect("smt.insert_batch()");
+
+            if let Some(spent_coin) =
+                wallet.unspent_money_coins.iter().find(|x| x.nullifier() == nullifier).cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+        }
+
+        // Keep track of the burn receipt so we're able to prove it
+        let receipt = params.receipt();
+        wallet.bridge_burn_tree.append(receipt.to_leaf());
+        let leaf_position = wallet.bridge_burn_tree.mark().unwrap();
+        wallet.bridge_burn_leafs.push((receipt.nullifier, leaf_position));
+
+        if let Some(ref fee_params) = fee_params {
+            wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+            // Attempt to decrypt the encrypted note in the fee output
+            if let Ok(note) = fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) {
+                let owncoin = OwnCoin {
+                    coin: fee_params.output.coin,
+                    note,
+                    secret: wallet.keypair.secret,
+                    leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+                };
+
+                debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+                wallet.unspent_money_coins.push(owncoin.clone());
+                found_owncoins.push(owncoin);
+            }
+        }
+
+        Ok(found_owncoins)
+    }
+
+    /// Create the inclusion proof of a bridge burn executed by the given [`Holder`]
+    pub fn bridge_burn_proof(
+        &self,
+        holder: &Holder,
+        params: &MoneyBridgeBurnParamsV1,
+    ) -> Option<BridgeBurnProof> {
+        let wallet = self.holders.get(holder).unwrap();
+        let receipt = params.receipt();
+        let (_, leaf_position) =
+            wallet.bridge_burn_leafs.iter().find(|(x, _)| x == &receipt.nullifier)?;
+        BridgeBurnProof::new(receipt, &wallet.bridge_burn_tree, *leaf_position)
+    }
+
+    /// Check if the given root is part of the on-chain set of bridge burn roots
+    pub fn bridge_burn_root_exists(&self, holder: &Holder, root: &MerkleNode) -> Result<bool> {
+        let wallet = self.holders.get(holder).unwrap();
+        let blockchain = &wallet.validator.blockchain;
+
+        let roots_db = blockchain.contracts.lookup(
+            &blockchain.sled_db,
+            &MONEY_CONTRACT_ID,
+            MONEY_CONTRACT_BRIDGE_BURN_ROOTS_TREE,
+        )?;
+
+        Ok(roots_db.contains_key(serialize(root))?)
+    }
+
+    /// Set the bridge authorities of a token using `Money::BridgeAuthoritySet`
+    pub async fn bridge_authority_set(
+        &mut self,
+        holder: &Holder,
+        token_blind: BaseBlind,
+        authority_set: BridgeAuthoritySet,
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyBridgeAuthoritySetParamsV1, Option<MoneyFeeParamsV1>)> {
+        let wallet = self.holders.get(holder).unwrap();
+        let mint_authority = wallet.token_mint_authority;
+
+        let (frz_pk, frz_zkbin) =
+            self.proving_keys.get(MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1).unwrap();
+
+        let auth_func_id = FuncRef {
+            contract_id: *MONEY_CONTRACT_ID,
+            func_code: MoneyFunction::AuthTokenMintV1 as u8,
+        }
+        .to_func_id();
+
+        let (mint_auth_x, mint_auth_y) = mint_authority.public.xy();
+
+        let token_attrs = TokenAttributes {
+            auth_parent: auth_func_id,
+            user_data: poseidon_hash([mint_auth_x, mint_auth_y]),
+            blind: token_blind,
+        };
+
+        let builder = BridgeAuthoritySetCallBuilder {
+            mint_keypair: mint_authority,
+            token_attrs,
+            authority_set,
+            freeze_zkbin: frz_zkbin.clone(),
+            freeze_pk: frz_pk.clone(),
+        };
+        let debris = builder.build()?;
+        let mut data = vec![MoneyFunction::BridgeAuthoritySetV1 as u8];
+        debris.params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let mut tx_builder =
+            TransactionBuilder::new(ContractCallLeaf { call, proofs: debris.proofs }, vec![])?;
+
+        // If we have tx fees enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let sigs = tx.create_sigs(&[mint_authority.secret])?;
+            tx.signatures = vec![sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&[mint_authority.secret])?;
+        tx.signatures = vec![sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, debris.params, fee_params))
+    }
+
+    /// Execute the transaction created by `bridge_authority_set()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_bridge_authority_set_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        _params: &MoneyBridgeAuthoritySetParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet
+            .add_transaction("money::bridge_authority_set", tx, block_height, self.verify_fees)
+            .await?;
+
+        let mut found_owncoins = vec![];
+        if !append {
+            return Ok(found_owncoins)
+        }
+
+        if let Some(ref fee_params) = fee_params {
+            let nullifier = fee_params.input.nullifier.inner();
+            wallet
+                .money_null_smt
+                .insert_batch(vec![(nullifier, nullifier)])
+                .expect("smt.insert_batch()");
+
+            if let Some(spent_coin) = wallet
+                .unspent_money_coins
+                .iter()
+                .find(|x| x.nullifier() == fee_params.input.nullifier)
+                .cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != fee_params.input.nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+
+            wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+            // Attempt to decrypt the encrypted note in the fee output
+            if let Ok(note) = fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) {
+                let owncoin = OwnCoin {
+                    coin: fee_params.output.coin,
+                    note,
+                    secret: wallet.keypair.secret,
+                    leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+                };
+
+                debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+                wallet.unspent_money_coins.push(owncoin.clone());
+                found_owncoins.push(owncoin);
+            }
+        }
+
+        Ok(found_owncoins)
+    }
+
+    /// Mint coins locked on an external chain for a given recipient using
+    /// `Money::BridgeMint`, signed by the given bridge authorities. The
+    /// holder relaying the transaction pays its fee.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn bridge_mint(
+        &mut self,
+        holder: &Holder,
+        authorities: &[Keypair],
+        recipient: &Holder,
+        token_id: TokenId,
+        value: u64,
+        source: [u8; 32],
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyBridgeMintParamsV1, Option<MoneyFeeParamsV1>)> {
+        let rcpt = self.holders.get(recipient).unwrap().keypair.public;
+
+        let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+
+        let builder = BridgeMintCallBuilder {
+            value,
+            token_id,
+            source,
+            recipient: rcpt,
+            spend_hook: FuncId::none(),
+            user_data: pallas::Base::ZERO,
+            signers: authorities.iter().map(|x| x.public).collect(),
+            mint_zkbin: mint_zkbin.clone(),
+            mint_pk: mint_pk.clone(),
+        };
+        let debris = builder.build()?;
+        let mut data = vec![MoneyFunction::BridgeMintV1 as u8];
+        debris.params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let mut tx_builder =
+            TransactionBuilder::new(ContractCallLeaf { call, proofs: debris.proofs }, vec![])?;
+
+        let authority_secrets: Vec<_> = authorities.iter().map(|x| x.secret).collect();
+
+        // If we have tx fees enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let sigs = tx.create_sigs(&authority_secrets)?;
+            tx.signatures = vec![sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&authority_secrets)?;
+        tx.signatures = vec![sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, debris.params, fee_params))
+    }
+
+    /// Execute the transaction created by `bridge_mint()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_bridge_mint_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        params: &MoneyBridgeMintParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("money::bridge_mint", tx, block_height, self.verify_fees).await?;
+
+        let mut found_owncoins = vec![];
+        if !append {
+            return Ok(found_owncoins)
+        }
+
+        if let Some(ref fee_params) = fee_params {
+            let nullifier = fee_params.input.nullifier.inner();
+            wallet
+                .money_null_smt
+                .insert_batch(vec![(nullifier, nullifier)])
+                .expect("smt.insert_batch()");
+
+            if let Some(spent_coin) = wallet
+                .unspent_money_coins
+                .iter()
+                .find(|x| x.nullifier() == fee_params.input.nullifier)
+                .cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != fee_params.input.nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+        }
+
+        let mut outputs = vec![params.output.clone()];
+        if let Some(ref fee_params) = fee_params {
+            outputs.push(fee_params.output.clone());
+        }
+
+        for output in outputs {
+            wallet.money_merkle_tree.append(MerkleNode::from(output.coin.inner()));
+
+            // Attempt to decrypt the encrypted note
+            if let Ok(note) = output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) {
+                let owncoin = OwnCoin {
+                    coin: output.coin,
+                    note,
+                    secret: wallet.keypair.secret,
+                    leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+                };
+
+                debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+                wallet.unspent_money_coins.push(owncoin.clone());
+                found_owncoins.push(owncoin);
+            }
+        }
+
+        Ok(found_owncoins)
+    }
+}
@@ -0,0 +1,159 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    zk::halo2::Field,
+    Result,
+};
+use darkfi_money_contract::{
+    client::{
+        coinjoin::{collect_signatures, CoinJoinCallBuilder, CoinJoinSession},
+        transfer_v1::{TransferCallInput, TransferCallOutput},
+        OwnCoin,
+    },
+    model::{MoneyFeeParamsV1, MoneyTransferParamsV1},
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{contract_id::MONEY_CONTRACT_ID, Blind, FuncId},
+    pasta::pallas,
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use rand::rngs::OsRng;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Create a CoinJoin `Money::Transfer` transaction coordinated by the
+    /// given [`Holder`]. Each payment is a tuple of the paying holder, the
+    /// coin they spend, the recipient, and the value being paid, with any
+    /// change sent back to the paying holder. All coins must share a token.
+    ///
+    /// Returns the [`Transaction`], and the transaction parameters.
+    pub async fn coinjoin(
+        &mut self,
+        coordinator: &Holder,
+        payments: &[(Holder, OwnCoin, Holder, u64)],
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyTransferParamsV1, Option<MoneyFeeParamsV1>)> {
+        let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        // The coordinator opens the session, and every participant
+        // builds their contribution for it
+        let session = CoinJoinSession::new(payments[0].1.note.token_id);
+        let mut contributions = vec![];
+        for (holder, coin, recipient, value) in payments {
+            let wallet = self.holders.get(holder).unwrap();
+            let rcpt = self.holders.get(recipient).unwrap().keypair.public;
+
+            let input = TransferCallInput {
+                coin: coin.clone(),
+                merkle_path: wallet.money_merkle_tree.witness(coin.leaf_position, 0).unwrap(),
+                user_data_blind: Blind::random(&mut OsRng),
+                frozen_root: wallet.money_frozen_smt.root(),
+                frozen_path: wallet.money_frozen_smt.prove_membership(&coin.note.token_id.inner()),
+            };
+
+            let mut outputs = vec![TransferCallOutput {
+                public_key: rcpt,
+                value: *value,
+                token_id: session.token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            }];
+
+            if coin.note.value > *value {
+                outputs.push(TransferCallOutput {
+                    public_key: wallet.keypair.public,
+                    value: coin.note.value - value,
+                    token_id: session.token_id,
+                    spend_hook: FuncId::none(),
+                    user_data: pallas::Base::ZERO,
+                    blind: Blind::random(&mut OsRng),
+                });
+            }
+
+            let builder = CoinJoinCallBuilder {
+                session,
+                inputs: vec![input],
+                outputs,
+                mint_zkbin: mint_zkbin.clone(),
+                mint_pk: mint_pk.clone(),
+                burn_zkbin: burn_zkbin.clone(),
+                burn_pk: burn_pk.clone(),
+            };
+
+            contributions.push(builder.build()?);
+        }
+
+        // The coordinator merges the contributions
+        let (params, proofs) = session.join(&contributions)?;
+
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+        let mut tx_builder = TransactionBuilder::new(ContractCallLeaf { call, proofs }, vec![])?;
+
+        // Every participant signs their own inputs, and the coordinator
+        // puts the signatures in place.
+        let sign = |tx: &Transaction| -> Result<_> {
+            let mut partial_sigs = vec![];
+            for ((_, coin, _, _), contribution) in payments.iter().zip(&contributions) {
+                partial_sigs.push(contribution.sign(tx, 0, &[coin.clone()])?);
+            }
+            collect_signatures(tx, 0, &params, &partial_sigs)
+        };
+
+        // If we have tx fees enabled, the coordinator makes an offering
+        // before the final signing round
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            tx.signatures = vec![sign(&tx)?];
+
+            let spent_coins: Vec<OwnCoin> = payments
+                .iter()
+                .filter(|(holder, _, _, _)| holder == coordinator)
+                .map(|(_, coin, _, _)| coin.clone())
+                .collect();
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(coordinator, tx, block_height, &spent_coins).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and collect all signatures
+        let mut tx = tx_builder.build()?;
+        tx.signatures = vec![sign(&tx)?];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, params, fee_params))
+    }
+}
@@ -29,7 +29,7 @@ use darkfi_money_contract::{
         fee_v1::{create_fee_proof, FeeCallInput, FeeCallOutput, FEE_CALL_GAS},
         MoneyNote, OwnCoin,
     },
-    model::{token_id::DARK_TOKEN_ID, Input, MoneyFeeParamsV1, Output},
+    model::{token_id::DARK_TOKEN_ID, Input, MoneyFeeParamsV1, Output, TokenId},
     MoneyFunction, MONEY_CONTRACT_ZKAS_FEE_NS_V1,
 };
 use darkfi_sdk::{
@@ -50,9 +50,15 @@ impl TestHarness {
     /// Create an empty transaction that includes a `Money::Fee` call.
     /// This is generally used to test the actual fee call, and also to
     /// see the gas usage of the call without other parts.
+    ///
+    /// The fee is paid in `token_id`, spending `token_fee` of it. For the
+    /// native token this should be `FEE_CALL_GAS`, otherwise the amount
+    /// given by the token's whitelisted conversion rate.
     pub async fn create_empty_fee_call(
         &mut self,
         holder: &Holder,
+        token_id: TokenId,
+        token_fee: u64,
     ) -> Result<(Transaction, MoneyFeeParamsV1)> {
         let wallet = self.holders.get(holder).unwrap();
 
@@ -60,7 +66,7 @@ impl TestHarness {
         let coin = wallet
             .unspent_money_coins
             .iter()
-            .find(|x| x.note.token_id == *DARK_TOKEN_ID && x.note.value > FEE_CALL_GAS)
+            .find(|x| x.note.token_id == token_id && x.note.value > token_fee)
             .unwrap();
 
         // Input and output setup
@@ -72,7 +78,7 @@ impl TestHarness {
 
         let output = FeeCallOutput {
             public_key: wallet.keypair.public,
-            value: coin.note.value - FEE_CALL_GAS,
+            value: coin.note.value - token_fee,
             token_id: coin.note.token_id,
             blind: Blind::random(&mut OsRng),
             spend_hook: FuncId::none(),
@@ -114,6 +120,7 @@ impl TestHarness {
             user_data: output.user_data,
             value_blind: output_value_blind,
             token_blind,
+            payment_id: None,
             memo: vec![],
         };
 
@@ -125,6 +132,7 @@ impl TestHarness {
                 token_commit: public_inputs.token_commit,
                 nullifier: public_inputs.nullifier,
                 merkle_root: public_inputs.merkle_root,
+                frozen_root: pallas::Base::ZERO,
                 user_data_enc: public_inputs.input_user_data_enc,
                 signature_public: public_inputs.signature_public,
             },
@@ -135,6 +143,7 @@ impl TestHarness {
                 note: encrypted_note,
             },
             fee_value_blind,
+            token_id: coin.note.token_id,
             token_blind,
         };
 
@@ -282,6 +291,7 @@ impl TestHarness {
             user_data: output.user_data,
             value_blind: output_value_blind,
             token_blind,
+            payment_id: None,
             memo: vec![],
         };
 
@@ -293,6 +303,7 @@ impl TestHarness {
                 token_commit: public_inputs.token_commit,
                 nullifier: public_inputs.nullifier,
                 merkle_root: public_inputs.merkle_root,
+                frozen_root: pallas::Base::ZERO,
                 user_data_enc: public_inputs.input_user_data_enc,
                 signature_public: public_inputs.signature_public,
             },
@@ -303,6 +314,7 @@ impl TestHarness {
                 note: encrypted_note,
             },
             fee_value_blind,
+            token_id: coin.note.token_id,
             token_blind,
         };
 
@@ -0,0 +1,80 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_money_contract::{
+    client::fee_token_rate_v1::FeeTokenRateCallBuilder,
+    model::{FeeTokenRate, MoneyFeeTokenRateParamsV1, TokenId},
+    MoneyFunction,
+};
+use darkfi_sdk::{
+    crypto::{PublicKey, MONEY_CONTRACT_ID},
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Whitelist `token_id` for paying fees with the given `rate`, or remove
+    /// it from the whitelist if `rate` is `None`, using `Money::FeeTokenRate`.
+    /// The call is signed with the `holder`'s keypair as the fee governance key,
+    /// which is optionally handed over to `new_governance_public`.
+    pub async fn fee_token_rate(
+        &mut self,
+        holder: &Holder,
+        token_id: TokenId,
+        rate: Option<FeeTokenRate>,
+        new_governance_public: Option<PublicKey>,
+    ) -> Result<(Transaction, MoneyFeeTokenRateParamsV1)> {
+        let wallet = self.holders.get(holder).unwrap();
+        let governance_keypair = wallet.keypair;
+
+        let builder =
+            FeeTokenRateCallBuilder { governance_keypair, token_id, rate, new_governance_public };
+        let debris = builder.build()?;
+
+        let mut data = vec![MoneyFunction::FeeTokenRateV1 as u8];
+        debris.params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let mut tx_builder =
+            TransactionBuilder::new(ContractCallLeaf { call, proofs: vec![] }, vec![])?;
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&[governance_keypair.secret])?;
+        tx.signatures = vec![sigs];
+
+        Ok((tx, debris.params))
+    }
+
+    /// Execute the transaction created by `fee_token_rate()` for a given [`Holder`].
+    pub async fn execute_fee_token_rate_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        block_height: u64,
+    ) -> Result<()> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+        wallet.add_transaction("money::fee_token_rate", tx, block_height, self.verify_fees).await?;
+
+        Ok(())
+    }
+}
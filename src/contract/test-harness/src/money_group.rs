@@ -0,0 +1,229 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    zk::halo2::Field,
+    Result,
+};
+use darkfi_money_contract::{
+    client::{
+        group_spend_v1::{group_escrow_keypair, group_lock_coin, group_spend_hook},
+        transfer_v1 as xfer, OwnCoin,
+    },
+    model::{
+        CoinAttributes, GroupCustody, MoneyFeeParamsV1, MoneyGroupSpendParamsV1,
+        MoneyTransferParamsV1, TokenId,
+    },
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{
+        contract_id::MONEY_CONTRACT_ID,
+        frost::{self, KeyPackage, PublicKeyPackage, SigningPackage},
+        schnorr::Signature,
+        BaseBlind, Blind, FuncId,
+    },
+    dark_tree::DarkTree,
+    pasta::pallas,
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use rand::rngs::OsRng;
+
+use super::{Holder, TestHarness};
+
+/// Run both FROST signing rounds over the transaction with the given
+/// members' keys, returning the group signature.
+fn group_sign(
+    tx: &Transaction,
+    keys: &[KeyPackage],
+    pubkeys: &PublicKeyPackage,
+) -> Result<Signature> {
+    let mut nonces = vec![];
+    let mut commitments = vec![];
+    for key in keys {
+        let (n, c) = frost::commit(key, &mut OsRng);
+        nonces.push(n);
+        commitments.push(c);
+    }
+
+    let package = SigningPackage::new(commitments, tx.sighash()?.as_bytes())?;
+
+    let mut shares = vec![];
+    for (key, n) in keys.iter().zip(nonces) {
+        shares.push(frost::sign(&package, n, key)?);
+    }
+
+    Ok(frost::aggregate(&package, &shares, pubkeys)?)
+}
+
+impl TestHarness {
+    /// Create a `Money::Transfer` transaction paying `value` from `holder`'s
+    /// `owncoins` into a coin held by a group. The escrow key of the coin is
+    /// added to the `members`' wallets, so they find it when executing the
+    /// transaction.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn group_lock(
+        &mut self,
+        holder: &Holder,
+        members: &[Holder],
+        custody: &GroupCustody,
+        value: u64,
+        token_id: TokenId,
+        owncoins: &[OwnCoin],
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyTransferParamsV1, Option<MoneyFeeParamsV1>, Vec<OwnCoin>)> {
+        let escrow = group_escrow_keypair(custody);
+        for member in members {
+            self.holders.get_mut(member).unwrap().escrow_secrets.push(escrow.secret);
+        }
+
+        let lock_coin = group_lock_coin(custody, value, token_id, Blind::random(&mut OsRng));
+        self.transfer_coin(holder, lock_coin, owncoins, block_height).await
+    }
+
+    /// Create a `Money::GroupSpend` transaction, where `holder` spends every
+    /// coin held under `custody` into a single coin for `recipient`, and the
+    /// members holding `keys` sign it for the group described by `pubkeys`.
+    ///
+    /// The resulting transaction can be executed with `execute_transfer_tx()`,
+    /// using the returned child transfer parameters.
+    pub async fn group_spend(
+        &mut self,
+        holder: &Holder,
+        custody: &GroupCustody,
+        keys: &[KeyPackage],
+        pubkeys: &PublicKeyPackage,
+        recipient: &Holder,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        MoneyTransferParamsV1,
+        MoneyGroupSpendParamsV1,
+        Option<MoneyFeeParamsV1>,
+    )> {
+        let recipient_public = self.holders.get(recipient).unwrap().keypair.public;
+        let wallet = self.holders.get(holder).unwrap();
+
+        let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        // Find the coins held under these terms
+        let spend_hook = group_spend_hook();
+        let user_data = custody.to_bulla();
+        let group_coins: Vec<OwnCoin> = wallet
+            .unspent_money_coins
+            .iter()
+            .filter(|x| x.note.spend_hook == spend_hook && x.note.user_data == user_data)
+            .cloned()
+            .collect();
+        assert!(!group_coins.is_empty());
+
+        let mut inputs = vec![];
+        let mut user_data_blinds = vec![];
+        for coin in &group_coins {
+            let user_data_blind = BaseBlind::random(&mut OsRng);
+            inputs.push(xfer::TransferCallInput {
+                coin: coin.clone(),
+                merkle_path: wallet.money_merkle_tree.witness(coin.leaf_position, 0).unwrap(),
+                user_data_blind,
+                frozen_root: wallet.money_frozen_smt.root(),
+                frozen_path: wallet.money_frozen_smt.prove_membership(&coin.note.token_id.inner()),
+            });
+            user_data_blinds.push(user_data_blind);
+        }
+
+        let xfer_builder = xfer::TransferCallBuilder {
+            clear_inputs: vec![],
+            inputs,
+            outputs: vec![CoinAttributes {
+                public_key: recipient_public,
+                value: group_coins.iter().map(|x| x.note.value).sum(),
+                token_id: group_coins[0].note.token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            }],
+            payment_ids: vec![],
+            viewing_keys: vec![],
+            memos: vec![],
+            mint_zkbin: mint_zkbin.clone(),
+            mint_pk: mint_pk.clone(),
+            burn_zkbin: burn_zkbin.clone(),
+            burn_pk: burn_pk.clone(),
+        };
+
+        let (xfer_params, xfer_secrets) = xfer_builder.build()?;
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        xfer_params.encode_async(&mut data).await?;
+        let xfer_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let spend_params = MoneyGroupSpendParamsV1 { custody: custody.clone(), user_data_blinds };
+        let mut data = vec![MoneyFunction::GroupSpendV1 as u8];
+        spend_params.encode_async(&mut data).await?;
+        let spend_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        // We need to construct this tree, where spend is the parent:
+        //
+        //   spend ->
+        //       xfer
+        //
+
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: spend_call, proofs: vec![] },
+            vec![DarkTree::new(
+                ContractCallLeaf { call: xfer_call, proofs: xfer_secrets.proofs },
+                vec![],
+                None,
+                None,
+            )],
+        )?;
+
+        // If fees are enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+            let spend_sigs = vec![group_sign(&tx, keys, pubkeys)?];
+            tx.signatures = vec![xfer_sigs, spend_sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &group_coins).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        // The spend call is signed by the group.
+        let mut tx = tx_builder.build()?;
+        let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+        let spend_sigs = vec![group_sign(&tx, keys, pubkeys)?];
+        tx.signatures = vec![xfer_sigs, spend_sigs];
+
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, xfer_params, spend_params, fee_params))
+    }
+}
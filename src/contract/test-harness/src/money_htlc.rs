@@ -0,0 +1,191 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    zk::halo2::Field,
+    Result,
+};
+use darkfi_money_contract::{
+    client::{
+        htlc_spend_v1::{htlc_escrow_keypair, htlc_lock_coin, htlc_spend_hook},
+        transfer_v1 as xfer, OwnCoin,
+    },
+    model::{
+        CoinAttributes, Htlc, MoneyFeeParamsV1, MoneyHtlcSpendParamsV1, MoneyTransferParamsV1,
+        TokenId,
+    },
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{contract_id::MONEY_CONTRACT_ID, BaseBlind, Blind, FuncId},
+    dark_tree::DarkTree,
+    pasta::pallas,
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use rand::rngs::OsRng;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Create a `Money::Transfer` transaction locking `value` from `holder`'s
+    /// `owncoins` into an HTLC coin. The escrow key of the coin is added to
+    /// both parties' wallets, so they find it when executing the transaction.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn htlc_lock(
+        &mut self,
+        holder: &Holder,
+        recipient: &Holder,
+        htlc: &Htlc,
+        value: u64,
+        token_id: TokenId,
+        owncoins: &[OwnCoin],
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyTransferParamsV1, Option<MoneyFeeParamsV1>, Vec<OwnCoin>)> {
+        let escrow = htlc_escrow_keypair(htlc);
+        for party in [holder, recipient] {
+            self.holders.get_mut(party).unwrap().escrow_secrets.push(escrow.secret);
+        }
+
+        let lock_coin = htlc_lock_coin(htlc, value, token_id, Blind::random(&mut OsRng));
+        self.transfer_coin(holder, lock_coin, owncoins, block_height).await
+    }
+
+    /// Create a `Money::HtlcSpend` transaction, where `holder` claims the HTLC
+    /// coin with the given `preimage`, or refunds it when `preimage` is `None`.
+    /// The whole value is sent to `holder`.
+    ///
+    /// The resulting transaction can be executed with `execute_transfer_tx()`,
+    /// using the returned child transfer parameters.
+    pub async fn htlc_spend(
+        &mut self,
+        holder: &Holder,
+        htlc: &Htlc,
+        preimage: Option<[u8; 32]>,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        MoneyTransferParamsV1,
+        MoneyHtlcSpendParamsV1,
+        Option<MoneyFeeParamsV1>,
+    )> {
+        let wallet = self.holders.get(holder).unwrap();
+
+        let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        // Find the coin locked under these terms
+        let spend_hook = htlc_spend_hook();
+        let user_data = htlc.to_bulla();
+        let htlc_coin = wallet
+            .unspent_money_coins
+            .iter()
+            .find(|x| x.note.spend_hook == spend_hook && x.note.user_data == user_data)
+            .unwrap()
+            .clone();
+
+        let holder_secret = wallet.keypair.secret;
+        let user_data_blind = BaseBlind::random(&mut OsRng);
+        let xfer_builder = xfer::TransferCallBuilder {
+            clear_inputs: vec![],
+            inputs: vec![xfer::TransferCallInput {
+                coin: htlc_coin.clone(),
+                merkle_path: wallet.money_merkle_tree.witness(htlc_coin.leaf_position, 0).unwrap(),
+                user_data_blind,
+                frozen_root: wallet.money_frozen_smt.root(),
+                frozen_path: wallet
+                    .money_frozen_smt
+                    .prove_membership(&htlc_coin.note.token_id.inner()),
+            }],
+            outputs: vec![CoinAttributes {
+                public_key: wallet.keypair.public,
+                value: htlc_coin.note.value,
+                token_id: htlc_coin.note.token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            }],
+            payment_ids: vec![],
+            viewing_keys: vec![],
+            memos: vec![],
+            mint_zkbin: mint_zkbin.clone(),
+            mint_pk: mint_pk.clone(),
+            burn_zkbin: burn_zkbin.clone(),
+            burn_pk: burn_pk.clone(),
+        };
+
+        let (xfer_params, xfer_secrets) = xfer_builder.build()?;
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        xfer_params.encode_async(&mut data).await?;
+        let xfer_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let spend_params = MoneyHtlcSpendParamsV1 { htlc: htlc.clone(), user_data_blind, preimage };
+        let mut data = vec![MoneyFunction::HtlcSpendV1 as u8];
+        spend_params.encode_async(&mut data).await?;
+        let spend_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        // We need to construct this tree, where spend is the parent:
+        //
+        //   spend ->
+        //       xfer
+        //
+
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: spend_call, proofs: vec![] },
+            vec![DarkTree::new(
+                ContractCallLeaf { call: xfer_call, proofs: xfer_secrets.proofs },
+                vec![],
+                None,
+                None,
+            )],
+        )?;
+
+        // If fees are enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+            let spend_sigs = tx.create_sigs(&[holder_secret])?;
+            tx.signatures = vec![xfer_sigs, spend_sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[htlc_coin]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        // The spend call is signed by the party allowed to spend the coin.
+        let mut tx = tx_builder.build()?;
+        let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+        let spend_sigs = tx.create_sigs(&[holder_secret])?;
+        tx.signatures = vec![xfer_sigs, spend_sigs];
+
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, xfer_params, spend_params, fee_params))
+    }
+}
@@ -0,0 +1,309 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_money_contract::{
+    client::{
+        nft_burn_v1::NftBurnCallBuilder, nft_mint_v1::NftMintCallBuilder, MoneyNote, OwnCoin,
+    },
+    model::{MoneyFeeParamsV1, MoneyNftBurnParamsV1, MoneyNftMintParamsV1, Nft, TokenId},
+    MoneyFunction, MONEY_CONTRACT_NFTS_TREE, MONEY_CONTRACT_ZKAS_BURN_NS_V1,
+    MONEY_CONTRACT_ZKAS_NFT_MINT_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{contract_id::MONEY_CONTRACT_ID, BaseBlind, MerkleNode},
+    pasta::pallas,
+    ContractCall,
+};
+use darkfi_serial::{deserialize, serialize, AsyncEncodable};
+use log::debug;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Mint an NFT committing to `metadata` for a given recipient using `Money::NftMint`
+    pub async fn nft_mint(
+        &mut self,
+        holder: &Holder,
+        recipient: &Holder,
+        metadata: pallas::Base,
+        token_blind: BaseBlind,
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyNftMintParamsV1, Option<MoneyFeeParamsV1>)> {
+        let rcpt = self.holders.get(recipient).unwrap().keypair.public;
+
+        let (mint_pk, mint_zkbin) =
+            self.proving_keys.get(MONEY_CONTRACT_ZKAS_NFT_MINT_NS_V1).unwrap();
+
+        let builder = NftMintCallBuilder {
+            recipient: rcpt,
+            metadata,
+            token_blind,
+            mint_zkbin: mint_zkbin.clone(),
+            mint_pk: mint_pk.clone(),
+        };
+        let debris = builder.build()?;
+        let mut data = vec![MoneyFunction::NftMintV1 as u8];
+        debris.params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let mut tx_builder =
+            TransactionBuilder::new(ContractCallLeaf { call, proofs: debris.proofs }, vec![])?;
+
+        // If we have tx fees enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let sigs = tx.create_sigs(&[])?;
+            tx.signatures = vec![sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&[])?;
+        tx.signatures = vec![sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, debris.params, fee_params))
+    }
+
+    /// Execute the transaction created by `nft_mint()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_nft_mint_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        params: &MoneyNftMintParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("money::nft_mint", tx, block_height, self.verify_fees).await?;
+
+        let mut found_owncoins = vec![];
+        if !append {
+            return Ok(found_owncoins)
+        }
+
+        wallet.money_merkle_tree.append(MerkleNode::from(params.coin.inner()));
+
+        // Attempt to decrypt the encrypted note of the minted NFT
+        if let Ok(note) = params.note.decrypt::<MoneyNote>(&wallet.keypair.secret) {
+            let owncoin = OwnCoin {
+                coin: params.coin,
+                note,
+                secret: wallet.keypair.secret,
+                leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+            };
+
+            debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+            wallet.unspent_money_coins.push(owncoin.clone());
+            found_owncoins.push(owncoin);
+        }
+
+        if let Some(ref fee_params) = fee_params {
+            let nullifier = fee_params.input.nullifier.inner();
+            wallet
+                .money_null_smt
+                .insert_batch(vec![(nullifier, nullifier)])
+                .expect("smt.insert_batch()");
+
+            if let Some(spent_coin) = wallet
+                .unspent_money_coins
+                .iter()
+                .find(|x| x.nullifier() == fee_params.input.nullifier)
+                .cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != fee_params.input.nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+
+            wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+            // Attempt to decrypt the encrypted note in the fee output
+            if let Ok(note) = fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) {
+                let owncoin = OwnCoin {
+                    coin: fee_params.output.coin,
+                    note,
+                    secret: wallet.keypair.secret,
+                    leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+                };
+
+                debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+                wallet.unspent_money_coins.push(owncoin.clone());
+                found_owncoins.push(owncoin);
+            }
+        }
+
+        Ok(found_owncoins)
+    }
+
+    /// Burn the NFT held in `owncoin` using `Money::NftBurn`
+    pub async fn nft_burn(
+        &mut self,
+        holder: &Holder,
+        owncoin: &OwnCoin,
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyNftBurnParamsV1, Option<MoneyFeeParamsV1>)> {
+        let wallet = self.holders.get(holder).unwrap();
+
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        let builder = NftBurnCallBuilder {
+            coin: owncoin.clone(),
+            merkle_path: wallet.money_merkle_tree.witness(owncoin.leaf_position, 0).unwrap(),
+            frozen_root: wallet.money_frozen_smt.root(),
+            frozen_path: wallet.money_frozen_smt.prove_membership(&owncoin.note.token_id.inner()),
+            burn_zkbin: burn_zkbin.clone(),
+            burn_pk: burn_pk.clone(),
+        };
+        let debris = builder.build()?;
+        let mut data = vec![MoneyFunction::NftBurnV1 as u8];
+        debris.params.encode_async(&mut data).await?;
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let mut tx_builder =
+            TransactionBuilder::new(ContractCallLeaf { call, proofs: debris.proofs }, vec![])?;
+
+        // If we have tx fees enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let sigs = tx.create_sigs(&[debris.signature_secret])?;
+            tx.signatures = vec![sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[owncoin.clone()]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&[debris.signature_secret])?;
+        tx.signatures = vec![sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, debris.params, fee_params))
+    }
+
+    /// Execute the transaction created by `nft_burn()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_nft_burn_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        params: &MoneyNftBurnParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("money::nft_burn", tx, block_height, self.verify_fees).await?;
+
+        let mut found_owncoins = vec![];
+        if !append {
+            return Ok(found_owncoins)
+        }
+
+        let mut nullifiers = vec![params.input.nullifier];
+        if let Some(ref fee_params) = fee_params {
+            nullifiers.push(fee_params.input.nullifier);
+        }
+
+        for nullifier in nullifiers {
+            let n = nullifier.inner();
+            wallet.money_null_smt.insert_batch(vec![(n, n)]).expect("smt.insert_batch()");
+
+            if let Some(spent_coin) =
+                wallet.unspent_money_coins.iter().find(|x| x.nullifier() == nullifier).cloned()
+            {
+                debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                wallet.unspent_money_coins.retain(|x| x.nullifier() != nullifier);
+                wallet.spent_money_coins.push(spent_coin.clone());
+            }
+        }
+
+        if let Some(ref fee_params) = fee_params {
+            wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+            // Attempt to decrypt the encrypted note in the fee output
+            if let Ok(note) = fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) {
+                let owncoin = OwnCoin {
+                    coin: fee_params.output.coin,
+                    note,
+                    secret: wallet.keypair.secret,
+                    leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+                };
+
+                debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+                wallet.unspent_money_coins.push(owncoin.clone());
+                found_owncoins.push(owncoin);
+            }
+        }
+
+        Ok(found_owncoins)
+    }
+
+    /// Fetch the on-chain record of an NFT from the given holder's `Money` state.
+    pub fn nft(&self, holder: &Holder, token_id: TokenId) -> Result<Option<Nft>> {
+        let wallet = self.holders.get(holder).unwrap();
+        let blockchain = &wallet.validator.blockchain;
+
+        let nfts_db = blockchain.contracts.lookup(
+            &blockchain.sled_db,
+            &MONEY_CONTRACT_ID,
+            MONEY_CONTRACT_NFTS_TREE,
+        )?;
+
+        match nfts_db.get(serialize(&token_id))? {
+            Some(data) => Ok(Some(deserialize(&data)?)),
+            None => Ok(None),
+        }
+    }
+}
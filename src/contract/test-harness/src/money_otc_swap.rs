@@ -27,11 +27,11 @@ use darkfi_money_contract::{
     MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
 };
 use darkfi_sdk::{
-    crypto::{contract_id::MONEY_CONTRACT_ID, BaseBlind, Blind, FuncId, MerkleNode},
+    crypto::{contract_id::MONEY_CONTRACT_ID, BaseBlind, Blind, FuncId, MerkleNode, SecretKey},
     pasta::pallas,
     ContractCall,
 };
-use darkfi_serial::AsyncEncodable;
+use darkfi_serial::{deserialize, AsyncEncodable};
 use log::debug;
 use rand::rngs::OsRng;
 
@@ -60,12 +60,6 @@ impl TestHarness {
         let rcpt_user_data = pallas::Base::ZERO;
         let rcpt_user_data_blind = Blind::random(&mut OsRng);
 
-        // Create blinding factors for commitments
-        let value_send_blind = Blind::random(&mut OsRng);
-        let value_recv_blind = Blind::random(&mut OsRng);
-        let token_send_blind = BaseBlind::random(&mut OsRng);
-        let token_recv_blind = BaseBlind::random(&mut OsRng);
-
         // Build the first half of the swap for Holder0
         let builder = SwapCallBuilder {
             pubkey: wallet0.keypair.public,
@@ -76,56 +70,39 @@ impl TestHarness {
             user_data_blind_send: rcpt_user_data_blind,
             spend_hook_recv: rcpt_spend_hook,
             user_data_recv: rcpt_user_data,
-            value_blinds: [value_send_blind, value_recv_blind],
-            token_blinds: [token_send_blind, token_recv_blind],
+            value_blinds: [Blind::random(&mut OsRng), Blind::random(&mut OsRng)],
+            token_blinds: [BaseBlind::random(&mut OsRng), BaseBlind::random(&mut OsRng)],
             coin: owncoin0.clone(),
             tree: wallet0.money_merkle_tree.clone(),
+            frozen_tokens: wallet0.money_frozen_smt.clone(),
             mint_zkbin: mint_zkbin.clone(),
             mint_pk: mint_pk.clone(),
             burn_zkbin: burn_zkbin.clone(),
             burn_pk: burn_pk.clone(),
         };
 
-        let debris0 = builder.build()?;
-        assert!(debris0.params.inputs.len() == 1);
-        assert!(debris0.params.outputs.len() == 1);
-
-        // Build the second half of the swap for Holder1
-        let builder = SwapCallBuilder {
-            pubkey: wallet1.keypair.public,
-            value_send: owncoin1.note.value,
-            token_id_send: owncoin1.note.token_id,
-            value_recv: owncoin0.note.value,
-            token_id_recv: owncoin0.note.token_id,
-            user_data_blind_send: rcpt_user_data_blind,
-            spend_hook_recv: rcpt_spend_hook,
-            user_data_recv: rcpt_user_data,
-            value_blinds: [value_recv_blind, value_send_blind],
-            token_blinds: [token_recv_blind, token_send_blind],
-            coin: owncoin1.clone(),
-            tree: wallet1.money_merkle_tree.clone(),
-            mint_zkbin: mint_zkbin.clone(),
-            mint_pk: mint_pk.clone(),
-            burn_zkbin: burn_zkbin.clone(),
-            burn_pk: burn_pk.clone(),
-        };
-
-        let debris1 = builder.build()?;
-        assert!(debris1.params.inputs.len() == 1);
-        assert!(debris1.params.outputs.len() == 1);
-
-        // Holder1 then combines the halves
-        let swap_full_params = MoneyTransferParamsV1 {
-            inputs: vec![debris0.params.inputs[0].clone(), debris1.params.inputs[0].clone()],
-            outputs: vec![debris0.params.outputs[0].clone(), debris1.params.outputs[0].clone()],
-        };
-
-        let swap_full_proofs = vec![
-            debris0.proofs[0].clone(),
-            debris1.proofs[0].clone(),
-            debris0.proofs[1].clone(),
-            debris1.proofs[1].clone(),
-        ];
+        let partial = builder.build_partial()?;
+
+        // Holder1 builds the second half of the swap from the partial data
+        let builder = partial.counterparty_builder(
+            wallet1.keypair.public,
+            owncoin1.clone(),
+            wallet1.money_merkle_tree.clone(),
+            wallet1.money_frozen_smt.clone(),
+            mint_zkbin.clone(),
+            mint_pk.clone(),
+            burn_zkbin.clone(),
+            burn_pk.clone(),
+        );
+
+        // Holder1 then verifies and combines the halves
+        let debris1 = partial.complete(builder.build()?)?;
+        let swap_full_params = debris1.params;
+        let swap_full_proofs = debris1.proofs;
+
+        // Holder0 recovers their signature secret from their output note
+        let note: MoneyNote = swap_full_params.outputs[0].note.decrypt(&wallet0.keypair.secret)?;
+        let signature_secret0: SecretKey = deserialize(&note.memo)?;
 
         // Encode the contract call
         let mut data = vec![MoneyFunction::OtcSwapV1 as u8];
@@ -143,7 +120,7 @@ impl TestHarness {
             tx.signatures = vec![sigs];
 
             // First holder gets the partially signed transaction and adds their signature
-            let sigs = tx.create_sigs(&[debris0.signature_secret])?;
+            let sigs = tx.create_sigs(&[signature_secret0])?;
             tx.signatures[0].insert(0, sigs[0]);
 
             let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
@@ -160,7 +137,7 @@ impl TestHarness {
         let sigs = tx.create_sigs(&[debris1.signature_secret])?;
         tx.signatures = vec![sigs];
         // First holder gets the partially signed transaction and adds their signature
-        let sigs = tx.create_sigs(&[debris0.signature_secret])?;
+        let sigs = tx.create_sigs(&[signature_secret0])?;
         tx.signatures[0].insert(0, sigs[0]);
 
         if let Some(fee_signature_secrets) = fee_signature_secrets {
@@ -23,23 +23,31 @@ use darkfi::{
 };
 use darkfi_money_contract::{
     client::{
-        auth_token_mint_v1::AuthTokenMintCallBuilder, token_freeze_v1::TokenFreezeCallBuilder,
-        token_mint_v1::TokenMintCallBuilder, MoneyNote, OwnCoin,
+        auth_token_mint_v1::{AuthTokenMintCallBuilder, AuthTokenMintSupplyCap},
+        token_freeze_v1::TokenFreezeCallBuilder,
+        token_mint_v1::TokenMintCallBuilder,
+        token_supply_cap_v1::TokenSupplyCapCallBuilder,
+        MoneyNote, OwnCoin,
     },
     model::{
         CoinAttributes, MoneyAuthTokenMintParamsV1, MoneyFeeParamsV1, MoneyTokenFreezeParamsV1,
-        MoneyTokenMintParamsV1, TokenAttributes,
+        MoneyTokenMintParamsV1, MoneyTokenSupplyCapParamsV1, TokenAttributes, TokenId,
     },
-    MoneyFunction, MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1,
-    MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1,
+    MoneyFunction, MONEY_CONTRACT_TOKEN_FREEZE_TREE, MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE,
+    MONEY_CONTRACT_TOKEN_SUPPLY_TREE, MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1,
+    MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1,
+    MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1,
 };
 use darkfi_sdk::{
-    crypto::{poseidon_hash, BaseBlind, Blind, FuncId, FuncRef, MerkleNode, MONEY_CONTRACT_ID},
+    crypto::{
+        poseidon_hash, BaseBlind, Blind, FuncId, FuncRef, MerkleNode, ScalarBlind,
+        MONEY_CONTRACT_ID,
+    },
     dark_tree::DarkTree,
     pasta::pallas,
     ContractCall,
 };
-use darkfi_serial::AsyncEncodable;
+use darkfi_serial::{deserialize, serialize, AsyncEncodable};
 use log::debug;
 use rand::rngs::OsRng;
 
@@ -90,6 +98,23 @@ impl TestHarness {
 
         let token_id = token_attrs.to_token_id();
 
+        // Capped tokens have to prove the new supply stays within the cap
+        let supply_cap = match self.token_supply_cap(holder, token_id)? {
+            Some(supply_cap) => {
+                let (supply, supply_blind) = self.token_supply(holder, token_id);
+                let (supply_cap_pk, supply_cap_zkbin) =
+                    self.proving_keys.get(MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1).unwrap();
+                Some(AuthTokenMintSupplyCap {
+                    supply_cap,
+                    supply,
+                    supply_blind,
+                    supply_cap_zkbin: supply_cap_zkbin.clone(),
+                    supply_cap_pk: supply_cap_pk.clone(),
+                })
+            }
+            None => None,
+        };
+
         let coin_attrs = CoinAttributes {
             public_key: rcpt,
             value: amount,
@@ -118,8 +143,17 @@ impl TestHarness {
             mint_keypair: mint_authority,
             auth_mint_zkbin: auth_mint_zkbin.clone(),
             auth_mint_pk: auth_mint_pk.clone(),
+            supply_cap,
         };
         let auth_debris = builder.build()?;
+
+        // Keep the value blind around, so we can track the token's supply
+        // once the mint gets executed.
+        self.holders.get_mut(holder).unwrap().pending_token_mints.push((
+            mint_debris.params.coin,
+            amount,
+            auth_debris.value_blind,
+        ));
         let mut data = vec![MoneyFunction::AuthTokenMintV1 as u8];
         auth_debris.params.encode_async(&mut data).await?;
         let auth_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
@@ -206,6 +240,14 @@ impl TestHarness {
         let mut found_owncoins = vec![];
 
         if append {
+            // If we built this mint, account for it in the token's supply
+            if let Some(idx) =
+                wallet.pending_token_mints.iter().position(|x| x.0 == mint_params.coin)
+            {
+                let (_, value, value_blind) = wallet.pending_token_mints.remove(idx);
+                wallet.token_mints.push((auth_params.token_id, value, value_blind));
+            }
+
             wallet.money_merkle_tree.append(MerkleNode::from(mint_params.coin.inner()));
 
             // Attempt to decrypt the encrypted note of the minted token
@@ -246,10 +288,11 @@ impl TestHarness {
         Ok(found_owncoins)
     }
 
-    /// Freeze the supply of a minted token
+    /// Freeze the supply of a minted token, irreversibly revoking its mint authority
     pub async fn token_freeze(
         &mut self,
         holder: &Holder,
+        token_blind: BaseBlind,
         block_height: u64,
     ) -> Result<(Transaction, MoneyTokenFreezeParamsV1, Option<MoneyFeeParamsV1>)> {
         let wallet = self.holders.get(holder).unwrap();
@@ -265,7 +308,6 @@ impl TestHarness {
         .to_func_id();
 
         let (mint_auth_x, mint_auth_y) = mint_authority.public.xy();
-        let token_blind = BaseBlind::random(&mut OsRng);
 
         let token_attrs = TokenAttributes {
             auth_parent: auth_func_id,
@@ -381,4 +423,223 @@ impl TestHarness {
 
         Ok(found_owncoins)
     }
+
+    /// Set a hard cap on the supply of a token minted by the given [`Holder`]
+    pub async fn token_cap(
+        &mut self,
+        holder: &Holder,
+        token_blind: BaseBlind,
+        supply_cap: u64,
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyTokenSupplyCapParamsV1, Option<MoneyFeeParamsV1>)> {
+        let wallet = self.holders.get(holder).unwrap();
+        let mint_authority = wallet.token_mint_authority;
+
+        let (frz_pk, frz_zkbin) =
+            self.proving_keys.get(MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1).unwrap();
+        let (supply_cap_pk, supply_cap_zkbin) =
+            self.proving_keys.get(MONEY_CONTRACT_ZKAS_TOKEN_SUPPLY_CAP_NS_V1).unwrap();
+
+        let auth_func_id = FuncRef {
+            contract_id: *MONEY_CONTRACT_ID,
+            func_code: MoneyFunction::AuthTokenMintV1 as u8,
+        }
+        .to_func_id();
+
+        let (mint_auth_x, mint_auth_y) = mint_authority.public.xy();
+
+        let token_attrs = TokenAttributes {
+            auth_parent: auth_func_id,
+            user_data: poseidon_hash([mint_auth_x, mint_auth_y]),
+            blind: token_blind,
+        };
+        let token_id = token_attrs.to_token_id();
+
+        // If the token was already minted, we have to prove its supply
+        let supply = match self.token_supply_commit(holder, token_id)? {
+            Some(_) => Some(self.token_supply(holder, token_id)),
+            None => None,
+        };
+
+        // Create the supply cap call
+        let builder = TokenSupplyCapCallBuilder {
+            mint_keypair: mint_authority,
+            token_attrs,
+            supply_cap,
+            supply,
+            freeze_zkbin: frz_zkbin.clone(),
+            freeze_pk: frz_pk.clone(),
+            supply_cap_zkbin: supply_cap_zkbin.clone(),
+            supply_cap_pk: supply_cap_pk.clone(),
+        };
+        let cap_debris = builder.build()?;
+        let mut data = vec![MoneyFunction::TokenSupplyCapV1 as u8];
+        cap_debris.params.encode_async(&mut data).await?;
+        let cap_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        // Create the TransactionBuilder containing the above call
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: cap_call, proofs: cap_debris.proofs },
+            vec![],
+        )?;
+
+        // If we have tx fees enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let cap_sigs = tx.create_sigs(&[mint_authority.secret])?;
+            tx.signatures = vec![cap_sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let cap_sigs = tx.create_sigs(&[mint_authority.secret])?;
+        tx.signatures = vec![cap_sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, cap_debris.params, fee_params))
+    }
+
+    /// Execute the transaction created by `token_cap()` for a given [`Holder`].
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_token_cap_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        _cap_params: &MoneyTokenSupplyCapParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+
+        // Execute the transaction
+        wallet.add_transaction("money::token_cap", tx, block_height, self.verify_fees).await?;
+
+        let mut found_owncoins = vec![];
+        if let Some(ref fee_params) = fee_params {
+            if append {
+                let nullifier = fee_params.input.nullifier.inner();
+                wallet
+                    .money_null_smt
+                    .insert_batch(vec![(nullifier, nullifier)])
+                    .expect("smt.insert_batch()");
+
+                if let Some(spent_coin) = wallet
+                    .unspent_money_coins
+                    .iter()
+                    .find(|x| x.nullifier() == fee_params.input.nullifier)
+                    .cloned()
+                {
+                    debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                    wallet
+                        .unspent_money_coins
+                        .retain(|x| x.nullifier() != fee_params.input.nullifier);
+                    wallet.spent_money_coins.push(spent_coin.clone());
+                }
+
+                wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+                // Attempt to decrypt the encrypted note
+                if let Ok(note) =
+                    fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret)
+                {
+                    let owncoin = OwnCoin {
+                        coin: fee_params.output.coin,
+                        note: note.clone(),
+                        secret: wallet.keypair.secret,
+                        leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+                    };
+
+                    debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+                    wallet.unspent_money_coins.push(owncoin.clone());
+                    found_owncoins.push(owncoin);
+                }
+            }
+        }
+
+        Ok(found_owncoins)
+    }
+
+    /// Compute the total supply of a token and the sum of its mints' value
+    /// blinds, from the executed mints of the given holder's mint authority.
+    pub fn token_supply(&self, holder: &Holder, token_id: TokenId) -> (u64, ScalarBlind) {
+        let wallet = self.holders.get(holder).unwrap();
+
+        let mut supply = 0;
+        let mut supply_blind = Blind::ZERO;
+        for (_, value, value_blind) in wallet.token_mints.iter().filter(|x| x.0 == token_id) {
+            supply += value;
+            supply_blind += *value_blind;
+        }
+
+        (supply, supply_blind)
+    }
+
+    /// Fetch the hard supply cap of a token from the given holder's `Money` state.
+    pub fn token_supply_cap(&self, holder: &Holder, token_id: TokenId) -> Result<Option<u64>> {
+        let wallet = self.holders.get(holder).unwrap();
+        let blockchain = &wallet.validator.blockchain;
+
+        let token_supply_caps_db = blockchain.contracts.lookup(
+            &blockchain.sled_db,
+            &MONEY_CONTRACT_ID,
+            MONEY_CONTRACT_TOKEN_SUPPLY_CAPS_TREE,
+        )?;
+
+        match token_supply_caps_db.get(serialize(&token_id))? {
+            Some(data) => Ok(Some(deserialize(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Check in the given holder's `Money` state whether a token's mint
+    /// authority has been revoked, fixing its supply forever.
+    pub fn token_mint_frozen(&self, holder: &Holder, token_id: TokenId) -> Result<bool> {
+        let wallet = self.holders.get(holder).unwrap();
+        let blockchain = &wallet.validator.blockchain;
+
+        let token_freeze_db = blockchain.contracts.lookup(
+            &blockchain.sled_db,
+            &MONEY_CONTRACT_ID,
+            MONEY_CONTRACT_TOKEN_FREEZE_TREE,
+        )?;
+
+        Ok(token_freeze_db.contains_key(serialize(&token_id))?)
+    }
+
+    /// Fetch the accumulated value commitment of a token's authorized
+    /// mints from the given holder's `Money` state.
+    pub fn token_supply_commit(
+        &self,
+        holder: &Holder,
+        token_id: TokenId,
+    ) -> Result<Option<pallas::Point>> {
+        let wallet = self.holders.get(holder).unwrap();
+        let blockchain = &wallet.validator.blockchain;
+
+        let token_supply_db = blockchain.contracts.lookup(
+            &blockchain.sled_db,
+            &MONEY_CONTRACT_ID,
+            MONEY_CONTRACT_TOKEN_SUPPLY_TREE,
+        )?;
+
+        match token_supply_db.get(serialize(&token_id))? {
+            Some(data) => Ok(Some(deserialize(&data)?)),
+            None => Ok(None),
+        }
+    }
 }
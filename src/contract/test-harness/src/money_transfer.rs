@@ -16,21 +16,30 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::iter;
+
 use darkfi::{
     tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    zk::halo2::Field,
     Result,
 };
 use darkfi_money_contract::{
-    client::{transfer_v1::make_transfer_call, MoneyNote, OwnCoin},
-    model::{Input, MoneyFeeParamsV1, MoneyTransferParamsV1, Output, TokenId},
+    client::{
+        coin_selection::LargestFirst,
+        transfer_v1::{self as xfer, make_transfer_call},
+        MoneyNote, OwnCoin,
+    },
+    model::{CoinAttributes, Input, MoneyFeeParamsV1, MoneyTransferParamsV1, Output, TokenId},
     MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
 };
 use darkfi_sdk::{
-    crypto::{contract_id::MONEY_CONTRACT_ID, MerkleNode},
+    crypto::{contract_id::MONEY_CONTRACT_ID, Blind, FuncId, MerkleNode, ViewingKey},
+    pasta::pallas,
     ContractCall,
 };
 use darkfi_serial::AsyncEncodable;
 use log::debug;
+use rand::rngs::OsRng;
 
 use super::{Holder, TestHarness};
 
@@ -47,7 +56,9 @@ impl TestHarness {
     ) -> Result<(Transaction, (MoneyTransferParamsV1, Option<MoneyFeeParamsV1>), Vec<OwnCoin>)>
     {
         let wallet = self.holders.get(holder).unwrap();
-        let rcpt = self.holders.get(recipient).unwrap().keypair.public;
+        let rcpt_wallet = self.holders.get(recipient).unwrap();
+        let rcpt = rcpt_wallet.keypair.public;
+        let rcpt_viewing_key = ViewingKey::derive(&rcpt_wallet.keypair.secret).public_key();
 
         let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
         let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
@@ -56,10 +67,16 @@ impl TestHarness {
         let (params, secrets, mut spent_coins) = make_transfer_call(
             wallet.keypair,
             rcpt,
+            Some(rcpt_viewing_key),
             amount,
             token_id,
+            None,
+            vec![],
             owncoins.to_owned(),
+            // Deterministic selection keeps the tests reproducible
+            &LargestFirst,
             wallet.money_merkle_tree.clone(),
+            &wallet.money_frozen_smt,
             mint_zkbin.clone(),
             mint_pk.clone(),
             burn_zkbin.clone(),
@@ -166,15 +183,19 @@ impl TestHarness {
 
             wallet.money_merkle_tree.append(MerkleNode::from(output.coin.inner()));
 
-            // Attempt to decrypt the output note to see if this is a coin for the holder.
-            let Ok(note) = output.note.decrypt::<MoneyNote>(&wallet.keypair.secret) else {
+            // Attempt to decrypt the output note to see if this is a coin for the holder,
+            // either owned by their keypair or by one of their escrow keys.
+            let Some((note, secret)) = iter::once(&wallet.keypair.secret)
+                .chain(wallet.escrow_secrets.iter())
+                .find_map(|secret| Some((MoneyNote::decrypt(&output.note, secret).ok()?, *secret)))
+            else {
                 continue
             };
 
             let owncoin = OwnCoin {
                 coin: output.coin,
                 note: note.clone(),
-                secret: wallet.keypair.secret,
+                secret,
                 leaf_position: wallet.money_merkle_tree.mark().unwrap(),
             };
 
@@ -185,4 +206,103 @@ impl TestHarness {
 
         Ok(found_owncoins)
     }
+
+    /// Create a `Money::Transfer` transaction paying the given coin from
+    /// `holder`'s `owncoins`, sending any change back to `holder`. This is
+    /// used to lock funds into coins with a spend hook.
+    pub async fn transfer_coin(
+        &mut self,
+        holder: &Holder,
+        coin: CoinAttributes,
+        owncoins: &[OwnCoin],
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyTransferParamsV1, Option<MoneyFeeParamsV1>, Vec<OwnCoin>)> {
+        let wallet = self.holders.get(holder).unwrap();
+
+        let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+
+        let mut spent_coins = owncoins.to_vec();
+        spent_coins
+            .retain(|x| x.note.token_id == coin.token_id && x.note.spend_hook == FuncId::none());
+        let input_value: u64 = spent_coins.iter().map(|x| x.note.value).sum();
+        assert!(input_value >= coin.value);
+
+        let inputs = spent_coins
+            .iter()
+            .map(|coin| xfer::TransferCallInput {
+                coin: coin.clone(),
+                merkle_path: wallet.money_merkle_tree.witness(coin.leaf_position, 0).unwrap(),
+                user_data_blind: Blind::random(&mut OsRng),
+                frozen_root: wallet.money_frozen_smt.root(),
+                frozen_path: wallet.money_frozen_smt.prove_membership(&coin.note.token_id.inner()),
+            })
+            .collect();
+
+        let token_id = coin.token_id;
+        let value = coin.value;
+        let mut outputs = vec![coin];
+        if input_value > value {
+            outputs.push(CoinAttributes {
+                public_key: wallet.keypair.public,
+                value: input_value - value,
+                token_id,
+                spend_hook: FuncId::none(),
+                user_data: pallas::Base::ZERO,
+                blind: Blind::random(&mut OsRng),
+            });
+        }
+
+        let xfer_builder = xfer::TransferCallBuilder {
+            clear_inputs: vec![],
+            inputs,
+            outputs,
+            payment_ids: vec![],
+            viewing_keys: vec![],
+            memos: vec![],
+            mint_zkbin: mint_zkbin.clone(),
+            mint_pk: mint_pk.clone(),
+            burn_zkbin: burn_zkbin.clone(),
+            burn_pk: burn_pk.clone(),
+        };
+
+        let (xfer_params, xfer_secrets) = xfer_builder.build()?;
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        xfer_params.encode_async(&mut data).await?;
+        let xfer_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: xfer_call, proofs: xfer_secrets.proofs },
+            vec![],
+        )?;
+
+        // If fees are enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+            tx.signatures = vec![sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &spent_coins).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            spent_coins.extend_from_slice(&spent_fee_coins);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+        tx.signatures = vec![sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, xfer_params, fee_params, spent_coins))
+    }
 }
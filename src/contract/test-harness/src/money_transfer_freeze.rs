@@ -0,0 +1,198 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_money_contract::{
+    client::{transfer_freeze_v1::TransferFreezeCallBuilder, MoneyNote, OwnCoin},
+    model::{MoneyFeeParamsV1, MoneyTransferFreezeParamsV1, TokenAttributes},
+    MoneyFunction, MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{poseidon_hash, BaseBlind, FuncRef, MerkleNode, MONEY_CONTRACT_ID},
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use log::debug;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Freeze or unfreeze transfers of the token minted by `holder` with
+    /// `token_blind`, using `Money::TransferFreeze` or `Money::TransferUnfreeze`
+    /// depending on `func`.
+    pub async fn transfer_freeze(
+        &mut self,
+        holder: &Holder,
+        token_blind: BaseBlind,
+        func: MoneyFunction,
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyTransferFreezeParamsV1, Option<MoneyFeeParamsV1>)> {
+        assert!(matches!(
+            func,
+            MoneyFunction::TransferFreezeV1 | MoneyFunction::TransferUnfreezeV1
+        ));
+
+        let wallet = self.holders.get(holder).unwrap();
+        let mint_authority = wallet.token_mint_authority;
+
+        let (frz_pk, frz_zkbin) =
+            self.proving_keys.get(MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1).unwrap();
+
+        let auth_func_id = FuncRef {
+            contract_id: *MONEY_CONTRACT_ID,
+            func_code: MoneyFunction::AuthTokenMintV1 as u8,
+        }
+        .to_func_id();
+
+        let (mint_auth_x, mint_auth_y) = mint_authority.public.xy();
+
+        let token_attrs = TokenAttributes {
+            auth_parent: auth_func_id,
+            user_data: poseidon_hash([mint_auth_x, mint_auth_y]),
+            blind: token_blind,
+        };
+
+        // Create the freeze call
+        let builder = TransferFreezeCallBuilder {
+            mint_keypair: mint_authority,
+            token_attrs,
+            freeze_zkbin: frz_zkbin.clone(),
+            freeze_pk: frz_pk.clone(),
+        };
+        let freeze_debris = builder.build()?;
+        let mut data = vec![func as u8];
+        freeze_debris.params.encode_async(&mut data).await?;
+        let freeze_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        // Create the TransactionBuilder containing the above call
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: freeze_call, proofs: freeze_debris.proofs },
+            vec![],
+        )?;
+
+        // If we have tx fees enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let freeze_sigs = tx.create_sigs(&[mint_authority.secret])?;
+            tx.signatures = vec![freeze_sigs];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let freeze_sigs = tx.create_sigs(&[mint_authority.secret])?;
+        tx.signatures = vec![freeze_sigs];
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, freeze_debris.params, fee_params))
+    }
+
+    /// Execute the transaction created by `transfer_freeze()` for a given [`Holder`].
+    /// The holder's SMT of frozen tokens is updated so later transfers prove
+    /// against the new root.
+    ///
+    /// Returns any found [`OwnCoin`]s.
+    pub async fn execute_transfer_freeze_tx(
+        &mut self,
+        holder: &Holder,
+        tx: Transaction,
+        freeze_params: &MoneyTransferFreezeParamsV1,
+        fee_params: &Option<MoneyFeeParamsV1>,
+        block_height: u64,
+        append: bool,
+    ) -> Result<Vec<OwnCoin>> {
+        let wallet = self.holders.get_mut(holder).unwrap();
+        let func = MoneyFunction::try_from(tx.calls[0].data.data[0])?;
+
+        // Execute the transaction
+        wallet
+            .add_transaction("money::transfer_freeze", tx, block_height, self.verify_fees)
+            .await?;
+
+        let token_id = freeze_params.token_id.inner();
+        match func {
+            MoneyFunction::TransferFreezeV1 => wallet
+                .money_frozen_smt
+                .insert_batch(vec![(token_id, token_id)])
+                .expect("smt.insert_batch()"),
+            _ => wallet
+                .money_frozen_smt
+                .remove_leaves(vec![(token_id, token_id)])
+                .expect("smt.remove_leaves()"),
+        }
+
+        let mut found_owncoins = vec![];
+        if let Some(ref fee_params) = fee_params {
+            if append {
+                let nullifier = fee_params.input.nullifier.inner();
+                wallet
+                    .money_null_smt
+                    .insert_batch(vec![(nullifier, nullifier)])
+                    .expect("smt.insert_batch()");
+
+                if let Some(spent_coin) = wallet
+                    .unspent_money_coins
+                    .iter()
+                    .find(|x| x.nullifier() == fee_params.input.nullifier)
+                    .cloned()
+                {
+                    debug!("Found spent OwnCoin({}) for {:?}", spent_coin.coin, holder);
+                    wallet
+                        .unspent_money_coins
+                        .retain(|x| x.nullifier() != fee_params.input.nullifier);
+                    wallet.spent_money_coins.push(spent_coin.clone());
+                }
+
+                wallet.money_merkle_tree.append(MerkleNode::from(fee_params.output.coin.inner()));
+
+                // Attempt to decrypt the encrypted note
+                if let Ok(note) =
+                    fee_params.output.note.decrypt::<MoneyNote>(&wallet.keypair.secret)
+                {
+                    let owncoin = OwnCoin {
+                        coin: fee_params.output.coin,
+                        note: note.clone(),
+                        secret: wallet.keypair.secret,
+                        leaf_position: wallet.money_merkle_tree.mark().unwrap(),
+                    };
+
+                    debug!("Found new OwnCoin({}) for {:?}", owncoin.coin, holder);
+                    wallet.unspent_money_coins.push(owncoin.clone());
+                    found_owncoins.push(owncoin);
+                }
+            }
+        }
+
+        Ok(found_owncoins)
+    }
+}
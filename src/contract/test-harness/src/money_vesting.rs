@@ -0,0 +1,180 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi::{
+    tx::{ContractCallLeaf, Transaction, TransactionBuilder},
+    Result,
+};
+use darkfi_money_contract::{
+    client::{
+        transfer_v1 as xfer,
+        vesting_claim_v1::{vesting_lock_coin, vesting_spend_hook, VestingClaimCallBuilder},
+        OwnCoin,
+    },
+    model::{MoneyFeeParamsV1, MoneyTransferParamsV1, MoneyVestingClaimParamsV1, VestingSchedule},
+    MoneyFunction, MONEY_CONTRACT_ZKAS_BURN_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
+    MONEY_CONTRACT_ZKAS_VESTING_CLAIM_NS_V1,
+};
+use darkfi_sdk::{
+    crypto::{contract_id::MONEY_CONTRACT_ID, BaseBlind, Blind},
+    dark_tree::DarkTree,
+    ContractCall,
+};
+use darkfi_serial::AsyncEncodable;
+use rand::rngs::OsRng;
+
+use super::{Holder, TestHarness};
+
+impl TestHarness {
+    /// Create a `Money::Transfer` transaction locking the schedule's total
+    /// value from `holder`'s `owncoins` into a vesting coin.
+    pub async fn vesting_lock(
+        &mut self,
+        holder: &Holder,
+        schedule: &VestingSchedule,
+        owncoins: &[OwnCoin],
+        block_height: u64,
+    ) -> Result<(Transaction, MoneyTransferParamsV1, Option<MoneyFeeParamsV1>, Vec<OwnCoin>)> {
+        let lock_coin = vesting_lock_coin(schedule, Blind::random(&mut OsRng));
+        self.transfer_coin(holder, lock_coin, owncoins, block_height).await
+    }
+
+    /// Create a `Money::VestingClaim` transaction, where `holder` is the
+    /// schedule's recipient claiming `claim_value` from its vesting coin.
+    ///
+    /// The resulting transaction can be executed with `execute_transfer_tx()`,
+    /// using the returned child transfer parameters.
+    pub async fn vesting_claim(
+        &mut self,
+        holder: &Holder,
+        schedule: &VestingSchedule,
+        claim_value: u64,
+        block_height: u64,
+    ) -> Result<(
+        Transaction,
+        MoneyTransferParamsV1,
+        MoneyVestingClaimParamsV1,
+        Option<MoneyFeeParamsV1>,
+    )> {
+        let wallet = self.holders.get(holder).unwrap();
+
+        let (mint_pk, mint_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_MINT_NS_V1).unwrap();
+        let (burn_pk, burn_zkbin) = self.proving_keys.get(MONEY_CONTRACT_ZKAS_BURN_NS_V1).unwrap();
+        let (claim_pk, claim_zkbin) =
+            self.proving_keys.get(MONEY_CONTRACT_ZKAS_VESTING_CLAIM_NS_V1).unwrap();
+
+        // Find the coin locked under this schedule
+        let spend_hook = vesting_spend_hook();
+        let user_data = schedule.to_bulla();
+        let vesting_coin = wallet
+            .unspent_money_coins
+            .iter()
+            .find(|x| x.note.spend_hook == spend_hook && x.note.user_data == user_data)
+            .unwrap()
+            .clone();
+
+        let claim_builder = VestingClaimCallBuilder {
+            schedule: schedule.clone(),
+            input_value: vesting_coin.note.value,
+            claim_value,
+            input_user_data_blind: BaseBlind::random(&mut OsRng),
+            claim_coin_blind: BaseBlind::random(&mut OsRng),
+            change_coin_blind: BaseBlind::random(&mut OsRng),
+            claim_height: block_height,
+        };
+
+        let xfer_builder = xfer::TransferCallBuilder {
+            clear_inputs: vec![],
+            inputs: vec![xfer::TransferCallInput {
+                coin: vesting_coin.clone(),
+                merkle_path: wallet
+                    .money_merkle_tree
+                    .witness(vesting_coin.leaf_position, 0)
+                    .unwrap(),
+                user_data_blind: claim_builder.input_user_data_blind,
+                frozen_root: wallet.money_frozen_smt.root(),
+                frozen_path: wallet
+                    .money_frozen_smt
+                    .prove_membership(&vesting_coin.note.token_id.inner()),
+            }],
+            outputs: vec![claim_builder.claim_coin(), claim_builder.change_coin()],
+            payment_ids: vec![],
+            viewing_keys: vec![],
+            memos: vec![],
+            mint_zkbin: mint_zkbin.clone(),
+            mint_pk: mint_pk.clone(),
+            burn_zkbin: burn_zkbin.clone(),
+            burn_pk: burn_pk.clone(),
+        };
+
+        let (xfer_params, xfer_secrets) = xfer_builder.build()?;
+        let mut data = vec![MoneyFunction::TransferV1 as u8];
+        xfer_params.encode_async(&mut data).await?;
+        let xfer_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        let (claim_params, claim_proofs) = claim_builder.make(claim_zkbin, claim_pk)?;
+        let mut data = vec![MoneyFunction::VestingClaimV1 as u8];
+        claim_params.encode_async(&mut data).await?;
+        let claim_call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data };
+
+        // We need to construct this tree, where claim is the parent:
+        //
+        //   claim ->
+        //       xfer
+        //
+
+        let mut tx_builder = TransactionBuilder::new(
+            ContractCallLeaf { call: claim_call, proofs: claim_proofs },
+            vec![DarkTree::new(
+                ContractCallLeaf { call: xfer_call, proofs: xfer_secrets.proofs },
+                vec![],
+                None,
+                None,
+            )],
+        )?;
+
+        // If fees are enabled, make an offering
+        let mut fee_params = None;
+        let mut fee_signature_secrets = None;
+        if self.verify_fees {
+            let mut tx = tx_builder.build()?;
+            let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+            tx.signatures = vec![xfer_sigs, vec![]];
+
+            let (fee_call, fee_proofs, fee_secrets, _spent_fee_coins, fee_call_params) =
+                self.append_fee_call(holder, tx, block_height, &[vesting_coin]).await?;
+
+            // Append the fee call to the transaction
+            tx_builder.append(ContractCallLeaf { call: fee_call, proofs: fee_proofs }, vec![])?;
+            fee_signature_secrets = Some(fee_secrets);
+            fee_params = Some(fee_call_params);
+        }
+
+        // Now build the actual transaction and sign it with necessary keys.
+        let mut tx = tx_builder.build()?;
+        let xfer_sigs = tx.create_sigs(&xfer_secrets.signature_secrets)?;
+        tx.signatures = vec![xfer_sigs, vec![]];
+
+        if let Some(fee_signature_secrets) = fee_signature_secrets {
+            let sigs = tx.create_sigs(&fee_signature_secrets)?;
+            tx.signatures.push(sigs);
+        }
+
+        Ok((tx, xfer_params, claim_params, fee_params))
+    }
+}
@@ -0,0 +1,225 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fmt::Write as _,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use darkfi::{tx::Transaction, Result};
+use darkfi_sdk::tx::TransactionHash;
+use darkfi_serial::serialize;
+
+/// Atomic pointer to a [`TxStatsRecorder`]
+pub type TxStatsRecorderPtr = Arc<Mutex<TxStatsRecorder>>;
+
+/// Execution costs of a transaction executed by the harness
+#[derive(Clone, Debug)]
+pub struct TxStats {
+    /// Name of the executed call, e.g. `money::transfer`
+    pub callname: String,
+    /// Hash of the executed transaction
+    pub tx_hash: TransactionHash,
+    /// Gas used by the transaction, as accounted by the validator
+    pub gas_used: u64,
+    /// Size of the serialized transaction in bytes
+    pub tx_size: usize,
+    /// Total size in bytes of the ZK proofs of each contract call
+    pub proof_sizes: Vec<usize>,
+    /// Time spent verifying and applying the transaction
+    pub verify_time: Duration,
+}
+
+impl TxStats {
+    /// Gather the [`TxStats`] of a transaction given its measured gas and
+    /// verification time.
+    pub fn new(callname: &str, tx: &Transaction, gas_used: u64, verify_time: Duration) -> Self {
+        let proof_sizes =
+            tx.proofs.iter().map(|p| p.iter().map(|p| p.as_ref().len()).sum()).collect();
+
+        Self {
+            callname: callname.to_string(),
+            tx_hash: tx.hash(),
+            gas_used,
+            tx_size: serialize(tx).len(),
+            proof_sizes,
+            verify_time,
+        }
+    }
+
+    /// Total size in bytes of all the transaction ZK proofs
+    pub fn proof_size(&self) -> usize {
+        self.proof_sizes.iter().sum()
+    }
+}
+
+/// Upper bounds on the execution costs of a call. Unset bounds are not checked.
+#[derive(Clone, Debug, Default)]
+pub struct CallLimits {
+    /// Maximum gas a transaction may use
+    pub max_gas: Option<u64>,
+    /// Maximum total size in bytes of a transaction's ZK proofs
+    pub max_proof_size: Option<usize>,
+    /// Maximum time a transaction verification may take
+    pub max_verify_time: Option<Duration>,
+}
+
+impl CallLimits {
+    /// Check the given [`TxStats`] against these limits, returning a
+    /// description of every exceeded one.
+    pub fn check(&self, stats: &TxStats) -> Vec<String> {
+        let mut violations = vec![];
+
+        if let Some(max) = self.max_gas {
+            if stats.gas_used > max {
+                violations.push(format!(
+                    "{} tx {} used {} gas, limit is {}",
+                    stats.callname, stats.tx_hash, stats.gas_used, max
+                ));
+            }
+        }
+
+        if let Some(max) = self.max_proof_size {
+            if stats.proof_size() > max {
+                violations.push(format!(
+                    "{} tx {} has {} bytes of proofs, limit is {}",
+                    stats.callname,
+                    stats.tx_hash,
+                    stats.proof_size(),
+                    max
+                ));
+            }
+        }
+
+        if let Some(max) = self.max_verify_time {
+            if stats.verify_time > max {
+                violations.push(format!(
+                    "{} tx {} took {:?} to verify, limit is {:?}",
+                    stats.callname, stats.tx_hash, stats.verify_time, max
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+/// Recorder of the [`TxStats`] of all transactions executed by the harness.
+/// Since every holder executes the same transactions, only the first
+/// execution of each transaction is recorded.
+#[derive(Debug, Default)]
+pub struct TxStatsRecorder {
+    records: Vec<TxStats>,
+}
+
+impl TxStatsRecorder {
+    /// Instantiate a new [`TxStatsRecorderPtr`]
+    pub fn new() -> TxStatsRecorderPtr {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// Record the given [`TxStats`], unless its transaction was already recorded
+    pub fn record(&mut self, stats: TxStats) {
+        if self.records.iter().any(|r| r.tx_hash == stats.tx_hash) {
+            return
+        }
+        self.records.push(stats);
+    }
+
+    /// All recorded [`TxStats`], in execution order
+    pub fn records(&self) -> &[TxStats] {
+        &self.records
+    }
+
+    /// Recorded [`TxStats`] of the given call name
+    pub fn by_callname<'a>(&'a self, callname: &'a str) -> impl Iterator<Item = &'a TxStats> {
+        self.records.iter().filter(move |r| r.callname == callname)
+    }
+
+    /// Check all recorded transactions of the given call name against the
+    /// given limits, returning a description of every exceeded one.
+    pub fn check_limits(&self, callname: &str, limits: &CallLimits) -> Vec<String> {
+        self.by_callname(callname).flat_map(|r| limits.check(r)).collect()
+    }
+
+    /// Render the records as CSV, with one line per transaction. Proof sizes
+    /// of each contract call are separated by `;`.
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("callname,tx_hash,gas_used,tx_size,proof_sizes,verify_time_us\n");
+        for r in &self.records {
+            let proof_sizes: Vec<String> = r.proof_sizes.iter().map(|s| s.to_string()).collect();
+            writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                r.callname,
+                r.tx_hash,
+                r.gas_used,
+                r.tx_size,
+                proof_sizes.join(";"),
+                r.verify_time.as_micros()
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    /// Write the CSV report to the given path
+    pub fn write_csv(&self, path: &str) -> Result<()> {
+        std::fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tx_stats_limits() {
+        let stats = |callname: &str, hash: u8, gas_used: u64| TxStats {
+            callname: callname.to_string(),
+            tx_hash: TransactionHash([hash; 32]),
+            gas_used,
+            tx_size: 1000,
+            proof_sizes: vec![400, 0, 200],
+            verify_time: Duration::from_millis(10),
+        };
+
+        let mut recorder = TxStatsRecorder::default();
+        recorder.record(stats("money::transfer", 1, 100));
+        recorder.record(stats("money::transfer", 1, 100));
+        recorder.record(stats("money::transfer", 2, 300));
+        recorder.record(stats("money::fee", 3, 50));
+        assert_eq!(recorder.records().len(), 3);
+        assert_eq!(recorder.by_callname("money::transfer").count(), 2);
+        assert_eq!(recorder.to_csv().lines().count(), 4);
+
+        let limits = CallLimits { max_gas: Some(200), ..Default::default() };
+        assert_eq!(recorder.check_limits("money::transfer", &limits).len(), 1);
+        assert!(recorder.check_limits("money::fee", &limits).is_empty());
+
+        let limits = CallLimits {
+            max_proof_size: Some(500),
+            max_verify_time: Some(Duration::from_millis(5)),
+            ..Default::default()
+        };
+        assert_eq!(recorder.check_limits("money::fee", &limits).len(), 2);
+    }
+}
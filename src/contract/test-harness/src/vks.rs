@@ -30,16 +30,20 @@ use darkfi::{
     Result,
 };
 use darkfi_dao_contract::{
-    DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_ENC_COIN_NS,
-    DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_NS, DAO_CONTRACT_ZKAS_DAO_EXEC_NS,
+    DAO_CONTRACT_ZKAS_DAO_AMEND_NS, DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_ENC_COIN_NS,
+    DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_NS, DAO_CONTRACT_ZKAS_DAO_CANCEL_NS,
+    DAO_CONTRACT_ZKAS_DAO_EXEC_MULTI_NS, DAO_CONTRACT_ZKAS_DAO_EXEC_NS,
     DAO_CONTRACT_ZKAS_DAO_MINT_NS, DAO_CONTRACT_ZKAS_DAO_PROPOSE_INPUT_NS,
-    DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS, DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS,
-    DAO_CONTRACT_ZKAS_DAO_VOTE_MAIN_NS,
+    DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS, DAO_CONTRACT_ZKAS_DAO_REVEAL_TALLY_NS,
+    DAO_CONTRACT_ZKAS_DAO_SETTLE_DEPOSIT_NS, DAO_CONTRACT_ZKAS_DAO_VETO_NS,
+    DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS, DAO_CONTRACT_ZKAS_DAO_VOTE_MAIN_NS,
+    DAO_CONTRACT_ZKAS_DAO_VOTE_MULTI_NS,
 };
 use darkfi_money_contract::{
     MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1, MONEY_CONTRACT_ZKAS_BURN_NS_V1,
     MONEY_CONTRACT_ZKAS_FEE_NS_V1, MONEY_CONTRACT_ZKAS_MINT_NS_V1,
-    MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1,
+    MONEY_CONTRACT_ZKAS_NFT_MINT_NS_V1, MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1,
+    MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1, MONEY_CONTRACT_ZKAS_VESTING_CLAIM_NS_V1,
 };
 use darkfi_sdk::crypto::{DAO_CONTRACT_ID, MONEY_CONTRACT_ID};
 use darkfi_serial::{deserialize, serialize};
@@ -127,15 +131,25 @@ pub fn get_cached_pks_and_vks() -> Result<(Pks, Vks)> {
         &include_bytes!("../../money/proof/token_mint_v1.zk.bin")[..],
         &include_bytes!("../../money/proof/token_freeze_v1.zk.bin")[..],
         &include_bytes!("../../money/proof/auth_token_mint_v1.zk.bin")[..],
+        &include_bytes!("../../money/proof/vesting_claim_v1.zk.bin")[..],
+        &include_bytes!("../../money/proof/nft_mint_v1.zk.bin")[..],
+        &include_bytes!("../../money/proof/token_supply_cap_v1.zk.bin")[..],
         // DAO
         &include_bytes!("../../dao/proof/mint.zk.bin")[..],
         &include_bytes!("../../dao/proof/propose-input.zk.bin")[..],
         &include_bytes!("../../dao/proof/propose-main.zk.bin")[..],
         &include_bytes!("../../dao/proof/vote-input.zk.bin")[..],
         &include_bytes!("../../dao/proof/vote-main.zk.bin")[..],
+        &include_bytes!("../../dao/proof/vote-multi.zk.bin")[..],
         &include_bytes!("../../dao/proof/exec.zk.bin")[..],
+        &include_bytes!("../../dao/proof/exec-multi.zk.bin")[..],
         &include_bytes!("../../dao/proof/auth-money-transfer.zk.bin")[..],
         &include_bytes!("../../dao/proof/auth-money-transfer-enc-coin.zk.bin")[..],
+        &include_bytes!("../../dao/proof/amend.zk.bin")[..],
+        &include_bytes!("../../dao/proof/reveal-tally.zk.bin")[..],
+        &include_bytes!("../../dao/proof/cancel.zk.bin")[..],
+        &include_bytes!("../../dao/proof/veto.zk.bin")[..],
+        &include_bytes!("../../dao/proof/settle-deposit.zk.bin")[..],
     ];
 
     let mut pks = vec![];
@@ -194,7 +208,9 @@ pub fn inject(sled_db: &sled::Db, vks: &Vks) -> Result<()> {
             MONEY_CONTRACT_ZKAS_BURN_NS_V1 |
             MONEY_CONTRACT_ZKAS_TOKEN_MINT_NS_V1 |
             MONEY_CONTRACT_ZKAS_TOKEN_FRZ_NS_V1 |
-            MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1 => {
+            MONEY_CONTRACT_ZKAS_AUTH_TOKEN_MINT_NS_V1 |
+            MONEY_CONTRACT_ZKAS_VESTING_CLAIM_NS_V1 |
+            MONEY_CONTRACT_ZKAS_NFT_MINT_NS_V1 => {
                 let key = serialize(&namespace.as_str());
                 let value = serialize(&(bincode.clone(), vk.clone()));
                 money_tree.insert(key, value)?;
@@ -204,11 +220,18 @@ pub fn inject(sled_db: &sled::Db, vks: &Vks) -> Result<()> {
             DAO_CONTRACT_ZKAS_DAO_MINT_NS |
             DAO_CONTRACT_ZKAS_DAO_VOTE_INPUT_NS |
             DAO_CONTRACT_ZKAS_DAO_VOTE_MAIN_NS |
+            DAO_CONTRACT_ZKAS_DAO_VOTE_MULTI_NS |
             DAO_CONTRACT_ZKAS_DAO_PROPOSE_INPUT_NS |
             DAO_CONTRACT_ZKAS_DAO_PROPOSE_MAIN_NS |
             DAO_CONTRACT_ZKAS_DAO_EXEC_NS |
+            DAO_CONTRACT_ZKAS_DAO_EXEC_MULTI_NS |
             DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_NS |
-            DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_ENC_COIN_NS => {
+            DAO_CONTRACT_ZKAS_DAO_AUTH_MONEY_TRANSFER_ENC_COIN_NS |
+            DAO_CONTRACT_ZKAS_DAO_AMEND_NS |
+            DAO_CONTRACT_ZKAS_DAO_REVEAL_TALLY_NS |
+            DAO_CONTRACT_ZKAS_DAO_CANCEL_NS |
+            DAO_CONTRACT_ZKAS_DAO_VETO_NS |
+            DAO_CONTRACT_ZKAS_DAO_SETTLE_DEPOSIT_NS => {
                 let key = serialize(&namespace.as_str());
                 let value = serialize(&(bincode.clone(), vk.clone()));
                 dao_tree.insert(key, value)?;
@@ -263,6 +263,10 @@ pub enum Error {
     #[error("JSON-RPC connections exhausted")]
     RpcConnectionsExhausted,
 
+    #[cfg(feature = "rpc")]
+    #[error("JSON-RPC client is banned")]
+    RpcClientBanned,
+
     #[cfg(feature = "rpc")]
     #[error("JSON-RPC server stopped")]
     RpcServerStopped,
@@ -283,6 +287,12 @@ pub enum Error {
     #[error("Signature could not be verified")]
     InvalidSignature,
 
+    #[error("Signer failed: {0}")]
+    SignerError(String),
+
+    #[error("Transaction bundle error: {0}")]
+    TxBundleError(String),
+
     #[error("State transition failed")]
     StateTransitionError,
 
@@ -391,6 +401,15 @@ pub enum Error {
     #[error("zkas bincode not found in sled database")]
     ZkasBincodeNotFound,
 
+    #[error("No zkas circuit version deployed at height {0}")]
+    ZkasVersionNotFound(u64),
+
+    #[error("Database is in read-only mode")]
+    DatabaseReadOnly,
+
+    #[error("Invalid gas schedule: {0}")]
+    InvalidGasSchedule(String),
+
     // ===================
     // wasm runtime errors
     // ===================
@@ -426,6 +445,10 @@ pub enum Error {
     #[error("Invalid DarkTree: {0}")]
     DarkTreeError(darkfi_sdk::error::DarkTreeError),
 
+    #[cfg(feature = "darkfi-sdk")]
+    #[error("FROST error: {0}")]
+    FrostError(darkfi_sdk::error::FrostError),
+
     #[cfg(feature = "blockchain")]
     #[error("contract wasm bincode not found")]
     WasmBincodeNotFound,
@@ -496,6 +519,9 @@ pub enum Error {
     #[error("Detached task stopped")]
     DetachedTaskStopped,
 
+    #[error("Task panicked: {0}")]
+    TaskPanicked(String),
+
     #[error("Addition overflow")]
     AdditionOverflow,
 
@@ -542,6 +568,15 @@ pub enum Error {
     #[error("DAG sync failed")]
     DagSyncFailed,
 
+    #[error("Event graph attachments are not enabled")]
+    AttachmentsDisabled,
+
+    #[error("Attachment exceeds the size limit")]
+    AttachmentTooLarge,
+
+    #[error("Attachment is invalid")]
+    AttachmentInvalid,
+
     // =========
     // Catch-all
     // =========
@@ -613,6 +648,12 @@ pub enum ClientFailed {
     #[error("Invalid token ID: {0}")]
     InvalidTokenId(String),
 
+    #[error("Invalid memo length: {0}")]
+    InvalidMemo(usize),
+
+    #[error("Coin blind doesn't commit to the payment ID of output {0}")]
+    InvalidPaymentId(usize),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 
@@ -830,3 +871,10 @@ impl From<darkfi_sdk::error::DarkTreeError> for Error {
         Self::DarkTreeError(err)
     }
 }
+
+#[cfg(feature = "darkfi-sdk")]
+impl From<darkfi_sdk::error::FrostError> for Error {
+    fn from(err: darkfi_sdk::error::FrostError) -> Self {
+        Self::FrostError(err)
+    }
+}
@@ -0,0 +1,55 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Binary attachments of event graph events.
+//!
+//! Attachment contents are kept out of the DAG, in a [`Geode`] store
+//! of content-addressed chunks. Events reference them by including an
+//! [`Attachment`] in their content, in whichever way the app defines,
+//! and peers fetch the chunks on demand over the P2P network.
+//!
+//! [`Geode`]: crate::geode::Geode
+
+use darkfi_serial::{async_trait, SerialDecodable, SerialEncodable};
+
+use crate::geode::MAX_CHUNK_SIZE;
+
+/// Maximum size of an attachment, in bytes (8 MiB)
+pub const MAX_ATTACHMENT_SIZE: usize = 8 * 1024 * 1024;
+
+/// Reference to an attachment stored in Geode
+#[derive(Clone, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct Attachment {
+    /// Geode file hash, i.e. the BLAKE3 hash of the contents
+    pub file_hash: blake3::Hash,
+    /// Size of the contents, in bytes
+    pub size: u64,
+    /// Hashes of the contents' chunks, in order
+    pub chunks: Vec<blake3::Hash>,
+}
+
+impl Attachment {
+    /// Check that the attachment is within the size limit, and that
+    /// its chunks match its size.
+    pub fn is_valid(&self) -> bool {
+        let Ok(size) = usize::try_from(self.size) else { return false };
+        size > 0 &&
+            size <= MAX_ATTACHMENT_SIZE &&
+            self.chunks.len() == size.div_ceil(MAX_CHUNK_SIZE)
+    }
+}
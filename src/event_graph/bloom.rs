@@ -0,0 +1,105 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use darkfi_serial::{async_trait, SerialDecodable, SerialEncodable};
+
+/// Maximum size of a filter we accept from peers, in bits (2 MiB)
+pub const MAX_FILTER_BITS: u64 = 1 << 24;
+/// Maximum number of hash functions we accept from peers
+const MAX_FILTER_HASHES: u8 = 32;
+/// Bits used per inserted ID, giving a ~1% false positive rate
+const BITS_PER_ID: u64 = 10;
+/// Number of hash functions, optimal for `BITS_PER_ID`
+const N_HASHES: u8 = 7;
+
+/// A Bloom filter over event IDs. A syncing node sends it to its peers
+/// to tell them which events it already knows, so they only reply with
+/// the missing ones. A false positive means the peer skips an event we
+/// don't have, which we then request by its ID.
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct EventIdFilter {
+    /// Filter bit array
+    bits: Vec<u64>,
+    /// Number of bits set per inserted ID
+    n_hashes: u8,
+}
+
+impl EventIdFilter {
+    /// Create an empty filter sized for the given number of IDs.
+    pub fn new(n_ids: usize) -> Self {
+        let n_bits = (n_ids as u64 * BITS_PER_ID).clamp(64, MAX_FILTER_BITS);
+        Self { bits: vec![0; n_bits.div_ceil(64) as usize], n_hashes: N_HASHES }
+    }
+
+    /// Check the filter is well-formed and within our size limits.
+    pub fn is_valid(&self) -> bool {
+        !self.bits.is_empty() &&
+            self.bits.len() as u64 * 64 <= MAX_FILTER_BITS &&
+            self.n_hashes > 0 &&
+            self.n_hashes <= MAX_FILTER_HASHES
+    }
+
+    /// Bit positions of the given ID. Event IDs are already uniformly
+    /// distributed, so we derive them with double hashing over its bytes.
+    fn positions(&self, id: &blake3::Hash) -> Vec<u64> {
+        let bytes = id.as_bytes();
+        let h1 = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) | 1;
+        let n_bits = self.bits.len() as u64 * 64;
+
+        (0..self.n_hashes as u64).map(|i| h1.wrapping_add(i.wrapping_mul(h2)) % n_bits).collect()
+    }
+
+    /// Insert an ID into the filter.
+    pub fn insert(&mut self, id: &blake3::Hash) {
+        for pos in self.positions(id) {
+            self.bits[(pos / 64) as usize] |= 1 << (pos % 64);
+        }
+    }
+
+    /// Check if an ID might be in the filter.
+    pub fn contains(&self, id: &blake3::Hash) -> bool {
+        self.positions(id)
+            .into_iter()
+            .all(|pos| self.bits[(pos / 64) as usize] & (1 << (pos % 64)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_id_filter() {
+        let ids: Vec<_> = (0..10000u32).map(|i| blake3::hash(&i.to_le_bytes())).collect();
+        let others: Vec<_> = (10000..20000u32).map(|i| blake3::hash(&i.to_le_bytes())).collect();
+
+        let mut filter = EventIdFilter::new(ids.len());
+        assert!(filter.is_valid());
+        for id in ids.iter() {
+            filter.insert(id);
+        }
+
+        // No false negatives
+        assert!(ids.iter().all(|id| filter.contains(id)));
+
+        // False positives stay around the expected rate
+        let false_positives = others.iter().filter(|id| filter.contains(id)).count();
+        assert!(false_positives < others.len() / 50);
+    }
+}
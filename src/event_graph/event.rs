@@ -67,6 +67,11 @@ impl Event {
         &self.content
     }
 
+    /// Return the event's timestamp, in seconds since UNIX epoch
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
     /*
     /// Check if an [`Event`] is considered too old.
     fn is_too_old(&self) -> bool {
@@ -76,11 +81,14 @@ impl Event {
 
     /// Fully validate an event for the correct layout against provided
     /// DAG [`sled::Tree`] reference and enforce relevant age, assuming
-    /// some possibility for a time drift. Optionally, provide an overlay
-    /// to use that instead of actual referenced DAG.
+    /// some possibility for a time drift. Parents that have been pruned
+    /// are looked up in the provided checkpoints [`sled::Tree`].
+    /// Optionally, provide an overlay to use that instead of actual
+    /// referenced DAG.
     pub async fn validate(
         &self,
         dag: &sled::Tree,
+        checkpoints: &sled::Tree,
         genesis_timestamp: u64,
         days_rotation: u64,
         overlay: Option<&SledTreeOverlay>,
@@ -129,12 +137,23 @@ impl Event {
             } else {
                 dag.get(parent_id.as_bytes())?
             };
-            if parent_bytes.is_none() {
-                return Ok(false)
-            }
 
-            let parent: Event = deserialize_async(&parent_bytes.unwrap()).await?;
-            if self.layer <= parent.layer {
+            // If the parent is not in the DAG, it might have been pruned,
+            // in which case we only know about its layer.
+            let parent_layer = match parent_bytes {
+                Some(parent_bytes) => {
+                    let parent: Event = deserialize_async(&parent_bytes).await?;
+                    parent.layer
+                }
+                None => {
+                    let Some(layer_bytes) = checkpoints.get(parent_id.as_bytes())? else {
+                        return Ok(false)
+                    };
+                    deserialize_async(&layer_bytes).await?
+                }
+            };
+
+            if self.layer <= parent_layer {
                 return Ok(false)
             }
 
@@ -152,7 +171,14 @@ impl Event {
         let genesis_timestamp = event_graph.current_genesis.read().await.timestamp;
 
         // Perform validation
-        self.validate(&event_graph.dag, genesis_timestamp, event_graph.days_rotation, None).await
+        self.validate(
+            &event_graph.dag,
+            &event_graph.checkpoints,
+            genesis_timestamp,
+            event_graph.days_rotation,
+            None,
+        )
+        .await
     }
 
     /// Validate a new event for the correct layout and enforce relevant age,
@@ -216,7 +242,7 @@ mod tests {
         let ex = Arc::new(Executor::new());
         let p2p = P2p::new(Settings::default(), ex.clone()).await;
         let sled_db = sled::Config::new().temporary(true).open().unwrap();
-        EventGraph::new(p2p, sled_db, "dag", 1, ex).await
+        EventGraph::new(p2p, sled_db, "dag", 1, 0, ex).await
     }
 
     #[test]
@@ -28,6 +28,7 @@ use log::{debug, error, info, warn};
 use num_bigint::BigUint;
 use sled_overlay::SledTreeOverlay;
 use smol::{
+    io::Cursor,
     lock::{OnceCell, RwLock},
     Executor,
 };
@@ -35,9 +36,10 @@ use tinyjson::JsonValue::{self};
 
 use crate::{
     event_graph::util::seconds_until_next_rotation,
-    net::P2pPtr,
+    geode::Geode,
+    net::{ChannelPtr, P2pPtr},
     rpc::{
-        jsonrpc::{JsonResponse, JsonResult},
+        jsonrpc::{ErrorCode, JsonError, JsonResponse, JsonResult},
         util::json_map,
     },
     system::{
@@ -54,11 +56,25 @@ pub use event::Event;
 
 /// P2P protocol implementation for the Event Graph
 pub mod proto;
-use proto::{EventRep, EventReq, TipRep, TipReq, REPLY_TIMEOUT};
+use proto::{
+    ChunkRep, ChunkReq, EventRep, EventReq, FilterRep, FilterReq, TipRep, TipReq,
+    MAX_FILTER_REP_EVENTS, REPLY_TIMEOUT,
+};
+
+/// Bloom filter of event IDs, used for DAG sync
+pub mod bloom;
+use bloom::EventIdFilter;
+
+/// Signed moderation actions, applied by apps
+pub mod moderation;
+
+/// Binary attachments stored in Geode
+pub mod attachment;
+use attachment::{Attachment, MAX_ATTACHMENT_SIZE};
 
 /// Utility functions
 mod util;
-use util::{generate_genesis, next_rotation_timestamp};
+use util::{generate_genesis, next_rotation_timestamp, retention_cutoff_timestamp};
 
 // Debugging event graph
 pub(crate) mod deg;
@@ -77,6 +93,8 @@ const GENESIS_CONTENTS: &[u8] = &[0x47, 0x45, 0x4e, 0x45, 0x53, 0x49, 0x53];
 const N_EVENT_PARENTS: usize = 5;
 /// Allowed timestamp drift in seconds
 const EVENT_TIME_DRIFT: u64 = 60;
+/// Interval between DAG retention prunes, in seconds
+const RETENTION_PRUNE_INTERVAL: u64 = 3600;
 /// Null event ID
 pub const NULL_ID: blake3::Hash = blake3::Hash::from_bytes([0x00; blake3::OUT_LEN]);
 
@@ -89,6 +107,18 @@ pub struct EventGraph {
     p2p: P2pPtr,
     /// Sled tree containing the DAG
     dag: sled::Tree,
+    /// Sled tree indexing the DAG events by their layer, keyed by the
+    /// big-endian layer followed by the event ID
+    layers: sled::Tree,
+    /// Sled tree containing the checkpoint heads of the DAG, i.e. the
+    /// pruned events still referenced by retained ones, mapped to
+    /// their layer
+    checkpoints: sled::Tree,
+    /// Sled tree mapping attachment file hashes to the IDs of the
+    /// events referencing them
+    attachment_refs: sled::Tree,
+    /// Geode instance storing attachments, if enabled
+    attachments: OnceCell<Geode>,
     /// The set of unreferenced DAG tips
     unreferenced_tips: RwLock<BTreeMap<u64, HashSet<blake3::Hash>>>,
     /// A `HashSet` containg event IDs and their 1-level parents.
@@ -99,6 +129,8 @@ pub struct EventGraph {
     broadcasted_ids: RwLock<HashSet<blake3::Hash>>,
     /// DAG Pruning Task
     prune_task: OnceCell<StoppableTaskPtr>,
+    /// DAG Retention Task
+    retention_task: OnceCell<StoppableTaskPtr>,
     /// Event subscriber, this notifies whenever an event is
     /// inserted into the DAG
     pub event_sub: SubscriberPtr<Event>,
@@ -106,6 +138,8 @@ pub struct EventGraph {
     current_genesis: RwLock<Event>,
     /// Currently configured DAG rotation, in days
     days_rotation: u64,
+    /// Currently configured DAG retention, in days
+    days_retention: u64,
     /// Flag signalling DAG has finished initial sync
     pub synced: RwLock<bool>,
     /// Enable graph debugging
@@ -117,14 +151,20 @@ pub struct EventGraph {
 impl EventGraph {
     /// Create a new [`EventGraph`] instance.
     /// * `days_rotation` marks the lifetime of the DAG before it's pruned.
+    /// * `days_retention` marks the age after which events are pruned from
+    ///   the DAG, leaving checkpoint heads in their place. 0 keeps them.
     pub async fn new(
         p2p: P2pPtr,
         sled_db: sled::Db,
         dag_tree_name: &str,
         days_rotation: u64,
+        days_retention: u64,
         ex: Arc<Executor<'_>>,
     ) -> Result<EventGraphPtr> {
         let dag = sled_db.open_tree(dag_tree_name)?;
+        let layers = sled_db.open_tree(format!("{dag_tree_name}_layers"))?;
+        let checkpoints = sled_db.open_tree(format!("{dag_tree_name}_checkpoints"))?;
+        let attachment_refs = sled_db.open_tree(format!("{dag_tree_name}_attachments"))?;
         let unreferenced_tips = RwLock::new(BTreeMap::new());
         let broadcasted_ids = RwLock::new(HashSet::new());
         let event_sub = Subscriber::new();
@@ -134,20 +174,29 @@ impl EventGraph {
         let self_ = Arc::new(Self {
             p2p,
             dag: dag.clone(),
+            layers,
+            checkpoints: checkpoints.clone(),
+            attachment_refs,
+            attachments: OnceCell::new(),
             unreferenced_tips,
             broadcasted_ids,
             prune_task: OnceCell::new(),
+            retention_task: OnceCell::new(),
             event_sub,
             current_genesis: RwLock::new(current_genesis.clone()),
             days_rotation,
+            days_retention,
             synced: RwLock::new(false),
             deg_enabled: RwLock::new(false),
             deg_subscriber: Subscriber::new(),
         });
 
-        // Check if we have it in our DAG.
+        // Check if we have it in our DAG, or if it got pruned by retention.
         // If not, we can prune the DAG and insert this new genesis event.
-        if !dag.contains_key(current_genesis.id().as_bytes())? {
+        let genesis_id = current_genesis.id();
+        if !dag.contains_key(genesis_id.as_bytes())? &&
+            !checkpoints.contains_key(genesis_id.as_bytes())?
+        {
             info!(
                 target: "event_graph::new()",
                 "[EVENTGRAPH] DAG does not contain current genesis, pruning existing data",
@@ -155,6 +204,11 @@ impl EventGraph {
             self_.dag_prune(current_genesis).await?;
         }
 
+        // DAGs written before the layer index existed need it built
+        if self_.layers.is_empty() && !dag.is_empty() {
+            self_.rebuild_layers().await?;
+        }
+
         // Find the unreferenced tips in the current DAG state.
         *self_.unreferenced_tips.write().await = self_.find_unreferenced_tips().await;
 
@@ -174,6 +228,19 @@ impl EventGraph {
             );
         }
 
+        // Spawn the DAG retention task
+        if days_retention > 0 {
+            let retention_task = StoppableTask::new();
+            let _ = self_.retention_task.set(retention_task.clone()).await;
+
+            retention_task.clone().start(
+                self_.clone().dag_retention_task(),
+                |_| async move {},
+                Error::DetachedTaskStopped,
+                ex.clone(),
+            );
+        }
+
         Ok(self_)
     }
 
@@ -181,6 +248,63 @@ impl EventGraph {
         self.days_rotation
     }
 
+    pub fn days_retention(&self) -> u64 {
+        self.days_retention
+    }
+
+    /// Key of an event in the layer index
+    fn layer_key(layer: u64, event_id: &blake3::Hash) -> Vec<u8> {
+        let mut key = layer.to_be_bytes().to_vec();
+        key.extend_from_slice(event_id.as_bytes());
+        key
+    }
+
+    /// Rebuild the layer index from the events in the DAG
+    async fn rebuild_layers(&self) -> Result<()> {
+        info!(target: "event_graph::rebuild_layers()", "[EVENTGRAPH] Building DAG layer index");
+        let mut batch = sled::Batch::default();
+        for key in self.layers.iter().keys() {
+            batch.remove(key?);
+        }
+        for iter_elem in self.dag.iter() {
+            let (id, event) = iter_elem?;
+            let id = blake3::Hash::from_bytes((&id as &[u8]).try_into().unwrap());
+            let event: Event = deserialize_async(&event).await?;
+            batch.insert(Self::layer_key(event.layer, &id), vec![]);
+        }
+
+        self.layers.apply_batch(batch)?;
+        Ok(())
+    }
+
+    /// Check if an event is in the DAG, or is a checkpoint head
+    /// left in place of a pruned event.
+    pub(super) fn is_known(&self, event_id: &blake3::Hash) -> bool {
+        self.dag.contains_key(event_id.as_bytes()).unwrap() ||
+            self.checkpoints.contains_key(event_id.as_bytes()).unwrap()
+    }
+
+    /// Timestamp before which events fall out of the configured retention.
+    /// Returns 0 if no retention is configured.
+    pub(super) fn retention_cutoff(&self) -> u64 {
+        if self.days_retention == 0 {
+            return 0
+        }
+
+        retention_cutoff_timestamp(self.days_retention)
+    }
+
+    /// Note down a pruned event ID and its layer as a checkpoint head,
+    /// so events referencing it can still be validated.
+    pub(super) async fn add_checkpoint(&self, event_id: &blake3::Hash, layer: u64) -> Result<()> {
+        debug!(
+            target: "event_graph::add_checkpoint()",
+            "Adding checkpoint head {} at layer {}", event_id, layer,
+        );
+        self.checkpoints.insert(event_id.as_bytes(), serialize_async(&layer).await)?;
+        Ok(())
+    }
+
     async fn _handle_stop(&self, sled_db: sled::Db) {
         info!(target: "event_graph::_handle_stop()", "[EVENTGRAPH] Prune task stopped, flushing sled");
         sled_db.flush_async().await.unwrap();
@@ -292,11 +416,17 @@ impl EventGraph {
         for tip in considered_tips.iter() {
             assert!(tip != &NULL_ID);
 
-            if !self.dag.contains_key(tip.as_bytes()).unwrap() {
+            if !self.is_known(tip) {
                 missing_parents.insert(*tip);
             }
         }
 
+        // Events older than our retention are not inserted, and instead
+        // become checkpoint heads for the events referencing them.
+        let retention_cutoff = self.retention_cutoff();
+        // Lowest layer of the fetched events referencing each missing parent
+        let mut missing_parents_children: HashMap<blake3::Hash, u64> = HashMap::new();
+
         if missing_parents.is_empty() {
             *self.synced.write().await = true;
             info!(target: "event_graph::dag_sync()", "[EVENTGRAPH] DAG synced successfully!");
@@ -307,6 +437,35 @@ impl EventGraph {
         let mut received_events: BTreeMap<u64, Vec<Event>> = BTreeMap::new();
         let mut received_events_hashes = HashSet::new();
 
+        // First let a peer send us everything we're missing in bulk.
+        if let Err(e) =
+            self.dag_sync_filter(&channels, &mut received_events, &mut received_events_hashes).await
+        {
+            warn!(
+                target: "event_graph::dag_sync()",
+                "[EVENTGRAPH] Sync: Failed fetching events by filter: {}", e,
+            );
+        }
+
+        // Whatever we didn't get in bulk, we fetch backwards: the tips
+        // we didn't receive, and the parents of received events we don't
+        // have, which the peer skipped due to filter false positives.
+        missing_parents.retain(|tip| !received_events_hashes.contains(tip));
+        for event in received_events.values().flatten() {
+            for parent_id in event.parents.iter() {
+                if parent_id == &NULL_ID ||
+                    received_events_hashes.contains(parent_id) ||
+                    self.is_known(parent_id)
+                {
+                    continue
+                }
+
+                missing_parents.insert(*parent_id);
+                let child_layer = missing_parents_children.entry(*parent_id).or_insert(event.layer);
+                *child_layer = (*child_layer).min(event.layer);
+            }
+        }
+
         while !missing_parents.is_empty() {
             let mut found_event = false;
 
@@ -380,6 +539,13 @@ impl EventGraph {
                         "Got correct parent event {}", parent_id,
                     );
 
+                    if parent.timestamp < retention_cutoff {
+                        self.add_checkpoint(&parent_id, parent.layer).await?;
+                        missing_parents.remove(&parent_id);
+                        found_event = true;
+                        continue
+                    }
+
                     if let Some(layer_events) = received_events.get_mut(&parent.layer) {
                         layer_events.push(parent.clone());
                     } else {
@@ -399,7 +565,7 @@ impl EventGraph {
 
                         if !missing_parents.contains(upper_parent) &&
                             !received_events_hashes.contains(upper_parent) &&
-                            !self.dag.contains_key(upper_parent.as_bytes()).unwrap()
+                            !self.is_known(upper_parent)
                         {
                             debug!(
                                 target: "event_graph::dag_sync()",
@@ -407,6 +573,10 @@ impl EventGraph {
                             );
                             missing_parents.insert(*upper_parent);
                         }
+
+                        let child_layer =
+                            missing_parents_children.entry(*upper_parent).or_insert(parent.layer);
+                        *child_layer = (*child_layer).min(parent.layer);
                     }
                 }
 
@@ -414,6 +584,24 @@ impl EventGraph {
             }
 
             if !found_event {
+                // Peers might have already pruned the ancestors of the events
+                // we fetched. If retention is enabled, we do the same and keep
+                // them as checkpoint heads.
+                if self.days_retention > 0 &&
+                    missing_parents.iter().all(|p| missing_parents_children.contains_key(p))
+                {
+                    warn!(
+                        target: "event_graph::dag_sync()",
+                        "[EVENTGRAPH] Sync: Peers no longer have {} events, keeping them as checkpoints",
+                        missing_parents.len(),
+                    );
+                    for parent_id in missing_parents.drain() {
+                        let layer = missing_parents_children[&parent_id].saturating_sub(1);
+                        self.add_checkpoint(&parent_id, layer).await?;
+                    }
+                    break
+                }
+
                 error!(
                     target: "event_graph::dag_sync()",
                     "[EVENTGRAPH] Sync: Failed to get all events",
@@ -438,6 +626,100 @@ impl EventGraph {
         Ok(())
     }
 
+    /// Fetch the events we're missing in bulk from the first responsive peer,
+    /// by sending it a filter of the event IDs we already know. Rounds are
+    /// repeated until the peer has nothing more to send us. Received events
+    /// are noted down in the provided maps, to be inserted by `dag_sync()`.
+    async fn dag_sync_filter(
+        &self,
+        channels: &[ChannelPtr],
+        received_events: &mut BTreeMap<u64, Vec<Event>>,
+        received_events_hashes: &mut HashSet<blake3::Hash>,
+    ) -> Result<()> {
+        // Collect the IDs we know, including checkpoint heads
+        let mut known = vec![];
+        for key in self.dag.iter().keys().chain(self.checkpoints.iter().keys()) {
+            known.push(blake3::Hash::from_bytes((&key? as &[u8]).try_into().unwrap()));
+        }
+
+        let retention_cutoff = self.retention_cutoff();
+
+        'channels: for channel in channels.iter() {
+            let url = channel.address();
+
+            let filter_rep_sub = match channel.subscribe_msg::<FilterRep>().await {
+                Ok(v) => v,
+                Err(e) => {
+                    error!(
+                        target: "event_graph::dag_sync_filter()",
+                        "[EVENTGRAPH] Sync: Couldn't subscribe FilterRep for peer {}, skipping ({})",
+                        url, e,
+                    );
+                    continue
+                }
+            };
+
+            loop {
+                let mut filter = EventIdFilter::new(known.len() + received_events_hashes.len());
+                for id in known.iter().chain(received_events_hashes.iter()) {
+                    filter.insert(id);
+                }
+
+                if let Err(e) = channel.send(&FilterReq(filter)).await {
+                    error!(
+                        target: "event_graph::dag_sync_filter()",
+                        "[EVENTGRAPH] Sync: Couldn't contact peer {}, skipping ({})", url, e,
+                    );
+                    continue 'channels
+                }
+
+                let events = match timeout(REPLY_TIMEOUT, filter_rep_sub.receive()).await {
+                    Ok(Ok(rep)) => rep.0.clone(),
+                    _ => {
+                        error!(
+                            target: "event_graph::dag_sync_filter()",
+                            "[EVENTGRAPH] Sync: Peer {} didn't reply with events in time, skipping",
+                            url,
+                        );
+                        continue 'channels
+                    }
+                };
+
+                debug!(
+                    target: "event_graph::dag_sync_filter()",
+                    "Got {} events by filter from {}", events.len(), url,
+                );
+
+                let mut n_new = 0;
+                for event in events.iter() {
+                    let event_id = event.id();
+                    if received_events_hashes.contains(&event_id) || self.is_known(&event_id) {
+                        continue
+                    }
+
+                    // Events older than our retention become checkpoint heads
+                    if event.timestamp < retention_cutoff {
+                        self.add_checkpoint(&event_id, event.layer).await?;
+                        known.push(event_id);
+                        n_new += 1;
+                        continue
+                    }
+
+                    received_events.entry(event.layer).or_default().push(event.clone());
+                    received_events_hashes.insert(event_id);
+                    n_new += 1;
+                }
+
+                // A full reply means the peer might have more for us
+                if events.len() < MAX_FILTER_REP_EVENTS || n_new == 0 {
+                    return Ok(())
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Atomically prune the DAG and insert the given event as genesis.
     async fn dag_prune(&self, genesis_event: Event) -> Result<()> {
         debug!(target: "event_graph::dag_prune()", "Pruning DAG...");
@@ -463,6 +745,21 @@ impl EventGraph {
             panic!("Failed pruning DAG, sled apply_batch error: {}", e);
         }
 
+        // Same for the layer index
+        let mut layers_batch = sled::Batch::default();
+        for key in self.layers.iter().keys() {
+            layers_batch.remove(key.unwrap());
+        }
+        layers_batch.insert(Self::layer_key(0, &genesis_event.id()), vec![]);
+        if let Err(e) = self.layers.apply_batch(layers_batch) {
+            panic!("Failed pruning DAG layer index, sled apply_batch error: {}", e);
+        }
+
+        // Checkpoint heads refer to the previous DAG, so drop them as well
+        if let Err(e) = self.checkpoints.clear() {
+            panic!("Failed clearing DAG checkpoints, sled clear error: {}", e);
+        }
+
         // Clear unreferenced tips and bcast ids
         *unreferenced_tips = BTreeMap::new();
         unreferenced_tips.insert(0, HashSet::from([genesis_event.id()]));
@@ -505,6 +802,148 @@ impl EventGraph {
 
             // Trigger DAG prune
             self.dag_prune(current_genesis).await?;
+
+            if let Err(e) = self.attachments_gc().await {
+                error!(
+                    target: "event_graph::dag_prune_task()",
+                    "[EVENTGRAPH] Attachments garbage collection failed: {}", e,
+                );
+            }
+        }
+    }
+
+    /// Atomically prune the DAG events that are older than the configured
+    /// retention, replacing them with checkpoint heads so the retained
+    /// events referencing them stay valid. The unreferenced tips are
+    /// never pruned, so new events can always be built on top of them.
+    /// Returns the number of pruned events.
+    pub async fn dag_prune_old(&self) -> Result<usize> {
+        let cutoff = self.retention_cutoff();
+        if cutoff == 0 {
+            return Ok(0)
+        }
+        debug!(target: "event_graph::dag_prune_old()", "Pruning DAG events older than {}", cutoff);
+
+        // Acquire exclusive locks to unreferenced_tips and broadcasted_ids
+        // while this operation is happening, same as in `dag_prune()`.
+        let unreferenced_tips = self.unreferenced_tips.write().await;
+        let mut broadcasted_ids = self.broadcasted_ids.write().await;
+        let genesis_id = self.current_genesis.read().await.id();
+
+        let tips: HashSet<blake3::Hash> =
+            unreferenced_tips.values().flat_map(|tips| tips.iter().copied()).collect();
+
+        // Split the DAG into the events to prune and the ones we retain
+        let mut pruned: HashMap<blake3::Hash, u64> = HashMap::new();
+        let mut retained = vec![];
+        for iter_elem in self.dag.iter() {
+            let (id, event) = iter_elem?;
+            let id = blake3::Hash::from_bytes((&id as &[u8]).try_into().unwrap());
+            let event: Event = deserialize_async(&event).await?;
+            if event.timestamp < cutoff && !tips.contains(&id) {
+                pruned.insert(id, event.layer);
+            } else {
+                retained.push(event);
+            }
+        }
+
+        if pruned.is_empty() {
+            return Ok(0)
+        }
+
+        // The new checkpoint heads are the pruned events, or previous
+        // checkpoint heads, that are still referenced by retained events.
+        let mut checkpoints: HashMap<blake3::Hash, u64> = HashMap::new();
+        for event in retained.iter() {
+            for parent_id in event.parents.iter() {
+                if parent_id == &NULL_ID {
+                    continue
+                }
+
+                if let Some(layer) = pruned.get(parent_id) {
+                    checkpoints.insert(*parent_id, *layer);
+                    continue
+                }
+
+                if let Some(layer_bytes) = self.checkpoints.get(parent_id.as_bytes())? {
+                    checkpoints.insert(*parent_id, deserialize_async(&layer_bytes).await?);
+                }
+            }
+        }
+
+        // We always keep the genesis, so we know it's not missing on restart
+        if pruned.contains_key(&genesis_id) ||
+            self.checkpoints.contains_key(genesis_id.as_bytes())?
+        {
+            checkpoints.insert(genesis_id, 0);
+        }
+
+        let mut batch = sled::Batch::default();
+        for id in pruned.keys() {
+            batch.remove(id.as_bytes());
+        }
+
+        let mut checkpoints_batch = sled::Batch::default();
+        for key in self.checkpoints.iter().keys() {
+            checkpoints_batch.remove(key?);
+        }
+        for (id, layer) in checkpoints.iter() {
+            checkpoints_batch.insert(id.as_bytes(), serialize_async(layer).await);
+        }
+
+        // Write the checkpoint heads first, so a failure in between
+        // can't leave retained events with unknown parents.
+        debug!(target: "event_graph::dag_prune_old()", "Applying batches...");
+        if let Err(e) = self.checkpoints.apply_batch(checkpoints_batch) {
+            panic!("Failed writing DAG checkpoints, sled apply_batch error: {}", e);
+        }
+        if let Err(e) = self.dag.apply_batch(batch) {
+            panic!("Failed pruning old DAG events, sled apply_batch error: {}", e);
+        }
+
+        // Index entries of missing events are skipped, so the layer
+        // index can be updated last.
+        let mut layers_batch = sled::Batch::default();
+        for (id, layer) in pruned.iter() {
+            layers_batch.remove(Self::layer_key(*layer, id));
+        }
+        if let Err(e) = self.layers.apply_batch(layers_batch) {
+            panic!("Failed pruning DAG layer index, sled apply_batch error: {}", e);
+        }
+
+        // We can't serve the pruned events anymore
+        broadcasted_ids.retain(|id| !pruned.contains_key(id));
+        drop(unreferenced_tips);
+        drop(broadcasted_ids);
+
+        info!(
+            target: "event_graph::dag_prune_old()",
+            "[EVENTGRAPH] Pruned {} old events, keeping {} checkpoint heads",
+            pruned.len(), checkpoints.len(),
+        );
+        Ok(pruned.len())
+    }
+
+    /// Background task periodically pruning the DAG events older than
+    /// the configured retention.
+    async fn dag_retention_task(self: Arc<Self>) -> Result<()> {
+        debug!(target: "event_graph::dag_retention_task()", "Spawned background DAG retention task");
+
+        loop {
+            if self.dag_prune_old().await? > 0 {
+                if let Err(e) = self.attachments_gc().await {
+                    error!(
+                        target: "event_graph::dag_retention_task()",
+                        "[EVENTGRAPH] Attachments garbage collection failed: {}", e,
+                    );
+                }
+            }
+
+            debug!(
+                target: "event_graph::dag_retention_task()",
+                "Sleeping {}s until next retention prune", RETENTION_PRUNE_INTERVAL,
+            );
+            sleep(RETENTION_PRUNE_INTERVAL).await;
         }
     }
 
@@ -547,7 +986,13 @@ impl EventGraph {
             );
 
             if !event
-                .validate(&self.dag, genesis_timestamp, self.days_rotation, Some(&overlay))
+                .validate(
+                    &self.dag,
+                    &self.checkpoints,
+                    genesis_timestamp,
+                    self.days_rotation,
+                    Some(&overlay),
+                )
                 .await?
             {
                 error!(target: "event_graph::dag_insert()", "Event {} is invalid!", event_id);
@@ -566,8 +1011,18 @@ impl EventGraph {
         // Aggregate changes into a single batch
         let batch = overlay.aggregate().unwrap();
 
-        // Atomically apply the batch.
+        // Index the events by their layer first, since index entries of
+        // events missing from the DAG are skipped.
+        let mut layers_batch = sled::Batch::default();
+        for (event, event_id) in events.iter().zip(ids.iter()) {
+            layers_batch.insert(Self::layer_key(event.layer, event_id), vec![]);
+        }
+
+        // Atomically apply the batches.
         // Panic if something is corrupted.
+        if let Err(e) = self.layers.apply_batch(layers_batch) {
+            panic!("Failed applying dag_insert layer index batch to sled: {}", e);
+        }
         if let Err(e) = self.dag.apply_batch(batch) {
             panic!("Failed applying dag_insert batch to sled: {}", e);
         }
@@ -655,6 +1110,204 @@ impl EventGraph {
         Ok(Some(event))
     }
 
+    /// Fetch the events from the DAG that are not in the given filter,
+    /// ordered by their layer, up to the given limit. The layer index
+    /// is walked in order, so we stop as soon as the limit is reached.
+    pub async fn dag_missing_events(
+        &self,
+        filter: &EventIdFilter,
+        limit: usize,
+    ) -> Result<Vec<Event>> {
+        let mut events = vec![];
+        for key in self.layers.iter().keys() {
+            if events.len() >= limit {
+                break
+            }
+
+            let key = key?;
+            let id = blake3::Hash::from_bytes((&key[8..]).try_into().unwrap());
+            if filter.contains(&id) {
+                continue
+            }
+
+            let Some(event) = self.dag.get(id.as_bytes())? else { continue };
+            events.push(deserialize_async::<Event>(&event).await?);
+        }
+
+        Ok(events)
+    }
+
+    /// Enable attachments, stored in the given Geode instance. It should
+    /// be dedicated to this event graph, since garbage collection removes
+    /// anything not referenced by the DAG.
+    pub async fn enable_attachments(&self, geode: Geode) {
+        let _ = self.attachments.set(geode).await;
+    }
+
+    /// Store the given data as an attachment, so an event can reference it.
+    /// Once the event is created, it should be noted down with
+    /// `attachment_reference()` to keep the attachment from being
+    /// garbage collected.
+    pub async fn attachment_insert(&self, data: &[u8]) -> Result<Attachment> {
+        let Some(geode) = self.attachments.get() else { return Err(Error::AttachmentsDisabled) };
+        if data.is_empty() {
+            return Err(Error::AttachmentInvalid)
+        }
+
+        if data.len() > MAX_ATTACHMENT_SIZE {
+            return Err(Error::AttachmentTooLarge)
+        }
+
+        let (file_hash, chunks) = geode.insert(Cursor::new(data)).await?;
+        Ok(Attachment { file_hash, size: data.len() as u64, chunks })
+    }
+
+    /// Note down that the given event references the given attachment.
+    pub async fn attachment_reference(
+        &self,
+        event_id: &blake3::Hash,
+        attachment: &Attachment,
+    ) -> Result<()> {
+        let key = attachment.file_hash.as_bytes();
+        let mut refs: HashSet<blake3::Hash> = match self.attachment_refs.get(key)? {
+            Some(refs) => deserialize_async(&refs).await?,
+            None => HashSet::new(),
+        };
+
+        if refs.insert(*event_id) {
+            self.attachment_refs.insert(key, serialize_async(&refs).await)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the contents of an attachment referenced by the given event.
+    /// The chunks we don't have are requested from our peers.
+    pub async fn attachment_fetch(
+        &self,
+        event_id: &blake3::Hash,
+        attachment: &Attachment,
+    ) -> Result<Vec<u8>> {
+        let Some(geode) = self.attachments.get() else { return Err(Error::AttachmentsDisabled) };
+        if !attachment.is_valid() {
+            return Err(Error::AttachmentInvalid)
+        }
+
+        self.attachment_reference(event_id, attachment).await?;
+
+        let chunked_file = match geode.get(&attachment.file_hash).await {
+            Ok(v) => v,
+            Err(Error::GeodeFileNotFound) => {
+                geode.insert_file(&attachment.file_hash, &attachment.chunks).await?;
+                geode.get(&attachment.file_hash).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Request the chunks we're missing
+        for (chunk_hash, chunk_path) in chunked_file.iter() {
+            if chunk_path.is_some() {
+                continue
+            }
+
+            let chunk = self.attachment_request_chunk(chunk_hash).await?;
+            geode.insert_chunk(&chunk).await?;
+        }
+
+        // Assemble the contents and make sure they're what the event refers to
+        let mut data = Vec::with_capacity(attachment.size as usize);
+        for chunk_hash in attachment.chunks.iter() {
+            let chunk_path = geode.get_chunk(chunk_hash).await?;
+            data.append(&mut smol::fs::read(chunk_path).await?);
+        }
+
+        if data.len() as u64 != attachment.size || blake3::hash(&data) != attachment.file_hash {
+            return Err(Error::AttachmentInvalid)
+        }
+
+        Ok(data)
+    }
+
+    /// Request an attachment chunk from our peers, returning the first
+    /// valid reply.
+    async fn attachment_request_chunk(&self, chunk_hash: &blake3::Hash) -> Result<Vec<u8>> {
+        for channel in self.p2p.hosts().channels().await.iter() {
+            let url = channel.address();
+
+            let chunk_rep_sub = match channel.subscribe_msg::<ChunkRep>().await {
+                Ok(v) => v,
+                Err(e) => {
+                    error!(
+                        target: "event_graph::attachment_request_chunk()",
+                        "[EVENTGRAPH] Couldn't subscribe ChunkRep for peer {}, skipping ({})",
+                        url, e,
+                    );
+                    continue
+                }
+            };
+
+            if let Err(e) = channel.send(&ChunkReq(*chunk_hash)).await {
+                error!(
+                    target: "event_graph::attachment_request_chunk()",
+                    "[EVENTGRAPH] Couldn't contact peer {}, skipping ({})", url, e,
+                );
+                continue
+            }
+
+            let chunk = match timeout(REPLY_TIMEOUT, chunk_rep_sub.receive()).await {
+                Ok(Ok(rep)) => rep.0.clone(),
+                _ => {
+                    debug!(
+                        target: "event_graph::attachment_request_chunk()",
+                        "Peer {} didn't reply with chunk {} in time", url, chunk_hash,
+                    );
+                    continue
+                }
+            };
+
+            if &blake3::hash(&chunk) == chunk_hash {
+                return Ok(chunk)
+            }
+
+            debug!(
+                target: "event_graph::attachment_request_chunk()",
+                "Peer {} doesn't have chunk {}", url, chunk_hash,
+            );
+        }
+
+        Err(Error::GeodeChunkNotFound)
+    }
+
+    /// Drop the attachment references of events no longer in the DAG, and
+    /// remove the attachments and chunks no event references anymore.
+    pub async fn attachments_gc(&self) -> Result<()> {
+        let Some(geode) = self.attachments.get() else { return Ok(()) };
+
+        let mut keep = HashSet::new();
+        for iter_elem in self.attachment_refs.iter() {
+            let (file_hash, refs) = iter_elem?;
+            let mut refs: HashSet<blake3::Hash> = deserialize_async(&refs).await?;
+            refs.retain(|id| self.dag.contains_key(id.as_bytes()).unwrap_or(true));
+
+            if refs.is_empty() {
+                self.attachment_refs.remove(&file_hash)?;
+                continue
+            }
+
+            self.attachment_refs.insert(&file_hash, serialize_async(&refs).await)?;
+            keep.insert(blake3::Hash::from_bytes((&file_hash as &[u8]).try_into().unwrap()));
+        }
+
+        let (files, chunks) = geode.retain(&keep).await?;
+        info!(
+            target: "event_graph::attachments_gc()",
+            "[EVENTGRAPH] Removed {} unreferenced attachments ({} chunks)",
+            files.len(), chunks.len(),
+        );
+
+        Ok(())
+    }
+
     /// Get next layer along with its N_EVENT_PARENTS from the unreferenced
     /// tips of the DAG. Since tips are mapped by their layer, we go backwards
     /// until we fill the vector, ensuring we always use latest layers tips as
@@ -769,7 +1422,8 @@ impl EventGraph {
 
         for parent_id in event.parents.iter() {
             if !visited.contains(parent_id) && parent_id != &NULL_ID {
-                let p_event = self.dag_get(parent_id).await.unwrap().unwrap();
+                // Pruned parents are not part of the ordering
+                let Some(p_event) = self.dag_get(parent_id).await.unwrap() else { continue };
                 self.dfs_topological_sort(p_event, visited, ordered_events).await;
             }
         }
@@ -886,4 +1540,138 @@ impl EventGraph {
 
         JsonResponse::new(result, id).into()
     }
+
+    /// Collect the current DAG state for inspection: the heads the next
+    /// event would reference, the DAG depth, the unreferenced tips and
+    /// the number of events per layer, along with the checkpoint heads.
+    async fn dag_state(&self) -> Result<JsonValue> {
+        let mut layers: BTreeMap<u64, usize> = BTreeMap::new();
+        for iter_elem in self.dag.iter() {
+            let (_, event) = iter_elem?;
+            let event: Event = deserialize_async(&event).await?;
+            *layers.entry(event.layer).or_default() += 1;
+        }
+
+        let mut checkpoints = HashMap::new();
+        for iter_elem in self.checkpoints.iter() {
+            let (id, layer) = iter_elem?;
+            let id = blake3::Hash::from_bytes((&id as &[u8]).try_into().unwrap());
+            let layer: u64 = deserialize_async(&layer).await?;
+            checkpoints.insert(id.to_string(), JsonValue::Number(layer as f64));
+        }
+
+        let (_, heads) = self.get_next_layer_with_parents().await;
+        let heads = heads
+            .iter()
+            .filter(|id| *id != &NULL_ID)
+            .map(|id| JsonValue::String(id.to_string()))
+            .collect();
+
+        let unreferenced_tips = self
+            .unreferenced_tips
+            .read()
+            .await
+            .iter()
+            .map(|(layer, tips)| {
+                let tips = tips.iter().map(|id| JsonValue::String(id.to_string())).collect();
+                (layer.to_string(), JsonValue::Array(tips))
+            })
+            .collect();
+
+        let depth = layers.last_key_value().map(|(layer, _)| *layer).unwrap_or(0);
+        let n_events = layers.values().sum::<usize>();
+        let layers = layers
+            .into_iter()
+            .map(|(layer, count)| (layer.to_string(), JsonValue::Number(count as f64)))
+            .collect();
+
+        Ok(json_map([
+            (
+                "current_genesis",
+                JsonValue::String(self.current_genesis.read().await.id().to_string()),
+            ),
+            ("synced", JsonValue::Boolean(*self.synced.read().await)),
+            ("depth", JsonValue::Number(depth as f64)),
+            ("n_events", JsonValue::Number(n_events as f64)),
+            ("heads", JsonValue::Array(heads)),
+            ("unreferenced_tips", JsonValue::Object(unreferenced_tips)),
+            ("layers", JsonValue::Object(layers)),
+            ("checkpoints", JsonValue::Object(checkpoints)),
+        ]))
+    }
+
+    /// JSON-RPC handler returning the output of `dag_state()`.
+    pub async fn eventgraph_state(&self, id: u16, _params: JsonValue) -> JsonResult {
+        match self.dag_state().await {
+            Ok(state) => JsonResponse::new(state, id).into(),
+            Err(e) => {
+                error!(target: "event_graph::eventgraph_state()", "Failed reading DAG state: {}", e);
+                JsonError::new(ErrorCode::InternalError, None, id).into()
+            }
+        }
+    }
+
+    /// Fetch an event from the DAG along with its parents. Parents that
+    /// were pruned by retention are returned with their checkpoint layer,
+    /// and unknown ones as `null`. Returns `null` if we don't have the event.
+    async fn dag_get_with_parents(&self, event_id: &blake3::Hash) -> Result<JsonValue> {
+        let Some(event) = self.dag_get(event_id).await? else { return Ok(JsonValue::Null) };
+
+        let mut parents = vec![];
+        for parent_id in event.parents.iter() {
+            if parent_id == &NULL_ID {
+                continue
+            }
+
+            let parent = match self.dag_get(parent_id).await? {
+                Some(parent) => JsonValue::from(parent),
+                None => match self.checkpoints.get(parent_id.as_bytes())? {
+                    Some(layer) => {
+                        let layer: u64 = deserialize_async(&layer).await?;
+                        json_map([("checkpoint", JsonValue::Number(layer as f64))])
+                    }
+                    None => JsonValue::Null,
+                },
+            };
+
+            parents.push(json_map([
+                ("id", JsonValue::String(parent_id.to_string())),
+                ("event", parent),
+            ]));
+        }
+
+        Ok(json_map([
+            ("id", JsonValue::String(event_id.to_string())),
+            ("event", JsonValue::from(event)),
+            ("parents", JsonValue::Array(parents)),
+        ]))
+    }
+
+    /// JSON-RPC handler returning the output of `dag_get_with_parents()`
+    /// for the hex-encoded event ID given in `params`.
+    pub async fn eventgraph_get_event(&self, id: u16, params: JsonValue) -> JsonResult {
+        let Some(params) = params.get::<Vec<JsonValue>>() else {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        };
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        }
+
+        let Ok(event_id) = blake3::Hash::from_hex(params[0].get::<String>().unwrap()) else {
+            return JsonError::new(
+                ErrorCode::InvalidParams,
+                Some("Invalid event ID".to_string()),
+                id,
+            )
+            .into()
+        };
+
+        match self.dag_get_with_parents(&event_id).await {
+            Ok(event) => JsonResponse::new(event, id).into(),
+            Err(e) => {
+                error!(target: "event_graph::eventgraph_get_event()", "Failed reading event: {}", e);
+                JsonError::new(ErrorCode::InternalError, None, id).into()
+            }
+        }
+    }
 }
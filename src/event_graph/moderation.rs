@@ -0,0 +1,233 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Signed moderation actions for event graph apps.
+//!
+//! Moderation actions are regular events whose content starts with
+//! [`MODERATION_MAGIC`]. The P2P protocol doesn't treat them in any
+//! special way, it is up to the apps to collect them in a
+//! [`ModerationView`] and apply the ones signed by the operators they
+//! trust when materializing their views of the DAG.
+
+use std::collections::HashMap;
+
+use darkfi_serial::{
+    async_trait, deserialize, serialize, Encodable, SerialDecodable, SerialEncodable,
+};
+
+/// Prefix of event contents carrying a moderation action
+pub const MODERATION_MAGIC: &[u8; 8] = b"DFMODACT";
+
+/// Signature domain of moderation actions
+const MODERATION_DOMAIN: &[u8] = b"DarkFi:EventGraphModeration";
+
+/// Kind of a moderation action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub enum ModerationKind {
+    /// Hide the event with the hex-encoded ID given as target
+    Hide = 0x00,
+    /// Ban the app-defined identity given as target, e.g. a nickname
+    Ban = 0x01,
+    /// Lift a ban of the identity given as target
+    Unban = 0x02,
+}
+
+/// A moderation action signed by an operator
+#[derive(Clone, Debug, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub struct Moderation {
+    /// Scope the action applies to, e.g. a channel name
+    pub scope: String,
+    /// Kind of the action
+    pub kind: ModerationKind,
+    /// Target of the action, see [`ModerationKind`]
+    pub target: String,
+    /// Creation timestamp, used to order actions on the same target
+    pub timestamp: u64,
+    /// Ed25519 public key of the operator
+    pub operator: [u8; 32],
+    /// Operator's signature over the above fields
+    pub signature: [u8; 64],
+}
+
+impl Moderation {
+    /// Create and sign a new moderation action with the given operator keypair.
+    pub fn new(
+        scope: &str,
+        kind: ModerationKind,
+        target: &str,
+        timestamp: u64,
+        keypair: &ed25519_compact::KeyPair,
+    ) -> Self {
+        let message = Self::signed_message(scope, kind, target, timestamp);
+        let signature = keypair.sk.sign(message, None);
+
+        Self {
+            scope: scope.to_string(),
+            kind,
+            target: target.to_string(),
+            timestamp,
+            operator: *keypair.pk,
+            signature: *signature,
+        }
+    }
+
+    fn signed_message(scope: &str, kind: ModerationKind, target: &str, timestamp: u64) -> Vec<u8> {
+        let mut message = MODERATION_DOMAIN.to_vec();
+        scope.encode(&mut message).unwrap();
+        kind.encode(&mut message).unwrap();
+        target.encode(&mut message).unwrap();
+        timestamp.encode(&mut message).unwrap();
+        message
+    }
+
+    /// Verify the operator's signature of the action.
+    pub fn verify(&self) -> bool {
+        let Ok(operator) = ed25519_compact::PublicKey::from_slice(&self.operator) else {
+            return false
+        };
+        let signature = ed25519_compact::Signature::new(self.signature);
+        let message = Self::signed_message(&self.scope, self.kind, &self.target, self.timestamp);
+        operator.verify(message, &signature).is_ok()
+    }
+
+    /// Encode the action as event content.
+    pub fn to_content(&self) -> Vec<u8> {
+        let mut content = MODERATION_MAGIC.to_vec();
+        content.append(&mut serialize(self));
+        content
+    }
+
+    /// Decode an action from event content.
+    /// Returns `None` if the content is not a moderation action.
+    pub fn from_content(content: &[u8]) -> Option<Self> {
+        let data = content.strip_prefix(MODERATION_MAGIC)?;
+        deserialize(data).ok()
+    }
+}
+
+/// Collection of verified moderation actions, used by apps to decide
+/// what to show. The operators of a scope are given on lookups, so they
+/// can be reconfigured without collecting the actions again.
+#[derive(Debug, Default)]
+pub struct ModerationView {
+    /// Moderation actions by scope, ordered by timestamp
+    actions: HashMap<String, Vec<Moderation>>,
+}
+
+impl ModerationView {
+    /// Create a new empty [`ModerationView`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a moderation action to the view. Returns `false` if its
+    /// signature is invalid or if we already have it.
+    pub fn insert(&mut self, moderation: Moderation) -> bool {
+        if !moderation.verify() {
+            return false
+        }
+
+        let actions = self.actions.entry(moderation.scope.clone()).or_default();
+        if actions.contains(&moderation) {
+            return false
+        }
+
+        let index = actions.partition_point(|a| a.timestamp <= moderation.timestamp);
+        actions.insert(index, moderation);
+        true
+    }
+
+    /// Check if an event was hidden in the given scope by any of the operators.
+    pub fn is_hidden(&self, scope: &str, event_id: &blake3::Hash, operators: &[[u8; 32]]) -> bool {
+        let Some(actions) = self.actions.get(scope) else { return false };
+        let event_id = event_id.to_string();
+
+        actions.iter().any(|a| {
+            a.kind == ModerationKind::Hide &&
+                a.target == event_id &&
+                operators.contains(&a.operator)
+        })
+    }
+
+    /// Check if an identity was banned in the given scope at the given
+    /// time, i.e. if the latest ban or unban of it by any of the operators
+    /// up to that time is a ban. Messages sent before a ban stay visible.
+    pub fn is_banned(&self, scope: &str, target: &str, at: u64, operators: &[[u8; 32]]) -> bool {
+        let Some(actions) = self.actions.get(scope) else { return false };
+
+        actions
+            .iter()
+            .rev()
+            .skip_while(|a| a.timestamp > at)
+            .find(|a| {
+                a.kind != ModerationKind::Hide &&
+                    a.target == target &&
+                    operators.contains(&a.operator)
+            })
+            .is_some_and(|a| a.kind == ModerationKind::Ban)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moderation_view() {
+        let operator = ed25519_compact::KeyPair::generate();
+        let stranger = ed25519_compact::KeyPair::generate();
+        let operators = [*operator.pk];
+
+        let event_id = blake3::hash(b"spam");
+        let hide =
+            Moderation::new("#dev", ModerationKind::Hide, &event_id.to_string(), 1, &operator);
+        let ban = Moderation::new("#dev", ModerationKind::Ban, "spammer", 2, &operator);
+        let unban = Moderation::new("#dev", ModerationKind::Unban, "spammer", 3, &operator);
+        let fake = Moderation::new("#dev", ModerationKind::Ban, "anon", 4, &stranger);
+
+        // Actions survive the roundtrip through event content
+        let content = ban.to_content();
+        assert_eq!(Moderation::from_content(&content), Some(ban.clone()));
+        assert_eq!(Moderation::from_content(b"not a moderation action"), None);
+
+        // Tampered actions are rejected
+        let mut tampered = ban.clone();
+        tampered.target = "anon".to_string();
+        assert!(!tampered.verify());
+
+        let mut view = ModerationView::new();
+        assert!(view.insert(hide.clone()));
+        assert!(!view.insert(hide));
+        assert!(!view.insert(tampered));
+        assert!(view.insert(fake));
+
+        assert!(view.is_hidden("#dev", &event_id, &operators));
+        assert!(!view.is_hidden("#random", &event_id, &operators));
+        assert!(!view.is_hidden("#dev", &event_id, &[*stranger.pk]));
+
+        // Unknown operators don't count
+        assert!(!view.is_banned("#dev", "anon", 5, &operators));
+
+        // The latest action wins, regardless of insertion order
+        assert!(view.insert(unban));
+        assert!(view.insert(ban));
+        assert!(!view.is_banned("#dev", "spammer", 1, &operators));
+        assert!(view.is_banned("#dev", "spammer", 2, &operators));
+        assert!(!view.is_banned("#dev", "spammer", 3, &operators));
+    }
+}
@@ -19,24 +19,24 @@
 // TODO: FIXME: Some of the protocols should block operations until DAG is synced.
 
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{
         atomic::{AtomicUsize, Ordering::SeqCst},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use darkfi_serial::{async_trait, SerialDecodable, SerialEncodable};
 use log::{debug, error, trace, warn};
 use smol::Executor;
 
-use super::{Event, EventGraphPtr, NULL_ID};
+use super::{bloom::EventIdFilter, Event, EventGraphPtr, NULL_ID};
 use crate::{
     event_graph::{deg, deg::degev, DegEvent},
     impl_p2p_message,
     net::*,
-    system::timeout::timeout,
+    system::{msleep, timeout::timeout},
     util::time::NanoTimestamp,
     Error, Result,
 };
@@ -46,6 +46,58 @@ use crate::{
 const MALICIOUS_THRESHOLD: usize = 5;
 /// Time to wait for a parent ID reply
 pub(super) const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximum number of events sent in a single `FilterRep`
+pub(super) const MAX_FILTER_REP_EVENTS: usize = 1000;
+/// Rate limit of the `TipReq`s we answer a peer, as (requests per second, burst)
+const TIP_REQ_RATE_LIMIT: (f64, f64) = (1.0, 5.0);
+/// Rate limit of the `FilterReq`s we answer a peer. Each one makes us walk
+/// the DAG and reply with up to `MAX_FILTER_REP_EVENTS` events.
+const FILTER_REQ_RATE_LIMIT: (f64, f64) = (1.0, 5.0);
+/// Rate limit of the `ChunkReq`s we answer a peer
+const CHUNK_REQ_RATE_LIMIT: (f64, f64) = (20.0, 100.0);
+
+/// Token bucket limiting how often we answer a kind of request of a peer.
+/// Each handler owns its bucket, so no synchronization is needed. Requests
+/// over the limit are delayed rather than dropped, so honest peers syncing
+/// a large DAG just get slower replies, while a flooding peer can't make
+/// us do more work than the limit allows.
+struct RequestBucket {
+    /// Tokens refilled per second
+    rate: f64,
+    /// Maximum number of tokens in the bucket
+    burst: f64,
+    /// Tokens currently in the bucket
+    tokens: f64,
+    /// Last time the bucket got refilled
+    last: Instant,
+}
+
+impl RequestBucket {
+    fn new((rate, burst): (f64, f64)) -> Self {
+        Self { rate, burst, tokens: burst, last: Instant::now() }
+    }
+
+    /// Refill the bucket
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = now;
+    }
+
+    /// Take a token from the bucket, waiting for one to be refilled if
+    /// it's empty
+    async fn take(&mut self) {
+        self.refill();
+        if self.tokens < 1.0 {
+            let wait = (1.0 - self.tokens) / self.rate;
+            msleep((wait * 1000.0).ceil() as u64).await;
+            self.refill();
+        }
+
+        self.tokens = (self.tokens - 1.0).max(0.0);
+    }
+}
 
 /// P2P protocol implementation for the Event Graph.
 pub struct ProtocolEventGraph {
@@ -63,6 +115,10 @@ pub struct ProtocolEventGraph {
     tip_req_sub: MessageSubscription<TipReq>,
     /// `MessageSubscriber` for `TipRep`
     _tip_rep_sub: MessageSubscription<TipRep>,
+    /// `MessageSubscriber` for `FilterReq`
+    filter_req_sub: MessageSubscription<FilterReq>,
+    /// `MessageSubscriber` for `ChunkReq`
+    chunk_req_sub: MessageSubscription<ChunkReq>,
     /// Peer malicious message count
     malicious_count: AtomicUsize,
     /// P2P jobs manager pointer
@@ -94,6 +150,29 @@ impl_p2p_message!(TipReq, "EventGraph::TipReq");
 pub struct TipRep(pub BTreeMap<u64, HashSet<blake3::Hash>>);
 impl_p2p_message!(TipRep, "EventGraph::TipRep");
 
+/// A P2P message representing a request for the events a peer has,
+/// that are not in the given filter of the event IDs we know
+#[derive(Clone, SerialEncodable, SerialDecodable)]
+pub struct FilterReq(pub EventIdFilter);
+impl_p2p_message!(FilterReq, "EventGraph::FilterReq");
+
+/// A P2P message representing a reply with the events not in the
+/// requested filter, ordered by their layer
+#[derive(Clone, SerialEncodable, SerialDecodable)]
+pub struct FilterRep(pub Vec<Event>);
+impl_p2p_message!(FilterRep, "EventGraph::FilterRep");
+
+/// A P2P message representing a request for an attachment chunk
+#[derive(Clone, SerialEncodable, SerialDecodable)]
+pub struct ChunkReq(pub blake3::Hash);
+impl_p2p_message!(ChunkReq, "EventGraph::ChunkReq");
+
+/// A P2P message representing a reply with an attachment chunk,
+/// empty if we don't have it
+#[derive(Clone, SerialEncodable, SerialDecodable)]
+pub struct ChunkRep(pub Vec<u8>);
+impl_p2p_message!(ChunkRep, "EventGraph::ChunkRep");
+
 #[async_trait]
 impl ProtocolBase for ProtocolEventGraph {
     async fn start(self: Arc<Self>, ex: Arc<Executor<'_>>) -> Result<()> {
@@ -101,6 +180,8 @@ impl ProtocolBase for ProtocolEventGraph {
         self.jobsman.clone().spawn(self.clone().handle_event_put(), ex.clone()).await;
         self.jobsman.clone().spawn(self.clone().handle_event_req(), ex.clone()).await;
         self.jobsman.clone().spawn(self.clone().handle_tip_req(), ex.clone()).await;
+        self.jobsman.clone().spawn(self.clone().handle_filter_req(), ex.clone()).await;
+        self.jobsman.clone().spawn(self.clone().handle_chunk_req(), ex.clone()).await;
         Ok(())
     }
 
@@ -117,12 +198,18 @@ impl ProtocolEventGraph {
         msg_subsystem.add_dispatch::<EventRep>().await;
         msg_subsystem.add_dispatch::<TipReq>().await;
         msg_subsystem.add_dispatch::<TipRep>().await;
+        msg_subsystem.add_dispatch::<FilterReq>().await;
+        msg_subsystem.add_dispatch::<FilterRep>().await;
+        msg_subsystem.add_dispatch::<ChunkReq>().await;
+        msg_subsystem.add_dispatch::<ChunkRep>().await;
 
         let ev_put_sub = channel.subscribe_msg::<EventPut>().await?;
         let ev_req_sub = channel.subscribe_msg::<EventReq>().await?;
         let ev_rep_sub = channel.subscribe_msg::<EventRep>().await?;
         let tip_req_sub = channel.subscribe_msg::<TipReq>().await?;
         let _tip_rep_sub = channel.subscribe_msg::<TipRep>().await?;
+        let filter_req_sub = channel.subscribe_msg::<FilterReq>().await?;
+        let chunk_req_sub = channel.subscribe_msg::<ChunkReq>().await?;
 
         Ok(Arc::new(Self {
             channel: channel.clone(),
@@ -132,6 +219,8 @@ impl ProtocolEventGraph {
             ev_rep_sub,
             tip_req_sub,
             _tip_rep_sub,
+            filter_req_sub,
+            chunk_req_sub,
             malicious_count: AtomicUsize::new(0),
             jobsman: ProtocolJobsManager::new("ProtocolEventGraph", channel.clone()),
         }))
@@ -231,7 +320,7 @@ impl ProtocolEventGraph {
                     continue
                 }
 
-                if !self.event_graph.dag.contains_key(parent_id.as_bytes()).unwrap() {
+                if !self.event_graph.is_known(parent_id) {
                     missing_parents.insert(*parent_id);
                 }
             }
@@ -248,6 +337,13 @@ impl ProtocolEventGraph {
                 let mut received_events: BTreeMap<u64, Vec<Event>> = BTreeMap::new();
                 let mut received_events_hashes = HashSet::new();
 
+                // Events older than our retention are not inserted, and instead
+                // become checkpoint heads for the events referencing them.
+                let retention_cutoff = self.event_graph.retention_cutoff();
+                // Lowest layer of the events referencing each missing parent
+                let mut missing_parents_children: HashMap<blake3::Hash, u64> =
+                    missing_parents.iter().map(|p| (*p, event.layer)).collect();
+
                 debug!(
                     target: "event_graph::protocol::handle_event_put()",
                     "Event has {} missing parents. Requesting...", missing_parents.len(),
@@ -277,6 +373,21 @@ impl ProtocolEventGraph {
                     };
                     let parents = parents.0.clone();
 
+                    // The peer might have already pruned the requested parents.
+                    // If retention is enabled, we do the same and keep them as
+                    // checkpoint heads.
+                    if parents.is_empty() && self.event_graph.days_retention() > 0 {
+                        debug!(
+                            target: "event_graph::protocol::handle_event_put()",
+                            "Peer no longer has {:?}, keeping them as checkpoints", missing_parents,
+                        );
+                        for parent_id in missing_parents.drain() {
+                            let layer = missing_parents_children[&parent_id].saturating_sub(1);
+                            self.event_graph.add_checkpoint(&parent_id, layer).await?;
+                        }
+                        break
+                    }
+
                     for parent in parents {
                         let parent_id = parent.id();
                         if !missing_parents.contains(&parent_id) {
@@ -294,6 +405,12 @@ impl ProtocolEventGraph {
                             "Got correct parent event {}", parent.id(),
                         );
 
+                        if parent.timestamp < retention_cutoff {
+                            self.event_graph.add_checkpoint(&parent_id, parent.layer).await?;
+                            missing_parents.remove(&parent_id);
+                            continue
+                        }
+
                         if let Some(layer_events) = received_events.get_mut(&parent.layer) {
                             layer_events.push(parent.clone());
                         } else {
@@ -312,11 +429,7 @@ impl ProtocolEventGraph {
 
                             if !missing_parents.contains(upper_parent) &&
                                 !received_events_hashes.contains(upper_parent) &&
-                                !self
-                                    .event_graph
-                                    .dag
-                                    .contains_key(upper_parent.as_bytes())
-                                    .unwrap()
+                                !self.event_graph.is_known(upper_parent)
                             {
                                 debug!(
                                     target: "event_graph::protocol::handle_event_put()",
@@ -324,6 +437,11 @@ impl ProtocolEventGraph {
                                 );
                                 missing_parents.insert(*upper_parent);
                             }
+
+                            let child_layer = missing_parents_children
+                                .entry(*upper_parent)
+                                .or_insert(parent.layer);
+                            *child_layer = (*child_layer).min(parent.layer);
                         }
                     }
                 } // <-- while !missing_parents.is_empty()
@@ -431,13 +549,21 @@ impl ProtocolEventGraph {
                     continue
                 }
 
-                // At this point we should have it in our DAG.
-                // This code panics if this is not the case.
+                // At this point we should have it in our DAG, unless it
+                // got pruned by retention, in which case we just omit it.
                 debug!(
                     target: "event_graph::protocol::handle_event_req()",
                     "Fetching event {:?} from DAG", event_id,
                 );
-                events.push(self.event_graph.dag_get(event_id).await.unwrap().unwrap());
+                match self.event_graph.dag_get(event_id).await? {
+                    Some(event) => events.push(event),
+                    None => {
+                        debug!(
+                            target: "event_graph::protocol::handle_event_req()",
+                            "Event {:?} has been pruned", event_id,
+                        );
+                    }
+                }
             }
 
             // Check if the incoming event is older than the genesis event. If so, something
@@ -486,6 +612,7 @@ impl ProtocolEventGraph {
     /// This is triggered when someone requests the current unreferenced
     /// tips of our DAG.
     async fn handle_tip_req(self: Arc<Self>) -> Result<()> {
+        let mut bucket = RequestBucket::new(TIP_REQ_RATE_LIMIT);
         loop {
             self.tip_req_sub.receive().await?;
             degev!(self, RecvMessage, {
@@ -507,7 +634,7 @@ impl ProtocolEventGraph {
                 continue
             }
 
-            // TODO: Rate limit
+            bucket.take().await;
 
             // We received a tip request. Let's find them, add them to
             // our bcast ids list, and reply with them.
@@ -536,4 +663,108 @@ impl ProtocolEventGraph {
             self.channel.send(&TipRep(layers)).await?;
         }
     }
+
+    /// Protocol function handling `FilterReq`.
+    /// This is triggered when a syncing peer asks for the events it is
+    /// missing, telling us the ones it already knows with a filter.
+    async fn handle_filter_req(self: Arc<Self>) -> Result<()> {
+        let mut bucket = RequestBucket::new(FILTER_REQ_RATE_LIMIT);
+        loop {
+            let filter = match self.filter_req_sub.receive().await {
+                Ok(v) => v.0.clone(),
+                Err(_) => continue,
+            };
+            degev!(self, RecvMessage, {
+                info: vec![],
+                cmd: "FilterReq".to_string(),
+                time: NanoTimestamp::current_time(),
+            });
+            trace!(
+                target: "event_graph::protocol::handle_filter_req()",
+                "Got FilterReq [{}]", self.channel.address(),
+            );
+
+            // Check if node has finished syncing its DAG
+            if !*self.event_graph.synced.read().await {
+                debug!(
+                    target: "event_graph::protocol::handle_filter_req()",
+                    "DAG is still syncing, skipping..."
+                );
+                continue
+            }
+
+            bucket.take().await;
+
+            if !filter.is_valid() {
+                self.clone().increase_malicious_count().await?;
+                continue
+            }
+
+            let events =
+                self.event_graph.dag_missing_events(&filter, MAX_FILTER_REP_EVENTS).await?;
+
+            // The peer will request the parents it is missing because of
+            // filter false positives, so note them down as legitimate.
+            let mut bcast_ids = self.event_graph.broadcasted_ids.write().await;
+            for event in events.iter() {
+                for parent_id in event.parents.iter() {
+                    if parent_id != &NULL_ID {
+                        bcast_ids.insert(*parent_id);
+                    }
+                }
+            }
+            drop(bcast_ids);
+
+            let info = events.iter().map(|event| event.id().to_string()).collect();
+
+            degev!(self, SendMessage, {
+                info,
+                cmd: "FilterRep".to_string(),
+                time: NanoTimestamp::current_time(),
+            });
+
+            self.channel.send(&FilterRep(events)).await?;
+        }
+    }
+
+    /// Protocol function handling `ChunkReq`.
+    /// This is triggered whenever someone requests an attachment chunk
+    /// from us. We reply with the chunk if attachments are enabled and
+    /// we have it, or with an empty reply otherwise.
+    async fn handle_chunk_req(self: Arc<Self>) -> Result<()> {
+        let mut bucket = RequestBucket::new(CHUNK_REQ_RATE_LIMIT);
+        loop {
+            let chunk_hash = match self.chunk_req_sub.receive().await {
+                Ok(v) => v.0,
+                Err(_) => continue,
+            };
+            degev!(self, RecvMessage, {
+                info: vec![chunk_hash.to_string()],
+                cmd: "ChunkReq".to_string(),
+                time: NanoTimestamp::current_time(),
+            });
+            trace!(
+                target: "event_graph::protocol::handle_chunk_req()",
+                "Got ChunkReq: {} [{}]", chunk_hash, self.channel.address(),
+            );
+
+            bucket.take().await;
+
+            let chunk = match self.event_graph.attachments.get() {
+                Some(geode) => match geode.get_chunk(&chunk_hash).await {
+                    Ok(chunk_path) => smol::fs::read(chunk_path).await.unwrap_or_default(),
+                    Err(_) => vec![],
+                },
+                None => vec![],
+            };
+
+            degev!(self, SendMessage, {
+                info: vec![chunk_hash.to_string()],
+                cmd: "ChunkRep".to_string(),
+                time: NanoTimestamp::current_time(),
+            });
+
+            self.channel.send(&ChunkRep(chunk)).await?;
+        }
+    }
 }
@@ -18,7 +18,7 @@
 
 // cargo +nightly test --release --features=event-graph --lib eventgraph_propagation -- --include-ignored
 
-use std::sync::Arc;
+use std::{sync::Arc, time::UNIX_EPOCH};
 
 use log::{info, warn};
 use rand::{prelude::SliceRandom, rngs::ThreadRng};
@@ -28,8 +28,9 @@ use url::Url;
 use crate::{
     event_graph::{
         proto::{EventPut, ProtocolEventGraph},
-        Event, EventGraph,
+        Event, EventGraph, NULL_ID, N_EVENT_PARENTS,
     },
+    geode::{Geode, MAX_CHUNK_SIZE},
     net::{P2p, Settings, SESSION_NET},
     system::sleep,
 };
@@ -91,7 +92,7 @@ async fn spawn_node(
 
     let p2p = P2p::new(settings, ex.clone()).await;
     let sled_db = sled::Config::new().temporary(true).open().unwrap();
-    let event_graph = EventGraph::new(p2p.clone(), sled_db, "dag", 1, ex.clone()).await.unwrap();
+    let event_graph = EventGraph::new(p2p.clone(), sled_db, "dag", 1, 0, ex.clone()).await.unwrap();
     *event_graph.synced.write().await = true;
     let event_graph_ = event_graph.clone();
 
@@ -452,3 +453,98 @@ async fn eventgraph_chaotic_propagation_real(ex: Arc<Executor<'static>>) {
         eg.p2p.clone().stop().await;
     }
 }
+
+#[test]
+fn eventgraph_retention_prune() {
+    smol::block_on(async {
+        let ex = Arc::new(Executor::new());
+        let p2p = P2p::new(Settings::default(), ex.clone()).await;
+        let sled_db = sled::Config::new().temporary(true).open().unwrap();
+        // The executor is never run, so the retention task won't race with us
+        let event_graph = EventGraph::new(p2p, sled_db, "dag", 0, 1, ex).await.unwrap();
+        let genesis_id = event_graph.current_genesis.read().await.id();
+
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+        let make_event = |timestamp, content: u8, parent, layer| {
+            let mut parents = [NULL_ID; N_EVENT_PARENTS];
+            parents[0] = parent;
+            Event { timestamp, content: vec![content], parents, layer }
+        };
+
+        let old1 = make_event(now - 3 * 86400, 1, genesis_id, 1);
+        let old2 = make_event(now - 2 * 86400, 2, old1.id(), 2);
+        let new1 = make_event(now, 3, old2.id(), 3);
+        event_graph.dag_insert(&[old1.clone(), old2.clone(), new1.clone()]).await.unwrap();
+
+        // Genesis and both old events get pruned, the tip is retained
+        assert_eq!(event_graph.dag_prune_old().await.unwrap(), 3);
+        assert!(event_graph.dag_get(&old2.id()).await.unwrap().is_none());
+        assert!(event_graph.dag_get(&new1.id()).await.unwrap().is_some());
+
+        // Only the referenced pruned event and genesis are kept as checkpoints
+        assert!(event_graph.is_known(&old2.id()));
+        assert!(event_graph.is_known(&genesis_id));
+        assert!(!event_graph.is_known(&old1.id()));
+
+        // Events referencing checkpoint heads are still valid
+        let mut parents = [NULL_ID; N_EVENT_PARENTS];
+        parents[0] = new1.id();
+        parents[1] = old2.id();
+        let new2 = Event { timestamp: now, content: vec![4], parents, layer: 4 };
+        assert!(new2.dag_validate(&event_graph).await.unwrap());
+        event_graph.dag_insert(&[new2.clone()]).await.unwrap();
+
+        // Ordering skips the pruned ancestors
+        assert_eq!(event_graph.order_events().await, vec![new1.id(), new2.id()]);
+
+        // Nothing left to prune
+        assert_eq!(event_graph.dag_prune_old().await.unwrap(), 0);
+    });
+}
+
+#[test]
+fn eventgraph_attachments() {
+    smol::block_on(async {
+        let ex = Arc::new(Executor::new());
+        let p2p = P2p::new(Settings::default(), ex.clone()).await;
+        let sled_db = sled::Config::new().temporary(true).open().unwrap();
+        let event_graph = EventGraph::new(p2p, sled_db, "dag", 0, 1, ex).await.unwrap();
+        let genesis_id = event_graph.current_genesis.read().await.id();
+
+        let geode_path =
+            std::env::temp_dir().join(format!("darkfi-eventgraph-geode-{}", std::process::id()));
+        event_graph.enable_attachments(Geode::new(&geode_path).await.unwrap()).await;
+
+        let data: Vec<u8> = (0..MAX_CHUNK_SIZE * 2 + 42).map(|i| i as u8).collect();
+        let attachment = event_graph.attachment_insert(&data).await.unwrap();
+        assert!(attachment.is_valid());
+        assert_eq!(attachment.chunks.len(), 3);
+
+        // Reference it from an old event, which gets pruned by retention
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+        let mut parents = [NULL_ID; N_EVENT_PARENTS];
+        parents[0] = genesis_id;
+        let old = Event { timestamp: now - 2 * 86400, content: vec![1], parents, layer: 1 };
+        parents[0] = old.id();
+        let new = Event { timestamp: now, content: vec![2], parents, layer: 2 };
+        event_graph.dag_insert(&[old.clone(), new.clone()]).await.unwrap();
+
+        // All the chunks are local, so no peers are needed
+        assert_eq!(event_graph.attachment_fetch(&old.id(), &attachment).await.unwrap(), data);
+
+        // Referenced attachments survive garbage collection
+        event_graph.attachments_gc().await.unwrap();
+        assert_eq!(event_graph.attachment_fetch(&old.id(), &attachment).await.unwrap(), data);
+
+        // Once the referencing event is pruned, the attachment goes away
+        event_graph.dag_prune_old().await.unwrap();
+        event_graph.attachments_gc().await.unwrap();
+        let geode = event_graph.attachments.get().unwrap();
+        assert!(geode.get(&attachment.file_hash).await.is_err());
+        for chunk_hash in attachment.chunks.iter() {
+            assert!(geode.get_chunk(chunk_hash).await.is_err());
+        }
+
+        smol::fs::remove_dir_all(geode_path).await.unwrap();
+    });
+}
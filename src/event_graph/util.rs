@@ -49,6 +49,13 @@ pub(super) fn days_since(midnight_ts: u64) -> u64 {
     elapsed_seconds / DAY as u64
 }
 
+/// Calculate the timestamp before which events fall out of the
+/// configured DAG retention.
+pub(super) fn retention_cutoff_timestamp(days_retention: u64) -> u64 {
+    let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+    now.saturating_sub(days_retention * DAY as u64)
+}
+
 /// Calculate the timestamp of the next DAG rotation.
 pub(super) fn next_rotation_timestamp(starting_timestamp: u64, rotation_period: u64) -> u64 {
     // Prevent division by 0
@@ -21,7 +21,9 @@
 //!
 //! The API supports file insertion and retrieval. There is intentionally no
 //! `remove` support. File removal should be handled externally, and then it
-//! is only required to run `garbage_collect()` to clean things up.
+//! is only required to run `garbage_collect()` to clean things up. Users
+//! tracking which files they need can instead use `retain()`, which drops
+//! every other file along with the chunks only they were using.
 //!
 //! The filesystem hierarchy stores two directories: `files` and `chunks`.
 //! `chunks` store [`MAX_CHUNK_SIZE`] files, where the filename is a BLAKE3
@@ -254,6 +256,71 @@ impl Geode {
         Ok((deleted_files, deleted_chunks))
     }
 
+    /// Remove the metadata of all files not in the given set, and then the
+    /// chunks not used by any of the remaining files.
+    /// Returns sets representing removed files and removed chunks, respectively.
+    pub async fn retain(
+        &self,
+        keep: &HashSet<blake3::Hash>,
+    ) -> Result<(HashSet<blake3::Hash>, HashSet<blake3::Hash>)> {
+        info!(target: "geode::retain()", "[Geode] Removing unused files and chunks");
+        let mut removed_files = HashSet::new();
+        let mut removed_chunks = HashSet::new();
+        let mut used_chunks = HashSet::new();
+
+        let mut file_paths = fs::read_dir(&self.files_path).await?;
+        while let Some(file) = file_paths.next().await {
+            let Ok(entry) = file else { continue };
+            let path = entry.path();
+
+            let Some(file_hash) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| blake3::Hash::from_hex(n).ok())
+            else {
+                continue
+            };
+
+            if keep.contains(&file_hash) {
+                // Corrupted metadata is left for `garbage_collect()`
+                if let Ok(chunk_hashes) = Self::read_metadata(&path).await {
+                    used_chunks.extend(chunk_hashes);
+                }
+                continue
+            }
+
+            fs::remove_file(&path).await?;
+            removed_files.insert(file_hash);
+        }
+
+        let mut chunk_paths = fs::read_dir(&self.chunks_path).await?;
+        while let Some(chunk) = chunk_paths.next().await {
+            let Ok(entry) = chunk else { continue };
+            let path = entry.path();
+
+            let Some(chunk_hash) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| blake3::Hash::from_hex(n).ok())
+            else {
+                continue
+            };
+
+            if used_chunks.contains(&chunk_hash) {
+                continue
+            }
+
+            fs::remove_file(&path).await?;
+            removed_chunks.insert(chunk_hash);
+        }
+
+        info!(
+            target: "geode::retain()",
+            "[Geode] Removed {} files and {} chunks", removed_files.len(), removed_chunks.len(),
+        );
+        Ok((removed_files, removed_chunks))
+    }
+
     /// Insert a file into Geode. The function expects any kind of byte stream, which
     /// can either be another file on the filesystem, a buffer, etc.
     /// Returns a tuple of `(blake3::Hash, Vec<blake3::Hash>)` which represents the
@@ -103,7 +103,7 @@ impl Acceptor {
 
         loop {
             // Refuse new connections if we're up to the connection limit
-            let limit = self.session.upgrade().unwrap().p2p().settings().inbound_connections;
+            let limit = self.session.upgrade().unwrap().p2p().inbound_connections();
             if self.clone().conn_count.load(SeqCst) >= limit {
                 // This will get notified every time an inbound channel is stopped.
                 // These channels are the channels spawned below on listener.next().is_ok().
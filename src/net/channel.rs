@@ -26,13 +26,13 @@ use std::{
 };
 
 use darkfi_serial::{async_trait, serialize, SerialDecodable, SerialEncodable};
-use log::{debug, error, info};
 use rand::{rngs::OsRng, Rng};
 use smol::{
     io::{self, ReadHalf, WriteHalf},
     lock::Mutex,
     Executor,
 };
+use tracing::{debug, debug_span, error, info, instrument, Instrument};
 use url::Url;
 
 use super::{
@@ -84,6 +84,8 @@ pub struct Channel {
     stopped: AtomicBool,
     /// Weak pointer to respective session
     session: SessionWeakPtr,
+    /// Peer clock offset from ours in seconds, as reported in its version message
+    clock_offset: Mutex<Option<i64>>,
     /// Channel debug info
     pub info: ChannelInfo,
 }
@@ -115,6 +117,7 @@ impl Channel {
             receive_task: StoppableTask::new(),
             stopped: AtomicBool::new(false),
             session,
+            clock_offset: Mutex::new(None),
             info,
         })
     }
@@ -260,6 +263,7 @@ impl Channel {
     }
 
     /// Run the receive loop. Start receiving messages or handle network failure.
+    #[instrument(target = "net", skip_all, fields(peer = %self.address(), channel = self.info.id))]
     async fn main_receive_loop(self: Arc<Self>) -> Result<()> {
         debug!(target: "net::channel::main_receive_loop()", "[START] {:?}", self);
 
@@ -300,7 +304,13 @@ impl Channel {
             });
 
             // Send result to our subscribers
-            match self.message_subsystem.notify(&packet.command, &packet.payload).await {
+            let span = debug_span!("message", command = %packet.command);
+            match self
+                .message_subsystem
+                .notify(&packet.command, &packet.payload)
+                .instrument(span)
+                .await
+            {
                 Ok(()) => {}
                 // If we're getting messages without dispatchers, it's spam.
                 Err(Error::MissingDispatcher) => {
@@ -349,6 +359,17 @@ impl Channel {
         &self.info.connect_addr
     }
 
+    /// Returns the peer clock offset from ours in seconds, if the peer
+    /// has sent its version message.
+    pub async fn clock_offset(&self) -> Option<i64> {
+        *self.clock_offset.lock().await
+    }
+
+    /// Record the peer clock offset from ours in seconds.
+    pub(super) async fn set_clock_offset(&self, offset: i64) {
+        *self.clock_offset.lock().await = Some(offset);
+    }
+
     /// Returns the inner [`MessageSubsystem`] reference
     pub fn message_subsystem(&self) -> &MessageSubsystem {
         &self.message_subsystem
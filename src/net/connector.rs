@@ -24,7 +24,7 @@ use url::Url;
 use super::{
     channel::{Channel, ChannelPtr},
     hosts::HostColor,
-    session::SessionWeakPtr,
+    session::{SessionWeakPtr, SESSION_MANUAL, SESSION_SEED},
     settings::SettingsPtr,
     transport::Dialer,
 };
@@ -46,7 +46,8 @@ impl Connector {
 
     /// Establish an outbound connection
     pub async fn connect(&self, url: &Url) -> Result<(Url, ChannelPtr)> {
-        let hosts = self.session.upgrade().unwrap().p2p().hosts();
+        let session = self.session.upgrade().unwrap();
+        let hosts = session.p2p().hosts();
         if hosts.container.contains(HostColor::Black as usize, url).await ||
             hosts.block_all_ports(url.host_str().unwrap().to_string()).await
         {
@@ -70,7 +71,14 @@ impl Connector {
             }
         }
 
-        let dialer = Dialer::new(endpoint.clone()).await?;
+        // Each connection class can be tunneled through its own proxy chain
+        let proxy_chain = match session.type_id() {
+            SESSION_MANUAL => &self.settings.manual_proxy_chain,
+            SESSION_SEED => &self.settings.seed_proxy_chain,
+            _ => &self.settings.outbound_proxy_chain,
+        };
+
+        let dialer = Dialer::new(endpoint.clone()).await?.with_proxy_chain(proxy_chain)?;
         let timeout = Duration::from_secs(self.settings.outbound_connect_timeout);
         let ptstream = dialer.dial(Some(timeout)).await?;
 
@@ -408,6 +408,23 @@ impl HostContainer {
         self.hostlists[color as usize].read().await.iter().cloned().collect()
     }
 
+    /// Return up to `limit` hosts of a hostlist, starting at `offset`.
+    pub async fn fetch_page(
+        &self,
+        color: HostColor,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<(Url, u64)> {
+        self.hostlists[color as usize]
+            .read()
+            .await
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
     /// Get the oldest entry from a hostlist.
     pub async fn fetch_last(&self, color: HostColor) -> ((Url, u64), usize) {
         let list = self.hostlists[color as usize].read().await;
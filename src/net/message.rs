@@ -17,8 +17,8 @@
  */
 
 use darkfi_serial::{
-    async_trait, AsyncDecodable, AsyncEncodable, Decodable, Encodable, SerialDecodable,
-    SerialEncodable,
+    async_trait, AsyncDecodable, AsyncEncodable, Decodable, Encodable, LimitedReader,
+    SerialDecodable, SerialEncodable,
 };
 use log::trace;
 use smol::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -28,9 +28,19 @@ use crate::{Error, Result};
 
 const MAGIC_BYTES: [u8; 4] = [0xd9, 0xef, 0xb6, 0x7d];
 
+/// Maximum encoded size of a packet command, in bytes.
+const MAX_COMMAND_LEN: u64 = 256;
+
+/// Maximum encoded size of a packet payload, in bytes. Individual messages
+/// can enforce tighter limits through [`Message::MAX_BYTES`].
+pub const MAX_PACKET_SIZE: u64 = 128 * 1024 * 1024;
+
 /// Generic message template.
 pub trait Message: 'static + Send + Sync + Encodable + Decodable {
     const NAME: &'static str;
+    /// Maximum encoded size of the message. Larger payloads are
+    /// dropped without being decoded.
+    const MAX_BYTES: u64 = MAX_PACKET_SIZE;
 }
 
 #[macro_export]
@@ -40,6 +50,12 @@ macro_rules! impl_p2p_message {
             const NAME: &'static str = $nm;
         }
     };
+    ($st:ty, $nm:expr, $max:expr) => {
+        impl Message for $st {
+            const NAME: &'static str = $nm;
+            const MAX_BYTES: u64 = $max;
+        }
+    };
 }
 
 /// Outbound keepalive message.
@@ -134,11 +150,13 @@ pub async fn read_packet<R: AsyncRead + Unpin + Send + Sized>(stream: &mut R) ->
     }
 
     // The type of the message.
-    let command = String::decode_async(stream).await?;
+    let command =
+        String::decode_async(&mut LimitedReader::new(&mut *stream, MAX_COMMAND_LEN)).await?;
     trace!(target: "net::message", "Read command: {}", command);
 
     // The message-dependent data (see message types)
-    let payload = Vec::<u8>::decode_async(stream).await?;
+    let payload =
+        Vec::<u8>::decode_async(&mut LimitedReader::new(&mut *stream, MAX_PACKET_SIZE)).await?;
     trace!(target: "net::message", "Read payload {} bytes", payload.len());
 
     Ok(Packet { command, payload })
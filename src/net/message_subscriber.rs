@@ -16,9 +16,10 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{any::Any, collections::HashMap, io::Cursor, sync::Arc, time::Duration};
+use std::{any::Any, collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
+use darkfi_serial::deserialize_strict_limited;
 use futures::stream::{FuturesUnordered, StreamExt};
 use log::{debug, warn};
 use rand::{rngs::OsRng, Rng};
@@ -192,8 +193,10 @@ impl<M: Message> MessageDispatcherInterface for MessageDispatcher<M> {
     /// and dispatch it across subscriber channels.
     async fn trigger(&self, payload: &[u8]) {
         // Deserialize data into type, send down the pipes.
-        let cursor = Cursor::new(payload);
-        match M::decode(cursor) {
+        // Decoding is strict, so peers can't relay malleated encodings
+        // of consensus-critical data, and payloads over the message's
+        // size limit are dropped without being decoded.
+        match deserialize_strict_limited::<M>(payload, M::MAX_BYTES) {
             Ok(message) => {
                 let message = Ok(Arc::new(message));
                 self._trigger_all(message).await
@@ -18,7 +18,10 @@
 
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 use futures::{stream::FuturesUnordered, TryFutureExt};
@@ -34,7 +37,8 @@ use super::{
     protocol::{protocol_registry::ProtocolRegistry, register_default_protocols},
     session::{
         InboundSession, InboundSessionPtr, ManualSession, ManualSessionPtr, OutboundSession,
-        OutboundSessionPtr, RefineSession, RefineSessionPtr, SeedSyncSession,
+        OutboundSessionPtr, RefineSession, RefineSessionPtr, SeedSyncSession, SESSION_MANUAL,
+        SESSION_OUTBOUND,
     },
     settings::{Settings, SettingsPtr},
 };
@@ -43,6 +47,10 @@ use crate::{
     Result,
 };
 
+/// Minimum number of distinct outbound or manual hosts whose reported clocks are
+/// needed to estimate network time
+pub const MIN_CLOCK_PEERS: usize = 3;
+
 /// Set of channels that are awaiting connection
 pub type PendingChannels = Mutex<HashSet<Url>>;
 /// Set of connected channels
@@ -60,6 +68,8 @@ pub struct P2p {
     protocol_registry: ProtocolRegistry,
     /// P2P network settings
     settings: SettingsPtr,
+    /// Inbound connection slots limit, adjustable at runtime
+    inbound_connections: AtomicUsize,
     /// Boolean lock marking if peer discovery is active
     pub peer_discovery_running: Mutex<bool>,
 
@@ -88,12 +98,21 @@ impl P2p {
     /// Creates a weak pointer to self that is used by all sessions to access the
     /// p2p parent class.
     pub async fn new(settings: Settings, executor: ExecutorPtr) -> P2pPtr {
+        #[cfg(feature = "p2p-tor")]
+        if !settings.tor_bridges.is_empty() {
+            let (bridges, pt_path) = (settings.tor_bridges.clone(), settings.tor_pt_path.clone());
+            if let Err(e) = super::transport::set_tor_bridges(bridges, pt_path) {
+                warn!(target: "net::p2p::new", "Unable to configure Tor bridges: {}", e);
+            }
+        }
+
         let settings = Arc::new(settings);
 
         let self_ = Arc::new(Self {
             executor,
             hosts: Hosts::new(settings.clone()),
             protocol_registry: ProtocolRegistry::new(),
+            inbound_connections: AtomicUsize::new(settings.inbound_connections),
             settings,
             peer_discovery_running: Mutex::new(false),
 
@@ -213,11 +232,46 @@ impl P2p {
         !self.hosts().channels().await.is_empty()
     }
 
+    /// Returns the median clock offset in seconds of the connected outbound
+    /// and manual peers from our local clock, as reported in their version
+    /// messages.
+    /// Inbound peers choose to connect to us, so they aren't sampled, and
+    /// each host is sampled once. Returns `None` unless at least
+    /// [`MIN_CLOCK_PEERS`] hosts have reported their time.
+    pub async fn peer_clock_offset(&self) -> Option<i64> {
+        let mut offsets = HashMap::new();
+        for channel in self.hosts().channels().await {
+            let type_id = channel.session_type_id();
+            if type_id != SESSION_OUTBOUND && type_id != SESSION_MANUAL {
+                continue
+            }
+
+            let Some(host) = channel.address().host_str().map(String::from) else { continue };
+            if let Some(offset) = channel.clock_offset().await {
+                offsets.insert(host, offset);
+            }
+        }
+
+        median_clock_offset(offsets.into_values().collect())
+    }
+
     /// Return an atomic pointer to the set network settings
     pub fn settings(&self) -> SettingsPtr {
         self.settings.clone()
     }
 
+    /// Return the current inbound connection slots limit
+    pub fn inbound_connections(&self) -> usize {
+        self.inbound_connections.load(Ordering::SeqCst)
+    }
+
+    /// Change the inbound connection slots limit at runtime.
+    /// Already established connections above the new limit are kept,
+    /// the limit only applies to newly accepted ones.
+    pub fn set_inbound_connections(&self, limit: usize) {
+        self.inbound_connections.store(limit, Ordering::SeqCst);
+    }
+
     /// Return an atomic pointer to the list of hosts
     pub fn hosts(&self) -> HostsPtr {
         self.hosts.clone()
@@ -275,3 +329,31 @@ impl P2p {
         self.dnet_subscriber.notify(event).await;
     }
 }
+
+/// Median of the given clock offsets, if there are at least [`MIN_CLOCK_PEERS`]
+fn median_clock_offset(mut offsets: Vec<i64>) -> Option<i64> {
+    if offsets.len() < MIN_CLOCK_PEERS {
+        return None
+    }
+
+    offsets.sort_unstable();
+    Some(offsets[offsets.len() / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_clock_offset() {
+        // Too few samples to estimate network time
+        assert_eq!(median_clock_offset(vec![]), None);
+        assert_eq!(median_clock_offset(vec![5, -5]), None);
+
+        assert_eq!(median_clock_offset(vec![3, -1, 2]), Some(2));
+        assert_eq!(median_clock_offset(vec![0, 10, -10, 1]), Some(1));
+
+        // A minority of outliers can't move the median
+        assert_eq!(median_clock_offset(vec![1, 0, -1, 3600, 3600]), Some(1));
+    }
+}
@@ -184,9 +184,13 @@ impl ProtocolVersion {
         );
 
         // Receive version message
-        let _version = self.version_sub.receive().await?;
+        let version = self.version_sub.receive().await?;
         // TODO: self.channel.set_remote_node_id(version.node_id.clone()).await;
 
+        // Record how far the peer clock is from ours, used for clock sanity checks
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+        self.channel.set_clock_offset(version.timestamp as i64 - now as i64).await;
+
         // Send verack
         let verack = VerackMessage { app_version: self.settings.app_version.clone() };
         self.channel.send(&verack).await?;
@@ -80,6 +80,17 @@ pub struct Settings {
     /// Nodes to avoid interacting with for the duration of the program,
     /// in the format ["scheme://host", [port, port]]
     pub blacklist: Vec<(Url, Vec<u16>)>,
+    /// Proxy chain outbound and refinery connections are dialed through,
+    /// as an ordered list of `socks5://` or `socks5+tls://` hops
+    pub outbound_proxy_chain: Vec<Url>,
+    /// Proxy chain manual connections are dialed through
+    pub manual_proxy_chain: Vec<Url>,
+    /// Proxy chain seed connections are dialed through
+    pub seed_proxy_chain: Vec<Url>,
+    /// Bridge lines the Tor transport reaches the Tor network through
+    pub tor_bridges: Vec<String>,
+    /// Pluggable transport binary serving the Tor bridges (e.g. obfs4proxy)
+    pub tor_pt_path: Option<String>,
 }
 
 impl Default for Settings {
@@ -111,6 +122,11 @@ impl Default for Settings {
             anchor_connect_count: 2,
             time_with_no_connections: 30,
             blacklist: vec![],
+            outbound_proxy_chain: vec![],
+            manual_proxy_chain: vec![],
+            seed_proxy_chain: vec![],
+            tor_bridges: vec![],
+            tor_pt_path: None,
         }
     }
 }
@@ -119,7 +135,9 @@ impl Default for Settings {
 // from TOML files.
 
 /// Defines the network settings.
-#[derive(Clone, Debug, serde::Deserialize, structopt::StructOpt, structopt_toml::StructOptToml)]
+#[derive(
+    Clone, Debug, PartialEq, serde::Deserialize, structopt::StructOpt, structopt_toml::StructOptToml,
+)]
 #[structopt()]
 pub struct SettingsOpt {
     /// P2P accept address node listens to for inbound connections
@@ -223,6 +241,32 @@ pub struct SettingsOpt {
     #[serde(default)]
     #[structopt(skip)]
     pub blacklist: Vec<(Url, Vec<u16>)>,
+
+    /// Proxy chain for outbound connections, as an ordered
+    /// list of `socks5://` or `socks5+tls://` hops
+    #[serde(default)]
+    #[structopt(long)]
+    pub outbound_proxy_chain: Vec<Url>,
+
+    /// Proxy chain for manual connections
+    #[serde(default)]
+    #[structopt(long)]
+    pub manual_proxy_chain: Vec<Url>,
+
+    /// Proxy chain for seed connections
+    #[serde(default)]
+    #[structopt(long)]
+    pub seed_proxy_chain: Vec<Url>,
+
+    /// Bridge lines to reach the Tor network through
+    #[serde(default)]
+    #[structopt(long)]
+    pub tor_bridges: Vec<String>,
+
+    /// Pluggable transport binary serving the Tor bridges
+    #[serde(default)]
+    #[structopt(long)]
+    pub tor_pt_path: Option<String>,
 }
 
 impl From<SettingsOpt> for Settings {
@@ -267,6 +311,11 @@ impl From<SettingsOpt> for Settings {
                 .time_with_no_connections
                 .unwrap_or(def.time_with_no_connections),
             blacklist: opt.blacklist,
+            outbound_proxy_chain: opt.outbound_proxy_chain,
+            manual_proxy_chain: opt.manual_proxy_chain,
+            seed_proxy_chain: opt.seed_proxy_chain,
+            tor_bridges: opt.tor_bridges,
+            tor_pt_path: opt.tor_pt_path,
         }
     }
 }
@@ -28,6 +28,7 @@ use crate::{Error, Result};
 
 /// TLS upgrade mechanism
 pub(crate) mod tls;
+pub use tls::{identity_public_key, load_or_create_identity, parse_pinned_key};
 
 #[cfg(feature = "p2p-tcp")]
 /// TCP transport
@@ -36,6 +37,8 @@ pub(crate) mod tcp;
 #[cfg(feature = "p2p-tor")]
 /// Tor transport
 pub(crate) mod tor;
+#[cfg(feature = "p2p-tor")]
+pub use tor::set_tor_bridges;
 
 #[cfg(feature = "p2p-nym")]
 /// Nym transport
@@ -45,6 +48,10 @@ pub(crate) mod nym;
 /// Unix socket transport
 pub(crate) mod unix;
 
+/// Transport-level proxy chaining
+pub mod proxy;
+pub use proxy::ProxyChain;
+
 /// Dialer variants
 #[derive(Debug, Clone)]
 pub enum DialerVariant {
@@ -99,6 +106,10 @@ pub struct Dialer {
     endpoint: Url,
     /// The dialer variant (transport protocol)
     variant: DialerVariant,
+    /// Optional pinned server public key for TLS variants
+    tls_pinned_key: Option<ed25519_compact::PublicKey>,
+    /// Optional proxy chain to reach the endpoint through
+    proxy_chain: Option<ProxyChain>,
 }
 
 macro_rules! enforce_hostport {
@@ -131,7 +142,7 @@ impl Dialer {
                 enforce_hostport!(endpoint);
                 let variant = tcp::TcpDialer::new(None).await?;
                 let variant = DialerVariant::Tcp(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_pinned_key: None, proxy_chain: None })
             }
 
             #[cfg(feature = "p2p-tcp")]
@@ -140,7 +151,7 @@ impl Dialer {
                 enforce_hostport!(endpoint);
                 let variant = tcp::TcpDialer::new(None).await?;
                 let variant = DialerVariant::TcpTls(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_pinned_key: None, proxy_chain: None })
             }
 
             #[cfg(feature = "p2p-tor")]
@@ -149,7 +160,7 @@ impl Dialer {
                 enforce_hostport!(endpoint);
                 let variant = tor::TorDialer::new().await?;
                 let variant = DialerVariant::Tor(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_pinned_key: None, proxy_chain: None })
             }
 
             #[cfg(feature = "p2p-tor")]
@@ -158,7 +169,7 @@ impl Dialer {
                 enforce_hostport!(endpoint);
                 let variant = tor::TorDialer::new().await?;
                 let variant = DialerVariant::TorTls(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_pinned_key: None, proxy_chain: None })
             }
 
             #[cfg(feature = "p2p-nym")]
@@ -167,7 +178,7 @@ impl Dialer {
                 enforce_hostport!(endpoint);
                 let variant = nym::NymDialer::new().await?;
                 let variant = DialerVariant::Nym(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_pinned_key: None, proxy_chain: None })
             }
 
             #[cfg(feature = "p2p-nym")]
@@ -176,7 +187,7 @@ impl Dialer {
                 enforce_hostport!(endpoint);
                 let variant = nym::NymDialer::new().await?;
                 let variant = DialerVariant::NymTls(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_pinned_key: None, proxy_chain: None })
             }
 
             #[cfg(feature = "p2p-unix")]
@@ -185,13 +196,47 @@ impl Dialer {
                 // Build a Unix socket dialer
                 let variant = unix::UnixDialer::new().await?;
                 let variant = DialerVariant::Unix(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_pinned_key: None, proxy_chain: None })
             }
 
             x => Err(Error::UnsupportedTransport(x.to_string())),
         }
     }
 
+    /// Only accept a TLS server presenting a certificate for the given
+    /// public key. Has no effect on non-TLS transports.
+    pub fn with_pinned_server_key(mut self, key: ed25519_compact::PublicKey) -> Self {
+        self.tls_pinned_key = Some(key);
+        self
+    }
+
+    /// Reach the endpoint through the given proxy chain, see [`ProxyChain`].
+    /// Only TCP endpoints can be dialed through a chain. An empty chain
+    /// leaves the dialer untouched.
+    pub fn with_proxy_chain(mut self, hops: &[Url]) -> Result<Self> {
+        if hops.is_empty() {
+            return Ok(self)
+        }
+
+        if !matches!(self.endpoint.scheme(), "tcp" | "tcp+tls") {
+            return Err(Error::UnsupportedTransport(format!(
+                "{} over a proxy chain",
+                self.endpoint.scheme()
+            )))
+        }
+
+        self.proxy_chain = Some(ProxyChain::new(hops)?);
+        Ok(self)
+    }
+
+    /// Build the TLS upgrade used for dialing, honoring a pinned server key
+    async fn tls_upgrade(&self) -> tls::TlsUpgrade {
+        match self.tls_pinned_key {
+            Some(key) => tls::TlsUpgrade::with_pinned_server(key).await,
+            None => tls::TlsUpgrade::new().await,
+        }
+    }
+
     /// Dial an instantiated [`Dialer`]. This creates a connection and returns a stream.
 
     /// The Tor-based Dialer variants can panic: this is intended. There exists validation
@@ -200,6 +245,25 @@ impl Dialer {
     /// in this case that the user is alerted to this problem via a panic.
     pub async fn dial(&self, timeout: Option<Duration>) -> Result<Box<dyn PtStream>> {
         match &self.variant {
+            #[cfg(feature = "p2p-tcp")]
+            DialerVariant::Tcp(_) if self.proxy_chain.is_some() => {
+                let host = self.endpoint.host_str().unwrap();
+                let port = self.endpoint.port().unwrap();
+                let chain = self.proxy_chain.as_ref().unwrap();
+                chain.dial(host, port, timeout).await
+            }
+
+            #[cfg(feature = "p2p-tcp")]
+            DialerVariant::TcpTls(_) if self.proxy_chain.is_some() => {
+                let host = self.endpoint.host_str().unwrap();
+                let port = self.endpoint.port().unwrap();
+                let chain = self.proxy_chain.as_ref().unwrap();
+                let stream = chain.dial(host, port, timeout).await?;
+                let tlsupgrade = self.tls_upgrade().await;
+                let stream = tlsupgrade.upgrade_dialer_tls(stream).await?;
+                Ok(Box::new(stream))
+            }
+
             #[cfg(feature = "p2p-tcp")]
             DialerVariant::Tcp(dialer) => {
                 // NOTE: sockaddr here is an array, can contain both ipv4 and ipv6
@@ -212,7 +276,7 @@ impl Dialer {
             DialerVariant::TcpTls(dialer) => {
                 let sockaddr = self.endpoint.socket_addrs(|| None)?;
                 let stream = dialer.do_dial(sockaddr[0], timeout).await?;
-                let tlsupgrade = tls::TlsUpgrade::new().await;
+                let tlsupgrade = self.tls_upgrade().await;
                 let stream = tlsupgrade.upgrade_dialer_tls(stream).await?;
                 Ok(Box::new(stream))
             }
@@ -252,7 +316,7 @@ impl Dialer {
                     }
                 };
                 let stream = result?;
-                let tlsupgrade = tls::TlsUpgrade::new().await;
+                let tlsupgrade = self.tls_upgrade().await;
                 let stream = tlsupgrade.upgrade_dialer_tls(stream).await?;
                 Ok(Box::new(stream))
             }
@@ -296,6 +360,8 @@ pub struct Listener {
     endpoint: Url,
     /// The listener variant (transport protocol)
     variant: ListenerVariant,
+    /// Optional persistent PEM-encoded identity keypair for TLS variants
+    tls_identity: Option<String>,
 }
 
 impl Listener {
@@ -309,7 +375,7 @@ impl Listener {
                 enforce_hostport!(endpoint);
                 let variant = tcp::TcpListener::new(1024).await?;
                 let variant = ListenerVariant::Tcp(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_identity: None })
             }
 
             #[cfg(feature = "p2p-tcp")]
@@ -318,7 +384,7 @@ impl Listener {
                 enforce_hostport!(endpoint);
                 let variant = tcp::TcpListener::new(1024).await?;
                 let variant = ListenerVariant::TcpTls(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_identity: None })
             }
 
             #[cfg(feature = "p2p-unix")]
@@ -326,13 +392,21 @@ impl Listener {
                 enforce_abspath!(endpoint);
                 let variant = unix::UnixListener::new().await?;
                 let variant = ListenerVariant::Unix(variant);
-                Ok(Self { endpoint, variant })
+                Ok(Self { endpoint, variant, tls_identity: None })
             }
 
             x => Err(Error::UnsupportedTransport(x.to_string())),
         }
     }
 
+    /// Use a persistent PEM-encoded identity keypair for TLS variants, so
+    /// clients are able to pin our certificate. Has no effect on non-TLS
+    /// transports.
+    pub fn with_tls_identity(mut self, keypair_pem: String) -> Self {
+        self.tls_identity = Some(keypair_pem);
+        self
+    }
+
     /// Listen on an instantiated [`Listener`].
     /// This will open a socket and return the listener.
     pub async fn listen(&self) -> Result<Box<dyn PtListener>> {
@@ -348,7 +422,10 @@ impl Listener {
             ListenerVariant::TcpTls(listener) => {
                 let sockaddr = self.endpoint.socket_addrs(|| None)?;
                 let l = listener.do_listen(sockaddr[0]).await?;
-                let tlsupgrade = tls::TlsUpgrade::new().await;
+                let tlsupgrade = match self.tls_identity {
+                    Some(ref keypair_pem) => tls::TlsUpgrade::with_identity(keypair_pem).await?,
+                    None => tls::TlsUpgrade::new().await,
+                };
                 let l = tlsupgrade.upgrade_listener_tcp_tls(l).await?;
                 Ok(Box::new(l))
             }
@@ -379,6 +456,10 @@ impl PtStream for smol::net::TcpStream {}
 #[cfg(feature = "p2p-tcp")]
 impl PtStream for futures_rustls::TlsStream<smol::net::TcpStream> {}
 
+impl PtStream for Box<dyn PtStream> {}
+
+impl PtStream for futures_rustls::TlsStream<Box<dyn PtStream>> {}
+
 #[cfg(feature = "p2p-tor")]
 impl PtStream for arti_client::DataStream {}
 
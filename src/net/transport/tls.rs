@@ -16,7 +16,7 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::sync::Arc;
+use std::{fs, io::Write, path::Path, sync::Arc};
 
 use futures_rustls::{
     rustls::{
@@ -29,14 +29,17 @@ use futures_rustls::{
     },
     TlsAcceptor, TlsConnector, TlsStream,
 };
-use log::error;
+use log::{error, info};
 use rustls_pemfile::pkcs8_private_keys;
 use x509_parser::{
     parse_x509_certificate,
     prelude::{GeneralName, ParsedExtension, X509Certificate},
 };
 
-use crate::Result;
+use crate::{
+    util::{encoding::base64, file::load_file},
+    Error, Result,
+};
 
 /// Validate certificate DNSName.
 fn validate_dnsname(cert: &X509Certificate) -> std::result::Result<(), rustls::Error> {
@@ -69,7 +72,12 @@ fn validate_dnsname(cert: &X509Certificate) -> std::result::Result<(), rustls::E
 }
 
 #[derive(Debug)]
-struct ServerCertificateVerifier;
+struct ServerCertificateVerifier {
+    /// Optional pinned server public key. When set, only a server
+    /// presenting a certificate for this key is accepted.
+    pinned: Option<ed25519_compact::PublicKey>,
+}
+
 impl ServerCertVerifier for ServerCertificateVerifier {
     fn verify_server_cert(
         &self,
@@ -94,6 +102,19 @@ impl ServerCertVerifier for ServerCertificateVerifier {
         // Validate DNSName
         validate_dnsname(&cert)?;
 
+        // Check the server identity, if we have it pinned
+        if let Some(pinned) = &self.pinned {
+            let Ok(public_key) = ed25519_compact::PublicKey::from_der(cert.public_key().raw) else {
+                error!(target: "net::tls::verify_server_cert", "[net::tls] Failed parsing server public key");
+                return Err(rustls::CertificateError::BadEncoding.into())
+            };
+
+            if &public_key != pinned {
+                error!(target: "net::tls::verify_server_cert", "[net::tls] Server public key does not match the pinned one");
+                return Err(rustls::CertificateError::ApplicationVerificationFailure.into())
+            }
+        }
+
         Ok(ServerCertVerified::assertion())
     }
 
@@ -259,12 +280,32 @@ impl TlsUpgrade {
     pub async fn new() -> Self {
         // On each instantiation, generate a new keypair and certificate
         let keypair_pem = ed25519_compact::KeyPair::generate().to_pem();
-        let secret_key = pkcs8_private_keys(&mut keypair_pem.as_bytes()).next().unwrap().unwrap();
-        let secret_key = PrivateKeyDer::Pkcs8(secret_key);
+        Self::build(&keypair_pem, None)
+    }
+
+    /// Instantiate using a persistent identity keypair, so the certificate
+    /// presented to peers stays the same across restarts. Used by services
+    /// that clients pin, like JSON-RPC servers.
+    pub async fn with_identity(keypair_pem: &str) -> Result<Self> {
+        if ed25519_compact::KeyPair::from_pem(keypair_pem).is_err() {
+            return Err(Error::ParseFailed("Invalid TLS identity keypair"))
+        }
+
+        Ok(Self::build(keypair_pem, None))
+    }
 
+    /// Instantiate a client-side upgrade which only accepts a server
+    /// presenting a certificate for the given public key.
+    pub async fn with_pinned_server(server_key: ed25519_compact::PublicKey) -> Self {
+        let keypair_pem = ed25519_compact::KeyPair::generate().to_pem();
+        Self::build(&keypair_pem, Some(server_key))
+    }
+
+    /// Create the DER-encoded self-signed certificate for the given keypair
+    fn certificate(keypair_pem: &str) -> Vec<u8> {
         let mut cert_params = rcgen::CertificateParams::new(&[]);
         cert_params.alg = &rcgen::PKCS_ED25519;
-        cert_params.key_pair = Some(rcgen::KeyPair::from_pem(&keypair_pem).unwrap());
+        cert_params.key_pair = Some(rcgen::KeyPair::from_pem(keypair_pem).unwrap());
         cert_params.subject_alt_names = vec![rcgen::SanType::DnsName("dark.fi".to_string())];
         cert_params.extended_key_usages = vec![
             rcgen::ExtendedKeyUsagePurpose::ClientAuth,
@@ -272,7 +313,13 @@ impl TlsUpgrade {
         ];
 
         let certificate = rcgen::Certificate::from_params(cert_params).unwrap();
-        let certificate = certificate.serialize_der().unwrap();
+        certificate.serialize_der().unwrap()
+    }
+
+    fn build(keypair_pem: &str, pinned: Option<ed25519_compact::PublicKey>) -> Self {
+        let secret_key = pkcs8_private_keys(&mut keypair_pem.as_bytes()).next().unwrap().unwrap();
+        let secret_key = PrivateKeyDer::Pkcs8(secret_key);
+        let certificate = Self::certificate(keypair_pem);
 
         // Server-side config
         let client_cert_verifier = Arc::new(ClientCertificateVerifier {});
@@ -284,7 +331,7 @@ impl TlsUpgrade {
         );
 
         // Client-side config
-        let server_cert_verifier = Arc::new(ServerCertificateVerifier {});
+        let server_cert_verifier = Arc::new(ServerCertificateVerifier { pinned });
         let client_config = Arc::new(
             ClientConfig::builder_with_protocol_versions(&[&TLS13])
                 .dangerous()
@@ -316,3 +363,106 @@ impl TlsUpgrade {
         Ok((TlsAcceptor::from(self.server_config), listener))
     }
 }
+
+/// Load a persistent TLS identity keypair from the given path, creating
+/// it if it does not exist yet. Returns the PEM-encoded keypair.
+pub fn load_or_create_identity(path: &Path) -> Result<String> {
+    if path.exists() {
+        return load_file(path)
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let keypair_pem = ed25519_compact::KeyPair::generate().to_pem();
+
+    // Only the owner may ever read the secret key
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(keypair_pem.as_bytes())?;
+
+    info!(target: "net::tls", "[net::tls] Created new TLS identity in {:?}", path);
+    Ok(keypair_pem)
+}
+
+/// Return the base64-encoded public key of the given PEM-encoded identity
+/// keypair. This is what clients use to pin the server.
+pub fn identity_public_key(keypair_pem: &str) -> Result<String> {
+    let Ok(keypair) = ed25519_compact::KeyPair::from_pem(keypair_pem) else {
+        return Err(Error::ParseFailed("Invalid TLS identity keypair"))
+    };
+
+    Ok(base64::encode(&keypair.pk[..]))
+}
+
+/// Parse a base64-encoded public key used for server pinning
+pub fn parse_pinned_key(key: &str) -> Result<ed25519_compact::PublicKey> {
+    let Some(bytes) = base64::decode(key) else {
+        return Err(Error::ParseFailed("Invalid base64 TLS public key"))
+    };
+
+    match ed25519_compact::PublicKey::from_slice(&bytes) {
+        Ok(pk) => Ok(pk),
+        Err(_) => Err(Error::ParseFailed("Invalid TLS public key")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_persistence() {
+        let path =
+            std::env::temp_dir().join(format!("darkfi_tls_identity_{}.pem", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let keypair_pem = load_or_create_identity(&path).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Reloading gives back the same identity
+        let reloaded = load_or_create_identity(&path).unwrap();
+        assert_eq!(reloaded, keypair_pem);
+        assert_eq!(
+            identity_public_key(&reloaded).unwrap(),
+            identity_public_key(&keypair_pem).unwrap()
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_server_key_pinning() {
+        let server_pem = ed25519_compact::KeyPair::generate().to_pem();
+        let other_pem = ed25519_compact::KeyPair::generate().to_pem();
+        let certificate = CertificateDer::from(TlsUpgrade::certificate(&server_pem));
+        let server_name = ServerName::try_from("dark.fi").unwrap();
+
+        let verify = |pinned: &str| {
+            let pinned = parse_pinned_key(&identity_public_key(pinned).unwrap()).unwrap();
+            let verifier = ServerCertificateVerifier { pinned: Some(pinned) };
+            verifier.verify_server_cert(&certificate, &[], &server_name, &[], UnixTime::now())
+        };
+
+        assert!(verify(&server_pem).is_ok());
+        assert!(verify(&other_pem).is_err());
+
+        // Without a pinned key, any server identity is accepted
+        let verifier = ServerCertificateVerifier { pinned: None };
+        assert!(verifier
+            .verify_server_cert(&certificate, &[], &server_name, &[], UnixTime::now())
+            .is_ok());
+    }
+}
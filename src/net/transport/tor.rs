@@ -16,18 +16,76 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::time::Duration;
+use std::{sync::OnceLock, time::Duration};
 
-use arti_client::{config::BoolOrAuto, DataStream, StreamPrefs, TorClient};
+use arti_client::{
+    config::{pt::TransportConfigBuilder, BoolOrAuto, BridgeConfigBuilder, CfgPath},
+    DataStream, StreamPrefs, TorClient, TorClientConfig,
+};
 use log::debug;
 use smol::lock::OnceCell;
 use tor_rtcompat::PreferredRuntime;
 
-use crate::{system::timeout::timeout, Result};
+use crate::{system::timeout::timeout, Error, Result};
 
 /// A static for `TorClient` reusability
 static TOR_CLIENT: OnceCell<TorClient<PreferredRuntime>> = OnceCell::new();
 
+/// Bridge lines and pluggable transport binary path the `TorClient`
+/// gets bootstrapped with, if configured
+static TOR_BRIDGES: OnceLock<(Vec<String>, Option<String>)> = OnceLock::new();
+
+/// Make the Tor transport reach the Tor network through the given bridges
+/// (e.g. `obfs4 1.2.3.4:443 <fingerprint> cert=... iat-mode=0`), served by
+/// the pluggable transport binary at `pt_path` (e.g. `obfs4proxy`) when
+/// they use one. Since the `TorClient` is shared by the whole process,
+/// this must be called before the first Tor connection, and only once.
+pub fn set_tor_bridges(bridges: Vec<String>, pt_path: Option<String>) -> Result<()> {
+    if TOR_CLIENT.get().is_some() {
+        return Err(Error::TorError("Tor client is already bootstrapped".to_string()))
+    }
+
+    TOR_BRIDGES
+        .set((bridges, pt_path))
+        .map_err(|_| Error::TorError("Tor bridges are already configured".to_string()))
+}
+
+/// Build a `TorClientConfig` using the given bridges and pluggable transport
+fn bridges_config(bridges: &[String], pt_path: Option<&str>) -> Result<TorClientConfig> {
+    let mut builder = TorClientConfig::builder();
+
+    // Bridge lines using a pluggable transport start with its name,
+    // while direct ones start with the bridge address.
+    let mut protocols = vec![];
+    for line in bridges {
+        let bridge: BridgeConfigBuilder =
+            line.parse().map_err(|e| Error::TorError(format!("Invalid bridge line: {e}")))?;
+        builder.bridges().bridges().push(bridge);
+
+        let Some(name) = line.split_whitespace().next() else { continue };
+        if name.contains(':') {
+            continue
+        }
+        if let Ok(protocol) = name.parse() {
+            if !protocols.contains(&protocol) {
+                protocols.push(protocol);
+            }
+        }
+    }
+
+    if !protocols.is_empty() {
+        let Some(pt_path) = pt_path else {
+            return Err(Error::TorError("Bridges need a pluggable transport binary".to_string()))
+        };
+
+        let mut transport = TransportConfigBuilder::default();
+        transport.protocols(protocols).path(CfgPath::new(pt_path.to_string())).run_on_startup(true);
+        builder.bridges().transports().push(transport);
+    }
+
+    builder.build().map_err(|e| Error::TorError(format!("Invalid Tor bridges config: {e}")))
+}
+
 /// Tor Dialer implementation
 #[derive(Debug, Clone)]
 pub struct TorDialer;
@@ -51,8 +109,17 @@ impl TorDialer {
         // the Tor dialer
         let client = TOR_CLIENT
             .get_or_try_init(|| async {
-                debug!(target: "net::tor::do_dial", "Bootstrapping...");
-                TorClient::builder().create_bootstrapped().await
+                let Some((bridges, pt_path)) = TOR_BRIDGES.get() else {
+                    debug!(target: "net::tor::do_dial", "Bootstrapping...");
+                    return Ok(TorClient::builder().create_bootstrapped().await?)
+                };
+
+                debug!(
+                    target: "net::tor::do_dial",
+                    "Bootstrapping through {} bridges...", bridges.len(),
+                );
+                let config = bridges_config(bridges, pt_path.as_deref())?;
+                Ok::<_, Error>(TorClient::builder().config(config).create_bootstrapped().await?)
             })
             .await?;
 
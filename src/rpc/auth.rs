@@ -0,0 +1,504 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Optional JSON-RPC request authentication.
+//!
+//! Requests carry an `auth` object next to the usual JSON-RPC fields.
+//! Two schemes are supported:
+//!
+//! * Static bearer tokens:
+//!   `"auth": {"token": "<secret>"}`
+//! * HMAC-signed requests, where the secret never goes over the wire:
+//!   `"auth": {"key": "<key id>", "ts": <unix secs>, "nonce": "<hex>", "mac": "<hex>"}`
+//!
+//! The HMAC is a keyed BLAKE3 hash over the request ID, method, timestamp,
+//! nonce, and a canonical (sorted-keys) encoding of the request params.
+//! The server remembers the nonces it accepted until their timestamp falls
+//! out of the allowed window, so a captured request can't be replayed. Each
+//! configured token optionally carries a method allowlist, so one can hand
+//! out read-only tokens while keeping admin methods for a separate one.
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+    sync::Mutex,
+};
+
+use darkfi_sdk::AsHex;
+use log::debug;
+use rand::{rngs::OsRng, RngCore};
+use tinyjson::JsonValue;
+
+use super::jsonrpc::JsonRequest;
+use crate::{util::time::Timestamp, Error, Result};
+
+/// Context string used to derive HMAC keys from configured secrets
+const HMAC_KEY_CONTEXT: &str = "darkfi:rpc:auth:hmac:v1";
+
+/// Default allowed clock difference for HMAC-signed requests, in seconds
+pub const DEFAULT_MAX_SKEW: u64 = 60;
+
+/// Maximum number of HMAC nonces remembered within the allowed window
+const MAX_SEEN_NONCES: usize = 65536;
+
+/// Credential kind of a configured token
+#[derive(Clone, Debug)]
+pub enum RpcAuthCredential {
+    /// Static bearer token, compared by its hash
+    Bearer(blake3::Hash),
+    /// HMAC key derived from the configured secret
+    Hmac([u8; 32]),
+}
+
+/// A configured authentication token along with its method allowlist
+#[derive(Clone, Debug)]
+pub struct RpcAuthToken {
+    /// Token identifier, used for logging and as the HMAC key ID
+    pub id: String,
+    /// Token credential
+    pub credential: RpcAuthCredential,
+    /// Allowed methods. `None` allows every method.
+    /// Entries may be exact method names, `prefix.*`, or `*`.
+    pub methods: Option<Vec<String>>,
+}
+
+impl RpcAuthToken {
+    /// Create a new bearer token with an optional method allowlist
+    pub fn bearer(id: &str, secret: &str, methods: Option<Vec<String>>) -> Self {
+        let credential = RpcAuthCredential::Bearer(blake3::hash(secret.as_bytes()));
+        Self { id: id.to_string(), credential, methods }
+    }
+
+    /// Create a new HMAC token with an optional method allowlist
+    pub fn hmac(id: &str, secret: &str, methods: Option<Vec<String>>) -> Self {
+        let credential =
+            RpcAuthCredential::Hmac(blake3::derive_key(HMAC_KEY_CONTEXT, secret.as_bytes()));
+        Self { id: id.to_string(), credential, methods }
+    }
+
+    /// Check if the given method is allowed for this token
+    pub fn allows(&self, method: &str) -> bool {
+        let Some(ref methods) = self.methods else { return true };
+        methods.iter().any(|m| method_matches(m, method))
+    }
+}
+
+/// Check if the given method matches a method pattern, which may be an
+/// exact method name, `prefix.*`, or `*`.
+pub(super) fn method_matches(pattern: &str, method: &str) -> bool {
+    if pattern == "*" {
+        return true
+    }
+
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        return method.starts_with(prefix)
+    }
+
+    pattern == method
+}
+
+impl FromStr for RpcAuthToken {
+    type Err = Error;
+
+    /// Parse a token from its configuration string, in the form of
+    /// `<bearer|hmac>:<id>:<secret>[:<method>,<method>,...]`
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.splitn(4, ':').collect();
+        if parts.len() < 3 || parts[1].is_empty() || parts[2].is_empty() {
+            return Err(Error::ParseFailed("Invalid RPC auth token string"))
+        }
+
+        let methods = match parts.get(3) {
+            Some(m) if !m.is_empty() => Some(m.split(',').map(|m| m.trim().to_string()).collect()),
+            _ => None,
+        };
+
+        match parts[0] {
+            "bearer" => Ok(Self::bearer(parts[1], parts[2], methods)),
+            "hmac" => Ok(Self::hmac(parts[1], parts[2], methods)),
+            _ => Err(Error::ParseFailed("Unknown RPC auth token kind")),
+        }
+    }
+}
+
+/// Reasons an incoming request was refused
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RpcAuthFailure {
+    /// Request does not carry a (valid) `auth` object
+    MissingCredentials,
+    /// Credentials did not match any configured token
+    InvalidCredentials,
+    /// HMAC timestamp is outside of the allowed window
+    Expired,
+    /// HMAC nonce was already used within the allowed window
+    Replayed,
+    /// Too many HMAC-signed requests within the allowed window
+    TooManyRequests,
+    /// Token is valid but not allowed to call the requested method
+    MethodNotAllowed,
+}
+
+impl RpcAuthFailure {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::MissingCredentials => "missing credentials",
+            Self::InvalidCredentials => "invalid credentials",
+            Self::Expired => "request signature expired",
+            Self::Replayed => "request signature replayed",
+            Self::TooManyRequests => "too many signed requests",
+            Self::MethodNotAllowed => "method not allowed",
+        }
+    }
+}
+
+/// Nonces of accepted HMAC-signed requests, kept until their timestamp
+/// falls out of the allowed window
+#[derive(Debug, Default)]
+struct SeenNonces {
+    /// Hashes of the key ID and nonce of each accepted request
+    set: HashSet<blake3::Hash>,
+    /// The same hashes along with their request timestamps, in the order
+    /// they were accepted
+    order: VecDeque<(u64, blake3::Hash)>,
+}
+
+impl SeenNonces {
+    /// Record the nonce of an accepted request, failing if it was already
+    /// seen, or if the cache is full of nonces still within the window.
+    fn insert(
+        &mut self,
+        key: &str,
+        nonce: &str,
+        ts: u64,
+        now: u64,
+        max_skew: u64,
+    ) -> std::result::Result<(), RpcAuthFailure> {
+        // Forget nonces whose requests would be refused as expired anyway
+        while let Some((seen_ts, hash)) = self.order.front() {
+            if now.abs_diff(*seen_ts) <= max_skew {
+                break
+            }
+            self.set.remove(hash);
+            self.order.pop_front();
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(key.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(nonce.as_bytes());
+        let hash = hasher.finalize();
+
+        if self.set.contains(&hash) {
+            return Err(RpcAuthFailure::Replayed)
+        }
+
+        if self.set.len() >= MAX_SEEN_NONCES {
+            return Err(RpcAuthFailure::TooManyRequests)
+        }
+
+        self.set.insert(hash);
+        self.order.push_back((ts, hash));
+        Ok(())
+    }
+}
+
+/// Authentication policy enforced by the JSON-RPC server
+#[derive(Debug)]
+pub struct RpcAuth {
+    /// Configured tokens
+    tokens: Vec<RpcAuthToken>,
+    /// Allowed clock difference for HMAC-signed requests, in seconds
+    max_skew: u64,
+    /// Nonces of recently accepted HMAC-signed requests
+    seen_nonces: Mutex<SeenNonces>,
+}
+
+impl RpcAuth {
+    /// Create a new authentication policy from the given tokens
+    pub fn new(tokens: Vec<RpcAuthToken>) -> Self {
+        Self { tokens, max_skew: DEFAULT_MAX_SKEW, seen_nonces: Mutex::new(SeenNonces::default()) }
+    }
+
+    /// Parse an authentication policy from configuration strings.
+    /// Returns `None` if no tokens are configured, meaning authentication
+    /// is disabled.
+    pub fn from_config(tokens: &[String]) -> Result<Option<Self>> {
+        if tokens.is_empty() {
+            return Ok(None)
+        }
+
+        let mut parsed = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            parsed.push(RpcAuthToken::from_str(token)?);
+        }
+
+        Ok(Some(Self::new(parsed)))
+    }
+
+    /// Set the allowed clock difference for HMAC-signed requests
+    pub fn with_max_skew(mut self, max_skew: u64) -> Self {
+        self.max_skew = max_skew;
+        self
+    }
+
+    /// Verify the given request against the configured tokens.
+    /// Returns the ID of the matched token on success.
+    pub fn verify(&self, req: &JsonRequest) -> std::result::Result<&str, RpcAuthFailure> {
+        let Some(JsonValue::Object(ref auth)) = req.auth else {
+            return Err(RpcAuthFailure::MissingCredentials)
+        };
+
+        let token = if let Some(JsonValue::String(secret)) = auth.get("token") {
+            let hash = blake3::hash(secret.as_bytes());
+            // blake3::Hash equality is constant-time
+            self.tokens
+                .iter()
+                .find(|t| matches!(t.credential, RpcAuthCredential::Bearer(h) if h == hash))
+        } else if let (
+            Some(JsonValue::String(key)),
+            Some(JsonValue::Number(ts)),
+            Some(JsonValue::String(nonce)),
+            Some(JsonValue::String(mac)),
+        ) = (auth.get("key"), auth.get("ts"), auth.get("nonce"), auth.get("mac"))
+        {
+            let Ok(mac) = blake3::Hash::from_hex(mac) else {
+                return Err(RpcAuthFailure::InvalidCredentials)
+            };
+
+            let ts = *ts as u64;
+            let now = Timestamp::current_time().inner();
+            if now.abs_diff(ts) > self.max_skew {
+                return Err(RpcAuthFailure::Expired)
+            }
+
+            let token = self.tokens.iter().find(|t| match t.credential {
+                RpcAuthCredential::Hmac(ref k) if &t.id == key => {
+                    hmac_digest(k, req.id, &req.method, ts, nonce, &req.params) == mac
+                }
+                _ => false,
+            });
+
+            // Only remember nonces of authentic requests, so others can't fill the cache
+            if let Some(token) = token {
+                self.seen_nonces.lock().unwrap().insert(
+                    &token.id,
+                    nonce,
+                    ts,
+                    now,
+                    self.max_skew,
+                )?;
+            }
+
+            token
+        } else {
+            return Err(RpcAuthFailure::MissingCredentials)
+        };
+
+        let Some(token) = token else { return Err(RpcAuthFailure::InvalidCredentials) };
+
+        if !token.allows(&req.method) {
+            debug!(
+                target: "rpc::auth",
+                "Token \"{}\" is not allowed to call {}", token.id, req.method,
+            );
+            return Err(RpcAuthFailure::MethodNotAllowed)
+        }
+
+        Ok(&token.id)
+    }
+}
+
+/// Client-side credentials used to authenticate outgoing requests
+#[derive(Clone, Debug)]
+pub enum RpcClientAuth {
+    /// Static bearer token
+    Bearer(String),
+    /// HMAC key ID and secret
+    Hmac(String, String),
+}
+
+impl RpcClientAuth {
+    /// Attach credentials to the given request
+    pub fn sign(&self, req: &mut JsonRequest) {
+        let auth = match self {
+            Self::Bearer(token) => {
+                HashMap::from([("token".to_string(), JsonValue::String(token.clone()))])
+            }
+            Self::Hmac(id, secret) => {
+                let key = blake3::derive_key(HMAC_KEY_CONTEXT, secret.as_bytes());
+                let ts = Timestamp::current_time().inner();
+                let mut nonce = [0u8; 16];
+                OsRng.fill_bytes(&mut nonce);
+                let nonce = nonce.hex();
+                let mac = hmac_digest(&key, req.id, &req.method, ts, &nonce, &req.params);
+                HashMap::from([
+                    ("key".to_string(), JsonValue::String(id.clone())),
+                    ("ts".to_string(), JsonValue::Number(ts as f64)),
+                    ("nonce".to_string(), JsonValue::String(nonce)),
+                    ("mac".to_string(), JsonValue::String(mac.to_hex().to_string())),
+                ])
+            }
+        };
+
+        req.auth = Some(JsonValue::Object(auth));
+    }
+}
+
+/// Compute the keyed BLAKE3 digest over the authenticated request fields
+fn hmac_digest(
+    key: &[u8; 32],
+    id: u16,
+    method: &str,
+    ts: u64,
+    nonce: &str,
+    params: &JsonValue,
+) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new_keyed(key);
+    hasher.update(&id.to_le_bytes());
+    hasher.update(method.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(&ts.to_le_bytes());
+    hasher.update(nonce.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(canonical_json(params).as_bytes());
+    hasher.finalize()
+}
+
+/// Deterministic JSON encoding with sorted object keys, so both sides
+/// compute the same digest regardless of map iteration order.
+fn canonical_json(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let entries: Vec<String> = keys
+                .into_iter()
+                .map(|k| {
+                    let key = JsonValue::String(k.clone()).stringify().unwrap();
+                    format!("{}:{}", key, canonical_json(&map[k]))
+                })
+                .collect();
+            format!("{{{}}}", entries.join(","))
+        }
+        JsonValue::Array(arr) => {
+            let entries: Vec<String> = arr.iter().map(canonical_json).collect();
+            format!("[{}]", entries.join(","))
+        }
+        _ => value.stringify().unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rpc_auth_tokens() {
+        let auth = RpcAuth::from_config(&[
+            "bearer:reader:r34d0nly:blockchain.*,ping".to_string(),
+            "hmac:admin:s3cr3t".to_string(),
+        ])
+        .unwrap()
+        .unwrap();
+
+        // Unauthenticated requests are refused
+        let req = JsonRequest::new("ping", JsonValue::Array(vec![]));
+        assert_eq!(auth.verify(&req), Err(RpcAuthFailure::MissingCredentials));
+
+        // Bearer token respects its allowlist
+        let reader = RpcClientAuth::Bearer("r34d0nly".to_string());
+        let mut req = JsonRequest::new("blockchain.get_block", JsonValue::Array(vec![]));
+        reader.sign(&mut req);
+        assert_eq!(auth.verify(&req), Ok("reader"));
+
+        let mut req = JsonRequest::new("dnet_switch", JsonValue::Array(vec![]));
+        reader.sign(&mut req);
+        assert_eq!(auth.verify(&req), Err(RpcAuthFailure::MethodNotAllowed));
+
+        // Wrong bearer token
+        let mut req = JsonRequest::new("ping", JsonValue::Array(vec![]));
+        RpcClientAuth::Bearer("nope".to_string()).sign(&mut req);
+        assert_eq!(auth.verify(&req), Err(RpcAuthFailure::InvalidCredentials));
+
+        // HMAC-signed request with object params
+        let admin = RpcClientAuth::Hmac("admin".to_string(), "s3cr3t".to_string());
+        let params = JsonValue::Object(HashMap::from([
+            ("b".to_string(), JsonValue::Boolean(true)),
+            ("a".to_string(), JsonValue::Number(1.0)),
+        ]));
+        let mut req = JsonRequest::new("dnet_switch", params);
+        admin.sign(&mut req);
+        assert_eq!(auth.verify(&req), Ok("admin"));
+
+        // Tampering with the params invalidates the signature
+        req.params = JsonValue::Array(vec![JsonValue::Boolean(false)]);
+        assert_eq!(auth.verify(&req), Err(RpcAuthFailure::InvalidCredentials));
+
+        // Wrong HMAC secret
+        let mut req = JsonRequest::new("ping", JsonValue::Array(vec![]));
+        RpcClientAuth::Hmac("admin".to_string(), "wrong".to_string()).sign(&mut req);
+        assert_eq!(auth.verify(&req), Err(RpcAuthFailure::InvalidCredentials));
+    }
+
+    #[test]
+    fn rpc_auth_hmac_replay() {
+        let auth = RpcAuth::from_config(&["hmac:admin:s3cr3t".to_string()]).unwrap().unwrap();
+        let admin = RpcClientAuth::Hmac("admin".to_string(), "s3cr3t".to_string());
+
+        let mut req = JsonRequest::new("ping", JsonValue::Array(vec![]));
+        admin.sign(&mut req);
+        assert_eq!(auth.verify(&req), Ok("admin"));
+
+        // The same signed request can't be replayed
+        assert_eq!(auth.verify(&req), Err(RpcAuthFailure::Replayed));
+
+        // A fresh signature over the same request is accepted
+        admin.sign(&mut req);
+        assert_eq!(auth.verify(&req), Ok("admin"));
+
+        // Requests without a nonce are refused
+        let Some(JsonValue::Object(fields)) = req.auth.as_mut() else { panic!() };
+        fields.remove("nonce");
+        assert_eq!(auth.verify(&req), Err(RpcAuthFailure::MissingCredentials));
+    }
+
+    #[test]
+    fn rpc_auth_nonce_expiry() {
+        let mut seen = SeenNonces::default();
+        assert_eq!(seen.insert("admin", "00", 1000, 1000, 60), Ok(()));
+        assert_eq!(seen.insert("admin", "00", 1000, 1030, 60), Err(RpcAuthFailure::Replayed));
+
+        // Nonces are per key
+        assert_eq!(seen.insert("other", "00", 1000, 1030, 60), Ok(()));
+
+        // Nonces are forgotten once their requests would have expired
+        assert_eq!(seen.insert("admin", "01", 1061, 1061, 60), Ok(()));
+        assert_eq!(seen.order.len(), 1);
+        assert_eq!(seen.insert("admin", "00", 1061, 1061, 60), Ok(()));
+
+        // The cache is bounded
+        let mut seen = SeenNonces::default();
+        for i in 0..MAX_SEEN_NONCES {
+            assert_eq!(seen.insert("admin", &i.to_string(), 1000, 1000, 60), Ok(()));
+        }
+        assert_eq!(
+            seen.insert("admin", "full", 1000, 1000, 60),
+            Err(RpcAuthFailure::TooManyRequests)
+        );
+        assert_eq!(seen.insert("admin", "full", 1061, 1061, 60), Ok(()));
+    }
+}
@@ -16,25 +16,106 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-use log::{debug, error};
-use smol::{channel, io::BufReader, Executor};
+use log::{debug, error, warn};
+use smol::{channel, io::BufReader, lock::RwLock, Executor};
 use tinyjson::JsonValue;
 use url::Url;
 
 use super::{
+    auth::method_matches,
     common::{read_from_stream, write_to_stream, INIT_BUF_SIZE, READ_TIMEOUT},
     jsonrpc::*,
 };
 use crate::{
-    net::transport::{Dialer, PtStream},
-    system::{io_timeout, StoppableTask, StoppableTaskPtr, SubscriberPtr},
+    net::transport::{parse_pinned_key, Dialer, PtStream},
+    system::{io_timeout, msleep, StoppableTask, StoppableTaskPtr, SubscriberPtr},
     Error, Result,
 };
 
-/// JSON-RPC client implementation using asynchronous channels.
-pub struct RpcClient {
+/// Policy used to redial the server after the connection got lost
+#[derive(Clone, Debug)]
+pub struct ReconnectPolicy {
+    /// Delay before the first redial attempt
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between redial attempts, which doubles
+    /// after each failed attempt
+    pub max_backoff: Duration,
+    /// Maximum number of consecutive failed redial attempts before giving
+    /// up. `None` keeps trying forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay to wait for before the given redial attempt, starting from 1
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(31);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Policy used to retry requests whose connection got lost after they
+/// were sent. Only idempotent methods should be retried, since the server
+/// may have already executed them. Requests that never left the client
+/// are always resent after reconnecting.
+#[derive(Clone, Debug, Default)]
+pub struct RetryPolicy {
+    /// Maximum number of retries of a single request
+    pub max_retries: u32,
+    /// Patterns of the methods that are safe to retry: an exact method
+    /// name, `prefix.*`, or `*`
+    pub methods: Vec<String>,
+}
+
+impl RetryPolicy {
+    /// Check if requests to the given method can be retried
+    fn retries(&self, method: &str) -> bool {
+        self.max_retries > 0 && self.methods.iter().any(|m| method_matches(m, method))
+    }
+}
+
+/// Parameters used to redial the server
+struct Redial {
+    endpoint: Url,
+    server_key: Option<ed25519_compact::PublicKey>,
+    policy: ReconnectPolicy,
+    ex: Arc<Executor<'static>>,
+}
+
+/// Auxiliary function to dial the given endpoint, optionally pinning the
+/// server TLS public key
+async fn dial(
+    endpoint: Url,
+    server_key: Option<ed25519_compact::PublicKey>,
+) -> Result<Box<dyn PtStream>> {
+    // TODO: Could add a timeout here
+    let mut dialer = Dialer::new(endpoint).await?;
+    if let Some(key) = server_key {
+        dialer = dialer.with_pinned_server_key(key);
+    }
+    dialer.dial(None).await
+}
+
+/// A single client-server connection, running the request-reply loop
+#[derive(Clone)]
+struct RpcConnection {
     /// The channel used to send JSON-RPC request objects.
     /// The `bool` marks if we should have a reply read timeout.
     req_send: channel::Sender<(JsonRequest, bool)>,
@@ -42,25 +123,20 @@ pub struct RpcClient {
     rep_recv: channel::Receiver<JsonResult>,
     /// The channel used to skip waiting for a JSON-RPC client request
     req_skip_send: channel::Sender<()>,
-    /// The stoppable task pointer, used on [`RpcClient::stop()`]
+    /// The stoppable task pointer of the request-reply loop
     task: StoppableTaskPtr,
+    /// Number of reconnections that led to this connection
+    generation: u64,
 }
 
-impl RpcClient {
-    /// Instantiate a new JSON-RPC client that connects to the given endpoint.
-    /// The function takes an `Executor` object, which is needed to start the
-    /// `StoppableTask` which represents the client-server connection.
-    pub async fn new(endpoint: Url, ex: Arc<Executor<'_>>) -> Result<Self> {
+impl RpcConnection {
+    /// Start the request-reply loop over an established stream
+    fn start(stream: Box<dyn PtStream>, generation: u64, ex: Arc<Executor<'_>>) -> Self {
         // Instantiate communication channels
         let (req_send, req_recv) = channel::unbounded();
         let (rep_send, rep_recv) = channel::unbounded();
         let (req_skip_send, req_skip_recv) = channel::unbounded();
 
-        // Instantiate Dialer and dial the server
-        // TODO: Could add a timeout here
-        let dialer = Dialer::new(endpoint).await?;
-        let stream = dialer.dial(None).await?;
-
         // Create the StoppableTask running the request-reply loop.
         // This represents the actual connection, which can be stopped
         // using `RpcClient::stop()`.
@@ -77,14 +153,7 @@ impl RpcClient {
             ex.clone(),
         );
 
-        Ok(Self { req_send, rep_recv, task, req_skip_send })
-    }
-
-    /// Stop the JSON-RPC client. This will trigger `stop()` on the inner
-    /// `StoppableTaskPtr` resulting in stopping the internal reqrep loop
-    /// and therefore closing the connection.
-    pub async fn stop(&self) {
-        self.task.stop().await;
+        Self { req_send, rep_recv, req_skip_send, task, generation }
     }
 
     /// Internal function that loops on a given stream and multiplexes the data
@@ -132,23 +201,189 @@ impl RpcClient {
             rep_send.send(rep).await?;
         }
     }
+}
+
+/// JSON-RPC client implementation using asynchronous channels.
+///
+/// Clients created with [`RpcClient::new_reconnecting()`] redial the server
+/// with exponential backoff when the connection gets lost, resuming their
+/// subscriptions, and optionally retry idempotent requests following their
+/// [`RetryPolicy`]. Requests must not be issued concurrently on a single
+/// client, use a [`RpcClientPool`] for that.
+pub struct RpcClient {
+    /// Current connection, replaced upon reconnection
+    conn: RwLock<RpcConnection>,
+    /// Optional redial parameters, enabling reconnection
+    redial: Option<Redial>,
+    /// Retry policy of requests whose connection got lost
+    retry: RetryPolicy,
+    /// Marks if the client was stopped, preventing reconnection
+    stopped: AtomicBool,
+}
+
+impl RpcClient {
+    /// Instantiate a new JSON-RPC client that connects to the given endpoint.
+    /// The function takes an `Executor` object, which is needed to start the
+    /// `StoppableTask` which represents the client-server connection.
+    pub async fn new(endpoint: Url, ex: Arc<Executor<'_>>) -> Result<Self> {
+        let stream = dial(endpoint, None).await?;
+        Ok(Self::start(stream, ex))
+    }
+
+    /// Instantiate a new JSON-RPC client that connects to the given `tcp+tls://`
+    /// endpoint, only accepting a server presenting a TLS certificate for the
+    /// given base64-encoded public key.
+    pub async fn new_pinned(
+        endpoint: Url,
+        server_key: &str,
+        ex: Arc<Executor<'_>>,
+    ) -> Result<Self> {
+        let server_key = parse_pinned_key(server_key)?;
+        let stream = dial(endpoint, Some(server_key)).await?;
+        Ok(Self::start(stream, ex))
+    }
+
+    /// Instantiate a new JSON-RPC client that connects to the given endpoint,
+    /// optionally pinning the server TLS public key, and redials it following
+    /// the given policy whenever the connection gets lost.
+    pub async fn new_reconnecting(
+        endpoint: Url,
+        server_key: Option<&str>,
+        policy: ReconnectPolicy,
+        ex: Arc<Executor<'static>>,
+    ) -> Result<Self> {
+        let server_key = match server_key {
+            Some(key) => Some(parse_pinned_key(key)?),
+            None => None,
+        };
+        let stream = dial(endpoint.clone(), server_key).await?;
+
+        let mut client = Self::start(stream, ex.clone());
+        client.redial = Some(Redial { endpoint, server_key, policy, ex });
+        Ok(client)
+    }
+
+    /// Set the retry policy of requests whose connection got lost.
+    /// Only applies to clients created with [`RpcClient::new_reconnecting()`].
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Internal function starting the request-reply loop over an established stream
+    fn start(stream: Box<dyn PtStream>, ex: Arc<Executor<'_>>) -> Self {
+        Self {
+            conn: RwLock::new(RpcConnection::start(stream, 0, ex)),
+            redial: None,
+            retry: RetryPolicy::default(),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Stop the JSON-RPC client. This will trigger `stop()` on the inner
+    /// `StoppableTaskPtr` resulting in stopping the internal reqrep loop
+    /// and therefore closing the connection.
+    pub async fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // If a reconnection is in progress, the lost connection is already
+        // stopped, and the reconnection will bail out seeing the flag.
+        if let Some(conn) = self.conn.try_read() {
+            conn.task.stop().await;
+        }
+    }
+
+    /// Internal function to grab the current connection
+    async fn connection(&self) -> RpcConnection {
+        self.conn.read().await.clone()
+    }
+
+    /// Internal function replacing the lost connection of the given
+    /// generation, redialing the server with exponential backoff.
+    /// Returns the original error if the client can't reconnect.
+    async fn reconnect(&self, generation: u64, error: Error) -> Result<()> {
+        let Some(ref redial) = self.redial else { return Err(error) };
+        if self.stopped.load(Ordering::SeqCst) {
+            return Err(error)
+        }
+
+        let mut conn = self.conn.write().await;
+        // Someone else already replaced the connection
+        if conn.generation != generation {
+            return Ok(())
+        }
+
+        warn!(target: "rpc::client", "[RPC] Lost connection to {}: {}", redial.endpoint, error);
+        conn.task.stop().await;
+
+        let mut attempt = 0;
+        loop {
+            if self.stopped.load(Ordering::SeqCst) {
+                return Err(Error::RpcClientStopped)
+            }
+
+            match dial(redial.endpoint.clone(), redial.server_key).await {
+                Ok(_) if self.stopped.load(Ordering::SeqCst) => return Err(Error::RpcClientStopped),
+                Ok(stream) => {
+                    debug!(target: "rpc::client", "[RPC] Reconnected to {}", redial.endpoint);
+                    *conn = RpcConnection::start(stream, generation + 1, redial.ex.clone());
+                    return Ok(())
+                }
+                Err(e) => {
+                    attempt += 1;
+                    if redial.policy.max_attempts.is_some_and(|max| attempt >= max) {
+                        return Err(e)
+                    }
+
+                    let backoff = redial.policy.backoff(attempt);
+                    warn!(
+                        target: "rpc::client",
+                        "[RPC] Failed redialing {}: {}, retrying in {:?}", redial.endpoint, e, backoff,
+                    );
+                    msleep(backoff.as_millis() as u64).await;
+                }
+            }
+        }
+    }
 
     /// Send a given JSON-RPC request over the instantiated client and
     /// return a possible result. If the response is an error, returns
     /// a `JsonRpcError`.
     pub async fn request(&self, req: JsonRequest) -> Result<JsonValue> {
-        let req_id = req.id;
-        debug!(target: "rpc::client", "--> {}", req.stringify()?);
+        let mut retries = 0;
+        loop {
+            let conn = self.connection().await;
+            debug!(target: "rpc::client", "--> {}", req.stringify()?);
+
+            // If the connection is closed, the sender will get an error
+            // for sending to a closed channel. The request never left,
+            // so it's always safe to resend it after reconnecting.
+            if let Err(e) = conn.req_send.send((req.clone(), true)).await {
+                self.reconnect(conn.generation, e.into()).await?;
+                continue
+            }
 
-        // If the connection is closed, the sender will get an error
-        // for sending to a closed channel.
-        self.req_send.send((req, true)).await?;
+            // If the connection is closed, the receiver will get an error
+            // for waiting on a closed channel. The server might have executed
+            // the request, so only retry it if allowed. Otherwise, the next
+            // request will reconnect.
+            let reply = match conn.rep_recv.recv().await {
+                Ok(v) => v,
+                Err(e) => {
+                    if retries >= self.retry.max_retries || !self.retry.retries(&req.method) {
+                        return Err(e.into())
+                    }
+                    self.reconnect(conn.generation, e.into()).await?;
+                    retries += 1;
+                    continue
+                }
+            };
 
-        // If the connection is closed, the receiver will get an error
-        // for waiting on a closed channel.
-        let reply = self.rep_recv.recv().await?;
+            return Self::handle_reply(req.id, reply)
+        }
+    }
 
-        // Handle the response
+    /// Internal function handling the reply to a JSON-RPC request
+    fn handle_reply(req_id: u16, reply: JsonResult) -> Result<JsonValue> {
         match reply {
             JsonResult::Response(rep) | JsonResult::SubscriberWithReply(_, rep) => {
                 debug!(target: "rpc::client", "<-- {}", rep.stringify()?);
@@ -204,26 +439,45 @@ impl RpcClient {
 
     /// Listen instantiated client for notifications.
     /// NOTE: Subscriber listeners must perform response handling.
+    /// Reconnecting clients resubscribe after redialing the server, so
+    /// notifications sent in the meantime are missed.
     pub async fn subscribe(&self, req: JsonRequest, sub: SubscriberPtr<JsonResult>) -> Result<()> {
+        loop {
+            let conn = self.connection().await;
+            match Self::conn_subscribe(&conn, req.clone(), &sub).await {
+                // Protocol errors are final, anything else means the connection got lost
+                Err(Error::JsonRpcError(e)) => return Err(Error::JsonRpcError(e)),
+                Err(e) => self.reconnect(conn.generation, e).await?,
+                Ok(()) => return Ok(()),
+            }
+        }
+    }
+
+    /// Internal function listening for notifications over the given connection
+    async fn conn_subscribe(
+        conn: &RpcConnection,
+        req: JsonRequest,
+        sub: &SubscriberPtr<JsonResult>,
+    ) -> Result<()> {
         // Perform initial request
         debug!(target: "rpc::client", "--> {}", req.stringify()?);
         let req_id = req.id;
 
         // If the connection is closed, the sender will get an error for
         // sending to a closed channel.
-        self.req_send.send((req, false)).await?;
+        conn.req_send.send((req, false)).await?;
 
         // Now loop and listen to notifications
         loop {
             // If the connection is closed, the receiver will get an error
             // for waiting on a closed channel.
-            let notification = self.rep_recv.recv().await?;
+            let notification = conn.rep_recv.recv().await?;
 
             // Handle the response
             match notification {
                 JsonResult::Notification(ref n) => {
                     debug!(target: "rpc::client", "<-- {}", n.stringify()?);
-                    self.req_skip_send.send(()).await?;
+                    conn.req_skip_send.send(()).await?;
                     sub.notify(notification.clone()).await;
                     continue
                 }
@@ -255,6 +509,72 @@ impl RpcClient {
     }
 }
 
+/// Pool of JSON-RPC clients, allowing requests to be executed concurrently.
+/// Each request grabs an idle client, waiting for one to become available.
+pub struct RpcClientPool {
+    /// Pooled clients
+    clients: Vec<RpcClient>,
+    /// Channel used to hand back the indexes of idle clients
+    idle_send: channel::Sender<usize>,
+    /// Channel used to grab the indexes of idle clients
+    idle_recv: channel::Receiver<usize>,
+}
+
+/// Guard handing a client back to the pool when dropped, so it doesn't
+/// leak if the request future gets canceled
+struct PooledClient<'a> {
+    pool: &'a RpcClientPool,
+    index: usize,
+}
+
+impl Drop for PooledClient<'_> {
+    fn drop(&mut self) {
+        // The channel is unbounded and owned by the pool, so this can't fail
+        let _ = self.pool.idle_send.try_send(self.index);
+    }
+}
+
+impl RpcClientPool {
+    /// Create a new pool out of the given clients
+    pub fn new(clients: Vec<RpcClient>) -> Self {
+        let (idle_send, idle_recv) = channel::unbounded();
+        for index in 0..clients.len() {
+            let _ = idle_send.try_send(index);
+        }
+
+        Self { clients, idle_send, idle_recv }
+    }
+
+    /// Number of pooled clients
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Check if the pool has no clients
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Send a given JSON-RPC request over an idle client of the pool and
+    /// return a possible result.
+    pub async fn request(&self, req: JsonRequest) -> Result<JsonValue> {
+        if self.clients.is_empty() {
+            return Err(Error::RpcClientStopped)
+        }
+
+        let index = self.idle_recv.recv().await?;
+        let client = PooledClient { pool: self, index };
+        self.clients[client.index].request(req).await
+    }
+
+    /// Stop all the pooled clients
+    pub async fn stop(&self) {
+        for client in &self.clients {
+            client.stop().await;
+        }
+    }
+}
+
 /// Highly experimental JSON-RPC client implementation using asynchronous channels,
 /// with each new request canceling waiting for the previous one. All requests are
 /// executed without a timeout.
@@ -398,3 +718,31 @@ impl RpcChadClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rpc_client_policies() {
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(3),
+            max_attempts: None,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(3));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(3));
+
+        let retry = RetryPolicy {
+            max_retries: 3,
+            methods: vec!["ping".to_string(), "blockchain.*".to_string()],
+        };
+        assert!(retry.retries("ping"));
+        assert!(retry.retries("blockchain.get_block"));
+        assert!(!retry.retries("tx.broadcast"));
+        assert!(!RetryPolicy::default().retries("ping"));
+    }
+}
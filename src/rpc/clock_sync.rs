@@ -51,6 +51,17 @@ pub async fn ntp_request() -> Result<Timestamp> {
     Ok(timestamp)
 }
 
+/// Returns the offset in seconds of NTP time from the system clock.
+/// A positive offset means the system clock is behind.
+pub async fn ntp_clock_offset() -> Result<i64> {
+    // Start elapsed time counter to cover for NTP request and processing time
+    let ntp_request_start = Timestamp::current_time();
+    let ntp_time = ntp_request().await?;
+    let ntp_time = ntp_time.checked_add(ntp_request_start.elapsed()?)?;
+
+    Ok(ntp_time.inner() as i64 - Timestamp::current_time().inner() as i64)
+}
+
 /// This is a very simple check to verify that the system time is correct.
 /// Retry loop is used in case discrepancies are found.
 /// If all retries fail, system clock is considered invalid.
@@ -16,12 +16,23 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+#[cfg(feature = "tx")]
+use darkfi_sdk::AsHex;
+#[cfg(feature = "tx")]
+use darkfi_serial::serialize;
+
 use super::util::*;
 use crate::net;
 
+#[cfg(feature = "blockchain")]
+use crate::blockchain;
+
 #[cfg(feature = "event-graph")]
 use crate::event_graph;
 
+#[cfg(feature = "tx")]
+use crate::tx;
+
 #[cfg(feature = "net")]
 impl From<net::channel::ChannelInfo> for JsonValue {
     fn from(info: net::channel::ChannelInfo) -> JsonValue {
@@ -168,3 +179,70 @@ impl From<event_graph::deg::DegEvent> for JsonValue {
         }
     }
 }
+
+#[cfg(feature = "blockchain")]
+impl From<blockchain::Header> for JsonValue {
+    fn from(header: blockchain::Header) -> JsonValue {
+        let tree_root = match header.tree.root(0) {
+            Some(root) => JsonStr(root.to_string()),
+            None => JsonValue::Null,
+        };
+        json_map([
+            ("hash", JsonStr(header.hash().to_string())),
+            ("version", JsonNum(header.version.into())),
+            ("previous", JsonStr(header.previous.to_string())),
+            ("height", JsonNum(header.height as f64)),
+            ("timestamp", JsonNum(header.timestamp.inner() as f64)),
+            ("nonce", JsonNum(header.nonce as f64)),
+            ("tree_root", tree_root),
+        ])
+    }
+}
+
+#[cfg(feature = "blockchain")]
+impl From<blockchain::BlockInfo> for JsonValue {
+    fn from(block: blockchain::BlockInfo) -> JsonValue {
+        let txs = block.txs.iter().map(|tx| JsonStr(tx.hash().to_string())).collect();
+        json_map([
+            ("hash", JsonStr(block.hash().to_string())),
+            ("header", block.header.into()),
+            ("txs", JsonArray(txs)),
+            ("signature", JsonStr(serialize(&block.signature).hex())),
+        ])
+    }
+}
+
+#[cfg(feature = "tx")]
+impl From<tx::Transaction> for JsonValue {
+    fn from(tx: tx::Transaction) -> JsonValue {
+        let mut calls = Vec::with_capacity(tx.calls.len());
+        for (i, call) in tx.calls.iter().enumerate() {
+            let parent_index = match call.parent_index {
+                Some(index) => JsonNum(index as f64),
+                None => JsonValue::Null,
+            };
+            let children = call.children_indexes.iter().map(|i| JsonNum(*i as f64)).collect();
+            let function = match call.data.data.first() {
+                Some(function) => JsonNum((*function).into()),
+                None => JsonValue::Null,
+            };
+            let proofs = tx.proofs.get(i).map_or(0, |p| p.len());
+            let signatures = match tx.signatures.get(i) {
+                Some(sigs) => sigs.iter().map(|sig| JsonStr(serialize(sig).hex())).collect(),
+                None => vec![],
+            };
+
+            calls.push(json_map([
+                ("contract_id", JsonStr(call.data.contract_id.to_string())),
+                ("function", function),
+                ("data", JsonStr(call.data.data.hex())),
+                ("parent_index", parent_index),
+                ("children_indexes", JsonArray(children)),
+                ("proofs", JsonNum(proofs as f64)),
+                ("signatures", JsonArray(signatures)),
+            ]));
+        }
+
+        json_map([("hash", JsonStr(tx.hash().to_string())), ("calls", JsonArray(calls))])
+    }
+}
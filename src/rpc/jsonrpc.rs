@@ -47,6 +47,10 @@ pub enum ErrorCode {
     IdMismatch,
     /// Invalid/Unexpected reply
     InvalidReply,
+    /// Request failed authentication or authorization
+    Unauthorized,
+    /// Request exceeded the server rate limits
+    RateLimited,
     /// Reserved for implementation-defined server-errors.
     ServerError(i32),
 }
@@ -61,6 +65,8 @@ impl ErrorCode {
             Self::InternalError => -32603,
             Self::IdMismatch => -32360,
             Self::InvalidReply => -32361,
+            Self::Unauthorized => -32362,
+            Self::RateLimited => -32363,
             Self::ServerError(c) => c,
         }
     }
@@ -74,6 +80,8 @@ impl ErrorCode {
             Self::InternalError => "internal error".to_string(),
             Self::IdMismatch => "id mismatch".to_string(),
             Self::InvalidReply => "invalid reply".to_string(),
+            Self::Unauthorized => "unauthorized".to_string(),
+            Self::RateLimited => "rate limited".to_string(),
             Self::ServerError(_) => "server error".to_string(),
         }
     }
@@ -156,6 +164,8 @@ pub struct JsonRequest {
     pub method: String,
     /// Request parameters
     pub params: JsonValue,
+    /// Optional request credentials, see [`super::auth`]
+    pub auth: Option<JsonValue>,
 }
 // ANCHOR_END: jsonrequest
 
@@ -164,7 +174,13 @@ impl JsonRequest {
     /// The request ID is chosen randomly.
     pub fn new(method: &str, params: JsonValue) -> Self {
         assert!(params.is_object() || params.is_array());
-        Self { jsonrpc: "2.0", id: OsRng::gen(&mut OsRng), method: method.to_string(), params }
+        Self {
+            jsonrpc: "2.0",
+            id: OsRng::gen(&mut OsRng),
+            method: method.to_string(),
+            params,
+            auth: None,
+        }
     }
 
     /// Convert the object into a JSON string
@@ -176,12 +192,18 @@ impl JsonRequest {
 
 impl From<&JsonRequest> for JsonValue {
     fn from(req: &JsonRequest) -> JsonValue {
-        JsonValue::Object(HashMap::from([
+        let mut map = HashMap::from([
             ("jsonrpc".to_string(), JsonValue::String(req.jsonrpc.to_string())),
             ("id".to_string(), JsonValue::Number(req.id.into())),
             ("method".to_string(), JsonValue::String(req.method.clone())),
             ("params".to_string(), req.params.clone()),
-        ]))
+        ]);
+
+        if let Some(ref auth) = req.auth {
+            map.insert("auth".to_string(), auth.clone());
+        }
+
+        JsonValue::Object(map)
     }
 }
 
@@ -233,6 +255,7 @@ impl TryFrom<&JsonValue> for JsonRequest {
             id: *map["id"].get::<f64>().unwrap() as u16,
             method: map["method"].get::<String>().unwrap().clone(),
             params: map["params"].clone(),
+            auth: map.get("auth").cloned(),
         })
     }
 }
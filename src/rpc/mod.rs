@@ -28,13 +28,28 @@ pub mod client;
 /// Server-side JSON-RPC implementation
 pub mod server;
 
+/// Optional JSON-RPC request authentication
+pub mod auth;
+
+/// Optional JSON-RPC request rate limiting
+pub mod ratelimit;
+
+/// JSON-RPC method registry, used for introspection
+pub mod schema;
+
+/// Cursor-based pagination convention for large results
+pub mod pagination;
+
+/// Read-only HTTP/REST facade over JSON-RPC handlers
+pub mod rest;
+
 /// Clock sync utility module
 pub mod clock_sync;
 
 /// Various `From` implementations
 pub mod from_impl;
 
-/// Provides optional `p2p.get_info()` method
+/// Provides optional `p2p.get_info()` and `p2p.get_hosts()` methods
 pub mod p2p_method;
 
 /// Json helper methods and types
@@ -19,10 +19,11 @@
 use async_trait::async_trait;
 
 use super::{
-    jsonrpc::{JsonResponse, JsonResult},
+    jsonrpc::{ErrorCode, JsonError, JsonResponse, JsonResult},
+    pagination::{Page, PageRequest},
     util::*,
 };
-use crate::net;
+use crate::net::{self, hosts::HostColor};
 
 #[async_trait]
 pub trait HandlerP2p: Sync + Send {
@@ -53,5 +54,49 @@ pub trait HandlerP2p: Sync + Send {
         JsonResponse::new(result, id).into()
     }
 
+    /// Page through the known hosts of the given hostlist (`grey`, `white`,
+    /// `gold`, `black` or `dark`), ordered by last seen, most recent first.
+    /// The cursor is the offset into the hostlist, which may shift between
+    /// calls as hosts get updated.
+    async fn p2p_get_hosts(&self, id: u16, params: JsonValue) -> JsonResult {
+        let params = params.get::<Vec<JsonValue>>().unwrap();
+        let Ok((params, page)) = PageRequest::split_params(params) else {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        };
+        if params.len() != 1 || !params[0].is_string() {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        }
+
+        let color = match params[0].get::<String>().unwrap().as_str() {
+            "grey" => HostColor::Grey,
+            "white" => HostColor::White,
+            "gold" => HostColor::Gold,
+            "black" => HostColor::Black,
+            "dark" => HostColor::Dark,
+            _ => return JsonError::new(ErrorCode::InvalidParams, None, id).into(),
+        };
+
+        let page = page.unwrap_or_default();
+        let Ok(cursor) = page.u64_cursor() else {
+            return JsonError::new(ErrorCode::InvalidParams, None, id).into()
+        };
+        let offset = match cursor {
+            Some(c) => match usize::try_from(c).ok().and_then(|c| c.checked_add(1)) {
+                Some(offset) => offset,
+                None => return JsonError::new(ErrorCode::InvalidParams, None, id).into(),
+            },
+            None => 0,
+        };
+
+        let hosts = self.p2p().hosts().container.fetch_page(color, offset, page.limit + 1).await;
+        let entries = hosts.into_iter().enumerate().map(|(i, (url, last_seen))| {
+            let host =
+                json_map([("url", JsonStr(url.into())), ("last_seen", JsonNum(last_seen as f64))]);
+            ((offset + i).to_string(), host)
+        });
+
+        JsonResponse::new(Page::collect(entries, page.limit).to_json(), id).into()
+    }
+
     fn p2p(&self) -> net::P2pPtr;
 }
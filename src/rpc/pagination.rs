@@ -0,0 +1,235 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Cursor-based pagination convention for JSON-RPC methods returning
+//! large result sets.
+//!
+//! A paginated method accepts an optional trailing object in its params
+//! array: `{"cursor": "...", "limit": 100}`. Both fields are optional.
+//! The cursor is opaque to clients; they should only ever pass back a
+//! `next_cursor` they previously received. The reply is an object:
+//! `{"items": [...], "next_cursor": "..."}`, where `next_cursor` is
+//! `null` once the last page has been returned.
+use std::collections::HashMap;
+
+use tinyjson::JsonValue;
+
+use crate::{Error, Result};
+
+/// Number of items returned when a request doesn't specify a limit
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+
+/// Maximum number of items a single page can contain
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+/// Pagination parameters of a JSON-RPC request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageRequest {
+    /// Return items after this cursor. `None` starts from the beginning.
+    pub cursor: Option<String>,
+    /// Maximum number of items to return
+    pub limit: usize,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self { cursor: None, limit: DEFAULT_PAGE_LIMIT }
+    }
+}
+
+impl PageRequest {
+    /// Create a new `PageRequest`. The limit is clamped to `[1, MAX_PAGE_LIMIT]`.
+    pub fn new(cursor: Option<String>, limit: usize) -> Self {
+        Self { cursor, limit: limit.clamp(1, MAX_PAGE_LIMIT) }
+    }
+
+    /// Parse a `PageRequest` from its JSON object representation.
+    pub fn from_json(value: &JsonValue) -> Result<Self> {
+        let Some(map) = value.get::<HashMap<String, JsonValue>>() else {
+            return Err(Error::ParseFailed("Page request is not an object"))
+        };
+
+        let cursor = match map.get("cursor") {
+            None | Some(JsonValue::Null) => None,
+            Some(JsonValue::String(s)) => Some(s.clone()),
+            Some(_) => return Err(Error::ParseFailed("Page cursor is not a string")),
+        };
+
+        let limit = match map.get("limit") {
+            None | Some(JsonValue::Null) => DEFAULT_PAGE_LIMIT,
+            Some(JsonValue::Number(n)) if *n >= 1.0 && n.fract() == 0.0 => *n as usize,
+            Some(_) => return Err(Error::ParseFailed("Page limit is not a positive integer")),
+        };
+
+        Ok(Self::new(cursor, limit))
+    }
+
+    /// Split a JSON-RPC params array into the method's positional params
+    /// and an optional trailing `PageRequest` object.
+    pub fn split_params(params: &[JsonValue]) -> Result<(&[JsonValue], Option<Self>)> {
+        match params.last() {
+            Some(last @ JsonValue::Object(_)) => {
+                Ok((&params[..params.len() - 1], Some(Self::from_json(last)?)))
+            }
+            _ => Ok((params, None)),
+        }
+    }
+
+    /// Interpret the cursor as a `u64` key, as used by height or
+    /// index ordered stores.
+    pub fn u64_cursor(&self) -> Result<Option<u64>> {
+        match self.cursor {
+            Some(ref c) => match c.parse::<u64>() {
+                Ok(v) => Ok(Some(v)),
+                Err(_) => Err(Error::ParseFailed("Page cursor is not a valid u64")),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Convert the `PageRequest` into its JSON object representation,
+    /// to be appended to a request's params.
+    pub fn to_json(&self) -> JsonValue {
+        let cursor = match self.cursor {
+            Some(ref c) => JsonValue::String(c.clone()),
+            None => JsonValue::Null,
+        };
+
+        JsonValue::Object(HashMap::from([
+            ("cursor".to_string(), cursor),
+            ("limit".to_string(), JsonValue::Number(self.limit as f64)),
+        ]))
+    }
+}
+
+/// A single page of JSON-RPC results
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page {
+    /// The items in this page
+    pub items: Vec<JsonValue>,
+    /// Cursor to request the next page with, `None` if this is the last one
+    pub next_cursor: Option<String>,
+}
+
+impl Page {
+    /// Build a `Page` from an iterator of `(cursor, item)` entries, already
+    /// positioned right after the requested cursor. At most `limit + 1`
+    /// entries are consumed, the extra one telling us whether another
+    /// page follows.
+    pub fn collect<I>(entries: I, limit: usize) -> Self
+    where
+        I: IntoIterator<Item = (String, JsonValue)>,
+    {
+        let mut items = Vec::with_capacity(limit);
+        let mut last_cursor = None;
+        let mut has_more = false;
+
+        for (cursor, item) in entries {
+            if items.len() == limit {
+                has_more = true;
+                break
+            }
+            items.push(item);
+            last_cursor = Some(cursor);
+        }
+
+        Self { items, next_cursor: if has_more { last_cursor } else { None } }
+    }
+
+    /// Convert the `Page` into its JSON object representation
+    pub fn to_json(self) -> JsonValue {
+        let next_cursor = match self.next_cursor {
+            Some(c) => JsonValue::String(c),
+            None => JsonValue::Null,
+        };
+
+        JsonValue::Object(HashMap::from([
+            ("items".to_string(), JsonValue::Array(self.items)),
+            ("next_cursor".to_string(), next_cursor),
+        ]))
+    }
+
+    /// Parse a `Page` from its JSON object representation
+    pub fn from_json(value: &JsonValue) -> Result<Self> {
+        let Some(map) = value.get::<HashMap<String, JsonValue>>() else {
+            return Err(Error::ParseFailed("Page is not an object"))
+        };
+
+        let Some(JsonValue::Array(items)) = map.get("items") else {
+            return Err(Error::ParseFailed("Page items is not an array"))
+        };
+
+        let next_cursor = match map.get("next_cursor") {
+            None | Some(JsonValue::Null) => None,
+            Some(JsonValue::String(s)) => Some(s.clone()),
+            Some(_) => return Err(Error::ParseFailed("Page next_cursor is not a string")),
+        };
+
+        Ok(Self { items: items.clone(), next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rpc_pagination() {
+        let entries = |start: u64| {
+            (start..10).map(|i| (i.to_string(), JsonValue::Number(i as f64))).collect::<Vec<_>>()
+        };
+
+        // Walk all pages and check we see every item exactly once
+        let mut request = PageRequest::new(None, 4);
+        let mut seen = vec![];
+        loop {
+            let start = request.u64_cursor().unwrap().map_or(0, |c| c + 1);
+            let page = Page::collect(entries(start), request.limit);
+            let page = Page::from_json(&page.to_json()).unwrap();
+            for item in &page.items {
+                seen.push(*item.get::<f64>().unwrap() as u64);
+            }
+            match page.next_cursor {
+                Some(c) => request = PageRequest::new(Some(c), request.limit),
+                None => break,
+            }
+        }
+        assert_eq!(seen, (0..10).collect::<Vec<_>>());
+
+        // A page ending exactly on the last item has no next cursor
+        let page = Page::collect(entries(5), 5);
+        assert_eq!(page.items.len(), 5);
+        assert!(page.next_cursor.is_none());
+
+        // Trailing page objects are split off the positional params
+        let params = vec![JsonValue::String("white".to_string()), request.to_json()];
+        let (positional, page_req) = PageRequest::split_params(&params).unwrap();
+        assert_eq!(positional.len(), 1);
+        assert_eq!(page_req.unwrap(), request);
+
+        // Limits are clamped
+        assert_eq!(PageRequest::new(None, 0).limit, 1);
+        assert_eq!(PageRequest::new(None, MAX_PAGE_LIMIT + 1).limit, MAX_PAGE_LIMIT);
+
+        let bad = JsonValue::Object(HashMap::from([(
+            "limit".to_string(),
+            JsonValue::String("10".to_string()),
+        )]));
+        assert!(PageRequest::from_json(&bad).is_err());
+    }
+}
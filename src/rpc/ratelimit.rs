@@ -0,0 +1,364 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Optional JSON-RPC request rate limiting.
+//!
+//! Every connection gets its own token buckets: one shared by all of its
+//! requests, and one per configured method pattern, so expensive calls
+//! like block range scans can be given a tighter quota than the rest.
+//! Limits are configured as `<rate>[:<burst>]`, where `rate` is the
+//! number of requests refilled per second and `burst` the bucket size.
+//!
+//! Requests exceeding a limit are refused. Optionally, clients getting
+//! refused too often within a minute have their host banned for a while,
+//! dropping their connection and refusing new ones.
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::debug;
+use url::Url;
+
+use super::auth::method_matches;
+use crate::{Error, Result};
+
+/// Default duration of a client ban, in seconds
+pub const DEFAULT_BAN_DURATION: u64 = 600;
+
+/// Window in which refused requests count towards a ban
+const STRIKE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Token bucket parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Tokens refilled per second
+    pub rate: f64,
+    /// Maximum number of tokens in the bucket
+    pub burst: f64,
+}
+
+impl FromStr for RateLimit {
+    type Err = Error;
+
+    /// Parse a limit from its configuration string, in the form of
+    /// `<rate>[:<burst>]`. The burst defaults to the rate, rounded up.
+    fn from_str(s: &str) -> Result<Self> {
+        let (rate, burst) = match s.split_once(':') {
+            Some((rate, burst)) => (rate, Some(burst)),
+            None => (s, None),
+        };
+
+        let Ok(rate) = rate.trim().parse::<f64>() else {
+            return Err(Error::ParseFailed("Invalid RPC rate limit rate"))
+        };
+        let burst = match burst {
+            Some(b) => match b.trim().parse::<f64>() {
+                Ok(b) => b,
+                Err(_) => return Err(Error::ParseFailed("Invalid RPC rate limit burst")),
+            },
+            None => rate.ceil(),
+        };
+
+        if !rate.is_finite() || rate <= 0.0 || !burst.is_finite() || burst < 1.0 {
+            return Err(Error::ParseFailed("Invalid RPC rate limit"))
+        }
+
+        Ok(Self { rate, burst })
+    }
+}
+
+/// A rate limit applied to the methods matching a pattern
+#[derive(Clone, Debug, PartialEq)]
+pub struct MethodRateLimit {
+    /// Method pattern: an exact method name, `prefix.*`, or `*`
+    pub method: String,
+    /// Limit applied to the matching methods
+    pub limit: RateLimit,
+}
+
+impl FromStr for MethodRateLimit {
+    type Err = Error;
+
+    /// Parse a method limit from its configuration string, in the form of
+    /// `<method>:<rate>[:<burst>]`
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((method, limit)) = s.split_once(':') else {
+            return Err(Error::ParseFailed("Invalid RPC method rate limit string"))
+        };
+
+        if method.is_empty() {
+            return Err(Error::ParseFailed("Invalid RPC method rate limit string"))
+        }
+
+        Ok(Self { method: method.to_string(), limit: RateLimit::from_str(limit)? })
+    }
+}
+
+/// A token bucket refilled continuously over time
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self { limit, tokens: limit.burst, last: now }
+    }
+
+    /// Refill the bucket and try to take a token from it
+    fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.rate).min(self.limit.burst);
+        self.last = now;
+
+        if self.tokens < 1.0 {
+            return false
+        }
+
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// Reasons an incoming request was refused
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RateLimitFailure {
+    /// The connection exceeded its overall request rate
+    Connection,
+    /// The connection exceeded the request rate of the method
+    Method,
+}
+
+impl RateLimitFailure {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Connection => "rate limit exceeded",
+            Self::Method => "method rate limit exceeded",
+        }
+    }
+}
+
+/// Rate limiting state of a single connection
+#[derive(Clone, Debug)]
+pub struct ConnectionRateLimiter {
+    /// Bucket shared by all requests
+    conn: Option<TokenBucket>,
+    /// Buckets of the configured method patterns, in configuration order
+    methods: Vec<(String, TokenBucket)>,
+}
+
+impl ConnectionRateLimiter {
+    /// Check if a request to the given method is allowed, consuming a token
+    /// from the connection bucket and from the bucket of the first method
+    /// pattern matching it.
+    pub fn check(&mut self, method: &str) -> std::result::Result<(), RateLimitFailure> {
+        self.check_at(method, Instant::now())
+    }
+
+    fn check_at(
+        &mut self,
+        method: &str,
+        now: Instant,
+    ) -> std::result::Result<(), RateLimitFailure> {
+        if let Some(ref mut bucket) = self.conn {
+            if !bucket.try_take(now) {
+                return Err(RateLimitFailure::Connection)
+            }
+        }
+
+        if let Some((_, bucket)) = self.methods.iter_mut().find(|(m, _)| method_matches(m, method))
+        {
+            if !bucket.try_take(now) {
+                return Err(RateLimitFailure::Method)
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rate limiting policy enforced by the JSON-RPC server
+#[derive(Debug)]
+pub struct RpcRateLimit {
+    /// Overall request limit of each connection
+    conn: Option<RateLimit>,
+    /// Per-method request limits of each connection
+    methods: Vec<MethodRateLimit>,
+    /// Number of refused requests within [`STRIKE_WINDOW`] leading to a ban.
+    /// `None` disables bans.
+    ban_threshold: Option<u32>,
+    /// Duration of a client ban
+    ban_duration: Duration,
+    /// Refused requests count and window start, by client host
+    strikes: Mutex<HashMap<String, (u32, Instant)>>,
+    /// Ban expiry, by client host
+    bans: Mutex<HashMap<String, Instant>>,
+}
+
+impl RpcRateLimit {
+    /// Create a new rate limiting policy from the given limits
+    pub fn new(conn: Option<RateLimit>, methods: Vec<MethodRateLimit>) -> Self {
+        Self {
+            conn,
+            methods,
+            ban_threshold: None,
+            ban_duration: Duration::from_secs(DEFAULT_BAN_DURATION),
+            strikes: Mutex::new(HashMap::new()),
+            bans: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Parse a rate limiting policy from configuration strings.
+    /// Returns `None` if no limits are configured, meaning rate limiting
+    /// is disabled.
+    pub fn from_config(conn: Option<&str>, methods: &[String]) -> Result<Option<Self>> {
+        if conn.is_none() && methods.is_empty() {
+            return Ok(None)
+        }
+
+        let conn = match conn {
+            Some(c) => Some(RateLimit::from_str(c)?),
+            None => None,
+        };
+
+        let mut parsed = Vec::with_capacity(methods.len());
+        for method in methods {
+            parsed.push(MethodRateLimit::from_str(method)?);
+        }
+
+        Ok(Some(Self::new(conn, parsed)))
+    }
+
+    /// Ban clients whose requests got refused `threshold` times within a
+    /// minute, for the given number of seconds
+    pub fn with_ban(mut self, threshold: u32, duration: u64) -> Self {
+        self.ban_threshold = Some(threshold);
+        self.ban_duration = Duration::from_secs(duration);
+        self
+    }
+
+    /// Create the rate limiting state of a new connection
+    pub fn connection_limiter(&self) -> ConnectionRateLimiter {
+        let now = Instant::now();
+        ConnectionRateLimiter {
+            conn: self.conn.map(|l| TokenBucket::new(l, now)),
+            methods: self
+                .methods
+                .iter()
+                .map(|m| (m.method.clone(), TokenBucket::new(m.limit, now)))
+                .collect(),
+        }
+    }
+
+    /// Check if the host of the given client address is currently banned
+    pub fn is_banned(&self, addr: &Url) -> bool {
+        let Some(host) = addr.host_str() else { return false };
+        self.is_banned_at(host, Instant::now())
+    }
+
+    fn is_banned_at(&self, host: &str, now: Instant) -> bool {
+        let mut bans = self.bans.lock().unwrap();
+        match bans.get(host) {
+            Some(until) if *until > now => true,
+            Some(_) => {
+                bans.remove(host);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record a refused request from the given client address.
+    /// Returns `true` if its host got banned because of it.
+    pub fn strike(&self, addr: &Url) -> bool {
+        let Some(host) = addr.host_str() else { return false };
+        self.strike_at(host, Instant::now())
+    }
+
+    fn strike_at(&self, host: &str, now: Instant) -> bool {
+        let Some(threshold) = self.ban_threshold else { return false };
+
+        let mut strikes = self.strikes.lock().unwrap();
+        // Forget about expired strike windows, so the map doesn't grow unbounded
+        strikes.retain(|_, (_, since)| now.saturating_duration_since(*since) < STRIKE_WINDOW);
+
+        let (count, _) = strikes.entry(host.to_string()).or_insert((0, now));
+        *count += 1;
+        if *count < threshold {
+            return false
+        }
+
+        strikes.remove(host);
+        drop(strikes);
+
+        debug!(target: "rpc::ratelimit", "Banning {} for {:?}", host, self.ban_duration);
+        self.bans.lock().unwrap().insert(host.to_string(), now + self.ban_duration);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rpc_rate_limits() {
+        assert!(RateLimit::from_str("0").is_err());
+        assert!(RateLimit::from_str("1:0").is_err());
+        assert!(MethodRateLimit::from_str("ping").is_err());
+        assert_eq!(RateLimit::from_str("0.5").unwrap(), RateLimit { rate: 0.5, burst: 1.0 });
+
+        let rl = RpcRateLimit::from_config(
+            Some("10:3"),
+            &["blockchain.get_blocks:1:1".to_string(), "blockchain.*:5".to_string()],
+        )
+        .unwrap()
+        .unwrap()
+        .with_ban(2, 60);
+
+        let now = Instant::now();
+        let mut limiter = rl.connection_limiter();
+
+        // First matching pattern applies
+        assert_eq!(limiter.check_at("blockchain.get_blocks", now), Ok(()));
+        assert_eq!(limiter.check_at("blockchain.get_blocks", now), Err(RateLimitFailure::Method));
+
+        // Connection burst is exhausted
+        assert_eq!(limiter.check_at("ping", now), Ok(()));
+        assert_eq!(limiter.check_at("ping", now), Err(RateLimitFailure::Connection));
+
+        // Buckets refill over time
+        let later = now + Duration::from_secs(1);
+        assert_eq!(limiter.check_at("blockchain.get_blocks", later), Ok(()));
+
+        // Bans kick in after enough strikes and expire
+        assert!(!rl.strike_at("127.0.0.1", now));
+        assert!(!rl.is_banned_at("127.0.0.1", now));
+        assert!(rl.strike_at("127.0.0.1", now));
+        assert!(rl.is_banned_at("127.0.0.1", now));
+        assert!(!rl.is_banned_at("127.0.0.2", now));
+        assert!(!rl.is_banned_at("127.0.0.1", now + Duration::from_secs(61)));
+
+        assert!(RpcRateLimit::from_config(None, &[]).unwrap().is_none());
+    }
+}
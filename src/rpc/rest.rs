@@ -0,0 +1,404 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Read-only HTTP/REST facade over a JSON-RPC [`RequestHandler`].
+//!
+//! Each [`RestRoute`] maps a `GET` path onto a JSON-RPC method. Path
+//! segments written as `{name}` are captured and passed to the method
+//! as positional string params, followed by the route's query params
+//! in their declared order. Missing query params are passed as empty
+//! strings. Paged routes additionally accept the `cursor` and `limit`
+//! query params, forwarded as a trailing pagination object.
+//!
+//! Successful calls reply with the JSON-RPC `result` as the response
+//! body, while failures reply with `{"error": {"code": ..., "message": ...}}`
+//! and a matching HTTP status. When the handler enforces authentication,
+//! clients pass their bearer token in an `Authorization: Bearer` header.
+use std::{collections::HashMap, io::ErrorKind, sync::Arc};
+
+use log::{debug, error, info};
+use smol::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tinyjson::JsonValue;
+use url::Url;
+
+use super::{
+    jsonrpc::{ErrorCode, JsonRequest, JsonResult},
+    pagination::PageRequest,
+    server::RequestHandler,
+};
+use crate::{
+    net::transport::{Listener, PtListener, PtStream},
+    system::StoppableTask,
+    Error, Result,
+};
+
+/// Maximum accepted size of an HTTP request head
+const MAX_REQUEST_HEAD_SIZE: u64 = 8192;
+
+/// Mapping of a `GET` path onto a JSON-RPC method
+#[derive(Clone, Debug)]
+pub struct RestRoute {
+    /// Path pattern, e.g. `/blocks/{height}`
+    pub path: &'static str,
+    /// JSON-RPC method to call
+    pub method: &'static str,
+    /// Query params passed as positional params after the path captures
+    pub query: &'static [&'static str],
+    /// Forward `cursor` and `limit` query params as a pagination object
+    pub paged: bool,
+}
+
+impl RestRoute {
+    /// Try to match the given path segments, returning the captured ones
+    fn captures(&self, segments: &[String]) -> Option<Vec<String>> {
+        let pattern: Vec<&str> = self.path.split('/').filter(|s| !s.is_empty()).collect();
+        if pattern.len() != segments.len() {
+            return None
+        }
+
+        let mut captures = vec![];
+        for (p, s) in pattern.iter().zip(segments) {
+            if p.starts_with('{') && p.ends_with('}') {
+                captures.push(s.clone());
+            } else if p != s {
+                return None
+            }
+        }
+
+        Some(captures)
+    }
+}
+
+/// Decode a percent-encoded path segment
+fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = segment.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/// Resolve a request target against the given routes, returning the
+/// JSON-RPC method and params to call. Returns `Ok(None)` if no route
+/// matches.
+fn resolve(routes: &[RestRoute], target: &str) -> Result<Option<(&'static str, JsonValue)>> {
+    let Ok(url) = Url::parse(&format!("http://localhost{target}")) else {
+        return Err(Error::ParseFailed("Invalid HTTP request target"))
+    };
+
+    let mut segments = vec![];
+    for segment in url.path().split('/').filter(|s| !s.is_empty()) {
+        let Some(segment) = percent_decode(segment) else {
+            return Err(Error::ParseFailed("Invalid percent-encoding in HTTP request path"))
+        };
+        segments.push(segment);
+    }
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    for route in routes {
+        let Some(captures) = route.captures(&segments) else { continue };
+
+        let mut params: Vec<JsonValue> = captures.into_iter().map(JsonValue::String).collect();
+        for name in route.query {
+            params.push(JsonValue::String(query.get(*name).cloned().unwrap_or_default()));
+        }
+
+        if route.paged && (query.contains_key("cursor") || query.contains_key("limit")) {
+            let limit = match query.get("limit") {
+                Some(l) => match l.parse::<usize>() {
+                    Ok(v) => v,
+                    Err(_) => return Err(Error::ParseFailed("Invalid page limit")),
+                },
+                None => PageRequest::default().limit,
+            };
+            params.push(PageRequest::new(query.get("cursor").cloned(), limit).to_json());
+        }
+
+        return Ok(Some((route.method, JsonValue::Array(params))))
+    }
+
+    Ok(None)
+}
+
+/// Write an HTTP response with the given status and JSON body
+async fn write_response(
+    stream: &mut BufReader<Box<dyn PtStream>>,
+    status: u16,
+    body: &JsonValue,
+) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.stringify()?;
+
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.get_mut().write_all(response.as_bytes()).await?;
+    stream.get_mut().flush().await?;
+    Ok(())
+}
+
+/// Build a JSON error body
+fn error_body(code: i32, message: &str) -> JsonValue {
+    JsonValue::Object(HashMap::from([(
+        "error".to_string(),
+        JsonValue::Object(HashMap::from([
+            ("code".to_string(), JsonValue::Number(code as f64)),
+            ("message".to_string(), JsonValue::String(message.to_string())),
+        ])),
+    )]))
+}
+
+/// Map a JSON-RPC error code onto an HTTP status
+fn error_status(code: i32) -> u16 {
+    match code {
+        c if c == ErrorCode::Unauthorized.code() => 401,
+        c if c == ErrorCode::MethodNotFound.code() => 404,
+        c if c == ErrorCode::InternalError.code() => 500,
+        _ => 400,
+    }
+}
+
+/// Serve a single HTTP request over the given stream
+async fn serve_request(
+    stream: Box<dyn PtStream>,
+    addr: Url,
+    routes: &[RestRoute],
+    rh: Arc<impl RequestHandler + 'static>,
+) -> Result<()> {
+    let mut stream = BufReader::new(stream);
+
+    // Read the request head
+    let mut head = vec![];
+    {
+        let mut reader = (&mut stream).take(MAX_REQUEST_HEAD_SIZE);
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(Error::ParseFailed("Incomplete HTTP request head"))
+            }
+            let line = line.trim_end().to_string();
+            if line.is_empty() {
+                break
+            }
+            head.push(line);
+        }
+    }
+
+    let Some(request_line) = head.first() else {
+        return Err(Error::ParseFailed("Empty HTTP request"))
+    };
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        let body = error_body(ErrorCode::InvalidRequest.code(), "Malformed request line");
+        return write_response(&mut stream, 400, &body).await
+    };
+    debug!(target: "rpc::rest", "{} --> {} {}", addr, method, target);
+
+    if method != "GET" {
+        let body = error_body(ErrorCode::InvalidRequest.code(), "Only GET is supported");
+        return write_response(&mut stream, 405, &body).await
+    }
+
+    let (rpc_method, params) = match resolve(routes, target) {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            let body = error_body(ErrorCode::MethodNotFound.code(), "Unknown endpoint");
+            return write_response(&mut stream, 404, &body).await
+        }
+        Err(e) => {
+            let body = error_body(ErrorCode::InvalidParams.code(), &e.to_string());
+            return write_response(&mut stream, 400, &body).await
+        }
+    };
+
+    let mut req = JsonRequest::new(rpc_method, params);
+
+    // Enforce authentication if the handler is configured with it
+    if let Some(auth) = rh.auth() {
+        let token = head.iter().skip(1).find_map(|h| {
+            let (name, value) = h.split_once(':')?;
+            if !name.trim().eq_ignore_ascii_case("authorization") {
+                return None
+            }
+            value.trim().strip_prefix("Bearer ").map(|t| t.trim().to_string())
+        });
+        if let Some(token) = token {
+            req.auth = Some(JsonValue::Object(HashMap::from([(
+                "token".to_string(),
+                JsonValue::String(token),
+            )])));
+        }
+
+        if let Err(e) = auth.verify(&req) {
+            debug!(target: "rpc::rest", "{} --> {} refused: {}", addr, rpc_method, e.message());
+            let body = error_body(ErrorCode::Unauthorized.code(), e.message());
+            return write_response(&mut stream, 401, &body).await
+        }
+        req.auth = None;
+    }
+
+    match rh.handle_request(req).await {
+        JsonResult::Response(rep) => write_response(&mut stream, 200, &rep.result).await,
+        JsonResult::Error(e) => {
+            let body = error_body(e.error.code, &e.error.message);
+            write_response(&mut stream, error_status(e.error.code), &body).await
+        }
+        _ => {
+            let body =
+                error_body(ErrorCode::InvalidRequest.code(), "Subscriptions are not supported");
+            write_response(&mut stream, 400, &body).await
+        }
+    }
+}
+
+/// Accept loop of the REST server, serving each incoming connection
+/// in its own task.
+async fn run_accept_loop(
+    listener: Box<dyn PtListener>,
+    routes: &'static [RestRoute],
+    rh: Arc<impl RequestHandler + 'static>,
+    ex: Arc<smol::Executor<'_>>,
+) -> Result<()> {
+    loop {
+        match listener.next().await {
+            Ok((stream, url)) => {
+                if rh.rate_limit().is_some_and(|rl| rl.is_banned(&url)) {
+                    debug!(target: "rpc::rest", "[REST] Refusing conn from banned {}", url);
+                    continue
+                }
+
+                let task = StoppableTask::new();
+                let task_ = task.clone();
+                let rh_ = rh.clone();
+                task.clone().start(
+                    serve_request(stream, url.clone(), routes, rh.clone()),
+                    move |res| async move {
+                        if let Err(e) = res {
+                            debug!(target: "rpc::rest", "Failed serving {}: {}", url, e);
+                        }
+                        rh_.unmark_connection(task_).await;
+                    },
+                    Error::ChannelStopped,
+                    ex.clone(),
+                );
+                rh.mark_connection(task).await;
+            }
+
+            // As per accept(2) recommendation:
+            Err(e) if e.raw_os_error().is_some() => match e.raw_os_error().unwrap() {
+                libc::EAGAIN | libc::ECONNABORTED | libc::EPROTO | libc::EINTR => continue,
+                _ => {
+                    error!(target: "rpc::rest::run_accept_loop()", "[REST] Server failed listening: {}", e);
+                    return Err(e.into())
+                }
+            },
+
+            // In case a TLS handshake fails, we'll get this:
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => continue,
+
+            Err(e) => {
+                error!(target: "rpc::rest::run_accept_loop()", "[REST] Unhandled listener.next() error: {}", e);
+                return Err(e.into())
+            }
+        }
+    }
+}
+
+/// Start a REST server bound to the given accept URL, mapping the given
+/// routes onto the [`RequestHandler`] JSON-RPC methods.
+pub async fn listen_and_serve_rest(
+    accept_url: Url,
+    routes: &'static [RestRoute],
+    rh: Arc<impl RequestHandler + 'static>,
+    ex: Arc<smol::Executor<'_>>,
+) -> Result<()> {
+    info!(target: "rpc::rest", "[REST] Starting server on {}", accept_url);
+    let listener = Listener::new(accept_url).await?.listen().await?;
+    run_accept_loop(listener, routes, rh, ex).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTES: &[RestRoute] = &[
+        RestRoute { path: "/blocks", method: "blockchain.get_blocks", query: &[], paged: true },
+        RestRoute {
+            path: "/blocks/{height}",
+            method: "blockchain.get_block_by_height",
+            query: &[],
+            paged: false,
+        },
+        RestRoute {
+            path: "/contracts/{id}/{tree}",
+            method: "contract.db_get",
+            query: &["key"],
+            paged: false,
+        },
+    ];
+
+    #[test]
+    fn rest_resolve() {
+        let (method, params) = resolve(ROUTES, "/blocks/42").unwrap().unwrap();
+        assert_eq!(method, "blockchain.get_block_by_height");
+        assert_eq!(params, JsonValue::Array(vec![JsonValue::String("42".to_string())]));
+
+        let (method, params) = resolve(ROUTES, "/blocks?limit=10&cursor=9").unwrap().unwrap();
+        assert_eq!(method, "blockchain.get_blocks");
+        let page = PageRequest::new(Some("9".to_string()), 10);
+        assert_eq!(params, JsonValue::Array(vec![page.to_json()]));
+
+        let (_, params) = resolve(ROUTES, "/blocks").unwrap().unwrap();
+        assert_eq!(params, JsonValue::Array(vec![]));
+
+        let (method, params) =
+            resolve(ROUTES, "/contracts/abc/info%20tree?key=AB%2BC").unwrap().unwrap();
+        assert_eq!(method, "contract.db_get");
+        assert_eq!(
+            params,
+            JsonValue::Array(vec![
+                JsonValue::String("abc".to_string()),
+                JsonValue::String("info tree".to_string()),
+                JsonValue::String("AB+C".to_string()),
+            ])
+        );
+
+        assert!(resolve(ROUTES, "/unknown").unwrap().is_none());
+        assert!(resolve(ROUTES, "/blocks?limit=ten").is_err());
+    }
+}
@@ -0,0 +1,125 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! JSON-RPC method registry, used for introspection.
+//!
+//! Handlers declare the methods they serve, along with their parameter
+//! schemas, as a static [`RpcMethod`] table returned by
+//! [`RequestHandler::methods()`](super::server::RequestHandler::methods).
+//! The server then refuses any method missing from it, so the table stays
+//! in sync with the dispatcher, and clients can list it with the
+//! `rpc.methods` method.
+use std::collections::HashMap;
+
+use tinyjson::JsonValue;
+
+/// Description of a positional JSON-RPC method parameter
+#[derive(Clone, Copy, Debug)]
+pub struct RpcParam {
+    /// Parameter name
+    pub name: &'static str,
+    /// JSON type of the parameter: `string`, `number`, `bool`, `array`,
+    /// `object`, or `page` for a pagination object
+    pub kind: &'static str,
+    /// Marks if the parameter may be omitted
+    pub optional: bool,
+}
+
+impl RpcParam {
+    /// Describe a required parameter
+    pub const fn required(name: &'static str, kind: &'static str) -> Self {
+        Self { name, kind, optional: false }
+    }
+
+    /// Describe an optional parameter
+    pub const fn optional(name: &'static str, kind: &'static str) -> Self {
+        Self { name, kind, optional: true }
+    }
+
+    fn to_json(self) -> JsonValue {
+        JsonValue::Object(HashMap::from([
+            ("name".to_string(), JsonValue::String(self.name.to_string())),
+            ("type".to_string(), JsonValue::String(self.kind.to_string())),
+            ("optional".to_string(), JsonValue::Boolean(self.optional)),
+        ]))
+    }
+}
+
+/// Description of a JSON-RPC method served by a handler
+#[derive(Clone, Copy, Debug)]
+pub struct RpcMethod {
+    /// Method name
+    pub name: &'static str,
+    /// Positional parameters
+    pub params: &'static [RpcParam],
+    /// Short description of the result, e.g. `base64 BlockInfo`
+    pub returns: &'static str,
+}
+
+impl RpcMethod {
+    /// Encode the method description into a JSON object. `auth` marks if
+    /// the server requires credentials to call it.
+    pub fn to_json(&self, auth: bool) -> JsonValue {
+        JsonValue::Object(HashMap::from([
+            ("name".to_string(), JsonValue::String(self.name.to_string())),
+            (
+                "params".to_string(),
+                JsonValue::Array(self.params.iter().map(|p| p.to_json()).collect()),
+            ),
+            ("returns".to_string(), JsonValue::String(self.returns.to_string())),
+            ("auth".to_string(), JsonValue::Boolean(auth)),
+        ]))
+    }
+}
+
+/// Check if the given method is in the registry. An empty registry means
+/// the handler doesn't declare its methods, so everything is let through.
+pub fn is_registered(methods: &[RpcMethod], method: &str) -> bool {
+    methods.is_empty() || methods.iter().any(|m| m.name == method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rpc_method_registry() {
+        const METHODS: &[RpcMethod] = &[
+            RpcMethod { name: "ping", params: &[], returns: "string" },
+            RpcMethod {
+                name: "blockchain.get_blocks",
+                params: &[RpcParam::optional("page", "page")],
+                returns: "page of base64 BlockInfo",
+            },
+        ];
+
+        assert!(is_registered(&[], "anything"));
+        assert!(is_registered(METHODS, "ping"));
+        assert!(!is_registered(METHODS, "pong"));
+
+        let json = METHODS[1].to_json(true).stringify().unwrap();
+        let parsed: JsonValue = json.parse().unwrap();
+        let method = parsed.get::<HashMap<String, JsonValue>>().unwrap();
+        assert_eq!(method["name"].get::<String>().unwrap(), "blockchain.get_blocks");
+        assert_eq!(method["auth"].get::<bool>(), Some(&true));
+        let params = method["params"].get::<Vec<JsonValue>>().unwrap();
+        let param = params[0].get::<HashMap<String, JsonValue>>().unwrap();
+        assert_eq!(param["type"].get::<String>().unwrap(), "page");
+        assert_eq!(param["optional"].get::<bool>(), Some(&true));
+    }
+}
@@ -19,17 +19,20 @@
 use std::{collections::HashSet, io::ErrorKind, sync::Arc};
 
 use async_trait::async_trait;
-use log::{debug, error, info};
 use smol::{
     io::{BufReader, ReadHalf, WriteHalf},
     lock::{Mutex, MutexGuard},
 };
 use tinyjson::JsonValue;
+use tracing::{debug, error, info, info_span, instrument, Instrument};
 use url::Url;
 
 use super::{
+    auth::RpcAuth,
     common::{read_from_stream, write_to_stream, INIT_BUF_SIZE},
     jsonrpc::*,
+    ratelimit::RpcRateLimit,
+    schema::{is_registered, RpcMethod},
 };
 use crate::{
     net::transport::{Listener, PtListener, PtStream},
@@ -48,6 +51,35 @@ pub trait RequestHandler: Sync + Send {
 
     async fn connections_mut(&self) -> MutexGuard<'_, HashSet<StoppableTaskPtr>>;
 
+    /// Optional authentication policy enforced on incoming requests.
+    /// Returning `None` (the default) leaves the server unauthenticated.
+    /// The policy is queried on every request, so handlers can swap it
+    /// at runtime.
+    fn auth(&self) -> Option<Arc<RpcAuth>> {
+        None
+    }
+
+    /// Optional rate limiting policy enforced on incoming requests.
+    /// Returning `None` (the default) leaves the server unlimited.
+    /// Per-connection limits are taken when a connection is accepted,
+    /// while bans are checked against the current policy.
+    fn rate_limit(&self) -> Option<Arc<RpcRateLimit>> {
+        None
+    }
+
+    /// Optional registry of the methods served by the handler. When not
+    /// empty, requests to methods missing from it are refused, and it can
+    /// be listed by routing `rpc.methods` to [`RequestHandler::rpc_methods()`].
+    fn methods(&self) -> &'static [RpcMethod] {
+        &[]
+    }
+
+    async fn rpc_methods(&self, id: u16, _params: JsonValue) -> JsonResult {
+        let auth = self.auth().is_some();
+        let methods = self.methods().iter().map(|m| m.to_json(auth)).collect();
+        JsonResponse::new(JsonValue::Array(methods), id).into()
+    }
+
     async fn connections(&self) -> Vec<StoppableTaskPtr> {
         self.connections_mut().await.iter().cloned().collect()
     }
@@ -76,6 +108,7 @@ pub trait RequestHandler: Sync + Send {
 /// Accept function that should run inside a loop for accepting incoming
 /// JSON-RPC requests and passing them to the [`RequestHandler`].
 #[allow(clippy::type_complexity)]
+#[instrument(target = "rpc::server", skip_all, fields(peer = %addr))]
 pub async fn accept(
     reader: Arc<Mutex<BufReader<ReadHalf<Box<dyn PtStream>>>>>,
     writer: Arc<Mutex<WriteHalf<Box<dyn PtStream>>>>,
@@ -99,6 +132,9 @@ pub async fn accept(
     // We'll hold our background tasks here
     let tasks = Arc::new(Mutex::new(HashSet::new()));
 
+    // Rate limiting state of this connection, if the handler is configured with it
+    let mut limiter = rh.rate_limit().map(|rl| rl.connection_limiter());
+
     loop {
         let mut buf = Vec::with_capacity(INIT_BUF_SIZE);
 
@@ -130,7 +166,7 @@ pub async fn accept(
         };
 
         // Cast to JsonRequest
-        let req = match JsonRequest::try_from(&val) {
+        let mut req = match JsonRequest::try_from(&val) {
             Ok(v) => v,
             Err(e) => {
                 error!(
@@ -141,9 +177,59 @@ pub async fn accept(
             }
         };
 
-        debug!(target: "rpc::server", "{} --> {}", addr, val.stringify()?);
+        // Enforce rate limits before doing any further work on the request
+        if let Some(Err(e)) = limiter.as_mut().map(|l| l.check(&req.method)) {
+            debug!(
+                target: "rpc::server",
+                "{} --> {} refused: {}", addr, req.method, e.message(),
+            );
+            let rep: JsonResult =
+                JsonError::new(ErrorCode::RateLimited, Some(e.message().to_string()), req.id)
+                    .into();
+            let mut writer_lock = writer.lock().await;
+            write_to_stream(&mut writer_lock, &rep).await?;
+            drop(writer_lock);
+
+            // Drop the connection if the client got banned
+            if rh.rate_limit().is_some_and(|rl| rl.strike(&addr)) {
+                info!(target: "rpc::server", "[RPC] Banned {} for exceeding rate limits", addr);
+                return Err(Error::RpcClientBanned)
+            }
+            continue
+        }
+
+        // Enforce authentication if the handler is configured with it
+        let auth = rh.auth();
+        if let Some(Err(e)) = auth.as_ref().map(|auth| auth.verify(&req)) {
+            debug!(
+                target: "rpc::server",
+                "{} --> {} refused: {}", addr, req.method, e.message(),
+            );
+            let rep: JsonResult =
+                JsonError::new(ErrorCode::Unauthorized, Some(e.message().to_string()), req.id)
+                    .into();
+            let mut writer_lock = writer.lock().await;
+            write_to_stream(&mut writer_lock, &rep).await?;
+            drop(writer_lock);
+            continue
+        }
+
+        // Refuse methods missing from the handler registry
+        if !is_registered(rh.methods(), &req.method) {
+            debug!(target: "rpc::server", "{} --> {} refused: not registered", addr, req.method);
+            let rep: JsonResult = JsonError::new(ErrorCode::MethodNotFound, None, req.id).into();
+            let mut writer_lock = writer.lock().await;
+            write_to_stream(&mut writer_lock, &rep).await?;
+            drop(writer_lock);
+            continue
+        }
+
+        // Credentials are not passed further, so they don't end up in logs
+        req.auth = None;
+        debug!(target: "rpc::server", "{} --> {}", addr, req.stringify()?);
 
-        let rep = rh.handle_request(req).await;
+        let span = info_span!("rpc_request", id = req.id, method = %req.method);
+        let rep = rh.handle_request(req).instrument(span).await;
 
         match rep {
             JsonResult::Subscriber(subscriber) => {
@@ -274,6 +360,11 @@ async fn run_accept_loop(
     loop {
         match listener.next().await {
             Ok((stream, url)) => {
+                if rh.rate_limit().is_some_and(|rl| rl.is_banned(&url)) {
+                    debug!(target: "rpc::server", "[RPC] Refusing conn from banned {}", url);
+                    continue
+                }
+
                 let rh_ = rh.clone();
                 info!(target: "rpc::server", "[RPC] Server accepted conn from {}", url);
 
@@ -344,6 +435,22 @@ pub async fn listen_and_serve(
     run_accept_loop(listener, rh, conn_limit, ex.clone()).await
 }
 
+/// Start a JSON-RPC server like [`listen_and_serve()`], using the given
+/// persistent PEM-encoded identity keypair for `tcp+tls://` listeners.
+/// Since the server certificate then stays the same across restarts,
+/// remote clients are able to pin its public key.
+pub async fn listen_and_serve_tls(
+    accept_url: Url,
+    tls_identity: String,
+    rh: Arc<impl RequestHandler + 'static>,
+    conn_limit: Option<usize>,
+    ex: Arc<smol::Executor<'_>>,
+) -> Result<()> {
+    let listener =
+        Listener::new(accept_url).await?.with_tls_identity(tls_identity).listen().await?;
+    run_accept_loop(listener, rh, conn_limit, ex.clone()).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
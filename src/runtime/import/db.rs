@@ -68,7 +68,7 @@ pub(crate) fn db_init(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_len: u
 
     // Subtract used gas. Here we count the length read from the memory slice.
     // TODO: There should probably be an additional fee to open a new sled tree.
-    env.subtract_gas(&mut store, ptr_len as u64);
+    env.subtract_byte_gas(&mut store, ptr_len as u64);
 
     // This takes lock of the blockchain overlay reference in the wasm env
     let contracts = &env.blockchain.lock().unwrap().contracts;
@@ -224,7 +224,7 @@ pub(crate) fn db_lookup(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_len:
     }
 
     // Subtract used gas. Here we count the length read from the memory slice.
-    env.subtract_gas(&mut store, ptr_len as u64);
+    env.subtract_byte_gas(&mut store, ptr_len as u64);
 
     // Read memory location that contains the ContractId and DB name
     let memory_view = env.memory_view(&store);
@@ -344,7 +344,7 @@ pub(crate) fn db_set(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_len: u3
     }
 
     // Subtract used gas. Here we count the length hread from the memory slice.
-    env.subtract_gas(&mut store, ptr_len as u64);
+    env.subtract_byte_gas(&mut store, ptr_len as u64);
 
     // Ensure that it is possible to read from the memory that this function needs
     let memory_view = env.memory_view(&store);
@@ -476,7 +476,7 @@ pub(crate) fn db_del(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_len: u3
     }
 
     // Subtract used gas. Here we count the length of the looked-up key.
-    env.subtract_gas(&mut store, ptr_len as u64);
+    env.subtract_byte_gas(&mut store, ptr_len as u64);
 
     // Ensure that it is possible to read from the memory that this function needs
     let memory_view = env.memory_view(&store);
@@ -592,7 +592,7 @@ pub(crate) fn db_get(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_len: u3
     }
 
     // Subtract used gas. Here we count the length of the looked-up key.
-    env.subtract_gas(&mut store, ptr_len as u64);
+    env.subtract_byte_gas(&mut store, ptr_len as u64);
 
     // Ensure that it is possible to read memory
     let memory_view = env.memory_view(&store);
@@ -693,7 +693,7 @@ pub(crate) fn db_get(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_len: u3
     }
 
     // Subtract used gas. Here we count the length of the data read from db.
-    env.subtract_gas(&mut store, return_data.len() as u64);
+    env.subtract_byte_gas(&mut store, return_data.len() as u64);
 
     // Copy the data (Vec<u8>) to the VM by pushing it to the objects Vector.
     let mut objects = env.objects.borrow_mut();
@@ -729,7 +729,7 @@ pub(crate) fn db_contains_key(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, pt
     }
 
     // Subtract used gas. Here we count the length of the looked-up key.
-    env.subtract_gas(&mut store, ptr_len as u64);
+    env.subtract_byte_gas(&mut store, ptr_len as u64);
 
     // Ensure memory is readable
     let memory_view = env.memory_view(&store);
@@ -835,7 +835,7 @@ pub(crate) fn zkas_db_set(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_le
     }
 
     // Subtract used gas. Here we count the length read from the memory slice.
-    env.subtract_gas(&mut store, ptr_len as u64);
+    env.subtract_byte_gas(&mut store, ptr_len as u64);
 
     let memory_view = env.memory_view(&store);
 
@@ -881,11 +881,27 @@ pub(crate) fn zkas_db_set(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_le
         }
     };
 
-    // Subtract used gas. We count 100 gas per opcode, witness, and literal.
-    // This is likely bad.
+    // Decoding verified the hash and author signature the binary carries,
+    // but deployed circuits must carry them, so that any later corruption
+    // of the stored bytes is caught when they're loaded back.
+    let Some(hash) = zkbin.hash else {
+        error!(
+            target: "runtime::db::zkas_db_set",
+            "[WASM] [{}] zkas_db_set(): zkas bincode is missing its .integrity section", cid,
+        );
+        return darkfi_sdk::error::DB_SET_FAILED
+    };
+
+    debug!(
+        target: "runtime::db::zkas_db_set",
+        "[WASM] [{}] zkas_db_set(): Deploying \"{}\" ({}), author: {:?}",
+        cid, zkbin.namespace, blake3::Hash::from(hash), zkbin.author,
+    );
+
+    // Subtract used gas. We count a scheduled cost per opcode, witness, and literal.
     // TODO: This should be better-priced.
-    let gas_cost =
-        (zkbin.literals.len() + zkbin.witnesses.len() + zkbin.opcodes.len()) as u64 * 100;
+    let items = (zkbin.literals.len() + zkbin.witnesses.len() + zkbin.opcodes.len()) as u64;
+    let gas_cost = items.saturating_mul(env.gas_schedule.host.zkas_item);
     env.subtract_gas(&mut store, gas_cost);
 
     // Because of `Runtime::Deploy`, we should be sure that the zkas db is index zero.
@@ -967,6 +983,29 @@ pub(crate) fn zkas_db_set(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_le
         return darkfi_sdk::error::DB_SET_FAILED
     }
 
+    // Record the new circuit version, so historical blocks remain verifiable
+    // with the superseded ones.
+    let version = match env.blockchain.lock().unwrap().contracts.insert_zkas_version(
+        &cid,
+        &zkbin.namespace,
+        env.verifying_block_height,
+        zkbin_bytes.clone(),
+        vk_buf.clone(),
+    ) {
+        Ok(v) => v,
+        Err(e) => {
+            error!(
+                target: "runtime::db::zkas_db_set",
+                "[WASM] [{}] zkas_db_set(): Couldn't record zkas circuit version: {}", cid, e,
+            );
+            return darkfi_sdk::error::DB_SET_FAILED
+        }
+    };
+    debug!(
+        target: "runtime::db::zkas_db_set",
+        "[WASM] [{}] zkas_db_set(): Deployed version {} of {} zkas circuit", cid, version, zkbin.namespace,
+    );
+
     // Insert the key-value pair into the database.
     let key = serialize(&zkbin.namespace);
     let value = serialize(&(zkbin_bytes, vk_buf));
@@ -989,7 +1028,7 @@ pub(crate) fn zkas_db_set(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, ptr_le
     drop(db_handles);
 
     // Subtract used gas. Here we count the bytes written into the db.
-    env.subtract_gas(&mut store, (key.len() + value.len()) as u64);
+    env.subtract_byte_gas(&mut store, (key.len() + value.len()) as u64);
 
     wasm::entrypoint::SUCCESS
 }
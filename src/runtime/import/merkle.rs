@@ -52,7 +52,7 @@ pub(crate) fn merkle_add(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, len: u3
 
     // Subtract used gas. Here we count the length read from the memory slice.
     // This makes calling the function which returns early have some (small) cost.
-    env.subtract_gas(&mut store, len as u64);
+    env.subtract_byte_gas(&mut store, len as u64);
 
     let memory_view = env.memory_view(&store);
     let Ok(mem_slice) = ptr.slice(&memory_view, len) else {
@@ -308,7 +308,7 @@ pub(crate) fn merkle_add(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, len: u3
     drop(lock);
     drop(db_handles);
     let spent_gas = return_data.len() + tree_data.len() + (coins_len * 32);
-    env.subtract_gas(&mut store, spent_gas as u64);
+    env.subtract_byte_gas(&mut store, spent_gas as u64);
 
     wasm::entrypoint::SUCCESS
 }
@@ -117,7 +117,7 @@ pub(crate) fn sparse_merkle_insert_batch(
 
     // Subtract used gas. Here we count the length read from the memory slice.
     // This makes calling the function which returns early have some (small) cost.
-    env.subtract_gas(&mut store, len as u64);
+    env.subtract_byte_gas(&mut store, len as u64);
 
     let memory_view = env.memory_view(&store);
     let Ok(mem_slice) = ptr.slice(&memory_view, len) else {
@@ -306,7 +306,7 @@ pub(crate) fn sparse_merkle_insert_batch(
     drop(lock);
     drop(db_handles);
     let spent_gas = leaves_len * 32;
-    env.subtract_gas(&mut store, spent_gas as u64);
+    env.subtract_byte_gas(&mut store, spent_gas as u64);
 
     wasm::entrypoint::SUCCESS
 }
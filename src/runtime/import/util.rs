@@ -18,7 +18,10 @@
 
 use std::io::Cursor;
 
-use darkfi_sdk::wasm;
+use darkfi_sdk::{
+    crypto::{ecvrf::VrfProof, PublicKey},
+    wasm,
+};
 use darkfi_serial::Decodable;
 use log::{debug, error};
 use wasmer::{FunctionEnvMut, WasmPtr};
@@ -31,7 +34,7 @@ pub(crate) fn drk_log(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, len: u32)
     let (env, mut store) = ctx.data_and_store_mut();
 
     // Subtract used gas. Here we count the length of the string.
-    env.subtract_gas(&mut store, len as u64);
+    env.subtract_byte_gas(&mut store, len as u64);
 
     let memory_view = env.memory_view(&store);
     match ptr.read_utf8_string(&memory_view, len) {
@@ -71,7 +74,7 @@ pub(crate) fn set_return_data(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, le
     }
 
     // Subtract used gas. Here we count the length read from the memory slice.
-    env.subtract_gas(&mut store, len as u64);
+    env.subtract_byte_gas(&mut store, len as u64);
 
     let memory_view = env.memory_view(&store);
     let Ok(slice) = ptr.slice(&memory_view, len) else { return darkfi_sdk::error::INTERNAL_ERROR };
@@ -125,7 +128,7 @@ pub(crate) fn get_object_bytes(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, i
     }
 
     // Subtract used gas. Here we count the bytes written to the memory slice
-    env.subtract_gas(&mut store, obj.len() as u64);
+    env.subtract_byte_gas(&mut store, obj.len() as u64);
 
     // Read N bytes from the object and write onto the ptr.
     let memory_view = env.memory_view(&store);
@@ -189,7 +192,7 @@ pub(crate) fn get_object_size(mut ctx: FunctionEnvMut<Env>, idx: u32) -> i64 {
 
     // Subtract used gas. Here we count the size of the object.
     // TODO: This could probably be fixed-cost
-    env.subtract_gas(&mut store, obj_len as u64);
+    env.subtract_byte_gas(&mut store, obj_len as u64);
 
     obj_len as i64
 }
@@ -213,7 +216,7 @@ pub(crate) fn get_verifying_block_height(mut ctx: FunctionEnvMut<Env>) -> i64 {
 
     // Subtract used gas. Here we count the size of the object.
     // u64 is 8 bytes.
-    env.subtract_gas(&mut store, 8);
+    env.subtract_byte_gas(&mut store, 8);
 
     assert!(env.verifying_block_height <= i64::MAX as u64);
     env.verifying_block_height as i64
@@ -237,7 +240,7 @@ pub(crate) fn get_tx_hash(mut ctx: FunctionEnvMut<Env>) -> i64 {
     }
 
     // Subtract used gas. Here we count the size of the object.
-    env.subtract_gas(&mut store, 32);
+    env.subtract_byte_gas(&mut store, 32);
 
     // Return the length of the objects Vector.
     // This is the location of the data that was retrieved and pushed
@@ -265,7 +268,7 @@ pub(crate) fn get_call_index(mut ctx: FunctionEnvMut<Env>) -> i64 {
 
     // Subtract used gas. Here we count the size of the object.
     // u32 is 4 bytes.
-    env.subtract_gas(&mut store, 4);
+    env.subtract_byte_gas(&mut store, 4);
 
     env.call_idx as i64
 }
@@ -302,7 +305,7 @@ pub(crate) fn get_blockchain_time(mut ctx: FunctionEnvMut<Env>) -> i64 {
 
     // Subtract used gas. Here we count the size of the object.
     // u64 is 8 bytes.
-    env.subtract_gas(&mut store, 8);
+    env.subtract_byte_gas(&mut store, 8);
 
     // Create the return object
     let mut ret = Vec::with_capacity(8);
@@ -354,7 +357,7 @@ pub(crate) fn get_last_block_height(mut ctx: FunctionEnvMut<Env>) -> i64 {
 
     // Subtract used gas. Here we count the size of the object.
     // u64 is 8 bytes.
-    env.subtract_gas(&mut store, 8);
+    env.subtract_byte_gas(&mut store, 8);
 
     // Create the return object
     let mut ret = Vec::with_capacity(8);
@@ -393,7 +396,7 @@ pub(crate) fn get_tx(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>) -> i64 {
     }
 
     // Subtract used gas. Here we count the length of the looked-up hash.
-    env.subtract_gas(&mut store, blake3::OUT_LEN as u64);
+    env.subtract_byte_gas(&mut store, blake3::OUT_LEN as u64);
 
     // Ensure that it is possible to read memory
     let memory_view = env.memory_view(&store);
@@ -464,7 +467,7 @@ pub(crate) fn get_tx(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>) -> i64 {
     }
 
     // Subtract used gas. Here we count the length of the data read from db.
-    env.subtract_gas(&mut store, return_data.len() as u64);
+    env.subtract_byte_gas(&mut store, return_data.len() as u64);
 
     // Copy the data (Vec<u8>) to the VM by pushing it to the objects Vector.
     let mut objects = env.objects.borrow_mut();
@@ -501,7 +504,7 @@ pub(crate) fn get_tx_location(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>) ->
     }
 
     // Subtract used gas. Here we count the length of the looked-up hash.
-    env.subtract_gas(&mut store, blake3::OUT_LEN as u64);
+    env.subtract_byte_gas(&mut store, blake3::OUT_LEN as u64);
 
     // Ensure that it is possible to read memory
     let memory_view = env.memory_view(&store);
@@ -572,7 +575,7 @@ pub(crate) fn get_tx_location(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>) ->
     }
 
     // Subtract used gas. Here we count the length of the data read from db.
-    env.subtract_gas(&mut store, return_data.len() as u64);
+    env.subtract_byte_gas(&mut store, return_data.len() as u64);
 
     // Copy the data (Vec<u8>) to the VM by pushing it to the objects Vector.
     let mut objects = env.objects.borrow_mut();
@@ -585,3 +588,119 @@ pub(crate) fn get_tx_location(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>) ->
     objects.push(return_data.to_vec());
     (objects.len() - 1) as i64
 }
+
+/// Verifies an ECVRF proof for a given public key and input.
+///
+/// The buffer at `ptr` should deserialize into a `PublicKey`, the input
+/// `Vec<u8>`, and the `VrfProof`. On success, the VRF output is pushed to
+/// the object store and its index is returned. If the proof is invalid,
+/// returns `VRF_VERIFY_FAILED`. Otherwise, returns an error code.
+///
+/// Permissions: deploy, metadata, exec
+pub(crate) fn vrf_verify(mut ctx: FunctionEnvMut<Env>, ptr: WasmPtr<u8>, len: u32) -> i64 {
+    let (env, mut store) = ctx.data_and_store_mut();
+    let cid = env.contract_id;
+
+    if let Err(e) =
+        acl_allow(env, &[ContractSection::Deploy, ContractSection::Metadata, ContractSection::Exec])
+    {
+        error!(
+            target: "runtime::util::vrf_verify",
+            "[WASM] [{}] vrf_verify(): Called in unauthorized section: {}", cid, e,
+        );
+        return darkfi_sdk::error::CALLER_ACCESS_DENIED
+    }
+
+    // Subtract used gas. Here we count the length read from the memory slice,
+    // plus a fixed cost for the curve arithmetic done during verification.
+    // TODO: This should be better-priced.
+    env.subtract_byte_gas(&mut store, len as u64);
+    let gas_cost = env.gas_schedule.host.vrf_verify;
+    env.subtract_gas(&mut store, gas_cost);
+
+    let memory_view = env.memory_view(&store);
+    let Ok(mem_slice) = ptr.slice(&memory_view, len) else {
+        error!(
+            target: "runtime::util::vrf_verify",
+            "[WASM] [{}] vrf_verify(): Failed to make slice from ptr", cid,
+        );
+        return darkfi_sdk::error::INTERNAL_ERROR
+    };
+
+    let mut buf = vec![0_u8; len as usize];
+    if let Err(e) = mem_slice.read_slice(&mut buf) {
+        error!(
+            target: "runtime::util::vrf_verify",
+            "[WASM] [{}] vrf_verify(): Failed to read from memory slice: {}", cid, e,
+        );
+        return darkfi_sdk::error::INTERNAL_ERROR
+    };
+
+    let mut buf_reader = Cursor::new(buf);
+
+    // The buffer should deserialize into:
+    // - PublicKey
+    // - Vec<u8> (VRF input)
+    // - VrfProof
+    let public_key: PublicKey = match Decodable::decode(&mut buf_reader) {
+        Ok(v) => v,
+        Err(e) => {
+            error!(
+                target: "runtime::util::vrf_verify",
+                "[WASM] [{}] vrf_verify(): Failed to decode PublicKey: {}", cid, e,
+            );
+            return darkfi_sdk::error::INTERNAL_ERROR
+        }
+    };
+
+    let alpha: Vec<u8> = match Decodable::decode(&mut buf_reader) {
+        Ok(v) => v,
+        Err(e) => {
+            error!(
+                target: "runtime::util::vrf_verify",
+                "[WASM] [{}] vrf_verify(): Failed to decode VRF input: {}", cid, e,
+            );
+            return darkfi_sdk::error::INTERNAL_ERROR
+        }
+    };
+
+    let proof: VrfProof = match Decodable::decode(&mut buf_reader) {
+        Ok(v) => v,
+        Err(e) => {
+            error!(
+                target: "runtime::util::vrf_verify",
+                "[WASM] [{}] vrf_verify(): Failed to decode VrfProof: {}", cid, e,
+            );
+            return darkfi_sdk::error::INTERNAL_ERROR
+        }
+    };
+
+    // Make sure there are no trailing bytes in the buffer. This means we've used all data that was
+    // supplied.
+    if buf_reader.position() != len as u64 {
+        error!(
+            target: "runtime::util::vrf_verify",
+            "[WASM] [{}] vrf_verify(): Trailing bytes in argument stream", cid,
+        );
+        return darkfi_sdk::error::INTERNAL_ERROR
+    }
+
+    let Some(output) = proof.verify_output(public_key, &alpha) else {
+        debug!(
+            target: "runtime::util::vrf_verify",
+            "[WASM] [{}] vrf_verify(): Proof verification failed", cid,
+        );
+        return darkfi_sdk::error::VRF_VERIFY_FAILED
+    };
+
+    // Copy the output to the VM by pushing it to the objects Vector.
+    let mut objects = env.objects.borrow_mut();
+    if objects.len() == u32::MAX as usize {
+        return darkfi_sdk::error::DATA_TOO_LARGE
+    }
+
+    // Return the length of the objects Vector.
+    // This is the location of the data that was pushed
+    objects.push(output.as_bytes().to_vec());
+    (objects.len() - 1) as i64
+}
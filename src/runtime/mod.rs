@@ -19,6 +19,9 @@
 /// Main WASM VM runtime implementation
 pub mod vm_runtime;
 
+/// Compiled WASM module cache
+pub mod module_cache;
+
 /// VM memory access (read/write)
 pub(crate) mod memory;
 
@@ -0,0 +1,226 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::PathBuf,
+    sync::{Mutex, OnceLock, RwLock},
+};
+
+use darkfi_serial::serialize;
+use log::{debug, warn};
+use wasmer::{Module, Store};
+
+use crate::{blockchain::contract_store::OpcodeCosts, Result};
+
+/// Number of compiled modules kept in memory
+const MODULE_CACHE_CAPACITY: usize = 32;
+
+/// Process-wide compiled module cache
+static MODULE_CACHE: OnceLock<ModuleCache> = OnceLock::new();
+
+/// Minimal least-recently-used map
+struct Lru<T> {
+    /// Cached entries
+    entries: HashMap<[u8; 32], T>,
+    /// Entries keys, least recently used first
+    order: VecDeque<[u8; 32]>,
+    /// Maximum number of entries
+    capacity: usize,
+}
+
+impl<T: Clone> Lru<T> {
+    fn new(capacity: usize) -> Self {
+        Self { entries: HashMap::new(), order: VecDeque::new(), capacity }
+    }
+
+    fn get(&mut self, key: &[u8; 32]) -> Option<T> {
+        let entry = self.entries.get(key)?.clone();
+        self.order.retain(|k| k != key);
+        self.order.push_back(*key);
+        Some(entry)
+    }
+
+    fn insert(&mut self, key: [u8; 32], entry: T) {
+        if self.entries.insert(key, entry).is_some() {
+            self.order.retain(|k| k != &key);
+        } else if self.entries.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.order.push_back(key);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// Cache of compiled wasm modules, so deployed contracts don't get
+/// recompiled on every call. Since the gas metering is compiled into the
+/// modules, they are keyed by the bytecode hash along with the opcode
+/// costs they were metered with. Modules are kept in an in-memory LRU,
+/// and optionally serialized to a directory to survive restarts.
+pub struct ModuleCache {
+    /// In-memory cache
+    memory: Mutex<Lru<Module>>,
+    /// Optional on-disk cache directory
+    disk: RwLock<Option<PathBuf>>,
+}
+
+impl ModuleCache {
+    fn new() -> Self {
+        Self { memory: Mutex::new(Lru::new(MODULE_CACHE_CAPACITY)), disk: RwLock::new(None) }
+    }
+
+    /// Access the process-wide module cache
+    pub fn global() -> &'static Self {
+        MODULE_CACHE.get_or_init(Self::new)
+    }
+
+    /// Set or unset the directory compiled modules get cached in
+    pub fn set_disk_cache(&self, path: Option<PathBuf>) -> Result<()> {
+        if let Some(ref path) = path {
+            fs::create_dir_all(path)?;
+        }
+        *self.disk.write().unwrap() = path;
+        Ok(())
+    }
+
+    /// Drop all modules cached in memory
+    pub fn clear(&self) {
+        self.memory.lock().unwrap().clear();
+    }
+
+    /// Cache key of a wasm bytecode compiled with the given opcode costs
+    fn key(wasm_bytes: &[u8], costs: &OpcodeCosts) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(wasm_bytes);
+        hasher.update(&serialize(costs));
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Fetch the compiled module of given bytecode from the cache, or compile
+    /// it with the given store's engine, which must meter opcodes with the
+    /// given costs, and cache it.
+    pub(crate) fn get_or_compile(
+        &self,
+        store: &Store,
+        wasm_bytes: &[u8],
+        costs: &OpcodeCosts,
+    ) -> Result<Module> {
+        let key = Self::key(wasm_bytes, costs);
+        if let Some(module) = self.memory.lock().unwrap().get(&key) {
+            debug!(target: "runtime::module_cache", "Using cached module");
+            return Ok(module)
+        }
+
+        let module = match self.load(store, &key) {
+            Some(module) => module,
+            None => {
+                debug!(target: "runtime::module_cache", "Compiling module");
+                let module = Module::new(store, wasm_bytes)?;
+                self.store(&key, &module);
+                module
+            }
+        };
+
+        self.memory.lock().unwrap().insert(key, module.clone());
+        Ok(module)
+    }
+
+    /// Path of a cached module in the on-disk cache, if enabled
+    fn path(&self, key: &[u8; 32]) -> Option<PathBuf> {
+        let dir = self.disk.read().unwrap().clone()?;
+        Some(dir.join(format!("{}.wasmu", blake3::Hash::from(*key).to_hex())))
+    }
+
+    /// Load a module from the on-disk cache. The serialized artifact is
+    /// prefixed with its hash, so corrupted files are recompiled instead.
+    fn load(&self, store: &Store, key: &[u8; 32]) -> Option<Module> {
+        let path = self.path(key)?;
+        let bytes = fs::read(&path).ok()?;
+        if bytes.len() < 32 || blake3::hash(&bytes[32..]).as_bytes() != &bytes[..32] {
+            warn!(target: "runtime::module_cache", "Ignoring corrupted cached module {:?}", path);
+            return None
+        }
+
+        // SAFETY: The artifact was serialized by us into the node's own
+        // cache directory, and checked against its hash above. Artifacts
+        // of other wasmer versions or targets are refused by wasmer.
+        match unsafe { Module::deserialize(store, bytes[32..].to_vec()) } {
+            Ok(module) => {
+                debug!(target: "runtime::module_cache", "Loaded cached module {:?}", path);
+                Some(module)
+            }
+            Err(e) => {
+                warn!(target: "runtime::module_cache", "Failed loading cached module {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Write a module to the on-disk cache, if enabled. Failures are
+    /// only logged, since the module can always be recompiled.
+    fn store(&self, key: &[u8; 32], module: &Module) {
+        let Some(path) = self.path(key) else { return };
+        let artifact = match module.serialize() {
+            Ok(v) => v,
+            Err(e) => {
+                warn!(target: "runtime::module_cache", "Failed serializing module: {}", e);
+                return
+            }
+        };
+
+        let mut bytes = blake3::hash(&artifact).as_bytes().to_vec();
+        bytes.extend_from_slice(&artifact);
+
+        // Write to a temporary file first, so readers never see partial files
+        let tmp_path = path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp_path, &bytes).and_then(|_| fs::rename(&tmp_path, &path)) {
+            warn!(target: "runtime::module_cache", "Failed writing cached module {:?}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_eviction() {
+        let mut lru = Lru::new(2);
+        lru.insert([1; 32], 1);
+        lru.insert([2; 32], 2);
+
+        // Touching the first entry makes the second one the oldest
+        assert_eq!(lru.get(&[1; 32]), Some(1));
+        lru.insert([3; 32], 3);
+        assert_eq!(lru.get(&[2; 32]), None);
+        assert_eq!(lru.get(&[1; 32]), Some(1));
+        assert_eq!(lru.get(&[3; 32]), Some(3));
+
+        // Replacing an entry doesn't evict anything
+        lru.insert([3; 32], 4);
+        assert_eq!(lru.get(&[1; 32]), Some(1));
+        assert_eq!(lru.get(&[3; 32]), Some(4));
+    }
+}
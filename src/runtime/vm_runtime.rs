@@ -26,7 +26,7 @@ use darkfi_serial::serialize;
 use log::{debug, error, info};
 use wasmer::{
     imports, wasmparser::Operator, AsStoreMut, AsStoreRef, CompilerConfig, Function, FunctionEnv,
-    Instance, Memory, MemoryView, Module, Pages, Store, Value, WASM_PAGE_SIZE,
+    Instance, Memory, MemoryView, Pages, Store, Value, WASM_PAGE_SIZE,
 };
 use wasmer_compiler_singlepass::Singlepass;
 use wasmer_middlewares::{
@@ -34,9 +34,12 @@ use wasmer_middlewares::{
     Metering,
 };
 
-use super::{import, import::db::DbHandle, memory::MemoryManipulation};
+use super::{import, import::db::DbHandle, memory::MemoryManipulation, module_cache::ModuleCache};
 use crate::{
-    blockchain::{contract_store::SMART_CONTRACT_ZKAS_DB_NAME, BlockchainOverlayPtr},
+    blockchain::{
+        contract_store::{GasSchedule, OpcodeCosts, SMART_CONTRACT_ZKAS_DB_NAME},
+        BlockchainOverlayPtr,
+    },
     Error, Result,
 };
 
@@ -103,6 +106,8 @@ pub struct Env {
     pub call_idx: u32,
     /// Parent `Instance`
     pub instance: Option<Arc<Instance>>,
+    /// Gas schedule active at the verifying block height
+    pub gas_schedule: GasSchedule,
 }
 
 impl Env {
@@ -137,6 +142,50 @@ impl Env {
             }
         }
     }
+
+    /// Subtract the gas cost of processing given number of bytes,
+    /// as priced by the active gas schedule
+    pub fn subtract_byte_gas(&mut self, ctx: &mut impl AsStoreMut, bytes: u64) {
+        self.subtract_gas(ctx, bytes.saturating_mul(self.gas_schedule.host.byte));
+    }
+}
+
+/// Gas cost of a wasm opcode under the given opcode costs.
+/// https://docs.rs/wasmparser/latest/wasmparser/enum.Operator.html
+fn opcode_cost(costs: &OpcodeCosts, operator: &Operator) -> u64 {
+    match operator {
+        Operator::Call { .. } | Operator::CallIndirect { .. } => costs.call,
+
+        Operator::MemoryGrow { .. } => costs.memory_grow,
+
+        Operator::I32Load { .. } |
+        Operator::I64Load { .. } |
+        Operator::F32Load { .. } |
+        Operator::F64Load { .. } |
+        Operator::I32Load8S { .. } |
+        Operator::I32Load8U { .. } |
+        Operator::I32Load16S { .. } |
+        Operator::I32Load16U { .. } |
+        Operator::I64Load8S { .. } |
+        Operator::I64Load8U { .. } |
+        Operator::I64Load16S { .. } |
+        Operator::I64Load16U { .. } |
+        Operator::I64Load32S { .. } |
+        Operator::I64Load32U { .. } |
+        Operator::I32Store { .. } |
+        Operator::I64Store { .. } |
+        Operator::F32Store { .. } |
+        Operator::F64Store { .. } |
+        Operator::I32Store8 { .. } |
+        Operator::I32Store16 { .. } |
+        Operator::I64Store8 { .. } |
+        Operator::I64Store16 { .. } |
+        Operator::I64Store32 { .. } |
+        Operator::MemoryCopy { .. } |
+        Operator::MemoryFill { .. } => costs.memory,
+
+        _ => costs.default,
+    }
 }
 
 /// Define a wasm runtime.
@@ -160,12 +209,18 @@ impl Runtime {
         call_idx: u32,
     ) -> Result<Self> {
         info!(target: "runtime::vm_runtime", "[WASM] Instantiating a new runtime");
+        // Grab the gas schedule active at the verifying block height, so
+        // old blocks are verified against the costs they were produced with.
+        let gas_schedule =
+            blockchain.lock().unwrap().contracts.get_gas_schedule_at(verifying_block_height)?;
+        debug!(target: "runtime::vm_runtime", "Using gas schedule version {}", gas_schedule.version);
+
         // This function will be called for each `Operator` encountered during
         // the wasm module execution. It should return the cost of the operator
-        // that it received as its first argument. For now, every wasm opcode
-        // has a cost of `1`.
-        // https://docs.rs/wasmparser/latest/wasmparser/enum.Operator.html
-        let cost_function = |_operator: &Operator| -> u64 { 1 };
+        // that it received as its first argument, as priced by the schedule.
+        let opcode_costs = gas_schedule.opcodes;
+        let cost_function =
+            move |operator: &Operator| -> u64 { opcode_cost(&opcode_costs, operator) };
 
         // `Metering` needs to be configured with a limit and a cost function.
         // For each `Operator`, the metering middleware will call the cost
@@ -177,8 +232,8 @@ impl Runtime {
         compiler_config.push_middleware(metering);
         let mut store = Store::new(compiler_config);
 
-        debug!(target: "runtime::vm_runtime", "Compiling module");
-        let module = Module::new(&store, wasm_bytes)?;
+        // Compile the module, unless it was already compiled with the same costs
+        let module = ModuleCache::global().get_or_compile(&store, wasm_bytes, &opcode_costs)?;
 
         // Initialize data
         let db_handles = RefCell::new(vec![]);
@@ -202,6 +257,7 @@ impl Runtime {
                 tx_hash,
                 call_idx,
                 instance: None,
+                gas_schedule,
             },
         );
 
@@ -326,6 +382,12 @@ impl Runtime {
                     &ctx,
                     import::util::get_tx_location,
                 ),
+
+                "vrf_verify_" => Function::new_typed_with_env(
+                    &mut store,
+                    &ctx,
+                    import::util::vrf_verify,
+                ),
             }
         };
 
@@ -275,6 +275,9 @@ impl Proof {
             literals: Vec::new(),
             witnesses: Vec::new(),
             opcodes: Vec::new(),
+            debug_lines: None,
+            hash: None,
+            author: None,
         };
         let empty_circuit = zk::vm::ZkCircuit::new(Vec::new(), &zkbin);
         let curr_circuits: Vec<ZkCircuit> = circuits
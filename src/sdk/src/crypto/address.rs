@@ -0,0 +1,408 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Versioned, checksummed address encoding.
+//!
+//! Addresses are encoded with bech32m (BIP350). The human-readable part
+//! names both the network and the kind of object the address points to,
+//! e.g. `dpk1...` for a mainnet public key or `tddao1...` for a testnet
+//! DAO bulla, so an address meant for another network or purpose is
+//! rejected when it is decoded instead of being silently accepted. The
+//! first data symbol is the address version, followed by the payload.
+//!
+//! Unlike BIP173, the overall length isn't limited to 90 characters, as
+//! diversified addresses carry two public keys.
+
+use core::str::FromStr;
+
+use super::{ContractId, DiversifiedAddress, PublicKey};
+use crate::error::ContractError;
+
+/// Version of the addresses created by [`encode_address`]
+pub const ADDRESS_VERSION: u8 = 0;
+
+/// bech32 alphabet, indexed by 5-bit value
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// Constant the bech32m checksum is XORed with
+const BECH32M_CONST: u32 = 0x2bc830a3;
+
+/// Network an address is valid on
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Localnet,
+}
+
+impl Network {
+    const ALL: [Self; 3] = [Self::Mainnet, Self::Testnet, Self::Localnet];
+
+    /// Prefix of the human-readable part of addresses on this network
+    fn hrp_prefix(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "d",
+            Self::Testnet => "td",
+            Self::Localnet => "ld",
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = ContractError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "localnet" => Ok(Self::Localnet),
+            _ => Err(ContractError::IoError(format!("Unknown network {s}"))),
+        }
+    }
+}
+
+impl core::fmt::Display for Network {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let name = match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Localnet => "localnet",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Kind of object an address points to
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AddressKind {
+    PublicKey,
+    DiversifiedAddress,
+    DaoBulla,
+    ContractId,
+}
+
+impl AddressKind {
+    const ALL: [Self; 4] =
+        [Self::PublicKey, Self::DiversifiedAddress, Self::DaoBulla, Self::ContractId];
+
+    /// Suffix of the human-readable part of addresses of this kind
+    fn hrp_suffix(&self) -> &'static str {
+        match self {
+            Self::PublicKey => "pk",
+            Self::DiversifiedAddress => "addr",
+            Self::DaoBulla => "dao",
+            Self::ContractId => "cid",
+        }
+    }
+}
+
+impl core::fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let name = match self {
+            Self::PublicKey => "public key",
+            Self::DiversifiedAddress => "diversified address",
+            Self::DaoBulla => "DAO bulla",
+            Self::ContractId => "contract ID",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Types that can be encoded as an address
+pub trait Addressable: Sized {
+    /// Kind of the addresses of this type
+    const KIND: AddressKind;
+
+    /// Bytes the address encodes
+    fn address_payload(&self) -> Vec<u8>;
+
+    /// Parse the bytes an address encodes
+    fn from_address_payload(payload: &[u8]) -> Result<Self, ContractError>;
+
+    /// Encode as an address on the given network
+    fn to_address(&self, network: Network) -> String {
+        encode_address(network, Self::KIND, &self.address_payload())
+    }
+
+    /// Decode an address, verifying it is of this type and meant for
+    /// the given network.
+    fn from_address(network: Network, address: &str) -> Result<Self, ContractError> {
+        let (addr_network, kind, payload) = decode_address(address)?;
+        if addr_network != network {
+            return Err(ContractError::IoError(format!(
+                "Address is for {addr_network}, expected {network}"
+            )))
+        }
+        if kind != Self::KIND {
+            return Err(ContractError::IoError(format!(
+                "Address is a {kind}, expected a {}",
+                Self::KIND
+            )))
+        }
+
+        Self::from_address_payload(&payload)
+    }
+}
+
+/// Encode `payload` as a current version address of the given network
+/// and kind.
+pub fn encode_address(network: Network, kind: AddressKind, payload: &[u8]) -> String {
+    let hrp = format!("{}{}", network.hrp_prefix(), kind.hrp_suffix());
+
+    let mut data = vec![ADDRESS_VERSION];
+    data.extend(convert_bits(payload, 8, 5, true).unwrap());
+    data.extend(checksum(&hrp, &data));
+
+    let mut address = hrp;
+    address.push('1');
+    address.extend(data.iter().map(|d| CHARSET[*d as usize] as char));
+    address
+}
+
+/// Decode an address, returning its network, kind, and payload.
+pub fn decode_address(address: &str) -> Result<(Network, AddressKind, Vec<u8>), ContractError> {
+    // Either all lowercase or all uppercase is accepted
+    if address.to_lowercase() != address && address.to_uppercase() != address {
+        return Err(ContractError::IoError("Address has mixed case".to_string()))
+    }
+    let address = address.to_lowercase();
+
+    let Some((hrp, data)) = address.rsplit_once('1') else {
+        return Err(ContractError::IoError("Address has no separator".to_string()))
+    };
+
+    let mut values = Vec::with_capacity(data.len());
+    for c in data.bytes() {
+        let Some(v) = CHARSET.iter().position(|x| *x == c) else {
+            return Err(ContractError::IoError(format!("Invalid address character {}", c as char)))
+        };
+        values.push(v as u8);
+    }
+
+    // At least the version and the checksum
+    if values.len() < 7 {
+        return Err(ContractError::IoError("Address is too short".to_string()))
+    }
+
+    let mut check = hrp_expand(hrp);
+    check.extend(&values);
+    if polymod(&check) != BECH32M_CONST {
+        return Err(ContractError::IoError("Invalid address checksum".to_string()))
+    }
+
+    let Some((network, kind)) = Network::ALL
+        .iter()
+        .flat_map(|n| AddressKind::ALL.iter().map(move |k| (*n, *k)))
+        .find(|(n, k)| format!("{}{}", n.hrp_prefix(), k.hrp_suffix()) == hrp)
+    else {
+        return Err(ContractError::IoError(format!("Unknown address prefix {hrp}")))
+    };
+
+    let values = &values[..values.len() - 6];
+    if values[0] != ADDRESS_VERSION {
+        return Err(ContractError::IoError(format!("Unsupported address version {}", values[0])))
+    }
+
+    let Some(payload) = convert_bits(&values[1..], 5, 8, false) else {
+        return Err(ContractError::IoError("Invalid address padding".to_string()))
+    };
+
+    Ok((network, kind, payload))
+}
+
+/// bech32 checksum generator
+fn polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+    let mut chk = 1u32;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ *v as u32;
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+
+    chk
+}
+
+/// Expand the human-readable part for the checksum computation
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut values: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 31));
+    values
+}
+
+/// Compute the six bech32m checksum symbols of `data`
+fn checksum(hrp: &str, data: &[u8]) -> Vec<u8> {
+    let mut values = hrp_expand(hrp);
+    values.extend(data);
+    values.extend([0u8; 6]);
+
+    let pm = polymod(&values) ^ BECH32M_CONST;
+    (0..6).map(|i| ((pm >> (5 * (5 - i))) & 31) as u8).collect()
+}
+
+/// Regroup `data` from `from`-bit to `to`-bit values. Without `pad`,
+/// leftover bits have to be zero and fewer than `from`.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let mut out = vec![];
+    let max = (1u32 << to) - 1;
+
+    for v in data {
+        acc = (acc << from) | *v as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return None
+    }
+
+    Some(out)
+}
+
+impl Addressable for PublicKey {
+    const KIND: AddressKind = AddressKind::PublicKey;
+
+    fn address_payload(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+
+    fn from_address_payload(payload: &[u8]) -> Result<Self, ContractError> {
+        let Ok(bytes) = payload.try_into() else {
+            return Err(ContractError::IoError("Invalid public key address length".to_string()))
+        };
+        Self::from_bytes(bytes)
+    }
+}
+
+impl Addressable for DiversifiedAddress {
+    const KIND: AddressKind = AddressKind::DiversifiedAddress;
+
+    fn address_payload(&self) -> Vec<u8> {
+        let mut bytes = self.public_key.to_bytes().to_vec();
+        bytes.extend_from_slice(&self.viewing_key.to_bytes());
+        bytes
+    }
+
+    fn from_address_payload(payload: &[u8]) -> Result<Self, ContractError> {
+        if payload.len() != 64 {
+            return Err(ContractError::IoError("Invalid diversified address length".to_string()))
+        }
+
+        Ok(Self {
+            public_key: PublicKey::from_bytes(payload[..32].try_into().unwrap())?,
+            viewing_key: PublicKey::from_bytes(payload[32..].try_into().unwrap())?,
+        })
+    }
+}
+
+impl Addressable for ContractId {
+    const KIND: AddressKind = AddressKind::ContractId;
+
+    fn address_payload(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+
+    fn from_address_payload(payload: &[u8]) -> Result<Self, ContractError> {
+        let Ok(bytes) = payload.try_into() else {
+            return Err(ContractError::IoError("Invalid contract ID address length".to_string()))
+        };
+        Self::from_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{SecretKey, MONEY_CONTRACT_ID};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn bech32m_vectors() {
+        // Valid strings from BIP350
+        for s in ["a1lqfn3a", "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", "?1v759aa"] {
+            let (hrp, data) = s.rsplit_once('1').unwrap();
+            let mut values = hrp_expand(hrp);
+            values
+                .extend(data.bytes().map(|c| CHARSET.iter().position(|x| *x == c).unwrap() as u8));
+            assert_eq!(polymod(&values), BECH32M_CONST);
+        }
+    }
+
+    #[test]
+    fn address_roundtrip() {
+        let public = PublicKey::from_secret(SecretKey::random(&mut OsRng));
+        let address = public.to_address(Network::Mainnet);
+        assert!(address.starts_with("dpk1"));
+        assert_eq!(PublicKey::from_address(Network::Mainnet, &address).unwrap(), public);
+        assert_eq!(
+            PublicKey::from_address(Network::Mainnet, &address.to_uppercase()).unwrap(),
+            public
+        );
+
+        // Wrong network or kind is rejected
+        assert!(PublicKey::from_address(Network::Testnet, &address).is_err());
+        assert!(ContractId::from_address(Network::Mainnet, &address).is_err());
+
+        // A single changed character breaks the checksum
+        let mut tampered = address.clone().into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'q' { b'p' } else { b'q' };
+        let tampered = String::from_utf8(tampered).unwrap();
+        assert!(PublicKey::from_address(Network::Mainnet, &tampered).is_err());
+
+        let address = MONEY_CONTRACT_ID.to_address(Network::Testnet);
+        assert!(address.starts_with("tdcid1"));
+        assert_eq!(
+            ContractId::from_address(Network::Testnet, &address).unwrap(),
+            *MONEY_CONTRACT_ID
+        );
+
+        let diversified = DiversifiedAddress::from_secret(&SecretKey::random(&mut OsRng));
+        let address = diversified.to_address(Network::Localnet);
+        assert!(address.starts_with("ldaddr1"));
+        assert_eq!(
+            DiversifiedAddress::from_address(Network::Localnet, &address).unwrap(),
+            diversified
+        );
+
+        // Unknown versions are rejected
+        let hrp = "dpk";
+        let mut data = vec![1u8];
+        data.extend(convert_bits(&public.to_bytes(), 8, 5, true).unwrap());
+        data.extend(checksum(hrp, &data));
+        let future: String = format!(
+            "{hrp}1{}",
+            data.iter().map(|d| CHARSET[*d as usize] as char).collect::<String>()
+        );
+        assert!(decode_address(&future).is_err());
+    }
+}
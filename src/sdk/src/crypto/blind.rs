@@ -22,6 +22,7 @@ use darkfi_serial::{Decodable, Encodable, SerialDecodable, SerialEncodable};
 
 use pasta_curves::{group::ff::Field, pallas};
 use rand_core::{CryptoRng, RngCore};
+use subtle::{Choice, ConstantTimeEq};
 
 #[cfg(feature = "async")]
 pub trait EncDecode: Encodable + Decodable + AsyncEncodable + AsyncDecodable {}
@@ -32,7 +33,8 @@ impl EncDecode for pallas::Base {}
 impl EncDecode for pallas::Scalar {}
 
 /// Blinding factor used in bullas. Every bulla should contain one.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+/// Equality is checked in constant time.
+#[derive(Debug, Copy, Clone, Eq, SerialEncodable, SerialDecodable)]
 pub struct Blind<F: Field + EncDecode>(pub F);
 
 impl<F: Field + EncDecode> Blind<F> {
@@ -47,6 +49,18 @@ impl<F: Field + EncDecode> Blind<F> {
     }
 }
 
+impl<F: Field + EncDecode> ConstantTimeEq for Blind<F> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<F: Field + EncDecode> PartialEq for Blind<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
 impl<'a, 'b, F: Field + EncDecode> std::ops::Add<&'b Blind<F>> for &'a Blind<F> {
     type Output = Blind<F>;
 
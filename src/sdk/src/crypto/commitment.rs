@@ -0,0 +1,144 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Convenience API over Pedersen value commitments.
+//!
+//! A value commitment is a `pallas::Point` of the form `v * V + r * R`,
+//! where `v` is the committed value, `r` is its blind, and `V` and `R`
+//! are fixed generators. Commitments are additive, so the sum of many
+//! commitments commits to the sum of their values under the sum of their
+//! blinds. Clients should build, combine, and expose commitments through
+//! this module rather than deriving the generators or coordinates manually.
+
+use lazy_static::lazy_static;
+use pasta_curves::{
+    arithmetic::{CurveAffine, CurveExt},
+    group::{ff::Field, Curve, Group, GroupEncoding},
+    pallas,
+};
+
+use super::{
+    blind::ScalarBlind,
+    constants::fixed_bases::{
+        VALUE_COMMITMENT_PERSONALIZATION, VALUE_COMMITMENT_R_BYTES, VALUE_COMMITMENT_V_BYTES,
+    },
+    pedersen::{pedersen_commitment_base, pedersen_commitment_u64, ValueOpeningProof},
+};
+use crate::error::ContractError;
+
+lazy_static! {
+    /// Generator the committed 64-bit value is multiplied with
+    pub static ref VALUE_COMMIT_VALUE: pallas::Point =
+        pallas::Point::hash_to_curve(VALUE_COMMITMENT_PERSONALIZATION)(&VALUE_COMMITMENT_V_BYTES);
+
+    /// Generator the commitment blind is multiplied with
+    pub static ref VALUE_COMMIT_RANDOM: pallas::Point =
+        pallas::Point::hash_to_curve(VALUE_COMMITMENT_PERSONALIZATION)(&VALUE_COMMITMENT_R_BYTES);
+}
+
+/// Commit to a 64-bit `value` with the given `blind`
+pub fn commit(value: u64, blind: ScalarBlind) -> pallas::Point {
+    pedersen_commitment_u64(value, blind)
+}
+
+/// Commit to a full-width base field `value` with the given `blind`
+pub fn commit_base(value: pallas::Base, blind: ScalarBlind) -> pallas::Point {
+    pedersen_commitment_base(value, blind)
+}
+
+/// Add two commitments, committing to the sum of their values
+pub fn add(a: &pallas::Point, b: &pallas::Point) -> pallas::Point {
+    a + b
+}
+
+/// Subtract `b` from `a`, committing to the difference of their values
+pub fn sub(a: &pallas::Point, b: &pallas::Point) -> pallas::Point {
+    a - b
+}
+
+/// Sum the given commitments. An empty iterator returns the identity,
+/// which commits to zero with a zero blind.
+pub fn sum<'a>(commits: impl IntoIterator<Item = &'a pallas::Point>) -> pallas::Point {
+    commits.into_iter().fold(pallas::Point::identity(), |acc, c| acc + c)
+}
+
+/// Check that `commitment` opens to `value` with `blind`
+pub fn verify_opening(commitment: &pallas::Point, value: u64, blind: ScalarBlind) -> bool {
+    commit(value, blind) == *commitment
+}
+
+/// Check a proof that `commitment` opens to `value`, without knowing its blind
+pub fn verify_opening_proof(
+    commitment: &pallas::Point,
+    value: u64,
+    proof: &ValueOpeningProof,
+) -> bool {
+    proof.verify(commitment, value)
+}
+
+/// Affine coordinates of a commitment, in the order ZK circuits take them
+/// as public inputs. The identity maps to `(0, 0)`, as it does in-circuit.
+pub fn xy(commitment: &pallas::Point) -> (pallas::Base, pallas::Base) {
+    match Option::from(commitment.to_affine().coordinates()) {
+        Some(coords) => (*coords.x(), *coords.y()),
+        None => (pallas::Base::ZERO, pallas::Base::ZERO),
+    }
+}
+
+/// Encode a commitment into 32 bytes
+pub fn to_bytes(commitment: &pallas::Point) -> [u8; 32] {
+    commitment.to_bytes()
+}
+
+/// Decode a commitment from 32 bytes. Returns an error if the
+/// encoding is not a valid point.
+pub fn from_bytes(bytes: &[u8; 32]) -> Result<pallas::Point, ContractError> {
+    match Option::from(pallas::Point::from_bytes(bytes)) {
+        Some(commitment) => Ok(commitment),
+        None => Err(ContractError::IoError("Invalid value commitment encoding".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Blind;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn value_commitments() {
+        let a_blind = ScalarBlind::random(&mut OsRng);
+        let b_blind = ScalarBlind::random(&mut OsRng);
+        let a = commit(30, a_blind);
+        let b = commit(12, b_blind);
+
+        assert!(verify_opening(&add(&a, &b), 42, &a_blind + &b_blind));
+        assert!(verify_opening(&sub(&a, &b), 18, Blind(a_blind.inner() - b_blind.inner())));
+        assert!(!verify_opening(&a, 31, a_blind));
+        assert_eq!(sum([&a, &b]), add(&a, &b));
+        assert_eq!(sum(&[]), commit(0, ScalarBlind::ZERO));
+
+        let proof = ValueOpeningProof::create(42, &a_blind + &b_blind, &mut OsRng);
+        assert!(verify_opening_proof(&sum([&a, &b]), 42, &proof));
+
+        assert_eq!(from_bytes(&to_bytes(&a)).unwrap(), a);
+        assert_eq!(xy(&pallas::Point::identity()), (pallas::Base::ZERO, pallas::Base::ZERO));
+        let coords = a.to_affine().coordinates().unwrap();
+        assert_eq!(xy(&a), (*coords.x(), *coords.y()));
+    }
+}
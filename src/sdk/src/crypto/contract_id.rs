@@ -22,7 +22,10 @@ use darkfi_serial::{serialize, SerialDecodable, SerialEncodable};
 use lazy_static::lazy_static;
 use pasta_curves::{group::ff::PrimeField, pallas};
 
-use super::{poseidon_hash, PublicKey, SecretKey};
+use super::{
+    domains::{self, domain},
+    poseidon_hash, PublicKey, SecretKey,
+};
 use crate::error::ContractError;
 
 lazy_static! {
@@ -32,7 +35,7 @@ lazy_static! {
     // the contracts with 0 as their x coordinate can never have a valid signature.
 
     /// Derivation prefix for `ContractId`
-    pub static ref CONTRACT_ID_PREFIX: pallas::Base = pallas::Base::from(42);
+    pub static ref CONTRACT_ID_PREFIX: pallas::Base = domain(domains::CONTRACT_ID);
 
     /// Contract ID for the native money contract
     pub static ref MONEY_CONTRACT_ID: ContractId =
@@ -45,6 +48,10 @@ lazy_static! {
     /// Contract ID for the native Deployooor contract
     pub static ref DEPLOYOOOR_CONTRACT_ID: ContractId =
         ContractId::from(poseidon_hash([*CONTRACT_ID_PREFIX, pallas::Base::zero(), pallas::Base::from(2)]));
+
+    /// Contract ID for the native token metadata Registry contract
+    pub static ref REGISTRY_CONTRACT_ID: ContractId =
+        ContractId::from(poseidon_hash([*CONTRACT_ID_PREFIX, pallas::Base::zero(), pallas::Base::from(3)]));
 }
 
 /// ContractId represents an on-chain identifier for a certain smart contract.
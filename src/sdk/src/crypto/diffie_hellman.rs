@@ -23,6 +23,7 @@ use super::{util::fp_mod_fv, PublicKey, SecretKey};
 use crate::error::ContractError;
 
 pub const KDF_SAPLING_PERSONALIZATION: &[u8; 16] = b"DarkFiSaplingKDF";
+pub const KDF_XCHACHA_PERSONALIZATION: &[u8; 16] = b"DarkFiXChaChaKDF";
 
 /// Sapling key agreement for note encryption.
 /// Implements section 5.4.4.3 of the Zcash Protocol Specification
@@ -40,11 +41,23 @@ pub fn kdf_sapling(dhsecret: &PublicKey, epk: &PublicKey) -> Blake2bHash {
     // The P.to_bytes() for P ∈ ℙₚ function used on affine curves it not perfectly constant time,
     // but it's close enough. The function returns 0 when P = ∞ is the identity which is the
     // edge case but almost never occurs.
+    kdf_sapling_encoded(&dhsecret.inner().to_bytes(), &epk.inner().to_bytes())
+}
+
+/// Sapling KDF over already encoded points, for callers that batch the
+/// normalization of many points before encoding them.
+pub fn kdf_sapling_encoded(dhsecret: &[u8; 32], epk: &[u8; 32]) -> Blake2bHash {
+    kdf_personalized(KDF_SAPLING_PERSONALIZATION, dhsecret, epk)
+}
+
+/// Sapling-style KDF over encoded points with a custom personalization,
+/// so distinct note encryption schemes never share derived keys.
+pub fn kdf_personalized(personal: &[u8; 16], dhsecret: &[u8; 32], epk: &[u8; 32]) -> Blake2bHash {
     Blake2bParams::new()
         .hash_length(32)
-        .personal(KDF_SAPLING_PERSONALIZATION)
+        .personal(personal)
         .to_state()
-        .update(&dhsecret.inner().to_bytes())
-        .update(&epk.inner().to_bytes())
+        .update(dhsecret)
+        .update(epk)
         .finalize()
 }
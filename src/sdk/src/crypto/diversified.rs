@@ -0,0 +1,129 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Diversified addresses.
+//!
+//! A spending [`SecretKey`] derives any number of diversified keypairs,
+//! indexed by a `u64`. Their public addresses can't be linked to each
+//! other or to the spending key without knowing it, so a fresh address
+//! can be handed out to every counterparty. The diversified secrets are
+//! regular `SecretKey`s, so coins sent to them are spent with the usual
+//! circuits, and all of them can be recovered from the spending key.
+
+use core::str::FromStr;
+
+#[cfg(feature = "async")]
+use darkfi_serial::async_trait;
+use darkfi_serial::{SerialDecodable, SerialEncodable};
+use lazy_static::lazy_static;
+use pasta_curves::pallas;
+
+use super::{
+    domains::{self, domain},
+    poseidon_hash, Keypair, PublicKey, SecretKey, ViewingKey,
+};
+use crate::error::ContractError;
+
+lazy_static! {
+    /// Derivation prefix for diversified keys
+    pub static ref DIVERSIFIER_PREFIX: pallas::Base = domain(domains::DIVERSIFIER);
+}
+
+/// Derive the diversified [`Keypair`] at `index` from the given spending key
+pub fn diversify(secret: &SecretKey, index: u64) -> Keypair {
+    let secret = poseidon_hash([*DIVERSIFIER_PREFIX, secret.inner(), pallas::Base::from(index)]);
+    Keypair::new(SecretKey::from(secret))
+}
+
+/// Address handed out to receive payments, either on a wallet's own key
+/// or on one of its diversified keys.
+///
+/// Coins are sent to `public_key`, while their notes are encrypted to
+/// `viewing_key`, the public key of its [`ViewingKey`]. Since the address
+/// carries both, every payment made to it is visible to the viewing key.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, SerialEncodable, SerialDecodable)]
+pub struct DiversifiedAddress {
+    pub public_key: PublicKey,
+    pub viewing_key: PublicKey,
+}
+
+impl DiversifiedAddress {
+    /// Create the address of the given (diversified) `SecretKey`
+    pub fn from_secret(secret: &SecretKey) -> Self {
+        Self {
+            public_key: PublicKey::from_secret(*secret),
+            viewing_key: ViewingKey::derive(secret).public_key(),
+        }
+    }
+}
+
+impl FromStr for DiversifiedAddress {
+    type Err = ContractError;
+
+    /// Tries to create a `DiversifiedAddress` object from a base58 encoded string.
+    fn from_str(enc: &str) -> Result<Self, Self::Err> {
+        let decoded = bs58::decode(enc).into_vec()?;
+        if decoded.len() != 64 {
+            return Err(Self::Err::IoError(
+                "Failed decoding DiversifiedAddress from bytes, len is not 64".to_string(),
+            ))
+        }
+
+        Ok(Self {
+            public_key: PublicKey::from_bytes(decoded[..32].try_into().unwrap())?,
+            viewing_key: PublicKey::from_bytes(decoded[32..].try_into().unwrap())?,
+        })
+    }
+}
+
+impl core::fmt::Display for DiversifiedAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let mut bytes = self.public_key.to_bytes().to_vec();
+        bytes.extend_from_slice(&self.viewing_key.to_bytes());
+        write!(f, "{}", bs58::encode(bytes).into_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::note::AeadEncryptedNote;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn diversified_addresses() {
+        let secret = SecretKey::random(&mut OsRng);
+        let first = diversify(&secret, 0);
+        let second = diversify(&secret, 1);
+        assert_eq!(first, diversify(&secret, 0));
+        assert_ne!(first.public, second.public);
+        assert_ne!(first.public, PublicKey::from_secret(secret));
+
+        // Notes sent to an address are decrypted with its viewing key only
+        let address = DiversifiedAddress::from_secret(&first.secret);
+        assert_eq!(address.public_key, first.public);
+        let note = AeadEncryptedNote::encrypt(&42u64, &address.viewing_key, &mut OsRng).unwrap();
+        let viewing_key = ViewingKey::derive(&first.secret);
+        assert_eq!(note.decrypt::<u64>(&viewing_key.inner()).unwrap(), 42);
+        assert!(note.decrypt::<u64>(&ViewingKey::derive(&second.secret).inner()).is_err());
+
+        let encoded = address.to_string();
+        assert_eq!(DiversifiedAddress::from_str(&encoded).unwrap(), address);
+        assert!(DiversifiedAddress::from_str(&first.public.to_string()).is_err());
+    }
+}
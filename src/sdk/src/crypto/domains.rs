@@ -0,0 +1,94 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Domain separation tags for Poseidon-based derivations.
+//!
+//! Every value derived with [`poseidon_hash`](super::poseidon_hash) from a
+//! fixed number of inputs should start with one of these tags, so that
+//! values derived in different contexts can never collide, and a value
+//! from one context can't be replayed as another. All tags are kept in
+//! this module, so a new derivation can't accidentally reuse one.
+//!
+//! Circuits must use the same numbers, witnessed as constants with
+//! `witness_base`.
+//!
+//! # Migration
+//!
+//! Tags change consensus-critical values, so they can only be applied
+//! together with new versions of the circuits computing them:
+//!
+//! * Contract IDs, token IDs, payment IDs, viewing keys, and diversified
+//!   keys have always been tagged. Their existing numbers are kept.
+//! * [`NULLIFIER`], [`COIN`], [`DAO_BULLA`], and [`DAO_PROPOSAL_BULLA`]
+//!   are reserved. Applying them changes the values already stored on
+//!   chain, e.g. a coin spent before a nullifier tag could be spent
+//!   again under the tagged nullifier. They get applied with `Burn_V2`,
+//!   `Fee_V2`, and the next DAO circuits, which must keep checking the
+//!   untagged values. Until then, these derivations are separated from
+//!   the others by their arity only.
+
+use pasta_curves::pallas;
+
+/// Tag of [`ContractId`](super::ContractId) derivation
+pub const CONTRACT_ID: u64 = 42;
+/// Tag of token ID derivation in the Money contract
+pub const TOKEN_ID: u64 = 69;
+/// Tag of coin blinds committing to a payment ID
+pub const PAYMENT_ID: u64 = 70;
+/// Tag of [`ViewingKey`](super::ViewingKey) derivation
+pub const VIEWING_KEY: u64 = 71;
+/// Tag of diversified key derivation
+pub const DIVERSIFIER: u64 = 72;
+/// Tag reserved for coin nullifiers
+pub const NULLIFIER: u64 = 73;
+/// Tag reserved for coin commitments
+pub const COIN: u64 = 74;
+/// Tag reserved for DAO bullas
+pub const DAO_BULLA: u64 = 75;
+/// Tag reserved for DAO proposal bullas
+pub const DAO_PROPOSAL_BULLA: u64 = 76;
+
+/// All the tags, by name. Every tag must be listed here.
+pub const ALL: [(&str, u64); 9] = [
+    ("CONTRACT_ID", CONTRACT_ID),
+    ("TOKEN_ID", TOKEN_ID),
+    ("PAYMENT_ID", PAYMENT_ID),
+    ("VIEWING_KEY", VIEWING_KEY),
+    ("DIVERSIFIER", DIVERSIFIER),
+    ("NULLIFIER", NULLIFIER),
+    ("COIN", COIN),
+    ("DAO_BULLA", DAO_BULLA),
+    ("DAO_PROPOSAL_BULLA", DAO_PROPOSAL_BULLA),
+];
+
+/// Return the given tag as the field element prepended to hash inputs
+pub fn domain(tag: u64) -> pallas::Base {
+    pallas::Base::from(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn domains_are_distinct() {
+        let tags: HashSet<u64> = ALL.iter().map(|(_, tag)| *tag).collect();
+        assert_eq!(tags.len(), ALL.len());
+    }
+}
@@ -106,9 +106,19 @@ impl VrfProof {
         hasher.finalize() == self.c
     }
 
+    /// Verify a `VrfProof` given a `PublicKey` and a seed input `alpha_string`,
+    /// and return the VRF output only if the proof is valid.
+    pub fn verify_output(&self, Y: PublicKey, alpha_string: &[u8]) -> Option<blake3::Hash> {
+        if !self.verify(Y, alpha_string) {
+            return None
+        }
+
+        Some(self.hash_output())
+    }
+
     /// Returns the VRF output.
     /// **It is necessary** to do `VrfProof::verify` first in order to trust this function's output.
-    /// TODO: FIXME: We should enforce verification before getting the output.
+    /// Prefer `VrfProof::verify_output`, which enforces verification.
     pub fn hash_output(&self) -> blake3::Hash {
         let mut hasher = blake3::Hasher::new();
         hasher.update(VRF_DOMAIN.as_bytes());
@@ -134,10 +144,12 @@ mod tests {
 
         let proof = VrfProof::prove(secret_key, &input);
         assert!(proof.verify(public_key, &input));
+        assert_eq!(proof.verify_output(public_key, &input), Some(proof.hash_output()));
 
         // Forged public key
         let forged_public_key = PublicKey::from_secret(SecretKey::random(&mut OsRng));
         assert!(!proof.verify(forged_public_key, &input));
+        assert!(proof.verify_output(forged_public_key, &input).is_none());
 
         // Forged input
         let forged_input = [0xde, 0xad, 0xba, 0xbe];
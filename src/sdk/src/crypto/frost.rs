@@ -0,0 +1,972 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! FROST: Flexible Round-Optimized Schnorr Threshold signatures
+//! <https://datatracker.ietf.org/doc/html/rfc9591>
+//!
+//! A `t`-of-`n` group shares a single `SecretKey` using Shamir secret
+//! sharing. Any `t` members can then jointly produce a [`Signature`]
+//! over a message in two rounds, without ever reconstructing the
+//! secret. The aggregated signature is an ordinary Schnorr signature
+//! which verifies against the group `PublicKey` through [`SchnorrPublic`],
+//! so on-chain it is indistinguishable from a single-signer one.
+//!
+//! The same shares also decrypt [`ElGamalEncryptedNote`]s sent to the
+//! group public key: each member publishes a [`DecryptionShare`] of the
+//! Diffie-Hellman point with a proof it used its key share, and any `t`
+//! of them combine into the point that opens the note.
+//!
+//! Keys can be generated by a trusted dealer who splits an existing
+//! secret with [`split_secret`], or without any dealer through the
+//! three round distributed key generation of [`dkg_part1`], [`dkg_part2`]
+//! and [`dkg_part3`]. In the latter, each participant deals a random
+//! polynomial of its own, and the group secret is the sum of their
+//! constant terms, which nobody ever learns. Either way, Feldman
+//! commitments to the sharing polynomials let every participant check
+//! the shares it receives.
+//!
+//! Note that this only covers Schnorr signatures. Spending a Money coin
+//! additionally requires proving knowledge of the coin secret inside the
+//! `Burn_V1` circuit, so coins held by a group are locked under the
+//! `Money::GroupSpend` hook, which makes the coin secret known to all
+//! members and requires a group signature to spend.
+//!
+//! [`SchnorrPublic`]: super::schnorr::SchnorrPublic
+//! [`ElGamalEncryptedNote`]: super::note::ElGamalEncryptedNote
+#![allow(non_snake_case)]
+
+use std::collections::{BTreeMap, BTreeSet};
+
+#[cfg(feature = "async")]
+use darkfi_serial::async_trait;
+use darkfi_serial::{SerialDecodable, SerialEncodable};
+use halo2_gadgets::ecc::chip::FixedPoint;
+use pasta_curves::{
+    group::{ff::Field, Group, GroupEncoding},
+    pallas,
+};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{
+    constants::{NullifierK, DRK_SCHNORR_DOMAIN},
+    schnorr::Signature,
+    util::{fp_mod_fv, hash_to_scalar, zeroize_copy},
+    PublicKey, SecretKey,
+};
+use crate::error::{FrostError, FrostResult};
+
+/// Prefix domain used for deriving the binding factors
+const FROST_DOMAIN: &[u8] = b"DarkFi:FROST";
+
+/// Prefix domain used for the decryption share proofs
+const FROST_DECRYPT_DOMAIN: &[u8] = b"DarkFi:FROST_Decrypt";
+
+/// Prefix domain used for the DKG proofs of knowledge
+const FROST_DKG_DOMAIN: &[u8] = b"DarkFi:FROST_DKG";
+
+/// A participant's share of the group secret, as handed out by the dealer.
+/// The share should be verified with [`SecretShare::verify`] before use.
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct SecretShare {
+    index: u16,
+    value: pallas::Scalar,
+    commitments: Vec<pallas::Point>,
+}
+
+impl SecretShare {
+    /// Participant index of this share
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Check the share against the dealer's polynomial commitments and
+    /// return the participant's [`KeyPackage`] on success.
+    pub fn verify(&self) -> FrostResult<KeyPackage> {
+        if self.index == 0 || self.commitments.is_empty() {
+            return Err(FrostError::InvalidIndex(self.index))
+        }
+
+        let verifying_share = NullifierK.generator() * self.value;
+        if verifying_share != evaluate_commitments(&self.commitments, self.index) {
+            return Err(FrostError::InvalidSecretShare(self.index))
+        }
+
+        let Ok(group_public) = PublicKey::try_from(self.commitments[0]) else {
+            return Err(FrostError::InvalidSecretShare(self.index))
+        };
+
+        Ok(KeyPackage {
+            index: self.index,
+            share: self.value,
+            verifying_share,
+            group_public,
+            threshold: self.commitments.len() as u16,
+        })
+    }
+}
+
+impl Drop for SecretShare {
+    fn drop(&mut self) {
+        zeroize_copy(&mut self.value);
+    }
+}
+
+/// A participant's signing key material
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct KeyPackage {
+    index: u16,
+    share: pallas::Scalar,
+    verifying_share: pallas::Point,
+    group_public: PublicKey,
+    threshold: u16,
+}
+
+impl KeyPackage {
+    /// Participant index
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// The group public key that aggregated signatures verify against
+    pub fn group_public(&self) -> PublicKey {
+        self.group_public
+    }
+
+    /// Minimum number of signers needed to produce a signature
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+}
+
+impl Drop for KeyPackage {
+    fn drop(&mut self) {
+        zeroize_copy(&mut self.share);
+    }
+}
+
+/// Public information about the group, used by the aggregator to check
+/// signature shares.
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct PublicKeyPackage {
+    group_public: PublicKey,
+    threshold: u16,
+    /// Verifying share of each participant, sorted by index
+    verifying_shares: Vec<(u16, pallas::Point)>,
+}
+
+impl PublicKeyPackage {
+    /// The group public key
+    pub fn group_public(&self) -> PublicKey {
+        self.group_public
+    }
+
+    /// Minimum number of signers needed to produce a signature
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    fn verifying_share(&self, index: u16) -> Option<pallas::Point> {
+        self.verifying_shares.iter().find(|(i, _)| *i == index).map(|(_, p)| *p)
+    }
+}
+
+/// Split `secret` into `participants` shares, any `threshold` of which
+/// can sign for `PublicKey::from_secret(secret)`.
+pub fn split_secret(
+    secret: &SecretKey,
+    threshold: u16,
+    participants: u16,
+    rng: &mut (impl CryptoRng + RngCore),
+) -> FrostResult<(Vec<SecretShare>, PublicKeyPackage)> {
+    if threshold < 2 || threshold > participants {
+        return Err(FrostError::InvalidThreshold(threshold, participants))
+    }
+
+    let mut coefficients = vec![fp_mod_fv(secret.inner())];
+    for _ in 1..threshold {
+        coefficients.push(pallas::Scalar::random(&mut *rng));
+    }
+
+    let commitments: Vec<pallas::Point> =
+        coefficients.iter().map(|a| NullifierK.generator() * a).collect();
+
+    let mut shares = Vec::with_capacity(participants as usize);
+    let mut verifying_shares = Vec::with_capacity(participants as usize);
+    for index in 1..=participants {
+        let value = evaluate_polynomial(&coefficients, index);
+        verifying_shares.push((index, NullifierK.generator() * value));
+        shares.push(SecretShare { index, value, commitments: commitments.clone() });
+    }
+
+    let pubkeys = PublicKeyPackage {
+        group_public: PublicKey::from_secret(*secret),
+        threshold,
+        verifying_shares,
+    };
+
+    // The polynomial reveals the secret, so it must not outlive the shares
+    coefficients.iter_mut().for_each(zeroize_copy);
+
+    Ok((shares, pubkeys))
+}
+
+/// A participant's secret DKG state, kept between the rounds
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct DkgSecretPackage {
+    index: u16,
+    threshold: u16,
+    participants: u16,
+    coefficients: Vec<pallas::Scalar>,
+    commitments: Vec<pallas::Point>,
+}
+
+impl DkgSecretPackage {
+    /// Participant index
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Number of participants in the key generation
+    pub fn participants(&self) -> u16 {
+        self.participants
+    }
+
+    /// Check the round one packages of the other participants, and return
+    /// them keyed by participant index.
+    fn check_round1<'a>(
+        &self,
+        round1: &'a [DkgRound1Package],
+    ) -> FrostResult<BTreeMap<u16, &'a DkgRound1Package>> {
+        let mut packages = BTreeMap::new();
+        for package in round1.iter().filter(|p| p.index != self.index) {
+            if package.index == 0 || package.index > self.participants {
+                return Err(FrostError::InvalidIndex(package.index))
+            }
+            if packages.insert(package.index, package).is_some() {
+                return Err(FrostError::DuplicateIndex(package.index))
+            }
+            if package.commitments.len() != self.threshold as usize || !package.verify() {
+                return Err(FrostError::InvalidProofOfKnowledge(package.index))
+            }
+        }
+
+        for index in (1..=self.participants).filter(|i| *i != self.index) {
+            if !packages.contains_key(&index) {
+                return Err(FrostError::MissingPackage(index))
+            }
+        }
+
+        Ok(packages)
+    }
+}
+
+impl Drop for DkgSecretPackage {
+    fn drop(&mut self) {
+        self.coefficients.iter_mut().for_each(zeroize_copy);
+    }
+}
+
+/// DKG round one message, broadcast to every other participant. It holds
+/// the commitments to the participant's polynomial and a Schnorr proof of
+/// knowledge of its constant term, so nobody can pick its commitments as
+/// a function of the others' to bias the group key.
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct DkgRound1Package {
+    index: u16,
+    commitments: Vec<pallas::Point>,
+    proof_commit: pallas::Point,
+    proof_response: pallas::Scalar,
+}
+
+impl DkgRound1Package {
+    /// Participant index
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Challenge of the proof of knowledge of `log_G(commitments[0])`
+    fn challenge(index: u16, constant: &pallas::Point, R: &pallas::Point) -> pallas::Scalar {
+        hash_to_scalar(
+            FROST_DKG_DOMAIN,
+            &[&index.to_le_bytes(), &constant.to_bytes(), &R.to_bytes()],
+        )
+    }
+
+    /// Verify the proof of knowledge of the polynomial's constant term
+    fn verify(&self) -> bool {
+        let challenge = Self::challenge(self.index, &self.commitments[0], &self.proof_commit);
+        NullifierK.generator() * self.proof_response - self.commitments[0] * challenge ==
+            self.proof_commit
+    }
+}
+
+/// DKG round two message, holding the share of one participant's
+/// polynomial for another. It must only be sent to its receiver,
+/// over a confidential channel.
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct DkgRound2Package {
+    sender: u16,
+    receiver: u16,
+    value: pallas::Scalar,
+}
+
+impl DkgRound2Package {
+    /// Index of the participant that dealt this share
+    pub fn sender(&self) -> u16 {
+        self.sender
+    }
+
+    /// Index of the participant this share is for
+    pub fn receiver(&self) -> u16 {
+        self.receiver
+    }
+}
+
+impl Drop for DkgRound2Package {
+    fn drop(&mut self) {
+        zeroize_copy(&mut self.value);
+    }
+}
+
+/// DKG round one: deal a random polynomial and commit to it. The secret
+/// package is kept, and the round one package is broadcast.
+pub fn dkg_part1(
+    index: u16,
+    threshold: u16,
+    participants: u16,
+    rng: &mut (impl CryptoRng + RngCore),
+) -> FrostResult<(DkgSecretPackage, DkgRound1Package)> {
+    if threshold < 2 || threshold > participants {
+        return Err(FrostError::InvalidThreshold(threshold, participants))
+    }
+    if index == 0 || index > participants {
+        return Err(FrostError::InvalidIndex(index))
+    }
+
+    let coefficients: Vec<pallas::Scalar> =
+        (0..threshold).map(|_| pallas::Scalar::random(&mut *rng)).collect();
+    let commitments: Vec<pallas::Point> =
+        coefficients.iter().map(|a| NullifierK.generator() * a).collect();
+
+    let mut nonce = pallas::Scalar::random(&mut *rng);
+    let proof_commit = NullifierK.generator() * nonce;
+    let challenge = DkgRound1Package::challenge(index, &commitments[0], &proof_commit);
+    let proof_response = nonce + coefficients[0] * challenge;
+    zeroize_copy(&mut nonce);
+
+    let package =
+        DkgRound1Package { index, commitments: commitments.clone(), proof_commit, proof_response };
+
+    Ok((DkgSecretPackage { index, threshold, participants, coefficients, commitments }, package))
+}
+
+/// DKG round two: check everyone's round one packages, and evaluate our
+/// polynomial for each of the other participants. Each of the returned
+/// packages must only be sent to its receiver.
+pub fn dkg_part2(
+    secret: &DkgSecretPackage,
+    round1: &[DkgRound1Package],
+) -> FrostResult<Vec<DkgRound2Package>> {
+    let packages = secret.check_round1(round1)?;
+
+    Ok(packages
+        .keys()
+        .map(|receiver| DkgRound2Package {
+            sender: secret.index,
+            receiver: *receiver,
+            value: evaluate_polynomial(&secret.coefficients, *receiver),
+        })
+        .collect())
+}
+
+/// DKG round three: check the shares we received against their dealers'
+/// commitments, and combine them into our [`KeyPackage`] along with the
+/// group's [`PublicKeyPackage`].
+pub fn dkg_part3(
+    secret: &DkgSecretPackage,
+    round1: &[DkgRound1Package],
+    round2: &[DkgRound2Package],
+) -> FrostResult<(KeyPackage, PublicKeyPackage)> {
+    let packages = secret.check_round1(round1)?;
+
+    let mut share = evaluate_polynomial(&secret.coefficients, secret.index);
+    let mut group_commitments = secret.commitments.clone();
+    for (sender, package) in &packages {
+        let Some(received) =
+            round2.iter().find(|p| p.sender == *sender && p.receiver == secret.index)
+        else {
+            return Err(FrostError::MissingPackage(*sender))
+        };
+
+        if NullifierK.generator() * received.value !=
+            evaluate_commitments(&package.commitments, secret.index)
+        {
+            return Err(FrostError::InvalidSecretShare(*sender))
+        }
+
+        share += received.value;
+        for (acc, C) in group_commitments.iter_mut().zip(&package.commitments) {
+            *acc += C;
+        }
+    }
+
+    // The group polynomial is the sum of everyone's, so its commitments
+    // give the group key and every participant's verifying share.
+    let Ok(group_public) = PublicKey::try_from(group_commitments[0]) else {
+        return Err(FrostError::InvalidSecretShare(secret.index))
+    };
+
+    let verifying_shares: Vec<(u16, pallas::Point)> = (1..=secret.participants)
+        .map(|index| (index, evaluate_commitments(&group_commitments, index)))
+        .collect();
+
+    let verifying_share = NullifierK.generator() * share;
+    if verifying_shares[secret.index as usize - 1].1 != verifying_share {
+        return Err(FrostError::InvalidSecretShare(secret.index))
+    }
+
+    let key = KeyPackage {
+        index: secret.index,
+        share,
+        verifying_share,
+        group_public,
+        threshold: secret.threshold,
+    };
+    zeroize_copy(&mut share);
+
+    let pubkeys = PublicKeyPackage { group_public, threshold: secret.threshold, verifying_shares };
+
+    Ok((key, pubkeys))
+}
+
+/// Round one secret nonces. These must be used for exactly one signature
+/// and are consumed by [`sign`].
+#[derive(SerialEncodable, SerialDecodable)]
+pub struct SigningNonces {
+    hiding: pallas::Scalar,
+    binding: pallas::Scalar,
+    commitments: SigningCommitments,
+}
+
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        zeroize_copy(&mut self.hiding);
+        zeroize_copy(&mut self.binding);
+    }
+}
+
+/// Round one public commitments, sent to the coordinator
+#[derive(Copy, Clone, Debug, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct SigningCommitments {
+    index: u16,
+    hiding: pallas::Point,
+    binding: pallas::Point,
+}
+
+impl SigningCommitments {
+    /// Participant index
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+/// Round one: generate fresh nonces and their public commitments.
+pub fn commit(
+    key: &KeyPackage,
+    rng: &mut (impl CryptoRng + RngCore),
+) -> (SigningNonces, SigningCommitments) {
+    let hiding = pallas::Scalar::random(&mut *rng);
+    let binding = pallas::Scalar::random(&mut *rng);
+
+    let commitments = SigningCommitments {
+        index: key.index,
+        hiding: NullifierK.generator() * hiding,
+        binding: NullifierK.generator() * binding,
+    };
+
+    (SigningNonces { hiding, binding, commitments }, commitments)
+}
+
+/// The message and set of round one commitments chosen by the
+/// coordinator, sent to every signer in round two.
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct SigningPackage {
+    /// Commitments sorted by participant index
+    commitments: Vec<SigningCommitments>,
+    message: Vec<u8>,
+}
+
+impl SigningPackage {
+    /// Create a new `SigningPackage`, rejecting duplicate participants.
+    pub fn new(mut commitments: Vec<SigningCommitments>, message: &[u8]) -> FrostResult<Self> {
+        commitments.sort_by_key(|c| c.index);
+
+        let mut seen = BTreeSet::new();
+        for c in &commitments {
+            if c.index == 0 {
+                return Err(FrostError::InvalidIndex(c.index))
+            }
+            if !seen.insert(c.index) {
+                return Err(FrostError::DuplicateIndex(c.index))
+            }
+        }
+
+        Ok(Self { commitments, message: message.to_vec() })
+    }
+
+    /// The message being signed
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Compute each signer's binding factor
+    fn binding_factors(&self, group_public: &PublicKey) -> BTreeMap<u16, pallas::Scalar> {
+        let mut encoded = vec![];
+        for c in &self.commitments {
+            encoded.extend_from_slice(&c.index.to_le_bytes());
+            encoded.extend_from_slice(&c.hiding.to_bytes());
+            encoded.extend_from_slice(&c.binding.to_bytes());
+        }
+
+        let group_bytes = group_public.to_bytes();
+        self.commitments
+            .iter()
+            .map(|c| {
+                let rho = hash_to_scalar(
+                    FROST_DOMAIN,
+                    &[&group_bytes, &encoded, &self.message, &c.index.to_le_bytes()],
+                );
+                (c.index, rho)
+            })
+            .collect()
+    }
+
+    /// Compute the group commitment `R` and the Schnorr challenge
+    fn group_commitment(
+        &self,
+        group_public: &PublicKey,
+        binding_factors: &BTreeMap<u16, pallas::Scalar>,
+    ) -> (pallas::Point, pallas::Scalar) {
+        let mut R = pallas::Point::identity();
+        for c in &self.commitments {
+            R += c.hiding + c.binding * binding_factors[&c.index];
+        }
+
+        // Same transcript as SchnorrSecret::sign, so the aggregated
+        // signature verifies as a regular one.
+        let commit_bytes = R.to_bytes();
+        let pubkey_bytes = group_public.to_bytes();
+        let transcript = &[&commit_bytes[..], &pubkey_bytes[..], &self.message[..]];
+        let challenge = hash_to_scalar(DRK_SCHNORR_DOMAIN, transcript);
+
+        (R, challenge)
+    }
+
+    fn signers(&self) -> Vec<u16> {
+        self.commitments.iter().map(|c| c.index).collect()
+    }
+}
+
+/// A signer's round two response
+#[derive(Copy, Clone, Debug, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct SignatureShare {
+    index: u16,
+    response: pallas::Scalar,
+}
+
+impl SignatureShare {
+    /// Participant index
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+/// Round two: produce this participant's signature share.
+pub fn sign(
+    package: &SigningPackage,
+    nonces: SigningNonces,
+    key: &KeyPackage,
+) -> FrostResult<SignatureShare> {
+    if package.commitments.len() < key.threshold as usize {
+        return Err(FrostError::NotEnoughSigners(package.commitments.len(), key.threshold))
+    }
+
+    // Make sure the coordinator included our commitments unmodified
+    if !package.commitments.contains(&nonces.commitments) {
+        return Err(FrostError::MissingCommitment(key.index))
+    }
+
+    let binding_factors = package.binding_factors(&key.group_public);
+    let (_, challenge) = package.group_commitment(&key.group_public, &binding_factors);
+    let lambda = lagrange_coefficient(key.index, &package.signers());
+
+    let response = nonces.hiding +
+        nonces.binding * binding_factors[&key.index] +
+        lambda * key.share * challenge;
+
+    Ok(SignatureShare { index: key.index, response })
+}
+
+/// Verify the signature shares and combine them into a [`Signature`]
+/// for the group public key.
+pub fn aggregate(
+    package: &SigningPackage,
+    shares: &[SignatureShare],
+    pubkeys: &PublicKeyPackage,
+) -> FrostResult<Signature> {
+    if package.commitments.len() < pubkeys.threshold as usize {
+        return Err(FrostError::NotEnoughSigners(package.commitments.len(), pubkeys.threshold))
+    }
+
+    let binding_factors = package.binding_factors(&pubkeys.group_public);
+    let (R, challenge) = package.group_commitment(&pubkeys.group_public, &binding_factors);
+    let signers = package.signers();
+
+    let mut response = pallas::Scalar::ZERO;
+    for c in &package.commitments {
+        let Some(share) = shares.iter().find(|s| s.index == c.index) else {
+            return Err(FrostError::InvalidSignatureShare(c.index))
+        };
+
+        let Some(verifying_share) = pubkeys.verifying_share(c.index) else {
+            return Err(FrostError::InvalidIndex(c.index))
+        };
+
+        // Check each share so a misbehaving signer can be identified
+        let lambda = lagrange_coefficient(c.index, &signers);
+        let expected = c.hiding +
+            c.binding * binding_factors[&c.index] +
+            verifying_share * (lambda * challenge);
+        if NullifierK.generator() * share.response != expected {
+            return Err(FrostError::InvalidSignatureShare(c.index))
+        }
+
+        response += share.response;
+    }
+
+    Ok(Signature::from_parts(R, response))
+}
+
+/// A participant's share of the Diffie-Hellman point `ephem_public * secret`
+/// for the group secret, with a Chaum-Pedersen proof that it was computed
+/// with the participant's key share.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct DecryptionShare {
+    index: u16,
+    point: pallas::Point,
+    challenge: pallas::Scalar,
+    response: pallas::Scalar,
+}
+
+impl DecryptionShare {
+    /// Participant index
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Challenge of the proof that `log_G(verifying_share) == log_E(point)`
+    fn challenge(
+        verifying_share: &pallas::Point,
+        ephem_public: &pallas::Point,
+        point: &pallas::Point,
+        R_G: &pallas::Point,
+        R_E: &pallas::Point,
+    ) -> pallas::Scalar {
+        hash_to_scalar(
+            FROST_DECRYPT_DOMAIN,
+            &[
+                &verifying_share.to_bytes(),
+                &ephem_public.to_bytes(),
+                &point.to_bytes(),
+                &R_G.to_bytes(),
+                &R_E.to_bytes(),
+            ],
+        )
+    }
+}
+
+/// Produce this participant's share for decrypting a note with the
+/// given ephemeral public key.
+pub fn decryption_share(
+    key: &KeyPackage,
+    ephem_public: &PublicKey,
+    rng: &mut (impl CryptoRng + RngCore),
+) -> DecryptionShare {
+    let E = ephem_public.inner();
+    let point = E * key.share;
+
+    let mut nonce = pallas::Scalar::random(&mut *rng);
+    let R_G = NullifierK.generator() * nonce;
+    let R_E = E * nonce;
+    let challenge = DecryptionShare::challenge(&key.verifying_share, &E, &point, &R_G, &R_E);
+    let response = nonce + challenge * key.share;
+    zeroize_copy(&mut nonce);
+
+    DecryptionShare { index: key.index, point, challenge, response }
+}
+
+/// Verify the decryption shares and combine them into the Diffie-Hellman
+/// point `ephem_public * secret` of the group secret.
+pub fn combine_decryption_shares(
+    ephem_public: &PublicKey,
+    shares: &[DecryptionShare],
+    pubkeys: &PublicKeyPackage,
+) -> FrostResult<PublicKey> {
+    let mut seen = BTreeSet::new();
+    for share in shares {
+        if !seen.insert(share.index) {
+            return Err(FrostError::DuplicateIndex(share.index))
+        }
+    }
+    let indices: Vec<u16> = seen.into_iter().collect();
+
+    if shares.len() < pubkeys.threshold as usize {
+        return Err(FrostError::NotEnoughSigners(shares.len(), pubkeys.threshold))
+    }
+
+    let E = ephem_public.inner();
+    let mut point = pallas::Point::identity();
+    for share in shares {
+        let Some(verifying_share) = pubkeys.verifying_share(share.index) else {
+            return Err(FrostError::InvalidIndex(share.index))
+        };
+
+        // Check each share so a misbehaving participant can be identified
+        let R_G = NullifierK.generator() * share.response - verifying_share * share.challenge;
+        let R_E = E * share.response - share.point * share.challenge;
+        let challenge = DecryptionShare::challenge(&verifying_share, &E, &share.point, &R_G, &R_E);
+        if challenge != share.challenge {
+            return Err(FrostError::InvalidDecryptionShare(share.index))
+        }
+
+        point += share.point * lagrange_coefficient(share.index, &indices);
+    }
+
+    // The point can only be the identity for a zero group secret
+    PublicKey::try_from(point).map_err(|_| FrostError::InvalidSignature)
+}
+
+/// Evaluate the sharing polynomial at `index`
+fn evaluate_polynomial(coefficients: &[pallas::Scalar], index: u16) -> pallas::Scalar {
+    let x = pallas::Scalar::from(index as u64);
+    coefficients.iter().rev().fold(pallas::Scalar::ZERO, |acc, a| acc * x + a)
+}
+
+/// Evaluate the Feldman commitments at `index`, giving `G * f(index)`
+fn evaluate_commitments(commitments: &[pallas::Point], index: u16) -> pallas::Point {
+    let x = pallas::Scalar::from(index as u64);
+    commitments.iter().rev().fold(pallas::Point::identity(), |acc, C| acc * x + C)
+}
+
+/// Lagrange coefficient of `index` at zero over the given signer set
+fn lagrange_coefficient(index: u16, signers: &[u16]) -> pallas::Scalar {
+    let x_i = pallas::Scalar::from(index as u64);
+
+    let mut num = pallas::Scalar::ONE;
+    let mut den = pallas::Scalar::ONE;
+    for j in signers.iter().filter(|j| **j != index) {
+        let x_j = pallas::Scalar::from(*j as u64);
+        num *= x_j;
+        den *= x_j - x_i;
+    }
+
+    // Indices are distinct and nonzero, so the denominator is invertible
+    num * den.invert().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{note::ElGamalEncryptedNote, schnorr::SchnorrPublic};
+    use darkfi_serial::{deserialize, serialize};
+    use rand::rngs::OsRng;
+
+    fn sign_with(
+        keys: &[KeyPackage],
+        pubkeys: &PublicKeyPackage,
+        message: &[u8],
+    ) -> FrostResult<Signature> {
+        let mut nonces = vec![];
+        let mut commitments = vec![];
+        for key in keys {
+            let (n, c) = commit(key, &mut OsRng);
+            nonces.push(n);
+            commitments.push(c);
+        }
+
+        let package = SigningPackage::new(commitments, message)?;
+
+        let mut shares = vec![];
+        for (key, n) in keys.iter().zip(nonces) {
+            shares.push(sign(&package, n, key)?);
+        }
+
+        aggregate(&package, &shares, pubkeys)
+    }
+
+    #[test]
+    fn test_frost_signature() {
+        let secret = SecretKey::random(&mut OsRng);
+        let (shares, pubkeys) = split_secret(&secret, 2, 3, &mut OsRng).unwrap();
+        assert_eq!(pubkeys.group_public(), PublicKey::from_secret(secret));
+
+        let keys: Vec<KeyPackage> = shares.iter().map(|s| s.verify().unwrap()).collect();
+        let message: &[u8] = b"aaaahhhh we're signiiinngg";
+
+        // Any 2 of the 3 can sign, and the result is a regular signature
+        for signers in [[0, 1], [0, 2], [1, 2]] {
+            let subset = [keys[signers[0]].clone(), keys[signers[1]].clone()];
+            let signature = sign_with(&subset, &pubkeys, message).unwrap();
+            assert!(pubkeys.group_public().verify(message, &signature));
+
+            let de = deserialize(&serialize(&signature)).unwrap();
+            assert!(pubkeys.group_public().verify(message, &de));
+        }
+
+        // All 3 can sign as well
+        let signature = sign_with(&keys, &pubkeys, message).unwrap();
+        assert!(pubkeys.group_public().verify(message, &signature));
+
+        // A single signer is below the threshold
+        assert!(sign_with(&keys[..1], &pubkeys, message).is_err());
+    }
+
+    #[test]
+    fn test_frost_decryption() {
+        let secret = SecretKey::random(&mut OsRng);
+        let (shares, pubkeys) = split_secret(&secret, 2, 3, &mut OsRng).unwrap();
+        let keys: Vec<KeyPackage> = shares.iter().map(|s| s.verify().unwrap()).collect();
+
+        let values = [pallas::Base::from(42), pallas::Base::from(69)];
+        let ephem_secret = SecretKey::random(&mut OsRng);
+        let note =
+            ElGamalEncryptedNote::encrypt_unsafe(values, &ephem_secret, &pubkeys.group_public())
+                .unwrap();
+
+        // Any 2 of the 3 can decrypt
+        for signers in [[0, 1], [0, 2], [1, 2]] {
+            let dec_shares: Vec<DecryptionShare> = signers
+                .iter()
+                .map(|i| decryption_share(&keys[*i], &note.ephem_public, &mut OsRng))
+                .collect();
+            let shared_point =
+                combine_decryption_shares(&note.ephem_public, &dec_shares, &pubkeys).unwrap();
+            assert_eq!(note.decrypt_unsafe_shared(&shared_point), values);
+        }
+
+        // A single share is below the threshold
+        let share0 = decryption_share(&keys[0], &note.ephem_public, &mut OsRng);
+        assert!(combine_decryption_shares(&note.ephem_public, &[share0], &pubkeys).is_err());
+
+        // A share not made with the participant's key is caught
+        let mut share1 = decryption_share(&keys[1], &note.ephem_public, &mut OsRng);
+        share1.point += NullifierK.generator();
+        match combine_decryption_shares(&note.ephem_public, &[share0, share1], &pubkeys) {
+            Err(FrostError::InvalidDecryptionShare(i)) => assert_eq!(i, keys[1].index()),
+            _ => panic!("Tampered decryption share was accepted"),
+        }
+    }
+
+    #[test]
+    fn test_frost_dkg() {
+        let (threshold, participants) = (2, 3);
+
+        let mut secrets = vec![];
+        let mut round1 = vec![];
+        for index in 1..=participants {
+            let (secret, package) = dkg_part1(index, threshold, participants, &mut OsRng).unwrap();
+            secrets.push(secret);
+            round1.push(package);
+        }
+
+        let mut round2 = vec![];
+        for secret in &secrets {
+            round2.extend(dkg_part2(secret, &round1).unwrap());
+        }
+
+        let mut keys = vec![];
+        let mut pubkeys = vec![];
+        for secret in &secrets {
+            let (key, pubkey) = dkg_part3(secret, &round1, &round2).unwrap();
+            keys.push(key);
+            pubkeys.push(pubkey);
+        }
+
+        // Everyone ends up with the same group key
+        let group_public = pubkeys[0].group_public();
+        for (key, pubkey) in keys.iter().zip(&pubkeys) {
+            assert_eq!(key.group_public(), group_public);
+            assert_eq!(pubkey.verifying_shares, pubkeys[0].verifying_shares);
+        }
+
+        // Any 2 of the 3 can sign for it
+        let message: &[u8] = b"no dealer was harmed";
+        for signers in [[0, 1], [0, 2], [1, 2]] {
+            let subset = [keys[signers[0]].clone(), keys[signers[1]].clone()];
+            let signature = sign_with(&subset, &pubkeys[0], message).unwrap();
+            assert!(group_public.verify(message, &signature));
+        }
+
+        // A forged proof of knowledge is rejected
+        let mut forged = round1.clone();
+        forged[1].proof_response += pallas::Scalar::ONE;
+        match dkg_part2(&secrets[0], &forged) {
+            Err(FrostError::InvalidProofOfKnowledge(i)) => assert_eq!(i, 2),
+            _ => panic!("Forged proof of knowledge was accepted"),
+        }
+
+        // A missing participant is caught
+        assert!(dkg_part2(&secrets[0], &round1[..2]).is_err());
+
+        // A share that doesn't match its dealer's commitments is caught
+        let mut tampered = round2.clone();
+        for package in tampered.iter_mut().filter(|p| p.receiver == 1) {
+            package.value += pallas::Scalar::ONE;
+        }
+        assert!(dkg_part3(&secrets[0], &round1, &tampered).is_err());
+    }
+
+    #[test]
+    fn test_frost_invalid_shares() {
+        let secret = SecretKey::random(&mut OsRng);
+        assert!(split_secret(&secret, 1, 3, &mut OsRng).is_err());
+        assert!(split_secret(&secret, 4, 3, &mut OsRng).is_err());
+
+        let (mut shares, pubkeys) = split_secret(&secret, 2, 3, &mut OsRng).unwrap();
+
+        // A tampered share fails verification against the commitments
+        shares[0].value += pallas::Scalar::ONE;
+        assert!(shares[0].verify().is_err());
+        shares[0].value -= pallas::Scalar::ONE;
+
+        // A tampered signature share is caught by the aggregator
+        let keys: Vec<KeyPackage> = shares.iter().map(|s| s.verify().unwrap()).collect();
+        let (n0, c0) = commit(&keys[0], &mut OsRng);
+        let (n1, c1) = commit(&keys[1], &mut OsRng);
+        let package = SigningPackage::new(vec![c0, c1], b"hello").unwrap();
+
+        let s0 = sign(&package, n0, &keys[0]).unwrap();
+        let mut s1 = sign(&package, n1, &keys[1]).unwrap();
+        s1.response += pallas::Scalar::ONE;
+
+        match aggregate(&package, &[s0, s1], &pubkeys) {
+            Err(FrostError::InvalidSignatureShare(i)) => assert_eq!(i, keys[1].index()),
+            _ => panic!("Tampered signature share was accepted"),
+        }
+    }
+}
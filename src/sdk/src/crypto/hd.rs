@@ -0,0 +1,220 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Hierarchical deterministic key derivation.
+//!
+//! An [`ExtendedSecretKey`] pairs a [`SecretKey`] with a chain code, and
+//! derives child keys from both of them. Starting from a master key built
+//! from a random seed, any number of keys can be derived along paths such
+//! as `m/0'/3'`, so a single seed backup recovers all of them.
+//!
+//! Only hardened derivation is supported: a child key can't be derived
+//! from its parent's public key, and a leaked child secret together with
+//! the parent chain code does not reveal the parent secret.
+
+use core::str::FromStr;
+
+use pasta_curves::group::ff::PrimeField;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use super::{util::hash_to_base, Keypair, SecretKey};
+use crate::error::ContractError;
+
+/// BLAKE2b personalization of the master key derivation
+const HD_MASTER_PERSONA: &[u8] = b"DarkFi:HD_Master";
+/// BLAKE2b personalization of the child key derivation
+const HD_CHILD_PERSONA: &[u8] = b"DarkFi:HD_Child";
+/// BLAKE3 context of the master chain code derivation
+const HD_CHAIN_CODE_CONTEXT: &str = "DarkFi 2024-10-01 HD master chain code";
+
+/// Flag marking a derivation index as hardened
+pub const HARDENED: u32 = 1 << 31;
+
+/// A [`SecretKey`] able to derive child keys.
+/// Equality is checked in constant time, and `Debug` output is redacted.
+#[derive(Copy, Clone, Eq)]
+pub struct ExtendedSecretKey {
+    /// The secret key at this position of the tree
+    pub secret: SecretKey,
+    /// Entropy mixed into the derivation of the children
+    pub chain_code: [u8; 32],
+}
+
+impl ExtendedSecretKey {
+    /// Create the master key from the given seed
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self {
+            secret: SecretKey::from(hash_to_base(HD_MASTER_PERSONA, &[seed])),
+            chain_code: blake3::derive_key(HD_CHAIN_CODE_CONTEXT, seed),
+        }
+    }
+
+    /// Derive the hardened child key at `index`. The hardened flag is
+    /// set on the index if it isn't already.
+    pub fn derive_child(&self, index: u32) -> Self {
+        let index = (index | HARDENED).to_le_bytes();
+        let mut secret = self.secret.inner().to_repr();
+
+        let child_secret = hash_to_base(HD_CHILD_PERSONA, &[&self.chain_code, &secret, &index]);
+
+        let mut hasher = blake3::Hasher::new_keyed(&self.chain_code);
+        hasher.update(&secret);
+        hasher.update(&index);
+        secret.zeroize();
+
+        Self { secret: SecretKey::from(child_secret), chain_code: *hasher.finalize().as_bytes() }
+    }
+
+    /// Derive the key at the given path, relative to this key
+    pub fn derive_path(&self, path: &DerivationPath) -> Self {
+        path.0.iter().fold(*self, |mut key, index| {
+            let child = key.derive_child(*index);
+            key.zeroize();
+            child
+        })
+    }
+
+    /// Return the [`Keypair`] of this key
+    pub fn keypair(&self) -> Keypair {
+        Keypair::new(self.secret)
+    }
+}
+
+impl ConstantTimeEq for ExtendedSecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.secret.ct_eq(&other.secret) & self.chain_code[..].ct_eq(&other.chain_code[..])
+    }
+}
+
+impl PartialEq for ExtendedSecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl core::fmt::Debug for ExtendedSecretKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("ExtendedSecretKey(<redacted>)")
+    }
+}
+
+impl Zeroize for ExtendedSecretKey {
+    fn zeroize(&mut self) {
+        self.secret.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+
+/// Path of hardened indexes in the key tree, written as `m/0'/3'`
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Create a path from the given indexes, setting their hardened flag
+    pub fn new(indexes: &[u32]) -> Self {
+        Self(indexes.iter().map(|x| x | HARDENED).collect())
+    }
+
+    /// Return the path extended with the hardened `index`
+    pub fn child(&self, index: u32) -> Self {
+        let mut indexes = self.0.clone();
+        indexes.push(index | HARDENED);
+        Self(indexes)
+    }
+
+    /// Reference the hardened indexes of the path
+    pub fn indexes(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = ContractError;
+
+    /// Parse a path such as `m/0'/3'`. Every index has to be hardened,
+    /// marked with either `'` or `h`.
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(ContractError::IoError("Derivation path must start with m".to_string()))
+        }
+
+        let mut indexes = vec![];
+        for part in parts {
+            let Some(index) = part.strip_suffix('\'').or_else(|| part.strip_suffix('h')) else {
+                return Err(ContractError::IoError(format!(
+                    "Derivation path index {part} is not hardened"
+                )))
+            };
+
+            let Ok(index) = index.parse::<u32>() else {
+                return Err(ContractError::IoError(format!("Invalid derivation path index {part}")))
+            };
+
+            if index >= HARDENED {
+                return Err(ContractError::IoError(format!(
+                    "Derivation path index {part} is out of range"
+                )))
+            }
+
+            indexes.push(index | HARDENED);
+        }
+
+        Ok(Self(indexes))
+    }
+}
+
+impl core::fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index & !HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hd_derivation() {
+        let master = ExtendedSecretKey::from_seed(&[42u8; 32]);
+        assert_eq!(master, ExtendedSecretKey::from_seed(&[42u8; 32]));
+        assert_ne!(master, ExtendedSecretKey::from_seed(&[43u8; 32]));
+
+        // Children are deterministic, distinct, and always hardened
+        let first = master.derive_child(0);
+        assert_eq!(first, master.derive_child(HARDENED));
+        assert_ne!(first.secret, master.derive_child(1).secret);
+        assert_ne!(first.secret, master.secret);
+
+        let path = DerivationPath::from_str("m/0'/3h").unwrap();
+        assert_eq!(path, DerivationPath::new(&[0, 3]));
+        assert_eq!(path, DerivationPath::default().child(0).child(3));
+        assert_eq!(path.to_string(), "m/0'/3'");
+        assert_eq!(master.derive_path(&path), first.derive_child(3));
+
+        assert!(DerivationPath::from_str("m/0/3'").is_err());
+        assert!(DerivationPath::from_str("0'/3'").is_err());
+        assert!(DerivationPath::from_str("m/2147483648'").is_err());
+        assert_eq!(DerivationPath::from_str("m").unwrap(), DerivationPath::default());
+    }
+}
@@ -31,8 +31,13 @@ use pasta_curves::{
     pallas,
 };
 use rand_core::{CryptoRng, RngCore};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
 
-use super::{constants::NullifierK, util::fp_mod_fv};
+use super::{
+    constants::NullifierK,
+    util::{fp_mod_fv, zeroize_copy},
+};
 use crate::error::ContractError;
 
 /// Keypair structure holding a `SecretKey` and its respective `PublicKey`
@@ -54,6 +59,12 @@ impl Keypair {
     }
 }
 
+impl Zeroize for Keypair {
+    fn zeroize(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
 impl Default for Keypair {
     /// Default Keypair used in genesis block generation
     fn default() -> Self {
@@ -64,7 +75,21 @@ impl Default for Keypair {
 }
 
 /// Structure holding a secret key, wrapping a `pallas::Base` element.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, SerialEncodable, SerialDecodable)]
+///
+/// `SecretKey` is `Copy`, so it can't wipe itself when dropped, and neither
+/// can the `Copy` types wrapping it: [`Keypair`],
+/// [`ViewingKey`](super::ViewingKey) and
+/// [`ExtendedSecretKey`](super::ExtendedSecretKey). Their holders call
+/// [`Zeroize::zeroize`] on them once they're no longer needed, as HD key
+/// derivation does for intermediate keys, `drk` does for the master and
+/// child keys of each derived secret, and note encryption does for its
+/// ephemeral secret. Secret material owned by non-`Copy` types is wiped on
+/// drop: `Mnemonic`, MuSig2 `SecretNonces`, and the FROST `SecretShare`,
+/// `KeyPackage`, `DkgSecretPackage`, `DkgRound2Package` and `SigningNonces`.
+///
+/// Equality is checked in constant time, see [`ConstantTimeEq`].
+/// `Debug` output is redacted.
+#[derive(Copy, Clone, Eq, SerialEncodable, SerialDecodable)]
 pub struct SecretKey(pallas::Base);
 
 impl SecretKey {
@@ -88,6 +113,30 @@ impl SecretKey {
     }
 }
 
+impl ConstantTimeEq for SecretKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl core::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("SecretKey(<redacted>)")
+    }
+}
+
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        zeroize_copy(&mut self.0);
+    }
+}
+
 impl From<pallas::Base> for SecretKey {
     fn from(x: pallas::Base) -> Self {
         Self(x)
@@ -99,14 +148,19 @@ impl FromStr for SecretKey {
 
     /// Tries to create a `SecretKey` object from a base58 encoded string.
     fn from_str(enc: &str) -> Result<Self, Self::Err> {
-        let decoded = bs58::decode(enc).into_vec()?;
+        let mut decoded = bs58::decode(enc).into_vec()?;
         if decoded.len() != 32 {
+            decoded.zeroize();
             return Err(Self::Err::IoError(
                 "Failed decoding SecretKey from bytes, len is not 32".to_string(),
             ))
         }
 
-        Self::from_bytes(decoded.try_into().unwrap())
+        let mut bytes: [u8; 32] = decoded.as_slice().try_into().unwrap();
+        decoded.zeroize();
+        let secret = Self::from_bytes(bytes);
+        bytes.zeroize();
+        secret
     }
 }
 
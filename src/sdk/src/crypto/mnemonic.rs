@@ -0,0 +1,279 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! BIP39 mnemonic seed phrases.
+//!
+//! A [`Mnemonic`] encodes 128 to 256 bits of entropy together with a
+//! checksum as a list of 12 to 24 words from the English BIP39 wordlist,
+//! which is far easier to back up by hand than raw key bytes. The phrase
+//! and an optional passphrase are stretched with PBKDF2-HMAC-SHA512 into
+//! a 64-byte seed, from which the HD master key is created.
+//!
+//! Passphrases are used as given, without Unicode normalization, so
+//! non-ASCII passphrases may not be portable to other BIP39 wallets.
+
+use core::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::Zeroize;
+
+use super::ExtendedSecretKey;
+use crate::error::ContractError;
+
+/// Number of PBKDF2 rounds used to stretch the phrase into a seed
+const PBKDF2_ROUNDS: u32 = 2048;
+
+lazy_static! {
+    /// The 2048 words of the English BIP39 wordlist
+    static ref WORDLIST: Vec<&'static str> = include_str!("wordlist/english.txt").lines().collect();
+}
+
+/// A BIP39 mnemonic phrase
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mnemonic {
+    entropy: Vec<u8>,
+}
+
+impl Mnemonic {
+    /// Generate a random mnemonic of `word_count` words, which has to be
+    /// one of 12, 15, 18, 21, or 24.
+    pub fn generate(
+        rng: &mut (impl CryptoRng + RngCore),
+        word_count: usize,
+    ) -> Result<Self, ContractError> {
+        if ![12, 15, 18, 21, 24].contains(&word_count) {
+            return Err(ContractError::IoError(format!("Invalid mnemonic word count {word_count}")))
+        }
+
+        let mut entropy = vec![0u8; word_count * 4 / 3];
+        rng.fill_bytes(&mut entropy);
+        let mnemonic = Self::from_entropy(&entropy);
+        entropy.zeroize();
+        mnemonic
+    }
+
+    /// Create a mnemonic encoding the given entropy, which has to be 16,
+    /// 20, 24, 28, or 32 bytes long.
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, ContractError> {
+        if ![16, 20, 24, 28, 32].contains(&entropy.len()) {
+            return Err(ContractError::IoError(format!(
+                "Invalid mnemonic entropy length {}",
+                entropy.len()
+            )))
+        }
+
+        Ok(Self { entropy: entropy.to_vec() })
+    }
+
+    /// Reference the entropy encoded by the mnemonic
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    /// Return the words of the mnemonic phrase
+    pub fn words(&self) -> Vec<&'static str> {
+        // The phrase is the entropy followed by the first ENT/32 bits of
+        // its SHA-256 hash, split into 11-bit indexes into the wordlist.
+        let checksum = Sha256::digest(&self.entropy);
+        let mut bits = self.entropy.clone();
+        bits.push(checksum[0]);
+
+        let word_count = self.entropy.len() * 3 / 4;
+        let words = (0..word_count)
+            .map(|i| {
+                let index = (0..11).fold(0usize, |acc, j| {
+                    let bit = i * 11 + j;
+                    (acc << 1) | ((bits[bit / 8] >> (7 - bit % 8)) & 1) as usize
+                });
+                WORDLIST[index]
+            })
+            .collect();
+
+        bits.zeroize();
+        words
+    }
+
+    /// Derive the 64-byte seed of the mnemonic, protected with an
+    /// optional `passphrase`. An empty passphrase is a valid one.
+    pub fn to_seed(&self, passphrase: &str) -> [u8; 64] {
+        let mut phrase = self.to_string();
+        let mut salt = format!("mnemonic{passphrase}");
+        let seed = pbkdf2_sha512(phrase.as_bytes(), salt.as_bytes(), PBKDF2_ROUNDS);
+        phrase.zeroize();
+        salt.zeroize();
+        seed
+    }
+
+    /// Create the HD master key from the seed of the mnemonic
+    pub fn to_master_key(&self, passphrase: &str) -> ExtendedSecretKey {
+        let mut seed = self.to_seed(passphrase);
+        let key = ExtendedSecretKey::from_seed(&seed);
+        seed.zeroize();
+        key
+    }
+}
+
+impl Drop for Mnemonic {
+    fn drop(&mut self) {
+        self.entropy.zeroize();
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = ContractError;
+
+    /// Parse a phrase of whitespace-separated words, verifying that every
+    /// word is in the wordlist and that the checksum matches.
+    fn from_str(phrase: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        if ![12, 15, 18, 21, 24].contains(&words.len()) {
+            return Err(ContractError::IoError(format!(
+                "Invalid mnemonic word count {}",
+                words.len()
+            )))
+        }
+
+        let mut bits = vec![0u8; (words.len() * 11 + 7) / 8];
+        for (i, word) in words.iter().enumerate() {
+            let lowercase = word.to_lowercase();
+            let Ok(index) = WORDLIST.binary_search(&lowercase.as_str()) else {
+                bits.zeroize();
+                return Err(ContractError::IoError(format!("Unknown mnemonic word {word}")))
+            };
+
+            for j in 0..11 {
+                if (index >> (10 - j)) & 1 == 1 {
+                    let bit = i * 11 + j;
+                    bits[bit / 8] |= 1 << (7 - bit % 8);
+                }
+            }
+        }
+
+        let entropy_len = words.len() * 4 / 3;
+        let checksum_bits = entropy_len / 4;
+        let checksum = Sha256::digest(&bits[..entropy_len]);
+        let valid = bits[entropy_len] >> (8 - checksum_bits) == checksum[0] >> (8 - checksum_bits);
+
+        let mnemonic = Self::from_entropy(&bits[..entropy_len]);
+        bits.zeroize();
+
+        if !valid {
+            return Err(ContractError::IoError("Invalid mnemonic checksum".to_string()))
+        }
+
+        mnemonic
+    }
+}
+
+impl core::fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.words().join(" "))
+    }
+}
+
+/// PBKDF2 with HMAC-SHA512, producing a single 64-byte block
+fn pbkdf2_sha512(password: &[u8], salt: &[u8], rounds: u32) -> [u8; 64] {
+    let prf = Hmac::<Sha512>::new_from_slice(password).expect("HMAC accepts keys of any length");
+
+    let mut mac = prf.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+    let mut block = mac.finalize().into_bytes();
+
+    let mut output = [0u8; 64];
+    output.copy_from_slice(&block);
+
+    for _ in 1..rounds {
+        let mut mac = prf.clone();
+        mac.update(&block);
+        block = mac.finalize().into_bytes();
+        output.iter_mut().zip(block.iter()).for_each(|(o, b)| *o ^= b);
+    }
+
+    block.as_mut_slice().zeroize();
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn bip39_vectors() {
+        assert_eq!(WORDLIST.len(), 2048);
+
+        // Reference vectors from the BIP39 specification
+        let vectors = [
+            (
+                [0x00; 16],
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            ),
+            (
+                [0x7f; 16],
+                "legal winner thank year wave sausage worth useful legal winner thank yellow",
+                "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+            ),
+        ];
+
+        for (entropy, phrase, seed) in vectors {
+            let mnemonic = Mnemonic::from_entropy(&entropy).unwrap();
+            assert_eq!(mnemonic.to_string(), phrase);
+            assert_eq!(Mnemonic::from_str(phrase).unwrap(), mnemonic);
+            assert_eq!(hex(&mnemonic.to_seed("TREZOR")), seed);
+        }
+
+        let mnemonic = Mnemonic::from_entropy(&[0x80; 32]).unwrap();
+        assert_eq!(
+            mnemonic.to_string(),
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless"
+        );
+    }
+
+    #[test]
+    fn mnemonic_roundtrip() {
+        for word_count in [12, 15, 18, 21, 24] {
+            let mnemonic = Mnemonic::generate(&mut OsRng, word_count).unwrap();
+            assert_eq!(mnemonic.words().len(), word_count);
+            assert_eq!(Mnemonic::from_str(&mnemonic.to_string()).unwrap(), mnemonic);
+            assert_eq!(
+                mnemonic.to_master_key(""),
+                ExtendedSecretKey::from_seed(&mnemonic.to_seed(""))
+            );
+            assert_ne!(mnemonic.to_seed(""), mnemonic.to_seed("passphrase"));
+        }
+
+        assert!(Mnemonic::generate(&mut OsRng, 13).is_err());
+        assert!(Mnemonic::from_entropy(&[0; 15]).is_err());
+
+        // Wrong checksum, unknown word, and wrong length
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert!(Mnemonic::from_str(phrase).is_err());
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon darkfi";
+        assert!(Mnemonic::from_str(phrase).is_err());
+        assert!(Mnemonic::from_str("abandon about").is_err());
+    }
+}
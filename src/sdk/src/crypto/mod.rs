@@ -36,12 +36,18 @@ pub use keypair::{Keypair, PublicKey, SecretKey};
 
 /// Contract ID definitions and methods
 pub mod contract_id;
-pub use contract_id::{ContractId, DAO_CONTRACT_ID, DEPLOYOOOR_CONTRACT_ID, MONEY_CONTRACT_ID};
+pub use contract_id::{
+    ContractId, DAO_CONTRACT_ID, DEPLOYOOOR_CONTRACT_ID, MONEY_CONTRACT_ID, REGISTRY_CONTRACT_ID,
+};
 
 /// Function ID definitions and methods
 pub mod func_ref;
 pub use func_ref::{FuncId, FuncRef};
 
+/// Spend hook declaration and validation helpers
+pub mod spend_hook;
+pub use spend_hook::{SpendHook, SpendHookRegistry};
+
 /// Merkle node definitions
 pub mod merkle_node;
 pub use merkle_node::{MerkleNode, MerkleTree};
@@ -49,13 +55,45 @@ pub use merkle_node::{MerkleNode, MerkleTree};
 /// Note encryption
 pub mod note;
 
+/// Incoming viewing keys for note decryption
+pub mod viewing_key;
+pub use viewing_key::ViewingKey;
+
+/// Diversified addresses derived from a single spending key
+pub mod diversified;
+pub use diversified::{diversify, DiversifiedAddress};
+
+/// Hierarchical deterministic key derivation
+pub mod hd;
+pub use hd::{DerivationPath, ExtendedSecretKey};
+
+/// Versioned, checksummed address encoding
+pub mod address;
+pub use address::{Addressable, Network};
+
+/// BIP39 mnemonic seed phrases
+pub mod mnemonic;
+pub use mnemonic::Mnemonic;
+
+/// Domain separation tags for Poseidon-based derivations
+pub mod domains;
+
 /// Pedersen commitment utilities
 pub mod pedersen;
-pub use pedersen::{pedersen_commitment_base, pedersen_commitment_u64};
+pub use pedersen::{pedersen_commitment_base, pedersen_commitment_u64, ValueOpeningProof};
+
+/// Value commitment convenience API
+pub mod commitment;
 
 /// Schnorr signature traits
 pub mod schnorr;
 
+/// FROST threshold Schnorr signatures
+pub mod frost;
+
+/// MuSig2 Schnorr multi-signatures
+pub mod musig;
+
 /// MiMC VDF
 pub mod mimc_vdf;
 
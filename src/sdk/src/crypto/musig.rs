@@ -0,0 +1,381 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! MuSig2: Simple Two-Round Schnorr Multi-Signatures
+//! <https://eprint.iacr.org/2020/1261>
+//!
+//! A set of `n` signers, each holding its own [`SecretKey`], aggregates
+//! their public keys into a single [`PublicKey`]. All `n` of them can then
+//! jointly produce a [`Signature`] over a message in two rounds: first
+//! every signer publishes a pair of nonce commitments, then a partial
+//! signature. The aggregated signature is an ordinary Schnorr signature
+//! which verifies against the aggregated key through [`SchnorrPublic`],
+//! so on-chain it is indistinguishable from a single-signer one.
+//!
+//! Unlike [`frost`](super::frost), no dealer is needed and nobody ever
+//! knows the aggregated secret, but every signer has to take part in
+//! each signature. The same caveat applies to coins: spending one still
+//! requires proving knowledge of its secret inside the `Burn_V1`
+//! circuit, so the signers can co-sign transactions but cannot own coins
+//! under the aggregated key.
+//!
+//! [`SchnorrPublic`]: super::schnorr::SchnorrPublic
+#![allow(non_snake_case)]
+
+#[cfg(feature = "async")]
+use darkfi_serial::async_trait;
+use darkfi_serial::{SerialDecodable, SerialEncodable};
+use halo2_gadgets::ecc::chip::FixedPoint;
+use pasta_curves::{
+    group::{ff::Field, Group, GroupEncoding},
+    pallas,
+};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{
+    constants::{NullifierK, DRK_SCHNORR_DOMAIN},
+    schnorr::Signature,
+    util::{fp_mod_fv, hash_to_scalar, zeroize_copy},
+    PublicKey, SecretKey,
+};
+use crate::error::{MusigError, MusigResult};
+
+/// Prefix domain used for deriving the key aggregation coefficients.
+/// BLAKE2b personalizations are limited to 16 bytes.
+const MUSIG_KEYAGG_DOMAIN: &[u8] = b"DarkFi:MuSig2Agg";
+/// Prefix domain used for deriving the nonce binding factor
+const MUSIG_NONCE_DOMAIN: &[u8] = b"DarkFi:MuSig2Non";
+
+/// The signer keys and their aggregated public key
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct KeyAggContext {
+    /// Signer keys, sorted by their encoding
+    pubkeys: Vec<PublicKey>,
+    /// Aggregation coefficient of each signer key
+    coefficients: Vec<pallas::Scalar>,
+    aggregate: PublicKey,
+}
+
+impl KeyAggContext {
+    /// Aggregate the given signer keys. The keys are sorted first, so
+    /// every signer gets the same result regardless of their order.
+    pub fn new(mut pubkeys: Vec<PublicKey>) -> MusigResult<Self> {
+        if pubkeys.is_empty() {
+            return Err(MusigError::NoSigners)
+        }
+
+        pubkeys.sort_by_key(|p| p.to_bytes());
+        for i in 1..pubkeys.len() {
+            if pubkeys[i] == pubkeys[i - 1] {
+                return Err(MusigError::DuplicateSigner(i))
+            }
+        }
+
+        // Each key is weighted with a coefficient bound to the whole
+        // key set, which prevents rogue key attacks.
+        let mut encoded = vec![];
+        for p in &pubkeys {
+            encoded.extend_from_slice(&p.to_bytes());
+        }
+
+        let coefficients: Vec<pallas::Scalar> = pubkeys
+            .iter()
+            .map(|p| hash_to_scalar(MUSIG_KEYAGG_DOMAIN, &[&encoded, &p.to_bytes()]))
+            .collect();
+
+        let mut X = pallas::Point::identity();
+        for (p, a) in pubkeys.iter().zip(&coefficients) {
+            X += p.inner() * a;
+        }
+
+        let Ok(aggregate) = PublicKey::try_from(X) else { return Err(MusigError::IdentityPoint) };
+
+        Ok(Self { pubkeys, coefficients, aggregate })
+    }
+
+    /// The aggregated public key that signatures verify against
+    pub fn aggregate_key(&self) -> PublicKey {
+        self.aggregate
+    }
+
+    /// The signer keys, sorted by their encoding
+    pub fn pubkeys(&self) -> &[PublicKey] {
+        &self.pubkeys
+    }
+
+    fn position(&self, pubkey: &PublicKey) -> MusigResult<usize> {
+        self.pubkeys.iter().position(|p| p == pubkey).ok_or(MusigError::UnknownSigner)
+    }
+}
+
+/// Round one secret nonces. These must be used for exactly one signature
+/// and are consumed by [`sign`].
+pub struct SecretNonces {
+    k1: pallas::Scalar,
+    k2: pallas::Scalar,
+    public: PublicNonces,
+}
+
+impl Drop for SecretNonces {
+    fn drop(&mut self) {
+        zeroize_copy(&mut self.k1);
+        zeroize_copy(&mut self.k2);
+    }
+}
+
+/// Round one public nonces, sent to every other signer
+#[derive(Copy, Clone, Debug, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct PublicNonces {
+    signer: PublicKey,
+    R1: pallas::Point,
+    R2: pallas::Point,
+}
+
+impl PublicNonces {
+    /// Public key of the signer these nonces belong to
+    pub fn signer(&self) -> PublicKey {
+        self.signer
+    }
+}
+
+/// Round one: generate fresh nonces and their public commitments.
+pub fn commit(
+    secret: &SecretKey,
+    rng: &mut (impl CryptoRng + RngCore),
+) -> (SecretNonces, PublicNonces) {
+    let k1 = pallas::Scalar::random(&mut *rng);
+    let k2 = pallas::Scalar::random(&mut *rng);
+
+    let public = PublicNonces {
+        signer: PublicKey::from_secret(*secret),
+        R1: NullifierK.generator() * k1,
+        R2: NullifierK.generator() * k2,
+    };
+
+    (SecretNonces { k1, k2, public }, public)
+}
+
+/// The message and the public nonces of every signer, from which all of
+/// them derive the same signing state in round two.
+#[derive(Clone, Debug, SerialEncodable, SerialDecodable)]
+pub struct SigningSession {
+    /// Public nonces, in the order of the key set
+    nonces: Vec<PublicNonces>,
+    message: Vec<u8>,
+    /// Nonce binding factor
+    b: pallas::Scalar,
+    /// Aggregated nonce
+    R: pallas::Point,
+    challenge: pallas::Scalar,
+}
+
+impl SigningSession {
+    /// Create a new `SigningSession`, requiring the nonces of every signer
+    /// of the key set.
+    pub fn new(ctx: &KeyAggContext, nonces: &[PublicNonces], message: &[u8]) -> MusigResult<Self> {
+        let mut ordered = Vec::with_capacity(ctx.pubkeys.len());
+        for (i, pubkey) in ctx.pubkeys.iter().enumerate() {
+            let Some(n) = nonces.iter().find(|n| n.signer == *pubkey) else {
+                return Err(MusigError::MissingNonces(i))
+            };
+            ordered.push(*n);
+        }
+
+        let mut R1 = pallas::Point::identity();
+        let mut R2 = pallas::Point::identity();
+        for n in &ordered {
+            R1 += n.R1;
+            R2 += n.R2;
+        }
+
+        let aggregate_bytes = ctx.aggregate.to_bytes();
+        let b = hash_to_scalar(
+            MUSIG_NONCE_DOMAIN,
+            &[&aggregate_bytes, &R1.to_bytes(), &R2.to_bytes(), message],
+        );
+
+        let R = R1 + R2 * b;
+        if bool::from(R.is_identity()) {
+            return Err(MusigError::IdentityPoint)
+        }
+
+        // Same transcript as SchnorrSecret::sign, so the aggregated
+        // signature verifies as a regular one.
+        let commit_bytes = R.to_bytes();
+        let transcript = &[&commit_bytes[..], &aggregate_bytes[..], message];
+        let challenge = hash_to_scalar(DRK_SCHNORR_DOMAIN, transcript);
+
+        Ok(Self { nonces: ordered, message: message.to_vec(), b, R, challenge })
+    }
+
+    /// The message being signed
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+}
+
+/// A signer's round two response
+#[derive(Copy, Clone, Debug, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct PartialSignature {
+    signer: PublicKey,
+    response: pallas::Scalar,
+}
+
+impl PartialSignature {
+    /// Public key of the signer that produced this partial signature
+    pub fn signer(&self) -> PublicKey {
+        self.signer
+    }
+}
+
+/// Round two: produce this signer's partial signature.
+pub fn sign(
+    ctx: &KeyAggContext,
+    session: &SigningSession,
+    nonces: SecretNonces,
+    secret: &SecretKey,
+) -> MusigResult<PartialSignature> {
+    let signer = PublicKey::from_secret(*secret);
+    let i = ctx.position(&signer)?;
+
+    // Make sure the session was built with our nonces unmodified
+    if session.nonces[i] != nonces.public {
+        return Err(MusigError::MissingNonces(i))
+    }
+
+    let mut x = fp_mod_fv(secret.inner());
+    let response = nonces.k1 + nonces.k2 * session.b + session.challenge * ctx.coefficients[i] * x;
+    zeroize_copy(&mut x);
+
+    Ok(PartialSignature { signer, response })
+}
+
+/// Verify the partial signatures and combine them into a [`Signature`]
+/// for the aggregated public key.
+pub fn aggregate(
+    ctx: &KeyAggContext,
+    session: &SigningSession,
+    partials: &[PartialSignature],
+) -> MusigResult<Signature> {
+    let mut response = pallas::Scalar::ZERO;
+    for (i, pubkey) in ctx.pubkeys.iter().enumerate() {
+        let Some(partial) = partials.iter().find(|s| s.signer == *pubkey) else {
+            return Err(MusigError::InvalidPartialSignature(i))
+        };
+
+        // Check each partial signature so a misbehaving signer can be
+        // identified
+        let n = &session.nonces[i];
+        let expected =
+            n.R1 + n.R2 * session.b + pubkey.inner() * (session.challenge * ctx.coefficients[i]);
+        if NullifierK.generator() * partial.response != expected {
+            return Err(MusigError::InvalidPartialSignature(i))
+        }
+
+        response += partial.response;
+    }
+
+    Ok(Signature::from_parts(session.R, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::schnorr::SchnorrPublic;
+    use darkfi_serial::{deserialize, serialize};
+    use rand::rngs::OsRng;
+
+    fn sign_with(
+        ctx: &KeyAggContext,
+        secrets: &[SecretKey],
+        message: &[u8],
+    ) -> MusigResult<Signature> {
+        let mut secret_nonces = vec![];
+        let mut public_nonces = vec![];
+        for secret in secrets {
+            let (s, p) = commit(secret, &mut OsRng);
+            secret_nonces.push(s);
+            public_nonces.push(p);
+        }
+
+        let session = SigningSession::new(ctx, &public_nonces, message)?;
+
+        let mut partials = vec![];
+        for (secret, n) in secrets.iter().zip(secret_nonces) {
+            partials.push(sign(ctx, &session, n, secret)?);
+        }
+
+        aggregate(ctx, &session, &partials)
+    }
+
+    #[test]
+    fn test_musig_signature() {
+        let secrets: Vec<SecretKey> = (0..3).map(|_| SecretKey::random(&mut OsRng)).collect();
+        let pubkeys: Vec<PublicKey> = secrets.iter().map(|s| PublicKey::from_secret(*s)).collect();
+
+        // The key order doesn't matter
+        let ctx = KeyAggContext::new(pubkeys.clone()).unwrap();
+        let reversed = KeyAggContext::new(pubkeys.iter().rev().copied().collect()).unwrap();
+        assert_eq!(ctx.aggregate_key(), reversed.aggregate_key());
+
+        let message: &[u8] = b"aaaahhhh we're all signiiinngg";
+        let signature = sign_with(&ctx, &secrets, message).unwrap();
+        assert!(ctx.aggregate_key().verify(message, &signature));
+        assert!(!ctx.aggregate_key().verify(b"something else", &signature));
+
+        let de = deserialize(&serialize(&signature)).unwrap();
+        assert!(ctx.aggregate_key().verify(message, &de));
+
+        // Every signer has to take part
+        assert!(sign_with(&ctx, &secrets[..2], message).is_err());
+
+        // A single signer works too
+        let ctx = KeyAggContext::new(vec![pubkeys[0]]).unwrap();
+        let signature = sign_with(&ctx, &secrets[..1], message).unwrap();
+        assert!(ctx.aggregate_key().verify(message, &signature));
+    }
+
+    #[test]
+    fn test_musig_invalid() {
+        let secrets: Vec<SecretKey> = (0..2).map(|_| SecretKey::random(&mut OsRng)).collect();
+        let pubkeys: Vec<PublicKey> = secrets.iter().map(|s| PublicKey::from_secret(*s)).collect();
+
+        assert!(KeyAggContext::new(vec![]).is_err());
+        assert!(KeyAggContext::new(vec![pubkeys[0], pubkeys[0]]).is_err());
+
+        let ctx = KeyAggContext::new(pubkeys).unwrap();
+        let (n0, p0) = commit(&secrets[0], &mut OsRng);
+        let (n1, p1) = commit(&secrets[1], &mut OsRng);
+        let session = SigningSession::new(&ctx, &[p0, p1], b"hello").unwrap();
+
+        // An outsider can't sign
+        let (n2, _) = commit(&SecretKey::random(&mut OsRng), &mut OsRng);
+        assert!(sign(&ctx, &session, n2, &SecretKey::random(&mut OsRng)).is_err());
+
+        // A tampered partial signature is caught by the aggregator
+        let s0 = sign(&ctx, &session, n0, &secrets[0]).unwrap();
+        let mut s1 = sign(&ctx, &session, n1, &secrets[1]).unwrap();
+        s1.response += pallas::Scalar::ONE;
+
+        let i = ctx.position(&s1.signer()).unwrap();
+        match aggregate(&ctx, &session, &[s0, s1]) {
+            Err(MusigError::InvalidPartialSignature(j)) => assert_eq!(i, j),
+            _ => panic!("Tampered partial signature was accepted"),
+        }
+    }
+}
@@ -16,68 +16,261 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit};
+use blake2b_simd::Hash as Blake2bHash;
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit, XChaCha20Poly1305};
 use darkfi_serial::{Decodable, Encodable, SerialDecodable, SerialEncodable};
-use pasta_curves::{group::ff::Field, pallas};
+use pasta_curves::{
+    group::{ff::Field, prime::PrimeCurveAffine, Curve, Group, GroupEncoding, Wnaf},
+    pallas,
+};
 use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroize;
 
 #[cfg(feature = "async")]
 use darkfi_serial::async_trait;
 
-use super::{diffie_hellman, poseidon_hash, util::fp_mod_fv, PublicKey, SecretKey};
+use super::{
+    diffie_hellman, poseidon_hash,
+    util::{fp_mod_fv, zeroize_copy},
+    PublicKey, SecretKey,
+};
 use crate::error::ContractError;
 
 /// AEAD tag length in bytes
 pub const AEAD_TAG_SIZE: usize = 16;
 
-/// An encrypted note using Diffie-Hellman and ChaCha20Poly1305
+/// XChaCha20Poly1305 nonce length in bytes
+pub const XCHACHA_NONCE_SIZE: usize = 24;
+
+/// Algorithm used to encrypt an [`AeadEncryptedNote`].
+///
+/// It is serialized in front of the note as a version tag, so notes
+/// encrypted with an older algorithm remain decryptable once new ones
+/// are added. Discriminants must never be reused.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, SerialEncodable, SerialDecodable)]
+pub enum AeadAlgorithm {
+    /// Sapling KDF and ChaCha20Poly1305 with a zero nonce. This is fine
+    /// since the ephemeral key, and therefore the derived key, is fresh
+    /// for every note.
+    #[default]
+    ChaCha20Poly1305 = 0x00,
+    /// Sapling KDF with its own personalization and XChaCha20Poly1305,
+    /// with a random nonce prepended to the ciphertext.
+    XChaCha20Poly1305 = 0x01,
+}
+
+impl AeadAlgorithm {
+    /// Derive the symmetric key from the encoded shared secret and
+    /// ephemeral public key
+    fn kdf(&self, dhsecret: &[u8; 32], epk: &[u8; 32]) -> Blake2bHash {
+        match self {
+            Self::ChaCha20Poly1305 => diffie_hellman::kdf_sapling_encoded(dhsecret, epk),
+            Self::XChaCha20Poly1305 => diffie_hellman::kdf_personalized(
+                diffie_hellman::KDF_XCHACHA_PERSONALIZATION,
+                dhsecret,
+                epk,
+            ),
+        }
+    }
+
+    /// Encrypt the given plaintext with the derived key
+    fn seal(
+        &self,
+        key: &Blake2bHash,
+        plaintext: &[u8],
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> Vec<u8> {
+        match self {
+            Self::ChaCha20Poly1305 => {
+                // The zero padding is part of the original ciphertext format,
+                // so it is kept for compatibility.
+                let mut ciphertext = vec![0_u8; plaintext.len() + AEAD_TAG_SIZE];
+                ciphertext[..plaintext.len()].copy_from_slice(plaintext);
+
+                ChaCha20Poly1305::new(key.as_ref().into())
+                    .encrypt_in_place([0u8; 12][..].into(), &[], &mut ciphertext)
+                    .unwrap();
+
+                ciphertext
+            }
+
+            Self::XChaCha20Poly1305 => {
+                let mut nonce = [0_u8; XCHACHA_NONCE_SIZE];
+                rng.fill_bytes(&mut nonce);
+
+                let mut buf = Vec::with_capacity(plaintext.len() + AEAD_TAG_SIZE);
+                buf.extend_from_slice(plaintext);
+
+                XChaCha20Poly1305::new(key.as_ref().into())
+                    .encrypt_in_place(nonce[..].into(), &[], &mut buf)
+                    .unwrap();
+
+                let mut ciphertext = nonce.to_vec();
+                ciphertext.append(&mut buf);
+                ciphertext
+            }
+        }
+    }
+
+    /// Decrypt the given ciphertext with the derived key
+    fn open(&self, key: &Blake2bHash, ciphertext: &[u8]) -> Result<Vec<u8>, ContractError> {
+        let (cipher_ret, plaintext) = match self {
+            Self::ChaCha20Poly1305 => {
+                let mut plaintext = ciphertext.to_vec();
+                let ret = ChaCha20Poly1305::new(key.as_ref().into()).decrypt_in_place(
+                    [0u8; 12][..].into(),
+                    &[],
+                    &mut plaintext,
+                );
+                (ret, plaintext)
+            }
+
+            Self::XChaCha20Poly1305 => {
+                if ciphertext.len() < XCHACHA_NONCE_SIZE {
+                    return Err(ContractError::IoError("Note ciphertext too short".to_string()))
+                }
+
+                let (nonce, ciphertext) = ciphertext.split_at(XCHACHA_NONCE_SIZE);
+                let mut plaintext = ciphertext.to_vec();
+                let ret = XChaCha20Poly1305::new(key.as_ref().into()).decrypt_in_place(
+                    nonce.into(),
+                    &[],
+                    &mut plaintext,
+                );
+                (ret, plaintext)
+            }
+        };
+
+        match cipher_ret {
+            Ok(()) => Ok(plaintext),
+            Err(e) => Err(ContractError::IoError(format!("Note decrypt failed: {}", e))),
+        }
+    }
+}
+
+/// An encrypted note using Diffie-Hellman and an [`AeadAlgorithm`]
 #[derive(Debug, Clone, PartialEq, Eq, SerialEncodable, SerialDecodable)]
 pub struct AeadEncryptedNote {
+    pub algorithm: AeadAlgorithm,
     pub ciphertext: Vec<u8>,
     pub ephem_public: PublicKey,
 }
 
 impl AeadEncryptedNote {
+    /// Encrypt the note to the given public key using the default [`AeadAlgorithm`]
     pub fn encrypt(
         note: &impl Encodable,
         public: &PublicKey,
         rng: &mut (impl CryptoRng + RngCore),
     ) -> Result<Self, ContractError> {
-        let ephem_secret = SecretKey::random(rng);
+        Self::encrypt_with(AeadAlgorithm::default(), note, public, rng)
+    }
+
+    /// Encrypt the note to the given public key using the given [`AeadAlgorithm`]
+    pub fn encrypt_with(
+        algorithm: AeadAlgorithm,
+        note: &impl Encodable,
+        public: &PublicKey,
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> Result<Self, ContractError> {
+        let mut ephem_secret = SecretKey::random(rng);
         let ephem_public = PublicKey::from_secret(ephem_secret);
-        let shared_secret = diffie_hellman::sapling_ka_agree(&ephem_secret, public)?;
-        let key = diffie_hellman::kdf_sapling(&shared_secret, &ephem_public);
+        let shared_secret = diffie_hellman::sapling_ka_agree(&ephem_secret, public);
+        ephem_secret.zeroize();
+        let key =
+            algorithm.kdf(&shared_secret?.inner().to_bytes(), &ephem_public.inner().to_bytes());
 
         let mut input = Vec::new();
         note.encode(&mut input)?;
-        let input_len = input.len();
-
-        let mut ciphertext = vec![0_u8; input_len + AEAD_TAG_SIZE];
-        ciphertext[..input_len].copy_from_slice(&input);
+        let ciphertext = algorithm.seal(&key, &input, rng);
+        input.zeroize();
 
-        ChaCha20Poly1305::new(key.as_ref().into())
-            .encrypt_in_place([0u8; 12][..].into(), &[], &mut ciphertext)
-            .unwrap();
-
-        Ok(Self { ciphertext, ephem_public })
+        Ok(Self { algorithm, ciphertext, ephem_public })
     }
 
     pub fn decrypt<D: Decodable>(&self, secret: &SecretKey) -> Result<D, ContractError> {
         let shared_secret = diffie_hellman::sapling_ka_agree(secret, &self.ephem_public)?;
-        let key = diffie_hellman::kdf_sapling(&shared_secret, &self.ephem_public);
-
-        let ct_len = self.ciphertext.len();
-        let mut plaintext = vec![0_u8; ct_len];
-        plaintext.copy_from_slice(&self.ciphertext);
-
-        match ChaCha20Poly1305::new(key.as_ref().into()).decrypt_in_place(
-            [0u8; 12][..].into(),
-            &[],
-            &mut plaintext,
-        ) {
-            Ok(()) => Ok(D::decode(&plaintext[..ct_len - AEAD_TAG_SIZE])?),
-            Err(e) => Err(ContractError::IoError(format!("Note decrypt failed: {}", e))),
+        let key = self
+            .algorithm
+            .kdf(&shared_secret.inner().to_bytes(), &self.ephem_public.inner().to_bytes());
+        self.decrypt_with_key(&key)
+    }
+
+    /// Attempt to decrypt each of the given notes with each of the given
+    /// secret keys, spreading the notes over the available cores.
+    ///
+    /// Returns, for each note, the index of the first secret key able to
+    /// decrypt it along with its plaintext, or `None` if none could.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn trial_decrypt<D: Decodable + Send>(
+        notes: &[Self],
+        secrets: &[SecretKey],
+    ) -> Vec<Option<(usize, D)>> {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = notes.len().div_ceil(threads).max(1);
+
+        std::thread::scope(|s| {
+            let handles: Vec<_> = notes
+                .chunks(chunk_size)
+                .map(|chunk| s.spawn(move || Self::trial_decrypt_batch(chunk, secrets)))
+                .collect();
+
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        })
+    }
+
+    /// Single threaded version of [`AeadEncryptedNote::trial_decrypt`].
+    /// The windowed form of each secret key is computed once for the whole
+    /// batch, and the resulting points are normalized with a single field
+    /// inversion, instead of one per note and key.
+    pub fn trial_decrypt_batch<D: Decodable>(
+        notes: &[Self],
+        secrets: &[SecretKey],
+    ) -> Vec<Option<(usize, D)>> {
+        let mut decrypted: Vec<Option<(usize, D)>> = notes.iter().map(|_| None).collect();
+        if notes.is_empty() {
+            return decrypted
         }
+
+        let ephem_publics: Vec<pallas::Point> =
+            notes.iter().map(|n| n.ephem_public.inner()).collect();
+        let mut ephem_affines = vec![pallas::Affine::identity(); notes.len()];
+        pallas::Point::batch_normalize(&ephem_publics, &mut ephem_affines);
+
+        let mut shared = vec![pallas::Point::identity(); notes.len()];
+        let mut shared_affines = vec![pallas::Affine::identity(); notes.len()];
+        for (secret_idx, secret) in secrets.iter().enumerate() {
+            // Same constant time windowed multiplication as `sapling_ka_agree`
+            let mut wnaf_table = Wnaf::new();
+            let mut wnaf = wnaf_table.scalar(&fp_mod_fv(secret.inner()));
+            for (point, ephem_public) in shared.iter_mut().zip(&ephem_publics) {
+                *point = wnaf.base(*ephem_public);
+            }
+            pallas::Point::batch_normalize(&shared, &mut shared_affines);
+
+            for (i, note) in notes.iter().enumerate() {
+                // The identity is not a valid shared secret
+                if decrypted[i].is_some() || bool::from(shared_affines[i].is_identity()) {
+                    continue
+                }
+
+                let key =
+                    note.algorithm.kdf(&shared_affines[i].to_bytes(), &ephem_affines[i].to_bytes());
+                if let Ok(plaintext) = note.decrypt_with_key(&key) {
+                    decrypted[i] = Some((secret_idx, plaintext));
+                }
+            }
+        }
+
+        decrypted
+    }
+
+    /// Decrypt the note using the key derived from its shared secret
+    fn decrypt_with_key<D: Decodable>(&self, key: &Blake2bHash) -> Result<D, ContractError> {
+        let mut plaintext = self.algorithm.open(key, &self.ciphertext)?;
+        let ret = D::decode(&plaintext[..]).map_err(ContractError::from);
+        plaintext.zeroize();
+        ret
     }
 }
 
@@ -134,9 +327,19 @@ impl<const N: usize> ElGamalEncryptedNote<N> {
     /// on the plaintexts.
     pub fn decrypt_unsafe(&self, secret: &SecretKey) -> Result<[pallas::Base; N], ContractError> {
         // Derive shared secret using DH
-        let (ss_x, ss_y) =
-            PublicKey::try_from(self.ephem_public.inner() * fp_mod_fv(secret.inner()))?.xy();
-        let shared_secret = poseidon_hash([ss_x, ss_y]);
+        let shared_point =
+            PublicKey::try_from(self.ephem_public.inner() * fp_mod_fv(secret.inner()))?;
+        Ok(self.decrypt_unsafe_shared(&shared_point))
+    }
+
+    /// Decrypt the `ElGamalEncryptedNote` using the Diffie-Hellman point
+    /// `ephem_public * secret`, for when the secret is shared by a group
+    /// that computes the point jointly.
+    ///
+    /// Note that this does not do any message authentication.
+    pub fn decrypt_unsafe_shared(&self, shared_point: &PublicKey) -> [pallas::Base; N] {
+        let (ss_x, ss_y) = shared_point.xy();
+        let mut shared_secret = poseidon_hash([ss_x, ss_y]);
 
         let mut blinds = [pallas::Base::ZERO; N];
         for (i, item) in blinds.iter_mut().enumerate().take(N) {
@@ -148,7 +351,10 @@ impl<const N: usize> ElGamalEncryptedNote<N> {
             decrypted_values[i] = self.encrypted_values[i] - blinds[i];
         }
 
-        Ok(decrypted_values)
+        zeroize_copy(&mut shared_secret);
+        blinds.iter_mut().for_each(zeroize_copy);
+
+        decrypted_values
     }
 }
 
@@ -172,6 +378,59 @@ mod tests {
         assert_eq!(plaintext, plaintext2);
     }
 
+    #[test]
+    fn test_aead_note_xchacha() {
+        let plaintext = "gm world";
+        let keypair = Keypair::random(&mut OsRng);
+
+        let encrypted_note = AeadEncryptedNote::encrypt_with(
+            AeadAlgorithm::XChaCha20Poly1305,
+            &plaintext,
+            &keypair.public,
+            &mut OsRng,
+        )
+        .unwrap();
+
+        // The algorithm tag survives serialization
+        let encrypted_note: AeadEncryptedNote =
+            darkfi_serial::deserialize(&darkfi_serial::serialize(&encrypted_note)).unwrap();
+        assert_eq!(encrypted_note.algorithm, AeadAlgorithm::XChaCha20Poly1305);
+
+        let plaintext2: String = encrypted_note.decrypt(&keypair.secret).unwrap();
+        assert_eq!(plaintext, plaintext2);
+
+        // Keys are domain separated, so swapping the tag breaks decryption
+        let mut swapped = encrypted_note.clone();
+        swapped.algorithm = AeadAlgorithm::ChaCha20Poly1305;
+        assert!(swapped.decrypt::<String>(&keypair.secret).is_err());
+    }
+
+    #[test]
+    fn test_aead_trial_decrypt() {
+        let keypairs: Vec<_> = (0..3).map(|_| Keypair::random(&mut OsRng)).collect();
+        let stranger = Keypair::random(&mut OsRng);
+
+        let mut notes = vec![];
+        for i in 0..20 {
+            let public = if i % 4 == 3 { stranger.public } else { keypairs[i % 4].public };
+            notes.push(
+                AeadEncryptedNote::encrypt(&format!("note {i}"), &public, &mut OsRng).unwrap(),
+            );
+        }
+
+        let secrets: Vec<_> = keypairs.iter().map(|k| k.secret).collect();
+        let decrypted = AeadEncryptedNote::trial_decrypt::<String>(&notes, &secrets);
+        assert_eq!(decrypted, AeadEncryptedNote::trial_decrypt_batch::<String>(&notes, &secrets));
+
+        for (i, result) in decrypted.into_iter().enumerate() {
+            if i % 4 == 3 {
+                assert!(result.is_none());
+            } else {
+                assert_eq!(result, Some((i % 4, format!("note {i}"))));
+            }
+        }
+    }
+
     #[test]
     fn test_elgamal_note() {
         const N_MSGS: usize = 10;
@@ -16,26 +16,28 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+#[cfg(feature = "async")]
+use darkfi_serial::async_trait;
+use darkfi_serial::{SerialDecodable, SerialEncodable};
 use halo2_gadgets::ecc::chip::FixedPoint;
-use pasta_curves::{arithmetic::CurveExt, pallas};
+use pasta_curves::{
+    group::{ff::Field, GroupEncoding},
+    pallas,
+};
+use rand_core::{CryptoRng, RngCore};
 
 use super::{
     blind::ScalarBlind,
-    constants::{
-        fixed_bases::{
-            VALUE_COMMITMENT_PERSONALIZATION, VALUE_COMMITMENT_R_BYTES, VALUE_COMMITMENT_V_BYTES,
-        },
-        NullifierK,
-    },
-    util::fp_mod_fv,
+    commitment::{VALUE_COMMIT_RANDOM, VALUE_COMMIT_VALUE},
+    constants::{NullifierK, DRK_VALUE_OPENING_DOMAIN},
+    util::{fp_mod_fv, hash_to_scalar},
 };
 
 /// Pedersen commitment for a full-width base field element.
 #[allow(non_snake_case)]
 pub fn pedersen_commitment_base(value: pallas::Base, blind: ScalarBlind) -> pallas::Point {
-    let hasher = pallas::Point::hash_to_curve(VALUE_COMMITMENT_PERSONALIZATION);
     let V = NullifierK.generator();
-    let R = hasher(&VALUE_COMMITMENT_R_BYTES);
+    let R = *VALUE_COMMIT_RANDOM;
 
     V * fp_mod_fv(value) + R * blind.inner()
 }
@@ -43,16 +45,61 @@ pub fn pedersen_commitment_base(value: pallas::Base, blind: ScalarBlind) -> pall
 /// Pedersen commitment for a 64-bit value, in the base field.
 #[allow(non_snake_case)]
 pub fn pedersen_commitment_u64(value: u64, blind: ScalarBlind) -> pallas::Point {
-    let hasher = pallas::Point::hash_to_curve(VALUE_COMMITMENT_PERSONALIZATION);
-    let V = hasher(&VALUE_COMMITMENT_V_BYTES);
-    let R = hasher(&VALUE_COMMITMENT_R_BYTES);
+    let V = *VALUE_COMMIT_VALUE;
+    let R = *VALUE_COMMIT_RANDOM;
 
     V * fp_mod_fv(pallas::Base::from(value)) + R * blind.inner()
 }
 
+/// Proof that a commitment made with [`pedersen_commitment_u64`] commits
+/// to a given public value, without revealing its blind. This is a Schnorr
+/// proof of knowledge of the blind, over the commitment's random base.
+///
+/// Since commitments are additive, the sum of many commitments can be
+/// opened to the sum of their values without revealing any single one.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct ValueOpeningProof {
+    commit: pallas::Point,
+    response: pallas::Scalar,
+}
+
+impl ValueOpeningProof {
+    /// Prove that the commitment made with `blind` commits to `value`
+    #[allow(non_snake_case)]
+    pub fn create(value: u64, blind: ScalarBlind, rng: &mut (impl CryptoRng + RngCore)) -> Self {
+        let R = *VALUE_COMMIT_RANDOM;
+
+        let mask = pallas::Scalar::random(rng);
+        let commit = R * mask;
+
+        let commitment = pedersen_commitment_u64(value, blind);
+        let challenge = Self::challenge(&commit, &commitment, value);
+        Self { commit, response: mask + challenge * blind.inner() }
+    }
+
+    /// Verify that `commitment` commits to `value`
+    #[allow(non_snake_case)]
+    pub fn verify(&self, commitment: &pallas::Point, value: u64) -> bool {
+        let V = *VALUE_COMMIT_VALUE;
+        let R = *VALUE_COMMIT_RANDOM;
+
+        // What remains once the value is removed must be a multiple of R
+        let blind_commit = *commitment - V * fp_mod_fv(pallas::Base::from(value));
+        let challenge = Self::challenge(&self.commit, commitment, value);
+        R * self.response == self.commit + blind_commit * challenge
+    }
+
+    fn challenge(commit: &pallas::Point, commitment: &pallas::Point, value: u64) -> pallas::Scalar {
+        let transcript: &[&[u8]] =
+            &[&commit.to_bytes(), &commitment.to_bytes(), &value.to_le_bytes()];
+        hash_to_scalar(DRK_VALUE_OPENING_DOMAIN, transcript)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::OsRng;
 
     #[test]
     fn pedersen_commitment() {
@@ -74,4 +121,16 @@ mod tests {
             pedersen_commitment_u64(a_value + b_value, &a_blind + &b_blind)
         );
     }
+
+    #[test]
+    fn value_opening_proof() {
+        let a_blind = ScalarBlind::random(&mut OsRng);
+        let b_blind = ScalarBlind::random(&mut OsRng);
+        let sum = pedersen_commitment_u64(10, a_blind) + pedersen_commitment_u64(20, b_blind);
+
+        let proof = ValueOpeningProof::create(30, &a_blind + &b_blind, &mut OsRng);
+        assert!(proof.verify(&sum, 30));
+        assert!(!proof.verify(&sum, 31));
+        assert!(!proof.verify(&pedersen_commitment_u64(10, a_blind), 30));
+    }
 }
@@ -20,17 +20,22 @@
 use darkfi_serial::async_trait;
 use darkfi_serial::{SerialDecodable, SerialEncodable};
 use halo2_gadgets::ecc::chip::FixedPoint;
+use halo2_proofs::arithmetic::best_multiexp;
 use pasta_curves::{
-    group::{ff::PrimeField, Group, GroupEncoding},
+    group::{ff::PrimeField, prime::PrimeCurveAffine, Curve, Group, GroupEncoding},
     pallas,
 };
+use zeroize::Zeroize;
 
 use super::{
     constants::{NullifierK, DRK_SCHNORR_DOMAIN},
-    util::{fp_mod_fv, hash_to_scalar},
+    util::{fp_mod_fv, hash_to_scalar, zeroize_copy},
     PublicKey, SecretKey,
 };
 
+/// Prefix domain used for deriving the batch verification weights
+const SCHNORR_BATCH_DOMAIN: &[u8] = b"DarkFi:SchnBatch";
+
 /// Schnorr signature with a commit and response
 #[derive(Debug, Clone, Copy, Eq, PartialEq, SerialEncodable, SerialDecodable)]
 pub struct Signature {
@@ -43,6 +48,12 @@ impl Signature {
     pub fn dummy() -> Self {
         Self { commit: pallas::Point::identity(), response: pallas::Scalar::zero() }
     }
+
+    /// Assemble a `Signature` from its commit and response, e.g. after
+    /// aggregating threshold signature shares.
+    pub(crate) fn from_parts(commit: pallas::Point, response: pallas::Scalar) -> Self {
+        Self { commit, response }
+    }
 }
 
 /// Trait for secret keys that implements a signature creation
@@ -61,7 +72,9 @@ pub trait SchnorrPublic {
 impl SchnorrSecret for SecretKey {
     fn sign(&self, message: &[u8]) -> Signature {
         // Derive a deterministic nonce
-        let mask = hash_to_scalar(DRK_SCHNORR_DOMAIN, &[&self.inner().to_repr(), message]);
+        let mut secret_bytes = self.inner().to_repr();
+        let mut mask = hash_to_scalar(DRK_SCHNORR_DOMAIN, &[&secret_bytes, message]);
+        secret_bytes.zeroize();
 
         let commit = NullifierK.generator() * mask;
 
@@ -70,7 +83,10 @@ impl SchnorrSecret for SecretKey {
         let transcript = &[&commit_bytes, &pubkey_bytes, message];
 
         let challenge = hash_to_scalar(DRK_SCHNORR_DOMAIN, transcript);
-        let response = mask + challenge * fp_mod_fv(self.inner());
+        let mut secret = fp_mod_fv(self.inner());
+        let response = mask + challenge * secret;
+        zeroize_copy(&mut secret);
+        zeroize_copy(&mut mask);
 
         Signature { commit, response }
     }
@@ -87,6 +103,109 @@ impl SchnorrPublic for PublicKey {
     }
 }
 
+/// Verify a batch of signatures at once, by checking a random linear
+/// combination of their verification equations with a single multi-scalar
+/// multiplication.
+///
+/// The weights are derived by hashing the whole batch, so no RNG is needed
+/// and the result is deterministic. Returns `false` if any signature is
+/// invalid or if the input lengths differ. An empty batch is valid.
+pub fn verify_batch(pubkeys: &[PublicKey], messages: &[&[u8]], signatures: &[Signature]) -> bool {
+    if pubkeys.len() != messages.len() || pubkeys.len() != signatures.len() {
+        return false
+    }
+
+    if pubkeys.is_empty() {
+        return true
+    }
+
+    let challenges: Vec<pallas::Scalar> = pubkeys
+        .iter()
+        .zip(messages)
+        .zip(signatures)
+        .map(|((public, message), signature)| {
+            let commit_bytes = signature.commit.to_bytes();
+            let pubkey_bytes = public.to_bytes();
+            hash_to_scalar(DRK_SCHNORR_DOMAIN, &[&commit_bytes, &pubkey_bytes, message])
+        })
+        .collect();
+
+    // The challenges bind the commits, public keys, and messages, so along
+    // with the responses they commit to the entire batch.
+    let mut hasher = blake3::Hasher::new();
+    for (challenge, signature) in challenges.iter().zip(signatures) {
+        hasher.update(&challenge.to_repr());
+        hasher.update(&signature.response.to_repr());
+    }
+    let batch_hash = hasher.finalize();
+
+    // sum(z_i * s_i) * G - sum(z_i * c_i * P_i) - sum(z_i * R_i) == 0
+    let n = pubkeys.len();
+    let mut scalars = Vec::with_capacity(2 * n + 1);
+    let mut points = Vec::with_capacity(2 * n);
+    let mut generator_scalar = pallas::Scalar::zero();
+    for (i, (challenge, (public, signature))) in
+        challenges.iter().zip(pubkeys.iter().zip(signatures)).enumerate()
+    {
+        let weight = hash_to_scalar(
+            SCHNORR_BATCH_DOMAIN,
+            &[batch_hash.as_bytes(), &(i as u64).to_le_bytes()],
+        );
+
+        generator_scalar += weight * signature.response;
+        scalars.push(-(weight * challenge));
+        points.push(public.inner());
+        scalars.push(-weight);
+        points.push(signature.commit);
+    }
+
+    let mut bases = vec![pallas::Affine::identity(); points.len()];
+    pallas::Point::batch_normalize(&points, &mut bases);
+    scalars.push(generator_scalar);
+    bases.push(NullifierK.generator());
+
+    bool::from(best_multiexp(&scalars, &bases).is_identity())
+}
+
+/// Deterministic fallback for [`verify_batch`], verifying each signature
+/// on its own. Returns the index of the first invalid signature, or of the
+/// first entry missing a counterpart if the input lengths differ.
+pub fn find_invalid(
+    pubkeys: &[PublicKey],
+    messages: &[&[u8]],
+    signatures: &[Signature],
+) -> Option<usize> {
+    let n = pubkeys.len().min(messages.len()).min(signatures.len());
+
+    for i in 0..n {
+        if !pubkeys[i].verify(messages[i], &signatures[i]) {
+            return Some(i)
+        }
+    }
+
+    if pubkeys.len() != n || messages.len() != n || signatures.len() != n {
+        return Some(n)
+    }
+
+    None
+}
+
+/// Verify a batch of signatures with [`verify_batch`], falling back to
+/// [`find_invalid`] when the batch fails, to identify the culprit.
+pub fn verify_batch_or_find(
+    pubkeys: &[PublicKey],
+    messages: &[&[u8]],
+    signatures: &[Signature],
+) -> Result<(), usize> {
+    if verify_batch(pubkeys, messages, signatures) {
+        return Ok(())
+    }
+
+    // The batch can only fail if an individual signature fails, so
+    // this always finds one.
+    Err(find_invalid(pubkeys, messages, signatures).unwrap_or(0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,4 +225,33 @@ mod tests {
         let de = deserialize(&ser).unwrap();
         assert!(public.verify(message, &de));
     }
+
+    #[test]
+    fn test_schnorr_batch_verification() {
+        let secrets: Vec<_> = (0..8).map(|_| SecretKey::random(&mut OsRng)).collect();
+        let pubkeys: Vec<_> = secrets.iter().map(|s| PublicKey::from_secret(*s)).collect();
+        let messages: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; i as usize + 1]).collect();
+        let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+        let mut signatures: Vec<_> =
+            secrets.iter().zip(&messages).map(|(s, m)| s.sign(m)).collect();
+
+        assert!(verify_batch(&[], &[], &[]));
+        assert!(verify_batch(&pubkeys, &messages, &signatures));
+        assert_eq!(verify_batch_or_find(&pubkeys, &messages, &signatures), Ok(()));
+
+        // Mismatched lengths
+        assert!(!verify_batch(&pubkeys[..7], &messages, &signatures));
+        assert_eq!(find_invalid(&pubkeys[..7], &messages, &signatures), Some(7));
+
+        // A signature over the wrong message
+        signatures[5] = secrets[5].sign(b"forged");
+        assert!(!verify_batch(&pubkeys, &messages, &signatures));
+        assert_eq!(verify_batch_or_find(&pubkeys, &messages, &signatures), Err(5));
+
+        // Valid signatures attached to the wrong entries
+        signatures[5] = signatures[3];
+        signatures[3] = secrets[5].sign(messages[5]);
+        assert!(!verify_batch(&pubkeys, &messages, &signatures));
+        assert_eq!(find_invalid(&pubkeys, &messages, &signatures), Some(3));
+    }
 }
@@ -0,0 +1,131 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Spend hooks let a contract take ownership of coins. A coin whose
+//! `spend_hook` is set can only be spent by a `Money::Transfer` call whose
+//! parent is the hooked function, which interprets the coin's `user_data`.
+//!
+//! This module provides helpers to declare hooks with structured
+//! parameters, to validate the call tree on the hooked contract's side,
+//! and to tell which hook a coin is encumbered by on the client side.
+
+use pasta_curves::pallas;
+
+use super::{poseidon_hash, FuncId, FuncRef};
+use crate::{dark_tree::DarkLeaf, error::GenericResult, msg, ContractCall, ContractError};
+
+/// A spend hook encumbering a coin, along with its user data
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SpendHook {
+    /// Function the coin's spending call must be a child of
+    pub func: FuncRef,
+    /// Data interpreted by the hooked function
+    pub user_data: pallas::Base,
+}
+
+impl SpendHook {
+    pub fn new(func: FuncRef, user_data: pallas::Base) -> Self {
+        Self { func, user_data }
+    }
+
+    /// Create a spend hook whose user data commits to the given parameters.
+    /// The hooked function has to open the commitment, usually in ZK.
+    pub fn with_params<const N: usize>(func: FuncRef, params: [pallas::Base; N]) -> Self {
+        Self { func, user_data: poseidon_hash(params) }
+    }
+
+    /// The `FuncId` set as the coin's spend hook
+    pub fn func_id(&self) -> FuncId {
+        self.func.to_func_id()
+    }
+
+    /// Check if a coin's `spend_hook` and `user_data` match this hook
+    pub fn matches(&self, spend_hook: FuncId, user_data: pallas::Base) -> bool {
+        self.func_id() == spend_hook && self.user_data == user_data
+    }
+}
+
+/// Registry of known hooked functions, used by clients to tell which
+/// hook a coin is encumbered by, since coins only hold its `FuncId`.
+#[derive(Clone, Debug, Default)]
+pub struct SpendHookRegistry {
+    hooks: Vec<(FuncId, FuncRef, String)>,
+}
+
+impl SpendHookRegistry {
+    /// Register a hooked function under a human readable name
+    pub fn register(&mut self, name: &str, func: FuncRef) {
+        let func_id = func.to_func_id();
+        self.hooks.retain(|(id, _, _)| *id != func_id);
+        self.hooks.push((func_id, func, name.to_string()));
+    }
+
+    /// Find the function and name of a coin's `spend_hook`, if known
+    pub fn lookup(&self, spend_hook: &FuncId) -> Option<(FuncRef, &str)> {
+        self.hooks.iter().find(|(id, _, _)| id == spend_hook).map(|(_, f, n)| (*f, n.as_str()))
+    }
+}
+
+/// Used by a hooked function to read the call spending its coins. The
+/// call at `call_idx` must have a single child, calling `child_func`.
+pub fn spend_hook_child<'a>(
+    calls: &'a [DarkLeaf<ContractCall>],
+    call_idx: u32,
+    child_func: &FuncRef,
+) -> GenericResult<&'a ContractCall> {
+    let self_ = &calls[call_idx as usize];
+    if self_.children_indexes.len() != 1 {
+        msg!("[SpendHook] Error: Expected a single child call");
+        return Err(ContractError::SpendHookInvalidChild)
+    }
+
+    let child = &calls[self_.children_indexes[0]].data;
+    if child.contract_id != child_func.contract_id ||
+        child.data.is_empty() ||
+        child.data[0] != child_func.func_code
+    {
+        msg!("[SpendHook] Error: Child call does not call the expected function");
+        return Err(ContractError::SpendHookInvalidChild)
+    }
+
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{DAO_CONTRACT_ID, MONEY_CONTRACT_ID};
+
+    #[test]
+    fn spend_hook_registry_lookup() {
+        let exec = FuncRef { contract_id: *DAO_CONTRACT_ID, func_code: 0x04 };
+        let claim = FuncRef { contract_id: *MONEY_CONTRACT_ID, func_code: 0x0b };
+
+        let mut registry = SpendHookRegistry::default();
+        registry.register("Dao::Exec", exec);
+        registry.register("Money::VestingClaim", claim);
+
+        let hook = SpendHook::with_params(claim, [pallas::Base::from(42)]);
+        assert!(hook.matches(claim.to_func_id(), poseidon_hash([pallas::Base::from(42)])));
+        assert!(!hook.matches(exec.to_func_id(), hook.user_data));
+
+        assert_eq!(registry.lookup(&hook.func_id()), Some((claim, "Money::VestingClaim")));
+        assert_eq!(registry.lookup(&exec.to_func_id()), Some((exec, "Dao::Exec")));
+        assert_eq!(registry.lookup(&FuncId::none()), None);
+    }
+}
@@ -53,6 +53,16 @@ pub fn hash_to_base(persona: &[u8], vals: &[&[u8]]) -> pallas::Base {
     hash_to_field_elem(persona, vals)
 }
 
+/// Overwrite a field element, or any other plain `Copy` value, with its
+/// default (zero) value in a way the compiler can't optimize away.
+/// Used to wipe secrets from memory once they're no longer needed.
+pub fn zeroize_copy<T: Copy + Default>(value: &mut T) {
+    // SAFETY: `value` is a valid, aligned, and exclusive reference, and
+    // `T: Copy` has no drop glue that could be skipped by overwriting it.
+    unsafe { core::ptr::write_volatile(value, T::default()) };
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
 /// Converts from pallas::Base to pallas::Scalar (aka $x \pmod{r_\mathbb{P}}$).
 ///
 /// This requires no modular reduction because Pallas' base field is smaller than its
@@ -0,0 +1,121 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Incoming viewing keys.
+//!
+//! A viewing key is derived from a spending [`SecretKey`] and is able to
+//! decrypt notes that were encrypted to its public counterpart, without
+//! being able to spend the coins those notes describe. This allows a
+//! wallet owner to selectively disclose their incoming payments, e.g. to
+//! an auditor, by handing out the viewing key instead of the secret key.
+
+use core::str::FromStr;
+
+#[cfg(feature = "async")]
+use darkfi_serial::async_trait;
+use darkfi_serial::{SerialDecodable, SerialEncodable};
+use lazy_static::lazy_static;
+use pasta_curves::pallas;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use super::{
+    domains::{self, domain},
+    poseidon_hash, PublicKey, SecretKey,
+};
+use crate::error::ContractError;
+
+lazy_static! {
+    /// Derivation prefix for `ViewingKey`
+    pub static ref VIEWING_KEY_PREFIX: pallas::Base = domain(domains::VIEWING_KEY);
+}
+
+/// Incoming viewing key, wrapping the `SecretKey` notes are encrypted to.
+/// Equality is checked in constant time through the inner `SecretKey`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, SerialEncodable, SerialDecodable)]
+pub struct ViewingKey(SecretKey);
+
+impl ViewingKey {
+    /// Derive the `ViewingKey` belonging to the given spending `SecretKey`
+    pub fn derive(secret: &SecretKey) -> Self {
+        Self(SecretKey::from(poseidon_hash([*VIEWING_KEY_PREFIX, secret.inner()])))
+    }
+
+    /// Get the inner `SecretKey` used for note decryption
+    pub fn inner(&self) -> SecretKey {
+        self.0
+    }
+
+    /// Get the `PublicKey` senders should encrypt notes to
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_secret(self.0)
+    }
+}
+
+impl ConstantTimeEq for ViewingKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl Zeroize for ViewingKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl FromStr for ViewingKey {
+    type Err = ContractError;
+
+    /// Tries to create a `ViewingKey` object from a base58 encoded string.
+    fn from_str(enc: &str) -> Result<Self, Self::Err> {
+        Ok(Self(SecretKey::from_str(enc)?))
+    }
+}
+
+impl core::fmt::Display for ViewingKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::note::AeadEncryptedNote;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn viewing_key_decrypts_but_secret_does_not() {
+        let secret = SecretKey::random(&mut OsRng);
+        let viewing_key = ViewingKey::derive(&secret);
+        assert_eq!(viewing_key, ViewingKey::derive(&secret));
+        assert!(bool::from(viewing_key.ct_eq(&ViewingKey::derive(&secret))));
+        let other = ViewingKey::derive(&SecretKey::random(&mut OsRng));
+        assert!(!bool::from(viewing_key.ct_eq(&other)));
+        assert_ne!(viewing_key.public_key(), PublicKey::from_secret(secret));
+
+        let note =
+            AeadEncryptedNote::encrypt(&42u64, &viewing_key.public_key(), &mut OsRng).unwrap();
+        assert_eq!(note.decrypt::<u64>(&viewing_key.inner()).unwrap(), 42);
+        assert!(note.decrypt::<u64>(&secret).is_err());
+
+        let encoded = viewing_key.to_string();
+        assert_eq!(ViewingKey::from_str(&encoded).unwrap(), viewing_key);
+    }
+}
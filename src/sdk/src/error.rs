@@ -95,6 +95,12 @@ pub enum ContractError {
 
     #[error("Hex string is not properly formatted")]
     HexFmtErr,
+
+    #[error("Spend hook child call is invalid")]
+    SpendHookInvalidChild,
+
+    #[error("VRF proof verification failed")]
+    VrfVerifyFailed,
 }
 
 /// Builtin return values occupy the upper 32 bits
@@ -126,6 +132,8 @@ pub const SMT_DEL_FAILED: i64 = to_builtin!(19);
 pub const GET_SYSTEM_TIME_FAILED: i64 = to_builtin!(20);
 pub const DATA_TOO_LARGE: i64 = to_builtin!(21);
 pub const HEX_FMT_ERR: i64 = to_builtin!(22);
+pub const SPEND_HOOK_INVALID_CHILD: i64 = to_builtin!(23);
+pub const VRF_VERIFY_FAILED: i64 = to_builtin!(24);
 
 impl From<ContractError> for i64 {
     fn from(err: ContractError) -> Self {
@@ -151,6 +159,8 @@ impl From<ContractError> for i64 {
             ContractError::GetSystemTimeFailed => GET_SYSTEM_TIME_FAILED,
             ContractError::DataTooLarge => DATA_TOO_LARGE,
             ContractError::HexFmtErr => HEX_FMT_ERR,
+            ContractError::SpendHookInvalidChild => SPEND_HOOK_INVALID_CHILD,
+            ContractError::VrfVerifyFailed => VRF_VERIFY_FAILED,
             ContractError::Custom(error) => {
                 if error == 0 {
                     CUSTOM_ZERO
@@ -187,6 +197,8 @@ impl From<i64> for ContractError {
             GET_SYSTEM_TIME_FAILED => Self::GetSystemTimeFailed,
             DATA_TOO_LARGE => Self::DataTooLarge,
             HEX_FMT_ERR => Self::HexFmtErr,
+            SPEND_HOOK_INVALID_CHILD => Self::SpendHookInvalidChild,
+            VRF_VERIFY_FAILED => Self::VrfVerifyFailed,
             _ => Self::Custom(error as u32),
         }
     }
@@ -231,3 +243,68 @@ pub enum DarkTreeError {
     #[error("DarkTree max capacity has been exceeded")]
     MaxCapacityExceeded,
 }
+
+/// Main result type used by the FROST threshold signing protocol.
+pub type FrostResult<T> = ResultGeneric<T, FrostError>;
+
+/// General FROST related errors.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum FrostError {
+    #[error("Invalid FROST threshold: {0} of {1}")]
+    InvalidThreshold(u16, u16),
+
+    #[error("Invalid FROST participant index: {0}")]
+    InvalidIndex(u16),
+
+    #[error("Duplicate FROST participant index: {0}")]
+    DuplicateIndex(u16),
+
+    #[error("Secret share {0} does not match the polynomial commitments")]
+    InvalidSecretShare(u16),
+
+    #[error("Not enough FROST signers: {0} (Expected: >= {1})")]
+    NotEnoughSigners(usize, u16),
+
+    #[error("Missing signing commitment for participant: {0}")]
+    MissingCommitment(u16),
+
+    #[error("Signature share {0} is invalid")]
+    InvalidSignatureShare(u16),
+
+    #[error("Aggregated signature does not verify")]
+    InvalidSignature,
+
+    #[error("Decryption share {0} is invalid")]
+    InvalidDecryptionShare(u16),
+
+    #[error("Invalid DKG proof of knowledge from participant: {0}")]
+    InvalidProofOfKnowledge(u16),
+
+    #[error("Missing DKG package from participant: {0}")]
+    MissingPackage(u16),
+}
+
+/// Main result type used by the MuSig2 multi-signature protocol.
+pub type MusigResult<T> = ResultGeneric<T, MusigError>;
+
+/// General MuSig2 related errors.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum MusigError {
+    #[error("No MuSig2 signer keys given")]
+    NoSigners,
+
+    #[error("Duplicate MuSig2 signer key: {0}")]
+    DuplicateSigner(usize),
+
+    #[error("MuSig2 signer is not part of the key set")]
+    UnknownSigner,
+
+    #[error("Missing public nonces for signer: {0}")]
+    MissingNonces(usize),
+
+    #[error("Partial signature {0} is invalid")]
+    InvalidPartialSignature(usize),
+
+    #[error("Aggregated key or nonce is the identity point")]
+    IdentityPoint,
+}
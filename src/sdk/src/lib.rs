@@ -20,6 +20,7 @@ pub use bridgetree;
 pub use num_bigint;
 pub use num_traits;
 pub use pasta_curves as pasta;
+pub use subtle;
 
 /// Blockchain structures
 pub mod blockchain;
@@ -20,6 +20,7 @@ use darkfi_serial::{Decodable, Encodable};
 use std::io::Cursor;
 
 use crate::{
+    crypto::{ecvrf::VrfProof, PublicKey},
     error::{ContractError, GenericResult},
     tx::TransactionHash,
 };
@@ -175,6 +176,32 @@ pub fn get_tx_location(hash: &TransactionHash) -> GenericResult<(u64, u64)> {
     Ok((Decodable::decode(&mut cursor)?, Decodable::decode(cursor)?))
 }
 
+/// Everyone can call this. Verifies a VRF proof for the given public key
+/// and input, and returns the VRF output if the proof is valid. Returns
+/// [`ContractError::VrfVerifyFailed`] otherwise.
+///
+/// ```
+/// output = vrf_verify(public_key, alpha, proof)?;
+/// ```
+pub fn vrf_verify(
+    public_key: &PublicKey,
+    alpha: &[u8],
+    proof: &VrfProof,
+) -> GenericResult<blake3::Hash> {
+    let mut buf = vec![];
+    public_key.encode(&mut buf)?;
+    alpha.to_vec().encode(&mut buf)?;
+    proof.encode(&mut buf)?;
+
+    let len = u32::try_from(buf.len()).map_err(|_| ContractError::DataTooLarge)?;
+    let ret = unsafe { vrf_verify_(buf.as_ptr(), len) };
+    let obj = parse_retval_u32(ret)?;
+    let mut output = [0u8; blake3::OUT_LEN];
+    assert_eq!(get_object_size(obj), blake3::OUT_LEN as i64);
+    get_object_bytes(&mut output, obj);
+    Ok(blake3::Hash::from(output))
+}
+
 extern "C" {
     fn set_return_data_(ptr: *const u8, len: u32) -> i64;
     fn get_object_bytes_(ptr: *const u8, len: u32) -> i64;
@@ -187,4 +214,5 @@ extern "C" {
     fn get_last_block_height_() -> i64;
     fn get_tx_(ptr: *const u8) -> i64;
     fn get_tx_location_(ptr: *const u8) -> i64;
+    fn vrf_verify_(ptr: *const u8, len: u32) -> i64;
 }
@@ -18,12 +18,16 @@
 
 //! Derive (de)serialization for enums and structs, see src/serial/derive
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{
-    Fields, FieldsNamed, FieldsUnnamed, Index, ItemEnum, ItemStruct, WhereClause, WherePredicate,
+    ext::IdentExt, Fields, FieldsNamed, FieldsUnnamed, Index, ItemEnum, ItemStruct, WhereClause,
+    WherePredicate,
 };
 
-use super::{contains_initialize_with, contains_skip, discriminant_map, VariantParts};
+use super::{
+    check_extensible, contains_ext, contains_initialize_with, contains_skip, discriminant_map,
+    VariantParts,
+};
 
 fn named_fields(
     cratename: &Ident,
@@ -280,7 +284,9 @@ pub fn async_struct_ser(input: &ItemStruct, cratename: Ident) -> syn::Result<Tok
         Clone::clone,
     );
 
+    let extensible = check_extensible(input)?;
     let mut body = TokenStream::new();
+    let mut ext_body = TokenStream::new();
 
     match &input.fields {
         Fields::Named(fields) => {
@@ -290,12 +296,32 @@ pub fn async_struct_ser(input: &ItemStruct, cratename: Ident) -> syn::Result<Tok
                 }
 
                 let field_name = field.ident.as_ref().unwrap();
+                let field_type = &field.ty;
+
+                if contains_ext(&field.attrs) {
+                    // Trailing fields holding their default value are left
+                    // out, so the encoding stays canonical for decoders
+                    // that don't know about them.
+                    ext_body.extend(quote! {
+                        #cratename::Encodable::encode(&self.#field_name, &mut ext)?;
+                        if self.#field_name != <#field_type as Default>::default() {
+                            ext_len = ext.len();
+                        }
+                    });
+                    where_clause.predicates.push(
+                        syn::parse2(quote! {
+                            #field_type: #cratename::Encodable + PartialEq + Default
+                        })
+                        .unwrap(),
+                    );
+                    continue
+                }
+
                 let delta = quote! {
                     len += self.#field_name.encode_async(s).await?;
                 };
                 body.extend(delta);
 
-                let field_type = &field.ty;
                 where_clause.predicates.push(
                     syn::parse2(quote! {
                         #field_type: #cratename::AsyncEncodable
@@ -303,6 +329,16 @@ pub fn async_struct_ser(input: &ItemStruct, cratename: Ident) -> syn::Result<Tok
                     .unwrap(),
                 );
             }
+
+            if extensible {
+                body.extend(quote! {
+                    let mut ext: Vec<u8> = Vec::new();
+                    let mut ext_len = 0;
+                    #ext_body
+                    ext.truncate(ext_len);
+                    len += ext.encode_async(s).await?;
+                });
+            }
         }
         Fields::Unnamed(fields) => {
             for field_idx in 0..fields.unnamed.len() {
@@ -340,7 +376,71 @@ pub fn async_struct_de(input: &ItemStruct, cratename: Ident) -> syn::Result<Toke
     );
 
     let init_method = contains_initialize_with(&input.attrs);
+    let extensible = check_extensible(input)?;
     let return_value = match &input.fields {
+        Fields::Named(fields) if extensible => {
+            // Decode into locals so the extension section is read after
+            // all the base fields, whatever the position of skipped fields.
+            let mut body = TokenStream::new();
+            let mut init = TokenStream::new();
+            let mut ext_read = false;
+            for field in &fields.named {
+                let field_name = field.ident.as_ref().unwrap();
+                let local = format_ident!("__{}", field_name.unraw());
+                let field_type = &field.ty;
+
+                if contains_skip(&field.attrs) {
+                    body.extend(quote! {
+                        let #local: #field_type = Default::default();
+                    });
+                } else if contains_ext(&field.attrs) {
+                    if !ext_read {
+                        body.extend(quote! {
+                            let mut ext = std::io::Cursor::new(<Vec<u8> as #cratename::AsyncDecodable>::decode_async(d).await?);
+                        });
+                        ext_read = true;
+                    }
+                    where_clause.predicates.push(
+                        syn::parse2(quote! {
+                            #field_type: #cratename::Decodable + Default
+                        })
+                        .unwrap(),
+                    );
+                    body.extend(quote! {
+                        let #local: #field_type = if (ext.position() as usize) < ext.get_ref().len() {
+                            #cratename::Decodable::decode(&mut ext)?
+                        } else {
+                            Default::default()
+                        };
+                    });
+                } else {
+                    where_clause.predicates.push(
+                        syn::parse2(quote! {
+                            #field_type: #cratename::AsyncDecodable
+                        })
+                        .unwrap(),
+                    );
+                    body.extend(quote! {
+                        let #local: #field_type = #cratename::AsyncDecodable::decode_async(d).await?;
+                    });
+                }
+                init.extend(quote! { #field_name: #local, });
+            }
+
+            // Unknown trailing extension data is skipped
+            if !ext_read {
+                body.extend(quote! {
+                    <Vec<u8> as #cratename::AsyncDecodable>::decode_async(d).await?;
+                });
+            }
+
+            quote! {
+                {
+                    #body
+                    Self { #init }
+                }
+            }
+        }
         Fields::Named(fields) => {
             let mut body = TokenStream::new();
             for field in &fields.named {
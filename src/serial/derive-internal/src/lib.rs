@@ -21,7 +21,10 @@ use std::collections::HashMap;
 
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
-use syn::{punctuated::Punctuated, token::Comma, Attribute, Path, Variant, WherePredicate};
+use syn::{
+    punctuated::Punctuated, token::Comma, Attribute, Fields, ItemStruct, Path, Variant,
+    WherePredicate,
+};
 
 mod sync_derive;
 pub use sync_derive::{enum_de, enum_ser, struct_de, struct_ser};
@@ -76,3 +79,55 @@ pub fn contains_initialize_with(attrs: &[Attribute]) -> Option<Path> {
 
     None
 }
+
+pub fn contains_extensible(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("serial_extensible"))
+}
+
+pub fn contains_ext(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("serial_ext"))
+}
+
+/// Checks the placement of `serial_extensible` and `serial_ext` attributes,
+/// returning whether the struct carries an extension section.
+///
+/// An extensible struct encodes its `serial_ext` fields into a trailing,
+/// length-prefixed section. Decoders fill fields missing from the section
+/// with their `Default` value and skip any bytes they don't know about, so
+/// new `serial_ext` fields can be appended without breaking old decoders.
+///
+/// Trailing fields holding their `Default` value are left out of the
+/// section, so each value has a single encoding. Strict decoding rejects
+/// unknown extension data, since it can't re-encode it: data that doesn't
+/// use a new field stays decodable by strict old decoders, while data using
+/// it is only accepted by them once they upgrade.
+pub fn check_extensible(input: &ItemStruct) -> syn::Result<bool> {
+    let extensible = contains_extensible(&input.attrs);
+
+    let Fields::Named(fields) = &input.fields else {
+        if extensible {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "serial_extensible is only supported on structs with named fields",
+            ))
+        }
+        return Ok(false)
+    };
+
+    let mut seen_ext = false;
+    for field in &fields.named {
+        if contains_ext(&field.attrs) {
+            if !extensible {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "serial_ext fields require the struct to be serial_extensible",
+                ))
+            }
+            seen_ext = true;
+        } else if seen_ext && !contains_skip(&field.attrs) {
+            return Err(syn::Error::new_spanned(field, "serial_ext fields must be trailing"))
+        }
+    }
+
+    Ok(extensible)
+}
@@ -28,7 +28,7 @@ use darkfi_derive_internal::{async_enum_de, async_enum_ser, async_struct_de, asy
 
 use darkfi_derive_internal::{enum_de, enum_ser, struct_de, struct_ser};
 
-#[proc_macro_derive(SerialEncodable, attributes(skip_serialize))]
+#[proc_macro_derive(SerialEncodable, attributes(skip_serialize, serial_extensible, serial_ext))]
 pub fn darkfi_serialize(input: TokenStream) -> TokenStream {
     let found_crate = crate_name("darkfi-serial").expect("darkfi-serial is found in Cargo.toml");
 
@@ -41,9 +41,15 @@ pub fn darkfi_serialize(input: TokenStream) -> TokenStream {
 
     let res: syn::Result<TokenStream2> = if let Ok(input) = syn::parse::<ItemStruct>(input.clone())
     {
-        let sync_tokens = struct_ser(&input, cratename.clone()).unwrap();
+        let sync_tokens = match struct_ser(&input, cratename.clone()) {
+            Ok(tokens) => tokens,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
         #[cfg(feature = "async")]
-        let async_tokens = async_struct_ser(&input, cratename).unwrap();
+        let async_tokens = match async_struct_ser(&input, cratename) {
+            Ok(tokens) => tokens,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
         #[cfg(not(feature = "async"))]
         let async_tokens = quote! {};
 
@@ -72,7 +78,7 @@ pub fn darkfi_serialize(input: TokenStream) -> TokenStream {
     TokenStream::from(res.unwrap_or_else(|err| err.to_compile_error()))
 }
 
-#[proc_macro_derive(SerialDecodable, attributes(skip_serialize))]
+#[proc_macro_derive(SerialDecodable, attributes(skip_serialize, serial_extensible, serial_ext))]
 pub fn darkfi_deserialize(input: TokenStream) -> TokenStream {
     let found_crate = crate_name("darkfi-serial").expect("darkfi-serial is found in Cargo.toml");
 
@@ -85,9 +91,15 @@ pub fn darkfi_deserialize(input: TokenStream) -> TokenStream {
 
     let res: syn::Result<TokenStream2> = if let Ok(input) = syn::parse::<ItemStruct>(input.clone())
     {
-        let sync_tokens = struct_de(&input, cratename.clone()).unwrap();
+        let sync_tokens = match struct_de(&input, cratename.clone()) {
+            Ok(tokens) => tokens,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
         #[cfg(feature = "async")]
-        let async_tokens = async_struct_de(&input, cratename).unwrap();
+        let async_tokens = match async_struct_de(&input, cratename) {
+            Ok(tokens) => tokens,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
         #[cfg(not(feature = "async"))]
         let async_tokens = quote! {};
 
@@ -27,7 +27,7 @@ pub use futures_lite::{
     AsyncWriteExt as FutAsyncWriteExt,
 };
 
-use crate::{endian, VarInt};
+use crate::{endian, limits, VarInt};
 
 /// Data which can asynchronously be encoded in a consensus-consistent way.
 #[async_trait]
@@ -75,6 +75,44 @@ pub async fn deserialize_async<T: AsyncDecodable>(data: &[u8]) -> Result<T> {
     Ok(rv)
 }
 
+/// Asynchronously deserialize an object from a vector of at most `limit`
+/// bytes. See [`crate::deserialize_limited`].
+pub async fn deserialize_async_limited<T: AsyncDecodable>(data: &[u8], limit: u64) -> Result<T> {
+    if data.len() as u64 > limit {
+        return Err(limits::limit_exceeded())
+    }
+
+    deserialize_async(data).await
+}
+
+/// Asynchronously deserialize an object from a vector, enforcing its
+/// canonical encoding. See [`crate::deserialize_strict`].
+pub async fn deserialize_async_strict<T: AsyncEncodable + AsyncDecodable>(
+    data: &[u8],
+) -> Result<T> {
+    let rv: T = deserialize_async(data).await?;
+
+    // Fail if the data is not the canonical encoding of the decoded object.
+    if serialize_async(&rv).await != data {
+        return Err(Error::new(ErrorKind::Other, "Non-canonical encoding on deserialization"))
+    }
+
+    Ok(rv)
+}
+
+/// Asynchronously deserialize an object from a vector of at most `limit`
+/// bytes, enforcing its canonical encoding. See [`crate::deserialize_strict_limited`].
+pub async fn deserialize_async_strict_limited<T: AsyncEncodable + AsyncDecodable>(
+    data: &[u8],
+    limit: u64,
+) -> Result<T> {
+    if data.len() as u64 > limit {
+        return Err(limits::limit_exceeded())
+    }
+
+    deserialize_async_strict(data).await
+}
+
 /// Extensions of `AsyncWrite` to encode data as per Bitcoin consensus.
 #[async_trait]
 pub trait AsyncWriteExt {
@@ -545,7 +583,8 @@ impl<T: AsyncDecodable + Send> AsyncDecodable for Vec<T> {
     async fn decode_async<D: AsyncRead + Unpin + Send>(d: &mut D) -> Result<Self> {
         let len = VarInt::decode_async(d).await?.0;
         let mut ret = Vec::new();
-        ret.try_reserve(len as usize).map_err(|_| std::io::ErrorKind::InvalidData)?;
+        ret.try_reserve(limits::prealloc_len::<T>(len))
+            .map_err(|_| std::io::ErrorKind::InvalidData)?;
         for _ in 0..len {
             ret.push(AsyncDecodable::decode_async(d).await?);
         }
@@ -572,7 +611,8 @@ impl<T: AsyncDecodable + Send> AsyncDecodable for VecDeque<T> {
     async fn decode_async<D: AsyncRead + Unpin + Send>(d: &mut D) -> Result<Self> {
         let len = VarInt::decode_async(d).await?.0;
         let mut ret = VecDeque::new();
-        ret.try_reserve(len as usize).map_err(|_| std::io::ErrorKind::InvalidData)?;
+        ret.try_reserve(limits::prealloc_len::<T>(len))
+            .map_err(|_| std::io::ErrorKind::InvalidData)?;
         for _ in 0..len {
             ret.push_back(AsyncDecodable::decode_async(d).await?);
         }
@@ -663,7 +703,14 @@ impl AsyncEncodable for &str {
 impl AsyncDecodable for String {
     #[inline]
     async fn decode_async<D: AsyncRead + Unpin + Send>(d: &mut D) -> Result<String> {
-        match String::from_utf8(AsyncDecodable::decode_async(d).await?) {
+        let len = VarInt::decode_async(d).await?.0;
+
+        let mut buf = Vec::with_capacity(limits::prealloc_len::<u8>(len));
+        if d.take(len).read_to_end(&mut buf).await? as u64 != len {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "String truncated"))
+        }
+
+        match String::from_utf8(buf) {
             Ok(v) => Ok(v),
             Err(_) => Err(Error::new(ErrorKind::Other, "Invalid UTF-8 for string")),
         }
@@ -28,12 +28,15 @@ pub use darkfi_derive::{SerialDecodable, SerialEncodable};
 mod async_lib;
 #[cfg(feature = "async")]
 pub use async_lib::{
-    async_trait, deserialize_async, deserialize_async_partial, serialize_async, AsyncDecodable,
+    async_trait, deserialize_async, deserialize_async_limited, deserialize_async_partial,
+    deserialize_async_strict, deserialize_async_strict_limited, serialize_async, AsyncDecodable,
     AsyncEncodable, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, FutAsyncReadExt,
     FutAsyncWriteExt,
 };
 
 mod endian;
+mod limits;
+pub use limits::{LimitedReader, MAX_PREALLOC_BYTES};
 mod types;
 
 /// Data which can be encoded in a consensus-consistent way.
@@ -80,6 +83,50 @@ pub fn deserialize<T: Decodable>(data: &[u8]) -> Result<T, Error> {
     Ok(rv)
 }
 
+/// Deserialize an object from a vector of at most `limit` bytes.
+/// Will error without decoding anything if the vector is larger.
+/// Streams are bounded with a [`LimitedReader`] instead.
+pub fn deserialize_limited<T: Decodable>(data: &[u8], limit: u64) -> Result<T, Error> {
+    if data.len() as u64 > limit {
+        return Err(limits::limit_exceeded())
+    }
+
+    deserialize(data)
+}
+
+/// Deserialize an object from a vector, enforcing its canonical encoding.
+/// Will error if said deserialization doesn't consume the entire vector,
+/// or if re-encoding the decoded object doesn't reproduce the exact input
+/// bytes. This rejects every malleable encoding (e.g. non-minimal VarInts,
+/// non-canonical field elements or booleans other than `0` and `1`), and
+/// should be used for all consensus-critical data.
+///
+/// For `#[serial_extensible]` structs, extension data for fields the
+/// decoder doesn't know about is rejected, since it can't be re-encoded.
+pub fn deserialize_strict<T: Encodable + Decodable>(data: &[u8]) -> Result<T, Error> {
+    let rv: T = deserialize(data)?;
+
+    // Fail if the data is not the canonical encoding of the decoded object.
+    if serialize(&rv) != data {
+        return Err(Error::new(ErrorKind::Other, "Non-canonical encoding on deserialization"))
+    }
+
+    Ok(rv)
+}
+
+/// Deserialize an object from a vector of at most `limit` bytes, enforcing
+/// its canonical encoding. See [`deserialize_limited`] and [`deserialize_strict`].
+pub fn deserialize_strict_limited<T: Encodable + Decodable>(
+    data: &[u8],
+    limit: u64,
+) -> Result<T, Error> {
+    if data.len() as u64 > limit {
+        return Err(limits::limit_exceeded())
+    }
+
+    deserialize_strict(data)
+}
+
 /// Extensions of `Write` to encode data as per Bitcoin consensus.
 pub trait WriteExt {
     /// Output a 128-bit unsigned int
@@ -481,7 +528,8 @@ impl<T: Decodable> Decodable for Vec<T> {
     fn decode<D: Read>(mut d: D) -> Result<Self, Error> {
         let len = VarInt::decode(&mut d)?.0;
         let mut ret = Vec::new();
-        ret.try_reserve(len as usize).map_err(|_| std::io::ErrorKind::InvalidData)?;
+        ret.try_reserve(limits::prealloc_len::<T>(len))
+            .map_err(|_| std::io::ErrorKind::InvalidData)?;
         for _ in 0..len {
             ret.push(Decodable::decode(&mut d)?);
         }
@@ -506,7 +554,8 @@ impl<T: Decodable> Decodable for VecDeque<T> {
     fn decode<D: Read>(mut d: D) -> Result<Self, Error> {
         let len = VarInt::decode(&mut d)?.0;
         let mut ret = VecDeque::new();
-        ret.try_reserve(len as usize).map_err(|_| std::io::ErrorKind::InvalidData)?;
+        ret.try_reserve(limits::prealloc_len::<T>(len))
+            .map_err(|_| std::io::ErrorKind::InvalidData)?;
         for _ in 0..len {
             ret.push_back(Decodable::decode(&mut d)?);
         }
@@ -588,8 +637,15 @@ impl Encodable for &str {
 
 impl Decodable for String {
     #[inline]
-    fn decode<D: Read>(d: D) -> Result<String, Error> {
-        match String::from_utf8(Decodable::decode(d)?) {
+    fn decode<D: Read>(mut d: D) -> Result<String, Error> {
+        let len = VarInt::decode(&mut d)?.0;
+
+        let mut buf = Vec::with_capacity(limits::prealloc_len::<u8>(len));
+        if (&mut d).take(len).read_to_end(&mut buf)? as u64 != len {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "String truncated"))
+        }
+
+        match String::from_utf8(buf) {
             Ok(v) => Ok(v),
             Err(_) => Err(Error::new(ErrorKind::Other, "Invalid UTF-8 for string")),
         }
@@ -1007,4 +1063,136 @@ mod tests {
         assert_eq!(ts1, ts1_n);
         assert_eq!(ts1_n, TestStruct1(baz));
     }
+
+    #[test]
+    fn deserialize_strict_test() {
+        let ts0 = TestStruct0 { foo: 44, bar: true, baz: String::from("foobarbaz") };
+        let ts0_s = serialize(&ts0);
+        assert_eq!(deserialize_strict::<TestStruct0>(&ts0_s).unwrap(), ts0);
+
+        // Any nonzero byte decodes as `true`, but only `1` is canonical
+        let mut malleated = ts0_s.clone();
+        malleated[8] = 2;
+        assert_eq!(deserialize::<TestStruct0>(&malleated).unwrap(), ts0);
+        assert!(deserialize_strict::<TestStruct0>(&malleated).is_err());
+
+        // Same goes for `Option` tags
+        assert!(deserialize::<Option<u8>>(&[2, 7]).is_ok());
+        assert!(deserialize_strict::<Option<u8>>(&[2, 7]).is_err());
+        assert_eq!(deserialize_strict::<Option<u8>>(&[1, 7]).unwrap(), Some(7));
+
+        // Trailing bytes
+        let mut trailing = ts0_s;
+        trailing.push(0);
+        assert!(deserialize_strict::<TestStruct0>(&trailing).is_err());
+
+        // Non-minimal VarInt
+        assert!(deserialize_strict::<VarInt>(&[0xfd, 0x01, 0x00]).is_err());
+
+        // Hashed collections encode in a fixed order regardless of insertion
+        let set_a: std::collections::HashSet<u32> = (0..64).collect();
+        let set_b: std::collections::HashSet<u32> = (0..64).rev().collect();
+        let set_s = serialize(&set_a);
+        assert_eq!(set_s, serialize(&set_b));
+        assert_eq!(deserialize_strict::<std::collections::HashSet<u32>>(&set_s).unwrap(), set_a);
+        let set_a_s = futures_lite::future::block_on(serialize_async(&set_a));
+        assert_eq!(set_a_s, set_s);
+
+        let map: std::collections::HashMap<u32, u8> = (0..64).map(|i| (i, i as u8)).collect();
+        let map_s = serialize(&map);
+        assert_eq!(deserialize_strict::<std::collections::HashMap<u32, u8>>(&map_s).unwrap(), map);
+        let map_a_s = futures_lite::future::block_on(serialize_async(&map));
+        assert_eq!(map_a_s, map_s);
+    }
+
+    #[test]
+    fn decode_limits_test() {
+        let v = serialize(&vec![1u32, 2, 3]);
+        assert_eq!(deserialize_limited::<Vec<u32>>(&v, v.len() as u64).unwrap(), vec![1, 2, 3]);
+        assert!(deserialize_limited::<Vec<u32>>(&v, v.len() as u64 - 1).is_err());
+        assert!(deserialize_strict_limited::<Vec<u32>>(&v, v.len() as u64 - 1).is_err());
+        assert!(futures_lite::future::block_on(deserialize_async_limited::<Vec<u32>>(
+            &v,
+            v.len() as u64 - 1
+        ))
+        .is_err());
+
+        // Readers fail once their budget is spent
+        let st = serialize(&String::from("darkfi"));
+        let mut reader = LimitedReader::new(Cursor::new(&st), st.len() as u64);
+        assert_eq!(String::decode(&mut reader).unwrap(), "darkfi");
+        assert_eq!(reader.remaining(), 0);
+        let mut reader = LimitedReader::new(Cursor::new(&st), 4);
+        assert!(String::decode(&mut reader).is_err());
+        let mut reader = LimitedReader::new(futures_lite::io::Cursor::new(&v), 8);
+        assert!(futures_lite::future::block_on(Vec::<u32>::decode_async(&mut reader)).is_err());
+
+        // A forged length prefix doesn't allocate before data arrives
+        let mut forged = serialize(&VarInt(u32::MAX as u64));
+        forged.extend_from_slice(&[0u8; 16]);
+        assert!(deserialize::<Vec<[u8; 32]>>(&forged).is_err());
+        assert!(deserialize::<String>(&forged).is_err());
+        assert!(futures_lite::future::block_on(deserialize_async::<Vec<u64>>(&forged)).is_err());
+    }
+
+    #[derive(Debug, PartialEq, SerialEncodable, SerialDecodable)]
+    #[serial_extensible]
+    struct ExtStructV1 {
+        foo: u64,
+        bar: String,
+    }
+
+    #[derive(Debug, PartialEq, SerialEncodable, SerialDecodable)]
+    #[serial_extensible]
+    struct ExtStructV2 {
+        foo: u64,
+        bar: String,
+        #[serial_ext]
+        baz: Option<u32>,
+        #[serial_ext]
+        qux: Vec<u8>,
+    }
+
+    #[test]
+    fn derive_extensible_struct() {
+        let v1 = ExtStructV1 { foo: 44, bar: String::from("foobarbaz") };
+        let v2 = ExtStructV2 {
+            foo: 44,
+            bar: String::from("foobarbaz"),
+            baz: Some(42),
+            qux: vec![1, 2, 3],
+        };
+
+        // Old decoders skip the fields they don't know about
+        let v2_s = serialize(&v2);
+        assert_eq!(deserialize::<ExtStructV2>(&v2_s).unwrap(), v2);
+        assert_eq!(deserialize::<ExtStructV1>(&v2_s).unwrap(), v1);
+
+        // New decoders default the fields missing from old encodings
+        let v1_s = serialize(&v1);
+        let v2_n = deserialize::<ExtStructV2>(&v1_s).unwrap();
+        assert_eq!(v2_n, ExtStructV2 { baz: None, qux: vec![], ..v2 });
+
+        // Old strict decoders reject extension data they can't re-encode
+        assert!(deserialize_strict::<ExtStructV1>(&v2_s).is_err());
+
+        // Defaulted trailing fields are left out, so data not using the
+        // new fields stays canonical for both old and new strict decoders
+        let v2_n_s = serialize(&v2_n);
+        assert_eq!(v2_n_s, v1_s);
+        assert_eq!(deserialize_strict::<ExtStructV1>(&v2_n_s).unwrap(), v1);
+        assert_eq!(deserialize_strict::<ExtStructV2>(&v1_s).unwrap(), v2_n);
+
+        // Only the trailing defaulted fields are trimmed
+        let v2_t =
+            ExtStructV2 { foo: 44, bar: String::from("foobarbaz"), baz: Some(42), qux: vec![] };
+        let v2_t_s = serialize(&v2_t);
+        assert!(v2_t_s.len() > v1_s.len());
+        assert_eq!(deserialize_strict::<ExtStructV2>(&v2_t_s).unwrap(), v2_t);
+
+        let v2_a = futures_lite::future::block_on(serialize_async(&v2_n));
+        assert_eq!(v2_a, serialize(&v2_n));
+        let v1_a = futures_lite::future::block_on(deserialize_async::<ExtStructV1>(&v2_s));
+        assert_eq!(v1_a.unwrap(), v1);
+    }
 }
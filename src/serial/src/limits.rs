@@ -0,0 +1,112 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Limits enforced while decoding untrusted data.
+//!
+//! Length prefixes are attacker-controlled, so decoders never preallocate
+//! more than [`MAX_PREALLOC_BYTES`] before the elements have actually been
+//! read. Every decoded element or string byte has to be read from the
+//! input, so bounding the input with a [`LimitedReader`] also bounds the
+//! element counts and string lengths a decode can produce. In-memory
+//! buffers are bounded with [`crate::deserialize_limited`].
+use std::{
+    io::{Error, ErrorKind, Read, Result},
+    mem::size_of,
+};
+
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "async")]
+use futures_lite::AsyncRead;
+
+/// Maximum number of bytes reserved for a collection up front. Larger
+/// collections grow as their elements are decoded.
+pub const MAX_PREALLOC_BYTES: usize = 1 << 20;
+
+/// Reader wrapper failing once more than a given number of bytes has been
+/// read from the inner reader. Decoding from it can't consume, and so
+/// can't allocate for, more data than the limit allows.
+pub struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R> LimitedReader<R> {
+    /// Wrap `inner`, allowing at most `limit` bytes to be read from it
+    pub fn new(inner: R, limit: u64) -> Self {
+        Self { inner, remaining: limit }
+    }
+
+    /// Number of bytes that can still be read
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Unwrap the inner reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Clamp a read buffer to the remaining budget, erroring once it's spent
+    fn clamp(&self, len: usize) -> Result<usize> {
+        if self.remaining == 0 && len > 0 {
+            return Err(limit_exceeded())
+        }
+
+        Ok(len.min(self.remaining.try_into().unwrap_or(usize::MAX)))
+    }
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let len = self.clamp(buf.len())?;
+        let n = self.inner.read(&mut buf[..len])?;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin> AsyncRead for LimitedReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let this = self.get_mut();
+        let len = this.clamp(buf.len())?;
+        let n = futures_lite::ready!(Pin::new(&mut this.inner).poll_read(cx, &mut buf[..len]))?;
+        this.remaining -= n as u64;
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// Error returned when decoding runs past its limit
+pub(crate) fn limit_exceeded() -> Error {
+    Error::new(ErrorKind::InvalidData, "Data exceeds decode limit")
+}
+
+/// Number of elements of `T` to reserve up front for a collection of `len`
+pub(crate) fn prealloc_len<T>(len: u64) -> usize {
+    let max = (MAX_PREALLOC_BYTES / size_of::<T>().max(1)) as u64;
+    len.min(max) as usize
+}
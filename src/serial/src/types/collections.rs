@@ -23,20 +23,25 @@ use std::{
 };
 
 #[cfg(feature = "async")]
-use crate::{AsyncDecodable, AsyncEncodable};
+use crate::{serialize_async, AsyncDecodable, AsyncEncodable, AsyncWriteExt};
 #[cfg(feature = "async")]
 use async_trait::async_trait;
 #[cfg(feature = "async")]
 use futures_lite::{AsyncRead, AsyncWrite};
 
-use crate::{Decodable, Encodable, VarInt};
+use crate::{serialize, Decodable, Encodable, VarInt, WriteExt};
 
+// Hashed collections iterate in an arbitrary order, so their entries are
+// written sorted by their encoding to keep it canonical.
 impl<T: Encodable> Encodable for HashSet<T> {
     fn encode<S: Write>(&self, mut s: S) -> Result<usize> {
         let mut len = 0;
         len += VarInt(self.len() as u64).encode(&mut s)?;
-        for c in self.iter() {
-            len += c.encode(&mut s)?;
+        let mut entries: Vec<Vec<u8>> = self.iter().map(serialize).collect();
+        entries.sort_unstable();
+        for c in entries {
+            s.write_slice(&c)?;
+            len += c.len();
         }
         Ok(len)
     }
@@ -48,8 +53,14 @@ impl<T: AsyncEncodable + Sync> AsyncEncodable for HashSet<T> {
     async fn encode_async<S: AsyncWrite + Unpin + Send>(&self, s: &mut S) -> Result<usize> {
         let mut len = 0;
         len += VarInt(self.len() as u64).encode_async(s).await?;
+        let mut entries = Vec::with_capacity(self.len());
         for c in self.iter() {
-            len += c.encode_async(s).await?;
+            entries.push(serialize_async(c).await);
+        }
+        entries.sort_unstable();
+        for c in entries {
+            s.write_slice_async(&c).await?;
+            len += c.len();
         }
         Ok(len)
     }
@@ -191,9 +202,13 @@ impl<T: Encodable, U: Encodable> Encodable for HashMap<T, U> {
     fn encode<S: Write>(&self, mut s: S) -> Result<usize> {
         let mut len = 0;
         len += VarInt(self.len() as u64).encode(&mut s)?;
-        for c in self.iter() {
-            len += c.0.encode(&mut s)?;
-            len += c.1.encode(&mut s)?;
+        let mut entries: Vec<(Vec<u8>, Vec<u8>)> =
+            self.iter().map(|c| (serialize(c.0), serialize(c.1))).collect();
+        entries.sort_unstable();
+        for c in entries {
+            s.write_slice(&c.0)?;
+            s.write_slice(&c.1)?;
+            len += c.0.len() + c.1.len();
         }
         Ok(len)
     }
@@ -205,9 +220,15 @@ impl<T: AsyncEncodable + Sync, U: AsyncEncodable + Sync> AsyncEncodable for Hash
     async fn encode_async<S: AsyncWrite + Unpin + Send>(&self, s: &mut S) -> Result<usize> {
         let mut len = 0;
         len += VarInt(self.len() as u64).encode_async(s).await?;
+        let mut entries = Vec::with_capacity(self.len());
         for c in self.iter() {
-            len += c.0.encode_async(s).await?;
-            len += c.1.encode_async(s).await?;
+            entries.push((serialize_async(c.0).await, serialize_async(c.1).await));
+        }
+        entries.sort_unstable();
+        for c in entries {
+            s.write_slice_async(&c.0).await?;
+            s.write_slice_async(&c.1).await?;
+            len += c.0.len() + c.1.len();
         }
         Ok(len)
     }
@@ -33,6 +33,10 @@ pub use lazy_weak::LazyWeak;
 pub mod stoppable_task;
 pub use stoppable_task::{StoppableTask, StoppableTaskPtr};
 
+/// Supervision of stoppable tasks with restart policies
+pub mod supervisor;
+pub use supervisor::{EscalationHandler, RestartPolicy, Supervisor, SupervisorPtr};
+
 /// Simple broadcast (publish-subscribe) class
 pub mod subscriber;
 pub use subscriber::{Subscriber, SubscriberPtr, Subscription};
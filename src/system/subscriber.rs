@@ -95,6 +95,11 @@ impl<T: Clone> Subscriber<T> {
         self.subs.lock().await.remove(&sub_id);
     }
 
+    /// Check if there are no listening subscriptions
+    pub async fn is_empty(&self) -> bool {
+        self.subs.lock().await.is_empty()
+    }
+
     /// Publish a message to all listening subscriptions.
     pub async fn notify(&self, message_result: T) {
         self.notify_with_exclude(message_result, &[]).await
@@ -0,0 +1,269 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    panic::AssertUnwindSafe,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+use smol::{
+    future::{Future, FutureExt},
+    lock::Mutex,
+    Executor, Timer,
+};
+
+use super::{StoppableTask, StoppableTaskPtr};
+use crate::{Error, Result};
+
+pub type SupervisorPtr = Arc<Supervisor>;
+
+/// Handler called when a supervised task keeps failing, with the task name
+/// and its last error. The supervisor stops restarting the task afterwards.
+pub type EscalationHandler = Arc<dyn Fn(&str, &Error) + Send + Sync>;
+
+/// What to do when a supervised task exits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Restart the task whenever it exits, including successfully
+    Always,
+    /// Restart the task right away when it fails or panics
+    OnFailure,
+    /// Restart the task when it fails or panics, doubling the delay
+    /// before each consecutive restart, up to `max`
+    Backoff { initial: Duration, max: Duration },
+}
+
+/// Supervises long-running [`StoppableTask`]s, restarting them according to
+/// their [`RestartPolicy`] when they fail or panic. Tasks failing more than
+/// `max_failures` times within `failure_window` are given up on, and their
+/// last error is escalated to the configured [`EscalationHandler`].
+///
+/// ```rust
+///     let supervisor = Supervisor::new(5, Duration::from_secs(60), escalation_handler);
+///     supervisor.spawn("sync", RestartPolicy::OnFailure, move || my_method(), executor).await;
+///     ...
+///     supervisor.stop().await;
+/// ```
+pub struct Supervisor {
+    /// Supervised tasks, by name
+    children: Mutex<HashMap<String, StoppableTaskPtr>>,
+    /// Failures tolerated within `failure_window` before escalating
+    max_failures: usize,
+    /// Window in which failures are counted
+    failure_window: Duration,
+    /// Handler called when a task keeps failing
+    escalation: EscalationHandler,
+}
+
+impl Supervisor {
+    pub fn new(
+        max_failures: usize,
+        failure_window: Duration,
+        escalation: EscalationHandler,
+    ) -> SupervisorPtr {
+        Arc::new(Self {
+            children: Mutex::new(HashMap::new()),
+            max_failures,
+            failure_window,
+            escalation,
+        })
+    }
+
+    /// Spawn a supervised task, built by calling `factory` on every (re)start.
+    /// A task already supervised under the same name is stopped first.
+    pub async fn spawn<F, Fut>(
+        self: &Arc<Self>,
+        name: &str,
+        policy: RestartPolicy,
+        factory: F,
+        executor: Arc<Executor<'static>>,
+    ) where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        if let Some(task) = self.children.lock().await.remove(name) {
+            task.stop().await;
+        }
+
+        let task = StoppableTask::new();
+        let name_ = name.to_string();
+        task.clone().start(
+            self.clone().supervise(name_.clone(), policy, factory),
+            |res| async move {
+                match res {
+                    Ok(()) | Err(Error::DetachedTaskStopped) => { /* Do nothing */ }
+                    Err(e) => error!(
+                        target: "system::supervisor",
+                        "Supervised task {} gave up: {}", name_, e,
+                    ),
+                }
+            },
+            Error::DetachedTaskStopped,
+            executor,
+        );
+
+        self.children.lock().await.insert(name.to_string(), task);
+    }
+
+    /// Stop all supervised tasks.
+    pub async fn stop(&self) {
+        let children: Vec<_> = self.children.lock().await.drain().collect();
+        for (name, task) in children {
+            info!(target: "system::supervisor", "Stopping supervised task {}...", name);
+            task.stop().await;
+        }
+    }
+
+    /// Run a task to completion, restarting it according to `policy`.
+    async fn supervise<F, Fut>(
+        self: Arc<Self>,
+        name: String,
+        policy: RestartPolicy,
+        factory: F,
+    ) -> Result<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let mut failures = VecDeque::new();
+        let mut backoff = None;
+
+        loop {
+            let err = match AssertUnwindSafe(factory()).catch_unwind().await {
+                Ok(Ok(())) if policy != RestartPolicy::Always => return Ok(()),
+                Ok(Ok(())) => {
+                    info!(target: "system::supervisor", "Task {} exited, restarting", name);
+                    backoff = None;
+                    continue
+                }
+                Ok(Err(e)) => e,
+                Err(panic) => Error::TaskPanicked(panic_message(panic)),
+            };
+
+            warn!(target: "system::supervisor", "Task {} failed: {}", name, err);
+
+            // Forget failures outside the window, and escalate if the
+            // task keeps failing within it.
+            let now = Instant::now();
+            failures.push_back(now);
+            while failures.front().is_some_and(|t| now.duration_since(*t) > self.failure_window) {
+                failures.pop_front();
+            }
+            if failures.len() > self.max_failures {
+                (self.escalation)(&name, &err);
+                return Err(err)
+            }
+
+            // A failure after a quiet period starts over from the initial delay
+            if failures.len() == 1 {
+                backoff = None;
+            }
+
+            if let RestartPolicy::Backoff { initial, max } = policy {
+                let delay = backoff.map_or(initial, |d: Duration| (d * 2).min(max));
+                info!(
+                    target: "system::supervisor",
+                    "Restarting task {} in {}ms", name, delay.as_millis(),
+                );
+                Timer::after(delay).await;
+                backoff = Some(delay);
+            }
+        }
+    }
+}
+
+/// Extract a readable message from a panic payload
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = panic.downcast_ref::<&str>() {
+        return msg.to_string()
+    }
+
+    if let Some(msg) = panic.downcast_ref::<String>() {
+        return msg.clone()
+    }
+
+    String::from("unknown panic")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    #[test]
+    fn supervisor_restarts_and_escalates() {
+        let executor = Arc::new(Executor::new());
+        let executor_ = executor.clone();
+        smol::block_on(executor.run(async move {
+            let (escalation_tx, escalation_rx) = smol::channel::unbounded();
+            let supervisor = Supervisor::new(
+                2,
+                Duration::from_secs(60),
+                Arc::new(move |name: &str, _: &Error| {
+                    escalation_tx.try_send(name.to_string()).unwrap();
+                }),
+            );
+
+            // Task panicking twice before succeeding gets restarted
+            let runs = Arc::new(AtomicUsize::new(0));
+            let (done_tx, done_rx) = smol::channel::bounded(1);
+            let runs_ = runs.clone();
+            supervisor
+                .spawn(
+                    "flaky",
+                    RestartPolicy::OnFailure,
+                    move || {
+                        let runs = runs_.clone();
+                        let done_tx = done_tx.clone();
+                        async move {
+                            if runs.fetch_add(1, SeqCst) < 2 {
+                                panic!("flaky task")
+                            }
+                            done_tx.send(()).await.unwrap();
+                            Ok(())
+                        }
+                    },
+                    executor_.clone(),
+                )
+                .await;
+            done_rx.recv().await.unwrap();
+            assert_eq!(runs.load(SeqCst), 3);
+
+            // Task that keeps failing gets escalated
+            let policy = RestartPolicy::Backoff {
+                initial: Duration::from_millis(1),
+                max: Duration::from_millis(4),
+            };
+            supervisor
+                .spawn(
+                    "broken",
+                    policy,
+                    || async { Err(Error::Custom(String::from("broken task"))) },
+                    executor_,
+                )
+                .await;
+            assert_eq!(escalation_rx.recv().await.unwrap(), "broken");
+
+            supervisor.stop().await;
+        }))
+    }
+}
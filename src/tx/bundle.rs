@@ -0,0 +1,189 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Transaction bundles for offline signing.
+//!
+//! A [`TransactionBundle`] carries a transaction whose calls and proofs are
+//! final, along with the public keys each call must be signed with and the
+//! signatures collected so far. An online machine builds the proofs and
+//! exports the bundle, offline machines holding the secret keys add their
+//! signatures with [`TransactionBundle::sign`], and the resulting bundles
+//! get merged and finalized into a broadcastable [`Transaction`].
+
+use darkfi_sdk::crypto::{
+    schnorr::{SchnorrPublic, Signature},
+    PublicKey,
+};
+#[cfg(feature = "async-serial")]
+use darkfi_serial::async_trait;
+use darkfi_serial::{SerialDecodable, SerialEncodable};
+
+use super::{signer::Signer, Transaction};
+use crate::{Error, Result};
+
+/// A [`Transaction`] awaiting its signatures
+#[derive(Clone, Eq, PartialEq, SerialEncodable, SerialDecodable)]
+pub struct TransactionBundle {
+    /// Transaction with its calls and proofs, and without signatures
+    pub tx: Transaction,
+    /// Public keys each call has to be signed with
+    pub signers: Vec<Vec<PublicKey>>,
+    /// Signatures collected so far, in the order of `signers`
+    pub signatures: Vec<Vec<Option<Signature>>>,
+}
+
+impl TransactionBundle {
+    /// Create a bundle for the given transaction, where each call has to be
+    /// signed with the corresponding public keys in `signers`. Any signature
+    /// already attached to the transaction is discarded.
+    pub fn new(mut tx: Transaction, signers: Vec<Vec<PublicKey>>) -> Result<Self> {
+        if signers.len() != tx.calls.len() {
+            return Err(Error::TxBundleError(format!(
+                "Expected signers for {} calls, got {}",
+                tx.calls.len(),
+                signers.len()
+            )))
+        }
+
+        tx.signatures = vec![];
+        let signatures = signers.iter().map(|keys| vec![None; keys.len()]).collect();
+        Ok(Self { tx, signers, signatures })
+    }
+
+    /// Hash signed by every signature of the bundle. Signers should compute
+    /// it themselves rather than trusting a given value.
+    pub fn sighash(&self) -> Result<blake3::Hash> {
+        self.tx.sighash()
+    }
+
+    /// Add the missing signatures the given [`Signer`] is able to produce,
+    /// returning how many were added.
+    pub fn sign(&mut self, signer: &dyn Signer) -> Result<usize> {
+        let mut added = 0;
+        for (keys, sigs) in self.signers.iter().zip(self.signatures.iter_mut()) {
+            for (public, sig) in keys.iter().zip(sigs.iter_mut()) {
+                if sig.is_some() {
+                    continue
+                }
+
+                if let Ok(signature) = signer.sign(&self.tx, public) {
+                    *sig = Some(signature);
+                    added += 1;
+                }
+            }
+        }
+
+        Ok(added)
+    }
+
+    /// Merge the signatures of another bundle of the same transaction into
+    /// this one. Incoming signatures get verified before being accepted.
+    pub fn merge(&mut self, other: &Self) -> Result<()> {
+        if self.tx != other.tx || self.signers != other.signers {
+            return Err(Error::TxBundleError("Bundles are for different transactions".to_string()))
+        }
+
+        let sighash = self.sighash()?;
+        for ((keys, sigs), other_sigs) in
+            self.signers.iter().zip(self.signatures.iter_mut()).zip(other.signatures.iter())
+        {
+            if other_sigs.len() != keys.len() {
+                return Err(Error::TxBundleError("Malformed bundle signatures".to_string()))
+            }
+
+            for ((public, sig), other_sig) in keys.iter().zip(sigs.iter_mut()).zip(other_sigs) {
+                if sig.is_some() {
+                    continue
+                }
+                let Some(other_sig) = other_sig else { continue };
+
+                if !public.verify(sighash.as_bytes(), other_sig) {
+                    return Err(Error::InvalidSignature)
+                }
+                *sig = Some(*other_sig);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check if every signature of the bundle has been collected
+    pub fn is_complete(&self) -> bool {
+        self.signatures.iter().flatten().all(|sig| sig.is_some())
+    }
+
+    /// Attach the collected signatures to the transaction, and verify them.
+    pub fn finalize(self) -> Result<Transaction> {
+        if !self.is_complete() {
+            return Err(Error::TxBundleError("Bundle is missing signatures".to_string()))
+        }
+
+        let mut tx = self.tx;
+        tx.signatures =
+            self.signatures.into_iter().map(|sigs| sigs.into_iter().flatten().collect()).collect();
+        tx.verify_sigs(self.signers)?;
+
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use darkfi_sdk::{
+        crypto::{schnorr::SchnorrSecret, SecretKey, MONEY_CONTRACT_ID},
+        dark_tree::DarkLeaf,
+        tx::ContractCall,
+    };
+    use darkfi_serial::{deserialize, serialize};
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::tx::signer::LocalSigner;
+
+    #[test]
+    fn bundle_offline_signing() {
+        let call = ContractCall { contract_id: *MONEY_CONTRACT_ID, data: vec![0] };
+        let tx = Transaction {
+            calls: vec![DarkLeaf { data: call, parent_index: None, children_indexes: vec![] }],
+            proofs: vec![vec![]],
+            signatures: vec![],
+        };
+
+        let secrets = [SecretKey::random(&mut OsRng), SecretKey::random(&mut OsRng)];
+        let signers = vec![secrets.iter().map(|s| PublicKey::from_secret(*s)).collect()];
+        let bundle = TransactionBundle::new(tx, signers).unwrap();
+        assert!(!bundle.is_complete());
+
+        // Two offline machines each sign with their own key
+        let mut first: TransactionBundle = deserialize(&serialize(&bundle)).unwrap();
+        assert_eq!(first.sign(&LocalSigner::new(&secrets[..1])).unwrap(), 1);
+        let mut second = bundle.clone();
+        assert_eq!(second.sign(&LocalSigner::new(&secrets[1..])).unwrap(), 1);
+        assert!(first.clone().finalize().is_err());
+
+        // Forged signatures are rejected
+        let mut forged = bundle.clone();
+        forged.signatures[0][0] = Some(SecretKey::random(&mut OsRng).sign(b"forged"));
+        assert!(first.clone().merge(&forged).is_err());
+
+        first.merge(&second).unwrap();
+        assert!(first.is_complete());
+        let tx = first.finalize().unwrap();
+        assert_eq!(tx.signatures[0].len(), 2);
+    }
+}
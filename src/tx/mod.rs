@@ -20,7 +20,7 @@ use std::collections::HashMap;
 
 use darkfi_sdk::{
     crypto::{
-        schnorr::{SchnorrPublic, SchnorrSecret, Signature},
+        schnorr::{SchnorrPublic, Signature},
         PublicKey, SecretKey,
     },
     dark_tree::{dark_forest_leaf_vec_integrity_check, DarkForest, DarkLeaf, DarkTree},
@@ -38,10 +38,20 @@ use log::{debug, error};
 
 use crate::{
     error::TxVerifyFailed,
-    zk::{proof::VerifyingKey, Proof},
+    zk::{
+        proof::{verify_batch, VerifyingKey},
+        Proof,
+    },
     Error, Result,
 };
 
+/// Transaction signers
+pub mod signer;
+use signer::{LocalSigner, Signer};
+
+/// Transaction bundles for offline signing
+pub mod bundle;
+
 macro_rules! zip {
     ($x:expr) => ($x);
     ($x:expr, $($y:expr), +) => (
@@ -49,6 +59,43 @@ macro_rules! zip {
     )
 }
 
+/// ZK proofs collected from a set of transactions, along with the keys of
+/// their verifying keys and their public inputs, so they can be verified
+/// at once.
+#[derive(Default)]
+pub struct ZkpBatch {
+    entries: Vec<([u8; 32], String, Proof, Vec<pallas::Base>)>,
+}
+
+impl ZkpBatch {
+    /// Check if the batch contains no proofs.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Verify all the collected proofs at once, using the given verifying
+    /// keys. Returns `false` if any proof is invalid, or its verifying key
+    /// is missing.
+    pub fn verify(
+        &self,
+        verifying_keys: &HashMap<[u8; 32], HashMap<String, VerifyingKey>>,
+    ) -> bool {
+        let mut proofs = Vec::with_capacity(self.entries.len());
+        let mut vks = Vec::with_capacity(self.entries.len());
+        let mut public_inputs = Vec::with_capacity(self.entries.len());
+        for (contract_id, zk_ns, proof, public_vals) in &self.entries {
+            let Some(vk) = verifying_keys.get(contract_id).and_then(|m| m.get(zk_ns)) else {
+                return false
+            };
+            proofs.push(proof);
+            vks.push(vk);
+            public_inputs.push(public_vals.as_slice());
+        }
+
+        verify_batch(&proofs, &vks, &public_inputs)
+    }
+}
+
 // ANCHOR: transaction
 /// A Transaction contains an arbitrary number of `ContractCall` objects,
 /// along with corresponding ZK proofs and Schnorr signatures. `DarkLeaf`
@@ -119,13 +166,58 @@ impl Transaction {
         Ok(())
     }
 
-    /// Verify Schnorr signatures for the entire transaction.
-    pub fn verify_sigs(&self, pub_table: Vec<Vec<PublicKey>>) -> Result<()> {
-        // Hash the transaction without the signatures
+    /// Collect the ZK proofs of the transaction into the given batch, instead
+    /// of verifying them right away. The batch is left untouched if any of
+    /// the verifying keys is missing.
+    pub fn batch_zkps(
+        &self,
+        verifying_keys: &HashMap<[u8; 32], HashMap<String, VerifyingKey>>,
+        zkp_table: Vec<Vec<(String, Vec<pallas::Base>)>>,
+        batch: &mut ZkpBatch,
+    ) -> Result<()> {
+        if self.calls.len() != self.proofs.len() || self.calls.len() != zkp_table.len() {
+            return Err(TxVerifyFailed::InvalidZkProof.into())
+        }
+
+        let mut entries = vec![];
+        for (call, (proofs, pubvals)) in zip!(self.calls, self.proofs, zkp_table) {
+            if proofs.len() != pubvals.len() {
+                return Err(TxVerifyFailed::InvalidZkProof.into())
+            }
+
+            let contract_id = call.data.contract_id.to_bytes();
+            for (proof, (zk_ns, public_vals)) in proofs.iter().zip(pubvals.iter()) {
+                let has_vk =
+                    verifying_keys.get(&contract_id).is_some_and(|m| m.contains_key(zk_ns));
+                if !has_vk {
+                    error!(
+                        target: "tx::batch_zkps",
+                        "[TX] {}::{} circuit VK nonexistent",
+                        call.data.contract_id, zk_ns,
+                    );
+                    return Err(TxVerifyFailed::InvalidZkProof.into())
+                }
+
+                entries.push((contract_id, zk_ns.clone(), proof.clone(), public_vals.clone()));
+            }
+        }
+
+        batch.entries.extend(entries);
+        Ok(())
+    }
+
+    /// Hash the transaction without the signatures, producing the
+    /// message every signature is made over.
+    pub fn sighash(&self) -> Result<blake3::Hash> {
         let mut hasher = blake3::Hasher::new();
         self.calls.encode(&mut hasher)?;
         self.proofs.encode(&mut hasher)?;
-        let data_hash = hasher.finalize();
+        Ok(hasher.finalize())
+    }
+
+    /// Verify Schnorr signatures for the entire transaction.
+    pub fn verify_sigs(&self, pub_table: Vec<Vec<PublicKey>>) -> Result<()> {
+        let data_hash = self.sighash()?;
 
         debug!(
             target: "tx::verify_sigs",
@@ -159,11 +251,18 @@ impl Transaction {
 
     /// Create Schnorr signatures for the entire transaction.
     pub fn create_sigs(&self, secret_keys: &[SecretKey]) -> Result<Vec<Signature>> {
-        // Hash the transaction without the signatures
-        let mut hasher = blake3::Hasher::new();
-        self.calls.encode(&mut hasher)?;
-        self.proofs.encode(&mut hasher)?;
-        let data_hash = hasher.finalize();
+        let public_keys: Vec<_> = secret_keys.iter().map(|s| PublicKey::from_secret(*s)).collect();
+        self.create_sigs_with(&LocalSigner::new(secret_keys), &public_keys)
+    }
+
+    /// Create Schnorr signatures for the entire transaction with the given
+    /// [`Signer`], for each of the given public keys.
+    pub fn create_sigs_with(
+        &self,
+        signer: &dyn Signer,
+        public_keys: &[PublicKey],
+    ) -> Result<Vec<Signature>> {
+        let data_hash = self.sighash()?;
 
         debug!(
             target: "tx::create_sigs",
@@ -171,12 +270,12 @@ impl Transaction {
         );
 
         let mut sigs = vec![];
-        for secret in secret_keys {
+        for public in public_keys {
             debug!(
                 target: "tx::create_sigs",
-                "[TX] Creating signature with public key: {}", PublicKey::from_secret(*secret),
+                "[TX] Creating signature with public key: {}", public,
             );
-            let signature = secret.sign(&data_hash.as_bytes()[..]);
+            let signature = signer.sign(self, public)?;
             sigs.push(signature);
         }
 
@@ -217,7 +316,7 @@ impl std::fmt::Debug for Transaction {
 use crate::net::Message;
 
 #[cfg(feature = "net")]
-crate::impl_p2p_message!(Transaction, "tx");
+crate::impl_p2p_message!(Transaction, "tx", MAX_TX_SIZE);
 
 /// Calls tree bounds definitions
 // TODO: increase min to 2 when fees are implement
@@ -225,6 +324,11 @@ pub const MIN_TX_CALLS: usize = 1;
 // TODO: verify max value
 pub const MAX_TX_CALLS: usize = 20;
 
+/// Maximum encoded transaction size, in bytes. Larger transactions are
+/// rejected before being decoded.
+// TODO: verify max value
+pub const MAX_TX_SIZE: u64 = 128 * 1024;
+
 /// Auxiliarry structure containing all the information
 /// required to execute a contract call.
 #[derive(Clone)]
@@ -0,0 +1,234 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Transaction signers.
+//!
+//! A [`Signer`] produces the Schnorr signatures of a transaction for the
+//! public keys it holds the secrets of. Signers are handed the transaction
+//! itself rather than its signature hash, so an external device can show
+//! what it's about to sign and compute the hash on its own.
+//!
+//! [`LocalSigner`] keeps secret keys in memory, while [`SocketSigner`]
+//! forwards the signing requests to an external process, such as a hardware
+//! wallet bridge, listening on a local socket served with [`serve_signer`].
+//! Both ends share a secret authentication key: the serving end opens every
+//! connection with a random challenge, and only answers requests carrying a
+//! keyed hash of the challenge and the request under that key.
+
+#[cfg(unix)]
+use std::{
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+};
+
+#[cfg(unix)]
+use darkfi_sdk::crypto::schnorr::SchnorrPublic;
+use darkfi_sdk::crypto::{
+    schnorr::{SchnorrSecret, Signature},
+    PublicKey, SecretKey,
+};
+#[cfg(all(unix, feature = "async-serial"))]
+use darkfi_serial::async_trait;
+#[cfg(unix)]
+use darkfi_serial::{serialize, Decodable, Encodable, SerialDecodable, SerialEncodable};
+#[cfg(unix)]
+use log::{debug, error, info};
+#[cfg(unix)]
+use rand::{rngs::OsRng, RngCore};
+
+use super::Transaction;
+use crate::{Error, Result};
+
+/// Producer of signatures for a set of public keys
+pub trait Signer {
+    /// Sign the signature hash of `tx` with the secret key of `public`
+    fn sign(&self, tx: &Transaction, public: &PublicKey) -> Result<Signature>;
+}
+
+/// [`Signer`] over secret keys held in memory
+pub struct LocalSigner {
+    keys: Vec<(PublicKey, SecretKey)>,
+}
+
+impl LocalSigner {
+    /// Create a signer over the given secret keys
+    pub fn new(secrets: &[SecretKey]) -> Self {
+        Self { keys: secrets.iter().map(|s| (PublicKey::from_secret(*s), *s)).collect() }
+    }
+}
+
+impl Signer for LocalSigner {
+    fn sign(&self, tx: &Transaction, public: &PublicKey) -> Result<Signature> {
+        match self.keys.iter().find(|(p, _)| p == public) {
+            Some((_, secret)) => Ok(secret.sign(tx.sighash()?.as_bytes())),
+            None => Err(Error::SignerError(format!("Unknown public key {public}"))),
+        }
+    }
+}
+
+/// Signing request sent to a [`serve_signer`] socket
+#[cfg(unix)]
+#[derive(SerialEncodable, SerialDecodable)]
+struct SignRequest {
+    tx: Transaction,
+    public: PublicKey,
+    /// Keyed hash of the connection challenge and the request
+    auth: [u8; 32],
+}
+
+/// Compute the authentication hash of a signing request under the given key
+#[cfg(unix)]
+fn request_auth(
+    auth_key: &[u8; 32],
+    challenge: &[u8; 32],
+    tx: &Transaction,
+    public: &PublicKey,
+) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new_keyed(auth_key);
+    hasher.update(challenge);
+    hasher.update(&serialize(tx));
+    hasher.update(&serialize(public));
+    hasher.finalize()
+}
+
+/// [`Signer`] forwarding requests to a [`serve_signer`] socket. Each request
+/// gets answered with the signature, or `None` if the key is unknown to the
+/// serving end. Returned signatures are verified before being accepted.
+#[cfg(unix)]
+pub struct SocketSigner {
+    path: PathBuf,
+    auth_key: [u8; 32],
+}
+
+#[cfg(unix)]
+impl SocketSigner {
+    /// Create a signer connecting to the socket at the given path, and
+    /// authenticating its requests with the given key
+    pub fn new(path: &Path, auth_key: [u8; 32]) -> Self {
+        Self { path: path.to_path_buf(), auth_key }
+    }
+}
+
+#[cfg(unix)]
+impl Signer for SocketSigner {
+    fn sign(&self, tx: &Transaction, public: &PublicKey) -> Result<Signature> {
+        let mut stream = UnixStream::connect(&self.path)?;
+
+        let mut challenge = [0u8; 32];
+        stream.read_exact(&mut challenge)?;
+
+        let auth = *request_auth(&self.auth_key, &challenge, tx, public).as_bytes();
+        let request = SignRequest { tx: tx.clone(), public: *public, auth };
+        request.encode(&mut stream)?;
+        stream.flush()?;
+
+        let Some(signature) = Option::<Signature>::decode(&mut stream)? else {
+            return Err(Error::SignerError(format!("Signer refused public key {public}")))
+        };
+
+        if !public.verify(tx.sighash()?.as_bytes(), &signature) {
+            return Err(Error::SignerError(format!("Invalid signature for public key {public}")))
+        }
+
+        Ok(signature)
+    }
+}
+
+/// Answer the signing requests of [`SocketSigner`] clients connecting to
+/// the given listener with the given signer, one request per connection.
+/// Requests not authenticated with `auth_key` are dropped.
+#[cfg(unix)]
+pub fn serve_signer(
+    listener: &UnixListener,
+    signer: &dyn Signer,
+    auth_key: &[u8; 32],
+) -> Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let mut reader = BufReader::new(&stream);
+        let mut writer = BufWriter::new(&stream);
+
+        let mut challenge = [0u8; 32];
+        OsRng.fill_bytes(&mut challenge);
+        writer.write_all(&challenge)?;
+        writer.flush()?;
+
+        let request = match SignRequest::decode(&mut reader) {
+            Ok(r) => r,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => continue,
+            Err(e) => {
+                error!(target: "tx::signer::serve_signer", "Malformed signing request: {e}");
+                continue
+            }
+        };
+
+        // blake3::Hash equality runs in constant time
+        let auth = request_auth(auth_key, &challenge, &request.tx, &request.public);
+        if auth != blake3::Hash::from(request.auth) {
+            error!(target: "tx::signer::serve_signer", "Unauthenticated signing request");
+            continue
+        }
+
+        info!(
+            target: "tx::signer::serve_signer",
+            "Signing transaction {} with public key: {}", request.tx.hash(), request.public,
+        );
+        debug!(target: "tx::signer::serve_signer", "{:#?}", request.tx);
+        let signature = signer.sign(&request.tx, &request.public).ok();
+        signature.encode(&mut writer)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socket_signer() {
+        let secret = SecretKey::random(&mut OsRng);
+        let public = PublicKey::from_secret(secret);
+        let tx = Transaction::default();
+
+        let mut auth_key = [0u8; 32];
+        OsRng.fill_bytes(&mut auth_key);
+
+        let path = std::env::temp_dir().join(format!("darkfi_signer_{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            serve_signer(&listener, &LocalSigner::new(&[secret]), &auth_key)
+        });
+
+        let signer = SocketSigner::new(&path, auth_key);
+        let signature = signer.sign(&tx, &public).unwrap();
+        assert!(public.verify(tx.sighash().unwrap().as_bytes(), &signature));
+
+        let unknown = PublicKey::from_secret(SecretKey::random(&mut OsRng));
+        assert!(signer.sign(&tx, &unknown).is_err());
+
+        // Requests authenticated with another key go unanswered
+        let forger = SocketSigner::new(&path, [0u8; 32]);
+        assert!(forger.sign(&tx, &public).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -80,6 +80,13 @@ pub fn get_log_level(verbosity_level: u8) -> simplelog::LevelFilter {
     }
 }
 
+/// Change the maximum log level at runtime. The loggers set up by
+/// [`async_daemonize`] let everything through and rely on this global
+/// filter, so verbosity can be changed without reinitializing them.
+pub fn set_log_level(verbosity_level: u8) {
+    log::set_max_level(get_log_level(verbosity_level));
+}
+
 pub fn get_log_config(verbosity_level: u8) -> simplelog::Config {
     match env::var("LOG_TARGETS") {
         Ok(x) => {
@@ -173,7 +180,9 @@ macro_rules! async_daemonize {
             darkfi::util::cli::spawn_config(&cfg_path, CONFIG_FILE_CONTENTS.as_bytes())?;
             let args = Args::from_args_with_toml(&std::fs::read_to_string(cfg_path)?).unwrap();
 
-            let log_level = darkfi::util::cli::get_log_level(args.verbose);
+            // Loggers are created permissive and filtered by the global max
+            // level, so it can be changed at runtime with `set_log_level()`.
+            let log_level = simplelog::LevelFilter::Trace;
             let log_config = darkfi::util::cli::get_log_config(args.verbose);
 
             // Setup terminal logger
@@ -197,6 +206,7 @@ macro_rules! async_daemonize {
                     simplelog::CombinedLogger::init(vec![term_logger])?;
                 }
             }
+            darkfi::util::cli::set_log_level(args.verbose);
 
             // https://docs.rs/smol/latest/smol/struct.Executor.html#examples
             let n_threads = std::thread::available_parallelism().unwrap().get();
@@ -168,6 +168,9 @@ pub fn decode(data: &str) -> Option<Vec<u8>> {
     }
 
     let data = data.as_bytes();
+    if data.is_empty() {
+        return Some(vec![])
+    }
 
     let mut len = data.len();
 
@@ -240,7 +243,8 @@ mod tests {
 
     #[test]
     pub fn b64_encdec() {
-        const EXAMPLES: [(&[u8], &str); 3] = [
+        const EXAMPLES: [(&[u8], &str); 4] = [
+            (b"", ""),
             (b"abc123!?$*&()'-=@~", "YWJjMTIzIT8kKiYoKSctPUB+"),
             (b"gm world", "Z20gd29ybGQ="),
             (
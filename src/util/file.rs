@@ -17,8 +17,11 @@
  */
 
 use std::{
-    fs::File,
-    io::{BufReader, Read, Write},
+    ffi::CString,
+    fs::{self, File},
+    io::{self, BufReader, Read, Write},
+    mem,
+    os::unix::ffi::OsStrExt,
     path::Path,
 };
 
@@ -56,3 +59,35 @@ pub fn save_json_file(path: &Path, value: &JsonValue, pretty: bool) -> Result<()
 
     Ok(())
 }
+
+/// Returns the total size in bytes of the files under given path,
+/// recursing into directories. Symlinks are not followed.
+pub fn dir_size(path: &Path) -> Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len())
+    }
+
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += dir_size(&entry?.path())?;
+    }
+
+    Ok(size)
+}
+
+/// Returns the space in bytes available to unprivileged users on the
+/// filesystem containing given path.
+pub fn free_space(path: &Path) -> Result<u64> {
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput).into())
+    };
+
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error().into())
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
@@ -41,3 +41,7 @@ pub mod ringbuffer;
 /// This is an insecure PRNG used for simulations and tests.
 #[cfg(feature = "rand")]
 pub mod pcg;
+
+/// OpenTelemetry (OTLP) export of tracing spans
+#[cfg(feature = "otlp")]
+pub mod telemetry;
@@ -0,0 +1,61 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Opt-in export of `tracing` spans to an OpenTelemetry collector.
+//!
+//! Subsystems are instrumented with `tracing` spans carrying identifiers
+//! such as block hashes, peer addresses and JSON-RPC request IDs. Their
+//! events are always forwarded to the `log` facade, so terminal and file
+//! logging are unaffected. When OTLP export is enabled, spans and the
+//! events within them are additionally shipped to the given collector,
+//! where they can be correlated across subsystems.
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime::AsyncStd, trace, Resource};
+use tracing_subscriber::layer::SubscriberExt;
+use url::Url;
+
+use crate::{Error, Result};
+
+/// Install a global `tracing` subscriber exporting spans over OTLP/HTTP to
+/// the given collector endpoint, e.g. `http://127.0.0.1:4318/v1/traces`.
+/// Spans are tagged with `service_name`.
+pub fn init_otlp_tracing(endpoint: &Url, service_name: &'static str) -> Result<()> {
+    let exporter = opentelemetry_otlp::new_exporter().http().with_endpoint(endpoint.as_str());
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(
+            trace::config()
+                .with_resource(Resource::new(vec![KeyValue::new("service.name", service_name)])),
+        )
+        .install_batch(AsyncStd)
+        .map_err(|e| Error::Custom(format!("Failed installing OTLP exporter: {}", e)))?;
+
+    let subscriber =
+        tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| Error::Custom(format!("Failed installing tracing subscriber: {}", e)))
+}
+
+/// Flush pending spans and shut down the OTLP exporter.
+pub fn shutdown_otlp_tracing() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
@@ -33,7 +33,7 @@ use crate::{
         block_store::{BlockDifficulty, BlockRanks},
         BlockInfo, Blockchain, BlockchainOverlay, BlockchainOverlayPtr, Header, HeaderHash,
     },
-    tx::Transaction,
+    tx::{Transaction, MAX_TX_SIZE},
     util::time::Timestamp,
     validator::{
         pow::PoWModule,
@@ -46,6 +46,9 @@ use crate::{
 // Consensus configuration
 /// Block/proposal maximum transactions, exluding producer transaction
 pub const TXS_CAP: usize = 50;
+/// Block/proposal maximum encoded size, in bytes. Fits `TXS_CAP` transactions
+/// plus the producer transaction, with headroom for the header and signature.
+pub const MAX_BLOCK_SIZE: u64 = (TXS_CAP as u64 + 2) * MAX_TX_SIZE;
 
 /// This struct represents the information required by the consensus algorithm
 pub struct Consensus {
@@ -79,6 +79,7 @@ pub fn circuit_gas_use(zkbin: &ZkBinary) -> u64 {
             Opcode::LessThanStrict => 100,
             Opcode::LessThanLoose => 100,
             Opcode::BoolCheck => 20,
+            Opcode::TableLookup => 20,
             Opcode::CondSelect => 10,
             Opcode::ZeroCondSelect => 10,
             Opcode::ConstrainEqualBase => 10,
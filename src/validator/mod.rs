@@ -19,13 +19,14 @@
 use std::sync::Arc;
 
 use darkfi_sdk::crypto::MerkleTree;
-use log::{debug, error, info, warn};
 use num_bigint::BigUint;
 use smol::lock::RwLock;
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
 
 use crate::{
     blockchain::{
         block_store::{BlockDifficulty, BlockInfo, BlockRanks},
+        contract_store::GAS_SCHEDULE_UPGRADES,
         Blockchain, BlockchainOverlay,
     },
     error::TxVerifyFailed,
@@ -81,6 +82,9 @@ pub struct Validator {
     pub consensus: Consensus,
     /// Flag signalling node has finished initial sync
     pub synced: RwLock<bool>,
+    /// Flag signalling node refuses to write new blocks and transactions
+    /// to the database, e.g. when running low on disk space
+    pub read_only: RwLock<bool>,
     /// Flag to enable tx fee verification
     pub verify_fees: bool,
 }
@@ -95,6 +99,13 @@ impl Validator {
         // Create an overlay over whole blockchain so we can write stuff
         let overlay = BlockchainOverlay::new(&blockchain)?;
 
+        // Store the hard-forked gas schedule upgrades. New ones must
+        // activate above our current tip.
+        let tip_height = blockchain.last().ok().map(|(height, _)| height);
+        for schedule in GAS_SCHEDULE_UPGRADES {
+            overlay.lock().unwrap().contracts.insert_gas_schedule(schedule, tip_height)?;
+        }
+
         // Deploy native wasm contracts
         deploy_native_contracts(&overlay).await?;
 
@@ -120,6 +131,7 @@ impl Validator {
             blockchain,
             consensus,
             synced: RwLock::new(false),
+            read_only: RwLock::new(false),
             verify_fees: config.verify_fees,
         });
 
@@ -129,7 +141,12 @@ impl Validator {
 
     /// The node retrieves a transaction, validates its state transition,
     /// and appends it to the pending txs store.
+    #[instrument(target = "validator", skip_all, fields(tx = %tx.hash()))]
     pub async fn append_tx(&self, tx: &Transaction, write: bool) -> Result<()> {
+        if write && *self.read_only.read().await {
+            return Err(Error::DatabaseReadOnly)
+        }
+
         let tx_hash = tx.hash();
 
         // Check if we have already seen this tx
@@ -304,7 +321,13 @@ impl Validator {
     }
 
     /// The node locks its consensus state and tries to append provided proposal.
+    #[instrument(target = "validator", skip_all, fields(block = %proposal.hash))]
     pub async fn append_proposal(&self, proposal: &Proposal) -> Result<()> {
+        // Proposals would pile up in memory without finalization
+        if *self.read_only.read().await {
+            return Err(Error::DatabaseReadOnly)
+        }
+
         // Grab append lock so we restrict concurrent calls of this function
         let append_lock = self.consensus.append_lock.write().await;
 
@@ -320,11 +343,19 @@ impl Validator {
     /// The node checks if best fork can be finalized.
     /// If proposals can be finalized, node appends them to canonical,
     /// and rebuilds the best fork.
+    #[instrument(target = "validator", skip_all)]
     pub async fn finalization(&self) -> Result<Vec<BlockInfo>> {
         // Grab append lock so no new proposals can be appended while
         // we execute finalization
         let append_lock = self.consensus.append_lock.write().await;
 
+        // Keep proposals in their forks until writes are allowed again
+        if *self.read_only.read().await {
+            warn!(target: "validator::finalization", "Database is in read-only mode, skipping finalization");
+            drop(append_lock);
+            return Ok(vec![])
+        }
+
         info!(target: "validator::finalization", "Performing finalization check");
 
         // Grab best fork index that can be finalized
@@ -396,7 +427,12 @@ impl Validator {
     // ==========================
 
     /// Validate a set of [`BlockInfo`] in sequence and apply them if all are valid.
+    #[instrument(target = "validator", skip_all, fields(blocks = blocks.len()))]
     pub async fn add_blocks(&self, blocks: &[BlockInfo]) -> Result<()> {
+        if *self.read_only.read().await {
+            return Err(Error::DatabaseReadOnly)
+        }
+
         debug!(target: "validator::add_blocks", "Instantiating BlockchainOverlay");
         let overlay = BlockchainOverlay::new(&self.blockchain)?;
 
@@ -423,7 +459,9 @@ impl Validator {
             }
 
             // Verify block
-            if verify_block(&overlay, &module, block, previous).await.is_err() {
+            let span =
+                info_span!("verify_block", block = %block.hash(), height = block.header.height);
+            if verify_block(&overlay, &module, block, previous).instrument(span).await.is_err() {
                 error!(target: "validator::add_blocks", "Erroneous block found in set");
                 overlay.lock().unwrap().overlay.lock().unwrap().purge_new_trees()?;
                 return Err(Error::BlockIsInvalid(block.hash().as_string()))
@@ -599,6 +637,12 @@ impl Validator {
         // Create a time keeper and a PoW module to validate each block
         let mut module = PoWModule::new(blockchain.clone(), pow_target, pow_fixed_difficulty)?;
 
+        // Blocks get verified against the hard-forked gas schedules,
+        // starting from an empty chain
+        for schedule in GAS_SCHEDULE_UPGRADES {
+            overlay.lock().unwrap().contracts.insert_gas_schedule(schedule, None)?;
+        }
+
         // Deploy native wasm contracts
         deploy_native_contracts(&overlay).await?;
 
@@ -22,7 +22,7 @@ use std::{
         Arc,
     },
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use darkfi_sdk::num_traits::{One, Zero};
@@ -69,7 +69,7 @@ const CUT_END: usize = 660;
 /// How many most recent blocks to use to verify new blocks' timestamp
 const BLOCKCHAIN_TIMESTAMP_CHECK_WINDOW: usize = 60;
 /// Time limit in the future of what blocks can be
-const BLOCK_FUTURE_TIME_LIMIT: Timestamp = Timestamp::from_u64(60 * 60 * 2);
+pub const BLOCK_FUTURE_TIME_LIMIT: Timestamp = Timestamp::from_u64(60 * 60 * 2);
 
 /// This struct represents the information required by the PoW algorithm
 #[derive(Clone)]
@@ -80,6 +80,12 @@ pub struct PoWModule {
     pub fixed_difficulty: Option<BigUint>,
     /// Latest block timestamps ringbuffer
     pub timestamps: RingBuffer<Timestamp, BUF_SIZE>,
+    /// Sorted copy of the timestamps inside the difficulty window,
+    /// meaning the first DIFFICULTY_WINDOW items of the ringbuffer.
+    /// Maintained incrementally on every append, by removing the evicted
+    /// timestamp and inserting the one entering the window, so next
+    /// difficulty calculation doesn't have to sort the window each time.
+    pub sorted_timestamps: Vec<Timestamp>,
     /// Latest block cummulative difficulties ringbuffer
     pub difficulties: RingBuffer<BigUint, BUF_SIZE>,
     /// Total blocks cummulative difficulty
@@ -95,7 +101,9 @@ impl PoWModule {
         target: usize,
         fixed_difficulty: Option<BigUint>,
     ) -> Result<Self> {
-        // Retrieving last BUF_SIZE difficulties from blockchain to build the buffers
+        // Retrieving last BUF_SIZE difficulties from blockchain to build the buffers.
+        // The difficulty records are what persists the window, so rebuilding it
+        // takes the same bounded time however long the chain is.
         let mut timestamps = RingBuffer::<Timestamp, BUF_SIZE>::new();
         let mut difficulties = RingBuffer::<BigUint, BUF_SIZE>::new();
         let mut cummulative_difficulty = BigUint::zero();
@@ -106,12 +114,26 @@ impl PoWModule {
             cummulative_difficulty = difficulty.cummulative_difficulty;
         }
 
+        // Build the sorted difficulty window from the retrieved timestamps,
+        // sorting at most DIFFICULTY_WINDOW items. From here on it's only
+        // updated incrementally by append().
+        let mut sorted_timestamps: Vec<Timestamp> =
+            timestamps.iter().take(DIFFICULTY_WINDOW).cloned().collect();
+        sorted_timestamps.sort_unstable();
+
         // If a fixed difficulty has been set, assert its greater than zero
         if let Some(diff) = &fixed_difficulty {
             assert!(diff > &BigUint::zero());
         }
 
-        Ok(Self { target, fixed_difficulty, timestamps, difficulties, cummulative_difficulty })
+        Ok(Self {
+            target,
+            fixed_difficulty,
+            timestamps,
+            sorted_timestamps,
+            difficulties,
+            cummulative_difficulty,
+        })
     }
 
     /// Compute the next mining difficulty, based on current ring buffers.
@@ -119,9 +141,8 @@ impl PoWModule {
     /// If a fixed difficulty has been set, this function will always
     /// return that after first 2 difficulties.
     pub fn next_difficulty(&self) -> Result<BigUint> {
-        // Retrieve first DIFFICULTY_WINDOW timestamps from the ring buffer
-        let mut timestamps: Vec<Timestamp> =
-            self.timestamps.iter().take(DIFFICULTY_WINDOW).cloned().collect();
+        // Grab the already sorted first DIFFICULTY_WINDOW timestamps
+        let timestamps = &self.sorted_timestamps;
 
         // Check we have enough timestamps
         let length = timestamps.len();
@@ -134,9 +155,6 @@ impl PoWModule {
             return Ok(diff.clone())
         }
 
-        // Sort the timestamps vector
-        timestamps.sort_unstable();
-
         // Grab cutoff indexes
         let (cut_begin, cut_end) = self.cutoff(length)?;
 
@@ -251,7 +269,7 @@ impl PoWModule {
         let target = self.next_mine_target()?;
 
         // Setup verifier
-        let flags = RandomXFlags::default();
+        let flags = randomx_flags();
         let cache = RandomXCache::new(flags, block.header.previous.inner()).unwrap();
         let vm = RandomXVM::new(flags, &cache).unwrap();
         debug!(target: "validator::pow::verify_block", "[VERIFIER] Setup time: {:?}", verifier_setup.elapsed());
@@ -272,6 +290,19 @@ impl PoWModule {
 
     /// Append provided timestamp and difficulty to the ring buffers
     pub fn append(&mut self, timestamp: Timestamp, difficulty: &BigUint) {
+        // Keep the sorted difficulty window in sync with the ring buffer.
+        // The window is always the first DIFFICULTY_WINDOW items of it.
+        let length = self.timestamps.len();
+        if length == BUF_SIZE {
+            // Buffer is full, so its front item gets evicted on push and
+            // the first lagged item (right after the window) enters it.
+            remove_sorted(&mut self.sorted_timestamps, &self.timestamps[0]);
+            insert_sorted(&mut self.sorted_timestamps, self.timestamps[DIFFICULTY_WINDOW]);
+        } else if length < DIFFICULTY_WINDOW {
+            // Window is not filled yet, so new item enters it directly
+            insert_sorted(&mut self.sorted_timestamps, timestamp);
+        }
+
         self.timestamps.push(timestamp);
         self.cummulative_difficulty += difficulty;
         self.difficulties.push(self.cummulative_difficulty.clone());
@@ -302,6 +333,21 @@ impl PoWModule {
     }
 }
 
+/// Insert provided item into a sorted vector, keeping it sorted
+fn insert_sorted<T: Ord>(v: &mut Vec<T>, item: T) {
+    let index = match v.binary_search(&item) {
+        Ok(i) | Err(i) => i,
+    };
+    v.insert(index, item);
+}
+
+/// Remove an occurrence of provided item from a sorted vector
+fn remove_sorted<T: Ord>(v: &mut Vec<T>, item: &T) {
+    if let Ok(index) = v.binary_search(item) {
+        v.remove(index);
+    }
+}
+
 impl std::fmt::Display for PoWModule {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "PoWModule:")?;
@@ -312,6 +358,163 @@ impl std::fmt::Display for PoWModule {
     }
 }
 
+/// Retrieve the RandomX flags to use on this machine.
+/// RandomX detects JIT, hardware AES and Argon2 SIMD support at runtime,
+/// so we only have to account for platform restrictions it misses.
+pub fn randomx_flags() -> RandomXFlags {
+    #[allow(unused_mut)]
+    let mut flags = RandomXFlags::default();
+
+    // Apple Silicon enforces W^X on memory pages, so the JIT compiler
+    // has to run in secure mode, never mapping code writable and
+    // executable at the same time.
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    if flags.contains(RandomXFlags::JIT) {
+        flags |= RandomXFlags::SECURE;
+    }
+
+    flags
+}
+
+/// CPU features RandomX performance depends on, detected at runtime
+#[derive(Debug, Clone, Default)]
+pub struct CpuFeatures {
+    /// Hardware AES, used by the RandomX VM and dataset generation
+    pub aes: bool,
+    /// SIMD used by the Argon2 cache initialization:
+    /// NEON on aarch64, AVX2 on x86_64
+    pub simd: bool,
+}
+
+impl std::fmt::Display for CpuFeatures {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let simd = if cfg!(target_arch = "aarch64") { "NEON" } else { "AVX2" };
+        write!(f, "AES: {}, {}: {}", self.aes, simd, self.simd)
+    }
+}
+
+/// Detect the CPU features of this machine
+pub fn cpu_features() -> CpuFeatures {
+    #[cfg(target_arch = "aarch64")]
+    let features = CpuFeatures {
+        aes: std::arch::is_aarch64_feature_detected!("aes"),
+        simd: std::arch::is_aarch64_feature_detected!("neon"),
+    };
+
+    #[cfg(target_arch = "x86_64")]
+    let features = CpuFeatures {
+        aes: std::arch::is_x86_feature_detected!("aes"),
+        simd: std::arch::is_x86_feature_detected!("avx2"),
+    };
+
+    #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+    let features = CpuFeatures::default();
+
+    features
+}
+
+/// Results of a RandomX self-benchmark
+#[derive(Debug, Clone)]
+pub struct RandomXBenchmark {
+    /// Detected CPU features
+    pub cpu_features: CpuFeatures,
+    /// RandomX flags in use
+    pub flags: RandomXFlags,
+    /// Time it took to initialize a light mode cache
+    pub cache_init: Duration,
+    /// Light mode (block verification) hashes per second
+    pub verify_hashrate: f64,
+    /// Time it took to initialize the full dataset
+    pub dataset_init: Duration,
+    /// Fast mode (mining) hashes per second, over all threads
+    pub mining_hashrate: f64,
+}
+
+impl std::fmt::Display for RandomXBenchmark {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "CPU features: {}", self.cpu_features)?;
+        writeln!(f, "RandomX flags: {:?}", self.flags)?;
+        writeln!(f, "Cache init time: {:?}", self.cache_init)?;
+        writeln!(f, "Verification hashrate: {:.2} H/s", self.verify_hashrate)?;
+        writeln!(f, "Dataset init time: {:?}", self.dataset_init)?;
+        write!(f, "Mining hashrate: {:.2} H/s", self.mining_hashrate)
+    }
+}
+
+/// Benchmark RandomX on this machine, computing `hashes` hashes in both
+/// light (verification) and fast (mining) mode, the latter using given
+/// number of threads. Useful to check a node performs well enough,
+/// especially on ARM hardware.
+pub fn randomx_benchmark(threads: usize, hashes: u64) -> Result<RandomXBenchmark> {
+    let threads = threads.max(1);
+    let hashes = hashes.max(1);
+    let key = blake3::hash(b"darkfi:randomx:benchmark");
+    let flags = randomx_flags();
+
+    // Light mode, as used when verifying blocks
+    let setup = Instant::now();
+    let cache = match RandomXCache::new(flags, key.as_bytes()) {
+        Ok(cache) => cache,
+        Err(e) => return Err(Error::Custom(format!("RandomX cache init failed: {e:?}"))),
+    };
+    let cache_init = setup.elapsed();
+    let vm = match RandomXVM::new(flags, &cache) {
+        Ok(vm) => vm,
+        Err(e) => return Err(Error::Custom(format!("RandomX VM init failed: {e:?}"))),
+    };
+    let timer = Instant::now();
+    for nonce in 0..hashes {
+        vm.hash(&nonce.to_le_bytes());
+    }
+    let verify_hashrate = hashes as f64 / timer.elapsed().as_secs_f64();
+
+    // Fast mode, as used when mining blocks
+    let flags_fast = flags | RandomXFlags::FULLMEM;
+    let setup = Instant::now();
+    let dataset = match RandomXDataset::new(flags_fast, key.as_bytes(), threads) {
+        Ok(dataset) => Arc::new(dataset),
+        Err(e) => return Err(Error::Custom(format!("RandomX dataset init failed: {e:?}"))),
+    };
+    let dataset_init = setup.elapsed();
+
+    let timer = Instant::now();
+    let mut handles = vec![];
+    for t in 0..threads as u64 {
+        let dataset = Arc::clone(&dataset);
+        handles.push(thread::spawn(move || {
+            let vm = match RandomXVM::new_fast(flags_fast, &dataset) {
+                Ok(vm) => vm,
+                Err(e) => return Err(format!("RandomX VM init failed: {e:?}")),
+            };
+            let mut nonce = t;
+            while nonce < hashes {
+                vm.hash(&nonce.to_le_bytes());
+                nonce += threads as u64;
+            }
+            Ok(())
+        }));
+    }
+
+    // Every thread must have done its share for the hashrate to hold
+    for handle in handles {
+        match handle.join() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(Error::Custom(e)),
+            Err(_) => return Err(Error::Custom("RandomX benchmark thread panicked".to_string())),
+        }
+    }
+    let mining_hashrate = hashes as f64 / timer.elapsed().as_secs_f64();
+
+    Ok(RandomXBenchmark {
+        cpu_features: cpu_features(),
+        flags,
+        cache_init,
+        verify_hashrate,
+        dataset_init,
+        mining_hashrate,
+    })
+}
+
 /// Mine provided block, based on provided PoW module next mine target
 pub fn mine_block(
     target: &BigUint,
@@ -325,7 +528,7 @@ pub fn mine_block(
     // Get the PoW input. The key changes with every mined block.
     let input = miner_block.header.previous;
     debug!(target: "validator::pow::mine_block", "[MINER] PoW input: {}", input);
-    let flags = RandomXFlags::default() | RandomXFlags::FULLMEM;
+    let flags = randomx_flags() | RandomXFlags::FULLMEM;
     debug!(target: "validator::pow::mine_block", "[MINER] Initializing RandomX dataset...");
     let dataset = Arc::new(RandomXDataset::new(flags, input.inner(), threads).unwrap());
     debug!(target: "validator::pow::mine_block", "[MINER] Setup time: {:?}", miner_setup.elapsed());
@@ -407,12 +610,18 @@ mod tests {
     use darkfi_sdk::num_traits::Num;
     use num_bigint::BigUint;
 
+    use darkfi_sdk::num_traits::{One, Zero};
+
     use crate::{
-        blockchain::{BlockInfo, Blockchain},
+        blockchain::{
+            block_store::{BlockDifficulty, BlockRanks},
+            BlockInfo, Blockchain,
+        },
+        util::time::Timestamp,
         Result,
     };
 
-    use super::PoWModule;
+    use super::{PoWModule, BUF_SIZE, DIFFICULTY_WINDOW};
 
     const DEFAULT_TEST_THREADS: usize = 2;
     const DEFAULT_TEST_DIFFICULTY_TARGET: usize = 120;
@@ -449,6 +658,95 @@ mod tests {
         Ok(())
     }
 
+    /// Naive next difficulty calculation, sorting the whole difficulty
+    /// window every time, used to verify the incremental one.
+    fn naive_next_difficulty(module: &PoWModule) -> Result<BigUint> {
+        let mut timestamps: Vec<Timestamp> =
+            module.timestamps.iter().take(DIFFICULTY_WINDOW).cloned().collect();
+
+        let length = timestamps.len();
+        if length < 2 {
+            return Ok(BigUint::one())
+        }
+
+        timestamps.sort_unstable();
+
+        let (cut_begin, cut_end) = module.cutoff(length)?;
+        let cut_end = cut_end - 1;
+
+        let mut time_span = timestamps[cut_end].checked_sub(timestamps[cut_begin])?;
+        if time_span.inner() == 0 {
+            time_span = 1.into();
+        }
+
+        let total_work = &module.difficulties[cut_end] - &module.difficulties[cut_begin];
+        Ok((total_work * module.target + time_span.inner() - BigUint::one()) / time_span.inner())
+    }
+
+    #[test]
+    fn test_incremental_difficulty() -> Result<()> {
+        let sled_db = sled::Config::new().temporary(true).open()?;
+        let blockchain = Blockchain::new(&sled_db)?;
+        let mut module = PoWModule::new(blockchain, DEFAULT_TEST_DIFFICULTY_TARGET, None)?;
+
+        // Use jittery, sometimes out of order, timestamps so the sorted
+        // window actually differs from the insertion order.
+        let mut timestamp = 1_700_000_000_u64;
+        for n in 0..(BUF_SIZE * 3) as u64 {
+            let jitter = (n * 7919) % 240;
+            let ts = if n % 5 == 0 { timestamp - jitter.min(60) } else { timestamp + jitter };
+            timestamp += DEFAULT_TEST_DIFFICULTY_TARGET as u64;
+
+            let difficulty = module.next_difficulty()?;
+            assert_eq!(difficulty, naive_next_difficulty(&module)?);
+            module.append(ts.into(), &difficulty);
+
+            // Sorted window must always mirror the ring buffer window
+            let mut expected: Vec<Timestamp> =
+                module.timestamps.iter().take(DIFFICULTY_WINDOW).cloned().collect();
+            expected.sort_unstable();
+            assert_eq!(module.sorted_timestamps, expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_difficulty_window_restart() -> Result<()> {
+        let sled_db = sled::Config::new().temporary(true).open()?;
+        let blockchain = Blockchain::new(&sled_db)?;
+        let mut module = PoWModule::new(blockchain.clone(), DEFAULT_TEST_DIFFICULTY_TARGET, None)?;
+
+        // Persist each appended difficulty, like finalization does
+        let mut timestamp = 1_700_000_000_u64;
+        for height in 0..(BUF_SIZE * 2) as u64 {
+            let jitter = (height * 7919) % 240;
+            let ts = if height % 5 == 0 { timestamp - jitter.min(60) } else { timestamp + jitter };
+            timestamp += DEFAULT_TEST_DIFFICULTY_TARGET as u64;
+
+            let difficulty = module.next_difficulty()?;
+            module.append(ts.into(), &difficulty);
+            let ranks =
+                BlockRanks::new(BigUint::zero(), BigUint::zero(), BigUint::zero(), BigUint::zero());
+            let record = BlockDifficulty::new(
+                height,
+                ts.into(),
+                difficulty,
+                module.cummulative_difficulty.clone(),
+                ranks,
+            );
+            blockchain.blocks.insert_difficulty(&[record])?;
+        }
+
+        // A restarted module rebuilds the same window from the stored records
+        let restarted = PoWModule::new(blockchain, DEFAULT_TEST_DIFFICULTY_TARGET, None)?;
+        assert_eq!(restarted.sorted_timestamps, module.sorted_timestamps);
+        assert_eq!(restarted.cummulative_difficulty, module.cummulative_difficulty);
+        assert_eq!(restarted.next_difficulty()?, module.next_difficulty()?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_miner_correctness() -> Result<()> {
         // Default setup
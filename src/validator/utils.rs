@@ -17,17 +17,20 @@
  */
 
 use darkfi_sdk::{
-    crypto::{DAO_CONTRACT_ID, DEPLOYOOOR_CONTRACT_ID, MONEY_CONTRACT_ID},
+    crypto::{DAO_CONTRACT_ID, DEPLOYOOOR_CONTRACT_ID, MONEY_CONTRACT_ID, REGISTRY_CONTRACT_ID},
     tx::TransactionHash,
 };
 use log::info;
 use num_bigint::BigUint;
-use randomx::{RandomXCache, RandomXFlags, RandomXVM};
+use randomx::{RandomXCache, RandomXVM};
 
 use crate::{
     blockchain::{BlockInfo, BlockchainOverlayPtr},
     runtime::vm_runtime::Runtime,
-    validator::consensus::{Fork, Proposal},
+    validator::{
+        consensus::{Fork, Proposal},
+        pow::randomx_flags,
+    },
     Error, Result,
 };
 
@@ -53,6 +56,9 @@ pub async fn deploy_native_contracts(overlay: &BlockchainOverlayPtr) -> Result<(
     // The Deployooor contract uses an empty payload to deploy itself.
     let deployooor_contract_deploy_payload = vec![];
 
+    // The Registry contract uses an empty payload to deploy itself.
+    let registry_contract_deploy_payload = vec![];
+
     let native_contracts = vec![
         (
             "Money Contract",
@@ -72,6 +78,12 @@ pub async fn deploy_native_contracts(overlay: &BlockchainOverlayPtr) -> Result<(
             include_bytes!("../contract/deployooor/darkfi_deployooor_contract.wasm").to_vec(),
             deployooor_contract_deploy_payload,
         ),
+        (
+            "Registry Contract",
+            *REGISTRY_CONTRACT_ID,
+            include_bytes!("../contract/registry/darkfi_registry_contract.wasm").to_vec(),
+            registry_contract_deploy_payload,
+        ),
     ];
 
     // Grab last known block height to verify against next one.
@@ -121,7 +133,7 @@ pub fn block_rank(block: &BlockInfo, target: &BigUint) -> (BigUint, BigUint) {
     let target_distance_sq = &target_distance * &target_distance;
 
     // Setup RandomX verifier
-    let flags = RandomXFlags::default();
+    let flags = randomx_flags();
     let cache = RandomXCache::new(flags, block.header.previous.inner()).unwrap();
     let vm = RandomXVM::new(flags, &cache).unwrap();
 
@@ -28,7 +28,7 @@ use darkfi_sdk::{
     deploy::DeployParamsV1,
     pasta::pallas,
 };
-use darkfi_serial::{deserialize_async, serialize_async, AsyncDecodable, AsyncEncodable};
+use darkfi_serial::{deserialize_async_strict, serialize_async, AsyncDecodable, AsyncEncodable};
 use log::{debug, error, warn};
 use num_bigint::BigUint;
 use smol::io::Cursor;
@@ -39,7 +39,7 @@ use crate::{
     },
     error::TxVerifyFailed,
     runtime::vm_runtime::Runtime,
-    tx::{Transaction, MAX_TX_CALLS, MIN_TX_CALLS},
+    tx::{Transaction, ZkpBatch, MAX_TX_CALLS, MIN_TX_CALLS},
     validator::{
         consensus::{Consensus, Fork, Proposal, TXS_CAP},
         fees::{circuit_gas_use, PALLAS_SCHNORR_SIGNATURE_FEE},
@@ -317,8 +317,11 @@ pub async fn verify_producer_transaction(
             continue
         }
 
-        let (_zkbin, vk) =
-            overlay.lock().unwrap().contracts.get_zkas(&call.data.contract_id, zkas_ns)?;
+        let (_zkbin, vk) = overlay.lock().unwrap().contracts.get_zkas_at(
+            &call.data.contract_id,
+            zkas_ns,
+            verifying_block_height,
+        )?;
 
         inner_vk_map.insert(zkas_ns.to_string(), vk);
     }
@@ -372,7 +375,9 @@ pub async fn verify_producer_transaction(
 
 /// Verify WASM execution, signatures, and ZK proofs for a given [`Transaction`],
 /// and apply it to the provided overlay. Additionally, append its hash to the
-/// provided Merkle tree.
+/// provided Merkle tree. If a [`ZkpBatch`] is provided, the ZK proofs are
+/// collected into it instead of being verified, and the caller is responsible
+/// for verifying the batch.
 pub async fn verify_transaction(
     overlay: &BlockchainOverlayPtr,
     verifying_block_height: u64,
@@ -380,6 +385,7 @@ pub async fn verify_transaction(
     tree: &mut MerkleTree,
     verifying_keys: &mut HashMap<[u8; 32], HashMap<String, VerifyingKey>>,
     verify_fee: bool,
+    zkp_batch: Option<&mut ZkpBatch>,
 ) -> Result<u64> {
     let tx_hash = tx.hash();
     debug!(target: "validator::verification::verify_transaction", "Validating transaction {}", tx_hash);
@@ -482,15 +488,18 @@ pub async fn verify_transaction(
         for (zkas_ns, _) in &zkp_pub {
             let inner_vk_map = verifying_keys.get_mut(&call.data.contract_id.to_bytes()).unwrap();
 
-            // TODO: This will be a problem in case of ::deploy, unless we force a different
-            // namespace and disable updating existing circuit. Might be a smart idea to do
-            // so in order to have to care less about being able to verify historical txs.
             if inner_vk_map.contains_key(zkas_ns.as_str()) {
                 continue
             }
 
-            let (zkbin, vk) =
-                overlay.lock().unwrap().contracts.get_zkas(&call.data.contract_id, zkas_ns)?;
+            // Use the circuit version active at the verifying block height,
+            // so historical transactions verify against the keys they were
+            // created with, even after the contract was upgraded.
+            let (zkbin, vk) = overlay.lock().unwrap().contracts.get_zkas_at(
+                &call.data.contract_id,
+                zkas_ns,
+                verifying_block_height,
+            )?;
 
             inner_vk_map.insert(zkas_ns.to_string(), vk);
             circuits_to_verify.push(zkbin);
@@ -517,7 +526,8 @@ pub async fn verify_transaction(
         {
             debug!(target: "validator::verification::verify_transaction", "Deploying new contract");
             // Deserialize the deployment parameters
-            let deploy_params: DeployParamsV1 = deserialize_async(&call.data.data[1..]).await?;
+            let deploy_params: DeployParamsV1 =
+                deserialize_async_strict(&call.data.data[1..]).await?;
             let deploy_cid = ContractId::derive_public(deploy_params.public_key);
 
             // Instantiate the new deployment runtime
@@ -552,7 +562,8 @@ pub async fn verify_transaction(
 
     if verify_fee {
         // Deserialize the fee call to find the paid fee
-        let fee: u64 = match deserialize_async(&tx.calls[fee_call_idx].data.data[1..9]).await {
+        let fee: u64 = match deserialize_async_strict(&tx.calls[fee_call_idx].data.data[1..9]).await
+        {
             Ok(v) => v,
             Err(e) => {
                 error!(
@@ -597,6 +608,23 @@ pub async fn verify_transaction(
     }
     debug!(target: "validator::verification::verify_transaction", "Signature verification successful");
 
+    if let Some(batch) = zkp_batch {
+        debug!(target: "validator::verification::verify_transaction", "Batching ZK proofs for transaction {}", tx_hash);
+        if let Err(e) = tx.batch_zkps(verifying_keys, zkp_table, batch) {
+            error!(
+                target: "validator::verification::verify_transaction",
+                "[VALIDATOR] ZK proof batching for tx {} failed: {}", tx_hash, e,
+            );
+            return Err(TxVerifyFailed::InvalidZkProof.into())
+        }
+
+        // Append hash to merkle tree
+        append_tx_to_merkle_tree(tree, tx);
+
+        debug!(target: "validator::verification::verify_transaction", "Transaction {} verified successfully, pending ZK proofs", tx_hash);
+        return Ok(gas_used)
+    }
+
     debug!(target: "validator::verification::verify_transaction", "Verifying ZK proofs for transaction {}", tx_hash);
     if let Err(e) = tx.verify_zkps(verifying_keys, zkp_table).await {
         error!(
@@ -618,6 +646,10 @@ pub async fn verify_transaction(
 /// In case any of the transactions fail, they will be returned to the caller as an error.
 /// If all transactions are valid, the function will return the accumulated gas used from
 /// all the transactions. Additionally, their hash is appended to the provided Merkle tree.
+///
+/// The ZK proofs of all the transactions are verified at once, at the end. If the batch
+/// turns out invalid, the overlay and tree are restored and the transactions are verified
+/// again one by one, to find out which ones are erroneous.
 pub async fn verify_transactions(
     overlay: &BlockchainOverlayPtr,
     verifying_block_height: u64,
@@ -630,6 +662,51 @@ pub async fn verify_transactions(
         return Ok(0)
     }
 
+    // Keep the initial state around, in case the ZK proofs batch fails
+    let overlay_snapshot = overlay.lock().unwrap().overlay.lock().unwrap().clone();
+    let tree_snapshot = tree.clone();
+
+    let mut zkp_batch = ZkpBatch::default();
+    let (mut gas_used, mut erroneous_txs, vks) = verify_transactions_pass(
+        overlay,
+        verifying_block_height,
+        txs,
+        tree,
+        verify_fees,
+        Some(&mut zkp_batch),
+    )
+    .await?;
+
+    if !zkp_batch.is_empty() && !zkp_batch.verify(&vks) {
+        warn!(
+            target: "validator::verification::verify_transactions",
+            "ZK proofs batch verification failed, verifying transactions individually",
+        );
+        *overlay.lock().unwrap().overlay.lock().unwrap() = overlay_snapshot;
+        *tree = tree_snapshot;
+        (gas_used, erroneous_txs, _) =
+            verify_transactions_pass(overlay, verifying_block_height, txs, tree, verify_fees, None)
+                .await?;
+    }
+
+    if erroneous_txs.is_empty() {
+        Ok(gas_used)
+    } else {
+        Err(TxVerifyFailed::ErroneousTxs(erroneous_txs).into())
+    }
+}
+
+/// Auxiliary function to verify a set of [`Transaction`] in sequence, reverting
+/// the erroneous ones. Returns the accumulated gas used, the erroneous transactions,
+/// and the verifying keys used.
+async fn verify_transactions_pass(
+    overlay: &BlockchainOverlayPtr,
+    verifying_block_height: u64,
+    txs: &[Transaction],
+    tree: &mut MerkleTree,
+    verify_fees: bool,
+    mut zkp_batch: Option<&mut ZkpBatch>,
+) -> Result<(u64, Vec<Transaction>, HashMap<[u8; 32], HashMap<String, VerifyingKey>>)> {
     // Tracker for failed txs
     let mut erroneous_txs = vec![];
 
@@ -649,8 +726,16 @@ pub async fn verify_transactions(
     // Iterate over transactions and attempt to verify them
     for tx in txs {
         overlay.lock().unwrap().checkpoint();
-        match verify_transaction(overlay, verifying_block_height, tx, tree, &mut vks, verify_fees)
-            .await
+        match verify_transaction(
+            overlay,
+            verifying_block_height,
+            tx,
+            tree,
+            &mut vks,
+            verify_fees,
+            zkp_batch.as_deref_mut(),
+        )
+        .await
         {
             Ok(gas) => gas_used += gas,
             Err(e) => {
@@ -661,11 +746,7 @@ pub async fn verify_transactions(
         }
     }
 
-    if erroneous_txs.is_empty() {
-        Ok(gas_used)
-    } else {
-        Err(TxVerifyFailed::ErroneousTxs(erroneous_txs).into())
-    }
+    Ok((gas_used, erroneous_txs, vks))
 }
 
 /// Verify given [`Proposal`] against provided consensus state,
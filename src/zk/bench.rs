@@ -0,0 +1,206 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Benchmarking of the shipped zkas circuits.
+//!
+//! Every circuit in [`CIRCUITS`] is proven and verified using the
+//! witnesses stored next to it, measuring time and memory, and the
+//! results can be written as JSON so that regressions coming from
+//! halo2 or zkas changes can be tracked across runs.
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use darkfi_sdk::pasta::pallas;
+use log::info;
+use rand::rngs::OsRng;
+use tinyjson::JsonValue::{
+    self, Array as JsonArray, Null as JsonNull, Number as JsonNum, Object as JsonObj,
+    String as JsonStr,
+};
+
+use super::{
+    empty_witnesses, import_witness_json, Proof, ProvingKey, VerifyingKey, Witness, ZkCircuit,
+};
+use crate::{zkas::ZkBinary, Result};
+
+/// Shipped circuits as `(name, zkbin path, witness json path)`,
+/// relative to the repository root.
+#[rustfmt::skip]
+pub const CIRCUITS: &[(&str, &str, &str)] = &[
+    ("Arithmetic", "proof/arithmetic.zk.bin", "proof/witness/arithmetic.json"),
+    ("Opcodes", "proof/opcodes.zk.bin", "proof/witness/opcodes.json"),
+    ("SMT", "proof/smt.zk.bin", "proof/witness/smt.json"),
+    ("DAO::mint", "src/contract/dao/proof/mint.zk.bin", "src/contract/dao/proof/witness/mint.json"),
+    ("DAO::propose-input", "src/contract/dao/proof/propose-input.zk.bin", "src/contract/dao/proof/witness/propose-input.json"),
+    ("DAO::propose", "src/contract/dao/proof/propose-main.zk.bin", "src/contract/dao/proof/witness/propose-main.json"),
+    ("DAO::vote-input", "src/contract/dao/proof/vote-input.zk.bin", "src/contract/dao/proof/witness/vote-input.json"),
+    ("DAO::vote", "src/contract/dao/proof/vote-main.zk.bin", "src/contract/dao/proof/witness/vote-main.json"),
+    ("DAO::exec", "src/contract/dao/proof/exec.zk.bin", "src/contract/dao/proof/witness/exec.json"),
+    ("DAO::auth_xfer-coin", "src/contract/dao/proof/auth-money-transfer-enc-coin.zk.bin", "src/contract/dao/proof/witness/auth-money-transfer-enc-coin.json"),
+    ("DAO::auth_xfer", "src/contract/dao/proof/auth-money-transfer.zk.bin", "src/contract/dao/proof/witness/auth-money-transfer.json"),
+    ("Money::xfer-mint", "src/contract/money/proof/mint_v1.zk.bin", "src/contract/money/proof/witness/mint_v1.json"),
+    ("Money::xfer-burn", "src/contract/money/proof/burn_v1.zk.bin", "src/contract/money/proof/witness/burn_v1.json"),
+    ("Money::fee", "src/contract/money/proof/fee_v1.zk.bin", "src/contract/money/proof/witness/fee_v1.json"),
+    ("Money::auth_token-mint", "src/contract/money/proof/auth_token_mint_v1.zk.bin", "src/contract/money/proof/witness/auth_token_mint_v1.json"),
+    ("Money::token-mint", "src/contract/money/proof/token_mint_v1.zk.bin", "src/contract/money/proof/witness/token_mint_v1.json"),
+    ("Money::token-freeze", "src/contract/money/proof/token_freeze_v1.zk.bin", "src/contract/money/proof/witness/token_freeze_v1.json"),
+];
+
+/// Measurements of a single circuit
+#[derive(Clone, Debug)]
+pub struct ZkBenchResult {
+    pub name: String,
+    /// The circuit's k param
+    pub k: u32,
+    /// Rows used by the circuit
+    pub rows: usize,
+    /// Time to build the proving and verifying keys
+    pub keygen_time: Duration,
+    /// Mean time to create a proof
+    pub prove_time: Duration,
+    /// Mean time to verify a proof
+    pub verify_time: Duration,
+    /// Size of a proof in bytes
+    pub proof_size: usize,
+    /// Size of the serialized proving key in bytes
+    pub pk_size: usize,
+    /// Peak resident memory of the process after proving, in bytes.
+    /// Only available on Linux.
+    pub peak_memory: Option<u64>,
+}
+
+impl ZkBenchResult {
+    pub fn to_json(&self) -> JsonValue {
+        let micros = |d: Duration| JsonNum(d.as_micros() as f64);
+        JsonObj(HashMap::from([
+            ("name".to_string(), JsonStr(self.name.clone())),
+            ("k".to_string(), JsonNum(self.k.into())),
+            ("rows".to_string(), JsonNum(self.rows as f64)),
+            ("keygen_us".to_string(), micros(self.keygen_time)),
+            ("prove_us".to_string(), micros(self.prove_time)),
+            ("verify_us".to_string(), micros(self.verify_time)),
+            ("proof_size".to_string(), JsonNum(self.proof_size as f64)),
+            ("pk_size".to_string(), JsonNum(self.pk_size as f64)),
+            ("peak_memory".to_string(), self.peak_memory.map_or(JsonNull, |m| JsonNum(m as f64))),
+        ]))
+    }
+}
+
+/// Format benchmark results as a JSON array
+pub fn results_to_json(results: &[ZkBenchResult]) -> String {
+    JsonArray(results.iter().map(|r| r.to_json()).collect()).format().unwrap()
+}
+
+/// Peak resident set size of the current process
+fn peak_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Build the keys for a circuit, then prove and verify it `iterations`
+/// times with the given witnesses and public inputs.
+pub fn bench_circuit(
+    name: &str,
+    zkbin: &ZkBinary,
+    witnesses: Vec<Witness>,
+    public_inputs: &[pallas::Base],
+    iterations: u32,
+) -> Result<ZkBenchResult> {
+    assert!(iterations > 0);
+    info!(target: "zk::bench", "Benchmarking {}", name);
+
+    let empty_circuit = ZkCircuit::new(empty_witnesses(zkbin)?, zkbin);
+    let rows = empty_circuit.stats()?.rows;
+
+    let now = Instant::now();
+    let pk = ProvingKey::build(zkbin.k, &empty_circuit);
+    let vk = VerifyingKey::build(zkbin.k, &empty_circuit);
+    let keygen_time = now.elapsed();
+
+    let mut pk_bytes = vec![];
+    pk.write(&mut pk_bytes)?;
+
+    let circuit = ZkCircuit::new(witnesses, zkbin);
+    let now = Instant::now();
+    let mut proof = Proof::default();
+    for _ in 0..iterations {
+        proof = Proof::create(&pk, &[circuit.clone()], public_inputs, OsRng)?;
+    }
+    let prove_time = now.elapsed() / iterations;
+    let peak_memory = peak_memory();
+
+    let now = Instant::now();
+    for _ in 0..iterations {
+        proof.verify(&vk, public_inputs)?;
+    }
+    let verify_time = now.elapsed() / iterations;
+
+    Ok(ZkBenchResult {
+        name: name.to_string(),
+        k: zkbin.k,
+        rows,
+        keygen_time,
+        prove_time,
+        verify_time,
+        proof_size: proof.as_ref().len(),
+        pk_size: pk_bytes.len(),
+        peak_memory,
+    })
+}
+
+/// Benchmark all of the [`CIRCUITS`], with paths relative to `root`
+pub fn bench_circuits(root: &Path, iterations: u32) -> Result<Vec<ZkBenchResult>> {
+    let mut results = Vec::with_capacity(CIRCUITS.len());
+    for (name, zkbin_path, witness_path) in CIRCUITS {
+        let zkbin = ZkBinary::decode(&fs::read(root.join(zkbin_path))?)?;
+        let (witnesses, public_inputs) = import_witness_json(root.join(witness_path));
+        results.push(bench_circuit(name, &zkbin, witnesses, &public_inputs, iterations)?);
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zk_bench_circuit() -> Result<()> {
+        let zkbin = ZkBinary::decode(include_bytes!("../../proof/arithmetic.zk.bin"))?;
+        let (witnesses, public_inputs) = import_witness_json("proof/witness/arithmetic.json");
+
+        let result = bench_circuit("Arithmetic", &zkbin, witnesses, &public_inputs, 1)?;
+        assert_eq!(result.k, zkbin.k);
+        assert!(result.proof_size > 0 && result.pk_size > 0);
+        assert!(result.prove_time > Duration::ZERO);
+
+        let json: JsonValue = results_to_json(&[result]).parse().unwrap();
+        let results: &Vec<JsonValue> = json.get().unwrap();
+        let result: &HashMap<String, JsonValue> = results[0].get().unwrap();
+        let name: &String = result["name"].get().unwrap();
+        assert_eq!(name, "Arithmetic");
+
+        Ok(())
+    }
+}
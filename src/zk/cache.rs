@@ -0,0 +1,188 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! On-disk cache of zkas circuit keys.
+//!
+//! Generating proving and verifying keys takes a while for bigger circuits,
+//! so they are serialized to a cache directory, keyed by the hash of the
+//! circuit's zkas binary and its `k` parameter, and loaded from there on
+//! subsequent runs. Each file carries a checksum of its contents, so
+//! corrupted entries get regenerated.
+use std::{
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use log::{debug, warn};
+
+use super::{empty_witnesses, ProvingKey, VerifyingKey, ZkCircuit};
+use crate::{zkas::ZkBinary, Result};
+
+/// Size of the checksum appended to cache files
+const CHECKSUM_LEN: usize = blake3::OUT_LEN;
+
+/// On-disk cache of zkas circuit proving and verifying keys
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    /// Directory holding the cache files
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    /// Open the cache in the given directory, creating it if needed
+    pub fn new(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self { dir: dir.to_path_buf() })
+    }
+
+    /// Decode the given zkas binary and grab its proving key, loading it
+    /// from the cache, or generating and storing it if not found.
+    pub fn proving_key(&self, bincode: &[u8]) -> Result<(ZkBinary, ProvingKey)> {
+        let zkbin = ZkBinary::decode(bincode)?;
+        let path = self.entry_path(bincode, &zkbin, "pk");
+
+        if let Some(data) = self.load(&path) {
+            let circuit = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin);
+            match ProvingKey::read(&mut Cursor::new(data), circuit) {
+                Ok(pk) => return Ok((zkbin, pk)),
+                Err(e) => warn!(target: "zk::cache", "Failed reading cached {:?}: {}", path, e),
+            }
+        }
+
+        debug!(target: "zk::cache", "Building {} proving key", zkbin.namespace);
+        let circuit = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin);
+        let pk = ProvingKey::build(zkbin.k, &circuit);
+
+        let mut data = vec![];
+        pk.write(&mut data)?;
+        self.store(&path, data)?;
+
+        Ok((zkbin, pk))
+    }
+
+    /// Decode the given zkas binary and grab its verifying key, loading it
+    /// from the cache, or generating and storing it if not found.
+    pub fn verifying_key(&self, bincode: &[u8]) -> Result<(ZkBinary, VerifyingKey)> {
+        let zkbin = ZkBinary::decode(bincode)?;
+        let path = self.entry_path(bincode, &zkbin, "vk");
+
+        if let Some(data) = self.load(&path) {
+            let circuit = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin);
+            match VerifyingKey::read(&mut Cursor::new(data), circuit) {
+                Ok(vk) => return Ok((zkbin, vk)),
+                Err(e) => warn!(target: "zk::cache", "Failed reading cached {:?}: {}", path, e),
+            }
+        }
+
+        debug!(target: "zk::cache", "Building {} verifying key", zkbin.namespace);
+        let circuit = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin);
+        let vk = VerifyingKey::build(zkbin.k, &circuit);
+
+        let mut data = vec![];
+        vk.write(&mut data)?;
+        self.store(&path, data)?;
+
+        Ok((zkbin, vk))
+    }
+
+    /// Path of the cache entry of the given circuit key type
+    fn entry_path(&self, bincode: &[u8], zkbin: &ZkBinary, typ: &str) -> PathBuf {
+        let hash = blake3::hash(bincode);
+        self.dir.join(format!("{}-{}.{}", hash.to_hex(), zkbin.k, typ))
+    }
+
+    /// Read a cache entry, returning `None` if it's missing or corrupted
+    fn load(&self, path: &Path) -> Option<Vec<u8>> {
+        let mut data = match fs::read(path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!(target: "zk::cache", "Failed reading {:?}: {}", path, e);
+                return None
+            }
+        };
+
+        if data.len() < CHECKSUM_LEN {
+            warn!(target: "zk::cache", "Cache entry {:?} is corrupted", path);
+            return None
+        }
+
+        let checksum = data.split_off(data.len() - CHECKSUM_LEN);
+        if blake3::hash(&data).as_bytes()[..] != checksum[..] {
+            warn!(target: "zk::cache", "Cache entry {:?} is corrupted", path);
+            return None
+        }
+
+        debug!(target: "zk::cache", "Loaded {:?}", path);
+        Some(data)
+    }
+
+    /// Write a cache entry along with its checksum. The data is written to
+    /// a temporary file first, so concurrent readers never see partial data.
+    fn store(&self, path: &Path, mut data: Vec<u8>) -> Result<()> {
+        let checksum = blake3::hash(&data);
+        data.extend_from_slice(checksum.as_bytes());
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, &data)?;
+        fs::rename(&tmp_path, path)?;
+
+        debug!(target: "zk::cache", "Stored {:?}", path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zk_key_cache() -> Result<()> {
+        let bincode = include_bytes!("../../proof/arithmetic.zk.bin");
+        let dir = std::env::temp_dir().join(format!("darkfi-zk-key-cache-{}", std::process::id()));
+        let cache = KeyCache::new(&dir)?;
+
+        // First access generates the keys, the second one loads them
+        let (zkbin, vk) = cache.verifying_key(bincode)?;
+        let (_, cached_vk) = cache.verifying_key(bincode)?;
+        let (mut vk_buf, mut cached_vk_buf) = (vec![], vec![]);
+        vk.write(&mut vk_buf)?;
+        cached_vk.write(&mut cached_vk_buf)?;
+        assert_eq!(vk_buf, cached_vk_buf);
+
+        // Corrupted entries get regenerated
+        let path = cache.entry_path(bincode, &zkbin, "vk");
+        let mut data = fs::read(&path)?;
+        data[0] ^= 1;
+        fs::write(&path, data)?;
+        assert!(cache.load(&path).is_none());
+        cache.verifying_key(bincode)?;
+        assert!(cache.load(&path).is_some());
+
+        let (_, pk) = cache.proving_key(bincode)?;
+        let (_, cached_pk) = cache.proving_key(bincode)?;
+        let (mut pk_buf, mut cached_pk_buf) = (vec![], vec![]);
+        pk.write(&mut pk_buf)?;
+        cached_pk.write(&mut cached_pk_buf)?;
+        assert_eq!(pk_buf, cached_pk_buf);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -31,10 +31,13 @@ use {
     },
 };
 
+use std::fmt;
+
 use darkfi_sdk::pasta::pallas;
-use log::error;
+use halo2_proofs::dev::MockProver;
+use log::{debug, error};
 
-use super::{Witness, ZkCircuit};
+use super::{DebugOpValue, Witness, ZkCircuit};
 use crate::{zkas, Error, Result};
 
 #[cfg(feature = "tinyjson")]
@@ -252,3 +255,127 @@ pub fn zkas_type_checks(
     }
     Ok(())
 }
+
+/// The first opcode of a circuit whose constraints are not satisfied
+#[derive(Clone, Debug)]
+pub struct ZkDebugFailure {
+    /// Index of the opcode in the circuit
+    pub index: usize,
+    /// The failing opcode
+    pub opcode: zkas::Opcode,
+    /// Source line of the opcode, if the binary carries debug info
+    pub line: Option<usize>,
+    /// Failures reported by the halo2 mock prover
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for ZkDebugFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Opcode #{} `{}`", self.index, self.opcode.name())?;
+        if let Some(line) = self.line {
+            write!(f, " at line {}", line)?;
+        }
+        writeln!(f, " is not satisfied:")?;
+        for error in &self.errors {
+            writeln!(f, "  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Report produced by [`debug_circuit`]
+#[derive(Clone, Debug)]
+pub struct ZkDebugReport {
+    /// Values produced by each opcode during witness generation
+    pub trace: Vec<DebugOpValue>,
+    /// The first unsatisfied opcode, `None` if the circuit is satisfied
+    pub failure: Option<ZkDebugFailure>,
+}
+
+/// Run the circuit with the given witnesses and public inputs through the
+/// halo2 mock prover, tracing the values produced by each opcode. If the
+/// constraints are not satisfied, the circuit is bisected over its opcodes
+/// to find the first one failing, which is reported along with its zkas
+/// source line. This is slow, so it is only meant for debugging.
+pub fn debug_circuit(
+    zkbin: &zkas::ZkBinary,
+    witnesses: &[Witness],
+    public_inputs: &[pallas::Base],
+) -> Result<ZkDebugReport> {
+    zkas_type_checks(&ZkCircuit::new(witnesses.to_vec(), zkbin), zkbin, public_inputs)?;
+
+    // Run the circuit truncated to its first `n` opcodes
+    let run = |n: usize| {
+        let mut circuit = ZkCircuit::new(witnesses.to_vec(), zkbin);
+        circuit.opcodes.truncate(n);
+        circuit.enable_trace();
+
+        let result = match MockProver::run(zkbin.k, &circuit, vec![public_inputs.to_vec()]) {
+            Ok(prover) => {
+                prover.verify().map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+            }
+            Err(e) => Err(vec![format!("Synthesis failed: {}", e)]),
+        };
+
+        let trace = circuit.tracer.opvalues.take().unwrap_or_default();
+        (result, trace)
+    };
+
+    let (result, trace) = run(zkbin.opcodes.len());
+    let Err(mut errors) = result else { return Ok(ZkDebugReport { trace, failure: None }) };
+
+    // With the witnesses type checked, the empty circuit is satisfied, so
+    // we look for the shortest failing prefix, whose last opcode is the
+    // first one failing.
+    let (mut lo, mut hi) = (0, zkbin.opcodes.len());
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        debug!(target: "zk::debug", "Checking the first {} opcodes", mid);
+        match run(mid).0 {
+            Ok(()) => lo = mid,
+            Err(e) => {
+                hi = mid;
+                errors = e;
+            }
+        }
+    }
+
+    let index = hi - 1;
+    let failure = ZkDebugFailure {
+        index,
+        opcode: zkbin.opcodes[index].0,
+        line: zkbin.debug_lines.as_ref().map(|lines| lines[index]),
+        errors,
+    };
+
+    Ok(ZkDebugReport { trace, failure: Some(failure) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::circuit::Value;
+
+    #[test]
+    fn zk_debug_circuit() -> Result<()> {
+        let bincode = include_bytes!("../../proof/arithmetic.zk.bin");
+        let zkbin = zkas::ZkBinary::decode(bincode)?;
+
+        let (a, b) = (pallas::Base::from(4), pallas::Base::from(2));
+        let witnesses = vec![Witness::Base(Value::known(a)), Witness::Base(Value::known(b))];
+
+        let report = debug_circuit(&zkbin, &witnesses, &[a + b, a * b, a - b])?;
+        assert!(report.failure.is_none());
+        assert_eq!(report.trace.len(), zkbin.opcodes.len());
+
+        // A wrong product should be reported at its constrain_instance
+        let report = debug_circuit(&zkbin, &witnesses, &[a + b, a * a, a - b])?;
+        let failure = report.failure.unwrap();
+        assert_eq!(failure.index, 3);
+        assert_eq!(failure.opcode, zkas::Opcode::ConstrainInstance);
+        assert_eq!(failure.line, Some(16));
+        assert!(!failure.errors.is_empty());
+
+        Ok(())
+    }
+}
@@ -40,5 +40,8 @@ pub mod cond_select;
 /// Conditional selection based on lhs (will output lhs if lhs==0, otherwise rhs)
 pub mod zero_cond;
 
+/// Lookups into built-in tables of byte and 4-bit bitwise operations
+pub mod table_lookup;
+
 /// Poseidon-based sparse Merkle tree chip
 pub mod smt;
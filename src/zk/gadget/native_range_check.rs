@@ -16,6 +16,7 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use halo2_gadgets::utilities::lookup_range_check::LookupRangeCheckConfig;
 use halo2_proofs::{
     circuit::{AssignedCell, Chip, Layouter, Region, Value},
     pasta::{
@@ -305,6 +306,49 @@ impl<const WINDOW_SIZE: usize, const NUM_BITS: usize> NativeRangeCheckChip<WINDO
     }
 }
 
+/// Range check an assigned value to an arbitrary number of bits, up to 253,
+/// using the `WINDOW_SIZE`-bit lookup range check. The value is decomposed
+/// into `num_bits / WINDOW_SIZE` full windows, and the remaining high bits
+/// are constrained with a short range check.
+pub fn lookup_range_check<const WINDOW_SIZE: usize>(
+    config: &LookupRangeCheckConfig<pallas::Base, WINDOW_SIZE>,
+    mut layouter: impl Layouter<pallas::Base>,
+    value: AssignedCell<pallas::Base, pallas::Base>,
+    num_bits: usize,
+) -> Result<(), plonk::Error> {
+    assert!(num_bits > 0 && num_bits <= 253);
+    let num_words = num_bits / WINDOW_SIZE;
+    let short_bits = num_bits % WINDOW_SIZE;
+
+    if num_words == 0 {
+        return config.copy_short_check(
+            layouter.namespace(|| format!("{}-bit short range check", short_bits)),
+            value,
+            short_bits,
+        )
+    }
+
+    // A strict check constrains the final running sum to zero, which is
+    // exactly what we want when there are no remaining bits.
+    let zs = config.copy_check(
+        layouter.namespace(|| format!("{}-word range check", num_words)),
+        value,
+        num_words,
+        short_bits == 0,
+    )?;
+
+    if short_bits == 0 {
+        return Ok(())
+    }
+
+    // The last running sum element holds the remaining high bits
+    config.copy_short_check(
+        layouter.namespace(|| format!("{}-bit short range check", short_bits)),
+        zs[num_words].clone(),
+        short_bits,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -407,6 +451,69 @@ mod tests {
         };
     }
 
+    #[test]
+    fn lookup_range_check_bits() {
+        const WINDOW_SIZE: usize = 3;
+
+        #[derive(Default)]
+        struct LookupRangeCheckCircuit {
+            a: Value<pallas::Base>,
+            num_bits: usize,
+        }
+
+        impl Circuit<pallas::Base> for LookupRangeCheckCircuit {
+            type Config = (LookupRangeCheckConfig<pallas::Base, WINDOW_SIZE>, Column<Advice>);
+            type FloorPlanner = floor_planner::V1;
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                Self { a: Value::unknown(), num_bits: self.num_bits }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+                let w = meta.advice_column();
+                meta.enable_equality(w);
+                let z = meta.advice_column();
+                let table_column = meta.lookup_table_column();
+
+                let constants = meta.fixed_column();
+                meta.enable_constant(constants);
+                (LookupRangeCheckConfig::configure(meta, z, table_column), w)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<pallas::Base>,
+            ) -> Result<(), plonk::Error> {
+                config.0.load(&mut layouter)?;
+                let a = assign_free_advice(layouter.namespace(|| "load a"), config.1, self.a)?;
+                lookup_range_check(
+                    &config.0,
+                    layouter.namespace(|| "range check a"),
+                    a,
+                    self.num_bits,
+                )
+            }
+        }
+
+        // Short check only, full windows only, and both
+        for num_bits in [2, 6, 8] {
+            let max = pallas::Base::from((1 << num_bits) - 1);
+            for a in [pallas::Base::zero(), pallas::Base::one(), max] {
+                let circuit = LookupRangeCheckCircuit { a: Value::known(a), num_bits };
+                let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+                prover.assert_satisfied();
+            }
+
+            for a in [max + pallas::Base::one(), -pallas::Base::one()] {
+                let circuit = LookupRangeCheckCircuit { a: Value::known(a), num_bits };
+                let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+        }
+    }
+
     // cargo test --release --all-features --lib native_range_check -- --nocapture
     #[test]
     fn native_range_check_2() {
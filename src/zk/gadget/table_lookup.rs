@@ -0,0 +1,252 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    pasta::pallas,
+    plonk,
+    plonk::{Advice, Column, ConstraintSystem, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+/// Built-in lookup tables, addressed by their tag.
+/// Every row of a table is a triple `(a, b, c)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u64)]
+pub enum LookupTable {
+    /// `(x, 0, 0)` for every byte `x`
+    Byte = 0,
+    /// `(a, b, a ^ b)` for every pair of 4-bit values
+    Xor4 = 1,
+    /// `(a, b, a & b)` for every pair of 4-bit values
+    And4 = 2,
+}
+
+impl LookupTable {
+    pub const ALL: [Self; 3] = [Self::Byte, Self::Xor4, Self::And4];
+
+    pub fn from_repr(tag: u64) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| *t as u64 == tag)
+    }
+
+    /// Rows of the table
+    pub fn rows(&self) -> Vec<[u64; 3]> {
+        let nibbles = || (0..16).flat_map(|a| (0..16).map(move |b| (a, b)));
+        match self {
+            Self::Byte => (0..256).map(|x| [x, 0, 0]).collect(),
+            Self::Xor4 => nibbles().map(|(a, b)| [a, b, a ^ b]).collect(),
+            Self::And4 => nibbles().map(|(a, b)| [a, b, a & b]).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TableLookupConfig {
+    selector: Selector,
+    tag: Column<Fixed>,
+    advices: [Column<Advice>; 3],
+    table_tag: TableColumn,
+    table: [TableColumn; 3],
+}
+
+/// Chip checking that a triple of cells is a row of one of the
+/// [`LookupTable`]s, which all share the same tagged table columns.
+#[derive(Clone, Debug)]
+pub struct TableLookupChip {
+    config: TableLookupConfig,
+}
+
+impl TableLookupChip {
+    pub fn construct(config: TableLookupConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configure the chip.
+    ///
+    /// Advice columns:
+    /// * `[0]` - a
+    /// * `[1]` - b
+    /// * `[2]` - c
+    pub fn configure(
+        meta: &mut ConstraintSystem<pallas::Base>,
+        advices: [Column<Advice>; 3],
+    ) -> TableLookupConfig {
+        for i in advices {
+            meta.enable_equality(i);
+        }
+
+        let selector = meta.complex_selector();
+        let tag = meta.fixed_column();
+        let table_tag = meta.lookup_table_column();
+        let table = [(); 3].map(|_| meta.lookup_table_column());
+
+        // When the selector is off, this looks up (0, 0, 0, 0),
+        // which is the first row of the byte table.
+        meta.lookup(|meta| {
+            let s = meta.query_selector(selector);
+            let tag = meta.query_fixed(tag, Rotation::cur());
+            let mut lookups = vec![(s.clone() * tag, table_tag)];
+            for (advice, column) in advices.iter().zip(table) {
+                let value = meta.query_advice(*advice, Rotation::cur());
+                lookups.push((s.clone() * value, column));
+            }
+            lookups
+        });
+
+        TableLookupConfig { selector, tag, advices, table_tag, table }
+    }
+
+    /// Load all the built-in tables
+    pub fn load_table(
+        &self,
+        layouter: &mut impl Layouter<pallas::Base>,
+    ) -> Result<(), plonk::Error> {
+        layouter.assign_table(
+            || "lookup tables",
+            |mut table| {
+                let mut offset = 0;
+                for lookup_table in LookupTable::ALL {
+                    let tag = pallas::Base::from(lookup_table as u64);
+                    for row in lookup_table.rows() {
+                        table.assign_cell(
+                            || "tag",
+                            self.config.table_tag,
+                            offset,
+                            || Value::known(tag),
+                        )?;
+                        for (column, value) in self.config.table.iter().zip(row) {
+                            table.assign_cell(
+                                || "table cell",
+                                *column,
+                                offset,
+                                || Value::known(pallas::Base::from(value)),
+                            )?;
+                        }
+                        offset += 1;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Constrain `(a, b, c)` to be a row of `table`
+    pub fn lookup(
+        &self,
+        mut layouter: impl Layouter<pallas::Base>,
+        table: LookupTable,
+        cells: [AssignedCell<pallas::Base, pallas::Base>; 3],
+    ) -> Result<(), plonk::Error> {
+        layouter.assign_region(
+            || format!("{:?} table lookup", table),
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                region.assign_fixed(
+                    || "tag",
+                    self.config.tag,
+                    0,
+                    || Value::known(pallas::Base::from(table as u64)),
+                )?;
+                for (cell, advice) in cells.iter().zip(self.config.advices) {
+                    cell.copy_advice(|| "lookup input", &mut region, advice, 0)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::assign_free_advice;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::{Circuit, Error},
+    };
+
+    #[derive(Default)]
+    struct MyCircuit {
+        lookups: Vec<(LookupTable, [u64; 3])>,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = (TableLookupConfig, [Column<Advice>; 3]);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+            (TableLookupChip::configure(meta, advices), advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let chip = TableLookupChip::construct(config.0);
+            chip.load_table(&mut layouter)?;
+
+            for (table, row) in &self.lookups {
+                let mut cells = vec![];
+                for (value, advice) in row.iter().zip(config.1) {
+                    cells.push(assign_free_advice(
+                        layouter.namespace(|| "load value"),
+                        advice,
+                        Value::known(pallas::Base::from(*value)),
+                    )?);
+                }
+                chip.lookup(layouter.namespace(|| "lookup"), *table, cells.try_into().unwrap())?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn table_lookup() {
+        let lookups = vec![
+            (LookupTable::Byte, [0xab, 0, 0]),
+            (LookupTable::Xor4, [0b1010, 0b0110, 0b1100]),
+            (LookupTable::And4, [0b1010, 0b0110, 0b0010]),
+        ];
+        let prover = MockProver::run(10, &MyCircuit { lookups }, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        // Out of range
+        let lookups = vec![(LookupTable::Byte, [256, 0, 0])];
+        let prover = MockProver::run(10, &MyCircuit { lookups }, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Wrong result
+        let lookups = vec![(LookupTable::Xor4, [0b1010, 0b0110, 0b0010])];
+        let prover = MockProver::run(10, &MyCircuit { lookups }, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Row of another table
+        let lookups = vec![(LookupTable::And4, [0b1010, 0b0110, 0b1100])];
+        let prover = MockProver::run(10, &MyCircuit { lookups }, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -31,15 +31,31 @@ pub mod gadget;
 pub mod proof;
 pub use proof::{Proof, ProvingKey, VerifyingKey};
 
+/// Concurrent proof creation
+pub mod session;
+pub use session::ProvingSession;
+
+/// Circuit size and cost reporting
+pub mod stats;
+pub use stats::ZkCircuitStats;
+
+/// On-disk cache of circuit keys
+pub mod cache;
+pub use cache::KeyCache;
+
 /// Trace computation of intermediate values in circuit
 mod tracer;
 pub use tracer::DebugOpValue;
 
 mod debug;
-pub use debug::zkas_type_checks;
+pub use debug::{debug_circuit, zkas_type_checks, ZkDebugFailure, ZkDebugReport};
 #[cfg(feature = "tinyjson")]
 pub use debug::{export_witness_json, import_witness_json};
 
+/// Benchmarking of the shipped circuits
+#[cfg(feature = "tinyjson")]
+pub mod bench;
+
 pub mod halo2 {
     pub use halo2_proofs::{
         arithmetic::Field,
@@ -25,7 +25,7 @@ use darkfi_serial::{SerialDecodable, SerialEncodable};
 use halo2_proofs::{
     helpers::SerdeFormat,
     plonk,
-    plonk::{Circuit, SingleVerifier},
+    plonk::{BatchVerifier, Circuit, SingleVerifier},
     poly::commitment::Params,
     transcript::{Blake2bRead, Blake2bWrite},
 };
@@ -215,3 +215,35 @@ impl Proof {
         Proof(bytes)
     }
 }
+
+/// Verify a batch of proofs against their verifying keys and public inputs,
+/// given in the same order. Proofs sharing a verifying key are checked
+/// together, amortizing the multiscalar multiplication work among them.
+/// Returns `false` if any proof is invalid, without telling which one, so
+/// callers should fall back to verifying them individually to find out.
+pub fn verify_batch(
+    proofs: &[&Proof],
+    vks: &[&VerifyingKey],
+    public_inputs: &[&[pallas::Base]],
+) -> bool {
+    if proofs.len() != vks.len() || proofs.len() != public_inputs.len() {
+        return false
+    }
+
+    // Group the proofs by verifying key, which are compared by reference
+    // since callers hold them in a single map.
+    let mut batches: Vec<(&VerifyingKey, BatchVerifier<vesta::Affine>)> = vec![];
+    for ((proof, vk), instances) in proofs.iter().zip(vks).zip(public_inputs) {
+        let index = match batches.iter().position(|(k, _)| std::ptr::eq(*k, *vk)) {
+            Some(i) => i,
+            None => {
+                batches.push((vk, BatchVerifier::new()));
+                batches.len() - 1
+            }
+        };
+
+        batches[index].1.add_proof(vec![vec![instances.to_vec()]], proof.0.clone());
+    }
+
+    batches.into_iter().all(|(vk, batch)| batch.finalize(&vk.params, &vk.vk))
+}
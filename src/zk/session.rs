@@ -0,0 +1,172 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Concurrent proof creation.
+//!
+//! Transactions with many inputs, outputs or calls need a proof for each
+//! of them, and these don't depend on each other. A [`ProvingSession`]
+//! collects the circuits to prove and creates their proofs concurrently
+//! on a pool of threads, returning them in the order they were queued.
+use std::{
+    num::NonZeroUsize,
+    sync::Mutex,
+    thread::{available_parallelism, scope},
+};
+
+use darkfi_sdk::pasta::pallas;
+use log::debug;
+use rand::rngs::OsRng;
+
+use super::{Proof, ProvingKey, ZkCircuit};
+use crate::{Error, Result};
+
+/// A queued proof
+struct ProvingJob<'a> {
+    pk: &'a ProvingKey,
+    circuit: ZkCircuit,
+    public_inputs: Vec<pallas::Base>,
+}
+
+/// A set of independent proofs to create concurrently
+#[derive(Default)]
+pub struct ProvingSession<'a> {
+    jobs: Vec<ProvingJob<'a>>,
+    threads: Option<NonZeroUsize>,
+}
+
+impl<'a> ProvingSession<'a> {
+    /// Create a new session, using as many threads as available cores
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of threads used to create the proofs
+    pub fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Queue a circuit to prove with the given key and public inputs.
+    /// Returns the index of its proof in the output of [`Self::prove`].
+    pub fn push(
+        &mut self,
+        pk: &'a ProvingKey,
+        circuit: ZkCircuit,
+        public_inputs: Vec<pallas::Base>,
+    ) -> usize {
+        self.jobs.push(ProvingJob { pk, circuit, public_inputs });
+        self.jobs.len() - 1
+    }
+
+    /// Number of queued proofs
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Check if the session has no queued proofs
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Create all queued proofs, returning them in the order they were
+    /// queued. Fails with the first error encountered.
+    pub fn prove(self) -> Result<Vec<Proof>> {
+        let threads = self
+            .threads
+            .or_else(|| available_parallelism().ok())
+            .map_or(1, |n| n.get())
+            .min(self.jobs.len());
+
+        debug!(target: "zk::session", "Creating {} proofs on {} threads", self.jobs.len(), threads);
+
+        let mut results: Vec<Option<Result<Proof>>> = Vec::with_capacity(self.jobs.len());
+        results.resize_with(self.jobs.len(), || None);
+        let results = Mutex::new(results);
+        let queue = Mutex::new(self.jobs.into_iter().enumerate());
+
+        scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| loop {
+                    // The lock is released right after taking the next job
+                    let Some((index, job)) = queue.lock().unwrap().next() else { break };
+
+                    let proof = Proof::create(job.pk, &[job.circuit], &job.public_inputs, OsRng)
+                        .map_err(Error::from);
+                    let failed = proof.is_err();
+                    results.lock().unwrap()[index] = Some(proof);
+
+                    // No point in going on, the whole session fails
+                    if failed {
+                        break
+                    }
+                });
+            }
+        });
+
+        let results = results.into_inner().unwrap();
+        let mut proofs = Vec::with_capacity(results.len());
+        for result in results {
+            match result {
+                Some(proof) => proofs.push(proof?),
+                // Jobs are only skipped after another one failed, so we
+                // return that error instead.
+                None => continue,
+            }
+        }
+
+        Ok(proofs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        zk::{empty_witnesses, halo2::Value, Witness},
+        zkas::ZkBinary,
+    };
+
+    #[test]
+    fn zk_proving_session() -> Result<()> {
+        let bincode = include_bytes!("../../proof/arithmetic.zk.bin");
+        let zkbin = ZkBinary::decode(bincode)?;
+        let circuit = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin);
+        let pk = ProvingKey::build(zkbin.k, &circuit);
+        let vk = crate::zk::VerifyingKey::build(zkbin.k, &circuit);
+
+        let mut session = ProvingSession::new().with_threads(NonZeroUsize::new(2).unwrap());
+        let mut expected = vec![];
+        for i in 0..4_u64 {
+            let (a, b) = (pallas::Base::from(i + 2), pallas::Base::from(i));
+            let witnesses = vec![Witness::Base(Value::known(a)), Witness::Base(Value::known(b))];
+            let public_inputs = vec![a + b, a * b, a - b];
+            let index = session.push(&pk, ZkCircuit::new(witnesses, &zkbin), public_inputs.clone());
+            assert_eq!(index, i as usize);
+            expected.push(public_inputs);
+        }
+        assert_eq!(session.len(), 4);
+
+        let proofs = session.prove()?;
+        assert_eq!(proofs.len(), 4);
+        for (proof, public_inputs) in proofs.iter().zip(expected.iter()) {
+            proof.verify(&vk, public_inputs)?;
+        }
+
+        Ok(())
+    }
+}
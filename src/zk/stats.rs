@@ -0,0 +1,280 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Constraint-count and cost reporting for zkvm circuits.
+//!
+//! [`ZkCircuit::stats`] lays out a circuit without creating any proof,
+//! recording how many rows it uses along with the shape of its
+//! constraint system, so that the cost of proving it can be priced.
+use std::{fmt, time::Duration};
+
+use darkfi_sdk::pasta::pallas;
+use halo2_proofs::{
+    circuit::Value,
+    plonk,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner,
+        Instance, Selector,
+    },
+};
+
+use super::ZkCircuit;
+use crate::Result;
+
+/// Rough single-core cost of one point in a multiscalar multiplication,
+/// used to turn [`ZkCircuitStats::msm_points`] into a proving time.
+const NANOS_PER_MSM_POINT: u64 = 1_000;
+
+/// Size and cost figures of a zkvm circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZkCircuitStats {
+    /// The circuit's k param, i.e. it has `2^k` rows
+    pub k: u32,
+    /// Rows used by the circuit's regions and lookup tables
+    pub rows: usize,
+    /// Rows reserved for blinding factors at the end of the circuit
+    pub blinding_rows: usize,
+    /// Number of advice columns
+    pub advice_columns: usize,
+    /// Number of fixed columns, not counting selectors
+    pub fixed_columns: usize,
+    /// Number of instance columns
+    pub instance_columns: usize,
+    /// Number of selectors
+    pub selectors: usize,
+    /// Number of custom gates
+    pub gates: usize,
+    /// Number of lookup arguments
+    pub lookups: usize,
+    /// Maximum degree of the constraint system
+    pub degree: usize,
+}
+
+impl ZkCircuitStats {
+    /// Rows available to the circuit, besides those reserved for blinding
+    pub fn usable_rows(&self) -> usize {
+        (1 << self.k) - self.blinding_rows - 1
+    }
+
+    /// Number of `2^k` sized multiscalar multiplications needed to commit
+    /// to the prover's polynomials: the advice columns, three for each
+    /// lookup argument, and the pieces of the quotient polynomial.
+    pub fn msm_count(&self) -> usize {
+        self.advice_columns + 3 * self.lookups + self.degree.saturating_sub(1)
+    }
+
+    /// Total points in the prover's multiscalar multiplications, which
+    /// dominate proving time. This is a machine-independent measure of
+    /// proving cost.
+    pub fn msm_points(&self) -> u64 {
+        (self.msm_count() as u64) << self.k
+    }
+
+    /// Estimated single-core proving time. Permutation arguments and FFTs
+    /// aren't accounted for, so this is a lower bound meant for comparing
+    /// circuits rather than an accurate figure.
+    pub fn estimated_proving_time(&self) -> Duration {
+        Duration::from_nanos(self.msm_points() * NANOS_PER_MSM_POINT)
+    }
+}
+
+impl fmt::Display for ZkCircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "k:                {}", self.k)?;
+        writeln!(f, "rows:             {} / {}", self.rows, self.usable_rows())?;
+        writeln!(f, "advice columns:   {}", self.advice_columns)?;
+        writeln!(f, "fixed columns:    {}", self.fixed_columns)?;
+        writeln!(f, "instance columns: {}", self.instance_columns)?;
+        writeln!(f, "selectors:        {}", self.selectors)?;
+        writeln!(f, "gates:            {}", self.gates)?;
+        writeln!(f, "lookups:          {}", self.lookups)?;
+        writeln!(f, "degree:           {}", self.degree)?;
+        writeln!(f, "msm points:       {}", self.msm_points())?;
+        write!(f, "proving time:     ~{:?}", self.estimated_proving_time())
+    }
+}
+
+/// Assignment backend recording the highest row the circuit touches
+#[derive(Default)]
+struct RowCounter {
+    rows: usize,
+}
+
+impl RowCounter {
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl Assignment<pallas::Base> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        _: A,
+        _: &Selector,
+        row: usize,
+    ) -> std::result::Result<(), plonk::Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(
+        &self,
+        _: Column<Instance>,
+        _: usize,
+    ) -> std::result::Result<Value<pallas::Base>, plonk::Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> std::result::Result<(), plonk::Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<pallas::Base>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> std::result::Result<(), plonk::Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<pallas::Base>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> std::result::Result<(), plonk::Error> {
+        self.touch(left_row.max(right_row));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<pallas::Base>>,
+    ) -> std::result::Result<(), plonk::Error> {
+        // Fills the rest of a lookup table column, which is no more
+        // used rows than the table itself.
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+impl ZkCircuit {
+    /// Lay out the circuit and report its size and estimated cost.
+    /// Witness values aren't needed, so this works on circuits built
+    /// with [`super::empty_witnesses`].
+    pub fn stats(&self) -> Result<ZkCircuitStats> {
+        let mut cs = ConstraintSystem::default();
+        let config = ZkCircuit::configure_with_params(&mut cs, self.params());
+
+        let mut counter = RowCounter::default();
+        <ZkCircuit as Circuit<pallas::Base>>::FloorPlanner::synthesize(
+            &mut counter,
+            self,
+            config,
+            cs.constants().clone(),
+        )?;
+
+        Ok(ZkCircuitStats {
+            k: self.k,
+            rows: counter.rows,
+            blinding_rows: cs.blinding_factors(),
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns(),
+            instance_columns: cs.num_instance_columns(),
+            selectors: cs.num_selectors(),
+            gates: cs.gates().len(),
+            lookups: cs.lookups().len(),
+            degree: cs.degree(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{zk::empty_witnesses, zkas::ZkBinary};
+
+    #[test]
+    fn zk_circuit_stats() -> Result<()> {
+        let bincode = include_bytes!("../../proof/arithmetic.zk.bin");
+        let zkbin = ZkBinary::decode(bincode)?;
+        let circuit = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin);
+
+        let stats = circuit.stats()?;
+        assert_eq!(stats.k, zkbin.k);
+        assert_eq!(stats.advice_columns, 10);
+        assert_eq!(stats.instance_columns, 1);
+        assert!(stats.rows > 0 && stats.rows <= stats.usable_rows());
+        assert!(stats.estimated_proving_time() > Duration::ZERO);
+
+        // Circuits using lookup tables get an extra lookup argument
+        let bincode = include_bytes!("../../proof/opcodes.zk.bin");
+        let zkbin = ZkBinary::decode(bincode)?;
+        let opcodes = ZkCircuit::new(empty_witnesses(&zkbin)?, &zkbin).stats()?;
+        assert_eq!(opcodes.lookups, stats.lookups + 1);
+        assert!(opcodes.rows > stats.rows);
+
+        Ok(())
+    }
+}
@@ -62,9 +62,10 @@ use super::{
         arithmetic::{ArithChip, ArithConfig, ArithInstruction},
         cond_select::{ConditionalSelectChip, ConditionalSelectConfig},
         less_than::{LessThanChip, LessThanConfig},
-        native_range_check::{NativeRangeCheckChip, NativeRangeCheckConfig},
+        native_range_check::{lookup_range_check, NativeRangeCheckChip, NativeRangeCheckConfig},
         small_range_check::{SmallRangeCheckChip, SmallRangeCheckConfig},
         smt,
+        table_lookup::{LookupTable, TableLookupChip, TableLookupConfig},
         zero_cond::{ZeroCondChip, ZeroCondConfig},
     },
     tracer::ZkTracer,
@@ -112,6 +113,9 @@ enum VmChip {
     /// 253 bit native range check
     NativeRange253(NativeRangeCheckConfig<K, 253>),
 
+    /// Lookup range check, used for arbitrary bit ranges
+    LookupRange(LookupRangeCheckConfig<pallas::Base, K>),
+
     /// 253 bit `a < b` check
     LessThan(LessThanConfig<K, 253>),
 
@@ -123,6 +127,9 @@ enum VmChip {
 
     /// Zero-Cond selection
     ZeroCond(ZeroCondConfig<pallas::Base>),
+
+    /// Lookups into the built-in tables
+    TableLookup(TableLookupConfig),
 }
 
 /// zkvm configuration
@@ -241,6 +248,16 @@ impl VmConfig {
         Some(NativeRangeCheckChip::construct(range_config.clone()))
     }
 
+    fn lookuprange_config(&self) -> Option<LookupRangeCheckConfig<pallas::Base, K>> {
+        let Some(VmChip::LookupRange(range_config)) =
+            self.chips.iter().find(|&c| matches!(c, VmChip::LookupRange(_)))
+        else {
+            return None
+        };
+
+        Some(*range_config)
+    }
+
     fn lessthan_chip(&self) -> Option<LessThanChip<K, 253>> {
         let Some(VmChip::LessThan(lessthan_config)) =
             self.chips.iter().find(|&c| matches!(c, VmChip::LessThan(_)))
@@ -260,6 +277,16 @@ impl VmConfig {
 
         Some(SmallRangeCheckChip::construct(boolcheck_config.clone()))
     }
+
+    fn tablelookup_chip(&self) -> Option<TableLookupChip> {
+        let Some(VmChip::TableLookup(tablelookup_config)) =
+            self.chips.iter().find(|&c| matches!(c, VmChip::TableLookup(_)))
+        else {
+            return None
+        };
+
+        Some(TableLookupChip::construct(tablelookup_config.clone()))
+    }
 }
 
 /// Configuration parameters for the circuit.
@@ -276,10 +303,12 @@ pub struct ZkParams {
     init_boolcheck: bool,
     init_condselect: bool,
     init_zerocond: bool,
+    init_tablelookup: bool,
 }
 
 #[derive(Clone)]
 pub struct ZkCircuit {
+    pub(super) k: u32,
     constants: Vec<String>,
     pub(super) witnesses: Vec<Witness>,
     literals: Vec<(LitType, String)>,
@@ -292,6 +321,7 @@ impl ZkCircuit {
         let constants = circuit_code.constants.iter().map(|x| x.1.clone()).collect();
         let literals = circuit_code.literals.clone();
         Self {
+            k: circuit_code.k,
             constants,
             witnesses,
             literals,
@@ -312,6 +342,7 @@ impl Circuit<pallas::Base> for ZkCircuit {
 
     fn without_witnesses(&self) -> Self {
         Self {
+            k: self.k,
             constants: self.constants.clone(),
             witnesses: self.witnesses.clone(),
             literals: self.literals.clone(),
@@ -378,6 +409,9 @@ impl Circuit<pallas::Base> for ZkCircuit {
         // Conditions on which we enable the zero cond selection chip
         let init_zerocond = opcodes.contains(&Opcode::ZeroCondSelect);
 
+        // Conditions on which we enable the table lookup chip
+        let init_tablelookup = opcodes.contains(&Opcode::TableLookup);
+
         ZkParams {
             init_ecc,
             init_poseidon,
@@ -388,12 +422,13 @@ impl Circuit<pallas::Base> for ZkCircuit {
             init_boolcheck,
             init_condselect,
             init_zerocond,
+            init_tablelookup,
         }
     }
 
     fn configure_with_params(
         meta: &mut ConstraintSystem<pallas::Base>,
-        _params: Self::Params,
+        params: Self::Params,
     ) -> Self::Config {
         // Advice columns used in the circuit
         let mut advices = vec![];
@@ -524,7 +559,7 @@ impl Circuit<pallas::Base> for ZkCircuit {
         let zerocond_config = ZeroCondChip::configure(meta, advices[1..5].try_into().unwrap());
 
         // Later we'll use this for optimisation
-        let chips = vec![
+        let mut chips = vec![
             VmChip::Ecc(ecc_config),
             VmChip::Merkle((merkle_cfg1, merkle_cfg2)),
             VmChip::SparseTree(smt_config),
@@ -533,12 +568,21 @@ impl Circuit<pallas::Base> for ZkCircuit {
             VmChip::Arithmetic(arith_config),
             VmChip::NativeRange64(native_64_range_check_config),
             VmChip::NativeRange253(native_253_range_check_config),
+            VmChip::LookupRange(range_check),
             VmChip::LessThan(lessthan_config),
             VmChip::BoolCheck(boolcheck_config),
             VmChip::CondSelect(condselect_config),
             VmChip::ZeroCond(zerocond_config),
         ];
 
+        // The lookup tables are only configured for circuits using them,
+        // so the keys of other circuits don't change.
+        if params.init_tablelookup {
+            let tablelookup_config =
+                TableLookupChip::configure(meta, advices[0..3].try_into().unwrap());
+            chips.push(VmChip::TableLookup(tablelookup_config));
+        }
+
         VmConfig { primary, witness: advices[0], chips }
     }
 
@@ -563,9 +607,6 @@ impl Circuit<pallas::Base> for ZkCircuit {
         // Offset for public inputs
         let mut public_inputs_offset = 0;
 
-        // Offset for literals
-        let mut literals_offset = 0;
-
         // Load the Sinsemilla generator lookup table used by the whole circuit.
         if let Some(VmChip::Sinsemilla((sinsemilla_cfg1, _))) =
             config.chips.iter().find(|&c| matches!(c, VmChip::Sinsemilla(_)))
@@ -595,6 +636,7 @@ impl Circuit<pallas::Base> for ZkCircuit {
 
         // Construct the 253-bit NativeRangeCheck and LessThan chips.
         let rangecheck253_chip = config.rangecheck253_chip();
+        let lookuprange_config = config.lookuprange_config();
         let lessthan_chip = config.lessthan_chip();
 
         if let Some(VmChip::NativeRange253(rangecheck253_config)) =
@@ -624,6 +666,13 @@ impl Circuit<pallas::Base> for ZkCircuit {
         // Construct the zero_cond selection chip
         let zerocond_chip = config.zerocond_chip();
 
+        // Construct the table lookup chip and load its tables
+        let tablelookup_chip = config.tablelookup_chip();
+        if let Some(ref chip) = tablelookup_chip {
+            trace!(target: "zk::vm", "Initializing lookup tables");
+            chip.load_table(&mut layouter)?;
+        }
+
         // Construct sparse Merkle tree chip
         let smt_chip = config.smt_chip().unwrap();
 
@@ -1069,10 +1118,8 @@ impl Circuit<pallas::Base> for ZkCircuit {
 
                 Opcode::WitnessBase => {
                     trace!(target: "zk::vm", "Executing `WitnessBase{:?}` opcode", opcode.1);
-                    //let args = &opcode.1;
-
-                    let lit = litheap[literals_offset];
-                    literals_offset += 1;
+                    let args = &opcode.1;
+                    let lit = litheap[args[0].1];
 
                     let witness = assign_free_advice(
                         layouter.namespace(|| "Witness literal"),
@@ -1096,8 +1143,7 @@ impl Circuit<pallas::Base> for ZkCircuit {
                     trace!(target: "zk::vm", "Executing `RangeCheck{:?}` opcode", opcode.1);
                     let args = &opcode.1;
 
-                    let lit = litheap[literals_offset];
-                    literals_offset += 1;
+                    let lit = litheap[args[0].1];
 
                     let arg = heap[args[1].1].clone();
 
@@ -1114,6 +1160,16 @@ impl Circuit<pallas::Base> for ZkCircuit {
                                 arg.try_into()?,
                             )?;
                         }
+                        // Other ranges are checked using the lookup range check,
+                        // whose table is loaded along with the native ones.
+                        x @ 1..=252 => {
+                            lookup_range_check(
+                                lookuprange_config.as_ref().unwrap(),
+                                layouter.namespace(|| format!("copy range check {}", x)),
+                                arg.try_into()?,
+                                x as usize,
+                            )?;
+                        }
                         x => {
                             error!(target: "zk::vm", "Unsupported bit-range {} for range_check", x);
                             return Err(plonk::Error::Synthesis)
@@ -1208,6 +1264,27 @@ impl Circuit<pallas::Base> for ZkCircuit {
                     heap.push(HeapVar::Base(out));
                 }
 
+                Opcode::TableLookup => {
+                    trace!(target: "zk::vm", "Executing `TableLookup{:?}` opcode", opcode.1);
+                    let args = &opcode.1;
+
+                    let Some(table) = LookupTable::from_repr(litheap[args[0].1]) else {
+                        error!(target: "zk::vm", "Unknown lookup table {}", litheap[args[0].1]);
+                        return Err(plonk::Error::Synthesis)
+                    };
+
+                    let a: AssignedCell<Fp, Fp> = heap[args[1].1].clone().try_into()?;
+                    let b: AssignedCell<Fp, Fp> = heap[args[2].1].clone().try_into()?;
+                    let c: AssignedCell<Fp, Fp> = heap[args[3].1].clone().try_into()?;
+
+                    tablelookup_chip.as_ref().unwrap().lookup(
+                        layouter.namespace(|| "table_lookup"),
+                        table,
+                        [a, b, c],
+                    )?;
+                    self.tracer.push_void();
+                }
+
                 Opcode::ConstrainEqualBase => {
                     trace!(target: "zk::vm", "Executing `ConstrainEqualBase{:?}` opcode", opcode.1);
                     let args = &opcode.1;
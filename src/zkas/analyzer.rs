@@ -92,13 +92,12 @@ impl Analyzer {
             }
 
             // Edge-cases for some opcodes
-            #[allow(clippy::single_match)]
             match &statement.opcode {
                 Opcode::RangeCheck => {
                     if let Arg::Lit(arg0) = &statement.rhs[0] {
-                        if &arg0.name != "64" && &arg0.name != "253" {
+                        if !matches!(arg0.name.parse::<u64>(), Ok(1..=253)) {
                             return Err(self.error.abort(
-                                "Supported range checks are only 64 and 253 bits.",
+                                "Supported range checks are between 1 and 253 bits.",
                                 arg0.line,
                                 arg0.column,
                             ))
@@ -112,6 +111,24 @@ impl Analyzer {
                     }
                 }
 
+                Opcode::TableLookup => {
+                    if let Arg::Lit(arg0) = &statement.rhs[0] {
+                        if !matches!(arg0.name.parse::<u64>(), Ok(0..=2)) {
+                            return Err(self.error.abort(
+                                "Supported lookup tables are 0 (byte), 1 (xor4) and 2 (and4).",
+                                arg0.line,
+                                arg0.column,
+                            ))
+                        }
+                    } else {
+                        return Err(self.error.abort(
+                            "Invalid argument for table_lookup opcode.",
+                            statement.line,
+                            0,
+                        ))
+                    }
+                }
+
                 _ => {}
             }
 
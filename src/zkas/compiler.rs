@@ -18,6 +18,7 @@
 
 use std::{io::Result, str::Chars};
 
+use darkfi_sdk::crypto::{schnorr::SchnorrSecret, PublicKey, SecretKey};
 use darkfi_serial::{serialize, VarInt};
 
 use super::{
@@ -30,6 +31,8 @@ use super::{
 pub const BINARY_VERSION: u8 = 2;
 /// Magic bytes prepended to the binary
 pub const MAGIC_BYTES: [u8; 4] = [0x0b, 0x01, 0xb1, 0x35];
+/// Marker of the trailing section holding the binary's hash and signature
+pub const INTEGRITY_MARKER: &[u8] = b".integrity";
 
 pub struct Compiler {
     namespace: String,
@@ -39,6 +42,7 @@ pub struct Compiler {
     statements: Vec<Statement>,
     literals: Vec<Literal>,
     debug_info: bool,
+    author: Option<SecretKey>,
     error: ErrorEmitter,
 }
 
@@ -60,7 +64,23 @@ impl Compiler {
         let lines: Vec<String> = source.as_str().lines().map(|x| x.to_string()).collect();
         let error = ErrorEmitter::new("Compiler", filename, lines);
 
-        Self { namespace, k, constants, witnesses, statements, literals, debug_info, error }
+        Self {
+            namespace,
+            k,
+            constants,
+            witnesses,
+            statements,
+            literals,
+            debug_info,
+            author: None,
+            error,
+        }
+    }
+
+    /// Sign the compiled binary with the given author key
+    pub fn with_author(mut self, secret: SecretKey) -> Self {
+        self.author = Some(secret);
+        self
     }
 
     pub fn compile(&self) -> Result<Vec<u8>> {
@@ -91,8 +111,15 @@ impl Compiler {
         // Currently, our literals are only Uint64 types, in the binary we'll
         // add them here in the .literal section. In the VM, they will be on
         // their own heap, used for reference by opcodes.
+        // Repeated literals are only written once, and opcodes reference
+        // them by their index.
         bincode.extend_from_slice(b".literal");
+        let mut literals: Vec<&Literal> = vec![];
         for i in &self.literals {
+            if literals.iter().any(|l| l.typ == i.typ && l.name == i.name) {
+                continue
+            }
+            literals.push(i);
             bincode.push(i.typ as u8);
             bincode.extend_from_slice(&serialize(&i.name));
         }
@@ -133,7 +160,7 @@ impl Compiler {
                         ))
                     }
                     Arg::Lit(lit) => {
-                        if let Some(found) = Compiler::lookup_literal(&self.literals, &lit.name) {
+                        if let Some(found) = Compiler::lookup_literal(&literals, &lit.name) {
                             bincode.push(HeapType::Lit as u8);
                             bincode.extend_from_slice(&serialize(&VarInt(found as u64)));
                             continue
@@ -150,12 +177,30 @@ impl Compiler {
             }
         }
 
-        // If we're not doing debug info, we're done here and can return.
-        if !self.debug_info {
-            return Ok(bincode)
+        // If we're doing debug info, in the .debug section we write the
+        // source line of each statement, in the same order as the .circuit
+        // section.
+        if self.debug_info {
+            bincode.extend_from_slice(b".debug");
+            for i in &self.statements {
+                bincode.extend_from_slice(&serialize(&VarInt(i.line as u64)));
+            }
         }
 
-        // TODO: Otherwise, we proceed appending debug info.
+        // Finally, the .integrity section holds the hash of everything
+        // written before it and, optionally, the author's signature of
+        // that hash.
+        let hash = blake3::hash(&bincode);
+        bincode.extend_from_slice(INTEGRITY_MARKER);
+        bincode.extend_from_slice(hash.as_bytes());
+        match self.author {
+            Some(secret) => {
+                bincode.push(1);
+                bincode.extend_from_slice(&serialize(&PublicKey::from_secret(secret)));
+                bincode.extend_from_slice(&serialize(&secret.sign(hash.as_bytes())));
+            }
+            None => bincode.push(0),
+        }
 
         Ok(bincode)
     }
@@ -170,7 +215,7 @@ impl Compiler {
         None
     }
 
-    fn lookup_literal(literals: &[Literal], name: &str) -> Option<usize> {
+    fn lookup_literal(literals: &[&Literal], name: &str) -> Option<usize> {
         for (idx, n) in literals.iter().enumerate() {
             if n.name == name {
                 return Some(idx)
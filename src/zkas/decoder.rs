@@ -16,10 +16,14 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use darkfi_serial::{deserialize_partial, VarInt};
+use darkfi_sdk::crypto::{
+    schnorr::{SchnorrPublic, Signature},
+    PublicKey,
+};
+use darkfi_serial::{deserialize, deserialize_partial, VarInt};
 
 use super::{
-    compiler::MAGIC_BYTES,
+    compiler::{INTEGRITY_MARKER, MAGIC_BYTES},
     constants::{MAX_K, MAX_NS_LEN, MIN_BIN_SIZE},
     types::HeapType,
     LitType, Opcode, VarType,
@@ -36,8 +40,19 @@ pub struct ZkBinary {
     pub literals: Vec<(LitType, String)>,
     pub witnesses: Vec<VarType>,
     pub opcodes: Vec<(Opcode, Vec<(HeapType, usize)>)>,
+    /// Source lines of the opcodes, if the binary carries debug info
+    pub debug_lines: Option<Vec<usize>>,
+    /// Content hash, if the binary carries an `.integrity` section
+    pub hash: Option<[u8; 32]>,
+    /// Public key of the author who signed the binary, if any
+    pub author: Option<PublicKey>,
 }
 
+/// Size of an unsigned `.integrity` section: marker, hash and signed flag
+const INTEGRITY_LEN: usize = INTEGRITY_MARKER.len() + 32 + 1;
+/// Size of a signed `.integrity` section, adding a public key and signature
+const SIGNED_INTEGRITY_LEN: usize = INTEGRITY_LEN + 32 + 64;
+
 // https://stackoverflow.com/questions/35901547/how-can-i-find-a-subsequence-in-a-u8-slice
 fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     haystack.windows(needle.len()).position(|window| window == needle)
@@ -73,6 +88,10 @@ impl ZkBinary {
             return Err(ZkasErr("Namespace too long".to_string()))
         }
 
+        // The .integrity section has a fixed size and closes the binary, so
+        // we split it off first and look for the other sections before it.
+        let (bytes, hash, author) = ZkBinary::parse_integrity(bytes)?;
+
         let constants_offset = match find_subslice(bytes, b".constant") {
             Some(v) => v,
             None => return Err(ZkasErr("Could not find .constant section".to_string())),
@@ -118,15 +137,69 @@ impl ZkBinary {
         let literals_section = &bytes[literals_offset + b".literal".len()..witness_offset];
         let witness_section = &bytes[witness_offset + b".witness".len()..circuit_offset];
         let circuit_section = &bytes[circuit_offset + b".circuit".len()..debug_offset];
+        let debug_section = bytes.get(debug_offset + b".debug".len()..);
 
         let constants = ZkBinary::parse_constants(constants_section)?;
         let literals = ZkBinary::parse_literals(literals_section)?;
         let witnesses = ZkBinary::parse_witness(witness_section)?;
         let opcodes = ZkBinary::parse_circuit(circuit_section)?;
+        let debug_lines = match debug_section {
+            Some(bytes) => Some(ZkBinary::parse_debug(bytes, opcodes.len())?),
+            None => None,
+        };
+
+        Ok(Self {
+            namespace,
+            k,
+            constants,
+            literals,
+            witnesses,
+            opcodes,
+            debug_lines,
+            hash,
+            author,
+        })
+    }
+
+    /// Split off and verify the trailing `.integrity` section, if present.
+    /// Returns the bytes preceding it, along with the hash and author.
+    #[allow(clippy::type_complexity)]
+    fn parse_integrity(bytes: &[u8]) -> Result<(&[u8], Option<[u8; 32]>, Option<PublicKey>)> {
+        let has_marker = |len: usize| {
+            bytes.len() >= len &&
+                bytes[bytes.len() - len..].starts_with(INTEGRITY_MARKER) &&
+                bytes[bytes.len() - len + INTEGRITY_LEN - 1] ==
+                    (len == SIGNED_INTEGRITY_LEN) as u8
+        };
+
+        let section_len = if has_marker(INTEGRITY_LEN) {
+            INTEGRITY_LEN
+        } else if has_marker(SIGNED_INTEGRITY_LEN) {
+            SIGNED_INTEGRITY_LEN
+        } else {
+            // Binaries compiled before the section existed
+            return Ok((bytes, None, None))
+        };
+
+        let (body, section) = bytes.split_at(bytes.len() - section_len);
+        let section = &section[INTEGRITY_MARKER.len()..];
+
+        let hash: [u8; 32] = section[..32].try_into().unwrap();
+        if blake3::hash(body).as_bytes() != &hash {
+            return Err(ZkasErr("Binary hash does not match its contents".to_string()))
+        }
+
+        if section_len == INTEGRITY_LEN {
+            return Ok((body, Some(hash), None))
+        }
 
-        // TODO: Debug info
+        let author: PublicKey = deserialize(&section[33..65])?;
+        let signature: Signature = deserialize(&section[65..])?;
+        if !author.verify(&hash, &signature) {
+            return Err(ZkasErr("Invalid author signature".to_string()))
+        }
 
-        Ok(Self { namespace, k, constants, literals, witnesses, opcodes })
+        Ok((body, Some(hash), Some(author)))
     }
 
     fn parse_constants(bytes: &[u8]) -> Result<Vec<(VarType, String)>> {
@@ -258,11 +331,104 @@ impl ZkBinary {
 
         Ok(opcodes)
     }
+
+    fn parse_debug(bytes: &[u8], opcodes_len: usize) -> Result<Vec<usize>> {
+        let mut lines = vec![];
+
+        let mut iter_offset = 0;
+        while iter_offset < bytes.len() {
+            let (line, offset) = deserialize_partial::<VarInt>(&bytes[iter_offset..])?;
+            iter_offset += offset;
+
+            lines.push(line.0 as usize);
+        }
+
+        if lines.len() != opcodes_len {
+            return Err(ZkasErr(format!(
+                "Debug info holds {} lines, but circuit has {} opcodes",
+                lines.len(),
+                opcodes_len
+            )))
+        }
+
+        Ok(lines)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::zkas::ZkBinary;
+    use darkfi_sdk::{
+        crypto::{PublicKey, SecretKey},
+        pasta::pallas,
+    };
+
+    use crate::zkas::{Analyzer, Compiler, Lexer, Parser, ZkBinary};
+
+    #[test]
+    fn zkbin_integrity() {
+        let source = r#"k = 11;
+field = "pallas";
+
+constant "Integrity" {}
+
+witness "Integrity" {
+    Base a,
+}
+
+circuit "Integrity" {
+    constrain_instance(a);
+}
+"#;
+
+        let compile = |author: Option<SecretKey>| {
+            let tokens = Lexer::new("integrity.zk", source.chars()).lex().unwrap();
+            let parser = Parser::new("integrity.zk", source.chars(), tokens);
+            let (namespace, k, constants, witnesses, statements) = parser.parse().unwrap();
+            let mut analyzer =
+                Analyzer::new("integrity.zk", source.chars(), constants, witnesses, statements);
+            analyzer.analyze_types().unwrap();
+            let mut compiler = Compiler::new(
+                "integrity.zk",
+                source.chars(),
+                namespace,
+                k,
+                analyzer.constants,
+                analyzer.witnesses,
+                analyzer.statements,
+                analyzer.literals,
+                true,
+            );
+            if let Some(secret) = author {
+                compiler = compiler.with_author(secret);
+            }
+            compiler.compile().unwrap()
+        };
+
+        // Unsigned binaries carry only the hash
+        let bincode = compile(None);
+        let zkbin = ZkBinary::decode(&bincode).unwrap();
+        assert_eq!(zkbin.hash.unwrap(), *blake3::hash(&bincode[..bincode.len() - 43]).as_bytes());
+        assert!(zkbin.author.is_none());
+        assert_eq!(zkbin.debug_lines.unwrap(), vec![11]);
+
+        // Signed binaries also carry the author
+        let secret = SecretKey::from(pallas::Base::from(42));
+        let bincode = compile(Some(secret));
+        let zkbin = ZkBinary::decode(&bincode).unwrap();
+        assert_eq!(zkbin.author.unwrap(), PublicKey::from_secret(secret));
+
+        // Corrupting the contents is caught by the hash
+        let mut corrupted = bincode.clone();
+        corrupted[6] ^= 1;
+        assert!(ZkBinary::decode(&corrupted).is_err());
+
+        // Swapping the author is caught by the signature
+        let mut swapped = bincode.clone();
+        let offset = bincode.len() - 96;
+        let other = PublicKey::from_secret(SecretKey::from(pallas::Base::from(43)));
+        swapped[offset..offset + 32].copy_from_slice(&darkfi_serial::serialize(&other));
+        assert!(ZkBinary::decode(&swapped).is_err());
+    }
 
     #[test]
     fn panic_regression_001() {
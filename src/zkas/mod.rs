@@ -49,6 +49,10 @@ pub use parser::Parser;
 pub mod analyzer;
 pub use analyzer::Analyzer;
 
+/// Optimizer module
+pub mod optimizer;
+pub use optimizer::Optimizer;
+
 /// Compiler module
 pub mod compiler;
 pub use compiler::Compiler;
@@ -81,6 +81,9 @@ pub enum Opcode {
     /// Check if a field element fits in a boolean (Either 0 or 1)
     BoolCheck = 0x53,
 
+    /// Check that three Base field elements form a row of a built-in lookup table
+    TableLookup = 0x54,
+
     /// Conditionally select between two base field elements given a boolean
     CondSelect = 0x60,
 
@@ -121,6 +124,7 @@ impl Opcode {
             "less_than_strict" => Some(Self::LessThanStrict),
             "less_than_loose" => Some(Self::LessThanLoose),
             "bool_check" => Some(Self::BoolCheck),
+            "table_lookup" => Some(Self::TableLookup),
             "cond_select" => Some(Self::CondSelect),
             "zero_cond" => Some(Self::ZeroCondSelect),
             "constrain_equal_base" => Some(Self::ConstrainEqualBase),
@@ -151,6 +155,7 @@ impl Opcode {
             0x51 => Some(Self::LessThanStrict),
             0x52 => Some(Self::LessThanLoose),
             0x53 => Some(Self::BoolCheck),
+            0x54 => Some(Self::TableLookup),
             0x60 => Some(Self::CondSelect),
             0x61 => Some(Self::ZeroCondSelect),
             0xe0 => Some(Self::ConstrainEqualBase),
@@ -182,6 +187,7 @@ impl Opcode {
             Self::LessThanStrict => "less_than_strict",
             Self::LessThanLoose => "less_than_loose",
             Self::BoolCheck => "bool_check",
+            Self::TableLookup => "table_lookup",
             Self::CondSelect => "cond_select",
             Self::ZeroCondSelect => "zero_cond",
             Self::ConstrainEqualBase => "constrain_equal_base",
@@ -243,6 +249,10 @@ impl Opcode {
 
             Opcode::BoolCheck => (vec![], vec![VarType::Base]),
 
+            Opcode::TableLookup => {
+                (vec![], vec![VarType::Uint64, VarType::Base, VarType::Base, VarType::Base])
+            }
+
             Opcode::CondSelect => {
                 (vec![VarType::Base], vec![VarType::Base, VarType::Base, VarType::Base])
             }
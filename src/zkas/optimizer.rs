@@ -0,0 +1,230 @@
+/* This file is part of DarkFi (https://dark.fi)
+ *
+ * Copyright (C) 2020-2024 Dyne.org foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as
+ * published by the Free Software Foundation, either version 3 of the
+ * License, or (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    ast::{Arg, Literal, Statement, StatementType},
+    LitType, Opcode,
+};
+
+/// Compile-time evaluation of constant expressions.
+///
+/// Works on the statements produced by the analyzer, so nested function
+/// calls are already flattened. Base arithmetic over `witness_base` values
+/// is evaluated into a single `witness_base`, as long as it doesn't
+/// overflow `u64`, repeated `witness_base` values are merged into one
+/// variable, and `witness_base` statements left unused are removed.
+pub struct Optimizer {
+    statements: Vec<Statement>,
+}
+
+impl Optimizer {
+    pub fn new(statements: Vec<Statement>) -> Self {
+        Self { statements }
+    }
+
+    /// Run the optimizations, returning the new statements and the literals
+    /// they reference, in order of appearance and without duplicates.
+    pub fn optimize(self) -> (Vec<Statement>, Vec<Literal>) {
+        let statements = if Self::has_reassignments(&self.statements) {
+            // References are resolved by name, so renaming variables is
+            // only safe if every name is assigned once.
+            self.statements
+        } else {
+            Self::eliminate_dead(Self::fold(self.statements))
+        };
+
+        let literals = Self::literals(&statements);
+        (statements, literals)
+    }
+
+    fn has_reassignments(statements: &[Statement]) -> bool {
+        let mut names = HashSet::new();
+        statements.iter().filter_map(|s| s.lhs.as_ref()).any(|v| !names.insert(v.name.as_str()))
+    }
+
+    /// Evaluate constant expressions and merge repeated constants
+    fn fold(statements: Vec<Statement>) -> Vec<Statement> {
+        // Known constant value of a variable
+        let mut consts: HashMap<String, u64> = HashMap::new();
+        // First variable holding a constant value
+        let mut canonical: HashMap<u64, String> = HashMap::new();
+        // Variables merged into another one holding the same value
+        let mut renames: HashMap<String, String> = HashMap::new();
+
+        let mut folded = Vec::with_capacity(statements.len());
+        for mut stmt in statements {
+            for arg in stmt.rhs.iter_mut() {
+                if let Arg::Var(v) = arg {
+                    if let Some(name) = renames.get(&v.name) {
+                        v.name.clone_from(name);
+                    }
+                }
+            }
+
+            let value = match (stmt.opcode, stmt.rhs.as_slice()) {
+                (Opcode::WitnessBase, [Arg::Lit(lit)]) => lit.name.parse::<u64>().ok(),
+                (
+                    Opcode::BaseAdd | Opcode::BaseMul | Opcode::BaseSub,
+                    [Arg::Var(a), Arg::Var(b)],
+                ) => match (consts.get(&a.name), consts.get(&b.name)) {
+                    (Some(a), Some(b)) => match stmt.opcode {
+                        Opcode::BaseAdd => a.checked_add(*b),
+                        Opcode::BaseMul => a.checked_mul(*b),
+                        _ => a.checked_sub(*b),
+                    },
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            let (Some(value), Some(lhs)) = (value, stmt.lhs.clone()) else {
+                folded.push(stmt);
+                continue
+            };
+
+            if let Some(name) = canonical.get(&value) {
+                renames.insert(lhs.name, name.clone());
+                continue
+            }
+
+            if stmt.opcode != Opcode::WitnessBase {
+                let literal = Literal {
+                    name: value.to_string(),
+                    typ: LitType::Uint64,
+                    line: lhs.line,
+                    column: lhs.column,
+                };
+                stmt.opcode = Opcode::WitnessBase;
+                stmt.rhs = vec![Arg::Lit(literal)];
+            }
+
+            canonical.insert(value, lhs.name.clone());
+            consts.insert(lhs.name, value);
+            folded.push(stmt);
+        }
+
+        folded
+    }
+
+    /// Remove `witness_base` statements whose result is never referenced
+    fn eliminate_dead(statements: Vec<Statement>) -> Vec<Statement> {
+        let referenced: HashSet<String> = statements
+            .iter()
+            .flat_map(|s| s.rhs.iter())
+            .filter_map(|arg| match arg {
+                Arg::Var(v) => Some(v.name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        statements
+            .into_iter()
+            .filter(|s| {
+                s.opcode != Opcode::WitnessBase ||
+                    s.typ != StatementType::Assign ||
+                    referenced.contains(&s.lhs.as_ref().unwrap().name)
+            })
+            .collect()
+    }
+
+    fn literals(statements: &[Statement]) -> Vec<Literal> {
+        let mut literals: Vec<Literal> = vec![];
+        for arg in statements.iter().flat_map(|s| s.rhs.iter()) {
+            if let Arg::Lit(lit) = arg {
+                if !literals.iter().any(|l| l.typ == lit.typ && l.name == lit.name) {
+                    literals.push(lit.clone());
+                }
+            }
+        }
+
+        literals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zkas::{Analyzer, Compiler, Lexer, Parser, ZkBinary};
+
+    #[test]
+    fn zkas_constant_folding() {
+        let source = r#"k = 11;
+field = "pallas";
+
+constant "Fold" {}
+
+witness "Fold" {
+    Base a,
+}
+
+circuit "Fold" {
+    one = witness_base(1);
+    two = witness_base(2);
+    three = base_add(one, two);
+    six = base_mul(three, two);
+    also_one = witness_base(1);
+    r = base_add(a, also_one);
+    constrain_instance(base_mul(r, six));
+    range_check(64, a);
+    range_check(64, r);
+}
+"#;
+
+        let lexer = Lexer::new("fold.zk", source.chars());
+        let tokens = lexer.lex().unwrap();
+        let parser = Parser::new("fold.zk", source.chars(), tokens);
+        let (namespace, k, constants, witnesses, statements) = parser.parse().unwrap();
+        let mut analyzer =
+            Analyzer::new("fold.zk", source.chars(), constants, witnesses, statements);
+        analyzer.analyze_types().unwrap();
+
+        let (statements, literals) = Optimizer::new(analyzer.statements).optimize();
+        let opcodes: Vec<Opcode> = statements.iter().map(|s| s.opcode).collect();
+        assert_eq!(
+            opcodes,
+            vec![
+                Opcode::WitnessBase,
+                Opcode::WitnessBase,
+                Opcode::BaseAdd,
+                Opcode::BaseMul,
+                Opcode::ConstrainInstance,
+                Opcode::RangeCheck,
+                Opcode::RangeCheck,
+            ]
+        );
+        let names: Vec<&str> = literals.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, vec!["1", "6", "64"]);
+
+        let compiler = Compiler::new(
+            "fold.zk",
+            source.chars(),
+            namespace,
+            k,
+            analyzer.constants,
+            analyzer.witnesses,
+            statements,
+            literals,
+            false,
+        );
+        let zkbin = ZkBinary::decode(&compiler.compile().unwrap()).unwrap();
+        assert_eq!(zkbin.opcodes.len(), 7);
+        assert_eq!(zkbin.literals.len(), 3);
+    }
+}
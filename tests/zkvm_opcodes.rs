@@ -34,7 +34,7 @@ use rand::rngs::OsRng;
 
 use darkfi::{
     zk::{
-        proof::{ProvingKey, VerifyingKey},
+        proof::{verify_batch, ProvingKey, VerifyingKey},
         vm::ZkCircuit,
         vm_heap::{empty_witnesses, Witness},
         Proof,
@@ -133,5 +133,12 @@ fn zkvm_opcodes() -> Result<()> {
     let verifying_key = VerifyingKey::build(zkbin.k, &circuit);
     proof.verify(&verifying_key, &public_inputs)?;
 
+    // Batch verification must agree with the individual one
+    let vks = [&verifying_key, &verifying_key];
+    assert!(verify_batch(&[&proof, &proof], &vks, &[&public_inputs, &public_inputs]));
+    let mut bad_inputs = public_inputs.clone();
+    bad_inputs[0] += pallas::Base::ONE;
+    assert!(!verify_batch(&[&proof, &proof], &vks, &[&public_inputs, &bad_inputs]));
+
     Ok(())
 }